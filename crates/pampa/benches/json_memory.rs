@@ -0,0 +1,154 @@
+//! Peak memory of the JSON writer on large documents
+//!
+//! Measures the peak heap growth of `write_with_config` against the size of
+//! its output, for synthetic documents producing roughly 3MB to 12MB of
+//! JSON (source info interning is quadratic, so larger documents take
+//! minutes). The output goes to a writer that only counts bytes, so the peak
+//! is the writer's own memory: the source info pool and its lookup tables,
+//! plus whatever it holds of the serialized document.
+//!
+//! Run with: cargo bench --bench json_memory
+
+use pampa::pandoc::{ASTContext, Block, Inline, Pandoc, Paragraph, Space, Str};
+use pampa::writers::json::{JsonConfig, write_with_config};
+use quarto_pandoc_types::ConfigValue;
+use quarto_source_map::{FileId, SourceInfo};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Allocator wrapper that tracks current and peak heap usage.
+struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn record_alloc(size: usize) {
+    let now = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(now, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Run `f` and return the peak heap growth (in bytes) while it ran.
+fn peak_during(f: impl FnOnce()) -> usize {
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    f();
+    PEAK.load(Ordering::Relaxed) - baseline
+}
+
+/// A document of `paragraphs` paragraphs of 100 words, each word with its
+/// own source range as produced by the qmd reader.
+fn synthetic_document(paragraphs: usize) -> Pandoc {
+    let mut offset = 0;
+    let mut source = |len: usize| {
+        let info = SourceInfo::Original {
+            file_id: FileId(0),
+            start_offset: offset,
+            end_offset: offset + len,
+        };
+        offset += len;
+        info
+    };
+    let blocks = (0..paragraphs)
+        .map(|p| {
+            let mut content = Vec::with_capacity(200);
+            for w in 0..100 {
+                if w > 0 {
+                    content.push(Inline::Space(Space {
+                        source_info: source(1),
+                    }));
+                }
+                let text = format!("word{}", (p * 100 + w) % 997);
+                content.push(Inline::Str(Str {
+                    source_info: source(text.len()),
+                    text,
+                }));
+            }
+            Block::Paragraph(Paragraph {
+                content,
+                source_info: source(2),
+            })
+        })
+        .collect();
+    Pandoc {
+        meta: ConfigValue::default(),
+        blocks,
+    }
+}
+
+fn mb(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+fn main() {
+    println!("JSON Writer Peak Memory");
+    println!("=======================\n");
+    println!(
+        "{:>10} {:>12} {:>12} {:>14}",
+        "paragraphs", "output MB", "peak MB", "peak / output"
+    );
+
+    let context = ASTContext::anonymous();
+    let config = JsonConfig::default();
+
+    for paragraphs in [250, 500, 1_000] {
+        let pandoc = synthetic_document(paragraphs);
+
+        let mut output_len = 0;
+        let peak = peak_during(|| {
+            let mut counter = ByteCounter(0);
+            write_with_config(&pandoc, &context, &mut counter, &config)
+                .expect("write_with_config failed");
+            output_len = counter.0;
+        });
+
+        println!(
+            "{:>10} {:>12.1} {:>12.1} {:>13.2}x",
+            paragraphs,
+            mb(output_len),
+            mb(peak),
+            peak as f64 / output_len as f64
+        );
+    }
+}
+
+/// Writer that discards output and counts bytes, so the output itself is
+/// not part of the measured memory.
+struct ByteCounter(usize);
+
+impl std::io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
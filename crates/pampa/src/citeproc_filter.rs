@@ -21,7 +21,7 @@ use crate::unified_filter::CiteprocFilterError;
 use quarto_pandoc_types::{ConfigMapEntry, ConfigValue, ConfigValueKind};
 
 /// Default CSL style (Chicago Manual of Style, author-date format).
-const DEFAULT_CSL_STYLE: &str = include_str!("../resources/csl/chicago-author-date.csl");
+const DEFAULT_CSL_STYLE: &str = quarto_citeproc::DEFAULT_STYLE;
 
 /// Configuration for the citeproc filter, extracted from document metadata.
 #[derive(Debug)]
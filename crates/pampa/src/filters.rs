@@ -7,6 +7,8 @@ use crate::filter_context::FilterContext;
 use crate::pandoc::{self, AsInline, Block, Blocks, Inline, Inlines, MetaBlock};
 use quarto_pandoc_types::{ConfigMapEntry, ConfigValue, ConfigValueKind};
 
+pub mod native;
+
 // filters are destructive and take ownership of the input
 
 pub enum FilterReturn<T, U> {
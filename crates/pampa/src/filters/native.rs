@@ -0,0 +1,785 @@
+/*
+ * filters/native.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Native Rust filters that run in-process on the AST.
+ */
+
+//! Native Rust filters.
+//!
+//! A [`Filter`] is a Rust value with one hook per Block and Inline variant.
+//! Hooks receive the node by mutable reference, so they can edit it in place,
+//! and return a [`Visit`] that says what to do next:
+//!
+//! - [`Visit::Continue`]: keep the node and walk its children
+//! - [`Visit::Skip`]: keep the node but don't walk its children
+//! - [`Visit::Replace`]: splice the given nodes in place of this one. The
+//!   replacement is not walked again.
+//! - [`Visit::Stop`]: end the walk right away
+//!
+//! Filters walk [`WalkOrder::Topdown`] (parents before children) by default,
+//! or [`WalkOrder::Bottomup`] (children before parents). In bottom-up order
+//! children have already been walked, so `Skip` means the same as `Continue`.
+//!
+//! Unlike JSON and Lua filters, native filters edit the AST directly, with no
+//! serialization round trip. A [`FilterRegistry`] maps names to filter
+//! factories, so transforms and extensions compiled as Rust can be looked up
+//! and run by name.
+//!
+//! For the common "find nodes and replace them" case, [`ReplaceFilter`]
+//! builds a filter from closures instead of a dedicated type.
+
+use crate::pandoc::{
+    Block, BlockQuote, Blocks, BulletList, Caption, CaptionBlock, Cite, Code, CodeBlock,
+    ConfigValue, ConfigValueKind, CustomNode, DefinitionList, Delete, Div, EditComment, Emph,
+    Figure, Header, Highlight, HorizontalRule, Image, Inline, Inlines, Insert, LineBlock,
+    LineBreak, Link, Math, MetaBlock, Note, NoteDefinitionFencedBlock, NoteDefinitionPara,
+    NoteReference, OrderedList, Pandoc, Paragraph, Plain, Quoted, RawBlock, RawInline, Shortcode,
+    Slot, SmallCaps, SoftBreak, Space, Span, Str, Strikeout, Strong, Subscript, Superscript, Table,
+    Underline,
+};
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+/// What to do after a filter hook has seen a node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Visit<T> {
+    /// Keep the node (with any in-place edits) and walk its children.
+    Continue,
+    /// Keep the node but don't walk its children.
+    Skip,
+    /// Replace the node with these nodes, which are not walked.
+    Replace(Vec<T>),
+    /// Stop the whole walk.
+    Stop,
+}
+
+/// The order in which a filter visits parents and children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WalkOrder {
+    #[default]
+    Topdown,
+    Bottomup,
+}
+
+// Generates the `Filter` trait: one default hook per variant, plus
+// `visit_block` and `visit_inline`, which dispatch to them.
+macro_rules! define_native_filter {
+    (
+        blocks { $($bvariant:ident($btype:ty) => $bhook:ident),* $(,)? }
+        inlines { $($ivariant:ident($itype:ty) => $ihook:ident),* $(,)? }
+    ) => {
+        /// A filter compiled as Rust. See the [module docs](self).
+        ///
+        /// Implement the hooks for the variants the filter cares about; every
+        /// hook defaults to [`Visit::Continue`]. To handle all blocks or all
+        /// inlines at once, override [`Filter::visit_block`] or
+        /// [`Filter::visit_inline`] instead.
+        pub trait Filter {
+            /// The name used in diagnostics and in a [`FilterRegistry`].
+            fn name(&self) -> &str;
+
+            fn order(&self) -> WalkOrder {
+                WalkOrder::Topdown
+            }
+
+            /// Called for every block. Dispatches to the per-variant hooks.
+            fn visit_block(&mut self, block: &mut Block) -> Visit<Block> {
+                match block {
+                    $(Block::$bvariant(node) => self.$bhook(node),)*
+                }
+            }
+
+            /// Called for every inline. Dispatches to the per-variant hooks.
+            fn visit_inline(&mut self, inline: &mut Inline) -> Visit<Inline> {
+                match inline {
+                    $(Inline::$ivariant(node) => self.$ihook(node),)*
+                    Inline::Attr(..) => Visit::Continue,
+                }
+            }
+
+            $(
+                #[doc = concat!("Called for each `", stringify!($bvariant), "` block.")]
+                fn $bhook(&mut self, _node: &mut $btype) -> Visit<Block> {
+                    Visit::Continue
+                }
+            )*
+
+            $(
+                #[doc = concat!("Called for each `", stringify!($ivariant), "` inline.")]
+                fn $ihook(&mut self, _node: &mut $itype) -> Visit<Inline> {
+                    Visit::Continue
+                }
+            )*
+        }
+    };
+}
+
+define_native_filter! {
+    blocks {
+        Plain(Plain) => visit_plain,
+        Paragraph(Paragraph) => visit_paragraph,
+        LineBlock(LineBlock) => visit_line_block,
+        CodeBlock(CodeBlock) => visit_code_block,
+        RawBlock(RawBlock) => visit_raw_block,
+        BlockQuote(BlockQuote) => visit_block_quote,
+        OrderedList(OrderedList) => visit_ordered_list,
+        BulletList(BulletList) => visit_bullet_list,
+        DefinitionList(DefinitionList) => visit_definition_list,
+        Header(Header) => visit_header,
+        HorizontalRule(HorizontalRule) => visit_horizontal_rule,
+        Table(Table) => visit_table,
+        Figure(Figure) => visit_figure,
+        Div(Div) => visit_div,
+        BlockMetadata(MetaBlock) => visit_block_metadata,
+        NoteDefinitionPara(NoteDefinitionPara) => visit_note_definition_para,
+        NoteDefinitionFencedBlock(NoteDefinitionFencedBlock) => visit_note_definition_fenced_block,
+        CaptionBlock(CaptionBlock) => visit_caption_block,
+        Custom(CustomNode) => visit_custom_block,
+    }
+    inlines {
+        Str(Str) => visit_str,
+        Emph(Emph) => visit_emph,
+        Underline(Underline) => visit_underline,
+        Strong(Strong) => visit_strong,
+        Strikeout(Strikeout) => visit_strikeout,
+        Superscript(Superscript) => visit_superscript,
+        Subscript(Subscript) => visit_subscript,
+        SmallCaps(SmallCaps) => visit_small_caps,
+        Quoted(Quoted) => visit_quoted,
+        Cite(Cite) => visit_cite,
+        Code(Code) => visit_code,
+        Space(Space) => visit_space,
+        SoftBreak(SoftBreak) => visit_soft_break,
+        LineBreak(LineBreak) => visit_line_break,
+        Math(Math) => visit_math,
+        RawInline(RawInline) => visit_raw_inline,
+        Link(Link) => visit_link,
+        Image(Image) => visit_image,
+        Note(Note) => visit_note,
+        Span(Span) => visit_span,
+        Shortcode(Shortcode) => visit_shortcode,
+        NoteReference(NoteReference) => visit_note_reference,
+        Insert(Insert) => visit_insert,
+        Delete(Delete) => visit_delete,
+        Highlight(Highlight) => visit_highlight,
+        EditComment(EditComment) => visit_edit_comment,
+        Custom(CustomNode) => visit_custom_inline,
+    }
+}
+
+/// Walk a whole document: metadata first, then the blocks.
+///
+/// Returns `ControlFlow::Break` if a hook returned [`Visit::Stop`].
+pub fn walk_document(filter: &mut dyn Filter, doc: &mut Pandoc) -> ControlFlow<()> {
+    walk_meta(filter, &mut doc.meta)?;
+    walk_blocks(filter, &mut doc.blocks)
+}
+
+pub fn walk_blocks(filter: &mut dyn Filter, blocks: &mut Blocks) -> ControlFlow<()> {
+    walk_nodes(filter, blocks)
+}
+
+pub fn walk_inlines(filter: &mut dyn Filter, inlines: &mut Inlines) -> ControlFlow<()> {
+    walk_nodes(filter, inlines)
+}
+
+/// Walk the Pandoc content inside metadata values.
+pub fn walk_meta(filter: &mut dyn Filter, value: &mut ConfigValue) -> ControlFlow<()> {
+    match &mut value.value {
+        ConfigValueKind::Map(entries) => {
+            for entry in entries {
+                walk_meta(filter, &mut entry.value)?;
+            }
+            ControlFlow::Continue(())
+        }
+        ConfigValueKind::Array(items) => {
+            for item in items {
+                walk_meta(filter, item)?;
+            }
+            ControlFlow::Continue(())
+        }
+        ConfigValueKind::PandocBlocks(blocks) => walk_blocks(filter, blocks),
+        ConfigValueKind::PandocInlines(inlines) => walk_inlines(filter, inlines),
+        // Other variants don't hold Pandoc content
+        _ => ControlFlow::Continue(()),
+    }
+}
+
+/// A node the walker can visit and descend into.
+trait Walkable: Sized {
+    fn visit(filter: &mut dyn Filter, node: &mut Self) -> Visit<Self>;
+    fn walk_children(filter: &mut dyn Filter, node: &mut Self) -> ControlFlow<()>;
+}
+
+impl Walkable for Block {
+    fn visit(filter: &mut dyn Filter, node: &mut Self) -> Visit<Self> {
+        filter.visit_block(node)
+    }
+
+    fn walk_children(filter: &mut dyn Filter, node: &mut Self) -> ControlFlow<()> {
+        walk_block_children(filter, node)
+    }
+}
+
+impl Walkable for Inline {
+    fn visit(filter: &mut dyn Filter, node: &mut Self) -> Visit<Self> {
+        filter.visit_inline(node)
+    }
+
+    fn walk_children(filter: &mut dyn Filter, node: &mut Self) -> ControlFlow<()> {
+        walk_inline_children(filter, node)
+    }
+}
+
+fn walk_nodes<T: Walkable>(filter: &mut dyn Filter, nodes: &mut Vec<T>) -> ControlFlow<()> {
+    let mut i = 0;
+    while i < nodes.len() {
+        match walk_node(filter, &mut nodes[i])? {
+            Some(replacement) => {
+                let len = replacement.len();
+                nodes.splice(i..=i, replacement);
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+/// Visit `node` and its children in the filter's order. Returns the nodes
+/// that replace `node`, if the filter replaced it.
+fn walk_node<T: Walkable>(
+    filter: &mut dyn Filter,
+    node: &mut T,
+) -> ControlFlow<(), Option<Vec<T>>> {
+    let visit = match filter.order() {
+        WalkOrder::Topdown => match T::visit(filter, node) {
+            Visit::Continue => {
+                T::walk_children(filter, node)?;
+                Visit::Continue
+            }
+            visit => visit,
+        },
+        WalkOrder::Bottomup => {
+            T::walk_children(filter, node)?;
+            T::visit(filter, node)
+        }
+    };
+    match visit {
+        Visit::Continue | Visit::Skip => ControlFlow::Continue(None),
+        Visit::Replace(replacement) => ControlFlow::Continue(Some(replacement)),
+        Visit::Stop => ControlFlow::Break(()),
+    }
+}
+
+fn walk_caption(filter: &mut dyn Filter, caption: &mut Caption) -> ControlFlow<()> {
+    if let Some(short) = &mut caption.short {
+        walk_inlines(filter, short)?;
+    }
+    if let Some(long) = &mut caption.long {
+        walk_blocks(filter, long)?;
+    }
+    ControlFlow::Continue(())
+}
+
+fn walk_table(filter: &mut dyn Filter, table: &mut Table) -> ControlFlow<()> {
+    walk_caption(filter, &mut table.caption)?;
+    let rows = table
+        .head
+        .rows
+        .iter_mut()
+        .chain(
+            table
+                .bodies
+                .iter_mut()
+                .flat_map(|body| body.head.iter_mut().chain(body.body.iter_mut())),
+        )
+        .chain(table.foot.rows.iter_mut());
+    for row in rows {
+        for cell in &mut row.cells {
+            walk_blocks(filter, &mut cell.content)?;
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+fn walk_custom(filter: &mut dyn Filter, custom: &mut CustomNode) -> ControlFlow<()> {
+    for slot in custom.slots.values_mut() {
+        match slot {
+            Slot::Blocks(blocks) => walk_blocks(filter, blocks)?,
+            Slot::Inlines(inlines) => walk_inlines(filter, inlines)?,
+            // A single-node slot replaced by several nodes becomes a list slot
+            Slot::Block(block) => {
+                if let Some(mut replacement) = walk_node(filter, block.as_mut())? {
+                    *slot = match replacement.len() {
+                        1 => Slot::Block(Box::new(replacement.remove(0))),
+                        _ => Slot::Blocks(replacement),
+                    };
+                }
+            }
+            Slot::Inline(inline) => {
+                if let Some(mut replacement) = walk_node(filter, inline.as_mut())? {
+                    *slot = match replacement.len() {
+                        1 => Slot::Inline(Box::new(replacement.remove(0))),
+                        _ => Slot::Inlines(replacement),
+                    };
+                }
+            }
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+fn walk_block_children(filter: &mut dyn Filter, block: &mut Block) -> ControlFlow<()> {
+    match block {
+        Block::Plain(Plain { content, .. })
+        | Block::Paragraph(Paragraph { content, .. })
+        | Block::Header(Header { content, .. })
+        | Block::NoteDefinitionPara(NoteDefinitionPara { content, .. })
+        | Block::CaptionBlock(CaptionBlock { content, .. }) => walk_inlines(filter, content),
+        Block::BlockQuote(BlockQuote { content, .. })
+        | Block::Div(Div { content, .. })
+        | Block::NoteDefinitionFencedBlock(NoteDefinitionFencedBlock { content, .. }) => {
+            walk_blocks(filter, content)
+        }
+        Block::LineBlock(line_block) => {
+            for line in &mut line_block.content {
+                walk_inlines(filter, line)?;
+            }
+            ControlFlow::Continue(())
+        }
+        Block::BulletList(BulletList { content, .. })
+        | Block::OrderedList(OrderedList { content, .. }) => {
+            for item in content {
+                walk_blocks(filter, item)?;
+            }
+            ControlFlow::Continue(())
+        }
+        Block::DefinitionList(list) => {
+            for (term, definitions) in &mut list.content {
+                walk_inlines(filter, term)?;
+                for definition in definitions {
+                    walk_blocks(filter, definition)?;
+                }
+            }
+            ControlFlow::Continue(())
+        }
+        Block::Figure(figure) => {
+            walk_caption(filter, &mut figure.caption)?;
+            walk_blocks(filter, &mut figure.content)
+        }
+        Block::Table(table) => walk_table(filter, table),
+        Block::Custom(custom) => walk_custom(filter, custom),
+        Block::CodeBlock(_)
+        | Block::RawBlock(_)
+        | Block::HorizontalRule(_)
+        | Block::BlockMetadata(_) => ControlFlow::Continue(()),
+    }
+}
+
+fn walk_inline_children(filter: &mut dyn Filter, inline: &mut Inline) -> ControlFlow<()> {
+    match inline {
+        Inline::Emph(Emph { content, .. })
+        | Inline::Underline(Underline { content, .. })
+        | Inline::Strong(Strong { content, .. })
+        | Inline::Strikeout(Strikeout { content, .. })
+        | Inline::Superscript(Superscript { content, .. })
+        | Inline::Subscript(Subscript { content, .. })
+        | Inline::SmallCaps(SmallCaps { content, .. })
+        | Inline::Quoted(Quoted { content, .. })
+        | Inline::Link(Link { content, .. })
+        | Inline::Image(Image { content, .. })
+        | Inline::Span(Span { content, .. })
+        | Inline::Insert(Insert { content, .. })
+        | Inline::Delete(Delete { content, .. })
+        | Inline::Highlight(Highlight { content, .. })
+        | Inline::EditComment(EditComment { content, .. }) => walk_inlines(filter, content),
+        Inline::Cite(cite) => {
+            for citation in &mut cite.citations {
+                walk_inlines(filter, &mut citation.prefix)?;
+                walk_inlines(filter, &mut citation.suffix)?;
+            }
+            walk_inlines(filter, &mut cite.content)
+        }
+        Inline::Note(note) => walk_blocks(filter, &mut note.content),
+        Inline::Custom(custom) => walk_custom(filter, custom),
+        Inline::Str(_)
+        | Inline::Code(_)
+        | Inline::Space(_)
+        | Inline::SoftBreak(_)
+        | Inline::LineBreak(_)
+        | Inline::Math(_)
+        | Inline::RawInline(_)
+        | Inline::Shortcode(_)
+        | Inline::NoteReference(_)
+        | Inline::Attr(..) => ControlFlow::Continue(()),
+    }
+}
+
+/// Creates a fresh filter for each run.
+pub type FilterFactory = Arc<dyn Fn() -> Box<dyn Filter> + Send + Sync>;
+
+/// Native filters registered by name.
+///
+/// The registry stores factories rather than filters, so filters can keep
+/// per-run state in `&mut self` and a registry can be shared across threads.
+#[derive(Clone, Default)]
+pub struct FilterRegistry {
+    factories: BTreeMap<String, FilterFactory>,
+}
+
+impl FilterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a filter factory under `name`, replacing (and returning) any
+    /// factory already registered under that name.
+    pub fn register<F>(&mut self, name: impl Into<String>, factory: F) -> Option<FilterFactory>
+    where
+        F: Fn() -> Box<dyn Filter> + Send + Sync + 'static,
+    {
+        self.factories.insert(name.into(), Arc::new(factory))
+    }
+
+    pub fn unregister(&mut self, name: &str) -> Option<FilterFactory> {
+        self.factories.remove(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    pub fn factory(&self, name: &str) -> Option<FilterFactory> {
+        self.factories.get(name).cloned()
+    }
+
+    /// Create a new instance of the filter registered under `name`.
+    pub fn create(&self, name: &str) -> Option<Box<dyn Filter>> {
+        self.factories.get(name).map(|factory| factory())
+    }
+
+    /// Registered filter names, in sorted order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+
+    /// Run the filter registered under `name` on `doc`.
+    ///
+    /// Returns `None` if no filter is registered under `name`.
+    pub fn run(&self, name: &str, doc: &mut Pandoc) -> Option<ControlFlow<()>> {
+        let mut filter = self.create(name)?;
+        Some(walk_document(filter.as_mut(), doc))
+    }
+}
+
+type ReplaceFn<'a, T> = Box<dyn FnMut(&mut T) -> Option<Vec<T>> + 'a>;
+
+/// A "walk and replace" filter built from closures.
+///
+/// Each closure sees every block (or inline) and returns `Some(nodes)` to
+/// replace it or `None` to keep it. Closures can also edit the node in place
+/// and return `None`.
+///
+/// ```ignore
+/// let mut filter = ReplaceFilter::new("drop-emphasis").with_inlines(|inline| match inline {
+///     Inline::Emph(emph) => Some(std::mem::take(&mut emph.content)),
+///     _ => None,
+/// });
+/// walk_document(&mut filter, &mut doc);
+/// ```
+pub struct ReplaceFilter<'a> {
+    name: String,
+    order: WalkOrder,
+    blocks: Option<ReplaceFn<'a, Block>>,
+    inlines: Option<ReplaceFn<'a, Inline>>,
+}
+
+impl<'a> ReplaceFilter<'a> {
+    pub fn new(name: impl Into<String>) -> Self {
+        ReplaceFilter {
+            name: name.into(),
+            order: WalkOrder::Topdown,
+            blocks: None,
+            inlines: None,
+        }
+    }
+
+    pub fn with_order(mut self, order: WalkOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn with_blocks<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&mut Block) -> Option<Blocks> + 'a,
+    {
+        self.blocks = Some(Box::new(f));
+        self
+    }
+
+    pub fn with_inlines<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&mut Inline) -> Option<Inlines> + 'a,
+    {
+        self.inlines = Some(Box::new(f));
+        self
+    }
+}
+
+impl Filter for ReplaceFilter<'_> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn order(&self) -> WalkOrder {
+        self.order
+    }
+
+    fn visit_block(&mut self, block: &mut Block) -> Visit<Block> {
+        match self.blocks.as_mut().and_then(|f| f(block)) {
+            Some(replacement) => Visit::Replace(replacement),
+            None => Visit::Continue,
+        }
+    }
+
+    fn visit_inline(&mut self, inline: &mut Inline) -> Visit<Inline> {
+        match self.inlines.as_mut().and_then(|f| f(inline)) {
+            Some(replacement) => Visit::Replace(replacement),
+            None => Visit::Continue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pandoc::AttrSourceInfo;
+    use quarto_source_map::SourceInfo;
+
+    fn str_inline(text: &str) -> Inline {
+        Inline::Str(Str {
+            text: text.to_string(),
+            source_info: SourceInfo::default(),
+        })
+    }
+
+    fn emph(content: Inlines) -> Inline {
+        Inline::Emph(Emph {
+            content,
+            source_info: SourceInfo::default(),
+        })
+    }
+
+    fn para(content: Inlines) -> Block {
+        Block::Paragraph(Paragraph {
+            content,
+            source_info: SourceInfo::default(),
+        })
+    }
+
+    fn div(content: Blocks) -> Block {
+        Block::Div(Div {
+            attr: (String::new(), vec![], Default::default()),
+            content,
+            source_info: SourceInfo::default(),
+            attr_source: AttrSourceInfo::empty(),
+        })
+    }
+
+    fn doc(blocks: Blocks) -> Pandoc {
+        Pandoc {
+            meta: ConfigValue::default(),
+            blocks,
+        }
+    }
+
+    fn texts(inlines: &Inlines) -> Vec<String> {
+        inlines
+            .iter()
+            .map(|inline| match inline {
+                Inline::Str(s) => s.text.clone(),
+                Inline::Emph(e) => format!("emph{:?}", texts(&e.content)),
+                _ => "?".to_string(),
+            })
+            .collect()
+    }
+
+    fn para_texts(block: &Block) -> Vec<String> {
+        match block {
+            Block::Paragraph(p) => texts(&p.content),
+            _ => panic!("expected Paragraph, got {:?}", block),
+        }
+    }
+
+    /// Uppercases every Str and records the order in which it saw them.
+    struct Upper {
+        order: WalkOrder,
+        seen: Vec<String>,
+    }
+
+    impl Filter for Upper {
+        fn name(&self) -> &str {
+            "upper"
+        }
+
+        fn order(&self) -> WalkOrder {
+            self.order
+        }
+
+        fn visit_str(&mut self, node: &mut Str) -> Visit<Inline> {
+            self.seen.push(node.text.clone());
+            node.text = node.text.to_uppercase();
+            Visit::Continue
+        }
+
+        fn visit_emph(&mut self, _node: &mut Emph) -> Visit<Inline> {
+            self.seen.push("emph".to_string());
+            Visit::Continue
+        }
+    }
+
+    #[test]
+    fn test_hooks_edit_in_place_in_walk_order() {
+        let mut document = doc(vec![div(vec![para(vec![
+            str_inline("a"),
+            emph(vec![str_inline("b")]),
+        ])])]);
+
+        let mut topdown = Upper {
+            order: WalkOrder::Topdown,
+            seen: vec![],
+        };
+        assert!(walk_document(&mut topdown, &mut document).is_continue());
+        assert_eq!(topdown.seen, vec!["a", "emph", "b"]);
+
+        let Block::Div(d) = &document.blocks[0] else {
+            panic!("expected Div");
+        };
+        assert_eq!(para_texts(&d.content[0]), vec!["A", "emph[\"B\"]"]);
+
+        let mut bottomup = Upper {
+            order: WalkOrder::Bottomup,
+            seen: vec![],
+        };
+        let _ = walk_document(&mut bottomup, &mut document);
+        assert_eq!(bottomup.seen, vec!["A", "B", "emph"]);
+    }
+
+    #[test]
+    fn test_skip_and_stop() {
+        struct SkipEmphStopAtEnd(Vec<String>);
+
+        impl Filter for SkipEmphStopAtEnd {
+            fn name(&self) -> &str {
+                "skip"
+            }
+
+            fn visit_emph(&mut self, _node: &mut Emph) -> Visit<Inline> {
+                Visit::Skip
+            }
+
+            fn visit_str(&mut self, node: &mut Str) -> Visit<Inline> {
+                if node.text == "end" {
+                    return Visit::Stop;
+                }
+                self.0.push(node.text.clone());
+                Visit::Continue
+            }
+        }
+
+        let mut document = doc(vec![
+            para(vec![
+                str_inline("a"),
+                emph(vec![str_inline("hidden")]),
+                str_inline("end"),
+                str_inline("after"),
+            ]),
+            para(vec![str_inline("later")]),
+        ]);
+        let mut filter = SkipEmphStopAtEnd(vec![]);
+        assert!(walk_document(&mut filter, &mut document).is_break());
+        assert_eq!(filter.0, vec!["a"]);
+    }
+
+    #[test]
+    fn test_replace_filter_splices_without_revisiting() {
+        let mut visits = 0;
+        let mut filter = ReplaceFilter::new("unwrap-emph").with_inlines(|inline| {
+            visits += 1;
+            match inline {
+                Inline::Emph(e) => Some(std::mem::take(&mut e.content)),
+                _ => None,
+            }
+        });
+        let mut blocks = vec![para(vec![
+            emph(vec![str_inline("x"), str_inline("y")]),
+            str_inline("z"),
+        ])];
+        let _ = walk_blocks(&mut filter, &mut blocks);
+        drop(filter);
+
+        assert_eq!(para_texts(&blocks[0]), vec!["x", "y", "z"]);
+        // The emph and "z"; the spliced-in "x" and "y" are not visited
+        assert_eq!(visits, 2);
+    }
+
+    #[test]
+    fn test_replace_blocks_can_delete() {
+        let mut filter = ReplaceFilter::new("drop-divs").with_blocks(|block| match block {
+            Block::Div(_) => Some(vec![]),
+            _ => None,
+        });
+        let mut document = doc(vec![
+            para(vec![str_inline("keep")]),
+            div(vec![para(vec![str_inline("drop")])]),
+        ]);
+        let _ = walk_document(&mut filter, &mut document);
+        assert_eq!(document.blocks.len(), 1);
+        assert_eq!(para_texts(&document.blocks[0]), vec!["keep"]);
+    }
+
+    #[test]
+    fn test_walk_reaches_notes_and_meta() {
+        let note = Inline::Note(Note {
+            content: vec![para(vec![str_inline("note")])],
+            source_info: SourceInfo::default(),
+        });
+        let mut document = doc(vec![para(vec![note])]);
+        document.meta = ConfigValue {
+            value: ConfigValueKind::PandocInlines(vec![str_inline("title")]),
+            ..ConfigValue::default()
+        };
+
+        let mut filter = Upper {
+            order: WalkOrder::Topdown,
+            seen: vec![],
+        };
+        let _ = walk_document(&mut filter, &mut document);
+        assert_eq!(filter.seen, vec!["title", "note"]);
+    }
+
+    #[test]
+    fn test_registry_creates_fresh_filters() {
+        let mut registry = FilterRegistry::new();
+        registry.register("upper", || {
+            Box::new(Upper {
+                order: WalkOrder::Topdown,
+                seen: vec![],
+            })
+        });
+        assert!(registry.contains("upper"));
+        assert_eq!(registry.names().collect::<Vec<_>>(), vec!["upper"]);
+        assert_eq!(registry.create("upper").unwrap().name(), "upper");
+
+        let mut document = doc(vec![para(vec![str_inline("hi")])]);
+        assert_eq!(
+            registry.run("upper", &mut document),
+            Some(ControlFlow::Continue(()))
+        );
+        assert_eq!(para_texts(&document.blocks[0]), vec!["HI"]);
+        assert!(registry.run("missing", &mut document).is_none());
+    }
+}
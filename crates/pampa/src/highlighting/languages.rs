@@ -0,0 +1,802 @@
+/*
+ * highlighting/languages.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Language definitions and the table-driven lexer behind them.
+//!
+//! Each [`Language`] lists its word classes (keywords, types, ...) and its
+//! comment and string delimiters. This is deliberately simpler than a real
+//! grammar: it gets the common cases right (keywords, literals, comments,
+//! strings, function calls) without any per-language code.
+
+use super::{SourceLine, Token, TokenType, split_lines};
+
+/// Characters that form operator tokens.
+const OPERATOR_CHARS: &str = "+-*/%=<>!&|^~?:";
+
+/// A language the lexer knows how to highlight.
+#[derive(Debug)]
+pub struct Language {
+    /// Canonical name, used as the code block class in HTML output.
+    pub name: &'static str,
+    /// Other names the language is known by (matched case-insensitively).
+    pub aliases: &'static [&'static str],
+    pub keywords: &'static [&'static str],
+    pub control_flow: &'static [&'static str],
+    pub imports: &'static [&'static str],
+    pub types: &'static [&'static str],
+    pub constants: &'static [&'static str],
+    pub builtins: &'static [&'static str],
+    /// Prefixes that start a comment running to the end of the line.
+    pub line_comments: &'static [&'static str],
+    /// Opening and closing delimiters of block comments.
+    pub block_comment: Option<(&'static str, &'static str)>,
+    /// String delimiters, longest first (e.g. `"""` before `"`).
+    pub strings: &'static [&'static str],
+    /// Whether `'x'` is a character literal (rather than a string).
+    pub char_literals: bool,
+    /// Extra characters allowed inside identifiers (e.g. `.` in R).
+    pub identifier_chars: &'static str,
+    /// Prefix of variable references, such as `$` in shell scripts.
+    pub variable_prefix: Option<char>,
+    /// Prefix of attributes or decorators, such as `@` in Python.
+    pub attribute_prefix: Option<char>,
+    /// Prefix of preprocessor lines, such as `#` in C.
+    pub preprocessor_prefix: Option<char>,
+    /// Whether words are matched case-insensitively (e.g. SQL).
+    pub case_insensitive: bool,
+}
+
+const BASE: Language = Language {
+    name: "",
+    aliases: &[],
+    keywords: &[],
+    control_flow: &[],
+    imports: &[],
+    types: &[],
+    constants: &[],
+    builtins: &[],
+    line_comments: &[],
+    block_comment: None,
+    strings: &["\"", "'"],
+    char_literals: false,
+    identifier_chars: "",
+    variable_prefix: None,
+    attribute_prefix: None,
+    preprocessor_prefix: None,
+    case_insensitive: false,
+};
+
+/// All supported languages.
+pub static LANGUAGES: &[Language] = &[
+    Language {
+        name: "python",
+        aliases: &["py", "python3", "ipython"],
+        keywords: &[
+            "and", "as", "assert", "async", "await", "class", "def", "del", "global", "in", "is",
+            "lambda", "nonlocal", "not", "or", "pass", "with",
+        ],
+        control_flow: &[
+            "break", "continue", "elif", "else", "except", "finally", "for", "if", "raise",
+            "return", "try", "while", "yield",
+        ],
+        imports: &["import", "from"],
+        types: &[
+            "bool",
+            "bytes",
+            "complex",
+            "dict",
+            "float",
+            "frozenset",
+            "int",
+            "list",
+            "object",
+            "set",
+            "str",
+            "tuple",
+        ],
+        constants: &["True", "False", "None"],
+        builtins: &[
+            "abs",
+            "all",
+            "any",
+            "enumerate",
+            "filter",
+            "getattr",
+            "hasattr",
+            "isinstance",
+            "iter",
+            "len",
+            "map",
+            "max",
+            "min",
+            "next",
+            "open",
+            "print",
+            "range",
+            "repr",
+            "reversed",
+            "round",
+            "setattr",
+            "sorted",
+            "sum",
+            "super",
+            "type",
+            "zip",
+        ],
+        line_comments: &["#"],
+        strings: &["\"\"\"", "'''", "\"", "'"],
+        attribute_prefix: Some('@'),
+        ..BASE
+    },
+    Language {
+        name: "r",
+        aliases: &["rscript", "splus"],
+        keywords: &["function", "in"],
+        control_flow: &[
+            "if", "else", "for", "while", "repeat", "break", "next", "return",
+        ],
+        imports: &["library", "require", "requireNamespace"],
+        constants: &[
+            "TRUE",
+            "FALSE",
+            "NULL",
+            "NA",
+            "NaN",
+            "Inf",
+            "NA_integer_",
+            "NA_real_",
+            "NA_character_",
+        ],
+        builtins: &[
+            "apply",
+            "c",
+            "cat",
+            "data.frame",
+            "head",
+            "is.na",
+            "lapply",
+            "length",
+            "list",
+            "matrix",
+            "mean",
+            "names",
+            "paste",
+            "paste0",
+            "print",
+            "rep",
+            "sapply",
+            "seq",
+            "sum",
+            "tail",
+            "vapply",
+        ],
+        line_comments: &["#"],
+        identifier_chars: ".",
+        ..BASE
+    },
+    Language {
+        name: "julia",
+        aliases: &["jl"],
+        keywords: &[
+            "abstract",
+            "baremodule",
+            "begin",
+            "const",
+            "do",
+            "end",
+            "export",
+            "function",
+            "global",
+            "in",
+            "isa",
+            "let",
+            "local",
+            "macro",
+            "module",
+            "mutable",
+            "primitive",
+            "quote",
+            "struct",
+            "type",
+            "where",
+        ],
+        control_flow: &[
+            "if", "elseif", "else", "for", "while", "break", "continue", "return", "try", "catch",
+            "finally",
+        ],
+        imports: &["import", "using"],
+        types: &[
+            "Any", "Array", "Bool", "Char", "Dict", "Float32", "Float64", "Int", "Int8", "Int16",
+            "Int32", "Int64", "Matrix", "Nothing", "String", "UInt8", "Vector",
+        ],
+        constants: &["true", "false", "nothing", "missing", "NaN", "Inf"],
+        builtins: &[
+            "collect", "filter", "length", "map", "print", "println", "push!", "sum", "typeof",
+        ],
+        line_comments: &["#"],
+        block_comment: Some(("#=", "=#")),
+        strings: &["\"\"\"", "\""],
+        char_literals: true,
+        identifier_chars: "!",
+        attribute_prefix: Some('@'),
+        ..BASE
+    },
+    Language {
+        name: "rust",
+        aliases: &["rs"],
+        keywords: &[
+            "as", "async", "await", "const", "crate", "dyn", "enum", "extern", "fn", "impl", "in",
+            "let", "mod", "move", "mut", "pub", "ref", "self", "Self", "static", "struct", "super",
+            "trait", "type", "unsafe", "where",
+        ],
+        control_flow: &[
+            "break", "continue", "else", "for", "if", "loop", "match", "return", "while",
+        ],
+        imports: &["use"],
+        types: &[
+            "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "u8",
+            "u16", "u32", "u64", "u128", "usize", "Box", "Option", "Result", "String", "Vec",
+        ],
+        constants: &["true", "false", "None", "Some", "Ok", "Err"],
+        builtins: &[
+            "assert",
+            "assert_eq",
+            "eprintln",
+            "format",
+            "panic",
+            "print",
+            "println",
+            "vec",
+            "write",
+            "writeln",
+        ],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        strings: &["\""],
+        char_literals: true,
+        ..BASE
+    },
+    Language {
+        name: "javascript",
+        aliases: &["js", "jsx", "node", "typescript", "ts", "tsx", "ojs"],
+        keywords: &[
+            "async",
+            "await",
+            "class",
+            "const",
+            "delete",
+            "enum",
+            "extends",
+            "function",
+            "implements",
+            "in",
+            "instanceof",
+            "interface",
+            "let",
+            "new",
+            "of",
+            "static",
+            "super",
+            "this",
+            "type",
+            "typeof",
+            "var",
+            "void",
+            "yield",
+        ],
+        control_flow: &[
+            "break", "case", "catch", "continue", "default", "do", "else", "finally", "for", "if",
+            "return", "switch", "throw", "try", "while",
+        ],
+        imports: &["import", "export", "from", "require"],
+        types: &[
+            "Array", "Boolean", "Date", "Error", "Map", "Number", "Object", "Promise", "RegExp",
+            "Set", "String", "any", "boolean", "never", "number", "string", "unknown",
+        ],
+        constants: &["true", "false", "null", "undefined", "NaN", "Infinity"],
+        builtins: &[
+            "JSON",
+            "Math",
+            "console",
+            "document",
+            "parseFloat",
+            "parseInt",
+            "setTimeout",
+            "window",
+        ],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        strings: &["\"", "'", "`"],
+        ..BASE
+    },
+    Language {
+        name: "bash",
+        aliases: &["sh", "shell", "zsh", "console"],
+        keywords: &[
+            "alias", "declare", "export", "function", "in", "local", "readonly", "source", "unset",
+        ],
+        control_flow: &[
+            "if", "then", "else", "elif", "fi", "for", "while", "until", "do", "done", "case",
+            "esac", "return", "exit", "break", "continue",
+        ],
+        constants: &["true", "false"],
+        builtins: &[
+            "awk", "cat", "cd", "cp", "echo", "eval", "exec", "grep", "ls", "mkdir", "mv",
+            "printf", "pwd", "read", "rm", "sed", "set", "shift", "test",
+        ],
+        line_comments: &["#"],
+        variable_prefix: Some('$'),
+        ..BASE
+    },
+    Language {
+        name: "cpp",
+        aliases: &["c", "c++", "cc", "h", "hpp", "objectivec"],
+        keywords: &[
+            "class",
+            "const",
+            "constexpr",
+            "delete",
+            "enum",
+            "explicit",
+            "extern",
+            "friend",
+            "inline",
+            "mutable",
+            "namespace",
+            "new",
+            "noexcept",
+            "operator",
+            "private",
+            "protected",
+            "public",
+            "register",
+            "sizeof",
+            "static",
+            "struct",
+            "template",
+            "this",
+            "typedef",
+            "typename",
+            "union",
+            "using",
+            "virtual",
+            "volatile",
+        ],
+        control_flow: &[
+            "break", "case", "catch", "continue", "default", "do", "else", "for", "goto", "if",
+            "return", "switch", "throw", "try", "while",
+        ],
+        types: &[
+            "auto", "bool", "char", "double", "float", "int", "int8_t", "int16_t", "int32_t",
+            "int64_t", "long", "short", "signed", "size_t", "uint8_t", "uint16_t", "uint32_t",
+            "uint64_t", "unsigned", "void",
+        ],
+        constants: &["true", "false", "NULL", "nullptr"],
+        builtins: &["cin", "cout", "endl", "free", "malloc", "printf"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        strings: &["\""],
+        char_literals: true,
+        preprocessor_prefix: Some('#'),
+        ..BASE
+    },
+    Language {
+        name: "sql",
+        aliases: &["mysql", "postgresql", "sqlite"],
+        keywords: &[
+            "all",
+            "alter",
+            "and",
+            "as",
+            "asc",
+            "between",
+            "by",
+            "case",
+            "create",
+            "delete",
+            "desc",
+            "distinct",
+            "drop",
+            "else",
+            "end",
+            "exists",
+            "foreign",
+            "from",
+            "full",
+            "group",
+            "having",
+            "in",
+            "index",
+            "inner",
+            "insert",
+            "into",
+            "is",
+            "join",
+            "key",
+            "left",
+            "like",
+            "limit",
+            "not",
+            "offset",
+            "on",
+            "or",
+            "order",
+            "outer",
+            "primary",
+            "references",
+            "right",
+            "select",
+            "set",
+            "table",
+            "then",
+            "union",
+            "update",
+            "values",
+            "view",
+            "when",
+            "where",
+            "with",
+        ],
+        types: &[
+            "bigint",
+            "boolean",
+            "char",
+            "date",
+            "decimal",
+            "double",
+            "float",
+            "int",
+            "integer",
+            "numeric",
+            "real",
+            "smallint",
+            "text",
+            "timestamp",
+            "varchar",
+        ],
+        constants: &["null", "true", "false"],
+        builtins: &["avg", "coalesce", "count", "max", "min", "sum"],
+        line_comments: &["--"],
+        block_comment: Some(("/*", "*/")),
+        case_insensitive: true,
+        ..BASE
+    },
+    Language {
+        name: "lua",
+        aliases: &[],
+        keywords: &[
+            "and", "do", "end", "function", "in", "local", "not", "or", "then",
+        ],
+        control_flow: &[
+            "break", "else", "elseif", "for", "goto", "if", "repeat", "return", "until", "while",
+        ],
+        imports: &["require"],
+        constants: &["true", "false", "nil"],
+        builtins: &[
+            "ipairs", "math", "pairs", "print", "string", "table", "tonumber", "tostring", "type",
+        ],
+        line_comments: &["--"],
+        block_comment: Some(("--[[", "]]")),
+        ..BASE
+    },
+];
+
+/// Look up a language by name or alias, ignoring case.
+pub fn find_language(name: &str) -> Option<&'static Language> {
+    LANGUAGES.iter().find(|language| language.is_named(name))
+}
+
+fn starts_with_at(chars: &[char], pos: usize, prefix: &str) -> bool {
+    prefix
+        .chars()
+        .enumerate()
+        .all(|(offset, c)| chars.get(pos + offset) == Some(&c))
+}
+
+fn line_end(chars: &[char], pos: usize) -> usize {
+    chars[pos..]
+        .iter()
+        .position(|c| *c == '\n')
+        .map_or(chars.len(), |offset| pos + offset)
+}
+
+impl Language {
+    /// Whether `name` is this language's name or one of its aliases.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+            || self
+                .aliases
+                .iter()
+                .any(|alias| alias.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether a code block class selects this language. Quarto's executable
+    /// cell syntax (`{python}`) is accepted as well as plain names.
+    pub fn matches_class(&self, class: &str) -> bool {
+        self.is_named(class.trim_start_matches('{').trim_end_matches('}'))
+    }
+
+    fn is_identifier_start(&self, c: char) -> bool {
+        c.is_alphabetic() || c == '_'
+    }
+
+    fn is_identifier_char(&self, c: char) -> bool {
+        c.is_alphanumeric() || c == '_' || self.identifier_chars.contains(c)
+    }
+
+    fn word_in(&self, word: &str, list: &[&str]) -> bool {
+        if self.case_insensitive {
+            list.iter().any(|entry| entry.eq_ignore_ascii_case(word))
+        } else {
+            list.contains(&word)
+        }
+    }
+
+    fn classify_word(&self, word: &str, is_call: bool) -> TokenType {
+        if self.word_in(word, self.control_flow) {
+            TokenType::ControlFlow
+        } else if self.word_in(word, self.imports) {
+            TokenType::Import
+        } else if self.word_in(word, self.keywords) {
+            TokenType::Keyword
+        } else if self.word_in(word, self.types) {
+            TokenType::DataType
+        } else if self.word_in(word, self.constants) {
+            TokenType::Constant
+        } else if self.word_in(word, self.builtins) {
+            TokenType::BuiltIn
+        } else if is_call {
+            TokenType::Function
+        } else {
+            TokenType::Normal
+        }
+    }
+
+    /// End of a string whose opening delimiter ends just before `pos`.
+    fn string_end(&self, chars: &[char], mut pos: usize, delimiter: &str) -> usize {
+        while pos < chars.len() {
+            if chars[pos] == '\\' {
+                pos += 2;
+            } else if starts_with_at(chars, pos, delimiter) {
+                return pos + delimiter.chars().count();
+            } else {
+                pos += 1;
+            }
+        }
+        chars.len()
+    }
+
+    /// End of a character literal starting at `pos`, if there is one.
+    fn char_literal_end(&self, chars: &[char], pos: usize) -> Option<usize> {
+        let close = if chars.get(pos + 1) == Some(&'\\') {
+            let escape_end = chars[pos + 2..].iter().take(10).position(|c| *c == '\'')?;
+            pos + 2 + escape_end
+        } else {
+            pos + 2
+        };
+        (chars.get(close) == Some(&'\'') && chars.get(pos + 1) != Some(&'\'')).then_some(close + 1)
+    }
+
+    /// End of a numeric literal starting at `pos`, and whether it is an
+    /// integer, a float or a base-N literal.
+    fn number_end(&self, chars: &[char], pos: usize) -> (usize, TokenType) {
+        let mut i = pos;
+        let mut token_type = TokenType::DecVal;
+        let is_digit = |i: usize| chars.get(i).is_some_and(|c| c.is_ascii_digit());
+
+        if chars[i] == '0'
+            && matches!(chars.get(i + 1), Some('x' | 'X' | 'b' | 'B' | 'o' | 'O'))
+            && chars.get(i + 2).is_some_and(|c| c.is_ascii_hexdigit())
+        {
+            i += 2;
+            while chars
+                .get(i)
+                .is_some_and(|c| c.is_ascii_hexdigit() || *c == '_')
+            {
+                i += 1;
+            }
+            token_type = TokenType::BaseN;
+        } else {
+            while chars
+                .get(i)
+                .is_some_and(|c| c.is_ascii_digit() || *c == '_')
+            {
+                i += 1;
+            }
+            if chars.get(i) == Some(&'.') && is_digit(i + 1) {
+                i += 1;
+                while is_digit(i) {
+                    i += 1;
+                }
+                token_type = TokenType::Float;
+            }
+            if matches!(chars.get(i), Some('e' | 'E'))
+                && (is_digit(i + 1)
+                    || (matches!(chars.get(i + 1), Some('+' | '-')) && is_digit(i + 2)))
+            {
+                i += 2;
+                while is_digit(i) {
+                    i += 1;
+                }
+                token_type = TokenType::Float;
+            }
+        }
+
+        // Type suffixes such as `10L`, `1u32` or `2.0f64`
+        while chars.get(i).is_some_and(|c| c.is_alphanumeric()) {
+            i += 1;
+        }
+        (i, token_type)
+    }
+
+    /// Split `code` into highlighted lines.
+    pub fn tokenize(&self, code: &str) -> Vec<SourceLine> {
+        let chars: Vec<char> = code.chars().collect();
+        let mut tokens = Vec::new();
+        let mut at_line_start = true;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let (end, token_type) = if at_line_start && Some(c) == self.preprocessor_prefix {
+                (line_end(&chars, i), TokenType::Preprocessor)
+            } else if let Some((open, close)) = self.block_comment
+                && starts_with_at(&chars, i, open)
+            {
+                let body_start = i + open.chars().count();
+                let end = (body_start..chars.len())
+                    .find(|j| starts_with_at(&chars, *j, close))
+                    .map_or(chars.len(), |j| j + close.chars().count());
+                (end, TokenType::Comment)
+            } else if self
+                .line_comments
+                .iter()
+                .any(|prefix| starts_with_at(&chars, i, prefix))
+            {
+                (line_end(&chars, i), TokenType::Comment)
+            } else if let Some(end) = (self.char_literals && c == '\'')
+                .then(|| self.char_literal_end(&chars, i))
+                .flatten()
+            {
+                (end, TokenType::Char)
+            } else if let Some(delimiter) = self
+                .strings
+                .iter()
+                .find(|delimiter| starts_with_at(&chars, i, delimiter))
+            {
+                let body_start = i + delimiter.chars().count();
+                (
+                    self.string_end(&chars, body_start, delimiter),
+                    TokenType::String,
+                )
+            } else if c.is_ascii_digit()
+                || (c == '.' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()))
+            {
+                if c == '.' {
+                    let (end, _) = self.number_end(&chars, i + 1);
+                    (end, TokenType::Float)
+                } else {
+                    self.number_end(&chars, i)
+                }
+            } else if Some(c) == self.variable_prefix && chars.get(i + 1) == Some(&'{') {
+                let end = chars[i..]
+                    .iter()
+                    .position(|c| *c == '}')
+                    .map_or(chars.len(), |offset| i + offset + 1);
+                (end, TokenType::Variable)
+            } else if (Some(c) == self.variable_prefix || Some(c) == self.attribute_prefix)
+                && chars
+                    .get(i + 1)
+                    .is_some_and(|c| self.is_identifier_start(*c) || c.is_ascii_digit())
+            {
+                let mut end = i + 1;
+                while end < chars.len()
+                    && (self.is_identifier_char(chars[end])
+                        || (Some(c) == self.attribute_prefix && chars[end] == '.'))
+                {
+                    end += 1;
+                }
+                let token_type = if Some(c) == self.variable_prefix {
+                    TokenType::Variable
+                } else {
+                    TokenType::Attribute
+                };
+                (end, token_type)
+            } else if self.is_identifier_start(c) {
+                let mut end = i + 1;
+                while end < chars.len() && self.is_identifier_char(chars[end]) {
+                    end += 1;
+                }
+                let word: String = chars[i..end].iter().collect();
+                let is_call = chars.get(end) == Some(&'(');
+                (end, self.classify_word(&word, is_call))
+            } else if OPERATOR_CHARS.contains(c) {
+                let mut end = i + 1;
+                while end < chars.len() && OPERATOR_CHARS.contains(chars[end]) {
+                    end += 1;
+                }
+                (end, TokenType::Operator)
+            } else {
+                (i + 1, TokenType::Normal)
+            };
+
+            let text: String = chars[i..end].iter().collect();
+            at_line_start = text.ends_with('\n') || (at_line_start && text.trim().is_empty());
+            tokens.push(Token { token_type, text });
+            i = end;
+        }
+
+        split_lines(tokens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_types(language: &str, code: &str) -> Vec<(TokenType, String)> {
+        find_language(language)
+            .unwrap()
+            .tokenize(code)
+            .into_iter()
+            .flatten()
+            .filter(|token| token.token_type != TokenType::Normal)
+            .map(|token| (token.token_type, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_find_language_by_alias() {
+        assert_eq!(find_language("PY").unwrap().name, "python");
+        assert_eq!(find_language("ts").unwrap().name, "javascript");
+        assert!(find_language("klingon").is_none());
+    }
+
+    #[test]
+    fn test_r_identifiers_with_dots() {
+        let tokens = token_types("r", "x <- is.na(y) # check");
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenType::Operator, "<-".to_string()),
+                (TokenType::BuiltIn, "is.na".to_string()),
+                (TokenType::Comment, "# check".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_char_literals_and_lifetimes() {
+        let tokens = token_types("rust", "fn f<'a>(c: char) { '\\n' }");
+        assert!(tokens.contains(&(TokenType::Char, "'\\n'".to_string())));
+        assert!(!tokens.iter().any(|(_, text)| text.starts_with("'a")));
+    }
+
+    #[test]
+    fn test_block_comment_spans_lines() {
+        let lines = find_language("cpp").unwrap().tokenize("/* a\nb */ int x;");
+        assert_eq!(lines[0][0].token_type, TokenType::Comment);
+        assert_eq!(lines[1][0].token_type, TokenType::Comment);
+        assert_eq!(lines[1][0].text, "b */");
+    }
+
+    #[test]
+    fn test_preprocessor_and_shell_variables() {
+        let tokens = token_types("c", "#include <stdio.h>");
+        assert_eq!(
+            tokens,
+            vec![(TokenType::Preprocessor, "#include <stdio.h>".to_string())]
+        );
+        let tokens = token_types("bash", "echo \"$HOME\" ${PATH}");
+        assert!(tokens.contains(&(TokenType::Variable, "${PATH}".to_string())));
+    }
+
+    #[test]
+    fn test_sql_is_case_insensitive() {
+        let tokens = token_types("sql", "SELECT count(*) FROM t");
+        assert_eq!(tokens[0], (TokenType::Keyword, "SELECT".to_string()));
+        assert_eq!(tokens[1], (TokenType::BuiltIn, "count".to_string()));
+    }
+}
@@ -0,0 +1,288 @@
+/*
+ * highlighting/mod.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Syntax highlighting for code blocks.
+//!
+//! Code is split into [`Token`]s using a small table-driven lexer (see
+//! [`languages`]). Token types mirror Pandoc's (skylighting's), and each has
+//! the short CSS class Pandoc emits (`kw`, `st`, `co`, ...), so the HTML
+//! writer's output works with Pandoc highlighting stylesheets and vice versa.
+//!
+//! Writers consult [`highlight`] and render the tokens in their own syntax;
+//! the colors for a named `highlight-style` come from [`styles`].
+
+pub mod languages;
+pub mod styles;
+
+pub use languages::{Language, find_language};
+pub use styles::{HighlightStyle, Rgb};
+
+/// The kind of a highlighted token, matching Pandoc's token types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TokenType {
+    Normal,
+    Keyword,
+    DataType,
+    DecVal,
+    BaseN,
+    Float,
+    Constant,
+    Char,
+    SpecialChar,
+    String,
+    VerbatimString,
+    SpecialString,
+    Import,
+    Comment,
+    Documentation,
+    Annotation,
+    CommentVar,
+    Other,
+    Function,
+    Variable,
+    ControlFlow,
+    Operator,
+    BuiltIn,
+    Extension,
+    Preprocessor,
+    Attribute,
+    RegionMarker,
+    Information,
+    Warning,
+    Alert,
+    Error,
+}
+
+impl TokenType {
+    /// Every token type, in Pandoc's stylesheet order.
+    pub const ALL: [TokenType; 31] = [
+        TokenType::Alert,
+        TokenType::Annotation,
+        TokenType::Attribute,
+        TokenType::BaseN,
+        TokenType::BuiltIn,
+        TokenType::ControlFlow,
+        TokenType::Char,
+        TokenType::Constant,
+        TokenType::Comment,
+        TokenType::CommentVar,
+        TokenType::Documentation,
+        TokenType::DataType,
+        TokenType::DecVal,
+        TokenType::Error,
+        TokenType::Extension,
+        TokenType::Float,
+        TokenType::Function,
+        TokenType::Import,
+        TokenType::Information,
+        TokenType::Keyword,
+        TokenType::Operator,
+        TokenType::Other,
+        TokenType::Preprocessor,
+        TokenType::RegionMarker,
+        TokenType::SpecialChar,
+        TokenType::SpecialString,
+        TokenType::String,
+        TokenType::Variable,
+        TokenType::VerbatimString,
+        TokenType::Warning,
+        TokenType::Normal,
+    ];
+
+    /// The CSS class Pandoc uses for this token type, or `None` for plain text.
+    pub fn css_class(self) -> Option<&'static str> {
+        Some(match self {
+            TokenType::Normal => return None,
+            TokenType::Keyword => "kw",
+            TokenType::DataType => "dt",
+            TokenType::DecVal => "dv",
+            TokenType::BaseN => "bn",
+            TokenType::Float => "fl",
+            TokenType::Constant => "cn",
+            TokenType::Char => "ch",
+            TokenType::SpecialChar => "sc",
+            TokenType::String => "st",
+            TokenType::VerbatimString => "vs",
+            TokenType::SpecialString => "ss",
+            TokenType::Import => "im",
+            TokenType::Comment => "co",
+            TokenType::Documentation => "do",
+            TokenType::Annotation => "an",
+            TokenType::CommentVar => "cv",
+            TokenType::Other => "ot",
+            TokenType::Function => "fu",
+            TokenType::Variable => "va",
+            TokenType::ControlFlow => "cf",
+            TokenType::Operator => "op",
+            TokenType::BuiltIn => "bu",
+            TokenType::Extension => "ex",
+            TokenType::Preprocessor => "pp",
+            TokenType::Attribute => "at",
+            TokenType::RegionMarker => "re",
+            TokenType::Information => "in",
+            TokenType::Warning => "wa",
+            TokenType::Alert => "al",
+            TokenType::Error => "er",
+        })
+    }
+
+    /// Human-readable name, used in stylesheet comments.
+    pub fn name(self) -> &'static str {
+        match self {
+            TokenType::Normal => "Normal",
+            TokenType::Keyword => "Keyword",
+            TokenType::DataType => "DataType",
+            TokenType::DecVal => "DecVal",
+            TokenType::BaseN => "BaseN",
+            TokenType::Float => "Float",
+            TokenType::Constant => "Constant",
+            TokenType::Char => "Char",
+            TokenType::SpecialChar => "SpecialChar",
+            TokenType::String => "String",
+            TokenType::VerbatimString => "VerbatimString",
+            TokenType::SpecialString => "SpecialString",
+            TokenType::Import => "Import",
+            TokenType::Comment => "Comment",
+            TokenType::Documentation => "Documentation",
+            TokenType::Annotation => "Annotation",
+            TokenType::CommentVar => "CommentVar",
+            TokenType::Other => "Other",
+            TokenType::Function => "Function",
+            TokenType::Variable => "Variable",
+            TokenType::ControlFlow => "ControlFlow",
+            TokenType::Operator => "Operator",
+            TokenType::BuiltIn => "BuiltIn",
+            TokenType::Extension => "Extension",
+            TokenType::Preprocessor => "Preprocessor",
+            TokenType::Attribute => "Attribute",
+            TokenType::RegionMarker => "RegionMarker",
+            TokenType::Information => "Information",
+            TokenType::Warning => "Warning",
+            TokenType::Alert => "Alert",
+            TokenType::Error => "Error",
+        }
+    }
+}
+
+/// A run of source text with a single token type. Never spans a newline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub token_type: TokenType,
+    pub text: String,
+}
+
+/// A highlighted line of code.
+pub type SourceLine = Vec<Token>;
+
+/// Find the language to highlight a code block with from its classes.
+/// The first class naming a supported language wins.
+pub fn language_for_classes(classes: &[String]) -> Option<&'static Language> {
+    classes.iter().find_map(|class| {
+        languages::LANGUAGES
+            .iter()
+            .find(|language| language.matches_class(class))
+    })
+}
+
+/// Highlight `code` as `language`, returning one token list per line.
+///
+/// Returns `None` if the language is not supported; writers should then
+/// output the code block unhighlighted.
+pub fn highlight(language: &str, code: &str) -> Option<Vec<SourceLine>> {
+    find_language(language).map(|language| language.tokenize(code))
+}
+
+/// Split a flat token stream into lines, merging adjacent tokens of the same
+/// type. A trailing newline does not produce an extra empty line.
+pub(crate) fn split_lines(tokens: Vec<Token>) -> Vec<SourceLine> {
+    let mut lines: Vec<SourceLine> = vec![Vec::new()];
+    for token in tokens {
+        for (i, part) in token.text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Vec::new());
+            }
+            if part.is_empty() {
+                continue;
+            }
+            let line = lines.last_mut().expect("lines is never empty");
+            match line.last_mut() {
+                Some(last) if last.token_type == token.token_type => last.text.push_str(part),
+                _ => line.push(Token {
+                    token_type: token.token_type,
+                    text: part.to_string(),
+                }),
+            }
+        }
+    }
+    if lines.len() > 1 && lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classes_of(lines: &[SourceLine]) -> Vec<(Option<&'static str>, String)> {
+        lines
+            .iter()
+            .flatten()
+            .map(|token| (token.token_type.css_class(), token.text.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_unsupported_language() {
+        assert_eq!(highlight("not-a-language", "x"), None);
+    }
+
+    #[test]
+    fn test_highlight_python() {
+        let lines = highlight("python", "def f(x):\n    return x + 1  # done\n").unwrap();
+        assert_eq!(lines.len(), 2);
+        let tokens = classes_of(&lines);
+        assert!(tokens.contains(&(Some("kw"), "def".to_string())));
+        assert!(tokens.contains(&(Some("fu"), "f".to_string())));
+        assert!(tokens.contains(&(Some("cf"), "return".to_string())));
+        assert!(tokens.contains(&(Some("op"), "+".to_string())));
+        assert!(tokens.contains(&(Some("dv"), "1".to_string())));
+        assert!(tokens.contains(&(Some("co"), "# done".to_string())));
+    }
+
+    #[test]
+    fn test_multiline_string_is_split_per_line() {
+        let lines = highlight("python", "s = \"\"\"a\nb\"\"\"").unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[1],
+            vec![Token {
+                token_type: TokenType::String,
+                text: "b\"\"\"".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        let tokens = classes_of(&highlight("rust", "let x = 0xff + 1.5e3;").unwrap());
+        assert!(tokens.contains(&(Some("bn"), "0xff".to_string())));
+        assert!(tokens.contains(&(Some("fl"), "1.5e3".to_string())));
+    }
+
+    #[test]
+    fn test_language_for_classes() {
+        let classes = vec!["cell-code".to_string(), "{r}".to_string()];
+        assert_eq!(language_for_classes(&classes).unwrap().name, "r");
+        assert!(language_for_classes(&["unknown".to_string()]).is_none());
+    }
+
+    #[test]
+    fn test_empty_lines_are_kept() {
+        let lines = highlight("bash", "echo a\n\necho b").unwrap();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].is_empty());
+    }
+}
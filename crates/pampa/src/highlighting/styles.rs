@@ -0,0 +1,472 @@
+/*
+ * highlighting/styles.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Named highlighting styles (`highlight-style`) and their stylesheets.
+//!
+//! The colors follow Pandoc's built-in styles of the same names, so a
+//! document highlighted here looks the same as one rendered by Pandoc.
+
+// Colors are written as six-digit RGB hex, as in CSS
+#![allow(clippy::unreadable_literal)]
+
+use super::TokenType;
+
+/// An RGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    const fn hex(value: u32) -> Self {
+        Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8)
+    }
+
+    /// CSS notation, e.g. `#007020`.
+    pub fn to_css(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    /// LaTeX `rgb` model notation, e.g. `0.00,0.44,0.13`.
+    pub fn to_latex(self) -> String {
+        format!(
+            "{:.2},{:.2},{:.2}",
+            f64::from(self.0) / 255.0,
+            f64::from(self.1) / 255.0,
+            f64::from(self.2) / 255.0
+        )
+    }
+}
+
+/// How a single token type is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TokenStyle {
+    pub color: Option<Rgb>,
+    pub background: Option<Rgb>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl TokenStyle {
+    const fn color(value: u32) -> Self {
+        TokenStyle {
+            color: Some(Rgb::hex(value)),
+            background: None,
+            bold: false,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    const fn plain() -> Self {
+        TokenStyle {
+            color: None,
+            background: None,
+            bold: false,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    const fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    const fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    const fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    const fn on(mut self, value: u32) -> Self {
+        self.background = Some(Rgb::hex(value));
+        self
+    }
+
+    /// CSS declarations for this style, e.g. `color: #007020; font-weight: bold;`.
+    pub fn to_css(&self) -> String {
+        let mut declarations = Vec::new();
+        if let Some(color) = self.color {
+            declarations.push(format!("color: {};", color.to_css()));
+        }
+        if let Some(background) = self.background {
+            declarations.push(format!("background-color: {};", background.to_css()));
+        }
+        if self.bold {
+            declarations.push("font-weight: bold;".to_string());
+        }
+        if self.italic {
+            declarations.push("font-style: italic;".to_string());
+        }
+        if self.underline {
+            declarations.push("text-decoration: underline;".to_string());
+        }
+        declarations.join(" ")
+    }
+
+    /// Apply this style to the LaTeX `body`, e.g.
+    /// `\textcolor[rgb]{0.00,0.44,0.13}{\textbf{#1}}`.
+    fn to_latex(&self, default_color: Option<Rgb>, body: &str) -> String {
+        let mut latex = body.to_string();
+        if let Some(background) = self.background {
+            latex = format!("\\colorbox[rgb]{{{}}}{{{}}}", background.to_latex(), latex);
+        }
+        if self.italic {
+            latex = format!("\\textit{{{}}}", latex);
+        }
+        if self.bold {
+            latex = format!("\\textbf{{{}}}", latex);
+        }
+        if self.underline {
+            latex = format!("\\underline{{{}}}", latex);
+        }
+        if let Some(color) = self.color.or(default_color) {
+            latex = format!("\\textcolor[rgb]{{{}}}{{{}}}", color.to_latex(), latex);
+        }
+        latex
+    }
+}
+
+/// A named highlighting style.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightStyle {
+    pub name: &'static str,
+    /// Default text color of code blocks.
+    pub text_color: Option<Rgb>,
+    /// Background color of code blocks.
+    pub background_color: Option<Rgb>,
+    /// Styles of individual token types. Missing types are drawn as plain text.
+    pub tokens: &'static [(TokenType, TokenStyle)],
+}
+
+/// Names accepted by [`HighlightStyle::named`].
+pub const STYLE_NAMES: &[&str] = &["pygments", "tango", "monochrome", "breezedark"];
+
+const PYGMENTS: &[(TokenType, TokenStyle)] = &[
+    (TokenType::Alert, TokenStyle::color(0xff0000).bold()),
+    (
+        TokenType::Annotation,
+        TokenStyle::color(0x60a0b0).bold().italic(),
+    ),
+    (TokenType::Attribute, TokenStyle::color(0x7d9029)),
+    (TokenType::BaseN, TokenStyle::color(0x40a070)),
+    (TokenType::BuiltIn, TokenStyle::color(0x008000)),
+    (TokenType::ControlFlow, TokenStyle::color(0x007020).bold()),
+    (TokenType::Char, TokenStyle::color(0x4070a0)),
+    (TokenType::Constant, TokenStyle::color(0x880000)),
+    (TokenType::Comment, TokenStyle::color(0x60a0b0).italic()),
+    (
+        TokenType::CommentVar,
+        TokenStyle::color(0x60a0b0).bold().italic(),
+    ),
+    (
+        TokenType::Documentation,
+        TokenStyle::color(0xba2121).italic(),
+    ),
+    (TokenType::DataType, TokenStyle::color(0x902000)),
+    (TokenType::DecVal, TokenStyle::color(0x40a070)),
+    (TokenType::Error, TokenStyle::color(0xff0000).bold()),
+    (TokenType::Float, TokenStyle::color(0x40a070)),
+    (TokenType::Function, TokenStyle::color(0x06287e)),
+    (TokenType::Import, TokenStyle::color(0x008000).bold()),
+    (
+        TokenType::Information,
+        TokenStyle::color(0x60a0b0).bold().italic(),
+    ),
+    (TokenType::Keyword, TokenStyle::color(0x007020).bold()),
+    (TokenType::Operator, TokenStyle::color(0x666666)),
+    (TokenType::Other, TokenStyle::color(0x007020)),
+    (TokenType::Preprocessor, TokenStyle::color(0xbc7a00)),
+    (TokenType::SpecialChar, TokenStyle::color(0x4070a0)),
+    (TokenType::SpecialString, TokenStyle::color(0xbb6688)),
+    (TokenType::String, TokenStyle::color(0x4070a0)),
+    (TokenType::Variable, TokenStyle::color(0x19177c)),
+    (TokenType::VerbatimString, TokenStyle::color(0x4070a0)),
+    (
+        TokenType::Warning,
+        TokenStyle::color(0x60a0b0).bold().italic(),
+    ),
+];
+
+const TANGO: &[(TokenType, TokenStyle)] = &[
+    (TokenType::Alert, TokenStyle::color(0xef2929)),
+    (
+        TokenType::Annotation,
+        TokenStyle::color(0x8f5902).bold().italic(),
+    ),
+    (TokenType::Attribute, TokenStyle::color(0x204a87)),
+    (TokenType::BaseN, TokenStyle::color(0x0000cf)),
+    (TokenType::ControlFlow, TokenStyle::color(0x204a87).bold()),
+    (TokenType::Char, TokenStyle::color(0x4e9a06)),
+    (TokenType::Constant, TokenStyle::color(0x000000)),
+    (TokenType::Comment, TokenStyle::color(0x8f5902).italic()),
+    (
+        TokenType::CommentVar,
+        TokenStyle::color(0x8f5902).bold().italic(),
+    ),
+    (
+        TokenType::Documentation,
+        TokenStyle::color(0x8f5902).bold().italic(),
+    ),
+    (TokenType::DataType, TokenStyle::color(0x204a87)),
+    (TokenType::DecVal, TokenStyle::color(0x0000cf)),
+    (TokenType::Error, TokenStyle::color(0xa40000).bold()),
+    (TokenType::Float, TokenStyle::color(0x0000cf)),
+    (TokenType::Function, TokenStyle::color(0x204a87).bold()),
+    (
+        TokenType::Information,
+        TokenStyle::color(0x8f5902).bold().italic(),
+    ),
+    (TokenType::Keyword, TokenStyle::color(0x204a87).bold()),
+    (TokenType::Operator, TokenStyle::color(0xce5c00).bold()),
+    (TokenType::Other, TokenStyle::color(0x8f5902)),
+    (
+        TokenType::Preprocessor,
+        TokenStyle::color(0x8f5902).italic(),
+    ),
+    (TokenType::SpecialChar, TokenStyle::color(0xce5c00).bold()),
+    (TokenType::SpecialString, TokenStyle::color(0x4e9a06)),
+    (TokenType::String, TokenStyle::color(0x4e9a06)),
+    (TokenType::Variable, TokenStyle::color(0x000000)),
+    (TokenType::VerbatimString, TokenStyle::color(0x4e9a06)),
+    (
+        TokenType::Warning,
+        TokenStyle::color(0x8f5902).bold().italic(),
+    ),
+];
+
+const MONOCHROME: &[(TokenType, TokenStyle)] = &[
+    (TokenType::Alert, TokenStyle::plain().bold()),
+    (TokenType::Annotation, TokenStyle::plain().italic()),
+    (TokenType::ControlFlow, TokenStyle::plain().bold()),
+    (TokenType::Comment, TokenStyle::plain().italic()),
+    (TokenType::CommentVar, TokenStyle::plain().italic()),
+    (TokenType::Documentation, TokenStyle::plain().italic()),
+    (TokenType::DataType, TokenStyle::plain().underline()),
+    (TokenType::Error, TokenStyle::plain().bold()),
+    (TokenType::Information, TokenStyle::plain().italic()),
+    (TokenType::Keyword, TokenStyle::plain().bold()),
+    (TokenType::Preprocessor, TokenStyle::plain().bold()),
+    (TokenType::Warning, TokenStyle::plain().italic()),
+];
+
+const BREEZEDARK: &[(TokenType, TokenStyle)] = &[
+    (
+        TokenType::Alert,
+        TokenStyle::color(0x95da4c).on(0x4d1f24).bold(),
+    ),
+    (TokenType::Annotation, TokenStyle::color(0x3f8058)),
+    (TokenType::Attribute, TokenStyle::color(0x2980b9)),
+    (TokenType::BaseN, TokenStyle::color(0xf67400)),
+    (TokenType::BuiltIn, TokenStyle::color(0x7f8c8d)),
+    (TokenType::ControlFlow, TokenStyle::color(0xfdbc4b).bold()),
+    (TokenType::Char, TokenStyle::color(0x3daee9)),
+    (TokenType::Constant, TokenStyle::color(0x27aeae).bold()),
+    (TokenType::Comment, TokenStyle::color(0x7a7c7d)),
+    (TokenType::CommentVar, TokenStyle::color(0x7f8c8d)),
+    (TokenType::Documentation, TokenStyle::color(0xa43340)),
+    (TokenType::DataType, TokenStyle::color(0x2980b9)),
+    (TokenType::DecVal, TokenStyle::color(0xf67400)),
+    (TokenType::Error, TokenStyle::color(0xda4453).underline()),
+    (TokenType::Extension, TokenStyle::color(0x0099ff).bold()),
+    (TokenType::Float, TokenStyle::color(0xf67400)),
+    (TokenType::Function, TokenStyle::color(0x8e44ad)),
+    (TokenType::Import, TokenStyle::color(0x27ae60)),
+    (TokenType::Information, TokenStyle::color(0xc45b00)),
+    (TokenType::Keyword, TokenStyle::color(0xcfcfc2).bold()),
+    (TokenType::Operator, TokenStyle::color(0xcfcfc2)),
+    (TokenType::Other, TokenStyle::color(0x27ae60)),
+    (TokenType::Preprocessor, TokenStyle::color(0x27ae60)),
+    (
+        TokenType::RegionMarker,
+        TokenStyle::color(0x2980b9).on(0x153042),
+    ),
+    (TokenType::SpecialChar, TokenStyle::color(0x3daee9)),
+    (TokenType::SpecialString, TokenStyle::color(0xda4453)),
+    (TokenType::String, TokenStyle::color(0xf44f4f)),
+    (TokenType::Variable, TokenStyle::color(0x27aeae)),
+    (TokenType::VerbatimString, TokenStyle::color(0xda4453)),
+    (TokenType::Warning, TokenStyle::color(0xda4453)),
+];
+
+/// Layout rules shared by every style, matching Pandoc's highlighting CSS.
+const BASE_CSS: &str = "\
+pre > code.sourceCode { white-space: pre; position: relative; }
+pre > code.sourceCode > span { line-height: 1.25; }
+pre > code.sourceCode > span:empty { height: 1.2em; }
+.sourceCode { overflow: visible; }
+code.sourceCode > span { color: inherit; text-decoration: inherit; }
+div.sourceCode { margin: 1em 0; }
+pre.sourceCode { margin: 0; }
+@media screen {
+div.sourceCode { overflow: auto; }
+}
+@media print {
+pre > code.sourceCode { white-space: pre-wrap; }
+pre > code.sourceCode > span { display: inline-block; text-indent: -5em; padding-left: 5em; }
+}
+@media screen {
+pre > code.sourceCode > span > a:first-child::before { text-decoration: underline; }
+}
+";
+
+impl Default for HighlightStyle {
+    fn default() -> Self {
+        Self::named("pygments").expect("pygments is a built-in style")
+    }
+}
+
+impl HighlightStyle {
+    /// Look up a built-in style by name (case-insensitive).
+    pub fn named(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        let (name, text_color, background_color, tokens) = match name.as_str() {
+            "pygments" => ("pygments", None, None, PYGMENTS),
+            "tango" => ("tango", None, Some(Rgb::hex(0xf8f8f8)), TANGO),
+            "monochrome" => ("monochrome", None, None, MONOCHROME),
+            "breezedark" => (
+                "breezedark",
+                Some(Rgb::hex(0xcfcfc2)),
+                Some(Rgb::hex(0x232629)),
+                BREEZEDARK,
+            ),
+            _ => return None,
+        };
+        Some(HighlightStyle {
+            name,
+            text_color,
+            background_color,
+            tokens,
+        })
+    }
+
+    /// The style of `token_type`, if it is drawn differently from plain text.
+    pub fn token_style(&self, token_type: TokenType) -> Option<&TokenStyle> {
+        self.tokens
+            .iter()
+            .find(|(candidate, _)| *candidate == token_type)
+            .map(|(_, style)| style)
+    }
+
+    /// The stylesheet for HTML code blocks written with this style.
+    pub fn to_css(&self) -> String {
+        let mut css = String::from(BASE_CSS);
+        let block_style = TokenStyle {
+            color: self.text_color,
+            background: self.background_color,
+            ..TokenStyle::default()
+        };
+        if block_style != TokenStyle::default() {
+            css.push_str(&format!("div.sourceCode {{ {} }}\n", block_style.to_css()));
+            css.push_str("pre.sourceCode { background-color: inherit; }\n");
+        }
+        for token_type in TokenType::ALL {
+            let (Some(class), Some(style)) = (token_type.css_class(), self.token_style(token_type))
+            else {
+                continue;
+            };
+            css.push_str(&format!(
+                "code span.{} {{ {} }} /* {} */\n",
+                class,
+                style.to_css(),
+                token_type.name()
+            ));
+        }
+        css
+    }
+
+    /// The LaTeX preamble for code blocks written with this style: the
+    /// `Shaded` and `Highlighting` environments and one `\<Type>Tok` macro
+    /// per token type, as in Pandoc's `highlighting-macros`.
+    pub fn to_latex(&self) -> String {
+        let mut latex = String::from(
+            "\\usepackage{color}
+\\usepackage{fancyvrb}
+\\newcommand{\\VerbBar}{|}
+\\newcommand{\\VERB}{\\Verb[commandchars=\\\\\\{\\}]}
+\\DefineVerbatimEnvironment{Highlighting}{Verbatim}{commandchars=\\\\\\{\\}}
+% Add ',fontsize=\\small' for more characters per line
+",
+        );
+        match self.background_color {
+            Some(background) => {
+                let Rgb(r, g, b) = background;
+                latex.push_str("\\usepackage{framed}\n");
+                latex.push_str(&format!(
+                    "\\definecolor{{shadecolor}}{{RGB}}{{{},{},{}}}\n",
+                    r, g, b
+                ));
+                latex.push_str("\\newenvironment{Shaded}{\\begin{snugshade}}{\\end{snugshade}}\n");
+            }
+            None => latex.push_str("\\newenvironment{Shaded}{}{}\n"),
+        }
+        let mut macros: Vec<String> = TokenType::ALL
+            .iter()
+            .map(|&token_type| {
+                let style = self.token_style(token_type).copied().unwrap_or_default();
+                format!(
+                    "\\newcommand{{\\{}Tok}}[1]{{{}}}\n",
+                    token_type.name(),
+                    style.to_latex(self.text_color, "#1")
+                )
+            })
+            .collect();
+        macros.sort();
+        latex.extend(macros);
+        latex
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_styles() {
+        for name in STYLE_NAMES {
+            assert_eq!(HighlightStyle::named(name).unwrap().name, *name);
+        }
+        assert_eq!(HighlightStyle::named("Tango").unwrap().name, "tango");
+        assert!(HighlightStyle::named("nonexistent").is_none());
+    }
+
+    #[test]
+    fn test_pygments_css() {
+        let css = HighlightStyle::default().to_css();
+        assert!(css.contains("code span.kw { color: #007020; font-weight: bold; } /* Keyword */"));
+        assert!(css.contains("code span.co { color: #60a0b0; font-style: italic; } /* Comment */"));
+        assert!(!css.contains("div.sourceCode { color"));
+    }
+
+    #[test]
+    fn test_pygments_latex() {
+        let latex = HighlightStyle::default().to_latex();
+        assert!(latex.contains(
+            "\\DefineVerbatimEnvironment{Highlighting}{Verbatim}{commandchars=\\\\\\{\\}}\n"
+        ));
+        assert!(latex.contains("\\newenvironment{Shaded}{}{}\n"));
+        assert!(latex.contains(
+            "\\newcommand{\\KeywordTok}[1]{\\textcolor[rgb]{0.00,0.44,0.13}{\\textbf{#1}}}\n"
+        ));
+        assert!(latex.contains("\\newcommand{\\NormalTok}[1]{#1}\n"));
+        assert!(latex.find("\\AlertTok").unwrap() < latex.find("\\WarningTok").unwrap());
+    }
+
+    #[test]
+    fn test_dark_style_sets_block_colors() {
+        let css = HighlightStyle::named("breezedark").unwrap().to_css();
+        assert!(css.contains("div.sourceCode { color: #cfcfc2; background-color: #232629; }"));
+
+        let latex = HighlightStyle::named("breezedark").unwrap().to_latex();
+        assert!(latex.contains("\\definecolor{shadecolor}{RGB}{35,38,41}\n"));
+        assert!(
+            latex.contains("\\newcommand{\\NormalTok}[1]{\\textcolor[rgb]{0.81,0.81,0.76}{#1}}\n")
+        );
+    }
+}
@@ -0,0 +1,396 @@
+/*
+ * json_api_version.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Conversion between pampa's JSON AST and the JSON AST of specific
+ * pandoc-types versions, for external JSON filters.
+ */
+
+//! pandoc-types API versions for JSON filters.
+//!
+//! pampa's JSON is a superset of Pandoc's: nodes carry source information
+//! (`s`, `attrS`, ...) that filters can ignore, and a few node types exist
+//! only in pampa. Filters built on pandoc-types (or libraries that check
+//! `pandoc-api-version`) reject documents with unknown node types, and
+//! filters written for pandoc-types 1.22 don't know about `Figure`.
+//!
+//! When a filter asks for a specific [`ApiVersion`], [`downgrade`] rewrites
+//! the document into that version's node types before it is sent, and
+//! [`upgrade`] undoes the rewriting on the filter's output. Source
+//! information fields are kept, so filters that preserve unknown fields keep
+//! source tracking intact.
+//!
+//! Nodes that pampa already writes as standard nodes in its JSON:
+//!
+//! | pampa node                  | JSON representation                                       |
+//! |-----------------------------|-----------------------------------------------------------|
+//! | `Shortcode`                 | `Span` with class `quarto-shortcode__`                    |
+//! | `Insert`                    | `Span` with class `critic-insert`                         |
+//! | `Delete`                    | `Span` with class `critic-delete`                         |
+//! | `Highlight`                 | `Span` with class `critic-highlight`                      |
+//! | `EditComment`               | `Span` with class `critic-comment`                        |
+//! | `NoteReference`             | `Span` with class `footnote-ref`, attribute `data-ref`    |
+//! | custom nodes                | `Div`/`Span` with class `__quarto_custom_node`            |
+//!
+//! Nodes rewritten by [`downgrade`] for every version:
+//!
+//! | pampa node                  | pandoc-types representation                               |
+//! |-----------------------------|-----------------------------------------------------------|
+//! | `BlockMetadata`             | empty `Div` with class `__quarto_block_metadata`, the     |
+//! |                             | metadata as JSON in attribute `data-meta`                 |
+//! | `NoteDefinitionPara`        | `Div` with class `__quarto_note_definition`, attribute    |
+//! |                             | `data-note-id`, holding one `Para`                        |
+//! | `NoteDefinitionFencedBlock` | as `NoteDefinitionPara`, plus attribute `data-fenced`     |
+//!
+//! Nodes rewritten by [`downgrade`] for 1.22 only:
+//!
+//! | pandoc-types 1.23 node      | pandoc-types 1.22 representation                          |
+//! |-----------------------------|-----------------------------------------------------------|
+//! | `Figure`                    | `Div` with added class `__quarto_figure`; the caption is  |
+//! |                             | a last child `Div` with class `__quarto_figure_caption`,  |
+//! |                             | the short caption as JSON in attribute `data-short-caption` |
+//!
+//! [`upgrade`] also drops the 1.22 `Null` block, which 1.23 removed.
+
+use serde_json::{Map, Value, json};
+
+const BLOCK_METADATA_CLASS: &str = "__quarto_block_metadata";
+const NOTE_DEFINITION_CLASS: &str = "__quarto_note_definition";
+const FIGURE_CLASS: &str = "__quarto_figure";
+const FIGURE_CAPTION_CLASS: &str = "__quarto_figure_caption";
+
+/// A pandoc-types API version a JSON filter can request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiVersion {
+    /// pandoc-types 1.22 (Pandoc 2.11 to 2.19)
+    V1_22,
+    /// pandoc-types 1.23 (Pandoc 3.x), the version pampa writes
+    V1_23,
+}
+
+impl ApiVersion {
+    /// Parse a version such as `1.22` or `1.23.1`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.split('.');
+        match (parts.next(), parts.next()) {
+            (Some("1"), Some("22")) => Some(ApiVersion::V1_22),
+            (Some("1"), Some("23")) => Some(ApiVersion::V1_23),
+            _ => None,
+        }
+    }
+
+    /// The version of a document's `pandoc-api-version` field, if it is one
+    /// we know.
+    pub fn of_document(doc: &Value) -> Option<Self> {
+        let version = doc.get("pandoc-api-version")?.as_array()?;
+        match (version.first()?.as_u64()?, version.get(1)?.as_u64()?) {
+            (1, 22) => Some(ApiVersion::V1_22),
+            (1, 23) => Some(ApiVersion::V1_23),
+            _ => None,
+        }
+    }
+
+    /// The `pandoc-api-version` value Pandoc writes for this version.
+    pub fn to_json(self) -> Value {
+        match self {
+            ApiVersion::V1_22 => json!([1, 22, 2, 1]),
+            ApiVersion::V1_23 => json!([1, 23, 1]),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiVersion::V1_22 => write!(f, "1.22"),
+            ApiVersion::V1_23 => write!(f, "1.23"),
+        }
+    }
+}
+
+/// Rewrite a pampa JSON document into the node types of `version`.
+pub fn downgrade(doc: &mut Value, version: ApiVersion) {
+    if let Some(blocks) = doc.get_mut("blocks") {
+        walk(blocks, &mut |node| downgrade_node(node, version));
+    }
+    if let Some(meta) = doc.get_mut("meta") {
+        walk(meta, &mut |node| downgrade_node(node, version));
+    }
+    doc["pandoc-api-version"] = version.to_json();
+}
+
+/// Rewrite a document returned by a filter back into pampa's node types.
+///
+/// The document's own `pandoc-api-version` decides which rewrites apply;
+/// `requested` is used if the filter didn't write a version we know.
+pub fn upgrade(doc: &mut Value, requested: ApiVersion) {
+    let version = ApiVersion::of_document(doc).unwrap_or(requested);
+    if let Some(blocks) = doc.get_mut("blocks") {
+        walk(blocks, &mut |node| upgrade_node(node, version));
+    }
+    if let Some(meta) = doc.get_mut("meta") {
+        walk(meta, &mut |node| upgrade_node(node, version));
+    }
+    doc["pandoc-api-version"] = ApiVersion::V1_23.to_json();
+}
+
+/// Apply `f` to every node (object with a `t` field), children first, and
+/// drop `Null` blocks from every list.
+fn walk(value: &mut Value, f: &mut impl FnMut(&mut Map<String, Value>)) {
+    match value {
+        Value::Array(items) => {
+            items.retain(|item| node_tag(item) != Some("Null"));
+            for item in items {
+                walk(item, f);
+            }
+        }
+        Value::Object(obj) => {
+            for child in obj.values_mut() {
+                walk(child, f);
+            }
+            if obj.contains_key("t") {
+                f(obj);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn node_tag(value: &Value) -> Option<&str> {
+    value.get("t")?.as_str()
+}
+
+/// Replace `node` with a `Div`, keeping its source information fields.
+fn make_div(node: &mut Map<String, Value>, attr: Value, content: Vec<Value>) {
+    node.insert("t".to_string(), json!("Div"));
+    node.insert("c".to_string(), json!([attr, content]));
+}
+
+fn downgrade_node(node: &mut Map<String, Value>, version: ApiVersion) {
+    let Some(tag) = node.get("t").and_then(Value::as_str) else {
+        return;
+    };
+    let content = node.get("c").cloned().unwrap_or(Value::Null);
+    match tag {
+        "BlockMetadata" => {
+            let attr = json!([
+                "",
+                [BLOCK_METADATA_CLASS],
+                [["data-meta", content.to_string()]]
+            ]);
+            make_div(node, attr, vec![]);
+        }
+        "NoteDefinitionPara" | "NoteDefinitionFencedBlock" => {
+            let id = content.get(0).cloned().unwrap_or(json!(""));
+            let body = content.get(1).cloned().unwrap_or(json!([]));
+            if tag == "NoteDefinitionPara" {
+                let attr = json!(["", [NOTE_DEFINITION_CLASS], [["data-note-id", id]]]);
+                make_div(node, attr, vec![json!({"t": "Para", "c": body})]);
+            } else {
+                let attr = json!([
+                    "",
+                    [NOTE_DEFINITION_CLASS],
+                    [["data-note-id", id], ["data-fenced", "true"]]
+                ]);
+                let blocks = body.as_array().cloned().unwrap_or_default();
+                make_div(node, attr, blocks);
+            }
+        }
+        "Figure" if version == ApiVersion::V1_22 => {
+            let mut attr = content.get(0).cloned().unwrap_or(json!(["", [], []]));
+            if let Some(classes) = attr.get_mut(1).and_then(Value::as_array_mut) {
+                classes.push(json!(FIGURE_CLASS));
+            }
+            let caption = content.get(1).cloned().unwrap_or(json!([null, []]));
+            let mut caption_attributes = vec![];
+            if let Some(short) = caption.get(0).filter(|short| !short.is_null()) {
+                caption_attributes.push(json!(["data-short-caption", short.to_string()]));
+            }
+            let caption_div = json!({
+                "t": "Div",
+                "c": [
+                    ["", [FIGURE_CAPTION_CLASS], caption_attributes],
+                    caption.get(1).cloned().unwrap_or(json!([]))
+                ]
+            });
+            let mut blocks = content
+                .get(2)
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            blocks.push(caption_div);
+            make_div(node, attr, blocks);
+        }
+        _ => {}
+    }
+}
+
+fn upgrade_node(node: &mut Map<String, Value>, version: ApiVersion) {
+    if node.get("t").and_then(Value::as_str) != Some("Div") {
+        return;
+    }
+    let Some([attr, Value::Array(blocks)]) =
+        node.get("c").and_then(Value::as_array).map(Vec::as_slice)
+    else {
+        return;
+    };
+    let (attr, mut blocks) = (attr.clone(), blocks.clone());
+
+    if has_class(&attr, BLOCK_METADATA_CLASS) {
+        let meta = attribute(&attr, "data-meta")
+            .and_then(|meta| serde_json::from_str(meta).ok())
+            .unwrap_or(json!({"t": "MetaMap", "c": []}));
+        node.insert("t".to_string(), json!("BlockMetadata"));
+        node.insert("c".to_string(), meta);
+    } else if has_class(&attr, NOTE_DEFINITION_CLASS) {
+        let id = json!(attribute(&attr, "data-note-id").unwrap_or_default());
+        let single_para = match blocks.as_slice() {
+            [para] if node_tag(para) == Some("Para") => para.get("c").cloned(),
+            _ => None,
+        };
+        match single_para {
+            Some(inlines) if attribute(&attr, "data-fenced").is_none() => {
+                node.insert("t".to_string(), json!("NoteDefinitionPara"));
+                node.insert("c".to_string(), json!([id, inlines]));
+            }
+            _ => {
+                node.insert("t".to_string(), json!("NoteDefinitionFencedBlock"));
+                node.insert("c".to_string(), json!([id, blocks]));
+            }
+        }
+    } else if version == ApiVersion::V1_22 && has_class(&attr, FIGURE_CLASS) {
+        let mut attr = attr;
+        if let Some(classes) = attr.get_mut(1).and_then(Value::as_array_mut) {
+            classes.retain(|class| class != FIGURE_CLASS);
+        }
+        let has_caption = blocks.last().is_some_and(|last| {
+            node_tag(last) == Some("Div")
+                && last
+                    .pointer("/c/0")
+                    .is_some_and(|attr| has_class(attr, FIGURE_CAPTION_CLASS))
+        });
+        let caption = match blocks.pop() {
+            Some(caption_div) if has_caption => {
+                let caption_attr = caption_div.pointer("/c/0").cloned().unwrap_or_default();
+                let short = attribute(&caption_attr, "data-short-caption")
+                    .and_then(|short| serde_json::from_str(short).ok())
+                    .unwrap_or(Value::Null);
+                let long = caption_div.pointer("/c/1").cloned().unwrap_or(json!([]));
+                json!([short, long])
+            }
+            last => {
+                blocks.extend(last);
+                json!([null, []])
+            }
+        };
+        node.insert("t".to_string(), json!("Figure"));
+        node.insert("c".to_string(), json!([attr, caption, blocks]));
+    }
+}
+
+fn has_class(attr: &Value, class: &str) -> bool {
+    attr.get(1)
+        .and_then(Value::as_array)
+        .is_some_and(|classes| classes.iter().any(|c| c == class))
+}
+
+fn attribute<'a>(attr: &'a Value, key: &str) -> Option<&'a str> {
+    attr.get(2)?
+        .as_array()?
+        .iter()
+        .find_map(|pair| match pair.as_array()?.as_slice() {
+            [k, v] if k == key => v.as_str(),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(blocks: Value) -> Value {
+        json!({"pandoc-api-version": [1, 23, 1], "meta": {}, "blocks": blocks})
+    }
+
+    #[test]
+    fn test_parse_versions() {
+        assert_eq!(ApiVersion::parse("1.22"), Some(ApiVersion::V1_22));
+        assert_eq!(ApiVersion::parse("1.23.1"), Some(ApiVersion::V1_23));
+        assert_eq!(ApiVersion::parse("1.21"), None);
+        assert_eq!(ApiVersion::V1_22.to_string(), "1.22");
+        assert_eq!(
+            ApiVersion::of_document(&json!({"pandoc-api-version": [1, 22, 2, 1]})),
+            Some(ApiVersion::V1_22)
+        );
+    }
+
+    #[test]
+    fn test_note_definitions_round_trip() {
+        let original = doc(json!([
+            {"t": "NoteDefinitionPara", "c": ["a", [{"t": "Str", "c": "Note"}]], "s": 3},
+            {"t": "NoteDefinitionFencedBlock", "c": ["b", [{"t": "Para", "c": []}]], "s": 4}
+        ]));
+        let mut converted = original.clone();
+        downgrade(&mut converted, ApiVersion::V1_23);
+        assert_eq!(converted["blocks"][0]["t"], "Div");
+        assert_eq!(converted["blocks"][0]["s"], 3);
+        assert_eq!(converted["blocks"][1]["t"], "Div");
+
+        upgrade(&mut converted, ApiVersion::V1_23);
+        assert_eq!(converted, original);
+    }
+
+    #[test]
+    fn test_block_metadata_round_trip() {
+        let original = doc(json!([
+            {"t": "BlockMetadata", "c": {"t": "MetaMap", "c": [["key", {"t": "MetaString", "c": "v"}]]}}
+        ]));
+        let mut converted = original.clone();
+        downgrade(&mut converted, ApiVersion::V1_23);
+        assert_eq!(converted["blocks"][0]["c"][0][1][0], BLOCK_METADATA_CLASS);
+
+        upgrade(&mut converted, ApiVersion::V1_23);
+        assert_eq!(converted, original);
+    }
+
+    #[test]
+    fn test_figure_round_trip_through_1_22() {
+        let original = doc(json!([{
+            "t": "Figure",
+            "c": [
+                ["fig", ["wide"], []],
+                [[{"t": "Str", "c": "Short"}], [{"t": "Plain", "c": [{"t": "Str", "c": "Long"}]}]],
+                [{"t": "Plain", "c": [{"t": "Image", "c": [["", [], []], [], ["a.png", ""]]}]}]
+            ]
+        }]));
+
+        // 1.23 filters see the Figure unchanged
+        let mut converted = original.clone();
+        downgrade(&mut converted, ApiVersion::V1_23);
+        assert_eq!(converted, original);
+
+        let mut converted = original.clone();
+        downgrade(&mut converted, ApiVersion::V1_22);
+        assert_eq!(converted["pandoc-api-version"], json!([1, 22, 2, 1]));
+        assert_eq!(converted["blocks"][0]["t"], "Div");
+        assert_eq!(
+            converted["blocks"][0]["c"][0][1],
+            json!(["wide", FIGURE_CLASS])
+        );
+        assert_eq!(converted["blocks"][0]["c"][1].as_array().unwrap().len(), 2);
+
+        upgrade(&mut converted, ApiVersion::V1_22);
+        assert_eq!(converted, original);
+    }
+
+    #[test]
+    fn test_upgrade_drops_null_blocks() {
+        let mut filtered = json!({
+            "pandoc-api-version": [1, 22, 2, 1],
+            "meta": {},
+            "blocks": [{"t": "Null"}, {"t": "Para", "c": []}]
+        });
+        upgrade(&mut filtered, ApiVersion::V1_22);
+        assert_eq!(filtered, doc(json!([{"t": "Para", "c": []}])));
+    }
+}
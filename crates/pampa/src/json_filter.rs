@@ -9,6 +9,7 @@
  * extensibility.
  */
 
+use crate::json_api_version::{self, ApiVersion};
 use crate::pandoc::Pandoc;
 use crate::pandoc::ast_context::ASTContext;
 use crate::readers;
@@ -99,6 +100,22 @@ pub fn apply_json_filter(
     context: &ASTContext,
     filter_path: &Path,
     target_format: &str,
+) -> Result<(Pandoc, ASTContext, Vec<DiagnosticMessage>), JsonFilterError> {
+    apply_json_filter_with_api_version(pandoc, context, filter_path, target_format, None)
+}
+
+/// Apply a JSON filter that expects a specific pandoc-types API version.
+///
+/// With `api_version`, the document is converted to that version's node types
+/// before it is sent to the filter, and the filter's output is converted back
+/// (see [`json_api_version`]). Without it, the filter receives pampa's JSON
+/// unchanged.
+pub fn apply_json_filter_with_api_version(
+    pandoc: &Pandoc,
+    context: &ASTContext,
+    filter_path: &Path,
+    target_format: &str,
+    api_version: Option<ApiVersion>,
 ) -> Result<(Pandoc, ASTContext, Vec<DiagnosticMessage>), JsonFilterError> {
     // 1. Serialize document to JSON (including source locations - our format is a
     // superset of Pandoc's, so filters that don't understand source info will ignore it,
@@ -113,6 +130,13 @@ pub fn apply_json_filter(
             JsonFilterError::SerializationError(msgs.join("; "))
         },
     )?;
+    if let Some(version) = api_version {
+        let mut doc: serde_json::Value = serde_json::from_slice(&json_buf)
+            .map_err(|e| JsonFilterError::SerializationError(e.to_string()))?;
+        json_api_version::downgrade(&mut doc, version);
+        json_buf = serde_json::to_vec(&doc)
+            .map_err(|e| JsonFilterError::SerializationError(e.to_string()))?;
+    }
 
     // 2. Spawn the filter subprocess
     let mut child = Command::new(filter_path)
@@ -144,8 +168,14 @@ pub fn apply_json_filter(
     }
 
     // 6. Parse the output JSON
-    let json_output = String::from_utf8(output.stdout)
+    let mut json_output = String::from_utf8(output.stdout)
         .map_err(|_| JsonFilterError::InvalidUtf8Output(filter_path.to_owned()))?;
+    if let Some(version) = api_version {
+        let mut doc: serde_json::Value = serde_json::from_str(&json_output)
+            .map_err(|e| JsonFilterError::JsonParseError(filter_path.to_owned(), e.to_string()))?;
+        json_api_version::upgrade(&mut doc, version);
+        json_output = doc.to_string();
+    }
 
     let (filtered_pandoc, filtered_context) = readers::json::read(&mut json_output.as_bytes())
         .map_err(|e| JsonFilterError::JsonParseError(filter_path.to_owned(), e.to_string()))?;
@@ -378,6 +408,93 @@ sys.exit(1)
         }
     }
 
+    #[test]
+    fn test_filter_with_api_version_1_22() {
+        let dir = TempDir::new().unwrap();
+        // A filter that only understands pandoc-types 1.22 and fails on
+        // anything else, like filters built on older pandoc-types
+        let filter_path = dir.path().join("strict_1_22.py");
+        fs::write(
+            &filter_path,
+            r#"#!/usr/bin/env python3
+import sys
+import json
+
+KNOWN = {"Plain", "Para", "Div", "Str", "Image", "Space"}
+
+def check(obj):
+    if isinstance(obj, dict):
+        if "t" in obj and obj["t"] not in KNOWN:
+            sys.exit("unknown node type " + obj["t"])
+        for v in obj.values():
+            check(v)
+    elif isinstance(obj, list):
+        for item in obj:
+            check(item)
+
+doc = json.load(sys.stdin)
+if doc["pandoc-api-version"][:2] != [1, 22]:
+    sys.exit("unsupported version")
+check(doc["blocks"])
+json.dump(doc, sys.stdout)
+"#,
+        )
+        .unwrap();
+        let mut perms = fs::metadata(&filter_path).unwrap().permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&filter_path, perms).unwrap();
+
+        let str_inline = |text: &str| {
+            crate::pandoc::Inline::Str(crate::pandoc::Str {
+                text: text.to_string(),
+                source_info: quarto_source_map::SourceInfo::default(),
+            })
+        };
+        let pandoc = Pandoc {
+            meta: quarto_pandoc_types::ConfigValue::default(),
+            blocks: vec![crate::pandoc::Block::Figure(crate::pandoc::Figure {
+                attr: ("fig".to_string(), vec![], Default::default()),
+                caption: crate::pandoc::Caption {
+                    short: None,
+                    long: Some(vec![crate::pandoc::Block::Plain(crate::pandoc::Plain {
+                        content: vec![str_inline("Caption")],
+                        source_info: quarto_source_map::SourceInfo::default(),
+                    })]),
+                    source_info: quarto_source_map::SourceInfo::default(),
+                },
+                content: vec![crate::pandoc::Block::Plain(crate::pandoc::Plain {
+                    content: vec![str_inline("Content")],
+                    source_info: quarto_source_map::SourceInfo::default(),
+                })],
+                source_info: quarto_source_map::SourceInfo::default(),
+                attr_source: crate::pandoc::AttrSourceInfo::empty(),
+            })],
+        };
+        let context = ASTContext::new();
+
+        // Without a version, the filter rejects the Figure
+        assert!(apply_json_filter(&pandoc, &context, &filter_path, "html").is_err());
+
+        let (filtered, _, _) = apply_json_filter_with_api_version(
+            &pandoc,
+            &context,
+            &filter_path,
+            "html",
+            Some(ApiVersion::V1_22),
+        )
+        .unwrap();
+
+        match &filtered.blocks[0] {
+            crate::pandoc::Block::Figure(figure) => {
+                assert_eq!(figure.attr.0, "fig");
+                assert!(figure.attr.1.is_empty());
+                assert_eq!(figure.content.len(), 1);
+                assert_eq!(figure.caption.long.as_ref().map(Vec::len), Some(1));
+            }
+            other => panic!("Expected Figure block, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_nonexistent_filter() {
         let pandoc = Pandoc {
@@ -10,6 +10,7 @@
 pub mod errors;
 pub mod filter_context;
 pub mod filters;
+pub mod highlighting;
 #[cfg(feature = "lua-filter")]
 pub mod lua;
 pub mod options;
@@ -18,7 +18,7 @@ use crate::pandoc::ast_context::ASTContext;
 use crate::pandoc::{Block, Inline, Pandoc};
 
 use super::constructors::register_pandoc_namespace;
-use super::mediabag::create_shared_mediabag;
+use super::mediabag::{SharedMediaBag, create_shared_mediabag};
 use super::readwrite::{create_reader_options_table, create_writer_options_table};
 use super::runtime::{NativeRuntime, SystemRuntime};
 use super::types::{LuaBlock, LuaInline, blocks_to_lua_table, inlines_to_lua_table};
@@ -100,11 +100,72 @@ pub fn apply_lua_filter(
     context: &ASTContext,
     filter_path: &Path,
     target_format: &str,
+) -> FilterResult<(Pandoc, ASTContext, Vec<DiagnosticMessage>)> {
+    apply_lua_filter_with_mediabag(
+        pandoc,
+        context,
+        filter_path,
+        target_format,
+        &create_shared_mediabag(),
+    )
+}
+
+/// Apply a single Lua filter to a document, with `pandoc.mediabag` backed by
+/// `mediabag`.
+///
+/// Items the filter inserts or fetches remain in `mediabag` afterwards.
+pub fn apply_lua_filter_with_mediabag(
+    pandoc: &Pandoc,
+    context: &ASTContext,
+    filter_path: &Path,
+    target_format: &str,
+    mediabag: &SharedMediaBag,
 ) -> FilterResult<(Pandoc, ASTContext, Vec<DiagnosticMessage>)> {
     // Read filter file
     let filter_source = std::fs::read_to_string(filter_path)
         .map_err(|e| LuaFilterError::FileReadError(filter_path.to_owned(), e))?;
 
+    let lua = create_lua_state(filter_path, target_format, mediabag)?;
+
+    // Load and execute filter script
+    lua.load(&filter_source)
+        .set_name(filter_path.to_string_lossy())
+        .exec()?;
+
+    // Get filter functions from globals or return value
+    let filter_table = get_filter_table(&lua)?;
+
+    // Determine traversal mode
+    let walking_order = get_walking_order(&filter_table)?;
+
+    // Apply the filter using the appropriate traversal
+    let filtered_blocks = match walking_order {
+        WalkingOrder::Typewise => apply_typewise_filter(&lua, &filter_table, &pandoc.blocks)?,
+        WalkingOrder::Topdown => apply_topdown_filter(&lua, &filter_table, &pandoc.blocks)?,
+    };
+
+    // Extract any diagnostics emitted by the filter
+    let diagnostics = super::diagnostics::extract_lua_diagnostics(&lua)?;
+
+    // Return filtered document with diagnostics
+    let filtered_pandoc = Pandoc {
+        meta: pandoc.meta.clone(),
+        blocks: filtered_blocks,
+    };
+
+    Ok((filtered_pandoc, context.clone(), diagnostics))
+}
+
+/// Create a Lua state with the `pandoc` and `quarto` namespaces and the
+/// globals Pandoc sets for scripts (`FORMAT`, `PANDOC_VERSION`, ...).
+///
+/// Shared by filters and by other scripts run against documents, such as
+/// shortcodes.
+pub(crate) fn create_lua_state(
+    script_path: &Path,
+    target_format: &str,
+    mediabag: &SharedMediaBag,
+) -> Result<Lua> {
     // Create Lua state
     let lua = Lua::new();
 
@@ -113,12 +174,8 @@ pub fn apply_lua_filter(
     // to allow for sandboxed or WASM runtimes.
     let runtime: Arc<dyn SystemRuntime> = Arc::new(NativeRuntime::new());
 
-    // Create mediabag for storing media items
-    // In the future, this could be pre-populated from the document or passed in
-    let mediabag = create_shared_mediabag();
-
     // Register pandoc namespace with constructors (also registers quarto namespace)
-    register_pandoc_namespace(&lua, runtime, mediabag)?;
+    register_pandoc_namespace(&lua, runtime, mediabag.clone())?;
 
     // Set global variables
     // FORMAT - the target output format (html, latex, etc.)
@@ -139,10 +196,10 @@ pub fn apply_lua_filter(
     api_version_table.set(3, 1)?;
     lua.globals().set("PANDOC_API_VERSION", api_version_table)?;
 
-    // PANDOC_SCRIPT_FILE - path to the current filter script
+    // PANDOC_SCRIPT_FILE - path to the current script
     lua.globals().set(
         "PANDOC_SCRIPT_FILE",
-        filter_path.to_string_lossy().to_string(),
+        script_path.to_string_lossy().to_string(),
     )?;
 
     // PANDOC_READER_OPTIONS - reader options used for the input
@@ -155,33 +212,7 @@ pub fn apply_lua_filter(
     let writer_options = create_writer_options_table(&lua, None)?;
     lua.globals().set("PANDOC_WRITER_OPTIONS", writer_options)?;
 
-    // Load and execute filter script
-    lua.load(&filter_source)
-        .set_name(filter_path.to_string_lossy())
-        .exec()?;
-
-    // Get filter functions from globals or return value
-    let filter_table = get_filter_table(&lua)?;
-
-    // Determine traversal mode
-    let walking_order = get_walking_order(&filter_table)?;
-
-    // Apply the filter using the appropriate traversal
-    let filtered_blocks = match walking_order {
-        WalkingOrder::Typewise => apply_typewise_filter(&lua, &filter_table, &pandoc.blocks)?,
-        WalkingOrder::Topdown => apply_topdown_filter(&lua, &filter_table, &pandoc.blocks)?,
-    };
-
-    // Extract any diagnostics emitted by the filter
-    let diagnostics = super::diagnostics::extract_lua_diagnostics(&lua)?;
-
-    // Return filtered document with diagnostics
-    let filtered_pandoc = Pandoc {
-        meta: pandoc.meta.clone(),
-        blocks: filtered_blocks,
-    };
-
-    Ok((filtered_pandoc, context.clone(), diagnostics))
+    Ok(lua)
 }
 
 /// Apply multiple Lua filters in sequence
@@ -192,17 +223,39 @@ pub fn apply_lua_filters(
     context: ASTContext,
     filter_paths: &[std::path::PathBuf],
     target_format: &str,
+) -> FilterResult<(Pandoc, ASTContext, Vec<DiagnosticMessage>)> {
+    apply_lua_filters_with_mediabag(
+        pandoc,
+        context,
+        filter_paths,
+        target_format,
+        &create_shared_mediabag(),
+    )
+}
+
+/// Apply multiple Lua filters in sequence, sharing one mediabag between them
+/// as Pandoc does.
+///
+/// Callers that write output can collect the media items from `mediabag`
+/// once all filters have run.
+pub fn apply_lua_filters_with_mediabag(
+    pandoc: Pandoc,
+    context: ASTContext,
+    filter_paths: &[std::path::PathBuf],
+    target_format: &str,
+    mediabag: &SharedMediaBag,
 ) -> FilterResult<(Pandoc, ASTContext, Vec<DiagnosticMessage>)> {
     let mut current_pandoc = pandoc;
     let mut current_context = context;
     let mut all_diagnostics = Vec::new();
 
     for filter_path in filter_paths {
-        let (new_pandoc, new_context, diagnostics) = apply_lua_filter(
+        let (new_pandoc, new_context, diagnostics) = apply_lua_filter_with_mediabag(
             &current_pandoc,
             &current_context,
             filter_path,
             target_format,
+            mediabag,
         )?;
         current_pandoc = new_pandoc;
         current_context = new_context;
@@ -1706,6 +1706,70 @@ end
     assert!(diagnostics[1].title.contains("Error from filter 2"));
 }
 
+#[test]
+fn test_mediabag_shared_across_filters() {
+    let dir = TempDir::new().unwrap();
+    let filter1_path = dir.path().join("filter1.lua");
+    let filter2_path = dir.path().join("filter2.lua");
+
+    fs::write(
+        &filter1_path,
+        r#"
+function Str(elem)
+    pandoc.mediabag.insert("data.csv", "text/csv", "a,b")
+    return elem
+end
+"#,
+    )
+    .unwrap();
+
+    fs::write(
+        &filter2_path,
+        r#"
+function Str(elem)
+    local mime, contents = pandoc.mediabag.lookup("data.csv")
+    return pandoc.Str(mime .. ":" .. contents)
+end
+"#,
+    )
+    .unwrap();
+
+    let pandoc = Pandoc {
+        meta: quarto_pandoc_types::ConfigValue::default(),
+        blocks: vec![Block::Paragraph(crate::pandoc::Paragraph {
+            content: vec![Inline::Str(crate::pandoc::Str {
+                text: "test".to_string(),
+                source_info: quarto_source_map::SourceInfo::default(),
+            })],
+            source_info: quarto_source_map::SourceInfo::default(),
+        })],
+    };
+    let context = ASTContext::new();
+    let mediabag = create_shared_mediabag();
+
+    let (filtered, _, _) = apply_lua_filters_with_mediabag(
+        pandoc,
+        context,
+        &[filter1_path, filter2_path],
+        "html",
+        &mediabag,
+    )
+    .unwrap();
+
+    match &filtered.blocks[0] {
+        Block::Paragraph(p) => match &p.content[0] {
+            Inline::Str(s) => assert_eq!(s.text, "text/csv:a,b"),
+            _ => panic!("Expected Str inline"),
+        },
+        _ => panic!("Expected Paragraph block"),
+    }
+    // The caller keeps the items once the filters are done
+    assert_eq!(
+        mediabag.borrow().lookup("data.csv").unwrap().content,
+        b"a,b"
+    );
+}
+
 // ========================================================================
 // Phase 1: Inline element get_field tests (types.rs coverage)
 // ========================================================================
@@ -24,7 +24,12 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
+use crate::filter_context::FilterContext;
+use crate::filters::{Filter, FilterReturn, topdown_traverse_blocks};
+use crate::pandoc::{AttrSourceInfo, Block, Inline, Span};
+
 use super::runtime::SystemRuntime;
+use super::types::{blocks_to_lua_table, lua_table_to_blocks};
 
 /// A single entry in the mediabag
 #[derive(Debug, Clone)]
@@ -154,45 +159,12 @@ pub fn register_pandoc_mediabag(
     mb_table.set(
         "fetch",
         lua.create_function(move |lua, source: String| {
-            // First check if it's already in the mediabag
-            if let Some(entry) = mb.borrow().lookup(&source) {
-                return Ok((
+            match fetch_source(&mb, rt.as_ref(), &source) {
+                Some(entry) => Ok((
                     Value::String(lua.create_string(&entry.mime_type)?),
                     Value::String(lua.create_string(&entry.content)?),
-                ));
-            }
-
-            // Check if it looks like a URL
-            if source.starts_with("http://") || source.starts_with("https://") {
-                // Fetch from URL using runtime
-                match rt.fetch_url(&source) {
-                    Ok((content, mime_type)) => {
-                        // Store in mediabag for future lookups
-                        mb.borrow_mut()
-                            .insert(source.clone(), mime_type.clone(), content.clone());
-                        Ok((
-                            Value::String(lua.create_string(&mime_type)?),
-                            Value::String(lua.create_string(&content)?),
-                        ))
-                    }
-                    Err(_) => Ok((Value::Nil, Value::Nil)),
-                }
-            } else {
-                // Try to read from local file
-                match rt.file_read(Path::new(&source)) {
-                    Ok(content) => {
-                        // Guess MIME type from extension
-                        let mime_type = guess_mime_type(&source);
-                        // Store in mediabag
-                        mb.borrow_mut()
-                            .insert(source.clone(), mime_type.clone(), content.clone());
-                        Ok((
-                            Value::String(lua.create_string(&mime_type)?),
-                            Value::String(lua.create_string(&content)?),
-                        ))
-                    }
-                    Err(_) => Ok((Value::Nil, Value::Nil)),
-                }
+                )),
+                None => Ok((Value::Nil, Value::Nil)),
             }
         })?,
     )?;
@@ -202,15 +174,16 @@ pub fn register_pandoc_mediabag(
     // ═══════════════════════════════════════════════════════════════════════
 
     // fill(doc) - Fills the mediabag with the images in the given document
-    // This is a complex operation that would require walking the document AST
-    // For now, we provide a stub that returns the document unchanged
-    // TODO: Implement full document walking
+    // Images that cannot be fetched are replaced with a Span of class "image"
+    // holding the image description, as in Pandoc.
+    let mb = mediabag.clone();
+    let rt = runtime.clone();
     mb_table.set(
         "fill",
-        lua.create_function(|_, doc: Value| {
-            // Return the document unchanged for now
-            // Full implementation would walk the document, find Image elements,
-            // fetch their sources, and replace unfetchable images with Spans
+        lua.create_function(move |lua, doc: Table| {
+            let blocks = lua_table_to_blocks(lua, doc.get("blocks")?)?;
+            let blocks = fill_blocks(blocks, &mb, rt.as_ref());
+            doc.set("blocks", blocks_to_lua_table(lua, &blocks)?)?;
             Ok(doc)
         })?,
     )?;
@@ -399,6 +372,58 @@ pub fn register_pandoc_mediabag(
     Ok(())
 }
 
+/// Look up `source` in the mediabag, or fetch it from a URL or local file and
+/// store it in the mediabag under `source`.
+fn fetch_source(
+    mediabag: &SharedMediaBag,
+    runtime: &dyn SystemRuntime,
+    source: &str,
+) -> Option<MediaEntry> {
+    if let Some(entry) = mediabag.borrow().lookup(source) {
+        return Some(entry.clone());
+    }
+
+    let (content, mime_type) = if source.starts_with("http://") || source.starts_with("https://") {
+        runtime.fetch_url(source).ok()?
+    } else {
+        let content = runtime.file_read(Path::new(source)).ok()?;
+        (content, guess_mime_type(source))
+    };
+    mediabag
+        .borrow_mut()
+        .insert(source.to_string(), mime_type.clone(), content.clone());
+    Some(MediaEntry { mime_type, content })
+}
+
+/// Fetch the source of every image in `blocks` into the mediabag.
+///
+/// Images whose source can't be fetched become `Span`s with class `image`,
+/// keeping the description and recording the source and title as attributes.
+/// Data URIs are left alone since they carry their own content.
+fn fill_blocks(
+    blocks: Vec<Block>,
+    mediabag: &SharedMediaBag,
+    runtime: &dyn SystemRuntime,
+) -> Vec<Block> {
+    let mut filter = Filter::new().with_image(|image, _ctx| {
+        let (src, title) = &image.target;
+        if src.starts_with("data:") || fetch_source(mediabag, runtime, src).is_some() {
+            return FilterReturn::Unchanged(image);
+        }
+        let mut attributes = image.attr.2.clone();
+        attributes.insert("src".to_string(), src.clone());
+        attributes.insert("title".to_string(), title.clone());
+        let span = Span {
+            attr: (image.attr.0.clone(), vec!["image".to_string()], attributes),
+            content: image.content,
+            source_info: image.source_info,
+            attr_source: AttrSourceInfo::empty(),
+        };
+        FilterReturn::FilterResult(vec![Inline::Span(span)], false)
+    });
+    topdown_traverse_blocks(blocks, &mut filter, &mut FilterContext::new())
+}
+
 /// Guess MIME type from file extension
 fn guess_mime_type(filepath: &str) -> String {
     let path = Path::new(filepath);
@@ -877,6 +902,50 @@ mod tests {
         assert!(matches!(result, Value::Table(_)));
     }
 
+    #[test]
+    fn test_fill_fetches_images() {
+        let lua = Lua::new();
+        let runtime = Arc::new(NativeRuntime::new()) as Arc<dyn SystemRuntime>;
+        let mediabag = create_shared_mediabag();
+        crate::lua::constructors::register_pandoc_namespace(
+            &lua,
+            runtime.clone(),
+            mediabag.clone(),
+        )
+        .unwrap();
+
+        let temp = runtime.temp_dir("mediabag_fill_test").unwrap();
+        let image = temp.path().join("plot.png");
+        std::fs::write(&image, b"png bytes").unwrap();
+        let image_path = image.to_string_lossy().replace('\\', "/");
+
+        let (first, second): (String, String) = lua
+            .load(format!(
+                r#"
+                local doc = {{blocks = {{
+                    pandoc.Para({{
+                        pandoc.Image({{pandoc.Str("found")}}, "{}"),
+                        pandoc.Image({{pandoc.Str("missing")}}, "/nonexistent/123456789.png", "Title"),
+                    }}),
+                }}}}
+                doc = pandoc.mediabag.fill(doc)
+                local para = doc.blocks[1]
+                return para.content[1].t, para.content[2].t
+            "#,
+                image_path
+            ))
+            .eval()
+            .unwrap();
+
+        assert_eq!(first, "Image");
+        assert_eq!(second, "Span");
+        let bag = mediabag.borrow();
+        let entry = bag.lookup(&image_path).unwrap();
+        assert_eq!(entry.mime_type, "image/png");
+        assert_eq!(entry.content, b"png bytes");
+        assert_eq!(bag.len(), 1);
+    }
+
     #[test]
     fn test_fetch_from_mediabag_cache() {
         let (lua, _, mediabag) = create_test_lua();
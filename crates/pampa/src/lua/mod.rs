@@ -17,6 +17,7 @@ pub mod mediabag;
 mod path;
 mod readwrite;
 pub mod runtime;
+pub mod shortcode;
 mod system;
 mod text;
 mod types;
@@ -24,4 +25,6 @@ mod utils;
 
 pub use filter::{LuaFilterError, apply_lua_filters};
 #[allow(unused_imports)]
+pub use filter::{apply_lua_filter_with_mediabag, apply_lua_filters_with_mediabag};
+#[allow(unused_imports)]
 pub use runtime::{NativeRuntime, RuntimeError, RuntimeResult, SystemRuntime};
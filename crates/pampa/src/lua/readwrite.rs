@@ -197,7 +197,7 @@ fn parse_format_spec_from_lua(_lua: &Lua, value: Value) -> Result<ParsedFormat>
 }
 
 /// Phase 5: Convert ConfigValue to a Lua table (loses source info).
-fn config_value_to_lua(lua: &Lua, config: &ConfigValue) -> Result<Value> {
+pub(crate) fn config_value_to_lua(lua: &Lua, config: &ConfigValue) -> Result<Value> {
     match &config.value {
         ConfigValueKind::Scalar(yaml) => match yaml {
             yaml_rust2::Yaml::String(s) => Ok(Value::String(lua.create_string(s)?)),
@@ -418,13 +418,14 @@ fn pandoc_read(lua: &Lua, args: mlua::MultiValue) -> Result<Value> {
         "qmd" => {
             // Use the QMD reader
             let mut stderr = std::io::stderr();
-            match crate::readers::qmd::read(
+            match crate::readers::qmd::read_with_options(
                 &content,
                 false, // loose
                 "<pandoc.read>",
                 &mut stderr,
                 true, // prune_errors
                 None, // parent_source_info
+                &crate::readers::qmd::QmdReaderOptions::from_extensions(&parsed_format.extensions),
             ) {
                 Ok((pandoc, _context, _warnings)) => {
                     // Convert Rust Pandoc to Lua
@@ -556,7 +557,9 @@ fn pandoc_write(lua: &Lua, args: mlua::MultiValue) -> Result<mlua::String> {
         }
         "qmd" => {
             let mut buf = Vec::new();
-            crate::writers::qmd::write(&pandoc, &mut buf).map_err(|e| {
+            let options =
+                crate::writers::qmd::QmdWriterOptions::from_extensions(&parsed_format.extensions);
+            crate::writers::qmd::write_with_options(&pandoc, &mut buf, &options).map_err(|e| {
                 let messages: Vec<String> = e.iter().map(|d| d.title.clone()).collect();
                 Error::runtime(format!(
                     "pandoc.write (qmd) failed: {}",
@@ -566,6 +569,71 @@ fn pandoc_write(lua: &Lua, args: mlua::MultiValue) -> Result<mlua::String> {
             String::from_utf8(buf)
                 .map_err(|e| Error::runtime(format!("Invalid UTF-8 in output: {}", e)))?
         }
+        "gfm" => {
+            // Dropped-node warnings are not fatal for pandoc.write
+            let mut buf = Vec::new();
+            crate::writers::gfm::write(&pandoc, &mut buf).map_err(|e| {
+                let messages: Vec<String> = e.iter().map(|d| d.title.clone()).collect();
+                Error::runtime(format!(
+                    "pandoc.write (gfm) failed: {}",
+                    messages.join("; ")
+                ))
+            })?;
+            String::from_utf8(buf)
+                .map_err(|e| Error::runtime(format!("Invalid UTF-8 in output: {}", e)))?
+        }
+        "latex" => {
+            // Dropped-node warnings are not fatal for pandoc.write
+            let mut buf = Vec::new();
+            crate::writers::latex::write(&pandoc, &mut buf).map_err(|e| {
+                let messages: Vec<String> = e.iter().map(|d| d.title.clone()).collect();
+                Error::runtime(format!(
+                    "pandoc.write (latex) failed: {}",
+                    messages.join("; ")
+                ))
+            })?;
+            String::from_utf8(buf)
+                .map_err(|e| Error::runtime(format!("Invalid UTF-8 in output: {}", e)))?
+        }
+        "rst" => {
+            // Dropped-node warnings are not fatal for pandoc.write
+            let mut buf = Vec::new();
+            crate::writers::rst::write(&pandoc, &mut buf).map_err(|e| {
+                let messages: Vec<String> = e.iter().map(|d| d.title.clone()).collect();
+                Error::runtime(format!(
+                    "pandoc.write (rst) failed: {}",
+                    messages.join("; ")
+                ))
+            })?;
+            String::from_utf8(buf)
+                .map_err(|e| Error::runtime(format!("Invalid UTF-8 in output: {}", e)))?
+        }
+        "typst" => {
+            // Dropped-node warnings are not fatal for pandoc.write
+            let mut buf = Vec::new();
+            crate::writers::typst::write(&pandoc, &mut buf).map_err(|e| {
+                let messages: Vec<String> = e.iter().map(|d| d.title.clone()).collect();
+                Error::runtime(format!(
+                    "pandoc.write (typst) failed: {}",
+                    messages.join("; ")
+                ))
+            })?;
+            String::from_utf8(buf)
+                .map_err(|e| Error::runtime(format!("Invalid UTF-8 in output: {}", e)))?
+        }
+        "org" => {
+            // Dropped-node warnings are not fatal for pandoc.write
+            let mut buf = Vec::new();
+            crate::writers::org::write(&pandoc, &mut buf).map_err(|e| {
+                let messages: Vec<String> = e.iter().map(|d| d.title.clone()).collect();
+                Error::runtime(format!(
+                    "pandoc.write (org) failed: {}",
+                    messages.join("; ")
+                ))
+            })?;
+            String::from_utf8(buf)
+                .map_err(|e| Error::runtime(format!("Invalid UTF-8 in output: {}", e)))?
+        }
         "plain" => {
             // plaintext writer works on blocks, so we write them directly
             let mut buf = Vec::new();
@@ -669,6 +737,11 @@ mod tests {
         assert!(writers.get::<bool>("json").unwrap());
         assert!(writers.get::<bool>("native").unwrap());
         assert!(writers.get::<bool>("qmd").unwrap());
+        assert!(writers.get::<bool>("gfm").unwrap());
+        assert!(writers.get::<bool>("latex").unwrap());
+        assert!(writers.get::<bool>("rst").unwrap());
+        assert!(writers.get::<bool>("typst").unwrap());
+        assert!(writers.get::<bool>("org").unwrap());
         assert!(writers.get::<bool>("plain").unwrap());
     }
 
@@ -0,0 +1,275 @@
+/*
+ * lua/shortcode.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Lua shortcode execution.
+ *
+ * Shortcode scripts (contributed by Quarto extensions) define one function
+ * per shortcode, either in the table the script returns or as globals:
+ *
+ *     return {
+ *       ["hello"] = function(args, kwargs, meta, raw_args, context)
+ *         return pandoc.Str("Hello, " .. pandoc.utils.stringify(args[1]))
+ *       end
+ *     }
+ *
+ * Each function receives the positional arguments, the keyword arguments,
+ * the document metadata, the raw arguments and where the shortcode appears
+ * ("block", "inline" or "text"), and returns inlines, blocks or a string.
+ */
+
+use mlua::{Function, Lua, Table, Value};
+use quarto_error_reporting::DiagnosticMessage;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::pandoc::config_value::ConfigValue;
+use crate::pandoc::{Block, Inline};
+
+use super::filter::{FilterResult, LuaFilterError, create_lua_state};
+use super::mediabag::create_shared_mediabag;
+use super::readwrite::config_value_to_lua;
+use super::types::{LuaBlock, LuaInline};
+
+/// A call of a shortcode implemented in Lua.
+#[derive(Debug, Clone, Copy)]
+pub struct LuaShortcodeCall<'a> {
+    /// Shortcode name
+    pub name: &'a str,
+    /// Positional arguments
+    pub args: &'a [String],
+    /// Keyword arguments, in order
+    pub kwargs: &'a [(String, String)],
+    /// Document metadata
+    pub meta: &'a ConfigValue,
+    /// Where the shortcode appears: "block", "inline" or "text"
+    pub context: &'a str,
+}
+
+/// Content a Lua shortcode resolved to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LuaShortcodeOutput {
+    Inlines(Vec<Inline>),
+    Blocks(Vec<Block>),
+}
+
+/// List the shortcodes a script defines, sorted.
+pub fn lua_shortcode_names(script_path: &Path, target_format: &str) -> FilterResult<Vec<String>> {
+    let lua = create_lua_state(script_path, target_format, &create_shared_mediabag())?;
+    let shortcodes = load_shortcodes(&lua, script_path)?;
+    let mut names = Vec::new();
+    for pair in shortcodes.pairs::<Value, Value>() {
+        if let (Value::String(name), Value::Function(_)) = pair? {
+            names.push(name.to_str()?.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Run a shortcode defined by a script.
+///
+/// Returns the content the shortcode resolved to and the diagnostics it
+/// emitted via `quarto.warn()` or `quarto.error()`. A shortcode returning
+/// nothing resolves to no content; one returning a string resolves to that
+/// text.
+pub fn call_lua_shortcode(
+    script_path: &Path,
+    call: &LuaShortcodeCall,
+    target_format: &str,
+) -> FilterResult<(LuaShortcodeOutput, Vec<DiagnosticMessage>)> {
+    let lua = create_lua_state(script_path, target_format, &create_shared_mediabag())?;
+    let shortcodes = load_shortcodes(&lua, script_path)?;
+    let function: Function = match shortcodes.get::<Value>(call.name)? {
+        Value::Function(function) => function,
+        _ => {
+            return Err(LuaFilterError::InvalidReturn(format!(
+                "'{}' does not define the shortcode '{}'",
+                script_path.display(),
+                call.name
+            )));
+        }
+    };
+
+    let args = lua.create_sequence_from(call.args.iter().map(String::as_str))?;
+    let kwargs = lua.create_table()?;
+    for (key, value) in call.kwargs {
+        kwargs.set(key.as_str(), value.as_str())?;
+    }
+    let meta = config_value_to_lua(&lua, call.meta)?;
+    let raw_args = lua.create_sequence_from(call.args.iter().map(String::as_str))?;
+
+    let result: Value = function.call((args, kwargs, meta, raw_args, call.context))?;
+    let output = shortcode_output(&lua, result)?;
+    let diagnostics = super::diagnostics::extract_lua_diagnostics(&lua)?;
+    Ok((output, diagnostics))
+}
+
+/// Run a shortcode script and return its shortcode functions: the table it
+/// returns, or else the global functions it defined.
+fn load_shortcodes(lua: &Lua, script_path: &Path) -> FilterResult<Table> {
+    let source = std::fs::read_to_string(script_path)
+        .map_err(|e| LuaFilterError::FileReadError(script_path.to_owned(), e))?;
+
+    let globals = lua.globals();
+    let mut existing = HashSet::new();
+    for pair in globals.pairs::<Value, Value>() {
+        if let (Value::String(name), _) = pair? {
+            existing.insert(name.to_str()?.to_string());
+        }
+    }
+
+    let returned: Value = lua
+        .load(&source)
+        .set_name(script_path.to_string_lossy())
+        .eval()?;
+    if let Value::Table(table) = returned {
+        return Ok(table);
+    }
+
+    let shortcodes = lua.create_table()?;
+    for pair in globals.pairs::<Value, Value>() {
+        if let (Value::String(name), Value::Function(function)) = pair? {
+            let name = name.to_str()?.to_string();
+            if !existing.contains(&name) {
+                shortcodes.set(name, function)?;
+            }
+        }
+    }
+    Ok(shortcodes)
+}
+
+/// Convert what a shortcode function returned to content.
+fn shortcode_output(lua: &Lua, value: Value) -> FilterResult<LuaShortcodeOutput> {
+    match value {
+        Value::Nil => Ok(LuaShortcodeOutput::Inlines(Vec::new())),
+        Value::String(_) | Value::Integer(_) | Value::Number(_) | Value::Boolean(_) => {
+            let text = match &value {
+                Value::String(s) => s.to_str()?.to_string(),
+                Value::Integer(i) => i.to_string(),
+                Value::Number(n) => n.to_string(),
+                Value::Boolean(b) => b.to_string(),
+                _ => unreachable!(),
+            };
+            let pandoc: Table = lua.globals().get("pandoc")?;
+            let str_constructor: Function = pandoc.get("Str")?;
+            shortcode_output(lua, str_constructor.call(text)?)
+        }
+        Value::UserData(ud) => {
+            if let Ok(inline) = ud.borrow::<LuaInline>() {
+                Ok(LuaShortcodeOutput::Inlines(vec![inline.0.clone()]))
+            } else if let Ok(block) = ud.borrow::<LuaBlock>() {
+                Ok(LuaShortcodeOutput::Blocks(vec![block.0.clone()]))
+            } else {
+                Err(invalid_output("an unsupported value"))
+            }
+        }
+        Value::Table(table) => {
+            let mut inlines = Vec::new();
+            let mut blocks = Vec::new();
+            for item in table.sequence_values::<Value>() {
+                match item? {
+                    Value::UserData(ud) => {
+                        if let Ok(inline) = ud.borrow::<LuaInline>() {
+                            inlines.push(inline.0.clone());
+                        } else if let Ok(block) = ud.borrow::<LuaBlock>() {
+                            blocks.push(block.0.clone());
+                        } else {
+                            return Err(invalid_output("a list with an unsupported value"));
+                        }
+                    }
+                    _ => return Err(invalid_output("a list with a value that isn't an element")),
+                }
+            }
+            match (inlines.is_empty(), blocks.is_empty()) {
+                (_, true) => Ok(LuaShortcodeOutput::Inlines(inlines)),
+                (true, false) => Ok(LuaShortcodeOutput::Blocks(blocks)),
+                (false, false) => Err(invalid_output("a list mixing inlines and blocks")),
+            }
+        }
+        _ => Err(invalid_output("an unsupported value")),
+    }
+}
+
+fn invalid_output(what: &str) -> LuaFilterError {
+    LuaFilterError::InvalidReturn(format!(
+        "shortcode returned {} (expected inlines, blocks or a string)",
+        what
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pandoc::inline::Str;
+    use tempfile::TempDir;
+
+    fn write_script(dir: &TempDir, source: &str) -> std::path::PathBuf {
+        let path = dir.path().join("shortcodes.lua");
+        std::fs::write(&path, source).unwrap();
+        path
+    }
+
+    fn call<'a>(name: &'a str, args: &'a [String], meta: &'a ConfigValue) -> LuaShortcodeCall<'a> {
+        LuaShortcodeCall {
+            name,
+            args,
+            kwargs: &[],
+            meta,
+            context: "inline",
+        }
+    }
+
+    #[test]
+    fn test_shortcode_names() {
+        let dir = TempDir::new().unwrap();
+        let returned = write_script(
+            &dir,
+            "return { hello = function() return 'hi' end, version = 2 }",
+        );
+        assert_eq!(lua_shortcode_names(&returned, "html").unwrap(), ["hello"]);
+
+        let globals = write_script(
+            &dir,
+            "local helper = 1\nfunction b() end\nfunction a() return helper end\n",
+        );
+        assert_eq!(lua_shortcode_names(&globals, "html").unwrap(), ["a", "b"]);
+    }
+
+    #[test]
+    fn test_call_shortcode() {
+        let dir = TempDir::new().unwrap();
+        let script = write_script(
+            &dir,
+            r#"
+return {
+  hello = function(args, kwargs, meta, raw_args, context)
+    return pandoc.Str("Hello, " .. pandoc.utils.stringify(args[1]) .. " in " .. FORMAT)
+  end,
+  text = function() return 42 end,
+  block = function() return { pandoc.Para({ pandoc.Str("x") }) } end,
+}
+"#,
+        );
+        let meta = ConfigValue::default();
+        let args = ["world".to_string()];
+
+        let (output, _) =
+            call_lua_shortcode(&script, &call("hello", &args, &meta), "html").unwrap();
+        let LuaShortcodeOutput::Inlines(inlines) = output else {
+            panic!("expected inlines");
+        };
+        assert!(
+            matches!(&inlines[..], [Inline::Str(Str { text, .. })] if text == "Hello, world in html")
+        );
+
+        let (output, _) = call_lua_shortcode(&script, &call("text", &[], &meta), "html").unwrap();
+        assert!(matches!(output, LuaShortcodeOutput::Inlines(inlines)
+            if matches!(&inlines[..], [Inline::Str(Str { text, .. })] if text == "42")));
+
+        let (output, _) = call_lua_shortcode(&script, &call("block", &[], &meta), "html").unwrap();
+        assert!(matches!(output, LuaShortcodeOutput::Blocks(blocks) if blocks.len() == 1));
+
+        assert!(call_lua_shortcode(&script, &call("missing", &[], &meta), "html").is_err());
+    }
+}
@@ -6,6 +6,8 @@
  *
  * This module provides the `pandoc.utils` namespace with utility functions
  * like `pandoc.utils.stringify()`.
+ *
+ * Reference: https://pandoc.org/lua-filters.html#module-pandoc.utils
  */
 
 use mlua::{Function, Lua, Result, Table, Value};
@@ -14,6 +16,7 @@ use sha1::{Digest, Sha1};
 use crate::pandoc::{Block, Inline, LineBreak};
 use quarto_source_map::SourceInfo;
 
+use super::constructors::{LuaCaption, LuaCell};
 use super::types::{LuaBlock, LuaInline, filter_source_info, inlines_to_lua_table};
 
 /// Register the pandoc.utils namespace
@@ -737,7 +740,8 @@ fn create_to_roman_numeral(lua: &Lua) -> Result<Function> {
     })
 }
 
-/// Convert a Lua value (block, inline, list of elements) to plain text
+/// Convert a Lua value (block, inline, list of elements, metadata value or
+/// document) to plain text
 fn stringify_value(value: &Value) -> Result<String> {
     match value {
         Value::UserData(ud) => {
@@ -749,10 +753,44 @@ fn stringify_value(value: &Value) -> Result<String> {
             if let Ok(block) = ud.borrow::<LuaBlock>() {
                 return Ok(stringify_block(&block.0));
             }
+            if let Ok(caption) = ud.borrow::<LuaCaption>() {
+                return Ok(caption
+                    .0
+                    .long
+                    .as_deref()
+                    .map(stringify_blocks)
+                    .unwrap_or_default());
+            }
+            if let Ok(cell) = ud.borrow::<LuaCell>() {
+                return Ok(stringify_blocks(&cell.0.content));
+            }
             Ok(String::new())
         }
         Value::Table(table) => {
-            // Handle table of elements
+            // Metadata values are tables tagged with their constructor
+            match table.get::<Option<String>>("t")?.as_deref() {
+                Some("MetaString") => return table.get("text"),
+                Some("MetaBool") => return Ok(table.get::<bool>("value")?.to_string()),
+                Some("MetaInlines" | "MetaBlocks") => {
+                    return stringify_value(&table.get("content")?);
+                }
+                Some("MetaMap") => {
+                    let mut result = String::new();
+                    for pair in table.clone().pairs::<Value, Value>() {
+                        let (key, value) = pair?;
+                        if !matches!(&key, Value::String(k) if k == "t" || k == "tag") {
+                            result.push_str(&stringify_value(&value)?);
+                        }
+                    }
+                    return Ok(result);
+                }
+                _ => {}
+            }
+            // A document
+            if let Value::Table(_) = table.get::<Value>("blocks")? {
+                return stringify_value(&table.get("blocks")?);
+            }
+            // Handle table of elements (including MetaList)
             let mut result = String::new();
             for item in table.clone().sequence_values::<Value>() {
                 let item = item?;
@@ -761,6 +799,9 @@ fn stringify_value(value: &Value) -> Result<String> {
             Ok(result)
         }
         Value::String(s) => Ok(s.to_str()?.to_string()),
+        Value::Boolean(b) => Ok(b.to_string()),
+        Value::Integer(i) => Ok(i.to_string()),
+        Value::Number(n) => Ok(n.to_string()),
         _ => Ok(String::new()),
     }
 }
@@ -999,6 +1040,48 @@ mod tests {
         assert_eq!(result, "plain text");
     }
 
+    #[test]
+    fn test_stringify_meta_values() {
+        let lua = create_test_lua();
+
+        let result: String = lua
+            .load(
+                r#"
+                local title = {t = "MetaInlines", tag = "MetaInlines",
+                               content = {pandoc.Str("A"), pandoc.Space(), pandoc.Str("Title")}}
+                local draft = {t = "MetaBool", tag = "MetaBool", value = false}
+                local list = {t = "MetaList", tag = "MetaList",
+                              {t = "MetaString", tag = "MetaString", text = "x"}, title}
+                return pandoc.utils.stringify(title) .. "|"
+                    .. pandoc.utils.stringify(draft) .. "|"
+                    .. pandoc.utils.stringify(list) .. "|"
+                    .. pandoc.utils.stringify(2)
+            "#,
+            )
+            .eval()
+            .unwrap();
+
+        assert_eq!(result, "A Title|false|xA Title|2");
+    }
+
+    #[test]
+    fn test_stringify_document() {
+        let lua = create_test_lua();
+
+        let result: String = lua
+            .load(
+                r#"
+                local doc = {meta = {}, blocks = {pandoc.Para({pandoc.Str("one")}),
+                                                   pandoc.Para({pandoc.Str("two")})}}
+                return pandoc.utils.stringify(doc)
+            "#,
+            )
+            .eval()
+            .unwrap();
+
+        assert_eq!(result, "one\ntwo");
+    }
+
     #[test]
     fn test_stringify_quoted() {
         let lua = create_test_lua();
@@ -14,6 +14,9 @@ mod citeproc_filter;
 mod errors;
 mod filter_context;
 mod filters;
+mod highlighting;
+#[cfg_attr(not(feature = "json-filter"), allow(dead_code))]
+mod json_api_version;
 #[cfg(feature = "json-filter")]
 mod json_filter;
 #[cfg(feature = "lua-filter")]
@@ -62,6 +65,11 @@ struct Args {
     #[arg(long = "json-source-location", value_parser = ["full"])]
     json_source_location: Option<String>,
 
+    /// Write source positions (`file:line:col-line:col`) into the output:
+    /// `data-pos` attributes in HTML, `{- ... -}` comments in native
+    #[arg(long = "source-positions")]
+    source_positions: bool,
+
     #[arg(short = 'o', long = "output")]
     output: Option<String>,
 
@@ -77,6 +85,9 @@ struct Args {
     /// - "citeproc": built-in citation processor
     /// - *.lua: Lua filter
     /// - anything else: JSON filter (external executable)
+    ///
+    /// A JSON filter written for a specific pandoc-types API version can
+    /// request it with a suffix, e.g. `my-filter.py@1.22`.
     #[arg(short = 'F', long = "filter", action = clap::ArgAction::Append)]
     filters: Vec<String>,
 
@@ -85,7 +96,7 @@ struct Args {
     #[arg(long = "template")]
     template: Option<String>,
 
-    /// Use a template bundle JSON file
+    /// Use a template bundle (a JSON file, a bundle directory or a zip archive)
     #[arg(long = "template-bundle")]
     template_bundle: Option<std::path::PathBuf>,
 
@@ -222,15 +233,20 @@ fn main() {
         return;
     }
 
-    let (pandoc, context) = match args.from.as_str() {
+    // Formats may carry extension modifiers, e.g. `markdown+smart-raw_html`
+    let from_format = options::parse_format_string(&args.from);
+    let to_format = options::parse_format_string(&args.to);
+
+    let (pandoc, context) = match from_format.base_format.as_str() {
         "markdown" | "qmd" => {
-            let result = readers::qmd::read(
+            let result = readers::qmd::read_with_options(
                 input.as_bytes(),
                 args.loose,
                 input_filename,
                 &mut output_stream,
                 !args.no_prune_errors, // prune_errors = !no_prune_errors
                 None,
+                &readers::qmd::QmdReaderOptions::from_extensions(&from_format.extensions),
             );
             match result {
                 Ok((pandoc, context, warnings)) => {
@@ -309,7 +325,8 @@ fn main() {
             .map(|s| unified_filter::FilterSpec::parse(s))
             .collect();
 
-        match unified_filter::apply_filters(pandoc, context, &filter_specs, &args.to) {
+        match unified_filter::apply_filters(pandoc, context, &filter_specs, &to_format.base_format)
+        {
             Ok((filtered_pandoc, filtered_context, diagnostics)) => {
                 // Output any diagnostics from filters
                 if !diagnostics.is_empty() {
@@ -347,18 +364,11 @@ fn main() {
     #[cfg(feature = "template-fs")]
     let template_info: Option<(TemplateBundle, String)> = {
         if let Some(bundle_path) = &args.template_bundle {
-            let bundle_json = std::fs::read_to_string(bundle_path).unwrap_or_else(|e| {
-                eprintln!(
-                    "Failed to read template bundle '{}': {}",
-                    bundle_path.display(),
-                    e
-                );
-                std::process::exit(1);
-            });
-            match TemplateBundle::from_json(&bundle_json) {
+            // A JSON bundle, a bundle directory or a zipped bundle
+            match TemplateBundle::load(bundle_path) {
                 Ok(bundle) => Some((bundle, bundle_path.display().to_string())),
                 Err(e) => {
-                    eprintln!("Failed to parse template bundle: {}", e);
+                    eprintln!("Failed to load template bundle: {}", e);
                     std::process::exit(1);
                 }
             }
@@ -405,7 +415,7 @@ fn main() {
     let mut buf = Vec::new();
     let writer_result = if let Some((bundle, template_name)) = template_info {
         // Determine body format from --to
-        let body_format = match args.to.as_str() {
+        let body_format = match to_format.base_format.as_str() {
             "html" => BodyFormat::Html,
             "plaintext" | "plain" => BodyFormat::Plaintext,
             other => {
@@ -443,7 +453,7 @@ fn main() {
         }
     } else {
         // No template - use regular writers
-        match args.to.as_str() {
+        match to_format.base_format.as_str() {
             "json" => {
                 let json_config = writers::json::JsonConfig {
                     include_inline_locations: args
@@ -453,8 +463,19 @@ fn main() {
                 };
                 writers::json::write_with_config(&pandoc, &context, &mut buf, &json_config)
             }
-            "native" => writers::native::write(&pandoc, &context, &mut buf),
-            "markdown" | "qmd" => writers::qmd::write(&pandoc, &mut buf),
+            "native" => writers::native::write_with_config(
+                &pandoc,
+                &context,
+                &mut buf,
+                &writers::native::NativeConfig {
+                    source_positions: args.source_positions,
+                },
+            ),
+            "markdown" | "qmd" => writers::qmd::write_with_options(
+                &pandoc,
+                &mut buf,
+                &writers::qmd::QmdWriterOptions::from_extensions(&to_format.extensions),
+            ),
             "html" => {
                 // Check for section-divs: true in format.html.section-divs
                 let section_divs_enabled = should_sectionize(&pandoc.meta);
@@ -466,17 +487,49 @@ fn main() {
                 } else {
                     pandoc.clone()
                 };
-                writers::html::write(&pandoc_to_write, &context, &mut buf).map_err(|e| {
-                    vec![
-                        quarto_error_reporting::DiagnosticMessageBuilder::error(
-                            "IO error during write",
-                        )
-                        .with_code("Q-3-1")
-                        .problem(format!("Failed to write HTML output: {}", e))
-                        .build(),
-                    ]
-                })
+                let mut config = writers::html::extract_config_from_metadata(&pandoc_to_write.meta);
+                config.source_positions |= args.source_positions;
+                writers::html::write_document(&pandoc_to_write, &context, &mut buf, config)
+                    .map(|summary| {
+                        // Dropped raw content is reported as warnings, not errors
+                        for diagnostic in &summary.diagnostics {
+                            if args.json_errors {
+                                eprintln!("{}", diagnostic.to_json());
+                            } else {
+                                eprintln!("{}", diagnostic.to_text(Some(&context.source_context)));
+                            }
+                        }
+                    })
+                    .map_err(|e| {
+                        vec![
+                            quarto_error_reporting::DiagnosticMessageBuilder::error(
+                                "IO error during write",
+                            )
+                            .with_code("Q-3-1")
+                            .problem(format!("Failed to write HTML output: {}", e))
+                            .build(),
+                        ]
+                    })
             }
+            "gfm" | "latex" | "rst" | "typst" | "org" => match to_format.base_format.as_str() {
+                "gfm" => writers::gfm::write(&pandoc, &mut buf),
+                "latex" => writers::latex::write(&pandoc, &mut buf),
+                "rst" => writers::rst::write(&pandoc, &mut buf),
+                "typst" => writers::typst::write(&pandoc, &mut buf),
+                _ => writers::org::write(&pandoc, &mut buf),
+            }
+            .map(|diagnostics| {
+                // Diagnostics from these writers are warnings (dropped nodes), not errors
+                if args.json_errors {
+                    for diagnostic in &diagnostics {
+                        eprintln!("{}", diagnostic.to_json());
+                    }
+                } else {
+                    for diagnostic in &diagnostics {
+                        eprintln!("{}", diagnostic.to_text(Some(&context.source_context)));
+                    }
+                }
+            }),
             "plaintext" | "plain" => {
                 let (output, diagnostics) = writers::plaintext::blocks_to_string(&pandoc.blocks);
                 buf.extend_from_slice(output.as_bytes());
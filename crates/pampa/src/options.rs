@@ -54,6 +54,89 @@ pub struct ParsedFormat {
     pub extensions: ExtensionsDiff,
 }
 
+/// Markdown syntax extensions that readers and writers can toggle per
+/// invocation, e.g. via `markdown+smart-raw_html`.
+///
+/// Defaults match the qmd reader's behavior without any modifiers. Extension
+/// names Pampa doesn't know are ignored, as with `ReaderOptions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatExtensions {
+    /// `--`, `---` and `...` become en dashes, em dashes and ellipses.
+    ///
+    /// Disabled by default. Quotes are read as `Quoted` inlines either way.
+    pub smart: bool,
+    /// A paragraph containing only an image with a caption becomes a `Figure`.
+    pub implicit_figures: bool,
+    /// Bullet items starting with `[ ]` or `[x]` become `☐`/`☒` task items.
+    ///
+    /// Disabled by default: qmd reads these markers as bracketed spans.
+    pub task_lists: bool,
+    /// `[^id]` references and `[^id]: ...` definitions are footnotes.
+    pub footnotes: bool,
+    /// Inline HTML elements are passed through as raw HTML.
+    pub raw_html: bool,
+    /// `$...$` and `$$...$$` are TeX math.
+    pub tex_math_dollars: bool,
+    /// `:smile:` shortcodes become the corresponding Unicode emoji.
+    pub emoji: bool,
+    /// A bare `@handle` outside brackets becomes a `Span` with class
+    /// `mention` instead of an author-in-text citation.
+    pub mentions: bool,
+}
+
+impl Default for FormatExtensions {
+    fn default() -> Self {
+        Self {
+            smart: false,
+            implicit_figures: true,
+            task_lists: false,
+            footnotes: true,
+            raw_html: true,
+            tex_math_dollars: true,
+            emoji: false,
+            mentions: false,
+        }
+    }
+}
+
+impl FormatExtensions {
+    /// Build the extension set for a format's `+ext`/`-ext` modifiers,
+    /// starting from the defaults.
+    pub fn from_diff(diff: &ExtensionsDiff) -> Self {
+        let mut extensions = Self::default();
+        extensions.apply(diff);
+        extensions
+    }
+
+    /// Apply `+ext`/`-ext` modifiers. Disables win over enables.
+    pub fn apply(&mut self, diff: &ExtensionsDiff) {
+        for name in &diff.enable {
+            self.set(name, true);
+        }
+        for name in &diff.disable {
+            self.set(name, false);
+        }
+    }
+
+    /// Toggle an extension by its Pandoc name. Returns `false` if the
+    /// extension is not one Pampa knows about.
+    pub fn set(&mut self, name: &str, enabled: bool) -> bool {
+        let flag = match name {
+            "smart" => &mut self.smart,
+            "implicit_figures" => &mut self.implicit_figures,
+            "task_lists" => &mut self.task_lists,
+            "footnotes" => &mut self.footnotes,
+            "raw_html" => &mut self.raw_html,
+            "tex_math_dollars" => &mut self.tex_math_dollars,
+            "emoji" => &mut self.emoji,
+            "mentions" => &mut self.mentions,
+            _ => return false,
+        };
+        *flag = enabled;
+        true
+    }
+}
+
 // =============================================================================
 // Helper functions for extracting fields from options
 // =============================================================================
@@ -241,7 +324,8 @@ pub const SUPPORTED_READER_FORMATS: &[&str] = &["qmd", "markdown", "json"];
 
 /// Supported writer format names.
 pub const SUPPORTED_WRITER_FORMATS: &[&str] = &[
-    "html", "html5", "json", "native", "markdown", "qmd", "plain",
+    "html", "html5", "json", "native", "markdown", "qmd", "gfm", "latex", "rst", "typst", "org",
+    "plain",
 ];
 
 /// Check if a format is supported for reading.
@@ -424,6 +508,11 @@ mod tests {
         assert!(is_supported_writer_format("native"));
         assert!(is_supported_writer_format("markdown"));
         assert!(is_supported_writer_format("qmd"));
+        assert!(is_supported_writer_format("gfm"));
+        assert!(is_supported_writer_format("latex"));
+        assert!(is_supported_writer_format("rst"));
+        assert!(is_supported_writer_format("typst"));
+        assert!(is_supported_writer_format("org"));
         assert!(is_supported_writer_format("plain"));
         assert!(!is_supported_writer_format("docx"));
         assert!(!is_supported_writer_format("unknown"));
@@ -459,6 +548,38 @@ mod tests {
         assert!(diff.disable.is_empty());
     }
 
+    #[test]
+    fn test_format_extensions_default() {
+        let extensions = FormatExtensions::default();
+        assert!(!extensions.smart);
+        assert!(extensions.implicit_figures);
+        assert!(!extensions.task_lists);
+        assert!(extensions.footnotes);
+        assert!(extensions.raw_html);
+        assert!(extensions.tex_math_dollars);
+        assert!(!extensions.emoji);
+        assert!(!extensions.mentions);
+    }
+
+    #[test]
+    fn test_format_extensions_from_diff() {
+        let parsed = parse_format_string("markdown+task_lists+smart-raw_html+unknown");
+        let extensions = FormatExtensions::from_diff(&parsed.extensions);
+        assert!(extensions.task_lists);
+        assert!(extensions.smart);
+        assert!(!extensions.raw_html);
+        assert!(extensions.footnotes);
+    }
+
+    #[test]
+    fn test_format_extensions_set_unknown() {
+        let mut extensions = FormatExtensions::default();
+        assert!(!extensions.set("citations", false));
+        assert_eq!(extensions, FormatExtensions::default());
+        assert!(extensions.set("footnotes", false));
+        assert!(!extensions.footnotes);
+    }
+
     #[test]
     fn test_parse_format_string_with_trailing_invalid_chars() {
         // This tests the break case when we encounter a character that's not + or -
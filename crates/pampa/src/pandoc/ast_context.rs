@@ -3,6 +3,7 @@
  * Copyright (c) 2025 Posit, PBC
  */
 
+use crate::options::FormatExtensions;
 use quarto_source_map::{FileId, SourceContext};
 use std::cell::Cell;
 
@@ -23,6 +24,8 @@ pub struct ASTContext {
     /// as Substrings of this parent, enabling correct location tracking through
     /// nested parse operations.
     pub parent_source_info: Option<quarto_source_map::SourceInfo>,
+    /// Markdown extensions in effect for this parse (e.g. `smart`, `raw_html`)
+    pub extensions: FormatExtensions,
 }
 
 impl ASTContext {
@@ -36,6 +39,7 @@ impl ASTContext {
             example_list_counter: Cell::new(1),
             source_context,
             parent_source_info: None,
+            extensions: FormatExtensions::default(),
         }
     }
 
@@ -50,6 +54,7 @@ impl ASTContext {
             example_list_counter: Cell::new(1),
             source_context,
             parent_source_info: None,
+            extensions: FormatExtensions::default(),
         }
     }
 
@@ -63,6 +68,7 @@ impl ASTContext {
             example_list_counter: Cell::new(1),
             source_context,
             parent_source_info: None,
+            extensions: FormatExtensions::default(),
         }
     }
 
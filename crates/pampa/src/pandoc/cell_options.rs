@@ -0,0 +1,422 @@
+/*
+ * cell_options.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Code cell options (`#|` comments).
+//!
+//! Executable code blocks (```` ```{r} ````, ```` ```{python} ````, ...)
+//! carry their options as YAML in comment lines at the top of the cell:
+//!
+//! ````markdown
+//! ```{r}
+//! #| label: fig-scatter
+//! #| fig-cap: "A scatter plot"
+//! #| echo: false
+//! plot(cars)
+//! ```
+//! ````
+//!
+//! The reader parses these lines into the CodeBlock's key-value attributes
+//! (`label`, `fig-cap`, `echo`, ...) so later stages can consume them
+//! without re-parsing the cell. The option lines stay in the code text,
+//! which engines execute and which the QMD writer writes back unchanged.
+//!
+//! Values are stored as strings: scalars as their text (`true`, `7`,
+//! `A scatter plot`), lists and maps as JSON. Each attribute's source info
+//! points at its key and value in the `#|` line. Options are validated
+//! against the cell option schema; an invalid value is reported as a
+//! warning but still stored. Attributes set in the cell header
+//! (`{r echo=FALSE}`) take precedence over `#|` options.
+
+use once_cell::sync::Lazy;
+use quarto_error_reporting::DiagnosticMessageBuilder;
+use quarto_source_map::SourceInfo;
+use quarto_yaml::YamlWithSourceInfo;
+use quarto_yaml_validation::{Schema, SchemaRegistry};
+use yaml_rust2::Yaml;
+
+use crate::pandoc::ast_context::ASTContext;
+use crate::pandoc::attr::Attr;
+use crate::pandoc::block::CodeBlock;
+use crate::utils::diagnostic_collector::DiagnosticCollector;
+
+/// Schema for code cell options (the options of Quarto's `cell-*` schemas
+/// that stages in this crate and quarto-core consume). Unknown options are
+/// allowed, since engines define their own.
+const CELL_OPTIONS_SCHEMA: &str = r#"
+object:
+  properties:
+    label: string
+    echo:
+      anyOf:
+        - boolean
+        - enum: [fenced]
+    eval:
+      anyOf:
+        - boolean
+        - arrayOf: number
+    include: boolean
+    output:
+      anyOf:
+        - boolean
+        - enum: [asis]
+    warning: boolean
+    error: boolean
+    message: boolean
+    cache:
+      anyOf:
+        - boolean
+        - enum: [refresh]
+    code-fold:
+      anyOf:
+        - boolean
+        - enum: [show]
+    code-summary: string
+    code-overflow:
+      enum: [scroll, wrap]
+    code-line-numbers:
+      anyOf:
+        - boolean
+        - string
+    classes:
+      maybeArrayOf: string
+    column: string
+    panel:
+      enum: [tabset, input, sidebar, fill, center]
+    fig-cap:
+      maybeArrayOf: string
+    fig-subcap:
+      anyOf:
+        - boolean
+        - arrayOf: string
+    fig-alt:
+      maybeArrayOf: string
+    fig-width: number
+    fig-height: number
+    fig-dpi: number
+    fig-align:
+      enum: [default, left, right, center]
+    fig-format:
+      enum: [retina, png, jpeg, svg, pdf]
+    fig-pos: string
+    tbl-cap:
+      maybeArrayOf: string
+    tbl-subcap:
+      anyOf:
+        - boolean
+        - arrayOf: string
+    tbl-colwidths:
+      anyOf:
+        - boolean
+        - enum: [auto]
+        - arrayOf: number
+    lst-label: string
+    lst-cap: string
+    layout-ncol: number
+    layout-nrow: number
+    layout: any
+"#;
+
+static CELL_OPTIONS: Lazy<Schema> = Lazy::new(|| {
+    let yaml = quarto_yaml::parse(CELL_OPTIONS_SCHEMA).expect("cell option schema is valid YAML");
+    Schema::from_yaml(&yaml).expect("cell option schema is a valid schema")
+});
+
+/// The language of an executable code block: the braced first class, as in
+/// `{r}` or `{python}`.
+pub fn cell_language(attr: &Attr) -> Option<&str> {
+    attr.1
+        .first()?
+        .strip_prefix('{')?
+        .strip_suffix('}')
+        .filter(|lang| !lang.is_empty())
+}
+
+/// The comment prefix of option lines for `language` (`#|`, `//|`, ...).
+pub fn option_prefix(language: &str) -> &'static str {
+    match language.to_lowercase().as_str() {
+        "ojs" | "js" | "javascript" | "typescript" | "ts" | "c" | "cpp" | "c++" | "csharp"
+        | "java" | "scala" | "rust" | "go" | "swift" | "kotlin" | "dot" => "//|",
+        "sql" | "lua" | "haskell" => "--|",
+        "mermaid" => "%%|",
+        "matlab" | "octave" => "%|",
+        "fortran" => "!|",
+        _ => "#|",
+    }
+}
+
+/// One option line: the byte offset of its YAML text within the code, and
+/// the YAML text itself.
+struct OptionLine<'a> {
+    offset: usize,
+    yaml: &'a str,
+}
+
+/// The leading option lines of `text`.
+fn option_lines<'a>(text: &'a str, prefix: &str) -> Vec<OptionLine<'a>> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for line in text.split('\n') {
+        let Some(rest) = line.strip_prefix(prefix) else {
+            break;
+        };
+        // One space after the prefix is part of the prefix; further
+        // indentation belongs to the YAML
+        let skip = prefix.len() + usize::from(rest.starts_with(' '));
+        lines.push(OptionLine {
+            offset: offset + skip,
+            yaml: &line[skip..],
+        });
+        offset += line.len() + 1;
+    }
+    lines
+}
+
+/// The top-level option keys in the `#|` lines of an executable code block.
+///
+/// Used by writers that write the option lines back as code and so must
+/// not repeat the options as header attributes.
+pub fn cell_option_keys(codeblock: &CodeBlock) -> Vec<String> {
+    let Some(language) = cell_language(&codeblock.attr) else {
+        return Vec::new();
+    };
+    option_lines(&codeblock.text, option_prefix(language))
+        .iter()
+        .filter(|line| !line.yaml.starts_with([' ', '\t', '-']))
+        .filter_map(|line| line.yaml.split_once(':'))
+        .map(|(key, _)| key.trim().to_string())
+        .collect()
+}
+
+/// Parse the `#|` options of an executable code block into its attributes.
+///
+/// `content_source` is the source info of the code text, if it maps
+/// one-to-one onto the source; without it (e.g. for cells inside block
+/// quotes, whose continuation markers were removed) option locations fall
+/// back to the code block.
+pub fn read_cell_options(
+    codeblock: &mut CodeBlock,
+    content_source: Option<SourceInfo>,
+    context: &ASTContext,
+    diagnostics: &mut DiagnosticCollector,
+) {
+    let Some(language) = cell_language(&codeblock.attr) else {
+        return;
+    };
+    let lines = option_lines(&codeblock.text, option_prefix(language));
+    if lines.is_empty() {
+        return;
+    }
+
+    let yaml_text: String = lines
+        .iter()
+        .map(|line| format!("{}\n", line.yaml))
+        .collect();
+    let parent = match content_source {
+        Some(source) => SourceInfo::concat(
+            lines
+                .iter()
+                .map(|line| {
+                    let end = line.offset + line.yaml.len() + 1;
+                    (
+                        SourceInfo::substring(source.clone(), line.offset, end),
+                        line.yaml.len() + 1,
+                    )
+                })
+                .collect(),
+        ),
+        None => codeblock.source_info.clone(),
+    };
+
+    let yaml = match quarto_yaml::parse_with_parent(&yaml_text, parent.clone()) {
+        Ok(yaml) => yaml,
+        Err(e) => {
+            diagnostics.add(
+                DiagnosticMessageBuilder::warning("Invalid code cell options")
+                    .with_code("Q-2-36")
+                    .with_location(parent)
+                    .problem(format!(
+                        "The `{}` option lines are not valid YAML: {}",
+                        option_prefix(language),
+                        e
+                    ))
+                    .add_hint("Write one `key: value` pair per line, e.g. `#| echo: false`")
+                    .build(),
+            );
+            return;
+        }
+    };
+
+    if let Err(error) = quarto_yaml_validation::validate(
+        &yaml,
+        &CELL_OPTIONS,
+        &SchemaRegistry::new(),
+        &context.source_context,
+    ) {
+        let location = error
+            .yaml_node
+            .as_ref()
+            .map(|node| node.source_info.clone())
+            .unwrap_or(parent);
+        let problem = if error.instance_path.is_empty() {
+            format!(
+                "Cell options must be `key: value` pairs: {}",
+                error.message()
+            )
+        } else {
+            format!(
+                "Invalid value for `{}`: {}",
+                error.instance_path,
+                error.message()
+            )
+        };
+        diagnostics.add(
+            DiagnosticMessageBuilder::warning("Invalid code cell option")
+                .with_code("Q-2-37")
+                .with_location(location)
+                .problem(problem)
+                .build(),
+        );
+    }
+
+    let Some((entries, _)) = yaml.into_hash() else {
+        return;
+    };
+    let (_, _, attrs) = &mut codeblock.attr;
+    for entry in entries {
+        let Some(key) = entry.key.yaml.as_str().map(str::to_string) else {
+            continue;
+        };
+        if attrs.contains_key(&key) {
+            continue;
+        }
+        attrs.insert(key, option_value(&entry.value));
+        codeblock
+            .attr_source
+            .attributes
+            .push((Some(entry.key_span), Some(entry.value_span)));
+    }
+}
+
+/// An option value as an attribute string.
+fn option_value(value: &YamlWithSourceInfo) -> String {
+    match &value.yaml {
+        Yaml::String(s) | Yaml::Real(s) => s.clone(),
+        Yaml::Integer(i) => i.to_string(),
+        Yaml::Boolean(b) => b.to_string(),
+        Yaml::Null => String::new(),
+        other => yaml_to_json(other).to_string(),
+    }
+}
+
+fn yaml_to_json(yaml: &Yaml) -> serde_json::Value {
+    match yaml {
+        Yaml::String(s) => serde_json::Value::String(s.clone()),
+        Yaml::Real(s) => s
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map_or_else(
+                || serde_json::Value::String(s.clone()),
+                serde_json::Value::Number,
+            ),
+        Yaml::Integer(i) => serde_json::Value::from(*i),
+        Yaml::Boolean(b) => serde_json::Value::Bool(*b),
+        Yaml::Array(items) => items.iter().map(yaml_to_json).collect(),
+        Yaml::Hash(hash) => serde_json::Value::Object(
+            hash.iter()
+                .filter_map(|(k, v)| Some((k.as_str()?.to_string(), yaml_to_json(v))))
+                .collect(),
+        ),
+        _ => serde_json::Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pandoc::attr::AttrSourceInfo;
+    use hashlink::LinkedHashMap;
+
+    fn make_cell(classes: Vec<&str>, text: &str) -> CodeBlock {
+        CodeBlock {
+            attr: (
+                String::new(),
+                classes.into_iter().map(String::from).collect(),
+                LinkedHashMap::new(),
+            ),
+            text: text.to_string(),
+            source_info: SourceInfo::default(),
+            attr_source: AttrSourceInfo::empty(),
+        }
+    }
+
+    fn read(cell: &mut CodeBlock) -> Vec<quarto_error_reporting::DiagnosticMessage> {
+        let mut diagnostics = DiagnosticCollector::new();
+        read_cell_options(cell, None, &ASTContext::anonymous(), &mut diagnostics);
+        diagnostics.diagnostics().to_vec()
+    }
+
+    #[test]
+    fn test_reads_options_into_attributes() {
+        let mut cell = make_cell(
+            vec!["{r}"],
+            "#| label: fig-scatter\n#| fig-cap: \"A scatter plot\"\n#| echo: false\n#| fig-width: 7\n#| classes: [wide, dark]\nplot(cars)",
+        );
+        assert!(read(&mut cell).is_empty());
+
+        let attrs = &cell.attr.2;
+        assert_eq!(attrs.get("label").unwrap(), "fig-scatter");
+        assert_eq!(attrs.get("fig-cap").unwrap(), "A scatter plot");
+        assert_eq!(attrs.get("echo").unwrap(), "false");
+        assert_eq!(attrs.get("fig-width").unwrap(), "7");
+        assert_eq!(attrs.get("classes").unwrap(), r#"["wide","dark"]"#);
+        assert_eq!(cell.attr_source.attributes.len(), 5);
+        // The option lines stay in the code
+        assert!(cell.text.starts_with("#| label: fig-scatter\n"));
+    }
+
+    #[test]
+    fn test_header_attributes_take_precedence() {
+        let mut cell = make_cell(vec!["{r}"], "#| echo: false\n1 + 1");
+        cell.attr.2.insert("echo".to_string(), "TRUE".to_string());
+        read(&mut cell);
+        assert_eq!(cell.attr.2.get("echo").unwrap(), "TRUE");
+    }
+
+    #[test]
+    fn test_language_comment_prefix() {
+        let mut cell = make_cell(vec!["{ojs}"], "//| echo: false\nx = 1");
+        read(&mut cell);
+        assert_eq!(cell.attr.2.get("echo").unwrap(), "false");
+
+        // Not an executable cell
+        let mut cell = make_cell(vec!["python"], "#| echo: false\nx = 1");
+        read(&mut cell);
+        assert!(cell.attr.2.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_options_warn() {
+        let mut cell = make_cell(vec!["{python}"], "#| echo: sometimes\nx = 1");
+        let diagnostics = read(&mut cell);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("Q-2-37"));
+        assert_eq!(cell.attr.2.get("echo").unwrap(), "sometimes");
+
+        let mut cell = make_cell(vec!["{python}"], "#| echo: [false\nx = 1");
+        let diagnostics = read(&mut cell);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("Q-2-36"));
+        assert!(cell.attr.2.is_empty());
+    }
+
+    #[test]
+    fn test_cell_option_keys() {
+        let cell = make_cell(
+            vec!["{r}"],
+            "#| label: tbl-cars\n#| tbl-cap:\n#|   - First\n#|   - Second\nhead(cars)",
+        );
+        assert_eq!(cell_option_keys(&cell), vec!["label", "tbl-cap"]);
+    }
+}
@@ -4,6 +4,7 @@
  */
 
 pub mod ast_context;
+pub mod cell_options;
 pub mod location;
 pub mod meta;
 pub mod shortcode;
@@ -28,7 +28,7 @@ use crate::pandoc::treesitter_utils::pipe_table::{
     process_pipe_table, process_pipe_table_cell, process_pipe_table_delimiter_cell,
     process_pipe_table_delimiter_row, process_pipe_table_header_or_row,
 };
-use crate::pandoc::treesitter_utils::postprocess::{merge_strs, postprocess};
+use crate::pandoc::treesitter_utils::postprocess::{merge_strs, postprocess, replace_emoji};
 use crate::pandoc::treesitter_utils::quote_helpers::process_quoted;
 use crate::pandoc::treesitter_utils::section::process_section;
 use crate::pandoc::treesitter_utils::shortcode::{
@@ -515,26 +515,42 @@ fn native_visitor<T: Write>(
             // Node structure: '$' content '$'
             // Get the full text and strip the delimiters
             let full_text = node.utf8_text(input_bytes).unwrap();
-            let content = &full_text[1..full_text.len() - 1]; // Strip leading and trailing $
+            if context.extensions.tex_math_dollars {
+                let content = &full_text[1..full_text.len() - 1]; // Strip leading and trailing $
 
-            PandocNativeIntermediate::IntermediateInline(Inline::Math(Math {
-                math_type: MathType::InlineMath,
-                text: content.to_string(),
-                source_info: node_source_info_with_context(node, context),
-            }))
+                PandocNativeIntermediate::IntermediateInline(Inline::Math(Math {
+                    math_type: MathType::InlineMath,
+                    text: content.to_string(),
+                    source_info: node_source_info_with_context(node, context),
+                }))
+            } else {
+                // Without the tex_math_dollars extension the dollars are literal text
+                PandocNativeIntermediate::IntermediateInline(Inline::Str(Str {
+                    text: full_text.to_string(),
+                    source_info: node_source_info_with_context(node, context),
+                }))
+            }
         }
         "pandoc_display_math" => {
             // Extract display math content (text between $$ delimiters)
             // Node structure: '$$' content '$$'
             // Get the full text and strip the delimiters
             let full_text = node.utf8_text(input_bytes).unwrap();
-            let content = &full_text[2..full_text.len() - 2]; // Strip leading and trailing $$
+            if context.extensions.tex_math_dollars {
+                let content = &full_text[2..full_text.len() - 2]; // Strip leading and trailing $$
 
-            PandocNativeIntermediate::IntermediateInline(Inline::Math(Math {
-                math_type: MathType::DisplayMath,
-                text: content.to_string(),
-                source_info: node_source_info_with_context(node, context),
-            }))
+                PandocNativeIntermediate::IntermediateInline(Inline::Math(Math {
+                    math_type: MathType::DisplayMath,
+                    text: content.to_string(),
+                    source_info: node_source_info_with_context(node, context),
+                }))
+            } else {
+                // Without the tex_math_dollars extension the dollars are literal text
+                PandocNativeIntermediate::IntermediateInline(Inline::Str(Str {
+                    text: full_text.to_string(),
+                    source_info: node_source_info_with_context(node, context),
+                }))
+            }
         }
         "pandoc_str" => {
             let text = node.utf8_text(input_bytes).unwrap().to_string();
@@ -798,13 +814,22 @@ fn native_visitor<T: Write>(
                     },
                 };
 
-                let note_ref = Inline::NoteReference(NoteReference {
-                    id,
-                    source_info: quarto_source_map::SourceInfo::from_range(
-                        context.current_file_id(),
-                        note_ref_range,
-                    ),
-                });
+                let note_ref_source_info = quarto_source_map::SourceInfo::from_range(
+                    context.current_file_id(),
+                    note_ref_range,
+                );
+                // Without the footnotes extension the reference is literal text
+                let note_ref = if context.extensions.footnotes {
+                    Inline::NoteReference(NoteReference {
+                        id,
+                        source_info: note_ref_source_info,
+                    })
+                } else {
+                    Inline::Str(Str {
+                        text: trimmed.to_string(),
+                        source_info: note_ref_source_info,
+                    })
+                };
 
                 // Build result with leading Space if needed to distinguish
                 // "Hi [^ref]" from "Hi[^ref]"
@@ -1039,7 +1064,7 @@ fn native_visitor<T: Write>(
         "pandoc_block_quote" => process_block_quote(node, children, context),
         "pandoc_horizontal_rule" => process_thematic_break(node, context),
         "pandoc_code_block" => {
-            let result = process_fenced_code_block(node, children, context);
+            let mut result = process_fenced_code_block(node, children, context);
 
             // Check for Q-2-8 warning: code block options in header without classes
             // This warns about syntax like {r eval=FALSE} and suggests YAML block syntax
@@ -1066,6 +1091,25 @@ fn native_visitor<T: Write>(
                 }
             }
 
+            // Parse `#|` option comments of executable cells into attributes
+            if let PandocNativeIntermediate::IntermediateBlock(Block::CodeBlock(ref mut cb)) =
+                result
+            {
+                let mut cursor = node.walk();
+                let content_source = node
+                    .children(&mut cursor)
+                    .find(|child| child.kind() == "code_fence_content")
+                    // Only when no block continuation markers were removed
+                    .filter(|child| child.byte_range().len() <= cb.text.len() + 1)
+                    .map(|child| node_source_info_with_context(&child, context));
+                crate::pandoc::cell_options::read_cell_options(
+                    cb,
+                    content_source,
+                    context,
+                    error_collector,
+                );
+            }
+
             result
         }
         "pandoc_div" => process_fenced_div_block(node, children, context),
@@ -1182,6 +1226,17 @@ fn native_visitor<T: Write>(
                 }
 
                 PandocNativeIntermediate::IntermediateInlines(result)
+            } else if !context.extensions.raw_html {
+                // Without the raw_html extension the element is literal text
+                use crate::pandoc::location::{SourceInfoOptions, node_source_info_with_options};
+                PandocNativeIntermediate::IntermediateInline(Inline::Str(Str {
+                    text,
+                    source_info: node_source_info_with_options(
+                        node,
+                        context,
+                        &SourceInfoOptions::trim_all(),
+                    ),
+                }))
             } else {
                 // Get the source info with whitespace trimming for the warning
                 use crate::pandoc::location::{SourceInfoOptions, node_source_info_with_options};
@@ -1246,7 +1301,7 @@ pub fn treesitter_to_pandoc<T: Write>(
         );
         return Err(vec![diagnostic]);
     };
-    let result = match postprocess(pandoc, error_collector) {
+    let result = match postprocess(pandoc, &context.extensions, error_collector) {
         Ok(doc) => doc,
         Err(()) => {
             // Postprocess found errors, return the diagnostics from the collector
@@ -1257,6 +1312,11 @@ pub fn treesitter_to_pandoc<T: Write>(
             return Err(diagnostics);
         }
     };
-    let result = merge_strs(result);
+    let result = merge_strs(result, context.extensions.smart);
+    let result = if context.extensions.emoji {
+        replace_emoji(result)
+    } else {
+        result
+    };
     Ok(result)
 }
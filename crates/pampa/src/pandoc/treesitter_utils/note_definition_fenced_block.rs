@@ -7,7 +7,8 @@
  */
 
 use crate::pandoc::ast_context::ASTContext;
-use crate::pandoc::block::{Block, Blocks, NoteDefinitionFencedBlock};
+use crate::pandoc::attr::{AttrSourceInfo, empty_attr};
+use crate::pandoc::block::{Block, Blocks, Div, NoteDefinitionFencedBlock};
 use crate::pandoc::location::node_source_info_with_context;
 
 use super::pandocnativeintermediate::PandocNativeIntermediate;
@@ -45,6 +46,16 @@ pub fn process_note_definition_fenced_block(
         }
     }
 
+    // Without the footnotes extension the note body is an ordinary div
+    if !context.extensions.footnotes {
+        return PandocNativeIntermediate::IntermediateBlock(Block::Div(Div {
+            attr: empty_attr(),
+            content,
+            source_info: node_source_info_with_context(node, context),
+            attr_source: AttrSourceInfo::empty(),
+        }));
+    }
+
     PandocNativeIntermediate::IntermediateBlock(Block::NoteDefinitionFencedBlock(
         NoteDefinitionFencedBlock {
             id,
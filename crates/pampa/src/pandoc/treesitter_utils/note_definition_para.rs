@@ -7,7 +7,8 @@
  */
 
 use crate::pandoc::ast_context::ASTContext;
-use crate::pandoc::block::{Block, NoteDefinitionPara};
+use crate::pandoc::block::{Block, NoteDefinitionPara, Paragraph};
+use crate::pandoc::inline::{Inline, Space, Str};
 use crate::pandoc::location::node_source_info_with_context;
 
 use super::pandocnativeintermediate::PandocNativeIntermediate;
@@ -42,9 +43,29 @@ pub fn process_note_definition_para(
         }
     }
 
+    let source_info = node_source_info_with_context(node, context);
+
+    // Without the footnotes extension the definition is an ordinary paragraph
+    if !context.extensions.footnotes {
+        let mut para_content = vec![
+            Inline::Str(Str {
+                text: format!("[^{}]:", id),
+                source_info: source_info.clone(),
+            }),
+            Inline::Space(Space {
+                source_info: source_info.clone(),
+            }),
+        ];
+        para_content.extend(content);
+        return PandocNativeIntermediate::IntermediateBlock(Block::Paragraph(Paragraph {
+            content: para_content,
+            source_info,
+        }));
+    }
+
     PandocNativeIntermediate::IntermediateBlock(Block::NoteDefinitionPara(NoteDefinitionPara {
         id,
         content,
-        source_info: node_source_info_with_context(node, context),
+        source_info,
     }))
 }
@@ -18,13 +18,16 @@
 // list-table         | Div(.list-table) → Table            | Table → Div(.list-table) or pipe     | Yes (BulletList inside div)
 //                    | transform_list_table_div()          | write_table() / write_list_table()   |
 // -------------------|-------------------------------------|--------------------------------------|------------------------
-// definition-list    | Div(.definition-list) →             | DefinitionList → term/definition     | No (but always fully
-//                    | DefinitionList                      | syntax                               | rewritten regardless)
+// definition-list    | Div(.definition-list) →             | DefinitionList →                     | Yes (BulletList inside div)
+//                    | DefinitionList                      | Div(.definition-list)                |
 //                    | transform_definition_list_div()     | write_definitionlist()               |
 // -------------------|-------------------------------------|--------------------------------------|------------------------
+// grid-table         | Para (grid table text) → Table      | Table → grid table                   | No (Table blocks are
+//                    | readers::qmd::desugar_grid_tables() | write_table() / write_grid_table()   | always fully rewritten)
+// -------------------|-------------------------------------|--------------------------------------|------------------------
 //
 // INCREMENTAL WRITER COUPLING:
-// Both transforms' sugared forms are always fully rewritten by the incremental writer
+// All transforms' sugared forms are always fully rewritten by the incremental writer
 // (never incrementally spliced), so Option A reconciliation (post-desugared ASTs) is safe.
 // If adding a new sugar/desugar transform, document whether the sugared form uses
 // indentation boundaries. If it does NOT, evaluate whether Option A reconciliation can
@@ -36,13 +39,14 @@ use crate::filter_context::FilterContext;
 use crate::filters::{
     Filter, FilterReturn::FilterResult, FilterReturn::Unchanged, topdown_traverse,
 };
+use crate::options::FormatExtensions;
 use crate::pandoc::location::empty_source_info;
 use crate::pandoc::{
-    Attr, Block, Blocks, Caption, DefinitionList, Div, Figure, Inline, Inlines, Pandoc, Plain,
-    Space, Span, Str, Superscript, is_empty_attr,
+    Attr, Block, Blocks, Caption, CitationMode, Cite, DefinitionList, Div, Figure, Inline, Inlines,
+    Pandoc, Paragraph, Plain, Space, Span, Str, Superscript, is_empty_attr,
 };
-use crate::utils::autoid;
 use crate::utils::diagnostic_collector::DiagnosticCollector;
+use crate::utils::{autoid, emoji};
 use hashlink::LinkedHashMap;
 use quarto_error_reporting::DiagnosticMessageBuilder;
 use quarto_pandoc_types::AttrSourceInfo;
@@ -826,8 +830,45 @@ pub fn transform_divs(doc: Pandoc, error_collector: &mut DiagnosticCollector) ->
     topdown_traverse(doc, &mut filter, &mut ctx)
 }
 
+/// Replace a leading task list marker span (`[ ]`, `[x]` or `[X]`) followed
+/// by more content with Pandoc's `☐`/`☒` representation.
+///
+/// Returns true if a conversion was made.
+fn convert_task_list_marker(inlines: &mut Inlines) -> bool {
+    if inlines.len() < 2 {
+        return false;
+    }
+    let Inline::Span(span) = &inlines[0] else {
+        return false;
+    };
+    if !is_empty_attr(&span.attr) {
+        return false;
+    }
+    let marker = match span.content.as_slice() {
+        [] | [Inline::Space(_)] => "\u{2610}",
+        [Inline::Str(s)] if s.text == "x" || s.text == "X" => "\u{2612}",
+        _ => return false,
+    };
+    inlines[0] = Inline::Str(Str {
+        text: marker.to_string(),
+        source_info: span.source_info.clone(),
+    });
+    true
+}
+
 /// Apply post-processing transformations to the Pandoc AST
-pub fn postprocess(doc: Pandoc, error_collector: &mut DiagnosticCollector) -> Result<Pandoc, ()> {
+pub fn postprocess(
+    doc: Pandoc,
+    extensions: &FormatExtensions,
+    error_collector: &mut DiagnosticCollector,
+) -> Result<Pandoc, ()> {
+    let implicit_figures = extensions.implicit_figures;
+    let task_lists = extensions.task_lists;
+    let doc = if extensions.mentions {
+        resolve_mentions(doc)
+    } else {
+        doc
+    };
     let result = {
         // Wrap error_collector in RefCell for interior mutability across multiple closures
         let error_collector_ref = RefCell::new(error_collector);
@@ -908,13 +949,14 @@ pub fn postprocess(doc: Pandoc, error_collector: &mut DiagnosticCollector) -> Re
             })
             // attempt to desugar single-image paragraphs into figures
             // also convert trailing LineBreak to literal backslash (CommonMark spec)
-            .with_paragraph(|mut para, _ctx| {
+            .with_paragraph(move |mut para, _ctx| {
                 // Convert trailing LineBreak to literal backslash (CommonMark spec)
                 // Per spec, hard line breaks don't work at end of block elements
                 let trailing_lb_converted = convert_trailing_linebreak_to_str(&mut para.content);
 
                 // Check for single-image paragraph (for figure conversion)
-                if para.content.len() == 1
+                if implicit_figures
+                    && para.content.len() == 1
                     && let Some(Inline::Image(image)) = para.content.first()
                     && !image.content.is_empty()
                 {
@@ -1016,9 +1058,18 @@ pub fn postprocess(doc: Pandoc, error_collector: &mut DiagnosticCollector) -> Re
             })
             // Remove single empty spans from bullet list items
             // This allows `* []` to create truly empty list items in the AST
-            .with_bullet_list(|mut bullet_list, _ctx| {
+            // With task_lists, also turn leading `[ ]`/`[x]` spans into ☐/☒
+            .with_bullet_list(move |mut bullet_list, _ctx| {
                 let mut changed = false;
                 for item in &mut bullet_list.content {
+                    if task_lists
+                        && let Some(
+                            Block::Plain(Plain { content, .. })
+                            | Block::Paragraph(Paragraph { content, .. }),
+                        ) = item.first_mut()
+                    {
+                        changed |= convert_task_list_marker(content);
+                    }
                     // Check if item has exactly one block
                     if item.len() == 1 {
                         // Check if that block is Plain or Paragraph with single empty Span
@@ -1594,46 +1645,144 @@ pub fn postprocess(doc: Pandoc, error_collector: &mut DiagnosticCollector) -> Re
     if result.1 { Err(()) } else { Ok(result.0) }
 }
 
-/// Convert smart typography strings
+/// Turn bare `@handle` citations into `Span`s with class `mention` (the
+/// `mentions` extension). Runs before citations are numbered, since mentions
+/// are not citations.
+///
+/// An `@` right after a letter or digit is inside a word, as in `a@b.com`,
+/// and stays text. A Cite that is already the content of a `mention` Span,
+/// as in `[@alice]{.mention}`, becomes that span's text instead of a second
+/// mention.
+fn resolve_mentions(doc: Pandoc) -> Pandoc {
+    let mention_str = |cite: &Cite| {
+        let handle = cite.citations.first().map_or("", |c| c.id.as_str());
+        Inline::Str(Str {
+            text: format!("@{}", handle),
+            source_info: cite.source_info.clone(),
+        })
+    };
+    let mut ctx = FilterContext::new();
+    topdown_traverse(
+        doc,
+        &mut Filter::new().with_inlines(|inlines, _ctx| {
+            let mut result: Inlines = Vec::with_capacity(inlines.len());
+            for inline in inlines {
+                match inline {
+                    Inline::Cite(cite) if is_mention(&cite) => {
+                        let in_word = matches!(
+                            result.last(),
+                            Some(Inline::Str(s)) if s.text.ends_with(char::is_alphanumeric)
+                        );
+                        if in_word {
+                            result.extend(cite.content);
+                        } else {
+                            result.push(Inline::Span(Span {
+                                attr: (
+                                    String::new(),
+                                    vec!["mention".to_string()],
+                                    LinkedHashMap::new(),
+                                ),
+                                content: vec![mention_str(&cite)],
+                                source_info: cite.source_info,
+                                attr_source: AttrSourceInfo::empty(),
+                            }));
+                        }
+                    }
+                    Inline::Span(mut span) if span.attr.1.iter().any(|c| c == "mention") => {
+                        if let [Inline::Cite(cite)] = span.content.as_slice()
+                            && is_mention(cite)
+                        {
+                            span.content = vec![mention_str(cite)];
+                        }
+                        result.push(Inline::Span(span));
+                    }
+                    inline => result.push(inline),
+                }
+            }
+            FilterResult(result, true)
+        }),
+        &mut ctx,
+    )
+}
+
+/// A Cite is a mention candidate when it is a single author-in-text
+/// citation with no prefix or suffix, i.e. a bare `@handle`.
+fn is_mention(cite: &Cite) -> bool {
+    matches!(
+        cite.citations.as_slice(),
+        [citation] if citation.mode == CitationMode::AuthorInText
+            && citation.prefix.is_empty()
+            && citation.suffix.is_empty()
+    )
+}
+
+/// Replace `:shortcode:` emoji in Str text (the `emoji` extension).
+///
+/// Runs after `merge_strs` so shortcodes split across tokens are whole.
+pub fn replace_emoji(pandoc: Pandoc) -> Pandoc {
+    let mut ctx = FilterContext::new();
+    topdown_traverse(
+        pandoc,
+        &mut Filter::new().with_str(|mut s, _ctx| match emoji::replace_shortcodes(&s.text) {
+            Some(text) => {
+                s.text = text;
+                FilterResult(vec![Inline::Str(s)], false)
+            }
+            None => Unchanged(s),
+        }),
+        &mut ctx,
+    )
+}
+
+/// Convert smart typography: `---` to an em dash, `--` to an en dash and
+/// `...` to an ellipsis.
 fn as_smart_str(s: String) -> String {
-    if s == "..." {
-        "…".to_string()
-    } else if s == "--" {
-        "–".to_string()
-    } else if s == "---" {
-        "—".to_string()
-    } else {
-        s
+    if !s.contains("--") && !s.contains("...") {
+        return s;
     }
+    s.replace("---", "—").replace("--", "–").replace("...", "…")
+}
+
+/// Whether a Str is a backslash-escaped character (`\-`), which the reader
+/// produces as a one-character token spanning two source bytes.
+fn is_escaped_char(s: &Str) -> bool {
+    s.text.len() == 1 && s.source_info.end_offset() - s.source_info.start_offset() == 2
 }
 
-/// Merge consecutive Str inlines and apply smart typography
-pub fn merge_strs(pandoc: Pandoc) -> Pandoc {
+/// Merge consecutive Str inlines, applying smart typography when `smart` is set
+pub fn merge_strs(pandoc: Pandoc, smart: bool) -> Pandoc {
     let mut ctx = FilterContext::new();
     topdown_traverse(
         pandoc,
         &mut Filter::new().with_inlines(|inlines, _ctx| {
             let mut current_str: Option<String> = None;
             let mut current_source_info: Option<quarto_source_map::SourceInfo> = None;
+            // With smart, unescaped text since the last escaped character is
+            // converted as a whole: `...` after a space arrives as three `.`
+            // tokens, while `\.\.\.` must stay literal
+            let mut pending = String::new();
             let mut result: Inlines = Vec::new();
             let mut did_merge = false;
             for inline in inlines {
                 match inline {
                     Inline::Str(s) => {
-                        let str_text = as_smart_str(s.text.clone());
-                        if let Some(ref mut current) = current_str {
-                            current.push_str(&str_text);
-                            if let Some(ref mut info) = current_source_info {
-                                *info = info.combine(&s.source_info);
-                            }
+                        let current = current_str.get_or_insert_with(String::new);
+                        if smart && !is_escaped_char(&s) {
+                            pending.push_str(&s.text);
+                        } else {
+                            current.push_str(&as_smart_str(std::mem::take(&mut pending)));
+                            current.push_str(&s.text);
+                        }
+                        if let Some(ref mut info) = current_source_info {
+                            *info = info.combine(&s.source_info);
                             did_merge = true;
                         } else {
-                            current_str = Some(str_text);
                             current_source_info = Some(s.source_info);
                         }
                     }
                     _ => {
-                        if let Some(current) = current_str.take() {
+                        if let Some(mut current) = current_str.take() {
+                            current.push_str(&as_smart_str(std::mem::take(&mut pending)));
                             result.push(Inline::Str(Str {
                                 text: current,
                                 source_info: current_source_info
@@ -1645,7 +1794,8 @@ pub fn merge_strs(pandoc: Pandoc) -> Pandoc {
                     }
                 }
             }
-            if let Some(current) = current_str {
+            if let Some(mut current) = current_str {
+                current.push_str(&as_smart_str(pending));
                 result.push(Inline::Str(Str {
                     text: current,
                     source_info: current_source_info.unwrap_or_else(empty_source_info),
@@ -0,0 +1,578 @@
+/*
+ * grid_table.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Parsing of Pandoc-style grid and multiline tables.
+//!
+//! The tree-sitter grammar has no syntax for either, and their cells can hold
+//! text that does not parse as a paragraph (an unclosed code fence, for one).
+//! The qmd reader finds them in the source text with [`find_tables`] and
+//! hands tree-sitter the text with each table masked by [`mask_tables`], so a
+//! table reaches the reader as placeholder paragraphs covering its lines. The
+//! reader then parses the original text with [`parse_grid_table`] or
+//! [`parse_multiline_table`] and re-parses each cell's text as blocks.
+//!
+//! Supported grid table subset: one optional header row (ended by a `+===+`
+//! line), alignment colons on the header separator (or the top border for
+//! headerless tables), and no row or column spans.
+//!
+//! Supported multiline tables: at least two columns, with or without a
+//! header. Tables must start at the beginning of a line.
+
+use std::ops::Range;
+
+use crate::pandoc::table::{Alignment, ColWidth};
+
+/// The text layout of a grid table, before cell contents are parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridTable {
+    /// One alignment per column.
+    pub alignments: Vec<Alignment>,
+    /// One width per column.
+    pub widths: Vec<ColWidth>,
+    /// Header row cell texts, if the table has a header.
+    pub header: Option<Vec<String>>,
+    /// Body row cell texts.
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Parse `text` as a grid table. Returns `None` if it is not one, or if it
+/// uses features outside the supported subset (such as spanning cells).
+pub fn parse_grid_table(text: &str) -> Option<GridTable> {
+    let lines: Vec<Vec<char>> = text
+        .lines()
+        .map(|line| line.trim_end().chars().collect())
+        .collect();
+    if lines.len() < 3 {
+        return None;
+    }
+
+    // Column boundaries are the `+` positions of the top border
+    let boundaries: Vec<usize> = lines[0]
+        .iter()
+        .enumerate()
+        .filter_map(|(i, c)| (*c == '+').then_some(i))
+        .collect();
+    if boundaries.len() < 2 || boundaries[0] != 0 {
+        return None;
+    }
+    let num_cols = boundaries.len() - 1;
+
+    let mut alignments = separator_alignments(&lines[0], &boundaries, '-')?;
+    let mut header = None;
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut current: Vec<Vec<String>> = vec![Vec::new(); num_cols];
+    let mut current_has_lines = false;
+
+    for line in &lines[1..] {
+        match line.first() {
+            Some('+') => {
+                if !current_has_lines {
+                    return None;
+                }
+                let row = current.iter().map(|cell| dedent(cell)).collect();
+                current = vec![Vec::new(); num_cols];
+                current_has_lines = false;
+
+                if let Some(header_alignments) = separator_alignments(line, &boundaries, '=') {
+                    // Only a single header row, directly after the top border
+                    if header.is_some() || !rows.is_empty() {
+                        return None;
+                    }
+                    alignments = header_alignments;
+                    header = Some(row);
+                } else {
+                    separator_alignments(line, &boundaries, '-')?;
+                    rows.push(row);
+                }
+            }
+            Some('|') => {
+                if line.len() != lines[0].len() {
+                    return None;
+                }
+                for (col, window) in boundaries.windows(2).enumerate() {
+                    if line[window[0]] != '|' || line[window[1]] != '|' {
+                        return None;
+                    }
+                    current[col].push(line[window[0] + 1..window[1]].iter().collect());
+                }
+                current_has_lines = true;
+            }
+            _ => return None,
+        }
+    }
+
+    // The table must end with a border line
+    if current_has_lines {
+        return None;
+    }
+
+    Some(GridTable {
+        widths: vec![ColWidth::Default; alignments.len()],
+        alignments,
+        header,
+        rows,
+    })
+}
+
+/// Parse `text` as a multiline table. Returns `None` if it is not one.
+///
+/// The columns are given by the dashed line under the header, or by the
+/// first line of a headerless table. It needs at least two columns, since a
+/// single dashed line is a thematic break or a metadata fence. Rows are
+/// separated by blank lines and the table ends with a dashed line. As in
+/// Pandoc, alignments come from the position of the header text (or of the
+/// first row, without a header) relative to the dashes, and column widths
+/// from the dashed line's proportions of a 72-column line.
+pub fn parse_multiline_table(text: &str) -> Option<GridTable> {
+    let lines: Vec<Vec<char>> = text
+        .lines()
+        .map(|line| line.trim_end().chars().collect())
+        .collect();
+    let first = dashed_segments(lines.first()?)?;
+
+    // A header sits between a plain dashed line and the column separator
+    let (header_lines, separator) = if first.len() == 1 {
+        let separator = (1..lines.len()).find(|&i| dashed_segments(&lines[i]).is_some())?;
+        if separator < 2 || lines[1..separator].iter().any(Vec::is_empty) {
+            return None;
+        }
+        (&lines[1..separator], separator)
+    } else {
+        (&lines[..0], 0)
+    };
+    let segments = dashed_segments(&lines[separator])?;
+    if segments.len() < 2 {
+        return None;
+    }
+
+    // The last line closes the table; rows are the blank-separated groups before it
+    let last = lines.len() - 1;
+    if last <= separator + 1 || dashed_segments(&lines[last]).is_none() {
+        return None;
+    }
+    let body = &lines[separator + 1..last];
+    if body[0].is_empty() {
+        return None;
+    }
+    let rows: Vec<Vec<String>> = body
+        .split(|line| line.is_empty())
+        .filter(|group| !group.is_empty())
+        .map(|group| multiline_row(group, &segments))
+        .collect();
+
+    let starts: Vec<usize> = segments.iter().map(|(start, _)| *start).collect();
+    let alignment_lines = if header_lines.is_empty() {
+        &body[..1]
+    } else {
+        header_lines
+    };
+    let alignments = segments
+        .iter()
+        .enumerate()
+        .map(|(col, (_, dashes))| {
+            let pieces: Vec<String> = alignment_lines
+                .iter()
+                .map(|line| column_piece(line, &starts, col))
+                .collect();
+            multiline_alignment(&pieces, *dashes)
+        })
+        .collect();
+    let header = (!header_lines.is_empty()).then(|| multiline_row(header_lines, &segments));
+
+    Some(GridTable {
+        alignments,
+        widths: multiline_widths(&segments),
+        header,
+        rows,
+    })
+}
+
+/// The `(start, length)` of each run of dashes in a line made only of
+/// dashes and spaces, starting with a dash.
+fn dashed_segments(line: &[char]) -> Option<Vec<(usize, usize)>> {
+    if line.first() != Some(&'-') || !line.iter().all(|c| *c == '-' || *c == ' ') {
+        return None;
+    }
+    let mut segments: Vec<(usize, usize)> = Vec::new();
+    for (i, c) in line.iter().enumerate() {
+        if *c != '-' {
+            continue;
+        }
+        match segments.last_mut() {
+            Some((start, len)) if *start + *len == i => *len += 1,
+            _ => segments.push((i, 1)),
+        }
+    }
+    Some(segments)
+}
+
+/// The text of column `col` in `line`: from the column's start to the next
+/// column's, or to the end of the line for the last column.
+fn column_piece(line: &[char], starts: &[usize], col: usize) -> String {
+    let start = starts[col].min(line.len());
+    let end = starts
+        .get(col + 1)
+        .map_or(line.len(), |end| (*end).min(line.len()));
+    line[start..end]
+        .iter()
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+/// The cell texts of one multiline row: each column's pieces, trimmed and
+/// joined line by line.
+fn multiline_row(lines: &[Vec<char>], segments: &[(usize, usize)]) -> Vec<String> {
+    let starts: Vec<usize> = segments.iter().map(|(start, _)| *start).collect();
+    (0..segments.len())
+        .map(|col| {
+            let pieces: Vec<String> = lines
+                .iter()
+                .map(|line| column_piece(line, &starts, col).trim().to_string())
+                .collect();
+            pieces.join("\n").trim_matches('\n').to_string()
+        })
+        .collect()
+}
+
+/// A column's alignment from its longest line of header text: indented text
+/// that reaches the end of the dashes is right-aligned, flush-left text that
+/// stops short of it is left-aligned, and text short of both ends is
+/// centered.
+fn multiline_alignment(pieces: &[String], dashes: usize) -> Alignment {
+    let Some(longest) = pieces
+        .iter()
+        .filter(|piece| !piece.is_empty())
+        .max_by_key(|piece| piece.chars().count())
+    else {
+        return Alignment::Default;
+    };
+    let left_space = longest.starts_with(' ');
+    let right_space = longest.chars().count() < dashes;
+    match (left_space, right_space) {
+        (true, false) => Alignment::Right,
+        (false, true) => Alignment::Left,
+        (true, true) => Alignment::Center,
+        (false, false) => Alignment::Default,
+    }
+}
+
+/// Relative column widths, as Pandoc computes them: each column spans from
+/// its start to the next column's (the last one to the end of its dashes,
+/// plus one), as a fraction of 72 columns or of the table's width if wider.
+fn multiline_widths(segments: &[(usize, usize)]) -> Vec<ColWidth> {
+    let mut ends: Vec<usize> = segments.iter().skip(1).map(|(start, _)| *start).collect();
+    let (last_start, last_len) = segments[segments.len() - 1];
+    ends.push(last_start + last_len + 1);
+    let mut lengths: Vec<usize> = Vec::with_capacity(ends.len());
+    let mut previous = segments[0].0;
+    for end in &ends {
+        lengths.push(end - previous);
+        previous = *end;
+    }
+    // Inter-column spaces count towards every column but the last
+    if let [.., y, x] = lengths[..]
+        && x < y
+        && y - x <= 2
+    {
+        let n = lengths.len();
+        lengths[n - 1] = y;
+    }
+    let total = segments[0].0 + lengths.iter().sum::<usize>();
+    let quotient = total.max(72) as f64;
+    lengths
+        .into_iter()
+        .map(|length| ColWidth::Percentage(length as f64 / quotient))
+        .collect()
+}
+
+/// Find the grid and multiline tables in `input`. Each range covers the
+/// table's lines, from the start of the first to the end of the last
+/// (without its newline).
+///
+/// A table starts at the beginning of a line and has a blank line, a div
+/// fence, or the start or end of the input on either side.
+pub fn find_tables(input: &str) -> Vec<Range<usize>> {
+    let mut lines: Vec<Range<usize>> = Vec::new();
+    let mut start = 0;
+    for line in input.split_inclusive('\n') {
+        let end = start + line.trim_end_matches(['\n', '\r']).len();
+        lines.push(start..end);
+        start += line.len();
+    }
+    let text = |i: usize| &input[lines[i].clone()];
+    let is_boundary =
+        |i: usize| i >= lines.len() || text(i).trim().is_empty() || text(i).starts_with(":::");
+
+    let mut tables = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if i > 0 && !is_boundary(i - 1) {
+            i += 1;
+            continue;
+        }
+        let last = if text(i).starts_with('+') {
+            // A grid table runs to the next boundary
+            let last = (i..lines.len()).find(|&j| is_boundary(j + 1)).unwrap_or(i);
+            parse_grid_table(&input[lines[i].start..lines[last].end]).map(|_| last)
+        } else if text(i).starts_with('-') {
+            // A multiline table runs to the first dashed line before a boundary
+            (i + 1..lines.len())
+                .take_while(|&j| !text(j).starts_with(":::"))
+                .find(|&j| {
+                    is_boundary(j + 1)
+                        && dashed_segments(&text(j).trim_end().chars().collect::<Vec<_>>())
+                            .is_some()
+                })
+                .filter(|&last| {
+                    parse_multiline_table(&input[lines[i].start..lines[last].end]).is_some()
+                })
+        } else {
+            None
+        };
+        match last {
+            Some(last) => {
+                tables.push(lines[i].start..lines[last].end);
+                i = last + 1;
+            }
+            None => i += 1,
+        }
+    }
+    tables
+}
+
+/// Replace every non-blank line of each table with `x`s, keeping byte
+/// offsets and line breaks, so tree-sitter reads it as plain paragraphs.
+pub fn mask_tables(input: &[u8], tables: &[Range<usize>]) -> Vec<u8> {
+    let mut masked = input.to_vec();
+    for table in tables {
+        for line in masked[table.clone()].split_mut(|byte| *byte == b'\n') {
+            if !line.iter().all(u8::is_ascii_whitespace) {
+                line.fill(b'x');
+            }
+        }
+    }
+    masked
+}
+
+/// Check that `line` is a separator drawn with `fill` whose `+` signs sit
+/// exactly on `boundaries`, and read each column's alignment colons.
+fn separator_alignments(line: &[char], boundaries: &[usize], fill: char) -> Option<Vec<Alignment>> {
+    if line.len() != boundaries[boundaries.len() - 1] + 1 {
+        return None;
+    }
+    let mut alignments = Vec::new();
+    for window in boundaries.windows(2) {
+        if line[window[0]] != '+' || line[window[1]] != '+' {
+            return None;
+        }
+        let segment = &line[window[0] + 1..window[1]];
+        if segment.is_empty() || !segment.iter().all(|c| *c == fill || *c == ':') {
+            return None;
+        }
+        let left = segment[0] == ':';
+        let right = segment.len() > 1 && segment[segment.len() - 1] == ':';
+        alignments.push(match (left, right) {
+            (true, true) => Alignment::Center,
+            (true, false) => Alignment::Left,
+            (false, true) => Alignment::Right,
+            (false, false) => Alignment::Default,
+        });
+    }
+    Some(alignments)
+}
+
+/// Join a cell's lines, removing their common indentation and any leading or
+/// trailing blank lines.
+fn dedent(lines: &[String]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_grid_table_with_header() {
+        let text = "\
++-------+-----------+
+| Fruit | Notes     |
++:======+==========:+
+| Apple | - crisp   |
+|       | - red     |
++-------+-----------+
+| Pear  | Soft.     |
+|       |           |
+|       | Sweet.    |
++-------+-----------+";
+        let table = parse_grid_table(text).unwrap();
+        assert_eq!(table.alignments, vec![Alignment::Left, Alignment::Right]);
+        assert_eq!(
+            table.header,
+            Some(vec!["Fruit".to_string(), "Notes".to_string()])
+        );
+        assert_eq!(
+            table.rows,
+            vec![
+                vec!["Apple".to_string(), "- crisp\n- red".to_string()],
+                vec!["Pear".to_string(), "Soft.\n\nSweet.".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_grid_table_headerless() {
+        let text = "+:---:+---+\n| a   | b |\n+-----+---+";
+        let table = parse_grid_table(text).unwrap();
+        assert_eq!(
+            table.alignments,
+            vec![Alignment::Center, Alignment::Default]
+        );
+        assert_eq!(table.header, None);
+        assert_eq!(table.rows, vec![vec!["a".to_string(), "b".to_string()]]);
+    }
+
+    #[test]
+    fn test_parse_grid_table_rejects_spans() {
+        let text = "+---+---+\n| a | b |\n+---+---+\n| spanning  |\n+---+---+";
+        assert_eq!(parse_grid_table(text), None);
+    }
+
+    #[test]
+    fn test_parse_grid_table_rejects_non_tables() {
+        assert_eq!(parse_grid_table("Just a paragraph."), None);
+        assert_eq!(parse_grid_table("+---+\n| a |"), None);
+        assert_eq!(parse_grid_table("+---+\n| a |\n+---+\ntext"), None);
+    }
+
+    #[test]
+    fn test_parse_multiline_table_with_header() {
+        let text = "\
+-------------------------------------------
+ Centered   Default           Right Left
+  Header    Aligned         Aligned Aligned
+----------- ------- --------------- ---------
+   First    row                12.0 Spans
+                                    lines.
+
+  Second    row                 5.0 One.
+-------------------------------------------";
+        let table = parse_multiline_table(text).unwrap();
+        assert_eq!(
+            table.alignments,
+            vec![
+                Alignment::Center,
+                Alignment::Default,
+                Alignment::Right,
+                Alignment::Left
+            ]
+        );
+        assert_eq!(table.header.unwrap()[0], "Centered\nHeader".to_string());
+        assert_eq!(
+            table.rows,
+            vec![
+                vec![
+                    "First".to_string(),
+                    "row".to_string(),
+                    "12.0".to_string(),
+                    "Spans\nlines.".to_string()
+                ],
+                vec![
+                    "Second".to_string(),
+                    "row".to_string(),
+                    "5.0".to_string(),
+                    "One.".to_string()
+                ],
+            ]
+        );
+        // Column starts at 0, 12, 20 and 36; the last column ends past its dashes
+        assert_eq!(
+            table.widths,
+            vec![
+                ColWidth::Percentage(12.0 / 72.0),
+                ColWidth::Percentage(8.0 / 72.0),
+                ColWidth::Percentage(16.0 / 72.0),
+                ColWidth::Percentage(10.0 / 72.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_multiline_table_headerless() {
+        let text = "----- -----\n  a   b\n\nc         d\n----- -----";
+        let table = parse_multiline_table(text).unwrap();
+        assert_eq!(table.header, None);
+        // Alignments come from the first row
+        assert_eq!(table.alignments, vec![Alignment::Center, Alignment::Left]);
+        assert_eq!(
+            table.rows,
+            vec![
+                vec!["a".to_string(), "b".to_string()],
+                vec!["c".to_string(), "d".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_multiline_table_rejects_non_tables() {
+        // Metadata and thematic breaks have a single column
+        assert_eq!(parse_multiline_table("---\ntitle: x\n---"), None);
+        assert_eq!(parse_multiline_table("-----\ntext\n-----"), None);
+        // Rows must follow the separator directly
+        assert_eq!(parse_multiline_table("--- ---\n\na   b\n--- ---"), None);
+        // The table must be closed
+        assert_eq!(parse_multiline_table("--- ---\na   b"), None);
+    }
+
+    #[test]
+    fn test_find_tables() {
+        let input = "\
+Text.
+
++---+
+| a |
++---+
+
+::: {.div}
+--- ---
+a   b
+
+c   d
+--- ---
+:::
+
++---+
+| a |
++---+
+not a table
+";
+        let tables = find_tables(input);
+        let texts: Vec<&str> = tables.iter().map(|range| &input[range.clone()]).collect();
+        assert_eq!(
+            texts,
+            vec!["+---+\n| a |\n+---+", "--- ---\na   b\n\nc   d\n--- ---"]
+        );
+    }
+
+    #[test]
+    fn test_mask_tables() {
+        let input = "x\n\n--- ---\na   b\n\nc   d\n--- ---\n";
+        let masked = mask_tables(input.as_bytes(), &find_tables(input));
+        assert_eq!(
+            String::from_utf8(masked).unwrap(),
+            "x\n\nxxxxxxx\nxxxxx\n\nxxxxx\nxxxxxxx\n"
+        );
+    }
+}
@@ -1057,6 +1057,7 @@ fn read_ast_context(value: &Value) -> Result<ASTContext> {
         example_list_counter: std::cell::Cell::new(1),
         source_context,
         parent_source_info: None,
+        extensions: crate::options::FormatExtensions::default(),
     })
 }
 
@@ -4,6 +4,7 @@
  */
 
 pub mod commonmark;
+pub mod grid_table;
 pub mod json;
 pub mod qmd;
 pub mod qmd_error_message_table;
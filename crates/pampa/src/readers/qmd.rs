@@ -7,18 +7,26 @@ use crate::filter_context::FilterContext;
 use crate::filters::FilterReturn::Unchanged;
 use crate::filters::topdown_traverse;
 use crate::filters::{Filter, FilterReturn};
+use crate::options::FormatExtensions;
 use crate::pandoc::ast_context::ASTContext;
+use crate::pandoc::attr::{AttrSourceInfo, empty_attr};
 use crate::pandoc::block::MetaBlock;
 use crate::pandoc::rawblock_to_config_value;
-use crate::pandoc::{self, Block};
+use crate::pandoc::table::{Alignment, Cell, Row, Table, TableBody, TableFoot, TableHead};
+use crate::pandoc::{self, Block, Caption, Plain};
+use crate::readers::grid_table::{
+    GridTable, find_tables, mask_tables, parse_grid_table, parse_multiline_table,
+};
 use crate::readers::qmd_error_messages::{produce_diagnostic_messages, produce_error_message_json};
 use crate::traversals;
 use crate::utils::diagnostic_collector::DiagnosticCollector;
 use quarto_pandoc_types::{ConfigMapEntry, ConfigValueKind};
 use quarto_parse_errors::TreeSitterLogObserverTrait;
 use std::io::Write;
+use std::ops::Range;
 use tree_sitter::LogType;
-use tree_sitter_qmd::MarkdownParser;
+pub use tree_sitter::{InputEdit, Point};
+use tree_sitter_qmd::{MarkdownParser, MarkdownTree};
 
 fn print_whole_tree<T: Write>(cursor: &mut tree_sitter_qmd::MarkdownCursor, buf: &mut T) {
     let mut depth = 0;
@@ -47,13 +55,57 @@ pub fn read_bad_qmd_for_error_message(input_bytes: &[u8]) -> Vec<String> {
     produce_error_message_json(&log_observer)
 }
 
+/// Options controlling how the qmd reader interprets its input.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QmdReaderOptions {
+    /// Markdown extensions to honor while parsing.
+    pub extensions: FormatExtensions,
+}
+
+impl QmdReaderOptions {
+    /// Build reader options from a format's `+ext`/`-ext` modifiers.
+    pub fn from_extensions(extensions: &crate::options::ExtensionsDiff) -> Self {
+        Self {
+            extensions: FormatExtensions::from_diff(extensions),
+        }
+    }
+}
+
 pub fn read<T: Write>(
+    input_bytes: &[u8],
+    loose: bool,
+    filename: &str,
+    output_stream: &mut T,
+    prune_errors: bool,
+    parent_source_info: Option<quarto_source_map::SourceInfo>,
+) -> Result<
+    (
+        pandoc::Pandoc,
+        ASTContext,
+        Vec<quarto_error_reporting::DiagnosticMessage>,
+    ),
+    Vec<quarto_error_reporting::DiagnosticMessage>,
+> {
+    read_with_options(
+        input_bytes,
+        loose,
+        filename,
+        output_stream,
+        prune_errors,
+        parent_source_info,
+        &QmdReaderOptions::default(),
+    )
+}
+
+/// Like [`read`], but with explicit [`QmdReaderOptions`].
+pub fn read_with_options<T: Write>(
     input_bytes: &[u8],
     _loose: bool,
     filename: &str,
-    mut output_stream: &mut T,
+    output_stream: &mut T,
     prune_errors: bool,
     parent_source_info: Option<quarto_source_map::SourceInfo>,
+    options: &QmdReaderOptions,
 ) -> Result<
     (
         pandoc::Pandoc,
@@ -62,6 +114,134 @@ pub fn read<T: Write>(
     ),
     Vec<quarto_error_reporting::DiagnosticMessage>,
 > {
+    read_tree(
+        input_bytes,
+        None,
+        filename,
+        output_stream,
+        prune_errors,
+        parent_source_info,
+        options,
+    )
+    .result
+}
+
+/// The result of an incremental read: the concrete syntax tree for the text
+/// that was read, and the Pandoc AST (or parse errors) built from it.
+///
+/// The tree is returned even when the read fails, since it still matches
+/// the text and can be edited for the next [`reparse_with_edit`].
+pub struct IncrementalRead {
+    pub tree: MarkdownTree,
+    pub result: Result<
+        (
+            pandoc::Pandoc,
+            ASTContext,
+            Vec<quarto_error_reporting::DiagnosticMessage>,
+        ),
+        Vec<quarto_error_reporting::DiagnosticMessage>,
+    >,
+    /// Byte ranges of the grid and multiline tables masked from tree-sitter.
+    tables: Vec<Range<usize>>,
+}
+
+/// Like [`read_with_options`], but also returns the syntax tree so the
+/// document can later be re-read with [`reparse_with_edit`].
+pub fn read_with_tree<T: Write>(
+    input_bytes: &[u8],
+    filename: &str,
+    output_stream: &mut T,
+    prune_errors: bool,
+    options: &QmdReaderOptions,
+) -> IncrementalRead {
+    read_tree(
+        input_bytes,
+        None,
+        filename,
+        output_stream,
+        prune_errors,
+        None,
+        options,
+    )
+}
+
+/// Re-read a document after a text edit, reusing the unchanged parts of the
+/// previous syntax tree.
+///
+/// `previous` is the result of the last [`read_with_tree`] or
+/// [`reparse_with_edit`] call, `edit` describes the change from that text
+/// to `input_bytes` (see [`input_edit`]). Tree-sitter only re-parses the
+/// regions the edit touched; the Pandoc AST is then rebuilt from the
+/// updated tree, so the result is the same as reading `input_bytes` from
+/// scratch.
+///
+/// Documents with grid or multiline tables are parsed from scratch, since
+/// the masking of their tables can change outside the edited region.
+pub fn reparse_with_edit<T: Write>(
+    previous: &IncrementalRead,
+    edit: &InputEdit,
+    input_bytes: &[u8],
+    filename: &str,
+    output_stream: &mut T,
+    prune_errors: bool,
+    options: &QmdReaderOptions,
+) -> IncrementalRead {
+    let has_tables = !previous.tables.is_empty()
+        || std::str::from_utf8(input_bytes).is_ok_and(|text| !find_tables(text).is_empty());
+    let mut old_tree = previous.tree.clone();
+    old_tree.edit(edit);
+    read_tree(
+        input_bytes,
+        (!has_tables).then_some(&old_tree),
+        filename,
+        output_stream,
+        prune_errors,
+        None,
+        options,
+    )
+}
+
+/// Describe replacing `range` of `old_input` with `replacement` as a
+/// tree-sitter [`InputEdit`], computing the row/column positions.
+///
+/// Columns are byte offsets within the line, as tree-sitter expects.
+pub fn input_edit(old_input: &[u8], range: Range<usize>, replacement: &[u8]) -> InputEdit {
+    fn advance(mut point: Point, bytes: &[u8]) -> Point {
+        for &byte in bytes {
+            if byte == b'\n' {
+                point.row += 1;
+                point.column = 0;
+            } else {
+                point.column += 1;
+            }
+        }
+        point
+    }
+    let start_position = advance(Point::new(0, 0), &old_input[..range.start]);
+    InputEdit {
+        start_byte: range.start,
+        old_end_byte: range.end,
+        new_end_byte: range.start + replacement.len(),
+        start_position,
+        old_end_position: advance(start_position, &old_input[range.clone()]),
+        new_end_position: advance(start_position, replacement),
+    }
+}
+
+/// Parse `input_bytes`, reusing `old_tree` (already edited to match the new
+/// text) when given, and convert the tree to Pandoc.
+///
+/// Tree-sitter sees the text with its grid and multiline tables masked (see
+/// [`mask_tables`]); everything else reads the original text.
+fn read_tree<T: Write>(
+    input_bytes: &[u8],
+    old_tree: Option<&MarkdownTree>,
+    filename: &str,
+    mut output_stream: &mut T,
+    prune_errors: bool,
+    parent_source_info: Option<quarto_source_map::SourceInfo>,
+    options: &QmdReaderOptions,
+) -> IncrementalRead {
     let mut parser = MarkdownParser::default();
     let mut fast_log_observer = quarto_parse_errors::TreeSitterLogObserverFast::default();
     let mut log_observer = quarto_parse_errors::TreeSitterLogObserver::default();
@@ -77,24 +257,33 @@ pub fn read<T: Write>(
         let mut input_bytes_with_newline = Vec::with_capacity(input_bytes.len() + 1);
         input_bytes_with_newline.extend_from_slice(input_bytes);
         input_bytes_with_newline.push(b'\n');
-        return read(
+        return read_tree(
             &input_bytes_with_newline,
-            _loose,
+            old_tree,
             filename,
             output_stream,
             prune_errors,
             parent_source_info,
+            options,
         );
     }
 
+    let tables = std::str::from_utf8(input_bytes)
+        .map(find_tables)
+        .unwrap_or_default();
+    let masked_bytes = mask_tables(input_bytes, &tables);
     let tree = parser
-        .parse(input_bytes, None)
+        .parse(&masked_bytes, old_tree)
         .expect("Failed to parse input");
+    // Errors inside subtrees reused from `old_tree` are not logged again
+    let had_errors = fast_log_observer.had_errors()
+        || (old_tree.is_some() && tree.block_tree().root_node().has_error());
 
     // Create ASTContext early so we can use it for error diagnostics
     let mut context = ASTContext::with_filename(filename.to_string());
     // Store parent source info for recursive parses
     context.parent_source_info = parent_source_info;
+    context.extensions = options.extensions.clone();
     // Add the input content to the SourceContext for proper error rendering
     let input_str = String::from_utf8_lossy(input_bytes).to_string();
     context.source_context = quarto_source_map::SourceContext::new();
@@ -102,14 +291,14 @@ pub fn read<T: Write>(
         .source_context
         .add_file(filename.to_string(), Some(input_str));
 
-    if fast_log_observer.had_errors() {
+    if had_errors {
         parser.parser.set_logger(Some(Box::new(|log_type, message| {
             if log_type == LogType::Parse {
                 log_observer.log(log_type, message);
             }
         })));
         parser
-            .parse(input_bytes, None)
+            .parse(&masked_bytes, None)
             .expect("Failed to parse input");
         log_observer.parses.iter().for_each(|parse| {
             writeln!(output_stream, "tree-sitter parse:").unwrap();
@@ -141,7 +330,11 @@ pub fn read<T: Write>(
                     prune_diagnostics_by_error_nodes(diagnostics, &error_nodes, &outer_nodes);
             }
 
-            return Err(diagnostics);
+            return IncrementalRead {
+                tree,
+                result: Err(diagnostics),
+                tables,
+            };
         }
     }
 
@@ -153,7 +346,11 @@ pub fn read<T: Write>(
             "The input document is too deeply nested (max depth: {} > 100).",
             depth
         ));
-        return Err(vec![diagnostic]);
+        return IncrementalRead {
+            tree,
+            result: Err(vec![diagnostic]),
+            tables,
+        };
     }
 
     // Note: We no longer need to check parse_is_good(&tree) here because
@@ -174,7 +371,11 @@ pub fn read<T: Write>(
         Ok(pandoc) => pandoc,
         Err(diagnostics) => {
             // Return diagnostics directly
-            return Err(diagnostics);
+            return IncrementalRead {
+                tree,
+                result: Err(diagnostics),
+                tables,
+            };
         }
     };
     // Store ConfigMapEntry objects directly (Phase 5: no MetaValueWithSourceInfo conversion)
@@ -244,8 +445,209 @@ pub fn read<T: Write>(
         error_collector.add(diagnostic);
     }
 
+    // Grid and multiline tables were masked from tree-sitter and arrive as paragraphs
+    result = desugar_grid_tables(
+        result,
+        input_bytes,
+        &tables,
+        filename,
+        options,
+        &mut error_collector,
+    );
+
     // Collect all warnings
     let warnings = error_collector.into_diagnostics();
 
-    Ok((result, context, warnings))
+    IncrementalRead {
+        tree,
+        result: Ok((result, context, warnings)),
+        tables,
+    }
+}
+
+/// Replace the placeholder paragraphs of each masked table with a `Table`
+/// block read from the table's source text.
+///
+/// A multiline table's blank lines split it into several paragraphs: the
+/// first becomes the table and the rest are dropped.
+fn desugar_grid_tables(
+    doc: pandoc::Pandoc,
+    input_bytes: &[u8],
+    tables: &[Range<usize>],
+    filename: &str,
+    options: &QmdReaderOptions,
+    error_collector: &mut DiagnosticCollector,
+) -> pandoc::Pandoc {
+    if tables.is_empty() {
+        return doc;
+    }
+    let mut filter = Filter::new().with_paragraph(|para, _ctx| {
+        let start = para.source_info.start_offset();
+        let Some(table) = tables.iter().find(|table| table.contains(&start)) else {
+            return Unchanged(para);
+        };
+        if table.start != start {
+            return FilterReturn::FilterResult(vec![], false);
+        }
+        let Some(grid) = std::str::from_utf8(&input_bytes[table.clone()])
+            .ok()
+            .and_then(|text| {
+                if text.starts_with('+') {
+                    parse_grid_table(text)
+                } else {
+                    parse_multiline_table(text)
+                }
+            })
+        else {
+            return Unchanged(para);
+        };
+        // Cover the whole table, not just its first paragraph
+        let source_info = match para.source_info.clone() {
+            quarto_source_map::SourceInfo::Original {
+                file_id,
+                start_offset,
+                ..
+            } => quarto_source_map::SourceInfo::original(file_id, start_offset, table.end),
+            quarto_source_map::SourceInfo::Substring {
+                parent,
+                start_offset,
+                ..
+            } => quarto_source_map::SourceInfo::Substring {
+                parent,
+                start_offset,
+                end_offset: table.end,
+            },
+            other => other,
+        };
+        match grid_table_to_block(&grid, &source_info, filename, options, error_collector) {
+            Some(table) => FilterReturn::FilterResult(vec![table], false),
+            None => Unchanged(para),
+        }
+    });
+    let mut ctx = FilterContext::new();
+    topdown_traverse(doc, &mut filter, &mut ctx)
+}
+
+/// Build a `Table` from a parsed grid table, reading each cell's text as qmd.
+/// Returns `None` if any cell fails to parse, leaving the paragraph as is.
+///
+/// Cell text is stitched together from column slices of several source lines,
+/// so locations inside cells are reported relative to the table's start.
+fn grid_table_to_block(
+    grid: &GridTable,
+    source_info: &quarto_source_map::SourceInfo,
+    filename: &str,
+    options: &QmdReaderOptions,
+    error_collector: &mut DiagnosticCollector,
+) -> Option<Block> {
+    let head_rows = match &grid.header {
+        Some(cells) => vec![grid_table_row(
+            cells,
+            source_info,
+            filename,
+            options,
+            error_collector,
+        )?],
+        None => vec![],
+    };
+    let mut body_rows = Vec::new();
+    for cells in &grid.rows {
+        body_rows.push(grid_table_row(
+            cells,
+            source_info,
+            filename,
+            options,
+            error_collector,
+        )?);
+    }
+
+    Some(Block::Table(Table {
+        attr: empty_attr(),
+        caption: Caption {
+            short: None,
+            long: None,
+            source_info: source_info.clone(),
+        },
+        colspec: grid
+            .alignments
+            .iter()
+            .zip(&grid.widths)
+            .map(|(alignment, width)| (alignment.clone(), width.clone()))
+            .collect(),
+        head: TableHead {
+            attr: empty_attr(),
+            rows: head_rows,
+            source_info: source_info.clone(),
+            attr_source: AttrSourceInfo::empty(),
+        },
+        bodies: vec![TableBody {
+            attr: empty_attr(),
+            rowhead_columns: 0,
+            head: vec![],
+            body: body_rows,
+            source_info: source_info.clone(),
+            attr_source: AttrSourceInfo::empty(),
+        }],
+        foot: TableFoot {
+            attr: empty_attr(),
+            rows: vec![],
+            source_info: source_info.clone(),
+            attr_source: AttrSourceInfo::empty(),
+        },
+        source_info: source_info.clone(),
+        attr_source: AttrSourceInfo::empty(),
+    }))
+}
+
+fn grid_table_row(
+    cell_texts: &[String],
+    source_info: &quarto_source_map::SourceInfo,
+    filename: &str,
+    options: &QmdReaderOptions,
+    error_collector: &mut DiagnosticCollector,
+) -> Option<Row> {
+    let mut cells = Vec::new();
+    for text in cell_texts {
+        let content = if text.is_empty() {
+            vec![]
+        } else {
+            let (cell_doc, _context, warnings) = read_with_options(
+                text.as_bytes(),
+                false,
+                filename,
+                &mut std::io::sink(),
+                true,
+                Some(source_info.clone()),
+                options,
+            )
+            .ok()?;
+            for warning in warnings {
+                error_collector.add(warning);
+            }
+            // A lone paragraph is plain cell text, as in pipe tables
+            match <[Block; 1]>::try_from(cell_doc.blocks) {
+                Ok([Block::Paragraph(para)]) => vec![Block::Plain(Plain {
+                    content: para.content,
+                    source_info: para.source_info,
+                })],
+                Ok(blocks) => blocks.into(),
+                Err(blocks) => blocks,
+            }
+        };
+        cells.push(Cell {
+            attr: empty_attr(),
+            alignment: Alignment::Default,
+            row_span: 1,
+            col_span: 1,
+            content,
+            source_info: source_info.clone(),
+            attr_source: AttrSourceInfo::empty(),
+        });
+    }
+    Some(Row {
+        attr: empty_attr(),
+        cells,
+        source_info: source_info.clone(),
+        attr_source: AttrSourceInfo::empty(),
+    })
 }
@@ -10,6 +10,8 @@
 
 use std::collections::HashSet;
 
+use quarto_error_reporting::{DiagnosticMessage, Fix, FixEdit};
+
 // Re-export generic functions from quarto-parse-errors
 pub use quarto_parse_errors::{get_outer_error_nodes, prune_diagnostics_by_error_nodes};
 
@@ -20,20 +22,75 @@ use crate::readers::qmd_error_message_table::get_error_table;
 
 /// Produce structured DiagnosticMessage objects from parse errors.
 ///
-/// This is a QMD-specific wrapper that provides the error table automatically.
+/// This is a QMD-specific wrapper that provides the error table automatically,
+/// and attaches fixes to the errors that have an obvious one.
 pub fn produce_diagnostic_messages(
     input_bytes: &[u8],
     tree_sitter_log: &TreeSitterLogObserver,
     filename: &str,
     source_context: &quarto_source_map::SourceContext,
-) -> Vec<quarto_error_reporting::DiagnosticMessage> {
-    quarto_parse_errors::produce_diagnostic_messages(
+) -> Vec<DiagnosticMessage> {
+    let mut diagnostics = quarto_parse_errors::produce_diagnostic_messages(
         input_bytes,
         tree_sitter_log,
         get_error_table(),
         filename,
         source_context,
-    )
+    );
+    diagnostics.iter_mut().for_each(add_fix);
+    diagnostics
+}
+
+/// The closing delimiter missing at the end of the block, for the
+/// "Unclosed ..." errors.
+fn missing_closer(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "Q-2-1" | "Q-2-21" | "Q-2-22" | "Q-2-26" => "]",
+        "Q-2-5" | "Q-2-14" => "_",
+        "Q-2-11" => "\"",
+        "Q-2-12" => "*",
+        "Q-2-13" => "**",
+        "Q-2-15" => "__",
+        "Q-2-16" => "^",
+        "Q-2-17" => "~",
+        "Q-2-18" => "~~",
+        "Q-2-19" => "++]",
+        "Q-2-20" => "--]",
+        "Q-2-23" => "$",
+        "Q-2-24" => "`",
+        "Q-2-25" => "](url)",
+        _ => return None,
+    })
+}
+
+/// Attach the fix of a parse error, as the `qmd-syntax-helper` rules for
+/// these codes apply it.
+fn add_fix(diagnostic: &mut DiagnosticMessage) {
+    let Some(code) = diagnostic.code.as_deref() else {
+        return;
+    };
+    let fix = if let Some(closer) = missing_closer(code) {
+        // Unclosed delimiters are reported where the block ends
+        let Some(location) = &diagnostic.location else {
+            return;
+        };
+        Fix::new(
+            format!("Insert closing `{}`", closer),
+            vec![FixEdit::insert_before(location, closer)],
+        )
+    } else if code == "Q-2-7" {
+        // The first detail is the apostrophe that was read as an open quote
+        let Some(location) = diagnostic.details.first().and_then(|d| d.location.as_ref()) else {
+            return;
+        };
+        Fix::new(
+            "Escape the apostrophe",
+            vec![FixEdit::insert_before(location, "\\")],
+        )
+    } else {
+        return;
+    };
+    diagnostic.fixes.push(fix);
 }
 
 /// Produce error message JSON for corpus building.
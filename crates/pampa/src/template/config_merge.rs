@@ -26,128 +26,27 @@
 //! let template_ctx = config_to_template_context(&materialized, MetaWriter::Html);
 //! ```
 
-use crate::template::context::MetaWriter;
+use crate::template::context::{MetaWriter, metadata_to_context};
 use crate::writers::plaintext;
 use quarto_config::{ConfigMapEntry, ConfigValue, ConfigValueKind, MergeOp, MergedConfig};
 use quarto_doctemplate::{TemplateContext, TemplateValue};
 use quarto_error_reporting::DiagnosticMessage;
 use quarto_source_map::SourceInfo;
-use std::collections::HashMap;
 use yaml_rust2::Yaml;
 
 // =============================================================================
-// ConfigValue -> TemplateValue
+// ConfigValue -> TemplateContext
 // =============================================================================
 
-/// Context for ConfigValue to TemplateValue conversion.
-pub struct ConfigConversionContext {
-    pub writer: MetaWriter,
-    pub diagnostics: Vec<DiagnosticMessage>,
-}
-
-impl ConfigConversionContext {
-    pub fn new(writer: MetaWriter) -> Self {
-        Self {
-            writer,
-            diagnostics: Vec::new(),
-        }
-    }
-
-    fn add_diagnostics(&mut self, diags: Vec<DiagnosticMessage>) {
-        self.diagnostics.extend(diags);
-    }
-}
-
-/// Convert a `ConfigValue` to a `TemplateValue`.
-///
-/// This handles rendering of PandocInlines/PandocBlocks using the specified writer.
-pub fn config_to_template_value(
-    config: &ConfigValue,
-    ctx: &mut ConfigConversionContext,
-) -> TemplateValue {
-    match &config.value {
-        ConfigValueKind::Scalar(yaml) => yaml_to_template_value(yaml),
-        ConfigValueKind::Path(s) | ConfigValueKind::Glob(s) | ConfigValueKind::Expr(s) => {
-            // Deferred interpretation variants - just return as string
-            TemplateValue::String(s.clone())
-        }
-        ConfigValueKind::Array(items) => {
-            let values: Vec<TemplateValue> = items
-                .iter()
-                .map(|item| config_to_template_value(item, ctx))
-                .collect();
-            TemplateValue::List(values)
-        }
-        ConfigValueKind::Map(entries) => {
-            let map: HashMap<String, TemplateValue> = entries
-                .iter()
-                .map(|entry| {
-                    (
-                        entry.key.clone(),
-                        config_to_template_value(&entry.value, ctx),
-                    )
-                })
-                .collect();
-            TemplateValue::Map(map)
-        }
-        ConfigValueKind::PandocInlines(inlines) => {
-            let (rendered, diags) = ctx.writer.render_inlines(inlines);
-            ctx.add_diagnostics(diags);
-            TemplateValue::String(rendered)
-        }
-        ConfigValueKind::PandocBlocks(blocks) => {
-            let (rendered, diags) = ctx.writer.render_blocks(blocks);
-            ctx.add_diagnostics(diags);
-            TemplateValue::String(rendered)
-        }
-    }
-}
-
-/// Convert a YAML value to a TemplateValue.
-fn yaml_to_template_value(yaml: &Yaml) -> TemplateValue {
-    match yaml {
-        Yaml::String(s) => TemplateValue::String(s.clone()),
-        Yaml::Boolean(b) => TemplateValue::Bool(*b),
-        Yaml::Integer(i) => TemplateValue::String(i.to_string()),
-        Yaml::Real(r) => TemplateValue::String(r.clone()),
-        Yaml::Array(arr) => {
-            let values: Vec<TemplateValue> = arr.iter().map(yaml_to_template_value).collect();
-            TemplateValue::List(values)
-        }
-        Yaml::Hash(hash) => {
-            let map: HashMap<String, TemplateValue> = hash
-                .iter()
-                .filter_map(|(k, v)| {
-                    k.as_str()
-                        .map(|key| (key.to_string(), yaml_to_template_value(v)))
-                })
-                .collect();
-            TemplateValue::Map(map)
-        }
-        Yaml::Null => TemplateValue::String(String::new()),
-        Yaml::BadValue => TemplateValue::String(String::new()),
-        Yaml::Alias(_) => TemplateValue::String(String::new()),
-    }
-}
-
 /// Convert a `ConfigValue` to a `TemplateContext`.
 ///
-/// The config value should be a Map at the root level.
+/// The config value should be a Map at the root level. Values are converted
+/// with the shared [`metadata_to_context`] conversion.
 pub fn config_to_template_context(
     config: &ConfigValue,
     writer: MetaWriter,
 ) -> (TemplateContext, Vec<DiagnosticMessage>) {
-    let mut ctx = TemplateContext::new();
-    let mut conv_ctx = ConfigConversionContext::new(writer);
-
-    if let ConfigValueKind::Map(entries) = &config.value {
-        for entry in entries {
-            let template_value = config_to_template_value(&entry.value, &mut conv_ctx);
-            ctx.insert(entry.key.clone(), template_value);
-        }
-    }
-
-    (ctx, conv_ctx.diagnostics)
+    metadata_to_context(config, writer)
 }
 
 // =============================================================================
@@ -272,6 +171,7 @@ pub fn merged_metadata_to_context(
 mod tests {
     use super::*;
     use crate::pandoc::inline::{Emph, Inline, Space, Str};
+    use crate::template::context::{ConversionContext, meta_to_template_value};
 
     fn dummy_source_info() -> SourceInfo {
         SourceInfo::default()
@@ -293,8 +193,8 @@ mod tests {
     #[test]
     fn test_config_to_template_value_scalar() {
         let config = ConfigValue::new_string("test", dummy_source_info());
-        let mut ctx = ConfigConversionContext::new(MetaWriter::Html);
-        let result = config_to_template_value(&config, &mut ctx);
+        let mut ctx = ConversionContext::new(MetaWriter::Html);
+        let result = meta_to_template_value(&config, &mut ctx);
 
         assert_eq!(result, TemplateValue::String("test".to_string()));
     }
@@ -302,8 +202,8 @@ mod tests {
     #[test]
     fn test_config_to_template_value_bool() {
         let config = ConfigValue::new_bool(true, dummy_source_info());
-        let mut ctx = ConfigConversionContext::new(MetaWriter::Html);
-        let result = config_to_template_value(&config, &mut ctx);
+        let mut ctx = ConversionContext::new(MetaWriter::Html);
+        let result = meta_to_template_value(&config, &mut ctx);
 
         assert_eq!(result, TemplateValue::Bool(true));
     }
@@ -312,8 +212,8 @@ mod tests {
     fn test_config_to_template_value_inlines() {
         let inlines = vec![make_str("hello"), make_space(), make_str("world")];
         let config = ConfigValue::new_inlines(inlines, dummy_source_info());
-        let mut ctx = ConfigConversionContext::new(MetaWriter::Html);
-        let result = config_to_template_value(&config, &mut ctx);
+        let mut ctx = ConversionContext::new(MetaWriter::Html);
+        let result = meta_to_template_value(&config, &mut ctx);
 
         // Inlines rendered to HTML
         assert_eq!(result, TemplateValue::String("hello world".to_string()));
@@ -445,8 +345,8 @@ mod tests {
             ConfigValue::new_string("b", dummy_source_info()),
         ];
         let config = ConfigValue::new_array(items, dummy_source_info());
-        let mut ctx = ConfigConversionContext::new(MetaWriter::Html);
-        let result = config_to_template_value(&config, &mut ctx);
+        let mut ctx = ConversionContext::new(MetaWriter::Html);
+        let result = meta_to_template_value(&config, &mut ctx);
 
         match result {
             TemplateValue::List(items) => {
@@ -466,8 +366,8 @@ mod tests {
             value: ConfigValue::new_string("value", dummy_source_info()),
         }];
         let config = ConfigValue::new_map(entries, dummy_source_info());
-        let mut ctx = ConfigConversionContext::new(MetaWriter::Html);
-        let result = config_to_template_value(&config, &mut ctx);
+        let mut ctx = ConversionContext::new(MetaWriter::Html);
+        let result = meta_to_template_value(&config, &mut ctx);
 
         match result {
             TemplateValue::Map(map) => {
@@ -485,8 +385,8 @@ mod tests {
     fn test_config_to_template_value_path() {
         // Path variant should convert to string
         let config = ConfigValue::new_path("./data.csv".to_string(), dummy_source_info());
-        let mut ctx = ConfigConversionContext::new(MetaWriter::Html);
-        let result = config_to_template_value(&config, &mut ctx);
+        let mut ctx = ConversionContext::new(MetaWriter::Html);
+        let result = meta_to_template_value(&config, &mut ctx);
 
         assert_eq!(result, TemplateValue::String("./data.csv".to_string()));
     }
@@ -5,13 +5,14 @@
 
 //! Conversion from Pandoc metadata to template context.
 //!
-//! This module handles the conversion from `ConfigValue` to
-//! `TemplateValue`, including rendering `PandocInlines` and `PandocBlocks`
-//! using the appropriate writer.
+//! This module is the one place where document metadata becomes template
+//! values. It handles the conversion from `ConfigValue` to `TemplateValue`,
+//! rendering `PandocInlines` and `PandocBlocks` with the writer for the
+//! document's body format, so every format sees metadata the same way.
 
 use crate::pandoc::block::Block;
 use crate::pandoc::inline::Inlines;
-use crate::writers::{html, plaintext};
+use crate::writers::{html, latex, plaintext, typst};
 use quarto_doctemplate::{TemplateContext, TemplateValue};
 use quarto_error_reporting::DiagnosticMessage;
 use quarto_pandoc_types::{ConfigValue, ConfigValueKind};
@@ -29,6 +30,10 @@ pub enum MetaWriter {
     Html,
     /// Render as plain text (no markup).
     Plaintext,
+    /// Render as LaTeX.
+    Latex,
+    /// Render as Typst.
+    Typst,
 }
 
 impl MetaWriter {
@@ -49,6 +54,8 @@ impl MetaWriter {
                 (String::from_utf8_lossy(&buf).into_owned(), diagnostics)
             }
             MetaWriter::Plaintext => plaintext::inlines_to_string(inlines),
+            MetaWriter::Latex => latex::inlines_to_string(inlines),
+            MetaWriter::Typst => typst::inlines_to_string(inlines),
         }
     }
 
@@ -69,6 +76,24 @@ impl MetaWriter {
                 (String::from_utf8_lossy(&buf).into_owned(), diagnostics)
             }
             MetaWriter::Plaintext => plaintext::blocks_to_string(blocks),
+            MetaWriter::Latex => {
+                let (text, diagnostics) = latex::blocks_to_string(blocks);
+                (text.trim_end().to_string(), diagnostics)
+            }
+            MetaWriter::Typst => {
+                let (text, diagnostics) = typst::blocks_to_string(blocks);
+                (text.trim_end().to_string(), diagnostics)
+            }
+        }
+    }
+
+    /// Escape literal text so it reads as-is in this writer's output.
+    pub fn escape_text(&self, text: &str) -> String {
+        match self {
+            MetaWriter::Html => html::escape_html(text),
+            MetaWriter::Plaintext => text.to_string(),
+            MetaWriter::Latex => latex::escape_latex(text),
+            MetaWriter::Typst => typst::escape_typst(text),
         }
     }
 }
@@ -96,16 +121,11 @@ impl ConversionContext {
 ///
 /// This recursively converts the metadata structure, rendering any
 /// `PandocInlines` or `PandocBlocks` to strings using the specified writer.
+/// Scalar strings are passed through unescaped, as Pandoc does for
+/// variables; use [`meta_to_text`] for values inserted as document text.
 pub fn meta_to_template_value(meta: &ConfigValue, ctx: &mut ConversionContext) -> TemplateValue {
     match &meta.value {
-        ConfigValueKind::Scalar(yaml) => match yaml {
-            Yaml::String(s) => TemplateValue::String(s.clone()),
-            Yaml::Boolean(b) => TemplateValue::Bool(*b),
-            Yaml::Integer(i) => TemplateValue::String(i.to_string()),
-            Yaml::Real(r) => TemplateValue::String(r.clone()),
-            Yaml::Null => TemplateValue::Null,
-            _ => TemplateValue::Null,
-        },
+        ConfigValueKind::Scalar(yaml) => yaml_to_template_value(yaml),
         ConfigValueKind::PandocInlines(content) => {
             let (rendered, diags) = ctx.writer.render_inlines(content);
             ctx.add_diagnostics(diags);
@@ -140,6 +160,53 @@ pub fn meta_to_template_value(meta: &ConfigValue, ctx: &mut ConversionContext) -
     }
 }
 
+/// Convert a YAML scalar (or an untyped YAML tree) to a `TemplateValue`.
+///
+/// Numbers become their text, and null or invalid values become
+/// `TemplateValue::Null`, which renders as empty and is falsy.
+pub fn yaml_to_template_value(yaml: &Yaml) -> TemplateValue {
+    match yaml {
+        Yaml::String(s) => TemplateValue::String(s.clone()),
+        Yaml::Boolean(b) => TemplateValue::Bool(*b),
+        Yaml::Integer(i) => TemplateValue::String(i.to_string()),
+        Yaml::Real(r) => TemplateValue::String(r.clone()),
+        Yaml::Array(items) => {
+            TemplateValue::List(items.iter().map(yaml_to_template_value).collect())
+        }
+        Yaml::Hash(hash) => TemplateValue::Map(
+            hash.iter()
+                .filter_map(|(k, v)| {
+                    k.as_str()
+                        .map(|key| (key.to_string(), yaml_to_template_value(v)))
+                })
+                .collect(),
+        ),
+        Yaml::Null | Yaml::BadValue | Yaml::Alias(_) => TemplateValue::Null,
+    }
+}
+
+/// Render a single metadata value as text in the writer's format.
+///
+/// Inlines and blocks are written with the writer and scalar strings are
+/// escaped, so the result can be inserted into the document as-is (titles,
+/// authors, abstracts). Returns `None` for lists, maps, and empty values.
+pub fn meta_to_text(meta: &ConfigValue, ctx: &mut ConversionContext) -> Option<String> {
+    let text = match &meta.value {
+        ConfigValueKind::PandocInlines(inlines) => {
+            let (rendered, diags) = ctx.writer.render_inlines(inlines);
+            ctx.add_diagnostics(diags);
+            rendered
+        }
+        ConfigValueKind::PandocBlocks(blocks) => {
+            let (rendered, diags) = ctx.writer.render_blocks(blocks);
+            ctx.add_diagnostics(diags);
+            rendered
+        }
+        _ => ctx.writer.escape_text(meta.as_str()?),
+    };
+    (!text.is_empty()).then_some(text)
+}
+
 /// Build a template context from a Pandoc document's metadata.
 ///
 /// This converts all metadata fields to template values and adds them
@@ -319,4 +386,74 @@ mod tests {
             Some(&TemplateValue::String("<p>Hello</p>".to_string()))
         );
     }
+
+    #[test]
+    fn test_meta_null_to_template_value() {
+        let meta = ConfigValue::new_scalar(Yaml::Null, dummy_source_info());
+        let mut ctx = ConversionContext::new(MetaWriter::Html);
+        assert_eq!(meta_to_template_value(&meta, &mut ctx), TemplateValue::Null);
+    }
+
+    #[test]
+    fn test_nested_yaml_to_template_value() {
+        let yaml = Yaml::Array(vec![Yaml::Integer(1), Yaml::Null]);
+        assert_eq!(
+            yaml_to_template_value(&yaml),
+            TemplateValue::List(vec![
+                TemplateValue::String("1".to_string()),
+                TemplateValue::Null,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_meta_inlines_rendered_per_writer() {
+        use crate::pandoc::inline::{Emph, Inline, Str};
+
+        let meta = ConfigValue::new_inlines(
+            vec![Inline::Emph(Emph {
+                content: vec![Inline::Str(Str {
+                    text: "hi".to_string(),
+                    source_info: dummy_source_info(),
+                })],
+                source_info: dummy_source_info(),
+            })],
+            dummy_source_info(),
+        );
+        let render = |writer| {
+            let mut ctx = ConversionContext::new(writer);
+            meta_to_template_value(&meta, &mut ctx)
+        };
+        assert_eq!(
+            render(MetaWriter::Html),
+            TemplateValue::String("<em>hi</em>".to_string())
+        );
+        assert_eq!(
+            render(MetaWriter::Plaintext),
+            TemplateValue::String("hi".to_string())
+        );
+        assert_eq!(
+            render(MetaWriter::Latex),
+            TemplateValue::String("\\emph{hi}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_meta_to_text_escapes_strings() {
+        let meta = ConfigValue::new_string("R&D 100%", dummy_source_info());
+        let mut ctx = ConversionContext::new(MetaWriter::Latex);
+        assert_eq!(
+            meta_to_text(&meta, &mut ctx),
+            Some("R\\&D 100\\%".to_string())
+        );
+
+        let mut ctx = ConversionContext::new(MetaWriter::Html);
+        assert_eq!(
+            meta_to_text(&meta, &mut ctx),
+            Some("R&amp;D 100%".to_string())
+        );
+
+        let empty = ConfigValue::new_string("", dummy_source_info());
+        assert_eq!(meta_to_text(&empty, &mut ctx), None);
+    }
 }
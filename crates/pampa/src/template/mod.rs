@@ -24,7 +24,9 @@
 //!
 //! # Template Bundles
 //!
-//! Templates can be provided as self-contained bundles (JSON format):
+//! Templates can be provided as self-contained bundles, which may also carry
+//! resources and metadata defaults, and can be loaded from JSON, a directory
+//! or a zip archive (see [`quarto_doctemplate::bundle`]):
 //!
 //! ```json
 //! {
@@ -48,12 +50,12 @@ pub mod render;
 
 // Re-export main types for convenience
 pub use builtin::{BUILTIN_TEMPLATE_NAMES, get_builtin_template, is_builtin_template};
-pub use bundle::TemplateBundle;
+pub use bundle::{BundleError, TemplateBundle};
 // Phase 5: Removed legacy config_value_to_meta and meta_to_config_value exports
 pub use config_merge::{
     compute_template_defaults, config_to_template_context, merged_metadata_to_context,
 };
-pub use context::{MetaWriter, meta_to_template_value, pandoc_to_context};
+pub use context::{MetaWriter, meta_to_template_value, meta_to_text, pandoc_to_context};
 pub use render::{BodyFormat, TemplateRenderError, render_with_bundle, render_with_resolver};
 
 // Re-export quarto-doctemplate types that users may need
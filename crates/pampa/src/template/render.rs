@@ -114,7 +114,8 @@ fn render_body(
 /// This is the primary entry point for template rendering. It:
 /// 1. Compiles the template bundle
 /// 2. Renders the document body using the specified format
-/// 3. Converts metadata to template values
+/// 3. Converts metadata to template values, filling in the bundle's metadata
+///    defaults
 /// 4. Evaluates the template with the context
 ///
 /// # Arguments
@@ -145,8 +146,7 @@ pub fn render_with_bundle(
     // This ensures template file IDs are unique within the same context as the main document,
     // allowing diagnostics from templates to be correctly attributed to their source files.
     let template = bundle.compile_with_context(template_name, &mut context.source_context)?;
-    let resolver = bundle.to_resolver();
-    render_with_compiled_template(pandoc, context, &template, &resolver, body_format)
+    render_compiled(pandoc, context, &template, body_format, Some(bundle))
 }
 
 /// Render a Pandoc document using a compiled template and custom resolver.
@@ -167,6 +167,17 @@ pub fn render_with_compiled_template<R: PartialResolver>(
     template: &Template,
     _resolver: &R,
     body_format: BodyFormat,
+) -> Result<(String, Vec<DiagnosticMessage>), TemplateRenderError> {
+    render_compiled(pandoc, context, template, body_format, None)
+}
+
+/// Render with a compiled template, taking metadata defaults from `bundle`.
+fn render_compiled(
+    pandoc: &Pandoc,
+    context: &ASTContext,
+    template: &Template,
+    body_format: BodyFormat,
+    bundle: Option<&TemplateBundle>,
 ) -> Result<(String, Vec<DiagnosticMessage>), TemplateRenderError> {
     let mut all_diagnostics = Vec::new();
 
@@ -177,8 +188,12 @@ pub fn render_with_compiled_template<R: PartialResolver>(
     // Convert metadata to template context using the merged config system.
     // This merges template defaults (lang, pagetitle) with document metadata.
     let meta_writer = body_format.meta_writer();
-    let (template_ctx, meta_diags) = merged_metadata_to_context(&pandoc.meta, body, meta_writer);
+    let (mut template_ctx, meta_diags) =
+        merged_metadata_to_context(&pandoc.meta, body, meta_writer);
     all_diagnostics.extend(meta_diags);
+    if let Some(bundle) = bundle {
+        bundle.apply_metadata_defaults(&mut template_ctx);
+    }
 
     // Render the template
     let (result, template_diags) = template.render_with_diagnostics(&template_ctx);
@@ -22,6 +22,7 @@
 //! let config = TocConfig {
 //!     depth: 3,
 //!     title: Some("Contents".to_string()),
+//!     ..Default::default()
 //! };
 //!
 //! let toc = generate_toc(&document.blocks, &config);
@@ -34,6 +35,18 @@
 //! - `unlisted` class: Heading is excluded from TOC entirely
 //! - `unnumbered` class: Heading is included but without section number
 //!
+//! ## Section IDs and Numbers
+//!
+//! With `auto_identifiers`, headings without an ID get one generated from
+//! their text, with numeric suffixes to keep IDs unique (see [`SectionIds`]).
+//! Writers should run [`assign_section_ids`] on the document so the headings
+//! carry the same IDs the TOC links to. With `number_sections`, entries get
+//! section numbers such as "1.2". Otherwise entries take the number from the
+//! heading's `number` attribute, if a numbering pass has set one.
+//!
+//! [`document_headings`] exposes the flat, resolved outline for other
+//! consumers such as editor outlines.
+//!
 //! ## Section Structure
 //!
 //! This module works with both flat headers and sectionized blocks:
@@ -48,6 +61,7 @@ use crate::pandoc::inline::Inline;
 use quarto_pandoc_types::config_value::{ConfigMapEntry, ConfigValue};
 use quarto_source_map::SourceInfo;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use yaml_rust2::Yaml;
 
 /// Configuration for TOC generation.
@@ -58,6 +72,13 @@ pub struct TocConfig {
 
     /// Title for the TOC (e.g., "Table of Contents")
     pub title: Option<String>,
+
+    /// Number sections (e.g., "1.2.3"), skipping `unnumbered` headings,
+    /// instead of using the headings' `number` attributes
+    pub number_sections: bool,
+
+    /// Generate IDs for headings without one (see [`assign_section_ids`])
+    pub auto_identifiers: bool,
 }
 
 impl Default for TocConfig {
@@ -65,6 +86,8 @@ impl Default for TocConfig {
         Self {
             depth: 3,
             title: None,
+            number_sections: false,
+            auto_identifiers: false,
         }
     }
 }
@@ -248,9 +271,14 @@ impl NavigationToc {
 /// - Headings deeper than `config.depth` are excluded
 /// - For sectionized blocks, the ID is taken from the section Div
 /// - For flat headers, the ID is taken directly from the header
+/// - Headings without an ID are excluded, unless `config.auto_identifiers`
+///   is set, in which case they get the ID [`assign_section_ids`] would give them
 pub fn generate_toc(blocks: &[Block], config: &TocConfig) -> NavigationToc {
-    let flat_entries = collect_toc_entries(blocks, config.depth);
-    let entries = build_hierarchy(flat_entries);
+    let listed = document_headings(blocks, config)
+        .into_iter()
+        .filter(|h| !h.unlisted && h.level <= config.depth && !h.id.is_empty())
+        .collect();
+    let entries = build_hierarchy(listed);
 
     NavigationToc {
         title: config.title.clone(),
@@ -258,50 +286,216 @@ pub fn generate_toc(blocks: &[Block], config: &TocConfig) -> NavigationToc {
     }
 }
 
-/// Internal representation during collection
-struct FlatTocEntry {
-    id: String,
-    title: String,
-    level: i32,
-    number: Option<String>,
+/// A document heading, in document order.
+///
+/// This is the flat document outline that the TOC is built from. It includes
+/// headings the TOC leaves out (`unlisted` or deeper than `toc-depth`), so
+/// other consumers such as editor outlines can use it too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heading {
+    /// Section ID, or empty if the heading has none
+    pub id: String,
+
+    /// Heading text (plain text, not inlines)
+    pub title: String,
+
+    /// Heading level (1-6)
+    pub level: i32,
+
+    /// Section number, if numbering is enabled or the heading has a
+    /// `number` attribute
+    pub number: Option<String>,
+
+    /// Whether the heading has the `unlisted` class
+    pub unlisted: bool,
+
+    /// Whether the heading has the `unnumbered` class
+    pub unnumbered: bool,
+}
+
+/// Collect all headings in the document, with IDs and numbers resolved
+/// according to `config`.
+///
+/// Section numbers start at the shallowest numbered heading level, so a
+/// document whose top headings are `##` is numbered "1", "1.1", ... rather
+/// than "0.1", "0.1.1", .... Unnumbered headings don't advance the numbering.
+pub fn document_headings(blocks: &[Block], config: &TocConfig) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    collect_headings(blocks, &mut headings);
+
+    if config.auto_identifiers {
+        resolve_ids(&mut headings);
+    }
+
+    if config.number_sections {
+        let top = headings
+            .iter()
+            .filter(|h| !h.unnumbered)
+            .map(|h| h.level)
+            .min()
+            .unwrap_or(1);
+        let mut counters: Vec<usize> = Vec::new();
+        for heading in headings.iter_mut().filter(|h| !h.unnumbered) {
+            let depth = (heading.level - top) as usize + 1;
+            counters.resize(depth, 0);
+            counters[depth - 1] += 1;
+            heading.number = Some(
+                counters
+                    .iter()
+                    .map(|n| n.to_string())
+                    .collect::<Vec<_>>()
+                    .join("."),
+            );
+        }
+    }
+
+    headings
+}
+
+/// Give every heading without an ID an automatic one (see [`SectionIds`]).
+///
+/// Headings inside section Divs with an ID already have one. A section Div
+/// without an ID gets the generated ID on the Div, so it stays on the
+/// element the TOC links to; other headings get it on the Header.
+///
+/// [`generate_toc`] with `auto_identifiers` set produces the same IDs, so
+/// the TOC links resolve.
+pub fn assign_section_ids(blocks: &mut [Block]) {
+    let mut headings = Vec::new();
+    collect_headings(blocks, &mut headings);
+    let missing: Vec<bool> = headings.iter().map(|h| h.id.is_empty()).collect();
+    resolve_ids(&mut headings);
+
+    let mut resolved = headings
+        .into_iter()
+        .zip(missing)
+        .map(|(heading, missing)| missing.then_some(heading.id));
+    apply_ids(blocks, &mut resolved);
+}
+
+/// Generates unique section IDs from heading text.
+///
+/// IDs are slugs of the heading text (see [`slugify`]). A slug that is
+/// already taken gets a numeric suffix: `intro`, `intro-1`, `intro-2`, ...
+#[derive(Debug, Clone, Default)]
+pub struct SectionIds {
+    used: HashSet<String>,
+}
+
+impl SectionIds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark an explicit ID as taken, so generated IDs don't collide with it.
+    pub fn reserve(&mut self, id: &str) {
+        self.used.insert(id.to_string());
+    }
+
+    /// Generate a unique ID for a heading with the given text.
+    pub fn generate(&mut self, title: &str) -> String {
+        let base = slugify(title);
+        let mut id = base.clone();
+        let mut suffix = 0;
+        while self.used.contains(&id) {
+            suffix += 1;
+            id = format!("{}-{}", base, suffix);
+        }
+        self.used.insert(id.clone());
+        id
+    }
+}
+
+/// Convert heading text to an identifier, following Pandoc's
+/// `auto_identifiers` extension.
+///
+/// Keeps alphanumerics, `_`, `-` and `.`, turns whitespace into `-`,
+/// lowercases, and drops everything before the first letter. Text with no
+/// letters becomes `section`.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_whitespace() {
+            slug.push('-');
+        } else if c.is_alphanumeric() || matches!(c, '_' | '-' | '.') {
+            slug.extend(c.to_lowercase());
+        }
+    }
+    let slug = slug.trim_start_matches(|c: char| !c.is_alphabetic());
+    if slug.is_empty() {
+        "section".to_string()
+    } else {
+        slug.to_string()
+    }
 }
 
-/// Collect TOC entries from blocks (flat list, not hierarchical).
-fn collect_toc_entries(blocks: &[Block], max_depth: i32) -> Vec<FlatTocEntry> {
-    let mut entries = Vec::new();
+/// Fill in missing heading IDs. Explicit IDs are reserved first, so a
+/// generated ID never takes an ID used later in the document.
+fn resolve_ids(headings: &mut [Heading]) {
+    let mut ids = SectionIds::new();
+    for heading in headings.iter().filter(|h| !h.id.is_empty()) {
+        ids.reserve(&heading.id);
+    }
+    for heading in headings.iter_mut().filter(|h| h.id.is_empty()) {
+        heading.id = ids.generate(&heading.title);
+    }
+}
 
+/// Collect headings from blocks, in document order.
+///
+/// `apply_ids` must visit headings in the same order.
+fn collect_headings(blocks: &[Block], headings: &mut Vec<Heading>) {
     for block in blocks {
         match block {
             Block::Div(div) => {
-                // Check if this is a section Div
-                if is_section_div(div) {
-                    if let Some(entry) = extract_entry_from_section(div, max_depth) {
-                        entries.push(entry);
-                    }
-                    // Recurse into section content for nested sections
-                    entries.extend(collect_toc_entries(&div.content, max_depth));
+                if let Some(header) = section_header(div) {
+                    headings.push(section_heading(div, header));
+                    // Recurse into the rest of the section for nested sections
+                    collect_headings(&div.content[1..], headings);
                 } else {
                     // Non-section Div - recurse into content
-                    entries.extend(collect_toc_entries(&div.content, max_depth));
+                    collect_headings(&div.content, headings);
                 }
             }
             Block::Header(header) => {
                 // Direct header (non-sectionized document)
-                if let Some(entry) = extract_entry_from_header(header, max_depth) {
-                    entries.push(entry);
-                }
+                headings.push(header_heading(header));
             }
             // Other block types: recurse if they contain blocks
             Block::BlockQuote(bq) => {
-                entries.extend(collect_toc_entries(&bq.content, max_depth));
+                collect_headings(&bq.content, headings);
             }
             _ => {
                 // Other blocks don't contain headers
             }
         }
     }
+}
 
-    entries
+/// Write resolved IDs (`Some` for headings that had none) back to the
+/// headings, visiting them in the same order as `collect_headings`.
+fn apply_ids(blocks: &mut [Block], ids: &mut impl Iterator<Item = Option<String>>) {
+    for block in blocks {
+        match block {
+            Block::Div(div) => {
+                if section_header(div).is_some() {
+                    if let Some(Some(id)) = ids.next() {
+                        div.attr.0 = id;
+                    }
+                    apply_ids(&mut div.content[1..], ids);
+                } else {
+                    apply_ids(&mut div.content, ids);
+                }
+            }
+            Block::Header(header) => {
+                if let Some(Some(id)) = ids.next() {
+                    header.attr.0 = id;
+                }
+            }
+            Block::BlockQuote(bq) => apply_ids(&mut bq.content, ids),
+            _ => {}
+        }
+    }
 }
 
 /// Check if a Div is a section created by sectionize_blocks.
@@ -310,6 +504,17 @@ fn is_section_div(div: &Div) -> bool {
     classes.iter().any(|c| c == "section")
 }
 
+/// The header that starts a section Div, if `div` is a section.
+fn section_header(div: &Div) -> Option<&Header> {
+    if !is_section_div(div) {
+        return None;
+    }
+    match div.content.first() {
+        Some(Block::Header(header)) => Some(header),
+        _ => None,
+    }
+}
+
 /// Extract the heading level from a section Div's classes.
 fn get_section_level(div: &Div) -> Option<i32> {
     let (_, classes, _) = &div.attr;
@@ -323,90 +528,49 @@ fn get_section_level(div: &Div) -> Option<i32> {
     None
 }
 
-/// Extract a TOC entry from a section Div.
-fn extract_entry_from_section(div: &Div, max_depth: i32) -> Option<FlatTocEntry> {
-    let (id, classes, _) = &div.attr;
-
-    // Skip if unlisted
-    if classes.iter().any(|c| c == "unlisted") {
-        return None;
-    }
-
-    // Get level from levelN class
-    let level = get_section_level(div)?;
-
-    // Skip if beyond max depth
-    if level > max_depth {
-        return None;
-    }
-
-    // Skip if no ID
-    if id.is_empty() {
-        return None;
-    }
-
-    // Get the header from the section content
-    let header = div.content.first().and_then(|b| {
-        if let Block::Header(h) = b {
-            Some(h)
+/// The heading for a section Div and its header.
+///
+/// The ID is taken from the Div (falling back to the header), and classes
+/// on either the Div or the header apply.
+fn section_heading(div: &Div, header: &Header) -> Heading {
+    let (div_id, div_classes, div_attrs) = &div.attr;
+    let has_class = |class: &str| {
+        div_classes.iter().any(|c| c == class) || header.attr.1.iter().any(|c| c == class)
+    };
+
+    Heading {
+        id: if div_id.is_empty() {
+            header.attr.0.clone()
         } else {
-            None
-        }
-    })?;
-
-    // Extract title text from header
-    let title = inlines_to_text(&header.content);
-
-    // Check for unnumbered class (on header or section)
-    let is_numbered = !classes.iter().any(|c| c == "unnumbered")
-        && !header.attr.1.iter().any(|c| c == "unnumbered");
-
-    Some(FlatTocEntry {
-        id: id.clone(),
-        title,
-        level,
-        // TODO: Implement actual section numbering
-        number: if is_numbered { None } else { None },
-    })
-}
-
-/// Extract a TOC entry from a direct Header (non-sectionized document).
-fn extract_entry_from_header(header: &Header, max_depth: i32) -> Option<FlatTocEntry> {
-    let (id, classes, _) = &header.attr;
-    let level = header.level as i32;
-
-    // Skip if unlisted
-    if classes.iter().any(|c| c == "unlisted") {
-        return None;
-    }
-
-    // Skip if beyond max depth
-    if level > max_depth {
-        return None;
-    }
-
-    // Skip if no ID
-    if id.is_empty() {
-        return None;
+            div_id.clone()
+        },
+        title: inlines_to_text(&header.content),
+        level: get_section_level(div).unwrap_or(header.level as i32),
+        number: div_attrs
+            .get("number")
+            .or_else(|| header.attr.2.get("number"))
+            .cloned(),
+        unlisted: has_class("unlisted"),
+        unnumbered: has_class("unnumbered"),
     }
+}
 
-    // Extract title text
-    let title = inlines_to_text(&header.content);
-
-    // Check for unnumbered class
-    let is_numbered = !classes.iter().any(|c| c == "unnumbered");
+/// The heading for a direct Header (non-sectionized document).
+fn header_heading(header: &Header) -> Heading {
+    let (id, classes, attrs) = &header.attr;
 
-    Some(FlatTocEntry {
+    Heading {
         id: id.clone(),
-        title,
-        level,
-        // TODO: Implement actual section numbering
-        number: if is_numbered { None } else { None },
-    })
+        title: inlines_to_text(&header.content),
+        level: header.level as i32,
+        number: attrs.get("number").cloned(),
+        unlisted: classes.iter().any(|c| c == "unlisted"),
+        unnumbered: classes.iter().any(|c| c == "unnumbered"),
+    }
 }
 
 /// Convert inlines to plain text for TOC title.
-fn inlines_to_text(inlines: &[Inline]) -> String {
+pub fn inlines_to_text(inlines: &[Inline]) -> String {
     let mut text = String::new();
     for inline in inlines {
         match inline {
@@ -443,7 +607,7 @@ fn inlines_to_text(inlines: &[Inline]) -> String {
 }
 
 /// Build hierarchical structure from flat entries based on levels.
-fn build_hierarchy(flat_entries: Vec<FlatTocEntry>) -> Vec<TocEntry> {
+fn build_hierarchy(flat_entries: Vec<Heading>) -> Vec<TocEntry> {
     if flat_entries.is_empty() {
         return vec![];
     }
@@ -687,6 +851,7 @@ mod tests {
         let config = TocConfig {
             depth: 2,
             title: None,
+            ..Default::default()
         };
         let toc = generate_toc(&blocks, &config);
 
@@ -705,6 +870,7 @@ mod tests {
         let config = TocConfig {
             depth: 3,
             title: Some("Contents".to_string()),
+            ..Default::default()
         };
         let toc = generate_toc(&blocks, &config);
 
@@ -900,4 +1066,132 @@ mod tests {
         // Second h1 has 1 h2 child
         assert_eq!(toc.entries[1].children.len(), 1);
     }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Introduction"), "introduction");
+        assert_eq!(slugify("My Great Section!"), "my-great-section");
+        assert_eq!(slugify("1.2 Results (final)"), "results-final");
+        assert_eq!(slugify("v1.0_beta-2"), "v1.0_beta-2");
+        assert_eq!(slugify("Über straße"), "über-straße");
+        assert_eq!(slugify("123"), "section");
+    }
+
+    #[test]
+    fn test_section_ids_disambiguate() {
+        let mut ids = SectionIds::new();
+        ids.reserve("intro-1");
+        assert_eq!(ids.generate("Intro"), "intro");
+        assert_eq!(ids.generate("Intro"), "intro-2");
+        assert_eq!(ids.generate("Intro"), "intro-3");
+    }
+
+    #[test]
+    fn test_generate_toc_auto_identifiers() {
+        let blocks = vec![
+            make_header(2, "", vec![], "Results"),
+            make_header(2, "", vec![], "Results"),
+            make_header(2, "results-1", vec![], "Explicit"),
+            make_header(2, "", vec!["unlisted"], "Results"),
+        ];
+        let config = TocConfig {
+            auto_identifiers: true,
+            ..Default::default()
+        };
+        let toc = generate_toc(&blocks, &config);
+
+        let ids: Vec<&str> = toc.entries.iter().map(|e| e.id.as_str()).collect();
+        // The explicit "results-1" is reserved, so the second "Results" skips it
+        assert_eq!(ids, vec!["results", "results-2", "results-1"]);
+    }
+
+    #[test]
+    fn test_assign_section_ids_matches_toc() {
+        let mut blocks = vec![
+            make_header(1, "", vec![], "Intro"),
+            make_section(
+                2,
+                "",
+                vec![],
+                "Intro",
+                vec![make_header(3, "", vec![], "Detail")],
+            ),
+            make_header(1, "done", vec![], "Done"),
+        ];
+        let config = TocConfig {
+            auto_identifiers: true,
+            ..Default::default()
+        };
+        let toc = generate_toc(&blocks, &config);
+        assign_section_ids(&mut blocks);
+
+        let Block::Header(first) = &blocks[0] else {
+            panic!("expected Header");
+        };
+        let Block::Div(section) = &blocks[1] else {
+            panic!("expected Div");
+        };
+        let Block::Header(detail) = &section.content[1] else {
+            panic!("expected Header");
+        };
+        assert_eq!(first.attr.0, "intro");
+        assert_eq!(section.attr.0, "intro-1");
+        assert_eq!(detail.attr.0, "detail");
+
+        assert_eq!(toc.entries[0].id, "intro");
+        assert_eq!(toc.entries[0].children[0].id, "intro-1");
+        assert_eq!(toc.entries[0].children[0].children[0].id, "detail");
+        assert_eq!(toc.entries[1].id, "done");
+    }
+
+    #[test]
+    fn test_generate_toc_number_sections() {
+        let blocks = vec![
+            make_header(2, "a", vec![], "A"),
+            make_header(3, "a1", vec![], "A1"),
+            make_header(3, "a2", vec![], "A2"),
+            make_header(2, "preface", vec!["unnumbered"], "Preface"),
+            make_header(2, "b", vec![], "B"),
+            make_header(4, "b-deep", vec![], "Deep"),
+            make_header(2, "hidden", vec!["unlisted"], "Hidden"),
+            make_header(2, "c", vec![], "C"),
+        ];
+        let config = TocConfig {
+            depth: 4,
+            number_sections: true,
+            ..Default::default()
+        };
+        let toc = generate_toc(&blocks, &config);
+
+        let numbers: Vec<Option<&str>> = toc.entries.iter().map(|e| e.number.as_deref()).collect();
+        // Unlisted headings are numbered even though they're not in the TOC
+        assert_eq!(numbers, vec![Some("1"), None, Some("2"), Some("4")]);
+        assert_eq!(toc.entries[0].children[1].number.as_deref(), Some("1.2"));
+        assert_eq!(toc.entries[2].children[0].number.as_deref(), Some("2.0.1"));
+    }
+
+    #[test]
+    fn test_generate_toc_uses_number_attributes() {
+        let mut numbered = make_header(2, "a", vec![], "A");
+        if let Block::Header(header) = &mut numbered {
+            header.attr.2.insert("number".to_string(), "3".to_string());
+        }
+        let blocks = vec![numbered, make_header(2, "b", vec![], "B")];
+        let toc = generate_toc(&blocks, &TocConfig::default());
+
+        assert_eq!(toc.entries[0].number.as_deref(), Some("3"));
+        assert_eq!(toc.entries[1].number, None);
+    }
+
+    #[test]
+    fn test_document_headings_include_unlisted() {
+        let blocks = vec![
+            make_header(1, "a", vec![], "A"),
+            make_header(5, "deep", vec!["unlisted"], "Deep"),
+        ];
+        let headings = document_headings(&blocks, &TocConfig::default());
+        assert_eq!(headings.len(), 2);
+        assert!(headings[1].unlisted);
+        assert_eq!(headings[1].title, "Deep");
+    }
 }
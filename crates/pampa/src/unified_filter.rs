@@ -17,6 +17,8 @@ use std::path::PathBuf;
 
 use quarto_error_reporting::DiagnosticMessage;
 
+use crate::json_api_version::ApiVersion;
+
 use crate::pandoc::Pandoc;
 use crate::pandoc::ast_context::ASTContext;
 
@@ -29,6 +31,8 @@ pub enum FilterSpec {
     Lua(PathBuf),
     /// JSON filter (external executable).
     Json(PathBuf),
+    /// JSON filter that expects the JSON of a specific pandoc-types version.
+    JsonApi(PathBuf, ApiVersion),
 }
 
 impl FilterSpec {
@@ -37,12 +41,18 @@ impl FilterSpec {
     /// The filter type is determined by the argument:
     /// - `"citeproc"` → Built-in citeproc filter
     /// - Ends with `.lua` → Lua filter
+    /// - Ends with `@<version>`, e.g. `filter.py@1.22` → JSON filter using
+    ///   that pandoc-types API version
     /// - Everything else → JSON filter (external executable)
     pub fn parse(s: &str) -> Self {
         if s == "citeproc" {
             FilterSpec::Citeproc
         } else if s.ends_with(".lua") {
             FilterSpec::Lua(PathBuf::from(s))
+        } else if let Some((path, version)) = s.rsplit_once('@')
+            && let Some(version) = ApiVersion::parse(version)
+        {
+            FilterSpec::JsonApi(PathBuf::from(path), version)
         } else {
             FilterSpec::Json(PathBuf::from(s))
         }
@@ -53,7 +63,7 @@ impl FilterSpec {
         match self {
             FilterSpec::Citeproc => "citeproc",
             FilterSpec::Lua(_) => "Lua",
-            FilterSpec::Json(_) => "JSON",
+            FilterSpec::Json(_) | FilterSpec::JsonApi(..) => "JSON",
         }
     }
 }
@@ -64,6 +74,7 @@ impl std::fmt::Display for FilterSpec {
             FilterSpec::Citeproc => write!(f, "citeproc"),
             FilterSpec::Lua(path) => write!(f, "{}", path.display()),
             FilterSpec::Json(path) => write!(f, "{}", path.display()),
+            FilterSpec::JsonApi(path, version) => write!(f, "{}@{}", path.display(), version),
         }
     }
 }
@@ -208,11 +219,26 @@ pub fn apply_filter(
             Ok((new_pandoc, new_context, diagnostics))
         }
 
+        #[cfg(feature = "json-filter")]
+        FilterSpec::JsonApi(path, version) => {
+            let (new_pandoc, new_context, diagnostics) =
+                crate::json_filter::apply_json_filter_with_api_version(
+                    &pandoc,
+                    &context,
+                    path,
+                    target_format,
+                    Some(*version),
+                )?;
+            Ok((new_pandoc, new_context, diagnostics))
+        }
+
         #[cfg(not(feature = "json-filter"))]
-        FilterSpec::Json(path) => Err(FilterError::FilterNotAvailable(format!(
-            "JSON filter support not enabled: {}",
-            path.display()
-        ))),
+        FilterSpec::Json(path) | FilterSpec::JsonApi(path, _) => {
+            Err(FilterError::FilterNotAvailable(format!(
+                "JSON filter support not enabled: {}",
+                path.display()
+            )))
+        }
     }
 }
 
@@ -282,6 +308,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_json_filter_with_api_version() {
+        assert_eq!(
+            FilterSpec::parse("my-filter.py@1.22"),
+            FilterSpec::JsonApi(PathBuf::from("my-filter.py"), ApiVersion::V1_22)
+        );
+        assert_eq!(
+            FilterSpec::parse("./filter@1.23.1"),
+            FilterSpec::JsonApi(PathBuf::from("./filter"), ApiVersion::V1_23)
+        );
+        // Unknown versions are part of the path
+        assert_eq!(
+            FilterSpec::parse("./filter@2"),
+            FilterSpec::Json(PathBuf::from("./filter@2"))
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                FilterSpec::JsonApi(PathBuf::from("my.py"), ApiVersion::V1_22)
+            ),
+            "my.py@1.22"
+        );
+    }
+
     #[test]
     fn test_type_name() {
         assert_eq!(FilterSpec::Citeproc.type_name(), "citeproc");
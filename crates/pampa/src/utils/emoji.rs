@@ -0,0 +1,186 @@
+/*
+ * emoji.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! `:shortcode:` to Unicode emoji conversion for the `emoji` reader extension.
+//!
+//! Shortcode names follow GitHub's set; only the commonly used subset is
+//! included. Unknown shortcodes are left as literal text.
+
+/// Shortcode names and their emoji, sorted by name for binary search.
+const EMOJI: &[(&str, &str)] = &[
+    ("+1", "👍"),
+    ("-1", "👎"),
+    ("100", "💯"),
+    ("angry", "😠"),
+    ("arrow_down", "⬇️"),
+    ("arrow_left", "⬅️"),
+    ("arrow_right", "➡️"),
+    ("arrow_up", "⬆️"),
+    ("beer", "🍺"),
+    ("bell", "🔔"),
+    ("blush", "😊"),
+    ("book", "📖"),
+    ("books", "📚"),
+    ("boom", "💥"),
+    ("broken_heart", "💔"),
+    ("bug", "🐛"),
+    ("bulb", "💡"),
+    ("cake", "🍰"),
+    ("calendar", "📆"),
+    ("camera", "📷"),
+    ("cat", "🐱"),
+    ("chart_with_upwards_trend", "📈"),
+    ("check", "✔️"),
+    ("clap", "👏"),
+    ("coffee", "☕"),
+    ("confused", "😕"),
+    ("construction", "🚧"),
+    ("cool", "🆒"),
+    ("cry", "😢"),
+    ("dog", "🐶"),
+    ("eyes", "👀"),
+    ("fire", "🔥"),
+    ("grin", "😁"),
+    ("grinning", "😀"),
+    ("hammer", "🔨"),
+    ("heart", "❤️"),
+    ("heart_eyes", "😍"),
+    ("heavy_check_mark", "✔️"),
+    ("hourglass", "⌛"),
+    ("hugs", "🤗"),
+    ("information_source", "ℹ️"),
+    ("joy", "😂"),
+    ("key", "🔑"),
+    ("laughing", "😆"),
+    ("link", "🔗"),
+    ("lock", "🔒"),
+    ("mag", "🔍"),
+    ("memo", "📝"),
+    ("moon", "🌔"),
+    ("muscle", "💪"),
+    ("neutral_face", "😐"),
+    ("no_entry", "⛔"),
+    ("ok_hand", "👌"),
+    ("package", "📦"),
+    ("paperclip", "📎"),
+    ("partying_face", "🥳"),
+    ("pencil2", "✏️"),
+    ("point_right", "👉"),
+    ("pray", "🙏"),
+    ("pushpin", "📌"),
+    ("question", "❓"),
+    ("rainbow", "🌈"),
+    ("raised_hands", "🙌"),
+    ("relaxed", "☺️"),
+    ("rocket", "🚀"),
+    ("rofl", "🤣"),
+    ("rotating_light", "🚨"),
+    ("sad", "😞"),
+    ("scream", "😱"),
+    ("see_no_evil", "🙈"),
+    ("shrug", "🤷"),
+    ("slightly_smiling_face", "🙂"),
+    ("smile", "😄"),
+    ("smiley", "😃"),
+    ("smirk", "😏"),
+    ("sob", "😭"),
+    ("sparkles", "✨"),
+    ("star", "⭐"),
+    ("stuck_out_tongue", "😛"),
+    ("sunglasses", "😎"),
+    ("sunny", "☀️"),
+    ("sweat_smile", "😅"),
+    ("tada", "🎉"),
+    ("thinking", "🤔"),
+    ("thumbsdown", "👎"),
+    ("thumbsup", "👍"),
+    ("trophy", "🏆"),
+    ("umbrella", "☔"),
+    ("unamused", "😒"),
+    ("warning", "⚠️"),
+    ("wave", "👋"),
+    ("white_check_mark", "✅"),
+    ("wink", "😉"),
+    ("wrench", "🔧"),
+    ("x", "❌"),
+    ("yum", "😋"),
+    ("zap", "⚡"),
+];
+
+/// Look up the emoji for a shortcode name (without the surrounding colons).
+pub fn lookup(name: &str) -> Option<&'static str> {
+    EMOJI
+        .binary_search_by(|(candidate, _)| candidate.cmp(&name))
+        .ok()
+        .map(|index| EMOJI[index].1)
+}
+
+fn is_shortcode_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '+' || c == '-'
+}
+
+/// Replace every known `:shortcode:` in `text` with its emoji.
+///
+/// Returns `None` when nothing was replaced.
+pub fn replace_shortcodes(text: &str) -> Option<String> {
+    if !text.contains(':') {
+        return None;
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut replaced = false;
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let name_len = after
+            .find(|c: char| !is_shortcode_char(c))
+            .unwrap_or(after.len());
+        let name = &after[..name_len];
+        if after[name_len..].starts_with(':')
+            && let Some(emoji) = lookup(name)
+        {
+            result.push_str(emoji);
+            rest = &after[name_len + 1..];
+            replaced = true;
+        } else {
+            // Keep the colon; it may open the next shortcode (e.g. `a::smile:`)
+            result.push(':');
+            rest = after;
+        }
+    }
+    result.push_str(rest);
+    replaced.then_some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_is_sorted() {
+        assert!(EMOJI.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn test_replace_shortcodes() {
+        assert_eq!(replace_shortcodes(":smile:").as_deref(), Some("😄"));
+        assert_eq!(
+            replace_shortcodes("great:+1:!").as_deref(),
+            Some("great👍!")
+        );
+        assert_eq!(
+            replace_shortcodes(":tada::rocket:").as_deref(),
+            Some("🎉🚀")
+        );
+        assert_eq!(replace_shortcodes("a::smile:").as_deref(), Some("a:😄"));
+    }
+
+    #[test]
+    fn test_unknown_shortcodes_are_kept() {
+        assert_eq!(replace_shortcodes(":not_an_emoji:"), None);
+        assert_eq!(replace_shortcodes("10:30"), None);
+        assert_eq!(replace_shortcodes("plain"), None);
+    }
+}
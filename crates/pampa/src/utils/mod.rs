@@ -6,6 +6,7 @@
 pub mod autoid;
 pub mod concrete_tree_depth;
 pub mod diagnostic_collector;
+pub mod emoji;
 pub mod output;
 pub mod text;
 pub mod trim_source_location;
@@ -6,6 +6,7 @@
 use crate::readers;
 use crate::utils::output::VerboseOutput;
 use crate::writers::json::JsonConfig;
+use quarto_error_reporting::DiagnosticMessage;
 use std::io;
 
 fn pandoc_to_json(
@@ -59,6 +60,161 @@ pub fn parse_qmd(input: &[u8], include_resolved_locations: bool) -> String {
     pandoc_to_json(&pandoc, &context, include_resolved_locations).unwrap()
 }
 
+/// Options of [`convert`], passed from JavaScript as a JSON object.
+///
+/// These mirror the options of the `pampa` command line: `loose` is
+/// `--loose`, `json_source_location: "full"` is `--json-source-location full`
+/// and `source_positions` is `--source-positions`.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConvertOptions {
+    pub loose: bool,
+    pub json_source_location: Option<String>,
+    pub source_positions: bool,
+}
+
+fn convert_error(error: impl Into<String>, diagnostics: &[DiagnosticMessage]) -> String {
+    serde_json::json!({
+        "error": error.into(),
+        "diagnostics": diagnostics.iter().map(|d| d.to_json()).collect::<Vec<_>>()
+    })
+    .to_string()
+}
+
+/// Convert a document between formats.
+///
+/// Input formats are `qmd` (or `markdown`) and `json`; output formats are
+/// `qmd` (or `markdown`), `json`, `html` and `plaintext` (or `plain`).
+/// Formats may carry extension modifiers, e.g. `markdown+smart`, as on the
+/// command line. `options_json` holds [`ConvertOptions`]; an empty string
+/// uses the defaults.
+///
+/// # Returns
+///
+/// A JSON object with either:
+/// * `{ "output": "...", "diagnostics": [...] }` on success, with warnings
+/// * `{ "error": "...", "diagnostics": [...] }` on failure
+pub fn convert(
+    input: &[u8],
+    input_format: &str,
+    output_format: &str,
+    options_json: &str,
+) -> String {
+    let options: ConvertOptions = if options_json.trim().is_empty() {
+        ConvertOptions::default()
+    } else {
+        match serde_json::from_str(options_json) {
+            Ok(options) => options,
+            Err(e) => return convert_error(format!("Invalid conversion options: {}", e), &[]),
+        }
+    };
+    let include_inline_locations = match options.json_source_location.as_deref() {
+        None => false,
+        Some("full") => true,
+        Some(other) => {
+            return convert_error(
+                format!("Unknown json_source_location: '{}'. Use 'full'", other),
+                &[],
+            );
+        }
+    };
+
+    let from_format = crate::options::parse_format_string(input_format);
+    let to_format = crate::options::parse_format_string(output_format);
+
+    let mut diagnostics = Vec::new();
+    let (pandoc, context) = match from_format.base_format.as_str() {
+        "qmd" | "markdown" => {
+            let mut output = VerboseOutput::Sink(io::sink());
+            match readers::qmd::read_with_options(
+                input,
+                options.loose,
+                "<input>",
+                &mut output,
+                true,
+                None,
+                &readers::qmd::QmdReaderOptions::from_extensions(&from_format.extensions),
+            ) {
+                Ok((pandoc, context, warnings)) => {
+                    diagnostics.extend(warnings);
+                    (pandoc, context)
+                }
+                Err(errors) => return convert_error("Failed to parse QMD input", &errors),
+            }
+        }
+        "json" => match readers::json::read(&mut &input[..]) {
+            Ok(doc) => doc,
+            Err(e) => return convert_error(format!("Unable to read as json: {}", e), &[]),
+        },
+        _ => {
+            return convert_error(
+                format!(
+                    "Unknown input format: '{}'. Use 'qmd' or 'json'",
+                    input_format
+                ),
+                &[],
+            );
+        }
+    };
+
+    let mut buf = Vec::new();
+    let result = match to_format.base_format.as_str() {
+        "qmd" | "markdown" => crate::writers::qmd::write_with_options(
+            &pandoc,
+            &mut buf,
+            &crate::writers::qmd::QmdWriterOptions::from_extensions(&to_format.extensions),
+        ),
+        "json" => crate::writers::json::write_with_config(
+            &pandoc,
+            &context,
+            &mut buf,
+            &JsonConfig {
+                include_inline_locations,
+            },
+        ),
+        "html" => {
+            let mut config = crate::writers::html::extract_config_from_metadata(&pandoc.meta);
+            config.source_positions |= options.source_positions;
+            match crate::writers::html::write_document(&pandoc, &context, &mut buf, config) {
+                Ok(summary) => {
+                    diagnostics.extend(summary.diagnostics);
+                    Ok(())
+                }
+                Err(e) => {
+                    return convert_error(format!("Failed to write HTML output: {}", e), &[]);
+                }
+            }
+        }
+        "plaintext" | "plain" => {
+            let (output, warnings) = crate::writers::plaintext::blocks_to_string(&pandoc.blocks);
+            buf.extend_from_slice(output.as_bytes());
+            diagnostics.extend(warnings);
+            Ok(())
+        }
+        _ => {
+            return convert_error(
+                format!(
+                    "Unknown output format: '{}'. Use 'qmd', 'json', 'html' or 'plaintext'",
+                    output_format
+                ),
+                &[],
+            );
+        }
+    };
+    if let Err(errors) = result {
+        return convert_error(
+            format!("Unable to write as {}", to_format.base_format),
+            &errors,
+        );
+    }
+
+    serde_json::json!({
+        "output": String::from_utf8_lossy(&buf),
+        "diagnostics": diagnostics.iter().map(|d| d.to_json()).collect::<Vec<_>>()
+    })
+    .to_string()
+}
+
 /// Render a parsed document using a template bundle.
 ///
 /// This function is designed for WASM usage where filesystem access is not available.
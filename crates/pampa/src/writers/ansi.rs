@@ -8,8 +8,10 @@
  * Other blocks panic with helpful messages indicating they need implementation.
  */
 
+use crate::highlighting::{HighlightStyle, Rgb, language_for_classes};
 use crate::pandoc::{
-    Attr, Block, BlockQuote, BulletList, DefinitionList, Div, Inline, OrderedList, Pandoc,
+    Attr, Block, BlockQuote, BulletList, CodeBlock, DefinitionList, Div, Inline, OrderedList,
+    Pandoc,
 };
 use crossterm::style::{Color, Stylize};
 use std::io::Write;
@@ -163,6 +165,8 @@ pub struct AnsiConfig {
     pub indent: usize,
     /// Enable clickable hyperlinks (OSC 8)
     pub hyperlinks: bool,
+    /// Colors for syntax-highlighted code blocks
+    pub highlight_style: HighlightStyle,
 }
 
 impl Default for AnsiConfig {
@@ -172,6 +176,7 @@ impl Default for AnsiConfig {
             width: Self::detect_terminal_width(),
             indent: 2,
             hyperlinks: Self::detect_hyperlink_support(),
+            highlight_style: HighlightStyle::default(),
         }
     }
 }
@@ -563,19 +568,7 @@ fn write_block_with_depth(
             );
             Ok(LastBlockSpacing::None)
         }
-        Block::CodeBlock(_) => {
-            ctx.errors.push(
-                quarto_error_reporting::DiagnosticMessageBuilder::error(
-                    "CodeBlock not supported in ANSI format",
-                )
-                .with_code("Q-3-51")
-                .problem("CodeBlock elements cannot be rendered in ANSI terminal output")
-                .add_detail("ANSI output format does not yet support code blocks")
-                .add_hint("Consider using a different output format for documents with code blocks")
-                .build(),
-            );
-            Ok(LastBlockSpacing::None)
-        }
+        Block::CodeBlock(codeblock) => write_codeblock(codeblock, buf, config),
         Block::RawBlock(raw) => {
             // Only render raw content if format matches "ansi"
             if raw.format == "ansi" {
@@ -957,6 +950,52 @@ fn write_definitionlist(
     Ok(LastBlockSpacing::Paragraph)
 }
 
+/// Write a code block indented, syntax-highlighted when its language is
+/// supported and colors are enabled.
+fn write_codeblock(
+    codeblock: &CodeBlock,
+    buf: &mut dyn Write,
+    config: &AnsiConfig,
+) -> std::io::Result<LastBlockSpacing> {
+    let indent = " ".repeat(config.indent);
+    let language = language_for_classes(&codeblock.attr.1).filter(|_| config.colors);
+    let Some(language) = language else {
+        for line in codeblock.text.lines() {
+            writeln!(buf, "{}{}", indent, line)?;
+        }
+        return Ok(LastBlockSpacing::Paragraph);
+    };
+
+    for line in language.tokenize(&codeblock.text) {
+        write!(buf, "{}", indent)?;
+        for token in line {
+            let Some(style) = config.highlight_style.token_style(token.token_type) else {
+                write!(buf, "{}", token.text)?;
+                continue;
+            };
+            let mut styled = token.text.as_str().stylize();
+            if let Some(Rgb(r, g, b)) = style.color {
+                styled = styled.with(Color::Rgb { r, g, b });
+            }
+            if let Some(Rgb(r, g, b)) = style.background {
+                styled = styled.on(Color::Rgb { r, g, b });
+            }
+            if style.bold {
+                styled = styled.bold();
+            }
+            if style.italic {
+                styled = styled.italic();
+            }
+            if style.underline {
+                styled = styled.underlined();
+            }
+            write!(buf, "{}", styled)?;
+        }
+        writeln!(buf)?;
+    }
+    Ok(LastBlockSpacing::Paragraph)
+}
+
 /// Write a block quote with vertical line marker
 fn write_blockquote(
     blockquote: &BlockQuote,
@@ -0,0 +1,948 @@
+/*
+ * gfm.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! GitHub-Flavored Markdown writer for Pandoc AST.
+//!
+//! Unlike the qmd writer, this writer never emits Quarto-specific syntax
+//! (fenced divs, attribute blocks, shortcodes, editorial marks). The output
+//! is meant for README-style publishing and for tools that only understand
+//! GFM.
+//!
+//! # Design decisions
+//!
+//! - Tables are always written as pipe tables. Cells with block content are
+//!   flattened onto a single line; row/column spans emit a warning.
+//! - Task list items use Pandoc's convention: a bullet item whose first
+//!   inlines are `☐`/`☒` followed by a space is written as `[ ]`/`[x]`.
+//! - Links whose text equals their target are written as autolinks (`<url>`).
+//! - Divs, Spans and attributes are unwrapped; their content is kept.
+//! - Inline notes and referenced note definitions are collected and written
+//!   as GFM footnotes at the end of the document, numbered in the order they
+//!   are referenced. A definition referenced more than once keeps one number.
+//! - RawInline/RawBlock: echoed for `html`, `markdown`, `gfm` and
+//!   `commonmark`; other formats are dropped with a warning.
+//! - Document metadata is not written (GFM has no front matter).
+
+use crate::pandoc::block::{
+    BlockQuote, BulletList, CodeBlock, DefinitionList, Figure, Header, LineBlock, OrderedList,
+    RawBlock,
+};
+use crate::pandoc::inline::{Inline, Inlines, RawInline};
+use crate::pandoc::list::ListNumberDelim;
+use crate::pandoc::table::{Alignment, Cell, Table};
+use crate::pandoc::{Block, Blocks, Pandoc};
+use crate::writers::line_prefix::LinePrefixWriter;
+use crate::writers::notes::{NoteDefinitions, note_reference_id};
+use crate::writers::raw::{GFM_FORMATS, is_native_format};
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_source_map::SourceInfo;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Context for the GFM writer, threaded through all write functions.
+pub struct GfmWriterContext {
+    /// Warnings for nodes that have no GFM representation.
+    diagnostics: Vec<DiagnosticMessage>,
+
+    /// Contents of the notes referenced so far, in order of reference.
+    /// Written as `[^N]: ...` definitions after the document body.
+    notes: Vec<Blocks>,
+
+    /// Note definitions from the document, by id.
+    note_definitions: NoteDefinitions,
+
+    /// The number given to each note definition on its first reference.
+    note_numbers: HashMap<String, usize>,
+}
+
+impl GfmWriterContext {
+    pub fn new() -> Self {
+        Self {
+            diagnostics: Vec::new(),
+            notes: Vec::new(),
+            note_definitions: NoteDefinitions::default(),
+            note_numbers: HashMap::new(),
+        }
+    }
+
+    pub fn into_diagnostics(self) -> Vec<DiagnosticMessage> {
+        self.diagnostics
+    }
+
+    pub fn diagnostics(&self) -> &[DiagnosticMessage] {
+        &self.diagnostics
+    }
+
+    fn warn_dropped_node(&mut self, description: &str, source_info: &SourceInfo) {
+        let diag = DiagnosticMessageBuilder::warning(format!(
+            "Node dropped in GFM output: {}",
+            description
+        ))
+        .with_location(source_info.clone())
+        .build();
+        self.diagnostics.push(diag);
+    }
+
+    fn warn(&mut self, title: &str, problem: &str, source_info: &SourceInfo) {
+        let diag = DiagnosticMessageBuilder::warning(title)
+            .problem(problem)
+            .with_location(source_info.clone())
+            .build();
+        self.diagnostics.push(diag);
+    }
+}
+
+impl Default for GfmWriterContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Escaping helpers
+// ============================================================================
+
+/// Escape characters that would otherwise trigger GFM syntax.
+fn escape_gfm(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' | '$' => {
+                result.push('\\');
+                result.push(ch);
+            }
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+/// Return a run of backticks one longer than the longest run in `text`.
+fn backtick_fence(text: &str, minimum: usize) -> String {
+    let mut longest = 0;
+    let mut current = 0;
+    for ch in text.chars() {
+        if ch == '`' {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    "`".repeat((longest + 1).max(minimum))
+}
+
+fn escape_link_title(title: &str) -> String {
+    title.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// ============================================================================
+// Inline writing
+// ============================================================================
+
+fn write_inlines(
+    inlines: &[Inline],
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    for inline in inlines {
+        write_inline(inline, buf, ctx)?;
+    }
+    Ok(())
+}
+
+/// Flatten inlines to the plain text they display, for autolink detection.
+fn inlines_text(inlines: &[Inline]) -> Option<String> {
+    let mut text = String::new();
+    for inline in inlines {
+        match inline {
+            Inline::Str(s) => text.push_str(&s.text),
+            _ => return None,
+        }
+    }
+    Some(text)
+}
+
+fn is_autolink(link: &crate::pandoc::Link) -> bool {
+    let url = &link.target.0;
+    if !link.target.1.is_empty() || url.is_empty() {
+        return false;
+    }
+    let has_scheme = url.contains("://") || url.starts_with("mailto:");
+    if !has_scheme {
+        return false;
+    }
+    match inlines_text(&link.content) {
+        Some(text) => text == *url || url.strip_prefix("mailto:") == Some(text.as_str()),
+        None => false,
+    }
+}
+
+fn write_raw_inline(
+    raw: &RawInline,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    if is_native_format(&raw.format, GFM_FORMATS) {
+        write!(buf, "{}", raw.text)?;
+    } else {
+        ctx.warn_dropped_node(
+            &format!("RawInline with format '{}'", raw.format),
+            &raw.source_info,
+        );
+    }
+    Ok(())
+}
+
+fn write_inline(
+    inline: &Inline,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    match inline {
+        Inline::Str(s) => write!(buf, "{}", escape_gfm(&s.text))?,
+        Inline::Space(_) => write!(buf, " ")?,
+        Inline::SoftBreak(_) => writeln!(buf)?,
+        Inline::LineBreak(_) => writeln!(buf, "\\")?,
+
+        Inline::Emph(emph) => {
+            write!(buf, "*")?;
+            write_inlines(&emph.content, buf, ctx)?;
+            write!(buf, "*")?;
+        }
+        Inline::Strong(strong) => {
+            write!(buf, "**")?;
+            write_inlines(&strong.content, buf, ctx)?;
+            write!(buf, "**")?;
+        }
+        Inline::Strikeout(strikeout) => {
+            write!(buf, "~~")?;
+            write_inlines(&strikeout.content, buf, ctx)?;
+            write!(buf, "~~")?;
+        }
+        Inline::Delete(delete) => {
+            write!(buf, "~~")?;
+            write_inlines(&delete.content, buf, ctx)?;
+            write!(buf, "~~")?;
+        }
+        // GFM has no native syntax for these; HTML tags are allowed by GitHub
+        Inline::Superscript(sup) => {
+            write!(buf, "<sup>")?;
+            write_inlines(&sup.content, buf, ctx)?;
+            write!(buf, "</sup>")?;
+        }
+        Inline::Subscript(sub) => {
+            write!(buf, "<sub>")?;
+            write_inlines(&sub.content, buf, ctx)?;
+            write!(buf, "</sub>")?;
+        }
+
+        // Unwrap: keep content, drop markup that GFM cannot express
+        Inline::Underline(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::SmallCaps(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Span(node) => match note_reference_id(node) {
+            // The reader turns note references into empty spans
+            Some(id) => write_note_reference(id, &node.source_info, buf, ctx)?,
+            None => write_inlines(&node.content, buf, ctx)?,
+        },
+        Inline::Insert(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Highlight(node) => write_inlines(&node.content, buf, ctx)?,
+
+        Inline::Quoted(quoted) => {
+            let mark = match quoted.quote_type {
+                crate::pandoc::QuoteType::SingleQuote => "'",
+                crate::pandoc::QuoteType::DoubleQuote => "\"",
+            };
+            write!(buf, "{}", mark)?;
+            write_inlines(&quoted.content, buf, ctx)?;
+            write!(buf, "{}", mark)?;
+        }
+
+        Inline::Code(code) => {
+            let fence = backtick_fence(&code.text, 1);
+            if code.text.starts_with('`') || code.text.ends_with('`') {
+                write!(buf, "{} {} {}", fence, code.text, fence)?;
+            } else {
+                write!(buf, "{}{}{}", fence, code.text, fence)?;
+            }
+        }
+
+        Inline::Math(math) => match math.math_type {
+            crate::pandoc::MathType::InlineMath => write!(buf, "${}$", math.text)?,
+            crate::pandoc::MathType::DisplayMath => write!(buf, "$${}$$", math.text)?,
+        },
+
+        Inline::Link(link) => {
+            if is_autolink(link) {
+                write!(buf, "<{}>", link.target.0)?;
+            } else {
+                write!(buf, "[")?;
+                write_inlines(&link.content, buf, ctx)?;
+                write!(buf, "]({}", link.target.0)?;
+                if !link.target.1.is_empty() {
+                    write!(buf, " \"{}\"", escape_link_title(&link.target.1))?;
+                }
+                write!(buf, ")")?;
+            }
+        }
+        Inline::Image(image) => {
+            write!(buf, "![")?;
+            write_inlines(&image.content, buf, ctx)?;
+            write!(buf, "]({}", image.target.0)?;
+            if !image.target.1.is_empty() {
+                write!(buf, " \"{}\"", escape_link_title(&image.target.1))?;
+            }
+            write!(buf, ")")?;
+        }
+
+        Inline::Cite(cite) => {
+            if !cite.content.is_empty() {
+                write_inlines(&cite.content, buf, ctx)?;
+            } else {
+                // Unprocessed citation: keep the keys readable
+                for (i, citation) in cite.citations.iter().enumerate() {
+                    if i > 0 {
+                        write!(buf, "; ")?;
+                    }
+                    write!(buf, "@{}", escape_gfm(&citation.id))?;
+                }
+            }
+        }
+
+        Inline::Note(note) => {
+            ctx.notes.push(note.content.clone());
+            write!(buf, "[^{}]", ctx.notes.len())?;
+        }
+        Inline::NoteReference(noteref) => {
+            write_note_reference(&noteref.id, &noteref.source_info, buf, ctx)?
+        }
+
+        Inline::RawInline(raw) => write_raw_inline(raw, buf, ctx)?,
+
+        // Attributes have no GFM representation; dropping them loses nothing visible
+        Inline::Attr(_, _) => {}
+
+        Inline::EditComment(comment) => {
+            ctx.warn_dropped_node("EditComment", &comment.source_info);
+        }
+        Inline::Shortcode(shortcode) => {
+            ctx.warn_dropped_node(
+                &format!("Shortcode '{}'", shortcode.name),
+                &shortcode.source_info,
+            );
+        }
+        Inline::Custom(custom) => {
+            ctx.warn_dropped_node(
+                &format!("Custom inline ({})", custom.type_name),
+                &custom.source_info,
+            );
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Block writing
+// ============================================================================
+
+/// Write blocks separated by blank lines.
+fn write_blocks(
+    blocks: &[Block],
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    let mut wrote_any = false;
+    for block in blocks {
+        // Render to a scratch buffer so blocks that produce no output
+        // (dropped nodes, empty divs) don't leave stray blank lines
+        let mut scratch = Vec::new();
+        write_block(block, &mut scratch, ctx)?;
+        if scratch.is_empty() {
+            continue;
+        }
+        if wrote_any {
+            writeln!(buf)?;
+        }
+        buf.write_all(&scratch)?;
+        wrote_any = true;
+    }
+    Ok(())
+}
+
+fn write_header(
+    header: &Header,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    write!(buf, "{} ", "#".repeat(header.level.clamp(1, 6)))?;
+    write_inlines(&header.content, buf, ctx)?;
+    writeln!(buf)
+}
+
+fn write_codeblock(codeblock: &CodeBlock, buf: &mut dyn Write) -> io::Result<()> {
+    let fence = backtick_fence(&codeblock.text, 3);
+    // GFM info strings carry only the language; executable cell classes
+    // like `{r}` are unwrapped to `r`.
+    let language = codeblock.attr.1.first().map_or("", |class| {
+        class.trim_start_matches('{').trim_end_matches('}')
+    });
+    writeln!(buf, "{}{}", fence, language)?;
+    write!(buf, "{}", codeblock.text)?;
+    if !codeblock.text.ends_with('\n') {
+        writeln!(buf)?;
+    }
+    writeln!(buf, "{}", fence)
+}
+
+fn write_rawblock(
+    rawblock: &RawBlock,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    if is_native_format(&rawblock.format, GFM_FORMATS) {
+        write!(buf, "{}", rawblock.text)?;
+        if !rawblock.text.ends_with('\n') {
+            writeln!(buf)?;
+        }
+    } else {
+        ctx.warn_dropped_node(
+            &format!("RawBlock with format '{}'", rawblock.format),
+            &rawblock.source_info,
+        );
+    }
+    Ok(())
+}
+
+fn write_blockquote(
+    blockquote: &BlockQuote,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    let mut quote_writer = LinePrefixWriter::new(buf, "> ", "> ");
+    write_blocks(&blockquote.content, &mut quote_writer, ctx)
+}
+
+fn write_lineblock(
+    lineblock: &LineBlock,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    // GFM has no line blocks: emulate them with hard line breaks
+    for (i, line) in lineblock.content.iter().enumerate() {
+        if i > 0 {
+            writeln!(buf, "\\")?;
+        }
+        write_inlines(line, buf, ctx)?;
+    }
+    writeln!(buf)
+}
+
+/// A list is tight if the first block of every item is Plain (not Para).
+fn is_tight_list(items: &[Blocks]) -> bool {
+    items
+        .iter()
+        .all(|item| !item.is_empty() && matches!(item[0], Block::Plain(_)))
+}
+
+/// Detect a Pandoc-style task list marker (`☐`/`☒` followed by a space) at
+/// the start of a list item. Returns the GFM marker and the number of leading
+/// inlines it replaces.
+fn task_marker(item: &[Block]) -> Option<(&'static str, usize)> {
+    let inlines = match item.first()? {
+        Block::Plain(plain) => &plain.content,
+        Block::Paragraph(para) => &para.content,
+        _ => return None,
+    };
+    let marker = match inlines.first()? {
+        Inline::Str(s) if s.text == "\u{2610}" => "[ ]",
+        Inline::Str(s) if s.text == "\u{2612}" => "[x]",
+        _ => return None,
+    };
+    match inlines.get(1) {
+        Some(Inline::Space(_)) => Some((marker, 2)),
+        None => Some((marker, 1)),
+        _ => None,
+    }
+}
+
+/// Write a single list item's blocks through a prefixing writer.
+fn write_list_item(
+    item: &[Block],
+    tight: bool,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    if item.is_empty() {
+        return writeln!(buf);
+    }
+    for (i, block) in item.iter().enumerate() {
+        if i > 0 && !tight {
+            writeln!(buf)?;
+        }
+        write_block(block, buf, ctx)?;
+    }
+    Ok(())
+}
+
+fn write_bulletlist(
+    bulletlist: &BulletList,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    let tight = is_tight_list(&bulletlist.content);
+    for (i, item) in bulletlist.content.iter().enumerate() {
+        if i > 0 && !tight {
+            writeln!(buf)?;
+        }
+        let mut item_writer = LinePrefixWriter::new(&mut *buf, "- ", "  ");
+        if let Some((marker, skip)) = task_marker(item) {
+            write!(item_writer, "{} ", marker)?;
+            // Rewrite the first block without the marker inlines
+            let first = match &item[0] {
+                Block::Plain(plain) => &plain.content,
+                Block::Paragraph(para) => &para.content,
+                _ => unreachable!("task_marker only matches Plain/Paragraph"),
+            };
+            write_inlines(&first[skip..], &mut item_writer, ctx)?;
+            writeln!(item_writer)?;
+            for block in &item[1..] {
+                if !tight {
+                    writeln!(item_writer)?;
+                }
+                write_block(block, &mut item_writer, ctx)?;
+            }
+        } else {
+            write_list_item(item, tight, &mut item_writer, ctx)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_orderedlist(
+    orderedlist: &OrderedList,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    let (start, _style, delim) = &orderedlist.attr;
+    // GFM only knows decimal numbering with `.` or `)` delimiters
+    let delim = match delim {
+        ListNumberDelim::OneParen | ListNumberDelim::TwoParens => ")",
+        _ => ".",
+    };
+    let tight = is_tight_list(&orderedlist.content);
+    for (i, item) in orderedlist.content.iter().enumerate() {
+        if i > 0 && !tight {
+            writeln!(buf)?;
+        }
+        let marker = format!("{}{} ", start + i, delim);
+        let indent = " ".repeat(marker.len());
+        let mut item_writer = LinePrefixWriter::new(&mut *buf, marker, indent);
+        write_list_item(item, tight, &mut item_writer, ctx)?;
+    }
+    Ok(())
+}
+
+fn write_definitionlist(
+    deflist: &DefinitionList,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    // GFM has no definition lists: write each term in bold, followed by its
+    // definitions as ordinary blocks.
+    for (i, (term, definitions)) in deflist.content.iter().enumerate() {
+        if i > 0 {
+            writeln!(buf)?;
+        }
+        write!(buf, "**")?;
+        write_inlines(term, buf, ctx)?;
+        writeln!(buf, "**")?;
+        for definition in definitions {
+            writeln!(buf)?;
+            write_blocks(definition, buf, ctx)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_figure(
+    figure: &Figure,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    let mut blocks = figure.content.clone();
+    if let Some(long_caption) = &figure.caption.long {
+        blocks.extend(long_caption.iter().cloned());
+    }
+    write_blocks(&blocks, buf, ctx)
+}
+
+/// Render a cell's content on a single line for use in a pipe table.
+fn cell_to_string(cell: &Cell, ctx: &mut GfmWriterContext) -> io::Result<String> {
+    let mut parts = Vec::new();
+    for block in &cell.content {
+        let inlines: &Inlines = match block {
+            Block::Plain(plain) => &plain.content,
+            Block::Paragraph(para) => &para.content,
+            Block::Header(header) => &header.content,
+            other => {
+                let mut scratch = Vec::new();
+                write_block(other, &mut scratch, ctx)?;
+                parts.push(String::from_utf8_lossy(&scratch).trim().replace('\n', " "));
+                continue;
+            }
+        };
+        let mut scratch = Vec::new();
+        for inline in inlines {
+            match inline {
+                Inline::SoftBreak(_) => write!(scratch, " ")?,
+                Inline::LineBreak(_) => write!(scratch, "<br>")?,
+                other => write_inline(other, &mut scratch, ctx)?,
+            }
+        }
+        parts.push(String::from_utf8_lossy(&scratch).trim().to_string());
+    }
+    Ok(parts.join(" "))
+}
+
+fn write_table(table: &Table, buf: &mut dyn Write, ctx: &mut GfmWriterContext) -> io::Result<()> {
+    let num_cols = table.colspec.len();
+    if num_cols == 0 {
+        return Ok(());
+    }
+
+    let mut rows = Vec::new();
+    for row in table
+        .head
+        .rows
+        .iter()
+        .chain(
+            table
+                .bodies
+                .iter()
+                .flat_map(|b| b.head.iter().chain(&b.body)),
+        )
+        .chain(table.foot.rows.iter())
+    {
+        let mut cells = Vec::with_capacity(num_cols);
+        for cell in &row.cells {
+            if cell.row_span > 1 || cell.col_span > 1 {
+                ctx.warn(
+                    "Table cell span not supported in GFM output",
+                    "GFM pipe tables cannot express row or column spans; the cell is written once",
+                    &table.source_info,
+                );
+            }
+            cells.push(cell_to_string(cell, ctx)?);
+        }
+        cells.resize(num_cols, String::new());
+        cells.truncate(num_cols);
+        rows.push(cells);
+    }
+
+    // GFM requires a header row; use an empty one if the table has none
+    if table.head.rows.is_empty() {
+        rows.insert(0, vec![String::new(); num_cols]);
+    }
+
+    let mut widths = vec![3; num_cols];
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let write_row = |buf: &mut dyn Write, row: &[String]| -> io::Result<()> {
+        write!(buf, "|")?;
+        for (i, cell) in row.iter().enumerate() {
+            let pad = widths[i] - cell.chars().count();
+            write!(buf, " {}{} |", cell, " ".repeat(pad))?;
+        }
+        writeln!(buf)
+    };
+
+    write_row(buf, &rows[0])?;
+    write!(buf, "|")?;
+    for (i, (alignment, _)) in table.colspec.iter().enumerate() {
+        let sep = match alignment {
+            Alignment::Left => format!(":{}", "-".repeat(widths[i] - 1)),
+            Alignment::Center => format!(":{}:", "-".repeat(widths[i] - 2)),
+            Alignment::Right => format!("{}:", "-".repeat(widths[i] - 1)),
+            Alignment::Default => "-".repeat(widths[i]),
+        };
+        write!(buf, " {} |", sep)?;
+    }
+    writeln!(buf)?;
+    for row in &rows[1..] {
+        write_row(buf, row)?;
+    }
+
+    if let Some(long_caption) = &table.caption.long
+        && !long_caption.is_empty()
+    {
+        writeln!(buf)?;
+        write_blocks(long_caption, buf, ctx)?;
+    }
+    Ok(())
+}
+
+/// Write the footnote mark for a reference to a note definition, numbering
+/// the note on its first reference. A reference without a definition is
+/// dropped.
+fn write_note_reference(
+    id: &str,
+    source_info: &SourceInfo,
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    let number = match ctx.note_numbers.get(id) {
+        Some(number) => *number,
+        None => match ctx.note_definitions.get(id) {
+            Some(content) => {
+                ctx.notes.push(content.clone());
+                ctx.note_numbers.insert(id.to_string(), ctx.notes.len());
+                ctx.notes.len()
+            }
+            None => {
+                ctx.warn_dropped_node(
+                    &format!("NoteReference without definition '{}'", id),
+                    source_info,
+                );
+                return Ok(());
+            }
+        },
+    };
+    write!(buf, "[^{}]", number)
+}
+
+fn write_note_definition_blocks(
+    label: &str,
+    content: &[Block],
+    buf: &mut dyn Write,
+    ctx: &mut GfmWriterContext,
+) -> io::Result<()> {
+    let mut note_writer = LinePrefixWriter::new(buf, format!("[^{}]: ", label), "    ");
+    write_blocks(content, &mut note_writer, ctx)
+}
+
+fn write_block(block: &Block, buf: &mut dyn Write, ctx: &mut GfmWriterContext) -> io::Result<()> {
+    match block {
+        Block::Plain(plain) => {
+            write_inlines(&plain.content, buf, ctx)?;
+            writeln!(buf)?;
+        }
+        Block::Paragraph(para) => {
+            write_inlines(&para.content, buf, ctx)?;
+            writeln!(buf)?;
+        }
+        Block::Header(header) => write_header(header, buf, ctx)?,
+        Block::CodeBlock(codeblock) => write_codeblock(codeblock, buf)?,
+        Block::RawBlock(rawblock) => write_rawblock(rawblock, buf, ctx)?,
+        Block::BlockQuote(blockquote) => write_blockquote(blockquote, buf, ctx)?,
+        Block::LineBlock(lineblock) => write_lineblock(lineblock, buf, ctx)?,
+        Block::BulletList(bulletlist) => write_bulletlist(bulletlist, buf, ctx)?,
+        Block::OrderedList(orderedlist) => write_orderedlist(orderedlist, buf, ctx)?,
+        Block::DefinitionList(deflist) => write_definitionlist(deflist, buf, ctx)?,
+        Block::HorizontalRule(_) => writeln!(buf, "---")?,
+        Block::Table(table) => write_table(table, buf, ctx)?,
+        Block::Figure(figure) => write_figure(figure, buf, ctx)?,
+        // No fenced divs in GFM: keep the content only
+        Block::Div(div) => write_blocks(&div.content, buf, ctx)?,
+        // Written with the other notes after the body, when referenced
+        Block::NoteDefinitionPara(_) | Block::NoteDefinitionFencedBlock(_) => {}
+        Block::BlockMetadata(meta) => {
+            ctx.warn_dropped_node("BlockMetadata", &meta.source_info);
+        }
+        Block::CaptionBlock(caption) => {
+            ctx.warn_dropped_node("CaptionBlock", &caption.source_info);
+        }
+        Block::Custom(custom) => {
+            ctx.warn_dropped_node(
+                &format!("Custom block ({})", custom.type_name),
+                &custom.source_info,
+            );
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Entry points
+// ============================================================================
+
+fn write_impl(pandoc: &Pandoc, buf: &mut dyn Write, ctx: &mut GfmWriterContext) -> io::Result<()> {
+    ctx.note_definitions = NoteDefinitions::collect(&pandoc.blocks);
+    write_blocks(&pandoc.blocks, buf, ctx)?;
+
+    // Notes may themselves contain notes, so drain until no new ones appear
+    let mut next = 0;
+    while next < ctx.notes.len() {
+        if next == 0 && !pandoc.blocks.is_empty() {
+            writeln!(buf)?;
+        }
+        if next > 0 {
+            writeln!(buf)?;
+        }
+        let content = ctx.notes[next].clone();
+        next += 1;
+        write_note_definition_blocks(&next.to_string(), &content, buf, ctx)?;
+    }
+    Ok(())
+}
+
+/// Write a Pandoc document as GitHub-Flavored Markdown.
+///
+/// Returns the warnings for nodes that could not be represented in GFM.
+/// IO errors are returned as `Err`.
+pub fn write<T: Write>(
+    pandoc: &Pandoc,
+    buf: &mut T,
+) -> Result<Vec<DiagnosticMessage>, Vec<DiagnosticMessage>> {
+    let mut ctx = GfmWriterContext::new();
+    if let Err(e) = write_impl(pandoc, buf, &mut ctx) {
+        return Err(vec![
+            DiagnosticMessageBuilder::error("IO error during write")
+                .with_code("Q-3-1")
+                .problem(format!("Failed to write GFM output: {}", e))
+                .build(),
+        ]);
+    }
+    Ok(ctx.into_diagnostics())
+}
+
+/// Convert blocks to a GFM string, returning diagnostics.
+pub fn blocks_to_string(blocks: &[Block]) -> (String, Vec<DiagnosticMessage>) {
+    let pandoc = Pandoc {
+        meta: Default::default(),
+        blocks: blocks.to_vec(),
+    };
+    let mut buf = Vec::new();
+    let diagnostics = match write(&pandoc, &mut buf) {
+        Ok(diagnostics) | Err(diagnostics) => diagnostics,
+    };
+    (String::from_utf8_lossy(&buf).into_owned(), diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qmd_to_gfm(input: &str) -> (String, Vec<DiagnosticMessage>) {
+        let (pandoc, _context, _warnings) = crate::readers::qmd::read(
+            input.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .expect("Failed to parse QMD");
+        let mut buf = Vec::new();
+        let diagnostics = write(&pandoc, &mut buf).expect("Failed to write GFM");
+        (String::from_utf8(buf).unwrap(), diagnostics)
+    }
+
+    #[test]
+    fn test_emphasis_and_strikeout() {
+        let (out, diags) = qmd_to_gfm("*a* **b** ~~c~~\n");
+        assert_eq!(out, "*a* **b** ~~c~~\n");
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn test_header_drops_attributes() {
+        let (out, _) = qmd_to_gfm("## Title {#sec-title .unnumbered}\n");
+        assert_eq!(out, "## Title\n");
+    }
+
+    #[test]
+    fn test_fenced_div_is_unwrapped() {
+        let (out, _) = qmd_to_gfm("::: {.note}\n\nInside\n\n:::\n");
+        assert_eq!(out, "Inside\n");
+    }
+
+    #[test]
+    fn test_code_block_keeps_language_only() {
+        let (out, _) = qmd_to_gfm("```{.python filename=\"a.py\"}\nprint(1)\n```\n");
+        assert_eq!(out, "```python\nprint(1)\n```\n");
+    }
+
+    #[test]
+    fn test_autolink() {
+        let (out, _) = qmd_to_gfm("<https://example.com>\n");
+        assert_eq!(out, "<https://example.com>\n");
+    }
+
+    #[test]
+    fn test_link_with_title() {
+        let (out, _) = qmd_to_gfm("[text](https://example.com \"T\")\n");
+        assert_eq!(out, "[text](https://example.com \"T\")\n");
+    }
+
+    #[test]
+    fn test_pipe_table() {
+        let input = "| a | b |\n|:--|--:|\n| 1 | 2 |\n";
+        let (out, _) = qmd_to_gfm(input);
+        assert_eq!(out, "| a   | b   |\n| :-- | --: |\n| 1   | 2   |\n");
+    }
+
+    #[test]
+    fn test_task_list() {
+        let (out, _) = qmd_to_gfm("* \u{2610} todo\n* \u{2612} done\n");
+        assert_eq!(out, "- [ ] todo\n- [x] done\n");
+    }
+
+    #[test]
+    fn test_nested_list_indentation() {
+        let (out, _) = qmd_to_gfm("1. one\n\n    * nested\n\n2. two\n");
+        assert_eq!(out, "1. one\n\n   - nested\n\n2. two\n");
+    }
+
+    #[test]
+    fn test_blockquote_blank_lines_have_no_trailing_space() {
+        let (out, _) = qmd_to_gfm("> a\n>\n> b\n");
+        assert_eq!(out, "> a\n>\n> b\n");
+    }
+
+    #[test]
+    fn test_inline_note_becomes_footnote() {
+        let (out, _) = qmd_to_gfm("Text^[A note.]\n");
+        assert_eq!(out, "Text[^1]\n\n[^1]: A note.\n");
+    }
+
+    #[test]
+    fn test_inline_notes_and_definitions_share_numbers() {
+        let (out, diagnostics) = qmd_to_gfm(
+            "One^[Inline.] two[^1] three[^b] again[^1].\n\n[^1]: First.\n\n[^b]: Second[^b].\n",
+        );
+        assert_eq!(
+            out,
+            "One[^1] two[^2] three[^3] again[^2].\n\n\
+             [^1]: Inline.\n\n\
+             [^2]: First.\n\n\
+             [^3]: Second[^3].\n"
+        );
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_raw_latex_dropped_with_warning() {
+        let (out, diags) = qmd_to_gfm("`\\foo`{=latex}\n");
+        assert_eq!(out, "\n");
+        assert_eq!(diags.len(), 1);
+    }
+
+    #[test]
+    fn test_raw_html_passthrough() {
+        let (out, diags) = qmd_to_gfm("```{=html}\n<div>x</div>\n```\n");
+        assert_eq!(out, "<div>x</div>\n");
+        assert!(diags.is_empty());
+    }
+
+    #[test]
+    fn test_special_characters_escaped() {
+        let (out, _) = qmd_to_gfm("a \\| b \\$ c\n");
+        assert_eq!(out, "a \\| b \\$ c\n");
+    }
+}
@@ -3,23 +3,104 @@
  * Copyright (c) 2025 Posit, PBC
  */
 
-use crate::pandoc::{ASTContext, Attr, Block, CitationMode, Inline, Inlines, Pandoc};
+use crate::highlighting::{Language, language_for_classes};
+use crate::pandoc::{
+    ASTContext, Attr, Block, CitationMode, CodeBlock, Div, Inline, Inlines, Pandoc,
+};
 use crate::writers::html_source::build_source_map;
+use crate::writers::incremental::{block_source_info, inline_source_info};
 use crate::writers::json::{self, JsonConfig};
-use quarto_pandoc_types::ConfigValue;
+use crate::writers::mathml::tex_to_mathml;
+use crate::writers::notes::note_reference_id;
+use crate::writers::raw::{ForeignRawPolicy, HTML_FORMATS, is_native_format};
+use crate::writers::source_pos::source_pos;
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_pandoc_types::{ConfigValue, NodeId, assign_node_ids, block_at};
+use quarto_source_map::SourceInfo;
 use std::collections::HashMap;
 use std::io::Write;
-use std::marker::PhantomData;
 
 // =============================================================================
 // Configuration and Context
 // =============================================================================
 
+/// How math is rendered in HTML output (Pandoc's `--html-math-method`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MathMethod {
+    /// TeX in `\(...\)` / `\[...\]` delimiters, typeset by MathJax in the browser
+    #[default]
+    MathJax,
+    /// Bare TeX in `span.math`, typeset by KaTeX's auto-render script
+    KaTeX,
+    /// MathML converted at render time; needs no scripts
+    MathML,
+    /// Bare TeX in `span.math`, shown as-is
+    Plain,
+}
+
+impl MathMethod {
+    /// Parse a method name as used in `html-math-method`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "mathjax" => Some(MathMethod::MathJax),
+            "katex" => Some(MathMethod::KaTeX),
+            "mathml" => Some(MathMethod::MathML),
+            "plain" => Some(MathMethod::Plain),
+            _ => None,
+        }
+    }
+
+    /// The method's name as used in `html-math-method`.
+    pub fn name(self) -> &'static str {
+        match self {
+            MathMethod::MathJax => "mathjax",
+            MathMethod::KaTeX => "katex",
+            MathMethod::MathML => "mathml",
+            MathMethod::Plain => "plain",
+        }
+    }
+}
+
 /// Configuration for HTML output
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct HtmlConfig {
     /// Include source location tracking (data-loc, data-sid attributes)
     pub include_source_locations: bool,
+    /// Include `data-pos="file:line:col-line:col"` attributes resolved
+    /// directly from each node's source info (see [`source_pos`])
+    pub source_positions: bool,
+    /// Syntax-highlight code blocks in supported languages
+    pub highlight: bool,
+    /// How to render math
+    pub math: MathMethod,
+    /// What to do with raw content in formats other than HTML
+    pub foreign_raw: ForeignRawPolicy,
+    /// Include `data-quarto-node` attributes with each block's stable id
+    /// (see [`assign_node_ids`]), for patching a preview in place
+    pub node_ids: bool,
+}
+
+impl Default for HtmlConfig {
+    fn default() -> Self {
+        Self {
+            include_source_locations: false,
+            source_positions: false,
+            highlight: true,
+            math: MathMethod::default(),
+            foreign_raw: ForeignRawPolicy::default(),
+            node_ids: false,
+        }
+    }
+}
+
+/// Summary of what the HTML writer produced, for callers that add
+/// document-level dependencies (such as math scripts).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HtmlWriteSummary {
+    /// Whether the document contained any math
+    pub has_math: bool,
+    /// Warnings for content that was not written (e.g. `{=latex}` raw blocks)
+    pub diagnostics: Vec<DiagnosticMessage>,
 }
 
 /// Extract HTML configuration from document metadata.
@@ -29,18 +110,58 @@ pub struct HtmlConfig {
 /// format:
 ///   html:
 ///     source-location: full
+///     source-positions: true
+///     highlight-style: none
+///     html-math-method: katex
+///     foreign-raw: keep
+///     node-ids: true
 /// ```
 ///
 /// If `format.html.source-location` is set to "full", enables source location tracking.
+/// If `format.html.source-positions` is true, enables `data-pos` attributes.
+/// If `format.html.highlight-style` is set to "none", disables syntax highlighting.
+/// `format.html.html-math-method` selects the [`MathMethod`], either as a name or
+/// as a map with a `method` key; unknown methods fall back to MathJax.
+/// `format.html.foreign-raw` selects the [`ForeignRawPolicy`] for raw content
+/// in formats other than HTML (`warn` or `keep`).
+/// If `format.html.node-ids` is true, enables `data-quarto-node` attributes.
 pub fn extract_config_from_metadata(meta: &ConfigValue) -> HtmlConfig {
-    let include_source_locations = meta
-        .get("format")
-        .and_then(|f| f.get("html"))
+    let html = meta.get("format").and_then(|f| f.get("html"));
+    let include_source_locations = html
         .and_then(|h| h.get("source-location"))
         .is_some_and(|sl| sl.is_string_value("full"));
+    let source_positions = html
+        .and_then(|h| h.get("source-positions"))
+        .and_then(|sp| sp.as_bool())
+        .unwrap_or(false);
+    let highlight = !html
+        .and_then(|h| h.get("highlight-style"))
+        .is_some_and(|hs| hs.is_string_value("none"));
+    let math = html
+        .and_then(|h| h.get("html-math-method"))
+        .and_then(|m| {
+            m.as_str()
+                .or_else(|| m.get("method").and_then(|m| m.as_str()))
+        })
+        .and_then(MathMethod::from_name)
+        .unwrap_or_default();
+    let foreign_raw = html
+        .and_then(|h| h.get("foreign-raw"))
+        .and_then(|p| p.as_str())
+        .and_then(ForeignRawPolicy::from_name)
+        .unwrap_or_default();
+    let node_ids = html
+        .and_then(|h| h.get("node-ids"))
+        .and_then(|n| n.as_bool())
+        .unwrap_or(false);
 
     HtmlConfig {
         include_source_locations,
+        source_positions,
+        highlight,
+        math,
+        foreign_raw,
+        node_ids,
     }
 }
 
@@ -82,10 +203,24 @@ pub struct HtmlWriterContext<'ast, W: Write> {
     writer: W,
     /// Map from AST node pointers to source info
     source_map: HashMap<*const (), SourceNodeInfo>,
+    /// Map from block pointers to stable node ids
+    node_ids: HashMap<*const (), NodeId>,
+    /// Context for resolving `data-pos` positions
+    ast_context: Option<&'ast ASTContext>,
     /// Configuration
     config: HtmlConfig,
-    /// Lifetime marker
-    _phantom: PhantomData<&'ast ()>,
+    /// Number of highlighted code blocks written so far (for `cb<n>` ids)
+    code_block_count: usize,
+    /// Number of tabsets written so far (for `tabset-<n>-<m>` ids)
+    tabset_count: usize,
+    /// Whether any math has been written
+    has_math: bool,
+    /// Rendered footnote bodies, indexed by note number - 1
+    notes: Vec<String>,
+    /// Note definition blocks by id, for resolving `[^id]` references
+    note_definitions: HashMap<&'ast str, &'ast Block>,
+    /// Warnings for content that was not written
+    diagnostics: Vec<DiagnosticMessage>,
 }
 
 impl<'ast, W: Write> Write for HtmlWriterContext<'ast, W> {
@@ -104,8 +239,15 @@ impl<'ast, W: Write> HtmlWriterContext<'ast, W> {
         Self {
             writer,
             source_map: HashMap::new(),
+            node_ids: HashMap::new(),
+            ast_context: None,
             config: HtmlConfig::default(),
-            _phantom: PhantomData,
+            code_block_count: 0,
+            tabset_count: 0,
+            has_math: false,
+            notes: Vec::new(),
+            note_definitions: HashMap::new(),
+            diagnostics: Vec::new(),
         }
     }
 
@@ -114,16 +256,61 @@ impl<'ast, W: Write> HtmlWriterContext<'ast, W> {
         Self {
             writer,
             source_map: HashMap::new(),
+            node_ids: HashMap::new(),
+            ast_context: None,
             config,
-            _phantom: PhantomData,
+            code_block_count: 0,
+            tabset_count: 0,
+            has_math: false,
+            notes: Vec::new(),
+            note_definitions: HashMap::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Record the note definitions in `blocks` so `[^id]` references resolve.
+    fn collect_note_definitions(&mut self, blocks: &'ast [Block]) {
+        for block in blocks {
+            match block {
+                Block::NoteDefinitionPara(note) => {
+                    self.note_definitions.insert(note.id.as_str(), block);
+                }
+                Block::NoteDefinitionFencedBlock(note) => {
+                    self.note_definitions.insert(note.id.as_str(), block);
+                }
+                Block::Div(div) => self.collect_note_definitions(&div.content),
+                _ => {}
+            }
         }
     }
 
+    /// Record the node ids of `blocks` and their nested blocks.
+    fn assign_node_ids(&mut self, blocks: &[Block]) {
+        self.node_ids = assign_node_ids(blocks)
+            .iter()
+            .filter_map(|(path, id)| {
+                let block = block_at(blocks, path)?;
+                Some((block as *const Block as *const (), id))
+            })
+            .collect();
+    }
+
+    /// Look up the node id of a block
+    fn node_id(&self, block: &Block) -> Option<NodeId> {
+        let key = block as *const Block as *const ();
+        self.node_ids.get(&key).copied()
+    }
+
     /// Set the source map (populated by parallel walk in Phase B)
     pub fn set_source_map(&mut self, source_map: HashMap<*const (), SourceNodeInfo>) {
         self.source_map = source_map;
     }
 
+    /// Set the context used to resolve `data-pos` positions
+    pub fn set_ast_context(&mut self, ast_context: &'ast ASTContext) {
+        self.ast_context = Some(ast_context);
+    }
+
     /// Look up source info for a block
     pub fn get_block_info(&self, block: &Block) -> Option<&SourceNodeInfo> {
         let key = block as *const Block as *const ();
@@ -140,6 +327,43 @@ impl<'ast, W: Write> HtmlWriterContext<'ast, W> {
     pub fn include_source_locations(&self) -> bool {
         self.config.include_source_locations
     }
+
+    /// Check if any source attributes (`data-loc` or `data-pos`) are written
+    fn writes_source_attrs(&self) -> bool {
+        self.config.include_source_locations
+            || (self.config.source_positions && self.ast_context.is_some())
+    }
+
+    /// The `data-pos` value for a node, if positions are enabled
+    fn source_pos(&self, source_info: &SourceInfo) -> Option<String> {
+        if !self.config.source_positions {
+            return None;
+        }
+        source_pos(source_info, self.ast_context?)
+    }
+
+    /// Whether raw content tagged `format` should be written, warning when
+    /// foreign content is dropped.
+    fn should_write_raw(&mut self, kind: &str, format: &str, source_info: &SourceInfo) -> bool {
+        if is_native_format(format, HTML_FORMATS) {
+            return true;
+        }
+        match self.config.foreign_raw {
+            ForeignRawPolicy::Keep => true,
+            ForeignRawPolicy::Warn => {
+                self.diagnostics.push(
+                    DiagnosticMessageBuilder::warning(format!(
+                        "Node dropped in HTML output: {} with format '{}'",
+                        kind, format
+                    ))
+                    .with_location(source_info.clone())
+                    .add_hint("Set `foreign-raw: keep` to write it verbatim")
+                    .build(),
+                );
+                false
+            }
+        }
+    }
 }
 
 // =============================================================================
@@ -419,7 +643,7 @@ fn should_prefix_attribute(attr: &str) -> bool {
 // =============================================================================
 
 /// Escape HTML special characters
-fn escape_html(s: &str) -> String {
+pub(crate) fn escape_html(s: &str) -> String {
     s.chars()
         .map(|c| match c {
             '&' => "&amp;".to_string(),
@@ -459,14 +683,177 @@ fn write_attr<W: Write>(attr: &Attr, ctx: &mut HtmlWriterContext<'_, W>) -> std:
     Ok(())
 }
 
+/// Write a syntax-highlighted code block in Pandoc's markup:
+/// `<div class="sourceCode"><pre class="sourceCode lang"><code class="sourceCode lang">`
+/// with one `<span id="cb1-N">` per line, holding a line anchor and the
+/// highlighted tokens as `<span class="kw">`-style spans.
+fn write_highlighted_codeblock<W: Write>(
+    block: &Block,
+    codeblock: &CodeBlock,
+    language: &Language,
+    ctx: &mut HtmlWriterContext<'_, W>,
+) -> std::io::Result<()> {
+    let (id, classes, attrs) = &codeblock.attr;
+    ctx.code_block_count += 1;
+    let id = if id.is_empty() {
+        format!("cb{}", ctx.code_block_count)
+    } else {
+        id.clone()
+    };
+
+    // The language class is canonicalized; any other classes stay on <pre>
+    let mut pre_classes = vec!["sourceCode".to_string(), language.name.to_string()];
+    pre_classes.extend(
+        classes
+            .iter()
+            .filter(|class| !language.matches_class(class))
+            .cloned(),
+    );
+
+    write!(ctx, "<div class=\"sourceCode\"")?;
+    write_attr(&(id.clone(), vec![], attrs.clone()), ctx)?;
+    write_block_source_attrs(block, ctx)?;
+    write!(
+        ctx,
+        "><pre class=\"{}\"><code class=\"sourceCode {}\">",
+        escape_html(&pre_classes.join(" ")),
+        language.name
+    )?;
+
+    for (i, line) in language.tokenize(&codeblock.text).iter().enumerate() {
+        if i > 0 {
+            writeln!(ctx)?;
+        }
+        let line_id = format!("{}-{}", escape_html(&id), i + 1);
+        write!(
+            ctx,
+            "<span id=\"{line_id}\"><a href=\"#{line_id}\" aria-hidden=\"true\" tabindex=\"-1\"></a>"
+        )?;
+        for token in line {
+            match token.token_type.css_class() {
+                Some(class) => write!(
+                    ctx,
+                    "<span class=\"{}\">{}</span>",
+                    class,
+                    escape_html(&token.text)
+                )?,
+                None => write!(ctx, "{}", escape_html(&token.text))?,
+            }
+        }
+        write!(ctx, "</span>")?;
+    }
+
+    writeln!(ctx, "</code></pre></div>")?;
+    Ok(())
+}
+
+/// Write a tabset in Bootstrap's tab markup.
+///
+/// Each child Div with the `tab-pane` class (as produced by the panel
+/// transform) is a tab whose title is the pane's leading Header. The tab
+/// list is written as a `role="tablist"` nav, followed by the panes in a
+/// `tab-content` Div; the first tab starts active. Panes without an id get
+/// `tabset-<n>-<m>`. Children that aren't tab panes are written after the
+/// tab content unchanged.
+fn write_tabset<W: Write>(
+    block: &Block,
+    div: &Div,
+    ctx: &mut HtmlWriterContext<'_, W>,
+) -> std::io::Result<()> {
+    ctx.tabset_count += 1;
+    let tabset = ctx.tabset_count;
+
+    let panes: Vec<(String, &Div)> = div
+        .content
+        .iter()
+        .filter_map(tab_pane)
+        .enumerate()
+        .map(|(i, pane)| {
+            let id = if pane.attr.0.is_empty() {
+                format!("tabset-{}-{}", tabset, i + 1)
+            } else {
+                pane.attr.0.clone()
+            };
+            (id, pane)
+        })
+        .collect();
+
+    write!(ctx, "<div")?;
+    write_attr(&div.attr, ctx)?;
+    write_block_source_attrs(block, ctx)?;
+    writeln!(ctx, ">")?;
+
+    writeln!(ctx, "<ul class=\"nav nav-tabs\" role=\"tablist\">")?;
+    for (i, (id, pane)) in panes.iter().enumerate() {
+        let active = i == 0;
+        let id = escape_html(id);
+        write!(
+            ctx,
+            "<li class=\"nav-item\" role=\"presentation\"><a class=\"nav-link{}\" id=\"{id}-tab\" data-bs-toggle=\"tab\" data-bs-target=\"#{id}\" role=\"tab\" aria-controls=\"{id}\" aria-selected=\"{}\" href=\"\">",
+            if active { " active" } else { "" },
+            active
+        )?;
+        if let Some(Block::Header(header)) = pane.content.first() {
+            write_inlines(&header.content, ctx)?;
+        }
+        writeln!(ctx, "</a></li>")?;
+    }
+    writeln!(ctx, "</ul>")?;
+
+    writeln!(ctx, "<div class=\"tab-content\">")?;
+    for (i, (id, pane)) in panes.iter().enumerate() {
+        let mut classes = pane.attr.1.clone();
+        if i == 0 && !classes.iter().any(|c| c == "active") {
+            classes.push("active".to_string());
+        }
+        let mut attrs = pane.attr.2.clone();
+        attrs.insert("role".to_string(), "tabpanel".to_string());
+        attrs.insert("aria-labelledby".to_string(), format!("{}-tab", id));
+
+        write!(ctx, "<div")?;
+        write_attr(&(id.clone(), classes, attrs), ctx)?;
+        writeln!(ctx, ">")?;
+        let content = match pane.content.first() {
+            Some(Block::Header(_)) => &pane.content[1..],
+            _ => &pane.content[..],
+        };
+        write_blocks(content, ctx)?;
+        writeln!(ctx, "</div>")?;
+    }
+    writeln!(ctx, "</div>")?;
+
+    for child in div.content.iter().filter(|b| tab_pane(b).is_none()) {
+        write_block(child, ctx)?;
+    }
+
+    writeln!(ctx, "</div>")?;
+    Ok(())
+}
+
+/// The tab pane Div in `block`, if it is one.
+fn tab_pane(block: &Block) -> Option<&Div> {
+    match block {
+        Block::Div(div) if div.attr.1.iter().any(|c| c == "tab-pane") => Some(div),
+        _ => None,
+    }
+}
+
 /// Write source location attributes for a block element.
 ///
 /// Outputs `data-sid` (pool ID) and `data-loc` (resolved location) if
-/// source tracking is enabled and we have source info for this block.
+/// source tracking is enabled and we have source info for this block,
+/// `data-pos` if source positions are enabled, and `data-quarto-node` if node
+/// ids are enabled.
 fn write_block_source_attrs<W: Write>(
     block: &Block,
     ctx: &mut HtmlWriterContext<'_, W>,
 ) -> std::io::Result<()> {
+    if let Some(id) = ctx.node_id(block) {
+        write!(ctx, " data-quarto-node=\"{}\"", id)?;
+    }
+    if let Some(pos) = ctx.source_pos(block_source_info(block)) {
+        write!(ctx, " data-pos=\"{}\"", escape_html(&pos))?;
+    }
     if !ctx.include_source_locations() {
         return Ok(());
     }
@@ -492,11 +879,15 @@ fn write_block_source_attrs<W: Write>(
 /// Write source location attributes for an inline element.
 ///
 /// Outputs `data-sid` (pool ID) and `data-loc` (resolved location) if
-/// source tracking is enabled and we have source info for this inline.
+/// source tracking is enabled and we have source info for this inline, and
+/// `data-pos` if source positions are enabled.
 fn write_inline_source_attrs<W: Write>(
     inline: &Inline,
     ctx: &mut HtmlWriterContext<'_, W>,
 ) -> std::io::Result<()> {
+    if let Some(pos) = ctx.source_pos(inline_source_info(inline)) {
+        write!(ctx, " data-pos=\"{}\"", escape_html(&pos))?;
+    }
     if !ctx.include_source_locations() {
         return Ok(());
     }
@@ -526,7 +917,7 @@ fn write_inline<W: Write>(
 ) -> std::io::Result<()> {
     match inline {
         Inline::Str(s) => {
-            if ctx.include_source_locations() {
+            if ctx.writes_source_attrs() {
                 // Wrap in span for source tracking
                 write!(ctx, "<span")?;
                 write_inline_source_attrs(inline, ctx)?;
@@ -609,18 +1000,34 @@ fn write_inline<W: Write>(
             write!(ctx, ">{}</code>", escape_html(&c.text))?;
         }
         Inline::Math(m) => {
-            let class = match m.math_type {
-                crate::pandoc::MathType::InlineMath => "math inline",
-                crate::pandoc::MathType::DisplayMath => "math display",
-            };
-            // Use \(...\) for inline math and \[...\] for display math
-            let (open, close) = match m.math_type {
-                crate::pandoc::MathType::InlineMath => ("\\(", "\\)"),
-                crate::pandoc::MathType::DisplayMath => ("\\[", "\\]"),
+            ctx.has_math = true;
+            let display = m.math_type == crate::pandoc::MathType::DisplayMath;
+            let class = if display {
+                "math display"
+            } else {
+                "math inline"
             };
             write!(ctx, "<span class=\"{}\"", class)?;
             write_inline_source_attrs(inline, ctx)?;
-            write!(ctx, ">{}{}{}</span>", open, escape_html(&m.text), close)?;
+            write!(ctx, ">")?;
+            match ctx.config.math {
+                MathMethod::MathJax => {
+                    // Use \(...\) for inline math and \[...\] for display math
+                    let (open, close) = if display {
+                        ("\\[", "\\]")
+                    } else {
+                        ("\\(", "\\)")
+                    };
+                    write!(ctx, "{}{}{}", open, escape_html(&m.text), close)?;
+                }
+                MathMethod::KaTeX | MathMethod::Plain => {
+                    write!(ctx, "{}", escape_html(&m.text))?;
+                }
+                MathMethod::MathML => {
+                    write!(ctx, "{}", tex_to_mathml(&m.text, display))?;
+                }
+            }
+            write!(ctx, "</span>")?;
         }
         Inline::Link(link) => {
             write!(ctx, "<a href=\"{}\"", escape_html(&link.target.0))?;
@@ -647,11 +1054,14 @@ fn write_inline<W: Write>(
             write!(ctx, " />")?;
         }
         Inline::RawInline(raw) => {
-            // Only output raw HTML if format is "html"
-            if raw.format == "html" {
+            if ctx.should_write_raw("RawInline", &raw.format, &raw.source_info) {
                 write!(ctx, "{}", raw.text)?;
             }
         }
+        // The reader turns note references into empty spans
+        Inline::Span(span) if note_reference_id(span).is_some() => {
+            write_note_reference(note_reference_id(span).unwrap_or_default(), inline, ctx)?;
+        }
         Inline::Span(span) => {
             write!(ctx, "<span")?;
             write_attr(&span.attr, ctx)?;
@@ -661,16 +1071,10 @@ fn write_inline<W: Write>(
             write!(ctx, "</span>")?;
         }
         Inline::Note(note) => {
-            // Footnotes are rendered as superscript with a link
-            write!(
-                ctx,
-                "<sup class=\"footnote-ref\"><a href=\"#fn{}\">",
-                note.content.len()
-            )?;
-            write!(ctx, "[{}]", note.content.len())?;
-            write!(ctx, "</a></sup>")?;
-            // Note: Proper footnote handling would require collecting all footnotes
-            // and rendering them at the end of the document
+            write_note(NoteBody::Blocks(&note.content), inline, ctx)?;
+        }
+        Inline::NoteReference(reference) => {
+            write_note_reference(&reference.id, inline, ctx)?;
         }
         Inline::Cite(cite) => {
             // Collect all citation IDs for data-cites attribute
@@ -715,7 +1119,7 @@ fn write_inline<W: Write>(
             write!(ctx, "</span>")?;
         }
         // Quarto extensions - render as raw HTML or skip
-        Inline::Shortcode(_) | Inline::NoteReference(_) | Inline::Attr(_, _) => {
+        Inline::Shortcode(_) | Inline::Attr(_, _) => {
             // These should not appear in final output
         }
         Inline::Insert(ins) => {
@@ -755,7 +1159,7 @@ fn write_inline<W: Write>(
 
 /// Write a sequence of inlines
 fn write_inlines<W: Write>(
-    inlines: &Inlines,
+    inlines: &[Inline],
     ctx: &mut HtmlWriterContext<'_, W>,
 ) -> std::io::Result<()> {
     for inline in inlines {
@@ -818,16 +1222,20 @@ fn write_block<W: Write>(block: &Block, ctx: &mut HtmlWriterContext<'_, W>) -> s
             writeln!(ctx, "</div>")?;
         }
         Block::CodeBlock(codeblock) => {
-            write!(ctx, "<pre")?;
-            write_attr(&codeblock.attr, ctx)?;
-            write_block_source_attrs(block, ctx)?;
-            write!(ctx, "><code>")?;
-            write!(ctx, "{}", escape_html(&codeblock.text))?;
-            writeln!(ctx, "</code></pre>")?;
+            let language = language_for_classes(&codeblock.attr.1);
+            if let Some(language) = language.filter(|_| ctx.config.highlight) {
+                write_highlighted_codeblock(block, codeblock, language, ctx)?;
+            } else {
+                write!(ctx, "<pre")?;
+                write_attr(&codeblock.attr, ctx)?;
+                write_block_source_attrs(block, ctx)?;
+                write!(ctx, "><code>")?;
+                write!(ctx, "{}", escape_html(&codeblock.text))?;
+                writeln!(ctx, "</code></pre>")?;
+            }
         }
         Block::RawBlock(raw) => {
-            // Only output raw HTML if format is "html"
-            if raw.format == "html" {
+            if ctx.should_write_raw("RawBlock", &raw.format, &raw.source_info) {
                 writeln!(ctx, "{}", raw.text)?;
             }
         }
@@ -895,6 +1303,14 @@ fn write_block<W: Write>(block: &Block, ctx: &mut HtmlWriterContext<'_, W>) -> s
             write_attr(&header.attr, ctx)?;
             write_block_source_attrs(block, ctx)?;
             write!(ctx, ">")?;
+            // Section number assigned by a numbering pass (`number-sections`)
+            if let Some(number) = header.attr.2.get("number") {
+                write!(
+                    ctx,
+                    "<span class=\"header-section-number\">{}</span> ",
+                    escape_html(number)
+                )?;
+            }
             write_inlines(&header.content, ctx)?;
             writeln!(ctx, "</h{}>", header.level)?;
         }
@@ -977,6 +1393,9 @@ fn write_block<W: Write>(block: &Block, ctx: &mut HtmlWriterContext<'_, W>) -> s
             }
             writeln!(ctx, "</figure>")?;
         }
+        Block::Div(div) if div.attr.1.iter().any(|c| c == "panel-tabset") => {
+            write_tabset(block, div, ctx)?;
+        }
         Block::Div(div) => {
             // Use <section> tag for Divs with "section" class (from sectionize transform)
             let tag = if div.attr.1.contains(&"section".to_string()) {
@@ -995,20 +1414,8 @@ fn write_block<W: Write>(block: &Block, ctx: &mut HtmlWriterContext<'_, W>) -> s
         Block::BlockMetadata(_) => {
             // Metadata blocks don't render to HTML
         }
-        Block::NoteDefinitionPara(note) => {
-            // Note definitions would typically be collected and rendered as footnotes
-            write!(ctx, "<div class=\"footnote\" id=\"fn{}\"", note.id)?;
-            write_block_source_attrs(block, ctx)?;
-            write!(ctx, ">[{}] ", note.id)?;
-            write_inlines(&note.content, ctx)?;
-            writeln!(ctx, "</div>")?;
-        }
-        Block::NoteDefinitionFencedBlock(note) => {
-            write!(ctx, "<div class=\"footnote\" id=\"fn{}\"", note.id)?;
-            write_block_source_attrs(block, ctx)?;
-            writeln!(ctx, ">[{}]", note.id)?;
-            write_blocks(&note.content, ctx)?;
-            writeln!(ctx, "</div>")?;
+        Block::NoteDefinitionPara(_) | Block::NoteDefinitionFencedBlock(_) => {
+            // Note definitions are rendered where they are referenced
         }
         Block::CaptionBlock(caption) => {
             // Caption blocks are rendered as divs with caption class
@@ -1091,6 +1498,203 @@ fn write_blocks_inline<W: Write>(
     Ok(())
 }
 
+// =============================================================================
+// Footnotes
+// =============================================================================
+
+/// The content of a footnote.
+#[derive(Clone, Copy)]
+enum NoteBody<'a> {
+    /// An inline note (`^[...]`) or fenced note definition
+    Blocks(&'a [Block]),
+    /// A single-paragraph note definition
+    Inlines(&'a [Inline]),
+}
+
+/// The id of a note definition block (empty for other blocks).
+fn note_definition_id(block: &Block) -> &str {
+    match block {
+        Block::NoteDefinitionPara(note) => &note.id,
+        Block::NoteDefinitionFencedBlock(note) => &note.id,
+        _ => "",
+    }
+}
+
+/// Write the footnote for a reference to a note definition, or the
+/// reference as written when there is no definition with that id.
+fn write_note_reference<W: Write>(
+    id: &str,
+    inline: &Inline,
+    ctx: &mut HtmlWriterContext<'_, W>,
+) -> std::io::Result<()> {
+    // Remove the definition while rendering it, so a note that references
+    // itself doesn't recurse forever.
+    match ctx.note_definitions.remove(id) {
+        Some(definition) => {
+            let result = match definition {
+                Block::NoteDefinitionPara(note) => {
+                    write_note(NoteBody::Inlines(&note.content), inline, ctx)
+                }
+                Block::NoteDefinitionFencedBlock(note) => {
+                    write_note(NoteBody::Blocks(&note.content), inline, ctx)
+                }
+                _ => Ok(()),
+            };
+            ctx.note_definitions
+                .insert(note_definition_id(definition), definition);
+            result
+        }
+        None => write!(ctx, "[^{}]", escape_html(id)),
+    }
+}
+
+/// Write the reference mark for the next footnote and render its body for
+/// the footnotes section.
+///
+/// Notes are numbered in the order they are referenced, matching Pandoc.
+fn write_note<W: Write>(
+    body: NoteBody<'_>,
+    inline: &Inline,
+    ctx: &mut HtmlWriterContext<'_, W>,
+) -> std::io::Result<()> {
+    // Reserve the number before rendering, so notes nested in this one
+    // are numbered after it.
+    ctx.notes.push(String::new());
+    let number = ctx.notes.len();
+
+    write!(
+        ctx,
+        "<a href=\"#fn{0}\" class=\"footnote-ref\" id=\"fnref{0}\" role=\"doc-noteref\"",
+        number
+    )?;
+    write_inline_source_attrs(inline, ctx)?;
+    write!(ctx, "><sup>{}</sup></a>", number)?;
+
+    let html = render_to_string(ctx, |ctx| write_note_body(body, number, ctx))?;
+    ctx.notes[number - 1] = html;
+    Ok(())
+}
+
+/// Write a footnote body, with the backlink at the end of its last paragraph.
+fn write_note_body<W: Write>(
+    body: NoteBody<'_>,
+    number: usize,
+    ctx: &mut HtmlWriterContext<'_, W>,
+) -> std::io::Result<()> {
+    let backlink = format!(
+        "<a href=\"#fnref{}\" class=\"footnote-back\" role=\"doc-backlink\">\u{21A9}\u{FE0E}</a>",
+        number
+    );
+    match body {
+        NoteBody::Inlines(inlines) => {
+            write!(ctx, "<p>")?;
+            write_inlines(inlines, ctx)?;
+            write!(ctx, "{}</p>", backlink)?;
+        }
+        NoteBody::Blocks(blocks) => match blocks.split_last() {
+            Some((last @ Block::Paragraph(para), rest)) => {
+                write_blocks(rest, ctx)?;
+                write!(ctx, "<p")?;
+                write_block_source_attrs(last, ctx)?;
+                write!(ctx, ">")?;
+                write_inlines(&para.content, ctx)?;
+                write!(ctx, "{}</p>", backlink)?;
+            }
+            Some((Block::Plain(plain), rest)) => {
+                write_blocks(rest, ctx)?;
+                write_inlines(&plain.content, ctx)?;
+                write!(ctx, "{}", backlink)?;
+            }
+            _ => {
+                write_blocks(blocks, ctx)?;
+                write!(ctx, "<p>{}</p>", backlink)?;
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Render into a separate buffer that shares the context's state (source
+/// map, node ids, counters and notes).
+fn render_to_string<'ast, W: Write>(
+    ctx: &mut HtmlWriterContext<'ast, W>,
+    render: impl FnOnce(&mut HtmlWriterContext<'ast, Vec<u8>>) -> std::io::Result<()>,
+) -> std::io::Result<String> {
+    let mut buffer = HtmlWriterContext {
+        writer: Vec::new(),
+        source_map: std::mem::take(&mut ctx.source_map),
+        node_ids: std::mem::take(&mut ctx.node_ids),
+        ast_context: ctx.ast_context,
+        config: ctx.config.clone(),
+        code_block_count: ctx.code_block_count,
+        tabset_count: ctx.tabset_count,
+        has_math: ctx.has_math,
+        notes: std::mem::take(&mut ctx.notes),
+        note_definitions: std::mem::take(&mut ctx.note_definitions),
+        diagnostics: std::mem::take(&mut ctx.diagnostics),
+    };
+    let result = render(&mut buffer);
+
+    let HtmlWriterContext {
+        writer,
+        source_map,
+        node_ids,
+        code_block_count,
+        tabset_count,
+        has_math,
+        notes,
+        note_definitions,
+        diagnostics,
+        ..
+    } = buffer;
+    ctx.source_map = source_map;
+    ctx.node_ids = node_ids;
+    ctx.code_block_count = code_block_count;
+    ctx.tabset_count = tabset_count;
+    ctx.has_math = has_math;
+    ctx.notes = notes;
+    ctx.note_definitions = note_definitions;
+    ctx.diagnostics = diagnostics;
+
+    result?;
+    String::from_utf8(writer).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Write the footnotes section for the notes collected so far, if any.
+///
+/// Uses Pandoc's markup, so existing footnote CSS applies.
+fn write_footnotes_section<W: Write>(ctx: &mut HtmlWriterContext<'_, W>) -> std::io::Result<()> {
+    if ctx.notes.is_empty() {
+        return Ok(());
+    }
+    let notes = std::mem::take(&mut ctx.notes);
+    writeln!(
+        ctx,
+        "<section id=\"footnotes\" class=\"footnotes footnotes-end-of-document\" role=\"doc-endnotes\">"
+    )?;
+    writeln!(ctx, "<hr />")?;
+    writeln!(ctx, "<ol>")?;
+    for (i, note) in notes.iter().enumerate() {
+        writeln!(ctx, "<li id=\"fn{}\">{}</li>", i + 1, note)?;
+    }
+    writeln!(ctx, "</ol>")?;
+    writeln!(ctx, "</section>")?;
+    Ok(())
+}
+
+/// Write a whole document: its blocks followed by the footnotes section.
+fn write_document_body<'ast, W: Write>(
+    pandoc: &'ast Pandoc,
+    ctx: &mut HtmlWriterContext<'ast, W>,
+) -> std::io::Result<()> {
+    ctx.collect_note_definitions(&pandoc.blocks);
+    if ctx.config.node_ids {
+        ctx.assign_node_ids(&pandoc.blocks);
+    }
+    write_blocks(&pandoc.blocks, ctx)?;
+    write_footnotes_section(ctx)
+}
+
 // =============================================================================
 // Public API
 // =============================================================================
@@ -1102,8 +1706,7 @@ pub fn write_with_config<W: Write>(
     config: HtmlConfig,
 ) -> std::io::Result<()> {
     let mut ctx = HtmlWriterContext::with_config(writer, config);
-    write_blocks(&pandoc.blocks, &mut ctx)?;
-    Ok(())
+    write_document_body(pandoc, &mut ctx)
 }
 
 /// Write a Pandoc document to HTML with source location tracking.
@@ -1129,30 +1732,54 @@ pub fn write_with_source_tracking<W: Write>(
 ) -> std::io::Result<()> {
     let config = HtmlConfig {
         include_source_locations: true,
+        ..Default::default()
     };
+    write_document(pandoc, ast_context, writer, config)?;
+    Ok(())
+}
+
+/// Write a Pandoc document to HTML with an explicit configuration.
+///
+/// Source location tracking is set up when `config.include_source_locations`
+/// is set (see [`write_with_source_tracking`]). Returns a summary of what was
+/// written, so callers can add dependencies such as math scripts only when
+/// the document needs them.
+pub fn write_document<W: Write>(
+    pandoc: &Pandoc,
+    ast_context: &ASTContext,
+    writer: W,
+    config: HtmlConfig,
+) -> std::io::Result<HtmlWriteSummary> {
+    let include_source_locations = config.include_source_locations;
     let mut ctx = HtmlWriterContext::with_config(writer, config);
+    ctx.set_ast_context(ast_context);
 
-    // Generate JSON with source locations enabled
-    let json_config = JsonConfig {
-        include_inline_locations: true,
-    };
+    if include_source_locations {
+        // Generate JSON with source locations enabled
+        let json_config = JsonConfig {
+            include_inline_locations: true,
+        };
 
-    match json::write_pandoc(pandoc, ast_context, &json_config) {
-        Ok(json_value) => {
-            // Build source map by walking AST and JSON in parallel
-            let source_map = build_source_map(pandoc, &json_value);
-            ctx.set_source_map(source_map);
-        }
-        Err(_errors) => {
-            // If JSON generation fails, we continue without source tracking
-            // This is a graceful degradation - the HTML will still be valid
-            // but without source location attributes.
-            // TODO: Consider logging this failure for debugging
+        match json::write_pandoc(pandoc, ast_context, &json_config) {
+            Ok(json_value) => {
+                // Build source map by walking AST and JSON in parallel
+                let source_map = build_source_map(pandoc, &json_value);
+                ctx.set_source_map(source_map);
+            }
+            Err(_errors) => {
+                // If JSON generation fails, we continue without source tracking
+                // This is a graceful degradation - the HTML will still be valid
+                // but without source location attributes.
+                // TODO: Consider logging this failure for debugging
+            }
         }
     }
 
-    write_blocks(&pandoc.blocks, &mut ctx)?;
-    Ok(())
+    write_document_body(pandoc, &mut ctx)?;
+    Ok(HtmlWriteSummary {
+        has_math: ctx.has_math,
+        diagnostics: ctx.diagnostics,
+    })
 }
 
 /// Main entry point for the HTML writer.
@@ -1168,6 +1795,7 @@ pub fn write_with_source_tracking<W: Write>(
 ///
 /// When `source-location: full` is specified, the output HTML will include
 /// `data-sid` and `data-loc` attributes on elements for source tracking.
+/// See [`extract_config_from_metadata`] for the other options.
 ///
 /// # Arguments
 ///
@@ -1184,12 +1812,8 @@ pub fn write<W: Write>(
     writer: W,
 ) -> std::io::Result<()> {
     let config = extract_config_from_metadata(&pandoc.meta);
-
-    if config.include_source_locations {
-        write_with_source_tracking(pandoc, ast_context, writer)
-    } else {
-        write_with_config(pandoc, writer, config)
-    }
+    write_document(pandoc, ast_context, writer, config)?;
+    Ok(())
 }
 
 /// Public wrapper to write blocks (for external callers)
@@ -1209,6 +1833,7 @@ pub fn write_inlines_to<W: Write>(inlines: &Inlines, writer: W) -> std::io::Resu
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pandoc::MathType;
     use crate::pandoc::block::Paragraph;
     use crate::pandoc::inline::Str;
     use quarto_pandoc_types::{ConfigMapEntry, ConfigValue, ConfigValueKind, MergeOp};
@@ -1292,6 +1917,42 @@ mod tests {
         // (depending on whether source tracking worked correctly)
     }
 
+    #[test]
+    fn test_write_document_with_source_positions() {
+        let mut context = ASTContext::new();
+        let file_id = context
+            .source_context
+            .add_file("doc.qmd".to_string(), Some("Hello *there*\n".to_string()));
+        let para = Block::Paragraph(Paragraph {
+            content: vec![Inline::Str(Str {
+                text: "Hello".to_string(),
+                source_info: SourceInfo::original(file_id, 0, 5),
+            })],
+            source_info: SourceInfo::original(file_id, 0, 13),
+        });
+        let pandoc = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![para],
+        };
+        let config = HtmlConfig {
+            source_positions: true,
+            ..Default::default()
+        };
+
+        let mut output = Vec::new();
+        write_document(&pandoc, &context, &mut output, config).unwrap();
+        let html = String::from_utf8(output).unwrap();
+
+        assert!(
+            html.starts_with(
+                "<p data-pos=\"doc.qmd:1:1-1:14\"><span data-pos=\"doc.qmd:1:1-1:6\">Hello</span></p>"
+            ),
+            "{}",
+            html
+        );
+        assert!(!html.contains("data-loc"));
+    }
+
     #[test]
     fn test_resolved_location_to_data_loc_format() {
         let loc = ResolvedLocation {
@@ -1320,12 +1981,14 @@ mod tests {
     fn test_html_config_default() {
         let config = HtmlConfig::default();
         assert!(!config.include_source_locations);
+        assert!(config.highlight);
     }
 
     #[test]
     fn test_html_writer_context_include_source_locations() {
         let config = HtmlConfig {
             include_source_locations: true,
+            ..Default::default()
         };
         let ctx: HtmlWriterContext<'_, Vec<u8>> =
             HtmlWriterContext::with_config(Vec::new(), config);
@@ -1596,6 +2259,113 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_header_with_section_number() {
+        use crate::pandoc::ASTContext;
+        use crate::pandoc::block::Header;
+        use hashlink::LinkedHashMap;
+        use quarto_pandoc_types::attr::AttrSourceInfo;
+
+        let mut attrs = LinkedHashMap::new();
+        attrs.insert("number".to_string(), "1.2".to_string());
+        let header = Block::Header(Header {
+            level: 2,
+            attr: ("methods".to_string(), vec![], attrs),
+            content: vec![Inline::Str(Str {
+                text: "Methods".to_string(),
+                source_info: dummy_source_info(),
+            })],
+            source_info: dummy_source_info(),
+            attr_source: AttrSourceInfo::empty(),
+        });
+        let pandoc = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![header],
+        };
+
+        let mut output = Vec::new();
+        write(&pandoc, &ASTContext::anonymous(), &mut output).unwrap();
+        let html = String::from_utf8(output).unwrap();
+
+        assert_eq!(
+            html,
+            "<h2 id=\"methods\" data-number=\"1.2\"><span class=\"header-section-number\">1.2</span> Methods</h2>\n"
+        );
+    }
+
+    #[test]
+    fn test_tabset_renders_tab_markup() {
+        use crate::pandoc::ASTContext;
+        use crate::pandoc::block::Header;
+        use hashlink::LinkedHashMap;
+        use quarto_pandoc_types::attr::AttrSourceInfo;
+
+        let text = |s: &str| {
+            vec![Inline::Str(Str {
+                text: s.to_string(),
+                source_info: dummy_source_info(),
+            })]
+        };
+        let pane = |title: &str, body: &str| {
+            Block::Div(Div {
+                attr: (
+                    String::new(),
+                    vec!["tab-pane".to_string()],
+                    LinkedHashMap::new(),
+                ),
+                content: vec![
+                    Block::Header(Header {
+                        level: 2,
+                        attr: (String::new(), vec![], LinkedHashMap::new()),
+                        content: text(title),
+                        source_info: dummy_source_info(),
+                        attr_source: AttrSourceInfo::empty(),
+                    }),
+                    Block::Paragraph(Paragraph {
+                        content: text(body),
+                        source_info: dummy_source_info(),
+                    }),
+                ],
+                source_info: dummy_source_info(),
+                attr_source: AttrSourceInfo::empty(),
+            })
+        };
+        let tabset = Block::Div(Div {
+            attr: (
+                String::new(),
+                vec!["panel-tabset".to_string()],
+                LinkedHashMap::new(),
+            ),
+            content: vec![pane("R", "r code"), pane("Python", "py code")],
+            source_info: dummy_source_info(),
+            attr_source: AttrSourceInfo::empty(),
+        });
+        let pandoc = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![tabset],
+        };
+
+        let mut output = Vec::new();
+        write(&pandoc, &ASTContext::anonymous(), &mut output).unwrap();
+        let html = String::from_utf8(output).unwrap();
+
+        assert!(html.starts_with(
+            "<div class=\"panel-tabset\">\n<ul class=\"nav nav-tabs\" role=\"tablist\">\n"
+        ));
+        assert!(html.contains(
+            "<li class=\"nav-item\" role=\"presentation\"><a class=\"nav-link active\" id=\"tabset-1-1-tab\" data-bs-toggle=\"tab\" data-bs-target=\"#tabset-1-1\" role=\"tab\" aria-controls=\"tabset-1-1\" aria-selected=\"true\" href=\"\">R</a></li>\n"
+        ));
+        assert!(html.contains("aria-selected=\"false\" href=\"\">Python</a></li>"));
+        assert!(html.contains(
+            "<div id=\"tabset-1-1\" class=\"tab-pane active\" role=\"tabpanel\" aria-labelledby=\"tabset-1-1-tab\">\n<p>r code</p>\n</div>"
+        ));
+        assert!(html.contains(
+            "<div id=\"tabset-1-2\" class=\"tab-pane\" role=\"tabpanel\" aria-labelledby=\"tabset-1-2-tab\">\n<p>py code</p>\n</div>"
+        ));
+        // Tab titles are only written in the tab list
+        assert!(!html.contains("<h2"));
+    }
+
     #[test]
     fn test_div_without_section_class_renders_as_div_tag() {
         use crate::pandoc::ASTContext;
@@ -1643,4 +2413,300 @@ mod tests {
             html
         );
     }
+
+    #[test]
+    fn test_extract_config_highlight_style_none() {
+        let meta = make_config_map(vec![make_config_entry(
+            "format",
+            make_config_map(vec![make_config_entry(
+                "html",
+                make_config_map(vec![make_config_entry(
+                    "highlight-style",
+                    make_config_string("none"),
+                )]),
+            )]),
+        )]);
+        let config = extract_config_from_metadata(&meta);
+        assert!(!config.highlight);
+    }
+
+    fn codeblock_html(classes: &[&str], text: &str, config: HtmlConfig) -> String {
+        use hashlink::LinkedHashMap;
+        use quarto_pandoc_types::attr::AttrSourceInfo;
+
+        let codeblock = Block::CodeBlock(CodeBlock {
+            attr: (
+                String::new(),
+                classes.iter().map(|c| c.to_string()).collect(),
+                LinkedHashMap::new(),
+            ),
+            text: text.to_string(),
+            source_info: dummy_source_info(),
+            attr_source: AttrSourceInfo::empty(),
+        });
+        let pandoc = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![codeblock.clone(), codeblock],
+        };
+
+        let mut output = Vec::new();
+        write_with_config(&pandoc, &mut output, config).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_highlighted_codeblock() {
+        let html = codeblock_html(
+            &["{python}"],
+            "x = 1 # one\nprint(x)",
+            HtmlConfig::default(),
+        );
+        assert!(
+            html.starts_with(
+                "<div class=\"sourceCode\" id=\"cb1\"><pre class=\"sourceCode python\">\
+                 <code class=\"sourceCode python\"><span id=\"cb1-1\">\
+                 <a href=\"#cb1-1\" aria-hidden=\"true\" tabindex=\"-1\"></a>x \
+                 <span class=\"op\">=</span> <span class=\"dv\">1</span> \
+                 <span class=\"co\"># one</span></span>\n"
+            ),
+            "{}",
+            html
+        );
+        assert!(html.contains("<span class=\"bu\">print</span>(x)</span></code></pre></div>"));
+        // Each block gets its own id
+        assert!(html.contains("id=\"cb2\""), "{}", html);
+    }
+
+    #[test]
+    fn test_codeblock_not_highlighted() {
+        let expected = "<pre class=\"unknown\"><code>a &lt; b</code></pre>\n";
+        let html = codeblock_html(&["unknown"], "a < b", HtmlConfig::default());
+        assert!(html.starts_with(expected), "{}", html);
+
+        let config = HtmlConfig {
+            highlight: false,
+            ..Default::default()
+        };
+        let html = codeblock_html(&["python"], "x = 1", config);
+        assert!(html.starts_with("<pre class=\"python\"><code>x = 1</code></pre>"));
+    }
+
+    #[test]
+    fn test_extract_config_math_method() {
+        let html_with = |value: ConfigValue| {
+            make_config_map(vec![make_config_entry(
+                "format",
+                make_config_map(vec![make_config_entry(
+                    "html",
+                    make_config_map(vec![make_config_entry("html-math-method", value)]),
+                )]),
+            )])
+        };
+
+        let config = extract_config_from_metadata(&html_with(make_config_string("katex")));
+        assert_eq!(config.math, MathMethod::KaTeX);
+
+        let method_map = make_config_map(vec![make_config_entry(
+            "method",
+            make_config_string("mathml"),
+        )]);
+        let config = extract_config_from_metadata(&html_with(method_map));
+        assert_eq!(config.math, MathMethod::MathML);
+
+        let config = extract_config_from_metadata(&html_with(make_config_string("webtex")));
+        assert_eq!(config.math, MathMethod::MathJax);
+    }
+
+    fn math_html(math_type: MathType, text: &str, math: MathMethod) -> (String, HtmlWriteSummary) {
+        use crate::pandoc::{Math, Plain};
+
+        let pandoc = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![Block::Plain(Plain {
+                content: vec![Inline::Math(Math {
+                    math_type,
+                    text: text.to_string(),
+                    source_info: dummy_source_info(),
+                })],
+                source_info: dummy_source_info(),
+            })],
+        };
+        let config = HtmlConfig {
+            math,
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        let summary = write_document(&pandoc, &ASTContext::new(), &mut output, config).unwrap();
+        let html = String::from_utf8(output).unwrap();
+        (html.trim_end().to_string(), summary)
+    }
+
+    #[test]
+    fn test_math_methods() {
+        let (html, summary) = math_html(MathType::InlineMath, "a<b", MathMethod::MathJax);
+        assert_eq!(html, "<span class=\"math inline\">\\(a&lt;b\\)</span>");
+        assert!(summary.has_math);
+
+        let (html, _) = math_html(MathType::DisplayMath, "x^2", MathMethod::MathJax);
+        assert_eq!(html, "<span class=\"math display\">\\[x^2\\]</span>");
+
+        let (html, _) = math_html(MathType::DisplayMath, "x^2", MathMethod::KaTeX);
+        assert_eq!(html, "<span class=\"math display\">x^2</span>");
+
+        let (html, _) = math_html(MathType::InlineMath, "x^2", MathMethod::Plain);
+        assert_eq!(html, "<span class=\"math inline\">x^2</span>");
+
+        let (html, _) = math_html(MathType::InlineMath, "x^2", MathMethod::MathML);
+        assert!(
+            html.starts_with(
+                "<span class=\"math inline\"><math display=\"inline\" \
+                 xmlns=\"http://www.w3.org/1998/Math/MathML\"><semantics><mrow>\
+                 <msup><mi>x</mi><mn>2</mn></msup>"
+            ),
+            "{}",
+            html
+        );
+    }
+
+    #[test]
+    fn test_summary_without_math() {
+        let pandoc = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![],
+        };
+        let mut output = Vec::new();
+        let summary = write_document(
+            &pandoc,
+            &ASTContext::new(),
+            &mut output,
+            HtmlConfig::default(),
+        )
+        .unwrap();
+        assert!(!summary.has_math);
+    }
+
+    fn raw_html(format: &str, foreign_raw: ForeignRawPolicy) -> (String, HtmlWriteSummary) {
+        use crate::pandoc::{Plain, RawBlock, RawInline};
+
+        let pandoc = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![
+                Block::RawBlock(RawBlock {
+                    format: format.to_string(),
+                    text: "<hr />".to_string(),
+                    source_info: dummy_source_info(),
+                }),
+                Block::Plain(Plain {
+                    content: vec![Inline::RawInline(RawInline {
+                        format: format.to_string(),
+                        text: "<br />".to_string(),
+                        source_info: dummy_source_info(),
+                    })],
+                    source_info: dummy_source_info(),
+                }),
+            ],
+        };
+        let config = HtmlConfig {
+            foreign_raw,
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        let summary = write_document(&pandoc, &ASTContext::new(), &mut output, config).unwrap();
+        (String::from_utf8(output).unwrap(), summary)
+    }
+
+    #[test]
+    fn test_raw_html_is_written_verbatim() {
+        for format in ["html", "html5", "HTML"] {
+            let (html, summary) = raw_html(format, ForeignRawPolicy::Warn);
+            assert_eq!(html, "<hr />\n<br />\n");
+            assert!(summary.diagnostics.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_foreign_raw_is_dropped_with_warning() {
+        let (html, summary) = raw_html("latex", ForeignRawPolicy::Warn);
+        assert_eq!(html, "\n");
+        assert_eq!(summary.diagnostics.len(), 2);
+        assert!(summary.diagnostics[0].title.contains("RawBlock"));
+        assert!(summary.diagnostics[0].title.contains("'latex'"));
+        assert!(summary.diagnostics[1].title.contains("RawInline"));
+    }
+
+    #[test]
+    fn test_foreign_raw_keep() {
+        let (html, summary) = raw_html("latex", ForeignRawPolicy::Keep);
+        assert_eq!(html, "<hr />\n<br />\n");
+        assert!(summary.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_extract_foreign_raw_from_metadata() {
+        let html_with = |value: ConfigValue| {
+            make_config_map(vec![make_config_entry(
+                "format",
+                make_config_map(vec![make_config_entry(
+                    "html",
+                    make_config_map(vec![make_config_entry("foreign-raw", value)]),
+                )]),
+            )])
+        };
+        let config = extract_config_from_metadata(&html_with(make_config_string("keep")));
+        assert_eq!(config.foreign_raw, ForeignRawPolicy::Keep);
+        let config = extract_config_from_metadata(&html_with(make_config_string("bogus")));
+        assert_eq!(config.foreign_raw, ForeignRawPolicy::Warn);
+    }
+
+    #[test]
+    fn test_node_ids() {
+        let (pandoc, _context, _warnings) = crate::readers::qmd::read(
+            b"One.\n\n::: {.box}\n\nInside.\n\n:::\n",
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .unwrap();
+        let ids = assign_node_ids(&pandoc.blocks);
+        let write = |node_ids: bool| {
+            let mut output = Vec::new();
+            let config = HtmlConfig {
+                node_ids,
+                ..Default::default()
+            };
+            write_with_config(&pandoc, &mut output, config).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        let html = write(true);
+        let attr = |path: &[usize]| format!(" data-quarto-node=\"{}\"", ids.get(path).unwrap());
+        assert!(
+            html.contains(&format!("<p{}>One.</p>", attr(&[0]))),
+            "{}",
+            html
+        );
+        assert!(
+            html.contains(&format!("<div class=\"box\"{}>", attr(&[1]))),
+            "{}",
+            html
+        );
+        assert!(
+            html.contains(&format!("<p{}>Inside.</p>", attr(&[1, 0]))),
+            "{}",
+            html
+        );
+        assert!(!write(false).contains("data-quarto-node"));
+
+        let meta = make_config_map(vec![make_config_entry(
+            "format",
+            make_config_map(vec![make_config_entry(
+                "html",
+                make_config_map(vec![make_config_entry("node-ids", make_config_bool(true))]),
+            )]),
+        )]);
+        assert!(extract_config_from_metadata(&meta).node_ids);
+        assert!(!HtmlConfig::default().node_ids);
+    }
 }
@@ -452,7 +452,7 @@ fn block_source_span(block: &Block) -> Range<usize> {
 }
 
 /// Extract the SourceInfo from a Block.
-fn block_source_info(block: &Block) -> &SourceInfo {
+pub fn block_source_info(block: &Block) -> &SourceInfo {
     match block {
         Block::Paragraph(p) => &p.source_info,
         Block::Header(h) => &h.source_info,
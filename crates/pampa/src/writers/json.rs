@@ -37,15 +37,17 @@ pub struct JsonConfig {
 // `preserve_order` feature.
 
 /// Top-level Pandoc JSON document structure.
-/// Field order matches expected alphabetical JSON key order.
+/// Unlike the other structs, `astContext` comes last rather than in
+/// alphabetical order: its source info pool is only complete once every block
+/// has been written, and `write_with_config` streams the blocks first.
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct PandocDocumentJson {
-    ast_context: AstContextJson,
     blocks: Vec<Value>,
     meta: Value,
     #[serde(rename = "pandoc-api-version")]
     pandoc_api_version: [u32; 3],
+    ast_context: AstContextJson,
 }
 
 /// AST context with source info pool.
@@ -1782,38 +1784,17 @@ fn write_blocks(blocks: &[Block], ctx: &mut JsonWriterContext) -> Value {
     )
 }
 
-/// Generate JSON representation of a Pandoc document.
+/// Build the `astContext` object from the state left in `ctx` after
+/// serializing the document.
 ///
-/// This function is used internally by the HTML writer to build the source map.
-pub(crate) fn write_pandoc(
+/// Must run after the metadata and blocks have been serialized: it interns
+/// the metadata key sources and then snapshots the source info pool.
+fn ast_context_json(
     pandoc: &Pandoc,
     ast_context: &ASTContext,
-    config: &JsonConfig,
-) -> Result<Value, Vec<DiagnosticMessage>> {
-    // Create the JSON writer context
-    let mut ctx = JsonWriterContext::new(ast_context, config);
-
-    // Pre-serialize all Path/Glob/Expr ConfigValue variants.
-    // This builds temporary Inlines, serializes them to JSON (interning their
-    // SourceInfos into the pool), and stores the resulting JSON for later retrieval.
-    // This prevents memory reuse bugs where temporary Inlines created during
-    // the main serialization pass could have their memory addresses reused by
-    // subsequent allocations, causing the SourceInfoSerializer's pointer cache
-    // to return incorrect IDs.
-    precompute_all_json(pandoc, &mut ctx);
-
-    // Phase 5: Write ConfigValue directly without MetaValueWithSourceInfo conversion
-    // Serialize AST, which will build the pool
-    let meta_json = write_config_value_as_meta(&pandoc.meta, &mut ctx);
-    let blocks_json = write_blocks(&pandoc.blocks, &mut ctx);
-
-    // Check if any errors were accumulated
-    if !ctx.errors.is_empty() {
-        return Err(ctx.errors);
-    }
-
+    ctx: &mut JsonWriterContext,
+) -> AstContextJson {
     // Extract top-level key sources from metadata using the serializer
-    use quarto_pandoc_types::ConfigValueKind;
     let meta_top_level_key_sources: Option<Value> =
         if let ConfigValueKind::Map(ref entries) = pandoc.meta.value {
             // Sort entries by key for deterministic output
@@ -1871,42 +1852,117 @@ pub(crate) fn write_pandoc(
         .collect();
 
     // Build astContext with deterministic field ordering
-    let ast_context_json = AstContextJson {
+    AstContextJson {
         files,
         meta_top_level_key_sources,
         source_info_pool,
-    };
+    }
+}
+
+/// Generate JSON representation of a Pandoc document.
+///
+/// This function is used internally by the HTML writer to build the source map.
+pub(crate) fn write_pandoc(
+    pandoc: &Pandoc,
+    ast_context: &ASTContext,
+    config: &JsonConfig,
+) -> Result<Value, Vec<DiagnosticMessage>> {
+    // Create the JSON writer context
+    let mut ctx = JsonWriterContext::new(ast_context, config);
+
+    // Pre-serialize all Path/Glob/Expr ConfigValue variants.
+    // This builds temporary Inlines, serializes them to JSON (interning their
+    // SourceInfos into the pool), and stores the resulting JSON for later retrieval.
+    // This prevents memory reuse bugs where temporary Inlines created during
+    // the main serialization pass could have their memory addresses reused by
+    // subsequent allocations, causing the SourceInfoSerializer's pointer cache
+    // to return incorrect IDs.
+    precompute_all_json(pandoc, &mut ctx);
+
+    // Phase 5: Write ConfigValue directly without MetaValueWithSourceInfo conversion
+    // Serialize AST, which will build the pool
+    let meta_json = write_config_value_as_meta(&pandoc.meta, &mut ctx);
+    let blocks_json: Vec<Value> = pandoc
+        .blocks
+        .iter()
+        .map(|block| write_block(block, &mut ctx))
+        .collect();
+
+    // Check if any errors were accumulated
+    if !ctx.errors.is_empty() {
+        return Err(ctx.errors);
+    }
 
     // Build final document with deterministic field ordering
     let document = PandocDocumentJson {
-        ast_context: ast_context_json,
-        blocks: blocks_json.as_array().cloned().unwrap_or_default(),
+        blocks: blocks_json,
         meta: meta_json,
         pandoc_api_version: [1, 23, 1],
+        ast_context: ast_context_json(pandoc, ast_context, &mut ctx),
     };
 
     Ok(serde_json::to_value(document).unwrap())
 }
 
+/// Diagnostic for a failure while serializing or writing the JSON output.
+fn serialization_error(error: impl std::fmt::Display) -> Vec<DiagnosticMessage> {
+    vec![quarto_error_reporting::DiagnosticMessage {
+        code: Some("Q-3-38".to_string()),
+        title: "JSON serialization failed".to_string(),
+        kind: quarto_error_reporting::DiagnosticKind::Error,
+        problem: Some(format!("Failed to serialize AST to JSON: {}", error).into()),
+        details: vec![],
+        hints: vec![],
+        location: None,
+        fixes: vec![],
+    }]
+}
+
 /// Write Pandoc AST to JSON with custom configuration.
+///
+/// The document is written one top-level block at a time: each block's
+/// `Value` tree is built, written and dropped before the next one, and its
+/// source infos are added to the pool as it goes. Peak memory is the pool
+/// plus the largest block rather than the whole output. The pool is written
+/// last, in `astContext`, once every block has been written. The bytes are
+/// identical to building the whole document as one `Value` and serializing
+/// it.
+///
+/// If the AST has errors, the output written so far is incomplete and the
+/// errors are returned.
 pub fn write_with_config<W: std::io::Write>(
     pandoc: &Pandoc,
     context: &ASTContext,
     writer: &mut W,
     config: &JsonConfig,
 ) -> Result<(), Vec<DiagnosticMessage>> {
-    let json = write_pandoc(pandoc, context, config)?;
-    serde_json::to_writer(writer, &json).map_err(|e| {
-        vec![quarto_error_reporting::DiagnosticMessage {
-            code: Some("Q-3-38".to_string()),
-            title: "JSON serialization failed".to_string(),
-            kind: quarto_error_reporting::DiagnosticKind::Error,
-            problem: Some(format!("Failed to serialize AST to JSON: {}", e).into()),
-            details: vec![],
-            hints: vec![],
-            location: None,
-        }]
-    })?;
+    let mut ctx = JsonWriterContext::new(context, config);
+
+    // Same serialization order as write_pandoc, so pool IDs match
+    precompute_all_json(pandoc, &mut ctx);
+    let meta_json = write_config_value_as_meta(&pandoc.meta, &mut ctx);
+    let mut write_blocks = || -> Result<(), Box<dyn std::error::Error>> {
+        writer.write_all(b"{\"blocks\":[")?;
+        for (i, block) in pandoc.blocks.iter().enumerate() {
+            if i > 0 {
+                writer.write_all(b",")?;
+            }
+            serde_json::to_writer(&mut *writer, &write_block(block, &mut ctx))?;
+        }
+        writer.write_all(b"],\"meta\":")?;
+        serde_json::to_writer(&mut *writer, &meta_json)?;
+        writer.write_all(b",\"pandoc-api-version\":[1,23,1],\"astContext\":")?;
+        Ok(())
+    };
+    write_blocks().map_err(serialization_error)?;
+
+    if !ctx.errors.is_empty() {
+        return Err(ctx.errors);
+    }
+
+    let ast_context_json = ast_context_json(pandoc, context, &mut ctx);
+    serde_json::to_writer(&mut *writer, &ast_context_json).map_err(serialization_error)?;
+    writer.write_all(b"}").map_err(serialization_error)?;
     Ok(())
 }
 
@@ -2433,4 +2489,69 @@ mod tests {
             _ => panic!("Expected Custom block"),
         }
     }
+
+    #[test]
+    fn test_streamed_output_matches_value_serialization() {
+        let input = "---\ntitle: Streaming\nauthor: [A, B]\n---\n\n# Heading {#h .c}\n\nSome *text* with [a link](u) and $x$.\n\n- one\n- two\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n::: {.note}\nNested.\n:::\n";
+        let (pandoc, context, _warnings) = crate::readers::qmd::read(
+            input.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .expect("Failed to parse QMD");
+
+        for include_inline_locations in [false, true] {
+            let config = JsonConfig {
+                include_inline_locations,
+            };
+            let mut streamed = Vec::new();
+            write_with_config(&pandoc, &context, &mut streamed, &config).unwrap();
+            let value = write_pandoc(&pandoc, &context, &config).unwrap();
+            assert_eq!(
+                String::from_utf8(streamed).unwrap(),
+                serde_json::to_string(&value).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_streamed_output_puts_pool_last() {
+        let input = "Para *one*.\n\nPara **two**.\n";
+        let (pandoc, context, _warnings) = crate::readers::qmd::read(
+            input.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .expect("Failed to parse QMD");
+        let mut streamed = Vec::new();
+        write(&pandoc, &context, &mut streamed).unwrap();
+
+        // Blocks are written before the pool their source infos refer to
+        let text = String::from_utf8(streamed).unwrap();
+        assert!(text.starts_with("{\"blocks\":["), "{}", text);
+        assert!(text.find("\"astContext\"") > text.find("\"meta\""), "{}", text);
+
+        let (read_back, _context) = crate::readers::json::read(&mut text.as_bytes()).unwrap();
+        assert_eq!(read_back.blocks.len(), 2);
+    }
+
+    #[test]
+    fn test_streamed_output_empty_document() {
+        let pandoc = crate::pandoc::Pandoc {
+            meta: quarto_pandoc_types::ConfigValue::default(),
+            blocks: vec![],
+        };
+        let context = make_test_context();
+        let config = make_test_config();
+        let mut streamed = Vec::new();
+        write_with_config(&pandoc, &context, &mut streamed, &config).unwrap();
+        let value = write_pandoc(&pandoc, &context, &config).unwrap();
+        assert_eq!(streamed, serde_json::to_vec(&value).unwrap());
+    }
 }
@@ -0,0 +1,990 @@
+/*
+ * latex.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! LaTeX writer for Pandoc AST.
+//!
+//! Writes the document body only; the preamble (document class, packages,
+//! title block) is supplied by the template that embeds it, as with
+//! `pandoc -t latex` without `--standalone`.
+//!
+//! # Design decisions
+//!
+//! - Headers map to `\section` .. `\subparagraph`; the `unnumbered` class
+//!   selects the starred form. Ids become `\label`s, so `[text](#id)` links
+//!   are written as `\hyperref`.
+//! - Code blocks in a language the highlighter supports are written in
+//!   Pandoc's `Shaded`/`Highlighting` environments, one `\KeywordTok{...}`
+//!   style macro per token; the macros come from
+//!   [`HighlightStyle::to_latex`](crate::highlighting::HighlightStyle::to_latex)
+//!   in the preamble. Other code blocks use the `verbatim` environment.
+//! - Tables are written as `longtable`s with `booktabs` rules; block content
+//!   in cells is flattened onto one line.
+//! - Figures become floating `figure` environments; images use
+//!   `\includegraphics`, with percentage widths taken relative to
+//!   `\linewidth`.
+//! - Divs are unwrapped (an id becomes a `\label`).
+//! - RawBlock/RawInline in `latex` or `tex` is written verbatim; other
+//!   formats are dropped with a warning.
+//! - Inline notes and note references become `\footnote`s in place.
+//! - Citations without rendered content become `\cite{...}`.
+
+use crate::highlighting::{Language, language_for_classes};
+use crate::pandoc::block::{
+    BulletList, CodeBlock, DefinitionList, Div, Figure, Header, OrderedList, RawBlock,
+};
+use crate::pandoc::inline::{Image, Inline, Inlines};
+use crate::pandoc::list::ListNumberStyle;
+use crate::pandoc::table::{Alignment, Cell, ColWidth, Row, Table};
+use crate::pandoc::{Block, Blocks, Pandoc};
+use crate::writers::notes::{NoteDefinitions, ResolvedNote, note_reference_id};
+use crate::writers::raw::{LATEX_FORMATS, is_native_format};
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_source_map::SourceInfo;
+use std::io::{self, Write};
+
+/// Sectioning commands, indexed by header level - 1.
+const SECTION_COMMANDS: &[&str] = &[
+    "section",
+    "subsection",
+    "subsubsection",
+    "paragraph",
+    "subparagraph",
+];
+
+/// `enumerate` counters, indexed by nesting depth.
+const ENUM_COUNTERS: &[&str] = &["enumi", "enumii", "enumiii", "enumiv"];
+
+/// Configuration for the LaTeX writer.
+#[derive(Debug, Clone)]
+pub struct LatexConfig {
+    /// Syntax-highlight code blocks in supported languages. The preamble
+    /// must then define the highlighting macros.
+    pub highlight: bool,
+}
+
+impl Default for LatexConfig {
+    fn default() -> Self {
+        Self { highlight: true }
+    }
+}
+
+/// Context for the LaTeX writer, threaded through all write functions.
+pub struct LatexWriterContext {
+    config: LatexConfig,
+
+    /// Warnings for nodes that have no LaTeX representation.
+    diagnostics: Vec<DiagnosticMessage>,
+
+    /// Note definitions, written in place of their references.
+    notes: NoteDefinitions,
+
+    /// Current `enumerate` nesting depth.
+    enum_depth: usize,
+}
+
+impl LatexWriterContext {
+    pub fn new() -> Self {
+        Self::with_config(LatexConfig::default())
+    }
+
+    pub fn with_config(config: LatexConfig) -> Self {
+        Self {
+            config,
+            diagnostics: Vec::new(),
+            notes: NoteDefinitions::default(),
+            enum_depth: 0,
+        }
+    }
+
+    pub fn into_diagnostics(self) -> Vec<DiagnosticMessage> {
+        self.diagnostics
+    }
+
+    pub fn diagnostics(&self) -> &[DiagnosticMessage] {
+        &self.diagnostics
+    }
+
+    fn warn_dropped_node(&mut self, description: &str, source_info: &SourceInfo) {
+        let diag = DiagnosticMessageBuilder::warning(format!(
+            "Node dropped in LaTeX output: {}",
+            description
+        ))
+        .with_location(source_info.clone())
+        .build();
+        self.diagnostics.push(diag);
+    }
+}
+
+impl Default for LatexWriterContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Escaping helpers
+// ============================================================================
+
+/// Escape LaTeX special characters in text.
+pub fn escape_latex(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => result.push_str("\\textbackslash{}"),
+            '{' | '}' | '$' | '&' | '%' | '#' | '_' => {
+                result.push('\\');
+                result.push(ch);
+            }
+            '~' => result.push_str("\\textasciitilde{}"),
+            '^' => result.push_str("\\textasciicircum{}"),
+            '<' => result.push_str("\\textless{}"),
+            '>' => result.push_str("\\textgreater{}"),
+            '|' => result.push_str("\\textbar{}"),
+            '\u{a0}' => result.push('~'),
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+/// Escape a URL for `\href`/`\url`, where only `%`, `#` and `\` are special.
+fn escape_url(url: &str) -> String {
+    let mut result = String::with_capacity(url.len());
+    for ch in url.chars() {
+        match ch {
+            '%' | '#' | '\\' => {
+                result.push('\\');
+                result.push(ch);
+            }
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+/// Sanitize an identifier for use in `\label`/`\hyperref`.
+fn label(id: &str) -> String {
+    id.chars()
+        .filter(|c| !matches!(c, '\\' | '{' | '}' | '%' | '#' | '~' | '$'))
+        .collect()
+}
+
+// ============================================================================
+// Inline writing
+// ============================================================================
+
+fn write_inlines(
+    inlines: &[Inline],
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    for inline in inlines {
+        write_inline(inline, buf, ctx)?;
+    }
+    Ok(())
+}
+
+/// Render inlines to a string (for command arguments that need the text
+/// first).
+fn inlines_to_latex(inlines: &[Inline], ctx: &mut LatexWriterContext) -> io::Result<String> {
+    let mut scratch = Vec::new();
+    write_inlines(inlines, &mut scratch, ctx)?;
+    Ok(String::from_utf8_lossy(&scratch).into_owned())
+}
+
+fn write_command(
+    command: &str,
+    content: &[Inline],
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    write!(buf, "\\{}{{", command)?;
+    write_inlines(content, buf, ctx)?;
+    write!(buf, "}}")
+}
+
+/// The `\includegraphics` options for an image's `width`/`height`.
+fn graphics_options(image: &Image) -> String {
+    let options: Vec<String> = ["width", "height"]
+        .iter()
+        .filter_map(|key| {
+            let value = image.attr.2.get(*key)?;
+            let dimen = match value.strip_suffix('%') {
+                Some(pct) => {
+                    let pct: f64 = pct.trim().parse().ok()?;
+                    let base = if *key == "width" {
+                        "\\linewidth"
+                    } else {
+                        "\\textheight"
+                    };
+                    format!("{}{}", pct / 100.0, base)
+                }
+                None => value.clone(),
+            };
+            Some(format!("{}={}", key, dimen))
+        })
+        .collect();
+    if options.is_empty() {
+        String::new()
+    } else {
+        format!("[{}]", options.join(","))
+    }
+}
+
+fn write_image(image: &Image, buf: &mut dyn Write) -> io::Result<()> {
+    write!(
+        buf,
+        "\\includegraphics{}{{{}}}",
+        graphics_options(image),
+        image.target.0
+    )
+}
+
+fn write_inline(
+    inline: &Inline,
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    match inline {
+        Inline::Str(s) => write!(buf, "{}", escape_latex(&s.text))?,
+        Inline::Space(_) => write!(buf, " ")?,
+        Inline::SoftBreak(_) => writeln!(buf)?,
+        Inline::LineBreak(_) => writeln!(buf, "\\\\")?,
+
+        Inline::Emph(node) => write_command("emph", &node.content, buf, ctx)?,
+        Inline::Strong(node) => write_command("textbf", &node.content, buf, ctx)?,
+        Inline::Underline(node) => write_command("underline", &node.content, buf, ctx)?,
+        Inline::SmallCaps(node) => write_command("textsc", &node.content, buf, ctx)?,
+        Inline::Strikeout(node) => write_command("sout", &node.content, buf, ctx)?,
+        Inline::Delete(node) => write_command("sout", &node.content, buf, ctx)?,
+        Inline::Superscript(node) => write_command("textsuperscript", &node.content, buf, ctx)?,
+        Inline::Subscript(node) => write_command("textsubscript", &node.content, buf, ctx)?,
+
+        // Unwrap: keep content, drop markup without a standard command
+        Inline::Insert(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Highlight(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Span(span) => match note_reference_id(span) {
+            // The reader turns note references into empty spans
+            Some(id) => write_note_reference(id, &span.source_info, buf, ctx)?,
+            None => {
+                if !span.attr.0.is_empty() {
+                    write!(buf, "\\label{{{}}}", label(&span.attr.0))?;
+                }
+                write_inlines(&span.content, buf, ctx)?;
+            }
+        },
+
+        Inline::Quoted(quoted) => {
+            let (open, close) = match quoted.quote_type {
+                crate::pandoc::QuoteType::SingleQuote => ("`", "'"),
+                crate::pandoc::QuoteType::DoubleQuote => ("``", "''"),
+            };
+            write!(buf, "{}", open)?;
+            write_inlines(&quoted.content, buf, ctx)?;
+            write!(buf, "{}", close)?;
+        }
+
+        Inline::Code(code) => write!(buf, "\\texttt{{{}}}", escape_latex(&code.text))?,
+        Inline::Math(math) => match math.math_type {
+            crate::pandoc::MathType::InlineMath => write!(buf, "\\({}\\)", math.text)?,
+            crate::pandoc::MathType::DisplayMath => write!(buf, "\\[{}\\]", math.text)?,
+        },
+
+        Inline::Link(link) => {
+            let url = &link.target.0;
+            if let Some(anchor) = url.strip_prefix('#') {
+                write!(buf, "\\hyperref[{}]{{", label(anchor))?;
+                write_inlines(&link.content, buf, ctx)?;
+                write!(buf, "}}")?;
+            } else if crate::writers::plaintext::inlines_to_string(&link.content).0 == *url {
+                write!(buf, "\\url{{{}}}", escape_url(url))?;
+            } else {
+                write!(buf, "\\href{{{}}}{{", escape_url(url))?;
+                write_inlines(&link.content, buf, ctx)?;
+                write!(buf, "}}")?;
+            }
+        }
+        Inline::Image(image) => write_image(image, buf)?,
+
+        Inline::Cite(cite) => {
+            if !cite.content.is_empty() {
+                write_inlines(&cite.content, buf, ctx)?;
+            } else {
+                let ids: Vec<&str> = cite.citations.iter().map(|c| c.id.as_str()).collect();
+                write!(buf, "\\cite{{{}}}", ids.join(","))?;
+            }
+        }
+
+        Inline::Note(note) => write_footnote(&note.content, buf, ctx)?,
+        Inline::NoteReference(noteref) => {
+            write_note_reference(&noteref.id, &noteref.source_info, buf, ctx)?
+        }
+
+        Inline::RawInline(raw) => {
+            if is_native_format(&raw.format, LATEX_FORMATS) {
+                write!(buf, "{}", raw.text)?;
+            } else {
+                ctx.warn_dropped_node(
+                    &format!("RawInline with format '{}'", raw.format),
+                    &raw.source_info,
+                );
+            }
+        }
+
+        Inline::Attr(_, _) => {}
+        Inline::EditComment(comment) => {
+            ctx.warn_dropped_node("EditComment", &comment.source_info);
+        }
+        Inline::Shortcode(shortcode) => {
+            ctx.warn_dropped_node(
+                &format!("Shortcode '{}'", shortcode.name),
+                &shortcode.source_info,
+            );
+        }
+        Inline::Custom(custom) => {
+            ctx.warn_dropped_node(
+                &format!("Custom inline ({})", custom.type_name),
+                &custom.source_info,
+            );
+        }
+    }
+    Ok(())
+}
+
+fn write_footnote(
+    content: &[Block],
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    let body = blocks_to_latex(content, ctx)?;
+    write!(buf, "\\footnote{{{}}}", body.trim_end())
+}
+
+/// Write the footnote a note reference refers to, or drop the reference when
+/// there is no definition with its id.
+///
+/// A reference inside the note it refers to (directly or through other
+/// notes) is written as a bare `\footnotemark` instead.
+fn write_note_reference(
+    id: &str,
+    source_info: &SourceInfo,
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    match ctx.notes.enter(id) {
+        ResolvedNote::Content(content) => {
+            let result = write_footnote(&content, buf, ctx);
+            ctx.notes.leave();
+            result
+        }
+        ResolvedNote::Cycle => {
+            ctx.warn_dropped_node(
+                &format!("NoteReference inside note '{}' itself", id),
+                source_info,
+            );
+            write!(buf, "\\footnotemark{{}}")
+        }
+        ResolvedNote::Missing => {
+            ctx.warn_dropped_node(
+                &format!("NoteReference without definition '{}'", id),
+                source_info,
+            );
+            Ok(())
+        }
+    }
+}
+
+// ============================================================================
+// Block writing
+// ============================================================================
+
+/// Write blocks separated by blank lines.
+fn write_blocks(
+    blocks: &[Block],
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    let mut wrote_any = false;
+    for block in blocks {
+        let mut scratch = Vec::new();
+        write_block(block, &mut scratch, ctx)?;
+        if scratch.is_empty() {
+            continue;
+        }
+        if wrote_any {
+            writeln!(buf)?;
+        }
+        buf.write_all(&scratch)?;
+        wrote_any = true;
+    }
+    Ok(())
+}
+
+fn blocks_to_latex(blocks: &[Block], ctx: &mut LatexWriterContext) -> io::Result<String> {
+    let mut scratch = Vec::new();
+    write_blocks(blocks, &mut scratch, ctx)?;
+    Ok(String::from_utf8_lossy(&scratch).into_owned())
+}
+
+fn write_header(
+    header: &Header,
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    let level = header.level.clamp(1, SECTION_COMMANDS.len());
+    let star = if header.attr.1.iter().any(|c| c == "unnumbered") {
+        "*"
+    } else {
+        ""
+    };
+    let title = inlines_to_latex(&header.content, ctx)?.replace('\n', " ");
+    write!(
+        buf,
+        "\\{}{}{{{}}}",
+        SECTION_COMMANDS[level - 1],
+        star,
+        title
+    )?;
+    if !header.attr.0.is_empty() {
+        write!(buf, "\\label{{{}}}", label(&header.attr.0))?;
+    }
+    writeln!(buf)
+}
+
+/// Escape code for the `Highlighting` environment, where only `\`, `{` and
+/// `}` are special.
+fn escape_highlighted(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => result.push_str("\\textbackslash{}"),
+            '{' | '}' => {
+                result.push('\\');
+                result.push(ch);
+            }
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+fn write_codeblock(
+    codeblock: &CodeBlock,
+    buf: &mut dyn Write,
+    ctx: &LatexWriterContext,
+) -> io::Result<()> {
+    let language = language_for_classes(&codeblock.attr.1).filter(|_| ctx.config.highlight);
+    if let Some(language) = language {
+        return write_highlighted_codeblock(codeblock, language, buf);
+    }
+    writeln!(buf, "\\begin{{verbatim}}")?;
+    write!(buf, "{}", codeblock.text)?;
+    if !codeblock.text.is_empty() && !codeblock.text.ends_with('\n') {
+        writeln!(buf)?;
+    }
+    writeln!(buf, "\\end{{verbatim}}")
+}
+
+/// Write a syntax-highlighted code block as Pandoc does: a `Highlighting`
+/// (fancyvrb `Verbatim`) environment inside `Shaded`, with each token
+/// wrapped in the macro for its type, e.g. `\KeywordTok{def}`.
+fn write_highlighted_codeblock(
+    codeblock: &CodeBlock,
+    language: &Language,
+    buf: &mut dyn Write,
+) -> io::Result<()> {
+    writeln!(buf, "\\begin{{Shaded}}")?;
+    writeln!(buf, "\\begin{{Highlighting}}[]")?;
+    for line in language.tokenize(&codeblock.text) {
+        for token in line {
+            write!(
+                buf,
+                "\\{}Tok{{{}}}",
+                token.token_type.name(),
+                escape_highlighted(&token.text)
+            )?;
+        }
+        writeln!(buf)?;
+    }
+    writeln!(buf, "\\end{{Highlighting}}")?;
+    writeln!(buf, "\\end{{Shaded}}")
+}
+
+fn write_rawblock(
+    rawblock: &RawBlock,
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    if !is_native_format(&rawblock.format, LATEX_FORMATS) {
+        ctx.warn_dropped_node(
+            &format!("RawBlock with format '{}'", rawblock.format),
+            &rawblock.source_info,
+        );
+        return Ok(());
+    }
+    write!(buf, "{}", rawblock.text)?;
+    if !rawblock.text.ends_with('\n') {
+        writeln!(buf)?;
+    }
+    Ok(())
+}
+
+/// A list is tight if every item is a single Plain block.
+fn is_tight_list(items: &[Blocks]) -> bool {
+    items
+        .iter()
+        .all(|item| item.len() == 1 && matches!(item[0], Block::Plain(_)))
+}
+
+fn write_list_items(
+    items: &[Blocks],
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    if is_tight_list(items) {
+        writeln!(buf, "\\tightlist")?;
+    }
+    for item in items {
+        let body = blocks_to_latex(item, ctx)?;
+        writeln!(buf, "\\item")?;
+        if !body.is_empty() {
+            write!(buf, "  {}", body.trim_end().replace('\n', "\n  "))?;
+            writeln!(buf)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_bulletlist(
+    bulletlist: &BulletList,
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    writeln!(buf, "\\begin{{itemize}}")?;
+    write_list_items(&bulletlist.content, buf, ctx)?;
+    writeln!(buf, "\\end{{itemize}}")
+}
+
+fn write_orderedlist(
+    orderedlist: &OrderedList,
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    let (start, style, _delim) = &orderedlist.attr;
+    let counter = ENUM_COUNTERS.get(ctx.enum_depth).copied();
+    writeln!(buf, "\\begin{{enumerate}}")?;
+    if let Some(counter) = counter {
+        let numbering = match style {
+            ListNumberStyle::LowerRoman => Some("roman"),
+            ListNumberStyle::UpperRoman => Some("Roman"),
+            ListNumberStyle::LowerAlpha => Some("alph"),
+            ListNumberStyle::UpperAlpha => Some("Alph"),
+            _ => None,
+        };
+        if let Some(numbering) = numbering {
+            writeln!(
+                buf,
+                "\\def\\label{}{{\\{}{{{}}}.}}",
+                counter, numbering, counter
+            )?;
+        }
+        if *start > 1 {
+            writeln!(buf, "\\setcounter{{{}}}{{{}}}", counter, start - 1)?;
+        }
+    }
+    ctx.enum_depth += 1;
+    let result = write_list_items(&orderedlist.content, buf, ctx);
+    ctx.enum_depth -= 1;
+    result?;
+    writeln!(buf, "\\end{{enumerate}}")
+}
+
+fn write_definitionlist(
+    deflist: &DefinitionList,
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    writeln!(buf, "\\begin{{description}}")?;
+    for (term, definitions) in &deflist.content {
+        let term = inlines_to_latex(term, ctx)?.replace('\n', " ");
+        writeln!(buf, "\\item[{}]", term)?;
+        for definition in definitions {
+            let body = blocks_to_latex(definition, ctx)?;
+            if !body.is_empty() {
+                write!(buf, "  {}", body.trim_end().replace('\n', "\n  "))?;
+                writeln!(buf)?;
+            }
+        }
+    }
+    writeln!(buf, "\\end{{description}}")
+}
+
+fn write_div(div: &Div, buf: &mut dyn Write, ctx: &mut LatexWriterContext) -> io::Result<()> {
+    if !div.attr.0.is_empty() {
+        writeln!(
+            buf,
+            "\\protect\\phantomsection\\label{{{}}}",
+            label(&div.attr.0)
+        )?;
+    }
+    write_blocks(&div.content, buf, ctx)
+}
+
+fn write_figure(
+    figure: &Figure,
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    writeln!(buf, "\\begin{{figure}}")?;
+    writeln!(buf, "\\centering")?;
+    let content = blocks_to_latex(&figure.content, ctx)?;
+    write!(buf, "{}", content)?;
+    if let Some(long_caption) = &figure.caption.long
+        && !long_caption.is_empty()
+    {
+        let caption = blocks_to_latex(long_caption, ctx)?;
+        writeln!(buf, "\\caption{{{}}}", caption.trim_end())?;
+    }
+    if !figure.attr.0.is_empty() {
+        writeln!(buf, "\\label{{{}}}", label(&figure.attr.0))?;
+    }
+    writeln!(buf, "\\end{{figure}}")
+}
+
+/// Render a table cell on a single line.
+fn cell_to_latex(cell: &Cell, ctx: &mut LatexWriterContext) -> io::Result<String> {
+    let body = blocks_to_latex(&cell.content, ctx)?;
+    Ok(body
+        .trim_end()
+        .split("\n\n")
+        .map(|para| para.replace('\n', " "))
+        .collect::<Vec<_>>()
+        .join(" \\newline "))
+}
+
+/// Write table rows, one line each, padded to the table's column count.
+fn write_rows(
+    table: &Table,
+    rows: &[Row],
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    let num_cols = table.colspec.len();
+    for row in rows {
+        let mut cells = Vec::with_capacity(num_cols);
+        for cell in row.cells.iter().take(num_cols) {
+            if cell.row_span > 1 || cell.col_span > 1 {
+                ctx.warn_dropped_node("table cell span", &table.source_info);
+            }
+            cells.push(cell_to_latex(cell, ctx)?);
+        }
+        cells.resize(num_cols, String::new());
+        writeln!(buf, "{} \\\\", cells.join(" & "))?;
+    }
+    Ok(())
+}
+
+fn write_table(table: &Table, buf: &mut dyn Write, ctx: &mut LatexWriterContext) -> io::Result<()> {
+    let num_cols = table.colspec.len();
+    if num_cols == 0 {
+        return Ok(());
+    }
+
+    let columns: String = table
+        .colspec
+        .iter()
+        .map(|(alignment, width)| {
+            let align = match alignment {
+                Alignment::Center => "centering",
+                Alignment::Right => "raggedleft",
+                _ => "raggedright",
+            };
+            match width {
+                ColWidth::Percentage(pct) => {
+                    format!(">{{\\{}\\arraybackslash}}p{{{:.2}\\linewidth}}", align, pct)
+                }
+                ColWidth::Default => match alignment {
+                    Alignment::Center => "c".to_string(),
+                    Alignment::Right => "r".to_string(),
+                    _ => "l".to_string(),
+                },
+            }
+        })
+        .collect();
+    writeln!(buf, "\\begin{{longtable}}[]{{@{{}}{}@{{}}}}", columns)?;
+
+    if let Some(long_caption) = &table.caption.long
+        && !long_caption.is_empty()
+    {
+        let caption = blocks_to_latex(long_caption, ctx)?;
+        write!(
+            buf,
+            "\\caption{{{}}}",
+            caption.trim_end().replace('\n', " ")
+        )?;
+        if !table.attr.0.is_empty() {
+            write!(buf, "\\label{{{}}}", label(&table.attr.0))?;
+        }
+        writeln!(buf, "\\tabularnewline")?;
+    }
+
+    writeln!(buf, "\\toprule\\noalign{{}}")?;
+    if !table.head.rows.is_empty() {
+        write_rows(table, &table.head.rows, buf, ctx)?;
+        writeln!(buf, "\\midrule\\noalign{{}}")?;
+    }
+    writeln!(buf, "\\endhead")?;
+    for body in &table.bodies {
+        write_rows(table, &body.head, buf, ctx)?;
+        write_rows(table, &body.body, buf, ctx)?;
+    }
+    if !table.foot.rows.is_empty() {
+        writeln!(buf, "\\midrule\\noalign{{}}")?;
+        write_rows(table, &table.foot.rows, buf, ctx)?;
+    }
+    writeln!(buf, "\\bottomrule\\noalign{{}}")?;
+    writeln!(buf, "\\end{{longtable}}")
+}
+
+fn write_block(block: &Block, buf: &mut dyn Write, ctx: &mut LatexWriterContext) -> io::Result<()> {
+    match block {
+        Block::Plain(plain) => {
+            write_inlines(&plain.content, buf, ctx)?;
+            writeln!(buf)?;
+        }
+        Block::Paragraph(para) => {
+            write_inlines(&para.content, buf, ctx)?;
+            writeln!(buf)?;
+        }
+        Block::Header(header) => write_header(header, buf, ctx)?,
+        Block::CodeBlock(codeblock) => write_codeblock(codeblock, buf, ctx)?,
+        Block::RawBlock(rawblock) => write_rawblock(rawblock, buf, ctx)?,
+        Block::BlockQuote(blockquote) => {
+            writeln!(buf, "\\begin{{quote}}")?;
+            write_blocks(&blockquote.content, buf, ctx)?;
+            writeln!(buf, "\\end{{quote}}")?;
+        }
+        Block::LineBlock(lineblock) => {
+            let lines = lineblock
+                .content
+                .iter()
+                .map(|line| inlines_to_latex(line, ctx))
+                .collect::<io::Result<Vec<_>>>()?;
+            writeln!(buf, "{}", lines.join("\\\\\n"))?;
+        }
+        Block::BulletList(bulletlist) => write_bulletlist(bulletlist, buf, ctx)?,
+        Block::OrderedList(orderedlist) => write_orderedlist(orderedlist, buf, ctx)?,
+        Block::DefinitionList(deflist) => write_definitionlist(deflist, buf, ctx)?,
+        Block::HorizontalRule(_) => writeln!(
+            buf,
+            "\\begin{{center}}\\rule{{0.5\\linewidth}}{{0.5pt}}\\end{{center}}"
+        )?,
+        Block::Table(table) => write_table(table, buf, ctx)?,
+        Block::Figure(figure) => write_figure(figure, buf, ctx)?,
+        Block::Div(div) => write_div(div, buf, ctx)?,
+        // Written as footnotes at their references
+        Block::NoteDefinitionPara(_) | Block::NoteDefinitionFencedBlock(_) => {}
+        Block::BlockMetadata(meta) => {
+            ctx.warn_dropped_node("BlockMetadata", &meta.source_info);
+        }
+        Block::CaptionBlock(caption) => {
+            ctx.warn_dropped_node("CaptionBlock", &caption.source_info);
+        }
+        Block::Custom(custom) => {
+            ctx.warn_dropped_node(
+                &format!("Custom block ({})", custom.type_name),
+                &custom.source_info,
+            );
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Entry points
+// ============================================================================
+
+fn write_impl(
+    pandoc: &Pandoc,
+    buf: &mut dyn Write,
+    ctx: &mut LatexWriterContext,
+) -> io::Result<()> {
+    ctx.notes = NoteDefinitions::collect(&pandoc.blocks);
+    write_blocks(&pandoc.blocks, buf, ctx)
+}
+
+/// Write the body of a Pandoc document as LaTeX.
+///
+/// Returns the warnings for nodes that could not be represented in LaTeX.
+/// IO errors are returned as `Err`.
+pub fn write<T: Write>(
+    pandoc: &Pandoc,
+    buf: &mut T,
+) -> Result<Vec<DiagnosticMessage>, Vec<DiagnosticMessage>> {
+    write_with_config(pandoc, buf, LatexConfig::default())
+}
+
+/// Write the body of a Pandoc document as LaTeX with the given configuration.
+pub fn write_with_config<T: Write>(
+    pandoc: &Pandoc,
+    buf: &mut T,
+    config: LatexConfig,
+) -> Result<Vec<DiagnosticMessage>, Vec<DiagnosticMessage>> {
+    let mut ctx = LatexWriterContext::with_config(config);
+    if let Err(e) = write_impl(pandoc, buf, &mut ctx) {
+        return Err(vec![
+            DiagnosticMessageBuilder::error("IO error during write")
+                .with_code("Q-3-1")
+                .problem(format!("Failed to write LaTeX output: {}", e))
+                .build(),
+        ]);
+    }
+    Ok(ctx.into_diagnostics())
+}
+
+/// Render inlines (e.g. a metadata title) as LaTeX.
+pub fn inlines_to_string(inlines: &Inlines) -> (String, Vec<DiagnosticMessage>) {
+    let mut ctx = LatexWriterContext::new();
+    let text = inlines_to_latex(inlines, &mut ctx).unwrap_or_default();
+    (text, ctx.into_diagnostics())
+}
+
+/// Render blocks (e.g. a metadata abstract) as LaTeX.
+pub fn blocks_to_string(blocks: &[Block]) -> (String, Vec<DiagnosticMessage>) {
+    let mut ctx = LatexWriterContext::new();
+    ctx.notes = NoteDefinitions::collect(blocks);
+    let text = blocks_to_latex(blocks, &mut ctx).unwrap_or_default();
+    (text, ctx.into_diagnostics())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qmd_to_latex(input: &str) -> (String, Vec<DiagnosticMessage>) {
+        let (pandoc, _context, _warnings) = crate::readers::qmd::read(
+            input.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .expect("Failed to parse QMD");
+        let mut buf = Vec::new();
+        let diagnostics = write(&pandoc, &mut buf).expect("Failed to write LaTeX");
+        (String::from_utf8(buf).unwrap(), diagnostics)
+    }
+
+    #[test]
+    fn test_escape_latex() {
+        assert_eq!(escape_latex("50% of $x_1"), "50\\% of \\$x\\_1");
+        assert_eq!(
+            escape_latex("a\\b~c"),
+            "a\\textbackslash{}b\\textasciitilde{}c"
+        );
+    }
+
+    #[test]
+    fn test_headers_and_labels() {
+        let (out, _) = qmd_to_latex("# Intro {#sec-intro}\n\n## More {.unnumbered}\n");
+        assert_eq!(
+            out,
+            "\\section{Intro}\\label{sec-intro}\n\n\\subsection*{More}\\label{more}\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_markup() {
+        let (out, _) = qmd_to_latex("*a* **b** `c_d` $x^2$\n");
+        assert_eq!(out, "\\emph{a} \\textbf{b} \\texttt{c\\_d} \\(x^2\\)\n");
+    }
+
+    #[test]
+    fn test_links() {
+        let (out, _) = qmd_to_latex("[Quarto](https://quarto.org/#x) [see](#sec-a)\n");
+        assert_eq!(
+            out,
+            "\\href{https://quarto.org/\\#x}{Quarto} \\hyperref[sec-a]{see}\n"
+        );
+    }
+
+    #[test]
+    fn test_code_block() {
+        let (out, _) = qmd_to_latex("```\nx = {1}\n```\n");
+        assert_eq!(out, "\\begin{verbatim}\nx = {1}\n\\end{verbatim}\n");
+    }
+
+    #[test]
+    fn test_highlighted_code_block() {
+        let (out, _) = qmd_to_latex("```python\nif x:\n    y = {\"a\\\\b\": 1}\n```\n");
+        assert_eq!(
+            out,
+            "\\begin{Shaded}\n\\begin{Highlighting}[]\n\
+             \\ControlFlowTok{if}\\NormalTok{ x}\\OperatorTok{:}\n\
+             \\NormalTok{    y }\\OperatorTok{=}\\NormalTok{ \\{}\\StringTok{\"a\\textbackslash{}\\textbackslash{}b\"}\\OperatorTok{:}\\NormalTok{ }\\DecValTok{1}\\NormalTok{\\}}\n\
+             \\end{Highlighting}\n\\end{Shaded}\n"
+        );
+    }
+
+    #[test]
+    fn test_code_block_not_highlighted() {
+        let (pandoc, _context, _warnings) = crate::readers::qmd::read(
+            "```python\nx = 1\n```\n".as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .expect("Failed to parse QMD");
+        let mut buf = Vec::new();
+        write_with_config(&pandoc, &mut buf, LatexConfig { highlight: false }).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "\\begin{verbatim}\nx = 1\n\\end{verbatim}\n"
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        let (out, _) = qmd_to_latex("* a\n* b\n");
+        assert_eq!(
+            out,
+            "\\begin{itemize}\n\\tightlist\n\\item\n  a\n\\item\n  b\n\\end{itemize}\n"
+        );
+        let (out, _) = qmd_to_latex("3. a\n4. b\n");
+        assert!(out.contains("\\setcounter{enumi}{2}\n"));
+    }
+
+    #[test]
+    fn test_table() {
+        let (out, _) = qmd_to_latex("| a | b |\n|---|--:|\n| 1 | 2 |\n");
+        assert!(out.starts_with("\\begin{longtable}[]{@{}lr@{}}\n"));
+        assert!(out.contains("a & b \\\\\n\\midrule"));
+        assert!(out.contains("1 & 2 \\\\\n\\bottomrule"));
+    }
+
+    #[test]
+    fn test_raw_blocks() {
+        let (out, diagnostics) =
+            qmd_to_latex("```{=latex}\n\\newpage\n```\n\n```{=html}\n<br>\n```\n");
+        assert_eq!(out, "\\newpage\n");
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let (out, _) = qmd_to_latex("Text^[A note.] and[^1].\n\n[^1]: Another.\n");
+        assert_eq!(out, "Text\\footnote{A note.} and\\footnote{Another.}.\n");
+    }
+
+    #[test]
+    fn test_self_referencing_notes() {
+        let (out, diagnostics) = qmd_to_latex("A[^a].\n\n[^a]: See[^b].\n\n[^b]: Back[^a].\n");
+        assert_eq!(out, "A\\footnote{See\\footnote{Back\\footnotemark{}.}.}.\n");
+        assert_eq!(diagnostics.len(), 1);
+    }
+}
@@ -0,0 +1,72 @@
+/*
+ * line_prefix.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Line-prefixing writer shared by the text-based markup writers.
+//!
+//! Block containers in line-oriented formats (block quotes, list items,
+//! directive bodies, footnote definitions) are written by rendering their
+//! children through a writer that prepends an indentation or marker to each
+//! line.
+
+use std::io::{self, Write};
+
+/// A writer that prefixes every line written through it.
+///
+/// The first line gets `first`, subsequent lines get `rest`. Blank lines get
+/// the prefix with trailing whitespace removed, so block quotes and list
+/// items never produce lines ending in spaces.
+pub(crate) struct LinePrefixWriter<'a, W: Write + ?Sized> {
+    inner: &'a mut W,
+    first: String,
+    rest: String,
+    at_line_start: bool,
+    is_first_line: bool,
+}
+
+impl<'a, W: Write + ?Sized> LinePrefixWriter<'a, W> {
+    pub(crate) fn new(inner: &'a mut W, first: impl Into<String>, rest: impl Into<String>) -> Self {
+        Self {
+            inner,
+            first: first.into(),
+            rest: rest.into(),
+            at_line_start: true,
+            is_first_line: true,
+        }
+    }
+
+    fn current_prefix(&self) -> &str {
+        if self.is_first_line {
+            &self.first
+        } else {
+            &self.rest
+        }
+    }
+}
+
+impl<'a, W: Write + ?Sized> Write for LinePrefixWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            if self.at_line_start {
+                let prefix = if byte == b'\n' {
+                    self.current_prefix().trim_end().to_string()
+                } else {
+                    self.current_prefix().to_string()
+                };
+                self.inner.write_all(prefix.as_bytes())?;
+                self.at_line_start = false;
+                self.is_first_line = false;
+            }
+            self.inner.write_all(&[byte])?;
+            if byte == b'\n' {
+                self.at_line_start = true;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,744 @@
+/*
+ * mathml.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! TeX math to MathML conversion for the HTML writer's `mathml` math method.
+//!
+//! This covers the commonly used subset of TeX math: scripts, fractions,
+//! roots, accents, Greek letters and symbols, named functions, `\text` and
+//! font commands, `\left`/`\right` delimiters and matrix-like environments.
+//! Unsupported commands are emitted as `<merror>` so they stay visible.
+
+const MATHML_NS: &str = "http://www.w3.org/1998/Math/MathML";
+
+/// Convert TeX math to a complete `<math>` element.
+///
+/// Like Pandoc, the original TeX is kept as an `application/x-tex` annotation.
+pub(crate) fn tex_to_mathml(tex: &str, display: bool) -> String {
+    let mut parser = Parser::new(tex);
+    let mut nodes = Vec::new();
+    loop {
+        nodes.extend(parser.parse_row());
+        if parser.at_end() {
+            break;
+        }
+        // Unbalanced `}`, `&`, `\\`, `\right` or `\end` at the top level: skip it.
+        if parser.starts_with("\\\\") {
+            parser.pos += 2;
+        } else if parser.starts_with_command("right") {
+            parser.pos += "\\right".len();
+            parser.parse_delimiter();
+        } else if parser.starts_with_command("end") {
+            parser.pos += "\\end".len();
+            parser.parse_text_argument();
+        } else {
+            parser.pos += 1;
+        }
+    }
+    format!(
+        "<math display=\"{}\" xmlns=\"{}\"><semantics><mrow>{}</mrow>\
+         <annotation encoding=\"application/x-tex\">{}</annotation></semantics></math>",
+        if display { "block" } else { "inline" },
+        MATHML_NS,
+        nodes.concat(),
+        escape(tex)
+    )
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn mi(s: &str) -> String {
+    format!("<mi>{}</mi>", escape(s))
+}
+
+fn mo(s: &str) -> String {
+    format!("<mo>{}</mo>", escape(s))
+}
+
+fn mrow(nodes: Vec<String>) -> String {
+    if nodes.len() == 1 {
+        nodes.into_iter().next().unwrap_or_default()
+    } else {
+        format!("<mrow>{}</mrow>", nodes.concat())
+    }
+}
+
+fn greek(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "varrho" => "ϱ",
+        "sigma" => "σ",
+        "varsigma" => "ς",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        _ => return None,
+    })
+}
+
+/// Commands rendered as identifiers.
+fn identifier_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "ell" => "ℓ",
+        "hbar" => "ℏ",
+        "emptyset" => "∅",
+        "aleph" => "ℵ",
+        _ => return None,
+    })
+}
+
+/// Commands rendered as operators.
+fn operator_symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "times" => "×",
+        "cdot" => "⋅",
+        "div" => "÷",
+        "pm" => "±",
+        "mp" => "∓",
+        "ast" => "∗",
+        "star" => "⋆",
+        "circ" => "∘",
+        "bullet" => "∙",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "ll" => "≪",
+        "gg" => "≫",
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" | "implies" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "mapsto" => "↦",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "land" | "wedge" => "∧",
+        "lor" | "vee" => "∨",
+        "lnot" | "neg" => "¬",
+        "forall" => "∀",
+        "exists" => "∃",
+        "mid" => "∣",
+        "parallel" => "∥",
+        "perp" => "⊥",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "iiint" => "∭",
+        "oint" => "∮",
+        "bigcup" => "⋃",
+        "bigcap" => "⋂",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "lbrace" => "{",
+        "rbrace" => "}",
+        "vert" => "|",
+        "Vert" => "‖",
+        "colon" => ":",
+        _ => return None,
+    })
+}
+
+/// Named functions, rendered upright.
+const FUNCTIONS: &[&str] = &[
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "coth", "log", "ln", "lg", "exp", "lim", "liminf", "limsup", "max", "min", "sup", "inf", "arg",
+    "det", "dim", "gcd", "deg", "hom", "ker", "Pr",
+];
+
+fn accent(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "hat" | "widehat" => "^",
+        "bar" | "overline" => "¯",
+        "vec" => "→",
+        "dot" => "˙",
+        "ddot" => "¨",
+        "tilde" | "widetilde" => "~",
+        "check" => "ˇ",
+        "breve" => "˘",
+        "acute" => "´",
+        "grave" => "`",
+        _ => return None,
+    })
+}
+
+fn font_variant(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "mathrm" | "operatorname" => "normal",
+        "mathbf" | "boldsymbol" => "bold",
+        "mathit" => "italic",
+        "mathbb" => "double-struck",
+        "mathcal" => "script",
+        "mathfrak" => "fraktur",
+        "mathsf" => "sans-serif",
+        "mathtt" => "monospace",
+        _ => return None,
+    })
+}
+
+fn space_width(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "," => "0.167em",
+        ":" | ">" => "0.222em",
+        ";" => "0.278em",
+        " " => "0.333em",
+        "quad" => "1em",
+        "qquad" => "2em",
+        _ => return None,
+    })
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(tex: &str) -> Self {
+        Self {
+            chars: tex.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(offset, c)| self.chars.get(self.pos + offset) == Some(&c))
+    }
+
+    /// Whether the input continues with the command `\name` (and not a longer
+    /// command that merely starts with `name`).
+    fn starts_with_command(&self, name: &str) -> bool {
+        self.starts_with("\\")
+            && self.chars[self.pos + 1..].starts_with(&name.chars().collect::<Vec<_>>())
+            && !self
+                .chars
+                .get(self.pos + 1 + name.chars().count())
+                .is_some_and(|c| c.is_ascii_alphabetic())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Whether the current row ends here.
+    fn at_row_end(&self) -> bool {
+        self.at_end()
+            || self.peek() == Some('}')
+            || self.peek() == Some('&')
+            || self.starts_with("\\\\")
+            || self.starts_with_command("end")
+            || self.starts_with_command("right")
+    }
+
+    /// Parse a sequence of atoms (with their scripts) up to the end of the row.
+    fn parse_row(&mut self) -> Vec<String> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.at_row_end() {
+                break;
+            }
+            let Some(base) = self.parse_atom() else {
+                continue;
+            };
+            nodes.push(self.parse_scripts(base));
+        }
+        nodes
+    }
+
+    /// Attach any `_`, `^` and `'` scripts following `base`.
+    fn parse_scripts(&mut self, base: String) -> String {
+        let mut sub = None;
+        let mut sup = None;
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some('_') if sub.is_none() => {
+                    self.pos += 1;
+                    sub = Some(self.parse_argument());
+                }
+                Some('^') if sup.is_none() => {
+                    self.pos += 1;
+                    sup = Some(self.parse_argument());
+                }
+                Some('\'') if sup.is_none() => {
+                    let mut primes = String::new();
+                    while self.peek() == Some('\'') {
+                        self.pos += 1;
+                        primes.push('′');
+                    }
+                    sup = Some(mo(&primes));
+                }
+                _ => break,
+            }
+        }
+        match (sub, sup) {
+            (None, None) => base,
+            (Some(sub), None) => format!("<msub>{}{}</msub>", base, sub),
+            (None, Some(sup)) => format!("<msup>{}{}</msup>", base, sup),
+            (Some(sub), Some(sup)) => format!("<msubsup>{}{}{}</msubsup>", base, sub, sup),
+        }
+    }
+
+    /// Parse a command or script argument: a braced group or a single atom.
+    fn parse_argument(&mut self) -> String {
+        self.skip_whitespace();
+        if self.at_row_end() {
+            return "<mrow></mrow>".to_string();
+        }
+        self.parse_atom()
+            .unwrap_or_else(|| "<mrow></mrow>".to_string())
+    }
+
+    /// Parse the raw text of a braced argument (for `\text` and environment names).
+    fn parse_text_argument(&mut self) -> String {
+        self.skip_whitespace();
+        if self.peek() != Some('{') {
+            return self
+                .peek()
+                .map(|c| {
+                    self.pos += 1;
+                    c.to_string()
+                })
+                .unwrap_or_default();
+        }
+        self.pos += 1;
+        let mut depth = 0;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+        text
+    }
+
+    /// Parse a single atom. Returns `None` for input that produces no output.
+    fn parse_atom(&mut self) -> Option<String> {
+        let c = self.peek()?;
+        if c == '{' {
+            self.pos += 1;
+            let nodes = self.parse_row();
+            if self.peek() == Some('}') {
+                self.pos += 1;
+            }
+            return Some(mrow(nodes));
+        }
+        if c == '\\' {
+            return self.parse_command();
+        }
+        self.pos += 1;
+        if c.is_ascii_digit() || (c == '.' && self.peek().is_some_and(|n| n.is_ascii_digit())) {
+            let mut number = c.to_string();
+            while let Some(n) = self.peek() {
+                let continues_number = n.is_ascii_digit()
+                    || (n == '.'
+                        && self
+                            .chars
+                            .get(self.pos + 1)
+                            .is_some_and(char::is_ascii_digit));
+                if !continues_number {
+                    break;
+                }
+                number.push(n);
+                self.pos += 1;
+            }
+            return Some(format!("<mn>{}</mn>", number));
+        }
+        if c.is_alphabetic() {
+            return Some(mi(&c.to_string()));
+        }
+        Some(match c {
+            '-' => mo("−"),
+            '~' => "<mspace width=\"0.333em\"></mspace>".to_string(),
+            _ => mo(&c.to_string()),
+        })
+    }
+
+    fn parse_command(&mut self) -> Option<String> {
+        // Skip the backslash
+        self.pos += 1;
+        let name: String = match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                    self.pos += 1;
+                }
+                self.chars[start..self.pos].iter().collect()
+            }
+            Some(c) => {
+                self.pos += 1;
+                c.to_string()
+            }
+            None => return Some(mo("\\")),
+        };
+
+        if let Some(width) = space_width(&name) {
+            return Some(format!("<mspace width=\"{}\"></mspace>", width));
+        }
+        if let Some(letter) = greek(&name) {
+            return Some(mi(letter));
+        }
+        if let Some(symbol) = identifier_symbol(&name) {
+            return Some(mi(symbol));
+        }
+        if let Some(symbol) = operator_symbol(&name) {
+            return Some(mo(symbol));
+        }
+        if FUNCTIONS.contains(&name.as_str()) {
+            return Some(format!("<mi mathvariant=\"normal\">{}</mi>", name));
+        }
+        if let Some(mark) = accent(&name) {
+            let base = self.parse_argument();
+            return Some(format!(
+                "<mover accent=\"true\">{}{}</mover>",
+                base,
+                mo(mark)
+            ));
+        }
+        if let Some(variant) = font_variant(&name) {
+            let argument = self.parse_argument();
+            return Some(format!(
+                "<mstyle mathvariant=\"{}\">{}</mstyle>",
+                variant, argument
+            ));
+        }
+
+        Some(match name.as_str() {
+            "!" => return None,
+            "{" | "}" | "|" | "#" | "%" | "&" | "$" | "_" => mo(&name),
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let numerator = self.parse_argument();
+                let denominator = self.parse_argument();
+                format!("<mfrac>{}{}</mfrac>", numerator, denominator)
+            }
+            "binom" => {
+                let top = self.parse_argument();
+                let bottom = self.parse_argument();
+                format!(
+                    "<mrow><mo>(</mo><mfrac linethickness=\"0\">{}{}</mfrac><mo>)</mo></mrow>",
+                    top, bottom
+                )
+            }
+            "sqrt" => {
+                self.skip_whitespace();
+                if self.peek() == Some('[') {
+                    self.pos += 1;
+                    let mut index = Vec::new();
+                    while !self.at_end() && self.peek() != Some(']') {
+                        self.skip_whitespace();
+                        if self.peek() == Some(']') {
+                            break;
+                        }
+                        if let Some(atom) = self.parse_atom() {
+                            index.push(atom);
+                        }
+                    }
+                    self.pos += 1;
+                    let radicand = self.parse_argument();
+                    format!("<mroot>{}{}</mroot>", radicand, mrow(index))
+                } else {
+                    format!("<msqrt>{}</msqrt>", self.parse_argument())
+                }
+            }
+            "underline" => {
+                let base = self.parse_argument();
+                format!("<munder accentunder=\"true\">{}{}</munder>", base, mo("_"))
+            }
+            "text" | "textrm" | "mbox" => {
+                format!("<mtext>{}</mtext>", escape(&self.parse_text_argument()))
+            }
+            "left" => self.parse_fenced(),
+            "begin" => self.parse_environment(),
+            _ => format!("<merror><mtext>\\{}</mtext></merror>", escape(&name)),
+        })
+    }
+
+    /// Parse the delimiter following `\left` or `\right`.
+    fn parse_delimiter(&mut self) -> String {
+        self.skip_whitespace();
+        let delimiter = match self.peek() {
+            Some('\\') => {
+                self.pos += 1;
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                    self.pos += 1;
+                }
+                if self.pos == start && !self.at_end() {
+                    self.pos += 1;
+                }
+                let name: String = self.chars[start..self.pos].iter().collect();
+                operator_symbol(&name).map(str::to_string).unwrap_or(name)
+            }
+            Some(c) => {
+                self.pos += 1;
+                c.to_string()
+            }
+            None => String::new(),
+        };
+        if delimiter == "." {
+            String::new()
+        } else {
+            delimiter
+        }
+    }
+
+    /// Parse `\left<delim> ... \right<delim>`; the `\left` is already consumed.
+    fn parse_fenced(&mut self) -> String {
+        let open = self.parse_delimiter();
+        let body = self.parse_row();
+        let close = if self.starts_with_command("right") {
+            self.pos += "\\right".len();
+            self.parse_delimiter()
+        } else {
+            String::new()
+        };
+        fenced(&open, &close, &body.concat())
+    }
+
+    /// Parse `\begin{env} ... \end{env}`; the `\begin` is already consumed.
+    fn parse_environment(&mut self) -> String {
+        let env = self.parse_text_argument();
+        let mut rows = Vec::new();
+        loop {
+            let mut cells = Vec::new();
+            loop {
+                cells.push(format!("<mtd>{}</mtd>", mrow(self.parse_row())));
+                if self.peek() == Some('&') {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+            rows.push(format!("<mtr>{}</mtr>", cells.concat()));
+            if self.starts_with("\\\\") {
+                self.pos += 2;
+            } else {
+                break;
+            }
+        }
+        if self.starts_with_command("end") {
+            self.pos += "\\end".len();
+            self.parse_text_argument();
+        } else if self.peek() == Some('}') {
+            // Stray `}` inside the environment; consume it to make progress.
+            self.pos += 1;
+        }
+
+        let table = format!("<mtable>{}</mtable>", rows.concat());
+        let (open, close) = match env.trim_end_matches('*') {
+            "pmatrix" => ("(", ")"),
+            "bmatrix" => ("[", "]"),
+            "Bmatrix" => ("{", "}"),
+            "vmatrix" => ("|", "|"),
+            "Vmatrix" => ("‖", "‖"),
+            "cases" => ("{", ""),
+            _ => return table,
+        };
+        fenced(open, close, &table)
+    }
+}
+
+fn fenced(open: &str, close: &str, body: &str) -> String {
+    let mut out = String::from("<mrow>");
+    if !open.is_empty() {
+        out.push_str(&format!(
+            "<mo stretchy=\"true\" form=\"prefix\">{}</mo>",
+            escape(open)
+        ));
+    }
+    out.push_str(body);
+    if !close.is_empty() {
+        out.push_str(&format!(
+            "<mo stretchy=\"true\" form=\"postfix\">{}</mo>",
+            escape(close)
+        ));
+    }
+    out.push_str("</mrow>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The converted content, without the `<math>` wrapper and annotation.
+    fn convert(tex: &str) -> String {
+        let math = tex_to_mathml(tex, false);
+        let start = math.find("<semantics><mrow>").unwrap() + "<semantics><mrow>".len();
+        let end = math.find("</mrow><annotation").unwrap();
+        math[start..end].to_string()
+    }
+
+    #[test]
+    fn test_wrapper_and_annotation() {
+        assert_eq!(
+            tex_to_mathml("a<b", true),
+            "<math display=\"block\" xmlns=\"http://www.w3.org/1998/Math/MathML\">\
+             <semantics><mrow><mi>a</mi><mo>&lt;</mo><mi>b</mi></mrow>\
+             <annotation encoding=\"application/x-tex\">a&lt;b</annotation>\
+             </semantics></math>"
+        );
+    }
+
+    #[test]
+    fn test_scripts() {
+        assert_eq!(convert("x^2"), "<msup><mi>x</mi><mn>2</mn></msup>");
+        assert_eq!(
+            convert("a_{i}^{n+1}"),
+            "<msubsup><mi>a</mi><mi>i</mi>\
+             <mrow><mi>n</mi><mo>+</mo><mn>1</mn></mrow></msubsup>"
+        );
+        assert_eq!(convert("f'"), "<msup><mi>f</mi><mo>′</mo></msup>");
+    }
+
+    #[test]
+    fn test_fractions_and_roots() {
+        assert_eq!(
+            convert("\\frac{1}{2}"),
+            "<mfrac><mn>1</mn><mn>2</mn></mfrac>"
+        );
+        assert_eq!(convert("\\sqrt{x}"), "<msqrt><mi>x</mi></msqrt>");
+        assert_eq!(
+            convert("\\sqrt[3]{x}"),
+            "<mroot><mi>x</mi><mn>3</mn></mroot>"
+        );
+    }
+
+    #[test]
+    fn test_symbols_and_functions() {
+        assert_eq!(
+            convert("\\alpha \\leq \\sin x - 3.5"),
+            "<mi>α</mi><mo>≤</mo><mi mathvariant=\"normal\">sin</mi><mi>x</mi>\
+             <mo>−</mo><mn>3.5</mn>"
+        );
+        assert_eq!(
+            convert("\\sum_{i=1}^n"),
+            "<msubsup><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>1</mn></mrow>\
+             <mi>n</mi></msubsup>"
+        );
+    }
+
+    #[test]
+    fn test_text_and_fonts() {
+        assert_eq!(convert("\\text{if } x"), "<mtext>if </mtext><mi>x</mi>");
+        assert_eq!(
+            convert("\\mathbb{R}"),
+            "<mstyle mathvariant=\"double-struck\"><mi>R</mi></mstyle>"
+        );
+    }
+
+    #[test]
+    fn test_fences_and_matrices() {
+        assert_eq!(
+            convert("\\left( x \\right)"),
+            "<mrow><mo stretchy=\"true\" form=\"prefix\">(</mo><mi>x</mi>\
+             <mo stretchy=\"true\" form=\"postfix\">)</mo></mrow>"
+        );
+        assert_eq!(
+            convert("\\begin{pmatrix} a & b \\\\ c & d \\end{pmatrix}"),
+            "<mrow><mo stretchy=\"true\" form=\"prefix\">(</mo><mtable>\
+             <mtr><mtd><mi>a</mi></mtd><mtd><mi>b</mi></mtd></mtr>\
+             <mtr><mtd><mi>c</mi></mtd><mtd><mi>d</mi></mtd></mtr>\
+             </mtable><mo stretchy=\"true\" form=\"postfix\">)</mo></mrow>"
+        );
+    }
+
+    #[test]
+    fn test_unknown_command_and_unbalanced_input() {
+        assert_eq!(convert("\\foo"), "<merror><mtext>\\foo</mtext></merror>");
+        assert_eq!(convert("a}b"), "<mi>a</mi><mi>b</mi>");
+    }
+}
@@ -5,10 +5,20 @@
 
 #[cfg(feature = "terminal-support")]
 pub mod ansi;
+pub mod gfm;
 pub mod html;
 pub(crate) mod html_source;
 pub mod incremental;
 pub mod json;
+pub mod latex;
+pub(crate) mod line_prefix;
+pub(crate) mod mathml;
 pub mod native;
+pub(crate) mod notes;
+pub mod org;
 pub mod plaintext;
 pub mod qmd;
+pub mod raw;
+pub mod rst;
+pub mod source_pos;
+pub mod typst;
@@ -5,8 +5,42 @@
 
 use crate::pandoc::shortcode::shortcode_to_span;
 use crate::pandoc::{
-    Attr, Block, Citation, CitationMode, Inline, ListNumberDelim, MathType, Pandoc, QuoteType,
+    ASTContext, Attr, Block, Citation, CitationMode, Inline, ListNumberDelim, MathType, Pandoc,
+    QuoteType,
 };
+use crate::writers::incremental::{block_source_info, inline_source_info};
+use crate::writers::source_pos::source_pos;
+use quarto_source_map::SourceInfo;
+
+/// Configuration for native output
+#[derive(Debug, Clone, Default)]
+pub struct NativeConfig {
+    /// Precede each block and inline with a `{- file:line:col-line:col -}`
+    /// comment giving its source position (see [`source_pos`])
+    pub source_positions: bool,
+}
+
+/// Context threaded through native writer functions.
+struct NativeWriterContext<'a> {
+    ast_context: &'a ASTContext,
+    config: &'a NativeConfig,
+}
+
+/// Write a `{- position -}` marker for a node, if source positions are
+/// enabled and the node maps to a source file.
+fn write_source_pos<T: std::io::Write>(
+    source_info: &SourceInfo,
+    context: &NativeWriterContext<'_>,
+    buf: &mut T,
+) -> std::io::Result<()> {
+    if !context.config.source_positions {
+        return Ok(());
+    }
+    if let Some(pos) = source_pos(source_info, context.ast_context) {
+        write!(buf, "{{- {} -}} ", pos)?;
+    }
+    Ok(())
+}
 
 fn write_safe_string<T: std::io::Write>(text: &str, buf: &mut T) -> std::io::Result<()> {
     write!(buf, "\"")?;
@@ -98,7 +132,7 @@ fn write_native_colwidth<T: std::io::Write>(
 
 fn write_native_table_body<T: std::io::Write>(
     table_body: &crate::pandoc::TableBody,
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
@@ -113,7 +147,7 @@ fn write_native_table_body<T: std::io::Write>(
 
 fn write_inlines<T: std::io::Write>(
     inlines: &[Inline],
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
@@ -137,7 +171,7 @@ fn write_citation_mode<T: std::io::Write>(mode: &CitationMode, buf: &mut T) -> s
 }
 fn write_native_cell<T: std::io::Write>(
     cell: &crate::pandoc::Cell,
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
@@ -163,7 +197,7 @@ fn write_native_cell<T: std::io::Write>(
 
 fn write_native_row<T: std::io::Write>(
     row: &crate::pandoc::Row,
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
@@ -182,7 +216,7 @@ fn write_native_row<T: std::io::Write>(
 
 fn write_native_rows<T: std::io::Write>(
     rows: &Vec<crate::pandoc::Row>,
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
@@ -199,7 +233,7 @@ fn write_native_rows<T: std::io::Write>(
 
 fn write_native_table_foot<T: std::io::Write>(
     foot: &crate::pandoc::TableFoot,
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
@@ -213,10 +247,11 @@ fn write_native_table_foot<T: std::io::Write>(
 
 fn write_inline<T: std::io::Write>(
     text: &Inline,
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
+    write_source_pos(inline_source_info(text), context, buf)?;
     match text {
         Inline::Math(math_struct) => {
             write!(buf, "Math ")?;
@@ -528,7 +563,7 @@ fn write_list_number_style<T: std::io::Write>(
 
 fn write_short_caption<T: std::io::Write>(
     caption: &Option<Vec<Inline>>,
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
@@ -540,7 +575,7 @@ fn write_short_caption<T: std::io::Write>(
 
 fn write_long_caption<T: std::io::Write>(
     caption: &Option<Vec<Block>>,
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
@@ -562,7 +597,7 @@ fn write_long_caption<T: std::io::Write>(
 
 fn write_caption<T: std::io::Write>(
     caption: &crate::pandoc::Caption,
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
@@ -576,10 +611,11 @@ fn write_caption<T: std::io::Write>(
 
 fn write_block<T: std::io::Write>(
     block: &Block,
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
+    write_source_pos(block_source_info(block), context, buf)?;
     match block {
         Block::Plain(crate::pandoc::Plain { content, .. }) => {
             write!(buf, "Plain ")?;
@@ -881,11 +917,25 @@ pub fn write<T: std::io::Write>(
     pandoc: &Pandoc,
     context: &crate::pandoc::ast_context::ASTContext,
     buf: &mut T,
+) -> Result<(), Vec<quarto_error_reporting::DiagnosticMessage>> {
+    write_with_config(pandoc, context, buf, &NativeConfig::default())
+}
+
+/// Write a Pandoc document in native format with configuration
+pub fn write_with_config<T: std::io::Write>(
+    pandoc: &Pandoc,
+    context: &crate::pandoc::ast_context::ASTContext,
+    buf: &mut T,
+    config: &NativeConfig,
 ) -> Result<(), Vec<quarto_error_reporting::DiagnosticMessage>> {
     let mut errors = Vec::new();
+    let context = NativeWriterContext {
+        ast_context: context,
+        config,
+    };
 
     // Try to write - IO errors are fatal
-    if let Err(e) = write_impl(pandoc, context, buf, &mut errors) {
+    if let Err(e) = write_impl(pandoc, &context, buf, &mut errors) {
         // IO error - wrap and return
         return Err(vec![
             quarto_error_reporting::DiagnosticMessageBuilder::error("IO error during write")
@@ -905,7 +955,7 @@ pub fn write<T: std::io::Write>(
 
 fn write_impl<T: std::io::Write>(
     pandoc: &Pandoc,
-    context: &crate::pandoc::ast_context::ASTContext,
+    context: &NativeWriterContext<'_>,
     buf: &mut T,
     errors: &mut Vec<quarto_error_reporting::DiagnosticMessage>,
 ) -> std::io::Result<()> {
@@ -0,0 +1,145 @@
+/*
+ * notes.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Note definition lookup shared by the writers.
+//!
+//! The reader keeps `[^id]` references and their definitions apart: each
+//! reference becomes an empty `quarto-note-reference` span, each definition a
+//! `NoteDefinitionPara` or `NoteDefinitionFencedBlock` block. Writers whose
+//! notes are written where they are referenced (LaTeX's `\footnote`, Typst's
+//! `#footnote`) resolve a reference to its definition's content through
+//! [`NoteDefinitions`], which also guards against notes that reference
+//! themselves, directly or through other notes.
+
+use crate::pandoc::inline::Span;
+use crate::pandoc::{Block, Blocks, Paragraph};
+use std::collections::HashMap;
+
+/// The note id of a `quarto-note-reference` span, which the reader leaves in
+/// place of each `[^id]`.
+pub(crate) fn note_reference_id(span: &Span) -> Option<&str> {
+    if span.content.is_empty() && span.attr.1.iter().any(|c| c == "quarto-note-reference") {
+        span.attr.2.get("reference-id").map(String::as_str)
+    } else {
+        None
+    }
+}
+
+/// What a note reference resolves to.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ResolvedNote {
+    /// The content of the definition. The note is now being expanded, until
+    /// the matching [`NoteDefinitions::leave`].
+    Content(Blocks),
+    /// The note is already being expanded further up, so writing it again
+    /// would never terminate.
+    Cycle,
+    /// There is no definition with this id.
+    Missing,
+}
+
+/// Note definitions by id, collected from a document's blocks.
+#[derive(Debug, Default)]
+pub(crate) struct NoteDefinitions {
+    definitions: HashMap<String, Blocks>,
+
+    /// Ids of the notes being expanded, innermost last.
+    expanding: Vec<String>,
+}
+
+impl NoteDefinitions {
+    /// Collect the note definitions in `blocks`, including those nested in
+    /// divs. A single-paragraph definition becomes a one-paragraph note.
+    pub(crate) fn collect(blocks: &[Block]) -> Self {
+        let mut notes = Self::default();
+        notes.collect_from(blocks);
+        notes
+    }
+
+    fn collect_from(&mut self, blocks: &[Block]) {
+        for block in blocks {
+            match block {
+                Block::NoteDefinitionPara(refdef) => {
+                    let para = Block::Paragraph(Paragraph {
+                        content: refdef.content.clone(),
+                        source_info: refdef.source_info.clone(),
+                    });
+                    self.definitions.insert(refdef.id.clone(), vec![para]);
+                }
+                Block::NoteDefinitionFencedBlock(refdef) => {
+                    self.definitions
+                        .insert(refdef.id.clone(), refdef.content.clone());
+                }
+                Block::Div(div) => self.collect_from(&div.content),
+                _ => {}
+            }
+        }
+    }
+
+    /// The content of the definition with this id, if any.
+    pub(crate) fn get(&self, id: &str) -> Option<&Blocks> {
+        self.definitions.get(id)
+    }
+
+    /// Start expanding the note with this id.
+    ///
+    /// On [`ResolvedNote::Content`] the caller writes the content and then
+    /// calls [`leave`](Self::leave); otherwise there is nothing to leave.
+    pub(crate) fn enter(&mut self, id: &str) -> ResolvedNote {
+        if self.expanding.iter().any(|expanding| expanding == id) {
+            return ResolvedNote::Cycle;
+        }
+        match self.definitions.get(id) {
+            Some(content) => {
+                self.expanding.push(id.to_string());
+                ResolvedNote::Content(content.clone())
+            }
+            None => ResolvedNote::Missing,
+        }
+    }
+
+    /// Finish expanding the innermost note.
+    pub(crate) fn leave(&mut self) {
+        self.expanding.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes(input: &str) -> NoteDefinitions {
+        let (pandoc, _context, _warnings) = crate::readers::qmd::read(
+            input.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .unwrap();
+        NoteDefinitions::collect(&pandoc.blocks)
+    }
+
+    #[test]
+    fn test_collects_nested_definitions() {
+        let notes = notes("[^a]: One.\n\n::: {.box}\n\n[^b]: Two.\n\n:::\n");
+        assert!(notes.get("a").is_some());
+        assert!(notes.get("b").is_some());
+        assert!(notes.get("c").is_none());
+    }
+
+    #[test]
+    fn test_enter_detects_cycles() {
+        let mut notes = notes("[^a]: One.\n\n[^b]: Two.\n");
+        assert!(matches!(notes.enter("a"), ResolvedNote::Content(_)));
+        assert!(matches!(notes.enter("b"), ResolvedNote::Content(_)));
+        assert_eq!(notes.enter("a"), ResolvedNote::Cycle);
+        assert_eq!(notes.enter("b"), ResolvedNote::Cycle);
+        notes.leave();
+        assert!(matches!(notes.enter("b"), ResolvedNote::Content(_)));
+        assert_eq!(notes.enter("missing"), ResolvedNote::Missing);
+    }
+}
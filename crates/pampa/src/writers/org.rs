@@ -0,0 +1,830 @@
+/*
+ * org.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Emacs Org-mode writer for Pandoc AST.
+//!
+//! # Design decisions
+//!
+//! - Headers become `*` headlines; explicit ids are written as a
+//!   `CUSTOM_ID` property so `[[#id]]` links keep working.
+//! - Code blocks use `#+begin_src lang` (`#+begin_example` without a language).
+//! - Divs with a class become special blocks (`#+begin_<class>`); other divs
+//!   are unwrapped.
+//! - RawBlock becomes `#+begin_export <format>`, RawInline `@@format:text@@`.
+//! - Tables are written as Org tables; block content in cells is flattened.
+//! - Inline notes and referenced note definitions become numbered footnotes
+//!   (`[fn:N]`) defined at the end of the document, numbered in the order
+//!   they are referenced.
+//! - Metadata `title`, `author` and `date` become `#+title:` etc. keywords.
+
+use crate::pandoc::block::{
+    BulletList, CodeBlock, DefinitionList, Div, Figure, Header, OrderedList, RawBlock,
+};
+use crate::pandoc::inline::{Inline, Inlines};
+use crate::pandoc::list::ListNumberDelim;
+use crate::pandoc::table::{Alignment, Cell, Table};
+use crate::pandoc::{Block, Blocks, Pandoc};
+use crate::writers::line_prefix::LinePrefixWriter;
+use crate::writers::notes::{NoteDefinitions, note_reference_id};
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_source_map::SourceInfo;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Metadata keys written as `#+key:` lines, in output order.
+const METADATA_KEYWORDS: &[&str] = &["title", "author", "date"];
+
+/// Context for the Org writer, threaded through all write functions.
+pub struct OrgWriterContext {
+    /// Warnings for nodes that have no Org representation.
+    diagnostics: Vec<DiagnosticMessage>,
+
+    /// Contents of the notes referenced so far, in order of reference.
+    notes: Vec<Blocks>,
+
+    /// Note definitions from the document, by id.
+    note_definitions: NoteDefinitions,
+
+    /// The number given to each note definition on its first reference.
+    note_numbers: HashMap<String, usize>,
+}
+
+impl OrgWriterContext {
+    pub fn new() -> Self {
+        Self {
+            diagnostics: Vec::new(),
+            notes: Vec::new(),
+            note_definitions: NoteDefinitions::default(),
+            note_numbers: HashMap::new(),
+        }
+    }
+
+    pub fn into_diagnostics(self) -> Vec<DiagnosticMessage> {
+        self.diagnostics
+    }
+
+    pub fn diagnostics(&self) -> &[DiagnosticMessage] {
+        &self.diagnostics
+    }
+
+    fn warn_dropped_node(&mut self, description: &str, source_info: &SourceInfo) {
+        let diag = DiagnosticMessageBuilder::warning(format!(
+            "Node dropped in Org output: {}",
+            description
+        ))
+        .with_location(source_info.clone())
+        .build();
+        self.diagnostics.push(diag);
+    }
+}
+
+impl Default for OrgWriterContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Inline writing
+// ============================================================================
+
+fn write_inlines(
+    inlines: &[Inline],
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    for inline in inlines {
+        write_inline(inline, buf, ctx)?;
+    }
+    Ok(())
+}
+
+fn write_wrapped(
+    open: &str,
+    content: &[Inline],
+    close: &str,
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    write!(buf, "{}", open)?;
+    write_inlines(content, buf, ctx)?;
+    write!(buf, "{}", close)
+}
+
+fn write_inline(
+    inline: &Inline,
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    match inline {
+        Inline::Str(s) => write!(buf, "{}", s.text)?,
+        Inline::Space(_) => write!(buf, " ")?,
+        Inline::SoftBreak(_) => writeln!(buf)?,
+        Inline::LineBreak(_) => writeln!(buf, "\\\\")?,
+
+        Inline::Emph(node) => write_wrapped("/", &node.content, "/", buf, ctx)?,
+        Inline::Strong(node) => write_wrapped("*", &node.content, "*", buf, ctx)?,
+        Inline::Underline(node) => write_wrapped("_", &node.content, "_", buf, ctx)?,
+        Inline::Strikeout(node) => write_wrapped("+", &node.content, "+", buf, ctx)?,
+        Inline::Delete(node) => write_wrapped("+", &node.content, "+", buf, ctx)?,
+        Inline::Superscript(node) => write_wrapped("^{", &node.content, "}", buf, ctx)?,
+        Inline::Subscript(node) => write_wrapped("_{", &node.content, "}", buf, ctx)?,
+
+        Inline::SmallCaps(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Insert(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Highlight(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Span(span) if note_reference_id(span).is_some() => {
+            // The reader turns note references into empty spans
+            let id = note_reference_id(span).unwrap_or_default();
+            write_note_reference(id, &span.source_info, buf, ctx)?;
+        }
+        Inline::Span(span) => {
+            if !span.attr.0.is_empty() {
+                write!(buf, "<<{}>>", span.attr.0)?;
+            }
+            write_inlines(&span.content, buf, ctx)?;
+        }
+
+        Inline::Quoted(quoted) => {
+            let mark = match quoted.quote_type {
+                crate::pandoc::QuoteType::SingleQuote => "'",
+                crate::pandoc::QuoteType::DoubleQuote => "\"",
+            };
+            write_wrapped(mark, &quoted.content, mark, buf, ctx)?;
+        }
+
+        Inline::Code(code) => write!(buf, "~{}~", code.text)?,
+        Inline::Math(math) => match math.math_type {
+            crate::pandoc::MathType::InlineMath => write!(buf, "\\({}\\)", math.text)?,
+            crate::pandoc::MathType::DisplayMath => write!(buf, "\\[{}\\]", math.text)?,
+        },
+
+        Inline::Link(link) => {
+            let target = &link.target.0;
+            let text = crate::writers::plaintext::inlines_to_string(&link.content).0;
+            if text == *target || link.content.is_empty() {
+                write!(buf, "[[{}]]", target)?;
+            } else {
+                write!(buf, "[[{}][", target)?;
+                write_inlines(&link.content, buf, ctx)?;
+                write!(buf, "]]")?;
+            }
+        }
+        Inline::Image(image) => write!(buf, "[[{}]]", image.target.0)?,
+
+        Inline::Cite(cite) => {
+            if !cite.content.is_empty() {
+                write_inlines(&cite.content, buf, ctx)?;
+            } else {
+                let keys: Vec<String> = cite
+                    .citations
+                    .iter()
+                    .map(|c| format!("@{}", c.id))
+                    .collect();
+                write!(buf, "[cite:{}]", keys.join(";"))?;
+            }
+        }
+
+        Inline::Note(note) => {
+            ctx.notes.push(note.content.clone());
+            write!(buf, "[fn:{}]", ctx.notes.len())?;
+        }
+        Inline::NoteReference(noteref) => {
+            write_note_reference(&noteref.id, &noteref.source_info, buf, ctx)?
+        }
+
+        Inline::RawInline(raw) => {
+            if raw.format == "org" {
+                write!(buf, "{}", raw.text)?;
+            } else {
+                write!(buf, "@@{}:{}@@", raw.format, raw.text)?;
+            }
+        }
+
+        Inline::Attr(_, _) => {}
+        Inline::EditComment(comment) => {
+            ctx.warn_dropped_node("EditComment", &comment.source_info);
+        }
+        Inline::Shortcode(shortcode) => {
+            ctx.warn_dropped_node(
+                &format!("Shortcode '{}'", shortcode.name),
+                &shortcode.source_info,
+            );
+        }
+        Inline::Custom(custom) => {
+            ctx.warn_dropped_node(
+                &format!("Custom inline ({})", custom.type_name),
+                &custom.source_info,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Write the footnote mark for a reference to a note definition, numbering
+/// the note on its first reference. A reference without a definition is
+/// dropped.
+fn write_note_reference(
+    id: &str,
+    source_info: &SourceInfo,
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    let number = match ctx.note_numbers.get(id) {
+        Some(number) => *number,
+        None => match ctx.note_definitions.get(id) {
+            Some(content) => {
+                ctx.notes.push(content.clone());
+                ctx.note_numbers.insert(id.to_string(), ctx.notes.len());
+                ctx.notes.len()
+            }
+            None => {
+                ctx.warn_dropped_node(
+                    &format!("NoteReference without definition '{}'", id),
+                    source_info,
+                );
+                return Ok(());
+            }
+        },
+    };
+    write!(buf, "[fn:{}]", number)
+}
+
+// ============================================================================
+// Block writing
+// ============================================================================
+
+/// Write blocks separated by blank lines.
+fn write_blocks(
+    blocks: &[Block],
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    let mut wrote_any = false;
+    for block in blocks {
+        let mut scratch = Vec::new();
+        write_block(block, &mut scratch, ctx)?;
+        if scratch.is_empty() {
+            continue;
+        }
+        if wrote_any {
+            writeln!(buf)?;
+        }
+        buf.write_all(&scratch)?;
+        wrote_any = true;
+    }
+    Ok(())
+}
+
+/// Whether a header carries an id worth writing out. Ids that the reader
+/// generated from the header text are omitted, as in the qmd writer.
+fn has_explicit_id(header: &Header) -> bool {
+    !header.attr.0.is_empty()
+        && !(header.attr_source.id.is_none()
+            && header.attr.0 == crate::utils::autoid::auto_generated_id(&header.content))
+}
+
+fn write_header(
+    header: &Header,
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    write!(buf, "{} ", "*".repeat(header.level.max(1)))?;
+    write_inlines(&header.content, buf, ctx)?;
+    writeln!(buf)?;
+    if has_explicit_id(header) {
+        writeln!(buf, ":PROPERTIES:")?;
+        writeln!(buf, ":CUSTOM_ID: {}", header.attr.0)?;
+        writeln!(buf, ":END:")?;
+    }
+    Ok(())
+}
+
+/// Write text inside a `#+begin_`/`#+end_` block, protecting lines that Org
+/// would otherwise interpret as structure with a leading comma.
+fn write_literal_lines(text: &str, buf: &mut dyn Write) -> io::Result<()> {
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('*') || trimmed.starts_with("#+") {
+            write!(buf, ",")?;
+        }
+        writeln!(buf, "{}", line)?;
+    }
+    Ok(())
+}
+
+fn write_codeblock(codeblock: &CodeBlock, buf: &mut dyn Write) -> io::Result<()> {
+    match codeblock.attr.1.first() {
+        Some(language) => {
+            writeln!(
+                buf,
+                "#+begin_src {}",
+                language.trim_start_matches('{').trim_end_matches('}')
+            )?;
+            write_literal_lines(&codeblock.text, buf)?;
+            writeln!(buf, "#+end_src")
+        }
+        None => {
+            writeln!(buf, "#+begin_example")?;
+            write_literal_lines(&codeblock.text, buf)?;
+            writeln!(buf, "#+end_example")
+        }
+    }
+}
+
+fn write_rawblock(rawblock: &RawBlock, buf: &mut dyn Write) -> io::Result<()> {
+    if rawblock.format == "org" {
+        write!(buf, "{}", rawblock.text)?;
+        if !rawblock.text.ends_with('\n') {
+            writeln!(buf)?;
+        }
+        return Ok(());
+    }
+    writeln!(buf, "#+begin_export {}", rawblock.format)?;
+    write_literal_lines(&rawblock.text, buf)?;
+    writeln!(buf, "#+end_export")
+}
+
+/// A list is tight if the first block of every item is Plain (not Para).
+fn is_tight_list(items: &[Blocks]) -> bool {
+    items
+        .iter()
+        .all(|item| !item.is_empty() && matches!(item[0], Block::Plain(_)))
+}
+
+/// Detect a Pandoc-style task list marker (`☐`/`☒` followed by a space).
+/// Returns the Org checkbox and the number of leading inlines it replaces.
+fn task_marker(item: &[Block]) -> Option<(&'static str, usize)> {
+    let inlines = match item.first()? {
+        Block::Plain(plain) => &plain.content,
+        Block::Paragraph(para) => &para.content,
+        _ => return None,
+    };
+    let marker = match inlines.first()? {
+        Inline::Str(s) if s.text == "\u{2610}" => "[ ]",
+        Inline::Str(s) if s.text == "\u{2612}" => "[X]",
+        _ => return None,
+    };
+    match inlines.get(1) {
+        Some(Inline::Space(_)) => Some((marker, 2)),
+        None => Some((marker, 1)),
+        _ => None,
+    }
+}
+
+fn write_list_item(
+    item: &[Block],
+    tight: bool,
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    if item.is_empty() {
+        return writeln!(buf);
+    }
+    let mut blocks = item.iter();
+    if let Some((marker, skip)) = task_marker(item) {
+        let first = match &item[0] {
+            Block::Plain(plain) => &plain.content,
+            Block::Paragraph(para) => &para.content,
+            _ => unreachable!("task_marker only matches Plain/Paragraph"),
+        };
+        write!(buf, "{} ", marker)?;
+        write_inlines(&first[skip..], buf, ctx)?;
+        writeln!(buf)?;
+        blocks.next();
+    } else if let Some(first) = blocks.next() {
+        write_block(first, buf, ctx)?;
+    }
+    for block in blocks {
+        if !tight {
+            writeln!(buf)?;
+        }
+        write_block(block, buf, ctx)?;
+    }
+    Ok(())
+}
+
+fn write_bulletlist(
+    bulletlist: &BulletList,
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    let tight = is_tight_list(&bulletlist.content);
+    for (i, item) in bulletlist.content.iter().enumerate() {
+        if i > 0 && !tight {
+            writeln!(buf)?;
+        }
+        let mut item_writer = LinePrefixWriter::new(&mut *buf, "- ", "  ");
+        write_list_item(item, tight, &mut item_writer, ctx)?;
+    }
+    Ok(())
+}
+
+fn write_orderedlist(
+    orderedlist: &OrderedList,
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    let (start, _style, delim) = &orderedlist.attr;
+    let delim = match delim {
+        ListNumberDelim::OneParen | ListNumberDelim::TwoParens => ")",
+        _ => ".",
+    };
+    let tight = is_tight_list(&orderedlist.content);
+    for (i, item) in orderedlist.content.iter().enumerate() {
+        if i > 0 && !tight {
+            writeln!(buf)?;
+        }
+        let number = start + i;
+        // Org needs a cookie to start a list at an arbitrary number
+        let marker = if i == 0 && *start != 1 {
+            format!("{}{} [@{}] ", number, delim, number)
+        } else {
+            format!("{}{} ", number, delim)
+        };
+        let indent = " ".repeat(format!("{}{} ", number, delim).len());
+        let mut item_writer = LinePrefixWriter::new(&mut *buf, marker, indent);
+        write_list_item(item, tight, &mut item_writer, ctx)?;
+    }
+    Ok(())
+}
+
+fn write_definitionlist(
+    deflist: &DefinitionList,
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    for (term, definitions) in &deflist.content {
+        let mut item_writer = LinePrefixWriter::new(&mut *buf, "- ", "  ");
+        write_inlines(term, &mut item_writer, ctx)?;
+        write!(item_writer, " :: ")?;
+        let blocks: Blocks = definitions.iter().flatten().cloned().collect();
+        write_blocks(&blocks, &mut item_writer, ctx)?;
+        if blocks.is_empty() {
+            writeln!(item_writer)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_div(div: &Div, buf: &mut dyn Write, ctx: &mut OrgWriterContext) -> io::Result<()> {
+    if !div.attr.0.is_empty() {
+        writeln!(buf, "<<{}>>", div.attr.0)?;
+    }
+    match div.attr.1.first() {
+        Some(class) => {
+            writeln!(buf, "#+begin_{}", class)?;
+            write_blocks(&div.content, buf, ctx)?;
+            writeln!(buf, "#+end_{}", class)
+        }
+        None => write_blocks(&div.content, buf, ctx),
+    }
+}
+
+fn write_caption_keyword(
+    caption: &Option<Blocks>,
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    let Some(blocks) = caption else {
+        return Ok(());
+    };
+    let inlines: Inlines = blocks
+        .iter()
+        .flat_map(|block| match block {
+            Block::Plain(plain) => plain.content.clone(),
+            Block::Paragraph(para) => para.content.clone(),
+            _ => Vec::new(),
+        })
+        .collect();
+    if inlines.is_empty() {
+        return Ok(());
+    }
+    let mut scratch = Vec::new();
+    write_inlines(&inlines, &mut scratch, ctx)?;
+    writeln!(
+        buf,
+        "#+caption: {}",
+        String::from_utf8_lossy(&scratch).replace('\n', " ")
+    )
+}
+
+fn write_figure(
+    figure: &Figure,
+    buf: &mut dyn Write,
+    ctx: &mut OrgWriterContext,
+) -> io::Result<()> {
+    write_caption_keyword(&figure.caption.long, buf, ctx)?;
+    if !figure.attr.0.is_empty() {
+        writeln!(buf, "#+name: {}", figure.attr.0)?;
+    }
+    write_blocks(&figure.content, buf, ctx)
+}
+
+/// Render a cell's content on a single line for use in an Org table.
+fn cell_to_string(cell: &Cell, ctx: &mut OrgWriterContext) -> io::Result<String> {
+    let mut parts = Vec::new();
+    for block in &cell.content {
+        let mut scratch = Vec::new();
+        write_block(block, &mut scratch, ctx)?;
+        parts.push(
+            String::from_utf8_lossy(&scratch)
+                .trim()
+                .replace('\n', " ")
+                .replace('|', "\\vert{}"),
+        );
+    }
+    Ok(parts.join(" "))
+}
+
+fn write_table(table: &Table, buf: &mut dyn Write, ctx: &mut OrgWriterContext) -> io::Result<()> {
+    let num_cols = table.colspec.len();
+    if num_cols == 0 {
+        return Ok(());
+    }
+
+    write_caption_keyword(&table.caption.long, buf, ctx)?;
+    if !table.attr.0.is_empty() {
+        writeln!(buf, "#+name: {}", table.attr.0)?;
+    }
+
+    let mut rows = Vec::new();
+    for row in table
+        .head
+        .rows
+        .iter()
+        .chain(
+            table
+                .bodies
+                .iter()
+                .flat_map(|b| b.head.iter().chain(&b.body)),
+        )
+        .chain(table.foot.rows.iter())
+    {
+        let mut cells = Vec::with_capacity(num_cols);
+        for cell in &row.cells {
+            if cell.row_span > 1 || cell.col_span > 1 {
+                ctx.warn_dropped_node("table cell span", &table.source_info);
+            }
+            cells.push(cell_to_string(cell, ctx)?);
+        }
+        cells.resize(num_cols, String::new());
+        cells.truncate(num_cols);
+        rows.push(cells);
+    }
+
+    // Org expresses alignment with a cookie row like `| <l> | <r> |`
+    let has_alignment = table
+        .colspec
+        .iter()
+        .any(|(alignment, _)| *alignment != Alignment::Default);
+    if has_alignment {
+        let cookies: Vec<String> = table
+            .colspec
+            .iter()
+            .map(|(alignment, _)| {
+                match alignment {
+                    Alignment::Left => "<l>",
+                    Alignment::Center => "<c>",
+                    Alignment::Right => "<r>",
+                    Alignment::Default => "",
+                }
+                .to_string()
+            })
+            .collect();
+        rows.insert(0, cookies);
+    }
+
+    let mut widths = vec![1; num_cols];
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(cell.chars().count());
+        }
+    }
+
+    let header_end = usize::from(has_alignment) + table.head.rows.len();
+    for (r, row) in rows.iter().enumerate() {
+        write!(buf, "|")?;
+        for (i, cell) in row.iter().enumerate() {
+            let pad = widths[i] - cell.chars().count();
+            write!(buf, " {}{} |", cell, " ".repeat(pad))?;
+        }
+        writeln!(buf)?;
+        if !table.head.rows.is_empty() && r + 1 == header_end {
+            let rule: Vec<String> = widths.iter().map(|w| "-".repeat(w + 2)).collect();
+            writeln!(buf, "|{}|", rule.join("+"))?;
+        }
+    }
+    Ok(())
+}
+
+fn write_block(block: &Block, buf: &mut dyn Write, ctx: &mut OrgWriterContext) -> io::Result<()> {
+    match block {
+        Block::Plain(plain) => {
+            write_inlines(&plain.content, buf, ctx)?;
+            writeln!(buf)?;
+        }
+        Block::Paragraph(para) => {
+            write_inlines(&para.content, buf, ctx)?;
+            writeln!(buf)?;
+        }
+        Block::Header(header) => write_header(header, buf, ctx)?,
+        Block::CodeBlock(codeblock) => write_codeblock(codeblock, buf)?,
+        Block::RawBlock(rawblock) => write_rawblock(rawblock, buf)?,
+        Block::BlockQuote(blockquote) => {
+            writeln!(buf, "#+begin_quote")?;
+            write_blocks(&blockquote.content, buf, ctx)?;
+            writeln!(buf, "#+end_quote")?;
+        }
+        Block::LineBlock(lineblock) => {
+            writeln!(buf, "#+begin_verse")?;
+            for line in &lineblock.content {
+                write_inlines(line, buf, ctx)?;
+                writeln!(buf)?;
+            }
+            writeln!(buf, "#+end_verse")?;
+        }
+        Block::BulletList(bulletlist) => write_bulletlist(bulletlist, buf, ctx)?,
+        Block::OrderedList(orderedlist) => write_orderedlist(orderedlist, buf, ctx)?,
+        Block::DefinitionList(deflist) => write_definitionlist(deflist, buf, ctx)?,
+        Block::HorizontalRule(_) => writeln!(buf, "-----")?,
+        Block::Table(table) => write_table(table, buf, ctx)?,
+        Block::Figure(figure) => write_figure(figure, buf, ctx)?,
+        Block::Div(div) => write_div(div, buf, ctx)?,
+        // Written with the other notes after the body, when referenced
+        Block::NoteDefinitionPara(_) | Block::NoteDefinitionFencedBlock(_) => {}
+        Block::BlockMetadata(meta) => {
+            ctx.warn_dropped_node("BlockMetadata", &meta.source_info);
+        }
+        Block::CaptionBlock(caption) => {
+            ctx.warn_dropped_node("CaptionBlock", &caption.source_info);
+        }
+        Block::Custom(custom) => {
+            ctx.warn_dropped_node(
+                &format!("Custom block ({})", custom.type_name),
+                &custom.source_info,
+            );
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Entry points
+// ============================================================================
+
+fn write_impl(pandoc: &Pandoc, buf: &mut dyn Write, ctx: &mut OrgWriterContext) -> io::Result<()> {
+    ctx.note_definitions = NoteDefinitions::collect(&pandoc.blocks);
+    let mut need_blank = false;
+    for key in METADATA_KEYWORDS {
+        if let Some(value) = pandoc.meta.get(key).and_then(|v| v.as_plain_text()) {
+            writeln!(buf, "#+{}: {}", key, value)?;
+            need_blank = true;
+        }
+    }
+    if !pandoc.blocks.is_empty() {
+        if need_blank {
+            writeln!(buf)?;
+        }
+        write_blocks(&pandoc.blocks, buf, ctx)?;
+        need_blank = true;
+    }
+
+    // Notes may themselves contain notes, so drain until no new ones appear
+    let mut next = 0;
+    while next < ctx.notes.len() {
+        if need_blank {
+            writeln!(buf)?;
+        }
+        let content = ctx.notes[next].clone();
+        next += 1;
+        write!(buf, "[fn:{}] ", next)?;
+        write_blocks(&content, buf, ctx)?;
+        need_blank = true;
+    }
+    Ok(())
+}
+
+/// Write a Pandoc document as Emacs Org-mode.
+///
+/// Returns the warnings for nodes that could not be represented in Org.
+/// IO errors are returned as `Err`.
+pub fn write<T: Write>(
+    pandoc: &Pandoc,
+    buf: &mut T,
+) -> Result<Vec<DiagnosticMessage>, Vec<DiagnosticMessage>> {
+    let mut ctx = OrgWriterContext::new();
+    if let Err(e) = write_impl(pandoc, buf, &mut ctx) {
+        return Err(vec![
+            DiagnosticMessageBuilder::error("IO error during write")
+                .with_code("Q-3-1")
+                .problem(format!("Failed to write Org output: {}", e))
+                .build(),
+        ]);
+    }
+    Ok(ctx.into_diagnostics())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qmd_to_org(input: &str) -> (String, Vec<DiagnosticMessage>) {
+        let (pandoc, _context, _warnings) = crate::readers::qmd::read(
+            input.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .expect("Failed to parse QMD");
+        let mut buf = Vec::new();
+        let diagnostics = write(&pandoc, &mut buf).expect("Failed to write Org");
+        (String::from_utf8(buf).unwrap(), diagnostics)
+    }
+
+    #[test]
+    fn test_metadata_keywords() {
+        let (out, _) = qmd_to_org("---\ntitle: Notes\nauthor: Ada\n---\n\nBody\n");
+        assert_eq!(out, "#+title: Notes\n#+author: Ada\n\nBody\n");
+    }
+
+    #[test]
+    fn test_headline_with_custom_id() {
+        let (out, _) = qmd_to_org("## Setup {#sec-setup}\n");
+        assert_eq!(
+            out,
+            "** Setup\n:PROPERTIES:\n:CUSTOM_ID: sec-setup\n:END:\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_markup() {
+        let (out, _) = qmd_to_org("*a* **b** ~~c~~ `d` $x$\n");
+        assert_eq!(out, "/a/ *b* +c+ ~d~ \\(x\\)\n");
+    }
+
+    #[test]
+    fn test_links() {
+        let (out, _) = qmd_to_org("[Org](https://orgmode.org) <https://x.org>\n");
+        assert_eq!(out, "[[https://orgmode.org][Org]] [[https://x.org]]\n");
+    }
+
+    #[test]
+    fn test_src_block() {
+        let (out, _) = qmd_to_org("```r\n#+ not a keyword\nx <- 1\n```\n");
+        assert_eq!(out, "#+begin_src r\n,#+ not a keyword\nx <- 1\n#+end_src\n");
+    }
+
+    #[test]
+    fn test_blockquote() {
+        let (out, _) = qmd_to_org("> quoted\n");
+        assert_eq!(out, "#+begin_quote\nquoted\n#+end_quote\n");
+    }
+
+    #[test]
+    fn test_ordered_list_with_start() {
+        let (out, _) = qmd_to_org("3. c\n4. d\n");
+        assert_eq!(out, "3. [@3] c\n4. d\n");
+    }
+
+    #[test]
+    fn test_table_with_alignment() {
+        let (out, _) = qmd_to_org("| a | b |\n|:--|--:|\n| 1 | 2 |\n");
+        assert_eq!(
+            out,
+            "| <l> | <r> |\n| a   | b   |\n|-----+-----|\n| 1   | 2   |\n"
+        );
+    }
+
+    #[test]
+    fn test_div_becomes_special_block() {
+        let (out, _) = qmd_to_org("::: {.note}\n\nHi\n\n:::\n");
+        assert_eq!(out, "#+begin_note\nHi\n#+end_note\n");
+    }
+
+    #[test]
+    fn test_inline_note_becomes_footnote() {
+        let (out, _) = qmd_to_org("Text^[A note.]\n");
+        assert_eq!(out, "Text[fn:1]\n\n[fn:1] A note.\n");
+    }
+
+    #[test]
+    fn test_inline_notes_and_definitions_share_numbers() {
+        let (out, diagnostics) = qmd_to_org("One^[Inline.] two[^1] again[^1].\n\n[^1]: First.\n");
+        assert_eq!(
+            out,
+            "One[fn:1] two[fn:2] again[fn:2].\n\n[fn:1] Inline.\n\n[fn:2] First.\n"
+        );
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_raw_inline_export_snippet() {
+        let (out, _) = qmd_to_org("`<b>`{=html}\n");
+        assert_eq!(out, "@@html:<b>@@\n");
+    }
+}
@@ -11,7 +11,7 @@
 //!
 //! # Design decisions
 //!
-//! - RawInline/RawBlock: echo contents if format is "plaintext", otherwise drop with warning
+//! - RawInline/RawBlock: echo contents if format is "plaintext" (or "plain"), otherwise drop with warning
 //! - Unsupported nodes emit diagnostic warnings and are dropped
 //! - No HTML escaping (unlike `write_inlines_as_text` in html.rs)
 //! - Block structure mimics markdown writer for lists, code blocks, blockquotes, line blocks
@@ -28,6 +28,7 @@ use crate::pandoc::inline::{
     Superscript, Underline,
 };
 use crate::pandoc::table::Table;
+use crate::writers::raw::{PLAINTEXT_FORMATS, is_native_format};
 use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
 use quarto_source_map::SourceInfo;
 
@@ -148,7 +149,7 @@ fn write_inline<T: std::io::Write>(
             text,
             source_info,
         }) => {
-            if format == "plaintext" {
+            if is_native_format(format, PLAINTEXT_FORMATS) {
                 write!(buf, "{}", text)?;
             } else {
                 ctx.warn_dropped_node(&format!("RawInline with format '{}'", format), source_info);
@@ -290,7 +291,7 @@ fn write_block<T: std::io::Write>(
             text,
             source_info,
         }) => {
-            if format == "plaintext" {
+            if is_native_format(format, PLAINTEXT_FORMATS) {
                 write!(buf, "{}", text)?;
             } else {
                 ctx.warn_dropped_node(&format!("RawBlock with format '{}'", format), source_info);
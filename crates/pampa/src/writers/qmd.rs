@@ -3,14 +3,16 @@
  * Copyright (c) 2025 Posit, PBC
  */
 
-use crate::pandoc::attr::is_empty_attr;
+use crate::options::FormatExtensions;
+use crate::pandoc::attr::{AttrSourceInfo, empty_attr, is_empty_attr};
 use crate::pandoc::block::MetaBlock;
+use crate::pandoc::cell_options;
 use crate::pandoc::inline::Inline;
 use crate::pandoc::list::{ListNumberDelim, ListNumberStyle};
 use crate::pandoc::table::{Alignment, Cell, ColWidth, Row, Table};
 use crate::pandoc::{
-    Block, BlockQuote, BulletList, CodeBlock, DefinitionList, Figure, Header, HorizontalRule,
-    LineBlock, OrderedList, Pandoc, Paragraph, Plain, RawBlock, Str,
+    Block, BlockQuote, Blocks, BulletList, CodeBlock, DefinitionList, Figure, Header,
+    HorizontalRule, LineBlock, OrderedList, Pandoc, Paragraph, Plain, RawBlock, Space, Span, Str,
 };
 use hashlink::LinkedHashMap;
 use quarto_pandoc_types::{ConfigValue, ConfigValueKind};
@@ -35,6 +37,65 @@ pub struct EmphasisStackFrame {
     pub is_strong: bool,
 }
 
+/// Marker written before bullet list items.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BulletMarker {
+    /// `* item`
+    #[default]
+    Asterisk,
+    /// `- item`
+    Dash,
+    /// `+ item`
+    Plus,
+}
+
+impl BulletMarker {
+    /// The marker with the space that follows it.
+    fn as_str(self) -> &'static str {
+        match self {
+            BulletMarker::Asterisk => "* ",
+            BulletMarker::Dash => "- ",
+            BulletMarker::Plus => "+ ",
+        }
+    }
+}
+
+/// Options controlling how the QMD writer renders the AST.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QmdWriterOptions {
+    /// Markdown extensions the output should be read back with.
+    ///
+    /// The writer honors `smart` and `task_lists`:
+    /// - with `smart`, en/em dashes and ellipses are written back as the
+    ///   ASCII sequences the reader produces them from (`--`, `---`, `...`).
+    /// - with `task_lists`, `☐`/`☒` bullet items are written as `[ ]`/`[x]`.
+    pub extensions: FormatExtensions,
+
+    /// Column at which paragraph text is wrapped, or `None` to keep the line
+    /// breaks of the source.
+    ///
+    /// The width counts the paragraph's own text, not the indentation of
+    /// the list items or block quotes around it. Pipe table cells are never
+    /// wrapped.
+    pub wrap_width: Option<usize>,
+
+    /// Marker for bullet list items.
+    pub bullet_marker: BulletMarker,
+
+    /// Write pipe table cells without padding them to their column's width.
+    pub compact_tables: bool,
+}
+
+impl QmdWriterOptions {
+    /// Build writer options from a format's `+ext`/`-ext` modifiers.
+    pub fn from_extensions(extensions: &crate::options::ExtensionsDiff) -> Self {
+        Self {
+            extensions: FormatExtensions::from_diff(extensions),
+            ..Default::default()
+        }
+    }
+}
+
 /// Context for QMD writer, threaded through all write functions
 pub struct QmdWriterContext {
     /// Accumulated error messages during writing
@@ -44,6 +105,13 @@ pub struct QmdWriterContext {
     /// When writing nested Emph/Strong nodes, we check this stack to
     /// choose delimiters that won't create *** sequences.
     pub emphasis_stack: Vec<EmphasisStackFrame>,
+
+    /// Writer options in effect for this write
+    pub options: QmdWriterOptions,
+
+    /// Whether a paragraph is being wrapped: spaces and soft breaks are
+    /// written as [`BREAK`] for the wrapping to choose line breaks from.
+    wrapping: bool,
 }
 
 impl Default for QmdWriterContext {
@@ -54,9 +122,15 @@ impl Default for QmdWriterContext {
 
 impl QmdWriterContext {
     pub fn new() -> Self {
+        Self::with_options(QmdWriterOptions::default())
+    }
+
+    pub fn with_options(options: QmdWriterOptions) -> Self {
         Self {
             errors: Vec::new(),
             emphasis_stack: Vec::new(),
+            options,
+            wrapping: false,
         }
     }
 
@@ -136,14 +210,16 @@ struct BulletListContext<'a, W: Write + ?Sized> {
     inner: &'a mut W,
     at_line_start: bool,
     is_first_line: bool,
+    marker: BulletMarker,
 }
 
 impl<'a, W: Write + ?Sized> BulletListContext<'a, W> {
-    fn new(inner: &'a mut W) -> Self {
+    fn new(inner: &'a mut W, marker: BulletMarker) -> Self {
         Self {
             inner,
             at_line_start: true,
             is_first_line: true,
+            marker,
         }
     }
 }
@@ -154,9 +230,11 @@ impl<'a, W: Write + ?Sized> Write for BulletListContext<'a, W> {
         for &byte in buf {
             if self.at_line_start {
                 if self.is_first_line {
-                    self.inner.write_all(b"* ")?;
+                    self.inner.write_all(self.marker.as_str().as_bytes())?;
                     self.is_first_line = false;
-                } else {
+                } else if byte != b'\n' {
+                    // Blank lines stay empty: the reader doesn't treat a line of
+                    // spaces as blank, so loose items would read back as tight
                     self.inner.write_all(b"  ")?;
                 }
                 self.at_line_start = false;
@@ -233,7 +311,7 @@ impl<'a, W: Write + ?Sized> Write for OrderedListContext<'a, W> {
                         }
                     }
                     self.is_first_line = false;
-                } else {
+                } else if byte != b'\n' {
                     self.inner.write_all(self.indent.as_bytes())?;
                 }
                 self.at_line_start = false;
@@ -255,7 +333,7 @@ impl<'a, W: Write + ?Sized> Write for OrderedListContext<'a, W> {
 /// Convert a ConfigValue to a yaml_rust2::Yaml value
 /// Phase 5: Works directly with ConfigValue without MetaValueWithSourceInfo conversion
 /// PandocInlines and PandocBlocks are rendered using the qmd writer
-fn config_value_to_yaml(value: &ConfigValue) -> std::io::Result<Yaml> {
+fn config_value_to_yaml(value: &ConfigValue, options: &QmdWriterOptions) -> std::io::Result<Yaml> {
     match &value.value {
         ConfigValueKind::Scalar(yaml) => {
             // Pass through the yaml value directly
@@ -264,7 +342,7 @@ fn config_value_to_yaml(value: &ConfigValue) -> std::io::Result<Yaml> {
         ConfigValueKind::PandocInlines(content) => {
             // Render inlines using the qmd writer
             let mut buffer = Vec::<u8>::new();
-            let mut ctx = QmdWriterContext::new(); // Errors in metadata inlines are unexpected
+            let mut ctx = QmdWriterContext::with_options(options.clone()); // Errors in metadata inlines are unexpected
             for inline in content {
                 write_inline(inline, &mut buffer, &mut ctx)?;
             }
@@ -282,7 +360,7 @@ fn config_value_to_yaml(value: &ConfigValue) -> std::io::Result<Yaml> {
         ConfigValueKind::PandocBlocks(content) => {
             // Render blocks using the qmd writer
             let mut buffer = Vec::<u8>::new();
-            let mut ctx = QmdWriterContext::new(); // Errors in metadata blocks are unexpected
+            let mut ctx = QmdWriterContext::with_options(options.clone()); // Errors in metadata blocks are unexpected
             for (i, block) in content.iter().enumerate() {
                 if i > 0 {
                     writeln!(&mut buffer)?;
@@ -305,7 +383,7 @@ fn config_value_to_yaml(value: &ConfigValue) -> std::io::Result<Yaml> {
         ConfigValueKind::Array(items) => {
             let mut yaml_list = Vec::new();
             for item in items {
-                yaml_list.push(config_value_to_yaml(item)?);
+                yaml_list.push(config_value_to_yaml(item, options)?);
             }
             Ok(Yaml::Array(yaml_list))
         }
@@ -315,7 +393,7 @@ fn config_value_to_yaml(value: &ConfigValue) -> std::io::Result<Yaml> {
             for entry in entries {
                 yaml_map.insert(
                     Yaml::String(entry.key.clone()),
-                    config_value_to_yaml(&entry.value)?,
+                    config_value_to_yaml(&entry.value, options)?,
                 );
             }
             Ok(Yaml::Hash(yaml_map))
@@ -345,7 +423,7 @@ fn write_config_value_meta<T: std::io::Write + ?Sized>(
                 for entry in entries {
                     yaml_map.insert(
                         Yaml::String(entry.key.clone()),
-                        config_value_to_yaml(&entry.value)?,
+                        config_value_to_yaml(&entry.value, &ctx.options)?,
                     );
                 }
                 let yaml = Yaml::Hash(yaml_map);
@@ -476,6 +554,13 @@ fn write_bulletlist(
             writeln!(buf)?;
         }
 
+        let task_item = if ctx.options.extensions.task_lists {
+            task_list_item_to_span(item)
+        } else {
+            None
+        };
+        let item = task_item.as_ref().unwrap_or(item);
+
         // Check if this is an empty list item (single Plain/Para block with empty content)
         let is_empty_item = item.len() == 1
             && match &item[0] {
@@ -486,9 +571,9 @@ fn write_bulletlist(
 
         if is_empty_item {
             // Write "* []" for empty list items
-            writeln!(buf, "* []")?;
+            writeln!(buf, "{}[]", ctx.options.bullet_marker.as_str())?;
         } else {
-            let mut item_writer = BulletListContext::new(buf);
+            let mut item_writer = BulletListContext::new(buf, ctx.options.bullet_marker);
             for (j, block) in item.iter().enumerate() {
                 if j > 0 && !is_tight {
                     // Add a blank line between blocks within a list item in loose lists
@@ -501,6 +586,41 @@ fn write_bulletlist(
     Ok(())
 }
 
+/// Sugar for the reader's task_lists extension: rewrite a leading `☐`/`☒`
+/// marker as the empty or `x` span that `[ ]`/`[x]` parses to.
+fn task_list_item_to_span(item: &Blocks) -> Option<Blocks> {
+    let mut item = item.clone();
+    let content = match item.first_mut() {
+        Some(Block::Plain(plain)) => &mut plain.content,
+        Some(Block::Paragraph(para)) => &mut para.content,
+        _ => return None,
+    };
+    if content.len() < 2 {
+        return None;
+    }
+    let Inline::Str(marker) = &content[0] else {
+        return None;
+    };
+    let source_info = marker.source_info.clone();
+    let span_content = match marker.text.as_str() {
+        "\u{2610}" => vec![Inline::Space(Space {
+            source_info: source_info.clone(),
+        })],
+        "\u{2612}" => vec![Inline::Str(Str {
+            text: "x".to_string(),
+            source_info: source_info.clone(),
+        })],
+        _ => return None,
+    };
+    content[0] = Inline::Span(Span {
+        attr: empty_attr(),
+        content: span_content,
+        source_info,
+        attr_source: AttrSourceInfo::empty(),
+    });
+    Some(item)
+}
+
 fn write_orderedlist(
     orderedlist: &OrderedList,
     buf: &mut dyn std::io::Write,
@@ -611,8 +731,21 @@ fn write_codeblock(
         write!(buf, "`")?;
     }
 
-    // Write language/attributes if they exist
-    let (id, classes, keyvals) = &codeblock.attr;
+    // Write language/attributes if they exist. Options read from `#|` lines
+    // are written back with the code, not repeated in the header.
+    let option_keys = cell_options::cell_option_keys(codeblock);
+    let attr = if option_keys.is_empty() {
+        codeblock.attr.clone()
+    } else {
+        let (id, classes, keyvals) = &codeblock.attr;
+        let keyvals = keyvals
+            .iter()
+            .filter(|(k, _)| !option_keys.contains(k))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        (id.clone(), classes.clone(), keyvals)
+    };
+    let (id, classes, keyvals) = &attr;
 
     // Only write language as bare word if it's a single class with no other attributes
     if classes.len() == 1 && id.is_empty() && keyvals.is_empty() {
@@ -620,7 +753,7 @@ fn write_codeblock(
         write!(buf, "{}", classes[0])?;
     } else if !id.is_empty() || !classes.is_empty() || !keyvals.is_empty() {
         // Has attributes: write full attribute block (no space before it)
-        write_attr(&codeblock.attr, buf, ctx)?;
+        write_attr(&attr, buf, ctx)?;
     }
 
     writeln!(buf)?;
@@ -684,30 +817,48 @@ fn write_definitionlist(
     buf: &mut dyn std::io::Write,
     ctx: &mut QmdWriterContext,
 ) -> std::io::Result<()> {
-    for (i, (term, definitions)) in deflist.content.iter().enumerate() {
-        if i > 0 {
-            writeln!(buf)?;
-        }
-
-        // Write the term
-        for inline in term {
-            write_inline(inline, buf, ctx)?;
-        }
-        writeln!(buf)?;
-
-        // Write the definitions
-        for definition in definitions {
-            write!(buf, ":   ")?;
-            for (j, block) in definition.iter().enumerate() {
-                if j > 0 {
-                    writeln!(buf)?;
-                    write!(buf, "    ")?; // Indent subsequent blocks in definition
-                }
-                write_block(block, buf, ctx)?;
-            }
-        }
-    }
-    Ok(())
+    // The reader only recognizes the div form, so write each term as a bullet
+    // item whose nested bullet list holds the definitions:
+    //
+    // ::: {.definition-list}
+    //
+    // * term
+    //   - definition
+    //
+    // :::
+    //
+    // Reusing the bullet list writer gives multi-block and nested definitions
+    // the same continuation indentation as any other list item.
+    let items = deflist
+        .content
+        .iter()
+        .map(|(term, definitions)| {
+            vec![
+                Block::Plain(Plain {
+                    content: term.clone(),
+                    source_info: deflist.source_info.clone(),
+                }),
+                Block::BulletList(BulletList {
+                    content: definitions.clone(),
+                    source_info: deflist.source_info.clone(),
+                }),
+            ]
+        })
+        .collect();
+    let div = crate::pandoc::Div {
+        attr: (
+            String::new(),
+            vec!["definition-list".to_string()],
+            LinkedHashMap::new(),
+        ),
+        content: vec![Block::BulletList(BulletList {
+            content: items,
+            source_info: deflist.source_info.clone(),
+        })],
+        source_info: deflist.source_info.clone(),
+        attr_source: AttrSourceInfo::empty(),
+    };
+    write_div(&div, buf, ctx)
 }
 
 fn write_horizontalrule(
@@ -819,7 +970,7 @@ fn cell_has_simple_content(content: &[Block]) -> bool {
 }
 
 /// Check if a table can be written in pipe table format.
-/// Returns false if the table requires list-table format.
+/// Returns false if the table requires grid or list-table format.
 ///
 /// A table can use pipe format if:
 /// - No cells have row_span > 1 or col_span > 1
@@ -866,6 +1017,168 @@ fn table_can_use_pipe_format(table: &Table) -> bool {
     true
 }
 
+/// Check if a cell's blocks survive being written into a grid table cell.
+/// The reader turns a lone paragraph into Plain, so a cell may hold a single
+/// Plain, or any other blocks as long as none of them is Plain.
+fn cell_fits_grid_format(content: &[Block]) -> bool {
+    match content {
+        [] | [Block::Plain(_)] => true,
+        [Block::Paragraph(_)] => false,
+        blocks => !blocks.iter().any(|block| matches!(block, Block::Plain(_))),
+    }
+}
+
+/// Check if a table can be written in grid table format.
+/// Returns false if the table requires list-table format.
+///
+/// A table can use grid format if:
+/// - No cells have row_span > 1 or col_span > 1
+/// - It has at most one header row, a single body and no footer
+/// - It has no caption, attributes or explicit column widths
+/// - All cells satisfy `cell_fits_grid_format`
+fn table_can_use_grid_format(table: &Table) -> bool {
+    let [body] = table.bodies.as_slice() else {
+        return false;
+    };
+    if table.head.rows.len() > 1
+        || !table.foot.rows.is_empty()
+        || !body.head.is_empty()
+        || body.rowhead_columns != 0
+        || body.body.is_empty()
+        || !is_empty_attr(&table.attr)
+        || table.caption.short.is_some()
+        || table
+            .caption
+            .long
+            .as_ref()
+            .is_some_and(|long| !long.is_empty())
+        || table.colspec.iter().any(|(_, w)| *w != ColWidth::Default)
+    {
+        return false;
+    }
+
+    table.head.rows.iter().chain(&body.body).all(|row| {
+        row.cells.len() == table.colspec.len()
+            && row.cells.iter().all(|cell| {
+                cell.row_span <= 1 && cell.col_span <= 1 && cell_fits_grid_format(&cell.content)
+            })
+    })
+}
+
+/// Write a grid table border or separator line, with alignment colons when
+/// `alignments` is given.
+fn write_grid_separator(
+    widths: &[usize],
+    fill: char,
+    alignments: Option<&[Alignment]>,
+    buf: &mut dyn std::io::Write,
+) -> std::io::Result<()> {
+    write!(buf, "+")?;
+    for (i, width) in widths.iter().enumerate() {
+        let mut segment: Vec<char> = vec![fill; width + 2];
+        match alignments.map(|a| &a[i]) {
+            Some(Alignment::Left) => segment[0] = ':',
+            Some(Alignment::Right) => segment[width + 1] = ':',
+            Some(Alignment::Center) => {
+                segment[0] = ':';
+                segment[width + 1] = ':';
+            }
+            _ => {}
+        }
+        write!(buf, "{}+", segment.into_iter().collect::<String>())?;
+    }
+    writeln!(buf)
+}
+
+/// Write a table as a grid table.
+/// Used for tables whose cells hold block content (lists, several paragraphs,
+/// code blocks) that pipe tables cannot express. Every column is as wide as
+/// its widest cell line, and rows are padded so all cells in a row have the
+/// same number of lines.
+fn write_grid_table(
+    table: &Table,
+    buf: &mut dyn std::io::Write,
+    ctx: &mut QmdWriterContext,
+) -> std::io::Result<()> {
+    let num_cols = table.colspec.len();
+    let has_header = !table.head.rows.is_empty();
+    let rows: Vec<&Row> = table
+        .head
+        .rows
+        .iter()
+        .chain(table.bodies.iter().flat_map(|body| &body.body))
+        .collect();
+
+    // Render every cell into lines
+    let mut rendered: Vec<Vec<Vec<String>>> = Vec::new();
+    for row in &rows {
+        let mut cells = Vec::new();
+        for cell in &row.cells {
+            let mut buffer = Vec::<u8>::new();
+            if let [Block::Plain(plain)] = cell.content.as_slice() {
+                for inline in &plain.content {
+                    write_inline(inline, &mut buffer, ctx)?;
+                }
+            } else {
+                for (i, block) in cell.content.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(buffer)?;
+                    }
+                    write_block(block, &mut buffer, ctx)?;
+                }
+            }
+            let content = String::from_utf8(buffer)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            let content = content.trim_end_matches('\n');
+            cells.push(content.lines().map(|l| l.trim_end().to_string()).collect());
+        }
+        rendered.push(cells);
+    }
+
+    // Balance column widths: each column fits its widest line
+    let mut widths = vec![3; num_cols];
+    for cells in &rendered {
+        for (i, lines) in cells.iter().enumerate() {
+            for line in lines {
+                widths[i] = widths[i].max(line.chars().count());
+            }
+        }
+    }
+
+    let alignments: Vec<Alignment> = table.colspec.iter().map(|(a, _)| a.clone()).collect();
+    let has_alignments = alignments.iter().any(|a| *a != Alignment::Default);
+
+    let top_alignments = (has_alignments && !has_header).then_some(alignments.as_slice());
+    write_grid_separator(&widths, '-', top_alignments, buf)?;
+
+    for (row_idx, cells) in rendered.iter().enumerate() {
+        let height = cells
+            .iter()
+            .map(|lines| lines.len())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        for line_idx in 0..height {
+            write!(buf, "|")?;
+            for (i, lines) in cells.iter().enumerate() {
+                let line = lines.get(line_idx).map_or("", String::as_str);
+                let padding = widths[i] - line.chars().count();
+                write!(buf, " {}{} |", line, " ".repeat(padding))?;
+            }
+            writeln!(buf)?;
+        }
+
+        if row_idx == 0 && has_header {
+            let header_alignments = has_alignments.then_some(alignments.as_slice());
+            write_grid_separator(&widths, '=', header_alignments, buf)?;
+        } else {
+            write_grid_separator(&widths, '-', None, buf)?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Convert Alignment to character code for list-table
 fn alignment_to_char(align: &Alignment) -> char {
     match align {
@@ -1070,17 +1383,21 @@ fn write_list_table(
 }
 
 // INCREMENTAL WRITER COUPLING: This is the sugar decision point for tables. It chooses
-// between pipe table format and list-table div format. The incremental writer always fully
-// rewrites Table blocks (never incrementally splices them), so format changes between
-// pipe and list-table on rewrite are acceptable (canonicalization, not a bug).
+// between pipe table, grid table and list-table div format. The incremental writer always
+// fully rewrites Table blocks (never incrementally splices them), so format changes between
+// these on rewrite are acceptable (canonicalization, not a bug).
 // See: postprocess.rs transform registry.
 fn write_table(
     table: &Table,
     buf: &mut dyn std::io::Write,
     ctx: &mut QmdWriterContext,
 ) -> std::io::Result<()> {
-    // Use list-table format if the table has features that pipe tables don't support
+    // Fall back to grid tables for block content, and to list-table format for
+    // features that neither pipe nor grid tables support
     if !table_can_use_pipe_format(table) {
+        if table_can_use_grid_format(table) {
+            return write_grid_table(table, buf, ctx);
+        }
         return write_list_table(table, buf, ctx);
     }
 
@@ -1110,6 +1427,8 @@ fn write_table(
     let mut row_contents: Vec<Vec<String>> = Vec::new();
     let mut max_widths = vec![0; num_cols];
 
+    // Cells must stay on one line
+    let wrap_width = ctx.options.wrap_width.take();
     for row in &all_rows {
         let mut cell_strings = Vec::new();
         for (i, cell) in row.cells.iter().take(num_cols).enumerate() {
@@ -1130,19 +1449,26 @@ fn write_table(
         }
         row_contents.push(cell_strings);
     }
+    ctx.options.wrap_width = wrap_width;
 
     // Ensure minimum width of 3 for each column
     for width in &mut max_widths {
-        if *width < 3 {
+        if *width < 3 || ctx.options.compact_tables {
             *width = 3;
         }
     }
+    // Compact cells are written without padding
+    let cell_widths = if ctx.options.compact_tables {
+        vec![0; num_cols]
+    } else {
+        max_widths.clone()
+    };
 
     // Write header row (first row)
     if !row_contents.is_empty() {
         write!(buf, "|")?;
         for (i, content) in row_contents[0].iter().enumerate() {
-            write!(buf, " {:width$} |", content, width = max_widths[i])?;
+            write!(buf, " {:width$} |", content, width = cell_widths[i])?;
         }
         writeln!(buf)?;
 
@@ -1164,31 +1490,48 @@ fn write_table(
         for row_content in row_contents.iter().skip(1) {
             write!(buf, "|")?;
             for (i, content) in row_content.iter().enumerate() {
-                write!(buf, " {:width$} |", content, width = max_widths[i])?;
+                write!(buf, " {:width$} |", content, width = cell_widths[i])?;
             }
             writeln!(buf)?;
         }
     }
 
-    // Write caption if it exists
-    if let Some(ref long_caption) = table.caption.long
-        && !long_caption.is_empty()
-    {
-        writeln!(buf)?; // Blank line before caption
-        for block in long_caption {
+    // Write caption if it exists. The table's attributes (e.g. a `#tbl-`
+    // id) go at the end of the caption line, so a table with attributes gets
+    // one even without caption text.
+    let caption_lines: Vec<&[Inline]> = table
+        .caption
+        .long
+        .iter()
+        .flatten()
+        .filter_map(|block| match block {
             // Extract inline content from Plain or Paragraph blocks in caption
-            let content = match block {
-                Block::Plain(plain) => Some(&plain.content),
-                Block::Paragraph(para) => Some(&para.content),
-                _ => None,
-            };
-            if let Some(inlines) = content {
-                write!(buf, ": ")?;
-                for inline in inlines {
-                    write_inline(inline, buf, ctx)?;
+            Block::Plain(plain) => Some(plain.content.as_slice()),
+            Block::Paragraph(para) => Some(para.content.as_slice()),
+            _ => None,
+        })
+        .collect();
+    let has_attr = !is_empty_attr(&table.attr);
+    if !caption_lines.is_empty() || has_attr {
+        writeln!(buf)?; // Blank line before caption
+        let last = caption_lines.len().saturating_sub(1);
+        for (i, inlines) in caption_lines.iter().enumerate() {
+            write!(buf, ": ")?;
+            for inline in inlines.iter() {
+                write_inline(inline, buf, ctx)?;
+            }
+            if has_attr && i == last {
+                if !inlines.is_empty() {
+                    write!(buf, " ")?;
                 }
-                writeln!(buf)?;
+                write_attr(&table.attr, buf, ctx)?;
             }
+            writeln!(buf)?;
+        }
+        if caption_lines.is_empty() {
+            write!(buf, ": ")?;
+            write_attr(&table.attr, buf, ctx)?;
+            writeln!(buf)?;
         }
     }
 
@@ -1214,11 +1557,46 @@ fn determine_backticks(text: &str) -> String {
 }
 
 // Helper function to reverse smart quotes conversion
-// Converts Unicode right single quotation mark (') back to ASCII apostrophe (')
+// Converts right single quotation mark (') back to ASCII apostrophe (')
 fn reverse_smart_quotes(text: &str) -> String {
     text.replace('\u{2019}', "'")
 }
 
+// Helper function to reverse the smart extension's typography
+// Converts en/em dashes and ellipses back to the ASCII sequences the reader
+// produces them from
+fn reverse_smart_typography(text: &str) -> String {
+    text.replace('\u{2014}', "---")
+        .replace('\u{2013}', "--")
+        .replace('\u{2026}', "...")
+}
+
+// Helper function to escape ASCII sequences the smart extension would read as
+// typography: runs of two or more hyphens and of three or more dots are
+// written with every character escaped, so a literal `1--2` stays literal
+fn escape_smart_sequences(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let run = chars[i..].iter().take_while(|c| **c == ch).count();
+        let min_run = match ch {
+            '-' => 2,
+            '.' => 3,
+            _ => usize::MAX,
+        };
+        for _ in 0..run {
+            if run >= min_run {
+                result.push('\\');
+            }
+            result.push(ch);
+        }
+        i += run;
+    }
+    result
+}
+
 // Helper function to escape special markdown characters
 // This follows Pandoc's escaping strategy: escape characters that have special
 // markdown meaning to ensure proper roundtripping (qmd -> AST -> qmd).
@@ -1255,26 +1633,36 @@ fn escape_markdown(text: &str) -> String {
 fn write_str(
     s: &Str,
     buf: &mut dyn std::io::Write,
-    _ctx: &mut QmdWriterContext,
+    ctx: &mut QmdWriterContext,
 ) -> std::io::Result<()> {
     let text = reverse_smart_quotes(&s.text);
-    let escaped = escape_markdown(&text);
+    let escaped = if ctx.options.extensions.smart {
+        reverse_smart_typography(&escape_smart_sequences(&escape_markdown(&text)))
+    } else {
+        escape_markdown(&text)
+    };
     write!(buf, "{}", escaped)
 }
 
 fn write_space(
     _: &crate::pandoc::Space,
     buf: &mut dyn std::io::Write,
-    _ctx: &mut QmdWriterContext,
+    ctx: &mut QmdWriterContext,
 ) -> std::io::Result<()> {
+    if ctx.wrapping {
+        return write!(buf, "{}", BREAK);
+    }
     write!(buf, " ")
 }
 
 fn write_soft_break(
     _: &crate::pandoc::SoftBreak,
     buf: &mut dyn std::io::Write,
-    _ctx: &mut QmdWriterContext,
+    ctx: &mut QmdWriterContext,
 ) -> std::io::Result<()> {
+    if ctx.wrapping {
+        return write!(buf, "{}", BREAK);
+    }
     // Pandoc's writer for markdown outputs a space for soft breaks
     // We choose to deviate from Pandoc for roundtripping purposes
     writeln!(buf)
@@ -1535,6 +1923,17 @@ fn write_span(
         }
     }
 
+    // Mentions read back from a bare `@handle` when the extension is enabled
+    if ctx.options.extensions.mentions
+        && id.is_empty()
+        && classes == &["mention"]
+        && keyvals.is_empty()
+        && let [Inline::Str(s)] = span.content.as_slice()
+        && s.text.starts_with('@')
+    {
+        return write!(buf, "{}", s.text);
+    }
+
     // Spans use bracket syntax: [content]{#id .class key=value}
     // When attributes are empty, omit the {} suffix — the parser treats [content]
     // as a Span regardless. For empty content + empty attrs, write [ ] with a space
@@ -1716,9 +2115,68 @@ fn write_notereference(
 fn write_shortcode(
     shortcode: &crate::pandoc::Shortcode,
     buf: &mut dyn std::io::Write,
-    _ctx: &mut QmdWriterContext,
+    ctx: &mut QmdWriterContext,
+) -> std::io::Result<()> {
+    let (open, close) = if shortcode.is_escaped {
+        ("{{{<", ">}}}")
+    } else {
+        ("{{<", ">}}")
+    };
+    write!(buf, "{} {}", open, shortcode.name)?;
+    for arg in &shortcode.positional_args {
+        write!(buf, " ")?;
+        write_shortcode_arg(arg, false, buf, ctx)?;
+    }
+    // Keyword arguments are unordered; sort them so output is stable
+    let mut keys: Vec<&String> = shortcode.keyword_args.keys().collect();
+    keys.sort();
+    for key in keys {
+        write!(buf, " {}=", key)?;
+        write_shortcode_arg(&shortcode.keyword_args[key], true, buf, ctx)?;
+    }
+    write!(buf, " {}", close)
+}
+
+/// Write a shortcode argument. Strings are quoted unless they would be read
+/// back as the same naked string; a positional string that looks like a
+/// number is quoted so it isn't read back as one.
+fn write_shortcode_arg(
+    arg: &crate::pandoc::ShortcodeArg,
+    is_keyword_value: bool,
+    buf: &mut dyn std::io::Write,
+    ctx: &mut QmdWriterContext,
 ) -> std::io::Result<()> {
-    write!(buf, "{{{{{}}}}}", shortcode.name)
+    use crate::pandoc::ShortcodeArg;
+    match arg {
+        ShortcodeArg::String(text) => {
+            let naked = !text.is_empty()
+                && text
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_.~:/?#[]@!$%&()+,;-".contains(c))
+                && (is_keyword_value || text.parse::<f64>().is_err());
+            if naked {
+                write!(buf, "{}", text)
+            } else {
+                // The reader unescapes `\"` only, so backslashes stay as is
+                write!(buf, "\"{}\"", text.replace('"', "\\\""))
+            }
+        }
+        ShortcodeArg::Number(number) => write!(buf, "{}", number),
+        ShortcodeArg::Boolean(value) => write!(buf, "{}", value),
+        ShortcodeArg::Shortcode(shortcode) => write_shortcode(shortcode, buf, ctx),
+        ShortcodeArg::KeyValue(pairs) => {
+            let mut keys: Vec<&String> = pairs.keys().collect();
+            keys.sort();
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    write!(buf, " ")?;
+                }
+                write!(buf, "{}=", key)?;
+                write_shortcode_arg(&pairs[key], true, buf, ctx)?;
+            }
+            Ok(())
+        }
+    }
 }
 fn write_insert(
     insert: &crate::pandoc::Insert,
@@ -1906,9 +2364,7 @@ pub fn write_paragraph(
     buf: &mut dyn std::io::Write,
     ctx: &mut QmdWriterContext,
 ) -> std::io::Result<()> {
-    for inline in &para.content {
-        write_inline(inline, buf, ctx)?;
-    }
+    write_paragraph_inlines(&para.content, buf, ctx)?;
     writeln!(buf)?;
     Ok(())
 }
@@ -1918,13 +2374,101 @@ pub fn write_plain(
     buf: &mut dyn std::io::Write,
     ctx: &mut QmdWriterContext,
 ) -> std::io::Result<()> {
-    for inline in &plain.content {
-        write_inline(inline, buf, ctx)?;
-    }
+    write_paragraph_inlines(&plain.content, buf, ctx)?;
     writeln!(buf)?;
     Ok(())
 }
 
+/// Stands in for a space or soft break while a paragraph is being wrapped.
+const BREAK: char = '\u{1}';
+
+/// Write the inlines of a paragraph, wrapped at the `wrap_width` option.
+///
+/// Paragraphs nested in a paragraph being wrapped (in a note) are wrapped
+/// along with it.
+fn write_paragraph_inlines(
+    inlines: &[Inline],
+    buf: &mut dyn std::io::Write,
+    ctx: &mut QmdWriterContext,
+) -> std::io::Result<()> {
+    let width = match ctx.options.wrap_width {
+        Some(width) if !ctx.wrapping => width,
+        _ => {
+            for inline in inlines {
+                write_inline(inline, buf, ctx)?;
+            }
+            return Ok(());
+        }
+    };
+
+    let mut text = Vec::<u8>::new();
+    ctx.wrapping = true;
+    let result = inlines
+        .iter()
+        .try_for_each(|inline| write_inline(inline, &mut text, ctx));
+    ctx.wrapping = false;
+    result?;
+    let text = String::from_utf8(text)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    write!(buf, "{}", wrap_text(&text, width))
+}
+
+/// Fill the lines of `text` up to `width` characters, breaking only at
+/// [`BREAK`]s. Existing line breaks (hard breaks) are kept.
+///
+/// A word that would start a block (`- item`, `# heading`, `1. item`, ...)
+/// if it began a line is kept on the line before.
+fn wrap_text(text: &str, width: usize) -> String {
+    let mut lines = Vec::new();
+    for source_line in text.split('\n') {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for word in source_line.split(BREAK).filter(|word| !word.is_empty()) {
+            let word_width = word.chars().count();
+            if !line.is_empty() {
+                if line_width + 1 + word_width > width && !starts_block(word) {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                } else {
+                    line.push(' ');
+                    line_width += 1;
+                }
+            }
+            line.push_str(word);
+            line_width += word_width;
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Whether a line starting with `word` would be read as the start of a
+/// block rather than a paragraph continuation.
+fn starts_block(word: &str) -> bool {
+    if matches!(word, "-" | "+" | "*") || word.starts_with(['#', '>', '|', ':', '<']) {
+        return true;
+    }
+    // Code fences, and code spans, which the reader also takes as the
+    // start of a new paragraph at the start of a line
+    if word.starts_with('`') || word.starts_with("~~~") {
+        return true;
+    }
+    // Setext underlines and horizontal rules
+    if word.chars().all(|c| c == '=' || c == '-') {
+        return true;
+    }
+    // Ordered list markers: `1.`, `2)`, `a.`, `(i)`, `#.`
+    let marker = word.strip_prefix('(').unwrap_or(word);
+    let Some(number) = marker.strip_suffix(['.', ')']) else {
+        return false;
+    };
+    let is_roman = |c: char| "ivxlcdmIVXLCDM".contains(c);
+    !number.is_empty()
+        && (number.chars().all(|c| c.is_ascii_digit())
+            || number.len() == 1 && number.chars().all(|c| c.is_ascii_alphabetic())
+            || number.chars().all(is_roman))
+}
+
 /// Write a single block to the given buffer using a fresh writer context.
 ///
 /// This is a public wrapper around the private `write_block` function,
@@ -2008,7 +2552,16 @@ pub fn write<T: std::io::Write>(
     pandoc: &Pandoc,
     buf: &mut T,
 ) -> Result<(), Vec<quarto_error_reporting::DiagnosticMessage>> {
-    let mut ctx = QmdWriterContext::new();
+    write_with_options(pandoc, buf, &QmdWriterOptions::default())
+}
+
+/// Like [`write`], but with explicit [`QmdWriterOptions`].
+pub fn write_with_options<T: std::io::Write>(
+    pandoc: &Pandoc,
+    buf: &mut T,
+    options: &QmdWriterOptions,
+) -> Result<(), Vec<quarto_error_reporting::DiagnosticMessage>> {
+    let mut ctx = QmdWriterContext::with_options(options.clone());
 
     // Try to write - IO errors are fatal
     if let Err(e) = write_impl(pandoc, buf, &mut ctx) {
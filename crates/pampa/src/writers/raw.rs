@@ -0,0 +1,89 @@
+/*
+ * raw.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Handling of raw content (`RawBlock`/`RawInline`, e.g. ```` ```{=html} ````)
+//! shared by the writers.
+//!
+//! Raw content in one of a writer's own formats is written verbatim. Raw
+//! content in any other ("foreign") format is dropped with a warning, unless
+//! the writer is configured with [`ForeignRawPolicy::Keep`].
+
+/// What a writer does with raw content in a format it doesn't produce.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ForeignRawPolicy {
+    /// Drop the content and report a warning
+    #[default]
+    Warn,
+    /// Write the content verbatim, as if it were in the writer's format
+    Keep,
+}
+
+impl ForeignRawPolicy {
+    /// Parse a policy name (`warn` or `keep`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "warn" => Some(ForeignRawPolicy::Warn),
+            "keep" => Some(ForeignRawPolicy::Keep),
+            _ => None,
+        }
+    }
+
+    /// The policy's name.
+    pub fn name(self) -> &'static str {
+        match self {
+            ForeignRawPolicy::Warn => "warn",
+            ForeignRawPolicy::Keep => "keep",
+        }
+    }
+}
+
+/// Raw formats the HTML writer writes verbatim.
+pub const HTML_FORMATS: &[&str] = &["html", "html4", "html5"];
+
+/// Raw formats the GFM writer writes verbatim.
+pub const GFM_FORMATS: &[&str] = &["html", "html4", "html5", "markdown", "gfm", "commonmark"];
+
+/// Raw formats the plain-text writer writes verbatim.
+pub const PLAINTEXT_FORMATS: &[&str] = &["plaintext", "plain"];
+
+/// Raw formats the LaTeX writer writes verbatim.
+pub const LATEX_FORMATS: &[&str] = &["latex", "tex"];
+
+/// Raw formats the RST writer writes verbatim.
+pub const RST_FORMATS: &[&str] = &["rst"];
+
+/// Raw formats the Typst writer writes verbatim.
+pub const TYPST_FORMATS: &[&str] = &["typst"];
+
+/// Whether raw content tagged `format` is in one of `formats`.
+///
+/// Format names are compared case-insensitively, as in Pandoc.
+pub fn is_native_format(format: &str, formats: &[&str]) -> bool {
+    formats.iter().any(|f| f.eq_ignore_ascii_case(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_native_format() {
+        assert!(is_native_format("html", HTML_FORMATS));
+        assert!(is_native_format("HTML5", HTML_FORMATS));
+        assert!(!is_native_format("latex", HTML_FORMATS));
+        assert!(is_native_format("gfm", GFM_FORMATS));
+    }
+
+    #[test]
+    fn test_policy_names() {
+        assert_eq!(ForeignRawPolicy::default(), ForeignRawPolicy::Warn);
+        assert_eq!(
+            ForeignRawPolicy::from_name("Keep"),
+            Some(ForeignRawPolicy::Keep)
+        );
+        assert_eq!(ForeignRawPolicy::from_name("drop"), None);
+        assert_eq!(ForeignRawPolicy::Keep.name(), "keep");
+    }
+}
@@ -0,0 +1,896 @@
+/*
+ * rst.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! reStructuredText writer for Pandoc AST.
+//!
+//! Targets docutils/Sphinx so existing Sphinx projects can consume content
+//! authored in Quarto.
+//!
+//! # Design decisions
+//!
+//! - Section adornments follow the order `=`, `-`, `~`, `^`, `"`, `'`
+//!   (underline only). Explicit header ids become `.. _id:` targets.
+//! - Code blocks use Sphinx's `.. code-block:: lang` directive (`::` when
+//!   there is no language).
+//! - Tables are written with the `.. list-table::` directive, which supports
+//!   multi-block cells without grid-table layout.
+//! - Divs with classes become `.. container::` directives; other divs are
+//!   unwrapped.
+//! - RawBlocks of any format become `.. raw:: <format>` directives. RawInline
+//!   is echoed for `rst`, otherwise dropped with a warning.
+//! - Inline notes and referenced note definitions become numbered footnotes
+//!   (`[N]_`) whose bodies are written at the end of the document, numbered
+//!   in the order they are referenced.
+//! - A block quote right after an indented block (a directive body, literal
+//!   block, list or another quote) is preceded by an empty `..` comment, so
+//!   it is not read as part of that block.
+//! - The document title (from metadata) is written as an over/underlined
+//!   document title.
+
+use crate::pandoc::block::{
+    BulletList, CodeBlock, DefinitionList, Div, Figure, Header, OrderedList, RawBlock,
+};
+use crate::pandoc::inline::{Image, Inline, Inlines};
+use crate::pandoc::list::ListNumberDelim;
+use crate::pandoc::table::{Alignment, Table};
+use crate::pandoc::{Block, Blocks, Pandoc};
+use crate::writers::line_prefix::LinePrefixWriter;
+use crate::writers::notes::{NoteDefinitions, note_reference_id};
+use crate::writers::raw::{RST_FORMATS, is_native_format};
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_source_map::SourceInfo;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Section adornment characters, indexed by header level - 1.
+const SECTION_ADORNMENTS: &[char] = &['=', '-', '~', '^', '"', '\''];
+
+/// Indentation used for directive bodies and block quotes.
+const INDENT: &str = "   ";
+
+/// Context for the RST writer, threaded through all write functions.
+pub struct RstWriterContext {
+    /// Warnings for nodes that have no RST representation.
+    diagnostics: Vec<DiagnosticMessage>,
+
+    /// Contents of the notes referenced so far, in order of reference.
+    notes: Vec<Blocks>,
+
+    /// Note definitions from the document, by id.
+    note_definitions: NoteDefinitions,
+
+    /// The number given to each note definition on its first reference.
+    note_numbers: HashMap<String, usize>,
+}
+
+impl RstWriterContext {
+    pub fn new() -> Self {
+        Self {
+            diagnostics: Vec::new(),
+            notes: Vec::new(),
+            note_definitions: NoteDefinitions::default(),
+            note_numbers: HashMap::new(),
+        }
+    }
+
+    pub fn into_diagnostics(self) -> Vec<DiagnosticMessage> {
+        self.diagnostics
+    }
+
+    pub fn diagnostics(&self) -> &[DiagnosticMessage] {
+        &self.diagnostics
+    }
+
+    fn warn_dropped_node(&mut self, description: &str, source_info: &SourceInfo) {
+        let diag = DiagnosticMessageBuilder::warning(format!(
+            "Node dropped in RST output: {}",
+            description
+        ))
+        .with_location(source_info.clone())
+        .build();
+        self.diagnostics.push(diag);
+    }
+}
+
+impl Default for RstWriterContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Escaping helpers
+// ============================================================================
+
+/// Escape characters that start inline markup in RST.
+fn escape_rst(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' | '*' | '`' | '_' | '|' => {
+                result.push('\\');
+                result.push(ch);
+            }
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+fn write_role(role: &str, text: &str, buf: &mut dyn Write) -> io::Result<()> {
+    write!(buf, ":{}:`{}`", role, text.replace('`', "\\`"))
+}
+
+// ============================================================================
+// Inline writing
+// ============================================================================
+
+fn write_inlines(
+    inlines: &[Inline],
+    buf: &mut dyn Write,
+    ctx: &mut RstWriterContext,
+) -> io::Result<()> {
+    for inline in inlines {
+        write_inline(inline, buf, ctx)?;
+    }
+    Ok(())
+}
+
+/// Render inlines to a string (for titles and roles that need the text first).
+fn inlines_to_rst(inlines: &[Inline], ctx: &mut RstWriterContext) -> io::Result<String> {
+    let mut scratch = Vec::new();
+    write_inlines(inlines, &mut scratch, ctx)?;
+    Ok(String::from_utf8_lossy(&scratch).into_owned())
+}
+
+/// Render inlines as unescaped text, for contexts where markup is not allowed
+/// (roles and link text).
+fn inlines_text(inlines: &[Inline]) -> String {
+    crate::writers::plaintext::inlines_to_string(&inlines.to_vec()).0
+}
+
+fn write_inline(
+    inline: &Inline,
+    buf: &mut dyn Write,
+    ctx: &mut RstWriterContext,
+) -> io::Result<()> {
+    match inline {
+        Inline::Str(s) => write!(buf, "{}", escape_rst(&s.text))?,
+        Inline::Space(_) => write!(buf, " ")?,
+        Inline::SoftBreak(_) => writeln!(buf)?,
+        // RST has no inline hard break; a line block would be needed
+        Inline::LineBreak(_) => writeln!(buf)?,
+
+        Inline::Emph(emph) => write!(buf, "*{}*", inlines_text(&emph.content))?,
+        Inline::Strong(strong) => write!(buf, "**{}**", inlines_text(&strong.content))?,
+        Inline::Superscript(sup) => write_role("sup", &inlines_text(&sup.content), buf)?,
+        Inline::Subscript(sub) => write_role("sub", &inlines_text(&sub.content), buf)?,
+
+        // Unwrap: keep content, drop markup that RST cannot express
+        Inline::Underline(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::SmallCaps(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Strikeout(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Span(node) => match note_reference_id(node) {
+            // The reader turns note references into empty spans
+            Some(id) => write_note_reference(id, &node.source_info, buf, ctx)?,
+            None => write_inlines(&node.content, buf, ctx)?,
+        },
+        Inline::Insert(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Delete(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Highlight(node) => write_inlines(&node.content, buf, ctx)?,
+
+        Inline::Quoted(quoted) => {
+            let mark = match quoted.quote_type {
+                crate::pandoc::QuoteType::SingleQuote => "'",
+                crate::pandoc::QuoteType::DoubleQuote => "\"",
+            };
+            write!(buf, "{}", mark)?;
+            write_inlines(&quoted.content, buf, ctx)?;
+            write!(buf, "{}", mark)?;
+        }
+
+        Inline::Code(code) => write!(buf, "``{}``", code.text)?,
+        Inline::Math(math) => write_role("math", &math.text, buf)?,
+
+        Inline::Link(link) => {
+            let text = inlines_text(&link.content);
+            if text == link.target.0 {
+                write!(buf, "{}", link.target.0)?;
+            } else if let Some(anchor) = link.target.0.strip_prefix('#') {
+                // Internal reference to a `.. _id:` target
+                write!(buf, "`{} <{}_>`__", text, anchor)?;
+            } else {
+                write!(buf, "`{} <{}>`__", text, link.target.0)?;
+            }
+        }
+        Inline::Image(image) => {
+            // Inline images need substitution definitions, which we don't
+            // generate; keep the alt text so no content is lost.
+            ctx.warn_dropped_node("inline Image (alt text kept)", &image.source_info);
+            write_inlines(&image.content, buf, ctx)?;
+        }
+
+        Inline::Cite(cite) => {
+            if !cite.content.is_empty() {
+                write_inlines(&cite.content, buf, ctx)?;
+            } else {
+                for (i, citation) in cite.citations.iter().enumerate() {
+                    if i > 0 {
+                        write!(buf, " ")?;
+                    }
+                    write!(buf, "[{}]_", citation.id)?;
+                }
+            }
+        }
+
+        Inline::Note(note) => {
+            ctx.notes.push(note.content.clone());
+            write!(buf, " [{}]_", ctx.notes.len())?;
+        }
+        Inline::NoteReference(noteref) => {
+            write_note_reference(&noteref.id, &noteref.source_info, buf, ctx)?
+        }
+
+        Inline::RawInline(raw) => {
+            if is_native_format(&raw.format, RST_FORMATS) {
+                write!(buf, "{}", raw.text)?;
+            } else {
+                ctx.warn_dropped_node(
+                    &format!("RawInline with format '{}'", raw.format),
+                    &raw.source_info,
+                );
+            }
+        }
+
+        Inline::Attr(_, _) => {}
+        Inline::EditComment(comment) => {
+            ctx.warn_dropped_node("EditComment", &comment.source_info);
+        }
+        Inline::Shortcode(shortcode) => {
+            ctx.warn_dropped_node(
+                &format!("Shortcode '{}'", shortcode.name),
+                &shortcode.source_info,
+            );
+        }
+        Inline::Custom(custom) => {
+            ctx.warn_dropped_node(
+                &format!("Custom inline ({})", custom.type_name),
+                &custom.source_info,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Write the footnote mark for a reference to a note definition, numbering
+/// the note on its first reference. A reference without a definition is
+/// dropped.
+fn write_note_reference(
+    id: &str,
+    source_info: &SourceInfo,
+    buf: &mut dyn Write,
+    ctx: &mut RstWriterContext,
+) -> io::Result<()> {
+    let number = match ctx.note_numbers.get(id) {
+        Some(number) => *number,
+        None => match ctx.note_definitions.get(id) {
+            Some(content) => {
+                ctx.notes.push(content.clone());
+                ctx.note_numbers.insert(id.to_string(), ctx.notes.len());
+                ctx.notes.len()
+            }
+            None => {
+                ctx.warn_dropped_node(
+                    &format!("NoteReference without definition '{}'", id),
+                    source_info,
+                );
+                return Ok(());
+            }
+        },
+    };
+    write!(buf, " [{}]_", number)
+}
+
+// ============================================================================
+// Block writing
+// ============================================================================
+
+/// Write blocks separated by blank lines.
+fn write_blocks(
+    blocks: &[Block],
+    buf: &mut dyn Write,
+    ctx: &mut RstWriterContext,
+) -> io::Result<()> {
+    let mut previous: Option<&Block> = None;
+    for block in blocks {
+        let mut scratch = Vec::new();
+        write_block(block, &mut scratch, ctx)?;
+        if scratch.is_empty() {
+            continue;
+        }
+        if let Some(previous) = previous {
+            writeln!(buf)?;
+            if matches!(block, Block::BlockQuote(_)) && ends_indented_block(previous) {
+                // An empty comment ends the previous block, so the quote's
+                // indentation doesn't continue it
+                writeln!(buf, "..")?;
+                writeln!(buf)?;
+            }
+        }
+        buf.write_all(&scratch)?;
+        previous = Some(block);
+    }
+    Ok(())
+}
+
+/// Whether indented lines written after `block` would be read as part of
+/// it: directives and literal blocks take an indented body, and list items
+/// and block quotes continue with indented lines.
+fn ends_indented_block(block: &Block) -> bool {
+    match block {
+        Block::Paragraph(para) => {
+            sole_image(std::slice::from_ref(block)).is_some()
+                || matches!(para.content.as_slice(), [Inline::Math(math)]
+                    if matches!(math.math_type, crate::pandoc::MathType::DisplayMath))
+        }
+        Block::Div(div) if div.attr.1.is_empty() => div
+            .content
+            .iter()
+            .rev()
+            .find(|block| !is_note_definition(block))
+            .is_some_and(ends_indented_block),
+        Block::CodeBlock(_)
+        | Block::RawBlock(_)
+        | Block::BlockQuote(_)
+        | Block::BulletList(_)
+        | Block::OrderedList(_)
+        | Block::DefinitionList(_)
+        | Block::Table(_)
+        | Block::Figure(_)
+        | Block::Div(_) => true,
+        _ => false,
+    }
+}
+
+/// Note definitions write nothing in place; see [`write_note_reference`].
+fn is_note_definition(block: &Block) -> bool {
+    matches!(
+        block,
+        Block::NoteDefinitionPara(_) | Block::NoteDefinitionFencedBlock(_)
+    )
+}
+
+/// Write `content` as the indented body of a directive.
+fn write_directive_body(
+    content: &[Block],
+    buf: &mut dyn Write,
+    ctx: &mut RstWriterContext,
+) -> io::Result<()> {
+    let mut body = LinePrefixWriter::new(buf, INDENT, INDENT);
+    write_blocks(content, &mut body, ctx)
+}
+
+/// Whether a header carries an id worth writing out. Ids that the reader
+/// generated from the header text are omitted, as in the qmd writer.
+fn has_explicit_id(header: &Header) -> bool {
+    !header.attr.0.is_empty()
+        && !(header.attr_source.id.is_none()
+            && header.attr.0 == crate::utils::autoid::auto_generated_id(&header.content))
+}
+
+fn write_header(
+    header: &Header,
+    buf: &mut dyn Write,
+    ctx: &mut RstWriterContext,
+) -> io::Result<()> {
+    if has_explicit_id(header) {
+        writeln!(buf, ".. _{}:", header.attr.0)?;
+        writeln!(buf)?;
+    }
+    let title = inlines_to_rst(&header.content, ctx)?.replace('\n', " ");
+    let level = header.level.clamp(1, SECTION_ADORNMENTS.len());
+    let adornment = SECTION_ADORNMENTS[level - 1];
+    writeln!(buf, "{}", title)?;
+    writeln!(
+        buf,
+        "{}",
+        adornment.to_string().repeat(title.chars().count().max(1))
+    )
+}
+
+fn write_codeblock(codeblock: &CodeBlock, buf: &mut dyn Write) -> io::Result<()> {
+    match codeblock.attr.1.first() {
+        Some(language) => writeln!(
+            buf,
+            ".. code-block:: {}",
+            language.trim_start_matches('{').trim_end_matches('}')
+        )?,
+        None => writeln!(buf, "::")?,
+    }
+    writeln!(buf)?;
+    for line in codeblock.text.lines() {
+        if line.is_empty() {
+            writeln!(buf)?;
+        } else {
+            writeln!(buf, "{}{}", INDENT, line)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_rawblock(rawblock: &RawBlock, buf: &mut dyn Write) -> io::Result<()> {
+    if is_native_format(&rawblock.format, RST_FORMATS) {
+        write!(buf, "{}", rawblock.text)?;
+        if !rawblock.text.ends_with('\n') {
+            writeln!(buf)?;
+        }
+        return Ok(());
+    }
+    writeln!(buf, ".. raw:: {}", rawblock.format)?;
+    writeln!(buf)?;
+    for line in rawblock.text.lines() {
+        if line.is_empty() {
+            writeln!(buf)?;
+        } else {
+            writeln!(buf, "{}{}", INDENT, line)?;
+        }
+    }
+    Ok(())
+}
+
+/// A list is compact if every item is a single Plain block. RST requires
+/// blank lines around nested structure, so anything else is written loose.
+fn is_compact_list(items: &[Blocks]) -> bool {
+    items
+        .iter()
+        .all(|item| item.len() == 1 && matches!(item[0], Block::Plain(_)))
+}
+
+fn write_list_items(
+    items: &[Blocks],
+    markers: impl Iterator<Item = String>,
+    buf: &mut dyn Write,
+    ctx: &mut RstWriterContext,
+) -> io::Result<()> {
+    let compact = is_compact_list(items);
+    for (i, (item, marker)) in items.iter().zip(markers).enumerate() {
+        if i > 0 && !compact {
+            writeln!(buf)?;
+        }
+        let indent = " ".repeat(marker.chars().count());
+        let mut item_writer = LinePrefixWriter::new(&mut *buf, marker, indent);
+        if item.is_empty() {
+            writeln!(item_writer)?;
+        } else {
+            write_blocks(item, &mut item_writer, ctx)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_bulletlist(
+    bulletlist: &BulletList,
+    buf: &mut dyn Write,
+    ctx: &mut RstWriterContext,
+) -> io::Result<()> {
+    let markers = std::iter::repeat_with(|| "- ".to_string());
+    write_list_items(&bulletlist.content, markers, buf, ctx)
+}
+
+fn write_orderedlist(
+    orderedlist: &OrderedList,
+    buf: &mut dyn Write,
+    ctx: &mut RstWriterContext,
+) -> io::Result<()> {
+    let (start, _style, delim) = &orderedlist.attr;
+    let (open, close) = match delim {
+        ListNumberDelim::OneParen => ("", ")"),
+        ListNumberDelim::TwoParens => ("(", ")"),
+        _ => ("", "."),
+    };
+    let markers = (*start..).map(|n| format!("{}{}{} ", open, n, close));
+    write_list_items(&orderedlist.content, markers, buf, ctx)
+}
+
+fn write_definitionlist(
+    deflist: &DefinitionList,
+    buf: &mut dyn Write,
+    ctx: &mut RstWriterContext,
+) -> io::Result<()> {
+    for (i, (term, definitions)) in deflist.content.iter().enumerate() {
+        if i > 0 {
+            writeln!(buf)?;
+        }
+        writeln!(buf, "{}", inlines_to_rst(term, ctx)?.replace('\n', " "))?;
+        for (j, definition) in definitions.iter().enumerate() {
+            if j > 0 {
+                writeln!(buf)?;
+            }
+            write_directive_body(definition, buf, ctx)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_div(div: &Div, buf: &mut dyn Write, ctx: &mut RstWriterContext) -> io::Result<()> {
+    if div.attr.1.is_empty() {
+        if !div.attr.0.is_empty() {
+            writeln!(buf, ".. _{}:", div.attr.0)?;
+            writeln!(buf)?;
+        }
+        return write_blocks(&div.content, buf, ctx);
+    }
+    writeln!(buf, ".. container:: {}", div.attr.1.join(" "))?;
+    if !div.attr.0.is_empty() {
+        writeln!(buf, "{}:name: {}", INDENT, div.attr.0)?;
+    }
+    if !div.content.is_empty() {
+        writeln!(buf)?;
+        write_directive_body(&div.content, buf, ctx)?;
+    }
+    Ok(())
+}
+
+/// Return the image if `blocks` is exactly one paragraph holding one image.
+fn sole_image(blocks: &[Block]) -> Option<&Image> {
+    let [block] = blocks else {
+        return None;
+    };
+    let inlines: &Inlines = match block {
+        Block::Plain(plain) => &plain.content,
+        Block::Paragraph(para) => &para.content,
+        _ => return None,
+    };
+    match inlines.as_slice() {
+        [Inline::Image(image)] => Some(image),
+        _ => None,
+    }
+}
+
+fn write_image_directive(directive: &str, image: &Image, buf: &mut dyn Write) -> io::Result<()> {
+    writeln!(buf, ".. {}:: {}", directive, image.target.0)?;
+    let alt = inlines_text(&image.content);
+    if !alt.is_empty() {
+        writeln!(buf, "{}:alt: {}", INDENT, alt)?;
+    }
+    for (key, value) in &image.attr.2 {
+        if key == "width" || key == "height" {
+            writeln!(buf, "{}:{}: {}", INDENT, key, value)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_figure(
+    figure: &Figure,
+    buf: &mut dyn Write,
+    ctx: &mut RstWriterContext,
+) -> io::Result<()> {
+    let Some(image) = sole_image(&figure.content) else {
+        // Not a simple image figure: write the content, then the caption
+        let mut blocks = figure.content.clone();
+        if let Some(long_caption) = &figure.caption.long {
+            blocks.extend(long_caption.iter().cloned());
+        }
+        return write_blocks(&blocks, buf, ctx);
+    };
+    write_image_directive("figure", image, buf)?;
+    if !figure.attr.0.is_empty() {
+        writeln!(buf, "{}:name: {}", INDENT, figure.attr.0)?;
+    }
+    if let Some(long_caption) = &figure.caption.long
+        && !long_caption.is_empty()
+    {
+        writeln!(buf)?;
+        write_directive_body(long_caption, buf, ctx)?;
+    }
+    Ok(())
+}
+
+fn write_table(table: &Table, buf: &mut dyn Write, ctx: &mut RstWriterContext) -> io::Result<()> {
+    let num_cols = table.colspec.len();
+    if num_cols == 0 {
+        return Ok(());
+    }
+
+    let caption = match &table.caption.long {
+        Some(blocks) => crate::writers::plaintext::blocks_to_string(blocks)
+            .0
+            .replace('\n', " "),
+        None => String::new(),
+    };
+    if caption.is_empty() {
+        writeln!(buf, ".. list-table::")?;
+    } else {
+        writeln!(buf, ".. list-table:: {}", caption)?;
+    }
+    if !table.head.rows.is_empty() {
+        writeln!(buf, "{}:header-rows: {}", INDENT, table.head.rows.len())?;
+    }
+    if !table.attr.0.is_empty() {
+        writeln!(buf, "{}:name: {}", INDENT, table.attr.0)?;
+    }
+    let aligns: Vec<&str> = table
+        .colspec
+        .iter()
+        .map(|(alignment, _)| match alignment {
+            Alignment::Left => "left",
+            Alignment::Center => "center",
+            Alignment::Right => "right",
+            Alignment::Default => "",
+        })
+        .collect();
+    if aligns.iter().all(|a| *a == aligns[0]) && !aligns[0].is_empty() {
+        writeln!(buf, "{}:align: {}", INDENT, aligns[0])?;
+    }
+    writeln!(buf)?;
+
+    let mut body = LinePrefixWriter::new(&mut *buf, INDENT, INDENT);
+    for row in table
+        .head
+        .rows
+        .iter()
+        .chain(
+            table
+                .bodies
+                .iter()
+                .flat_map(|b| b.head.iter().chain(&b.body)),
+        )
+        .chain(table.foot.rows.iter())
+    {
+        let mut cells = row.cells.iter().take(num_cols).peekable();
+        for i in 0..num_cols {
+            let cell = cells.next();
+            if let Some(cell) = cell
+                && (cell.row_span > 1 || cell.col_span > 1)
+            {
+                ctx.warn_dropped_node("table cell span", &table.source_info);
+            }
+            let first = if i == 0 { "* - " } else { "  - " };
+            let mut cell_writer = LinePrefixWriter::new(&mut body, first, "    ");
+            match cell {
+                Some(cell) if !cell.content.is_empty() => {
+                    write_blocks(&cell.content, &mut cell_writer, ctx)?
+                }
+                _ => writeln!(cell_writer)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_block(block: &Block, buf: &mut dyn Write, ctx: &mut RstWriterContext) -> io::Result<()> {
+    match block {
+        Block::Plain(plain) => {
+            write_inlines(&plain.content, buf, ctx)?;
+            writeln!(buf)?;
+        }
+        Block::Paragraph(para) => {
+            if let Some(image) = sole_image(std::slice::from_ref(block)) {
+                write_image_directive("image", image, buf)?;
+            } else if let [Inline::Math(math)] = para.content.as_slice()
+                && matches!(math.math_type, crate::pandoc::MathType::DisplayMath)
+            {
+                writeln!(buf, ".. math::")?;
+                writeln!(buf)?;
+                for line in math.text.trim().lines() {
+                    writeln!(buf, "{}{}", INDENT, line)?;
+                }
+            } else {
+                write_inlines(&para.content, buf, ctx)?;
+                writeln!(buf)?;
+            }
+        }
+        Block::Header(header) => write_header(header, buf, ctx)?,
+        Block::CodeBlock(codeblock) => write_codeblock(codeblock, buf)?,
+        Block::RawBlock(rawblock) => write_rawblock(rawblock, buf)?,
+        Block::BlockQuote(blockquote) => {
+            let mut quote_writer = LinePrefixWriter::new(buf, INDENT, INDENT);
+            write_blocks(&blockquote.content, &mut quote_writer, ctx)?;
+        }
+        Block::LineBlock(lineblock) => {
+            for line in &lineblock.content {
+                write!(buf, "| ")?;
+                write_inlines(line, buf, ctx)?;
+                writeln!(buf)?;
+            }
+        }
+        Block::BulletList(bulletlist) => write_bulletlist(bulletlist, buf, ctx)?,
+        Block::OrderedList(orderedlist) => write_orderedlist(orderedlist, buf, ctx)?,
+        Block::DefinitionList(deflist) => write_definitionlist(deflist, buf, ctx)?,
+        Block::HorizontalRule(_) => writeln!(buf, "----")?,
+        Block::Table(table) => write_table(table, buf, ctx)?,
+        Block::Figure(figure) => write_figure(figure, buf, ctx)?,
+        Block::Div(div) => write_div(div, buf, ctx)?,
+        // Written with the other notes after the body, when referenced
+        Block::NoteDefinitionPara(_) | Block::NoteDefinitionFencedBlock(_) => {}
+        Block::BlockMetadata(meta) => {
+            ctx.warn_dropped_node("BlockMetadata", &meta.source_info);
+        }
+        Block::CaptionBlock(caption) => {
+            ctx.warn_dropped_node("CaptionBlock", &caption.source_info);
+        }
+        Block::Custom(custom) => {
+            ctx.warn_dropped_node(
+                &format!("Custom block ({})", custom.type_name),
+                &custom.source_info,
+            );
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Entry points
+// ============================================================================
+
+fn write_impl(pandoc: &Pandoc, buf: &mut dyn Write, ctx: &mut RstWriterContext) -> io::Result<()> {
+    ctx.note_definitions = NoteDefinitions::collect(&pandoc.blocks);
+    let mut need_blank = false;
+    if let Some(title) = pandoc.meta.get("title").and_then(|t| t.as_plain_text()) {
+        let rule = "=".repeat(title.chars().count());
+        writeln!(buf, "{}\n{}\n{}", rule, title, rule)?;
+        need_blank = true;
+    }
+    if !pandoc.blocks.is_empty() {
+        if need_blank {
+            writeln!(buf)?;
+        }
+        write_blocks(&pandoc.blocks, buf, ctx)?;
+        need_blank = true;
+    }
+
+    // Notes may themselves contain notes, so drain until no new ones appear
+    let mut next = 0;
+    while next < ctx.notes.len() {
+        if need_blank {
+            writeln!(buf)?;
+        }
+        let content = ctx.notes[next].clone();
+        next += 1;
+        let label = format!(".. [{}] ", next);
+        let mut note_writer = LinePrefixWriter::new(&mut *buf, label, INDENT);
+        write_blocks(&content, &mut note_writer, ctx)?;
+        need_blank = true;
+    }
+    Ok(())
+}
+
+/// Write a Pandoc document as reStructuredText.
+///
+/// Returns the warnings for nodes that could not be represented in RST.
+/// IO errors are returned as `Err`.
+pub fn write<T: Write>(
+    pandoc: &Pandoc,
+    buf: &mut T,
+) -> Result<Vec<DiagnosticMessage>, Vec<DiagnosticMessage>> {
+    let mut ctx = RstWriterContext::new();
+    if let Err(e) = write_impl(pandoc, buf, &mut ctx) {
+        return Err(vec![
+            DiagnosticMessageBuilder::error("IO error during write")
+                .with_code("Q-3-1")
+                .problem(format!("Failed to write RST output: {}", e))
+                .build(),
+        ]);
+    }
+    Ok(ctx.into_diagnostics())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn qmd_to_rst(input: &str) -> (String, Vec<DiagnosticMessage>) {
+        let (pandoc, _context, _warnings) = crate::readers::qmd::read(
+            input.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .expect("Failed to parse QMD");
+        let mut buf = Vec::new();
+        let diagnostics = write(&pandoc, &mut buf).expect("Failed to write RST");
+        (String::from_utf8(buf).unwrap(), diagnostics)
+    }
+
+    #[test]
+    fn test_title_from_metadata() {
+        let (out, _) = qmd_to_rst("---\ntitle: Hello\n---\n\nBody\n");
+        assert_eq!(out, "=====\nHello\n=====\n\nBody\n");
+    }
+
+    #[test]
+    fn test_headers_use_underline_adornment() {
+        let (out, _) = qmd_to_rst("# One\n\n## Two\n");
+        assert_eq!(out, "One\n===\n\nTwo\n---\n");
+    }
+
+    #[test]
+    fn test_header_id_becomes_target() {
+        let (out, _) = qmd_to_rst("# Intro {#sec-intro}\n");
+        assert_eq!(out, ".. _sec-intro:\n\nIntro\n=====\n");
+    }
+
+    #[test]
+    fn test_inline_markup() {
+        let (out, _) = qmd_to_rst("*a* **b** `c` $x^2$\n");
+        assert_eq!(out, "*a* **b** ``c`` :math:`x^2`\n");
+    }
+
+    #[test]
+    fn test_link() {
+        let (out, _) = qmd_to_rst("[Quarto](https://quarto.org)\n");
+        assert_eq!(out, "`Quarto <https://quarto.org>`__\n");
+    }
+
+    #[test]
+    fn test_code_block() {
+        let (out, _) = qmd_to_rst("```python\nx = 1\n\ny = 2\n```\n");
+        assert_eq!(out, ".. code-block:: python\n\n   x = 1\n\n   y = 2\n");
+    }
+
+    #[test]
+    fn test_nested_list() {
+        let (out, _) = qmd_to_rst("* a\n\n    1. b\n\n* c\n");
+        assert_eq!(out, "- a\n\n  1. b\n\n- c\n");
+    }
+
+    #[test]
+    fn test_list_table() {
+        let (out, _) = qmd_to_rst("| a | b |\n|---|---|\n| 1 | 2 |\n");
+        assert_eq!(
+            out,
+            ".. list-table::\n   :header-rows: 1\n\n   * - a\n     - b\n   * - 1\n     - 2\n"
+        );
+    }
+
+    #[test]
+    fn test_div_with_class_becomes_container() {
+        let (out, _) = qmd_to_rst("::: {.aside}\n\nText\n\n:::\n");
+        assert_eq!(out, ".. container:: aside\n\n   Text\n");
+    }
+
+    #[test]
+    fn test_raw_html_block_becomes_raw_directive() {
+        let (out, _) = qmd_to_rst("```{=html}\n<br>\n```\n");
+        assert_eq!(out, ".. raw:: html\n\n   <br>\n");
+    }
+
+    #[test]
+    fn test_inline_note_becomes_numbered_footnote() {
+        let (out, _) = qmd_to_rst("Text^[A note.]\n");
+        assert_eq!(out, "Text [1]_\n\n.. [1] A note.\n");
+    }
+
+    #[test]
+    fn test_inline_notes_and_definitions_share_numbers() {
+        let (out, diagnostics) = qmd_to_rst("One^[Inline.] two[^1] again[^1].\n\n[^1]: First.\n");
+        assert_eq!(
+            out,
+            "One [1]_ two [2]_ again [2]_.\n\n.. [1] Inline.\n\n.. [2] First.\n"
+        );
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_block_quote_after_indented_block() {
+        let (out, _) = qmd_to_rst("```python\nx = 1\n```\n\n> Quoted\n");
+        assert_eq!(
+            out,
+            ".. code-block:: python\n\n   x = 1\n\n..\n\n   Quoted\n"
+        );
+
+        let (out, _) = qmd_to_rst("Text\n\n> Quoted\n");
+        assert_eq!(out, "Text\n\n   Quoted\n");
+    }
+
+    #[test]
+    fn test_special_characters_escaped() {
+        let (out, _) = qmd_to_rst("a\\*b and snake\\_case\n");
+        assert_eq!(out, "a\\*b and snake\\_case\n");
+    }
+}
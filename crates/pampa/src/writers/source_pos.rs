@@ -0,0 +1,56 @@
+/*
+ * source_pos.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Source positions for writer output.
+ */
+
+//! Source positions written into output (`data-pos` in HTML).
+//!
+//! A position has the form `file:line:col-line:col`, with 1-based lines and
+//! columns, resolved from a node's [`SourceInfo`] through the document's
+//! source context. Unlike `data-loc`, which is resolved through a JSON round
+//! trip and names files by id, positions name files by path, so a preview
+//! can map a click straight back to the editor. Since paths may contain
+//! `:`, consumers should split positions from the right.
+
+use crate::pandoc::ASTContext;
+use quarto_source_map::SourceInfo;
+
+/// The `file:line:col-line:col` position of `source_info`, or `None` for
+/// nodes that don't map to a source file (e.g. nodes created by filters).
+pub fn source_pos(source_info: &SourceInfo, context: &ASTContext) -> Option<String> {
+    let (start, end) = source_info.map_range(0, source_info.length(), &context.source_context)?;
+    let file = context.source_context.get_file(start.file_id)?;
+    Some(format!(
+        "{}:{}:{}-{}:{}",
+        file.path,
+        start.location.row + 1,
+        start.location.column + 1,
+        end.location.row + 1,
+        end.location.column + 1
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_source_map::FileId;
+
+    #[test]
+    fn test_source_pos() {
+        let mut context = ASTContext::new();
+        let file_id = context.source_context.add_file(
+            "doc.qmd".to_string(),
+            Some("# Hi\n\nSome text\n".to_string()),
+        );
+        let source_info = SourceInfo::original(file_id, 6, 15);
+        assert_eq!(
+            source_pos(&source_info, &context).as_deref(),
+            Some("doc.qmd:3:1-3:10")
+        );
+
+        let unknown = SourceInfo::original(FileId(42), 0, 1);
+        assert_eq!(source_pos(&unknown, &context), None);
+    }
+}
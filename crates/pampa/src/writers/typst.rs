@@ -0,0 +1,971 @@
+/*
+ * typst.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Typst writer for Pandoc AST.
+//!
+//! Writes the document body only; page setup and the title block are
+//! supplied by the template that embeds it, as with `pandoc -t typst`
+//! without `--standalone`.
+//!
+//! # Design decisions
+//!
+//! - Headers use `=` markup; the `unnumbered` class selects
+//!   `#heading(numbering: none)`. Ids become `<label>`s, so `[text](#id)`
+//!   links are written as `#link(<id>)`.
+//! - Soft breaks are written as spaces, so every paragraph is on one line
+//!   and only its start needs escaping against list and heading markup.
+//! - Math is passed through between `$`s unchanged. Simple expressions are
+//!   valid Typst math; TeX commands are not converted.
+//! - Tables are written as `#table` calls (wrapped in `#figure` when they
+//!   have a caption or id); cell spans become `table.cell` arguments.
+//! - Divs are unwrapped, or wrapped in `#block` when they have an id.
+//! - RawBlock/RawInline in `typst` is written verbatim; other formats are
+//!   dropped with a warning.
+//! - Inline notes and note references become `#footnote`s in place.
+//! - Citations without rendered content become `#cite(<id>)`.
+//!
+//! [`write_with_line_map`] also records where each top-level block starts
+//! in the output, so Typst compile errors can be mapped back to the source.
+
+use crate::pandoc::block::{
+    BulletList, CodeBlock, DefinitionList, Div, Figure, Header, OrderedList, RawBlock,
+};
+use crate::pandoc::inline::{Image, Inline, Inlines};
+use crate::pandoc::list::{ListNumberDelim, ListNumberStyle};
+use crate::pandoc::table::{Alignment, Cell, ColWidth, Row, Table};
+use crate::pandoc::{Block, Blocks, Pandoc};
+use crate::writers::incremental::block_source_info;
+use crate::writers::notes::{NoteDefinitions, ResolvedNote, note_reference_id};
+use crate::writers::raw::{TYPST_FORMATS, is_native_format};
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_source_map::SourceInfo;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+/// Context for the Typst writer, threaded through all write functions.
+pub struct TypstWriterContext {
+    /// Warnings for nodes that have no Typst representation.
+    diagnostics: Vec<DiagnosticMessage>,
+
+    /// Note definitions, written in place of their references.
+    notes: NoteDefinitions,
+}
+
+impl TypstWriterContext {
+    pub fn new() -> Self {
+        Self {
+            diagnostics: Vec::new(),
+            notes: NoteDefinitions::default(),
+        }
+    }
+
+    pub fn into_diagnostics(self) -> Vec<DiagnosticMessage> {
+        self.diagnostics
+    }
+
+    pub fn diagnostics(&self) -> &[DiagnosticMessage] {
+        &self.diagnostics
+    }
+
+    fn warn_dropped_node(&mut self, description: &str, source_info: &SourceInfo) {
+        let diag = DiagnosticMessageBuilder::warning(format!(
+            "Node dropped in Typst output: {}",
+            description
+        ))
+        .with_location(source_info.clone())
+        .build();
+        self.diagnostics.push(diag);
+    }
+}
+
+impl Default for TypstWriterContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where the top-level blocks of a document start in Typst output.
+///
+/// Lines are 1-based, as in Typst diagnostics.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LineMap {
+    /// `(first output line, source)` pairs in output order.
+    entries: Vec<(usize, SourceInfo)>,
+}
+
+impl LineMap {
+    /// The source of the block that contains output `line`.
+    pub fn lookup(&self, line: usize) -> Option<&SourceInfo> {
+        let index = self.entries.partition_point(|(start, _)| *start <= line);
+        index.checked_sub(1).map(|i| &self.entries[i].1)
+    }
+
+    /// Shift all lines by `lines`, for output embedded after that many lines
+    /// of other content (e.g. a template preamble).
+    pub fn shifted(mut self, lines: usize) -> Self {
+        for (start, _) in &mut self.entries {
+            *start += lines;
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// ============================================================================
+// Escaping helpers
+// ============================================================================
+
+/// Escape Typst markup characters in text.
+pub fn escape_typst(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' | '#' | '$' | '*' | '_' | '`' | '<' | '>' | '@' | '[' | ']' | '~' | '"' | '/' => {
+                result.push('\\');
+                result.push(ch);
+            }
+            '\u{a0}' => result.push('~'),
+            _ => result.push(ch),
+        }
+    }
+    result
+}
+
+/// Escape markup that only has meaning at the start of a line (headings,
+/// list items, enumerations).
+fn escape_line_start(line: String) -> String {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if line.starts_with(['=', '-', '+']) {
+        format!("\\{}", line)
+    } else if digits > 0 && line[digits..].starts_with('.') {
+        format!("{}\\{}", &line[..digits], &line[digits..])
+    } else {
+        line
+    }
+}
+
+/// A Typst string literal.
+fn string_literal(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    for ch in text.chars() {
+        match ch {
+            '"' | '\\' => {
+                result.push('\\');
+                result.push(ch);
+            }
+            '\n' => result.push_str("\\n"),
+            _ => result.push(ch),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Sanitize an identifier for use as a `<label>`.
+fn label(id: &str) -> String {
+    id.chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+        .collect()
+}
+
+// ============================================================================
+// Inline writing
+// ============================================================================
+
+fn write_inlines(
+    inlines: &[Inline],
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    for inline in inlines {
+        write_inline(inline, buf, ctx)?;
+    }
+    Ok(())
+}
+
+/// Render inlines to a string (for arguments that need the text first).
+fn inlines_to_typst(inlines: &[Inline], ctx: &mut TypstWriterContext) -> io::Result<String> {
+    let mut scratch = Vec::new();
+    write_inlines(inlines, &mut scratch, ctx)?;
+    Ok(String::from_utf8_lossy(&scratch).into_owned())
+}
+
+fn write_function(
+    function: &str,
+    content: &[Inline],
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    write!(buf, "#{}[", function)?;
+    write_inlines(content, buf, ctx)?;
+    write!(buf, "]")
+}
+
+/// A CSS-style length as a Typst length, if Typst has an equivalent unit.
+fn typst_length(value: &str) -> Option<String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    match unit {
+        "%" | "pt" | "mm" | "cm" | "in" | "em" => Some(format!("{}{}", number, unit)),
+        // CSS pixels are 3/4 of a point
+        "px" | "" => Some(format!("{}pt", number * 0.75)),
+        _ => None,
+    }
+}
+
+fn write_image(image: &Image, buf: &mut dyn Write) -> io::Result<()> {
+    write!(buf, "#image({}", string_literal(&image.target.0))?;
+    for key in ["width", "height"] {
+        if let Some(length) = image.attr.2.get(key).and_then(|v| typst_length(v)) {
+            write!(buf, ", {}: {}", key, length)?;
+        }
+    }
+    write!(buf, ")")
+}
+
+fn write_inline(
+    inline: &Inline,
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    match inline {
+        Inline::Str(s) => write!(buf, "{}", escape_typst(&s.text))?,
+        Inline::Space(_) | Inline::SoftBreak(_) => write!(buf, " ")?,
+        Inline::LineBreak(_) => write!(buf, "#linebreak()")?,
+
+        Inline::Emph(node) => write_function("emph", &node.content, buf, ctx)?,
+        Inline::Strong(node) => write_function("strong", &node.content, buf, ctx)?,
+        Inline::Underline(node) => write_function("underline", &node.content, buf, ctx)?,
+        Inline::SmallCaps(node) => write_function("smallcaps", &node.content, buf, ctx)?,
+        Inline::Strikeout(node) => write_function("strike", &node.content, buf, ctx)?,
+        Inline::Delete(node) => write_function("strike", &node.content, buf, ctx)?,
+        Inline::Superscript(node) => write_function("super", &node.content, buf, ctx)?,
+        Inline::Subscript(node) => write_function("sub", &node.content, buf, ctx)?,
+        Inline::Highlight(node) => write_function("highlight", &node.content, buf, ctx)?,
+
+        // Unwrap: keep content, drop markup without a standard function
+        Inline::Insert(node) => write_inlines(&node.content, buf, ctx)?,
+        Inline::Span(span) => match note_reference_id(span) {
+            // The reader turns note references into empty spans
+            Some(id) => write_note_reference(id, &span.source_info, buf, ctx)?,
+            None if span.attr.0.is_empty() => write_inlines(&span.content, buf, ctx)?,
+            None => {
+                write_function("box", &span.content, buf, ctx)?;
+                write!(buf, "<{}>", label(&span.attr.0))?;
+            }
+        },
+
+        // Typst turns straight quotes into typographic ones
+        Inline::Quoted(quoted) => {
+            let quote = match quoted.quote_type {
+                crate::pandoc::QuoteType::SingleQuote => "'",
+                crate::pandoc::QuoteType::DoubleQuote => "\"",
+            };
+            write!(buf, "{}", quote)?;
+            write_inlines(&quoted.content, buf, ctx)?;
+            write!(buf, "{}", quote)?;
+        }
+
+        Inline::Code(code) => {
+            if code.text.contains('`') {
+                write!(buf, "#raw({})", string_literal(&code.text))?;
+            } else {
+                write!(buf, "`{}`", code.text)?;
+            }
+        }
+        Inline::Math(math) => match math.math_type {
+            crate::pandoc::MathType::InlineMath => write!(buf, "${}$", math.text.trim())?,
+            crate::pandoc::MathType::DisplayMath => write!(buf, "$ {} $", math.text.trim())?,
+        },
+
+        Inline::Link(link) => {
+            let url = &link.target.0;
+            if let Some(anchor) = url.strip_prefix('#') {
+                write!(buf, "#link(<{}>)[", label(anchor))?;
+                write_inlines(&link.content, buf, ctx)?;
+                write!(buf, "]")?;
+            } else if crate::writers::plaintext::inlines_to_string(&link.content).0 == *url {
+                write!(buf, "#link({})", string_literal(url))?;
+            } else {
+                write!(buf, "#link({})[", string_literal(url))?;
+                write_inlines(&link.content, buf, ctx)?;
+                write!(buf, "]")?;
+            }
+        }
+        Inline::Image(image) => write_image(image, buf)?,
+
+        Inline::Cite(cite) => {
+            if !cite.content.is_empty() {
+                write_inlines(&cite.content, buf, ctx)?;
+            } else {
+                for citation in &cite.citations {
+                    write!(buf, "#cite(<{}>)", label(&citation.id))?;
+                }
+            }
+        }
+
+        Inline::Note(note) => write_footnote(&note.content, buf, ctx)?,
+        Inline::NoteReference(noteref) => {
+            write_note_reference(&noteref.id, &noteref.source_info, buf, ctx)?
+        }
+
+        Inline::RawInline(raw) => {
+            if is_native_format(&raw.format, TYPST_FORMATS) {
+                write!(buf, "{}", raw.text)?;
+            } else {
+                ctx.warn_dropped_node(
+                    &format!("RawInline with format '{}'", raw.format),
+                    &raw.source_info,
+                );
+            }
+        }
+
+        Inline::Attr(_, _) => {}
+        Inline::EditComment(comment) => {
+            ctx.warn_dropped_node("EditComment", &comment.source_info);
+        }
+        Inline::Shortcode(shortcode) => {
+            ctx.warn_dropped_node(
+                &format!("Shortcode '{}'", shortcode.name),
+                &shortcode.source_info,
+            );
+        }
+        Inline::Custom(custom) => {
+            ctx.warn_dropped_node(
+                &format!("Custom inline ({})", custom.type_name),
+                &custom.source_info,
+            );
+        }
+    }
+    Ok(())
+}
+
+fn write_footnote(
+    content: &[Block],
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    let body = blocks_to_typst(content, ctx)?;
+    write!(buf, "#footnote[{}]", body.trim_end())
+}
+
+/// Write the footnote a note reference refers to, or drop the reference when
+/// there is no definition with its id.
+///
+/// A reference inside the note it refers to (directly or through other
+/// notes) is written as the superscript note id instead.
+fn write_note_reference(
+    id: &str,
+    source_info: &SourceInfo,
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    match ctx.notes.enter(id) {
+        ResolvedNote::Content(content) => {
+            let result = write_footnote(&content, buf, ctx);
+            ctx.notes.leave();
+            result
+        }
+        ResolvedNote::Cycle => {
+            ctx.warn_dropped_node(
+                &format!("NoteReference inside note '{}' itself", id),
+                source_info,
+            );
+            write!(buf, "#super[{}]", escape_typst(id))
+        }
+        ResolvedNote::Missing => {
+            ctx.warn_dropped_node(
+                &format!("NoteReference without definition '{}'", id),
+                source_info,
+            );
+            Ok(())
+        }
+    }
+}
+
+// ============================================================================
+// Block writing
+// ============================================================================
+
+/// Write blocks separated by blank lines.
+fn write_blocks(
+    blocks: &[Block],
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    let mut wrote_any = false;
+    for block in blocks {
+        let mut scratch = Vec::new();
+        write_block(block, &mut scratch, ctx)?;
+        if scratch.is_empty() {
+            continue;
+        }
+        if wrote_any {
+            writeln!(buf)?;
+        }
+        buf.write_all(&scratch)?;
+        wrote_any = true;
+    }
+    Ok(())
+}
+
+fn blocks_to_typst(blocks: &[Block], ctx: &mut TypstWriterContext) -> io::Result<String> {
+    let mut scratch = Vec::new();
+    write_blocks(blocks, &mut scratch, ctx)?;
+    Ok(String::from_utf8_lossy(&scratch).into_owned())
+}
+
+/// Write a paragraph's inlines on one line.
+fn write_text_block(
+    inlines: &[Inline],
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    let text = inlines_to_typst(inlines, ctx)?;
+    writeln!(buf, "{}", escape_line_start(text))
+}
+
+fn write_header(
+    header: &Header,
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    let title = inlines_to_typst(&header.content, ctx)?;
+    if header.attr.1.iter().any(|c| c == "unnumbered") {
+        write!(
+            buf,
+            "#heading(level: {}, numbering: none)[{}]",
+            header.level, title
+        )?;
+    } else {
+        write!(buf, "{} {}", "=".repeat(header.level.max(1)), title)?;
+    }
+    if !header.attr.0.is_empty() {
+        write!(buf, " <{}>", label(&header.attr.0))?;
+    }
+    writeln!(buf)
+}
+
+fn write_codeblock(codeblock: &CodeBlock, buf: &mut dyn Write) -> io::Result<()> {
+    // The fence must be longer than any backtick run in the code
+    let longest_run = codeblock
+        .text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    let lang = codeblock.attr.1.first().map_or("", String::as_str);
+    writeln!(buf, "{}{}", fence, lang)?;
+    write!(buf, "{}", codeblock.text)?;
+    if !codeblock.text.is_empty() && !codeblock.text.ends_with('\n') {
+        writeln!(buf)?;
+    }
+    writeln!(buf, "{}", fence)
+}
+
+fn write_rawblock(
+    rawblock: &RawBlock,
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    if !is_native_format(&rawblock.format, TYPST_FORMATS) {
+        ctx.warn_dropped_node(
+            &format!("RawBlock with format '{}'", rawblock.format),
+            &rawblock.source_info,
+        );
+        return Ok(());
+    }
+    write!(buf, "{}", rawblock.text)?;
+    if !rawblock.text.ends_with('\n') {
+        writeln!(buf)?;
+    }
+    Ok(())
+}
+
+/// A list is tight if every item is a single Plain block.
+fn is_tight_list(items: &[Blocks]) -> bool {
+    items
+        .iter()
+        .all(|item| item.len() == 1 && matches!(item[0], Block::Plain(_)))
+}
+
+/// Write list items with `marker`, indenting continuation lines. Loose
+/// lists separate items with blank lines.
+fn write_list_items(
+    marker: &str,
+    items: &[Blocks],
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    let tight = is_tight_list(items);
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 && !tight {
+            writeln!(buf)?;
+        }
+        let body = blocks_to_typst(item, ctx)?;
+        writeln!(
+            buf,
+            "{} {}",
+            marker,
+            body.trim_end()
+                .replace('\n', "\n  ")
+                .replace("\n  \n", "\n\n")
+        )?;
+    }
+    Ok(())
+}
+
+fn write_bulletlist(
+    bulletlist: &BulletList,
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    write_list_items("-", &bulletlist.content, buf, ctx)
+}
+
+/// The `numbering` pattern for an ordered list, if not the default.
+fn enum_numbering(style: &ListNumberStyle, delim: &ListNumberDelim) -> Option<String> {
+    let counter = match style {
+        ListNumberStyle::LowerRoman => "i",
+        ListNumberStyle::UpperRoman => "I",
+        ListNumberStyle::LowerAlpha => "a",
+        ListNumberStyle::UpperAlpha => "A",
+        _ => "1",
+    };
+    let pattern = match delim {
+        ListNumberDelim::OneParen => format!("{})", counter),
+        ListNumberDelim::TwoParens => format!("({})", counter),
+        _ => format!("{}.", counter),
+    };
+    (pattern != "1.").then_some(pattern)
+}
+
+fn write_orderedlist(
+    orderedlist: &OrderedList,
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    let (start, style, delim) = &orderedlist.attr;
+    let numbering = enum_numbering(style, delim);
+    if *start <= 1 && numbering.is_none() {
+        return write_list_items("+", &orderedlist.content, buf, ctx);
+    }
+
+    let mut args = Vec::new();
+    if let Some(numbering) = numbering {
+        args.push(format!("numbering: {}", string_literal(&numbering)));
+    }
+    if *start > 1 {
+        args.push(format!("start: {}", start));
+    }
+    writeln!(buf, "#block[")?;
+    writeln!(buf, "#set enum({})", args.join(", "))?;
+    write_list_items("+", &orderedlist.content, buf, ctx)?;
+    writeln!(buf, "]")
+}
+
+fn write_definitionlist(
+    deflist: &DefinitionList,
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    for (term, definitions) in &deflist.content {
+        let term = inlines_to_typst(term, ctx)?;
+        let mut bodies = Vec::with_capacity(definitions.len());
+        for definition in definitions {
+            bodies.push(blocks_to_typst(definition, ctx)?.trim_end().to_string());
+        }
+        writeln!(
+            buf,
+            "/ {}: {}",
+            term,
+            bodies
+                .join("\n\n")
+                .replace('\n', "\n  ")
+                .replace("\n  \n", "\n\n")
+        )?;
+    }
+    Ok(())
+}
+
+fn write_div(div: &Div, buf: &mut dyn Write, ctx: &mut TypstWriterContext) -> io::Result<()> {
+    if div.attr.0.is_empty() {
+        return write_blocks(&div.content, buf, ctx);
+    }
+    writeln!(buf, "#block[")?;
+    write_blocks(&div.content, buf, ctx)?;
+    writeln!(buf, "] <{}>", label(&div.attr.0))
+}
+
+/// Write a `caption: [...]` argument, if the caption is not empty.
+fn caption_argument(
+    caption: &Option<Blocks>,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<Option<String>> {
+    match caption {
+        Some(blocks) if !blocks.is_empty() => {
+            let text = blocks_to_typst(blocks, ctx)?;
+            Ok(Some(format!("caption: [{}]", text.trim_end())))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn write_figure(
+    figure: &Figure,
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<()> {
+    let content = blocks_to_typst(&figure.content, ctx)?;
+    writeln!(buf, "#figure([")?;
+    write!(buf, "{}", content)?;
+    write!(buf, "]")?;
+    if let Some(caption) = caption_argument(&figure.caption.long, ctx)? {
+        write!(buf, ", {}", caption)?;
+    }
+    write!(buf, ")")?;
+    if !figure.attr.0.is_empty() {
+        write!(buf, " <{}>", label(&figure.attr.0))?;
+    }
+    writeln!(buf)
+}
+
+/// Render a table cell as a content block (with `table.cell` for spans).
+fn cell_to_typst(cell: &Cell, ctx: &mut TypstWriterContext) -> io::Result<String> {
+    let body = blocks_to_typst(&cell.content, ctx)?;
+    let content = format!("[{}]", body.trim_end());
+    let mut spans = Vec::new();
+    if cell.col_span > 1 {
+        spans.push(format!("colspan: {}", cell.col_span));
+    }
+    if cell.row_span > 1 {
+        spans.push(format!("rowspan: {}", cell.row_span));
+    }
+    if spans.is_empty() {
+        Ok(content)
+    } else {
+        Ok(format!("table.cell({}){}", spans.join(", "), content))
+    }
+}
+
+fn rows_to_typst(rows: &[Row], ctx: &mut TypstWriterContext) -> io::Result<Vec<String>> {
+    let mut lines = Vec::with_capacity(rows.len());
+    for row in rows {
+        let cells = row
+            .cells
+            .iter()
+            .map(|cell| cell_to_typst(cell, ctx))
+            .collect::<io::Result<Vec<_>>>()?;
+        lines.push(cells.join(", "));
+    }
+    Ok(lines)
+}
+
+fn write_table(table: &Table, buf: &mut dyn Write, ctx: &mut TypstWriterContext) -> io::Result<()> {
+    if table.colspec.is_empty() {
+        return Ok(());
+    }
+
+    let columns: Vec<String> = table
+        .colspec
+        .iter()
+        .map(|(_, width)| match width {
+            ColWidth::Percentage(pct) => format!("{}%", (pct * 100.0).round()),
+            ColWidth::Default => "auto".to_string(),
+        })
+        .collect();
+    let aligns: Vec<&str> = table
+        .colspec
+        .iter()
+        .map(|(alignment, _)| match alignment {
+            Alignment::Center => "center",
+            Alignment::Right => "right",
+            _ => "left",
+        })
+        .collect();
+
+    let mut args = vec![
+        format!("columns: ({},)", columns.join(", ")),
+        format!("align: ({},)", aligns.join(", ")),
+    ];
+    let head = rows_to_typst(&table.head.rows, ctx)?;
+    if !head.is_empty() {
+        args.push(format!("table.header({})", head.join(",\n    ")));
+    }
+    for body in &table.bodies {
+        args.extend(rows_to_typst(&body.head, ctx)?);
+        args.extend(rows_to_typst(&body.body, ctx)?);
+    }
+    let foot = rows_to_typst(&table.foot.rows, ctx)?;
+    if !foot.is_empty() {
+        args.push(format!("table.footer({})", foot.join(",\n    ")));
+    }
+    let table_call = format!("table(\n  {},\n)", args.join(",\n  "));
+
+    let caption = caption_argument(&table.caption.long, ctx)?;
+    if caption.is_none() && table.attr.0.is_empty() {
+        return writeln!(buf, "#{}", table_call);
+    }
+    write!(buf, "#figure({}, kind: table", table_call)?;
+    if let Some(caption) = caption {
+        write!(buf, ", {}", caption)?;
+    }
+    write!(buf, ")")?;
+    if !table.attr.0.is_empty() {
+        write!(buf, " <{}>", label(&table.attr.0))?;
+    }
+    writeln!(buf)
+}
+
+fn write_block(block: &Block, buf: &mut dyn Write, ctx: &mut TypstWriterContext) -> io::Result<()> {
+    match block {
+        Block::Plain(plain) => write_text_block(&plain.content, buf, ctx)?,
+        Block::Paragraph(para) => write_text_block(&para.content, buf, ctx)?,
+        Block::Header(header) => write_header(header, buf, ctx)?,
+        Block::CodeBlock(codeblock) => write_codeblock(codeblock, buf)?,
+        Block::RawBlock(rawblock) => write_rawblock(rawblock, buf, ctx)?,
+        Block::BlockQuote(blockquote) => {
+            writeln!(buf, "#quote(block: true)[")?;
+            write_blocks(&blockquote.content, buf, ctx)?;
+            writeln!(buf, "]")?;
+        }
+        Block::LineBlock(lineblock) => {
+            let lines = lineblock
+                .content
+                .iter()
+                .map(|line| inlines_to_typst(line, ctx).map(escape_line_start))
+                .collect::<io::Result<Vec<_>>>()?;
+            writeln!(buf, "{}", lines.join(" \\\n"))?;
+        }
+        Block::BulletList(bulletlist) => write_bulletlist(bulletlist, buf, ctx)?,
+        Block::OrderedList(orderedlist) => write_orderedlist(orderedlist, buf, ctx)?,
+        Block::DefinitionList(deflist) => write_definitionlist(deflist, buf, ctx)?,
+        Block::HorizontalRule(_) => writeln!(buf, "#line(length: 100%)")?,
+        Block::Table(table) => write_table(table, buf, ctx)?,
+        Block::Figure(figure) => write_figure(figure, buf, ctx)?,
+        Block::Div(div) => write_div(div, buf, ctx)?,
+        // Written as footnotes at their references
+        Block::NoteDefinitionPara(_) | Block::NoteDefinitionFencedBlock(_) => {}
+        Block::BlockMetadata(meta) => {
+            ctx.warn_dropped_node("BlockMetadata", &meta.source_info);
+        }
+        Block::CaptionBlock(caption) => {
+            ctx.warn_dropped_node("CaptionBlock", &caption.source_info);
+        }
+        Block::Custom(custom) => {
+            ctx.warn_dropped_node(
+                &format!("Custom block ({})", custom.type_name),
+                &custom.source_info,
+            );
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Entry points
+// ============================================================================
+
+/// Write the top-level blocks like [`write_blocks`], recording the line each
+/// one starts on.
+fn write_impl(
+    pandoc: &Pandoc,
+    buf: &mut dyn Write,
+    ctx: &mut TypstWriterContext,
+) -> io::Result<LineMap> {
+    ctx.notes = NoteDefinitions::collect(&pandoc.blocks);
+    let mut line_map = LineMap::default();
+    let mut line = 1;
+    for block in &pandoc.blocks {
+        let mut scratch = Vec::new();
+        write_block(block, &mut scratch, ctx)?;
+        if scratch.is_empty() {
+            continue;
+        }
+        if !line_map.is_empty() {
+            writeln!(buf)?;
+            line += 1;
+        }
+        line_map
+            .entries
+            .push((line, block_source_info(block).clone()));
+        buf.write_all(&scratch)?;
+        line += scratch.split(|&b| b == b'\n').count() - 1;
+    }
+    Ok(line_map)
+}
+
+/// Write the body of a Pandoc document as Typst.
+///
+/// Returns the warnings for nodes that could not be represented in Typst.
+/// IO errors are returned as `Err`.
+pub fn write<T: Write>(
+    pandoc: &Pandoc,
+    buf: &mut T,
+) -> Result<Vec<DiagnosticMessage>, Vec<DiagnosticMessage>> {
+    write_with_line_map(pandoc, buf).map(|(diagnostics, _)| diagnostics)
+}
+
+/// Write the body of a Pandoc document as Typst, along with where each
+/// top-level block starts in the output.
+pub fn write_with_line_map<T: Write>(
+    pandoc: &Pandoc,
+    buf: &mut T,
+) -> Result<(Vec<DiagnosticMessage>, LineMap), Vec<DiagnosticMessage>> {
+    let mut ctx = TypstWriterContext::new();
+    match write_impl(pandoc, buf, &mut ctx) {
+        Ok(line_map) => Ok((ctx.into_diagnostics(), line_map)),
+        Err(e) => Err(vec![
+            DiagnosticMessageBuilder::error("IO error during write")
+                .with_code("Q-3-1")
+                .problem(format!("Failed to write Typst output: {}", e))
+                .build(),
+        ]),
+    }
+}
+
+/// Render inlines (e.g. a metadata title) as Typst.
+pub fn inlines_to_string(inlines: &Inlines) -> (String, Vec<DiagnosticMessage>) {
+    let mut ctx = TypstWriterContext::new();
+    let text = inlines_to_typst(inlines, &mut ctx).unwrap_or_default();
+    (text, ctx.into_diagnostics())
+}
+
+/// Render blocks (e.g. a metadata abstract) as Typst.
+pub fn blocks_to_string(blocks: &[Block]) -> (String, Vec<DiagnosticMessage>) {
+    let mut ctx = TypstWriterContext::new();
+    ctx.notes = NoteDefinitions::collect(blocks);
+    let text = blocks_to_typst(blocks, &mut ctx).unwrap_or_default();
+    (text, ctx.into_diagnostics())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(input: &str) -> Pandoc {
+        let (pandoc, _context, _warnings) = crate::readers::qmd::read(
+            input.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .expect("Failed to parse QMD");
+        pandoc
+    }
+
+    fn qmd_to_typst(input: &str) -> (String, Vec<DiagnosticMessage>) {
+        let mut buf = Vec::new();
+        let diagnostics = write(&read(input), &mut buf).expect("Failed to write Typst");
+        (String::from_utf8(buf).unwrap(), diagnostics)
+    }
+
+    #[test]
+    fn test_escape_typst() {
+        assert_eq!(escape_typst("a #b $c @d"), "a \\#b \\$c \\@d");
+        assert_eq!(escape_line_start("- x".to_string()), "\\- x");
+        assert_eq!(escape_line_start("12. x".to_string()), "12\\. x");
+        assert_eq!(escape_line_start("12 x".to_string()), "12 x");
+    }
+
+    #[test]
+    fn test_headers_and_labels() {
+        let (out, _) = qmd_to_typst("# Intro {#sec-intro}\n\n## More {.unnumbered}\n");
+        assert_eq!(
+            out,
+            "= Intro <sec-intro>\n\n#heading(level: 2, numbering: none)[More] <more>\n"
+        );
+    }
+
+    #[test]
+    fn test_inline_markup() {
+        let (out, _) = qmd_to_typst("*a* **b** `c_d` $x^2$\nnext\n");
+        assert_eq!(out, "#emph[a] #strong[b] `c_d` $x^2$ next\n");
+    }
+
+    #[test]
+    fn test_links() {
+        let (out, _) = qmd_to_typst("[Quarto](https://quarto.org) [see](#sec-a)\n");
+        assert_eq!(
+            out,
+            "#link(\"https://quarto.org\")[Quarto] #link(<sec-a>)[see]\n"
+        );
+    }
+
+    #[test]
+    fn test_code_block() {
+        let (out, _) = qmd_to_typst("```python\nx = `1`\n```\n");
+        assert_eq!(out, "```python\nx = `1`\n```\n");
+    }
+
+    #[test]
+    fn test_lists() {
+        let (out, _) = qmd_to_typst("* a\n* b\n");
+        assert_eq!(out, "- a\n- b\n");
+        let (out, _) = qmd_to_typst("3. a\n4. b\n");
+        assert_eq!(out, "#block[\n#set enum(start: 3)\n+ a\n+ b\n]\n");
+    }
+
+    #[test]
+    fn test_table() {
+        let (out, _) = qmd_to_typst("| a | b |\n|---|--:|\n| 1 | 2 |\n");
+        assert_eq!(
+            out,
+            "#table(\n  columns: (auto, auto,),\n  align: (left, right,),\n  table.header([a], [b]),\n  [1], [2],\n)\n"
+        );
+    }
+
+    #[test]
+    fn test_raw_blocks() {
+        let (out, diagnostics) =
+            qmd_to_typst("```{=typst}\n#pagebreak()\n```\n\n```{=html}\n<br>\n```\n");
+        assert_eq!(out, "#pagebreak()\n");
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_footnotes() {
+        let (out, _) = qmd_to_typst("Text^[A note.] and[^1].\n\n[^1]: Another.\n");
+        assert_eq!(out, "Text#footnote[A note.] and#footnote[Another.].\n");
+    }
+
+    #[test]
+    fn test_self_referencing_notes() {
+        let (out, diagnostics) = qmd_to_typst("A[^a].\n\n[^a]: See[^b].\n\n[^b]: Back[^a].\n");
+        assert_eq!(out, "A#footnote[See#footnote[Back#super[a].].].\n");
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_line_map() {
+        let pandoc = read("# One\n\nSome\ntext\n\n```\na\nb\n```\n\nEnd\n");
+        let mut buf = Vec::new();
+        let (_, line_map) = write_with_line_map(&pandoc, &mut buf).unwrap();
+        let out = String::from_utf8(buf).unwrap();
+        assert_eq!(out, "= One <one>\n\nSome text\n\n```\na\nb\n```\n\nEnd\n");
+
+        let source = |i: usize| Some(block_source_info(&pandoc.blocks[i]));
+        assert_eq!(line_map.lookup(1), source(0));
+        assert_eq!(line_map.lookup(3), source(1));
+        // Lines inside a block map to the block
+        assert_eq!(line_map.lookup(7), source(2));
+        assert_eq!(line_map.lookup(10), source(3));
+
+        let shifted = line_map.shifted(5);
+        assert_eq!(shifted.lookup(5), None);
+        assert_eq!(shifted.lookup(6), source(0));
+    }
+}
@@ -1210,17 +1210,11 @@ fn sugar_roundtrip_list_table_with_header() {
 
 // --- Definition-list sugar/desugar roundtrips ---
 
-// NOTE: Definition-list sugar/desugar roundtrip is LOSSY. The writer produces
-// Pandoc-native definition list syntax ("term\n:   definition\n") but the reader
-// only recognizes the `::: {.definition-list}` div syntax. This means:
-//   - KeepBefore (verbatim copy): works correctly (preserves original div syntax)
-//   - Rewrite: broken — writer output doesn't parse back to DefinitionList
-// This is a pre-existing writer bug, not specific to the incremental writer.
-// These tests are ignored until the writer is fixed to produce div syntax for
-// definition lists (or the reader is extended to parse Pandoc-native syntax).
+// The writer emits definition lists in the same `::: {.definition-list}` div
+// syntax the reader desugars, so a rewritten DefinitionList parses back to an
+// equivalent node.
 
 #[test]
-#[ignore = "definition-list sugar roundtrip is lossy: writer produces Pandoc-native syntax, reader expects div syntax"]
 fn sugar_roundtrip_definition_list_basic() {
     assert_sugar_roundtrip(
         "::: {.definition-list}\n* term one\n  - definition one\n* term two\n  - definition two\n\n:::\n",
@@ -1228,13 +1222,61 @@ fn sugar_roundtrip_definition_list_basic() {
 }
 
 #[test]
-#[ignore = "definition-list sugar roundtrip is lossy: writer produces Pandoc-native syntax, reader expects div syntax"]
 fn sugar_roundtrip_definition_list_multiple_defs() {
     assert_sugar_roundtrip(
         "::: {.definition-list}\n* term\n  - definition a\n  - definition b\n\n:::\n",
     );
 }
 
+#[test]
+fn sugar_roundtrip_definition_list_multi_block_defs() {
+    assert_sugar_roundtrip(
+        "::: {.definition-list}\n* term\n  - first paragraph\n\n    second paragraph\n\n  - other definition\n\n:::\n",
+    );
+}
+
+#[test]
+fn sugar_roundtrip_definition_list_nested() {
+    assert_sugar_roundtrip(
+        "::: {.definition-list}\n* outer\n  - outer definition\n\n    ::: {.definition-list}\n    * inner\n      - inner definition\n\n    :::\n\n:::\n",
+    );
+}
+
+/// Generate a definition-list div with 1-3 terms, each with 1-3 definitions.
+/// Definitions are single paragraphs in tight lists; with `loose`, they may
+/// span several paragraphs.
+fn gen_definition_list_block() -> BoxedStrategy<String> {
+    let definition = prop::collection::vec(gen_paragraph_text(), 1..3);
+    let term = (
+        gen_paragraph_text(),
+        prop::collection::vec(definition, 1..4),
+    );
+    (prop::collection::vec(term, 1..4), any::<bool>())
+        .prop_map(|(terms, loose)| {
+            let mut out = String::from("::: {.definition-list}\n");
+            for (term, definitions) in terms {
+                out.push_str(&format!("* {}\n", term));
+                for paragraphs in definitions {
+                    if loose {
+                        out.push_str(&format!("  - {}\n\n", paragraphs.join("\n\n    ")));
+                    } else {
+                        out.push_str(&format!("  - {}\n", paragraphs[0]));
+                    }
+                }
+            }
+            out.push_str("\n:::\n");
+            out
+        })
+        .boxed()
+}
+
+proptest! {
+    #[test]
+    fn proptest_sugar_roundtrip_definition_list(qmd in gen_definition_list_block()) {
+        assert_sugar_roundtrip(&qmd);
+    }
+}
+
 // --- Idempotence of incremental writer with sugared constructs ---
 
 #[test]
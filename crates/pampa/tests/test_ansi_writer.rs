@@ -1139,28 +1139,49 @@ fn test_color_parsing_ansi_dash() {
 }
 
 // ============================================================================
-// Error Case Tests (Unsupported Blocks)
+// Code Block Tests
 // ============================================================================
 
-#[test]
-fn test_unsupported_codeblock() {
+fn codeblock_doc(classes: Vec<String>, text: &str) -> Pandoc {
     use pampa::pandoc::CodeBlock;
 
-    let pandoc = Pandoc {
+    Pandoc {
         meta: Default::default(),
         blocks: vec![Block::CodeBlock(CodeBlock {
-            attr: empty_attr(),
-            text: "code".to_string(),
+            attr: (String::new(), classes, LinkedHashMap::new()),
+            text: text.to_string(),
             source_info: empty_source(),
             attr_source: empty_attr_source(),
         })],
-    };
+    }
+}
 
-    let mut buf = Vec::new();
-    let result = writers::ansi::write(&pandoc, &mut buf);
+#[test]
+fn test_codeblock_without_language() {
+    let output = write_ansi(&codeblock_doc(vec![], "line one\nline two"));
+    assert_eq!(output, "  line one\n  line two\n");
+}
+
+#[test]
+fn test_codeblock_highlighted() {
+    let pandoc = codeblock_doc(vec!["python".to_string()], "def f():\n    return 1");
+    let output = write_ansi(&pandoc);
+    assert!(output.contains('\x1b'), "{:?}", output);
+    assert!(output.contains("def"));
+    assert!(output.contains("return"));
+}
 
-    // Should return an error for unsupported block
-    assert!(result.is_err());
+#[test]
+fn test_codeblock_without_colors() {
+    let config = writers::ansi::AnsiConfig {
+        colors: false,
+        ..Default::default()
+    };
+    let pandoc = codeblock_doc(vec!["python".to_string()], "def f():\n    return 1");
+    let mut buf = Vec::new();
+    writers::ansi::write_with_config(&pandoc, &mut buf, &config).unwrap();
+    let output = String::from_utf8(buf).unwrap();
+    assert_eq!(output, "  def f():\n      return 1\n");
 }
 
 // Note: Table test removed due to complex struct initialization
@@ -205,3 +205,86 @@ fn test_info_string_without_braces() {
     assert_eq!(classes, vec!["python"]);
     assert!(attrs.is_empty());
 }
+
+#[test]
+fn test_cell_options_become_attributes() {
+    // `#|` option lines are parsed into attributes and kept in the code
+    let input = "```{r}\n#| label: fig-cars\n#| fig-cap: \"Speed and distance\"\n#| echo: false\nplot(cars)\n```\n";
+    let (id, classes, attrs) = parse_code_block_attrs(input);
+
+    assert_eq!(id, "");
+    assert_eq!(classes, vec!["{r}"]);
+    assert_eq!(
+        attrs,
+        vec![
+            ("label".to_string(), "fig-cars".to_string()),
+            ("fig-cap".to_string(), "Speed and distance".to_string()),
+            ("echo".to_string(), "false".to_string()),
+        ]
+    );
+
+    let result = parse_qmd(input);
+    let Block::CodeBlock(cb) = &result.blocks[0] else {
+        panic!("expected CodeBlock");
+    };
+    assert!(cb.text.starts_with("#| label: fig-cars\n"));
+}
+
+#[test]
+fn test_cell_option_source_locations() {
+    let input = "```{python}\n#| echo: false\n#| fig-cap: A plot\nx = 1\n```\n";
+    let (pandoc, context, warnings) = readers::qmd::read(
+        input.as_bytes(),
+        false,
+        "test.qmd",
+        &mut std::io::sink(),
+        true,
+        None,
+    )
+    .expect("Failed to parse QMD");
+    assert!(warnings.is_empty());
+
+    let Block::CodeBlock(cb) = &pandoc.blocks[0] else {
+        panic!("expected CodeBlock");
+    };
+    let (key, value) = &cb.attr_source.attributes[1];
+    let key = key
+        .as_ref()
+        .unwrap()
+        .map_offset(0, &context.source_context)
+        .unwrap();
+    let value = value
+        .as_ref()
+        .unwrap()
+        .map_offset(0, &context.source_context)
+        .unwrap();
+    assert_eq!((key.location.row, key.location.column), (2, 3));
+    assert_eq!((value.location.row, value.location.column), (2, 12));
+}
+
+#[test]
+fn test_invalid_cell_option_warns() {
+    let input = "```{python}\n#| echo: sometimes\nx = 1\n```\n";
+    let (_pandoc, _context, warnings) = readers::qmd::read(
+        input.as_bytes(),
+        false,
+        "test.qmd",
+        &mut std::io::sink(),
+        true,
+        None,
+    )
+    .expect("Failed to parse QMD");
+
+    let codes: Vec<_> = warnings.iter().filter_map(|w| w.code.as_deref()).collect();
+    assert_eq!(codes, vec!["Q-2-37"]);
+}
+
+#[test]
+fn test_cell_options_roundtrip_through_qmd_writer() {
+    // Options stay in the `#|` lines, not in the header
+    let input = "```{r}\n#| echo: false\n1 + 1\n```\n";
+    let pandoc = parse_qmd(input);
+    let mut buf = Vec::new();
+    pampa::writers::qmd::write(&pandoc, &mut buf).expect("Failed to write QMD");
+    assert_eq!(String::from_utf8(buf).unwrap(), input);
+}
@@ -391,3 +391,54 @@ fn test_error_corpus_json_snapshots() {
         }
     }
 }
+
+/// Applying the fixes attached to parse errors gives a document that parses.
+#[test]
+fn test_error_fixes_resolve_errors() {
+    let cases = [
+        ("Text *emph\n", "Text *emph*\n"),
+        ("Some **strong\n", "Some **strong**\n"),
+        ("Unfinished _emph.\n", "Unfinished _emph._\n"),
+        ("d'`code` here\n", "d\\'`code` here\n"),
+    ];
+    for (input, expected) in cases {
+        let diagnostics = pampa::readers::qmd::read(
+            input.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .expect_err("input has a parse error");
+        let mut edits: Vec<_> = diagnostics
+            .iter()
+            .flat_map(|diagnostic| &diagnostic.fixes)
+            .flat_map(|fix| &fix.edits)
+            .collect();
+        assert!(!edits.is_empty(), "no fix for {:?}", input);
+
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.location.start_offset()));
+        let mut fixed = input.to_string();
+        for edit in edits {
+            fixed.replace_range(
+                edit.location.start_offset()..edit.location.end_offset(),
+                &edit.new_text,
+            );
+        }
+        assert_eq!(fixed, expected);
+        assert!(
+            pampa::readers::qmd::read(
+                fixed.as_bytes(),
+                false,
+                "test.qmd",
+                &mut std::io::sink(),
+                true,
+                None,
+            )
+            .is_ok(),
+            "fixed input {:?} doesn't parse",
+            fixed
+        );
+    }
+}
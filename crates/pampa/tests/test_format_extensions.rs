@@ -0,0 +1,255 @@
+/*
+ * test_format_extensions.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Tests for markdown extensions toggled through `FormatExtensions`
+ * (e.g. `markdown+task_lists-raw_html`) in the qmd reader and writer.
+ *
+ * Run with: cargo test --test test_format_extensions
+ */
+
+use pampa::options::{FormatExtensions, parse_format_string};
+use pampa::pandoc::ASTContext;
+use pampa::readers::qmd::{QmdReaderOptions, read_with_options};
+use pampa::writers;
+use pampa::writers::qmd::{QmdWriterOptions, write_with_options};
+
+fn extensions(spec: &str) -> FormatExtensions {
+    FormatExtensions::from_diff(&parse_format_string(spec).extensions)
+}
+
+fn read(input: &str, spec: &str) -> pampa::pandoc::Pandoc {
+    let (pandoc, _context, _warnings) = read_with_options(
+        input.as_bytes(),
+        false,
+        "<test>",
+        &mut std::io::sink(),
+        true,
+        None,
+        &QmdReaderOptions {
+            extensions: extensions(spec),
+        },
+    )
+    .expect("Failed to parse input");
+    pandoc
+}
+
+fn to_native(input: &str, spec: &str) -> String {
+    let mut buf = Vec::new();
+    writers::native::write(&read(input, spec), &ASTContext::anonymous(), &mut buf).unwrap();
+    String::from_utf8(buf).expect("Invalid UTF-8 in output")
+}
+
+fn roundtrip(input: &str, spec: &str) -> String {
+    let options = QmdWriterOptions {
+        extensions: extensions(spec),
+        ..Default::default()
+    };
+    let mut buf = Vec::new();
+    write_with_options(&read(input, spec), &mut buf, &options).unwrap();
+    String::from_utf8(buf).expect("Invalid UTF-8 in output")
+}
+
+#[test]
+fn test_default_options_use_default_extensions() {
+    assert_eq!(
+        QmdReaderOptions::default().extensions,
+        FormatExtensions::default()
+    );
+    assert_eq!(
+        QmdWriterOptions::default().extensions,
+        FormatExtensions::default()
+    );
+}
+
+#[test]
+fn test_smart_quotes_dashes_ellipses() {
+    let result = to_native("\"hi\" it's 1--2 --- wait ...\n", "markdown+smart");
+    assert!(result.contains("Quoted DoubleQuote"), "{}", result);
+    assert!(result.contains("it\u{2019}s"), "{}", result);
+    assert!(result.contains('\u{2013}'), "{}", result);
+    assert!(result.contains('\u{2014}'), "{}", result);
+    assert!(result.contains('\u{2026}'), "{}", result);
+}
+
+#[test]
+fn test_no_smart_keeps_ascii_dashes() {
+    // Off by default; quotes are still read as Quoted
+    let result = to_native("\"hi\" it's 1--2 --- wait ...\n", "markdown");
+    assert!(result.contains("Quoted DoubleQuote"), "{}", result);
+    assert!(result.contains("1--2"), "{}", result);
+    assert!(result.contains("\"---\""), "{}", result);
+    assert!(!result.contains('\u{2013}'), "{}", result);
+    assert!(!result.contains('\u{2014}'), "{}", result);
+    assert!(!result.contains('\u{2026}'), "{}", result);
+}
+
+#[test]
+fn test_smart_inside_words() {
+    let result = to_native("1--2, 1990---2000, wait...\n", "markdown+smart");
+    assert!(result.contains("1\u{2013}2"), "{}", result);
+    assert!(result.contains("1990\u{2014}2000"), "{}", result);
+    assert!(result.contains("wait\u{2026}"), "{}", result);
+}
+
+#[test]
+fn test_smart_escaped_sequences_stay_literal() {
+    let input = "1\\-\\-2 and \\.\\.\\.\n";
+    let result = to_native(input, "markdown+smart");
+    assert!(result.contains("Str \"1--2\""), "{}", result);
+    assert!(result.contains("Str \"...\""), "{}", result);
+
+    // The writer escapes them again, so they survive another read
+    let once = roundtrip(input, "markdown+smart");
+    assert_eq!(to_native(&once, "markdown+smart"), result);
+}
+
+#[test]
+fn test_smart_roundtrip_is_stable() {
+    let input = "\"hi\" it's 'quoted' --- wait...\n";
+    let once = roundtrip(input, "markdown+smart");
+    assert_eq!(roundtrip(&once, "markdown+smart"), once);
+    assert!(!once.contains('\u{2014}'), "{}", once);
+}
+
+#[test]
+fn test_no_smart_roundtrip_is_stable() {
+    let input = "\"hi\" it's 'quoted' --- wait...\n";
+    let once = roundtrip(input, "markdown");
+    assert_eq!(roundtrip(&once, "markdown"), once);
+}
+
+#[test]
+fn test_implicit_figures() {
+    let input = "![A caption](image.png)\n";
+    assert!(to_native(input, "markdown").contains("Figure"));
+    let result = to_native(input, "markdown-implicit_figures");
+    assert!(!result.contains("Figure"), "{}", result);
+    assert!(result.contains("Para [Image"), "{}", result);
+}
+
+#[test]
+fn test_task_lists() {
+    let input = "* [ ] todo\n* [x] done\n";
+    let result = to_native(input, "markdown+task_lists");
+    assert!(result.contains("Str \"\u{2610}\""), "{}", result);
+    assert!(result.contains("Str \"\u{2612}\""), "{}", result);
+
+    // Off by default: the markers stay bracketed spans
+    let result = to_native(input, "markdown");
+    assert!(!result.contains('\u{2610}'), "{}", result);
+    assert!(result.contains("Span"), "{}", result);
+}
+
+#[test]
+fn test_task_lists_roundtrip() {
+    let input = "* [ ] todo\n* [x] done\n";
+    let once = roundtrip(input, "markdown+task_lists");
+    assert!(!once.contains('\u{2610}'), "{}", once);
+    assert_eq!(
+        to_native(&once, "markdown+task_lists"),
+        to_native(input, "markdown+task_lists")
+    );
+}
+
+#[test]
+fn test_footnotes() {
+    let input = "Hi[^1].\n\n[^1]: A note.\n";
+    let result = to_native(input, "markdown-footnotes");
+    assert!(!result.contains("Note"), "{}", result);
+    assert!(result.contains("[^1]"), "{}", result);
+}
+
+#[test]
+fn test_raw_html() {
+    let input = "Some <b>bold</b> text.\n";
+    assert!(to_native(input, "markdown").contains("RawInline"));
+    let result = to_native(input, "markdown-raw_html");
+    assert!(!result.contains("RawInline"), "{}", result);
+    assert!(result.contains("<b>"), "{}", result);
+}
+
+#[test]
+fn test_tex_math_dollars() {
+    let input = "Euler: $e^{i\\pi}$.\n";
+    assert!(to_native(input, "markdown").contains("Math InlineMath"));
+    let result = to_native(input, "markdown-tex_math_dollars");
+    assert!(!result.contains("Math"), "{}", result);
+    assert!(result.contains("$e^{i\\\\pi}$"), "{}", result);
+}
+
+#[test]
+fn test_emoji() {
+    let input = "Shipped :tada: and :rocket:, see :unknown: at 10:30.\n";
+    let result = to_native(input, "markdown+emoji");
+    assert!(result.contains("\u{1F389}"), "{}", result);
+    assert!(result.contains("\u{1F680}"), "{}", result);
+    assert!(result.contains(":unknown:"), "{}", result);
+    assert!(result.contains("10:30"), "{}", result);
+    let result = to_native(input, "markdown");
+    assert!(result.contains(":tada:"), "{}", result);
+}
+
+#[test]
+fn test_emoji_not_replaced_in_code() {
+    let result = to_native("Use `:smile:` for :smile:.\n", "markdown+emoji");
+    assert!(result.contains("Code"), "{}", result);
+    assert!(result.contains(":smile:"), "{}", result);
+    assert!(result.contains("\u{1F604}"), "{}", result);
+}
+
+#[test]
+fn test_mentions() {
+    let result = to_native("Thanks @alice for the review.\n", "markdown+mentions");
+    assert!(!result.contains("Cite"), "{}", result);
+    assert!(result.contains("\"mention\""), "{}", result);
+    assert!(result.contains("Str \"@alice\""), "{}", result);
+    let result = to_native("Thanks @alice for the review.\n", "markdown");
+    assert!(result.contains("Cite"), "{}", result);
+}
+
+#[test]
+fn test_mentions_keep_bracketed_citations() {
+    let result = to_native("As shown [@knuth84], thanks @bob.\n", "markdown+mentions");
+    assert!(result.contains("NormalCitation"), "{}", result);
+    assert!(result.contains("knuth84"), "{}", result);
+    assert!(result.contains("Str \"@bob\""), "{}", result);
+}
+
+#[test]
+fn test_mentions_roundtrip() {
+    let input = "Thanks @alice for the review.\n";
+    assert_eq!(roundtrip(input, "markdown+mentions"), input);
+}
+
+#[test]
+fn test_mentions_need_word_boundary() {
+    let result = to_native("Mail a@b.com or @carol.\n", "markdown+mentions");
+    assert!(result.contains("Str \"a@b.com\""), "{}", result);
+    assert_eq!(result.matches("\"mention\"").count(), 1, "{}", result);
+    assert!(result.contains("Str \"@carol\""), "{}", result);
+}
+
+#[test]
+fn test_mentions_reread_is_stable() {
+    let input = "Thanks @alice, and [@bob]{.mention}.\n";
+    let first = to_native(input, "markdown+mentions");
+    assert_eq!(first.matches("\"mention\"").count(), 2, "{}", first);
+
+    // Written back with or without the extension, the mentions read the same
+    for writer_spec in ["markdown+mentions", "markdown"] {
+        let options = QmdWriterOptions {
+            extensions: extensions(writer_spec),
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        write_with_options(&read(input, "markdown+mentions"), &mut buf, &options).unwrap();
+        let written = String::from_utf8(buf).unwrap();
+        assert_eq!(
+            to_native(&written, "markdown+mentions"),
+            first,
+            "{}",
+            written
+        );
+    }
+}
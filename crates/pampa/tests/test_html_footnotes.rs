@@ -0,0 +1,134 @@
+/*
+ * test_html_footnotes.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Tests for footnote numbering, backlinks and the footnotes section
+ * in the HTML writer.
+ */
+
+use pampa::pandoc::{ASTContext, treesitter_to_pandoc};
+use pampa::utils::diagnostic_collector::DiagnosticCollector;
+use pampa::writers::html::{write, write_blocks_to};
+use tree_sitter_qmd::MarkdownParser;
+
+fn parse(qmd: &str) -> pampa::pandoc::Pandoc {
+    let input_bytes = qmd.as_bytes();
+    let mut parser = MarkdownParser::default();
+    let tree = parser.parse(input_bytes, None).expect("Failed to parse");
+    let mut error_collector = DiagnosticCollector::new();
+    treesitter_to_pandoc(
+        &mut std::io::sink(),
+        &tree,
+        input_bytes,
+        &ASTContext::anonymous(),
+        &mut error_collector,
+    )
+    .unwrap()
+}
+
+/// Helper to render a QMD document to HTML
+fn render_qmd_to_html(qmd: &str) -> String {
+    let pandoc = parse(qmd);
+    let mut output = Vec::new();
+    write(&pandoc, &ASTContext::anonymous(), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+const SECTION_START: &str = "<section id=\"footnotes\" class=\"footnotes footnotes-end-of-document\" role=\"doc-endnotes\">\n<hr />\n<ol>\n";
+
+fn note_ref(n: usize) -> String {
+    format!(
+        "<a href=\"#fn{0}\" class=\"footnote-ref\" id=\"fnref{0}\" role=\"doc-noteref\"><sup>{0}</sup></a>",
+        n
+    )
+}
+
+fn backlink(n: usize) -> String {
+    format!(
+        "<a href=\"#fnref{}\" class=\"footnote-back\" role=\"doc-backlink\">\u{21A9}\u{FE0E}</a>",
+        n
+    )
+}
+
+#[test]
+fn test_inline_note() {
+    let html = render_qmd_to_html("Text^[A note.] here.\n");
+    assert_eq!(
+        html,
+        format!(
+            "<p>Text{} here.</p>\n{}<li id=\"fn1\"><p>A note.{}</p></li>\n</ol>\n</section>\n",
+            note_ref(1),
+            SECTION_START,
+            backlink(1)
+        )
+    );
+}
+
+#[test]
+fn test_notes_are_numbered_in_reference_order() {
+    let html = render_qmd_to_html(
+        "One[^b] and two[^a] and three^[Inline.]\n\n[^a]: Note a.\n\n[^b]: Note b.\n",
+    );
+    assert!(html.contains(&note_ref(1)), "{}", html);
+    assert!(html.contains(&note_ref(3)), "{}", html);
+    assert!(html.contains(&format!(
+        "<li id=\"fn1\"><p>Note b.{}</p></li>",
+        backlink(1)
+    )));
+    assert!(html.contains(&format!(
+        "<li id=\"fn2\"><p>Note a.{}</p></li>",
+        backlink(2)
+    )));
+    assert!(html.contains(&format!(
+        "<li id=\"fn3\"><p>Inline.{}</p></li>",
+        backlink(3)
+    )));
+    // Definitions are not rendered in place
+    assert_eq!(html.matches("Note a.").count(), 1, "{}", html);
+}
+
+#[test]
+fn test_multi_paragraph_note() {
+    let html = render_qmd_to_html(
+        "Text[^long].\n\n::: ^long\nFirst paragraph.\n\nSecond paragraph.\n:::\n",
+    );
+    assert!(
+        html.contains(&format!(
+            "<li id=\"fn1\"><p>First paragraph.</p>\n<p>Second paragraph.{}</p></li>",
+            backlink(1)
+        )),
+        "{}",
+        html
+    );
+}
+
+#[test]
+fn test_note_ending_in_list_gets_backlink_paragraph() {
+    let html = render_qmd_to_html("Text[^list].\n\n::: ^list\nItems:\n\n- one\n- two\n:::\n");
+    assert!(
+        html.contains(&format!("</ul>\n<p>{}</p></li>", backlink(1))),
+        "{}",
+        html
+    );
+}
+
+#[test]
+fn test_unresolved_reference() {
+    let html = render_qmd_to_html("Text[^missing].\n");
+    assert_eq!(html, "<p>Text[^missing].</p>\n");
+}
+
+#[test]
+fn test_no_section_without_notes() {
+    let html = render_qmd_to_html("Just text.\n");
+    assert!(!html.contains("footnotes"), "{}", html);
+}
+
+#[test]
+fn test_fragments_do_not_write_section() {
+    let pandoc = parse("Text^[A note.]\n");
+    let mut output = Vec::new();
+    write_blocks_to(&pandoc.blocks, &mut output).unwrap();
+    let html = String::from_utf8(output).unwrap();
+    assert_eq!(html, format!("<p>Text{}</p>\n", note_ref(1)));
+}
@@ -0,0 +1,133 @@
+/*
+ * test_incremental_reparse.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Tests for re-reading qmd documents after text edits, reusing the
+ * previous tree-sitter tree.
+ */
+
+use pampa::pandoc::ASTContext;
+use pampa::readers::qmd::{
+    IncrementalRead, QmdReaderOptions, input_edit, read_with_tree, reparse_with_edit,
+};
+use pampa::writers;
+
+fn to_native(read: &IncrementalRead) -> String {
+    let (pandoc, _context, _warnings) = read.result.as_ref().expect("Failed to read input");
+    let mut buf = Vec::new();
+    writers::native::write(pandoc, &ASTContext::anonymous(), &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+fn read(input: &str) -> IncrementalRead {
+    read_with_tree(
+        input.as_bytes(),
+        "<test>",
+        &mut std::io::sink(),
+        true,
+        &QmdReaderOptions::default(),
+    )
+}
+
+/// Replace `old` (which must occur once in `input`) with `new`, re-reading
+/// incrementally. Returns the new text and the incremental read.
+fn edit(
+    previous: &IncrementalRead,
+    input: &str,
+    old: &str,
+    new: &str,
+) -> (String, IncrementalRead) {
+    let start = input.find(old).expect("edit target not found");
+    let range = start..start + old.len();
+    let mut edited = input.to_string();
+    edited.replace_range(range.clone(), new);
+    let reread = reparse_with_edit(
+        previous,
+        &input_edit(input.as_bytes(), range, new.as_bytes()),
+        edited.as_bytes(),
+        "<test>",
+        &mut std::io::sink(),
+        true,
+        &QmdReaderOptions::default(),
+    );
+    (edited, reread)
+}
+
+#[test]
+fn test_input_edit_positions() {
+    let edit = input_edit(b"ab\ncd\nef\n", 4..7, b"XY\nZ");
+    assert_eq!(edit.start_byte, 4);
+    assert_eq!(edit.old_end_byte, 7);
+    assert_eq!(edit.new_end_byte, 8);
+    assert_eq!(
+        (edit.start_position.row, edit.start_position.column),
+        (1, 1)
+    );
+    assert_eq!(
+        (edit.old_end_position.row, edit.old_end_position.column),
+        (2, 1)
+    );
+    assert_eq!(
+        (edit.new_end_position.row, edit.new_end_position.column),
+        (2, 1)
+    );
+}
+
+#[test]
+fn test_reparse_matches_full_read() {
+    let input = "# Title\n\nFirst paragraph.\n\n- one\n- two\n\nLast *words*.\n";
+    let initial = read(input);
+
+    let (edited, reread) = edit(&initial, input, "First", "The **first**");
+    assert_eq!(to_native(&reread), to_native(&read(&edited)));
+
+    // Edits can be chained using the returned tree
+    let (edited, reread) = edit(&reread, &edited, "- two\n", "- two\n- three\n");
+    assert_eq!(to_native(&reread), to_native(&read(&edited)));
+    assert!(to_native(&reread).contains("three"));
+
+    let (edited, reread) = edit(&reread, &edited, "# Title\n\n", "");
+    assert_eq!(to_native(&reread), to_native(&read(&edited)));
+    assert!(!to_native(&reread).contains("Header"));
+}
+
+#[test]
+fn test_reparse_without_trailing_newline() {
+    let input = "Some text";
+    let initial = read(input);
+    let (edited, reread) = edit(&initial, input, "text", "more text");
+    assert_eq!(to_native(&reread), to_native(&read(&edited)));
+}
+
+#[test]
+fn test_reparse_reports_errors_and_recovers() {
+    let input = "Some text.\n";
+    let initial = read(input);
+
+    // An unclosed span is a parse error; the tree is still returned
+    let (broken, reread) = edit(&initial, input, "text.", "[text.");
+    assert!(reread.result.is_err());
+    assert!(read(&broken).result.is_err());
+
+    // Fixing the error using the tree from the failed read works
+    let (fixed, reread) = edit(&reread, &broken, "[text.", "[text].");
+    assert_eq!(to_native(&reread), to_native(&read(&fixed)));
+    assert!(to_native(&reread).contains("Span"));
+}
+
+#[test]
+fn test_reparse_creating_and_removing_tables() {
+    let input = "Intro.\n\n+---+---+\n| a | b |\n+---+---+\n\nEnd.\n";
+    let initial = read(input);
+    assert!(to_native(&initial).contains("Table"));
+
+    // Breaking the table's border turns it back into a paragraph
+    let (edited, reread) = edit(&initial, input, "+---+---+\n\n", "+---+\n\n");
+    assert_eq!(to_native(&reread), to_native(&read(&edited)));
+    assert!(!to_native(&reread).contains("Table"));
+
+    // An edit elsewhere that completes a table changes how its lines parse
+    let (edited, reread) = edit(&reread, &edited, "+---+\n\n", "+---+---+\n\n");
+    assert_eq!(to_native(&reread), to_native(&read(&edited)));
+    assert!(to_native(&reread).contains("Table"));
+}
@@ -0,0 +1,134 @@
+/*
+ * test_qmd_writer_options.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Tests for the layout options of the qmd writer: wrap width, bullet
+ * markers and compact pipe tables.
+ *
+ * Run with: cargo test --test test_qmd_writer_options
+ */
+
+use pampa::readers;
+use pampa::writers::qmd::{BulletMarker, QmdWriterOptions, write_with_options};
+
+fn write(input: &str, options: &QmdWriterOptions) -> String {
+    let (pandoc, _context, _warnings) = readers::qmd::read(
+        input.as_bytes(),
+        false,
+        "<test>",
+        &mut std::io::sink(),
+        true,
+        None,
+    )
+    .expect("Failed to parse input");
+    let mut buf = Vec::new();
+    write_with_options(&pandoc, &mut buf, options).unwrap();
+    String::from_utf8(buf).expect("Invalid UTF-8 in output")
+}
+
+fn wrapped(input: &str, width: usize) -> String {
+    let options = QmdWriterOptions {
+        wrap_width: Some(width),
+        ..Default::default()
+    };
+    write(input, &options)
+}
+
+#[test]
+fn test_default_keeps_line_breaks() {
+    let input = "A short\nparagraph with a\nbreak.\n";
+    assert_eq!(write(input, &QmdWriterOptions::default()), input);
+}
+
+#[test]
+fn test_wrap_fills_lines() {
+    let input = "The quick brown fox\njumps over the lazy dog and keeps running.\n";
+    assert_eq!(
+        wrapped(input, 20),
+        "The quick brown fox\njumps over the lazy\ndog and keeps\nrunning.\n"
+    );
+}
+
+#[test]
+fn test_wrap_joins_short_lines() {
+    let input = "One\ntwo\nthree *four five*.\n";
+    assert_eq!(wrapped(input, 72), "One two three *four five*.\n");
+}
+
+#[test]
+fn test_wrap_keeps_hard_breaks() {
+    let input = "First line\\\nsecond line\n";
+    assert_eq!(wrapped(input, 72), input);
+}
+
+#[test]
+fn test_wrap_never_starts_a_block() {
+    // Breaking before `-` or `1.` would start a list
+    let output = wrapped("aaaa bbbb - cccc 1. dddd\n", 9);
+    assert_eq!(output, "aaaa bbbb -\ncccc 1.\ndddd\n");
+}
+
+#[test]
+fn test_wrap_list_items_and_block_quotes() {
+    // The width doesn't count the `* ` and `> ` prefixes
+    let input = "* one two three four\n\n> five six seven eight\n";
+    assert_eq!(
+        wrapped(input, 10),
+        "* one two\n  three four\n\n> five six\n> seven\n> eight\n"
+    );
+}
+
+#[test]
+fn test_wrap_roundtrips() {
+    let input = "`Code span`, some *emphasized text*, a [link with words](https://example.com) and\nmore words after a soft break.\n";
+    let once = wrapped(input, 30);
+    assert_eq!(wrapped(&once, 30), once);
+    assert!(
+        once.lines()
+            .all(|line| line.chars().count() <= 30 || !line.contains(' ')),
+        "{}",
+        once
+    );
+}
+
+#[test]
+fn test_wrap_keeps_pipe_table_rows() {
+    let input = "| Name | Description |\n|------|-------------|\n| a | some longer text here |\n";
+    let output = wrapped(input, 10);
+    assert_eq!(output.lines().count(), 3, "{}", output);
+}
+
+#[test]
+fn test_bullet_markers() {
+    let input = "* one\n* two\n";
+    for (marker, expected) in [
+        (BulletMarker::Asterisk, "* one\n* two\n"),
+        (BulletMarker::Dash, "- one\n- two\n"),
+        (BulletMarker::Plus, "+ one\n+ two\n"),
+    ] {
+        let options = QmdWriterOptions {
+            bullet_marker: marker,
+            ..Default::default()
+        };
+        assert_eq!(write(input, &options), expected);
+    }
+}
+
+#[test]
+fn test_compact_tables() {
+    let input = "| Name | Value |\n|:-----|------:|\n| alpha | 1 |\n";
+    let padded = write(input, &QmdWriterOptions::default());
+    assert_eq!(
+        padded,
+        "| Name  | Value |\n| :---- | ----: |\n| alpha | 1     |\n"
+    );
+
+    let options = QmdWriterOptions {
+        compact_tables: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        write(input, &options),
+        "| Name | Value |\n| :-- | --: |\n| alpha | 1 |\n"
+    );
+}
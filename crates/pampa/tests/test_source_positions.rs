@@ -0,0 +1,91 @@
+/*
+ * test_source_positions.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Tests for `data-pos` source positions in the HTML and native writers.
+ */
+
+use pampa::pandoc::{ASTContext, Pandoc};
+use pampa::readers::qmd::read;
+use pampa::writers::html::{HtmlConfig, write_document};
+use pampa::writers::native::{self, NativeConfig};
+
+const INPUT: &str = "Hello *world*\n\nSecond paragraph\n";
+
+fn parse(input: &str) -> (Pandoc, ASTContext) {
+    let (pandoc, context, _warnings) = read(
+        input.as_bytes(),
+        false,
+        "doc.qmd",
+        &mut std::io::sink(),
+        true,
+        None,
+    )
+    .expect("Failed to read input");
+    (pandoc, context)
+}
+
+fn to_html(input: &str, config: HtmlConfig) -> String {
+    let (pandoc, context) = parse(input);
+    let mut buf = Vec::new();
+    write_document(&pandoc, &context, &mut buf, config).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+fn to_native(input: &str, config: &NativeConfig) -> String {
+    let (pandoc, context) = parse(input);
+    let mut buf = Vec::new();
+    native::write_with_config(&pandoc, &context, &mut buf, config).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn test_html_data_pos() {
+    let html = to_html(
+        INPUT,
+        HtmlConfig {
+            source_positions: true,
+            ..Default::default()
+        },
+    );
+    assert!(html.contains("<p data-pos=\"doc.qmd:1:1-"), "{}", html);
+    assert!(
+        html.contains("<span data-pos=\"doc.qmd:1:1-1:6\">Hello</span>"),
+        "{}",
+        html
+    );
+    assert!(
+        html.contains("<em data-pos=\"doc.qmd:1:7-1:14\">"),
+        "{}",
+        html
+    );
+    assert!(html.contains("<p data-pos=\"doc.qmd:3:1-"), "{}", html);
+    assert!(!html.contains("data-loc"));
+}
+
+#[test]
+fn test_html_without_data_pos() {
+    let html = to_html(INPUT, HtmlConfig::default());
+    assert!(!html.contains("data-pos"));
+    assert!(html.contains("<p>Hello <em>world</em></p>"), "{}", html);
+}
+
+#[test]
+fn test_native_position_markers() {
+    let output = to_native(
+        INPUT,
+        &NativeConfig {
+            source_positions: true,
+        },
+    );
+    assert!(output.starts_with("[ {- doc.qmd:1:1-"), "{}", output);
+    assert!(
+        output.contains("{- doc.qmd:1:1-1:6 -} Str \"Hello\""),
+        "{}",
+        output
+    );
+    assert!(output.contains("{- doc.qmd:3:1-"), "{}", output);
+
+    // Markers are opt-in
+    assert!(!to_native(INPUT, &NativeConfig::default()).contains("{-"));
+}
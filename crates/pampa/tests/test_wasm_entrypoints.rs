@@ -10,3 +10,61 @@ fn test_wasm_read_entrypoint() {
     // Basic smoke test - just verify we get valid JSON back
     assert!(result.starts_with('{'), "Expected JSON output");
 }
+
+fn convert(input: &str, from: &str, to: &str, options: &str) -> serde_json::Value {
+    let result = pampa::wasm_entry_points::convert(input.as_bytes(), from, to, options);
+    serde_json::from_str(&result).expect("Expected JSON output")
+}
+
+#[test]
+fn test_wasm_convert_targets() {
+    let input = "# Hello\n\nSome *text*.\n";
+
+    let html = convert(input, "qmd", "html", "");
+    assert!(html["output"].as_str().unwrap().contains("<em>text</em>"));
+
+    let plain = convert(input, "qmd", "plaintext", "");
+    assert!(plain["output"].as_str().unwrap().contains("Some text."));
+
+    let json = convert(input, "qmd", "json", "");
+    let qmd = convert(json["output"].as_str().unwrap(), "json", "qmd", "");
+    assert!(qmd["output"].as_str().unwrap().contains("# Hello"));
+}
+
+#[test]
+fn test_wasm_convert_json_source_location() {
+    let input = "Some *text*.\n";
+    let without = convert(input, "qmd", "json", "");
+    let with = convert(input, "qmd", "json", r#"{"json_source_location": "full"}"#);
+    assert!(with["output"].as_str().unwrap().len() > without["output"].as_str().unwrap().len());
+}
+
+#[test]
+fn test_wasm_convert_errors() {
+    let unknown_output = convert("text\n", "qmd", "docx", "");
+    assert!(
+        unknown_output["error"]
+            .as_str()
+            .unwrap()
+            .contains("Unknown output format")
+    );
+
+    let unknown_input = convert("text\n", "rst", "qmd", "");
+    assert!(
+        unknown_input["error"]
+            .as_str()
+            .unwrap()
+            .contains("Unknown input format")
+    );
+
+    let bad_options = convert("text\n", "qmd", "html", r#"{"lose": true}"#);
+    assert!(
+        bad_options["error"]
+            .as_str()
+            .unwrap()
+            .contains("Invalid conversion options")
+    );
+
+    let bad_json = convert("not json", "json", "qmd", "");
+    assert!(bad_json["error"].is_string());
+}
@@ -59,3 +59,7 @@ mod eval;
 pub use error::{Error, Result};
 pub use reference::{DateParts, Name, Reference};
 pub use types::{Citation, CitationItem, Processor};
+
+/// The default CSL style (Chicago Manual of Style, author-date format),
+/// used when a document doesn't name one.
+pub const DEFAULT_STYLE: &str = include_str!("../styles/chicago-author-date.csl");
@@ -6,6 +6,7 @@
 use crate::tag::parse_tag;
 use crate::types::{ConfigMapEntry, ConfigValue, ConfigValueKind, Interpretation, MergeOp};
 use quarto_error_reporting::DiagnosticMessage;
+use quarto_source_map::SourceInfo;
 use quarto_yaml::YamlWithSourceInfo;
 use yaml_rust2::Yaml;
 
@@ -107,6 +108,47 @@ pub fn config_value_from_yaml(
     }
 }
 
+/// Parse a YAML configuration file to a `ConfigValue`.
+///
+/// A file with no content (or only comments) is an empty map. Diagnostics
+/// from tag parsing are added to `diagnostics`.
+pub fn config_value_from_str(
+    content: &str,
+    filename: &str,
+    diagnostics: &mut Vec<DiagnosticMessage>,
+) -> quarto_yaml::Result<ConfigValue> {
+    if is_empty_config(content) {
+        return Ok(ConfigValue::new_map(Vec::new(), Default::default()));
+    }
+    let yaml = quarto_yaml::parse_file(content, filename)?;
+    Ok(config_value_from_yaml(yaml, diagnostics))
+}
+
+/// Parse a YAML configuration file to a `ConfigValue`, with source locations
+/// relative to `parent` (typically the whole file, registered in a
+/// `SourceContext`).
+///
+/// Like [`config_value_from_str`], a file with no content is an empty map.
+pub fn config_value_from_source(
+    content: &str,
+    parent: SourceInfo,
+    diagnostics: &mut Vec<DiagnosticMessage>,
+) -> quarto_yaml::Result<ConfigValue> {
+    if is_empty_config(content) {
+        return Ok(ConfigValue::new_map(Vec::new(), parent));
+    }
+    let yaml = quarto_yaml::parse_with_parent(content, parent)?;
+    Ok(config_value_from_yaml(yaml, diagnostics))
+}
+
+/// Whether a configuration file has only blank lines and comments.
+fn is_empty_config(content: &str) -> bool {
+    content.lines().all(|line| {
+        let line = line.trim();
+        line.is_empty() || line.starts_with('#') || line == "---"
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -400,6 +442,18 @@ format:
         assert!(matches!(files.value, ConfigValueKind::Path(_)));
     }
 
+    #[test]
+    fn test_config_value_from_str() {
+        let mut diagnostics = Vec::new();
+        let config = config_value_from_str("# comment\n", "_quarto.yml", &mut diagnostics).unwrap();
+        assert!(config.is_map() && config.is_empty());
+
+        let config =
+            config_value_from_str("title: Site\n", "_quarto.yml", &mut diagnostics).unwrap();
+        assert_eq!(config.get("title").unwrap().as_str(), Some("Site"));
+        assert!(config_value_from_str("a: [", "_quarto.yml", &mut diagnostics).is_err());
+    }
+
     #[test]
     fn test_map_key_source_tracking() {
         let yaml_content = "name: value";
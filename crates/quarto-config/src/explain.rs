@@ -0,0 +1,453 @@
+//! Explaining where merged configuration values come from.
+//!
+//! A merged value can be the work of several layers: a theme set in
+//! `_quarto.yml` and overridden in front matter, or a list extended by
+//! each layer. [`MergedConfig::explain`] lists every layer that has a value
+//! at a path and what became of it, so tools can answer "why is my option
+//! ignored?":
+//!
+//! ```rust,ignore
+//! let merged = MergedConfig::new(vec![&project, &document]);
+//! let explanation = merged.explain(&["format", "html", "theme"]);
+//! println!("{}", explanation.describe(&source_context));
+//! // format.html.theme
+//! //   _quarto.yml:3: "cosmo" (overridden by layer 1)
+//! //   doc.qmd:3: "darkly" (used)
+//! ```
+//!
+//! [`MergedConfig::diff`] compares two merged configs, e.g. a document's
+//! config with and without a profile.
+
+use quarto_source_map::SourceContext;
+
+use crate::materialize::{MaterializeOptions, materialize_cursor};
+use crate::merged::{MergedConfig, MergedCursor, MergedValue};
+use crate::types::{ConfigError, ConfigValue, ConfigValueKind, MergeOp};
+
+/// The layers that have a value at a path, and what became of each.
+#[derive(Debug, Clone)]
+pub struct ConfigExplanation<'a> {
+    /// The explained path
+    pub path: Vec<String>,
+    /// One entry per layer with a value at the path, lowest priority first
+    pub contributions: Vec<Contribution<'a>>,
+}
+
+/// A layer's value at an explained path.
+#[derive(Debug, Clone)]
+pub struct Contribution<'a> {
+    /// Which layer the value is in (index into layers)
+    pub layer_index: usize,
+    /// The layer's value; its `source_info` gives the file and line
+    pub value: &'a ConfigValue,
+    /// The value's merge operation (`!prefer` or `!concat`)
+    pub merge_op: MergeOp,
+    /// What became of the value
+    pub status: ContributionStatus,
+}
+
+/// What became of a layer's value in the merged config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContributionStatus {
+    /// The value is the merged value, or part of it (concatenated arrays
+    /// and field-wise merged maps)
+    Used,
+    /// A value from a higher-priority layer replaces it
+    Overridden { by: usize },
+    /// A `!prefer` value in a later layer, at this path or an enclosing
+    /// one, discards it
+    Reset { by: usize },
+}
+
+/// A path whose merged value differs between two configs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDifference {
+    /// Path of the value
+    pub path: Vec<String>,
+    /// The value in the first config (`None` if absent)
+    pub before: Option<ConfigValue>,
+    /// The value in the second config (`None` if absent)
+    pub after: Option<ConfigValue>,
+}
+
+impl<'a> MergedConfig<'a> {
+    /// Explain the merged value at `path`: which layers have a value there
+    /// and whether it was used, overridden or reset.
+    pub fn explain(&self, path: &[&str]) -> ConfigExplanation<'a> {
+        let layers = self.layers();
+        let mut contributions: Vec<Contribution<'a>> = layers
+            .iter()
+            .enumerate()
+            .filter_map(|(layer_index, layer)| {
+                let value = layer.get_path(path)?;
+                Some(Contribution {
+                    layer_index,
+                    value,
+                    merge_op: value.merge_op,
+                    status: ContributionStatus::Used,
+                })
+            })
+            .collect();
+
+        // A value hidden by an enclosing value is not used at all
+        if let Some(status) = self.ancestor_status(path) {
+            for contribution in &mut contributions {
+                contribution.status = status;
+            }
+            return ConfigExplanation {
+                path: to_path(path),
+                contributions,
+            };
+        }
+
+        let cursor = self.cursor().at_path(path);
+        let top = contributions.last().map(|c| c.layer_index);
+        match cursor.as_value() {
+            Some(MergedValue::Scalar(scalar)) => {
+                for contribution in &mut contributions {
+                    if contribution.layer_index != scalar.layer_index {
+                        contribution.status = ContributionStatus::Overridden {
+                            by: scalar.layer_index,
+                        };
+                    }
+                }
+            }
+            Some(value @ (MergedValue::Array(_) | MergedValue::Map(_))) => {
+                let is_array = matches!(value, MergedValue::Array(_));
+                let same_kind = |value: &ConfigValue| match &value.value {
+                    ConfigValueKind::Array(_) => is_array,
+                    ConfigValueKind::Map(_) => !is_array,
+                    _ => false,
+                };
+                // Arrays reset at their last `!prefer` array, maps at any
+                // `!prefer` value (see `MergedCursor::keys`)
+                let reset = contributions
+                    .iter()
+                    .rev()
+                    .find(|c| c.merge_op == MergeOp::Prefer && (!is_array || same_kind(c.value)))
+                    .map(|c| c.layer_index);
+                for contribution in &mut contributions {
+                    contribution.status = match (same_kind(contribution.value), reset, top) {
+                        (false, _, Some(top)) => ContributionStatus::Overridden { by: top },
+                        (true, Some(reset), _) if contribution.layer_index < reset => {
+                            ContributionStatus::Reset { by: reset }
+                        }
+                        _ => ContributionStatus::Used,
+                    };
+                }
+            }
+            None => {}
+        }
+
+        ConfigExplanation {
+            path: to_path(path),
+            contributions,
+        }
+    }
+
+    /// The paths whose merged values differ between `self` and `other`.
+    ///
+    /// Maps are compared key by key, so only the changed leaves are
+    /// reported; scalars and arrays are compared by value, ignoring source
+    /// locations. Differences are listed in key order, `self`'s keys first.
+    pub fn diff(&self, other: &MergedConfig<'_>) -> Result<Vec<ConfigDifference>, ConfigError> {
+        let mut differences = Vec::new();
+        diff_cursors(
+            &self.cursor(),
+            &other.cursor(),
+            &MaterializeOptions::default(),
+            &mut differences,
+        )?;
+        Ok(differences)
+    }
+
+    /// The status forced on values at `path` by an enclosing value: a
+    /// scalar or array replacing the enclosing map, or a `!prefer` map
+    /// leaving the key out.
+    fn ancestor_status(&self, path: &[&str]) -> Option<ContributionStatus> {
+        for depth in 0..path.len() {
+            let prefix = &path[..depth];
+            let cursor = self.cursor().at_path(prefix);
+            let top = || {
+                self.layers()
+                    .iter()
+                    .rposition(|layer| layer.get_path(prefix).is_some())
+                    .unwrap_or_default()
+            };
+            match cursor.as_value() {
+                None => return None,
+                Some(MergedValue::Map(map)) => {
+                    if !map.contains_key(path[depth]) {
+                        let reset = self
+                            .layers()
+                            .iter()
+                            .rposition(|layer| {
+                                layer
+                                    .get_path(prefix)
+                                    .is_some_and(|value| value.merge_op == MergeOp::Prefer)
+                            })
+                            .unwrap_or_else(top);
+                        return Some(ContributionStatus::Reset { by: reset });
+                    }
+                }
+                Some(_) => return Some(ContributionStatus::Overridden { by: top() }),
+            }
+        }
+        None
+    }
+}
+
+impl ConfigExplanation<'_> {
+    /// The contribution that supplies the merged value, for scalars.
+    pub fn winner(&self) -> Option<&Contribution<'_>> {
+        let used: Vec<_> = self
+            .contributions
+            .iter()
+            .filter(|c| c.status == ContributionStatus::Used)
+            .collect();
+        match used.as_slice() {
+            [only] => Some(only),
+            _ => None,
+        }
+    }
+
+    /// A human-readable explanation, one line per layer with its file and
+    /// line (resolved through `source_context`), value and status.
+    pub fn describe(&self, source_context: &SourceContext) -> String {
+        let mut lines = vec![self.path.join(".")];
+        if self.contributions.is_empty() {
+            lines.push("  (not set in any layer)".to_string());
+        }
+        for contribution in &self.contributions {
+            let location = contribution
+                .value
+                .source_info
+                .map_offset(0, source_context)
+                .and_then(|mapped| {
+                    let file = source_context.get_file(mapped.file_id)?;
+                    Some(format!("{}:{}", file.path, mapped.location.row + 1))
+                })
+                .unwrap_or_else(|| format!("layer {}", contribution.layer_index));
+            let tag = match contribution.merge_op {
+                MergeOp::Prefer if contribution.value.is_array() || contribution.value.is_map() => {
+                    " !prefer"
+                }
+                _ => "",
+            };
+            let status = match contribution.status {
+                ContributionStatus::Used => "used".to_string(),
+                ContributionStatus::Overridden { by } => format!("overridden by layer {}", by),
+                ContributionStatus::Reset { by } => format!("reset by !prefer in layer {}", by),
+            };
+            lines.push(format!(
+                "  {}:{} {} ({})",
+                location,
+                tag,
+                contribution.value.to_json(),
+                status
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+fn diff_cursors(
+    before: &MergedCursor<'_>,
+    after: &MergedCursor<'_>,
+    options: &MaterializeOptions,
+    differences: &mut Vec<ConfigDifference>,
+) -> Result<(), ConfigError> {
+    match (before.as_value(), after.as_value()) {
+        (None, None) => {}
+        (Some(MergedValue::Map(before_map)), Some(MergedValue::Map(after_map))) => {
+            let mut keys: Vec<&str> = before_map.keys().iter().map(String::as_str).collect();
+            for key in after_map.keys() {
+                if !keys.contains(&key.as_str()) {
+                    keys.push(key);
+                }
+            }
+            for key in keys {
+                diff_cursors(&before.at(key), &after.at(key), options, differences)?;
+            }
+        }
+        (before_value, after_value) => {
+            let path = before.path();
+            let before = match before_value {
+                Some(_) => Some(materialize_cursor(before, path.len(), options, path)?),
+                None => None,
+            };
+            let after = match after_value {
+                Some(_) => Some(materialize_cursor(after, path.len(), options, path)?),
+                None => None,
+            };
+            let same = match (&before, &after) {
+                (Some(before), Some(after)) => before.to_json() == after.to_json(),
+                _ => false,
+            };
+            if !same {
+                differences.push(ConfigDifference {
+                    path: path.to_vec(),
+                    before,
+                    after,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn to_path(path: &[&str]) -> Vec<String> {
+    path.iter().map(|key| (*key).to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::config_value_from_source;
+    use quarto_source_map::SourceInfo;
+
+    fn config(name: &str, content: &str, source_context: &mut SourceContext) -> ConfigValue {
+        let file_id = source_context.add_file(name.to_string(), Some(content.to_string()));
+        let parent = SourceInfo::original(file_id, 0, content.len());
+        let mut diagnostics = Vec::new();
+        config_value_from_source(content, parent, &mut diagnostics).expect("parse failed")
+    }
+
+    fn statuses(explanation: &ConfigExplanation<'_>) -> Vec<(usize, ContributionStatus)> {
+        explanation
+            .contributions
+            .iter()
+            .map(|c| (c.layer_index, c.status))
+            .collect()
+    }
+
+    #[test]
+    fn test_explain_scalar_override() {
+        let mut ctx = SourceContext::new();
+        let project = config(
+            "_quarto.yml",
+            "format:\n  html:\n    theme: cosmo\n    toc: true\n",
+            &mut ctx,
+        );
+        let document = config("doc.qmd", "format:\n  html:\n    theme: darkly\n", &mut ctx);
+        let merged = MergedConfig::new(vec![&project, &document]);
+
+        let explanation = merged.explain(&["format", "html", "theme"]);
+        assert_eq!(
+            statuses(&explanation),
+            vec![
+                (0, ContributionStatus::Overridden { by: 1 }),
+                (1, ContributionStatus::Used)
+            ]
+        );
+        assert_eq!(explanation.winner().unwrap().layer_index, 1);
+        assert_eq!(
+            explanation.describe(&ctx),
+            "format.html.theme\n  \
+             _quarto.yml:3: \"cosmo\" (overridden by layer 1)\n  \
+             doc.qmd:3: \"darkly\" (used)"
+        );
+
+        let missing = merged.explain(&["format", "pdf"]);
+        assert!(missing.contributions.is_empty());
+        assert!(missing.winner().is_none());
+    }
+
+    #[test]
+    fn test_explain_prefer_resets() {
+        let mut ctx = SourceContext::new();
+        let project = config(
+            "_quarto.yml",
+            "resources: [a.csv]\nformat:\n  html:\n    theme: cosmo\n",
+            &mut ctx,
+        );
+        let profile = config("_quarto-dev.yml", "resources: [b.csv]\n", &mut ctx);
+        let mut document = config(
+            "doc.qmd",
+            "resources: [c.csv]\nformat:\n  pdf: default\n",
+            &mut ctx,
+        );
+        // As if tagged `!prefer`
+        for key in ["resources", "format"] {
+            document.get_path_mut(&[key]).unwrap().merge_op = MergeOp::Prefer;
+        }
+        let extra = config("extra.yml", "resources: [d.csv]\n", &mut ctx);
+        let merged = MergedConfig::new(vec![&project, &profile, &document, &extra]);
+
+        assert_eq!(
+            statuses(&merged.explain(&["resources"])),
+            vec![
+                (0, ContributionStatus::Reset { by: 2 }),
+                (1, ContributionStatus::Reset { by: 2 }),
+                (2, ContributionStatus::Used),
+                (3, ContributionStatus::Used)
+            ]
+        );
+        // The enclosing `format` map is reset, so the theme is not used
+        assert_eq!(
+            statuses(&merged.explain(&["format", "html", "theme"])),
+            vec![(0, ContributionStatus::Reset { by: 2 })]
+        );
+    }
+
+    #[test]
+    fn test_explain_replaced_parent() {
+        let mut ctx = SourceContext::new();
+        let project = config("_quarto.yml", "format:\n  html:\n    toc: true\n", &mut ctx);
+        let document = config("doc.qmd", "format: pdf\n", &mut ctx);
+        let merged = MergedConfig::new(vec![&project, &document]);
+
+        assert_eq!(
+            statuses(&merged.explain(&["format", "html", "toc"])),
+            vec![(0, ContributionStatus::Overridden { by: 1 })]
+        );
+        assert_eq!(
+            statuses(&merged.explain(&["format"])),
+            vec![
+                (0, ContributionStatus::Overridden { by: 1 }),
+                (1, ContributionStatus::Used)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut ctx = SourceContext::new();
+        let project = config(
+            "_quarto.yml",
+            "title: Site\nformat:\n  html:\n    theme: cosmo\n    toc: true\n",
+            &mut ctx,
+        );
+        let profile = config(
+            "_quarto-print.yml",
+            "title: Site\nformat:\n  html:\n    theme: flatly\n  pdf: default\n",
+            &mut ctx,
+        );
+        let base = MergedConfig::new(vec![&project]);
+        let with_profile = base.with_layer(&profile);
+
+        let differences = base.diff(&with_profile).unwrap();
+        let summary: Vec<_> = differences
+            .iter()
+            .map(|d| {
+                (
+                    d.path.join("."),
+                    d.before.as_ref().and_then(ConfigValue::as_str),
+                    d.after.as_ref().and_then(ConfigValue::as_str),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "format.html.theme".to_string(),
+                    Some("cosmo"),
+                    Some("flatly")
+                ),
+                ("format.pdf".to_string(), None, Some("default")),
+            ]
+        );
+        assert!(base.diff(&base).unwrap().is_empty());
+    }
+}
@@ -18,6 +18,12 @@
 //! - [`ConfigValue`]: A configuration value with explicit merge semantics
 //! - [`MergeOp`]: Controls whether values prefer (override) or concat (append)
 //! - [`Interpretation`]: Hints for how strings should be interpreted (`!md`, `!str`, etc.)
+//! - Project profiles: [`active_profiles`] and [`merge_profile_layers`] layer
+//!   `_quarto-{profile}.yml` over `_quarto.yml`
+//! - Provenance: [`MergedConfig::explain`] lists the layers behind a value and
+//!   what became of each; [`MergedConfig::diff`] compares two merged configs
+//! - Schema validation: [`validate_config`] reports unknown, mistyped and
+//!   deprecated options at the location that supplied them
 //!
 //! # Example
 //!
@@ -38,10 +44,13 @@
 //! ```
 
 mod convert;
+mod explain;
 mod materialize;
 mod merged;
+mod profile;
 mod tag;
 mod types;
+mod validate;
 
 pub use types::{
     ConfigError, ConfigMapEntry, ConfigValue, ConfigValueKind, Interpretation,
@@ -50,13 +59,22 @@ pub use types::{
 
 pub use tag::{ParsedTag, parse_tag};
 
-pub use convert::config_value_from_yaml;
+pub use convert::{config_value_from_source, config_value_from_str, config_value_from_yaml};
 
 pub use merged::{
     MergedArray, MergedArrayItem, MergedConfig, MergedCursor, MergedMap, MergedScalar, MergedValue,
 };
 
+pub use explain::{ConfigDifference, ConfigExplanation, Contribution, ContributionStatus};
+
 pub use materialize::{MaterializeOptions, merge_with_diagnostics};
 
+pub use profile::{
+    QUARTO_PROFILE_ENV, UNLESS_PROFILE_KEY, WHEN_PROFILE_KEY, active_profiles,
+    merge_profile_layers, parse_profiles, profile_config_file, resolve_profile_conditions,
+};
+
+pub use validate::{cell_option_completion_model, config_completion_model, validate_config};
+
 // Re-export for convenience
 pub use quarto_source_map::SourceInfo;
@@ -67,7 +67,7 @@ impl<'a> MergedConfig<'a> {
 }
 
 /// Materialize a cursor's value into an owned ConfigValue.
-fn materialize_cursor(
+pub(crate) fn materialize_cursor(
     cursor: &MergedCursor<'_>,
     depth: usize,
     options: &MaterializeOptions,
@@ -125,6 +125,11 @@ impl<'a> MergedConfig<'a> {
         self.layers.len()
     }
 
+    /// The layers, lowest priority first.
+    pub(crate) fn layers(&self) -> &[&'a ConfigValue] {
+        &self.layers
+    }
+
     /// Get a cursor at the root.
     pub fn cursor(&'a self) -> MergedCursor<'a> {
         MergedCursor {
@@ -0,0 +1,283 @@
+//! Project profiles.
+//!
+//! A profile adjusts the project configuration for one kind of render
+//! (`production`, `draft`, `advanced`, ...). Profiles are activated with the
+//! `--profile` flag, or else the `QUARTO_PROFILE` environment variable, or
+//! else `profile: default:` in `_quarto.yml`. Both the flag and the variable
+//! take a comma-separated list.
+//!
+//! For each active profile, `_quarto-{profile}.yml` is merged over
+//! `_quarto.yml`, in the order the profiles were given, so later profiles
+//! take precedence. Profile groups (`profile: group:`) are lists of
+//! mutually exclusive profiles; when none of a group's profiles is active,
+//! its first one is.
+//!
+//! Within any of these files, a map with a `when-profile` key is only kept
+//! when one of the listed profiles is active, and a map with an
+//! `unless-profile` key only when none is:
+//!
+//! ```yaml
+//! website:
+//!   navbar:
+//!     - when-profile: internal
+//!       text: Dashboards
+//!       href: dashboards.qmd
+//! ```
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! let profiles = active_profiles(&cli_profiles, env.as_deref(), &base);
+//! let overlays: Vec<ConfigValue> = /* parse each `_quarto-{profile}.yml` */;
+//! let config = merge_profile_layers(&base, &overlays, &profiles)?;
+//! ```
+
+use crate::merged::MergedConfig;
+use crate::types::{ConfigError, ConfigMapEntry, ConfigValue, ConfigValueKind};
+
+/// Environment variable naming the active profiles.
+pub const QUARTO_PROFILE_ENV: &str = "QUARTO_PROFILE";
+
+/// Key of the condition that keeps a map only for the listed profiles.
+pub const WHEN_PROFILE_KEY: &str = "when-profile";
+
+/// Key of the condition that drops a map for the listed profiles.
+pub const UNLESS_PROFILE_KEY: &str = "unless-profile";
+
+/// Split a comma- or space-separated list of profile names.
+pub fn parse_profiles(spec: &str) -> Vec<String> {
+    spec.split([',', ' '])
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// The active profiles, in order of increasing precedence.
+///
+/// `cli` are the `--profile` values (each may be a comma-separated list),
+/// `env` is the value of `QUARTO_PROFILE`, and `config` is the base project
+/// configuration, consulted for `profile: default:` and `profile: group:`.
+pub fn active_profiles(cli: &[String], env: Option<&str>, config: &ConfigValue) -> Vec<String> {
+    let mut profiles: Vec<String> = cli.iter().flat_map(|spec| parse_profiles(spec)).collect();
+    if profiles.is_empty() {
+        profiles = env.map(parse_profiles).unwrap_or_default();
+    }
+    if profiles.is_empty() {
+        profiles = config
+            .get_path(&["profile", "default"])
+            .map(string_list)
+            .unwrap_or_default();
+    }
+
+    for group in profile_groups(config) {
+        if !group.iter().any(|name| profiles.contains(name))
+            && let Some(first) = group.into_iter().next()
+        {
+            profiles.insert(0, first);
+        }
+    }
+
+    let mut seen = Vec::new();
+    profiles.retain(|name| {
+        let first = !seen.contains(name);
+        seen.push(name.clone());
+        first
+    });
+    profiles
+}
+
+/// The configuration file of a profile, relative to the project directory.
+pub fn profile_config_file(profile: &str) -> String {
+    format!("_quarto-{}.yml", profile)
+}
+
+/// Drop the maps whose `when-profile`/`unless-profile` condition does not
+/// hold for `profiles`, and remove the condition keys from the others.
+pub fn resolve_profile_conditions(value: &ConfigValue, profiles: &[String]) -> ConfigValue {
+    let kind = match &value.value {
+        ConfigValueKind::Map(entries) => ConfigValueKind::Map(
+            entries
+                .iter()
+                .filter(|entry| !is_condition_key(&entry.key))
+                .filter(|entry| condition_holds(&entry.value, profiles))
+                .map(|entry| ConfigMapEntry {
+                    key: entry.key.clone(),
+                    key_source: entry.key_source.clone(),
+                    value: resolve_profile_conditions(&entry.value, profiles),
+                })
+                .collect(),
+        ),
+        ConfigValueKind::Array(items) => ConfigValueKind::Array(
+            items
+                .iter()
+                .filter(|item| condition_holds(item, profiles))
+                .map(|item| resolve_profile_conditions(item, profiles))
+                .collect(),
+        ),
+        other => other.clone(),
+    };
+    ConfigValue {
+        value: kind,
+        source_info: value.source_info.clone(),
+        merge_op: value.merge_op,
+    }
+}
+
+/// Merge the profile overlays over the base configuration, after resolving
+/// the profile conditions of every layer.
+///
+/// `overlays` are the parsed `_quarto-{profile}.yml` files of the active
+/// profiles, in the order of `profiles`.
+pub fn merge_profile_layers(
+    base: &ConfigValue,
+    overlays: &[ConfigValue],
+    profiles: &[String],
+) -> Result<ConfigValue, ConfigError> {
+    let layers: Vec<ConfigValue> = std::iter::once(base)
+        .chain(overlays)
+        .map(|layer| resolve_profile_conditions(layer, profiles))
+        .collect();
+    MergedConfig::new(layers.iter().collect()).materialize()
+}
+
+fn is_condition_key(key: &str) -> bool {
+    key == WHEN_PROFILE_KEY || key == UNLESS_PROFILE_KEY
+}
+
+/// Whether a value's profile condition (if any) holds.
+fn condition_holds(value: &ConfigValue, profiles: &[String]) -> bool {
+    let any_active = |names: &ConfigValue| {
+        string_list(names)
+            .iter()
+            .any(|name| profiles.contains(name))
+    };
+    if let Some(names) = value.get(WHEN_PROFILE_KEY)
+        && !any_active(names)
+    {
+        return false;
+    }
+    if let Some(names) = value.get(UNLESS_PROFILE_KEY)
+        && any_active(names)
+    {
+        return false;
+    }
+    true
+}
+
+/// A string or list of strings (each possibly comma-separated) as names.
+fn string_list(value: &ConfigValue) -> Vec<String> {
+    match value.as_array() {
+        Some(items) => items.iter().flat_map(string_list).collect(),
+        None => value
+            .as_plain_text()
+            .map(|text| parse_profiles(&text))
+            .unwrap_or_default(),
+    }
+}
+
+/// The `profile: group:` lists. A single list of names is one group.
+fn profile_groups(config: &ConfigValue) -> Vec<Vec<String>> {
+    let Some(groups) = config
+        .get_path(&["profile", "group"])
+        .and_then(ConfigValue::as_array)
+    else {
+        return Vec::new();
+    };
+    if groups.iter().all(|group| !group.is_array()) {
+        return vec![groups.iter().flat_map(string_list).collect()];
+    }
+    groups
+        .iter()
+        .map(string_list)
+        .filter(|group| !group.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::config_value_from_yaml;
+
+    fn config(yaml: &str) -> ConfigValue {
+        let yaml = quarto_yaml::parse(yaml).expect("parse failed");
+        let mut diagnostics = Vec::new();
+        let value = config_value_from_yaml(yaml, &mut diagnostics);
+        assert!(diagnostics.is_empty());
+        value
+    }
+
+    fn names(profiles: &[&str]) -> Vec<String> {
+        profiles.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_active_profiles_precedence() {
+        let base = config("profile:\n  default: draft\n");
+        assert_eq!(
+            active_profiles(&names(&["a,b", "c"]), Some("env"), &base),
+            names(&["a", "b", "c"])
+        );
+        assert_eq!(
+            active_profiles(&[], Some("env, other"), &base),
+            names(&["env", "other"])
+        );
+        assert_eq!(active_profiles(&[], None, &base), names(&["draft"]));
+        assert!(active_profiles(&[], None, &config("title: x\n")).is_empty());
+    }
+
+    #[test]
+    fn test_profile_groups() {
+        let base = config("profile:\n  group:\n    - [basic, advanced]\n    - [print, web]\n");
+        assert_eq!(
+            active_profiles(&names(&["advanced"]), None, &base),
+            names(&["print", "advanced"])
+        );
+
+        let single = config("profile:\n  group: [basic, advanced]\n");
+        assert_eq!(active_profiles(&[], None, &single), names(&["basic"]));
+    }
+
+    #[test]
+    fn test_resolve_profile_conditions() {
+        let value = config(
+            "website:\n  navbar:\n    - text: Home\n    - when-profile: internal\n      text: Dashboards\n    - unless-profile: [internal, staff]\n      text: Public\n  search:\n    when-profile: internal\n    type: overlay\n",
+        );
+
+        let internal = resolve_profile_conditions(&value, &names(&["internal"]));
+        let navbar = internal.get_path(&["website", "navbar"]).unwrap();
+        let texts: Vec<_> = navbar
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item.get("text").unwrap().as_str().unwrap())
+            .collect();
+        assert_eq!(texts, vec!["Home", "Dashboards"]);
+        let search = internal.get_path(&["website", "search"]).unwrap();
+        assert!(search.get("when-profile").is_none());
+        assert_eq!(search.get("type").unwrap().as_str(), Some("overlay"));
+
+        let public = resolve_profile_conditions(&value, &[]);
+        let navbar = public.get_path(&["website", "navbar"]).unwrap();
+        assert_eq!(navbar.as_array().unwrap().len(), 2);
+        assert!(public.get_path(&["website", "search"]).is_none());
+    }
+
+    #[test]
+    fn test_merge_profile_layers() {
+        let base = config("title: Site\nformat:\n  html:\n    theme: cosmo\n    toc: true\n");
+        let production = config("format:\n  html:\n    theme: darkly\n");
+        let print = config("format:\n  html:\n    theme: simplex\n    toc: false\n");
+
+        let merged = merge_profile_layers(
+            &base,
+            &[production, print],
+            &names(&["production", "print"]),
+        )
+        .unwrap();
+        let html = merged.get_path(&["format", "html"]).unwrap();
+        assert_eq!(html.get("theme").unwrap().as_str(), Some("simplex"));
+        assert_eq!(html.get("toc").unwrap().as_bool(), Some(false));
+        assert_eq!(merged.get("title").unwrap().as_str(), Some("Site"));
+    }
+}
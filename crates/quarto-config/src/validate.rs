@@ -0,0 +1,750 @@
+//! Validation of configuration against the Quarto schema.
+//!
+//! Project configuration (`_quarto.yml` with its profile overlays) and
+//! document metadata are checked after merging, so each value is checked
+//! once, in its final form. Every value keeps the source location of the
+//! layer that supplied it, so problems are reported in the file where the
+//! offending value was written.
+//!
+//! Problems are warnings, not errors: an invalid option is reported and the
+//! render continues. Three kinds of problems are reported:
+//!
+//! - unknown keys in closed sections (`project`, `profile`)
+//! - values of the wrong type (`toc: yes please`)
+//! - deprecated options (`self-contained` instead of `embed-resources`)
+//!
+//! Keys the schema doesn't know are accepted at the top level and in
+//! formats, as documents and templates may define their own metadata.
+//!
+//! The same schema drives editor completion: [`config_completion_model`]
+//! lists the options that may be written in front matter or `_quarto.yml`,
+//! and [`cell_option_completion_model`] those of executable code cells.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! for warning in validate_config(&merged, &source_context) {
+//!     eprintln!("{}", warning.to_text(Some(&source_context)));
+//! }
+//! ```
+
+use std::sync::LazyLock;
+
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_source_map::{SourceContext, SourceInfo};
+use quarto_yaml::{YamlHashEntry, YamlWithSourceInfo};
+use quarto_yaml_validation::error::{ValidationError, ValidationErrorKind};
+use quarto_yaml_validation::{CompletionNode, Schema, SchemaRegistry, completion_model};
+use yaml_rust2::Yaml;
+
+use crate::types::{ConfigMapEntry, ConfigValue, ConfigValueKind};
+
+/// Schemas of the known top-level options, by key.
+const QUARTO_SCHEMA: &str = r#"
+project:
+  object:
+    closed: true
+    description: Project type, output and rendering options
+    properties:
+      type:
+        enum:
+          values: [default, website, book, manuscript]
+          description: Project type
+      title:
+        schema: string
+        description: Project title
+      output-dir:
+        schema: path
+        description: Directory for rendered output
+      lib-dir:
+        schema: path
+        description: Directory for shared dependencies (JavaScript, CSS) of rendered documents
+      execute-dir:
+        enum:
+          values: [file, project]
+          description: Working directory for code execution
+      render:
+        schema:
+          arrayOf: path
+        description: Files to render, in order (defaults to all files)
+      resources:
+        schema:
+          maybeArrayOf: path
+        description: Files to copy to the output directory
+      pre-render:
+        schema:
+          maybeArrayOf: string
+        description: Scripts to run before rendering the project
+      post-render:
+        schema:
+          maybeArrayOf: string
+        description: Scripts to run after rendering the project
+      preview:
+        object:
+          description: Options for previewing the project
+profile:
+  object:
+    closed: true
+    description: Configuration profiles
+    properties:
+      default:
+        schema:
+          maybeArrayOf: string
+        description: Profiles to use when none is given
+      group:
+        schema:
+          arrayOf:
+            maybeArrayOf: string
+        description: Groups of mutually exclusive profiles
+website:
+  object:
+    description: Website options (navigation, search, footer)
+book:
+  object:
+    description: Book options (chapters, appendices, downloads)
+title:
+  schema: string
+  description: Document title
+subtitle:
+  schema: string
+  description: Document subtitle
+date:
+  schema: string
+  description: Document date
+lang:
+  schema: string
+  description: Document language as a BCP 47 code (e.g. `en-US`)
+toc:
+  schema: boolean
+  description: Include a table of contents
+toc-depth:
+  schema: number
+  description: Number of heading levels in the table of contents
+toc-title:
+  schema: string
+  description: Title of the table of contents
+number-sections:
+  schema: boolean
+  description: Number section headings
+engine:
+  enum:
+    values: [knitr, jupyter, julia, markdown]
+    description: Engine used to execute code cells
+execute:
+  schema:
+    ref: execute-options
+  description: Code execution options
+params:
+  object:
+    description: Document parameters
+metadata-files:
+  schema:
+    maybeArrayOf: path
+  description: Files whose metadata is merged into the document
+bibliography:
+  schema:
+    maybeArrayOf: path
+  description: Bibliography files (BibTeX, CSL JSON or CSL YAML)
+"#;
+
+/// Schema for the options of one format (`format: html: ...`).
+const FORMAT_OPTIONS_SCHEMA: &str = r#"
+object:
+  properties:
+    toc:
+      schema: boolean
+      description: Include a table of contents
+    toc-depth:
+      schema: number
+      description: Number of heading levels in the table of contents
+    toc-title:
+      schema: string
+      description: Title of the table of contents
+    toc-location:
+      enum:
+        values: [body, left, right, left-body, right-body]
+        description: Where to place the table of contents
+    number-sections:
+      schema: boolean
+      description: Number section headings
+    number-depth:
+      schema: number
+      description: Deepest heading level to number
+    theme:
+      schema:
+        maybeArrayOf: string
+      description: Bootstrap theme name or SCSS theme files
+    css:
+      schema:
+        maybeArrayOf: path
+      description: CSS stylesheets to include
+    embed-resources:
+      schema: boolean
+      description: Produce a standalone file with all resources embedded
+    self-contained:
+      schema: boolean
+      description: Deprecated, use `embed-resources`
+    minimal:
+      schema: boolean
+      description: Leave out the default theme and JavaScript
+    code-fold:
+      schema:
+        anyOf:
+          - boolean
+          - enum: [show]
+      description: Collapse code cells into expandable sections
+    code-tools:
+      schema: boolean
+      description: Show a menu for hiding and viewing the code
+    code-line-numbers:
+      schema: boolean
+      description: Number the lines of code blocks
+    execute:
+      schema:
+        ref: execute-options
+      description: Code execution options
+    output-file:
+      schema: path
+      description: Name of the rendered file
+    template:
+      schema: path
+      description: Custom template for the format
+    keep-tex:
+      schema: boolean
+      description: Keep the intermediate LaTeX file
+    keep-typ:
+      schema: boolean
+      description: Keep the intermediate Typst file
+"#;
+
+/// Schema for code execution options.
+const EXECUTE_OPTIONS_SCHEMA: &str = r#"
+object:
+  properties:
+    enabled:
+      schema: boolean
+      description: Execute code cells (for engines that don't by default)
+    eval:
+      schema: boolean
+      description: Evaluate code cells
+    echo:
+      schema:
+        anyOf:
+          - boolean
+          - enum: [fenced]
+      description: Include the source code in the output
+    output:
+      schema:
+        anyOf:
+          - boolean
+          - enum: [asis]
+      description: Include the results of executing the code
+    warning:
+      schema: boolean
+      description: Include warnings in the output
+    error:
+      schema: boolean
+      description: Include errors in the output instead of stopping the render
+    include:
+      schema: boolean
+      description: Include the cell (code and results) in the output
+    cache:
+      schema:
+        anyOf:
+          - boolean
+          - enum: [refresh]
+      description: Cache the results of computations
+    freeze:
+      schema:
+        anyOf:
+          - boolean
+          - enum: [auto]
+      description: Reuse computational output when rendering a project
+    daemon:
+      schema:
+        anyOf: [boolean, number]
+      description: Keep the Jupyter kernel alive between renders (seconds, or false)
+    daemon-restart:
+      schema: boolean
+      description: Restart the Jupyter kernel daemon before rendering
+    debug:
+      schema: boolean
+      description: Print debugging output for the Jupyter kernel
+    keep-md:
+      schema: boolean
+      description: Keep the markdown file produced by code execution
+    keep-ipynb:
+      schema: boolean
+      description: Keep the notebook produced by code execution
+    ipynb:
+      schema: boolean
+      description: Produce a notebook from the document
+"#;
+
+/// Schema for options of executable code cells (`#| fig-cap: ...`),
+/// besides the code execution options.
+const CELL_OPTIONS_SCHEMA: &str = r#"
+object:
+  properties:
+    label:
+      schema: string
+      description: Cell label, used as the identifier of its figure or table
+    fig-cap:
+      schema:
+        maybeArrayOf: string
+      description: Figure caption
+    fig-alt:
+      schema:
+        maybeArrayOf: string
+      description: Alternative text for figures
+    fig-width:
+      schema: number
+      description: Default width of figures, in inches
+    fig-height:
+      schema: number
+      description: Default height of figures, in inches
+    fig-align:
+      enum:
+        values: [default, left, right, center]
+        description: Horizontal alignment of figures
+    tbl-cap:
+      schema:
+        maybeArrayOf: string
+      description: Table caption
+    code-fold:
+      schema:
+        anyOf:
+          - boolean
+          - enum: [show]
+      description: Collapse the code into an expandable section
+    code-summary:
+      schema: string
+      description: Summary shown for folded code
+    code-line-numbers:
+      schema: boolean
+      description: Number the lines of the code
+    classes:
+      schema:
+        maybeArrayOf: string
+      description: Classes added to the cell output
+    file:
+      schema: path
+      description: File to read the cell code from
+"#;
+
+/// Formats offered under `format`, each with the format options.
+const FORMAT_NAMES: &[&str] = &[
+    "html",
+    "pdf",
+    "docx",
+    "epub",
+    "typst",
+    "revealjs",
+    "gfm",
+    "commonmark",
+];
+
+/// Deprecated options and their replacements, checked at the top level and
+/// in each format.
+const DEPRECATED_OPTIONS: &[(&str, &str)] = &[
+    ("self-contained", "embed-resources"),
+    ("site", "website"),
+    ("html-q-tags", "q-tags"),
+];
+
+static PROPERTIES: LazyLock<Vec<(String, Schema)>> = LazyLock::new(|| {
+    let yaml = quarto_yaml::parse(QUARTO_SCHEMA).expect("Quarto schema is valid YAML");
+    yaml.as_hash()
+        .expect("Quarto schema is a mapping")
+        .iter()
+        .map(|entry| {
+            let key = entry.key.yaml.as_str().expect("schema keys are strings");
+            let schema = Schema::from_yaml(&entry.value).expect("Quarto schema is valid");
+            (key.to_string(), schema)
+        })
+        .collect()
+});
+
+static FORMAT_OPTIONS: LazyLock<Schema> = LazyLock::new(|| parse_schema(FORMAT_OPTIONS_SCHEMA));
+
+static REGISTRY: LazyLock<SchemaRegistry> = LazyLock::new(|| {
+    let mut registry = SchemaRegistry::new();
+    registry.register(
+        "execute-options".to_string(),
+        parse_schema(EXECUTE_OPTIONS_SCHEMA),
+    );
+    registry
+});
+
+static COMPLETION_MODEL: LazyLock<CompletionNode> = LazyLock::new(|| {
+    let mut model = CompletionNode {
+        types: vec!["object".to_string()],
+        ..Default::default()
+    };
+    for (key, schema) in PROPERTIES.iter() {
+        model
+            .keys
+            .insert(key.clone(), completion_model(schema, &REGISTRY));
+    }
+
+    let mut format_options = completion_model(&FORMAT_OPTIONS, &REGISTRY);
+    remove_deprecated(&mut format_options);
+    let mut format = CompletionNode {
+        description: Some("Output formats and their options".to_string()),
+        types: vec!["string".to_string(), "object".to_string()],
+        values: FORMAT_NAMES.iter().map(|name| name.to_string()).collect(),
+        ..Default::default()
+    };
+    for name in FORMAT_NAMES {
+        format.keys.insert(name.to_string(), format_options.clone());
+    }
+    model.keys.insert("format".to_string(), format);
+    remove_deprecated(&mut model);
+    model
+});
+
+static CELL_COMPLETION_MODEL: LazyLock<CompletionNode> = LazyLock::new(|| {
+    let mut model = completion_model(&parse_schema(CELL_OPTIONS_SCHEMA), &REGISTRY);
+    let execute_options = completion_model(&parse_schema(EXECUTE_OPTIONS_SCHEMA), &REGISTRY);
+    for (key, node) in execute_options.keys {
+        model.keys.entry(key).or_insert(node);
+    }
+    model
+});
+
+/// Completions for front matter and `_quarto.yml`, from the Quarto schema.
+///
+/// Deprecated options are left out, and every known format is offered
+/// under `format` with the format options.
+pub fn config_completion_model() -> &'static CompletionNode {
+    &COMPLETION_MODEL
+}
+
+/// Completions for the options of executable code cells (`#| echo: false`).
+///
+/// These are the cell options (label, captions, figure sizes) and the code
+/// execution options, which may also be set per cell.
+pub fn cell_option_completion_model() -> &'static CompletionNode {
+    &CELL_COMPLETION_MODEL
+}
+
+fn remove_deprecated(node: &mut CompletionNode) {
+    for (deprecated, _) in DEPRECATED_OPTIONS {
+        node.keys.remove(*deprecated);
+    }
+}
+
+fn parse_schema(source: &str) -> Schema {
+    let yaml = quarto_yaml::parse(source).expect("schema is valid YAML");
+    Schema::from_yaml(&yaml).expect("schema is a valid schema")
+}
+
+/// Validate merged configuration against the Quarto schema.
+///
+/// `source_context` must hold the files the configuration was read from.
+/// Returns one warning per problem, located at the offending key or value.
+pub fn validate_config(
+    config: &ConfigValue,
+    source_context: &SourceContext,
+) -> Vec<DiagnosticMessage> {
+    let ConfigValueKind::Map(entries) = &config.value else {
+        return Vec::new();
+    };
+
+    let mut warnings = deprecation_warnings(entries, "");
+    for entry in entries {
+        if entry.key == "format" {
+            warnings.extend(validate_formats(&entry.value, source_context));
+            continue;
+        }
+        let Some((_, schema)) = PROPERTIES.iter().find(|(key, _)| *key == entry.key) else {
+            continue;
+        };
+        let yaml = to_yaml(&entry.value);
+        if let Err(error) =
+            quarto_yaml_validation::validate(&yaml, schema, &REGISTRY, source_context)
+        {
+            warnings.push(validation_warning(&error, &entry.key));
+        }
+    }
+    warnings
+}
+
+/// Validate `format`: a format name, a list of them, or options by format.
+fn validate_formats(
+    formats: &ConfigValue,
+    source_context: &SourceContext,
+) -> Vec<DiagnosticMessage> {
+    let ConfigValueKind::Map(entries) = &formats.value else {
+        return Vec::new();
+    };
+    let mut warnings = Vec::new();
+    for entry in entries {
+        // `pdf: default` selects a format with its default options
+        if !entry.value.is_map() {
+            continue;
+        }
+        let path = format!("format.{}", entry.key);
+        if let ConfigValueKind::Map(options) = &entry.value.value {
+            warnings.extend(deprecation_warnings(options, &path));
+        }
+        let yaml = to_yaml(&entry.value);
+        if let Err(error) =
+            quarto_yaml_validation::validate(&yaml, &FORMAT_OPTIONS, &REGISTRY, source_context)
+        {
+            warnings.push(validation_warning(&error, &path));
+        }
+    }
+    warnings
+}
+
+/// Warnings for the deprecated options among `entries`, found at `path`.
+fn deprecation_warnings(entries: &[ConfigMapEntry], path: &str) -> Vec<DiagnosticMessage> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let (_, replacement) = DEPRECATED_OPTIONS
+                .iter()
+                .find(|(deprecated, _)| *deprecated == entry.key)?;
+            Some(
+                DiagnosticMessageBuilder::warning("Deprecated configuration option")
+                    .with_location(entry.key_source.clone())
+                    .problem(format!("`{}` is deprecated", join_path(path, &entry.key)))
+                    .add_hint(format!("Use `{}` instead", replacement))
+                    .build(),
+            )
+        })
+        .collect()
+}
+
+/// A warning for a validation error in the value at `path`.
+fn validation_warning(error: &ValidationError, path: &str) -> DiagnosticMessage {
+    let full_path = if error.instance_path.is_empty() {
+        path.to_string()
+    } else {
+        join_path(path, &error.instance_path.to_string())
+    };
+    let location = match (&error.kind, &error.yaml_node) {
+        // Point unknown keys at the key itself rather than its mapping
+        (ValidationErrorKind::UnknownProperty { property }, Some(node)) => node
+            .as_hash()
+            .and_then(|entries| {
+                entries
+                    .iter()
+                    .find(|entry| entry.key.yaml.as_str() == Some(property.as_str()))
+            })
+            .map_or_else(|| node.source_info.clone(), |entry| entry.key_span.clone()),
+        (_, Some(node)) => node.source_info.clone(),
+        (_, None) => SourceInfo::default(),
+    };
+    let builder = match &error.kind {
+        ValidationErrorKind::UnknownProperty { property } => {
+            DiagnosticMessageBuilder::warning("Unknown configuration option").problem(format!(
+                "`{}` is not an option of `{}`",
+                property, full_path
+            ))
+        }
+        _ => DiagnosticMessageBuilder::warning("Invalid configuration value").problem(format!(
+            "Invalid value for `{}`: {}",
+            full_path,
+            error.message()
+        )),
+    };
+    builder
+        .with_code(error.error_code())
+        .with_location(location)
+        .build()
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Convert a configuration value to YAML for validation, keeping the
+/// source location of every node.
+///
+/// Markdown content becomes its plain text and `!path`/`!glob`/`!expr`
+/// values their strings, so all of them validate as strings.
+fn to_yaml(value: &ConfigValue) -> YamlWithSourceInfo {
+    let source_info = value.source_info.clone();
+    match &value.value {
+        ConfigValueKind::Scalar(yaml) => YamlWithSourceInfo::new_scalar(yaml.clone(), source_info),
+        ConfigValueKind::Array(items) => {
+            let children: Vec<YamlWithSourceInfo> = items.iter().map(to_yaml).collect();
+            let yaml = Yaml::Array(children.iter().map(|child| child.yaml.clone()).collect());
+            YamlWithSourceInfo::new_array(yaml, source_info, children)
+        }
+        ConfigValueKind::Map(entries) => {
+            let mut hash = yaml_rust2::yaml::Hash::new();
+            let children: Vec<YamlHashEntry> = entries
+                .iter()
+                .map(|entry| {
+                    let key = YamlWithSourceInfo::new_scalar(
+                        Yaml::String(entry.key.clone()),
+                        entry.key_source.clone(),
+                    );
+                    let child = to_yaml(&entry.value);
+                    hash.insert(key.yaml.clone(), child.yaml.clone());
+                    let value_span = child.source_info.clone();
+                    YamlHashEntry::new(
+                        key,
+                        child,
+                        entry.key_source.clone(),
+                        value_span,
+                        entry.key_source.clone(),
+                    )
+                })
+                .collect();
+            YamlWithSourceInfo::new_hash(Yaml::Hash(hash), source_info, children)
+        }
+        _ => {
+            let text = value.as_plain_text().unwrap_or_else(|| {
+                value
+                    .to_json()
+                    .as_str()
+                    .map(String::from)
+                    .unwrap_or_default()
+            });
+            YamlWithSourceInfo::new_scalar(Yaml::String(text), source_info)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::config_value_from_source;
+
+    /// Parse `content` as the file `name`, registered in `source_context`.
+    fn config(name: &str, content: &str, source_context: &mut SourceContext) -> ConfigValue {
+        let file_id = source_context.add_file(name.to_string(), Some(content.to_string()));
+        let parent = SourceInfo::original(file_id, 0, content.len());
+        let mut diagnostics = Vec::new();
+        config_value_from_source(content, parent, &mut diagnostics).expect("parse failed")
+    }
+
+    /// The file and 1-based line a warning points at.
+    fn position(warning: &DiagnosticMessage, source_context: &SourceContext) -> (String, usize) {
+        let location = warning.location.as_ref().expect("warning has a location");
+        let mapped = location.map_offset(0, source_context).unwrap();
+        let file = source_context.get_file(mapped.file_id).unwrap();
+        (file.path.clone(), mapped.location.row + 1)
+    }
+
+    #[test]
+    fn test_completion_model() {
+        let model = config_completion_model();
+        assert_eq!(
+            model.at_path(&["engine"]).unwrap().values,
+            vec!["knitr", "jupyter", "julia", "markdown"]
+        );
+        assert_eq!(
+            model.at_path(&["execute", "echo"]).unwrap().values,
+            vec!["true", "false", "fenced"]
+        );
+
+        let html = model.at_path(&["format", "html"]).unwrap();
+        assert!(html.keys.contains_key("toc-location"));
+        assert!(!html.keys.contains_key("self-contained"));
+        assert!(
+            model
+                .at_path(&["format", "html", "execute", "eval"])
+                .is_some()
+        );
+        assert_eq!(
+            model.at_path(&["toc"]).unwrap().description.as_deref(),
+            Some("Include a table of contents")
+        );
+        assert_eq!(
+            model
+                .at_path(&["format", "html", "execute", "echo"])
+                .unwrap()
+                .description
+                .as_deref(),
+            Some("Include the source code in the output")
+        );
+    }
+
+    #[test]
+    fn test_cell_option_completion_model() {
+        let model = cell_option_completion_model();
+        assert!(model.keys.contains_key("fig-cap"));
+        assert_eq!(
+            model.at_path(&["echo"]).unwrap().values,
+            vec!["true", "false", "fenced"]
+        );
+        assert_eq!(
+            model.at_path(&["label"]).unwrap().description.as_deref(),
+            Some("Cell label, used as the identifier of its figure or table")
+        );
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let mut ctx = SourceContext::new();
+        let value = config(
+            "_quarto.yml",
+            "project:\n  type: website\n  output-dir: _site\ntitle: Site\nformat:\n  html:\n    toc: true\n    theme: [cosmo, custom.scss]\n  pdf: default\nexecute:\n  echo: fenced\ncustom-key: anything\n",
+            &mut ctx,
+        );
+        assert!(validate_config(&value, &ctx).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_key_in_closed_section() {
+        let mut ctx = SourceContext::new();
+        let value = config(
+            "_quarto.yml",
+            "project:\n  type: website\n  outputdir: _site\n",
+            &mut ctx,
+        );
+        let warnings = validate_config(&value, &ctx);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].title, "Unknown configuration option");
+        assert_eq!(position(&warnings[0], &ctx), ("_quarto.yml".to_string(), 3));
+    }
+
+    #[test]
+    fn test_type_mismatch_points_at_originating_layer() {
+        let mut ctx = SourceContext::new();
+        let base = config("_quarto.yml", "format:\n  html:\n    toc: true\n", &mut ctx);
+        let overlay = config(
+            "_quarto-print.yml",
+            "title: Print\nformat:\n  html:\n    toc: sometimes\n",
+            &mut ctx,
+        );
+        let merged = crate::MergedConfig::new(vec![&base, &overlay])
+            .materialize()
+            .unwrap();
+
+        let warnings = validate_config(&merged, &ctx);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].title, "Invalid configuration value");
+        assert_eq!(
+            position(&warnings[0], &ctx),
+            ("_quarto-print.yml".to_string(), 4)
+        );
+    }
+
+    #[test]
+    fn test_deprecated_options() {
+        let mut ctx = SourceContext::new();
+        let value = config(
+            "_quarto.yml",
+            "site:\n  title: Old\nformat:\n  html:\n    self-contained: true\n",
+            &mut ctx,
+        );
+        let warnings = validate_config(&value, &ctx);
+        assert_eq!(warnings.len(), 2);
+        assert!(
+            warnings
+                .iter()
+                .all(|w| w.title == "Deprecated configuration option")
+        );
+        assert_eq!(position(&warnings[1], &ctx), ("_quarto.yml".to_string(), 5));
+    }
+}
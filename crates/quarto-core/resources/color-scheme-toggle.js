@@ -0,0 +1,55 @@
+/*
+ * color-scheme-toggle.js
+ * Light/dark color scheme toggle for Quarto HTML documents
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * The dark stylesheet is linked with the `quarto-color-alternate` class and
+ * starts out disabled. The reader's saved choice wins; otherwise the
+ * system's `prefers-color-scheme` decides.
+ */
+(function () {
+  const storageKey = "quarto-color-scheme";
+
+  function savedScheme() {
+    try {
+      return window.localStorage.getItem(storageKey);
+    } catch (e) {
+      return null;
+    }
+  }
+
+  function apply(dark) {
+    for (const link of document.querySelectorAll("link.quarto-color-alternate")) {
+      link.disabled = !dark;
+    }
+    const root = document.documentElement;
+    root.classList.toggle("quarto-dark", dark);
+    root.classList.toggle("quarto-light", !dark);
+    root.setAttribute("data-bs-theme", dark ? "dark" : "light");
+  }
+
+  const saved = savedScheme();
+  let dark = saved
+    ? saved === "dark"
+    : window.matchMedia("(prefers-color-scheme: dark)").matches;
+  apply(dark);
+
+  window.quartoToggleColorScheme = function () {
+    dark = !dark;
+    try {
+      window.localStorage.setItem(storageKey, dark ? "dark" : "light");
+    } catch (e) {
+      // Storage unavailable; the choice lasts for this page only
+    }
+    apply(dark);
+  };
+
+  document.addEventListener("DOMContentLoaded", function () {
+    for (const toggle of document.querySelectorAll(".quarto-color-scheme-toggle")) {
+      toggle.addEventListener("click", function (event) {
+        event.preventDefault();
+        window.quartoToggleColorScheme();
+      });
+    }
+  });
+})();
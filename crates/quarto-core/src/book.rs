@@ -0,0 +1,423 @@
+/*
+ * book.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Book project structure.
+ */
+
+//! Book project structure.
+//!
+//! A book project lists its chapters in `_quarto.yml`:
+//!
+//! ```yaml
+//! project:
+//!   type: book
+//! book:
+//!   title: "My Book"
+//!   chapters:
+//!     - index.qmd
+//!     - intro.qmd
+//!     - part: "Methods"
+//!       chapters:
+//!         - data.qmd
+//!         - models.qmd
+//!   appendices:
+//!     - tools.qmd
+//! ```
+//!
+//! [`Book`] turns this into an ordered chapter list with chapter numbers:
+//! a leading `index.qmd` is the unnumbered preface, the other chapters are
+//! numbered `1`, `2`, ... across parts, and appendices are lettered `A`,
+//! `B`, .... A part is either a title or a `.qmd` file for the part page,
+//! which is rendered but not numbered.
+//!
+//! The render pipeline uses the chapter number to prefix section and
+//! crossref numbers ("2.1", "Figure 2.1"), and the chapter order for
+//! prev/next navigation and the book sidebar. [`Book::combined_source`]
+//! concatenates the chapters into one document for single-file outputs.
+
+use std::path::{Path, PathBuf};
+
+use quarto_system_runtime::SystemRuntime;
+
+use crate::error::{QuartoError, Result};
+use crate::project::{ProjectContext, ProjectType, front_matter};
+
+/// A chapter of a book, in reading order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookChapter {
+    /// Input file path (absolute)
+    pub input: PathBuf,
+
+    /// Chapter number ("1", "A"), or `None` for unnumbered pages
+    pub number: Option<String>,
+
+    /// Index into [`Book::parts`] of the part containing this chapter
+    pub part: Option<usize>,
+
+    /// Whether this is an appendix
+    pub appendix: bool,
+}
+
+/// A part grouping several chapters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookPart {
+    /// Part title (or the part page's file name if the part is a file)
+    pub title: String,
+
+    /// Part page, if the part is given as a file
+    pub input: Option<PathBuf>,
+}
+
+/// Chapter structure of a book project.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Book {
+    /// Book title (`book.title`)
+    pub title: Option<String>,
+
+    /// Chapters, part pages and appendices in reading order
+    pub chapters: Vec<BookChapter>,
+
+    /// Parts, including the appendices part if there are appendices
+    pub parts: Vec<BookPart>,
+}
+
+impl Book {
+    /// The book structure of a project, if it is a book with chapters.
+    pub fn from_project(project: &ProjectContext) -> Option<Self> {
+        let config = project.config.as_ref()?;
+        if config.project_type != ProjectType::Book {
+            return None;
+        }
+        let book = config.raw.get("book")?;
+        Some(Self::from_config(&project.dir, book))
+    }
+
+    /// Build the book structure from the `book:` configuration.
+    pub fn from_config(dir: &Path, book: &serde_json::Value) -> Self {
+        let mut result = Self {
+            title: book.get("title").and_then(|t| t.as_str()).map(String::from),
+            ..Default::default()
+        };
+        let mut counter = 0;
+
+        for entry in book
+            .get("chapters")
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+        {
+            match entry {
+                serde_json::Value::String(file) => {
+                    let input = dir.join(file);
+                    let is_preface = result.chapters.is_empty() && is_index(&input);
+                    let number = (!is_preface).then(|| {
+                        counter += 1;
+                        counter.to_string()
+                    });
+                    result.push_chapter(input, number, None, false);
+                }
+                serde_json::Value::Object(part) => {
+                    let Some(title) = part.get("part").and_then(|p| p.as_str()) else {
+                        continue;
+                    };
+                    let input = is_source_file(title).then(|| dir.join(title));
+                    let index = result.parts.len();
+                    result.parts.push(BookPart {
+                        title: title.to_string(),
+                        input: input.clone(),
+                    });
+                    if let Some(input) = input {
+                        result.push_chapter(input, None, Some(index), false);
+                    }
+                    for file in chapter_files(part.get("chapters")) {
+                        counter += 1;
+                        result.push_chapter(
+                            dir.join(file),
+                            Some(counter.to_string()),
+                            Some(index),
+                            false,
+                        );
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let appendices = chapter_files(book.get("appendices"));
+        if !appendices.is_empty() {
+            let index = result.parts.len();
+            result.parts.push(BookPart {
+                title: "Appendices".to_string(),
+                input: None,
+            });
+            for (letter, file) in ('A'..='Z').zip(appendices) {
+                result.push_chapter(dir.join(file), Some(letter.to_string()), Some(index), true);
+            }
+        }
+
+        result
+    }
+
+    fn push_chapter(
+        &mut self,
+        input: PathBuf,
+        number: Option<String>,
+        part: Option<usize>,
+        appendix: bool,
+    ) {
+        self.chapters.push(BookChapter {
+            input,
+            number,
+            part,
+            appendix,
+        });
+    }
+
+    /// The position and chapter for an input file.
+    pub fn chapter(&self, input: &Path) -> Option<(usize, &BookChapter)> {
+        self.chapters
+            .iter()
+            .enumerate()
+            .find(|(_, chapter)| chapter.input == input)
+    }
+
+    /// The chapter before `input` in reading order.
+    pub fn previous(&self, input: &Path) -> Option<&BookChapter> {
+        let (index, _) = self.chapter(input)?;
+        index.checked_sub(1).map(|i| &self.chapters[i])
+    }
+
+    /// The chapter after `input` in reading order.
+    pub fn next(&self, input: &Path) -> Option<&BookChapter> {
+        let (index, _) = self.chapter(input)?;
+        self.chapters.get(index + 1)
+    }
+
+    /// Concatenate the chapters into a single document.
+    ///
+    /// Used for single-file outputs such as PDF. The book title and author
+    /// become the document's front matter; each chapter's own front matter
+    /// is dropped, with its `title` turned into the chapter heading. Parts
+    /// become unnumbered `.part` headings, and appendices are preceded by an
+    /// `.appendix` marker Div.
+    pub fn combined_source(
+        &self,
+        book: &serde_json::Value,
+        runtime: &dyn SystemRuntime,
+    ) -> Result<String> {
+        let mut output = String::from("---\n");
+        for key in ["title", "subtitle", "author", "date"] {
+            if let Some(value) = book.get(key) {
+                let yaml = serde_yaml::to_string(&serde_json::json!({ (key): value }))
+                    .map_err(|e| QuartoError::Other(e.to_string()))?;
+                output.push_str(&yaml);
+            }
+        }
+        output.push_str("---\n");
+
+        let mut current_part = None;
+        let mut in_appendix = false;
+        for chapter in &self.chapters {
+            if chapter.appendix && !in_appendix {
+                in_appendix = true;
+                output.push_str("\n::: {.appendix}\n:::\n");
+            } else if chapter.part != current_part
+                && let Some(part) = chapter.part.map(|i| &self.parts[i])
+                && part.input.is_none()
+            {
+                output.push_str(&format!("\n# {} {{.part .unnumbered}}\n", part.title));
+            }
+            current_part = chapter.part;
+
+            let source = runtime.file_read_string(&chapter.input).map_err(|e| {
+                QuartoError::Other(format!(
+                    "Failed to read chapter {}: {}",
+                    chapter.input.display(),
+                    e
+                ))
+            })?;
+            let (meta, body) = split_front_matter(&source);
+
+            output.push('\n');
+            if let Some(title) = meta
+                .as_ref()
+                .and_then(|m| m.get("title"))
+                .and_then(|t| t.as_str())
+            {
+                let class = if chapter.number.is_none() {
+                    " {.unnumbered}"
+                } else {
+                    ""
+                };
+                output.push_str(&format!("# {}{}\n\n", title, class));
+            }
+            output.push_str(body.trim_start_matches('\n'));
+            if !output.ends_with('\n') {
+                output.push('\n');
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// The files of a `chapters`/`appendices` list.
+fn chapter_files(value: Option<&serde_json::Value>) -> Vec<&str> {
+    value
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str())
+        .collect()
+}
+
+fn is_index(input: &Path) -> bool {
+    input.file_stem().is_some_and(|stem| stem == "index")
+}
+
+fn is_source_file(name: &str) -> bool {
+    name.ends_with(".qmd") || name.ends_with(".md")
+}
+
+/// Split a source into its parsed front matter and the rest.
+fn split_front_matter(source: &str) -> (Option<serde_json::Value>, &str) {
+    let Some(meta) = front_matter(source) else {
+        return (None, source);
+    };
+    let rest = source
+        .strip_prefix("---\n")
+        .or_else(|| source.strip_prefix("---\r\n"))
+        .unwrap_or(source);
+    let body = rest.find("\n---").map_or("", |end| {
+        rest[end + 4..]
+            .split_once('\n')
+            .map_or("", |(_, body)| body)
+    });
+    (Some(meta), body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::ProjectConfig;
+    use serde_json::json;
+
+    fn book_config() -> serde_json::Value {
+        json!({
+            "title": "My Book",
+            "author": "Jane Doe",
+            "chapters": [
+                "index.qmd",
+                "intro.qmd",
+                { "part": "Methods", "chapters": ["data.qmd", "models.qmd"] },
+                { "part": "results.qmd", "chapters": ["findings.qmd"] }
+            ],
+            "appendices": ["tools.qmd"]
+        })
+    }
+
+    fn numbers(book: &Book) -> Vec<(String, Option<&str>)> {
+        book.chapters
+            .iter()
+            .map(|c| {
+                (
+                    c.input.file_name().unwrap().to_string_lossy().into_owned(),
+                    c.number.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chapter_numbering() {
+        let book = Book::from_config(Path::new("/b"), &book_config());
+
+        assert_eq!(book.title.as_deref(), Some("My Book"));
+        assert_eq!(
+            numbers(&book),
+            vec![
+                ("index.qmd".to_string(), None),
+                ("intro.qmd".to_string(), Some("1")),
+                ("data.qmd".to_string(), Some("2")),
+                ("models.qmd".to_string(), Some("3")),
+                ("results.qmd".to_string(), None),
+                ("findings.qmd".to_string(), Some("4")),
+                ("tools.qmd".to_string(), Some("A")),
+            ]
+        );
+        assert_eq!(book.parts.len(), 3);
+        assert_eq!(book.parts[1].input, Some(PathBuf::from("/b/results.qmd")));
+        assert!(book.chapters[6].appendix);
+    }
+
+    #[test]
+    fn test_previous_and_next() {
+        let book = Book::from_config(Path::new("/b"), &book_config());
+
+        let intro = Path::new("/b/intro.qmd");
+        assert_eq!(
+            book.previous(intro).map(|c| c.input.as_path()),
+            Some(Path::new("/b/index.qmd"))
+        );
+        assert_eq!(
+            book.next(intro).map(|c| c.input.as_path()),
+            Some(Path::new("/b/data.qmd"))
+        );
+        assert!(book.previous(Path::new("/b/index.qmd")).is_none());
+        assert!(book.next(Path::new("/b/tools.qmd")).is_none());
+        assert!(book.chapter(Path::new("/b/other.qmd")).is_none());
+    }
+
+    #[test]
+    fn test_from_project_requires_book_type() {
+        let project = |project_type| ProjectContext {
+            dir: PathBuf::from("/b"),
+            config: Some(ProjectConfig {
+                project_type,
+                raw: json!({ "book": book_config() }),
+                ..Default::default()
+            }),
+            is_single_file: false,
+            files: vec![],
+            output_dir: PathBuf::from("/b/_book"),
+        };
+
+        assert!(Book::from_project(&project(ProjectType::Book)).is_some());
+        assert!(Book::from_project(&project(ProjectType::Website)).is_none());
+    }
+
+    #[test]
+    fn test_combined_source() {
+        let temp = tempfile::tempdir().unwrap();
+        let runtime = quarto_system_runtime::NativeRuntime::new();
+        let write = |name: &str, content: &str| {
+            std::fs::write(temp.path().join(name), content).unwrap();
+        };
+        write("index.qmd", "---\ntitle: Preface\n---\n\nWelcome.\n");
+        write("intro.qmd", "# Introduction\n\nSee @sec-data.\n");
+        write("data.qmd", "# Data {#sec-data}\n\nRows.");
+        write("models.qmd", "# Models\n");
+        write("results.qmd", "# Results\n");
+        write("findings.qmd", "# Findings\n");
+        write("tools.qmd", "# Tools\n");
+
+        let config = book_config();
+        let book = Book::from_config(temp.path(), &config);
+        let combined = book.combined_source(&config, &runtime).unwrap();
+
+        assert_eq!(
+            combined,
+            "---\ntitle: My Book\nauthor: Jane Doe\n---\n\
+             \n# Preface {.unnumbered}\n\nWelcome.\n\
+             \n# Introduction\n\nSee @sec-data.\n\
+             \n# Methods {.part .unnumbered}\n\
+             \n# Data {#sec-data}\n\nRows.\n\
+             \n# Models\n\
+             \n# Results\n\
+             \n# Findings\n\
+             \n::: {.appendix}\n:::\n\
+             \n# Tools\n"
+        );
+    }
+}
@@ -0,0 +1,221 @@
+/*
+ * brand.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Brand (`_brand.yml`) support for documents.
+ */
+
+//! Brand support for documents.
+//!
+//! A `_brand.yml` in the project directory (or, failing that, the
+//! document's directory) is read right after parsing (see
+//! [`ParseDocumentStage`](crate::stage::ParseDocumentStage)). Problems in
+//! the brand file are reported against the file itself. Its logos are
+//! recorded in document metadata as `brand.logo.{small,medium,large}`,
+//! each with a `path` relative to the document and an optional `alt`:
+//!
+//! - the navbar shows the smallest logo (see
+//!   [`WebsiteNavigationTransform`](crate::transforms::WebsiteNavigationTransform))
+//! - the HTML title block shows the medium logo
+//!
+//! Brand colors and typography are applied when compiling the theme CSS
+//! (see [`quarto_sass::Brand::to_sass_layer`]). A document opts out with
+//! `brand: false`; a document that sets `brand` itself is left alone.
+
+use std::path::{Component, Path};
+
+use quarto_error_reporting::DiagnosticMessage;
+use quarto_pandoc_types::config_value::ConfigValue;
+use quarto_sass::{BrandImage, find_brand_file, load_brand};
+use quarto_source_map::{SourceContext, SourceInfo};
+use quarto_system_runtime::SystemRuntime;
+
+/// Logo sizes, smallest first.
+pub const LOGO_SIZES: [&str; 3] = ["small", "medium", "large"];
+
+/// Read the brand file for the document at `path` and record its logos in
+/// `meta`.
+///
+/// Returns the brand file's diagnostics if it is invalid.
+pub fn apply_brand(
+    meta: &mut ConfigValue,
+    path: &Path,
+    project_dir: &Path,
+    runtime: &dyn SystemRuntime,
+    source_context: &mut SourceContext,
+) -> Result<(), Vec<DiagnosticMessage>> {
+    if meta.contains_path(&["brand"]) {
+        return Ok(());
+    }
+    let document_dir = path.parent().unwrap_or(Path::new(""));
+    let Some(brand_file) = find_brand_file(&[project_dir, document_dir], runtime) else {
+        return Ok(());
+    };
+    let brand = load_brand(&brand_file, runtime, source_context)?;
+
+    let logos = [&brand.logo.small, &brand.logo.medium, &brand.logo.large];
+    for (size, logo) in LOGO_SIZES.iter().zip(logos) {
+        let Some(BrandImage { path, alt }) = logo else {
+            continue;
+        };
+        meta.insert_path(
+            &["brand", "logo", size, "path"],
+            ConfigValue::new_path(relative_href(document_dir, path), SourceInfo::default()),
+        );
+        if let Some(alt) = alt {
+            meta.insert_path(
+                &["brand", "logo", size, "alt"],
+                ConfigValue::new_string(alt, SourceInfo::default()),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The logo a document's metadata records for the first available size in
+/// `sizes`: its path relative to the document and its alt text.
+pub fn brand_logo(meta: &ConfigValue, sizes: &[&str]) -> Option<(String, String)> {
+    sizes.iter().find_map(|size| {
+        let path = meta.get_path(&["brand", "logo", size, "path"])?.as_str()?;
+        let alt = meta
+            .get_path(&["brand", "logo", size, "alt"])
+            .and_then(ConfigValue::as_str)
+            .unwrap_or_default();
+        Some((path.to_string(), alt.to_string()))
+    })
+}
+
+/// A `/`-separated href from directory `from` to `to`.
+fn relative_href(from: &Path, to: &Path) -> String {
+    let from_components: Vec<Component> = from.components().collect();
+    let to_components: Vec<Component> = to.components().collect();
+    let common = from_components
+        .iter()
+        .zip(&to_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+    // Nothing in common with an absolute path: no relative href exists
+    if common == 0 && (from.is_absolute() || to.is_absolute()) {
+        return to.to_string_lossy().replace('\\', "/");
+    }
+
+    let mut parts: Vec<String> = from_components[common..]
+        .iter()
+        .map(|_| "..".to_string())
+        .collect();
+    parts.extend(
+        to_components[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_system_runtime::NativeRuntime;
+    use std::path::PathBuf;
+
+    fn write(dir: &Path, path: &str, content: &str) -> PathBuf {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn meta() -> ConfigValue {
+        ConfigValue::new_map(vec![], SourceInfo::default())
+    }
+
+    #[test]
+    fn test_relative_href() {
+        assert_eq!(
+            relative_href(Path::new("/p/posts"), Path::new("/p/logos/a.png")),
+            "../logos/a.png"
+        );
+        assert_eq!(
+            relative_href(Path::new("/p"), Path::new("/p/a.png")),
+            "a.png"
+        );
+    }
+
+    #[test]
+    fn test_logos_recorded_relative_to_document() {
+        let temp = tempfile::tempdir().unwrap();
+        write(
+            temp.path(),
+            "_brand.yml",
+            "logo:\n  small: logos/icon.png\n  medium:\n    path: logos/logo.png\n    alt: Acme\n",
+        );
+        let doc = write(temp.path(), "posts/post.qmd", "# Post\n");
+
+        let mut meta = meta();
+        apply_brand(
+            &mut meta,
+            &doc,
+            temp.path(),
+            &NativeRuntime::new(),
+            &mut SourceContext::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            brand_logo(&meta, &LOGO_SIZES),
+            Some(("../logos/icon.png".to_string(), String::new()))
+        );
+        assert_eq!(
+            brand_logo(&meta, &["medium"]),
+            Some(("../logos/logo.png".to_string(), "Acme".to_string()))
+        );
+        assert_eq!(brand_logo(&meta, &["large"]), None);
+    }
+
+    #[test]
+    fn test_brand_false_opts_out() {
+        let temp = tempfile::tempdir().unwrap();
+        write(temp.path(), "_brand.yml", "logo: logo.png\n");
+        let doc = write(temp.path(), "doc.qmd", "");
+
+        let mut meta = meta();
+        meta.insert_path(
+            &["brand"],
+            ConfigValue::new_bool(false, SourceInfo::default()),
+        );
+        apply_brand(
+            &mut meta,
+            &doc,
+            temp.path(),
+            &NativeRuntime::new(),
+            &mut SourceContext::new(),
+        )
+        .unwrap();
+
+        assert_eq!(brand_logo(&meta, &LOGO_SIZES), None);
+    }
+
+    #[test]
+    fn test_invalid_brand_reports_diagnostics() {
+        let temp = tempfile::tempdir().unwrap();
+        write(temp.path(), "_brand.yml", "logo:\n  tiny: logo.png\n");
+        let doc = write(temp.path(), "doc.qmd", "");
+
+        let mut source_context = SourceContext::new();
+        let errors = apply_brand(
+            &mut meta(),
+            &doc,
+            temp.path(),
+            &NativeRuntime::new(),
+            &mut source_context,
+        )
+        .unwrap_err();
+
+        assert_eq!(errors[0].title, "Invalid brand file");
+        let location = errors[0].location.as_ref().unwrap();
+        let mapped = location.map_offset(0, &source_context).unwrap();
+        assert_eq!(
+            source_context.get_file(mapped.file_id).unwrap().path,
+            temp.path().join("_brand.yml").to_string_lossy()
+        );
+    }
+}
@@ -0,0 +1,228 @@
+/*
+ * engine/cache.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Per-cell execution cache.
+ */
+
+//! Per-cell execution cache.
+//!
+//! Cells with `cache: true` (or all cells, with `execute: { cache: true }`
+//! in the document or `--cache` on the command line) have their outputs
+//! stored under a key derived from the cell source and its declared
+//! dependencies. When a later render computes the same key, the engine
+//! reuses the stored outputs instead of running the cell again.
+//!
+//! This is independent of freeze: freeze skips execution of whole documents,
+//! while the cell cache still runs the engine but skips unchanged cells.
+//!
+//! Entries are stored through [`SystemRuntime`] at
+//! `{project}/.quarto/cell-cache/{document}/{key}.md`, so the cache works
+//! with any runtime, including the hub's virtual filesystem.
+//!
+//! # Keys
+//!
+//! A key is a hash of the engine/kernel name, the cell source (including its
+//! `#|` option lines), the source of every cell it names in `dependson`, and
+//! the execution parameters. Keys use `std`'s `DefaultHasher`, so a toolchain
+//! change may invalidate the cache; the worst case is re-execution.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use quarto_system_runtime::SystemRuntime;
+
+/// Version of the cache entry format, mixed into every key.
+const CACHE_VERSION: u32 = 1;
+
+/// Store of cached cell outputs for one document.
+#[derive(Clone)]
+pub struct CellCache {
+    /// Runtime used for all cache I/O
+    runtime: Arc<dyn SystemRuntime>,
+
+    /// Directory holding this document's entries
+    dir: PathBuf,
+
+    /// Whether cells without a `cache` option are cached
+    default_enabled: bool,
+
+    /// Ignore existing entries (`--cache-refresh`), still writing new ones
+    refresh: bool,
+}
+
+impl CellCache {
+    /// Create a cache for `document` within `project_dir`.
+    pub fn new(runtime: Arc<dyn SystemRuntime>, project_dir: &Path, document: &Path) -> Self {
+        let relative = document.strip_prefix(project_dir).unwrap_or(document);
+        let name = relative
+            .to_string_lossy()
+            .replace(['/', '\\'], "-")
+            .trim_start_matches('-')
+            .to_string();
+
+        Self {
+            runtime,
+            dir: project_dir.join(".quarto").join("cell-cache").join(name),
+            default_enabled: false,
+            refresh: false,
+        }
+    }
+
+    /// Cache cells that don't set the `cache` option themselves.
+    pub fn with_default_enabled(mut self, enabled: bool) -> Self {
+        self.default_enabled = enabled;
+        self
+    }
+
+    /// Ignore existing entries, re-executing and overwriting them.
+    pub fn with_refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// The directory holding this document's entries.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Whether a cell with the given `cache` option should be cached.
+    pub fn is_enabled_for(&self, cell_option: Option<bool>) -> bool {
+        cell_option.unwrap_or(self.default_enabled)
+    }
+
+    /// Compute the key for a cell from its source and dependencies.
+    ///
+    /// `dependencies` are the sources of the cells named in `dependson`,
+    /// in declaration order.
+    pub fn key(
+        engine: &str,
+        source: &str,
+        dependencies: &[&str],
+        params: &serde_json::Map<String, serde_json::Value>,
+    ) -> String {
+        let mut hasher = DefaultHasher::new();
+        CACHE_VERSION.hash(&mut hasher);
+        engine.hash(&mut hasher);
+        source.hash(&mut hasher);
+        dependencies.hash(&mut hasher);
+        if !params.is_empty() {
+            serde_json::Value::Object(params.clone())
+                .to_string()
+                .hash(&mut hasher);
+        }
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// Look up the stored outputs for `key`.
+    ///
+    /// Always misses when refreshing.
+    pub fn get(&self, key: &str) -> Option<String> {
+        if self.refresh {
+            return None;
+        }
+        let bytes = self.runtime.file_read(&self.entry_path(key)).ok()?;
+        String::from_utf8(bytes).ok()
+    }
+
+    /// Store the outputs for `key`.
+    ///
+    /// Failures are logged and otherwise ignored: a cache that can't be
+    /// written only costs re-execution next time.
+    pub fn put(&self, key: &str, outputs: &str) {
+        let result = self.runtime.dir_create(&self.dir, true).and_then(|_| {
+            self.runtime
+                .file_write(&self.entry_path(key), outputs.as_bytes())
+        });
+        if let Err(e) = result {
+            tracing::warn!(key, "Failed to write cell cache entry: {}", e);
+        }
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.md", key))
+    }
+}
+
+impl std::fmt::Debug for CellCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CellCache")
+            .field("dir", &self.dir)
+            .field("default_enabled", &self.default_enabled)
+            .field("refresh", &self.refresh)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_system_runtime::NativeRuntime;
+
+    fn params() -> serde_json::Map<String, serde_json::Value> {
+        serde_json::Map::new()
+    }
+
+    #[test]
+    fn test_cache_dir_per_document() {
+        let cache = CellCache::new(
+            Arc::new(NativeRuntime::new()),
+            Path::new("/project"),
+            Path::new("/project/posts/analysis.qmd"),
+        );
+        assert_eq!(
+            cache.dir(),
+            Path::new("/project/.quarto/cell-cache/posts-analysis.qmd")
+        );
+    }
+
+    #[test]
+    fn test_key_depends_on_source_dependencies_and_params() {
+        let base = CellCache::key("python3", "x = 1", &[], &params());
+
+        assert_eq!(base, CellCache::key("python3", "x = 1", &[], &params()));
+        assert_ne!(base, CellCache::key("python3", "x = 2", &[], &params()));
+        assert_ne!(base, CellCache::key("julia", "x = 1", &[], &params()));
+        assert_ne!(
+            base,
+            CellCache::key("python3", "x = 1", &["y = 2"], &params())
+        );
+
+        let mut with_params = params();
+        with_params.insert("n".to_string(), serde_json::json!(1));
+        assert_ne!(base, CellCache::key("python3", "x = 1", &[], &with_params));
+    }
+
+    #[test]
+    fn test_is_enabled_for() {
+        let runtime: Arc<dyn SystemRuntime> = Arc::new(NativeRuntime::new());
+        let cache = CellCache::new(runtime, Path::new("/p"), Path::new("/p/doc.qmd"));
+        assert!(!cache.is_enabled_for(None));
+        assert!(cache.is_enabled_for(Some(true)));
+
+        let cache = cache.with_default_enabled(true);
+        assert!(cache.is_enabled_for(None));
+        assert!(!cache.is_enabled_for(Some(false)));
+    }
+
+    #[test]
+    fn test_get_and_put_roundtrip() {
+        let temp = tempfile::tempdir().unwrap();
+        let runtime: Arc<dyn SystemRuntime> = Arc::new(NativeRuntime::new());
+        let cache = CellCache::new(runtime, temp.path(), &temp.path().join("doc.qmd"));
+
+        let key = CellCache::key("python3", "print(1)", &[], &params());
+        assert!(cache.get(&key).is_none());
+
+        cache.put(&key, "\n```{.cell-output-stdout}\n1\n```\n");
+        assert_eq!(
+            cache.get(&key).as_deref(),
+            Some("\n```{.cell-output-stdout}\n1\n```\n")
+        );
+
+        let refreshing = cache.clone().with_refresh(true);
+        assert!(refreshing.get(&key).is_none());
+    }
+}
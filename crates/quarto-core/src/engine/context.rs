@@ -11,6 +11,7 @@ use std::path::PathBuf;
 
 use quarto_pandoc_types::ConfigValue;
 
+use super::cache::CellCache;
 use crate::stage::PandocIncludes;
 
 /// Context provided to execution engines.
@@ -51,6 +52,28 @@ pub struct ExecutionContext {
     /// For example, for `engine: { jupyter: { kernel: python3 } }`,
     /// this would contain the `{ kernel: python3 }` map.
     pub engine_config: Option<ConfigValue>,
+
+    /// Execution parameters (`-P KEY:VALUE`, `--execute-params`).
+    ///
+    /// Engines that support parameters define these as variables in the
+    /// session before the document's own code runs.
+    pub params: serde_json::Map<String, serde_json::Value>,
+
+    /// Kernel keepalive in seconds (`--execute-daemon`).
+    ///
+    /// `None` leaves the kernel lifetime to the engine, `Some(0)` shuts the
+    /// kernel down after execution, and any other value keeps it running for
+    /// reuse by later renders.
+    pub daemon: Option<u32>,
+
+    /// Restart a running kernel before execution (`--execute-daemon-restart`).
+    pub daemon_restart: bool,
+
+    /// Per-cell output cache, if caching is available for this render.
+    ///
+    /// Engines that execute cells one at a time consult it for cells with
+    /// `cache: true`; see [`CellCache`].
+    pub cache: Option<CellCache>,
 }
 
 impl ExecutionContext {
@@ -69,6 +92,10 @@ impl ExecutionContext {
             format: format.into(),
             quiet: false,
             engine_config: None,
+            params: serde_json::Map::new(),
+            daemon: None,
+            daemon_restart: false,
+            cache: None,
         }
     }
 
@@ -89,6 +116,71 @@ impl ExecutionContext {
         self.engine_config = config;
         self
     }
+
+    /// Set execution parameters.
+    pub fn with_params(mut self, params: serde_json::Map<String, serde_json::Value>) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Set kernel keepalive and restart behavior.
+    pub fn with_daemon(mut self, daemon: Option<u32>, restart: bool) -> Self {
+        self.daemon = daemon;
+        self.daemon_restart = restart;
+        self
+    }
+
+    /// Set the per-cell output cache.
+    pub fn with_cache(mut self, cache: Option<CellCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+}
+
+/// Render-level options controlling code execution.
+///
+/// These come from the `--execute*` command line flags and are carried on the
+/// [`StageContext`](crate::stage::StageContext) so the engine execution stage
+/// can build an [`ExecutionContext`] from them.
+#[derive(Debug, Clone)]
+pub struct ExecuteOptions {
+    /// Whether to execute code cells at all (`--no-execute` disables).
+    pub enabled: bool,
+
+    /// Execution parameters.
+    pub params: serde_json::Map<String, serde_json::Value>,
+
+    /// Working directory for execution, overriding the default.
+    pub dir: Option<PathBuf>,
+
+    /// Kernel keepalive in seconds (see [`ExecutionContext::daemon`]).
+    pub daemon: Option<u32>,
+
+    /// Restart a running kernel before execution.
+    pub daemon_restart: bool,
+
+    /// Cache cell outputs (`--cache` / `--no-cache`).
+    ///
+    /// `None` defers to the document's `execute: { cache: ... }` option,
+    /// `Some(false)` disables the cell cache entirely.
+    pub cache: Option<bool>,
+
+    /// Re-execute cached cells and overwrite their entries (`--cache-refresh`).
+    pub cache_refresh: bool,
+}
+
+impl Default for ExecuteOptions {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            params: serde_json::Map::new(),
+            dir: None,
+            daemon: None,
+            daemon_restart: false,
+            cache: None,
+            cache_refresh: false,
+        }
+    }
 }
 
 /// Result of engine execution.
@@ -188,6 +280,39 @@ mod tests {
         assert!(!ctx.quiet);
         assert!(ctx.project_dir.is_none());
         assert!(ctx.engine_config.is_none());
+        assert!(ctx.params.is_empty());
+        assert!(ctx.daemon.is_none());
+        assert!(!ctx.daemon_restart);
+    }
+
+    #[test]
+    fn test_execution_context_with_params_and_daemon() {
+        let mut params = serde_json::Map::new();
+        params.insert("alpha".to_string(), serde_json::json!(0.5));
+
+        let ctx = ExecutionContext::new(
+            PathBuf::from("/tmp"),
+            PathBuf::from("/project"),
+            PathBuf::from("/project/doc.qmd"),
+            "html",
+        )
+        .with_params(params)
+        .with_daemon(Some(0), true);
+
+        assert_eq!(ctx.params.get("alpha"), Some(&serde_json::json!(0.5)));
+        assert_eq!(ctx.daemon, Some(0));
+        assert!(ctx.daemon_restart);
+    }
+
+    #[test]
+    fn test_execute_options_default_enables_execution() {
+        let options = ExecuteOptions::default();
+
+        assert!(options.enabled);
+        assert!(options.params.is_empty());
+        assert!(options.dir.is_none());
+        assert!(options.cache.is_none());
+        assert!(!options.cache_refresh);
     }
 
     #[test]
@@ -17,15 +17,20 @@
 //! 1. Explicit `engine:` key with string value: `engine: knitr`
 //! 2. Explicit `engine:` key with map value: `engine: { jupyter: { kernel: python3 } }`
 //! 3. Engine-specific top-level keys: `jupyter: { kernel: python3 }`
-//! 4. Default to "markdown" if no engine is declared
+//! 4. Executable code cell languages ([`detect_document_engine`] only):
+//!    any `{r}` cell selects knitr, otherwise a `{python}`, `{julia}`, ...
+//!    cell selects jupyter
+//! 5. Default to "markdown" if no engine is declared
 //!
 //! # Future Enhancements
 //!
 //! In future phases, detection will also consider:
-//! - Code block languages (`{python}` → jupyter, `{r}` → knitr)
 //! - File extension (`.ipynb` → jupyter, `.Rmd` → knitr)
 
+use pampa::pandoc::cell_options::cell_language;
 use quarto_pandoc_types::ConfigValue;
+use quarto_pandoc_types::block::Block;
+use quarto_pandoc_types::pandoc::Pandoc;
 
 /// Known execution engine names.
 pub const KNOWN_ENGINES: &[&str] = &["markdown", "knitr", "jupyter"];
@@ -82,6 +87,25 @@ impl Default for DetectedEngine {
     }
 }
 
+/// Cell languages executed by the jupyter engine.
+///
+/// `{r}` cells are claimed by knitr, so R is not listed here.
+const JUPYTER_LANGUAGES: &[&str] = &[
+    "python",
+    "python3",
+    "py",
+    "julia",
+    "jl",
+    "ruby",
+    "rb",
+    "rust",
+    "rs",
+    "typescript",
+    "ts",
+    "javascript",
+    "js",
+];
+
 /// Check if a name is a known engine.
 pub fn is_known_engine(name: &str) -> bool {
     KNOWN_ENGINES.contains(&name)
@@ -148,13 +172,75 @@ fn extract_string_value(value: &ConfigValue) -> Option<&str> {
 /// assert_eq!(detected.name, "markdown");
 /// ```
 pub fn detect_engine(metadata: &ConfigValue) -> DetectedEngine {
+    detect_declared_engine(metadata).unwrap_or_default()
+}
+
+/// Detect the execution engine for a whole document.
+///
+/// Like [`detect_engine`], but when the metadata doesn't declare an engine
+/// the languages of the document's code cells decide: a document with any
+/// `{r}` cell is run by knitr, one with other executable cells (`{python}`,
+/// `{julia}`, ...) by jupyter. Documents without executable cells use the
+/// markdown engine.
+pub fn detect_document_engine(ast: &Pandoc) -> DetectedEngine {
+    detect_declared_engine(&ast.meta)
+        .or_else(|| detect_engine_from_languages(&ast.blocks))
+        .unwrap_or_default()
+}
+
+/// Detect the engine from the languages of executable code cells.
+fn detect_engine_from_languages(blocks: &[Block]) -> Option<DetectedEngine> {
+    let mut languages = Vec::new();
+    collect_cell_languages(blocks, &mut languages);
+
+    if languages.iter().any(|lang| lang == "r") {
+        return Some(DetectedEngine::new("knitr"));
+    }
+    if languages
+        .iter()
+        .any(|lang| JUPYTER_LANGUAGES.contains(&lang.as_str()))
+    {
+        return Some(DetectedEngine::new("jupyter"));
+    }
+    None
+}
+
+/// Collect the (lowercased) languages of all code cells in `blocks`.
+fn collect_cell_languages(blocks: &[Block], languages: &mut Vec<String>) {
+    for block in blocks {
+        match block {
+            Block::CodeBlock(cb) => {
+                if let Some(lang) = cell_language(&cb.attr) {
+                    languages.push(lang.to_lowercase());
+                }
+            }
+            Block::Div(div) => collect_cell_languages(&div.content, languages),
+            Block::BlockQuote(bq) => collect_cell_languages(&bq.content, languages),
+            Block::Figure(figure) => collect_cell_languages(&figure.content, languages),
+            Block::BulletList(list) => {
+                for item in &list.content {
+                    collect_cell_languages(item, languages);
+                }
+            }
+            Block::OrderedList(list) => {
+                for item in &list.content {
+                    collect_cell_languages(item, languages);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Detect an engine explicitly declared in metadata, if any.
+fn detect_declared_engine(metadata: &ConfigValue) -> Option<DetectedEngine> {
     // Case 1: Look for explicit "engine" key
     if let Some(engine_value) = metadata.get("engine") {
         // Case 1a: engine: markdown|knitr|jupyter (string value)
         if let Some(name) = extract_string_value(engine_value) {
             // Return the engine name even if unknown - the pipeline stage
             // will handle fallback and warning for unknown engines
-            return DetectedEngine::new(name);
+            return Some(DetectedEngine::new(name));
         }
 
         // Case 1b: engine: { knitr: ... } or engine: { jupyter: ... }
@@ -165,7 +251,10 @@ pub fn detect_engine(metadata: &ConfigValue) -> DetectedEngine {
 
                 // Return even if unknown - the pipeline stage will
                 // handle fallback and warning for unknown engines
-                return DetectedEngine::with_config(engine_name.clone(), first_entry.value.clone());
+                return Some(DetectedEngine::with_config(
+                    engine_name.clone(),
+                    first_entry.value.clone(),
+                ));
             }
         }
     }
@@ -181,12 +270,14 @@ pub fn detect_engine(metadata: &ConfigValue) -> DetectedEngine {
         }
 
         if let Some(config) = metadata.get(engine_name) {
-            return DetectedEngine::with_config(engine_name.to_string(), config.clone());
+            return Some(DetectedEngine::with_config(
+                engine_name.to_string(),
+                config.clone(),
+            ));
         }
     }
 
-    // Default: markdown engine (no execution)
-    DetectedEngine::default()
+    None
 }
 
 #[cfg(test)]
@@ -403,4 +494,50 @@ mod tests {
         let detected = detect_engine(&meta);
         assert_eq!(detected.name, "markdown");
     }
+
+    // === Document (cell language) detection tests ===
+
+    fn parse(qmd: &str) -> Pandoc {
+        let (ast, _, _) = pampa::readers::qmd::read(
+            qmd.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .expect("test document should parse");
+        ast
+    }
+
+    #[test]
+    fn test_detect_document_engine_r_cells_select_knitr() {
+        let ast =
+            parse("```{python}\nx = 1\n```\n\n::: {.callout-note}\n```{r}\nx <- 1\n```\n:::\n");
+
+        assert_eq!(detect_document_engine(&ast).name, "knitr");
+    }
+
+    #[test]
+    fn test_detect_document_engine_python_cells_select_jupyter() {
+        let ast = parse("# Analysis\n\n```{python}\nprint(1)\n```\n");
+
+        let detected = detect_document_engine(&ast);
+        assert_eq!(detected.name, "jupyter");
+        assert!(detected.config.is_none());
+    }
+
+    #[test]
+    fn test_detect_document_engine_ignores_non_executable_cells() {
+        let ast = parse("```{mermaid}\ngraph TD\n```\n\n```python\nprint(1)\n```\n");
+
+        assert_eq!(detect_document_engine(&ast).name, "markdown");
+    }
+
+    #[test]
+    fn test_detect_document_engine_metadata_takes_precedence() {
+        let ast = parse("---\nengine: markdown\n---\n\n```{r}\nx <- 1\n```\n");
+
+        assert_eq!(detect_document_engine(&ast).name, "markdown");
+    }
 }
@@ -10,6 +10,12 @@
 //! This module implements the text-in/text-out pattern required by [`ExecutionEngine`].
 //! It parses QMD input, executes code blocks via the Jupyter daemon, and returns
 //! markdown with outputs inserted.
+//!
+//! Execution parameters from the [`ExecutionContext`] are assigned in the
+//! kernel right after the cell tagged `parameters` (or before the first cell),
+//! and the context's daemon settings control whether the kernel is restarted
+//! first or shut down afterwards. Cells with `cache: true` reuse outputs from
+//! the context's [`CellCache`] when their source is unchanged.
 
 use std::path::PathBuf;
 
@@ -20,6 +26,7 @@ use super::error::JupyterError;
 use super::execute::{CellOutput, ExecuteResult as KernelExecuteResult, ExecuteStatus};
 use super::output::strip_ansi_codes;
 use super::session::SessionKey;
+use crate::engine::cache::CellCache;
 use crate::engine::context::{ExecuteResult, ExecutionContext};
 use crate::engine::error::ExecutionError;
 
@@ -59,7 +66,7 @@ pub fn execute_qmd(
     let kernel_name = map_language_to_kernel(&blocks[0].language);
 
     // Execute via async runtime
-    let result = execute_blocks_async(input, &blocks, &kernel_name, &ctx.cwd);
+    let result = execute_blocks_async(input, &blocks, &kernel_name, ctx);
 
     result.map_err(|e| ExecutionError::execution_failed("jupyter", e.to_string()))
 }
@@ -134,12 +141,172 @@ fn is_executable_language(language: &str) -> bool {
     )
 }
 
+/// Parse a code block's leading `#|` option lines as YAML.
+///
+/// Returns an empty map if there are no options or they aren't a valid
+/// YAML mapping.
+fn cell_options(block: &CodeBlock) -> serde_json::Map<String, serde_json::Value> {
+    let yaml = block
+        .code
+        .lines()
+        .map_while(|line| line.trim_start().strip_prefix("#|"))
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if yaml.trim().is_empty() {
+        return serde_json::Map::new();
+    }
+
+    match serde_yaml::from_str(&yaml) {
+        Ok(serde_json::Value::Object(options)) => options,
+        _ => serde_json::Map::new(),
+    }
+}
+
+/// Check if a cell is the document's parameters cell.
+///
+/// As in Quarto 1.x (and papermill), the parameters cell is the one tagged
+/// `parameters` via a `#| tags: [parameters]` option line.
+fn is_parameters_cell(options: &serde_json::Map<String, serde_json::Value>) -> bool {
+    match options.get("tags") {
+        Some(serde_json::Value::Array(tags)) => tags.iter().any(|tag| tag == "parameters"),
+        Some(tag) => tag == "parameters",
+        None => false,
+    }
+}
+
+/// Compute the cell cache key for `blocks[index]`, if it should be cached.
+///
+/// The key covers the cell source and the sources of the cells it names in
+/// its `dependson` option (a label or list of labels).
+fn cell_cache_key(
+    index: usize,
+    blocks: &[CodeBlock],
+    options: &[serde_json::Map<String, serde_json::Value>],
+    kernel_name: &str,
+    ctx: &ExecutionContext,
+) -> Option<String> {
+    let cache = ctx.cache.as_ref()?;
+    let cell_cache = options[index].get("cache").and_then(|v| v.as_bool());
+    if !cache.is_enabled_for(cell_cache) {
+        return None;
+    }
+
+    let labels: Vec<&str> = match options[index].get("dependson") {
+        Some(serde_json::Value::String(label)) => vec![label.as_str()],
+        Some(serde_json::Value::Array(labels)) => {
+            labels.iter().filter_map(|label| label.as_str()).collect()
+        }
+        _ => Vec::new(),
+    };
+    let dependencies: Vec<&str> = labels
+        .iter()
+        .filter_map(|label| {
+            options
+                .iter()
+                .position(|o| o.get("label").and_then(|v| v.as_str()) == Some(label))
+                .map(|i| blocks[i].code.as_str())
+        })
+        .collect();
+
+    Some(CellCache::key(
+        kernel_name,
+        &blocks[index].code,
+        &dependencies,
+        &ctx.params,
+    ))
+}
+
+/// Generate code assigning execution parameters in the given language.
+///
+/// Returns `None` if there are no parameters or the language has no
+/// known assignment syntax.
+fn parameters_code(
+    language: &str,
+    params: &serde_json::Map<String, serde_json::Value>,
+) -> Option<String> {
+    if params.is_empty() {
+        return None;
+    }
+
+    let assign = match language.to_lowercase().as_str() {
+        "python" | "python3" | "py" => " = ",
+        "julia" | "jl" => " = ",
+        "r" => " <- ",
+        _ => return None,
+    };
+
+    let mut code = String::new();
+    for (name, value) in params {
+        code.push_str(name);
+        code.push_str(assign);
+        code.push_str(&parameter_literal(language, value));
+        code.push('\n');
+    }
+    Some(code)
+}
+
+/// Format a JSON value as a literal in the given language.
+fn parameter_literal(language: &str, value: &serde_json::Value) -> String {
+    use serde_json::Value;
+
+    let language = language.to_lowercase();
+    let is_r = language == "r";
+    let is_julia = matches!(language.as_str(), "julia" | "jl");
+
+    match value {
+        Value::Null if is_r => "NULL".to_string(),
+        Value::Null if is_julia => "nothing".to_string(),
+        Value::Null => "None".to_string(),
+        Value::Bool(b) if is_r => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        Value::Bool(b) if is_julia => b.to_string(),
+        Value::Bool(b) => if *b { "True" } else { "False" }.to_string(),
+        // JSON number and string literals are valid in all three languages
+        Value::Number(_) | Value::String(_) => value.to_string(),
+        Value::Array(items) => {
+            let items: Vec<String> = items
+                .iter()
+                .map(|item| parameter_literal(&language, item))
+                .collect();
+            if is_r {
+                format!("list({})", items.join(", "))
+            } else {
+                format!("[{}]", items.join(", "))
+            }
+        }
+        Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(key, item)| {
+                    let key = serde_json::Value::String(key.clone()).to_string();
+                    let item = parameter_literal(&language, item);
+                    if is_r {
+                        format!("{} = {}", key, item)
+                    } else if is_julia {
+                        format!("{} => {}", key, item)
+                    } else {
+                        format!("{}: {}", key, item)
+                    }
+                })
+                .collect();
+            if is_r {
+                format!("list({})", entries.join(", "))
+            } else if is_julia {
+                format!("Dict({})", entries.join(", "))
+            } else {
+                format!("{{{}}}", entries.join(", "))
+            }
+        }
+    }
+}
+
 /// Execute code blocks asynchronously and build output markdown.
 fn execute_blocks_async(
     input: &str,
     blocks: &[CodeBlock],
     kernel_name: &str,
-    working_dir: &PathBuf,
+    ctx: &ExecutionContext,
 ) -> JupyterResult<ExecuteResult> {
     // Use tokio runtime to execute async code
     let rt = tokio::runtime::Builder::new_current_thread()
@@ -147,12 +314,7 @@ fn execute_blocks_async(
         .build()
         .map_err(|e| JupyterError::RuntimeLibError(e.to_string()))?;
 
-    rt.block_on(execute_blocks_inner(
-        input,
-        blocks,
-        kernel_name,
-        working_dir,
-    ))
+    rt.block_on(execute_blocks_inner(input, blocks, kernel_name, ctx))
 }
 
 /// Inner async function that does the actual execution.
@@ -160,20 +322,59 @@ async fn execute_blocks_inner(
     input: &str,
     blocks: &[CodeBlock],
     kernel_name: &str,
-    working_dir: &PathBuf,
+    ctx: &ExecutionContext,
 ) -> JupyterResult<ExecuteResult> {
     let daemon = daemon();
+    let working_dir: &PathBuf = &ctx.cwd;
+
+    // `--execute-daemon-restart`: start from a fresh kernel
+    if ctx.daemon_restart {
+        daemon
+            .shutdown_session(&SessionKey::new(kernel_name, working_dir.clone()))
+            .await?;
+    }
 
     // Start or get existing kernel session
     let key: SessionKey = daemon
         .get_or_start_session(kernel_name, working_dir)
         .await?;
 
+    let result = execute_blocks_in_session(input, blocks, &key, ctx).await;
+
+    // `--execute-daemon 0`: don't keep the kernel around
+    if ctx.daemon == Some(0) {
+        daemon.shutdown_session(&key).await?;
+    }
+
+    result
+}
+
+/// Execute the blocks in a running session and interleave their outputs.
+async fn execute_blocks_in_session(
+    input: &str,
+    blocks: &[CodeBlock],
+    key: &SessionKey,
+    ctx: &ExecutionContext,
+) -> JupyterResult<ExecuteResult> {
+    let daemon = daemon();
+
+    let options: Vec<_> = blocks.iter().map(cell_options).collect();
+
+    // Parameters are injected after the parameters cell, so they override its
+    // defaults, or before the first cell if there is none.
+    let parameters = parameters_code(&blocks[0].language, &ctx.params);
+    let parameters_index = options.iter().position(is_parameters_cell);
+    if parameters_index.is_none()
+        && let Some(code) = &parameters
+    {
+        execute_silently(key, code).await?;
+    }
+
     // Build output by processing blocks in order
     let mut output = String::new();
     let mut last_end = 0;
 
-    for block in blocks {
+    for (index, block) in blocks.iter().enumerate() {
         // Append content before this block
         output.push_str(&input[last_end..block.start]);
 
@@ -181,18 +382,40 @@ async fn execute_blocks_inner(
         output.push_str(&block.original);
         output.push('\n');
 
-        // Execute the code
-        let exec_result = daemon
-            .execute_in_session(&key, &block.code)
-            .await
-            .ok_or_else(|| JupyterError::NotConnected)??;
+        // Reuse cached outputs for unchanged cells
+        let cache_key = cell_cache_key(index, blocks, &options, &key.kernel_name, ctx);
+        let cached = cache_key
+            .as_ref()
+            .and_then(|k| ctx.cache.as_ref().and_then(|cache| cache.get(k)));
+
+        let output_md = match cached {
+            Some(output_md) => output_md,
+            None => {
+                // Execute the code
+                let exec_result = daemon
+                    .execute_in_session(key, &block.code)
+                    .await
+                    .ok_or(JupyterError::NotConnected)??;
+
+                // Format the outputs, storing them if the cell is cached
+                let output_md = format_outputs(&exec_result);
+                if let (Some(cache), Some(k)) = (&ctx.cache, &cache_key) {
+                    cache.put(k, &output_md);
+                }
+                output_md
+            }
+        };
 
-        // Format and append outputs
-        let output_md = format_outputs(&exec_result);
         if !output_md.is_empty() {
             output.push_str(&output_md);
         }
 
+        if parameters_index == Some(index)
+            && let Some(code) = &parameters
+        {
+            execute_silently(key, code).await?;
+        }
+
         last_end = block.end;
     }
 
@@ -202,6 +425,28 @@ async fn execute_blocks_inner(
     Ok(ExecuteResult::new(output))
 }
 
+/// Execute code whose outputs don't appear in the document.
+async fn execute_silently(key: &SessionKey, code: &str) -> JupyterResult<()> {
+    let result = daemon()
+        .execute_in_session(key, code)
+        .await
+        .ok_or(JupyterError::NotConnected)??;
+
+    if let ExecuteStatus::Error {
+        ename,
+        evalue,
+        traceback,
+    } = result.status
+    {
+        return Err(JupyterError::ExecutionError {
+            ename,
+            evalue,
+            traceback,
+        });
+    }
+    Ok(())
+}
+
 /// Format kernel outputs as markdown.
 fn format_outputs(result: &KernelExecuteResult) -> String {
     let mut output = String::new();
@@ -352,6 +597,98 @@ print("hello")
         assert_eq!(blocks.len(), 1);
     }
 
+    #[test]
+    fn test_is_parameters_cell() {
+        let blocks = parse_code_blocks(
+            "```{python}\n#| tags: [parameters]\nalpha = 0.1\n```\n\n```{python}\n#| label: fig-plot\nplot()\n```\n",
+        );
+        assert!(is_parameters_cell(&cell_options(&blocks[0])));
+        assert!(!is_parameters_cell(&cell_options(&blocks[1])));
+    }
+
+    #[test]
+    fn test_cell_options() {
+        let blocks =
+            parse_code_blocks("```{python}\n#| label: setup\n#| cache: true\nx = 1\n```\n");
+        let options = cell_options(&blocks[0]);
+        assert_eq!(options.get("label"), Some(&serde_json::json!("setup")));
+        assert_eq!(options.get("cache"), Some(&serde_json::json!(true)));
+    }
+
+    #[test]
+    fn test_cell_cache_key_follows_dependencies() {
+        let cache_ctx = |input: &str| {
+            let blocks = parse_code_blocks(input);
+            let options: Vec<_> = blocks.iter().map(cell_options).collect();
+            let runtime: std::sync::Arc<dyn quarto_system_runtime::SystemRuntime> =
+                std::sync::Arc::new(quarto_system_runtime::NativeRuntime::new());
+            let ctx = ExecutionContext::new(
+                PathBuf::from("/tmp"),
+                PathBuf::from("/project"),
+                PathBuf::from("/project/doc.qmd"),
+                "html",
+            )
+            .with_cache(Some(CellCache::new(
+                runtime,
+                std::path::Path::new("/project"),
+                std::path::Path::new("/project/doc.qmd"),
+            )));
+            (0..blocks.len())
+                .map(|i| cell_cache_key(i, &blocks, &options, "python3", &ctx))
+                .collect::<Vec<_>>()
+        };
+
+        let before = cache_ctx(
+            "```{python}\n#| label: data\nx = 1\n```\n\n```{python}\n#| cache: true\n#| dependson: data\nprint(x)\n```\n",
+        );
+        let after = cache_ctx(
+            "```{python}\n#| label: data\nx = 2\n```\n\n```{python}\n#| cache: true\n#| dependson: data\nprint(x)\n```\n",
+        );
+
+        // Only the cell with `cache: true` gets a key...
+        assert!(before[0].is_none());
+        assert!(before[1].is_some());
+        // ...which changes when a cell it depends on changes
+        assert_ne!(before[1], after[1]);
+    }
+
+    #[test]
+    fn test_parameters_code_python() {
+        let mut params = serde_json::Map::new();
+        params.insert("alpha".to_string(), serde_json::json!(0.5));
+        params.insert("name".to_string(), serde_json::json!("run \"a\""));
+        params.insert("flags".to_string(), serde_json::json!([true, null]));
+
+        let code = parameters_code("python", &params).unwrap();
+        assert!(code.contains("alpha = 0.5\n"));
+        assert!(code.contains("name = \"run \\\"a\\\"\"\n"));
+        assert!(code.contains("flags = [True, None]\n"));
+    }
+
+    #[test]
+    fn test_parameters_code_r_and_julia() {
+        let mut params = serde_json::Map::new();
+        params.insert("show".to_string(), serde_json::json!(false));
+        params.insert("opts".to_string(), serde_json::json!({ "n": 2 }));
+
+        let r = parameters_code("r", &params).unwrap();
+        assert!(r.contains("show <- FALSE\n"));
+        assert!(r.contains("opts <- list(\"n\" = 2)\n"));
+
+        let julia = parameters_code("julia", &params).unwrap();
+        assert!(julia.contains("show = false\n"));
+        assert!(julia.contains("opts = Dict(\"n\" => 2)\n"));
+    }
+
+    #[test]
+    fn test_parameters_code_empty_or_unsupported() {
+        let mut params = serde_json::Map::new();
+        assert!(parameters_code("python", &params).is_none());
+
+        params.insert("n".to_string(), serde_json::json!(1));
+        assert!(parameters_code("rust", &params).is_none());
+    }
+
     #[test]
     fn test_map_language_to_kernel() {
         assert_eq!(map_language_to_kernel("python"), "python3");
@@ -179,7 +179,7 @@ impl ExecutionEngine for KnitrEngine {
             lib_dir: None,
             dependencies: true,
             cwd: working_dir.clone(),
-            params: None,
+            params: (!ctx.params.is_empty()).then(|| serde_json::Value::Object(ctx.params.clone())),
             resource_dir: resource_dir.to_path_buf(),
             // Languages that Quarto handles (knitr passes them through unchanged)
             handled_languages: vec!["ojs".to_string(), "mermaid".to_string(), "dot".to_string()],
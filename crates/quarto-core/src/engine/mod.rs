@@ -18,6 +18,8 @@
 //! - [`ExecutionEngine`] trait - Interface for all execution engines
 //! - [`EngineRegistry`] - Collection of available engines
 //! - [`detect_engine`] - Detection of engine from document metadata
+//! - [`detect_document_engine`] - Detection from metadata or code cell languages
+//! - [`CellCache`] - Per-cell output cache (`cache: true`)
 //! - Concrete engines:
 //!   - [`MarkdownEngine`] - No-op engine (always available)
 //!   - [`KnitrEngine`] - R code execution (native only)
@@ -53,6 +55,7 @@
 //! let result = engine.execute(&qmd_content, &context)?;
 //! ```
 
+mod cache;
 mod context;
 mod detection;
 mod error;
@@ -67,8 +70,11 @@ pub mod jupyter;
 mod knitr;
 
 // Re-export public types
-pub use context::{ExecuteResult, ExecutionContext};
-pub use detection::{DetectedEngine, KNOWN_ENGINES, detect_engine, is_known_engine};
+pub use cache::CellCache;
+pub use context::{ExecuteOptions, ExecuteResult, ExecutionContext};
+pub use detection::{
+    DetectedEngine, KNOWN_ENGINES, detect_document_engine, detect_engine, is_known_engine,
+};
 pub use error::ExecutionError;
 pub use markdown::MarkdownEngine;
 pub use registry::EngineRegistry;
@@ -78,7 +84,7 @@ pub use traits::ExecutionEngine;
 #[cfg(not(target_arch = "wasm32"))]
 pub use jupyter::JupyterEngine;
 #[cfg(not(target_arch = "wasm32"))]
-pub use knitr::KnitrEngine;
+pub use knitr::{KnitrEngine, find_rscript};
 
 #[cfg(test)]
 mod tests {
@@ -63,6 +63,9 @@ pub enum QuartoError {
     #[error("Render error: {0}")]
     Render(String),
 
+    #[error("Render was cancelled")]
+    Cancelled,
+
     #[error("{0}")]
     Other(String),
 }
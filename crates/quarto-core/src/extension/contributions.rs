@@ -0,0 +1,370 @@
+/*
+ * extension/contributions.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * What extensions contribute to rendering.
+ */
+
+//! What extensions contribute to rendering.
+//!
+//! An extension's manifest lists its contributions under `contributes`:
+//!
+//! ```yaml
+//! contributes:
+//!   shortcodes:
+//!     - fontawesome.lua
+//!   filters:
+//!     - lightbox.lua
+//!   formats:
+//!     common:
+//!       toc: true
+//!     pdf:
+//!       template-partials: [title.tex]
+//!     html:
+//!       css: styles.css
+//! ```
+//!
+//! Shortcode and filter scripts are relative to the extension's directory.
+//! Each key of `formats` other than `common` is a format named after the
+//! extension and the base format it builds on (`acm-pdf` for the `pdf` key
+//! of the `acm` extension). Its metadata is `common` overlaid with the
+//! base format's, with the paths of templates, partials, filters and
+//! shortcodes made absolute so they resolve wherever the document is.
+//!
+//! A document sees the extensions of its own directory and of each parent
+//! directory up to the project's ([`document_extensions`]); nearer ones
+//! shadow farther ones with the same id.
+
+use std::path::{Path, PathBuf};
+
+use quarto_system_runtime::SystemRuntime;
+use serde_json::Value;
+
+use super::{Extension, discover_extensions};
+use crate::error::{QuartoError, Result};
+
+/// Key of the format metadata shared by all of an extension's formats.
+const COMMON_FORMAT: &str = "common";
+
+/// Format metadata keys holding paths relative to the extension.
+const PATH_KEYS: [&str; 4] = ["template", "template-partials", "filters", "shortcodes"];
+
+/// Filter entry marking where Quarto's own transforms run.
+pub const QUARTO_FILTER: &str = "quarto";
+
+/// A format contributed by an extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionFormat {
+    /// Name documents use, e.g. `acm-pdf`
+    pub name: String,
+    /// Base format, e.g. `pdf`
+    pub base: String,
+    /// Metadata of the format, paths made absolute
+    pub metadata: Value,
+}
+
+impl Extension {
+    /// Shortcode scripts the extension contributes.
+    pub fn shortcodes(&self) -> Vec<PathBuf> {
+        self.contributed_paths("shortcodes")
+    }
+
+    /// Filter scripts the extension contributes, in order.
+    pub fn filters(&self) -> Vec<PathBuf> {
+        self.contributed_paths("filters")
+    }
+
+    /// Formats the extension contributes.
+    pub fn formats(&self) -> Vec<ExtensionFormat> {
+        let formats = self.contribution("formats");
+        let Some(formats) = formats.as_ref().and_then(Value::as_object) else {
+            return Vec::new();
+        };
+        let common = formats.get(COMMON_FORMAT);
+        formats
+            .iter()
+            .filter(|(base, _)| *base != COMMON_FORMAT)
+            .map(|(base, metadata)| {
+                let mut merged = common.cloned().unwrap_or(Value::Null);
+                merge_metadata(&mut merged, metadata);
+                ExtensionFormat {
+                    name: format!("{}-{}", self.id.name, base),
+                    base: base.clone(),
+                    metadata: self.absolute_paths(merged),
+                }
+            })
+            .collect()
+    }
+
+    /// A contribution converted to JSON, if the manifest has it.
+    fn contribution(&self, kind: &str) -> Option<Value> {
+        let value = self.manifest.contributes.get(kind)?;
+        serde_json::to_value(value).ok()
+    }
+
+    /// Paths listed under a contribution, relative to the extension.
+    fn contributed_paths(&self, kind: &str) -> Vec<PathBuf> {
+        let paths = match self.contribution(kind) {
+            Some(Value::Array(items)) => items,
+            Some(item @ (Value::String(_) | Value::Object(_))) => vec![item],
+            _ => Vec::new(),
+        };
+        paths
+            .iter()
+            .filter_map(entry_path)
+            .map(|path| self.dir.join(path))
+            .collect()
+    }
+
+    /// Make the paths of format metadata absolute.
+    fn absolute_paths(&self, mut metadata: Value) -> Value {
+        let Some(map) = metadata.as_object_mut() else {
+            return metadata;
+        };
+        for key in PATH_KEYS {
+            let Some(value) = map.get_mut(key) else {
+                continue;
+            };
+            match value {
+                Value::Array(items) => {
+                    for item in items {
+                        self.absolute_entry(item);
+                    }
+                }
+                item => self.absolute_entry(item),
+            }
+        }
+        metadata
+    }
+
+    fn absolute_entry(&self, entry: &mut Value) {
+        let target = match entry {
+            Value::Object(map) => map.get_mut("path"),
+            value => Some(value),
+        };
+        if let Some(Value::String(path)) = target
+            && path != QUARTO_FILTER
+            && Path::new(path.as_str()).is_relative()
+        {
+            *path = self.dir.join(path.as_str()).to_string_lossy().into_owned();
+        }
+    }
+}
+
+/// The path of a shortcode or filter entry: a string, or a map with `path`.
+fn entry_path(entry: &Value) -> Option<&str> {
+    match entry {
+        Value::String(path) => Some(path),
+        Value::Object(map) => map.get("path").and_then(Value::as_str),
+        _ => None,
+    }
+}
+
+/// Overlay `overlay` onto `base`: maps merge key by key, anything else
+/// replaces what was there.
+pub fn merge_metadata(base: &mut Value, overlay: &Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge_metadata(base.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (_, Value::Null) => {}
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/// The extensions available to a document in `document_dir`.
+///
+/// Looks in `_extensions` of `document_dir` and each parent up to
+/// `project_dir`, keeping the nearest extension of each id. Documents
+/// outside `project_dir` see only their own directory's extensions.
+pub fn document_extensions(
+    document_dir: &Path,
+    project_dir: &Path,
+    runtime: &dyn SystemRuntime,
+) -> Result<Vec<Extension>> {
+    let mut extensions: Vec<Extension> = Vec::new();
+    let mut dir = Some(document_dir);
+    while let Some(current) = dir {
+        for extension in discover_extensions(current, runtime)? {
+            if !extensions.iter().any(|e| e.id == extension.id) {
+                extensions.push(extension);
+            }
+        }
+        if current == project_dir || !current.starts_with(project_dir) {
+            break;
+        }
+        dir = current.parent();
+    }
+    Ok(extensions)
+}
+
+/// Find the extension format `name` (`acm-pdf`, or `owner/acm-pdf` to pick
+/// the extension's owner too).
+pub fn find_format(extensions: &[Extension], name: &str) -> Option<ExtensionFormat> {
+    let (owner, name) = match name.split_once('/') {
+        Some((owner, name)) => (Some(owner), name),
+        None => (None, name),
+    };
+    extensions
+        .iter()
+        .filter(|e| owner.is_none() || e.id.owner.as_deref() == owner)
+        .flat_map(Extension::formats)
+        .find(|format| format.name == name)
+}
+
+/// Resolve the scripts of a `filters` list: paths relative to `base_dir`,
+/// or names of extensions contributing filters.
+///
+/// Returns the filters to run before Quarto's transforms and those to run
+/// after them; entries after the [`QUARTO_FILTER`] marker run after.
+pub fn resolve_filters(
+    entries: &[String],
+    base_dir: &Path,
+    extensions: &[Extension],
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut pre = Vec::new();
+    let mut post = Vec::new();
+    let mut after_quarto = false;
+    for entry in entries {
+        if entry == QUARTO_FILTER {
+            after_quarto = true;
+            continue;
+        }
+        let target = if after_quarto { &mut post } else { &mut pre };
+        let extension = extensions
+            .iter()
+            .find(|e| e.id.to_string() == *entry || e.id.name == *entry);
+        match extension {
+            Some(extension) => {
+                let filters = extension.filters();
+                if filters.is_empty() {
+                    return Err(QuartoError::other(format!(
+                        "Extension '{}' does not contribute any filters",
+                        extension.id
+                    )));
+                }
+                target.extend(filters);
+            }
+            None => target.push(base_dir.join(entry)),
+        }
+    }
+    Ok((pre, post))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extension::{EXTENSIONS_DIR, ExtensionId, ExtensionManifest};
+    use quarto_system_runtime::NativeRuntime;
+    use serde_json::json;
+
+    fn extension(name: &str, manifest: &str) -> Extension {
+        Extension {
+            id: ExtensionId::new(Some("quarto-ext"), name),
+            dir: PathBuf::from("/ext").join(name),
+            manifest: ExtensionManifest::parse(manifest).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_contributions() {
+        let ext = extension(
+            "acm",
+            "contributes:\n  shortcodes: [acm.lua]\n  filters:\n    - path: pre.lua\n    - post.lua\n  formats:\n    common:\n      toc: true\n      number-sections: true\n    pdf:\n      toc: false\n      template-partials: [title.tex]\n      filters: [quarto, acm-post.lua]\n",
+        );
+        assert_eq!(ext.shortcodes(), [PathBuf::from("/ext/acm/acm.lua")]);
+        assert_eq!(
+            ext.filters(),
+            [
+                PathBuf::from("/ext/acm/pre.lua"),
+                PathBuf::from("/ext/acm/post.lua")
+            ]
+        );
+
+        let format = find_format(&[ext.clone()], "acm-pdf").unwrap();
+        assert_eq!(format.base, "pdf");
+        assert_eq!(
+            format.metadata,
+            json!({
+                "toc": false,
+                "number-sections": true,
+                "template-partials": ["/ext/acm/title.tex"],
+                "filters": ["quarto", "/ext/acm/acm-post.lua"],
+            })
+        );
+        assert!(find_format(&[ext.clone()], "quarto-ext/acm-pdf").is_some());
+        assert!(find_format(&[ext.clone()], "other/acm-pdf").is_none());
+        assert!(find_format(&[ext], "acm-html").is_none());
+    }
+
+    #[test]
+    fn test_merge_metadata() {
+        let mut base = json!({"toc": true, "html": {"theme": "cosmo", "css": "a.css"}});
+        merge_metadata(
+            &mut base,
+            &json!({"html": {"theme": "darkly"}, "toc": null, "lang": "fr"}),
+        );
+        assert_eq!(
+            base,
+            json!({"toc": true, "html": {"theme": "darkly", "css": "a.css"}, "lang": "fr"})
+        );
+    }
+
+    #[test]
+    fn test_resolve_filters() {
+        let lightbox = extension("lightbox", "contributes:\n  filters: [lightbox.lua]\n");
+        let (pre, post) = resolve_filters(
+            &[
+                "first.lua".to_string(),
+                "lightbox".to_string(),
+                QUARTO_FILTER.to_string(),
+                "last.lua".to_string(),
+            ],
+            Path::new("/doc"),
+            &[lightbox],
+        )
+        .unwrap();
+        assert_eq!(
+            pre,
+            [
+                PathBuf::from("/doc/first.lua"),
+                PathBuf::from("/ext/lightbox/lightbox.lua")
+            ]
+        );
+        assert_eq!(post, [PathBuf::from("/doc/last.lua")]);
+
+        let empty = extension("empty", "title: Empty\n");
+        assert!(resolve_filters(&["empty".to_string()], Path::new("/doc"), &[empty]).is_err());
+    }
+
+    #[test]
+    fn test_document_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path();
+        let chapter = project.join("chapters");
+        for (root, name, title) in [
+            (project, "shared", "Project"),
+            (project, "override", "Project"),
+            (chapter.as_path(), "override", "Chapter"),
+        ] {
+            let ext = root.join(EXTENSIONS_DIR).join(name);
+            std::fs::create_dir_all(&ext).unwrap();
+            std::fs::write(ext.join("_extension.yml"), format!("title: {}\n", title)).unwrap();
+        }
+
+        let extensions = document_extensions(&chapter, project, &NativeRuntime::new()).unwrap();
+        let found: Vec<_> = extensions
+            .iter()
+            .map(|e| (e.id.to_string(), e.manifest.title.clone().unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("override".to_string(), "Chapter".to_string()),
+                ("shared".to_string(), "Project".to_string())
+            ]
+        );
+    }
+}
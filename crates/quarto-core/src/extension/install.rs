@@ -0,0 +1,585 @@
+/*
+ * extension/install.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Installing extensions from GitHub.
+ */
+
+//! Installing extensions from GitHub.
+//!
+//! An extension source is a GitHub repository, `owner/repo[@ref]`. The ref
+//! (branch, tag or commit; the default branch when omitted) is resolved to
+//! a commit, whose archive is downloaded through the [`SystemRuntime`] and
+//! searched for extensions:
+//! - each `_extensions/name/_extension.yml` or
+//!   `_extensions/owner/name/_extension.yml`, the owner defaulting to the
+//!   repository's owner
+//! - otherwise an `_extension.yml` at the root of the repository, named
+//!   after the repository
+//!
+//! Extensions are installed as `_extensions/owner/name` and recorded in the
+//! [lockfile](super::lock).
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_system_runtime::{RuntimeError, SystemRuntime};
+use serde::Deserialize;
+
+use super::lock::{ExtensionsLock, LockedExtension};
+use super::{EXTENSIONS_DIR, ExtensionId, ExtensionManifest, MANIFEST_FILES};
+use crate::error::{QuartoError, Result};
+
+/// GitHub API, used to resolve refs to commits.
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Host serving repository archives.
+const GITHUB_ARCHIVE_URL: &str = "https://codeload.github.com";
+
+/// A GitHub repository to install extensions from, `owner/repo[@ref]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionSource {
+    pub owner: String,
+    pub repo: String,
+    /// Branch, tag or commit; the default branch when absent
+    pub reference: Option<String>,
+}
+
+impl ExtensionSource {
+    /// Parse `owner/repo[@ref]`.
+    pub fn parse(source: &str) -> Option<Self> {
+        let (repository, reference) = match source.split_once('@') {
+            Some((repository, reference)) if !reference.is_empty() => {
+                (repository, Some(reference.to_string()))
+            }
+            Some(_) => return None,
+            None => (source, None),
+        };
+        let (owner, repo) = repository.split_once('/')?;
+        let valid = |part: &str| {
+            !part.is_empty()
+                && !part.starts_with('.')
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        };
+        if !valid(owner) || !valid(repo) {
+            return None;
+        }
+        Some(Self {
+            owner: owner.to_string(),
+            repo: repo.trim_end_matches(".git").to_string(),
+            reference,
+        })
+    }
+
+    /// The repository, `owner/repo`.
+    pub fn repository(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+
+    fn commit_url(&self) -> String {
+        format!(
+            "{}/repos/{}/{}/commits/{}",
+            GITHUB_API_URL,
+            self.owner,
+            self.repo,
+            self.reference.as_deref().unwrap_or("HEAD")
+        )
+    }
+
+    fn archive_url(&self, commit: &str) -> String {
+        format!(
+            "{}/{}/{}/zip/{}",
+            GITHUB_ARCHIVE_URL, self.owner, self.repo, commit
+        )
+    }
+}
+
+impl fmt::Display for ExtensionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.repo)?;
+        if let Some(reference) = &self.reference {
+            write!(f, "@{}", reference)?;
+        }
+        Ok(())
+    }
+}
+
+/// Why extensions couldn't be fetched from a source.
+#[derive(Debug)]
+pub enum FetchErrorKind {
+    /// The repository or ref doesn't exist
+    NotFound,
+    /// GitHub couldn't be reached
+    Offline(String),
+    /// GitHub answered with an error
+    Http(String),
+    /// The download isn't a usable extension
+    Invalid(String),
+}
+
+/// Fetching extensions from a source failed.
+#[derive(Debug)]
+pub struct FetchError {
+    pub source: ExtensionSource,
+    pub kind: FetchErrorKind,
+}
+
+impl FetchError {
+    fn new(source: &ExtensionSource, kind: FetchErrorKind) -> Self {
+        Self {
+            source: source.clone(),
+            kind,
+        }
+    }
+
+    fn from_runtime(source: &ExtensionSource, error: RuntimeError) -> Self {
+        let kind = match error {
+            RuntimeError::Network(message) if message.contains("status code 404") => {
+                FetchErrorKind::NotFound
+            }
+            RuntimeError::Network(message) if message.contains("status code") => {
+                FetchErrorKind::Http(message)
+            }
+            RuntimeError::Network(message) => FetchErrorKind::Offline(message),
+            other => FetchErrorKind::Offline(other.to_string()),
+        };
+        Self::new(source, kind)
+    }
+
+    /// Describe the failure and what to do about it.
+    pub fn to_diagnostic(&self) -> DiagnosticMessage {
+        let source = &self.source;
+        match &self.kind {
+            FetchErrorKind::NotFound => DiagnosticMessageBuilder::error("Extension not found")
+                .problem(format!("`{}` does not exist on GitHub", source))
+                .add_hint("Extensions are named `owner/repo`, optionally followed by `@ref`")
+                .build(),
+            FetchErrorKind::Offline(message) => {
+                DiagnosticMessageBuilder::error("Could not download extension")
+                    .problem(format!(
+                        "GitHub could not be reached to download `{}`",
+                        source
+                    ))
+                    .add_detail(message.clone())
+                    .add_hint("Check your network connection and try again")
+                    .add_note("Extensions already installed keep working offline")
+                    .build()
+            }
+            FetchErrorKind::Http(message) => {
+                DiagnosticMessageBuilder::error("Could not download extension")
+                    .problem(format!("GitHub refused to send `{}`", source))
+                    .add_detail(message.clone())
+                    .add_hint("GitHub may be limiting requests; try again later")
+                    .build()
+            }
+            FetchErrorKind::Invalid(message) => {
+                DiagnosticMessageBuilder::error("Invalid extension")
+                    .problem(format!(
+                        "`{}` does not contain a usable Quarto extension",
+                        source
+                    ))
+                    .add_detail(message.clone())
+                    .build()
+            }
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            FetchErrorKind::NotFound => write!(f, "{} does not exist on GitHub", self.source),
+            FetchErrorKind::Offline(message) | FetchErrorKind::Http(message) => {
+                write!(f, "Failed to download {}: {}", self.source, message)
+            }
+            FetchErrorKind::Invalid(message) => write!(f, "{}: {}", self.source, message),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+/// An extension downloaded and ready to install.
+#[derive(Debug, Clone)]
+pub struct FetchedExtension {
+    pub id: ExtensionId,
+    pub manifest: ExtensionManifest,
+    /// Files, relative to the extension directory
+    files: Vec<(PathBuf, Vec<u8>)>,
+}
+
+/// The extensions of a source at a commit.
+#[derive(Debug, Clone)]
+pub struct FetchedExtensions {
+    pub source: ExtensionSource,
+    pub commit: String,
+    pub extensions: Vec<FetchedExtension>,
+}
+
+#[derive(Deserialize)]
+struct Commit {
+    sha: String,
+}
+
+/// Resolve the source's ref to a commit.
+pub fn resolve_commit(
+    source: &ExtensionSource,
+    runtime: &dyn SystemRuntime,
+) -> std::result::Result<String, FetchError> {
+    let (body, _) = runtime
+        .fetch_url(&source.commit_url())
+        .map_err(|e| FetchError::from_runtime(source, e))?;
+    serde_json::from_slice::<Commit>(&body)
+        .map(|commit| commit.sha)
+        .map_err(|e| {
+            FetchError::new(
+                source,
+                FetchErrorKind::Http(format!("Unexpected response from GitHub: {}", e)),
+            )
+        })
+}
+
+/// Download the extensions of `source` at `commit`.
+pub fn fetch(
+    source: &ExtensionSource,
+    commit: &str,
+    runtime: &dyn SystemRuntime,
+) -> std::result::Result<FetchedExtensions, FetchError> {
+    let (archive, _) = runtime
+        .fetch_url(&source.archive_url(commit))
+        .map_err(|e| FetchError::from_runtime(source, e))?;
+    let extensions = extract_extensions(&archive, source)
+        .map_err(|message| FetchError::new(source, FetchErrorKind::Invalid(message)))?;
+    Ok(FetchedExtensions {
+        source: source.clone(),
+        commit: commit.to_string(),
+        extensions,
+    })
+}
+
+/// Find the extensions in a GitHub archive, whose entries are all in one
+/// top-level directory.
+fn extract_extensions(
+    archive: &[u8],
+    source: &ExtensionSource,
+) -> std::result::Result<Vec<FetchedExtension>, String> {
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(archive))
+        .map_err(|e| format!("Invalid archive: {}", e))?;
+
+    // Files by path, without the top-level directory
+    let mut files = BTreeMap::new();
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(|e| e.to_string())?;
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(path) = entry.enclosed_name() else {
+            continue;
+        };
+        let path: PathBuf = path.components().skip(1).collect();
+        let mut content = Vec::new();
+        entry
+            .read_to_end(&mut content)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        files.insert(path, content);
+    }
+
+    let mut found: BTreeMap<ExtensionId, (PathBuf, ExtensionManifest)> = BTreeMap::new();
+    for (path, content) in &files {
+        let is_manifest = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| MANIFEST_FILES.contains(&name));
+        let Some(dir) = path.parent().filter(|_| is_manifest) else {
+            continue;
+        };
+        let Some(id) = extension_id(dir, source) else {
+            continue;
+        };
+        let manifest = String::from_utf8(content.clone())
+            .map_err(|e| e.to_string())
+            .and_then(|content| ExtensionManifest::parse(&content).map_err(|e| e.to_string()))
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        found.entry(id).or_insert((dir.to_path_buf(), manifest));
+    }
+    // A root manifest only counts when there is no `_extensions` directory
+    if found.len() > 1 {
+        found.retain(|_, (dir, _)| dir.as_os_str() != "");
+    }
+    if found.is_empty() {
+        return Err(format!("No {} found in the repository", MANIFEST_FILES[0]));
+    }
+
+    Ok(found
+        .into_iter()
+        .map(|(id, (dir, manifest))| {
+            let files = files
+                .iter()
+                .filter_map(|(path, content)| {
+                    let relative = path.strip_prefix(&dir).ok()?;
+                    // Root extensions don't include the repository's `_extensions`
+                    (!relative.starts_with(EXTENSIONS_DIR))
+                        .then(|| (relative.to_path_buf(), content.clone()))
+                })
+                .collect();
+            FetchedExtension {
+                id,
+                manifest,
+                files,
+            }
+        })
+        .collect())
+}
+
+/// Id of the extension whose manifest is in `dir` of the archive.
+fn extension_id(dir: &Path, source: &ExtensionSource) -> Option<ExtensionId> {
+    let parts: Vec<&str> = dir
+        .components()
+        .map(|c| match c {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    match parts.as_slice() {
+        [] => Some(ExtensionId::new(Some(&source.owner), &source.repo)),
+        [.., EXTENSIONS_DIR, name] => Some(ExtensionId::new(Some(&source.owner), name)),
+        [.., EXTENSIONS_DIR, owner, name] => Some(ExtensionId::new(Some(owner), name)),
+        _ => None,
+    }
+}
+
+/// Install fetched extensions into `dir/_extensions`, replacing existing
+/// ones and those previously installed from the same source, and record
+/// them in `lock`.
+pub fn install(
+    dir: &Path,
+    fetched: &FetchedExtensions,
+    lock: &mut ExtensionsLock,
+    runtime: &dyn SystemRuntime,
+) -> Result<()> {
+    let repository = fetched.source.repository();
+    for previous in lock.installed_from(&repository) {
+        if !fetched.extensions.iter().any(|e| e.id == previous) {
+            remove(dir, &previous, lock, runtime)?;
+        }
+    }
+
+    for extension in &fetched.extensions {
+        let target = dir.join(EXTENSIONS_DIR).join(extension.id.relative_dir());
+        let io_error = |e: RuntimeError| {
+            QuartoError::other(format!("Failed to install {}: {}", extension.id, e))
+        };
+        if runtime.is_dir(&target).unwrap_or(false) {
+            runtime.dir_remove(&target, true).map_err(io_error)?;
+        }
+        for (relative, content) in &extension.files {
+            let path = target.join(relative);
+            if let Some(parent) = path.parent() {
+                runtime.dir_create(parent, true).map_err(io_error)?;
+            }
+            runtime.file_write(&path, content).map_err(io_error)?;
+        }
+        lock.insert(
+            &extension.id,
+            LockedExtension {
+                source: repository.clone(),
+                reference: fetched.source.reference.clone(),
+                commit: fetched.commit.clone(),
+                version: extension.manifest.version.clone(),
+            },
+        );
+    }
+    Ok(())
+}
+
+/// Remove an extension from `dir/_extensions` and from `lock`.
+pub fn remove(
+    dir: &Path,
+    id: &ExtensionId,
+    lock: &mut ExtensionsLock,
+    runtime: &dyn SystemRuntime,
+) -> Result<()> {
+    let root = dir.join(EXTENSIONS_DIR);
+    let target = root.join(id.relative_dir());
+    let io_error = |e: RuntimeError| QuartoError::other(format!("Failed to remove {}: {}", id, e));
+    if runtime.is_dir(&target).unwrap_or(false) {
+        runtime.dir_remove(&target, true).map_err(io_error)?;
+    }
+    // Drop the owner directory once its last extension is gone
+    if let Some(owner) = &id.owner {
+        let owner_dir = root.join(owner);
+        if runtime
+            .dir_list(&owner_dir)
+            .is_ok_and(|entries| entries.is_empty())
+        {
+            runtime.dir_remove(&owner_dir, false).map_err(io_error)?;
+        }
+    }
+    lock.remove(id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extension::discover_extensions;
+    use quarto_system_runtime::NativeRuntime;
+    use std::io::Write;
+
+    fn archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (path, content) in files {
+            zip.start_file(*path, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_parse_source() {
+        let source = ExtensionSource::parse("quarto-ext/fontawesome@v1.1.0").unwrap();
+        assert_eq!(source.repository(), "quarto-ext/fontawesome");
+        assert_eq!(source.reference.as_deref(), Some("v1.1.0"));
+        assert_eq!(source.to_string(), "quarto-ext/fontawesome@v1.1.0");
+        assert_eq!(
+            source.archive_url("abc"),
+            "https://codeload.github.com/quarto-ext/fontawesome/zip/abc"
+        );
+        assert!(ExtensionSource::parse("fontawesome").is_none());
+        assert!(ExtensionSource::parse("a/b@").is_none());
+        assert!(ExtensionSource::parse("a/../b").is_none());
+    }
+
+    #[test]
+    fn test_extract_extensions() {
+        let source = ExtensionSource::parse("quarto-journals/acm").unwrap();
+        let extensions = extract_extensions(
+            &archive(&[
+                ("acm-main/README.md", "# ACM"),
+                ("acm-main/template.qmd", "---\ntitle: Paper\n---\n"),
+                (
+                    "acm-main/_extensions/acm/_extension.yml",
+                    "title: ACM\nversion: 0.2.1\n",
+                ),
+                ("acm-main/_extensions/acm/partials/title.tex", "\\title{}"),
+                (
+                    "acm-main/_extensions/other/tool/_extension.yml",
+                    "title: Tool\n",
+                ),
+            ]),
+            &source,
+        )
+        .unwrap();
+        let ids: Vec<_> = extensions.iter().map(|e| e.id.to_string()).collect();
+        assert_eq!(ids, ["other/tool", "quarto-journals/acm"]);
+        let acm = &extensions[1];
+        assert_eq!(acm.manifest.version.as_deref(), Some("0.2.1"));
+        let paths: Vec<_> = acm.files.iter().map(|(p, _)| p.clone()).collect();
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("_extension.yml"),
+                PathBuf::from("partials/title.tex")
+            ]
+        );
+
+        // An extension at the root of the repository
+        let source = ExtensionSource::parse("someone/lightbox").unwrap();
+        let extensions = extract_extensions(
+            &archive(&[
+                ("lightbox-1/_extension.yml", "title: Lightbox\n"),
+                ("lightbox-1/lightbox.lua", "return {}"),
+            ]),
+            &source,
+        )
+        .unwrap();
+        assert_eq!(extensions[0].id.to_string(), "someone/lightbox");
+        assert_eq!(extensions[0].files.len(), 2);
+
+        assert!(extract_extensions(&archive(&[("x-1/README.md", "")]), &source).is_err());
+        assert!(extract_extensions(b"not a zip", &source).is_err());
+    }
+
+    #[test]
+    fn test_install_and_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = NativeRuntime::new();
+        let source = ExtensionSource::parse("quarto-ext/fontawesome").unwrap();
+        let fetched = |files: &[(&str, &str)], commit: &str| FetchedExtensions {
+            source: source.clone(),
+            commit: commit.to_string(),
+            extensions: extract_extensions(&archive(files), &source).unwrap(),
+        };
+        let mut lock = ExtensionsLock::default();
+
+        install(
+            dir.path(),
+            &fetched(
+                &[
+                    (
+                        "fa-1/_extensions/fontawesome/_extension.yml",
+                        "version: 1.0.0\n",
+                    ),
+                    ("fa-1/_extensions/fontawesome/old.lua", ""),
+                ],
+                "c1",
+            ),
+            &mut lock,
+            &runtime,
+        )
+        .unwrap();
+        let installed = dir.path().join("_extensions/quarto-ext/fontawesome");
+        assert!(installed.join("old.lua").exists());
+        let id = ExtensionId::new(Some("quarto-ext"), "fontawesome");
+        assert_eq!(lock.get(&id).unwrap().commit, "c1");
+
+        // Updating replaces the files
+        install(
+            dir.path(),
+            &fetched(
+                &[
+                    (
+                        "fa-2/_extensions/fontawesome/_extension.yml",
+                        "version: 1.1.0\n",
+                    ),
+                    ("fa-2/_extensions/fontawesome/new.lua", ""),
+                ],
+                "c2",
+            ),
+            &mut lock,
+            &runtime,
+        )
+        .unwrap();
+        assert!(!installed.join("old.lua").exists());
+        assert!(installed.join("new.lua").exists());
+        assert_eq!(lock.get(&id).unwrap().version.as_deref(), Some("1.1.0"));
+        assert_eq!(discover_extensions(dir.path(), &runtime).unwrap().len(), 1);
+
+        remove(dir.path(), &id, &mut lock, &runtime).unwrap();
+        assert!(!dir.path().join("_extensions/quarto-ext").exists());
+        assert!(lock.extensions.is_empty());
+    }
+
+    #[test]
+    fn test_fetch_error_diagnostics() {
+        let source = ExtensionSource::parse("a/b").unwrap();
+        let error = FetchError::from_runtime(
+            &source,
+            RuntimeError::Network("https://x: status code 404".to_string()),
+        );
+        assert!(matches!(error.kind, FetchErrorKind::NotFound));
+        let error = FetchError::from_runtime(
+            &source,
+            RuntimeError::Network("https://x: Dns Failed".to_string()),
+        );
+        assert!(matches!(error.kind, FetchErrorKind::Offline(_)));
+        let text = error.to_diagnostic().to_text(None);
+        assert!(text.contains("network connection"), "{}", text);
+    }
+}
@@ -0,0 +1,160 @@
+/*
+ * extension/lock.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * The lockfile recording where installed extensions came from.
+ */
+
+//! The lockfile recording where installed extensions came from.
+//!
+//! `_extensions/extensions.lock` maps each extension installed with
+//! `quarto add` to its GitHub source, the ref that was asked for (none for
+//! the default branch) and the commit that was installed:
+//!
+//! ```yaml
+//! quarto-ext/fontawesome:
+//!   source: quarto-ext/fontawesome
+//!   ref: v1.1.0
+//!   commit: 6d3c5a1f0e6c2a3b9e4b0a7f3f3b2d1c0e9f8a7b
+//!   version: 1.1.0
+//! ```
+//!
+//! `quarto update` fetches the ref again and reinstalls when the commit
+//! changed. Extensions copied into `_extensions` by hand have no entry.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use quarto_system_runtime::SystemRuntime;
+use serde::{Deserialize, Serialize};
+
+use super::{EXTENSIONS_DIR, ExtensionId};
+use crate::error::{QuartoError, Result};
+
+/// Name of the lockfile, inside `_extensions`.
+pub const LOCK_FILE: &str = "extensions.lock";
+
+/// Where an installed extension came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedExtension {
+    /// GitHub repository, `owner/repo`
+    pub source: String,
+
+    /// Ref asked for; the default branch when absent
+    #[serde(rename = "ref", default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+
+    /// Commit installed
+    pub commit: String,
+
+    /// Version from the extension's manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// Contents of the lockfile, by extension id.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtensionsLock {
+    pub extensions: BTreeMap<String, LockedExtension>,
+}
+
+impl ExtensionsLock {
+    /// Path of the lockfile of the extensions installed in `dir`.
+    pub fn path(dir: &Path) -> PathBuf {
+        dir.join(EXTENSIONS_DIR).join(LOCK_FILE)
+    }
+
+    /// Load the lockfile of `dir` (empty if there is none).
+    pub fn load(dir: &Path, runtime: &dyn SystemRuntime) -> Result<Self> {
+        let path = Self::path(dir);
+        if !runtime.is_file(&path).unwrap_or(false) {
+            return Ok(Self::default());
+        }
+        let content = runtime
+            .file_read_string(&path)
+            .map_err(|e| QuartoError::other(format!("Failed to read {}: {}", path.display(), e)))?;
+        Self::parse(&content).map_err(|e| QuartoError::other(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let extensions: Option<BTreeMap<String, LockedExtension>> =
+            serde_yaml::from_str(content)
+                .map_err(|e| QuartoError::other(format!("Invalid extensions lockfile: {}", e)))?;
+        Ok(Self {
+            extensions: extensions.unwrap_or_default(),
+        })
+    }
+
+    /// Write the lockfile of `dir`, or remove it when no extension is left.
+    pub fn save(&self, dir: &Path, runtime: &dyn SystemRuntime) -> Result<()> {
+        let path = Self::path(dir);
+        if self.extensions.is_empty() {
+            if runtime.is_file(&path).unwrap_or(false) {
+                runtime.file_remove(&path).map_err(|e| {
+                    QuartoError::other(format!("Failed to remove {}: {}", path.display(), e))
+                })?;
+            }
+            return Ok(());
+        }
+        let content = serde_yaml::to_string(&self.extensions)
+            .map_err(|e| QuartoError::other(e.to_string()))?;
+        runtime
+            .dir_create(&dir.join(EXTENSIONS_DIR), true)
+            .and_then(|()| runtime.file_write(&path, content.as_bytes()))
+            .map_err(|e| QuartoError::other(format!("Failed to write {}: {}", path.display(), e)))
+    }
+
+    pub fn get(&self, id: &ExtensionId) -> Option<&LockedExtension> {
+        self.extensions.get(&id.to_string())
+    }
+
+    pub fn insert(&mut self, id: &ExtensionId, locked: LockedExtension) {
+        self.extensions.insert(id.to_string(), locked);
+    }
+
+    pub fn remove(&mut self, id: &ExtensionId) -> Option<LockedExtension> {
+        self.extensions.remove(&id.to_string())
+    }
+
+    /// Ids of the extensions installed from `source`.
+    pub fn installed_from(&self, source: &str) -> Vec<ExtensionId> {
+        self.extensions
+            .iter()
+            .filter(|(_, locked)| locked.source.eq_ignore_ascii_case(source))
+            .filter_map(|(id, _)| ExtensionId::parse(id))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut lock = ExtensionsLock::default();
+        let id = ExtensionId::new(Some("quarto-ext"), "fontawesome");
+        lock.insert(
+            &id,
+            LockedExtension {
+                source: "quarto-ext/fontawesome".to_string(),
+                reference: Some("v1.1.0".to_string()),
+                commit: "abc123".to_string(),
+                version: Some("1.1.0".to_string()),
+            },
+        );
+        let saved = serde_yaml::to_string(&lock.extensions).unwrap();
+        assert!(saved.contains("ref: v1.1.0"));
+        let reloaded = ExtensionsLock::parse(&saved).unwrap();
+        assert_eq!(reloaded, lock);
+        assert_eq!(
+            reloaded.installed_from("Quarto-Ext/fontawesome"),
+            [id.clone()]
+        );
+        assert!(reloaded.installed_from("quarto-ext/other").is_empty());
+        assert_eq!(
+            ExtensionsLock::parse("").unwrap(),
+            ExtensionsLock::default()
+        );
+    }
+}
@@ -0,0 +1,282 @@
+/*
+ * extension/mod.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Quarto extensions installed in a project.
+ */
+
+//! Quarto extensions installed in a project.
+//!
+//! Extensions live in the `_extensions` directory next to `_quarto.yml` (or
+//! next to a single document), one directory per extension, each with an
+//! `_extension.yml` manifest. Extensions added with `quarto add` are
+//! namespaced by the GitHub owner they came from:
+//!
+//! ```text
+//! _extensions/
+//!   quarto-ext/
+//!     fontawesome/
+//!       _extension.yml
+//!       fontawesome.lua
+//! ```
+//!
+//! - [`discover_extensions`] finds the installed extensions
+//! - [`lock::ExtensionsLock`] records where they were installed from
+//! - [`install`] downloads, installs and removes them
+//! - [`contributions`] reads the shortcodes, filters and formats they
+//!   contribute to rendering
+
+pub mod contributions;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod install;
+pub mod lock;
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use quarto_system_runtime::SystemRuntime;
+use serde::{Deserialize, Deserializer};
+
+use crate::error::{QuartoError, Result};
+
+/// Directory holding a project's extensions.
+pub const EXTENSIONS_DIR: &str = "_extensions";
+
+/// Names of an extension's manifest, in order of preference.
+pub const MANIFEST_FILES: [&str; 2] = ["_extension.yml", "_extension.yaml"];
+
+/// Identifies an installed extension: `owner/name`, or just `name` for
+/// extensions installed without an owner directory.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExtensionId {
+    pub owner: Option<String>,
+    pub name: String,
+}
+
+impl ExtensionId {
+    pub fn new(owner: Option<&str>, name: &str) -> Self {
+        Self {
+            owner: owner.map(String::from),
+            name: name.to_string(),
+        }
+    }
+
+    /// Parse `owner/name` or `name`.
+    pub fn parse(id: &str) -> Option<Self> {
+        let valid = |part: &str| !part.is_empty() && !part.starts_with('.');
+        match id.split_once('/') {
+            Some((owner, name)) if valid(owner) && valid(name) && !name.contains('/') => {
+                Some(Self::new(Some(owner), name))
+            }
+            None if valid(id) => Some(Self::new(None, id)),
+            _ => None,
+        }
+    }
+
+    /// Directory of the extension, relative to `_extensions`.
+    pub fn relative_dir(&self) -> PathBuf {
+        match &self.owner {
+            Some(owner) => Path::new(owner).join(&self.name),
+            None => PathBuf::from(&self.name),
+        }
+    }
+}
+
+impl fmt::Display for ExtensionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.owner {
+            Some(owner) => write!(f, "{}/{}", owner, self.name),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Contents of `_extension.yml`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExtensionManifest {
+    #[serde(default)]
+    pub title: Option<String>,
+
+    #[serde(default)]
+    pub author: Option<String>,
+
+    /// Version, which YAML may have read as a number
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub version: Option<String>,
+
+    /// Quarto versions the extension works with, e.g. `>=1.3.0`
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub quarto_required: Option<String>,
+
+    /// What the extension contributes (`shortcodes`, `filters`, `formats`, ...)
+    #[serde(default)]
+    pub contributes: serde_yaml::Mapping,
+}
+
+impl ExtensionManifest {
+    pub fn parse(content: &str) -> Result<Self> {
+        let manifest: Option<Self> = serde_yaml::from_str(content)
+            .map_err(|e| QuartoError::other(format!("Invalid extension manifest: {}", e)))?;
+        Ok(manifest.unwrap_or_default())
+    }
+
+    /// Kinds of contributions, e.g. `["filters", "shortcodes"]`.
+    pub fn contribution_kinds(&self) -> Vec<&str> {
+        self.contributes.keys().filter_map(|k| k.as_str()).collect()
+    }
+}
+
+fn string_or_number<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<String>, D::Error> {
+    Ok(
+        match Option::<serde_yaml::Value>::deserialize(deserializer)? {
+            Some(serde_yaml::Value::String(s)) => Some(s),
+            Some(serde_yaml::Value::Number(n)) => Some(n.to_string()),
+            Some(serde_yaml::Value::Bool(b)) => Some(b.to_string()),
+            _ => None,
+        },
+    )
+}
+
+/// An extension found in `_extensions`.
+#[derive(Debug, Clone)]
+pub struct Extension {
+    pub id: ExtensionId,
+    /// Directory holding the manifest
+    pub dir: PathBuf,
+    pub manifest: ExtensionManifest,
+}
+
+/// Find the extensions installed in `dir/_extensions`, sorted by id.
+///
+/// Both `_extensions/owner/name` and `_extensions/name` are recognized.
+/// Returns an error naming the file if a manifest is invalid.
+pub fn discover_extensions(dir: &Path, runtime: &dyn SystemRuntime) -> Result<Vec<Extension>> {
+    let root = dir.join(EXTENSIONS_DIR);
+    let mut extensions = Vec::new();
+    for entry in list_dirs(&root, runtime)? {
+        let Some(first) = file_name(&entry) else {
+            continue;
+        };
+        if let Some(manifest) = read_manifest(&entry, runtime)? {
+            extensions.push(Extension {
+                id: ExtensionId::new(None, first),
+                dir: entry.clone(),
+                manifest,
+            });
+            continue;
+        }
+        for nested in list_dirs(&entry, runtime)? {
+            let Some(second) = file_name(&nested) else {
+                continue;
+            };
+            if let Some(manifest) = read_manifest(&nested, runtime)? {
+                extensions.push(Extension {
+                    id: ExtensionId::new(Some(first), second),
+                    dir: nested.clone(),
+                    manifest,
+                });
+            }
+        }
+    }
+    extensions.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(extensions)
+}
+
+/// Read the manifest of an extension directory, if it has one.
+fn read_manifest(dir: &Path, runtime: &dyn SystemRuntime) -> Result<Option<ExtensionManifest>> {
+    for name in MANIFEST_FILES {
+        let path = dir.join(name);
+        if runtime.is_file(&path).unwrap_or(false) {
+            let content = runtime.file_read_string(&path).map_err(|e| {
+                QuartoError::other(format!("Failed to read {}: {}", path.display(), e))
+            })?;
+            return ExtensionManifest::parse(&content)
+                .map(Some)
+                .map_err(|e| QuartoError::other(format!("{}: {}", path.display(), e)));
+        }
+    }
+    Ok(None)
+}
+
+/// Subdirectories of `dir`, skipping hidden ones; empty if `dir` doesn't exist.
+fn list_dirs(dir: &Path, runtime: &dyn SystemRuntime) -> Result<Vec<PathBuf>> {
+    if !runtime.is_dir(dir).unwrap_or(false) {
+        return Ok(Vec::new());
+    }
+    let mut dirs: Vec<_> = runtime
+        .dir_list(dir)
+        .map_err(|e| QuartoError::other(format!("Failed to list {}: {}", dir.display(), e)))?
+        .into_iter()
+        .filter(|path| {
+            file_name(path).is_some_and(|name| !name.starts_with('.'))
+                && runtime.is_dir(path).unwrap_or(false)
+        })
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+fn file_name(path: &Path) -> Option<&str> {
+    path.file_name().and_then(|name| name.to_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_system_runtime::NativeRuntime;
+
+    #[test]
+    fn test_extension_id() {
+        let id = ExtensionId::parse("quarto-ext/fontawesome").unwrap();
+        assert_eq!(id.owner.as_deref(), Some("quarto-ext"));
+        assert_eq!(id.relative_dir(), Path::new("quarto-ext/fontawesome"));
+        assert_eq!(id.to_string(), "quarto-ext/fontawesome");
+        assert_eq!(
+            ExtensionId::parse("lightbox").unwrap().to_string(),
+            "lightbox"
+        );
+        assert!(ExtensionId::parse("a/b/c").is_none());
+        assert!(ExtensionId::parse("../x").is_none());
+    }
+
+    #[test]
+    fn test_manifest() {
+        let manifest = ExtensionManifest::parse(
+            "title: Font Awesome\nversion: 1.0\nquarto-required: \">=1.2\"\ncontributes:\n  shortcodes:\n    - fontawesome.lua\n",
+        )
+        .unwrap();
+        assert_eq!(manifest.title.as_deref(), Some("Font Awesome"));
+        assert_eq!(manifest.version.as_deref(), Some("1.0"));
+        assert_eq!(manifest.quarto_required.as_deref(), Some(">=1.2"));
+        assert_eq!(manifest.contribution_kinds(), ["shortcodes"]);
+        assert_eq!(
+            ExtensionManifest::parse("").unwrap(),
+            ExtensionManifest::default()
+        );
+    }
+
+    #[test]
+    fn test_discover_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join(EXTENSIONS_DIR);
+        for (path, manifest) in [
+            ("quarto-ext/fontawesome", "title: Font Awesome\n"),
+            ("lightbox", "title: Lightbox\n"),
+        ] {
+            std::fs::create_dir_all(root.join(path)).unwrap();
+            std::fs::write(root.join(path).join("_extension.yml"), manifest).unwrap();
+        }
+        std::fs::create_dir_all(root.join("quarto-ext/empty")).unwrap();
+
+        let extensions = discover_extensions(dir.path(), &NativeRuntime::new()).unwrap();
+        let ids: Vec<_> = extensions.iter().map(|e| e.id.to_string()).collect();
+        assert_eq!(ids, ["lightbox", "quarto-ext/fontawesome"]);
+        assert_eq!(
+            extensions[1].manifest.title.as_deref(),
+            Some("Font Awesome")
+        );
+    }
+}
@@ -19,7 +19,7 @@ use std::path::PathBuf;
 pub enum FormatIdentifier {
     /// HTML output (native Rust pipeline)
     Html,
-    /// PDF output (requires Pandoc + LaTeX)
+    /// PDF output (LaTeX writer + xelatex or tectonic)
     Pdf,
     /// Word document (requires Pandoc)
     Docx,
@@ -109,6 +109,10 @@ pub struct Format {
 
     /// Format-specific metadata (merged from config and document)
     pub metadata: serde_json::Value,
+
+    /// Name of the extension format this is, e.g. `acm-pdf` for the `pdf`
+    /// format of the `acm` extension
+    pub extension_format: Option<String>,
 }
 
 impl Format {
@@ -119,6 +123,7 @@ impl Format {
             output_extension: "html".to_string(),
             native_pipeline: true,
             metadata: serde_json::Value::Null,
+            extension_format: None,
         }
     }
 
@@ -129,6 +134,29 @@ impl Format {
             output_extension: "pdf".to_string(),
             native_pipeline: false,
             metadata: serde_json::Value::Null,
+            extension_format: None,
+        }
+    }
+
+    /// Create a Typst format (PDF output via Typst)
+    pub fn typst() -> Self {
+        Self {
+            identifier: FormatIdentifier::Typst,
+            output_extension: "pdf".to_string(),
+            native_pipeline: false,
+            metadata: serde_json::Value::Null,
+            extension_format: None,
+        }
+    }
+
+    /// Create a RevealJS presentation format
+    pub fn revealjs() -> Self {
+        Self {
+            identifier: FormatIdentifier::Revealjs,
+            output_extension: "html".to_string(),
+            native_pipeline: true,
+            metadata: serde_json::Value::Null,
+            extension_format: None,
         }
     }
 
@@ -139,9 +167,18 @@ impl Format {
             output_extension: "docx".to_string(),
             native_pipeline: false,
             metadata: serde_json::Value::Null,
+            extension_format: None,
         }
     }
 
+    /// The name documents use for this format: the extension format's name,
+    /// or the identifier's.
+    pub fn name(&self) -> &str {
+        self.extension_format
+            .as_deref()
+            .unwrap_or(self.identifier.as_str())
+    }
+
     /// Set format metadata
     pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
         self.metadata = metadata;
@@ -508,6 +545,25 @@ mod tests {
         assert_eq!(format.metadata, serde_json::Value::Null);
     }
 
+    #[test]
+    fn test_format_typst() {
+        let format = Format::typst();
+
+        assert_eq!(format.identifier, FormatIdentifier::Typst);
+        assert_eq!(format.output_extension, "pdf");
+        assert!(!format.native_pipeline);
+    }
+
+    #[test]
+    fn test_format_revealjs() {
+        let format = Format::revealjs();
+
+        assert_eq!(format.identifier, FormatIdentifier::Revealjs);
+        assert_eq!(format.output_extension, "html");
+        assert!(format.native_pipeline);
+        assert!(format.is_html());
+    }
+
     #[test]
     fn test_format_pdf() {
         let format = Format::pdf();
@@ -528,6 +584,16 @@ mod tests {
         assert_eq!(format.metadata, serde_json::Value::Null);
     }
 
+    #[test]
+    fn test_format_name() {
+        assert_eq!(Format::pdf().name(), "pdf");
+        let format = Format {
+            extension_format: Some("acm-pdf".to_string()),
+            ..Format::pdf()
+        };
+        assert_eq!(format.name(), "acm-pdf");
+    }
+
     #[test]
     fn test_format_with_metadata() {
         let metadata = serde_json::json!({
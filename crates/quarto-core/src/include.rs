@@ -0,0 +1,388 @@
+/*
+ * include.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Resolution of the include shortcode.
+ */
+
+//! The `include` shortcode.
+//!
+//! A paragraph consisting only of `{{< include file.qmd >}}` is replaced by
+//! the blocks of `file.qmd`, resolved relative to the including file. The
+//! included file is read through the [`SystemRuntime`], added to the
+//! document's [`SourceContext`] and parsed as a substring of itself, so the
+//! source locations of included content (and any diagnostic reported
+//! against it) point into the included file rather than the includer.
+//!
+//! Included files may include other files. An include that would re-enter
+//! a file already being included is reported as a cycle, together with the
+//! chain of files that led to it. Front matter of included files is
+//! ignored; only their blocks are spliced in.
+//!
+//! Includes are resolved right after parsing (see
+//! [`ParseDocumentStage`](crate::stage::ParseDocumentStage)), before code
+//! execution, so cells in included files are executed with the document.
+
+use std::path::{Path, PathBuf};
+
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_pandoc_types::block::{
+    Block, BlockQuote, BulletList, DefinitionList, Div, OrderedList, Paragraph, Plain,
+};
+use quarto_pandoc_types::inline::Inline;
+use quarto_pandoc_types::shortcode::{Shortcode, ShortcodeArg};
+use quarto_source_map::{SourceContext, SourceInfo};
+use quarto_system_runtime::SystemRuntime;
+
+/// Replace `include` shortcodes in `blocks` with the included content.
+///
+/// `path` is the file the blocks were parsed from; includes are resolved
+/// relative to its directory and each included file is registered in
+/// `source_context`.
+///
+/// # Errors
+///
+/// Returns the error diagnostics if any include can't be resolved: a
+/// missing path argument, an unreadable file, a parse error in the included
+/// file, or an include cycle. On success, returns the parse warnings of the
+/// included files.
+pub fn resolve_includes(
+    blocks: &mut Vec<Block>,
+    path: &Path,
+    runtime: &dyn SystemRuntime,
+    source_context: &mut SourceContext,
+) -> std::result::Result<Vec<DiagnosticMessage>, Vec<DiagnosticMessage>> {
+    let mut resolver = IncludeResolver {
+        runtime,
+        source_context,
+        stack: vec![canonical(runtime, path)],
+        warnings: Vec::new(),
+        errors: Vec::new(),
+    };
+    resolver.resolve_blocks(blocks, path.parent().unwrap_or(Path::new("")));
+
+    if resolver.errors.is_empty() {
+        Ok(resolver.warnings)
+    } else {
+        Err(resolver.errors)
+    }
+}
+
+/// The target of an `include` shortcode standing alone in a paragraph.
+fn include_shortcode(block: &Block) -> Option<&Shortcode> {
+    let (Block::Paragraph(Paragraph { content, .. }) | Block::Plain(Plain { content, .. })) = block
+    else {
+        return None;
+    };
+
+    let mut shortcodes = content
+        .iter()
+        .filter(|inline| !matches!(inline, Inline::Space(_) | Inline::SoftBreak(_)));
+    match (shortcodes.next(), shortcodes.next()) {
+        (Some(Inline::Shortcode(shortcode)), None)
+            if shortcode.name == "include" && !shortcode.is_escaped =>
+        {
+            Some(shortcode)
+        }
+        _ => None,
+    }
+}
+
+/// Canonical form of a path for cycle detection, if it can be resolved.
+fn canonical(runtime: &dyn SystemRuntime, path: &Path) -> PathBuf {
+    runtime
+        .canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+struct IncludeResolver<'a> {
+    runtime: &'a dyn SystemRuntime,
+    source_context: &'a mut SourceContext,
+    /// Files currently being included, outermost first
+    stack: Vec<PathBuf>,
+    warnings: Vec<DiagnosticMessage>,
+    errors: Vec<DiagnosticMessage>,
+}
+
+impl IncludeResolver<'_> {
+    fn resolve_blocks(&mut self, blocks: &mut Vec<Block>, dir: &Path) {
+        let mut i = 0;
+        while i < blocks.len() {
+            if let Some(shortcode) = include_shortcode(&blocks[i]) {
+                let shortcode = shortcode.clone();
+                let included = self.include(&shortcode, dir);
+                let count = included.len();
+                blocks.splice(i..=i, included);
+                i += count;
+            } else {
+                self.resolve_block(&mut blocks[i], dir);
+                i += 1;
+            }
+        }
+    }
+
+    /// Resolve includes nested in container blocks.
+    fn resolve_block(&mut self, block: &mut Block, dir: &Path) {
+        match block {
+            Block::Div(Div { content, .. }) | Block::BlockQuote(BlockQuote { content, .. }) => {
+                self.resolve_blocks(content, dir);
+            }
+            Block::BulletList(BulletList { content, .. })
+            | Block::OrderedList(OrderedList { content, .. }) => {
+                for item in content {
+                    self.resolve_blocks(item, dir);
+                }
+            }
+            Block::DefinitionList(DefinitionList { content, .. }) => {
+                for (_, definitions) in content {
+                    for definition in definitions {
+                        self.resolve_blocks(definition, dir);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The blocks of the file an include shortcode refers to, with their
+    /// own includes resolved. Returns no blocks (and records an error) if
+    /// the file can't be included.
+    fn include(&mut self, shortcode: &Shortcode, dir: &Path) -> Vec<Block> {
+        let Some(ShortcodeArg::String(target)) = shortcode.positional_args.first() else {
+            self.errors.push(
+                DiagnosticMessageBuilder::error("Missing include path")
+                    .problem("The `include` shortcode requires a file to include")
+                    .add_hint("Use `{{< include file.qmd >}}`")
+                    .with_location(shortcode.source_info.clone())
+                    .build(),
+            );
+            return Vec::new();
+        };
+
+        let path = dir.join(target);
+        let key = canonical(self.runtime, &path);
+        if self.stack.contains(&key) {
+            let chain = self
+                .stack
+                .iter()
+                .chain([&key])
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" → ");
+            self.errors.push(
+                DiagnosticMessageBuilder::error("Include cycle")
+                    .problem(format!("Including `{}` would create a cycle", target))
+                    .add_info(format!("Include chain: {}", chain))
+                    .add_hint("Remove one of the includes in the chain")
+                    .with_location(shortcode.source_info.clone())
+                    .build(),
+            );
+            return Vec::new();
+        }
+
+        let content = match self.runtime.file_read_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.errors.push(
+                    DiagnosticMessageBuilder::error("Included file not found")
+                        .problem(format!("Could not read `{}`: {}", path.display(), e))
+                        .add_hint("Include paths are relative to the including file")
+                        .with_location(shortcode.source_info.clone())
+                        .build(),
+                );
+                return Vec::new();
+            }
+        };
+
+        // Register the file and parse it as a substring of itself, so every
+        // source location in the included blocks resolves to this file.
+        let source_name = path.display().to_string();
+        let file_id = self
+            .source_context
+            .add_file(source_name.clone(), Some(content.clone()));
+        let parent = SourceInfo::original(file_id, 0, content.len());
+
+        let parsed = pampa::readers::qmd::read(
+            content.as_bytes(),
+            false,
+            &source_name,
+            &mut std::io::sink(),
+            true,
+            Some(parent),
+        );
+        match parsed {
+            Ok((ast, _, warnings)) => {
+                self.warnings.extend(warnings);
+                let mut blocks = ast.blocks;
+                self.stack.push(key);
+                self.resolve_blocks(&mut blocks, path.parent().unwrap_or(Path::new("")));
+                self.stack.pop();
+                blocks
+            }
+            Err(diagnostics) => {
+                self.errors.extend(diagnostics);
+                Vec::new()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_source_map::FileId;
+    use quarto_system_runtime::NativeRuntime;
+
+    fn write(dir: &Path, path: &str, content: &str) -> PathBuf {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// Parse and resolve the includes of `path`, as `ParseDocumentStage` does.
+    fn resolve(
+        path: &Path,
+    ) -> (
+        Vec<Block>,
+        SourceContext,
+        std::result::Result<Vec<DiagnosticMessage>, Vec<DiagnosticMessage>>,
+    ) {
+        let content = std::fs::read_to_string(path).unwrap();
+        let mut source_context = SourceContext::new();
+        source_context.add_file(path.display().to_string(), Some(content.clone()));
+        let (ast, _, _) = pampa::readers::qmd::read(
+            content.as_bytes(),
+            false,
+            &path.display().to_string(),
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .unwrap();
+
+        let mut blocks = ast.blocks;
+        let result = resolve_includes(
+            &mut blocks,
+            path,
+            &NativeRuntime::new(),
+            &mut source_context,
+        );
+        (blocks, source_context, result)
+    }
+
+    fn text(block: &Block) -> String {
+        let Block::Paragraph(Paragraph { content, .. }) = block else {
+            panic!("Expected Paragraph, got {:?}", block);
+        };
+        content
+            .iter()
+            .map(|inline| match inline {
+                Inline::Str(s) => s.text.as_str(),
+                _ => " ",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_include_splices_blocks() {
+        let temp = tempfile::tempdir().unwrap();
+        let doc = write(
+            temp.path(),
+            "doc.qmd",
+            "Before.\n\n{{< include parts/_intro.qmd >}}\n\nAfter.\n",
+        );
+        write(
+            temp.path(),
+            "parts/_intro.qmd",
+            "---\ntitle: Ignored\n---\n\nFirst included.\n\n{{< include _nested.qmd >}}\n",
+        );
+        write(temp.path(), "parts/_nested.qmd", "Nested.\n");
+
+        let (blocks, _, result) = resolve(&doc);
+        assert!(result.unwrap().is_empty());
+        let texts: Vec<String> = blocks.iter().map(text).collect();
+        assert_eq!(
+            texts,
+            vec!["Before.", "First included.", "Nested.", "After."]
+        );
+    }
+
+    #[test]
+    fn test_included_source_info_maps_to_included_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let doc = write(
+            temp.path(),
+            "doc.qmd",
+            "Title line.\n\n{{< include _part.qmd >}}\n",
+        );
+        let part = write(temp.path(), "_part.qmd", "Line one.\n\nIncluded text.\n");
+
+        let (blocks, source_context, result) = resolve(&doc);
+        assert!(result.is_ok());
+
+        let Block::Paragraph(included) = &blocks[2] else {
+            panic!("Expected Paragraph");
+        };
+        let mapped = included.source_info.map_offset(0, &source_context).unwrap();
+        assert_eq!(mapped.file_id, FileId(1));
+        assert_eq!(mapped.location.row, 2);
+        assert_eq!(
+            source_context.get_file(mapped.file_id).unwrap().path,
+            part.display().to_string()
+        );
+    }
+
+    #[test]
+    fn test_include_cycle_is_reported() {
+        let temp = tempfile::tempdir().unwrap();
+        let doc = write(temp.path(), "doc.qmd", "{{< include _a.qmd >}}\n");
+        write(temp.path(), "_a.qmd", "A.\n\n{{< include _b.qmd >}}\n");
+        // Leaves the directory and comes back, so the cycle is only
+        // visible after canonicalization.
+        std::fs::create_dir(temp.path().join("sub")).unwrap();
+        write(
+            temp.path(),
+            "_b.qmd",
+            "B.\n\n{{< include sub/../doc.qmd >}}\n",
+        );
+
+        let (_, _, result) = resolve(&doc);
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].title, "Include cycle");
+    }
+
+    #[test]
+    fn test_missing_include_is_reported() {
+        let temp = tempfile::tempdir().unwrap();
+        let doc = write(temp.path(), "doc.qmd", "{{< include _missing.qmd >}}\n");
+
+        let (blocks, _, result) = resolve(&doc);
+        let errors = result.unwrap_err();
+        assert_eq!(errors[0].title, "Included file not found");
+        assert!(blocks.is_empty());
+    }
+
+    #[test]
+    fn test_inline_include_is_left_alone() {
+        let temp = tempfile::tempdir().unwrap();
+        let doc = write(
+            temp.path(),
+            "doc.qmd",
+            "Text {{< include _part.qmd >}} more.\n",
+        );
+        write(temp.path(), "_part.qmd", "Part.\n");
+
+        let (blocks, _, result) = resolve(&doc);
+        assert!(result.is_ok());
+        let Block::Paragraph(Paragraph { content, .. }) = &blocks[0] else {
+            panic!("Expected Paragraph");
+        };
+        assert!(
+            content
+                .iter()
+                .any(|inline| matches!(inline, Inline::Shortcode(s) if s.name == "include"))
+        );
+    }
+}
@@ -0,0 +1,413 @@
+/*
+ * latex.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * LaTeX engine invocation for PDF output.
+ */
+
+//! LaTeX engine invocation for PDF output.
+//!
+//! PDF is produced by running a LaTeX engine over the `.tex` file written
+//! next to the output. The engine is chosen with the format's `pdf-engine`
+//! option (`xelatex` or `tectonic`); without one, XeLaTeX is used if it is
+//! installed, then Tectonic.
+//!
+//! XeLaTeX is rerun until cross references settle (at most [`MAX_RUNS`]
+//! times). Tectonic reruns itself. When compilation fails, the log is
+//! scanned for missing files so the user can be told which packages to
+//! install.
+//!
+//! Like `latexmk -c`, the auxiliary files an engine leaves behind are removed
+//! after a successful run unless they are kept for debugging.
+
+use std::path::{Path, PathBuf};
+
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_system_runtime::SystemRuntime;
+
+use crate::render::BinaryDependencies;
+
+/// Maximum number of XeLaTeX runs for a single document.
+pub const MAX_RUNS: usize = 5;
+
+/// Extensions of the auxiliary files LaTeX engines write next to the
+/// output (`{stem}.{ext}`).
+pub const INTERMEDIATE_EXTENSIONS: &[&str] = &[
+    "aux",
+    "log",
+    "toc",
+    "out",
+    "lof",
+    "lot",
+    "fls",
+    "fdb_latexmk",
+    "synctex.gz",
+    "xdv",
+    "bbl",
+    "blg",
+    "bcf",
+    "run.xml",
+    "nav",
+    "snm",
+    "vrb",
+];
+
+/// Log messages asking for another run.
+const RERUN_PATTERNS: &[&str] = &[
+    "Rerun to get",
+    "Please rerun LaTeX",
+    "Label(s) may have changed",
+    "Table widths have changed",
+];
+
+/// A LaTeX engine that can produce PDF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PdfEngine {
+    /// XeLaTeX (e.g. from TinyTeX or TeX Live)
+    Xelatex,
+    /// Tectonic, which fetches packages on demand
+    Tectonic,
+}
+
+impl PdfEngine {
+    /// Parse a `pdf-engine` name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "xelatex" => Some(PdfEngine::Xelatex),
+            "tectonic" => Some(PdfEngine::Tectonic),
+            _ => None,
+        }
+    }
+
+    /// The engine's name.
+    pub fn name(self) -> &'static str {
+        match self {
+            PdfEngine::Xelatex => "xelatex",
+            PdfEngine::Tectonic => "tectonic",
+        }
+    }
+
+    /// The engine's binary, if it was discovered.
+    pub fn binary(self, binaries: &BinaryDependencies) -> Option<&Path> {
+        match self {
+            PdfEngine::Xelatex => binaries.xelatex.as_deref(),
+            PdfEngine::Tectonic => binaries.tectonic.as_deref(),
+        }
+    }
+
+    /// Select the engine for a `pdf-engine` option and return its binary.
+    ///
+    /// Without an option, the first available of XeLaTeX and Tectonic is
+    /// used.
+    pub fn select(
+        requested: Option<&str>,
+        binaries: &BinaryDependencies,
+    ) -> Result<(Self, PathBuf), DiagnosticMessage> {
+        let candidates = match requested {
+            Some(name) => match PdfEngine::from_name(name) {
+                Some(engine) => vec![engine],
+                None => {
+                    return Err(DiagnosticMessageBuilder::error("Unknown PDF engine")
+                        .problem(format!("pdf-engine `{}` is not supported", name))
+                        .add_hint("Use one of: xelatex, tectonic")
+                        .build());
+                }
+            },
+            None => vec![PdfEngine::Xelatex, PdfEngine::Tectonic],
+        };
+
+        for engine in &candidates {
+            if let Some(binary) = engine.binary(binaries) {
+                return Ok((*engine, binary.to_path_buf()));
+            }
+        }
+
+        let problem = match requested {
+            Some(name) => format!("pdf-engine `{}` was not found", name),
+            None => "No LaTeX engine (xelatex or tectonic) was found".to_string(),
+        };
+        Err(DiagnosticMessageBuilder::error("PDF engine not found")
+            .problem(problem)
+            .add_hint("Run `quarto install tinytex`, or install Tectonic and make sure it is on your PATH")
+            .add_hint("Or point QUARTO_XELATEX / QUARTO_TECTONIC at the binary")
+            .build())
+    }
+}
+
+/// Result of a successful LaTeX compilation.
+#[derive(Debug)]
+pub struct LatexCompilation {
+    /// The PDF written next to the `.tex` file
+    pub pdf: PathBuf,
+    /// Number of engine runs
+    pub runs: usize,
+    /// Auxiliary files left next to the output
+    pub intermediates: Vec<PathBuf>,
+}
+
+/// Compile `tex_path` to PDF with `engine`.
+///
+/// The PDF and auxiliary files are written to the directory of `tex_path`.
+/// On failure, returns diagnostics describing the LaTeX errors (and which
+/// files are missing, if any).
+pub fn compile(
+    runtime: &dyn SystemRuntime,
+    engine: PdfEngine,
+    binary: &Path,
+    tex_path: &Path,
+) -> Result<LatexCompilation, Vec<DiagnosticMessage>> {
+    let dir = tex_path.parent().unwrap_or_else(|| Path::new("."));
+    let log_path = tex_path.with_extension("log");
+    let command = binary.to_string_lossy();
+    let tex = tex_path.to_string_lossy();
+    let dir_str = dir.to_string_lossy();
+
+    let output_dir_arg = format!("-output-directory={}", dir_str);
+    let args: Vec<&str> = match engine {
+        PdfEngine::Xelatex => vec![
+            "-interaction=nonstopmode",
+            "-halt-on-error",
+            "-file-line-error",
+            output_dir_arg.as_str(),
+            &*tex,
+        ],
+        PdfEngine::Tectonic => vec!["--outdir", &*dir_str, "--keep-logs", &*tex],
+    };
+    // Tectonic reruns until stable on its own
+    let max_runs = match engine {
+        PdfEngine::Xelatex => MAX_RUNS,
+        PdfEngine::Tectonic => 1,
+    };
+
+    let mut runs = 0;
+    loop {
+        runs += 1;
+        let output = runtime.exec_command(&command, &args, None).map_err(|e| {
+            vec![
+                DiagnosticMessageBuilder::error("Failed to run PDF engine")
+                    .problem(format!("Could not run `{}`: {}", command, e))
+                    .build(),
+            ]
+        })?;
+        let log = runtime
+            .file_read_string(&log_path)
+            .unwrap_or_else(|_| String::from_utf8_lossy(&output.stdout).into_owned());
+
+        if !output.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(compilation_diagnostics(
+                engine,
+                &format!("{}\n{}", log, stderr),
+            ));
+        }
+        if runs >= max_runs || !needs_rerun(&log) {
+            break;
+        }
+    }
+
+    let pdf = tex_path.with_extension("pdf");
+    if !runtime.is_file(&pdf).unwrap_or(false) {
+        return Err(vec![
+            DiagnosticMessageBuilder::error("LaTeX compilation failed")
+                .problem(format!(
+                    "`{}` finished without writing {}",
+                    engine.name(),
+                    pdf.display()
+                ))
+                .build(),
+        ]);
+    }
+
+    let intermediates = intermediate_files(tex_path)
+        .into_iter()
+        .filter(|path| runtime.is_file(path).unwrap_or(false))
+        .collect();
+    Ok(LatexCompilation {
+        pdf,
+        runs,
+        intermediates,
+    })
+}
+
+/// The auxiliary files an engine may write for `tex_path`.
+pub fn intermediate_files(tex_path: &Path) -> Vec<PathBuf> {
+    let stem = tex_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let dir = tex_path.parent().unwrap_or_else(|| Path::new(""));
+    INTERMEDIATE_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", stem, ext)))
+        .collect()
+}
+
+/// Whether the log asks for another run to resolve references.
+pub fn needs_rerun(log: &str) -> bool {
+    RERUN_PATTERNS.iter().any(|pattern| log.contains(pattern))
+}
+
+/// Files the engine could not find, from `File `x.sty' not found` and
+/// `I can't find file `x'` errors, in order of appearance.
+pub fn missing_files(log: &str) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for line in log.lines() {
+        let name = quoted_after(line, "LaTeX Error: File `")
+            .or_else(|| quoted_after(line, "I can't find file `"));
+        if let Some(name) = name
+            && !files.iter().any(|f| f == name)
+        {
+            files.push(name.to_string());
+        }
+    }
+    files
+}
+
+/// The text between `prefix` and the closing `'` in `line`.
+fn quoted_after<'a>(line: &'a str, prefix: &str) -> Option<&'a str> {
+    let start = line.find(prefix)? + prefix.len();
+    let rest = &line[start..];
+    let name = &rest[..rest.find('\'')?];
+    (!name.is_empty()).then_some(name)
+}
+
+/// Diagnostics for a failed engine run.
+fn compilation_diagnostics(engine: PdfEngine, log: &str) -> Vec<DiagnosticMessage> {
+    let missing = missing_files(log);
+    if !missing.is_empty() {
+        let packages: Vec<&str> = missing
+            .iter()
+            .map(|file| {
+                file.rsplit_once('.')
+                    .map_or(file.as_str(), |(stem, _)| stem)
+            })
+            .collect();
+        let files = missing
+            .iter()
+            .map(|file| format!("`{}`", file))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut builder = DiagnosticMessageBuilder::error("Missing LaTeX packages")
+            .problem(format!("`{}` could not find {}", engine.name(), files));
+        builder = match engine {
+            PdfEngine::Xelatex => builder
+                .add_hint(format!(
+                    "Install the missing packages, e.g. `tlmgr install {}`",
+                    packages.join(" ")
+                ))
+                .add_hint(format!(
+                    "If a package is named differently, `tlmgr search --global --file {}` finds it",
+                    missing[0]
+                )),
+            PdfEngine::Tectonic => builder
+                .add_hint("Tectonic downloads packages on demand; check the package name and your network connection"),
+        };
+        return vec![builder.build()];
+    }
+
+    // Report the first LaTeX error with the line it occurred on
+    let lines: Vec<&str> = log.lines().collect();
+    let error = lines.iter().position(|line| {
+        line.starts_with('!') || line.contains(":error:") || is_file_line_error(line)
+    });
+    let mut builder = DiagnosticMessageBuilder::error("LaTeX compilation failed");
+    builder = match error {
+        Some(i) => {
+            let context: Vec<&str> = lines[i..]
+                .iter()
+                .take(6)
+                .take_while(|line| !line.trim().is_empty())
+                .copied()
+                .collect();
+            builder.problem(context.join("\n"))
+        }
+        None => builder.problem(format!("`{}` exited with an error", engine.name())),
+    };
+    vec![
+        builder
+            .add_hint("Render with `--debug` to keep the .tex and .log files for inspection")
+            .build(),
+    ]
+}
+
+/// Whether `line` is a `-file-line-error` style error (`./doc.tex:12: ...`).
+fn is_file_line_error(line: &str) -> bool {
+    let mut parts = line.splitn(3, ':');
+    let (Some(file), Some(number), Some(_)) = (parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    file.ends_with(".tex") && !number.is_empty() && number.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_engine() {
+        let binaries = BinaryDependencies {
+            tectonic: Some(PathBuf::from("/usr/bin/tectonic")),
+            ..Default::default()
+        };
+        let (engine, binary) = PdfEngine::select(None, &binaries).unwrap();
+        assert_eq!(engine, PdfEngine::Tectonic);
+        assert_eq!(binary, PathBuf::from("/usr/bin/tectonic"));
+
+        assert!(PdfEngine::select(Some("xelatex"), &binaries).is_err());
+        assert!(PdfEngine::select(Some("lualatex"), &binaries).is_err());
+        assert!(PdfEngine::select(None, &BinaryDependencies::default()).is_err());
+    }
+
+    #[test]
+    fn test_needs_rerun() {
+        assert!(needs_rerun(
+            "LaTeX Warning: Label(s) may have changed. Rerun to get cross-references right."
+        ));
+        assert!(!needs_rerun("Output written on doc.pdf (1 page)."));
+    }
+
+    #[test]
+    fn test_missing_files() {
+        let log = concat!(
+            "! LaTeX Error: File `fancyhdr.sty' not found.\n",
+            "! LaTeX Error: File `fancyhdr.sty' not found.\n",
+            "! I can't find file `koma.cls'.\n",
+        );
+        assert_eq!(missing_files(log), vec!["fancyhdr.sty", "koma.cls"]);
+    }
+
+    #[test]
+    fn test_missing_package_diagnostic() {
+        let diagnostics = compilation_diagnostics(
+            PdfEngine::Xelatex,
+            "./doc.tex:3: LaTeX Error: File `fancyhdr.sty' not found.\n",
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].title, "Missing LaTeX packages");
+        assert!(
+            diagnostics[0]
+                .hints
+                .iter()
+                .any(|hint| hint.as_str().contains("tlmgr install fancyhdr"))
+        );
+    }
+
+    #[test]
+    fn test_error_diagnostic_includes_context() {
+        let log = "This is XeTeX\n./doc.tex:12: Undefined control sequence.\nl.12 \\foo\n\nmore";
+        let diagnostics = compilation_diagnostics(PdfEngine::Xelatex, log);
+        assert_eq!(diagnostics[0].title, "LaTeX compilation failed");
+        let problem = diagnostics[0].problem.as_ref().unwrap().as_str();
+        assert!(problem.contains("Undefined control sequence"));
+        assert!(problem.contains("l.12 \\foo"));
+        assert!(!problem.contains("more"));
+    }
+
+    #[test]
+    fn test_intermediate_files() {
+        let files = intermediate_files(Path::new("/out/doc.tex"));
+        assert!(files.contains(&PathBuf::from("/out/doc.aux")));
+        assert!(files.contains(&PathBuf::from("/out/doc.synctex.gz")));
+        assert!(!files.contains(&PathBuf::from("/out/doc.pdf")));
+    }
+}
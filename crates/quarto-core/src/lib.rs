@@ -37,31 +37,64 @@
 //! ```
 
 pub mod artifact;
+pub mod book;
+pub mod brand;
 pub mod engine;
 pub mod error;
+pub mod extension;
 pub mod format;
+pub mod include;
+pub mod latex;
+pub mod listing;
+pub mod math;
+pub mod metadata_files;
+pub mod notebook;
+pub mod params;
 pub mod pipeline;
+pub mod postprocess;
 pub mod project;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod project_output;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod project_outputs;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod project_render;
 pub mod render;
 pub mod resources;
+pub mod revealjs;
 pub mod stage;
 pub mod template;
+pub mod tools;
 pub mod transform;
 pub mod transforms;
+pub mod typst;
 
 // Re-export commonly used types
 pub use artifact::{Artifact, ArtifactStore};
+pub use book::{Book, BookChapter, BookPart};
 pub use error::{ParseError, QuartoError, Result};
 pub use format::{Format, FormatIdentifier, extract_format_metadata};
+pub use listing::{FeedFormat, FeedOptions, FeedType, Listing, ListingItem, ListingType};
+pub use math::MathMethod;
 pub use pipeline::{
-    DEFAULT_CSS_ARTIFACT_PATH, HtmlRenderConfig, RenderOutput, build_html_pipeline,
-    build_html_pipeline_stages, build_html_pipeline_with_stages, build_wasm_html_pipeline,
-    render_qmd_to_html,
+    DEFAULT_CSS_ARTIFACT_PATH, HIGHLIGHT_CSS_ARTIFACT_PATH, HtmlRenderConfig, PdfRenderConfig,
+    PdfRenderOutput, PreparedDocument, RenderOutput, build_document_stages,
+    build_html_format_stages, build_html_pipeline, build_html_pipeline_stages,
+    build_html_pipeline_with_stages, build_latex_transform_pipeline, build_pdf_format_stages,
+    build_pdf_pipeline_stages, build_typst_format_stages, build_typst_pipeline_stages,
+    build_wasm_html_pipeline, prepare_document, render_prepared_to_html, render_prepared_to_pdf,
+    render_qmd_to_html, render_qmd_to_pdf,
 };
 pub use project::{DocumentInfo, ProjectConfig, ProjectContext, ProjectType};
+#[cfg(not(target_arch = "wasm32"))]
+pub use project_output::{
+    ProjectOutput, ProjectOutputContext, ProjectOutputPipeline, build_project_output_pipeline,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use project_render::{DependencyGraph, ProjectRenderResult, ProjectRenderer};
 pub use render::{BinaryDependencies, RenderContext, RenderOptions, RenderResult};
 pub use transform::{AstTransform, TransformPipeline};
 pub use transforms::{
-    CalloutResolveTransform, CalloutTransform, MetadataNormalizeTransform,
+    CalloutResolveTransform, CalloutTransform, MetadataNormalizeTransform, ProjectCrossrefs,
     ResourceCollectorTransform, TitleBlockTransform,
 };
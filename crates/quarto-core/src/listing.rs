@@ -0,0 +1,654 @@
+/*
+ * listing.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Document listings (blog post index pages).
+ */
+
+//! Document listings.
+//!
+//! A page with `listing:` in its front matter lists other documents of its
+//! project, typically the posts of a blog. The option is a map, or a list of
+//! maps for several listings on one page:
+//!
+//! - `id` - element id of the listing (default `listing`, then `listing-2`, ...)
+//! - `contents` - globs or directories, relative to the page, of the
+//!   documents to list (default: every other document in the page's
+//!   directory tree)
+//! - `type` - `default`, `table` or `grid`
+//! - `sort` - a field with optional `asc`/`desc` (default `"date desc"`), a
+//!   list of those, or `false` to keep file order
+//! - `max-items` - list at most this many documents
+//! - `categories` - show category filter chips
+//! - `include` / `exclude` - maps of field to glob; listed documents must
+//!   match every `include` entry and no `exclude` entry
+//! - `feed` - `true`, or a map of [`FeedOptions`], to publish the listing as
+//!   an RSS or Atom feed (written after the project is rendered)
+//!
+//! Listed documents contribute `title`, `date`, `author`, `description`,
+//! `categories` and `image` from their front matter. Documents with
+//! `draft: true` are not listed. Dates are compared as written, so they
+//! sort chronologically when given as `YYYY-MM-DD`.
+
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use quarto_system_runtime::SystemRuntime;
+use serde_json::Value;
+
+use crate::project::{ProjectContext, front_matter};
+
+/// How listed documents are laid out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListingType {
+    /// One row per document, with image, description and metadata
+    #[default]
+    Default,
+    /// A table of date, title and author
+    Table,
+    /// Cards in a grid
+    Grid,
+}
+
+/// A field to sort listed documents by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKey {
+    /// Field name (`date`, `title`, `author`, ...)
+    pub field: String,
+    /// Whether to sort in descending order
+    pub descending: bool,
+}
+
+impl SortKey {
+    fn parse(spec: &str) -> Option<Self> {
+        let mut parts = spec.split_whitespace();
+        let field = parts.next()?.to_string();
+        let descending = parts
+            .next()
+            .is_some_and(|dir| dir.eq_ignore_ascii_case("desc"));
+        Some(Self { field, descending })
+    }
+}
+
+/// Content of feed items.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeedType {
+    /// The document's description, or its first paragraph
+    #[default]
+    Partial,
+    /// The document's full rendered content
+    Full,
+}
+
+/// Feed document format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeedFormat {
+    /// RSS 2.0
+    #[default]
+    Rss,
+    /// Atom 1.0
+    Atom,
+}
+
+/// Options of a listing's `feed`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedOptions {
+    /// `type`: `partial` (default) or `full`
+    pub feed_type: FeedType,
+    /// `format`: `rss` (default) or `atom`
+    pub format: FeedFormat,
+    /// `items`: maximum number of items (default 20)
+    pub items: usize,
+    /// `title`: feed title (default: the listing page's title)
+    pub title: Option<String>,
+    /// `description`: feed description
+    pub description: Option<String>,
+    /// `categories`: also write a feed for each of these categories
+    pub categories: Vec<String>,
+}
+
+impl Default for FeedOptions {
+    fn default() -> Self {
+        Self {
+            feed_type: FeedType::default(),
+            format: FeedFormat::default(),
+            items: 20,
+            title: None,
+            description: None,
+            categories: Vec::new(),
+        }
+    }
+}
+
+impl FeedOptions {
+    fn parse(value: Option<&Value>) -> Option<Self> {
+        let value = value?;
+        if value.as_bool() == Some(false) || value.is_null() {
+            return None;
+        }
+        let string = |key: &str| value.get(key).and_then(Value::as_str).map(String::from);
+        let defaults = Self::default();
+
+        Some(Self {
+            feed_type: match string("type").as_deref() {
+                Some("full") => FeedType::Full,
+                _ => FeedType::Partial,
+            },
+            format: match string("format").as_deref() {
+                Some("atom") => FeedFormat::Atom,
+                _ => FeedFormat::Rss,
+            },
+            items: value
+                .get("items")
+                .and_then(Value::as_u64)
+                .map_or(defaults.items, |n| n as usize),
+            title: string("title"),
+            description: string("description"),
+            categories: strings(value.get("categories")),
+        })
+    }
+}
+
+/// One `listing:` entry of a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listing {
+    /// Element id of the listing
+    pub id: String,
+    /// Content globs, relative to the listing page
+    pub contents: Vec<String>,
+    /// Layout of the listing
+    pub listing_type: ListingType,
+    /// Sort keys, most significant first
+    pub sort: Vec<SortKey>,
+    /// Maximum number of documents to list
+    pub max_items: Option<usize>,
+    /// Whether to show category filter chips
+    pub categories: bool,
+    /// Field globs that listed documents must match
+    pub include: Vec<(String, String)>,
+    /// Field globs that listed documents must not match
+    pub exclude: Vec<(String, String)>,
+    /// Feed to publish for this listing
+    pub feed: Option<FeedOptions>,
+}
+
+impl Listing {
+    /// Parse the `listing` option of a page.
+    pub fn from_metadata(value: &Value) -> Vec<Self> {
+        let entries: Vec<&Value> = match value {
+            Value::Array(entries) => entries.iter().collect(),
+            Value::Null | Value::Bool(false) => Vec::new(),
+            other => vec![other],
+        };
+
+        entries
+            .into_iter()
+            .enumerate()
+            .map(|(i, entry)| Self::parse(entry, i))
+            .collect()
+    }
+
+    fn parse(value: &Value, index: usize) -> Self {
+        let default_id = if index == 0 {
+            "listing".to_string()
+        } else {
+            format!("listing-{}", index + 1)
+        };
+        let string = |key: &str| value.get(key).and_then(Value::as_str);
+
+        let listing_type = match string("type").or_else(|| value.as_str()) {
+            Some("table") => ListingType::Table,
+            Some("grid") => ListingType::Grid,
+            _ => ListingType::Default,
+        };
+
+        let sort = match value.get("sort") {
+            Some(Value::Bool(false)) => Vec::new(),
+            Some(Value::String(spec)) => SortKey::parse(spec).into_iter().collect(),
+            Some(Value::Array(specs)) => specs
+                .iter()
+                .filter_map(Value::as_str)
+                .filter_map(SortKey::parse)
+                .collect(),
+            _ => vec![SortKey {
+                field: "date".to_string(),
+                descending: true,
+            }],
+        };
+
+        Self {
+            id: string("id").map_or(default_id, String::from),
+            contents: strings(value.get("contents")),
+            listing_type,
+            sort,
+            max_items: value
+                .get("max-items")
+                .and_then(Value::as_u64)
+                .map(|n| n as usize),
+            categories: value
+                .get("categories")
+                .is_some_and(|c| c.as_bool() != Some(false)),
+            include: field_globs(value.get("include")),
+            exclude: field_globs(value.get("exclude")),
+            feed: FeedOptions::parse(value.get("feed")),
+        }
+    }
+
+    /// The documents this listing shows, filtered, sorted and truncated.
+    ///
+    /// `page` is the input path of the listing page; the front matter of
+    /// each candidate document is read through `runtime`.
+    pub fn items(
+        &self,
+        project: &ProjectContext,
+        page: &Path,
+        runtime: &dyn SystemRuntime,
+    ) -> Vec<ListingItem> {
+        let files: Vec<PathBuf> = project.files.iter().map(|doc| doc.input.clone()).collect();
+
+        let mut items: Vec<ListingItem> = listed_files(page, &self.contents, &files)
+            .into_iter()
+            .filter_map(|i| {
+                let doc = &project.files[i];
+                let meta = runtime
+                    .file_read_string(&doc.input)
+                    .ok()
+                    .and_then(|source| front_matter(&source))
+                    .unwrap_or(Value::Null);
+                if meta.get("draft").and_then(Value::as_bool) == Some(true) {
+                    return None;
+                }
+                Some(ListingItem::from_front_matter(
+                    &doc.input,
+                    doc.title.as_deref(),
+                    &meta,
+                ))
+            })
+            .filter(|item| {
+                self.include.iter().all(|(f, g)| item.matches(f, g))
+                    && !self.exclude.iter().any(|(f, g)| item.matches(f, g))
+            })
+            .collect();
+
+        items.sort_by(|a, b| {
+            self.sort
+                .iter()
+                .map(|key| compare_field(a, b, key))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+
+        if let Some(max) = self.max_items {
+            items.truncate(max);
+        }
+        items
+    }
+}
+
+/// A document shown in a listing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListingItem {
+    /// Input path of the document
+    pub input: PathBuf,
+    /// Title (front matter, first heading, or file stem)
+    pub title: String,
+    /// Date, as written in the front matter
+    pub date: Option<String>,
+    /// Author names, comma-separated
+    pub author: Option<String>,
+    /// Description
+    pub description: Option<String>,
+    /// Categories
+    pub categories: Vec<String>,
+    /// Image path, relative to the document
+    pub image: Option<String>,
+}
+
+impl ListingItem {
+    /// Build an item from a document's front matter.
+    pub fn from_front_matter(input: &Path, title: Option<&str>, meta: &Value) -> Self {
+        let string = |key: &str| match meta.get(key) {
+            Some(Value::String(s)) => Some(s.clone()),
+            Some(Value::Number(n)) => Some(n.to_string()),
+            _ => None,
+        };
+
+        let title = string("title")
+            .or_else(|| title.map(String::from))
+            .or_else(|| {
+                input
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+            })
+            .unwrap_or_default();
+
+        let authors: Vec<String> = match meta.get("author") {
+            Some(Value::Array(authors)) => authors.iter().filter_map(author_name).collect(),
+            Some(author) => author_name(author).into_iter().collect(),
+            None => Vec::new(),
+        };
+
+        Self {
+            input: input.to_path_buf(),
+            title,
+            date: string("date"),
+            author: (!authors.is_empty()).then(|| authors.join(", ")),
+            description: string("description"),
+            categories: strings(meta.get("categories")),
+            image: string("image"),
+        }
+    }
+
+    /// The values of a field, for filtering and sorting.
+    fn field(&self, name: &str) -> Vec<&str> {
+        match name {
+            "title" => vec![self.title.as_str()],
+            "categories" => self.categories.iter().map(String::as_str).collect(),
+            "date" => self.date.as_deref().into_iter().collect(),
+            "author" => self.author.as_deref().into_iter().collect(),
+            "description" => self.description.as_deref().into_iter().collect(),
+            "image" => self.image.as_deref().into_iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn matches(&self, field: &str, glob: &str) -> bool {
+        let pattern = glob::Pattern::new(glob).ok();
+        self.field(field).iter().any(|value| match &pattern {
+            Some(pattern) => pattern.matches(value),
+            None => *value == glob,
+        })
+    }
+}
+
+/// Compare two items by one key; items without the field sort last.
+fn compare_field(a: &ListingItem, b: &ListingItem, key: &SortKey) -> Ordering {
+    let value = |item: &ListingItem| item.field(&key.field).first().map(|v| v.to_lowercase());
+    match (value(a), value(b)) {
+        (Some(a), Some(b)) if key.descending => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn author_name(author: &Value) -> Option<String> {
+    match author {
+        Value::String(name) => Some(name.clone()),
+        Value::Object(_) => author.get("name").and_then(|name| match name {
+            Value::String(name) => Some(name.clone()),
+            // `name: {given: ..., family: ...}`
+            _ => {
+                let part = |key: &str| name.get(key).and_then(Value::as_str);
+                let parts: Vec<&str> = [part("given"), part("family")]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                (!parts.is_empty()).then(|| parts.join(" "))
+            }
+        }),
+        _ => None,
+    }
+}
+
+/// A string or list of strings.
+fn strings(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::String(s)) => vec![s.clone()],
+        Some(Value::Array(items)) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The entries of an `include`/`exclude` map.
+fn field_globs(value: Option<&Value>) -> Vec<(String, String)> {
+    let Some(Value::Object(map)) = value else {
+        return Vec::new();
+    };
+    map.iter()
+        .flat_map(|(field, globs)| {
+            strings(Some(globs))
+                .into_iter()
+                .map(move |glob| (field.clone(), glob))
+        })
+        .collect()
+}
+
+/// The indices of the files matched by a listing's content globs.
+///
+/// Globs are relative to the directory of the listing page; a glob that
+/// names a directory matches the files in it. Without globs, every other
+/// file in the page's directory tree is listed.
+pub(crate) fn listed_files(page: &Path, globs: &[String], files: &[PathBuf]) -> Vec<usize> {
+    let dir = page.parent().unwrap_or(Path::new(""));
+
+    if globs.is_empty() {
+        return files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| file.as_path() != page && file.starts_with(dir))
+            .map(|(i, _)| i)
+            .collect();
+    }
+
+    let patterns: Vec<glob::Pattern> = globs
+        .iter()
+        .filter_map(|g| glob::Pattern::new(g.trim_start_matches("./")).ok())
+        .collect();
+    let options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
+
+    files
+        .iter()
+        .enumerate()
+        .filter(|(_, file)| {
+            file.as_path() != page
+                && file.strip_prefix(dir).is_ok_and(|relative| {
+                    patterns.iter().any(|p| {
+                        p.matches_path_with(relative, options)
+                            || relative
+                                .ancestors()
+                                .skip(1)
+                                .any(|parent| p.matches_path_with(parent, options))
+                    })
+                })
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::DocumentInfo;
+    use quarto_system_runtime::NativeRuntime;
+    use serde_json::json;
+
+    fn write(dir: &Path, path: &str, content: &str) -> PathBuf {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn project(dir: &Path, files: Vec<PathBuf>) -> ProjectContext {
+        ProjectContext {
+            dir: dir.to_path_buf(),
+            config: None,
+            is_single_file: false,
+            files: files.into_iter().map(DocumentInfo::from_path).collect(),
+            output_dir: dir.join("_site"),
+        }
+    }
+
+    #[test]
+    fn test_parse_listing_options() {
+        let listings = Listing::from_metadata(&json!([
+            { "contents": "posts", "sort": ["title", "date desc"], "max-items": 3 },
+            { "id": "talks", "type": "grid", "categories": true,
+              "include": { "categories": ["r", "python"] } }
+        ]));
+
+        assert_eq!(listings.len(), 2);
+        assert_eq!(listings[0].id, "listing");
+        assert_eq!(listings[0].contents, vec!["posts"]);
+        assert_eq!(listings[0].listing_type, ListingType::Default);
+        assert_eq!(
+            listings[0].sort,
+            vec![
+                SortKey {
+                    field: "title".to_string(),
+                    descending: false
+                },
+                SortKey {
+                    field: "date".to_string(),
+                    descending: true
+                }
+            ]
+        );
+        assert_eq!(listings[0].max_items, Some(3));
+        assert!(!listings[0].categories);
+
+        assert_eq!(listings[1].id, "talks");
+        assert_eq!(listings[1].listing_type, ListingType::Grid);
+        assert!(listings[1].categories);
+        assert_eq!(
+            listings[1].include,
+            vec![
+                ("categories".to_string(), "r".to_string()),
+                ("categories".to_string(), "python".to_string())
+            ]
+        );
+        // Default sort is newest first
+        assert_eq!(listings[1].sort[0].field, "date");
+        assert!(listings[1].sort[0].descending);
+        assert_eq!(listings[1].feed, None);
+    }
+
+    #[test]
+    fn test_parse_feed_options() {
+        let listing = &Listing::from_metadata(&json!({ "feed": true }))[0];
+        assert_eq!(listing.feed, Some(FeedOptions::default()));
+
+        let listing = &Listing::from_metadata(&json!({
+            "feed": { "type": "full", "format": "atom", "items": 5, "categories": "r" }
+        }))[0];
+        let feed = listing.feed.as_ref().unwrap();
+        assert_eq!(feed.feed_type, FeedType::Full);
+        assert_eq!(feed.format, FeedFormat::Atom);
+        assert_eq!(feed.items, 5);
+        assert_eq!(feed.categories, vec!["r"]);
+    }
+
+    #[test]
+    fn test_item_from_front_matter() {
+        let item = ListingItem::from_front_matter(
+            Path::new("/p/posts/welcome/index.qmd"),
+            None,
+            &json!({
+                "title": "Welcome",
+                "date": "2024-01-05",
+                "author": [{ "name": "Ada Lovelace" }, "Charles Babbage"],
+                "categories": ["news", "code"],
+                "image": "thumbnail.jpg"
+            }),
+        );
+        assert_eq!(item.title, "Welcome");
+        assert_eq!(item.date.as_deref(), Some("2024-01-05"));
+        assert_eq!(
+            item.author.as_deref(),
+            Some("Ada Lovelace, Charles Babbage")
+        );
+        assert_eq!(item.categories, vec!["news", "code"]);
+        assert_eq!(item.image.as_deref(), Some("thumbnail.jpg"));
+
+        let untitled = ListingItem::from_front_matter(Path::new("/p/notes.qmd"), None, &json!({}));
+        assert_eq!(untitled.title, "notes");
+    }
+
+    #[test]
+    fn test_items_sorted_filtered_and_truncated() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let files = vec![
+            write(dir, "index.qmd", "---\nlisting:\n  contents: posts\n---\n"),
+            write(
+                dir,
+                "posts/old.qmd",
+                "---\ntitle: Old\ndate: 2023-03-01\ncategories: [r]\n---\n",
+            ),
+            write(
+                dir,
+                "posts/new/index.qmd",
+                "---\ntitle: New\ndate: 2024-06-01\ncategories: [python]\n---\n",
+            ),
+            write(
+                dir,
+                "posts/draft.qmd",
+                "---\ntitle: Draft\ndate: 2025-01-01\ndraft: true\n---\n",
+            ),
+            write(dir, "posts/undated.qmd", "---\ntitle: Undated\n---\n"),
+            write(dir, "about.qmd", "---\ntitle: About\n---\n"),
+        ];
+        let project = project(dir, files);
+        let runtime = NativeRuntime::new();
+        let page = dir.join("index.qmd");
+
+        let titles = |listing: &Listing| -> Vec<String> {
+            listing
+                .items(&project, &page, &runtime)
+                .into_iter()
+                .map(|item| item.title)
+                .collect()
+        };
+
+        let listing = &Listing::from_metadata(&json!({ "contents": "posts" }))[0];
+        assert_eq!(titles(listing), vec!["New", "Old", "Undated"]);
+
+        let listing =
+            &Listing::from_metadata(&json!({ "contents": "posts", "sort": "title desc" }))[0];
+        assert_eq!(titles(listing), vec!["Undated", "Old", "New"]);
+
+        let listing = &Listing::from_metadata(&json!({ "sort": "date", "max-items": 2 }))[0];
+        assert_eq!(titles(listing), vec!["Old", "New"]);
+
+        let listing = &Listing::from_metadata(&json!({
+            "contents": "posts/*.qmd",
+            "exclude": { "title": "Undated" }
+        }))[0];
+        assert_eq!(titles(listing), vec!["Old"]);
+
+        let listing = &Listing::from_metadata(&json!({ "include": { "categories": "py*" } }))[0];
+        assert_eq!(titles(listing), vec!["New"]);
+    }
+
+    #[test]
+    fn test_listed_files() {
+        let files: Vec<PathBuf> = [
+            "/p/blog.qmd",
+            "/p/posts/a.qmd",
+            "/p/posts/x/b.qmd",
+            "/p/c.qmd",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+        let page = Path::new("/p/blog.qmd");
+
+        assert_eq!(listed_files(page, &[], &files), vec![1, 2, 3]);
+        assert_eq!(
+            listed_files(page, &["posts".to_string()], &files),
+            vec![1, 2]
+        );
+        assert_eq!(
+            listed_files(page, &["posts/*.qmd".to_string()], &files),
+            vec![1]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+/*
+ * math.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Dependencies for rendering math in HTML.
+ */
+
+//! Math rendering dependencies.
+//!
+//! pampa's HTML writer emits the markup for the selected [`MathMethod`].
+//! MathJax and KaTeX also need scripts (and KaTeX a stylesheet) in the
+//! document head. [`store_math_dependencies`] records these in the
+//! ArtifactStore and returns the head markup, which the render pipeline
+//! exposes to the HTML template as `rendered.math`.
+//!
+//! MathML and plain math need no dependencies.
+
+use crate::artifact::{Artifact, ArtifactStore};
+
+pub use pampa::writers::html::MathMethod;
+
+/// MathJax 3 (TeX input, CHTML output) from the jsDelivr CDN.
+pub const MATHJAX_URL: &str = "https://cdn.jsdelivr.net/npm/mathjax@3/es5/tex-chtml-full.js";
+
+/// KaTeX script from the jsDelivr CDN.
+pub const KATEX_JS_URL: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.11/dist/katex.min.js";
+
+/// KaTeX stylesheet from the jsDelivr CDN.
+pub const KATEX_CSS_URL: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.11/dist/katex.min.css";
+
+/// Renders each `span.math` with KaTeX once the document has loaded
+/// (the same script Pandoc's HTML template uses).
+const KATEX_RENDER_SCRIPT: &str = r#"document.addEventListener("DOMContentLoaded", function () {
+  var mathElements = document.getElementsByClassName("math");
+  for (var i = 0; i < mathElements.length; i++) {
+    var texText = mathElements[i].firstChild;
+    if (mathElements[i].tagName == "SPAN" && texText) {
+      katex.render(texText.data, mathElements[i], {
+        displayMode: mathElements[i].classList.contains("display"),
+        throwOnError: false
+      });
+    }
+  }
+});
+"#;
+
+/// An artifact standing for a dependency loaded from `url`.
+fn url_artifact(url: &str, content_type: &str) -> Artifact {
+    Artifact::from_bytes(Vec::new(), content_type).with_metadata("url", serde_json::json!(url))
+}
+
+/// Store the script/style dependencies of `method` and return the markup
+/// that loads them from the document head.
+///
+/// Artifacts use the `js:<name>` / `css:<name>` keys. Dependencies loaded
+/// from a CDN have empty content and their address in the `url` metadata.
+///
+/// Returns `None` for methods that need no dependencies.
+pub fn store_math_dependencies(
+    method: MathMethod,
+    artifacts: &mut ArtifactStore,
+) -> Option<String> {
+    match method {
+        MathMethod::MathJax => {
+            artifacts.store("js:mathjax", url_artifact(MATHJAX_URL, "text/javascript"));
+            Some(format!(
+                "<script src=\"{}\" type=\"text/javascript\"></script>",
+                MATHJAX_URL
+            ))
+        }
+        MathMethod::KaTeX => {
+            artifacts.store("js:katex", url_artifact(KATEX_JS_URL, "text/javascript"));
+            artifacts.store("css:katex", url_artifact(KATEX_CSS_URL, "text/css"));
+            artifacts.store(
+                "js:katex-render",
+                Artifact::from_string(KATEX_RENDER_SCRIPT, "text/javascript"),
+            );
+            Some(format!(
+                "<script defer src=\"{}\"></script>\n<script>\n{}</script>\n\
+                 <link rel=\"stylesheet\" href=\"{}\">",
+                KATEX_JS_URL, KATEX_RENDER_SCRIPT, KATEX_CSS_URL
+            ))
+        }
+        MathMethod::MathML | MathMethod::Plain => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mathjax_dependencies() {
+        let mut artifacts = ArtifactStore::new();
+        let header = store_math_dependencies(MathMethod::MathJax, &mut artifacts).unwrap();
+        assert!(header.contains(MATHJAX_URL));
+        let artifact = artifacts.get("js:mathjax").unwrap();
+        assert_eq!(artifact.content_type, "text/javascript");
+        assert_eq!(
+            artifact.metadata.get("url"),
+            Some(&serde_json::json!(MATHJAX_URL))
+        );
+    }
+
+    #[test]
+    fn test_katex_dependencies() {
+        let mut artifacts = ArtifactStore::new();
+        let header = store_math_dependencies(MathMethod::KaTeX, &mut artifacts).unwrap();
+        assert!(header.contains(KATEX_JS_URL));
+        assert!(header.contains(KATEX_CSS_URL));
+        assert!(header.contains("katex.render"));
+        assert!(artifacts.get("js:katex").is_some());
+        assert!(artifacts.get("css:katex").is_some());
+        assert!(
+            artifacts
+                .get("js:katex-render")
+                .unwrap()
+                .as_string()
+                .contains("katex.render")
+        );
+    }
+
+    #[test]
+    fn test_mathml_and_plain_need_no_dependencies() {
+        let mut artifacts = ArtifactStore::new();
+        assert!(store_math_dependencies(MathMethod::MathML, &mut artifacts).is_none());
+        assert!(store_math_dependencies(MathMethod::Plain, &mut artifacts).is_none());
+        assert!(artifacts.get("js:mathjax").is_none());
+    }
+}
@@ -0,0 +1,338 @@
+/*
+ * metadata_files.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Metadata loaded from the files listed in `metadata-files`.
+ */
+
+//! Metadata loaded from the files listed in `metadata-files`.
+//!
+//! Both `_quarto.yml` and a document's front matter may list YAML files
+//! whose contents become document metadata:
+//!
+//! ```yaml
+//! metadata-files:
+//!   - _authors.yml
+//!   - _styles.yml
+//! ```
+//!
+//! Files listed by the project are relative to the project directory, files
+//! listed by the document to the document. They are read right after parsing
+//! (see [`ParseDocumentStage`](crate::stage::ParseDocumentStage)) and merged
+//! as config layers, lowest precedence first:
+//!
+//! 1. the project's `metadata-files`, in order
+//! 2. the document's `metadata-files`, in order
+//! 3. the document's front matter
+//! 4. metadata given on the command line (`-M KEY:VALUE`), see
+//!    [`apply_metadata_overrides`]
+//!
+//! Each file is registered in the [`SourceContext`], so its values keep
+//! source locations in that file and problems with them are reported there.
+//! Strings are read as markdown, as in front matter.
+
+use std::path::{Path, PathBuf};
+
+use pampa::pandoc::meta::yaml_to_config_value;
+use pampa::utils::diagnostic_collector::DiagnosticCollector;
+use quarto_config::MergedConfig;
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_pandoc_types::{ConfigValue, InterpretationContext};
+use quarto_source_map::{SourceContext, SourceInfo};
+use quarto_system_runtime::SystemRuntime;
+
+use crate::project::ProjectContext;
+
+/// Metadata key listing the files to load.
+pub const METADATA_FILES_KEY: &str = "metadata-files";
+
+/// Merge the metadata files of the project and of the document at `path`
+/// under the document's metadata `meta`.
+///
+/// Returns diagnostics if a file can't be read or isn't a YAML mapping.
+pub fn apply_metadata_files(
+    meta: &mut ConfigValue,
+    path: &Path,
+    project: &ProjectContext,
+    runtime: &dyn SystemRuntime,
+    source_context: &mut SourceContext,
+) -> Result<(), Vec<DiagnosticMessage>> {
+    // (file, where it was listed)
+    let mut files: Vec<(PathBuf, Option<SourceInfo>)> = Vec::new();
+    if let Some(config) = &project.config {
+        let listed =
+            config
+                .raw
+                .get(METADATA_FILES_KEY)
+                .map_or_else(Vec::new, |value| match value {
+                    serde_json::Value::Array(items) => {
+                        items.iter().filter_map(|item| item.as_str()).collect()
+                    }
+                    _ => value.as_str().into_iter().collect(),
+                });
+        files.extend(
+            listed
+                .into_iter()
+                .map(|file| (project.dir.join(file), None)),
+        );
+    }
+    if let Some(listed) = meta.get(METADATA_FILES_KEY) {
+        let document_dir = path.parent().unwrap_or(Path::new(""));
+        let items: Vec<&ConfigValue> = match listed.as_array() {
+            Some(items) => items.iter().collect(),
+            None => vec![listed],
+        };
+        for item in items {
+            if let Some(file) = item.as_plain_text() {
+                files.push((document_dir.join(file), Some(item.source_info.clone())));
+            }
+        }
+    }
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    let mut layers = Vec::new();
+    let mut diagnostics = Vec::new();
+    for (file, listed_at) in &files {
+        match load_metadata_file(file, listed_at.as_ref(), runtime, source_context) {
+            Ok(layer) => layers.push(layer),
+            Err(diagnostic) => diagnostics.push(diagnostic),
+        }
+    }
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+
+    let mut all_layers: Vec<&ConfigValue> = layers.iter().collect();
+    all_layers.push(meta);
+    let merged = MergedConfig::new(all_layers).materialize().map_err(|e| {
+        vec![
+            DiagnosticMessageBuilder::error("Metadata files could not be merged")
+                .problem(e.to_string())
+                .build(),
+        ]
+    })?;
+    *meta = merged;
+    Ok(())
+}
+
+/// Merge metadata overrides (`quarto render -M KEY:VALUE`) over the
+/// document's metadata `meta`.
+///
+/// The overrides are read as a YAML layer registered in the
+/// [`SourceContext`] as `<command line>`, so their strings are markdown and
+/// problems with them are reported like problems in a metadata file.
+pub fn apply_metadata_overrides(
+    meta: &mut ConfigValue,
+    overrides: &serde_json::Map<String, serde_json::Value>,
+    source_context: &mut SourceContext,
+) -> Result<(), Vec<DiagnosticMessage>> {
+    if overrides.is_empty() {
+        return Ok(());
+    }
+    let content = serde_yaml::to_string(overrides).map_err(|e| {
+        vec![
+            DiagnosticMessageBuilder::error("Invalid metadata override")
+                .problem(e.to_string())
+                .build(),
+        ]
+    })?;
+    let layer =
+        parse_metadata_layer("<command line>", content, source_context).map_err(|e| vec![e])?;
+    let merged = MergedConfig::new(vec![&*meta, &layer])
+        .materialize()
+        .map_err(|e| {
+            vec![
+                DiagnosticMessageBuilder::error("Metadata overrides could not be merged")
+                    .problem(e.to_string())
+                    .build(),
+            ]
+        })?;
+    *meta = merged;
+    Ok(())
+}
+
+/// Read one metadata file as a config layer.
+fn load_metadata_file(
+    file: &Path,
+    listed_at: Option<&SourceInfo>,
+    runtime: &dyn SystemRuntime,
+    source_context: &mut SourceContext,
+) -> Result<ConfigValue, DiagnosticMessage> {
+    let content = runtime.file_read_string(file).map_err(|e| {
+        let mut builder = DiagnosticMessageBuilder::error("Metadata file not readable")
+            .problem(format!("Could not read `{}`: {}", file.display(), e))
+            .add_hint("Paths in `metadata-files` are relative to the file that lists them");
+        if let Some(location) = listed_at {
+            builder = builder.with_location(location.clone());
+        }
+        builder.build()
+    })?;
+    parse_metadata_layer(&file.to_string_lossy(), content, source_context)
+}
+
+/// Parse YAML metadata registered in `source_context` under `name` as a
+/// config layer.
+fn parse_metadata_layer(
+    name: &str,
+    content: String,
+    source_context: &mut SourceContext,
+) -> Result<ConfigValue, DiagnosticMessage> {
+    let len = content.len();
+    let file_id = source_context.add_file(name.to_string(), Some(content.clone()));
+    let parent = SourceInfo::original(file_id, 0, len);
+
+    let yaml = quarto_yaml::parse_with_parent(&content, parent.clone()).map_err(|e| {
+        DiagnosticMessageBuilder::error("Invalid metadata file")
+            .with_location(parent.clone())
+            .problem(format!("`{}` is not valid YAML: {}", name, e))
+            .build()
+    })?;
+    // An empty file adds nothing
+    if yaml.yaml.is_null() {
+        return Ok(ConfigValue::default());
+    }
+
+    let mut collector = DiagnosticCollector::new();
+    let layer = yaml_to_config_value(
+        yaml,
+        InterpretationContext::DocumentMetadata,
+        &mut collector,
+    );
+    if !layer.is_map() {
+        return Err(DiagnosticMessageBuilder::error("Invalid metadata file")
+            .with_location(parent)
+            .problem(format!("`{}` must contain a YAML mapping", name))
+            .build());
+    }
+    Ok(layer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_system_runtime::NativeRuntime;
+
+    fn project(dir: &Path, config: Option<serde_json::Value>) -> ProjectContext {
+        ProjectContext {
+            dir: dir.to_path_buf(),
+            config: config.map(|raw| crate::project::ProjectConfig {
+                raw,
+                ..Default::default()
+            }),
+            is_single_file: false,
+            files: vec![],
+            output_dir: dir.to_path_buf(),
+        }
+    }
+
+    fn parse(source: &str) -> ConfigValue {
+        let (pandoc, _context, _warnings) = pampa::readers::qmd::read(
+            source.as_bytes(),
+            false,
+            "doc.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .unwrap();
+        pandoc.meta
+    }
+
+    fn text(meta: &ConfigValue, key: &str) -> Option<String> {
+        meta.get(key).and_then(ConfigValue::as_plain_text)
+    }
+
+    #[test]
+    fn test_metadata_file_precedence() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::create_dir(dir.join("posts")).unwrap();
+        std::fs::write(
+            dir.join("_common.yml"),
+            "author: Project\nlicense: CC-BY\nsubtitle: Common\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("posts/_post.yml"),
+            "author: Post\nsubtitle: Post\n",
+        )
+        .unwrap();
+        let project = project(
+            dir,
+            Some(serde_json::json!({ "metadata-files": ["_common.yml"] })),
+        );
+
+        let mut meta =
+            parse("---\ntitle: Hello\nsubtitle: Own\nmetadata-files: [_post.yml]\n---\n");
+        let mut source_context = SourceContext::new();
+        apply_metadata_files(
+            &mut meta,
+            &dir.join("posts/doc.qmd"),
+            &project,
+            &NativeRuntime::new(),
+            &mut source_context,
+        )
+        .unwrap();
+
+        assert_eq!(text(&meta, "title").as_deref(), Some("Hello"));
+        assert_eq!(text(&meta, "subtitle").as_deref(), Some("Own"));
+        assert_eq!(text(&meta, "author").as_deref(), Some("Post"));
+        assert_eq!(text(&meta, "license").as_deref(), Some("CC-BY"));
+
+        // Values keep the location of the file that supplied them
+        let license = meta.get("license").unwrap();
+        let mapped = license.source_info.map_offset(0, &source_context).unwrap();
+        let file = source_context.get_file(mapped.file_id).unwrap();
+        assert!(file.path.ends_with("_common.yml"));
+        assert_eq!(mapped.location.row, 1);
+    }
+
+    #[test]
+    fn test_metadata_overrides_take_precedence() {
+        let mut meta = parse("---\ntitle: Own\nauthor: Me\n---\n");
+        let mut overrides = serde_json::Map::new();
+        overrides.insert("title".into(), serde_json::json!("From CLI"));
+        overrides.insert("toc".into(), serde_json::json!(true));
+        let mut source_context = SourceContext::new();
+        apply_metadata_overrides(&mut meta, &overrides, &mut source_context).unwrap();
+
+        assert_eq!(text(&meta, "title").as_deref(), Some("From CLI"));
+        assert_eq!(text(&meta, "author").as_deref(), Some("Me"));
+        assert_eq!(meta.get("toc").and_then(ConfigValue::as_bool), Some(true));
+    }
+
+    #[test]
+    fn test_missing_metadata_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut meta = parse("---\nmetadata-files: missing.yml\n---\n");
+        let errors = apply_metadata_files(
+            &mut meta,
+            &temp.path().join("doc.qmd"),
+            &project(temp.path(), None),
+            &NativeRuntime::new(),
+            &mut SourceContext::new(),
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].title, "Metadata file not readable");
+        assert!(errors[0].location.is_some());
+    }
+
+    #[test]
+    fn test_metadata_file_must_be_a_mapping() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("list.yml"), "- a\n- b\n").unwrap();
+        let mut meta = parse("---\nmetadata-files: [list.yml]\n---\n");
+        let errors = apply_metadata_files(
+            &mut meta,
+            &temp.path().join("doc.qmd"),
+            &project(temp.path(), None),
+            &NativeRuntime::new(),
+            &mut SourceContext::new(),
+        )
+        .unwrap_err();
+        assert_eq!(errors[0].title, "Invalid metadata file");
+    }
+}
@@ -0,0 +1,703 @@
+/*
+ * notebook.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Jupyter notebooks and their conversion to and from qmd.
+ */
+
+//! Jupyter notebooks and their conversion to and from qmd.
+//!
+//! [`Notebook`] reads and writes `.ipynb` files (nbformat 4), keeping cell
+//! metadata and outputs as JSON. The conversions follow Quarto's notebook
+//! conventions:
+//!
+//! - YAML front matter is a raw cell at the top of the notebook. A notebook
+//!   without one gets `jupyter: <kernel>` front matter in qmd, and front
+//!   matter holding only that key becomes the notebook's kernelspec.
+//! - Markdown cells are written as they are; consecutive markdown in a qmd
+//!   becomes a single cell.
+//! - Code cells become executable chunks (```` ```{python} ````). Cell
+//!   options stay in the source as `#|` comments; cell tags are written as a
+//!   `#| tags: [...]` option.
+//! - Other raw cells become raw blocks (```` ```{=html} ````), with the format
+//!   taken from the cell's `raw_mimetype` (`ipynb` when it has none).
+//!
+//! qmd has no place for outputs, so converting to qmd drops them; see
+//! [`Notebook::copy_outputs_from`] to carry them over when a qmd is
+//! converted back over an existing notebook.
+
+use std::collections::{HashMap, VecDeque};
+
+use serde_json::{Map, Value, json};
+
+use crate::error::{QuartoError, Result};
+
+/// nbformat version written.
+const NBFORMAT: u64 = 4;
+
+/// nbformat minor version written (cell ids are required from 4.5).
+const NBFORMAT_MINOR: u64 = 5;
+
+/// Raw cell formats and their MIME types.
+const RAW_FORMATS: &[(&str, &str)] = &[
+    ("html", "text/html"),
+    ("latex", "text/latex"),
+    ("markdown", "text/markdown"),
+    ("rst", "text/restructuredtext"),
+    ("asciidoc", "text/asciidoc"),
+    ("python", "text/x-python"),
+];
+
+/// Raw format of raw cells without a MIME type.
+const IPYNB_FORMAT: &str = "ipynb";
+
+/// Kind of a notebook cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellType {
+    Markdown,
+    Code,
+    Raw,
+}
+
+impl CellType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Code => "code",
+            Self::Raw => "raw",
+        }
+    }
+}
+
+/// A notebook cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    pub cell_type: CellType,
+    pub id: Option<String>,
+    pub source: String,
+    pub metadata: Map<String, Value>,
+    /// Outputs of a code cell, as nbformat JSON
+    pub outputs: Vec<Value>,
+    pub execution_count: Option<u64>,
+}
+
+impl Cell {
+    fn new(cell_type: CellType, source: impl Into<String>) -> Self {
+        Self {
+            cell_type,
+            id: None,
+            source: source.into(),
+            metadata: Map::new(),
+            outputs: Vec::new(),
+            execution_count: None,
+        }
+    }
+
+    /// Tags of the cell (`metadata.tags`).
+    pub fn tags(&self) -> Vec<&str> {
+        self.metadata
+            .get("tags")
+            .and_then(Value::as_array)
+            .map(|tags| tags.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// A Jupyter notebook.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notebook {
+    pub metadata: Map<String, Value>,
+    pub cells: Vec<Cell>,
+}
+
+impl Notebook {
+    /// Parse an `.ipynb` file.
+    pub fn parse(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| QuartoError::other(format!("Invalid notebook: {}", e)))?;
+        let nbformat = value.get("nbformat").and_then(Value::as_u64);
+        if nbformat != Some(NBFORMAT) {
+            return Err(QuartoError::other(format!(
+                "Unsupported notebook format {} (expected nbformat 4)",
+                nbformat.map_or("unknown".to_string(), |v| v.to_string())
+            )));
+        }
+        let cells = value
+            .get("cells")
+            .and_then(Value::as_array)
+            .ok_or_else(|| QuartoError::other("Invalid notebook: missing cells"))?
+            .iter()
+            .map(parse_cell)
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            metadata: object(value.get("metadata")),
+            cells,
+        })
+    }
+
+    /// Serialize as an `.ipynb` file (nbformat 4.5, one-space indent as
+    /// Jupyter writes it).
+    pub fn to_json(&self) -> String {
+        let cells: Vec<Value> = self
+            .cells
+            .iter()
+            .enumerate()
+            .map(|(index, cell)| cell_to_json(cell, index))
+            .collect();
+        let notebook = json!({
+            "cells": cells,
+            "metadata": self.metadata,
+            "nbformat": NBFORMAT,
+            "nbformat_minor": NBFORMAT_MINOR,
+        });
+
+        let mut out = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+        let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+        serde::Serialize::serialize(&notebook, &mut serializer)
+            .expect("serializing JSON values cannot fail");
+        let mut json = String::from_utf8(out).expect("serde_json writes UTF-8");
+        json.push('\n');
+        json
+    }
+
+    /// Name of the notebook's kernel (`metadata.kernelspec.name`).
+    pub fn kernel_name(&self) -> Option<&str> {
+        self.metadata.get("kernelspec")?.get("name")?.as_str()
+    }
+
+    /// Language of the code cells.
+    pub fn language(&self) -> Option<&str> {
+        self.metadata
+            .get("kernelspec")
+            .and_then(|k| k.get("language"))
+            .or_else(|| self.metadata.get("language_info")?.get("name"))
+            .and_then(Value::as_str)
+    }
+
+    /// Remove all outputs and execution counts.
+    pub fn strip_outputs(&mut self) {
+        for cell in &mut self.cells {
+            cell.outputs.clear();
+            cell.execution_count = None;
+        }
+    }
+
+    /// Take outputs, execution counts and ids from the code cells of
+    /// `previous` whose source is unchanged, in order.
+    pub fn copy_outputs_from(&mut self, previous: &Notebook) {
+        let mut by_source: HashMap<&str, VecDeque<&Cell>> = HashMap::new();
+        for cell in previous
+            .cells
+            .iter()
+            .filter(|c| c.cell_type == CellType::Code)
+        {
+            by_source
+                .entry(cell.source.as_str())
+                .or_default()
+                .push_back(cell);
+        }
+        for cell in self
+            .cells
+            .iter_mut()
+            .filter(|c| c.cell_type == CellType::Code)
+        {
+            if let Some(old) = by_source
+                .get_mut(cell.source.as_str())
+                .and_then(VecDeque::pop_front)
+            {
+                cell.outputs = old.outputs.clone();
+                cell.execution_count = old.execution_count;
+                cell.id = old.id.clone();
+            }
+        }
+    }
+}
+
+fn parse_cell(value: &Value) -> Result<Cell> {
+    let cell_type = match value.get("cell_type").and_then(Value::as_str) {
+        Some("markdown") => CellType::Markdown,
+        Some("code") => CellType::Code,
+        Some("raw") => CellType::Raw,
+        other => {
+            return Err(QuartoError::other(format!(
+                "Invalid notebook: unknown cell type {:?}",
+                other.unwrap_or("(none)")
+            )));
+        }
+    };
+    // nbformat allows the source as one string or as a list of lines
+    let source = match value.get("source") {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    };
+    Ok(Cell {
+        cell_type,
+        id: value.get("id").and_then(Value::as_str).map(String::from),
+        source,
+        metadata: object(value.get("metadata")),
+        outputs: value
+            .get("outputs")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default(),
+        execution_count: value.get("execution_count").and_then(Value::as_u64),
+    })
+}
+
+fn cell_to_json(cell: &Cell, index: usize) -> Value {
+    let mut out = Map::new();
+    out.insert("cell_type".into(), cell.cell_type.as_str().into());
+    if cell.cell_type == CellType::Code {
+        out.insert("execution_count".into(), cell.execution_count.into());
+    }
+    let id = cell.id.clone().unwrap_or_else(|| format!("cell-{}", index));
+    out.insert("id".into(), id.into());
+    out.insert("metadata".into(), Value::Object(cell.metadata.clone()));
+    if cell.cell_type == CellType::Code {
+        out.insert("outputs".into(), Value::Array(cell.outputs.clone()));
+    }
+    let lines: Vec<Value> = cell
+        .source
+        .split_inclusive('\n')
+        .map(|line| line.into())
+        .collect();
+    out.insert("source".into(), Value::Array(lines));
+    Value::Object(out)
+}
+
+fn object(value: Option<&Value>) -> Map<String, Value> {
+    value
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Notebook to qmd
+// ============================================================================
+
+/// Convert a notebook to qmd. Outputs are dropped.
+pub fn notebook_to_qmd(notebook: &Notebook) -> String {
+    let language = notebook.language().unwrap_or("python").to_lowercase();
+    let mut parts = Vec::new();
+    let mut cells = notebook.cells.as_slice();
+
+    match cells.split_first() {
+        Some((first, rest))
+            if first.cell_type == CellType::Raw && is_front_matter(&first.source) =>
+        {
+            parts.push(first.source.trim_end().to_string());
+            cells = rest;
+        }
+        _ => {
+            if let Some(kernel) = notebook.kernel_name() {
+                parts.push(format!("---\njupyter: {}\n---", kernel));
+            }
+        }
+    }
+
+    for cell in cells {
+        let source = cell.source.trim_end_matches('\n');
+        match cell.cell_type {
+            CellType::Markdown => {
+                if !source.trim().is_empty() {
+                    parts.push(source.to_string());
+                }
+            }
+            CellType::Code => {
+                let mut body = String::new();
+                let tags = cell.tags();
+                if !tags.is_empty() && !has_option(source, "tags") {
+                    body.push_str(&format!("#| tags: [{}]\n", tags.join(", ")));
+                }
+                body.push_str(source);
+                parts.push(fenced(&format!("{{{}}}", language), &body));
+            }
+            CellType::Raw => {
+                let format = raw_format(&cell.metadata);
+                parts.push(fenced(&format!("{{={}}}", format), source));
+            }
+        }
+    }
+
+    let mut qmd = parts.join("\n\n");
+    qmd.push('\n');
+    qmd
+}
+
+/// A fenced block, with a fence longer than any backtick run in `body`.
+fn fenced(info: &str, body: &str) -> String {
+    let longest = body
+        .lines()
+        .map(|line| line.trim_start().chars().take_while(|&c| c == '`').count())
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    if body.is_empty() {
+        format!("{}{}\n{}", fence, info, fence)
+    } else {
+        format!("{}{}\n{}\n{}", fence, info, body, fence)
+    }
+}
+
+fn is_front_matter(source: &str) -> bool {
+    let mut lines = source.trim().lines();
+    lines.next() == Some("---") && lines.any(|line| line == "---" || line == "...")
+}
+
+/// Whether `source` sets a `#|` cell option.
+fn has_option(source: &str, name: &str) -> bool {
+    source
+        .lines()
+        .any(|line| option_value(line, name).is_some())
+}
+
+/// The value of a `#| name: value` line.
+fn option_value<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix("#|")?.trim_start();
+    let value = rest.strip_prefix(name)?.trim_start().strip_prefix(':')?;
+    Some(value.trim())
+}
+
+/// Format of a raw cell, from its MIME type.
+fn raw_format(metadata: &Map<String, Value>) -> String {
+    let mime = metadata
+        .get("raw_mimetype")
+        .or_else(|| metadata.get("format"))
+        .and_then(Value::as_str);
+    match mime {
+        Some(mime) => RAW_FORMATS
+            .iter()
+            .find(|(_, m)| *m == mime)
+            .map_or_else(|| mime.to_string(), |(format, _)| format.to_string()),
+        None => IPYNB_FORMAT.to_string(),
+    }
+}
+
+// ============================================================================
+// qmd to notebook
+// ============================================================================
+
+/// Convert a qmd document to a notebook (without outputs).
+pub fn qmd_to_notebook(qmd: &str) -> Notebook {
+    let (front_matter, body) = split_front_matter(qmd);
+    let mut cells = Vec::new();
+    let mut kernel = None;
+
+    if let Some(front_matter) = front_matter {
+        kernel = front_matter_kernel(front_matter);
+        if !is_only_kernel(front_matter) {
+            cells.push(Cell::new(CellType::Raw, front_matter.trim_end()));
+        }
+    }
+
+    let mut markdown: Vec<&str> = Vec::new();
+    let mut lines = body.lines();
+    while let Some(line) = lines.next() {
+        let Some((fence, info)) = open_fence(line) else {
+            markdown.push(line);
+            continue;
+        };
+        let mut content = Vec::new();
+        let mut closing = None;
+        for line in lines.by_ref() {
+            if is_closing_fence(line, fence) {
+                closing = Some(line);
+                break;
+            }
+            content.push(line);
+        }
+
+        match chunk_kind(info) {
+            Some(Chunk::Code(language)) => {
+                flush_markdown(&mut markdown, &mut cells);
+                kernel.get_or_insert_with(|| kernel_for_language(language));
+                cells.push(code_cell(&content));
+            }
+            Some(Chunk::Raw(format)) => {
+                flush_markdown(&mut markdown, &mut cells);
+                let mut cell = Cell::new(CellType::Raw, content.join("\n"));
+                if format != IPYNB_FORMAT {
+                    let mime = RAW_FORMATS
+                        .iter()
+                        .find(|(f, _)| *f == format)
+                        .map_or(format, |(_, mime)| mime);
+                    cell.metadata.insert("raw_mimetype".into(), mime.into());
+                }
+                cells.push(cell);
+            }
+            None => {
+                // Plain code block: part of the markdown
+                markdown.push(line);
+                markdown.extend(content);
+                markdown.extend(closing);
+            }
+        }
+    }
+    flush_markdown(&mut markdown, &mut cells);
+
+    let mut metadata = Map::new();
+    if let Some(kernel) = kernel {
+        metadata.insert("kernelspec".into(), kernel);
+    }
+    Notebook { metadata, cells }
+}
+
+enum Chunk<'a> {
+    Code(&'a str),
+    Raw(&'a str),
+}
+
+/// What a fence's info string opens: an executable chunk (`{python}`), a raw
+/// block (`{=html}`) or, for anything else, a plain code block.
+fn chunk_kind(info: &str) -> Option<Chunk<'_>> {
+    let inner = info.trim().strip_prefix('{')?.strip_suffix('}')?.trim();
+    if let Some(format) = inner.strip_prefix('=') {
+        return Some(Chunk::Raw(format.trim()));
+    }
+    let language = inner
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .next()
+        .filter(|l| !l.is_empty() && l.chars().all(|c| c.is_alphanumeric() || c == '_'))?;
+    Some(Chunk::Code(language))
+}
+
+/// The fence and info string of a line opening a fenced block.
+fn open_fence(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let marker = trimmed.chars().next().filter(|&c| c == '`' || c == '~')?;
+    let len = trimmed.chars().take_while(|&c| c == marker).count();
+    if len < 3 {
+        return None;
+    }
+    Some(trimmed.split_at(len))
+}
+
+fn is_closing_fence(line: &str, fence: &str) -> bool {
+    let trimmed = line.trim();
+    let marker = fence.as_bytes()[0] as char;
+    trimmed.len() >= fence.len() && trimmed.chars().all(|c| c == marker)
+}
+
+fn code_cell(content: &[&str]) -> Cell {
+    let mut cell = Cell::new(CellType::Code, "");
+    let mut source = Vec::new();
+    for line in content {
+        let tags = option_value(line, "tags")
+            .and_then(|value| serde_yaml::from_str::<Vec<String>>(value).ok());
+        match tags {
+            Some(tags) if !cell.metadata.contains_key("tags") => {
+                cell.metadata.insert("tags".into(), tags.into());
+            }
+            _ => source.push(*line),
+        }
+    }
+    cell.source = source.join("\n");
+    cell
+}
+
+fn flush_markdown(markdown: &mut Vec<&str>, cells: &mut Vec<Cell>) {
+    let text = markdown.join("\n");
+    markdown.clear();
+    let text = text.trim_matches('\n');
+    if !text.trim().is_empty() {
+        cells.push(Cell::new(CellType::Markdown, text));
+    }
+}
+
+/// Split off YAML front matter (with its delimiters).
+fn split_front_matter(qmd: &str) -> (Option<&str>, &str) {
+    let Some(rest) = qmd.strip_prefix("---\n") else {
+        return (None, qmd);
+    };
+    let mut offset = 4;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let line = line.trim_end();
+        if line == "---" || line == "..." {
+            return (Some(&qmd[..offset]), &qmd[offset..]);
+        }
+    }
+    (None, qmd)
+}
+
+fn parse_front_matter(front_matter: &str) -> Option<serde_yaml::Mapping> {
+    let yaml = front_matter.trim().strip_prefix("---")?;
+    let yaml = yaml
+        .trim_end()
+        .strip_suffix("---")
+        .or_else(|| yaml.trim_end().strip_suffix("..."))?;
+    serde_yaml::from_str(yaml).ok()
+}
+
+/// Kernelspec from the `jupyter:` front matter key.
+fn front_matter_kernel(front_matter: &str) -> Option<Value> {
+    let mapping = parse_front_matter(front_matter)?;
+    match mapping.get("jupyter")? {
+        serde_yaml::Value::String(name) => Some(kernelspec(name)),
+        value @ serde_yaml::Value::Mapping(_) => {
+            serde_json::to_value(value.get("kernelspec")?).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Whether the front matter holds nothing but `jupyter: <kernel>`.
+fn is_only_kernel(front_matter: &str) -> bool {
+    parse_front_matter(front_matter).is_some_and(|mapping| {
+        mapping.len() == 1 && matches!(mapping.get("jupyter"), Some(serde_yaml::Value::String(_)))
+    })
+}
+
+fn kernel_for_language(language: &str) -> Value {
+    let name = match language.to_lowercase().as_str() {
+        "python" | "python3" | "py" => "python3",
+        "r" => "ir",
+        "julia" | "jl" => "julia",
+        "typescript" | "ts" | "javascript" | "js" => "deno",
+        _ => language,
+    };
+    kernelspec(name)
+}
+
+/// Kernelspec metadata of a well-known kernel.
+fn kernelspec(name: &str) -> Value {
+    let (display_name, language) = match name {
+        "python3" => ("Python 3", "python"),
+        "ir" => ("R", "R"),
+        "deno" => ("Deno", "typescript"),
+        n if n.starts_with("julia") => ("Julia", "julia"),
+        n => (n, n),
+    };
+    json!({ "display_name": display_name, "language": language, "name": name })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A notebook exercising front matter, options, tags, raw cells and outputs.
+    const NOTEBOOK: &str = r###"{
+ "cells": [
+  { "cell_type": "raw", "id": "fm", "metadata": {}, "source": ["---\n", "title: Analysis\n", "---"] },
+  { "cell_type": "markdown", "id": "intro", "metadata": {}, "source": "## Setup\n\nSome *text*." },
+  { "cell_type": "code", "execution_count": 1, "id": "params", "metadata": { "tags": ["parameters"] },
+    "outputs": [], "source": ["alpha = 0.1"] },
+  { "cell_type": "code", "execution_count": 2, "id": "plot", "metadata": {},
+    "outputs": [ { "name": "stdout", "output_type": "stream", "text": ["0.1\n"] } ],
+    "source": ["#| label: fig-plot\n", "#| echo: false\n", "print(alpha)"] },
+  { "cell_type": "raw", "id": "raw", "metadata": { "raw_mimetype": "text/html" }, "source": "<hr>" },
+  { "cell_type": "markdown", "id": "end", "metadata": {}, "source": "Done:\n\n```python\nnot = 'a chunk'\n```" }
+ ],
+ "metadata": { "kernelspec": { "display_name": "Python 3", "language": "python", "name": "python3" } },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"###;
+
+    const QMD: &str = r###"---
+title: Analysis
+---
+
+## Setup
+
+Some *text*.
+
+```{python}
+#| tags: [parameters]
+alpha = 0.1
+```
+
+```{python}
+#| label: fig-plot
+#| echo: false
+print(alpha)
+```
+
+```{=html}
+<hr>
+```
+
+Done:
+
+```python
+not = 'a chunk'
+```
+"###;
+
+    fn cell_summary(notebook: &Notebook) -> Vec<(CellType, String, Map<String, Value>)> {
+        notebook
+            .cells
+            .iter()
+            .map(|c| (c.cell_type, c.source.clone(), c.metadata.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_notebook_to_qmd() {
+        let notebook = Notebook::parse(NOTEBOOK).unwrap();
+        assert_eq!(notebook_to_qmd(&notebook), QMD);
+    }
+
+    #[test]
+    fn test_notebook_round_trip() {
+        let notebook = Notebook::parse(NOTEBOOK).unwrap();
+        let converted = qmd_to_notebook(&notebook_to_qmd(&notebook));
+        assert_eq!(cell_summary(&converted), cell_summary(&notebook));
+        assert_eq!(converted.kernel_name(), Some("python3"));
+        assert!(converted.cells.iter().all(|c| c.outputs.is_empty()));
+
+        // Outputs come back from the original notebook
+        let mut converted = converted;
+        converted.copy_outputs_from(&notebook);
+        assert_eq!(converted.cells[3].outputs, notebook.cells[3].outputs);
+        assert_eq!(converted.cells[3].execution_count, Some(2));
+        assert_eq!(converted.cells[3].id.as_deref(), Some("plot"));
+
+        converted.strip_outputs();
+        assert!(converted.cells[3].outputs.is_empty());
+    }
+
+    #[test]
+    fn test_qmd_round_trip() {
+        let notebook = qmd_to_notebook(QMD);
+        let json = notebook.to_json();
+        let reparsed = Notebook::parse(&json).unwrap();
+        assert_eq!(notebook_to_qmd(&reparsed), QMD);
+        assert!(json.contains("\"nbformat_minor\": 5"));
+        assert!(json.contains("\"id\": \"cell-0\""));
+    }
+
+    #[test]
+    fn test_kernel_front_matter() {
+        // Only the kernel: no front matter cell
+        let qmd = "---\njupyter: ir\n---\n\n```{r}\nx <- 1\n```\n";
+        let notebook = qmd_to_notebook(qmd);
+        assert_eq!(notebook.kernel_name(), Some("ir"));
+        assert_eq!(notebook.cells.len(), 1);
+        assert_eq!(notebook_to_qmd(&notebook), qmd);
+
+        // No kernel given: inferred from the first chunk
+        let notebook = qmd_to_notebook("```{julia}\n1 + 1\n```\n");
+        assert_eq!(notebook.kernel_name(), Some("julia"));
+        assert_eq!(notebook.language(), Some("julia"));
+    }
+
+    #[test]
+    fn test_longer_fences() {
+        let mut notebook = qmd_to_notebook("````{python}\ns = '''\n```\n'''\n````\n");
+        assert_eq!(notebook.cells[0].source, "s = '''\n```\n'''");
+        notebook.cells[0].source = "x = '```'\n```".to_string();
+        assert!(notebook_to_qmd(&notebook).contains("````{python}\nx = '```'\n```\n````"));
+    }
+
+    #[test]
+    fn test_parse_rejects_old_nbformat() {
+        assert!(Notebook::parse(r#"{"nbformat": 3, "worksheets": []}"#).is_err());
+        assert!(Notebook::parse("not json").is_err());
+    }
+}
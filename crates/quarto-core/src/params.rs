@@ -0,0 +1,438 @@
+/*
+ * params.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Document parameters for parameterized reports.
+ */
+
+//! Document parameters for parameterized reports.
+//!
+//! A document declares its parameters under `params:` in the front matter,
+//! either with a default value or in the extended form:
+//!
+//! ```yaml
+//! params:
+//!   alpha: 0.1
+//!   region:
+//!     value: east
+//!     choices: [east, west]
+//!   ids:
+//!     value: null
+//!     type: list
+//! ```
+//!
+//! Values given with `-P KEY:VALUE` or `--execute-params` are checked
+//! against these declarations by [`resolve_params`]: a value of another type
+//! is coerced when that is unambiguous (with a warning, since the result may
+//! not be what was meant) and rejected otherwise. The declared defaults,
+//! overridden by the given values, are what the engine injects.
+//!
+//! Documents that declare no parameters (e.g. Jupyter documents with a
+//! `parameters` cell) take the given values unchecked.
+
+use quarto_error_reporting::{DiagnosticKind, DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_pandoc_types::ConfigValue;
+use quarto_source_map::SourceInfo;
+use serde_json::{Map, Value};
+
+/// The type of a declared parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamType {
+    Boolean,
+    Integer,
+    Number,
+    String,
+    List,
+    Map,
+    /// No type constraint (declared with a `null` default and no `type`)
+    Any,
+}
+
+impl ParamType {
+    /// Parse a `type:` declaration.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "boolean" | "bool" | "logical" => Some(Self::Boolean),
+            "integer" | "int" => Some(Self::Integer),
+            "number" | "numeric" | "float" => Some(Self::Number),
+            "string" | "character" => Some(Self::String),
+            "list" | "array" => Some(Self::List),
+            "map" | "object" => Some(Self::Map),
+            _ => None,
+        }
+    }
+
+    /// The type of a default value.
+    pub fn of(value: &Value) -> Self {
+        match value {
+            Value::Bool(_) => Self::Boolean,
+            Value::Number(n) if n.is_i64() || n.is_u64() => Self::Integer,
+            Value::Number(_) => Self::Number,
+            Value::String(_) => Self::String,
+            Value::Array(_) => Self::List,
+            Value::Object(_) => Self::Map,
+            Value::Null => Self::Any,
+        }
+    }
+
+    /// The name used in diagnostics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::List => "list",
+            Self::Map => "map",
+            Self::Any => "any",
+        }
+    }
+}
+
+/// A parameter declared in the front matter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamSpec {
+    /// Parameter name
+    pub name: String,
+    /// Default value
+    pub value: Value,
+    /// Expected type
+    pub param_type: ParamType,
+    /// Allowed values, if restricted
+    pub choices: Option<Vec<Value>>,
+    /// Location of the declaration
+    pub source_info: SourceInfo,
+}
+
+/// Read the parameter declarations from `params:` in `meta`.
+///
+/// Unknown `type:` names are reported as warnings and leave the parameter
+/// unconstrained.
+pub fn declared_params(meta: &ConfigValue) -> (Vec<ParamSpec>, Vec<DiagnosticMessage>) {
+    let mut specs = Vec::new();
+    let mut diagnostics = Vec::new();
+    let Some(entries) = meta.get("params").and_then(ConfigValue::as_map_entries) else {
+        return (specs, diagnostics);
+    };
+
+    for entry in entries {
+        let declaration = &entry.value;
+        // The extended form is a map with a `value` key
+        let extended = declaration.is_map() && declaration.contains_key("value");
+        let value = if extended {
+            declaration
+                .get("value")
+                .map_or(Value::Null, ConfigValue::to_json)
+        } else {
+            declaration.to_json()
+        };
+
+        let mut param_type = ParamType::of(&value);
+        let mut choices = None;
+        if extended {
+            if let Some(type_name) = declaration.get("type").and_then(ConfigValue::as_plain_text) {
+                match ParamType::parse(&type_name) {
+                    Some(declared) => param_type = declared,
+                    None => diagnostics.push(
+                        DiagnosticMessageBuilder::warning("Unknown parameter type")
+                            .problem(format!(
+                                "Parameter `{}` has unknown type `{}`",
+                                entry.key, type_name
+                            ))
+                            .add_hint("Use one of: boolean, integer, number, string, list, map")
+                            .with_location(declaration.source_info.clone())
+                            .build(),
+                    ),
+                }
+            }
+            choices = declaration
+                .get("choices")
+                .and_then(ConfigValue::as_array)
+                .map(|items| items.iter().map(ConfigValue::to_json).collect());
+        }
+        // An integer default doesn't rule out fractional values
+        if param_type == ParamType::Integer && !extended {
+            param_type = ParamType::Number;
+        }
+
+        specs.push(ParamSpec {
+            name: entry.key.clone(),
+            value,
+            param_type,
+            choices,
+            source_info: entry.key_source.clone(),
+        });
+    }
+    (specs, diagnostics)
+}
+
+/// Check the given parameter values against the declarations in `meta` and
+/// merge them over the declared defaults.
+///
+/// Returns the parameters to inject with the warnings raised, or every
+/// diagnostic if a value could not be used.
+pub fn resolve_params(
+    meta: &ConfigValue,
+    supplied: &Map<String, Value>,
+) -> Result<(Map<String, Value>, Vec<DiagnosticMessage>), Vec<DiagnosticMessage>> {
+    let (specs, mut diagnostics) = declared_params(meta);
+    if specs.is_empty() {
+        return Ok((supplied.clone(), diagnostics));
+    }
+
+    let mut params: Map<String, Value> = specs
+        .iter()
+        .map(|spec| (spec.name.clone(), spec.value.clone()))
+        .collect();
+
+    for (name, value) in supplied {
+        let Some(spec) = specs.iter().find(|spec| &spec.name == name) else {
+            diagnostics.push(
+                DiagnosticMessageBuilder::warning("Unknown parameter")
+                    .problem(format!(
+                        "Parameter `{}` is not declared in the document's `params`",
+                        name
+                    ))
+                    .add_hint(format!(
+                        "Declared parameters: {}",
+                        specs
+                            .iter()
+                            .map(|spec| spec.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                    .build(),
+            );
+            params.insert(name.clone(), value.clone());
+            continue;
+        };
+
+        match check_param(spec, value) {
+            Ok((value, warning)) => {
+                diagnostics.extend(warning);
+                params.insert(name.clone(), value);
+            }
+            Err(error) => diagnostics.push(error),
+        }
+    }
+
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.kind == DiagnosticKind::Error)
+    {
+        return Err(diagnostics);
+    }
+    Ok((params, diagnostics))
+}
+
+/// Check a given value against its declaration, coercing it if needed.
+fn check_param(
+    spec: &ParamSpec,
+    value: &Value,
+) -> Result<(Value, Option<DiagnosticMessage>), DiagnosticMessage> {
+    let expected = spec.param_type;
+    let (value, coerced) = match coerce(expected, value) {
+        Some(coerced) => {
+            let changed = &coerced != value;
+            (coerced, changed)
+        }
+        None => {
+            return Err(DiagnosticMessageBuilder::error("Invalid parameter value")
+                .problem(format!(
+                    "Parameter `{}` must be a {}, but was given `{}`",
+                    spec.name,
+                    expected.as_str(),
+                    value
+                ))
+                .with_location(spec.source_info.clone())
+                .build());
+        }
+    };
+
+    if let Some(choices) = &spec.choices
+        && !choices.contains(&value)
+    {
+        let choices: Vec<String> = choices.iter().map(Value::to_string).collect();
+        return Err(DiagnosticMessageBuilder::error("Invalid parameter value")
+            .problem(format!(
+                "Parameter `{}` must be one of {}, but was given `{}`",
+                spec.name,
+                choices.join(", "),
+                value
+            ))
+            .with_location(spec.source_info.clone())
+            .build());
+    }
+
+    let warning = coerced.then(|| {
+        let mut builder = DiagnosticMessageBuilder::warning("Parameter value converted")
+            .problem(format!(
+                "Parameter `{}` expects a {}; using `{}`",
+                spec.name,
+                expected.as_str(),
+                value
+            ))
+            .with_location(spec.source_info.clone());
+        if expected == ParamType::String {
+            builder = builder.add_hint(format!(
+                "Quote the value to pass it as written, e.g. `-P {}:'...'`",
+                spec.name
+            ));
+        }
+        builder.build()
+    });
+    Ok((value, warning))
+}
+
+/// Coerce `value` to `expected`, or `None` if it can't be.
+fn coerce(expected: ParamType, value: &Value) -> Option<Value> {
+    match (expected, value) {
+        (ParamType::Any, _) | (_, Value::Null) => Some(value.clone()),
+        (ParamType::Boolean, Value::Bool(_)) => Some(value.clone()),
+        (ParamType::Boolean, Value::String(s)) => match s.to_lowercase().as_str() {
+            "true" | "yes" => Some(Value::Bool(true)),
+            "false" | "no" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        (ParamType::Integer, Value::Number(n)) => {
+            if n.is_i64() || n.is_u64() {
+                Some(value.clone())
+            } else {
+                n.as_f64()
+                    .filter(|f| f.fract() == 0.0 && f.abs() < i64::MAX as f64)
+                    .map(|f| Value::from(f as i64))
+            }
+        }
+        (ParamType::Integer, Value::String(s)) => s.trim().parse::<i64>().ok().map(Value::from),
+        (ParamType::Number, Value::Number(_)) => Some(value.clone()),
+        (ParamType::Number, Value::String(s)) => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        (ParamType::String, Value::String(_)) => Some(value.clone()),
+        (ParamType::String, Value::Number(_) | Value::Bool(_)) => {
+            Some(Value::String(value.to_string()))
+        }
+        (ParamType::List, Value::Array(_)) => Some(value.clone()),
+        (ParamType::List, Value::Object(_)) => None,
+        (ParamType::List, _) => Some(Value::Array(vec![value.clone()])),
+        (ParamType::Map, Value::Object(_)) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn meta(front_matter: &str) -> ConfigValue {
+        let source = format!("---\n{}---\n\nText\n", front_matter);
+        let (pandoc, _context, _warnings) = pampa::readers::qmd::read(
+            source.as_bytes(),
+            false,
+            "doc.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .unwrap();
+        pandoc.meta
+    }
+
+    fn supplied(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn test_declared_params() {
+        let meta = meta(
+            "params:\n  alpha: 0.1\n  n: 10\n  label: Sales\n  region:\n    value: east\n    choices: [east, west]\n  ids:\n    value: null\n    type: list\n",
+        );
+        let (specs, diagnostics) = declared_params(&meta);
+        assert!(diagnostics.is_empty());
+        let types: Vec<_> = specs
+            .iter()
+            .map(|s| (s.name.as_str(), s.param_type))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                ("alpha", ParamType::Number),
+                ("n", ParamType::Number),
+                ("label", ParamType::String),
+                ("region", ParamType::String),
+                ("ids", ParamType::List),
+            ]
+        );
+        assert_eq!(specs[2].value, json!("Sales"));
+        assert_eq!(specs[3].choices, Some(vec![json!("east"), json!("west")]));
+    }
+
+    #[test]
+    fn test_defaults_are_overridden() {
+        let meta = meta("params:\n  alpha: 0.1\n  label: Sales\n");
+        let (params, diagnostics) =
+            resolve_params(&meta, &supplied(json!({ "alpha": 0.5 }))).unwrap();
+        assert!(diagnostics.is_empty());
+        assert_eq!(params.get("alpha"), Some(&json!(0.5)));
+        assert_eq!(params.get("label"), Some(&json!("Sales")));
+    }
+
+    #[test]
+    fn test_undeclared_params_pass_through() {
+        let (params, diagnostics) =
+            resolve_params(&meta("title: T\n"), &supplied(json!({ "x": 1 }))).unwrap();
+        assert!(diagnostics.is_empty());
+        assert_eq!(params.get("x"), Some(&json!(1)));
+
+        let (params, diagnostics) =
+            resolve_params(&meta("params:\n  a: 1\n"), &supplied(json!({ "x": 1 }))).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].title, "Unknown parameter");
+        assert_eq!(params.get("x"), Some(&json!(1)));
+    }
+
+    #[test]
+    fn test_coercion_warnings() {
+        let meta = meta(
+            "params:\n  id: \"007\"\n  ids:\n    value: []\n  n:\n    value: 1\n    type: integer\n",
+        );
+        let (params, diagnostics) =
+            resolve_params(&meta, &supplied(json!({ "id": 7, "ids": "a", "n": 3.0 }))).unwrap();
+        assert_eq!(params.get("id"), Some(&json!("7")));
+        assert_eq!(params.get("ids"), Some(&json!(["a"])));
+        assert_eq!(params.get("n"), Some(&json!(3)));
+        assert_eq!(diagnostics.len(), 3);
+        assert!(
+            diagnostics
+                .iter()
+                .all(|d| d.title == "Parameter value converted")
+        );
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let meta = meta(
+            "params:\n  n:\n    value: 1\n    type: integer\n  region:\n    value: east\n    choices: [east, west]\n  show: true\n",
+        );
+        let errors = resolve_params(
+            &meta,
+            &supplied(json!({ "n": 1.5, "region": "north", "show": "maybe" })),
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors.iter().all(|d| d.kind == DiagnosticKind::Error));
+        assert!(errors.iter().all(|d| d.location.is_some()));
+    }
+
+    #[test]
+    fn test_unknown_type() {
+        let meta = meta("params:\n  x:\n    value: 1\n    type: decimal\n");
+        let (specs, diagnostics) = declared_params(&meta);
+        assert_eq!(specs[0].param_type, ParamType::Integer);
+        assert_eq!(diagnostics[0].title, "Unknown parameter type");
+    }
+}
@@ -19,6 +19,22 @@
 //! 4. **Render body**: Pandoc AST → HTML body (via `pampa`)
 //! 5. **Apply template**: Wrap body with HTML template
 //!
+//! PDF output ([`render_qmd_to_pdf`]) shares the parse and execution stages,
+//! runs a smaller set of transforms, renders the AST to LaTeX (or Typst for
+//! `format: typst`) and compiles it with a LaTeX engine (or Typst).
+//!
+//! ## Multiple Formats
+//!
+//! Parsing and execution don't depend on the output format. To render one
+//! document to several formats, [`prepare_document`] runs them once and
+//! [`render_prepared_to_html`] / [`render_prepared_to_pdf`] render the
+//! result with each format's own stages and [`RenderContext`] (see
+//! [`RenderContext::for_format`]).
+//!
+//! Execution happens once, for the first format: engines are told that
+//! format (knitr, for one, picks its figure device from it), so with
+//! `--to html,pdf` the PDF uses the figures executed for HTML.
+//!
 //! ## Usage
 //!
 //! The main entry point is the async [`render_qmd_to_html`] function:
@@ -49,19 +65,28 @@ use std::sync::Arc;
 use quarto_doctemplate::Template;
 use quarto_error_reporting::DiagnosticMessage;
 use quarto_source_map::SourceContext;
+use tracing::Instrument;
 
 use crate::Result;
-use crate::render::RenderContext;
+use crate::format::FormatIdentifier;
+use crate::math::MathMethod;
+use crate::render::{BinaryDependencies, RenderContext};
 use crate::stage::stages::ApplyTemplateConfig;
 use crate::stage::{
-    ApplyTemplateStage, AstTransformsStage, EngineExecutionStage, LoadedSource, ParseDocumentStage,
-    Pipeline, PipelineData, PipelineStage, RenderHtmlBodyStage, StageContext,
+    ApplyTemplateStage, AstTransformsStage, CompileLatexStage, CompileTypstStage, DocumentAst,
+    EngineExecutionStage, LoadedSource, ParseDocumentStage, Pipeline, PipelineData, PipelineError,
+    PipelineStage, PostprocessHtmlStage, RenderHtmlBodyStage, RenderLatexStage, RenderTypstStage,
+    RenderedOutput, StageContext,
 };
 use crate::transform::TransformPipeline;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transforms::LuaFilterTransform;
 use crate::transforms::{
-    AppendixStructureTransform, CalloutResolveTransform, CalloutTransform, FootnotesTransform,
-    MetadataNormalizeTransform, ResourceCollectorTransform, SectionizeTransform,
-    ShortcodeResolveTransform, TitleBlockTransform, TocGenerateTransform, TocRenderTransform,
+    AppendixStructureTransform, CalloutResolveTransform, CalloutTransform, CrossrefTransform,
+    FootnotesTransform, HighlightStyleTransform, ListingTransform, MetadataNormalizeTransform,
+    NumberSectionsTransform, PanelTransform, ResourceCollectorTransform, RevealjsSlidesTransform,
+    SectionizeTransform, ShortcodeResolveTransform, SocialMetadataTransform, TitleBlockTransform,
+    TocGenerateTransform, TocRenderTransform, WebsiteNavigationTransform,
 };
 
 /// Well-known path for the default CSS artifact in WASM context.
@@ -70,6 +95,20 @@ use crate::transforms::{
 /// and the browser post-processor (to resolve the CSS reference).
 pub const DEFAULT_CSS_ARTIFACT_PATH: &str = "/.quarto/project-artifacts/styles.css";
 
+/// Well-known path for the syntax highlighting CSS artifact in WASM context.
+///
+/// The stylesheet is also inlined into the document by the HTML template, so
+/// consumers only need this path to serve it separately.
+pub const HIGHLIGHT_CSS_ARTIFACT_PATH: &str = "/.quarto/project-artifacts/highlight.css";
+
+/// Well-known path for the color scheme toggle script artifact in WASM
+/// context.
+///
+/// The script is also inlined into documents with dark mode, so consumers
+/// only need this path to serve it separately.
+pub const COLOR_SCHEME_TOGGLE_ARTIFACT_PATH: &str =
+    "/.quarto/project-artifacts/color-scheme-toggle.js";
+
 /// Configuration for HTML rendering.
 #[derive(Debug, Default)]
 pub struct HtmlRenderConfig<'a> {
@@ -77,8 +116,16 @@ pub struct HtmlRenderConfig<'a> {
     /// If empty, the default CSS artifact will be used.
     pub css_paths: &'a [String],
 
+    /// Dark color scheme CSS paths (relative to the output HTML). If
+    /// non-empty, the document gets a light/dark toggle.
+    pub dark_css_paths: &'a [String],
+
     /// Custom template to use. If `None`, the built-in HTML5 template is used.
     pub template: Option<&'a Template>,
+
+    /// How to render math. If `None`, the format's `html-math-method` is used
+    /// (MathJax by default).
+    pub math: Option<MathMethod>,
 }
 
 impl<'a> HtmlRenderConfig<'a> {
@@ -86,7 +133,9 @@ impl<'a> HtmlRenderConfig<'a> {
     pub fn with_css(css_paths: &'a [String]) -> Self {
         Self {
             css_paths,
+            dark_css_paths: &[],
             template: None,
+            math: None,
         }
     }
 
@@ -94,7 +143,9 @@ impl<'a> HtmlRenderConfig<'a> {
     pub fn with_template(template: &'a Template) -> Self {
         Self {
             css_paths: &[],
+            dark_css_paths: &[],
             template: Some(template),
+            math: None,
         }
     }
 }
@@ -110,6 +161,28 @@ pub struct RenderOutput {
     pub source_context: SourceContext,
 }
 
+/// Configuration for PDF rendering.
+#[derive(Debug, Default)]
+pub struct PdfRenderConfig {
+    /// Keep the `.tex` source and the LaTeX engine's auxiliary files
+    /// (`--debug`).
+    pub debug: bool,
+}
+
+/// Output from the PDF render pipeline.
+#[derive(Debug)]
+pub struct PdfRenderOutput {
+    /// The PDF that was written.
+    pub pdf_path: PathBuf,
+    /// Files kept next to the PDF (the `.tex` source with `keep-tex: true`
+    /// or in debug mode).
+    pub supporting_files: Vec<PathBuf>,
+    /// Diagnostics (warnings, errors, info) collected during rendering.
+    pub diagnostics: Vec<DiagnosticMessage>,
+    /// Source context for mapping locations in diagnostics.
+    pub source_context: SourceContext,
+}
+
 /// Build the standard HTML pipeline stages.
 ///
 /// Returns the stages as a vector, allowing callers to customize before
@@ -121,13 +194,50 @@ pub struct RenderOutput {
 /// 3. `AstTransformsStage` - Run Quarto transforms (callouts, metadata, etc.)
 /// 4. `RenderHtmlBodyStage` - Render AST to HTML body
 /// 5. `ApplyTemplateStage` - Apply HTML template
+/// 6. `PostprocessHtmlStage` - Resolve resources, rewrite links, inject analytics
 pub fn build_html_pipeline_stages() -> Vec<Box<dyn PipelineStage>> {
+    let mut stages = build_document_stages();
+    stages.extend(build_html_format_stages(&HtmlRenderConfig::default()));
+    stages
+}
+
+/// Build the format-independent stages: parsing and code execution.
+///
+/// Their output (a `DocumentAst`) is the input of the format-specific stages
+/// ([`build_html_format_stages`], [`build_pdf_format_stages`],
+/// [`build_typst_format_stages`]), so one execution can be rendered to
+/// several formats (see [`prepare_document`]).
+///
+/// This creates stages for:
+/// 1. `ParseDocumentStage` - Parse QMD to Pandoc AST
+/// 2. `EngineExecutionStage` - Execute code cells (jupyter, knitr, or markdown passthrough)
+pub fn build_document_stages() -> Vec<Box<dyn PipelineStage>> {
     vec![
         Box::new(ParseDocumentStage::new()),
         Box::new(EngineExecutionStage::new()),
+    ]
+}
+
+/// Build the HTML-specific stages, configured by `config`.
+///
+/// This creates stages for:
+/// 1. `AstTransformsStage` - Run Quarto transforms (callouts, metadata, etc.)
+/// 2. `RenderHtmlBodyStage` - Render AST to HTML body
+/// 3. `ApplyTemplateStage` - Apply HTML template
+/// 4. `PostprocessHtmlStage` - Resolve resources, rewrite links, inject analytics
+pub fn build_html_format_stages(config: &HtmlRenderConfig<'_>) -> Vec<Box<dyn PipelineStage>> {
+    let apply_config = ApplyTemplateConfig::new()
+        .with_css_paths(config.css_paths.to_vec())
+        .with_dark_css_paths(config.dark_css_paths.to_vec());
+    vec![
         Box::new(AstTransformsStage::new()),
-        Box::new(RenderHtmlBodyStage::new()),
-        Box::new(ApplyTemplateStage::new()),
+        Box::new(
+            config
+                .math
+                .map_or_else(RenderHtmlBodyStage::new, RenderHtmlBodyStage::with_math),
+        ),
+        Box::new(ApplyTemplateStage::with_config(apply_config)),
+        Box::new(PostprocessHtmlStage::new()),
     ]
 }
 
@@ -139,6 +249,7 @@ pub fn build_html_pipeline_stages() -> Vec<Box<dyn PipelineStage>> {
 /// 3. `AstTransformsStage` - Run Quarto transforms (callouts, metadata, etc.)
 /// 4. `RenderHtmlBodyStage` - Render AST to HTML body
 /// 5. `ApplyTemplateStage` - Apply HTML template
+/// 6. `PostprocessHtmlStage` - Resolve resources, rewrite links, inject analytics
 ///
 /// # Returns
 ///
@@ -164,6 +275,7 @@ pub fn build_html_pipeline() -> Pipeline {
 /// 2. `AstTransformsStage` - Run Quarto transforms (callouts, metadata, TOC, etc.)
 /// 3. `RenderHtmlBodyStage` - Render AST to HTML body
 /// 4. `ApplyTemplateStage` - Apply HTML template
+/// 5. `PostprocessHtmlStage` - Resolve resources, rewrite links, inject analytics
 ///
 /// # Returns
 ///
@@ -180,6 +292,7 @@ pub fn build_wasm_html_pipeline() -> Pipeline {
         Box::new(AstTransformsStage::new()),
         Box::new(RenderHtmlBodyStage::new()),
         Box::new(ApplyTemplateStage::new()),
+        Box::new(PostprocessHtmlStage::new()),
     ];
 
     Pipeline::new(stages).expect("WASM HTML pipeline stages should be compatible")
@@ -219,6 +332,80 @@ pub fn build_html_pipeline_with_stages(
     Pipeline::new(stages)
 }
 
+/// Build the PDF pipeline stages.
+///
+/// This creates stages for:
+/// 1. `ParseDocumentStage` - Parse QMD to Pandoc AST
+/// 2. `EngineExecutionStage` - Execute code cells (jupyter, knitr, or markdown passthrough)
+/// 3. `AstTransformsStage` - Run the LaTeX transforms ([`build_latex_transform_pipeline`])
+/// 4. `RenderLatexStage` - Render AST to a complete LaTeX document
+/// 5. `CompileLatexStage` - Run the PDF engine found in `binaries`
+pub fn build_pdf_pipeline_stages(
+    binaries: &BinaryDependencies,
+    config: &PdfRenderConfig,
+) -> Vec<Box<dyn PipelineStage>> {
+    let mut stages = build_document_stages();
+    stages.extend(build_pdf_format_stages(binaries, config));
+    stages
+}
+
+/// Build the PDF-specific stages (LaTeX).
+///
+/// This creates stages for:
+/// 1. `AstTransformsStage` - Run the LaTeX transforms ([`build_latex_transform_pipeline`])
+/// 2. `RenderLatexStage` - Render AST to a complete LaTeX document
+/// 3. `CompileLatexStage` - Run the PDF engine found in `binaries`
+pub fn build_pdf_format_stages(
+    binaries: &BinaryDependencies,
+    config: &PdfRenderConfig,
+) -> Vec<Box<dyn PipelineStage>> {
+    vec![
+        Box::new(AstTransformsStage::with_pipeline(
+            build_latex_transform_pipeline(),
+        )),
+        Box::new(RenderLatexStage::new()),
+        Box::new(CompileLatexStage::new(binaries.clone()).with_debug(config.debug)),
+    ]
+}
+
+/// Build the Typst pipeline stages (`format: typst`).
+///
+/// This creates stages for:
+/// 1. `ParseDocumentStage` - Parse QMD to Pandoc AST
+/// 2. `EngineExecutionStage` - Execute code cells (jupyter, knitr, or markdown passthrough)
+/// 3. `AstTransformsStage` - Run the LaTeX transforms ([`build_latex_transform_pipeline`]),
+///    which are format-independent
+/// 4. `RenderTypstStage` - Render AST to a complete Typst document
+/// 5. `CompileTypstStage` - Run the Typst binary found in `binaries`
+pub fn build_typst_pipeline_stages(
+    binaries: &BinaryDependencies,
+    config: &PdfRenderConfig,
+) -> Vec<Box<dyn PipelineStage>> {
+    let mut stages = build_document_stages();
+    stages.extend(build_typst_format_stages(binaries, config));
+    stages
+}
+
+/// Build the Typst-specific stages (`format: typst`).
+///
+/// This creates stages for:
+/// 1. `AstTransformsStage` - Run the LaTeX transforms ([`build_latex_transform_pipeline`]),
+///    which are format-independent
+/// 2. `RenderTypstStage` - Render AST to a complete Typst document
+/// 3. `CompileTypstStage` - Run the Typst binary found in `binaries`
+pub fn build_typst_format_stages(
+    binaries: &BinaryDependencies,
+    config: &PdfRenderConfig,
+) -> Vec<Box<dyn PipelineStage>> {
+    vec![
+        Box::new(AstTransformsStage::with_pipeline(
+            build_latex_transform_pipeline(),
+        )),
+        Box::new(RenderTypstStage::new()),
+        Box::new(CompileTypstStage::new(binaries.clone()).with_debug(config.debug)),
+    ]
+}
+
 /// Render QMD content to HTML.
 ///
 /// This is the unified async render pipeline used by both CLI and WASM. It:
@@ -226,13 +413,14 @@ pub fn build_html_pipeline_with_stages(
 /// 2. Runs the transform pipeline (callouts, metadata normalization, etc.)
 /// 3. Renders the AST to HTML body
 /// 4. Applies the HTML template
+/// 5. Post-processes the HTML (copies resources, rewrites links, adds analytics)
 ///
 /// # Arguments
 ///
 /// * `content` - The QMD source content as bytes
 /// * `source_name` - Name of the source file (for error messages)
 /// * `ctx` - Render context containing project, document, format info
-/// * `config` - HTML render configuration (CSS paths, template)
+/// * `config` - HTML render configuration (CSS paths, template, math method)
 /// * `runtime` - System runtime for filesystem operations
 ///
 /// # Returns
@@ -263,82 +451,267 @@ pub async fn render_qmd_to_html(
     config: &HtmlRenderConfig<'_>,
     runtime: Arc<dyn quarto_system_runtime::SystemRuntime>,
 ) -> Result<RenderOutput> {
-    // Create StageContext from RenderContext data
-    let mut stage_ctx = StageContext::new(
-        runtime,
-        ctx.format.clone(),
-        ctx.project.clone(),
-        ctx.document.clone(),
-    )
-    .map_err(|e| crate::error::QuartoError::Other(e.to_string()))?;
-
-    // Transfer artifacts from RenderContext to StageContext
-    stage_ctx.artifacts = std::mem::take(&mut ctx.artifacts);
+    let prepared = prepare_document(content, source_name, ctx, runtime.clone()).await?;
+    render_prepared_to_html(&prepared, ctx, config, runtime).await
+}
+
+/// Render QMD content to PDF.
+///
+/// Runs the PDF pipeline ([`build_pdf_pipeline_stages`]) with the engines in
+/// `ctx.binaries`, or the Typst pipeline ([`build_typst_pipeline_stages`])
+/// for `format: typst`. The PDF is written to the render's output path, with
+/// the `.tex`/`.typ` source next to it.
+///
+/// # Errors
+///
+/// Returns an error if parsing fails, no PDF engine is available, or
+/// compilation fails. LaTeX errors carry diagnostics naming the missing LaTeX
+/// packages, if any; Typst errors are located in the QMD source.
+pub async fn render_qmd_to_pdf(
+    content: &[u8],
+    source_name: &str,
+    ctx: &mut RenderContext<'_>,
+    config: &PdfRenderConfig,
+    runtime: Arc<dyn quarto_system_runtime::SystemRuntime>,
+) -> Result<PdfRenderOutput> {
+    let prepared = prepare_document(content, source_name, ctx, runtime.clone()).await?;
+    render_prepared_to_pdf(&prepared, ctx, config, runtime).await
+}
+
+/// A document parsed and executed once, ready to be rendered to any number
+/// of formats.
+///
+/// Produced by [`prepare_document`]. Each format-specific render
+/// ([`render_prepared_to_html`], [`render_prepared_to_pdf`]) starts from its
+/// own copy of the AST, so `quarto render --to html,pdf` runs the document's
+/// code once.
+#[derive(Debug, Clone)]
+pub struct PreparedDocument {
+    /// The parsed document, with execution results
+    pub doc: DocumentAst,
+    /// Diagnostics from parsing and execution
+    pub diagnostics: Vec<DiagnosticMessage>,
+    /// Source context for mapping locations in diagnostics
+    pub source_context: SourceContext,
+}
 
-    // Create input from content
+/// Run the format-independent stages ([`build_document_stages`]): parse the
+/// QMD content and execute its code.
+///
+/// Execution sees `ctx.format` as the target format, and its results are
+/// shared by every format the document is then rendered to: pass the first
+/// of the formats being rendered.
+///
+/// # Errors
+///
+/// Returns an error if parsing or execution fails.
+pub async fn prepare_document(
+    content: &[u8],
+    source_name: &str,
+    ctx: &mut RenderContext<'_>,
+    runtime: Arc<dyn quarto_system_runtime::SystemRuntime>,
+) -> Result<PreparedDocument> {
+    let source_context = document_source_context(content, source_name);
     let input = PipelineData::LoadedSource(LoadedSource::new(
         PathBuf::from(source_name),
         content.to_vec(),
     ));
+    let pipeline = Pipeline::new(build_document_stages())
+        .expect("document pipeline stages should be compatible");
+    let (result, diagnostics) = run_stages(pipeline, input, ctx, runtime).await?;
+
+    let output = result.map_err(|e| pipeline_error(e, &source_context))?;
+    let PipelineData::DocumentAst(doc) = output else {
+        return Err(crate::error::QuartoError::Other(
+            "Pipeline did not produce DocumentAst".to_string(),
+        ));
+    };
 
-    // Build pipeline based on config
-    // If custom CSS or template is specified, use a customized ApplyTemplateStage
-    let pipeline = if config.template.is_some() || !config.css_paths.is_empty() {
-        let apply_config = ApplyTemplateConfig::new().with_css_paths(config.css_paths.to_vec());
-        // If custom template is provided, we'd need to pass it too
-        // For now, css_paths is the main customization needed
+    Ok(PreparedDocument {
+        doc,
+        diagnostics,
+        source_context,
+    })
+}
 
-        let stages: Vec<Box<dyn PipelineStage>> = vec![
-            Box::new(ParseDocumentStage::new()),
-            Box::new(EngineExecutionStage::new()),
-            Box::new(AstTransformsStage::new()),
-            Box::new(RenderHtmlBodyStage::new()),
-            Box::new(ApplyTemplateStage::with_config(apply_config)),
-        ];
-        Pipeline::new(stages).expect("HTML pipeline stages should be compatible")
-    } else {
-        build_html_pipeline()
+/// Render a prepared document to HTML with the HTML-specific stages
+/// ([`build_html_format_stages`]).
+///
+/// `ctx` is the render context for the HTML format (see
+/// [`RenderContext::for_format`]). The output's diagnostics include those
+/// of preparing the document.
+///
+/// # Errors
+///
+/// Returns an error if transforms or rendering fail.
+pub async fn render_prepared_to_html(
+    prepared: &PreparedDocument,
+    ctx: &mut RenderContext<'_>,
+    config: &HtmlRenderConfig<'_>,
+    runtime: Arc<dyn quarto_system_runtime::SystemRuntime>,
+) -> Result<RenderOutput> {
+    // Custom templates are not passed to the template stage yet
+    let pipeline = Pipeline::new(build_html_format_stages(config))
+        .expect("HTML pipeline stages should be compatible");
+    let input = PipelineData::DocumentAst(prepared.doc.clone());
+    let (result, diagnostics) = run_stages(pipeline, input, ctx, runtime).await?;
+
+    let rendered = rendered_output(result, &prepared.source_context)?;
+    let mut all_diagnostics = prepared.diagnostics.clone();
+    all_diagnostics.extend(diagnostics);
+
+    Ok(RenderOutput {
+        html: rendered.content,
+        diagnostics: all_diagnostics,
+        source_context: prepared.source_context.clone(),
+    })
+}
+
+/// Render a prepared document to PDF with the PDF-specific stages
+/// ([`build_pdf_format_stages`]), or the Typst ones
+/// ([`build_typst_format_stages`]) for `format: typst`.
+///
+/// `ctx` is the render context for the PDF format (see
+/// [`RenderContext::for_format`]). The output's diagnostics include those
+/// of preparing the document.
+///
+/// # Errors
+///
+/// Returns an error if no PDF engine is available or compilation fails.
+pub async fn render_prepared_to_pdf(
+    prepared: &PreparedDocument,
+    ctx: &mut RenderContext<'_>,
+    config: &PdfRenderConfig,
+    runtime: Arc<dyn quarto_system_runtime::SystemRuntime>,
+) -> Result<PdfRenderOutput> {
+    let stages = match ctx.format.identifier {
+        FormatIdentifier::Typst => build_typst_format_stages(ctx.binaries, config),
+        _ => build_pdf_format_stages(ctx.binaries, config),
     };
+    let pipeline = Pipeline::new(stages).expect("PDF pipeline stages should be compatible");
+    let input = PipelineData::DocumentAst(prepared.doc.clone());
+    let (result, diagnostics) = run_stages(pipeline, input, ctx, runtime).await?;
+
+    let rendered = rendered_output(result, &prepared.source_context)?;
+    let mut all_diagnostics = prepared.diagnostics.clone();
+    all_diagnostics.extend(diagnostics);
+
+    Ok(PdfRenderOutput {
+        pdf_path: rendered.output_path,
+        supporting_files: rendered.supporting_files,
+        diagnostics: all_diagnostics,
+        source_context: prepared.source_context.clone(),
+    })
+}
+
+/// Run `pipeline` on `input` in a stage context built from `ctx`.
+///
+/// The render context's artifacts are moved into the stage context and back,
+/// so they accumulate across runs. Returns the pipeline's result with the
+/// diagnostics collected by the stages.
+///
+/// The stages run in a `render` tracing span recording the input and format.
+async fn run_stages(
+    pipeline: Pipeline,
+    input: PipelineData,
+    ctx: &mut RenderContext<'_>,
+    runtime: Arc<dyn quarto_system_runtime::SystemRuntime>,
+) -> Result<(
+    std::result::Result<PipelineData, PipelineError>,
+    Vec<DiagnosticMessage>,
+)> {
+    let span = tracing::info_span!(
+        "render",
+        input = %ctx.document.input.display(),
+        format = %ctx.format.identifier
+    );
+
+    // An explicit output path decides where outputs and resources go
+    let mut document = ctx.document.clone();
+    if let Some(output) = &ctx.options.output_path {
+        document.output = Some(output.clone());
+    }
 
-    // Run the async pipeline
-    let result = pipeline.run(input, &mut stage_ctx).await;
+    let mut stage_ctx =
+        StageContext::new(runtime, ctx.format.clone(), ctx.project.clone(), document)
+            .map_err(|e| crate::error::QuartoError::Other(e.to_string()))?
+            .with_execute_options(ctx.options.execute_options())
+            .with_crossrefs(ctx.options.crossrefs.clone())
+            .with_metadata_overrides(ctx.options.metadata.clone())
+            .with_cancellation(ctx.cancellation.clone())
+            .with_observer(ctx.observer.clone());
+    stage_ctx.artifacts = std::mem::take(&mut ctx.artifacts);
+
+    let result = pipeline.run(input, &mut stage_ctx).instrument(span).await;
 
-    // Transfer artifacts back to RenderContext
     ctx.artifacts = stage_ctx.artifacts;
+    Ok((result, stage_ctx.diagnostics))
+}
 
-    // Handle result
-    let output = result.map_err(|e| match e {
-        crate::stage::PipelineError::StageError { diagnostics, .. } if !diagnostics.is_empty() => {
-            // Create a SourceContext for the parse error
-            let mut source_context = SourceContext::new();
-            let content_str = String::from_utf8_lossy(content).to_string();
-            source_context.add_file(source_name.to_string(), Some(content_str));
+/// A source context holding the document, for rendering diagnostics.
+fn document_source_context(content: &[u8], source_name: &str) -> SourceContext {
+    let mut source_context = SourceContext::new();
+    source_context.add_file(
+        source_name.to_string(),
+        Some(String::from_utf8_lossy(content).to_string()),
+    );
+    source_context
+}
+
+/// Convert a pipeline error; stage errors with diagnostics become parse
+/// errors, which are reported with source snippets.
+fn pipeline_error(
+    error: PipelineError,
+    source_context: &SourceContext,
+) -> crate::error::QuartoError {
+    match error {
+        PipelineError::StageError { diagnostics, .. } if !diagnostics.is_empty() => {
             crate::error::QuartoError::Parse(crate::error::ParseError::new(
                 diagnostics,
-                source_context,
+                source_context.clone(),
             ))
         }
+        PipelineError::Cancelled => crate::error::QuartoError::Cancelled,
         other => crate::error::QuartoError::Other(other.to_string()),
-    })?;
-
-    // Extract the rendered output
-    let rendered = output.into_rendered_output().ok_or_else(|| {
-        crate::error::QuartoError::Other("Pipeline did not produce RenderedOutput".to_string())
-    })?;
-
-    // Collect diagnostics from the pipeline
-    let diagnostics = stage_ctx.diagnostics;
+    }
+}
 
-    // Create source context for the output
-    let mut source_context = SourceContext::new();
-    let content_str = String::from_utf8_lossy(content).to_string();
-    source_context.add_file(source_name.to_string(), Some(content_str));
+/// The `RenderedOutput` a format-specific pipeline produced.
+fn rendered_output(
+    result: std::result::Result<PipelineData, PipelineError>,
+    source_context: &SourceContext,
+) -> Result<RenderedOutput> {
+    result
+        .map_err(|e| pipeline_error(e, source_context))?
+        .into_rendered_output()
+        .ok_or_else(|| {
+            crate::error::QuartoError::Other("Pipeline did not produce RenderedOutput".to_string())
+        })
+}
 
-    Ok(RenderOutput {
-        html: rendered.content,
-        diagnostics,
-        source_context,
-    })
+/// Build the transform pipeline for LaTeX (and Typst) output.
+///
+/// Most HTML transforms have a counterpart in the template or the engine
+/// (title block, section numbering, TOC, footnotes), so only the
+/// format-independent transforms run:
+///
+/// 1. `ShortcodeResolveTransform` - Resolve shortcodes
+/// 2. `LuaFilterTransform::pre` - Run the document's Lua filters (native only)
+/// 3. `MetadataNormalizeTransform` - Add derived metadata
+/// 4. `CrossrefTransform` - Number labeled targets and resolve references
+/// 5. `HighlightStyleTransform` - Produce the `highlight-style` macros (PDF)
+/// 6. `LuaFilterTransform::post` - Run the Lua filters listed after `quarto`
+pub fn build_latex_transform_pipeline() -> TransformPipeline {
+    let mut pipeline = TransformPipeline::new();
+    pipeline.push(Box::new(ShortcodeResolveTransform::new()));
+    #[cfg(not(target_arch = "wasm32"))]
+    pipeline.push(Box::new(LuaFilterTransform::pre()));
+    pipeline.push(Box::new(MetadataNormalizeTransform::new()));
+    pipeline.push(Box::new(CrossrefTransform::new()));
+    pipeline.push(Box::new(HighlightStyleTransform::new()));
+    #[cfg(not(target_arch = "wasm32"))]
+    pipeline.push(Box::new(LuaFilterTransform::post()));
+    pipeline
 }
 
 /// Build the standard transform pipeline.
@@ -348,39 +721,69 @@ pub async fn render_qmd_to_html(
 /// ## Normalization Phase
 /// 1. `CalloutTransform` - Convert callout Divs to CustomNodes
 /// 2. `CalloutResolveTransform` - Resolve CustomNodes to structured Divs
-/// 3. `ShortcodeResolveTransform` - Resolve shortcodes (e.g., `{{< meta title >}}`)
-/// 4. `MetadataNormalizeTransform` - Add derived metadata (pagetitle, etc.)
-/// 5. `TitleBlockTransform` - Add title header from metadata if not present
-/// 6. `SectionizeTransform` - Wrap headers in section Divs (for HTML semantic structure)
-/// 7. `FootnotesTransform` - Extract footnotes and create footnotes section
+/// 3. `PanelTransform` - Restructure tabset and layout panel Divs
+/// 4. `ShortcodeResolveTransform` - Resolve shortcodes (e.g., `{{< meta title >}}`)
+/// 5. `LuaFilterTransform::pre` - Run the document's Lua filters (native only)
+/// 6. `MetadataNormalizeTransform` - Add derived metadata (pagetitle, etc.)
+/// 7. `NumberSectionsTransform` - Number headers (if number-sections: true)
+/// 8. `TitleBlockTransform` - Add title header from metadata if not present
+/// 9. `RevealjsSlidesTransform` - Split `format: revealjs` documents into slides
+/// 10. `SectionizeTransform` - Wrap headers in section Divs (for HTML semantic structure)
+/// 11. `ListingTransform` - Render `listing:` pages from the listed documents
+/// 12. `CrossrefTransform` - Number labeled targets and resolve `@fig-`, `@sec-`, ... references
+/// 13. `FootnotesTransform` - Extract footnotes and create footnotes section
 ///
 /// ## TOC Phase
-/// 8. `TocGenerateTransform` - Generate TOC from headers (if toc: true)
-/// 9. `TocRenderTransform` - Render TOC to HTML for template insertion
+/// 14. `TocGenerateTransform` - Generate TOC from headers (if toc: true)
+/// 15. `TocRenderTransform` - Render TOC to HTML for template insertion
+/// 16. `WebsiteNavigationTransform` - Render website navbar, sidebar and footer
 ///
 /// ## Finalization Phase
-/// 10. `AppendixStructureTransform` - Consolidate appendix content into container
-/// 11. `ResourceCollectorTransform` - Collect image dependencies
+/// 17. `AppendixStructureTransform` - Consolidate appendix content into container
+/// 18. `SocialMetadataTransform` - Render OpenGraph, Twitter card and favicon tags
+/// 19. `ResourceCollectorTransform` - Collect image dependencies
+/// 20. `HighlightStyleTransform` - Produce the `highlight-style` stylesheet
+/// 21. `LuaFilterTransform::post` - Run the Lua filters listed after `quarto`
 pub fn build_transform_pipeline() -> TransformPipeline {
     let mut pipeline = TransformPipeline::new();
 
     // === NORMALIZATION PHASE ===
     pipeline.push(Box::new(CalloutTransform::new()));
     pipeline.push(Box::new(CalloutResolveTransform::new()));
+    // Before SectionizeTransform and NumberSectionsTransform, so tab headings
+    // are moved into their panes first
+    pipeline.push(Box::new(PanelTransform::new()));
     pipeline.push(Box::new(ShortcodeResolveTransform::new()));
+    // After ShortcodeResolveTransform so filters see resolved shortcodes
+    #[cfg(not(target_arch = "wasm32"))]
+    pipeline.push(Box::new(LuaFilterTransform::pre()));
     pipeline.push(Box::new(MetadataNormalizeTransform::new()));
+    // Before TitleBlockTransform so the generated title header isn't numbered
+    pipeline.push(Box::new(NumberSectionsTransform::new()));
     pipeline.push(Box::new(TitleBlockTransform::new()));
+    // Before SectionizeTransform so headings below the slide level don't
+    // become nested sections (which reveal.js would show as slides)
+    pipeline.push(Box::new(RevealjsSlidesTransform::new()));
     pipeline.push(Box::new(SectionizeTransform::new()));
+    // After SectionizeTransform so an appended listing isn't inside the last section
+    pipeline.push(Box::new(ListingTransform::new()));
+    // After SectionizeTransform so section ids and numbers are on the section Divs
+    pipeline.push(Box::new(CrossrefTransform::new()));
     pipeline.push(Box::new(FootnotesTransform::new()));
 
     // === TOC PHASE ===
     // Must run after SectionizeTransform so section IDs are available
     pipeline.push(Box::new(TocGenerateTransform::new()));
     pipeline.push(Box::new(TocRenderTransform::new()));
+    pipeline.push(Box::new(WebsiteNavigationTransform::new()));
 
     // === FINALIZATION PHASE ===
     pipeline.push(Box::new(AppendixStructureTransform::new()));
+    pipeline.push(Box::new(SocialMetadataTransform::new()));
     pipeline.push(Box::new(ResourceCollectorTransform::new()));
+    pipeline.push(Box::new(HighlightStyleTransform::new()));
+    #[cfg(not(target_arch = "wasm32"))]
+    pipeline.push(Box::new(LuaFilterTransform::post()));
 
     pipeline
 }
@@ -429,6 +832,155 @@ mod tests {
         assert!(output.html.contains("<title>Test</title>"));
     }
 
+    #[test]
+    fn test_render_revealjs_presentation() {
+        let content = b"---\ntitle: Talk\n---\n\n## First\n\n- a\n- b\n\n::: notes\nSay hi\n:::\n\n## Second\n\nText\n";
+
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/test.qmd");
+        let format = Format::revealjs();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+
+        let config = HtmlRenderConfig::default();
+        let runtime = make_test_runtime();
+        let output = pollster::block_on(render_qmd_to_html(
+            content, "test.qmd", &mut ctx, &config, runtime,
+        ))
+        .unwrap();
+
+        let html = &output.html;
+        assert!(html.contains("<div class=\"reveal\">"));
+        assert!(html.contains("<section id=\"title-slide\""));
+        assert!(html.contains("<section id=\"first\" class=\"section slide level2\""));
+        assert!(html.contains("<section id=\"second\" class=\"section slide level2\""));
+        assert!(html.contains("<aside class=\"notes\">"));
+        assert!(html.contains("Reveal.initialize("));
+        assert!(ctx.artifacts.get("js:revealjs").is_some());
+    }
+
+    #[test]
+    fn test_render_prepared_document_to_several_formats() {
+        let content = b"---\ntitle: Test\n---\n\n## Intro\n\nHello, world!\n";
+
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/test.qmd");
+        let html = Format::html();
+        let slides = Format::revealjs();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &html, &binaries);
+        let runtime = make_test_runtime();
+
+        let prepared = pollster::block_on(prepare_document(
+            content,
+            "test.qmd",
+            &mut ctx,
+            runtime.clone(),
+        ))
+        .unwrap();
+
+        let config = HtmlRenderConfig::default();
+        let page = pollster::block_on(render_prepared_to_html(
+            &prepared,
+            &mut ctx,
+            &config,
+            runtime.clone(),
+        ))
+        .unwrap();
+        let mut slides_ctx = ctx.for_format(&slides);
+        let deck = pollster::block_on(render_prepared_to_html(
+            &prepared,
+            &mut slides_ctx,
+            &config,
+            runtime,
+        ))
+        .unwrap();
+
+        assert!(page.html.contains("<main class=\"content\""));
+        assert!(!page.html.contains("class=\"reveal\""));
+        assert!(deck.html.contains("class=\"reveal\""));
+        assert!(deck.html.contains("class=\"section slide level2\""));
+        // The prepared AST is unchanged by the renders
+        assert_eq!(prepared.doc.ast.blocks.len(), 2);
+    }
+
+    /// `quarto render --to html,pdf`: both formats are rendered from one
+    /// parse and execution, each with its own transforms.
+    #[cfg(unix)]
+    #[test]
+    fn test_render_prepared_document_to_html_and_pdf() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        // A stand-in for xelatex that writes the PDF next to the .tex
+        let xelatex = dir.join("fake-xelatex");
+        std::fs::write(
+            &xelatex,
+            "#!/bin/sh\nfor a; do t=\"$a\"; done\necho pdf > \"${t%.tex}.pdf\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&xelatex, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let content = b"---\ntitle: Test\n---\n\n## Intro\n\nHello, world!\n";
+        let project = ProjectContext {
+            dir: dir.to_path_buf(),
+            config: None,
+            is_single_file: true,
+            files: vec![DocumentInfo::from_path(dir.join("test.qmd"))],
+            output_dir: dir.to_path_buf(),
+        };
+        let html = Format::html();
+        let pdf = Format::pdf();
+        let binaries = BinaryDependencies {
+            xelatex: Some(xelatex),
+            ..Default::default()
+        };
+        let mut ctx = RenderContext::new(&project, &project.files[0], &html, &binaries);
+        let runtime = make_test_runtime();
+
+        let mut prepared = pollster::block_on(prepare_document(
+            content,
+            "test.qmd",
+            &mut ctx,
+            runtime.clone(),
+        ))
+        .unwrap();
+        // Neither format parses the source again: both render the prepared
+        // AST, here without its paragraph
+        prepared.doc.ast.blocks.truncate(1);
+
+        let page = pollster::block_on(render_prepared_to_html(
+            &prepared,
+            &mut ctx,
+            &HtmlRenderConfig::default(),
+            runtime.clone(),
+        ))
+        .unwrap();
+        let mut pdf_ctx = ctx.for_format(&pdf);
+        let output = pollster::block_on(render_prepared_to_pdf(
+            &prepared,
+            &mut pdf_ctx,
+            &PdfRenderConfig { debug: true },
+            runtime,
+        ))
+        .unwrap();
+        let tex = std::fs::read_to_string(dir.join("test.tex")).unwrap();
+
+        assert!(!page.html.contains("Hello, world!"));
+        assert!(!tex.contains("Hello, world!"));
+        // HTML transforms wrap headers in sections; the PDF renders from
+        // the AST without them
+        assert!(page.html.contains("<section id=\"intro\""));
+        assert!(tex.contains("\\subsection{Intro}"));
+        assert!(matches!(
+            prepared.doc.ast.blocks[0],
+            quarto_pandoc_types::Block::Header(_)
+        ));
+        assert_eq!(output.pdf_path, dir.join("test.pdf"));
+        assert!(output.pdf_path.exists());
+    }
+
     #[test]
     fn test_render_with_callout() {
         let content =
@@ -613,25 +1165,46 @@ mod tests {
     #[test]
     fn test_build_html_pipeline_stages() {
         let stages = build_html_pipeline_stages();
-        assert_eq!(stages.len(), 5);
+        assert_eq!(stages.len(), 6);
         assert_eq!(stages[0].name(), "parse-document");
         assert_eq!(stages[1].name(), "engine-execution");
         assert_eq!(stages[2].name(), "ast-transforms");
         assert_eq!(stages[3].name(), "render-html-body");
         assert_eq!(stages[4].name(), "apply-template");
+        assert_eq!(stages[5].name(), "postprocess-html");
     }
 
     #[test]
     fn test_build_html_pipeline() {
         let pipeline = build_html_pipeline();
-        assert_eq!(pipeline.len(), 5);
+        assert_eq!(pipeline.len(), 6);
     }
 
     #[test]
     fn test_build_wasm_html_pipeline() {
         let pipeline = build_wasm_html_pipeline();
-        // WASM pipeline has 4 stages (no engine execution)
-        assert_eq!(pipeline.len(), 4);
+        // WASM pipeline has 5 stages (no engine execution)
+        assert_eq!(pipeline.len(), 5);
+    }
+
+    #[test]
+    fn test_build_pdf_pipeline_stages() {
+        let stages =
+            build_pdf_pipeline_stages(&BinaryDependencies::default(), &PdfRenderConfig::default());
+        assert_eq!(stages.len(), 5);
+        let pipeline = Pipeline::new(stages).unwrap();
+        assert_eq!(pipeline.len(), 5);
+        assert_eq!(build_latex_transform_pipeline().len(), 5);
+    }
+
+    #[test]
+    fn test_build_typst_pipeline_stages() {
+        let stages = build_typst_pipeline_stages(
+            &BinaryDependencies::default(),
+            &PdfRenderConfig::default(),
+        );
+        let pipeline = Pipeline::new(stages).unwrap();
+        assert_eq!(pipeline.len(), 5);
     }
 
     #[test]
@@ -0,0 +1,207 @@
+/*
+ * postprocess/analytics.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Analytics snippet injection.
+ */
+
+//! Analytics snippet injection.
+//!
+//! `google-analytics` (a tracking ID, or a map with `tracking-id` and
+//! `anonymize-ip`) adds the Google tag to the document head. The option is
+//! read from the format and then from the website configuration:
+//!
+//! ```yaml
+//! website:
+//!   google-analytics:
+//!     tracking-id: "G-XXXXXXXXXX"
+//!     anonymize-ip: true
+//! ```
+//!
+//! `plausible-analytics` takes the snippet Plausible provides, either
+//! inline or as a path to a file (relative to the project) holding it.
+
+use serde_json::Value;
+
+use super::{HtmlDocument, HtmlPostprocessor, PostprocessContext};
+use crate::Result;
+
+/// Post-processor that injects the configured analytics snippets.
+pub struct AnalyticsPostprocessor;
+
+impl AnalyticsPostprocessor {
+    /// Create a new analytics post-processor.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for AnalyticsPostprocessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HtmlPostprocessor for AnalyticsPostprocessor {
+    fn name(&self) -> &str {
+        "analytics"
+    }
+
+    fn process(&self, doc: &mut HtmlDocument, ctx: &mut PostprocessContext<'_>) -> Result<()> {
+        let option = |key: &str| ctx.format_option(key).or_else(|| ctx.website_option(key));
+        let mut snippets = Vec::new();
+        let mut events = Vec::new();
+
+        if let Some(config) = option("google-analytics") {
+            match google_analytics(config) {
+                Some(snippet) => snippets.push(snippet),
+                None => events.push("ignoring google-analytics: invalid tracking id".to_string()),
+            }
+        }
+
+        if let Some(snippet) = option("plausible-analytics").and_then(Value::as_str) {
+            let snippet = snippet.trim();
+            if snippet.starts_with('<') {
+                snippets.push(format!("{}\n", snippet));
+            } else {
+                let path = ctx.project.dir.join(snippet);
+                match ctx.runtime.file_read(&path) {
+                    Ok(content) => snippets.push(String::from_utf8_lossy(&content).into_owned()),
+                    Err(e) => events.push(format!(
+                        "ignoring plausible-analytics: failed to read {}: {}",
+                        path.display(),
+                        e
+                    )),
+                }
+            }
+        }
+
+        if !snippets.is_empty() && !doc.insert_before_end_tag("head", snippets.concat()) {
+            events.push("not adding analytics: document has no </head>".to_string());
+        }
+        ctx.events.extend(events);
+        Ok(())
+    }
+}
+
+/// The Google tag for a `google-analytics` option, or `None` if the
+/// tracking ID is missing or not a plain ID.
+fn google_analytics(config: &Value) -> Option<String> {
+    let (id, anonymize) = match config {
+        Value::String(id) => (id.as_str(), false),
+        Value::Object(map) => (
+            map.get("tracking-id")?.as_str()?,
+            map.get("anonymize-ip")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+        ),
+        _ => return None,
+    };
+    let id = id.trim();
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return None;
+    }
+
+    let options = if anonymize {
+        ", { 'anonymize_ip': true }"
+    } else {
+        ""
+    };
+    Some(format!(
+        r#"<script async src="https://www.googletagmanager.com/gtag/js?id={id}"></script>
+<script>
+window.dataLayer = window.dataLayer || [];
+function gtag(){{dataLayer.push(arguments);}}
+gtag('js', new Date());
+gtag('config', '{id}'{options});
+</script>
+"#
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{ProjectConfig, ProjectContext};
+    use quarto_system_runtime::NativeRuntime;
+    use serde_json::json;
+    use std::path::Path;
+
+    fn process(html: &str, options: Value, raw: Value) -> (String, Vec<String>) {
+        let runtime = NativeRuntime::new();
+        let format = Format {
+            metadata: options,
+            ..Format::html()
+        };
+        let project = ProjectContext {
+            dir: "/project".into(),
+            config: Some(ProjectConfig {
+                raw,
+                ..ProjectConfig::default()
+            }),
+            is_single_file: false,
+            files: vec![],
+            output_dir: "/project/_site".into(),
+        };
+        let mut ctx = PostprocessContext::new(
+            &runtime,
+            &format,
+            &project,
+            Path::new("/project/index.qmd"),
+            Path::new("/project/_site/index.html"),
+        );
+        let mut doc = HtmlDocument::parse(html);
+        AnalyticsPostprocessor::new()
+            .process(&mut doc, &mut ctx)
+            .unwrap();
+        (doc.to_html(), ctx.events)
+    }
+
+    #[test]
+    fn test_google_analytics_from_website() {
+        let (html, events) = process(
+            "<html><head><title>T</title></head><body></body></html>",
+            Value::Null,
+            json!({ "website": { "google-analytics": {
+                "tracking-id": "G-ABC123", "anonymize-ip": true
+            } } }),
+        );
+        assert!(events.is_empty());
+        assert!(html.contains(
+            r#"<script async src="https://www.googletagmanager.com/gtag/js?id=G-ABC123"></script>"#
+        ));
+        assert!(html.contains("gtag('config', 'G-ABC123', { 'anonymize_ip': true });"));
+        assert!(html.ends_with("</script>\n</head><body></body></html>"));
+    }
+
+    #[test]
+    fn test_format_option_and_plausible_snippet() {
+        let (html, _) = process(
+            "<head></head>",
+            json!({
+                "google-analytics": "G-DOC",
+                "plausible-analytics": "<script defer data-domain=\"x.org\" src=\"p.js\"></script>"
+            }),
+            json!({ "website": { "google-analytics": "G-SITE" } }),
+        );
+        assert!(html.contains("gtag('config', 'G-DOC');"));
+        assert!(!html.contains("G-SITE"));
+        assert!(html.contains(r#"data-domain="x.org""#));
+    }
+
+    #[test]
+    fn test_invalid_tracking_id_is_ignored() {
+        let (html, events) = process(
+            "<head></head>",
+            json!({ "google-analytics": "G-1'); alert('x" }),
+            Value::Null,
+        );
+        assert_eq!(html, "<head></head>");
+        assert_eq!(events.len(), 1);
+    }
+}
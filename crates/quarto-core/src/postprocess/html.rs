@@ -0,0 +1,405 @@
+/*
+ * postprocess/html.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * A tag-level view of an HTML document.
+ */
+
+//! A tag-level view of an HTML document.
+//!
+//! Post-processors only need to look at and edit tags and their attributes,
+//! so [`HtmlDocument`] splits a document into tags, text, comments and raw
+//! element content (`<script>`, `<style>`, ...) without building a tree.
+//! Nodes that aren't modified serialize back to exactly their source text,
+//! so a post-processor that changes nothing leaves the document untouched.
+
+/// An HTML document as a flat list of nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HtmlDocument {
+    /// The nodes, in document order
+    pub nodes: Vec<HtmlNode>,
+}
+
+/// A node of an [`HtmlDocument`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum HtmlNode {
+    /// Text (or any source kept verbatim, like a `<!DOCTYPE>`)
+    Text(String),
+    /// A start tag
+    Element(StartTag),
+    /// An end tag, with the source it was parsed from
+    EndTag { name: String, source: String },
+    /// A comment, including its delimiters
+    Comment(String),
+}
+
+/// A start tag and its attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StartTag {
+    /// Tag name, lowercased
+    pub name: String,
+    /// Attributes in source order
+    attrs: Vec<HtmlAttr>,
+    /// Whether the tag ends with `/>`
+    self_closing: bool,
+    /// Source text, dropped once the tag is modified
+    source: Option<String>,
+}
+
+/// An attribute of a [`StartTag`].
+///
+/// Values are kept as written in the source (entities are not decoded).
+#[derive(Debug, Clone, PartialEq)]
+struct HtmlAttr {
+    name: String,
+    value: Option<String>,
+}
+
+/// Elements whose content is not markup.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title"];
+
+impl HtmlDocument {
+    /// Split `html` into nodes.
+    ///
+    /// This never fails: anything that doesn't look like a tag is text.
+    pub fn parse(html: &str) -> Self {
+        let mut nodes = Vec::new();
+        let mut text_start = 0;
+        let mut pos = 0;
+
+        while let Some(offset) = html[pos..].find('<') {
+            let start = pos + offset;
+            let rest = &html[start..];
+
+            let parsed = if rest.starts_with("<!--") {
+                let end = rest[4..]
+                    .find("-->")
+                    .map_or(html.len(), |i| start + 4 + i + 3);
+                Some((HtmlNode::Comment(html[start..end].to_string()), end))
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                let end = rest.find('>').map_or(html.len(), |i| start + i + 1);
+                Some((HtmlNode::Text(html[start..end].to_string()), end))
+            } else if let Some(name) = rest.strip_prefix("</") {
+                parse_end_tag(name).map(|(name, len)| {
+                    let end = start + 2 + len;
+                    let source = html[start..end].to_string();
+                    (HtmlNode::EndTag { name, source }, end)
+                })
+            } else {
+                parse_start_tag(rest).map(|(tag, len)| (HtmlNode::Element(tag), start + len))
+            };
+
+            let Some((node, end)) = parsed else {
+                pos = start + 1;
+                continue;
+            };
+
+            if text_start < start {
+                nodes.push(HtmlNode::Text(html[text_start..start].to_string()));
+            }
+            let raw = match &node {
+                HtmlNode::Element(tag) if !tag.self_closing => RAW_TEXT_ELEMENTS
+                    .iter()
+                    .find(|name| **name == tag.name)
+                    .copied(),
+                _ => None,
+            };
+            nodes.push(node);
+            pos = end;
+
+            // Keep raw element content as text up to its end tag
+            if let Some(name) = raw {
+                let content_end = find_end_tag(html, end, name).unwrap_or(html.len());
+                if end < content_end {
+                    nodes.push(HtmlNode::Text(html[end..content_end].to_string()));
+                }
+                pos = content_end;
+            }
+            text_start = pos;
+        }
+
+        if text_start < html.len() {
+            nodes.push(HtmlNode::Text(html[text_start..].to_string()));
+        }
+        Self { nodes }
+    }
+
+    /// Serialize the document.
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        for node in &self.nodes {
+            match node {
+                HtmlNode::Text(text) | HtmlNode::Comment(text) => html.push_str(text),
+                HtmlNode::EndTag { source, .. } => html.push_str(source),
+                HtmlNode::Element(tag) => tag.write(&mut html),
+            }
+        }
+        html
+    }
+
+    /// All start tags, in document order.
+    pub fn elements_mut(&mut self) -> impl Iterator<Item = &mut StartTag> {
+        self.nodes.iter_mut().filter_map(|node| match node {
+            HtmlNode::Element(tag) => Some(tag),
+            _ => None,
+        })
+    }
+
+    /// Insert `html` verbatim before the first `</name>` end tag.
+    ///
+    /// Returns `false` (and inserts nothing) if there is no such end tag.
+    pub fn insert_before_end_tag(&mut self, name: &str, html: impl Into<String>) -> bool {
+        let index = self
+            .nodes
+            .iter()
+            .position(|node| matches!(node, HtmlNode::EndTag { name: n, .. } if n == name));
+        match index {
+            Some(index) => {
+                self.nodes.insert(index, HtmlNode::Text(html.into()));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl StartTag {
+    /// Create a tag with no attributes.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into().to_ascii_lowercase(),
+            attrs: Vec::new(),
+            self_closing: false,
+            source: None,
+        }
+    }
+
+    /// The value of attribute `name`; `Some("")` for a bare attribute.
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|attr| attr.name.eq_ignore_ascii_case(name))
+            .map(|attr| attr.value.as_deref().unwrap_or_default())
+    }
+
+    /// Set attribute `name`, replacing any existing value.
+    pub fn set_attr(&mut self, name: &str, value: impl Into<String>) {
+        let value = Some(value.into());
+        self.source = None;
+        match self
+            .attrs
+            .iter_mut()
+            .find(|attr| attr.name.eq_ignore_ascii_case(name))
+        {
+            Some(attr) => attr.value = value,
+            None => self.attrs.push(HtmlAttr {
+                name: name.to_string(),
+                value,
+            }),
+        }
+    }
+
+    /// Add `token` to the space-separated list in attribute `name` (like
+    /// `class` or `rel`), unless it is already there.
+    pub fn add_to_list_attr(&mut self, name: &str, token: &str) {
+        let current = self.attr(name).unwrap_or_default();
+        if current.split_ascii_whitespace().any(|t| t == token) {
+            return;
+        }
+        let value = if current.trim().is_empty() {
+            token.to_string()
+        } else {
+            format!("{} {}", current.trim(), token)
+        };
+        self.set_attr(name, value);
+    }
+
+    fn write(&self, out: &mut String) {
+        if let Some(source) = &self.source {
+            out.push_str(source);
+            return;
+        }
+        out.push('<');
+        out.push_str(&self.name);
+        for attr in &self.attrs {
+            out.push(' ');
+            out.push_str(&attr.name);
+            if let Some(value) = &attr.value {
+                let quote = if value.contains('"') { '\'' } else { '"' };
+                out.push('=');
+                out.push(quote);
+                out.push_str(value);
+                out.push(quote);
+            }
+        }
+        out.push_str(if self.self_closing { " />" } else { ">" });
+    }
+}
+
+/// Parse a start tag at the beginning of `input` (which starts with `<`),
+/// returning the tag and its length in bytes.
+fn parse_start_tag(input: &str) -> Option<(StartTag, usize)> {
+    let bytes = input.as_bytes();
+    if !bytes.get(1).is_some_and(u8::is_ascii_alphabetic) {
+        return None;
+    }
+    let name_end = scan(bytes, 1, |b| {
+        !b.is_ascii_whitespace() && b != b'/' && b != b'>'
+    });
+    let mut tag = StartTag::new(&input[1..name_end]);
+    let mut pos = name_end;
+
+    loop {
+        pos = scan(bytes, pos, |b| b.is_ascii_whitespace());
+        match bytes.get(pos)? {
+            b'>' => {
+                pos += 1;
+                break;
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'>') => {
+                tag.self_closing = true;
+                pos += 2;
+                break;
+            }
+            b'/' => {
+                pos += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        let attr_end = scan(bytes, pos, |b| {
+            !b.is_ascii_whitespace() && b != b'=' && b != b'>' && b != b'/'
+        });
+        let name = input[pos..attr_end].to_string();
+        pos = scan(bytes, attr_end, |b| b.is_ascii_whitespace());
+
+        let value = if bytes.get(pos) == Some(&b'=') {
+            pos = scan(bytes, pos + 1, |b| b.is_ascii_whitespace());
+            match bytes.get(pos)? {
+                quote @ (b'"' | b'\'') => {
+                    let close = pos + 1 + input[pos + 1..].find(*quote as char)?;
+                    let value = input[pos + 1..close].to_string();
+                    pos = close + 1;
+                    Some(value)
+                }
+                _ => {
+                    let end = scan(bytes, pos, |b| !b.is_ascii_whitespace() && b != b'>');
+                    let value = input[pos..end].to_string();
+                    pos = end;
+                    Some(value)
+                }
+            }
+        } else {
+            None
+        };
+        tag.attrs.push(HtmlAttr { name, value });
+    }
+
+    tag.source = Some(input[..pos].to_string());
+    Some((tag, pos))
+}
+
+/// Parse an end tag from `input` (the text after `</`), returning the
+/// lowercased name and the length of the rest of the tag in bytes.
+fn parse_end_tag(input: &str) -> Option<(String, usize)> {
+    let bytes = input.as_bytes();
+    if !bytes.first().is_some_and(u8::is_ascii_alphabetic) {
+        return None;
+    }
+    let name_end = scan(bytes, 0, |b| !b.is_ascii_whitespace() && b != b'>');
+    let close = input.find('>')?;
+    Some((input[..name_end].to_ascii_lowercase(), close + 1))
+}
+
+/// Find the start of the `</name` end tag at or after `from`.
+fn find_end_tag(html: &str, from: usize, name: &str) -> Option<usize> {
+    let mut pos = from;
+    while let Some(offset) = html[pos..].find("</") {
+        let start = pos + offset;
+        let candidate = &html.as_bytes()[start + 2..];
+        if candidate.len() >= name.len()
+            && candidate[..name.len()].eq_ignore_ascii_case(name.as_bytes())
+        {
+            return Some(start);
+        }
+        pos = start + 2;
+    }
+    None
+}
+
+/// Advance from `pos` while `pred` holds.
+fn scan(bytes: &[u8], mut pos: usize, pred: impl Fn(u8) -> bool) -> usize {
+    while pos < bytes.len() && pred(bytes[pos]) {
+        pos += 1;
+    }
+    pos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<!-- <a href="commented.html"> -->
+<script>if (a < b && "</div>") { x = '<img src=no.png>'; }</script>
+</head>
+<body>
+<p class='lead'>1 < 2 and <img src=a.png alt="A &quot;quote&quot;" /></p>
+<input disabled>
+</body>
+</html>
+"#;
+
+    #[test]
+    fn test_round_trip_is_exact() {
+        assert_eq!(HtmlDocument::parse(PAGE).to_html(), PAGE);
+    }
+
+    #[test]
+    fn test_tags_and_attributes() {
+        let mut doc = HtmlDocument::parse(PAGE);
+        let names: Vec<String> = doc.elements_mut().map(|tag| tag.name.clone()).collect();
+        assert_eq!(
+            names,
+            vec!["html", "head", "script", "body", "p", "img", "input"]
+        );
+
+        let img = doc.elements_mut().find(|tag| tag.name == "img").unwrap();
+        assert_eq!(img.attr("src"), Some("a.png"));
+        assert_eq!(img.attr("ALT"), Some("A &quot;quote&quot;"));
+        assert!(img.self_closing);
+
+        let input = doc.elements_mut().find(|tag| tag.name == "input").unwrap();
+        assert_eq!(input.attr("disabled"), Some(""));
+        assert_eq!(input.attr("value"), None);
+    }
+
+    #[test]
+    fn test_modified_tags_are_reserialized() {
+        let mut doc = HtmlDocument::parse(r#"<a href="x.html" rel=nofollow>x</a>"#);
+        let a = doc.elements_mut().next().unwrap();
+        a.set_attr("href", "y.html");
+        a.add_to_list_attr("rel", "noopener");
+        a.add_to_list_attr("rel", "noopener");
+        a.set_attr("title", r#"say "hi""#);
+
+        assert_eq!(
+            doc.to_html(),
+            r#"<a href="y.html" rel="nofollow noopener" title='say "hi"'>x</a>"#
+        );
+    }
+
+    #[test]
+    fn test_insert_before_end_tag() {
+        let mut doc = HtmlDocument::parse("<head><title>T</title></HEAD><body></body>");
+        assert!(doc.insert_before_end_tag("head", "<meta>"));
+        assert!(!doc.insert_before_end_tag("main", "<x>"));
+        assert_eq!(
+            doc.to_html(),
+            "<head><title>T</title><meta></HEAD><body></body>"
+        );
+    }
+}
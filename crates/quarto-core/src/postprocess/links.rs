@@ -0,0 +1,189 @@
+/*
+ * postprocess/links.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * External link handling.
+ */
+
+//! External link handling.
+//!
+//! With `link-external-newwindow: true`, links to other sites open in a new
+//! window (`target="_blank"`), and every `target="_blank"` link to another
+//! site gets `rel="noopener"` so the opened page can't reach back through
+//! `window.opener`. With `link-external-icon: true`, external links get the
+//! `external` class and a small arrow after them.
+//!
+//! Links to the website's own `site-url` are not external.
+
+use super::{HtmlDocument, HtmlPostprocessor, PostprocessContext};
+use crate::Result;
+
+/// Style for `link-external-icon`.
+const EXTERNAL_ICON_CSS: &str = "<style>\na.external::after { content: \"\\2197\"; font-size: 0.75em; margin-left: 0.15em; }\n</style>\n";
+
+/// Post-processor for `link-external-newwindow` and `link-external-icon`.
+pub struct ExternalLinkPostprocessor;
+
+impl ExternalLinkPostprocessor {
+    /// Create a new external link post-processor.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ExternalLinkPostprocessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HtmlPostprocessor for ExternalLinkPostprocessor {
+    fn name(&self) -> &str {
+        "external-links"
+    }
+
+    fn process(&self, doc: &mut HtmlDocument, ctx: &mut PostprocessContext<'_>) -> Result<()> {
+        let enabled = |key: &str| {
+            ctx.format_option(key)
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+        };
+        let new_window = enabled("link-external-newwindow");
+        let icon = enabled("link-external-icon");
+        let site_host = ctx
+            .website_option("site-url")
+            .and_then(|v| v.as_str())
+            .and_then(url_host)
+            .map(str::to_ascii_lowercase);
+
+        let mut has_icons = false;
+        for tag in doc.elements_mut().filter(|tag| tag.name == "a") {
+            let Some(host) = tag.attr("href").and_then(url_host) else {
+                continue;
+            };
+            if site_host
+                .as_deref()
+                .is_some_and(|site| host.eq_ignore_ascii_case(site))
+            {
+                continue;
+            }
+
+            if new_window && tag.attr("target").is_none() {
+                tag.set_attr("target", "_blank");
+            }
+            if tag.attr("target") == Some("_blank") {
+                tag.add_to_list_attr("rel", "noopener");
+            }
+            if icon {
+                tag.add_to_list_attr("class", "external");
+                has_icons = true;
+            }
+        }
+
+        if has_icons {
+            doc.insert_before_end_tag("head", EXTERNAL_ICON_CSS);
+        }
+        Ok(())
+    }
+}
+
+/// The host of an absolute `http(s)` URL (or protocol-relative `//` URL).
+fn url_host(href: &str) -> Option<&str> {
+    let href = href.trim();
+    let lower = href.get(..8).unwrap_or(href).to_ascii_lowercase();
+    let rest = if lower.starts_with("https://") {
+        &href[8..]
+    } else if lower.starts_with("http://") {
+        &href[7..]
+    } else {
+        href.strip_prefix("//")?
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    // Drop any credentials and port
+    let host = host.rsplit('@').next().unwrap_or(host);
+    let host = host.split(':').next().unwrap_or(host);
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{ProjectConfig, ProjectContext};
+    use quarto_system_runtime::NativeRuntime;
+    use serde_json::json;
+    use std::path::{Path, PathBuf};
+
+    fn process(html: &str, options: serde_json::Value, website: serde_json::Value) -> String {
+        let runtime = NativeRuntime::new();
+        let format = Format {
+            metadata: options,
+            ..Format::html()
+        };
+        let project = ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: Some(ProjectConfig {
+                raw: json!({ "website": website }),
+                ..ProjectConfig::default()
+            }),
+            is_single_file: false,
+            files: vec![],
+            output_dir: PathBuf::from("/project/_site"),
+        };
+        let mut ctx = PostprocessContext::new(
+            &runtime,
+            &format,
+            &project,
+            Path::new("/project/index.qmd"),
+            Path::new("/project/_site/index.html"),
+        );
+        let mut doc = HtmlDocument::parse(html);
+        ExternalLinkPostprocessor::new()
+            .process(&mut doc, &mut ctx)
+            .unwrap();
+        doc.to_html()
+    }
+
+    const LINKS: &str = concat!(
+        "<head></head>",
+        r#"<a href="https://example.com/page">ext</a>"#,
+        r#"<a href="https://mysite.org/about.html">own</a>"#,
+        r#"<a href="about.html">rel</a>"#,
+        r#"<a href="http://other.net" target="_blank">blank</a>"#,
+    );
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://Example.com:8080/a?b"),
+            Some("Example.com")
+        );
+        assert_eq!(url_host("//cdn.example.com/x.js"), Some("cdn.example.com"));
+        assert_eq!(url_host("mailto:me@example.com"), None);
+        assert_eq!(url_host("about.html"), None);
+    }
+
+    #[test]
+    fn test_defaults_only_add_noopener() {
+        let html = process(LINKS, json!(null), json!({}));
+        assert_eq!(
+            html,
+            LINKS.replace(r#"target="_blank">"#, r#"target="_blank" rel="noopener">"#)
+        );
+    }
+
+    #[test]
+    fn test_new_window_and_icon() {
+        let html = process(
+            LINKS,
+            json!({ "link-external-newwindow": true, "link-external-icon": true }),
+            json!({ "site-url": "https://mysite.org/" }),
+        );
+        assert!(html.contains(
+            r#"<a href="https://example.com/page" target="_blank" rel="noopener" class="external">ext</a>"#
+        ));
+        assert!(html.contains(r#"<a href="https://mysite.org/about.html">own</a>"#));
+        assert!(html.contains(r#"<a href="about.html">rel</a>"#));
+        assert!(html.contains("a.external::after"));
+    }
+}
@@ -0,0 +1,118 @@
+/*
+ * postprocess/mod.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * HTML post-processing.
+ */
+
+//! HTML post-processing.
+//!
+//! Once the template has been applied, the complete HTML document is run
+//! through a sequence of [`HtmlPostprocessor`]s that work on a parsed
+//! [`HtmlDocument`] rather than on the raw string:
+//!
+//! - [`ResourcePostprocessor`] - Resolves relative resource references and
+//!   copies the referenced files next to the output
+//! - [`ExternalLinkPostprocessor`] - Applies `link-external-newwindow` and
+//!   `link-external-icon` to external links
+//! - [`AnalyticsPostprocessor`] - Injects the `google-analytics` snippet
+//!
+//! The sequence is run by
+//! [`PostprocessHtmlStage`](crate::stage::stages::PostprocessHtmlStage).
+
+mod analytics;
+mod html;
+mod links;
+mod resources;
+
+use std::path::{Path, PathBuf};
+
+use quarto_system_runtime::SystemRuntime;
+
+use crate::Result;
+use crate::format::Format;
+use crate::project::ProjectContext;
+
+pub use analytics::AnalyticsPostprocessor;
+pub use html::{HtmlDocument, HtmlNode, StartTag};
+pub use links::ExternalLinkPostprocessor;
+pub use resources::ResourcePostprocessor;
+
+/// A post-processing step over a rendered HTML document.
+pub trait HtmlPostprocessor: Send + Sync {
+    /// Name of the post-processor (for tracing).
+    fn name(&self) -> &str;
+
+    /// Process the document in place.
+    fn process(&self, doc: &mut HtmlDocument, ctx: &mut PostprocessContext<'_>) -> Result<()>;
+}
+
+/// What post-processors know about the document being processed.
+pub struct PostprocessContext<'a> {
+    /// Runtime used to inspect and copy files
+    pub runtime: &'a dyn SystemRuntime,
+
+    /// Target format (its metadata holds the `format.html` options)
+    pub format: &'a Format,
+
+    /// Project the document belongs to
+    pub project: &'a ProjectContext,
+
+    /// Source document
+    pub input_path: &'a Path,
+
+    /// Where the HTML will be written
+    pub output_path: &'a Path,
+
+    /// Files the output depends on, as written to the output directory
+    pub supporting_files: Vec<PathBuf>,
+
+    /// Notes about skipped work, reported as pipeline trace events
+    pub events: Vec<String>,
+}
+
+impl<'a> PostprocessContext<'a> {
+    /// Create a context for rendering `input_path` to `output_path`.
+    pub fn new(
+        runtime: &'a dyn SystemRuntime,
+        format: &'a Format,
+        project: &'a ProjectContext,
+        input_path: &'a Path,
+        output_path: &'a Path,
+    ) -> Self {
+        Self {
+            runtime,
+            format,
+            project,
+            input_path,
+            output_path,
+            supporting_files: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// A `format.html` option.
+    pub fn format_option(&self, key: &str) -> Option<&serde_json::Value> {
+        if self.format.metadata.is_null() {
+            return None;
+        }
+        self.format.metadata.get(key)
+    }
+
+    /// An option of the project's `website:` (or `book:`) configuration.
+    pub fn website_option(&self, key: &str) -> Option<&serde_json::Value> {
+        let raw = &self.project.config.as_ref()?.raw;
+        raw.get("website")
+            .and_then(|website| website.get(key))
+            .or_else(|| raw.get("book").and_then(|book| book.get(key)))
+    }
+}
+
+/// The default post-processors, in the order they run.
+pub fn default_postprocessors() -> Vec<Box<dyn HtmlPostprocessor>> {
+    vec![
+        Box::new(ResourcePostprocessor::new()),
+        Box::new(ExternalLinkPostprocessor::new()),
+        Box::new(AnalyticsPostprocessor::new()),
+    ]
+}
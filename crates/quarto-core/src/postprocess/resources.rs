@@ -0,0 +1,395 @@
+/*
+ * postprocess/resources.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Resolve and copy resources referenced by the rendered HTML.
+ */
+
+//! Resolve and copy resources referenced by the rendered HTML.
+//!
+//! Relative `src`/`href` references are resolved against the source
+//! document. A referenced file inside the project is copied to the same
+//! place under the output directory, so the reference keeps working when
+//! the output is written elsewhere (like `_site/`). A file outside the
+//! project can't be mirrored, so its reference is rewritten to point back
+//! at the original from the output location instead.
+//!
+//! References into the document's `{stem}_files/` directory are left alone:
+//! those files (themes, fonts) are generated straight into the output.
+
+use std::path::{Component, Path, PathBuf};
+
+use quarto_system_runtime::PathKind;
+
+use super::{HtmlDocument, HtmlPostprocessor, PostprocessContext};
+use crate::Result;
+
+/// Attributes that reference resources, by element.
+const RESOURCE_ATTRIBUTES: &[(&str, &str)] = &[
+    ("a", "href"),
+    ("audio", "src"),
+    ("embed", "src"),
+    ("img", "src"),
+    ("link", "href"),
+    ("object", "data"),
+    ("script", "src"),
+    ("source", "src"),
+    ("track", "src"),
+    ("video", "poster"),
+    ("video", "src"),
+];
+
+/// Link targets that are rendered documents rather than resources.
+const DOCUMENT_EXTENSIONS: &[&str] = &["html", "htm", "qmd", "md", "ipynb", "rmd"];
+
+/// Post-processor that resolves relative resource references and copies
+/// the referenced files to the output directory.
+///
+/// Copied files are recorded in
+/// [`PostprocessContext::supporting_files`].
+pub struct ResourcePostprocessor;
+
+impl ResourcePostprocessor {
+    /// Create a new resource post-processor.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ResourcePostprocessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HtmlPostprocessor for ResourcePostprocessor {
+    fn name(&self) -> &str {
+        "resources"
+    }
+
+    fn process(&self, doc: &mut HtmlDocument, ctx: &mut PostprocessContext<'_>) -> Result<()> {
+        let input_dir = normalize(ctx.input_path.parent().unwrap_or(Path::new(".")));
+        let output_dir = normalize(ctx.output_path.parent().unwrap_or(Path::new(".")));
+        let project_dir = normalize(&ctx.project.dir);
+        let output_root = output_root(&input_dir, &output_dir, &project_dir);
+        // Generated resources (`{stem}_files/`) are written to the output
+        // directly; a stale copy next to the source must not replace them
+        let generated_dir = ctx
+            .output_path
+            .file_stem()
+            .map(|stem| format!("{}_files/", stem.to_string_lossy()));
+
+        for tag in doc.elements_mut() {
+            for (element, attr) in RESOURCE_ATTRIBUTES {
+                if tag.name != *element {
+                    continue;
+                }
+                let Some(value) = tag.attr(attr).map(str::to_string) else {
+                    continue;
+                };
+                let Some((path, suffix)) = local_reference(&value) else {
+                    continue;
+                };
+                if (*element == "a" && is_document(path))
+                    || generated_dir
+                        .as_deref()
+                        .is_some_and(|dir| path.starts_with(dir))
+                {
+                    continue;
+                }
+
+                let source = normalize(&input_dir.join(percent_decode(path)));
+                if !ctx
+                    .runtime
+                    .path_exists(&source, Some(PathKind::File))
+                    .unwrap_or(false)
+                {
+                    continue;
+                }
+
+                let target = match source.strip_prefix(&project_dir) {
+                    // Files already in the output directory stay where they are
+                    Ok(_) if output_root != project_dir && source.starts_with(&output_root) => {
+                        source.clone()
+                    }
+                    Ok(relative) => {
+                        let target = output_root.join(relative);
+                        copy_resource(&source, &target, ctx);
+                        target
+                    }
+                    Err(_) => {
+                        ctx.events.push(format!(
+                            "not copying {} (outside the project)",
+                            source.display()
+                        ));
+                        source.clone()
+                    }
+                };
+
+                let resolved = relative_path(&output_dir, &target).replace(' ', "%20");
+                if resolved != path {
+                    let resolved = format!("{}{}", resolved, suffix);
+                    tag.set_attr(attr, resolved);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copy `source` to `target` (once per render), recording the copy.
+fn copy_resource(source: &Path, target: &Path, ctx: &mut PostprocessContext<'_>) {
+    if ctx.supporting_files.iter().any(|file| file == target) {
+        return;
+    }
+    if source != target {
+        let copied = match target.parent() {
+            Some(parent) => ctx.runtime.dir_create(parent, true),
+            None => Ok(()),
+        }
+        .and_then(|_| ctx.runtime.file_copy(source, target));
+        if let Err(e) = copied {
+            ctx.events
+                .push(format!("failed to copy {}: {}", source.display(), e));
+            return;
+        }
+    }
+    ctx.supporting_files.push(target.to_path_buf());
+}
+
+/// Split a reference to a local file into its path and its query/fragment
+/// suffix. URLs, site-absolute paths and fragments are not local.
+fn local_reference(value: &str) -> Option<(&str, &str)> {
+    let value = value.trim();
+    if value.is_empty() || value.starts_with(['#', '/', '\\', '$', '{']) {
+        return None;
+    }
+    let end = value.find(['?', '#']).unwrap_or(value.len());
+    let (path, suffix) = value.split_at(end);
+    // A scheme (`https:`, `mailto:`, `data:`, ...) comes before any slash
+    let scheme = path
+        .find(':')
+        .is_some_and(|colon| path.find('/').is_none_or(|slash| colon < slash));
+    if path.is_empty() || scheme {
+        return None;
+    }
+    Some((path, suffix))
+}
+
+fn is_document(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            DOCUMENT_EXTENSIONS
+                .iter()
+                .any(|d| d.eq_ignore_ascii_case(ext))
+        })
+}
+
+/// Decode `%XX` escapes; invalid escapes are kept as written.
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| path.to_string())
+}
+
+/// Where the project root lands in the output: the output directory minus
+/// the document's subdirectory within the project.
+fn output_root(input_dir: &Path, output_dir: &Path, project_dir: &Path) -> PathBuf {
+    let Ok(subdir) = input_dir.strip_prefix(project_dir) else {
+        return output_dir.to_path_buf();
+    };
+    let depth = subdir.components().count();
+    if depth > 0 && output_dir.ends_with(subdir) {
+        output_dir
+            .ancestors()
+            .nth(depth)
+            .unwrap_or(output_dir)
+            .to_path_buf()
+    } else {
+        output_dir.to_path_buf()
+    }
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push("..");
+                }
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// The `/`-separated path from directory `from` to `to`.
+fn relative_path(from: &Path, to: &Path) -> String {
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::ProjectContext;
+    use quarto_system_runtime::NativeRuntime;
+
+    fn project(dir: &Path, output_dir: &Path) -> ProjectContext {
+        ProjectContext {
+            dir: dir.to_path_buf(),
+            config: None,
+            is_single_file: false,
+            files: vec![],
+            output_dir: output_dir.to_path_buf(),
+        }
+    }
+
+    fn process(
+        html: &str,
+        project: &ProjectContext,
+        input: &Path,
+        output: &Path,
+    ) -> (String, Vec<PathBuf>) {
+        let runtime = NativeRuntime::new();
+        let format = Format::html();
+        let mut ctx = PostprocessContext::new(&runtime, &format, project, input, output);
+        let mut doc = HtmlDocument::parse(html);
+        ResourcePostprocessor::new()
+            .process(&mut doc, &mut ctx)
+            .unwrap();
+        (doc.to_html(), ctx.supporting_files)
+    }
+
+    #[test]
+    fn test_local_reference() {
+        assert_eq!(local_reference("img/a.png"), Some(("img/a.png", "")));
+        assert_eq!(
+            local_reference("data.csv?v=2#top"),
+            Some(("data.csv", "?v=2#top"))
+        );
+        assert_eq!(local_reference("https://example.com/a.png"), None);
+        assert_eq!(local_reference("mailto:me@example.com"), None);
+        assert_eq!(local_reference("data:image/png;base64,AAAA"), None);
+        assert_eq!(local_reference("//cdn.example.com/x.js"), None);
+        assert_eq!(local_reference("/about.html"), None);
+        assert_eq!(local_reference("#sec-intro"), None);
+    }
+
+    #[test]
+    fn test_paths() {
+        assert_eq!(percent_decode("my%20file%2x.png"), "my file%2x.png");
+        assert_eq!(normalize(Path::new("/a/./b/../c")), PathBuf::from("/a/c"));
+        assert_eq!(
+            relative_path(Path::new("/p/_site/posts"), Path::new("/p/_site/img/a.png")),
+            "../img/a.png"
+        );
+        assert_eq!(
+            output_root(
+                Path::new("/p/posts"),
+                Path::new("/p/_site/posts"),
+                Path::new("/p")
+            ),
+            PathBuf::from("/p/_site")
+        );
+    }
+
+    #[test]
+    fn test_copies_resources_into_output_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("posts")).unwrap();
+        std::fs::create_dir_all(dir.join("img")).unwrap();
+        std::fs::write(dir.join("img/logo one.png"), "png").unwrap();
+        std::fs::write(dir.join("posts/data.csv"), "a,b").unwrap();
+
+        let site = dir.join("_site");
+        let project = project(dir, &site);
+        let html = concat!(
+            r#"<img src="./../img/logo%20one.png">"#,
+            r#"<a href="data.csv#row-2">data</a>"#,
+            r#"<a href="other.qmd">other</a>"#,
+            r#"<img src="missing.png">"#,
+            r#"<img src="https://example.com/x.png">"#,
+        );
+        let (html, files) = process(
+            html,
+            &project,
+            &dir.join("posts/index.qmd"),
+            &site.join("posts/index.html"),
+        );
+
+        assert_eq!(
+            html,
+            concat!(
+                r#"<img src="../img/logo%20one.png">"#,
+                r#"<a href="data.csv#row-2">data</a>"#,
+                r#"<a href="other.qmd">other</a>"#,
+                r#"<img src="missing.png">"#,
+                r#"<img src="https://example.com/x.png">"#,
+            )
+        );
+        assert_eq!(
+            files,
+            vec![site.join("img/logo one.png"), site.join("posts/data.csv")]
+        );
+        assert_eq!(
+            std::fs::read_to_string(site.join("posts/data.csv")).unwrap(),
+            "a,b"
+        );
+        assert!(site.join("img/logo one.png").exists());
+    }
+
+    #[test]
+    fn test_rewrites_references_outside_the_project() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("doc")).unwrap();
+        std::fs::write(dir.join("shared.png"), "png").unwrap();
+
+        let out = dir.join("doc/out");
+        let project = project(&dir.join("doc"), &dir.join("doc"));
+        let (html, files) = process(
+            r#"<img src="../shared.png">"#,
+            &project,
+            &dir.join("doc/index.qmd"),
+            &out.join("index.html"),
+        );
+
+        assert_eq!(html, r#"<img src="../../shared.png">"#);
+        assert!(files.is_empty());
+    }
+}
@@ -13,15 +13,19 @@
 //!
 //! The project context provides:
 //! - Project root directory
-//! - Parsed configuration
+//! - Parsed configuration, with the active profiles' `_quarto-{profile}.yml`
+//!   merged in (see [`quarto_config::active_profiles`])
 //! - List of input files
 //! - Output directory resolution
 
 use std::path::{Path, PathBuf};
 
+use quarto_error_reporting::DiagnosticMessage;
 use quarto_pandoc_types::ConfigValue;
+use quarto_source_map::{SourceContext, SourceInfo};
 use quarto_system_runtime::SystemRuntime;
 
+use crate::book::Book;
 use crate::error::{QuartoError, Result};
 
 /// Project type enumeration
@@ -79,6 +83,15 @@ pub struct ProjectConfig {
     /// Raw configuration value for format-specific settings
     pub raw: serde_json::Value,
 
+    /// Active profiles, in order of increasing precedence
+    pub profiles: Vec<String>,
+
+    /// The configuration files read, which `diagnostics` point into
+    pub source_context: SourceContext,
+
+    /// Problems found validating the configuration against the schema
+    pub diagnostics: Vec<DiagnosticMessage>,
+
     /// Format configuration for merging with document metadata.
     ///
     /// This is used by the render pipeline to merge project-level format settings
@@ -100,6 +113,22 @@ impl ProjectConfig {
     }
 }
 
+/// Parse the YAML front matter at the start of a source file, if any.
+pub(crate) fn front_matter(source: &str) -> Option<serde_json::Value> {
+    let rest = source
+        .strip_prefix("---\n")
+        .or_else(|| source.strip_prefix("---\r\n"))?;
+    let end = rest.find("\n---")?;
+    serde_yaml::from_str(&rest[..end]).ok()
+}
+
+/// The text of the first level-one ATX heading, without its attributes.
+fn first_heading(source: &str) -> Option<String> {
+    let line = source.lines().find(|line| line.starts_with("# "))?;
+    let text = line[2..].split(" {").next().unwrap_or_default().trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
 /// Information about a document to be rendered
 #[derive(Debug, Clone)]
 pub struct DocumentInfo {
@@ -165,7 +194,21 @@ impl ProjectContext {
     /// If the path is a directory, looks for `_quarto.yml` in that directory and parents.
     ///
     /// If no `_quarto.yml` is found, creates a single-file pseudo-project.
+    ///
+    /// Profiles are taken from `QUARTO_PROFILE` or the configuration's
+    /// `profile: default:`; use [`ProjectContext::discover_with_profiles`]
+    /// for `--profile`.
     pub fn discover(path: impl AsRef<Path>, runtime: &dyn SystemRuntime) -> Result<Self> {
+        Self::discover_with_profiles(path, &[], runtime)
+    }
+
+    /// Discover project context from a path, with the profiles given on the
+    /// command line (`--profile`), which take the place of `QUARTO_PROFILE`.
+    pub fn discover_with_profiles(
+        path: impl AsRef<Path>,
+        profiles: &[String],
+        runtime: &dyn SystemRuntime,
+    ) -> Result<Self> {
         let path = path.as_ref();
 
         // Canonicalize the path
@@ -198,7 +241,7 @@ impl ProjectContext {
         };
 
         // Search for _quarto.yml
-        let (project_dir, config) = Self::find_project_config(&search_dir, runtime)?;
+        let (project_dir, config) = Self::find_project_config(&search_dir, profiles, runtime)?;
 
         // Determine if this is a single-file project
         let is_single_file = config.is_none() && input_file.is_some();
@@ -216,8 +259,7 @@ impl ProjectContext {
         let files = if let Some(input) = input_file {
             vec![DocumentInfo::from_path(input)]
         } else {
-            // TODO: Discover files based on project configuration
-            Vec::new()
+            Self::discover_files(&dir, config.as_ref(), &output_dir, runtime)?
         };
 
         Ok(Self {
@@ -251,9 +293,121 @@ impl ProjectContext {
         })
     }
 
+    /// Find the input files of a project directory.
+    ///
+    /// Collects `.qmd` and `.md` files recursively, skipping files and
+    /// directories whose names start with `_` or `.` as well as the output
+    /// directory. If the project sets `project: render:`, only files matching
+    /// those globs (relative to the project directory) are kept, and
+    /// `!`-prefixed globs exclude files. Files are returned sorted by path.
+    fn discover_files(
+        dir: &Path,
+        config: Option<&ProjectConfig>,
+        output_dir: &Path,
+        runtime: &dyn SystemRuntime,
+    ) -> Result<Vec<DocumentInfo>> {
+        // Books render their chapters, in reading order
+        if let Some(config) = config
+            && config.project_type == ProjectType::Book
+            && let Some(book) = config.raw.get("book")
+            && book.get("chapters").is_some()
+        {
+            let inputs = Book::from_config(dir, book)
+                .chapters
+                .into_iter()
+                .map(|chapter| chapter.input);
+            return Ok(Self::document_infos(inputs, runtime));
+        }
+
+        let mut inputs = Vec::new();
+        Self::collect_inputs(dir, output_dir, runtime, &mut inputs)?;
+
+        let patterns = config.map_or(&[][..], |c| c.render_patterns.as_slice());
+        if !patterns.is_empty() {
+            let compile = |pattern: &str| {
+                glob::Pattern::new(pattern).map_err(|e| {
+                    QuartoError::Other(format!("Invalid render pattern '{}': {}", pattern, e))
+                })
+            };
+            let mut include = Vec::new();
+            let mut exclude = Vec::new();
+            for pattern in patterns {
+                match pattern.strip_prefix('!') {
+                    Some(negated) => exclude.push(compile(negated)?),
+                    None => include.push(compile(pattern)?),
+                }
+            }
+
+            inputs.retain(|input| {
+                let relative = input.strip_prefix(dir).unwrap_or(input);
+                let matches = |p: &glob::Pattern| p.matches_path(relative);
+                (include.is_empty() || include.iter().any(matches)) && !exclude.iter().any(matches)
+            });
+        }
+
+        inputs.sort();
+        Ok(Self::document_infos(inputs, runtime))
+    }
+
+    /// Document infos for input files, with titles from their front matter.
+    fn document_infos(
+        inputs: impl IntoIterator<Item = PathBuf>,
+        runtime: &dyn SystemRuntime,
+    ) -> Vec<DocumentInfo> {
+        inputs
+            .into_iter()
+            .map(|input| {
+                let title = runtime.file_read_string(&input).ok().and_then(|source| {
+                    front_matter(&source)
+                        .and_then(|meta| meta.get("title")?.as_str().map(String::from))
+                        .or_else(|| first_heading(&source))
+                });
+                DocumentInfo {
+                    title,
+                    ..DocumentInfo::from_path(input)
+                }
+            })
+            .collect()
+    }
+
+    /// Recursively collect renderable files below `dir`.
+    fn collect_inputs(
+        dir: &Path,
+        output_dir: &Path,
+        runtime: &dyn SystemRuntime,
+        inputs: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        let entries = runtime
+            .dir_list(dir)
+            .map_err(|e| QuartoError::Other(format!("Failed to list {}: {}", dir.display(), e)))?;
+
+        for entry in entries {
+            let name = entry
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if name.starts_with('_') || name.starts_with('.') {
+                continue;
+            }
+
+            if runtime.is_dir(&entry).unwrap_or(false) {
+                if entry != output_dir {
+                    Self::collect_inputs(&entry, output_dir, runtime, inputs)?;
+                }
+            } else if matches!(
+                entry.extension().and_then(|e| e.to_str()),
+                Some("qmd" | "md")
+            ) {
+                inputs.push(entry);
+            }
+        }
+        Ok(())
+    }
+
     /// Search for `_quarto.yml` in directory and parents
     fn find_project_config(
         start_dir: &Path,
+        profiles: &[String],
         runtime: &dyn SystemRuntime,
     ) -> Result<(Option<PathBuf>, Option<ProjectConfig>)> {
         let mut current = start_dir.to_path_buf();
@@ -265,7 +419,7 @@ impl ProjectContext {
                 .map_err(|e| QuartoError::Other(format!("Failed to check config path: {}", e)))?;
             if exists {
                 // Found config file - parse it
-                let config = Self::parse_config(&config_path, runtime)?;
+                let config = Self::parse_config(&config_path, profiles, runtime)?;
                 return Ok((Some(current), Some(config)));
             }
 
@@ -275,7 +429,7 @@ impl ProjectContext {
                 .path_exists(&config_path_yaml, None)
                 .map_err(|e| QuartoError::Other(format!("Failed to check config path: {}", e)))?;
             if exists_yaml {
-                let config = Self::parse_config(&config_path_yaml, runtime)?;
+                let config = Self::parse_config(&config_path_yaml, profiles, runtime)?;
                 return Ok((Some(current), Some(config)));
             }
 
@@ -289,16 +443,44 @@ impl ProjectContext {
         }
     }
 
-    /// Parse a `_quarto.yml` file
-    fn parse_config(path: &Path, runtime: &dyn SystemRuntime) -> Result<ProjectConfig> {
-        let content = runtime
-            .file_read_string(path)
-            .map_err(|e| QuartoError::Other(format!("Failed to read config file: {}", e)))?;
+    /// Parse a `_quarto.yml` file, merging in the `_quarto-{profile}.yml`
+    /// files of the active profiles
+    fn parse_config(
+        path: &Path,
+        cli_profiles: &[String],
+        runtime: &dyn SystemRuntime,
+    ) -> Result<ProjectConfig> {
+        let mut source_context = SourceContext::new();
+        let base = Self::parse_config_layer(path, runtime, &mut source_context)?;
+
+        let env_profiles = runtime
+            .env_get(quarto_config::QUARTO_PROFILE_ENV)
+            .ok()
+            .flatten();
+        let profiles = quarto_config::active_profiles(cli_profiles, env_profiles.as_deref(), &base);
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut overlays = Vec::new();
+        for profile in &profiles {
+            let overlay_path = dir.join(quarto_config::profile_config_file(profile));
+            let exists = runtime
+                .path_exists(&overlay_path, None)
+                .map_err(|e| QuartoError::Other(format!("Failed to check config path: {}", e)))?;
+            if exists {
+                overlays.push(Self::parse_config_layer(
+                    &overlay_path,
+                    runtime,
+                    &mut source_context,
+                )?);
+            }
+        }
 
-        // Parse YAML
-        let value: serde_json::Value = serde_yaml::from_str(&content).map_err(|e| {
-            QuartoError::Other(format!("Failed to parse {}: {}", path.display(), e))
-        })?;
+        let merged =
+            quarto_config::merge_profile_layers(&base, &overlays, &profiles).map_err(|e| {
+                QuartoError::Other(format!("Failed to merge {}: {}", path.display(), e))
+            })?;
+        let diagnostics = quarto_config::validate_config(&merged, &source_context);
+        let value = merged.to_json();
 
         // Extract project configuration
         let project = value
@@ -332,10 +514,31 @@ impl ProjectContext {
             output_dir,
             render_patterns,
             raw: value,
+            profiles,
+            source_context,
+            diagnostics,
             format_config: None, // TODO: Parse with quarto-config for full source tracking
         })
     }
 
+    /// Parse one configuration file, adding it to `source_context`
+    fn parse_config_layer(
+        path: &Path,
+        runtime: &dyn SystemRuntime,
+        source_context: &mut SourceContext,
+    ) -> Result<ConfigValue> {
+        let content = runtime
+            .file_read_string(path)
+            .map_err(|e| QuartoError::Other(format!("Failed to read config file: {}", e)))?;
+        let file_id =
+            source_context.add_file(path.to_string_lossy().into_owned(), Some(content.clone()));
+        let parent = SourceInfo::original(file_id, 0, content.len());
+
+        let mut diagnostics = Vec::new();
+        quarto_config::config_value_from_source(&content, parent, &mut diagnostics)
+            .map_err(|e| QuartoError::Other(format!("Failed to parse {}: {}", path.display(), e)))
+    }
+
     /// Get the project type
     pub fn project_type(&self) -> ProjectType {
         self.config
@@ -652,4 +855,200 @@ mod tests {
 
         assert!(!context.is_multi_document());
     }
+
+    // === Project file discovery tests ===
+
+    fn write_files(dir: &Path, files: &[&str]) {
+        for file in files {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "# Title\n").unwrap();
+        }
+    }
+
+    fn relative_inputs(project: &ProjectContext) -> Vec<String> {
+        project
+            .files
+            .iter()
+            .map(|f| {
+                f.input
+                    .strip_prefix(&project.dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_discover_project_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let runtime = quarto_system_runtime::NativeRuntime::new();
+        std::fs::write(
+            temp.path().join("_quarto.yml"),
+            "project:\n  type: website\n",
+        )
+        .unwrap();
+        write_files(
+            temp.path(),
+            &[
+                "index.qmd",
+                "about.md",
+                "posts/first.qmd",
+                "_drafts/draft.qmd",
+                "_partial.qmd",
+                ".hidden/secret.qmd",
+                "data.csv",
+            ],
+        );
+
+        let project = ProjectContext::discover(temp.path(), &runtime).unwrap();
+        assert!(!project.is_single_file);
+        assert_eq!(
+            relative_inputs(&project),
+            vec!["about.md", "index.qmd", "posts/first.qmd"]
+        );
+    }
+
+    #[test]
+    fn test_discover_project_files_with_render_patterns() {
+        let temp = tempfile::tempdir().unwrap();
+        let runtime = quarto_system_runtime::NativeRuntime::new();
+        std::fs::write(
+            temp.path().join("_quarto.yml"),
+            "project:\n  render:\n    - \"*.qmd\"\n    - \"posts/*.qmd\"\n    - \"!posts/old.qmd\"\n",
+        )
+        .unwrap();
+        write_files(
+            temp.path(),
+            &["index.qmd", "notes.md", "posts/new.qmd", "posts/old.qmd"],
+        );
+
+        let project = ProjectContext::discover(temp.path(), &runtime).unwrap();
+        assert_eq!(
+            relative_inputs(&project),
+            vec!["index.qmd", "posts/new.qmd"]
+        );
+    }
+
+    #[test]
+    fn test_discover_project_with_profiles() {
+        let temp = tempfile::tempdir().unwrap();
+        let runtime = quarto_system_runtime::NativeRuntime::new();
+        std::fs::write(
+            temp.path().join("_quarto.yml"),
+            "project:\n  type: website\n  output-dir: _site\nprofile:\n  default: draft\nwebsite:\n  title: Site\n  navbar:\n    - text: Home\n    - when-profile: production\n      text: Status\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("_quarto-production.yml"),
+            "project:\n  output-dir: _prod\nwebsite:\n  title: Production Site\n",
+        )
+        .unwrap();
+        write_files(temp.path(), &["index.qmd"]);
+
+        let project = ProjectContext::discover(temp.path(), &runtime).unwrap();
+        let config = project.config.as_ref().unwrap();
+        assert_eq!(config.profiles, vec!["draft"]);
+        assert_eq!(config.raw["website"]["title"], "Site");
+        assert_eq!(config.raw["website"]["navbar"].as_array().unwrap().len(), 1);
+        assert!(project.output_dir.ends_with("_site"));
+
+        let profiles = vec!["production".to_string()];
+        let project =
+            ProjectContext::discover_with_profiles(temp.path(), &profiles, &runtime).unwrap();
+        let config = project.config.as_ref().unwrap();
+        assert_eq!(config.profiles, profiles);
+        assert_eq!(config.raw["website"]["title"], "Production Site");
+        assert_eq!(
+            config.raw["website"]["navbar"][1],
+            serde_json::json!({ "text": "Status" })
+        );
+        assert_eq!(config.project_type, ProjectType::Website);
+        assert!(project.output_dir.ends_with("_prod"));
+        assert!(config.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_discover_project_validates_config() {
+        let temp = tempfile::tempdir().unwrap();
+        let runtime = quarto_system_runtime::NativeRuntime::new();
+        std::fs::write(
+            temp.path().join("_quarto.yml"),
+            "project:\n  type: website\nformat:\n  html:\n    toc: true\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("_quarto-print.yml"),
+            "project:\n  outputdir: _print\n",
+        )
+        .unwrap();
+        write_files(temp.path(), &["index.qmd"]);
+
+        let profiles = vec!["print".to_string()];
+        let project =
+            ProjectContext::discover_with_profiles(temp.path(), &profiles, &runtime).unwrap();
+        let config = project.config.as_ref().unwrap();
+        assert_eq!(config.diagnostics.len(), 1);
+        let warning = &config.diagnostics[0];
+        assert_eq!(
+            warning.kind,
+            quarto_error_reporting::DiagnosticKind::Warning
+        );
+        let mapped = warning
+            .location
+            .as_ref()
+            .unwrap()
+            .map_offset(0, &config.source_context)
+            .unwrap();
+        let file = config.source_context.get_file(mapped.file_id).unwrap();
+        assert!(file.path.ends_with("_quarto-print.yml"));
+    }
+
+    #[test]
+    fn test_discover_project_file_titles() {
+        let temp = tempfile::tempdir().unwrap();
+        let runtime = quarto_system_runtime::NativeRuntime::new();
+        std::fs::write(
+            temp.path().join("_quarto.yml"),
+            "project:\n  type: website\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("index.qmd"),
+            "---\ntitle: Home Page\n---\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("intro.qmd"),
+            "# Introduction {#sec-intro}\n",
+        )
+        .unwrap();
+        std::fs::write(temp.path().join("notes.md"), "No front matter\n").unwrap();
+
+        let project = ProjectContext::discover(temp.path(), &runtime).unwrap();
+        let titles: Vec<Option<&str>> = project.files.iter().map(|f| f.title.as_deref()).collect();
+        assert_eq!(titles, vec![Some("Home Page"), Some("Introduction"), None]);
+    }
+
+    #[test]
+    fn test_discover_book_chapters_in_reading_order() {
+        let temp = tempfile::tempdir().unwrap();
+        let runtime = quarto_system_runtime::NativeRuntime::new();
+        std::fs::write(
+            temp.path().join("_quarto.yml"),
+            "project:\n  type: book\nbook:\n  chapters:\n    - index.qmd\n    - zebra.qmd\n    - apple.qmd\n",
+        )
+        .unwrap();
+        write_files(
+            temp.path(),
+            &["index.qmd", "apple.qmd", "zebra.qmd", "draft.qmd"],
+        );
+
+        let project = ProjectContext::discover(temp.path(), &runtime).unwrap();
+        assert_eq!(
+            relative_inputs(&project),
+            vec!["index.qmd", "zebra.qmd", "apple.qmd"]
+        );
+    }
 }
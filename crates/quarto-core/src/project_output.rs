@@ -0,0 +1,258 @@
+/*
+ * project_output.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Project-level outputs written after all documents are rendered.
+ */
+
+//! Project-level outputs.
+//!
+//! Some outputs of a project are not produced by rendering any one document
+//! but from the rendered project as a whole, like `sitemap.xml` or the feeds
+//! of blog listings. They are written by [`ProjectOutput`] steps, which
+//! [`ProjectRenderer`](crate::ProjectRenderer) runs in order after all
+//! documents are rendered:
+//!
+//! - [`ProjectOutput`] - The trait implemented by each step
+//! - [`ProjectOutputPipeline`] - Ordered collection of steps to run
+//!
+//! The standard steps are in [`crate::project_outputs`] and assembled by
+//! [`build_project_output_pipeline`].
+
+use std::path::PathBuf;
+
+use quarto_system_runtime::SystemRuntime;
+use serde_json::Value;
+
+use crate::Result;
+use crate::format::Format;
+use crate::project::{DocumentInfo, ProjectContext};
+use crate::project_outputs::{FeedOutput, SitemapOutput};
+
+/// What project output steps can see of the rendered project.
+pub struct ProjectOutputContext<'a> {
+    /// The rendered project
+    pub project: &'a ProjectContext,
+
+    /// Runtime for reading sources and outputs and writing results
+    pub runtime: &'a dyn SystemRuntime,
+
+    /// The format the project was rendered to
+    pub format: &'a Format,
+}
+
+impl ProjectOutputContext<'_> {
+    /// Where a document's output was written: mirrored under the project's
+    /// `output_dir`, or next to the input if there is none.
+    pub fn output_path(&self, document: &DocumentInfo) -> PathBuf {
+        if let Some(output) = &document.output {
+            return output.clone();
+        }
+
+        if self.project.output_dir != self.project.dir
+            && let Ok(relative) = document.input.strip_prefix(&self.project.dir)
+        {
+            let mut output = self.project.output_dir.join(relative);
+            output.set_extension(&self.format.output_extension);
+            return output;
+        }
+
+        self.format.output_path(&document.input)
+    }
+
+    /// A document's output path relative to the output directory, with `/`
+    /// separators (the path part of its URL).
+    pub fn output_href(&self, document: &DocumentInfo) -> String {
+        let output = self.output_path(document);
+        output
+            .strip_prefix(&self.project.output_dir)
+            .unwrap_or(&output)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// The `website` (or, for books, `book`) section of the project config.
+    pub fn website(&self) -> Option<&Value> {
+        let raw = &self.project.config.as_ref()?.raw;
+        raw.get("website").or_else(|| raw.get("book"))
+    }
+
+    /// The published URL of the site (`site-url`), without a trailing `/`.
+    pub fn site_url(&self) -> Option<&str> {
+        self.website()?
+            .get("site-url")
+            .and_then(Value::as_str)
+            .map(|url| url.trim_end_matches('/'))
+            .filter(|url| !url.is_empty())
+    }
+}
+
+/// A step that writes project-level output after all documents rendered.
+pub trait ProjectOutput: Send + Sync {
+    /// Human-readable name for this step.
+    ///
+    /// Used for logging and debugging.
+    fn name(&self) -> &str;
+
+    /// Write this step's output files.
+    ///
+    /// Returns the paths of the files written; a step with nothing to do
+    /// (e.g. a sitemap without `site-url`) returns none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an output file can't be written.
+    fn generate(&self, ctx: &ProjectOutputContext) -> Result<Vec<PathBuf>>;
+}
+
+/// A pipeline of project output steps to run in order.
+pub struct ProjectOutputPipeline {
+    outputs: Vec<Box<dyn ProjectOutput>>,
+}
+
+impl ProjectOutputPipeline {
+    /// Create a new empty pipeline.
+    pub fn new() -> Self {
+        Self {
+            outputs: Vec::new(),
+        }
+    }
+
+    /// Add a step to the pipeline.
+    pub fn push(&mut self, output: Box<dyn ProjectOutput>) {
+        self.outputs.push(output);
+    }
+
+    /// Get the number of steps in the pipeline.
+    pub fn len(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// Check if the pipeline is empty.
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    /// Run all steps in insertion order, returning the files they wrote.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error encountered. Execution stops on error.
+    pub fn execute(&self, ctx: &ProjectOutputContext) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+        for output in &self.outputs {
+            tracing::debug!(output = output.name(), "Running project output");
+            written.extend(output.generate(ctx)?);
+        }
+        Ok(written)
+    }
+
+    /// List the names of all steps in execution order.
+    pub fn output_names(&self) -> Vec<&str> {
+        self.outputs.iter().map(|o| o.name()).collect()
+    }
+}
+
+impl Default for ProjectOutputPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Build the standard project output pipeline.
+///
+/// 1. `SitemapOutput` - `sitemap.xml` for websites with a `site-url`
+/// 2. `FeedOutput` - RSS/Atom feeds of listings with `feed:`
+pub fn build_project_output_pipeline() -> ProjectOutputPipeline {
+    let mut pipeline = ProjectOutputPipeline::new();
+    pipeline.push(Box::new(SitemapOutput::new()));
+    pipeline.push(Box::new(FeedOutput::new()));
+    pipeline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{ProjectConfig, ProjectType};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    struct Recording {
+        name: &'static str,
+        calls: std::sync::Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl ProjectOutput for Recording {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn generate(&self, _ctx: &ProjectOutputContext) -> Result<Vec<PathBuf>> {
+            self.calls.lock().unwrap().push(self.name);
+            Ok(vec![PathBuf::from(self.name)])
+        }
+    }
+
+    fn project() -> ProjectContext {
+        ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: Some(ProjectConfig {
+                project_type: ProjectType::Website,
+                raw: json!({ "website": { "site-url": "https://example.com/blog/" } }),
+                ..Default::default()
+            }),
+            is_single_file: false,
+            files: vec![DocumentInfo::from_path("/project/posts/a.qmd")],
+            output_dir: PathBuf::from("/project/_site"),
+        }
+    }
+
+    #[test]
+    fn test_standard_pipeline() {
+        let pipeline = build_project_output_pipeline();
+        assert_eq!(pipeline.output_names(), vec!["sitemap", "feed"]);
+    }
+
+    #[test]
+    fn test_pipeline_runs_in_order() {
+        let calls = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let mut pipeline = ProjectOutputPipeline::new();
+        for name in ["first", "second"] {
+            pipeline.push(Box::new(Recording {
+                name,
+                calls: calls.clone(),
+            }));
+        }
+
+        let project = project();
+        let runtime = quarto_system_runtime::NativeRuntime::new();
+        let format = Format::html();
+        let ctx = ProjectOutputContext {
+            project: &project,
+            runtime: &runtime,
+            format: &format,
+        };
+        let written = pipeline.execute(&ctx).unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["first", "second"]);
+        assert_eq!(
+            written,
+            vec![PathBuf::from("first"), PathBuf::from("second")]
+        );
+    }
+
+    #[test]
+    fn test_context_urls() {
+        let project = project();
+        let runtime = quarto_system_runtime::NativeRuntime::new();
+        let format = Format::html();
+        let ctx = ProjectOutputContext {
+            project: &project,
+            runtime: &runtime,
+            format: &format,
+        };
+
+        assert_eq!(ctx.site_url(), Some("https://example.com/blog"));
+        assert_eq!(ctx.output_href(&project.files[0]), "posts/a.html");
+    }
+}
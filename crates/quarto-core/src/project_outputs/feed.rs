@@ -0,0 +1,599 @@
+/*
+ * feed.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Project output step that writes listing feeds.
+ */
+
+//! Feed project output.
+//!
+//! For each listing with a `feed` option (see [`FeedOptions`]), writes a
+//! feed of the listed documents next to the listing page's output:
+//! `index.xml` for `index.qmd` (a second listing with a feed on the same
+//! page gets `index-<id>.xml`). Each of the feed's `categories` also gets a
+//! feed of just its documents, `index-<category>.xml`.
+//!
+//! Feeds are RSS 2.0 by default, or Atom with `format: atom`. Item content
+//! depends on the feed `type`:
+//!
+//! - `partial` - the document's `description`, or else the first paragraph
+//!   of its rendered content
+//! - `full` - the document's rendered content (the page's `<main>`,
+//!   without the title block)
+//!
+//! Relative links and images in item content are made absolute. Feeds need
+//! absolute URLs, so they are only written for projects with a `site-url`.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde_json::Value;
+
+use super::{Timestamp, read_front_matter, write_output, xml_escape};
+use crate::Result;
+use crate::listing::{FeedFormat, FeedOptions, FeedType, Listing, ListingItem};
+use crate::project::DocumentInfo;
+use crate::project_output::{ProjectOutput, ProjectOutputContext};
+
+/// Matches `href="..."` and `src="..."` attributes.
+static URL_ATTR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\b(href|src)="([^"]*)""#).unwrap());
+
+/// Project output step that writes RSS/Atom feeds for listings.
+pub struct FeedOutput;
+
+impl FeedOutput {
+    /// Create a new feed output step.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for FeedOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProjectOutput for FeedOutput {
+    fn name(&self) -> &str {
+        "feed"
+    }
+
+    fn generate(&self, ctx: &ProjectOutputContext) -> Result<Vec<PathBuf>> {
+        let mut written = Vec::new();
+
+        for page in &ctx.project.files {
+            let meta = read_front_matter(ctx, &page.input);
+            let Some(listing) = meta.get("listing") else {
+                continue;
+            };
+            let feeds: Vec<(Listing, FeedOptions)> = Listing::from_metadata(listing)
+                .into_iter()
+                .filter_map(|listing| {
+                    let feed = listing.feed.clone()?;
+                    Some((listing, feed))
+                })
+                .collect();
+            if feeds.is_empty() {
+                continue;
+            }
+
+            let Some(site_url) = ctx.site_url() else {
+                tracing::warn!(
+                    "Skipping the feed of {}: feeds need `site-url` in the website configuration",
+                    page.input.display()
+                );
+                continue;
+            };
+
+            let output = ctx.output_path(page);
+            let stem = output
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            for (index, (listing, options)) in feeds.iter().enumerate() {
+                let base = if index == 0 {
+                    stem.clone()
+                } else {
+                    format!("{}-{}", stem, listing.id)
+                };
+                let items = listing.items(ctx.project, &page.input, ctx.runtime);
+                let feed = Feed::new(ctx, site_url, page, &meta, options);
+
+                let path = output.with_file_name(format!("{}.xml", base));
+                write_output(ctx, &path, &feed.render(&path, &items, None))?;
+                written.push(path);
+
+                for category in &options.categories {
+                    let in_category: Vec<ListingItem> = items
+                        .iter()
+                        .filter(|item| item.categories.contains(category))
+                        .cloned()
+                        .collect();
+                    let path = output.with_file_name(format!("{}-{}.xml", base, slug(category)));
+                    write_output(
+                        ctx,
+                        &path,
+                        &feed.render(&path, &in_category, Some(category.as_str())),
+                    )?;
+                    written.push(path);
+                }
+            }
+        }
+
+        Ok(written)
+    }
+}
+
+/// A feed of one listing.
+struct Feed<'a> {
+    ctx: &'a ProjectOutputContext<'a>,
+    site_url: &'a str,
+    options: &'a FeedOptions,
+    title: String,
+    description: String,
+    /// URL of the listing page
+    link: String,
+}
+
+impl<'a> Feed<'a> {
+    fn new(
+        ctx: &'a ProjectOutputContext<'a>,
+        site_url: &'a str,
+        page: &DocumentInfo,
+        meta: &Value,
+        options: &'a FeedOptions,
+    ) -> Self {
+        let website = ctx.website();
+        let string = |value: Option<&Value>, key: &str| {
+            value
+                .and_then(|v| v.get(key))
+                .and_then(Value::as_str)
+                .map(String::from)
+        };
+
+        let title = options
+            .title
+            .clone()
+            .or_else(|| string(Some(meta), "title"))
+            .or_else(|| page.title.clone())
+            .or_else(|| string(website, "title"))
+            .unwrap_or_default();
+        let description = options
+            .description
+            .clone()
+            .or_else(|| string(Some(meta), "description"))
+            .or_else(|| string(website, "description"))
+            .unwrap_or_else(|| title.clone());
+
+        Self {
+            ctx,
+            site_url,
+            options,
+            link: format!("{}/{}", site_url, ctx.output_href(page)),
+            title,
+            description,
+        }
+    }
+
+    fn url(&self, path: &Path) -> String {
+        let relative = path
+            .strip_prefix(&self.ctx.project.output_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        format!("{}/{}", self.site_url, relative)
+    }
+
+    fn document(&self, item: &ListingItem) -> DocumentInfo {
+        self.ctx
+            .project
+            .files
+            .iter()
+            .find(|doc| doc.input == item.input)
+            .cloned()
+            .unwrap_or_else(|| DocumentInfo::from_path(&item.input))
+    }
+
+    /// The item's content: its description or full rendered HTML.
+    fn content(&self, item: &ListingItem, link: &str) -> String {
+        let html = self
+            .ctx
+            .runtime
+            .file_read_string(&self.ctx.output_path(&self.document(item)))
+            .ok()
+            .map(|page| main_content(&page));
+        let base = link.rsplit_once('/').map_or(link, |(dir, _)| dir);
+
+        match (self.options.feed_type, html) {
+            (FeedType::Full, Some(html)) => absolute_urls(&html, base),
+            (FeedType::Partial, _) if item.description.is_some() => {
+                xml_escape(item.description.as_deref().unwrap_or_default())
+            }
+            (_, Some(html)) => first_paragraph(&html)
+                .map(|p| absolute_urls(p, base))
+                .unwrap_or_default(),
+            (_, None) => xml_escape(item.description.as_deref().unwrap_or_default()),
+        }
+    }
+
+    fn render(&self, path: &Path, items: &[ListingItem], category: Option<&str>) -> String {
+        let items = &items[..items.len().min(self.options.items)];
+        let title = match category {
+            Some(category) => format!("{} - {}", self.title, category),
+            None => self.title.clone(),
+        };
+        let updated = items
+            .iter()
+            .filter_map(|item| item.date.as_deref().and_then(Timestamp::parse))
+            .max();
+
+        match self.options.format {
+            FeedFormat::Rss => self.render_rss(path, items, &title, updated),
+            FeedFormat::Atom => self.render_atom(path, items, &title, updated),
+        }
+    }
+
+    fn render_rss(
+        &self,
+        path: &Path,
+        items: &[ListingItem],
+        title: &str,
+        updated: Option<Timestamp>,
+    ) -> String {
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n\
+             <channel>\n\
+             <title>{}</title>\n\
+             <link>{}</link>\n\
+             <atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>\n\
+             <description>{}</description>\n\
+             <generator>quarto</generator>\n",
+            xml_escape(title),
+            xml_escape(&self.link),
+            xml_escape(&self.url(path)),
+            xml_escape(&self.description)
+        );
+        if let Some(updated) = updated {
+            xml.push_str(&format!(
+                "<lastBuildDate>{}</lastBuildDate>\n",
+                updated.rfc822()
+            ));
+        }
+
+        for item in items {
+            let link = format!(
+                "{}/{}",
+                self.site_url,
+                self.ctx.output_href(&self.document(item))
+            );
+            xml.push_str(&format!(
+                "<item>\n<title>{}</title>\n",
+                xml_escape(&item.title)
+            ));
+            if let Some(author) = &item.author {
+                xml.push_str(&format!(
+                    "<dc:creator>{}</dc:creator>\n",
+                    xml_escape(author)
+                ));
+            }
+            xml.push_str(&format!(
+                "<link>{0}</link>\n<description>{1}</description>\n",
+                xml_escape(&link),
+                cdata(&self.content(item, &link))
+            ));
+            for category in &item.categories {
+                xml.push_str(&format!("<category>{}</category>\n", xml_escape(category)));
+            }
+            xml.push_str(&format!("<guid>{}</guid>\n", xml_escape(&link)));
+            if let Some(date) = item.date.as_deref().and_then(Timestamp::parse) {
+                xml.push_str(&format!("<pubDate>{}</pubDate>\n", date.rfc822()));
+            }
+            xml.push_str("</item>\n");
+        }
+
+        xml.push_str("</channel>\n</rss>\n");
+        xml
+    }
+
+    fn render_atom(
+        &self,
+        path: &Path,
+        items: &[ListingItem],
+        title: &str,
+        updated: Option<Timestamp>,
+    ) -> String {
+        let url = self.url(path);
+        let mut xml = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <feed xmlns=\"http://www.w3.org/2005/Atom\">\n\
+             <title>{}</title>\n\
+             <subtitle>{}</subtitle>\n\
+             <link href=\"{}\"/>\n\
+             <link href=\"{}\" rel=\"self\" type=\"application/atom+xml\"/>\n\
+             <id>{}</id>\n\
+             <updated>{}</updated>\n\
+             <generator>quarto</generator>\n",
+            xml_escape(title),
+            xml_escape(&self.description),
+            xml_escape(&self.link),
+            xml_escape(&url),
+            xml_escape(&url),
+            updated.unwrap_or_else(Timestamp::now).rfc3339()
+        );
+
+        for item in items {
+            let link = format!(
+                "{}/{}",
+                self.site_url,
+                self.ctx.output_href(&self.document(item))
+            );
+            let date = item.date.as_deref().and_then(Timestamp::parse);
+            xml.push_str(&format!(
+                "<entry>\n<title>{}</title>\n<link href=\"{1}\"/>\n<id>{1}</id>\n",
+                xml_escape(&item.title),
+                xml_escape(&link)
+            ));
+            if let Some(date) = date.or(updated) {
+                xml.push_str(&format!("<updated>{}</updated>\n", date.rfc3339()));
+            }
+            if let Some(author) = &item.author {
+                xml.push_str(&format!(
+                    "<author><name>{}</name></author>\n",
+                    xml_escape(author)
+                ));
+            }
+            for category in &item.categories {
+                xml.push_str(&format!("<category term=\"{}\"/>\n", xml_escape(category)));
+            }
+            let element = match self.options.feed_type {
+                FeedType::Full => "content",
+                FeedType::Partial => "summary",
+            };
+            xml.push_str(&format!(
+                "<{0} type=\"html\">{1}</{0}>\n</entry>\n",
+                element,
+                xml_escape(&self.content(item, &link))
+            ));
+        }
+
+        xml.push_str("</feed>\n");
+        xml
+    }
+}
+
+/// The content of a rendered page's `<main>`, without its title block.
+fn main_content(page: &str) -> String {
+    let content = page
+        .find("<main")
+        .and_then(|start| {
+            let open_end = start + page[start..].find('>')? + 1;
+            let close = page[open_end..].find("</main>")? + open_end;
+            Some(&page[open_end..close])
+        })
+        .unwrap_or(page);
+
+    let content = match content.find("<header id=\"title-block-header\"") {
+        Some(start) => match content[start..].find("</header>") {
+            Some(end) => format!(
+                "{}{}",
+                &content[..start],
+                &content[start + end + "</header>".len()..]
+            ),
+            None => content.to_string(),
+        },
+        None => content.to_string(),
+    };
+    content.trim().to_string()
+}
+
+/// The first `<p>` element of some HTML.
+fn first_paragraph(html: &str) -> Option<&str> {
+    let start = html.find("<p>").or_else(|| html.find("<p "))?;
+    let end = html[start..].find("</p>")? + start + "</p>".len();
+    Some(&html[start..end])
+}
+
+/// Resolve relative `href`/`src` URLs against `base` (the URL of the
+/// item's directory).
+fn absolute_urls(html: &str, base: &str) -> String {
+    URL_ATTR_RE
+        .replace_all(html, |caps: &regex::Captures| {
+            let url = &caps[2];
+            let absolute = url.is_empty()
+                || url.starts_with('#')
+                || url.starts_with('/')
+                || url.starts_with("data:")
+                || url.starts_with("mailto:")
+                || url.contains("://");
+            if absolute {
+                caps[0].to_string()
+            } else {
+                format!("{}=\"{}/{}\"", &caps[1], base, url.trim_start_matches("./"))
+            }
+        })
+        .into_owned()
+}
+
+/// Wrap text in a CDATA section.
+fn cdata(text: &str) -> String {
+    format!("<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+/// A file name part for a category.
+fn slug(category: &str) -> String {
+    category
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{ProjectConfig, ProjectContext, ProjectType};
+    use quarto_system_runtime::NativeRuntime;
+    use serde_json::json;
+
+    fn write(dir: &Path, path: &str, content: &str) -> PathBuf {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    const POST: &str = "<html><body><main class=\"content\" id=\"quarto-document-content\">\n\
+         <header id=\"title-block-header\"><h1 class=\"title\">Welcome</h1></header>\n\
+         <p>First <a href=\"other.html\">paragraph</a>.</p>\n\
+         <p><img src=\"plot.png\"></p>\n\
+         </main></body></html>";
+
+    fn project(dir: &Path, feed: &str, website: Value) -> ProjectContext {
+        let files = vec![
+            write(
+                dir,
+                "index.qmd",
+                &format!(
+                    "---\ntitle: My Blog\nlisting:\n  contents: posts\n  feed: {}\n---\n",
+                    feed
+                ),
+            ),
+            write(
+                dir,
+                "posts/welcome/index.qmd",
+                "---\ntitle: Welcome\ndate: 2024-01-05\nauthor: Ada\ncategories: [news]\n---\n",
+            ),
+            write(
+                dir,
+                "posts/code.qmd",
+                "---\ntitle: Code & Plots\ndate: 2024-03-10\ndescription: Plots <3\ncategories: [code]\n---\n",
+            ),
+        ];
+        write(dir, "_site/posts/welcome/index.html", POST);
+        write(
+            dir,
+            "_site/posts/code.html",
+            "<main><p>Code post.</p></main>",
+        );
+
+        ProjectContext {
+            dir: dir.to_path_buf(),
+            config: Some(ProjectConfig {
+                project_type: ProjectType::Website,
+                raw: json!({ "website": website }),
+                ..Default::default()
+            }),
+            is_single_file: false,
+            files: files.into_iter().map(DocumentInfo::from_path).collect(),
+            output_dir: dir.join("_site"),
+        }
+    }
+
+    fn generate(project: &ProjectContext) -> Vec<PathBuf> {
+        let runtime = NativeRuntime::new();
+        let format = Format::html();
+        let ctx = ProjectOutputContext {
+            project,
+            runtime: &runtime,
+            format: &format,
+        };
+        FeedOutput::new().generate(&ctx).unwrap()
+    }
+
+    #[test]
+    fn test_rss_feed_with_excerpts() {
+        let temp = tempfile::tempdir().unwrap();
+        let project = project(
+            temp.path(),
+            "true",
+            json!({ "site-url": "https://example.com" }),
+        );
+
+        let written = generate(&project);
+        assert_eq!(written, vec![temp.path().join("_site/index.xml")]);
+        let xml = std::fs::read_to_string(&written[0]).unwrap();
+
+        assert!(xml.contains("<title>My Blog</title>"));
+        assert!(xml.contains("<link>https://example.com/index.html</link>"));
+        assert!(xml.contains(
+            "<atom:link href=\"https://example.com/index.xml\" rel=\"self\" type=\"application/rss+xml\"/>"
+        ));
+        assert!(xml.contains("<lastBuildDate>Sun, 10 Mar 2024 00:00:00 GMT</lastBuildDate>"));
+
+        // Newest first; the description is the excerpt when there is one
+        let code = xml.find("<title>Code &amp; Plots</title>").unwrap();
+        let welcome = xml.find("<title>Welcome</title>").unwrap();
+        assert!(code < welcome);
+        assert!(xml.contains("<description><![CDATA[Plots &lt;3]]></description>"));
+
+        // Otherwise the first paragraph, with absolute links
+        assert!(xml.contains(
+            "<description><![CDATA[<p>First <a href=\"https://example.com/posts/welcome/other.html\">paragraph</a>.</p>]]></description>"
+        ));
+        assert!(xml.contains("<dc:creator>Ada</dc:creator>"));
+        assert!(xml.contains("<category>news</category>"));
+        assert!(xml.contains("<guid>https://example.com/posts/welcome/index.html</guid>"));
+        assert!(xml.contains("<pubDate>Fri, 05 Jan 2024 00:00:00 GMT</pubDate>"));
+    }
+
+    #[test]
+    fn test_full_atom_feed_and_category_feeds() {
+        let temp = tempfile::tempdir().unwrap();
+        let project = project(
+            temp.path(),
+            "{ type: full, format: atom, categories: [news] }",
+            json!({ "site-url": "https://example.com/" }),
+        );
+
+        let written = generate(&project);
+        assert_eq!(
+            written,
+            vec![
+                temp.path().join("_site/index.xml"),
+                temp.path().join("_site/index-news.xml")
+            ]
+        );
+
+        let xml = std::fs::read_to_string(&written[0]).unwrap();
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">"));
+        assert!(xml.contains("<updated>2024-03-10T00:00:00Z</updated>"));
+        // Full content without the title block, escaped
+        assert!(xml.contains("<content type=\"html\">&lt;p&gt;First"));
+        assert!(xml.contains("https://example.com/posts/welcome/plot.png"));
+        assert!(!xml.contains("title-block-header"));
+
+        let news = std::fs::read_to_string(&written[1]).unwrap();
+        assert!(news.contains("<title>My Blog - news</title>"));
+        assert!(news.contains("<title>Welcome</title>"));
+        assert!(!news.contains("Code &amp; Plots"));
+    }
+
+    #[test]
+    fn test_no_feed_without_site_url() {
+        let temp = tempfile::tempdir().unwrap();
+        let project = project(temp.path(), "true", json!({ "title": "Blog" }));
+        assert!(generate(&project).is_empty());
+    }
+
+    #[test]
+    fn test_main_content_and_excerpt() {
+        let content = main_content(POST);
+        assert!(content.starts_with("<p>First"));
+        assert!(content.ends_with("</p>"));
+        assert_eq!(
+            first_paragraph(&content),
+            Some("<p>First <a href=\"other.html\">paragraph</a>.</p>")
+        );
+    }
+}
@@ -0,0 +1,217 @@
+/*
+ * project_outputs/mod.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Standard project output steps.
+ */
+
+//! Standard project output steps.
+//!
+//! - [`SitemapOutput`] - Writes `sitemap.xml` for websites with a `site-url`
+//! - [`FeedOutput`] - Writes RSS/Atom feeds for listings with `feed:`
+//!
+//! These implement [`ProjectOutput`](crate::project_output::ProjectOutput)
+//! and are assembled by
+//! [`build_project_output_pipeline`](crate::project_output::build_project_output_pipeline).
+
+mod feed;
+mod sitemap;
+
+pub use feed::FeedOutput;
+pub use sitemap::SitemapOutput;
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+
+use crate::error::{QuartoError, Result};
+use crate::project::front_matter;
+use crate::project_output::ProjectOutputContext;
+
+/// Front matter of a project file, or `Null` if it has none.
+fn read_front_matter(ctx: &ProjectOutputContext, path: &Path) -> Value {
+    ctx.runtime
+        .file_read_string(path)
+        .ok()
+        .and_then(|source| front_matter(&source))
+        .unwrap_or(Value::Null)
+}
+
+/// Write an output file, creating its directory.
+fn write_output(ctx: &ProjectOutputContext, path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        ctx.runtime.dir_create(parent, true).map_err(|e| {
+            QuartoError::Other(format!("Failed to create {}: {}", parent.display(), e))
+        })?;
+    }
+    ctx.runtime
+        .file_write(path, content.as_bytes())
+        .map_err(|e| QuartoError::Other(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Escape XML special characters.
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// A UTC point in time, for sitemap and feed dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Timestamp {
+    /// Seconds since the Unix epoch
+    seconds: i64,
+}
+
+impl Timestamp {
+    /// Parse a front matter date: `YYYY-MM-DD` or `MM/DD/YYYY`, optionally
+    /// followed by a `HH:MM[:SS]` time. Time zones are ignored.
+    fn parse(date: &str) -> Option<Self> {
+        let date = date.trim();
+        let (day, time) = match date.find(['T', ' ']) {
+            Some(i) => (&date[..i], Some(&date[i + 1..])),
+            None => (date, None),
+        };
+
+        let numbers = |s: &str, sep: char| -> Option<Vec<i64>> {
+            s.split(sep).map(|n| n.trim().parse().ok()).collect()
+        };
+        let (year, month, day) = match (numbers(day, '-'), numbers(day, '/')) {
+            (Some(ymd), _) if ymd.len() == 3 => (ymd[0], ymd[1], ymd[2]),
+            (_, Some(mdy)) if mdy.len() == 3 => (mdy[2], mdy[0], mdy[1]),
+            _ => return None,
+        };
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+
+        let mut seconds = days_from_civil(year, month, day) * 86400;
+        if let Some(time) = time {
+            let hms: Vec<i64> = time
+                .get(..time.len().min(8))
+                .unwrap_or_default()
+                .split(':')
+                .map_while(|n| n.parse().ok())
+                .collect();
+            seconds += hms
+                .iter()
+                .zip([3600, 60, 1])
+                .map(|(n, s)| n * s)
+                .sum::<i64>();
+        }
+        Some(Self { seconds })
+    }
+
+    fn from_system_time(time: SystemTime) -> Option<Self> {
+        let seconds = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        Some(Self {
+            seconds: i64::try_from(seconds).ok()?,
+        })
+    }
+
+    fn now() -> Self {
+        Self::from_system_time(SystemTime::now()).unwrap_or(Self { seconds: 0 })
+    }
+
+    /// Date and time parts: (year, month, day, hour, minute, second).
+    fn parts(&self) -> (i64, i64, i64, i64, i64, i64) {
+        let days = self.seconds.div_euclid(86400);
+        let secs = self.seconds.rem_euclid(86400);
+        let (year, month, day) = civil_from_days(days);
+        (year, month, day, secs / 3600, secs % 3600 / 60, secs % 60)
+    }
+
+    /// RFC 3339, as used by sitemaps and Atom: `2024-01-05T00:00:00Z`.
+    fn rfc3339(&self) -> String {
+        let (y, mo, d, h, mi, s) = self.parts();
+        format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", y, mo, d, h, mi, s)
+    }
+
+    /// RFC 822, as used by RSS: `Fri, 05 Jan 2024 00:00:00 GMT`.
+    fn rfc822(&self) -> String {
+        const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+        const MONTHS: [&str; 12] = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ];
+        let (y, mo, d, h, mi, s) = self.parts();
+        // 1970-01-01 was a Thursday
+        let weekday = WEEKDAYS[self.seconds.div_euclid(86400).rem_euclid(7) as usize];
+        format!(
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+            weekday,
+            d,
+            MONTHS[(mo - 1) as usize],
+            y,
+            h,
+            mi,
+            s
+        )
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The proleptic Gregorian date of a day since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_formats() {
+        let date = Timestamp::parse("2024-01-05").unwrap();
+        assert_eq!(date.rfc3339(), "2024-01-05T00:00:00Z");
+        assert_eq!(date.rfc822(), "Fri, 05 Jan 2024 00:00:00 GMT");
+
+        let leap = Timestamp::parse("2024-02-29T13:45").unwrap();
+        assert_eq!(leap.rfc3339(), "2024-02-29T13:45:00Z");
+        assert_eq!(leap.rfc822(), "Thu, 29 Feb 2024 13:45:00 GMT");
+
+        let us = Timestamp::parse("3/1/2023").unwrap();
+        assert_eq!(us.rfc3339(), "2023-03-01T00:00:00Z");
+
+        assert_eq!(Timestamp::parse("1970-01-01").unwrap().seconds, 0);
+        assert!(Timestamp::parse("last week").is_none());
+        assert!(Timestamp::parse("2024-13-01").is_none());
+    }
+
+    #[test]
+    fn test_civil_round_trip() {
+        for days in [-719_468, -1, 0, 59, 365, 11016, 19727, 2_932_896] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(
+            xml_escape("A & <B> \"c\""),
+            "A &amp; &lt;B&gt; &quot;c&quot;"
+        );
+    }
+}
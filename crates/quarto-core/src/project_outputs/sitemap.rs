@@ -0,0 +1,181 @@
+/*
+ * sitemap.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Project output step that writes sitemap.xml.
+ */
+
+//! Sitemap project output.
+//!
+//! For website and book projects with a `site-url`, writes
+//! `sitemap.xml` to the output directory, listing the URL of every
+//! rendered page. A page's `lastmod` is its `date-modified` front matter,
+//! or else the modification time of its output file. Pages with
+//! `draft: true` are left out.
+
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use super::{Timestamp, read_front_matter, write_output, xml_escape};
+use crate::Result;
+use crate::project_output::{ProjectOutput, ProjectOutputContext};
+
+/// Project output step that writes `sitemap.xml`.
+pub struct SitemapOutput;
+
+impl SitemapOutput {
+    /// Create a new sitemap output step.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SitemapOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProjectOutput for SitemapOutput {
+    fn name(&self) -> &str {
+        "sitemap"
+    }
+
+    fn generate(&self, ctx: &ProjectOutputContext) -> Result<Vec<PathBuf>> {
+        let Some(site_url) = ctx.site_url() else {
+            return Ok(Vec::new());
+        };
+
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        );
+        for doc in &ctx.project.files {
+            let output = ctx.output_path(doc);
+            if !ctx.runtime.is_file(&output).unwrap_or(false) {
+                continue;
+            }
+            let meta = read_front_matter(ctx, &doc.input);
+            if meta.get("draft").and_then(Value::as_bool) == Some(true) {
+                continue;
+            }
+
+            let lastmod = meta
+                .get("date-modified")
+                .and_then(Value::as_str)
+                .and_then(Timestamp::parse)
+                .or_else(|| {
+                    let modified = ctx.runtime.path_metadata(&output).ok()?.modified?;
+                    Timestamp::from_system_time(modified)
+                });
+
+            xml.push_str("  <url>\n");
+            xml.push_str(&format!(
+                "    <loc>{}/{}</loc>\n",
+                xml_escape(site_url),
+                xml_escape(&ctx.output_href(doc))
+            ));
+            if let Some(lastmod) = lastmod {
+                xml.push_str(&format!("    <lastmod>{}</lastmod>\n", lastmod.rfc3339()));
+            }
+            xml.push_str("  </url>\n");
+        }
+        xml.push_str("</urlset>\n");
+
+        let path = ctx.project.output_dir.join("sitemap.xml");
+        write_output(ctx, &path, &xml)?;
+        Ok(vec![path])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectConfig, ProjectContext, ProjectType};
+    use quarto_system_runtime::NativeRuntime;
+    use serde_json::json;
+    use std::path::Path;
+
+    fn write(dir: &Path, path: &str, content: &str) -> PathBuf {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn project(dir: &Path, website: Value) -> ProjectContext {
+        let files = [
+            (
+                "index.qmd",
+                "---\ntitle: Home\ndate-modified: 2024-05-01\n---\n",
+            ),
+            ("posts/a.qmd", "# A\n"),
+            ("posts/draft.qmd", "---\ndraft: true\n---\n"),
+            ("unrendered.qmd", "# Not rendered\n"),
+        ]
+        .iter()
+        .map(|(path, content)| DocumentInfo::from_path(write(dir, path, content)))
+        .collect();
+        for output in [
+            "_site/index.html",
+            "_site/posts/a.html",
+            "_site/posts/draft.html",
+        ] {
+            write(dir, output, "<html></html>");
+        }
+
+        ProjectContext {
+            dir: dir.to_path_buf(),
+            config: Some(ProjectConfig {
+                project_type: ProjectType::Website,
+                raw: json!({ "website": website }),
+                ..Default::default()
+            }),
+            is_single_file: false,
+            files,
+            output_dir: dir.join("_site"),
+        }
+    }
+
+    #[test]
+    fn test_sitemap_lists_rendered_pages() {
+        let temp = tempfile::tempdir().unwrap();
+        let project = project(temp.path(), json!({ "site-url": "https://example.com/" }));
+        let runtime = NativeRuntime::new();
+        let format = Format::html();
+        let ctx = ProjectOutputContext {
+            project: &project,
+            runtime: &runtime,
+            format: &format,
+        };
+
+        let written = SitemapOutput::new().generate(&ctx).unwrap();
+        assert_eq!(written, vec![temp.path().join("_site/sitemap.xml")]);
+
+        let xml = std::fs::read_to_string(&written[0]).unwrap();
+        assert!(xml.contains(
+            "<loc>https://example.com/index.html</loc>\n    <lastmod>2024-05-01T00:00:00Z</lastmod>"
+        ));
+        assert!(xml.contains("<loc>https://example.com/posts/a.html</loc>\n    <lastmod>"));
+        assert!(!xml.contains("draft"));
+        assert!(!xml.contains("unrendered"));
+    }
+
+    #[test]
+    fn test_no_sitemap_without_site_url() {
+        let temp = tempfile::tempdir().unwrap();
+        let project = project(temp.path(), json!({ "title": "Site" }));
+        let runtime = NativeRuntime::new();
+        let format = Format::html();
+        let ctx = ProjectOutputContext {
+            project: &project,
+            runtime: &runtime,
+            format: &format,
+        };
+
+        assert!(SitemapOutput::new().generate(&ctx).unwrap().is_empty());
+        assert!(!temp.path().join("_site/sitemap.xml").exists());
+    }
+}
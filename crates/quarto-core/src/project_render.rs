@@ -0,0 +1,726 @@
+/*
+ * project_render.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Project-level render orchestration.
+ */
+
+//! Project-level render orchestration.
+//!
+//! Rendering a project renders each of its input files, but not in arbitrary
+//! order: a document that lists other documents, includes them, or refers to
+//! cross-reference ids defined in them should be rendered after them. This
+//! module builds a [`DependencyGraph`] from a lightweight scan of the project
+//! sources and a [`ProjectRenderer`] that walks it:
+//!
+//! - Files are grouped into levels; every file's dependencies are in earlier
+//!   levels. Files in the same level are rendered in parallel (rayon), except
+//!   files with executable code cells, which run one at a time so they don't
+//!   share kernels concurrently.
+//! - Incremental renders skip files whose sources (including included files
+//!   and the sources of their dependencies) are unchanged since the last
+//!   render and whose output still exists. A re-rendered file forces its
+//!   dependents to re-render. Fingerprints are stored through the
+//!   [`SystemRuntime`] in `.quarto/render-state.json`.
+//!
+//! # Dependencies
+//!
+//! - **Includes**: `{{< include file.qmd >}}` depends on the included file if
+//!   it is a project input; included files always count towards the
+//!   including file's fingerprint.
+//! - **Listings**: a document with `listing:` in its front matter depends on
+//!   the files matched by the listing's `contents` globs, or on every other
+//!   document in its directory tree if there are none.
+//! - **Cross-document references**: `@fig-x`, `@sec-x`, ... depend on the
+//!   document that defines the id (`{#fig-x}` or `#| label: fig-x`).
+//!
+//! Dependency cycles (e.g. two chapters referring to each other) are allowed;
+//! the files in a cycle are rendered together in one level.
+//!
+//! After all documents are rendered, the renderer runs the project output
+//! steps (see [`crate::project_output`]), e.g. the sitemap and listing feeds.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use quarto_system_runtime::SystemRuntime;
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::error::{QuartoError, Result};
+use crate::format::Format;
+use crate::listing::{Listing, listed_files};
+use crate::project::{DocumentInfo, ProjectContext, front_matter};
+use crate::project_output::{
+    ProjectOutputContext, ProjectOutputPipeline, build_project_output_pipeline,
+};
+
+/// Where incremental render state is stored, relative to the project dir.
+const RENDER_STATE_PATH: &str = ".quarto/render-state.json";
+
+/// Cross-reference prefixes that can link between documents.
+const CROSSREF_PREFIXES: &str = "fig|tbl|sec|eq|lst|thm|lem|cor|prp|cnj|def|exm|exr";
+
+/// Cell languages that make a document run an execution engine.
+const EXECUTABLE_LANGUAGES: &[&str] = &["r", "python", "python3", "py", "julia", "jl"];
+
+static INCLUDE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\{\{<\s*include\s+["']?([^"'\s>]+)["']?\s*>\}\}"#).expect("valid regex")
+});
+
+static CROSSREF_DEF_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"(?:\{{#|#\|\s*label:\s*)((?:{})-[\w-]+)",
+        CROSSREF_PREFIXES
+    ))
+    .expect("valid regex")
+});
+
+static CROSSREF_REF_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(r"@((?:{})-[\w-]*\w)", CROSSREF_PREFIXES)).expect("valid regex")
+});
+
+static CELL_LANGUAGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*```+\s*\{([A-Za-z][\w-]*)").expect("valid regex"));
+
+/// What a scan of one source file found.
+#[derive(Debug, Default)]
+struct SourceScan {
+    /// Resolved paths of included files
+    includes: Vec<PathBuf>,
+    /// Listing content globs, relative to the file's directory; `Some(vec![])`
+    /// for a listing without explicit contents
+    listing: Option<Vec<String>>,
+    /// Cross-reference ids defined in the file
+    defines: Vec<String>,
+    /// Cross-reference ids referenced by the file
+    references: Vec<String>,
+    /// Whether the file has executable code cells
+    executes: bool,
+}
+
+fn scan_source(path: &Path, source: &str) -> SourceScan {
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    let includes = INCLUDE_RE
+        .captures_iter(source)
+        .map(|cap| dir.join(&cap[1]))
+        .collect();
+
+    let listing = front_matter(source)
+        .and_then(|meta| meta.get("listing").cloned())
+        .map(|listing| {
+            Listing::from_metadata(&listing)
+                .into_iter()
+                .flat_map(|listing| listing.contents)
+                .collect()
+        });
+
+    let defines: Vec<String> = CROSSREF_DEF_RE
+        .captures_iter(source)
+        .map(|cap| cap[1].to_string())
+        .collect();
+    let references = CROSSREF_REF_RE
+        .captures_iter(source)
+        .map(|cap| cap[1].to_string())
+        .filter(|id| !defines.contains(id))
+        .collect();
+
+    let executes = CELL_LANGUAGE_RE
+        .captures_iter(source)
+        .any(|cap| EXECUTABLE_LANGUAGES.contains(&cap[1].to_lowercase().as_str()));
+
+    SourceScan {
+        includes,
+        listing,
+        defines,
+        references,
+        executes,
+    }
+}
+
+/// Render-order dependencies between the files of a project.
+#[derive(Debug, Clone)]
+pub struct DependencyGraph {
+    /// The project's input files
+    files: Vec<PathBuf>,
+    /// For each file, the indices of the files it depends on
+    dependencies: Vec<Vec<usize>>,
+    /// For each file, the non-project files it includes
+    includes: Vec<Vec<PathBuf>>,
+    /// For each file, whether it has executable code cells
+    executes: Vec<bool>,
+}
+
+impl DependencyGraph {
+    /// Build the graph for a project, reading sources through `runtime`.
+    pub fn build(project: &ProjectContext, runtime: &dyn SystemRuntime) -> Result<Self> {
+        let sources = project
+            .files
+            .iter()
+            .map(|doc| {
+                let source = runtime.file_read_string(&doc.input).map_err(|e| {
+                    QuartoError::Other(format!("Failed to read {}: {}", doc.input.display(), e))
+                })?;
+                Ok((doc.input.clone(), source))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::from_sources(&sources))
+    }
+
+    /// Build the graph from already loaded `(path, source)` pairs.
+    pub fn from_sources(sources: &[(PathBuf, String)]) -> Self {
+        let files: Vec<PathBuf> = sources.iter().map(|(path, _)| path.clone()).collect();
+        let scans: Vec<SourceScan> = sources
+            .iter()
+            .map(|(path, source)| scan_source(path, source))
+            .collect();
+
+        let index: HashMap<&Path, usize> = files
+            .iter()
+            .enumerate()
+            .map(|(i, path)| (path.as_path(), i))
+            .collect();
+
+        let mut definitions: HashMap<&str, usize> = HashMap::new();
+        for (i, scan) in scans.iter().enumerate() {
+            for id in &scan.defines {
+                definitions.entry(id.as_str()).or_insert(i);
+            }
+        }
+
+        let mut dependencies = Vec::with_capacity(files.len());
+        let mut includes = Vec::with_capacity(files.len());
+        for (i, scan) in scans.iter().enumerate() {
+            let mut deps = Vec::new();
+            let mut external = Vec::new();
+
+            for include in &scan.includes {
+                match index.get(include.as_path()) {
+                    Some(&j) => deps.push(j),
+                    None => external.push(include.clone()),
+                }
+            }
+
+            if let Some(globs) = &scan.listing {
+                deps.extend(listed_files(&files[i], globs, &files));
+            }
+
+            for id in &scan.references {
+                if let Some(&j) = definitions.get(id.as_str()) {
+                    deps.push(j);
+                }
+            }
+
+            deps.retain(|&j| j != i);
+            deps.sort_unstable();
+            deps.dedup();
+            dependencies.push(deps);
+            includes.push(external);
+        }
+
+        Self {
+            files,
+            dependencies,
+            includes,
+            executes: scans.iter().map(|scan| scan.executes).collect(),
+        }
+    }
+
+    /// The project's input files, in graph index order.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// The indices of the files that `index` depends on.
+    pub fn dependencies(&self, index: usize) -> &[usize] {
+        &self.dependencies[index]
+    }
+
+    /// Whether the file at `index` has executable code cells.
+    pub fn executes(&self, index: usize) -> bool {
+        self.executes[index]
+    }
+
+    /// Group files into render levels.
+    ///
+    /// Every file's dependencies are in earlier levels, except for files in
+    /// a dependency cycle, which share a level. Files within a level are in
+    /// index order.
+    pub fn levels(&self) -> Vec<Vec<usize>> {
+        let mut remaining: Vec<usize> = (0..self.files.len()).collect();
+        let mut done = vec![false; self.files.len()];
+        let mut levels = Vec::new();
+
+        while !remaining.is_empty() {
+            let mut level: Vec<usize> = remaining
+                .iter()
+                .copied()
+                .filter(|&i| self.dependencies[i].iter().all(|&j| done[j]))
+                .collect();
+
+            if level.is_empty() {
+                // Only cycles are left: take the strongly connected files
+                // whose outside dependencies are all rendered
+                level = self.cycle_level(&remaining, &done);
+            }
+
+            for &i in &level {
+                done[i] = true;
+            }
+            remaining.retain(|i| !done[*i]);
+            levels.push(level);
+        }
+
+        levels
+    }
+
+    /// The smallest set of remaining files closed under dependencies.
+    ///
+    /// The files reachable from a file through unrendered dependencies always
+    /// contain a cycle with no unrendered dependencies outside it; the
+    /// smallest such reachable set is exactly one of those cycles.
+    fn cycle_level(&self, remaining: &[usize], done: &[bool]) -> Vec<usize> {
+        let reachable_from = |start: usize| {
+            let mut reachable = HashSet::new();
+            let mut stack = vec![start];
+            while let Some(i) = stack.pop() {
+                if reachable.insert(i) {
+                    stack.extend(self.dependencies[i].iter().copied().filter(|&j| !done[j]));
+                }
+            }
+            reachable
+        };
+
+        let smallest = remaining
+            .iter()
+            .map(|&i| reachable_from(i))
+            .min_by_key(|reachable| reachable.len())
+            .unwrap_or_default();
+
+        let mut level: Vec<usize> = smallest.into_iter().collect();
+        level.sort_unstable();
+        level
+    }
+
+    /// Fingerprint of a file's render inputs.
+    ///
+    /// Covers the file's source, the files it includes, and the sources of
+    /// its direct dependencies (dirtiness propagates along the graph at
+    /// render time, so transitive dependencies are covered too).
+    fn fingerprint(&self, index: usize, runtime: &dyn SystemRuntime) -> Option<String> {
+        let read = |path: &Path| runtime.file_read(path).ok();
+
+        let mut hasher = DefaultHasher::new();
+        read(&self.files[index])?.hash(&mut hasher);
+        for include in &self.includes[index] {
+            // A missing include still contributes, so creating it re-renders
+            read(include).hash(&mut hasher);
+        }
+        for &dep in &self.dependencies[index] {
+            read(&self.files[dep]).hash(&mut hasher);
+        }
+        Some(format!("{:016x}", hasher.finish()))
+    }
+}
+
+/// Fingerprints from the previous render, keyed by project-relative path.
+#[derive(Debug, Default)]
+struct RenderState {
+    fingerprints: BTreeMap<String, String>,
+}
+
+impl RenderState {
+    fn load(project_dir: &Path, runtime: &dyn SystemRuntime) -> Self {
+        let fingerprints = runtime
+            .file_read(&project_dir.join(RENDER_STATE_PATH))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self { fingerprints }
+    }
+
+    fn save(&self, project_dir: &Path, runtime: &dyn SystemRuntime) {
+        let path = project_dir.join(RENDER_STATE_PATH);
+        let result = serde_json::to_vec_pretty(&self.fingerprints)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                if let Some(parent) = path.parent() {
+                    runtime
+                        .dir_create(parent, true)
+                        .map_err(|e| e.to_string())?;
+                }
+                runtime.file_write(&path, &json).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to save render state: {}", e);
+        }
+    }
+}
+
+/// Outcome of a project render.
+#[derive(Debug, Default)]
+pub struct ProjectRenderResult {
+    /// Files that were rendered, in render order
+    pub rendered: Vec<PathBuf>,
+
+    /// Files skipped by an incremental render because they were unchanged
+    pub skipped: Vec<PathBuf>,
+
+    /// Files written by the project output steps (sitemap, feeds, ...)
+    pub outputs: Vec<PathBuf>,
+}
+
+/// Renders all files of a project in dependency order.
+///
+/// The renderer decides order, parallelism and what to skip; the actual
+/// rendering of one document is done by the callback passed to
+/// [`ProjectRenderer::render`], which is expected to write the document's
+/// output (see [`ProjectRenderer::output_path`]).
+pub struct ProjectRenderer<'a> {
+    project: &'a ProjectContext,
+    runtime: &'a dyn SystemRuntime,
+    format: Format,
+    incremental: bool,
+    outputs: ProjectOutputPipeline,
+}
+
+impl<'a> ProjectRenderer<'a> {
+    /// Create a renderer for `project` that renders every file.
+    pub fn new(project: &'a ProjectContext, runtime: &'a dyn SystemRuntime) -> Self {
+        Self {
+            project,
+            runtime,
+            format: Format::html(),
+            incremental: false,
+            outputs: build_project_output_pipeline(),
+        }
+    }
+
+    /// Set the output format (used to locate outputs).
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Only re-render files whose inputs changed since the last render.
+    pub fn with_incremental(mut self, incremental: bool) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
+    /// Set the project output steps run after rendering (by default
+    /// [`build_project_output_pipeline`]).
+    pub fn with_outputs(mut self, outputs: ProjectOutputPipeline) -> Self {
+        self.outputs = outputs;
+        self
+    }
+
+    /// Where a document's output goes: mirrored under the project's
+    /// `output_dir`, or next to the input if there is none.
+    pub fn output_path(&self, document: &DocumentInfo) -> PathBuf {
+        self.output_context().output_path(document)
+    }
+
+    fn output_context(&self) -> ProjectOutputContext<'_> {
+        ProjectOutputContext {
+            project: self.project,
+            runtime: self.runtime,
+            format: &self.format,
+        }
+    }
+
+    /// Render the project, calling `render` for each document to render.
+    ///
+    /// Stops after the first level with a failed document and returns that
+    /// document's error; state for documents rendered so far is kept.
+    pub fn render<F>(&self, render: F) -> Result<ProjectRenderResult>
+    where
+        F: Fn(&DocumentInfo) -> Result<()> + Sync,
+    {
+        let graph = DependencyGraph::build(self.project, self.runtime)?;
+        let documents: HashMap<&Path, &DocumentInfo> = self
+            .project
+            .files
+            .iter()
+            .map(|doc| (doc.input.as_path(), doc))
+            .collect();
+
+        let mut state = if self.incremental {
+            RenderState::load(&self.project.dir, self.runtime)
+        } else {
+            RenderState::default()
+        };
+
+        let mut result = ProjectRenderResult::default();
+        let mut dirty = vec![false; graph.files().len()];
+
+        for level in graph.levels() {
+            // Decide what needs rendering in this level
+            let mut to_render = Vec::new();
+            for &i in &level {
+                let doc = documents[graph.files()[i].as_path()];
+                let key = self.state_key(doc);
+                let fingerprint = graph.fingerprint(i, self.runtime);
+
+                let unchanged = self.incremental
+                    && fingerprint.is_some()
+                    && state.fingerprints.get(&key) == fingerprint.as_ref()
+                    && !graph.dependencies(i).iter().any(|&j| dirty[j])
+                    && self
+                        .runtime
+                        .is_file(&self.output_path(doc))
+                        .unwrap_or(false);
+
+                if unchanged {
+                    result.skipped.push(doc.input.clone());
+                } else {
+                    dirty[i] = true;
+                    to_render.push((i, doc, key, fingerprint));
+                }
+            }
+
+            // Render documents without executable code in parallel, then
+            // the ones that run an engine one at a time
+            let (sequential, parallel): (Vec<_>, Vec<_>) = to_render
+                .into_iter()
+                .partition(|(i, _, _, _)| graph.executes(*i));
+
+            let mut outcomes: Vec<_> = parallel
+                .into_par_iter()
+                .map(|(_, doc, key, fingerprint)| (doc, key, fingerprint, render(doc)))
+                .collect();
+            for (_, doc, key, fingerprint) in sequential {
+                let outcome = render(doc);
+                outcomes.push((doc, key, fingerprint, outcome));
+            }
+
+            let mut failure = None;
+            for (doc, key, fingerprint, outcome) in outcomes {
+                match outcome {
+                    Ok(()) => {
+                        result.rendered.push(doc.input.clone());
+                        if let Some(fingerprint) = fingerprint {
+                            state.fingerprints.insert(key, fingerprint);
+                        }
+                    }
+                    Err(e) => {
+                        state.fingerprints.remove(&key);
+                        failure.get_or_insert(e);
+                    }
+                }
+            }
+
+            if let Some(e) = failure {
+                state.save(&self.project.dir, self.runtime);
+                return Err(e);
+            }
+        }
+
+        state.save(&self.project.dir, self.runtime);
+
+        result.outputs = self.outputs.execute(&self.output_context())?;
+        Ok(result)
+    }
+
+    fn state_key(&self, doc: &DocumentInfo) -> String {
+        doc.input
+            .strip_prefix(&self.project.dir)
+            .unwrap_or(&doc.input)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_system_runtime::NativeRuntime;
+    use std::sync::Mutex;
+
+    fn graph(sources: &[(&str, &str)]) -> DependencyGraph {
+        let sources: Vec<(PathBuf, String)> = sources
+            .iter()
+            .map(|(path, source)| (PathBuf::from(path), source.to_string()))
+            .collect();
+        DependencyGraph::from_sources(&sources)
+    }
+
+    #[test]
+    fn test_include_dependencies() {
+        let g = graph(&[
+            (
+                "/p/index.qmd",
+                "{{< include chapter.qmd >}}\n{{< include _part.qmd >}}\n",
+            ),
+            ("/p/chapter.qmd", "# Chapter\n"),
+        ]);
+
+        assert_eq!(g.dependencies(0), &[1]);
+        assert_eq!(g.includes[0], vec![PathBuf::from("/p/_part.qmd")]);
+        assert_eq!(g.levels(), vec![vec![1], vec![0]]);
+    }
+
+    #[test]
+    fn test_listing_dependencies() {
+        let g = graph(&[
+            ("/p/blog.qmd", "---\nlisting:\n  contents: posts\n---\n"),
+            ("/p/posts/a.qmd", "# A\n"),
+            ("/p/posts/b.qmd", "# B\n"),
+            ("/p/about.qmd", "# About\n"),
+            ("/p/all.qmd", "---\nlisting: {}\n---\n"),
+        ]);
+
+        assert_eq!(g.dependencies(0), &[1, 2]);
+        assert_eq!(g.dependencies(4), &[0, 1, 2, 3]);
+        assert_eq!(g.levels(), vec![vec![1, 2, 3], vec![0], vec![4]]);
+    }
+
+    #[test]
+    fn test_crossref_dependencies_and_cycles() {
+        let g = graph(&[
+            ("/p/intro.qmd", "See @fig-plot and @sec-methods.\n"),
+            (
+                "/p/methods.qmd",
+                "## Methods {#sec-methods}\n\nAs in @sec-results.\n",
+            ),
+            (
+                "/p/results.qmd",
+                "## Results {#sec-results}\n\nSee @sec-methods.\n\n```{python}\n#| label: fig-plot\nplot()\n```\n",
+            ),
+        ]);
+
+        assert_eq!(g.dependencies(0), &[1, 2]);
+        assert_eq!(g.dependencies(1), &[2]);
+        assert_eq!(g.dependencies(2), &[1]);
+        assert!(g.executes(2));
+        assert!(!g.executes(0));
+        // methods and results refer to each other and render together
+        assert_eq!(g.levels(), vec![vec![1, 2], vec![0]]);
+    }
+
+    fn write(dir: &Path, file: &str, content: &str) {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    fn project(dir: &Path, files: &[&str]) -> ProjectContext {
+        ProjectContext {
+            dir: dir.to_path_buf(),
+            config: None,
+            is_single_file: false,
+            files: files
+                .iter()
+                .map(|f| DocumentInfo::from_path(dir.join(f)))
+                .collect(),
+            output_dir: dir.join("_site"),
+        }
+    }
+
+    /// Render callback that writes a placeholder output and records calls.
+    fn render_into<'a>(
+        renderer: &'a ProjectRenderer<'a>,
+        calls: &'a Mutex<Vec<PathBuf>>,
+    ) -> impl Fn(&DocumentInfo) -> Result<()> + Sync + 'a {
+        move |doc| {
+            let output = renderer.output_path(doc);
+            std::fs::create_dir_all(output.parent().unwrap()).unwrap();
+            std::fs::write(&output, "<html></html>").unwrap();
+            calls.lock().unwrap().push(doc.input.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_render_order_and_output_paths() {
+        let temp = tempfile::tempdir().unwrap();
+        let runtime = NativeRuntime::new();
+        write(temp.path(), "index.qmd", "See @sec-a.\n");
+        write(temp.path(), "posts/a.qmd", "# A {#sec-a}\n");
+        let project = project(temp.path(), &["index.qmd", "posts/a.qmd"]);
+
+        let renderer = ProjectRenderer::new(&project, &runtime);
+        let calls = Mutex::new(Vec::new());
+        let result = renderer.render(render_into(&renderer, &calls)).unwrap();
+
+        let order = calls.into_inner().unwrap();
+        assert_eq!(
+            order,
+            vec![
+                temp.path().join("posts/a.qmd"),
+                temp.path().join("index.qmd")
+            ]
+        );
+        assert_eq!(result.rendered, order);
+        assert!(temp.path().join("_site/posts/a.html").exists());
+    }
+
+    #[test]
+    fn test_incremental_render_skips_unchanged_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let runtime = NativeRuntime::new();
+        write(temp.path(), "index.qmd", "{{< include _intro.qmd >}}\n");
+        write(temp.path(), "_intro.qmd", "Hello\n");
+        write(temp.path(), "refs.qmd", "See @sec-other.\n");
+        write(temp.path(), "other.qmd", "# Other {#sec-other}\n");
+        let project = project(temp.path(), &["index.qmd", "other.qmd", "refs.qmd"]);
+
+        let renderer = ProjectRenderer::new(&project, &runtime).with_incremental(true);
+        let first = renderer
+            .render(render_into(&renderer, &Mutex::new(Vec::new())))
+            .unwrap();
+        assert_eq!(first.rendered.len(), 3);
+
+        // Nothing changed: everything is skipped
+        let second = renderer
+            .render(render_into(&renderer, &Mutex::new(Vec::new())))
+            .unwrap();
+        assert!(second.rendered.is_empty());
+        assert_eq!(second.skipped.len(), 3);
+
+        // Changing an include re-renders its includer; changing a file
+        // re-renders the files that refer to it
+        write(temp.path(), "_intro.qmd", "Hello again\n");
+        write(temp.path(), "other.qmd", "# Other {#sec-other}\n\nMore.\n");
+        let third = renderer
+            .render(render_into(&renderer, &Mutex::new(Vec::new())))
+            .unwrap();
+        let mut rendered = third.rendered.clone();
+        rendered.sort();
+        assert_eq!(
+            rendered,
+            vec![
+                temp.path().join("index.qmd"),
+                temp.path().join("other.qmd"),
+                temp.path().join("refs.qmd"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_failure_stops_before_dependents() {
+        let temp = tempfile::tempdir().unwrap();
+        let runtime = NativeRuntime::new();
+        write(temp.path(), "a.qmd", "# A {#sec-a}\n");
+        write(temp.path(), "b.qmd", "See @sec-a.\n");
+        let project = project(temp.path(), &["a.qmd", "b.qmd"]);
+
+        let renderer = ProjectRenderer::new(&project, &runtime);
+        let calls = Mutex::new(Vec::new());
+        let result = renderer.render(|doc| {
+            calls.lock().unwrap().push(doc.input.clone());
+            Err(QuartoError::Other(format!(
+                "failed {}",
+                doc.input.display()
+            )))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(calls.into_inner().unwrap(), vec![temp.path().join("a.qmd")]);
+    }
+}
@@ -13,14 +13,19 @@
 //! - Writers use the context to determine output paths
 
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use quarto_analysis::AnalysisContext;
 use quarto_error_reporting::DiagnosticMessage;
 use quarto_system_runtime::SystemRuntime;
 
 use crate::artifact::ArtifactStore;
+use crate::engine::ExecuteOptions;
 use crate::format::Format;
 use crate::project::{DocumentInfo, ProjectContext};
+use crate::stage::{Cancellation, NoopObserver, PipelineObserver};
+use crate::tools::{self, Tool};
+use crate::transforms::ProjectCrossrefs;
 
 /// Binary dependencies available for rendering
 #[derive(Debug, Clone, Default)]
@@ -36,6 +41,15 @@ pub struct BinaryDependencies {
 
     /// Typst binary path
     pub typst: Option<PathBuf>,
+
+    /// XeLaTeX binary path (PDF engine)
+    pub xelatex: Option<PathBuf>,
+
+    /// Tectonic binary path (PDF engine)
+    pub tectonic: Option<PathBuf>,
+
+    /// Chromium binary path (for screenshots of HTML content)
+    pub chromium: Option<PathBuf>,
 }
 
 impl BinaryDependencies {
@@ -44,13 +58,23 @@ impl BinaryDependencies {
         Self::default()
     }
 
-    /// Discover binary dependencies from environment and PATH
+    /// Discover binary dependencies from environment and PATH, falling
+    /// back to the tools installed with `quarto install`
     pub fn discover(runtime: &dyn SystemRuntime) -> Self {
         Self {
             dart_sass: runtime.find_binary("sass", "QUARTO_DART_SASS"),
             esbuild: runtime.find_binary("esbuild", "QUARTO_ESBUILD"),
             pandoc: runtime.find_binary("pandoc", "QUARTO_PANDOC"),
             typst: runtime.find_binary("typst", "QUARTO_TYPST"),
+            xelatex: runtime
+                .find_binary("xelatex", "QUARTO_XELATEX")
+                .or_else(|| tools::installed_binary(Tool::TinyTex, "xelatex", runtime)),
+            tectonic: runtime.find_binary("tectonic", "QUARTO_TECTONIC"),
+            chromium: runtime
+                .find_binary("chromium", "QUARTO_CHROMIUM")
+                .or_else(|| {
+                    tools::installed_binary(Tool::Chromium, "chrome-headless-shell", runtime)
+                }),
         }
     }
 
@@ -63,6 +87,16 @@ impl BinaryDependencies {
     pub fn has_pandoc(&self) -> bool {
         self.pandoc.is_some()
     }
+
+    /// Check if a LaTeX engine (XeLaTeX or Tectonic) is available
+    pub fn has_latex(&self) -> bool {
+        self.xelatex.is_some() || self.tectonic.is_some()
+    }
+
+    /// Check if Chromium is available
+    pub fn has_chromium(&self) -> bool {
+        self.chromium.is_some()
+    }
 }
 
 /// Context for a single document render operation.
@@ -97,6 +131,15 @@ pub struct RenderContext<'a> {
 
     /// Diagnostics (warnings, errors, info) collected during transforms
     pub diagnostics: Vec<DiagnosticMessage>,
+
+    /// System runtime, for transforms that read other project files
+    pub runtime: Option<Arc<dyn SystemRuntime>>,
+
+    /// Cancellation token, checked between pipeline stages and transforms
+    pub cancellation: Cancellation,
+
+    /// Observer notified of the pipeline's progress
+    pub observer: Arc<dyn PipelineObserver>,
 }
 
 /// Options for rendering
@@ -113,6 +156,46 @@ pub struct RenderOptions {
 
     /// Custom output path (overrides format-determined path)
     pub output_path: Option<PathBuf>,
+
+    /// Execution parameters (`-P KEY:VALUE`, `--execute-params`)
+    pub execute_params: serde_json::Map<String, serde_json::Value>,
+
+    /// Working directory for code execution
+    pub execute_dir: Option<PathBuf>,
+
+    /// Kernel keepalive in seconds (`--execute-daemon`)
+    pub execute_daemon: Option<u32>,
+
+    /// Restart a kept-alive kernel before rendering
+    pub execute_daemon_restart: bool,
+
+    /// Cache cell outputs (`Some(false)` for `--no-cache`)
+    pub cache: Option<bool>,
+
+    /// Force refresh of the cell output cache
+    pub cache_refresh: bool,
+
+    /// Crossref targets shared across the project's documents (books)
+    pub crossrefs: Option<ProjectCrossrefs>,
+
+    /// Metadata overrides (`-M KEY:VALUE`), taking precedence over the
+    /// document's front matter and metadata files
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RenderOptions {
+    /// The execution options for the engine execution stage.
+    pub fn execute_options(&self) -> ExecuteOptions {
+        ExecuteOptions {
+            enabled: self.execute,
+            params: self.execute_params.clone(),
+            dir: self.execute_dir.clone(),
+            daemon: self.execute_daemon,
+            daemon_restart: self.execute_daemon_restart,
+            cache: self.cache,
+            cache_refresh: self.cache_refresh,
+        }
+    }
 }
 
 impl<'a> RenderContext<'a> {
@@ -131,6 +214,9 @@ impl<'a> RenderContext<'a> {
             binaries,
             options: RenderOptions::default(),
             diagnostics: Vec::new(),
+            runtime: None,
+            cancellation: Cancellation::new(),
+            observer: Arc::new(NoopObserver),
         }
     }
 
@@ -140,6 +226,46 @@ impl<'a> RenderContext<'a> {
         self
     }
 
+    /// Create with a system runtime
+    pub fn with_runtime(mut self, runtime: Arc<dyn SystemRuntime>) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// Create with a cancellation token, to stop the render from elsewhere
+    /// (e.g. when a newer render of the document starts)
+    pub fn with_cancellation(mut self, cancellation: Cancellation) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Create with an observer of the pipeline's progress
+    pub fn with_observer(mut self, observer: Arc<dyn PipelineObserver>) -> Self {
+        self.observer = observer;
+        self
+    }
+
+    /// A context for rendering the same document to `format`.
+    ///
+    /// The project, document, binaries, options, runtime, cancellation and
+    /// observer are shared; the
+    /// artifacts and diagnostics, which belong to a single format's render,
+    /// start out empty.
+    pub fn for_format(&self, format: &'a Format) -> Self {
+        Self {
+            artifacts: ArtifactStore::new(),
+            project: self.project,
+            document: self.document,
+            format,
+            binaries: self.binaries,
+            options: self.options.clone(),
+            diagnostics: Vec::new(),
+            runtime: self.runtime.clone(),
+            cancellation: self.cancellation.clone(),
+            observer: self.observer.clone(),
+        }
+    }
+
     /// Get the output path for this render
     ///
     /// Priority:
@@ -246,6 +372,18 @@ mod tests {
         assert!(deps.esbuild.is_none());
         assert!(deps.pandoc.is_none());
         assert!(deps.typst.is_none());
+        assert!(deps.xelatex.is_none());
+        assert!(deps.tectonic.is_none());
+        assert!(!deps.has_latex());
+    }
+
+    #[test]
+    fn test_binary_dependencies_has_latex_with_tectonic() {
+        let deps = BinaryDependencies {
+            tectonic: Some(PathBuf::from("/usr/bin/tectonic")),
+            ..Default::default()
+        };
+        assert!(deps.has_latex());
     }
 
     #[test]
@@ -298,6 +436,7 @@ mod tests {
             execute: true,
             use_freeze: false,
             output_path: Some(PathBuf::from("/output")),
+            ..Default::default()
         };
         let cloned = options.clone();
         assert_eq!(options.verbose, cloned.verbose);
@@ -305,6 +444,26 @@ mod tests {
         assert_eq!(options.output_path, cloned.output_path);
     }
 
+    #[test]
+    fn test_render_options_execute_options() {
+        let mut params = serde_json::Map::new();
+        params.insert("n".to_string(), serde_json::json!(10));
+        let options = RenderOptions {
+            execute: true,
+            execute_params: params,
+            execute_dir: Some(PathBuf::from("/work")),
+            execute_daemon: Some(60),
+            ..Default::default()
+        };
+
+        let execute = options.execute_options();
+        assert!(execute.enabled);
+        assert_eq!(execute.params.get("n"), Some(&serde_json::json!(10)));
+        assert_eq!(execute.dir, Some(PathBuf::from("/work")));
+        assert_eq!(execute.daemon, Some(60));
+        assert!(!execute.daemon_restart);
+    }
+
     // === RenderContext tests ===
 
     #[test]
@@ -430,6 +589,27 @@ mod tests {
         assert_eq!(ctx.diagnostics.len(), 1);
     }
 
+    #[test]
+    fn test_render_context_for_format() {
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let html = Format::html();
+        let pdf = Format::pdf();
+        let binaries = BinaryDependencies::new();
+
+        let options = RenderOptions {
+            execute: true,
+            ..Default::default()
+        };
+        let mut ctx = RenderContext::new(&project, &doc, &html, &binaries).with_options(options);
+        ctx.add_diagnostic(DiagnosticMessage::warning("Test warning".to_string()));
+
+        let pdf_ctx = ctx.for_format(&pdf);
+        assert_eq!(pdf_ctx.output_path(), PathBuf::from("/project/doc.pdf"));
+        assert!(pdf_ctx.options.execute);
+        assert!(pdf_ctx.diagnostics.is_empty());
+    }
+
     // === RenderResult tests ===
 
     #[test]
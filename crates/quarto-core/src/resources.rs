@@ -50,6 +50,10 @@ use crate::Result;
 /// Default CSS styles, embedded at compile time.
 pub const DEFAULT_CSS: &str = include_str!("../resources/styles.css");
 
+/// Script that switches between a document's light and dark stylesheets,
+/// embedded at compile time.
+pub const COLOR_SCHEME_TOGGLE_JS: &str = include_str!("../resources/color-scheme-toggle.js");
+
 /// Paths to HTML resources, relative to the output HTML file.
 ///
 /// These paths are suitable for use in HTML `<link>` and `<script>` tags.
@@ -57,6 +61,8 @@ pub const DEFAULT_CSS: &str = include_str!("../resources/styles.css");
 pub struct HtmlResourcePaths {
     /// Relative paths to CSS files
     pub css: Vec<String>,
+    /// Relative paths to dark color scheme CSS files (empty without dark mode)
+    pub dark_css: Vec<String>,
     /// Relative paths to JS files (for future use)
     pub js: Vec<String>,
     /// The resource directory path (absolute)
@@ -68,6 +74,7 @@ impl HtmlResourcePaths {
     pub fn empty() -> Self {
         Self {
             css: Vec::new(),
+            dark_css: Vec::new(),
             js: Vec::new(),
             resource_dir: PathBuf::new(),
         }
@@ -129,6 +136,7 @@ pub fn write_html_resources(
 
     Ok(HtmlResourcePaths {
         css: vec![css_relative],
+        dark_css: Vec::new(),
         js: Vec::new(), // No JS resources yet
         resource_dir,
     })
@@ -149,7 +157,10 @@ pub fn resource_dir_name(stem: &str) -> String {
 ///
 /// This is the SASS-enabled version of [`write_html_resources`]. It compiles
 /// SCSS to CSS based on the theme configuration, or uses default Bootstrap
-/// when no theme is specified.
+/// when no theme is specified. A config with dark themes also gets
+/// `styles-dark.css`, listed in [`HtmlResourcePaths::dark_css`]. Web
+/// fonts the CSS imports are downloaded into `{stem}_files/fonts/` (see
+/// [`quarto_sass::fonts`]) so the page works offline.
 ///
 /// # Arguments
 ///
@@ -189,7 +200,7 @@ pub fn write_html_resources_with_sass(
     context: &quarto_sass::ThemeContext,
     runtime: &dyn SystemRuntime,
 ) -> Result<HtmlResourcePaths> {
-    use quarto_sass::compile_theme_css;
+    use quarto_sass::{compile_dark_theme_css, compile_theme_css};
 
     // Create resource directory: {stem}_files/
     let resource_dir_name = format!("{}_files", stem);
@@ -206,6 +217,7 @@ pub fn write_html_resources_with_sass(
     // Compile CSS from theme config
     let css = compile_theme_css(theme_config, context)
         .map_err(|e| crate::error::QuartoError::other(format!("SASS compilation failed: {}", e)))?;
+    let css = write_embedded_fonts(&css, &resource_dir, context, runtime)?;
 
     // Write compiled CSS
     let css_filename = "styles.css";
@@ -218,16 +230,75 @@ pub fn write_html_resources_with_sass(
         ))
     })?;
 
+    // Compile and write the dark stylesheet, if dark mode is configured
+    let dark_css = compile_dark_theme_css(theme_config, context)
+        .map_err(|e| crate::error::QuartoError::other(format!("SASS compilation failed: {}", e)))?;
+    let mut dark_css_paths = Vec::new();
+    if let Some(dark_css) = dark_css {
+        let dark_css = write_embedded_fonts(&dark_css, &resource_dir, context, runtime)?;
+        let dark_filename = "styles-dark.css";
+        let dark_path = resource_dir.join(dark_filename);
+        runtime
+            .file_write(&dark_path, dark_css.as_bytes())
+            .map_err(|e| {
+                crate::error::QuartoError::other(format!(
+                    "Failed to write CSS to {}: {}",
+                    dark_path.display(),
+                    e
+                ))
+            })?;
+        dark_css_paths.push(format!("{}/{}", resource_dir_name, dark_filename));
+    }
+
     // Build relative paths for template
     let css_relative = format!("{}/{}", resource_dir_name, css_filename);
 
     Ok(HtmlResourcePaths {
         css: vec![css_relative],
+        dark_css: dark_css_paths,
         js: Vec::new(),
         resource_dir,
     })
 }
 
+/// Embed the web fonts `css` imports, writing the font files under
+/// `resource_dir` and returning the rewritten CSS.
+///
+/// Fonts that can't be downloaded stay remote imports.
+#[cfg(not(target_arch = "wasm32"))]
+fn write_embedded_fonts(
+    css: &str,
+    resource_dir: &Path,
+    context: &quarto_sass::ThemeContext,
+    runtime: &dyn SystemRuntime,
+) -> Result<String> {
+    let embedded = quarto_sass::embed_web_fonts(css, runtime, context.cache());
+    for url in &embedded.remote {
+        tracing::debug!("Could not embed web fonts from {}", url);
+    }
+    if !embedded.files.is_empty() {
+        let fonts_dir = resource_dir.join(quarto_sass::FONTS_DIR);
+        runtime.dir_create(&fonts_dir, true).map_err(|e| {
+            crate::error::QuartoError::other(format!(
+                "Failed to create font directory {}: {}",
+                fonts_dir.display(),
+                e
+            ))
+        })?;
+    }
+    for font in &embedded.files {
+        let path = resource_dir.join(&font.path);
+        runtime.file_write(&path, &font.content).map_err(|e| {
+            crate::error::QuartoError::other(format!(
+                "Failed to write font to {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+    }
+    Ok(embedded.css)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -285,6 +356,7 @@ mod tests {
     fn test_html_resource_paths_empty() {
         let paths = HtmlResourcePaths::empty();
         assert!(paths.css.is_empty());
+        assert!(paths.dark_css.is_empty());
         assert!(paths.js.is_empty());
     }
 
@@ -365,6 +437,29 @@ mod tests {
         // Should return correct relative path
         assert_eq!(paths.css.len(), 1);
         assert_eq!(paths.css[0], "mydoc_files/styles.css");
+        assert!(paths.dark_css.is_empty());
+    }
+
+    #[test]
+    fn test_write_html_resources_with_sass_dark_mode() {
+        use quarto_sass::{ThemeConfig, ThemeContext, ThemeSpec};
+
+        let runtime = NativeRuntime::new();
+        let temp = TempDir::new().unwrap();
+
+        let mut theme_config = ThemeConfig::new(vec![ThemeSpec::parse("cosmo").unwrap()], true);
+        theme_config.dark_themes = vec![ThemeSpec::parse("darkly").unwrap()];
+        let context = ThemeContext::new(temp.path().to_path_buf(), &runtime);
+
+        let paths =
+            write_html_resources_with_sass(temp.path(), "mydoc", &theme_config, &context, &runtime)
+                .unwrap();
+
+        assert_eq!(paths.css, vec!["mydoc_files/styles.css"]);
+        assert_eq!(paths.dark_css, vec!["mydoc_files/styles-dark.css"]);
+        let light = fs::read_to_string(temp.path().join("mydoc_files/styles.css")).unwrap();
+        let dark = fs::read_to_string(temp.path().join("mydoc_files/styles-dark.css")).unwrap();
+        assert_ne!(light, dark);
     }
 }
 
@@ -0,0 +1,237 @@
+/*
+ * revealjs.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * The reveal.js runtime for `format: revealjs`.
+ */
+
+//! The reveal.js runtime for `format: revealjs`.
+//!
+//! Presentations load reveal.js, its speaker notes plugin and a theme from
+//! the jsDelivr CDN. [`store_revealjs_dependencies`] records these in the
+//! ArtifactStore and returns the markup that loads them, which the slides
+//! transform exposes to the revealjs template as `rendered.revealjs.head`
+//! and `rendered.revealjs.scripts`.
+//!
+//! Presentation options from the format (`slide-number`, `transition`,
+//! `controls`, ...) are passed to `Reveal.initialize` under their reveal.js
+//! (camelCase) names.
+
+use serde_json::{Map, Value};
+
+use crate::artifact::{Artifact, ArtifactStore};
+use crate::format::Format;
+
+/// The reveal.js release loaded from the CDN.
+pub const REVEALJS_VERSION: &str = "5.1.0";
+
+/// Themes that ship with reveal.js. Other `theme` values are taken as the
+/// path of a stylesheet.
+pub const REVEALJS_THEMES: &[&str] = &[
+    "beige",
+    "black",
+    "black-contrast",
+    "blood",
+    "dracula",
+    "league",
+    "moon",
+    "night",
+    "serif",
+    "simple",
+    "sky",
+    "solarized",
+    "white",
+    "white-contrast",
+];
+
+/// Theme used when the format sets none.
+const DEFAULT_THEME: &str = "white";
+
+/// Format options passed to `Reveal.initialize`, with their reveal.js names.
+const REVEALJS_OPTIONS: &[(&str, &str)] = &[
+    ("controls", "controls"),
+    ("controls-tutorial", "controlsTutorial"),
+    ("controls-layout", "controlsLayout"),
+    ("progress", "progress"),
+    ("slide-number", "slideNumber"),
+    ("show-slide-number", "showSlideNumber"),
+    ("hash", "hash"),
+    ("history", "history"),
+    ("keyboard", "keyboard"),
+    ("overview", "overview"),
+    ("center", "center"),
+    ("touch", "touch"),
+    ("loop", "loop"),
+    ("rtl", "rtl"),
+    ("navigation-mode", "navigationMode"),
+    ("fragments", "fragments"),
+    ("embedded", "embedded"),
+    ("help", "help"),
+    ("show-notes", "showNotes"),
+    ("auto-play-media", "autoPlayMedia"),
+    ("auto-slide", "autoSlide"),
+    ("auto-slide-stoppable", "autoSlideStoppable"),
+    ("mouse-wheel", "mouseWheel"),
+    ("preview-links", "previewLinks"),
+    ("transition", "transition"),
+    ("transition-speed", "transitionSpeed"),
+    ("background-transition", "backgroundTransition"),
+    ("width", "width"),
+    ("height", "height"),
+    ("margin", "margin"),
+    ("min-scale", "minScale"),
+    ("max-scale", "maxScale"),
+];
+
+/// Marks the items of `.incremental` lists as fragments before reveal.js
+/// collects the fragments of each slide.
+const INCREMENTAL_SCRIPT: &str = r#"document
+  .querySelectorAll(".reveal .incremental > ul > li, .reveal .incremental > ol > li")
+  .forEach(function (item) { item.classList.add("fragment"); });
+"#;
+
+/// The markup loading the reveal.js runtime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevealjsDependencies {
+    /// Stylesheets, for the document head
+    pub head: String,
+    /// Scripts and the `Reveal.initialize` call, for the end of the body
+    pub scripts: String,
+}
+
+/// The CDN address of a file in the reveal.js package.
+pub fn revealjs_url(file: &str) -> String {
+    format!(
+        "https://cdn.jsdelivr.net/npm/reveal.js@{}/{}",
+        REVEALJS_VERSION, file
+    )
+}
+
+/// An artifact standing for a dependency loaded from `url`.
+fn url_artifact(url: &str, content_type: &str) -> Artifact {
+    Artifact::from_bytes(Vec::new(), content_type).with_metadata("url", serde_json::json!(url))
+}
+
+/// Store the reveal.js runtime for `format` and return the markup that
+/// loads it.
+///
+/// Artifacts use the `js:<name>` / `css:<name>` keys. Dependencies loaded
+/// from the CDN have empty content and their address in the `url`
+/// metadata; the initialization script is stored as `js:revealjs-init`.
+pub fn store_revealjs_dependencies(
+    format: &Format,
+    artifacts: &mut ArtifactStore,
+) -> RevealjsDependencies {
+    let theme = format.get_metadata_string("theme").unwrap_or(DEFAULT_THEME);
+    let theme_href = if REVEALJS_THEMES.contains(&theme) {
+        revealjs_url(&format!("dist/theme/{}.css", theme))
+    } else {
+        theme.to_string()
+    };
+
+    let stylesheets = [
+        ("css:revealjs-reset", revealjs_url("dist/reset.css")),
+        ("css:revealjs", revealjs_url("dist/reveal.css")),
+    ];
+    let mut head = Vec::new();
+    for (key, url) in &stylesheets {
+        artifacts.store(*key, url_artifact(url, "text/css"));
+        head.push(format!("<link rel=\"stylesheet\" href=\"{}\">", url));
+    }
+    artifacts.store("css:revealjs-theme", url_artifact(&theme_href, "text/css"));
+    head.push(format!(
+        "<link rel=\"stylesheet\" href=\"{}\" id=\"theme\">",
+        theme_href
+    ));
+
+    let scripts_urls = [
+        ("js:revealjs", revealjs_url("dist/reveal.js")),
+        ("js:revealjs-notes", revealjs_url("plugin/notes/notes.js")),
+    ];
+    let mut scripts = Vec::new();
+    for (key, url) in &scripts_urls {
+        artifacts.store(*key, url_artifact(url, "text/javascript"));
+        scripts.push(format!("<script src=\"{}\"></script>", url));
+    }
+    let init = init_script(format);
+    artifacts.store(
+        "js:revealjs-init",
+        Artifact::from_string(init.clone(), "text/javascript"),
+    );
+    scripts.push(format!("<script>\n{}</script>", init));
+
+    RevealjsDependencies {
+        head: head.join("\n"),
+        scripts: scripts.join("\n"),
+    }
+}
+
+/// The script that marks incremental lists and starts reveal.js with the
+/// format's options.
+fn init_script(format: &Format) -> String {
+    let mut options = Map::new();
+    options.insert("hash".to_string(), Value::Bool(true));
+    for (key, name) in REVEALJS_OPTIONS {
+        if let Some(value) = format.get_metadata(key) {
+            options.insert(name.to_string(), value.clone());
+        }
+    }
+    let options = Value::Object(options).to_string();
+    // The plugin list is code, not JSON
+    let options = format!(
+        "{}, plugins: [RevealNotes] }}",
+        options.strip_suffix('}').unwrap_or(&options)
+    );
+    format!("{}Reveal.initialize({});\n", INCREMENTAL_SCRIPT, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_dependencies() {
+        let mut artifacts = ArtifactStore::new();
+        let deps = store_revealjs_dependencies(&Format::revealjs(), &mut artifacts);
+        assert!(deps.head.contains(&revealjs_url("dist/reveal.css")));
+        assert!(deps.head.contains(&revealjs_url("dist/theme/white.css")));
+        assert!(deps.scripts.contains(&revealjs_url("dist/reveal.js")));
+        assert!(
+            deps.scripts
+                .contains("Reveal.initialize({\"hash\":true, plugins: [RevealNotes] });")
+        );
+        for key in [
+            "css:revealjs",
+            "css:revealjs-theme",
+            "js:revealjs",
+            "js:revealjs-notes",
+            "js:revealjs-init",
+        ] {
+            assert!(artifacts.get(key).is_some(), "missing {}", key);
+        }
+        assert_eq!(
+            artifacts.get("js:revealjs").unwrap().metadata.get("url"),
+            Some(&serde_json::json!(revealjs_url("dist/reveal.js")))
+        );
+    }
+
+    #[test]
+    fn test_options_and_custom_theme() {
+        let format = Format::revealjs().with_metadata(serde_json::json!({
+            "theme": "slides.css",
+            "slide-number": true,
+            "transition": "fade",
+            "incremental": true,
+        }));
+        let mut artifacts = ArtifactStore::new();
+        let deps = store_revealjs_dependencies(&format, &mut artifacts);
+        assert!(
+            deps.head
+                .contains("<link rel=\"stylesheet\" href=\"slides.css\" id=\"theme\">")
+        );
+        let init = artifacts.get("js:revealjs-init").unwrap().as_string();
+        assert!(init.contains("\"slideNumber\":true"));
+        assert!(init.contains("\"transition\":\"fade\""));
+        assert!(!init.contains("incremental\":"));
+    }
+}
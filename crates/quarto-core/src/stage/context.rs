@@ -26,8 +26,10 @@ use super::cancellation::Cancellation;
 use super::error::PipelineError;
 use super::observer::{NoopObserver, PipelineObserver};
 use crate::artifact::ArtifactStore;
+use crate::engine::ExecuteOptions;
 use crate::format::Format;
 use crate::project::{DocumentInfo, ProjectContext};
+use crate::transforms::ProjectCrossrefs;
 
 /// Owned context passed to all pipeline stages.
 ///
@@ -62,6 +64,15 @@ pub struct StageContext {
     /// Temporary directory for this pipeline run
     pub temp_dir: PathBuf,
 
+    /// Code execution options (`--execute`, `--execute-params`, ...)
+    pub execute: ExecuteOptions,
+
+    /// Crossref targets shared with the project's other documents (books)
+    pub crossrefs: Option<ProjectCrossrefs>,
+
+    /// Metadata overrides (`-M KEY:VALUE`), applied over the front matter
+    pub metadata_overrides: serde_json::Map<String, serde_json::Value>,
+
     // === Mutable state ===
     /// Artifact store for dependencies and intermediates
     pub artifacts: ArtifactStore,
@@ -102,6 +113,9 @@ impl StageContext {
             project,
             document,
             temp_dir,
+            execute: ExecuteOptions::default(),
+            crossrefs: None,
+            metadata_overrides: serde_json::Map::new(),
             artifacts: ArtifactStore::new(),
             diagnostics: Vec::new(),
             observer: Arc::new(NoopObserver),
@@ -127,6 +141,27 @@ impl StageContext {
         self
     }
 
+    /// Set code execution options.
+    pub fn with_execute_options(mut self, execute: ExecuteOptions) -> Self {
+        self.execute = execute;
+        self
+    }
+
+    /// Share crossref targets with the project's other documents.
+    pub fn with_crossrefs(mut self, crossrefs: Option<ProjectCrossrefs>) -> Self {
+        self.crossrefs = crossrefs;
+        self
+    }
+
+    /// Set metadata overrides (`-M KEY:VALUE`).
+    pub fn with_metadata_overrides(
+        mut self,
+        overrides: serde_json::Map<String, serde_json::Value>,
+    ) -> Self {
+        self.metadata_overrides = overrides;
+        self
+    }
+
     /// Check if cancellation has been requested.
     ///
     /// Stages should call this periodically during long-running
@@ -402,6 +437,7 @@ mod tests {
         assert!(!ctx.is_cancelled());
         assert!(ctx.diagnostics.is_empty());
         assert!(ctx.artifacts.is_empty());
+        assert!(ctx.execute.enabled);
     }
 
     #[test]
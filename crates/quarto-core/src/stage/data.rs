@@ -285,7 +285,7 @@ impl DocumentSource {
 ///
 /// Note: `ast_context` is mutable throughout the pipeline because
 /// AST transforms may create new objects that need source info tracking.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DocumentAst {
     /// Path to the source file
     pub path: PathBuf,
@@ -91,6 +91,7 @@ pub mod stages;
 mod traits;
 
 // Re-export public types
+pub use cancellation::Cancellation;
 pub use context::StageContext;
 pub use data::{
     DocumentAst, DocumentSource, ExecutedDocument, FinalOutput, LoadedSource, PandocIncludes,
@@ -103,8 +104,9 @@ pub use traits::PipelineStage;
 
 // Re-export concrete stages for convenience
 pub use stages::{
-    ApplyTemplateStage, AstTransformsStage, EngineExecutionStage, ParseDocumentStage,
-    RenderHtmlBodyStage,
+    ApplyTemplateStage, AstTransformsStage, CompileLatexStage, CompileTypstStage,
+    EngineExecutionStage, ParseDocumentStage, PostprocessHtmlStage, RenderHtmlBodyStage,
+    RenderLatexStage, RenderTypstStage,
 };
 
 // Re-export the trace_event macro
@@ -13,6 +13,11 @@
 //! - Stage composition validation
 //! - Sequential execution with observer notifications
 //! - Cancellation support
+//!
+//! Each stage runs in a `stage` tracing span, so log messages can be
+//! attributed to the stage that emitted them.
+
+use tracing::Instrument;
 
 use super::context::StageContext;
 use super::data::{PipelineData, PipelineDataKind};
@@ -166,7 +171,8 @@ impl Pipeline {
 
             ctx.observer.on_stage_start(stage.name(), idx, total);
 
-            match stage.run(data, ctx).await {
+            let span = tracing::info_span!("stage", name = stage.name());
+            match stage.run(data, ctx).instrument(span).await {
                 Ok(output) => {
                     ctx.observer.on_stage_complete(stage.name(), idx, total);
                     data = output;
@@ -10,14 +10,16 @@
 //! This stage wraps the rendered HTML body with a complete HTML document
 //! using the template engine.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
 use quarto_doctemplate::Template;
+use quarto_pandoc_types::ConfigValue;
+use quarto_source_map::SourceInfo;
 
 use crate::artifact::Artifact;
-use crate::pipeline::DEFAULT_CSS_ARTIFACT_PATH;
-use crate::resources::DEFAULT_CSS;
+use crate::pipeline::{COLOR_SCHEME_TOGGLE_ARTIFACT_PATH, DEFAULT_CSS_ARTIFACT_PATH};
+use crate::resources::{COLOR_SCHEME_TOGGLE_JS, DEFAULT_CSS};
 use crate::stage::{
     EventLevel, PipelineData, PipelineDataKind, PipelineError, PipelineStage, StageContext,
 };
@@ -29,6 +31,9 @@ use crate::trace_event;
 pub struct ApplyTemplateConfig {
     /// CSS paths to include in the document (relative to the output HTML).
     pub css_paths: Vec<String>,
+    /// Dark color scheme CSS paths; if non-empty, the document gets a
+    /// light/dark toggle.
+    pub dark_css_paths: Vec<String>,
     /// Custom template to use instead of the built-in default.
     pub template: Option<Template>,
 }
@@ -45,6 +50,12 @@ impl ApplyTemplateConfig {
         self
     }
 
+    /// Set dark color scheme CSS paths.
+    pub fn with_dark_css_paths(mut self, paths: Vec<String>) -> Self {
+        self.dark_css_paths = paths;
+        self
+    }
+
     /// Set a custom template.
     pub fn with_template(mut self, template: Template) -> Self {
         self.template = Some(template);
@@ -57,12 +68,15 @@ impl ApplyTemplateConfig {
 /// This stage:
 /// 1. Takes a RenderedOutput with HTML body content
 /// 2. Applies the HTML template with metadata
-/// 3. Stores the default CSS as an artifact
+/// 3. Stores the default CSS (and, with dark mode, the color scheme toggle
+///    script) as artifacts
 /// 4. Returns a RenderedOutput with the complete HTML document
 ///
 /// # Configuration
 ///
 /// - `css_paths`: CSS paths to include in the document
+/// - `dark_css_paths`: dark stylesheets, linked disabled at
+///   `rendered.color-scheme` along with the toggle script
 /// - `template`: Custom template (defaults to built-in HTML5 template)
 ///
 /// # Input
@@ -143,10 +157,39 @@ impl PipelineStage for ApplyTemplateStage {
         );
 
         // Get metadata from the rendered output
-        let metadata = rendered.metadata.clone();
+        let mut metadata = rendered.metadata.clone();
+
+        // Link the dark stylesheets and the toggle script
+        if !self.config.dark_css_paths.is_empty() {
+            ctx.artifacts.store(
+                "js:color-scheme-toggle",
+                Artifact::from_string(COLOR_SCHEME_TOGGLE_JS, "text/javascript")
+                    .with_path(PathBuf::from(COLOR_SCHEME_TOGGLE_ARTIFACT_PATH)),
+            );
+            metadata.insert_path(
+                &["rendered", "color-scheme"],
+                ConfigValue::new_string(
+                    color_scheme_head(&self.config.dark_css_paths),
+                    SourceInfo::default(),
+                ),
+            );
+        }
+
+        // A `template:` in the format metadata stands in for a template the
+        // pipeline wasn't configured with
+        let format_template = match &self.config.template {
+            Some(_) => None,
+            None => template::format_template(
+                &rendered.format,
+                None,
+                rendered.input_path.parent().unwrap_or(Path::new("")),
+                ctx.runtime.as_ref(),
+            )
+            .map_err(|e| PipelineError::stage_error(self.name(), e.to_string()))?,
+        };
 
         // Apply template
-        let html = match &self.config.template {
+        let html = match self.config.template.as_ref().or(format_template.as_ref()) {
             Some(template) => {
                 // Use custom template if provided
                 template::render_with_custom_template(template, &rendered.content, &metadata)
@@ -185,6 +228,41 @@ impl PipelineStage for ApplyTemplateStage {
     }
 }
 
+/// Styles for the color scheme toggle button: a moon in light mode, a sun
+/// in dark mode.
+const COLOR_SCHEME_TOGGLE_CSS: &str = r#".quarto-color-scheme-toggle {
+  position: fixed;
+  top: 0.75rem;
+  right: 0.75rem;
+  z-index: 1040;
+  border: 0;
+  background: none;
+  color: inherit;
+  font-size: 1.25rem;
+  line-height: 1;
+  cursor: pointer;
+}
+.quarto-color-scheme-toggle::before { content: "\263E"; }
+.quarto-dark .quarto-color-scheme-toggle::before { content: "\2600"; }
+"#;
+
+/// Head content for dark mode: the dark stylesheets (disabled until the
+/// toggle script enables them) and the script itself.
+fn color_scheme_head(dark_css_paths: &[String]) -> String {
+    let mut head: Vec<String> = dark_css_paths
+        .iter()
+        .map(|path| {
+            format!(
+                "<link rel=\"stylesheet\" href=\"{}\" class=\"quarto-color-scheme quarto-color-alternate\" disabled>",
+                path
+            )
+        })
+        .collect();
+    head.push(format!("<style>\n{}</style>", COLOR_SCHEME_TOGGLE_CSS));
+    head.push(format!("<script>\n{}</script>", COLOR_SCHEME_TOGGLE_JS));
+    head.join("\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -371,4 +449,55 @@ mod tests {
         // Should have the default CSS artifact stored
         assert!(ctx.artifacts.get("css:default").is_some());
     }
+
+    #[tokio::test]
+    async fn test_apply_template_dark_mode() {
+        let runtime = Arc::new(MockRuntime);
+        let project = ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![],
+            output_dir: PathBuf::from("/project"),
+        };
+        let doc = DocumentInfo::from_path("/project/test.qmd");
+        let format = Format::html();
+
+        let mut ctx = StageContext::new(runtime, format.clone(), project, doc).unwrap();
+
+        let stage = ApplyTemplateStage::with_config(
+            ApplyTemplateConfig::new()
+                .with_css_paths(vec!["test_files/styles.css".to_string()])
+                .with_dark_css_paths(vec!["test_files/styles-dark.css".to_string()]),
+        );
+
+        let rendered = RenderedOutput {
+            input_path: PathBuf::from("/project/test.qmd"),
+            output_path: PathBuf::from("/project/test.html"),
+            format,
+            content: "<p>Hello, world!</p>".to_string(),
+            is_intermediate: false,
+            supporting_files: vec![],
+            metadata: ConfigValue::null(SourceInfo::default()),
+        };
+
+        let output = stage
+            .run(PipelineData::RenderedOutput(rendered), &mut ctx)
+            .await
+            .unwrap();
+
+        let html = output
+            .into_rendered_output()
+            .expect("Should be RenderedOutput")
+            .content;
+        assert!(html.contains(
+            "<link rel=\"stylesheet\" href=\"test_files/styles-dark.css\" class=\"quarto-color-scheme quarto-color-alternate\" disabled>"
+        ));
+        assert!(html.contains("quartoToggleColorScheme"));
+        assert!(html.contains("<button type=\"button\" class=\"quarto-color-scheme-toggle\""));
+        let light = html.find("test_files/styles.css").unwrap();
+        let dark = html.find("test_files/styles-dark.css").unwrap();
+        assert!(light < dark);
+        assert!(ctx.artifacts.get("js:color-scheme-toggle").is_some());
+    }
 }
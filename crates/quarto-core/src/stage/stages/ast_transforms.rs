@@ -13,8 +13,9 @@
 use async_trait::async_trait;
 use quarto_config::MergedConfig;
 
+use crate::error::QuartoError;
 use crate::pipeline::build_transform_pipeline;
-use crate::render::{BinaryDependencies, RenderContext};
+use crate::render::{BinaryDependencies, RenderContext, RenderOptions};
 use crate::stage::{
     EventLevel, PipelineData, PipelineDataKind, PipelineError, PipelineStage, StageContext,
 };
@@ -141,7 +142,13 @@ impl PipelineStage for AstTransformsStage {
         // Create a RenderContext from StageContext data.
         // We use std::mem::take to temporarily transfer ownership of artifacts.
         let mut render_ctx =
-            RenderContext::new(&ctx.project, &ctx.document, &ctx.format, &binaries);
+            RenderContext::new(&ctx.project, &ctx.document, &ctx.format, &binaries)
+                .with_options(RenderOptions {
+                    crossrefs: ctx.crossrefs.clone(),
+                    ..Default::default()
+                })
+                .with_runtime(ctx.runtime.clone())
+                .with_cancellation(ctx.cancellation.clone());
 
         // Transfer artifacts to the RenderContext
         render_ctx.artifacts = std::mem::take(&mut ctx.artifacts);
@@ -156,7 +163,10 @@ impl PipelineStage for AstTransformsStage {
         ctx.diagnostics.extend(render_ctx.diagnostics);
 
         // Handle result
-        result.map_err(|e| PipelineError::stage_error(self.name(), e.to_string()))?;
+        result.map_err(|e| match e {
+            QuartoError::Cancelled => PipelineError::Cancelled,
+            e => PipelineError::stage_error(self.name(), e.to_string()),
+        })?;
 
         trace_event!(ctx, EventLevel::Debug, "AST transforms complete");
 
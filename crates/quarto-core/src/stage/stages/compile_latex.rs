@@ -0,0 +1,278 @@
+/*
+ * stage/stages/compile_latex.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Compile LaTeX to PDF.
+ */
+
+//! Compile LaTeX to PDF.
+//!
+//! This stage writes the LaTeX source next to the output PDF and runs the
+//! PDF engine over it (see [`crate::latex`]). Afterwards the engine's
+//! auxiliary files are removed, and the `.tex` file too unless the format
+//! sets `keep-tex: true`. In debug mode everything is kept.
+
+use async_trait::async_trait;
+
+use crate::latex::{PdfEngine, compile};
+use crate::render::BinaryDependencies;
+use crate::stage::{
+    EventLevel, PipelineData, PipelineDataKind, PipelineError, PipelineStage, StageContext,
+};
+use crate::trace_event;
+
+/// Compile LaTeX to PDF.
+///
+/// # Input
+///
+/// - `RenderedOutput` - Complete LaTeX document (intermediate)
+///
+/// # Output
+///
+/// - `RenderedOutput` - The LaTeX source, no longer intermediate; the PDF
+///   has been written to `output_path`
+///
+/// # Errors
+///
+/// Returns an error with diagnostics if no engine is available or
+/// compilation fails (including which LaTeX packages are missing).
+pub struct CompileLatexStage {
+    binaries: BinaryDependencies,
+    debug: bool,
+}
+
+impl CompileLatexStage {
+    /// Create a stage using the engines in `binaries`.
+    pub fn new(binaries: BinaryDependencies) -> Self {
+        Self {
+            binaries,
+            debug: false,
+        }
+    }
+
+    /// Keep the `.tex` and auxiliary files (`--debug`).
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+}
+
+#[async_trait]
+impl PipelineStage for CompileLatexStage {
+    fn name(&self) -> &str {
+        "compile-latex"
+    }
+
+    fn input_kind(&self) -> PipelineDataKind {
+        PipelineDataKind::RenderedOutput
+    }
+
+    fn output_kind(&self) -> PipelineDataKind {
+        PipelineDataKind::RenderedOutput
+    }
+
+    async fn run(
+        &self,
+        input: PipelineData,
+        ctx: &mut StageContext,
+    ) -> Result<PipelineData, PipelineError> {
+        let PipelineData::RenderedOutput(mut rendered) = input else {
+            return Err(PipelineError::unexpected_input(
+                self.name(),
+                self.input_kind(),
+                input.kind(),
+            ));
+        };
+
+        let requested = ctx
+            .format_metadata("pdf-engine")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let (engine, binary) =
+            PdfEngine::select(requested.as_deref(), &self.binaries).map_err(|diagnostic| {
+                PipelineError::stage_error_with_diagnostics(self.name(), vec![diagnostic])
+            })?;
+
+        let tex_path = rendered.output_path.with_extension("tex");
+        if let Some(dir) = tex_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            ctx.runtime.dir_create(dir, true).map_err(|e| {
+                PipelineError::stage_error(
+                    self.name(),
+                    format!("Failed to create {}: {}", dir.display(), e),
+                )
+            })?;
+        }
+        ctx.runtime
+            .file_write(&tex_path, rendered.content.as_bytes())
+            .map_err(|e| {
+                PipelineError::stage_error(
+                    self.name(),
+                    format!("Failed to write {}: {}", tex_path.display(), e),
+                )
+            })?;
+
+        trace_event!(
+            ctx,
+            EventLevel::Debug,
+            "compiling {} with {}",
+            tex_path.display(),
+            engine.name()
+        );
+        let compilation =
+            compile(ctx.runtime.as_ref(), engine, &binary, &tex_path).map_err(|diagnostics| {
+                PipelineError::stage_error_with_diagnostics(self.name(), diagnostics)
+            })?;
+        trace_event!(
+            ctx,
+            EventLevel::Debug,
+            "{} finished after {} run(s)",
+            engine.name(),
+            compilation.runs
+        );
+
+        if compilation.pdf != rendered.output_path {
+            ctx.runtime
+                .path_rename(&compilation.pdf, &rendered.output_path)
+                .map_err(|e| {
+                    PipelineError::stage_error(
+                        self.name(),
+                        format!(
+                            "Failed to move PDF to {}: {}",
+                            rendered.output_path.display(),
+                            e
+                        ),
+                    )
+                })?;
+        }
+
+        let keep_tex = self.debug
+            || ctx
+                .format_metadata("keep-tex")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+        if !self.debug {
+            for file in &compilation.intermediates {
+                let _ = ctx.runtime.file_remove(file);
+            }
+        }
+        if keep_tex {
+            rendered.supporting_files.push(tex_path);
+        } else {
+            let _ = ctx.runtime.file_remove(&tex_path);
+        }
+
+        rendered.is_intermediate = false;
+        Ok(PipelineData::RenderedOutput(rendered))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::stage::RenderedOutput;
+    use quarto_pandoc_types::ConfigValue;
+    use quarto_system_runtime::NativeRuntime;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    fn setup(dir: &Path) -> (StageContext, RenderedOutput) {
+        let project = ProjectContext {
+            dir: dir.to_path_buf(),
+            config: None,
+            is_single_file: true,
+            files: vec![],
+            output_dir: dir.to_path_buf(),
+        };
+        let doc = DocumentInfo::from_path(dir.join("doc.qmd"));
+        let ctx =
+            StageContext::new(Arc::new(NativeRuntime::new()), Format::pdf(), project, doc).unwrap();
+        let rendered = RenderedOutput {
+            input_path: dir.join("doc.qmd"),
+            output_path: dir.join("doc.pdf"),
+            format: Format::pdf(),
+            content: "\\documentclass{article}\\begin{document}Hi\\end{document}\n".to_string(),
+            is_intermediate: true,
+            supporting_files: vec![],
+            metadata: ConfigValue::default(),
+        };
+        (ctx, rendered)
+    }
+
+    #[tokio::test]
+    async fn test_missing_engine() {
+        let temp = tempfile::tempdir().unwrap();
+        let (mut ctx, rendered) = setup(temp.path());
+        let err = CompileLatexStage::new(BinaryDependencies::default())
+            .run(PipelineData::RenderedOutput(rendered), &mut ctx)
+            .await
+            .unwrap_err();
+        let PipelineError::StageError { diagnostics, .. } = err else {
+            panic!("expected a stage error");
+        };
+        assert_eq!(diagnostics[0].title, "PDF engine not found");
+    }
+
+    /// A stand-in for xelatex that writes the PDF and auxiliary files.
+    #[cfg(unix)]
+    fn fake_engine(dir: &Path) -> std::path::PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let script = dir.join("fake-xelatex");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\nfor a; do t=\"$a\"; done\nb=\"${t%.tex}\"\necho 'Output written' > \"$b.log\"\necho pdf > \"$b.pdf\"\necho aux > \"$b.aux\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        script
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_compile_and_clean_up() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let (mut ctx, rendered) = setup(dir);
+        let binaries = BinaryDependencies {
+            xelatex: Some(fake_engine(dir)),
+            ..Default::default()
+        };
+
+        let output = CompileLatexStage::new(binaries)
+            .run(PipelineData::RenderedOutput(rendered), &mut ctx)
+            .await
+            .unwrap();
+        let rendered = output.into_rendered_output().unwrap();
+
+        assert!(!rendered.is_intermediate);
+        assert!(dir.join("doc.pdf").exists());
+        assert!(!dir.join("doc.aux").exists());
+        assert!(!dir.join("doc.log").exists());
+        assert!(!dir.join("doc.tex").exists());
+        assert!(rendered.supporting_files.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_debug_keeps_intermediates() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let (mut ctx, rendered) = setup(dir);
+        let binaries = BinaryDependencies {
+            xelatex: Some(fake_engine(dir)),
+            ..Default::default()
+        };
+
+        let output = CompileLatexStage::new(binaries)
+            .with_debug(true)
+            .run(PipelineData::RenderedOutput(rendered), &mut ctx)
+            .await
+            .unwrap();
+        let rendered = output.into_rendered_output().unwrap();
+
+        assert!(dir.join("doc.aux").exists());
+        assert!(dir.join("doc.log").exists());
+        assert_eq!(rendered.supporting_files, vec![dir.join("doc.tex")]);
+    }
+}
@@ -0,0 +1,272 @@
+/*
+ * stage/stages/compile_typst.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Compile Typst to PDF.
+ */
+
+//! Compile Typst to PDF.
+//!
+//! This stage writes the Typst source next to the output PDF and runs the
+//! Typst binary over it (see [`crate::typst`]). Compile errors are located
+//! in the source document with the line map left by the render-typst stage.
+//! Afterwards the `.typ` file is removed unless the format sets
+//! `keep-typ: true` or debug mode is on.
+
+use async_trait::async_trait;
+use pampa::writers::typst::LineMap;
+
+use crate::render::BinaryDependencies;
+use crate::stage::stages::TYPST_LINE_MAP_ARTIFACT;
+use crate::stage::{
+    EventLevel, PipelineData, PipelineDataKind, PipelineError, PipelineStage, StageContext,
+};
+use crate::trace_event;
+use crate::typst::{compile, typst_not_found};
+
+/// Compile Typst to PDF.
+///
+/// # Input
+///
+/// - `RenderedOutput` - Complete Typst document (intermediate)
+///
+/// # Output
+///
+/// - `RenderedOutput` - The Typst source, no longer intermediate; the PDF
+///   has been written to `output_path`
+///
+/// # Errors
+///
+/// Returns an error with diagnostics if Typst is not available or
+/// compilation fails.
+pub struct CompileTypstStage {
+    binaries: BinaryDependencies,
+    debug: bool,
+}
+
+impl CompileTypstStage {
+    /// Create a stage using the Typst binary in `binaries`.
+    pub fn new(binaries: BinaryDependencies) -> Self {
+        Self {
+            binaries,
+            debug: false,
+        }
+    }
+
+    /// Keep the `.typ` file (`--debug`).
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+}
+
+#[async_trait]
+impl PipelineStage for CompileTypstStage {
+    fn name(&self) -> &str {
+        "compile-typst"
+    }
+
+    fn input_kind(&self) -> PipelineDataKind {
+        PipelineDataKind::RenderedOutput
+    }
+
+    fn output_kind(&self) -> PipelineDataKind {
+        PipelineDataKind::RenderedOutput
+    }
+
+    async fn run(
+        &self,
+        input: PipelineData,
+        ctx: &mut StageContext,
+    ) -> Result<PipelineData, PipelineError> {
+        let PipelineData::RenderedOutput(mut rendered) = input else {
+            return Err(PipelineError::unexpected_input(
+                self.name(),
+                self.input_kind(),
+                input.kind(),
+            ));
+        };
+
+        let Some(binary) = self.binaries.typst.clone() else {
+            return Err(PipelineError::stage_error_with_diagnostics(
+                self.name(),
+                vec![typst_not_found()],
+            ));
+        };
+
+        let typ_path = rendered.output_path.with_extension("typ");
+        if let Some(dir) = typ_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            ctx.runtime.dir_create(dir, true).map_err(|e| {
+                PipelineError::stage_error(
+                    self.name(),
+                    format!("Failed to create {}: {}", dir.display(), e),
+                )
+            })?;
+        }
+        ctx.runtime
+            .file_write(&typ_path, rendered.content.as_bytes())
+            .map_err(|e| {
+                PipelineError::stage_error(
+                    self.name(),
+                    format!("Failed to write {}: {}", typ_path.display(), e),
+                )
+            })?;
+
+        let line_map: Option<LineMap> = ctx
+            .artifacts
+            .get(TYPST_LINE_MAP_ARTIFACT)
+            .and_then(|artifact| serde_json::from_slice(&artifact.content).ok());
+
+        trace_event!(ctx, EventLevel::Debug, "compiling {}", typ_path.display());
+        let result = compile(
+            ctx.runtime.as_ref(),
+            &binary,
+            &typ_path,
+            &rendered.output_path,
+            line_map.as_ref(),
+        );
+
+        let keep_typ = self.debug
+            || ctx
+                .format_metadata("keep-typ")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+        // Failed compiles clean up too; `--debug` keeps the file to inspect
+        if !keep_typ {
+            let _ = ctx.runtime.file_remove(&typ_path);
+        }
+        result.map_err(|diagnostics| {
+            PipelineError::stage_error_with_diagnostics(self.name(), diagnostics)
+        })?;
+
+        if keep_typ {
+            rendered.supporting_files.push(typ_path);
+        }
+        rendered.is_intermediate = false;
+        Ok(PipelineData::RenderedOutput(rendered))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::stage::RenderedOutput;
+    use quarto_pandoc_types::ConfigValue;
+    use quarto_system_runtime::NativeRuntime;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    fn setup(dir: &Path) -> (StageContext, RenderedOutput) {
+        let project = ProjectContext {
+            dir: dir.to_path_buf(),
+            config: None,
+            is_single_file: true,
+            files: vec![],
+            output_dir: dir.to_path_buf(),
+        };
+        let doc = DocumentInfo::from_path(dir.join("doc.qmd"));
+        let ctx = StageContext::new(
+            Arc::new(NativeRuntime::new()),
+            Format::typst(),
+            project,
+            doc,
+        )
+        .unwrap();
+        let rendered = RenderedOutput {
+            input_path: dir.join("doc.qmd"),
+            output_path: dir.join("doc.pdf"),
+            format: Format::typst(),
+            content: "Hello\n".to_string(),
+            is_intermediate: true,
+            supporting_files: vec![],
+            metadata: ConfigValue::default(),
+        };
+        (ctx, rendered)
+    }
+
+    #[tokio::test]
+    async fn test_missing_typst() {
+        let temp = tempfile::tempdir().unwrap();
+        let (mut ctx, rendered) = setup(temp.path());
+        let err = CompileTypstStage::new(BinaryDependencies::default())
+            .run(PipelineData::RenderedOutput(rendered), &mut ctx)
+            .await
+            .unwrap_err();
+        let PipelineError::StageError { diagnostics, .. } = err else {
+            panic!("expected a stage error");
+        };
+        assert_eq!(diagnostics[0].title, "Typst not found");
+    }
+
+    /// A stand-in for typst that writes its last argument, or fails with a
+    /// short-format error when the source contains `#fail`.
+    #[cfg(unix)]
+    fn fake_typst(dir: &Path) -> std::path::PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let script = dir.join("fake-typst");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\nfor a; do src=\"$out\"; out=\"$a\"; done\nif grep -q '#fail' \"$src\"; then echo \"$src:1:2: error: unknown variable: fail\" >&2; exit 1; fi\necho pdf > \"$out\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        script
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_compile_and_clean_up() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let (mut ctx, rendered) = setup(dir);
+        let binaries = BinaryDependencies {
+            typst: Some(fake_typst(dir)),
+            ..Default::default()
+        };
+
+        let output = CompileTypstStage::new(binaries)
+            .run(PipelineData::RenderedOutput(rendered), &mut ctx)
+            .await
+            .unwrap();
+        let rendered = output.into_rendered_output().unwrap();
+
+        assert!(!rendered.is_intermediate);
+        assert!(dir.join("doc.pdf").exists());
+        assert!(!dir.join("doc.typ").exists());
+        assert!(rendered.supporting_files.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_compile_error() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let (mut ctx, mut rendered) = setup(dir);
+        rendered.content = "#fail\n".to_string();
+        let binaries = BinaryDependencies {
+            typst: Some(fake_typst(dir)),
+            ..Default::default()
+        };
+
+        let err = CompileTypstStage::new(binaries)
+            .with_debug(true)
+            .run(PipelineData::RenderedOutput(rendered), &mut ctx)
+            .await
+            .unwrap_err();
+        let PipelineError::StageError { diagnostics, .. } = err else {
+            panic!("expected a stage error");
+        };
+        assert_eq!(diagnostics[0].title, "Typst compilation failed");
+        assert!(
+            diagnostics[0]
+                .problem
+                .as_ref()
+                .unwrap()
+                .as_str()
+                .starts_with("unknown variable: fail (at doc.typ:1:2)")
+        );
+        assert!(dir.join("doc.typ").exists());
+    }
+}
@@ -9,9 +9,11 @@
 //!
 //! This stage handles execution of code cells in Quarto documents by:
 //!
-//! 1. Detecting which engine to use from document metadata
+//! 1. Detecting which engine to use from document metadata or cell languages
 //! 2. Serializing the AST to QMD format
-//! 3. Executing the engine on the QMD content
+//! 3. Executing the engine on the QMD content, with the execution
+//!    parameters checked against the document's `params` (see
+//!    [`crate::params`])
 //! 4. Parsing the result back to AST
 //! 5. Reconciling source locations between original and executed ASTs
 //!
@@ -29,7 +31,10 @@ use std::sync::Arc;
 
 use quarto_error_reporting::DiagnosticMessage;
 
-use crate::engine::{EngineRegistry, ExecutionContext, ExecutionEngine, detect_engine};
+use crate::engine::{
+    CellCache, EngineRegistry, ExecutionContext, ExecutionEngine, detect_document_engine,
+};
+use crate::params::resolve_params;
 use crate::stage::{
     DocumentAst, EventLevel, PipelineData, PipelineDataKind, PipelineError, PipelineStage,
     StageContext,
@@ -41,7 +46,7 @@ use crate::trace_event;
 /// This stage is the bridge between the AST-based pipeline and text-based
 /// execution engines (knitr, jupyter). It:
 ///
-/// 1. Detects the engine from document metadata
+/// 1. Detects the engine from document metadata or code cell languages
 /// 2. Serializes the AST to QMD for engine execution
 /// 3. Executes the engine
 /// 4. Parses the result back to AST
@@ -152,7 +157,7 @@ impl PipelineStage for EngineExecutionStage {
         };
 
         // Step 1: Detect engine from metadata
-        let detected = detect_engine(&doc_ast.ast.meta);
+        let detected = detect_document_engine(&doc_ast.ast);
 
         trace_event!(
             ctx,
@@ -188,6 +193,17 @@ impl PipelineStage for EngineExecutionStage {
             return Ok(PipelineData::DocumentAst(doc_ast));
         }
 
+        // Execution disabled (`--no-execute`): code cells pass through as-is
+        if !ctx.execute.enabled {
+            trace_event!(
+                ctx,
+                EventLevel::Debug,
+                "execution disabled - skipping engine: {}",
+                engine.name()
+            );
+            return Ok(PipelineData::DocumentAst(doc_ast));
+        }
+
         // Step 4: Serialize AST to QMD for engine execution
         let qmd = serialize_ast_to_qmd(&doc_ast.ast)?;
 
@@ -198,10 +214,27 @@ impl PipelineStage for EngineExecutionStage {
             qmd.len()
         );
 
-        // Step 5: Prepare execution context
+        // Step 5: Check the given parameters against the document's `params`
+        let params = match resolve_params(&doc_ast.ast.meta, &ctx.execute.params) {
+            Ok((params, warnings)) => {
+                ctx.add_diagnostics(warnings);
+                params
+            }
+            Err(diagnostics) => {
+                return Err(PipelineError::stage_error_with_diagnostics(
+                    self.name(),
+                    diagnostics,
+                ));
+            }
+        };
+
+        // Step 6: Prepare execution context
         let exec_context = ExecutionContext::new(
             ctx.temp_dir.clone(),
-            ctx.project.dir.clone(),
+            ctx.execute
+                .dir
+                .clone()
+                .unwrap_or_else(|| ctx.project.dir.clone()),
             doc_ast.path.clone(),
             &ctx.format.identifier.to_string(),
         )
@@ -210,9 +243,12 @@ impl PipelineStage for EngineExecutionStage {
         } else {
             Some(ctx.project.dir.clone())
         })
-        .with_engine_config(detected.config.clone());
+        .with_engine_config(detected.config.clone())
+        .with_params(params)
+        .with_daemon(ctx.execute.daemon, ctx.execute.daemon_restart)
+        .with_cache(cell_cache(ctx, &doc_ast));
 
-        // Step 6: Execute the engine
+        // Step 7: Execute the engine
         trace_event!(ctx, EventLevel::Info, "executing engine: {}", engine.name());
 
         let result = engine
@@ -226,7 +262,7 @@ impl PipelineStage for EngineExecutionStage {
             result.markdown.len()
         );
 
-        // Step 7: Parse the executed markdown back to AST
+        // Step 8: Parse the executed markdown back to AST
         let source_name = doc_ast.path.display().to_string();
         let (executed_ast, new_ast_context, parse_warnings) = pampa::readers::qmd::read(
             result.markdown.as_bytes(),
@@ -240,7 +276,7 @@ impl PipelineStage for EngineExecutionStage {
             PipelineError::stage_error_with_diagnostics(self.name(), diagnostics)
         })?;
 
-        // Step 8: Reconcile source locations
+        // Step 9: Reconcile source locations
         // For content that hasn't changed, preserve original source locations.
         // For new content (execution outputs), use locations from executed AST.
         // Uses the three-phase reconciliation algorithm from quarto-ast-reconcile.
@@ -256,11 +292,11 @@ impl PipelineStage for EngineExecutionStage {
             reconciliation_plan.stats.blocks_recursed
         );
 
-        // Step 9: Collect warnings
+        // Step 10: Collect warnings
         let mut warnings = doc_ast.warnings;
         warnings.extend(parse_warnings);
 
-        // Step 10: Return updated DocumentAst
+        // Step 11: Return updated DocumentAst
         Ok(PipelineData::DocumentAst(DocumentAst {
             path: doc_ast.path,
             ast: reconciled_ast,
@@ -271,6 +307,32 @@ impl PipelineStage for EngineExecutionStage {
     }
 }
 
+/// Create the per-cell output cache for a document, unless disabled.
+///
+/// `--cache` caches every cell, `--no-cache` disables the cache; otherwise
+/// the document's `execute: { cache: true }` decides whether cells without
+/// their own `cache` option are cached.
+fn cell_cache(ctx: &StageContext, doc_ast: &DocumentAst) -> Option<CellCache> {
+    if ctx.execute.cache == Some(false) {
+        return None;
+    }
+
+    let default_enabled = ctx.execute.cache.unwrap_or_else(|| {
+        doc_ast
+            .ast
+            .meta
+            .get_path(&["execute", "cache"])
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    });
+
+    Some(
+        CellCache::new(ctx.runtime.clone(), &ctx.project.dir, &doc_ast.path)
+            .with_default_enabled(default_enabled)
+            .with_refresh(ctx.execute.cache_refresh),
+    )
+}
+
 /// Serialize a Pandoc AST to QMD text.
 ///
 /// This produces QMD that can be fed to execution engines.
@@ -591,6 +653,144 @@ mod tests {
         assert!(qmd.contains("World"));
     }
 
+    /// Engine that records the execution context it was called with.
+    struct RecordingEngine {
+        calls: std::sync::Mutex<Vec<ExecutionContext>>,
+    }
+
+    impl ExecutionEngine for RecordingEngine {
+        fn name(&self) -> &str {
+            "jupyter"
+        }
+
+        fn execute(
+            &self,
+            input: &str,
+            ctx: &ExecutionContext,
+        ) -> Result<crate::engine::ExecuteResult, crate::engine::ExecutionError> {
+            self.calls.lock().unwrap().push(ctx.clone());
+            Ok(crate::engine::ExecuteResult::passthrough(input))
+        }
+    }
+
+    fn recording_stage() -> (EngineExecutionStage, Arc<RecordingEngine>) {
+        let engine = Arc::new(RecordingEngine {
+            calls: std::sync::Mutex::new(Vec::new()),
+        });
+        let mut registry = EngineRegistry::new();
+        registry.register(engine.clone());
+        (EngineExecutionStage::with_registry(registry), engine)
+    }
+
+    const JUPYTER_DOC: &[u8] =
+        b"---\ntitle: Test\nengine: jupyter\n---\n\n```{python}\nprint(n)\n```\n";
+
+    #[tokio::test]
+    async fn test_execution_disabled_skips_engine() {
+        let (stage, engine) = recording_stage();
+        let mut ctx = make_test_context();
+        ctx.execute.enabled = false;
+
+        let doc_ast = parse_qmd_to_ast(JUPYTER_DOC, "/project/test.qmd");
+        let output = stage
+            .run(PipelineData::DocumentAst(doc_ast), &mut ctx)
+            .await
+            .unwrap();
+
+        assert!(output.into_document_ast().is_some());
+        assert!(engine.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_options_reach_engine() {
+        let (stage, engine) = recording_stage();
+        let mut ctx = make_test_context();
+        ctx.execute
+            .params
+            .insert("n".to_string(), serde_json::json!(3));
+        ctx.execute.dir = Some(PathBuf::from("/work"));
+        ctx.execute.daemon = Some(0);
+
+        let doc_ast = parse_qmd_to_ast(JUPYTER_DOC, "/project/test.qmd");
+        stage
+            .run(PipelineData::DocumentAst(doc_ast), &mut ctx)
+            .await
+            .unwrap();
+
+        let calls = engine.calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].cwd, PathBuf::from("/work"));
+        assert_eq!(calls[0].params.get("n"), Some(&serde_json::json!(3)));
+        assert_eq!(calls[0].daemon, Some(0));
+        assert!(calls[0].cache.is_some());
+    }
+
+    const PARAMS_DOC: &[u8] = b"---\nengine: jupyter\nparams:\n  n: 1\n  label: Sales\n---\n\n```{python}\nprint(n)\n```\n";
+
+    #[tokio::test]
+    async fn test_params_are_checked_and_merged() {
+        let (stage, engine) = recording_stage();
+        let mut ctx = make_test_context();
+        ctx.execute
+            .params
+            .insert("label".to_string(), serde_json::json!(2024));
+
+        let doc_ast = parse_qmd_to_ast(PARAMS_DOC, "/project/test.qmd");
+        stage
+            .run(PipelineData::DocumentAst(doc_ast), &mut ctx)
+            .await
+            .unwrap();
+
+        let calls = engine.calls.lock().unwrap();
+        assert_eq!(calls[0].params.get("n"), Some(&serde_json::json!(1)));
+        assert_eq!(
+            calls[0].params.get("label"),
+            Some(&serde_json::json!("2024"))
+        );
+        assert!(
+            ctx.diagnostics
+                .iter()
+                .any(|d| d.title == "Parameter value converted")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_param_fails_before_execution() {
+        let (stage, engine) = recording_stage();
+        let mut ctx = make_test_context();
+        ctx.execute
+            .params
+            .insert("n".to_string(), serde_json::json!("many"));
+
+        let doc_ast = parse_qmd_to_ast(PARAMS_DOC, "/project/test.qmd");
+        let err = stage
+            .run(PipelineData::DocumentAst(doc_ast), &mut ctx)
+            .await
+            .unwrap_err();
+
+        let PipelineError::StageError { diagnostics, .. } = err else {
+            panic!("expected a stage error");
+        };
+        assert_eq!(diagnostics[0].title, "Invalid parameter value");
+        assert!(engine.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_no_cache_disables_cell_cache() {
+        let (stage, engine) = recording_stage();
+        let mut ctx = make_test_context();
+        ctx.execute.cache = Some(false);
+
+        let doc_ast = parse_qmd_to_ast(JUPYTER_DOC, "/project/test.qmd");
+        stage
+            .run(PipelineData::DocumentAst(doc_ast), &mut ctx)
+            .await
+            .unwrap();
+
+        let calls = engine.calls.lock().unwrap();
+        assert!(calls[0].cache.is_none());
+    }
+
     #[test]
     fn test_stage_is_send_sync() {
         fn assert_send_sync<T: Send + Sync>() {}
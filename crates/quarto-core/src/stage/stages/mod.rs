@@ -15,15 +15,30 @@
 //! - [`AstTransformsStage`] - Apply Quarto-specific AST transforms
 //! - [`RenderHtmlBodyStage`] - Render AST to HTML body
 //! - [`ApplyTemplateStage`] - Apply HTML template to rendered body
+//! - [`PostprocessHtmlStage`] - Post-process the complete HTML document
+//! - [`RenderLatexStage`] - Render AST to a complete LaTeX document
+//! - [`CompileLatexStage`] - Compile LaTeX to PDF
+//! - [`RenderTypstStage`] - Render AST to a complete Typst document
+//! - [`CompileTypstStage`] - Compile Typst to PDF
 
 mod apply_template;
 mod ast_transforms;
+mod compile_latex;
+mod compile_typst;
 mod engine_execution;
 mod parse_document;
+mod postprocess_html;
 mod render_html;
+mod render_latex;
+mod render_typst;
 
 pub use apply_template::{ApplyTemplateConfig, ApplyTemplateStage};
 pub use ast_transforms::AstTransformsStage;
+pub use compile_latex::CompileLatexStage;
+pub use compile_typst::CompileTypstStage;
 pub use engine_execution::EngineExecutionStage;
 pub use parse_document::ParseDocumentStage;
+pub use postprocess_html::PostprocessHtmlStage;
 pub use render_html::RenderHtmlBodyStage;
+pub use render_latex::RenderLatexStage;
+pub use render_typst::{RenderTypstStage, TYPST_LINE_MAP_ARTIFACT};
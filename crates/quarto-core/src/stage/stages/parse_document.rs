@@ -13,6 +13,9 @@
 use async_trait::async_trait;
 use quarto_source_map::SourceContext;
 
+use crate::brand::apply_brand;
+use crate::include::resolve_includes;
+use crate::metadata_files::{apply_metadata_files, apply_metadata_overrides};
 use crate::stage::{
     DocumentAst, EventLevel, PipelineData, PipelineDataKind, PipelineError, PipelineStage,
     StageContext,
@@ -25,7 +28,11 @@ use crate::trace_event;
 /// 1. Takes raw source content (LoadedSource)
 /// 2. Creates a SourceContext for error reporting
 /// 3. Parses the content using pampa
-/// 4. Returns a DocumentAst with the parsed AST and warnings
+/// 4. Splices in files included with `{{< include >}}`
+/// 5. Merges the files listed in `metadata-files` into the metadata, and
+///    checks the result against the Quarto schema (problems are warnings)
+/// 6. Reads the project's `_brand.yml`, recording its logos in metadata
+/// 7. Returns a DocumentAst with the parsed AST and warnings
 ///
 /// # Input
 ///
@@ -37,7 +44,8 @@ use crate::trace_event;
 ///
 /// # Errors
 ///
-/// Returns an error if parsing fails.
+/// Returns an error if parsing fails, an include or metadata file can't be
+/// resolved or the brand file is invalid.
 pub struct ParseDocumentStage;
 
 impl ParseDocumentStage {
@@ -107,7 +115,61 @@ impl PipelineStage for ParseDocumentStage {
         );
 
         match parse_result {
-            Ok((ast, ast_context, warnings)) => {
+            Ok((mut ast, ast_context, mut warnings)) => {
+                // Splice in `{{< include >}}`d files before anything else
+                // (including code execution) sees the document.
+                match resolve_includes(
+                    &mut ast.blocks,
+                    &source.path,
+                    ctx.runtime.as_ref(),
+                    &mut source_context,
+                ) {
+                    Ok(include_warnings) => warnings.extend(include_warnings),
+                    Err(diagnostics) => {
+                        return Err(PipelineError::stage_error_with_diagnostics(
+                            self.name(),
+                            diagnostics,
+                        ));
+                    }
+                }
+
+                if let Err(diagnostics) = apply_metadata_files(
+                    &mut ast.meta,
+                    &source.path,
+                    &ctx.project,
+                    ctx.runtime.as_ref(),
+                    &mut source_context,
+                ) {
+                    return Err(PipelineError::stage_error_with_diagnostics(
+                        self.name(),
+                        diagnostics,
+                    ));
+                }
+                if let Err(diagnostics) = apply_metadata_overrides(
+                    &mut ast.meta,
+                    &ctx.metadata_overrides,
+                    &mut source_context,
+                ) {
+                    return Err(PipelineError::stage_error_with_diagnostics(
+                        self.name(),
+                        diagnostics,
+                    ));
+                }
+                warnings.extend(quarto_config::validate_config(&ast.meta, &source_context));
+
+                if let Err(diagnostics) = apply_brand(
+                    &mut ast.meta,
+                    &source.path,
+                    &ctx.project.dir,
+                    ctx.runtime.as_ref(),
+                    &mut source_context,
+                ) {
+                    return Err(PipelineError::stage_error_with_diagnostics(
+                        self.name(),
+                        diagnostics,
+                    ));
+                }
+
                 // Log any warnings
                 if !warnings.is_empty() {
                     trace_event!(
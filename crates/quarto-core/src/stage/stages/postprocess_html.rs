@@ -0,0 +1,184 @@
+/*
+ * stage/stages/postprocess_html.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Post-process the complete HTML document.
+ */
+
+//! Post-process the complete HTML document.
+//!
+//! This stage parses the templated HTML into an
+//! [`HtmlDocument`](crate::postprocess::HtmlDocument) and runs the
+//! [`HtmlPostprocessor`]s over it: resource resolution and copying,
+//! external link handling and analytics injection.
+
+use async_trait::async_trait;
+
+use crate::postprocess::{
+    HtmlDocument, HtmlPostprocessor, PostprocessContext, default_postprocessors,
+};
+use crate::stage::{
+    EventLevel, PipelineData, PipelineDataKind, PipelineError, PipelineStage, StageContext,
+};
+use crate::trace_event;
+
+/// Post-process the complete HTML document.
+///
+/// # Input
+///
+/// - `RenderedOutput` - Complete HTML document
+///
+/// # Output
+///
+/// - `RenderedOutput` - The processed document; files copied to the
+///   output directory are added to `supporting_files`
+pub struct PostprocessHtmlStage {
+    postprocessors: Vec<Box<dyn HtmlPostprocessor>>,
+}
+
+impl PostprocessHtmlStage {
+    /// Create a stage running the default post-processors.
+    pub fn new() -> Self {
+        Self::with_postprocessors(default_postprocessors())
+    }
+
+    /// Create a stage running `postprocessors`, in order.
+    pub fn with_postprocessors(postprocessors: Vec<Box<dyn HtmlPostprocessor>>) -> Self {
+        Self { postprocessors }
+    }
+}
+
+impl Default for PostprocessHtmlStage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PipelineStage for PostprocessHtmlStage {
+    fn name(&self) -> &str {
+        "postprocess-html"
+    }
+
+    fn input_kind(&self) -> PipelineDataKind {
+        PipelineDataKind::RenderedOutput
+    }
+
+    fn output_kind(&self) -> PipelineDataKind {
+        PipelineDataKind::RenderedOutput
+    }
+
+    async fn run(
+        &self,
+        input: PipelineData,
+        ctx: &mut StageContext,
+    ) -> Result<PipelineData, PipelineError> {
+        let PipelineData::RenderedOutput(mut rendered) = input else {
+            return Err(PipelineError::unexpected_input(
+                self.name(),
+                self.input_kind(),
+                input.kind(),
+            ));
+        };
+
+        let mut doc = HtmlDocument::parse(&rendered.content);
+        let mut pp_ctx = PostprocessContext::new(
+            ctx.runtime.as_ref(),
+            &ctx.format,
+            &ctx.project,
+            &rendered.input_path,
+            &rendered.output_path,
+        );
+        let mut events = Vec::new();
+        for postprocessor in &self.postprocessors {
+            postprocessor
+                .process(&mut doc, &mut pp_ctx)
+                .map_err(|e| PipelineError::stage_error(self.name(), e.to_string()))?;
+            events.extend(
+                pp_ctx
+                    .events
+                    .drain(..)
+                    .map(|event| format!("{}: {}", postprocessor.name(), event)),
+            );
+        }
+        let supporting_files = pp_ctx.supporting_files;
+
+        for event in &events {
+            trace_event!(ctx, EventLevel::Debug, "{}", event);
+        }
+        trace_event!(
+            ctx,
+            EventLevel::Debug,
+            "post-processed HTML, {} supporting files",
+            supporting_files.len()
+        );
+
+        rendered.content = doc.to_html();
+        rendered.supporting_files.extend(supporting_files);
+        Ok(PipelineData::RenderedOutput(rendered))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::stage::RenderedOutput;
+    use quarto_pandoc_types::ConfigValue;
+    use quarto_system_runtime::NativeRuntime;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_postprocess_html() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::write(dir.join("plot.png"), "png").unwrap();
+
+        let project = ProjectContext {
+            dir: dir.to_path_buf(),
+            config: None,
+            is_single_file: false,
+            files: vec![],
+            output_dir: dir.join("_site"),
+        };
+        let format = Format {
+            metadata: serde_json::json!({ "link-external-newwindow": true }),
+            ..Format::html()
+        };
+        let doc = DocumentInfo::from_path(dir.join("index.qmd"));
+        let mut ctx =
+            StageContext::new(Arc::new(NativeRuntime::new()), format.clone(), project, doc)
+                .unwrap();
+
+        let rendered = RenderedOutput {
+            input_path: dir.join("index.qmd"),
+            output_path: dir.join("_site/index.html"),
+            format,
+            content: concat!(
+                "<html><head></head><body>",
+                r#"<img src="plot.png"><a href="https://quarto.org">Quarto</a>"#,
+                "</body></html>"
+            )
+            .to_string(),
+            is_intermediate: false,
+            supporting_files: vec![],
+            metadata: ConfigValue::default(),
+        };
+
+        let output = PostprocessHtmlStage::new()
+            .run(PipelineData::RenderedOutput(rendered), &mut ctx)
+            .await
+            .unwrap();
+        let rendered = output.into_rendered_output().unwrap();
+
+        assert!(rendered.content.contains(r#"<img src="plot.png">"#));
+        assert!(
+            rendered.content.contains(
+                r#"<a href="https://quarto.org" target="_blank" rel="noopener">Quarto</a>"#
+            )
+        );
+        assert_eq!(rendered.supporting_files, vec![dir.join("_site/plot.png")]);
+        assert!(dir.join("_site/plot.png").exists());
+    }
+}
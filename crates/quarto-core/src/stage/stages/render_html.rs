@@ -11,7 +11,12 @@
 //! HTML writer.
 
 use async_trait::async_trait;
+use pampa::writers::html::{extract_config_from_metadata, write_document};
+use quarto_error_reporting::DiagnosticMessageBuilder;
+use quarto_pandoc_types::ConfigValue;
+use quarto_source_map::SourceInfo;
 
+use crate::math::{MathMethod, store_math_dependencies};
 use crate::stage::{
     EventLevel, PipelineData, PipelineDataKind, PipelineError, PipelineStage, RenderedOutput,
     StageContext,
@@ -22,8 +27,22 @@ use crate::trace_event;
 ///
 /// This stage:
 /// 1. Takes a transformed DocumentAst
-/// 2. Renders it to HTML body using pampa::writers::html::write
-/// 3. Returns a RenderedOutput with the HTML content
+/// 2. Renders it to HTML body using pampa::writers::html::write_document
+/// 3. If the document has math, stores the math method's script/style
+///    dependencies as artifacts and sets `rendered.math` for the template
+/// 4. Returns a RenderedOutput with the HTML content
+///
+/// # Math
+///
+/// The math method is, in order of precedence: the one given to
+/// [`RenderHtmlBodyStage::with_math`], the format's `html-math-method`,
+/// the document's `format.html.html-math-method`, and MathJax.
+///
+/// # Node IDs
+///
+/// Blocks get `data-quarto-node` attributes with their stable ids when the
+/// format's or the document's `node-ids` is true. The preview uses them to
+/// patch the page in place.
 ///
 /// # Input
 ///
@@ -36,12 +55,43 @@ use crate::trace_event;
 /// # Errors
 ///
 /// Returns an error if rendering fails.
-pub struct RenderHtmlBodyStage;
+pub struct RenderHtmlBodyStage {
+    math: Option<MathMethod>,
+}
 
 impl RenderHtmlBodyStage {
     /// Create a new RenderHtmlBodyStage.
     pub fn new() -> Self {
-        Self
+        Self { math: None }
+    }
+
+    /// Create a RenderHtmlBodyStage that always uses the given math method.
+    pub fn with_math(math: MathMethod) -> Self {
+        Self { math: Some(math) }
+    }
+
+    /// The math method requested by this stage or the format metadata.
+    ///
+    /// Unknown `html-math-method` names produce a warning and are ignored.
+    fn requested_math_method(&self, ctx: &mut StageContext) -> Option<MathMethod> {
+        if self.math.is_some() {
+            return self.math;
+        }
+        let value = ctx.format_metadata("html-math-method")?;
+        let name = value
+            .as_str()
+            .or_else(|| value.get("method").and_then(|m| m.as_str()))?
+            .to_string();
+        let method = MathMethod::from_name(&name);
+        if method.is_none() {
+            ctx.add_diagnostic(
+                DiagnosticMessageBuilder::warning("Unknown math method")
+                    .problem(format!("html-math-method `{}` is not recognized", name))
+                    .add_hint("Use one of: mathjax, katex, mathml, plain")
+                    .build(),
+            );
+        }
+        method
     }
 }
 
@@ -85,11 +135,25 @@ impl PipelineStage for RenderHtmlBodyStage {
             doc.ast.blocks.len()
         );
 
+        let mut config = extract_config_from_metadata(&doc.ast.meta);
+        if let Some(math) = self.requested_math_method(ctx) {
+            config.math = math;
+        }
+        let math = config.math;
+        if let Some(node_ids) = ctx.format_metadata("node-ids").and_then(|v| v.as_bool()) {
+            config.node_ids = node_ids;
+        }
+
         // Render AST to HTML body
         let mut body_buf = Vec::new();
-        pampa::writers::html::write(&doc.ast, &doc.ast_context, &mut body_buf).map_err(|e| {
-            PipelineError::stage_error(self.name(), format!("Failed to write HTML body: {}", e))
-        })?;
+        let mut summary = write_document(&doc.ast, &doc.ast_context, &mut body_buf, config)
+            .map_err(|e| {
+                PipelineError::stage_error(self.name(), format!("Failed to write HTML body: {}", e))
+            })?;
+        // Content the writer dropped (e.g. `{=latex}` raw blocks) is reported as warnings
+        for diagnostic in summary.diagnostics.drain(..) {
+            ctx.add_diagnostic(diagnostic);
+        }
 
         let body = String::from_utf8(body_buf).map_err(|e| {
             PipelineError::stage_error(self.name(), format!("Invalid UTF-8 in HTML body: {}", e))
@@ -102,6 +166,22 @@ impl PipelineStage for RenderHtmlBodyStage {
             body.len()
         );
 
+        let mut metadata = doc.ast.meta;
+        if summary.has_math
+            && let Some(header) = store_math_dependencies(math, &mut ctx.artifacts)
+        {
+            trace_event!(
+                ctx,
+                EventLevel::Debug,
+                "adding {} math dependencies",
+                math.name()
+            );
+            metadata.insert_path(
+                &["rendered", "math"],
+                ConfigValue::new_string(header, SourceInfo::default()),
+            );
+        }
+
         // Calculate output path
         let output_path = ctx.output_path();
 
@@ -112,7 +192,7 @@ impl PipelineStage for RenderHtmlBodyStage {
             content: body,
             is_intermediate: false, // HTML body is not intermediate for HTML output
             supporting_files: vec![],
-            metadata: doc.ast.meta,
+            metadata,
         }))
     }
 }
@@ -300,5 +380,157 @@ mod tests {
         assert_eq!(rendered.input_path, PathBuf::from("/project/test.qmd"));
         assert_eq!(rendered.output_path, PathBuf::from("/project/test.html"));
         assert!(!rendered.is_intermediate);
+        // No math, so no math dependencies
+        assert!(ctx.artifacts.get("js:mathjax").is_none());
+    }
+
+    async fn render_math_document(
+        stage: RenderHtmlBodyStage,
+        format: Format,
+    ) -> (RenderedOutput, StageContext) {
+        use quarto_pandoc_types::block::{Block, Plain};
+        use quarto_pandoc_types::inline::{Inline, Math, MathType};
+
+        let blocks = vec![Block::Plain(Plain {
+            content: vec![Inline::Math(Math {
+                math_type: MathType::InlineMath,
+                text: "x^2".to_string(),
+                source_info: SourceInfo::default(),
+            })],
+            source_info: SourceInfo::default(),
+        })];
+        render_document(stage, format, blocks).await
+    }
+
+    async fn render_document(
+        stage: RenderHtmlBodyStage,
+        format: Format,
+        blocks: Vec<quarto_pandoc_types::block::Block>,
+    ) -> (RenderedOutput, StageContext) {
+        let runtime = Arc::new(MockRuntime);
+        let project = ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![],
+            output_dir: PathBuf::from("/project"),
+        };
+        let doc = DocumentInfo::from_path("/project/test.qmd");
+        let mut ctx = StageContext::new(runtime, format, project, doc).unwrap();
+
+        let ast = Pandoc {
+            meta: ConfigValue::default(),
+            blocks,
+        };
+        let doc_ast = DocumentAst {
+            path: PathBuf::from("/project/test.qmd"),
+            ast,
+            ast_context: pampa::pandoc::ASTContext::default(),
+            source_context: SourceContext::new(),
+            warnings: vec![],
+        };
+
+        let output = stage
+            .run(PipelineData::DocumentAst(doc_ast), &mut ctx)
+            .await
+            .unwrap();
+        (output.into_rendered_output().unwrap(), ctx)
+    }
+
+    fn math_header(rendered: &RenderedOutput) -> Option<&str> {
+        rendered
+            .metadata
+            .get("rendered")
+            .and_then(|r| r.get("math"))
+            .and_then(|m| m.as_str())
+    }
+
+    #[tokio::test]
+    async fn test_render_math_defaults_to_mathjax() {
+        let (rendered, ctx) =
+            render_math_document(RenderHtmlBodyStage::new(), Format::html()).await;
+        assert!(
+            rendered.content.contains("\\(x^2\\)"),
+            "{}",
+            rendered.content
+        );
+        assert!(ctx.artifacts.get("js:mathjax").is_some());
+        assert!(
+            math_header(&rendered)
+                .unwrap()
+                .contains(crate::math::MATHJAX_URL)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_render_math_method_from_format() {
+        let format = Format::html().with_metadata(serde_json::json!({
+            "html-math-method": "katex"
+        }));
+        let (rendered, ctx) = render_math_document(RenderHtmlBodyStage::new(), format).await;
+        assert!(
+            rendered
+                .content
+                .contains("<span class=\"math inline\">x^2</span>"),
+            "{}",
+            rendered.content
+        );
+        assert!(ctx.artifacts.get("js:katex").is_some());
+        assert!(ctx.artifacts.get("css:katex").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_render_math_stage_method_overrides_format() {
+        let format = Format::html().with_metadata(serde_json::json!({
+            "html-math-method": "katex"
+        }));
+        let (rendered, ctx) =
+            render_math_document(RenderHtmlBodyStage::with_math(MathMethod::MathML), format).await;
+        assert!(rendered.content.contains("<math display=\"inline\""));
+        assert!(ctx.artifacts.get("js:katex").is_none());
+        assert!(math_header(&rendered).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_render_unknown_math_method_warns() {
+        let format = Format::html().with_metadata(serde_json::json!({
+            "html-math-method": "webtex"
+        }));
+        let (_, ctx) = render_math_document(RenderHtmlBodyStage::new(), format).await;
+        assert_eq!(ctx.diagnostics.len(), 1);
+        assert!(ctx.artifacts.get("js:mathjax").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_render_node_ids_from_format() {
+        use quarto_pandoc_types::block::{Block, Paragraph};
+        use quarto_pandoc_types::inline::{Inline, Str};
+
+        let para = || {
+            vec![Block::Paragraph(Paragraph {
+                content: vec![Inline::Str(Str {
+                    text: "Text".to_string(),
+                    source_info: SourceInfo::default(),
+                })],
+                source_info: SourceInfo::default(),
+            })]
+        };
+        let id = quarto_pandoc_types::assign_node_ids(&para())
+            .get(&[0])
+            .unwrap();
+
+        let format = Format::html().with_metadata(serde_json::json!({ "node-ids": true }));
+        let (rendered, _) = render_document(RenderHtmlBodyStage::new(), format, para()).await;
+        assert!(
+            rendered
+                .content
+                .contains(&format!("<p data-quarto-node=\"{}\">Text</p>", id)),
+            "{}",
+            rendered.content
+        );
+
+        let (rendered, _) =
+            render_document(RenderHtmlBodyStage::new(), Format::html(), para()).await;
+        assert!(!rendered.content.contains("data-quarto-node"));
     }
 }
@@ -0,0 +1,373 @@
+/*
+ * stage/stages/render_latex.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Render AST to a complete LaTeX document.
+ */
+
+//! Render AST to a complete LaTeX document.
+//!
+//! This stage renders the Pandoc AST with pampa's LaTeX writer and wraps
+//! the body with the LaTeX template. Template options (`documentclass`,
+//! `geometry`, `toc`, ...) are read from the format first, then from the
+//! document metadata; title block metadata is converted to LaTeX.
+//!
+//! Code blocks are highlighted only when the highlight-style transform
+//! produced the macros they use (`rendered.highlighting-macros`).
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use pampa::template::context::{ConversionContext, MetaWriter, meta_to_text};
+use pampa::writers::latex::{self, LatexConfig};
+use quarto_doctemplate::{TemplateContext, TemplateValue};
+use quarto_pandoc_types::ConfigValue;
+
+use crate::stage::{
+    EventLevel, PipelineData, PipelineDataKind, PipelineError, PipelineStage, RenderedOutput,
+    StageContext,
+};
+use crate::template::{LATEX_TEMPLATE, format_template, latex_template};
+use crate::trace_event;
+
+/// Options passed to the template as written (raw LaTeX values).
+const TEMPLATE_OPTIONS: &[&str] = &[
+    "documentclass",
+    "classoption",
+    "fontsize",
+    "papersize",
+    "geometry",
+    "mainfont",
+    "toc",
+    "number-sections",
+    "header-includes",
+];
+
+/// Title block metadata converted to LaTeX.
+const TITLE_FIELDS: &[&str] = &["title", "subtitle", "date", "toc-title"];
+
+/// Render AST to a complete LaTeX document.
+///
+/// # Input
+///
+/// - `DocumentAst` - Transformed Pandoc AST
+///
+/// # Output
+///
+/// - `RenderedOutput` - The LaTeX source, marked intermediate; its
+///   `output_path` is the PDF to produce
+pub struct RenderLatexStage;
+
+impl RenderLatexStage {
+    /// Create a new RenderLatexStage.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for RenderLatexStage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PipelineStage for RenderLatexStage {
+    fn name(&self) -> &str {
+        "render-latex"
+    }
+
+    fn input_kind(&self) -> PipelineDataKind {
+        PipelineDataKind::DocumentAst
+    }
+
+    fn output_kind(&self) -> PipelineDataKind {
+        PipelineDataKind::RenderedOutput
+    }
+
+    async fn run(
+        &self,
+        input: PipelineData,
+        ctx: &mut StageContext,
+    ) -> Result<PipelineData, PipelineError> {
+        let PipelineData::DocumentAst(doc) = input else {
+            return Err(PipelineError::unexpected_input(
+                self.name(),
+                self.input_kind(),
+                input.kind(),
+            ));
+        };
+
+        let highlighting_macros = doc
+            .ast
+            .meta
+            .get_path(&["rendered", "highlighting-macros"])
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let config = LatexConfig {
+            highlight: highlighting_macros.is_some(),
+        };
+
+        let mut body_buf = Vec::new();
+        let diagnostics =
+            latex::write_with_config(&doc.ast, &mut body_buf, config).map_err(|diagnostics| {
+                PipelineError::stage_error_with_diagnostics(self.name(), diagnostics)
+            })?;
+        ctx.add_diagnostics(diagnostics);
+        let body = String::from_utf8(body_buf).map_err(|e| {
+            PipelineError::stage_error(self.name(), format!("Invalid UTF-8 in LaTeX body: {}", e))
+        })?;
+
+        let meta = &doc.ast.meta;
+        let mut tctx = TemplateContext::new();
+        tctx.insert("body", TemplateValue::String(body));
+        if let Some(macros) = highlighting_macros {
+            tctx.insert("highlighting-macros", TemplateValue::String(macros));
+        }
+
+        for key in TEMPLATE_OPTIONS {
+            let value = ctx
+                .format_metadata(key)
+                .map(TemplateValue::from)
+                .or_else(|| meta.get(key).map(raw_template_value));
+            if let Some(value) = value {
+                tctx.insert(*key, value);
+            }
+        }
+        if tctx.get("documentclass").is_none() {
+            tctx.insert(
+                "documentclass",
+                TemplateValue::String("article".to_string()),
+            );
+        }
+
+        for key in TITLE_FIELDS {
+            if let Some(value) = meta.get(key).and_then(to_latex) {
+                tctx.insert(*key, TemplateValue::String(value));
+            }
+        }
+        if let Some(abstract_) = meta.get("abstract").and_then(to_latex) {
+            tctx.insert("abstract", TemplateValue::String(abstract_));
+        }
+        let authors = authors(meta.get("author"));
+        if !authors.is_empty() {
+            let plain: Vec<String> = authors.iter().map(|(_, plain)| plain.clone()).collect();
+            tctx.insert(
+                "author-meta",
+                TemplateValue::String(latex::escape_latex(&plain.join(", "))),
+            );
+            tctx.insert(
+                "author",
+                TemplateValue::List(
+                    authors
+                        .into_iter()
+                        .map(|(tex, _)| TemplateValue::String(tex))
+                        .collect(),
+                ),
+            );
+        }
+        if let Some(title) = meta.get("title").and_then(ConfigValue::as_plain_text) {
+            tctx.insert(
+                "title-meta",
+                TemplateValue::String(latex::escape_latex(&title)),
+            );
+        }
+
+        // Images are written relative to the source document
+        if let Some(dir) = doc.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            let dir = dir.to_string_lossy().replace('\\', "/");
+            let dir = if dir.ends_with('/') {
+                dir
+            } else {
+                format!("{}/", dir)
+            };
+            tctx.insert("graphics-path", TemplateValue::String(dir));
+        }
+
+        // Extension formats and documents can bring their own template
+        let base_dir = doc.path.parent().unwrap_or(Path::new(""));
+        let template = match format_template(
+            &ctx.format,
+            Some((LATEX_TEMPLATE, &[])),
+            base_dir,
+            ctx.runtime.as_ref(),
+        ) {
+            Ok(Some(template)) => template,
+            Ok(None) => latex_template()
+                .map_err(|e| PipelineError::stage_error(self.name(), e.to_string()))?,
+            Err(e) => return Err(PipelineError::stage_error(self.name(), e.to_string())),
+        };
+        let content = template
+            .render(&tctx)
+            .map_err(|e| PipelineError::stage_error(self.name(), e.to_string()))?;
+
+        trace_event!(
+            ctx,
+            EventLevel::Debug,
+            "rendered {} bytes of LaTeX",
+            content.len()
+        );
+
+        Ok(PipelineData::RenderedOutput(RenderedOutput {
+            input_path: doc.path,
+            output_path: ctx.output_path(),
+            format: ctx.format.clone(),
+            content,
+            is_intermediate: true,
+            supporting_files: vec![],
+            metadata: doc.ast.meta,
+        }))
+    }
+}
+
+/// A document metadata option as a template value, taken literally.
+fn raw_template_value(value: &ConfigValue) -> TemplateValue {
+    if let Some(b) = value.as_bool() {
+        return TemplateValue::Bool(b);
+    }
+    if let Some(items) = value.as_array() {
+        return TemplateValue::List(items.iter().map(raw_template_value).collect());
+    }
+    match value.as_plain_text() {
+        Some(text) => TemplateValue::String(text),
+        None => TemplateValue::Null,
+    }
+}
+
+/// A metadata value as LaTeX text, rendered by the shared metadata
+/// conversion.
+fn to_latex(value: &ConfigValue) -> Option<String> {
+    meta_to_text(value, &mut ConversionContext::new(MetaWriter::Latex))
+}
+
+/// The document's authors as (LaTeX, plain text) pairs. Authors may be
+/// given as a single value, a list, or maps with a `name`.
+fn authors(value: Option<&ConfigValue>) -> Vec<(String, String)> {
+    let Some(value) = value else {
+        return Vec::new();
+    };
+    let items: Vec<&ConfigValue> = match value.as_array() {
+        Some(items) => items.iter().collect(),
+        None => vec![value],
+    };
+    items
+        .into_iter()
+        .filter_map(|item| {
+            let name = if item.is_map() {
+                item.get("name")?
+            } else {
+                item
+            };
+            Some((to_latex(name)?, name.as_plain_text()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::stage::{AstTransformsStage, LoadedSource, ParseDocumentStage};
+    use crate::transform::TransformPipeline;
+    use crate::transforms::HighlightStyleTransform;
+    use quarto_system_runtime::NativeRuntime;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    async fn render(source: &str, format: Format) -> String {
+        let project = ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![],
+            output_dir: PathBuf::from("/project"),
+        };
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let mut ctx =
+            StageContext::new(Arc::new(NativeRuntime::new()), format, project, doc).unwrap();
+        let input = PipelineData::LoadedSource(LoadedSource::new(
+            PathBuf::from("/project/doc.qmd"),
+            source.as_bytes().to_vec(),
+        ));
+        let parsed = ParseDocumentStage::new()
+            .run(input, &mut ctx)
+            .await
+            .unwrap();
+        let output = RenderLatexStage::new().run(parsed, &mut ctx).await.unwrap();
+        let rendered = output.into_rendered_output().unwrap();
+        assert!(rendered.is_intermediate);
+        assert_eq!(rendered.output_path, PathBuf::from("/project/doc.pdf"));
+        rendered.content
+    }
+
+    #[tokio::test]
+    async fn test_render_latex_document() {
+        let tex = render(
+            "---\ntitle: R&D *notes*\nauthor:\n  - name: Ann\n  - Bob\ntoc: true\n---\n\n# Intro\n\n50% done.\n",
+            Format::pdf(),
+        )
+        .await;
+        assert!(tex.starts_with("\\documentclass[]{article}\n"));
+        assert!(tex.contains("\\title{R\\&D \\emph{notes}}"));
+        assert!(tex.contains("pdftitle={R\\&D notes}"));
+        assert!(tex.contains("\\author{Ann \\and Bob}"));
+        assert!(tex.contains("\\graphicspath{{/project/}}"));
+        assert!(tex.contains("\\tableofcontents"));
+        assert!(tex.contains("\\section{Intro}\\label{intro}\n\n50\\% done.\n"));
+    }
+
+    #[tokio::test]
+    async fn test_code_highlighting_follows_macros() {
+        let source = "```python\nx = 1\n```\n";
+        let tex = render(source, Format::pdf()).await;
+        assert!(tex.contains("\\begin{verbatim}\nx = 1\n\\end{verbatim}\n"));
+        assert!(!tex.contains("Highlighting"));
+
+        // With the highlight-style transform, the macros are in the preamble
+        let project = ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![],
+            output_dir: PathBuf::from("/project"),
+        };
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let mut ctx =
+            StageContext::new(Arc::new(NativeRuntime::new()), Format::pdf(), project, doc).unwrap();
+        let input = PipelineData::LoadedSource(LoadedSource::new(
+            PathBuf::from("/project/doc.qmd"),
+            source.as_bytes().to_vec(),
+        ));
+        let parsed = ParseDocumentStage::new()
+            .run(input, &mut ctx)
+            .await
+            .unwrap();
+        let mut pipeline = TransformPipeline::new();
+        pipeline.push(Box::new(HighlightStyleTransform::new()));
+        let transformed = AstTransformsStage::with_pipeline(pipeline)
+            .run(parsed, &mut ctx)
+            .await
+            .unwrap();
+        let output = RenderLatexStage::new()
+            .run(transformed, &mut ctx)
+            .await
+            .unwrap();
+        let tex = output.into_rendered_output().unwrap().content;
+        assert!(tex.contains("\\newcommand{\\DecValTok}"));
+        assert!(tex.contains(
+            "\\begin{Highlighting}[]\n\\NormalTok{x }\\OperatorTok{=}\\NormalTok{ }\\DecValTok{1}\n"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_format_options_override_metadata() {
+        let format = Format::pdf().with_metadata(serde_json::json!({
+            "documentclass": "scrartcl",
+            "geometry": ["margin=1in", "a4paper"],
+        }));
+        let tex = render("---\ndocumentclass: report\n---\n\nText\n", format).await;
+        assert!(tex.starts_with("\\documentclass[]{scrartcl}\n"));
+        assert!(tex.contains("\\usepackage[margin=1in,a4paper]{geometry}"));
+    }
+}
@@ -0,0 +1,348 @@
+/*
+ * stage/stages/render_typst.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Render AST to a complete Typst document.
+ */
+
+//! Render AST to a complete Typst document.
+//!
+//! This stage renders the Pandoc AST with pampa's Typst writer and wraps the
+//! body with the Typst template bundle. Template options (`papersize`,
+//! `mainfont`, `toc`, ...) are read from the format first, then from the
+//! document metadata; title block metadata is converted to Typst.
+//!
+//! The writer's line map, shifted past the template preamble, is stored as
+//! the [`TYPST_LINE_MAP_ARTIFACT`] artifact so compile errors can be located
+//! in the source document.
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use pampa::template::context::{ConversionContext, MetaWriter, meta_to_text};
+use pampa::writers::typst;
+use quarto_doctemplate::{TemplateContext, TemplateValue};
+use quarto_pandoc_types::ConfigValue;
+use serde_json::Value;
+
+use crate::artifact::Artifact;
+use crate::stage::{
+    EventLevel, PipelineData, PipelineDataKind, PipelineError, PipelineStage, RenderedOutput,
+    StageContext,
+};
+use crate::template::{TYPST_PARTIALS, TYPST_TEMPLATE, format_template, typst_template};
+use crate::trace_event;
+
+/// Artifact key of the line map for the rendered `.typ` file (JSON).
+pub const TYPST_LINE_MAP_ARTIFACT: &str = "typst:line-map";
+
+/// Options passed to the template as written.
+const TEMPLATE_OPTIONS: &[&str] = &[
+    "mainfont",
+    "fontsize",
+    "papersize",
+    "section-numbering",
+    "toc",
+    "toc-depth",
+    "header-includes",
+];
+
+/// Title block metadata converted to Typst.
+const TITLE_FIELDS: &[&str] = &["title", "subtitle", "date", "abstract-title", "toc-title"];
+
+/// Numbering pattern for `number-sections: true`.
+const DEFAULT_SECTION_NUMBERING: &str = "1.1.a";
+
+/// Render AST to a complete Typst document.
+///
+/// # Input
+///
+/// - `DocumentAst` - Transformed Pandoc AST
+///
+/// # Output
+///
+/// - `RenderedOutput` - The Typst source, marked intermediate; its
+///   `output_path` is the PDF to produce
+pub struct RenderTypstStage;
+
+impl RenderTypstStage {
+    /// Create a new RenderTypstStage.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for RenderTypstStage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PipelineStage for RenderTypstStage {
+    fn name(&self) -> &str {
+        "render-typst"
+    }
+
+    fn input_kind(&self) -> PipelineDataKind {
+        PipelineDataKind::DocumentAst
+    }
+
+    fn output_kind(&self) -> PipelineDataKind {
+        PipelineDataKind::RenderedOutput
+    }
+
+    async fn run(
+        &self,
+        input: PipelineData,
+        ctx: &mut StageContext,
+    ) -> Result<PipelineData, PipelineError> {
+        let PipelineData::DocumentAst(doc) = input else {
+            return Err(PipelineError::unexpected_input(
+                self.name(),
+                self.input_kind(),
+                input.kind(),
+            ));
+        };
+
+        let mut body_buf = Vec::new();
+        let (diagnostics, line_map) =
+            typst::write_with_line_map(&doc.ast, &mut body_buf).map_err(|diagnostics| {
+                PipelineError::stage_error_with_diagnostics(self.name(), diagnostics)
+            })?;
+        ctx.add_diagnostics(diagnostics);
+        let body = String::from_utf8(body_buf).map_err(|e| {
+            PipelineError::stage_error(self.name(), format!("Invalid UTF-8 in Typst body: {}", e))
+        })?;
+
+        let meta = &doc.ast.meta;
+        let mut tctx = TemplateContext::new();
+        tctx.insert("body", TemplateValue::String(body.clone()));
+
+        for key in TEMPLATE_OPTIONS {
+            let value = ctx
+                .format_metadata(key)
+                .map(TemplateValue::from)
+                .or_else(|| meta.get(key).map(raw_template_value));
+            if let Some(value) = value {
+                tctx.insert(*key, value);
+            }
+        }
+        let number_sections = ctx
+            .format_metadata("number-sections")
+            .and_then(Value::as_bool)
+            .or_else(|| meta.get("number-sections").and_then(ConfigValue::as_bool))
+            .unwrap_or(false);
+        if number_sections && tctx.get("section-numbering").is_none() {
+            tctx.insert(
+                "section-numbering",
+                TemplateValue::String(DEFAULT_SECTION_NUMBERING.to_string()),
+            );
+        }
+        if tctx.get("toc-depth").is_none() {
+            tctx.insert("toc-depth", TemplateValue::String("3".to_string()));
+        }
+
+        for key in TITLE_FIELDS {
+            if let Some(value) = meta.get(key).and_then(to_typst) {
+                tctx.insert(*key, TemplateValue::String(value));
+            }
+        }
+        if let Some(abstract_) = meta.get("abstract").and_then(to_typst) {
+            tctx.insert("abstract", TemplateValue::String(abstract_));
+        }
+        if tctx.get("abstract-title").is_none() {
+            tctx.insert(
+                "abstract-title",
+                TemplateValue::String("Abstract".to_string()),
+            );
+        }
+        if tctx.get("toc-title").is_none() {
+            tctx.insert("toc-title", TemplateValue::String("Contents".to_string()));
+        }
+        let authors = authors(meta.get("author"));
+        if !authors.is_empty() {
+            tctx.insert(
+                "author",
+                TemplateValue::List(authors.into_iter().map(TemplateValue::String).collect()),
+            );
+        }
+
+        // `lang: en-US` sets both the language and the region
+        if let Some(lang) = meta.get("lang").and_then(ConfigValue::as_plain_text) {
+            let (language, region) = match lang.split_once(['-', '_']) {
+                Some((language, region)) => (language.to_string(), Some(region.to_string())),
+                None => (lang, None),
+            };
+            tctx.insert("lang", TemplateValue::String(language));
+            if let Some(region) = region {
+                tctx.insert("region", TemplateValue::String(region));
+            }
+        }
+
+        // Extension formats and documents can bring their own template or
+        // replace partials of the built-in one
+        let base_dir = doc.path.parent().unwrap_or(Path::new(""));
+        let template = match format_template(
+            &ctx.format,
+            Some((TYPST_TEMPLATE, TYPST_PARTIALS)),
+            base_dir,
+            ctx.runtime.as_ref(),
+        ) {
+            Ok(Some(template)) => template,
+            Ok(None) => typst_template()
+                .map_err(|e| PipelineError::stage_error(self.name(), e.to_string()))?,
+            Err(e) => return Err(PipelineError::stage_error(self.name(), e.to_string())),
+        };
+        let content = template
+            .render(&tctx)
+            .map_err(|e| PipelineError::stage_error(self.name(), e.to_string()))?;
+
+        // Locate the body so compile errors map to the right lines
+        if let Some(position) = content.rfind(body.as_str()).filter(|_| !body.is_empty()) {
+            let preamble_lines = content[..position].matches('\n').count();
+            let line_map = line_map.shifted(preamble_lines);
+            if let Ok(json) = serde_json::to_string(&line_map) {
+                ctx.artifacts.store(
+                    TYPST_LINE_MAP_ARTIFACT,
+                    Artifact::from_string(json, "application/json"),
+                );
+            }
+        }
+
+        trace_event!(
+            ctx,
+            EventLevel::Debug,
+            "rendered {} bytes of Typst",
+            content.len()
+        );
+
+        Ok(PipelineData::RenderedOutput(RenderedOutput {
+            input_path: doc.path,
+            output_path: ctx.output_path(),
+            format: ctx.format.clone(),
+            content,
+            is_intermediate: true,
+            supporting_files: vec![],
+            metadata: doc.ast.meta,
+        }))
+    }
+}
+
+/// A document metadata option as a template value, taken literally.
+fn raw_template_value(value: &ConfigValue) -> TemplateValue {
+    if let Some(b) = value.as_bool() {
+        return TemplateValue::Bool(b);
+    }
+    if let Some(items) = value.as_array() {
+        return TemplateValue::List(items.iter().map(raw_template_value).collect());
+    }
+    match value.as_plain_text() {
+        Some(text) => TemplateValue::String(text),
+        None => TemplateValue::Null,
+    }
+}
+
+/// A metadata value as Typst text, rendered by the shared metadata
+/// conversion.
+fn to_typst(value: &ConfigValue) -> Option<String> {
+    meta_to_text(value, &mut ConversionContext::new(MetaWriter::Typst))
+}
+
+/// The document's authors as Typst. Authors may be given as a single value,
+/// a list, or maps with a `name`.
+fn authors(value: Option<&ConfigValue>) -> Vec<String> {
+    let Some(value) = value else {
+        return Vec::new();
+    };
+    let items: Vec<&ConfigValue> = match value.as_array() {
+        Some(items) => items.iter().collect(),
+        None => vec![value],
+    };
+    items
+        .into_iter()
+        .filter_map(|item| {
+            let name = if item.is_map() {
+                item.get("name")?
+            } else {
+                item
+            };
+            to_typst(name)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::stage::{LoadedSource, ParseDocumentStage};
+    use pampa::writers::typst::LineMap;
+    use quarto_system_runtime::NativeRuntime;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    async fn render(source: &str, format: Format) -> (String, StageContext) {
+        let project = ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![],
+            output_dir: PathBuf::from("/project"),
+        };
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let mut ctx =
+            StageContext::new(Arc::new(NativeRuntime::new()), format, project, doc).unwrap();
+        let input = PipelineData::LoadedSource(LoadedSource::new(
+            PathBuf::from("/project/doc.qmd"),
+            source.as_bytes().to_vec(),
+        ));
+        let parsed = ParseDocumentStage::new()
+            .run(input, &mut ctx)
+            .await
+            .unwrap();
+        let output = RenderTypstStage::new().run(parsed, &mut ctx).await.unwrap();
+        let rendered = output.into_rendered_output().unwrap();
+        assert!(rendered.is_intermediate);
+        assert_eq!(rendered.output_path, PathBuf::from("/project/doc.pdf"));
+        (rendered.content, ctx)
+    }
+
+    #[tokio::test]
+    async fn test_render_typst_document() {
+        let (typ, _) = render(
+            "---\ntitle: R&D *notes*\nauthor:\n  - name: Ann\n  - Bob\nlang: de-CH\nnumber-sections: true\n---\n\n# Intro\n\nSome #text.\n",
+            Format::typst(),
+        )
+        .await;
+        assert!(typ.contains("  title: [R&D #emph[notes]],\n"));
+        assert!(typ.contains("  authors: ([Ann],[Bob],),\n"));
+        assert!(typ.contains("  lang: \"de\",\n  region: \"CH\",\n"));
+        assert!(typ.contains("  sectionnumbering: \"1.1.a\",\n"));
+        assert!(typ.ends_with("= Intro <intro>\n\nSome \\#text.\n"));
+    }
+
+    #[tokio::test]
+    async fn test_line_map_artifact() {
+        let (typ, ctx) = render("# Intro\n\nText\n", Format::typst()).await;
+        let artifact = ctx.artifacts.get(TYPST_LINE_MAP_ARTIFACT).unwrap();
+        let line_map: LineMap = serde_json::from_slice(&artifact.content).unwrap();
+
+        let text_line = typ.lines().position(|line| line == "Text").unwrap() + 1;
+        let source = line_map.lookup(text_line).unwrap();
+        assert_eq!(source.start_offset(), 9);
+        assert!(line_map.lookup(text_line - 3).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_format_options_override_metadata() {
+        let format = Format::typst().with_metadata(serde_json::json!({
+            "papersize": "a4",
+            "toc": true,
+        }));
+        let (typ, _) = render("---\npapersize: us-letter\n---\n\nText\n", format).await;
+        assert!(typ.contains("  paper: \"a4\",\n"));
+        assert!(typ.contains("  toc: true,\n  toc-title: [Contents],\n  toc-depth: 3,\n"));
+    }
+}
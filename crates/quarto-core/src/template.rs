@@ -11,6 +11,10 @@
 //! engine and the Quarto render pipeline. It handles:
 //!
 //! - Default HTML template for standalone documents
+//! - LaTeX template for PDF output
+//! - Typst template bundle for `format: typst`
+//! - Templates and partials named by format metadata (`template:`,
+//!   `template-partials:`), as extension formats provide
 //! - Conversion of Pandoc metadata to template values
 //! - Rendering documents through the template engine
 //!
@@ -20,11 +24,14 @@
 //! is passed as a template variable, allowing the template to control the
 //! overall document structure while the HTML writer controls content rendering.
 
-use quarto_doctemplate::{Template, TemplateContext, TemplateValue};
+use std::path::Path;
+
+use quarto_doctemplate::{MemoryResolver, Template, TemplateContext, TemplateValue};
 use quarto_pandoc_types::{ConfigValue, ConfigValueKind};
+use quarto_system_runtime::SystemRuntime;
 
 use crate::Result;
-use crate::format::Format;
+use crate::format::{Format, FormatIdentifier};
 
 // =============================================================================
 // Template Definitions
@@ -39,6 +46,10 @@ use crate::format::Format;
 /// - `$pagetitle$` / `$title$` - document title
 /// - `$body$` - rendered body content
 /// - `$css$` - CSS stylesheets (external files)
+/// - `$rendered.highlighting-css$` - syntax highlighting stylesheet (inlined)
+/// - `$rendered.math$` - math rendering scripts/styles (MathJax, KaTeX)
+/// - `$rendered.panels$` - tabset and layout panel scripts/styles
+/// - `$rendered.color-scheme$` - dark stylesheets and the color scheme script
 /// - `$lang$` - document language
 /// - `$header-includes$` - additional header content
 const MINIMAL_HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
@@ -52,6 +63,20 @@ $endif$
 $for(css)$
 <link rel="stylesheet" href="$css$">
 $endfor$
+$if(rendered.color-scheme)$
+$rendered.color-scheme$
+$endif$
+$if(rendered.highlighting-css)$
+<style>
+$rendered.highlighting-css$
+</style>
+$endif$
+$if(rendered.math)$
+$rendered.math$
+$endif$
+$if(rendered.panels)$
+$rendered.panels$
+$endif$
 $if(header-includes)$
 $header-includes$
 $endif$
@@ -76,11 +101,21 @@ $body$
 /// - `$author$` - document author(s)
 /// - `$date$` - publication date
 /// - `$abstract$` - document abstract
+/// - `$brand.logo.medium.path$` - brand logo for the title block (see [`crate::brand`])
 /// - `$body-classes$` - CSS classes for body element
 /// - `$page-layout$` - page layout type (article, full, etc.)
 /// - `$version$` - Quarto version for generator meta tag
 /// - `$rendered.navigation.toc$` - Rendered TOC HTML (if toc: true)
 /// - `$navigation.toc.title$` - TOC title (if set)
+/// - `$rendered.navigation.navbar$` - Website navbar HTML
+/// - `$rendered.navigation.sidebar$` - Website sidebar HTML
+/// - `$rendered.navigation.breadcrumbs$` - Website breadcrumbs HTML
+/// - `$rendered.navigation.footer$` - Website page footer HTML
+/// - `$rendered.navigation.page-nav$` - Book previous/next chapter links
+/// - `$rendered.social-meta$` - OpenGraph, Twitter card and favicon tags
+///
+/// With `$rendered.color-scheme$` set, the body starts with a light/dark
+/// toggle button.
 const FULL_HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
 <html$if(lang)$ lang="$lang$"$endif$>
 <head>
@@ -102,19 +137,47 @@ $endif$
 $if(canonical-url)$
 <link rel="canonical" href="$canonical-url$">
 $endif$
+$if(rendered.social-meta)$
+$rendered.social-meta$
+$endif$
 $if(pagetitle)$
 <title>$pagetitle$</title>
 $endif$
 $for(css)$
 <link rel="stylesheet" href="$css$">
 $endfor$
+$if(rendered.color-scheme)$
+$rendered.color-scheme$
+$endif$
+$if(rendered.highlighting-css)$
+<style>
+$rendered.highlighting-css$
+</style>
+$endif$
+$if(rendered.math)$
+$rendered.math$
+$endif$
+$if(rendered.panels)$
+$rendered.panels$
+$endif$
 $if(header-includes)$
 $header-includes$
 $endif$
 </head>
-<body class="fullcontent$if(body-classes)$ $body-classes$$endif$">
+<body class="$if(rendered.navigation.navbar)$nav-fixed $endif$$if(rendered.navigation.sidebar)$nav-sidebar floating $endif$fullcontent$if(body-classes)$ $body-classes$$endif$">
 
+$if(rendered.color-scheme)$
+<button type="button" class="quarto-color-scheme-toggle" title="Toggle dark mode" aria-label="Toggle dark mode"></button>
+$endif$
+$if(rendered.navigation.navbar)$
+<header id="quarto-header" class="headroom fixed-top">
+$rendered.navigation.navbar$
+</header>
+$endif$
 <div id="quarto-content" class="page-columns page-rows-contents page-layout-$page-layout$">
+$if(rendered.navigation.sidebar)$
+$rendered.navigation.sidebar$
+$endif$
 $if(rendered.navigation.toc)$
 <div id="quarto-margin-sidebar" class="sidebar margin-sidebar">
 <nav id="TOC" role="doc-toc" class="toc-active">
@@ -128,9 +191,15 @@ $endif$
 
 <main class="content" id="quarto-document-content">
 
+$if(rendered.navigation.breadcrumbs)$
+$rendered.navigation.breadcrumbs$
+$endif$
 $if(title)$
 <header id="title-block-header" class="quarto-title-block default">
 <div class="quarto-title">
+$if(brand.logo.medium.path)$
+<img src="$brand.logo.medium.path$" alt="$brand.logo.medium.alt$" class="quarto-title-logo">
+$endif$
 <h1 class="title">$title$</h1>
 $if(subtitle)$
 <p class="subtitle">$subtitle$</p>
@@ -160,12 +229,318 @@ $endif$
 $endif$
 
 $body$
+$if(rendered.navigation.page-nav)$
+$rendered.navigation.page-nav$
+$endif$
 </main>
 </div>
+$if(rendered.navigation.footer)$
+$rendered.navigation.footer$
+$endif$
 </body>
 </html>
 "#;
 
+/// reveal.js template for `format: revealjs` presentations.
+///
+/// The body holds the slides (see
+/// [`RevealjsSlidesTransform`](crate::transforms::RevealjsSlidesTransform));
+/// a title slide is generated from the title metadata.
+///
+/// Template variables (in addition to minimal):
+/// - `$title$`, `$subtitle$`, `$author$`, `$date$` - title slide
+/// - `$version$` - Quarto version for generator meta tag
+/// - `$rendered.revealjs.head$` - reveal.js and theme stylesheets
+/// - `$rendered.revealjs.scripts$` - reveal.js scripts and initialization
+const REVEALJS_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html$if(lang)$ lang="$lang$"$endif$>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0, maximum-scale=1.0, user-scalable=no">
+<meta name="generator" content="quarto-rust-$version$">
+$if(author)$
+<meta name="author" content="$author$">
+$endif$
+$if(date)$
+<meta name="dcterms.date" content="$date$">
+$endif$
+$if(pagetitle)$
+<title>$pagetitle$</title>
+$endif$
+$rendered.revealjs.head$
+$for(css)$
+<link rel="stylesheet" href="$css$">
+$endfor$
+$if(rendered.highlighting-css)$
+<style>
+$rendered.highlighting-css$
+</style>
+$endif$
+$if(rendered.math)$
+$rendered.math$
+$endif$
+$if(rendered.panels)$
+$rendered.panels$
+$endif$
+$if(header-includes)$
+$header-includes$
+$endif$
+</head>
+<body class="quarto-revealjs">
+<div class="reveal">
+<div class="slides">
+
+$if(title)$
+<section id="title-slide" class="quarto-title-block center">
+<h1 class="title">$title$</h1>
+$if(subtitle)$
+<p class="subtitle">$subtitle$</p>
+$endif$
+$if(author)$
+<div class="quarto-title-authors">$author$</div>
+$endif$
+$if(date)$
+<p class="date">$date$</p>
+$endif$
+</section>
+$endif$
+
+$body$
+</div>
+</div>
+$rendered.revealjs.scripts$
+</body>
+</html>
+"#;
+
+/// LaTeX template for PDF output.
+///
+/// Unlike the HTML templates, every variable holds LaTeX: metadata is
+/// converted with the LaTeX writer by the render-latex stage.
+///
+/// Template variables:
+/// - `$documentclass$`, `$classoption$`, `$fontsize$`, `$papersize$` - document class
+/// - `$geometry$` - options for the `geometry` package
+/// - `$mainfont$` - main font (XeLaTeX/Tectonic)
+/// - `$graphics-path$` - directory images are resolved against
+/// - `$title$`, `$subtitle$`, `$author$`, `$date$`, `$abstract$` - title block
+/// - `$title-meta$`, `$author-meta$` - plain-text PDF metadata
+/// - `$toc$`, `$toc-title$` - table of contents
+/// - `$number-sections$` - number section headings
+/// - `$highlighting-macros$` - syntax highlighting environments and macros
+/// - `$header-includes$` - additional preamble content
+/// - `$body$` - rendered body content
+pub const LATEX_TEMPLATE: &str = r#"\documentclass[$if(fontsize)$$fontsize$,$endif$$if(papersize)$$papersize$paper,$endif$$for(classoption)$$classoption$$sep$,$endfor$]{$documentclass$}
+\usepackage{amsmath,amssymb}
+\usepackage{iftex}
+\ifPDFTeX
+  \usepackage[T1]{fontenc}
+  \usepackage[utf8]{inputenc}
+\else
+  \usepackage{fontspec}
+\fi
+$if(mainfont)$
+\setmainfont{$mainfont$}
+$endif$
+\usepackage{graphicx}
+\makeatletter
+\def\maxwidth{\ifdim\Gin@nat@width>\linewidth\linewidth\else\Gin@nat@width\fi}
+\makeatother
+\setkeys{Gin}{width=\maxwidth,keepaspectratio}
+$if(graphics-path)$
+\graphicspath{{$graphics-path$}}
+$endif$
+\usepackage{longtable,booktabs,array,calc}
+\usepackage[normalem]{ulem}
+\providecommand{\tightlist}{\setlength{\itemsep}{0pt}\setlength{\parskip}{0pt}}
+$if(geometry)$
+\usepackage[$for(geometry)$$geometry$$sep$,$endfor$]{geometry}
+$endif$
+$if(number-sections)$
+$else$
+\setcounter{secnumdepth}{-\maxdimen}
+$endif$
+$for(header-includes)$
+$header-includes$
+$endfor$
+\usepackage{xcolor}
+$if(highlighting-macros)$
+$highlighting-macros$
+$endif$
+\usepackage[hidelinks]{hyperref}
+\hypersetup{$if(title-meta)$pdftitle={$title-meta$},$endif$$if(author-meta)$pdfauthor={$author-meta$},$endif$}
+$if(title)$
+\title{$title$$if(subtitle)$\\\large $subtitle$$endif$}
+$endif$
+\author{$for(author)$$author$$sep$ \and $endfor$}
+\date{$date$}
+
+\begin{document}
+$if(title)$
+\maketitle
+$endif$
+$if(abstract)$
+\begin{abstract}
+$abstract$
+\end{abstract}
+$endif$
+$if(toc)$
+$if(toc-title)$
+\renewcommand*\contentsname{$toc-title$}
+$endif$
+\tableofcontents
+$endif$
+
+$body$
+
+\end{document}
+"#;
+
+/// Typst template for `format: typst`.
+///
+/// The template is a bundle: the main template includes the partials in
+/// [`TYPST_PARTIALS`], so each piece can later be replaced on its own (as
+/// with Quarto's `template-partials`). Every variable holds Typst markup:
+/// metadata is converted with the Typst writer by the render-typst stage.
+///
+/// Template variables:
+/// - `$title$`, `$subtitle$`, `$author$`, `$date$`, `$abstract$` - title block
+/// - `$lang$`, `$region$` - document language
+/// - `$mainfont$`, `$fontsize$`, `$papersize$` - page and text setup
+/// - `$section-numbering$` - heading numbering pattern (e.g. `1.1.a`)
+/// - `$toc$`, `$toc-title$`, `$toc-depth$` - table of contents
+/// - `$header-includes$` - additional content before the document
+/// - `$body$` - rendered body content
+pub const TYPST_TEMPLATE: &str = r#"$definitions.typ()$
+
+$typst-template.typ()$
+
+$for(header-includes)$
+$header-includes$
+
+$endfor$
+$typst-show.typ()$
+
+$body$
+"#;
+
+/// Shared definitions for Typst documents.
+const TYPST_DEFINITIONS: &str = r#"// Definition lists: bold terms with indented descriptions
+#show terms: it => {
+  it.children
+    .map(child => [
+      #strong[#child.term]
+      #block(inset: (left: 1.5em, top: -0.4em))[#child.description]
+    ])
+    .join()
+}
+
+#set table(inset: 6pt, stroke: (x, y) => if y == 0 { (bottom: 0.5pt) })
+"#;
+
+/// The `article` function that lays out the page and title block.
+const TYPST_ARTICLE: &str = r#"#let article(
+  title: none,
+  subtitle: none,
+  authors: (),
+  date: none,
+  abstract: none,
+  abstract-title: none,
+  lang: "en",
+  region: "US",
+  font: none,
+  fontsize: 11pt,
+  paper: "us-letter",
+  sectionnumbering: none,
+  toc: false,
+  toc-title: none,
+  toc-depth: none,
+  doc,
+) = {
+  set page(paper: paper, numbering: "1")
+  set par(justify: true)
+  set text(lang: lang, region: region, size: fontsize)
+  set text(font: font) if font != none
+  set heading(numbering: sectionnumbering)
+
+  if title != none {
+    align(center, block(inset: 2em, {
+      text(weight: "bold", size: 1.5em, title)
+      if subtitle != none {
+        parbreak()
+        text(weight: "bold", size: 1.25em, subtitle)
+      }
+    }))
+  }
+  if authors.len() > 0 {
+    align(center, authors.join(", ", last: " and "))
+  }
+  if date != none {
+    align(center, block(inset: 1em, date))
+  }
+  if abstract != none {
+    block(inset: 2em)[#text(weight: "semibold", abstract-title) #h(1em) #abstract]
+  }
+  if toc {
+    block(above: 0em, below: 2em, outline(title: toc-title, depth: toc-depth))
+  }
+
+  doc
+}
+"#;
+
+/// The show rule that applies `article` to the document.
+const TYPST_SHOW: &str = r#"#show: doc => article(
+$if(title)$
+  title: [$title$],
+$endif$
+$if(subtitle)$
+  subtitle: [$subtitle$],
+$endif$
+$if(author)$
+  authors: ($for(author)$[$author$],$endfor$),
+$endif$
+$if(date)$
+  date: [$date$],
+$endif$
+$if(lang)$
+  lang: "$lang$",
+$endif$
+$if(region)$
+  region: "$region$",
+$endif$
+$if(abstract)$
+  abstract: [$abstract$],
+  abstract-title: [$abstract-title$],
+$endif$
+$if(mainfont)$
+  font: ("$mainfont$",),
+$endif$
+$if(fontsize)$
+  fontsize: $fontsize$,
+$endif$
+$if(papersize)$
+  paper: "$papersize$",
+$endif$
+$if(section-numbering)$
+  sectionnumbering: "$section-numbering$",
+$endif$
+$if(toc)$
+  toc: true,
+  toc-title: [$toc-title$],
+  toc-depth: $toc-depth$,
+$endif$
+  doc,
+)
+"#;
+
+/// The partials of the Typst template bundle, by name.
+pub const TYPST_PARTIALS: &[(&str, &str)] = &[
+    ("definitions.typ", TYPST_DEFINITIONS),
+    ("typst-template.typ", TYPST_ARTICLE),
+    ("typst-show.typ", TYPST_SHOW),
+];
+
 // =============================================================================
 // Template Compilation
 // =============================================================================
@@ -182,6 +557,88 @@ pub fn full_html_template() -> Result<Template> {
         .map_err(|e| crate::error::QuartoError::other(e.to_string()))
 }
 
+/// Compile the reveal.js template for `format: revealjs`.
+pub fn revealjs_template() -> Result<Template> {
+    Template::compile(REVEALJS_TEMPLATE)
+        .map_err(|e| crate::error::QuartoError::other(e.to_string()))
+}
+
+/// Compile the LaTeX template for PDF output.
+pub fn latex_template() -> Result<Template> {
+    Template::compile(LATEX_TEMPLATE).map_err(|e| crate::error::QuartoError::other(e.to_string()))
+}
+
+/// Compile the Typst template bundle for `format: typst`.
+pub fn typst_template() -> Result<Template> {
+    let resolver = MemoryResolver::with_partials(TYPST_PARTIALS.iter().copied());
+    Template::compile_with_resolver(TYPST_TEMPLATE, Path::new("template.typ"), &resolver, 0)
+        .map_err(|e| crate::error::QuartoError::other(e.to_string()))
+}
+
+/// Compile the template a format's metadata asks for, if it asks for one.
+///
+/// `template:` replaces the built-in template. `template-partials:` files
+/// replace the built-in partials with the same file name, or provide the
+/// partials a custom template includes. Relative paths are relative to
+/// `base_dir` (extension formats have theirs made absolute).
+///
+/// Returns `None` when the format uses `builtin` (a template and its
+/// partials) unchanged, or sets no `template:` and has no `builtin`.
+pub fn format_template(
+    format: &Format,
+    builtin: Option<(&str, &[(&str, &str)])>,
+    base_dir: &Path,
+    runtime: &dyn SystemRuntime,
+) -> Result<Option<Template>> {
+    let read = |path: &str| {
+        let path = base_dir.join(path);
+        runtime
+            .file_read_string(&path)
+            .map(|content| (path.clone(), content))
+            .map_err(|e| {
+                crate::error::QuartoError::other(format!(
+                    "Failed to read template {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+    };
+
+    let partial_paths: Vec<&str> = match format.get_metadata("template-partials") {
+        Some(serde_json::Value::Array(items)) => {
+            items.iter().filter_map(serde_json::Value::as_str).collect()
+        }
+        Some(serde_json::Value::String(path)) => vec![path.as_str()],
+        _ => Vec::new(),
+    };
+    let custom = format.get_metadata_string("template");
+    let (source, template_path, builtin_partials) = match (custom, builtin) {
+        (Some(path), builtin) => {
+            let (path, source) = read(path)?;
+            (source, path, builtin.map_or(&[][..], |(_, partials)| partials))
+        }
+        (None, Some((source, partials))) if !partial_paths.is_empty() => (
+            source.to_string(),
+            Path::new("template").to_path_buf(),
+            partials,
+        ),
+        _ => return Ok(None),
+    };
+
+    let mut resolver = MemoryResolver::with_partials(builtin_partials.iter().copied());
+    for path in partial_paths {
+        let (path, content) = read(path)?;
+        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            resolver.add(name, content);
+        }
+    }
+    Template::compile_with_resolver(&source, &template_path, &resolver, 0)
+        .map(Some)
+        .map_err(|e| {
+            crate::error::QuartoError::other(format!("{}: {}", template_path.display(), e))
+        })
+}
+
 /// Compile the default HTML template (minimal template for backwards compatibility).
 pub fn default_html_template() -> Result<Template> {
     minimal_html_template()
@@ -189,14 +646,17 @@ pub fn default_html_template() -> Result<Template> {
 
 /// Select and compile the appropriate template based on format configuration.
 ///
-/// Returns the minimal template when:
+/// `format: revealjs` uses the reveal.js template. Otherwise, returns the
+/// minimal template when:
 /// - `minimal: true` is set in format metadata
 /// - `theme: none` is set
 /// - `theme: pandoc` is set
 ///
 /// Otherwise returns the full template with Bootstrap-compatible structure.
 pub fn select_template(format: &Format) -> Result<Template> {
-    if format.use_minimal_html() {
+    if format.identifier == FormatIdentifier::Revealjs {
+        revealjs_template()
+    } else if format.use_minimal_html() {
         minimal_html_template()
     } else {
         full_html_template()
@@ -324,11 +784,17 @@ pub fn render_with_format(
     // Add metadata, but we'll handle css specially
     add_metadata_to_context_except(meta, &mut ctx, &["css"]);
 
-    // Build combined CSS list: default resources first, then user-specified
-    let mut css_list: Vec<TemplateValue> = css_paths
-        .iter()
-        .map(|p| TemplateValue::String(p.clone()))
-        .collect();
+    // Build combined CSS list: default resources first, then user-specified.
+    // Presentations are styled by their reveal.js theme instead of the
+    // default resources.
+    let mut css_list: Vec<TemplateValue> = if format.identifier == FormatIdentifier::Revealjs {
+        Vec::new()
+    } else {
+        css_paths
+            .iter()
+            .map(|p| TemplateValue::String(p.clone()))
+            .collect()
+    };
 
     // Add any user-specified CSS from metadata
     if let Some(user_css) = extract_css_from_meta(meta) {
@@ -565,6 +1031,102 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_latex_template_renders() {
+        let template = latex_template().unwrap();
+        let mut ctx = TemplateContext::new();
+        ctx.insert("documentclass", TemplateValue::String("article".into()));
+        ctx.insert("title", TemplateValue::String("A \\& B".into()));
+        ctx.insert(
+            "author",
+            TemplateValue::List(vec![
+                TemplateValue::String("Ann".into()),
+                TemplateValue::String("Bob".into()),
+            ]),
+        );
+        ctx.insert("body", TemplateValue::String("Hello.".into()));
+
+        let tex = template.render(&ctx).unwrap();
+        assert!(tex.starts_with("\\documentclass[]{article}\n"));
+        assert!(tex.contains("\\title{A \\& B}"));
+        assert!(tex.contains("\\author{Ann \\and Bob}"));
+        assert!(tex.contains("\\setcounter{secnumdepth}{-\\maxdimen}"));
+        assert!(tex.contains("\\maketitle"));
+        assert!(tex.contains("\nHello.\n\n\\end{document}"));
+    }
+
+    #[test]
+    fn test_typst_template_renders() {
+        let template = typst_template().unwrap();
+        let mut ctx = TemplateContext::new();
+        ctx.insert("title", TemplateValue::String("A \\# B".into()));
+        ctx.insert(
+            "author",
+            TemplateValue::List(vec![
+                TemplateValue::String("Ann".into()),
+                TemplateValue::String("Bob".into()),
+            ]),
+        );
+        ctx.insert("toc", TemplateValue::Bool(true));
+        ctx.insert("toc-title", TemplateValue::String("Contents".into()));
+        ctx.insert("toc-depth", TemplateValue::String("3".into()));
+        ctx.insert("body", TemplateValue::String("Hello.".into()));
+
+        let typ = template.render(&ctx).unwrap();
+        assert!(typ.starts_with("// Definition lists"));
+        assert!(typ.contains("#let article("));
+        assert!(typ.contains("  title: [A \\# B],\n"));
+        assert!(typ.contains("  authors: ([Ann],[Bob],),\n"));
+        assert!(typ.contains("  toc-depth: 3,\n"));
+        assert!(!typ.contains("subtitle: ["));
+        assert!(typ.ends_with("\nHello.\n"));
+    }
+
+    #[test]
+    fn test_format_template() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("typst-show.typ"),
+            "#show: custom.with($if(title)$title: [$title$]$endif$)",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("custom.html"), "<main>$body$</main>").unwrap();
+        let runtime = quarto_system_runtime::NativeRuntime::new();
+        let typst = Some((TYPST_TEMPLATE, TYPST_PARTIALS));
+
+        // The built-in template is used as is without customizations
+        let plain = Format::typst();
+        assert!(
+            format_template(&plain, typst, dir.path(), &runtime)
+                .unwrap()
+                .is_none()
+        );
+
+        // A partial replaces the built-in partial of the same name
+        let partials = Format::typst().with_metadata(serde_json::json!({
+            "template-partials": ["typst-show.typ"],
+        }));
+        let template = format_template(&partials, typst, dir.path(), &runtime)
+            .unwrap()
+            .unwrap();
+        let mut ctx = TemplateContext::new();
+        ctx.insert("title", TemplateValue::String("T".into()));
+        ctx.insert("body", TemplateValue::String("Hello.".into()));
+        let typ = template.render(&ctx).unwrap();
+        assert!(typ.contains("#show: custom.with(title: [T])"));
+        assert!(typ.contains("#let article("));
+
+        // `template:` replaces the template
+        let custom = Format::html().with_metadata(serde_json::json!({ "template": "custom.html" }));
+        let template = format_template(&custom, None, dir.path(), &runtime)
+            .unwrap()
+            .unwrap();
+        assert_eq!(template.render(&ctx).unwrap(), "<main>Hello.</main>");
+
+        let missing = Format::html().with_metadata(serde_json::json!({ "template": "none.html" }));
+        assert!(format_template(&missing, None, dir.path(), &runtime).is_err());
+    }
+
     #[test]
     fn test_render_simple_document() {
         let meta = ConfigValue::new_map(
@@ -638,6 +1200,32 @@ mod tests {
         assert!(output.contains("Body: Hello"));
     }
 
+    #[test]
+    fn test_render_revealjs() {
+        let meta = ConfigValue::new_map(
+            vec![ConfigMapEntry {
+                key: "title".to_string(),
+                key_source: dummy_source_info(),
+                value: ConfigValue::new_string("Talk", dummy_source_info()),
+            }],
+            dummy_source_info(),
+        );
+
+        let css_paths = vec!["lib/styles.css".to_string()];
+        let html = render_with_format(
+            "<section class=\"section slide level2\"></section>",
+            &meta,
+            &Format::revealjs(),
+            &css_paths,
+        )
+        .unwrap();
+        assert!(html.contains("<div class=\"reveal\">\n<div class=\"slides\">"));
+        assert!(html.contains("<section id=\"title-slide\""));
+        assert!(html.contains("<h1 class=\"title\">Talk</h1>"));
+        // The default stylesheet would fight the reveal.js theme
+        assert!(!html.contains("lib/styles.css"));
+    }
+
     #[test]
     fn test_render_with_resources() {
         let meta = ConfigValue::new_map(
@@ -1427,6 +2015,31 @@ mod tests {
         assert!(html.contains("<p class=\"subtitle\">A Subtitle</p>"));
     }
 
+    #[test]
+    fn test_full_template_title_block_brand_logo() {
+        let template = full_html_template().unwrap();
+
+        let mut meta = ConfigValue::default();
+        meta.insert_path(
+            &["brand", "logo", "medium", "path"],
+            ConfigValue::new_path("logo.svg".to_string(), dummy_source_info()),
+        );
+        meta.insert_path(
+            &["brand", "logo", "medium", "alt"],
+            ConfigValue::new_string("Acme", dummy_source_info()),
+        );
+        let mut ctx = TemplateContext::new();
+        add_metadata_to_context(&meta, &mut ctx);
+        ctx.insert("body", TemplateValue::String("<p>Content</p>".to_string()));
+        ctx.insert("title", TemplateValue::String("My Document".to_string()));
+
+        let html = template.render(&ctx).unwrap();
+
+        assert!(html.contains(
+            "<img src=\"logo.svg\" alt=\"Acme\" class=\"quarto-title-logo\">\n<h1 class=\"title\">"
+        ));
+    }
+
     #[test]
     fn test_full_template_no_title_block_without_title() {
         let template = full_html_template().unwrap();
@@ -1586,4 +2199,27 @@ mod tests {
         // Should have author meta
         assert!(html.contains("<meta name=\"author\" content=\"Jane Doe\">"));
     }
+
+    #[test]
+    fn test_render_with_format_inlines_highlighting_css() {
+        use crate::format::Format;
+
+        let mut meta = ConfigValue::null(dummy_source_info());
+        meta.insert_path(
+            &["rendered", "highlighting-css"],
+            ConfigValue::new_string("code span.kw { font-weight: bold; }", dummy_source_info()),
+        );
+
+        for format in [
+            Format::html(),
+            Format::html().with_metadata(serde_json::json!({"minimal": true})),
+        ] {
+            let html = render_with_format("<p>Hello</p>", &meta, &format, &[]).unwrap();
+            assert!(
+                html.contains("<style>\ncode span.kw { font-weight: bold; }\n</style>"),
+                "{}",
+                html
+            );
+        }
+    }
 }
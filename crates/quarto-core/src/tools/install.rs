@@ -0,0 +1,443 @@
+/*
+ * tools/install.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Downloading and installing tools.
+ */
+
+//! Downloading and installing tools.
+//!
+//! The latest release of a tool is looked up online ([`latest_release`]):
+//! - TinyTeX from the releases of
+//!   [rstudio/tinytex-releases](https://github.com/rstudio/tinytex-releases)
+//! - Chromium from the last known good Stable version of
+//!   [Chrome for Testing](https://googlechromelabs.github.io/chrome-for-testing/)
+//!
+//! The release archive is downloaded through the [`SystemRuntime`] and
+//! unpacked into the tool's directory, without the archive's top-level
+//! directory, replacing any previous installation. Unpacking writes to the
+//! file system directly, to keep the permissions of executables.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use quarto_system_runtime::SystemRuntime;
+use serde::Deserialize;
+
+use super::{InstalledTool, Tool, VERSION_FILE};
+use crate::error::{QuartoError, Result};
+
+/// GitHub API, used to find the latest TinyTeX release.
+const TINYTEX_RELEASES_URL: &str =
+    "https://api.github.com/repos/rstudio/tinytex-releases/releases/latest";
+
+/// Host of TinyTeX release archives.
+const TINYTEX_DOWNLOAD_URL: &str = "https://github.com/rstudio/tinytex-releases/releases/download";
+
+/// Latest Chrome for Testing versions, by channel.
+const CHROMIUM_VERSIONS_URL: &str =
+    "https://googlechromelabs.github.io/chrome-for-testing/last-known-good-versions.json";
+
+/// Host of Chrome for Testing archives.
+const CHROMIUM_DOWNLOAD_URL: &str = "https://storage.googleapis.com/chrome-for-testing-public";
+
+/// A release of a tool, ready to download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolRelease {
+    pub tool: Tool,
+    pub version: String,
+    /// Archive to download
+    pub url: String,
+}
+
+/// Formats of tool archives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// The format of an archive, from its file name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+}
+
+#[derive(Deserialize)]
+struct ChromiumVersions {
+    channels: ChromiumChannels,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ChromiumChannels {
+    stable: ChromiumChannel,
+}
+
+#[derive(Deserialize)]
+struct ChromiumChannel {
+    version: String,
+}
+
+/// Look up the latest release of `tool` for this platform.
+pub fn latest_release(tool: Tool, runtime: &dyn SystemRuntime) -> Result<ToolRelease> {
+    let (os, arch) = (runtime.os_name(), runtime.arch());
+    let unsupported = || {
+        QuartoError::other(format!(
+            "{} can't be installed on this platform ({} {})",
+            tool, os, arch
+        ))
+    };
+    match tool {
+        Tool::TinyTex => {
+            let release: GithubRelease = fetch_json(tool, TINYTEX_RELEASES_URL, runtime)?;
+            let asset = tinytex_asset(os, arch, &release.tag_name).ok_or_else(unsupported)?;
+            Ok(ToolRelease {
+                tool,
+                version: release.tag_name.trim_start_matches('v').to_string(),
+                url: format!("{}/{}/{}", TINYTEX_DOWNLOAD_URL, release.tag_name, asset),
+            })
+        }
+        Tool::Chromium => {
+            let platform = chromium_platform(os, arch).ok_or_else(unsupported)?;
+            let versions: ChromiumVersions = fetch_json(tool, CHROMIUM_VERSIONS_URL, runtime)?;
+            let version = versions.channels.stable.version;
+            Ok(ToolRelease {
+                tool,
+                url: format!(
+                    "{}/{}/{}/chrome-headless-shell-{}.zip",
+                    CHROMIUM_DOWNLOAD_URL, version, platform, platform
+                ),
+                version,
+            })
+        }
+    }
+}
+
+fn fetch_json<T: for<'de> Deserialize<'de>>(
+    tool: Tool,
+    url: &str,
+    runtime: &dyn SystemRuntime,
+) -> Result<T> {
+    let (body, _) = runtime
+        .fetch_url(url)
+        .map_err(|e| QuartoError::other(format!("Failed to look up the latest {}: {}", tool, e)))?;
+    serde_json::from_slice(&body).map_err(|e| {
+        QuartoError::other(format!(
+            "Unexpected response looking up the latest {}: {}",
+            tool, e
+        ))
+    })
+}
+
+/// Name of the TinyTeX archive of release `tag` for a platform.
+fn tinytex_asset(os: &str, arch: &str, tag: &str) -> Option<String> {
+    let extension = match (os, arch) {
+        ("windows", _) => "zip",
+        ("linux", "x86_64") => "tar.gz",
+        ("macos", _) => "tgz",
+        _ => return None,
+    };
+    Some(format!("TinyTeX-1-{}.{}", tag, extension))
+}
+
+/// Chrome for Testing name of a platform.
+fn chromium_platform(os: &str, arch: &str) -> Option<&'static str> {
+    match (os, arch) {
+        ("linux", "x86_64") => Some("linux64"),
+        ("macos", "aarch64") => Some("mac-arm64"),
+        ("macos", "x86_64") => Some("mac-x64"),
+        ("windows", "x86_64") => Some("win64"),
+        ("windows", "x86") => Some("win32"),
+        _ => None,
+    }
+}
+
+/// Download and install a release into the tool's directory.
+pub fn install(release: &ToolRelease, runtime: &dyn SystemRuntime) -> Result<InstalledTool> {
+    let format = ArchiveFormat::from_name(&release.url).ok_or_else(|| {
+        QuartoError::other(format!("Unsupported archive format: {}", release.url))
+    })?;
+    let (archive, _) = runtime
+        .fetch_url(&release.url)
+        .map_err(|e| QuartoError::other(format!("Failed to download {}: {}", release.tool, e)))?;
+    let dir = release.tool.install_dir(runtime)?;
+    install_archive(release.tool, &release.version, &archive, format, &dir)
+}
+
+/// Unpack a tool's archive into `dir`, replacing what was there.
+///
+/// The archive is unpacked next to `dir` first, so a failed install leaves
+/// the previous one in place.
+pub fn install_archive(
+    tool: Tool,
+    version: &str,
+    archive: &[u8],
+    format: ArchiveFormat,
+    dir: &Path,
+) -> Result<InstalledTool> {
+    let io_error =
+        |e: std::io::Error| QuartoError::other(format!("Failed to install {}: {}", tool, e));
+    let staging = dir.with_extension("partial");
+    if staging.exists() {
+        fs::remove_dir_all(&staging).map_err(io_error)?;
+    }
+    fs::create_dir_all(&staging).map_err(io_error)?;
+
+    let unpacked = match format {
+        ArchiveFormat::Zip => unpack_zip(archive, &staging),
+        ArchiveFormat::TarGz => unpack_tar_gz(archive, &staging),
+    };
+    if let Err(e) = unpacked {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(QuartoError::other(format!(
+            "Failed to unpack {}: {}",
+            tool, e
+        )));
+    }
+    fs::write(staging.join(VERSION_FILE), version).map_err(io_error)?;
+
+    if dir.exists() {
+        fs::remove_dir_all(dir).map_err(io_error)?;
+    }
+    fs::rename(&staging, dir).map_err(io_error)?;
+    Ok(InstalledTool {
+        tool,
+        version: version.to_string(),
+        dir: dir.to_path_buf(),
+    })
+}
+
+/// Remove an installed tool. Returns whether it was installed.
+pub fn uninstall(tool: Tool, runtime: &dyn SystemRuntime) -> Result<bool> {
+    let dir = tool.install_dir(runtime)?;
+    if !runtime.is_dir(&dir).unwrap_or(false) {
+        return Ok(false);
+    }
+    runtime
+        .dir_remove(&dir, true)
+        .map_err(|e| QuartoError::other(format!("Failed to uninstall {}: {}", tool, e)))?;
+    Ok(true)
+}
+
+/// Path of an archive entry without the archive's top-level directory, or
+/// `None` for the top-level directory itself and for paths leaving it.
+fn strip_top_dir(path: &Path) -> Option<PathBuf> {
+    let mut components = path
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir));
+    components.next()?;
+    let mut stripped = PathBuf::new();
+    for component in components {
+        match component {
+            Component::Normal(part) => stripped.push(part),
+            _ => return None,
+        }
+    }
+    (!stripped.as_os_str().is_empty()).then_some(stripped)
+}
+
+fn unpack_zip(archive: &[u8], dest: &Path) -> std::result::Result<(), String> {
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(archive))
+        .map_err(|e| format!("Invalid archive: {}", e))?;
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(|e| e.to_string())?;
+        let Some(relative) = entry.enclosed_name().as_deref().and_then(strip_top_dir) else {
+            continue;
+        };
+        let target = dest.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target).map_err(|e| e.to_string())?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        #[cfg(unix)]
+        if entry.is_symlink() {
+            let mut link = String::new();
+            entry.read_to_string(&mut link).map_err(|e| e.to_string())?;
+            std::os::unix::fs::symlink(link, &target).map_err(|e| e.to_string())?;
+            continue;
+        }
+        let mut content = Vec::new();
+        entry
+            .read_to_end(&mut content)
+            .map_err(|e| format!("Failed to read {}: {}", target.display(), e))?;
+        fs::write(&target, content).map_err(|e| e.to_string())?;
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&target, fs::Permissions::from_mode(mode))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+fn unpack_tar_gz(archive: &[u8], dest: &Path) -> std::result::Result<(), String> {
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+    let entries = tar
+        .entries()
+        .map_err(|e| format!("Invalid archive: {}", e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Invalid archive: {}", e))?;
+        let path = entry.path().map_err(|e| e.to_string())?;
+        let Some(relative) = strip_top_dir(&path) else {
+            continue;
+        };
+        let target = dest.join(relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        entry
+            .unpack(&target)
+            .map_err(|e| format!("Failed to unpack {}: {}", target.display(), e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn zip_archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (path, content) in files {
+            let options = zip::write::SimpleFileOptions::default().unix_permissions(0o755);
+            zip.start_file(*path, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn tar_gz_archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        let mut tar = tar::Builder::new(encoder);
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            tar.append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_release_assets() {
+        assert_eq!(
+            tinytex_asset("linux", "x86_64", "v2025.10").as_deref(),
+            Some("TinyTeX-1-v2025.10.tar.gz")
+        );
+        assert_eq!(
+            tinytex_asset("macos", "aarch64", "v2025.10").as_deref(),
+            Some("TinyTeX-1-v2025.10.tgz")
+        );
+        assert_eq!(tinytex_asset("linux", "aarch64", "v2025.10"), None);
+        assert_eq!(chromium_platform("macos", "aarch64"), Some("mac-arm64"));
+        assert_eq!(chromium_platform("linux", "aarch64"), None);
+        assert_eq!(
+            ArchiveFormat::from_name("TinyTeX-1-v2025.10.tgz"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(ArchiveFormat::from_name("TinyTeX.tar.xz"), None);
+    }
+
+    #[test]
+    fn test_strip_top_dir() {
+        assert_eq!(
+            strip_top_dir(Path::new("./.TinyTeX/bin/xelatex")),
+            Some(PathBuf::from("bin/xelatex"))
+        );
+        assert_eq!(strip_top_dir(Path::new(".TinyTeX/")), None);
+        assert_eq!(strip_top_dir(Path::new("top/../../etc/passwd")), None);
+    }
+
+    #[test]
+    fn test_install_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("tinytex");
+
+        let archive = tar_gz_archive(&[(".TinyTeX/bin/x86_64-linux/xelatex", "old")]);
+        install_archive(
+            Tool::TinyTex,
+            "2025.09",
+            &archive,
+            ArchiveFormat::TarGz,
+            &target,
+        )
+        .unwrap();
+        let archive = tar_gz_archive(&[
+            (".TinyTeX/bin/x86_64-linux/xelatex", "new"),
+            (".TinyTeX/texmf-dist/ls-R", ""),
+        ]);
+        let installed = install_archive(
+            Tool::TinyTex,
+            "2025.10",
+            &archive,
+            ArchiveFormat::TarGz,
+            &target,
+        )
+        .unwrap();
+
+        let xelatex = target.join("bin/x86_64-linux/xelatex");
+        assert_eq!(fs::read_to_string(&xelatex).unwrap(), "new");
+        assert!(target.join("texmf-dist/ls-R").is_file());
+        assert_eq!(installed.version, "2025.10");
+        assert_eq!(
+            fs::read_to_string(target.join(VERSION_FILE)).unwrap(),
+            "2025.10"
+        );
+        assert!(!dir.path().join("tinytex.partial").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&xelatex).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+
+        let chromium = dir.path().join("chromium");
+        let archive = zip_archive(&[(
+            "chrome-headless-shell-linux64/chrome-headless-shell",
+            "binary",
+        )]);
+        install_archive(
+            Tool::Chromium,
+            "131.0.6778.85",
+            &archive,
+            ArchiveFormat::Zip,
+            &chromium,
+        )
+        .unwrap();
+        assert!(chromium.join("chrome-headless-shell").is_file());
+
+        assert!(
+            install_archive(
+                Tool::Chromium,
+                "1",
+                b"not a zip",
+                ArchiveFormat::Zip,
+                &chromium
+            )
+            .is_err()
+        );
+        // A failed install keeps the previous one
+        assert!(chromium.join("chrome-headless-shell").is_file());
+    }
+}
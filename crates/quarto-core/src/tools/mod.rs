@@ -0,0 +1,175 @@
+/*
+ * tools/mod.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Tools Quarto installs for rendering.
+ */
+
+//! Tools Quarto installs for rendering.
+//!
+//! `quarto install` downloads [`Tool`]s that rendering needs but that are
+//! usually not on the system:
+//! - **TinyTeX**, a small TeX Live distribution, for PDF output
+//! - **Chromium** (the headless shell of Chrome for Testing), for
+//!   screenshots of HTML content
+//!
+//! Tools are installed into `quarto/tools/<tool>` of the user's data
+//! directory (`~/.local/share` on Linux, through [`SystemRuntime::xdg_dir`]),
+//! each with a `.version` file recording the version installed.
+//! [`BinaryDependencies::discover`](crate::BinaryDependencies::discover)
+//! falls back to the binaries of installed tools when a binary isn't given
+//! by its environment variable or found on `PATH`.
+//!
+//! Downloading and installing tools is native-only
+//! ([`install`](self::install)); finding installed tools works with any
+//! runtime.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod install;
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use quarto_system_runtime::{SystemRuntime, XdgDirKind};
+
+use crate::error::{QuartoError, Result};
+
+/// Directory of the installed tools, inside the user's data directory.
+pub const TOOLS_DIR: &str = "quarto/tools";
+
+/// File recording the installed version, inside a tool's directory.
+pub const VERSION_FILE: &str = ".version";
+
+/// A tool Quarto can install.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Tool {
+    TinyTex,
+    Chromium,
+}
+
+impl Tool {
+    /// All tools, in the order they're listed.
+    pub const ALL: [Tool; 2] = [Tool::TinyTex, Tool::Chromium];
+
+    /// Parse a tool name, ignoring case.
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|tool| tool.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Name used on the command line and for the tool's directory.
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::TinyTex => "tinytex",
+            Tool::Chromium => "chromium",
+        }
+    }
+
+    /// Name shown to users.
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Tool::TinyTex => "TinyTeX",
+            Tool::Chromium => "Chromium",
+        }
+    }
+
+    /// Directory the tool is installed into.
+    pub fn install_dir(self, runtime: &dyn SystemRuntime) -> Result<PathBuf> {
+        Ok(tools_dir(runtime)?.join(self.name()))
+    }
+
+    /// The tool's installation, if it is installed.
+    pub fn installed(self, runtime: &dyn SystemRuntime) -> Option<InstalledTool> {
+        let dir = self.install_dir(runtime).ok()?;
+        let version = runtime.file_read_string(&dir.join(VERSION_FILE)).ok()?;
+        Some(InstalledTool {
+            tool: self,
+            version: version.trim().to_string(),
+            dir,
+        })
+    }
+}
+
+impl fmt::Display for Tool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.display_name())
+    }
+}
+
+/// An installed tool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledTool {
+    pub tool: Tool,
+    pub version: String,
+    /// Directory the tool is installed in
+    pub dir: PathBuf,
+}
+
+impl InstalledTool {
+    /// Path of one of the tool's binaries.
+    ///
+    /// Binaries are at the root of the installation (Chromium) or in a
+    /// platform directory of `bin` (TinyTeX's `bin/x86_64-linux`).
+    pub fn binary(&self, name: &str, runtime: &dyn SystemRuntime) -> Option<PathBuf> {
+        let names = [name.to_string(), format!("{}.exe", name)];
+        let mut dirs = vec![self.dir.clone()];
+        dirs.extend(
+            runtime
+                .dir_list(&self.dir.join("bin"))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|dir| runtime.is_dir(dir).unwrap_or(false)),
+        );
+        dirs.iter()
+            .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+            .find(|path| runtime.is_file(path).unwrap_or(false))
+    }
+}
+
+/// Directory of the installed tools.
+pub fn tools_dir(runtime: &dyn SystemRuntime) -> Result<PathBuf> {
+    runtime
+        .xdg_dir(XdgDirKind::Data, Some(Path::new(TOOLS_DIR)))
+        .map_err(|e| QuartoError::other(format!("Failed to find the data directory: {}", e)))
+}
+
+/// Path of a binary of an installed tool.
+pub fn installed_binary(tool: Tool, name: &str, runtime: &dyn SystemRuntime) -> Option<PathBuf> {
+    tool.installed(runtime)?.binary(name, runtime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_system_runtime::NativeRuntime;
+
+    #[test]
+    fn test_parse_tool() {
+        assert_eq!(Tool::parse("tinytex"), Some(Tool::TinyTex));
+        assert_eq!(Tool::parse("TinyTeX"), Some(Tool::TinyTex));
+        assert_eq!(Tool::parse("chromium"), Some(Tool::Chromium));
+        assert_eq!(Tool::parse("pandoc"), None);
+    }
+
+    #[test]
+    fn test_installed_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let runtime = NativeRuntime::new();
+        let tinytex = InstalledTool {
+            tool: Tool::TinyTex,
+            version: "2025.10".to_string(),
+            dir: dir.path().join("tinytex"),
+        };
+        let bin = tinytex.dir.join("bin").join("x86_64-linux");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(bin.join("xelatex"), "").unwrap();
+        std::fs::write(tinytex.dir.join("bin").join("README"), "").unwrap();
+
+        assert_eq!(
+            tinytex.binary("xelatex", &runtime),
+            Some(bin.join("xelatex"))
+        );
+        assert_eq!(tinytex.binary("lualatex", &runtime), None);
+    }
+}
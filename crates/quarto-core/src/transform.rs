@@ -130,13 +130,18 @@ impl TransformPipeline {
     ///
     /// # Errors
     ///
-    /// Returns the first error encountered. Execution stops on error.
+    /// Returns the first error encountered. Execution stops on error, and
+    /// with [`QuartoError::Cancelled`](crate::QuartoError::Cancelled) when
+    /// the render is cancelled between transforms.
     pub fn execute(
         &self,
         ast: &mut quarto_pandoc_types::pandoc::Pandoc,
         ctx: &mut RenderContext,
     ) -> Result<()> {
         for transform in &self.transforms {
+            if ctx.cancellation.is_cancelled() {
+                return Err(crate::error::QuartoError::Cancelled);
+            }
             tracing::debug!(transform = transform.name(), "Running transform");
             transform.transform(ast, ctx)?;
         }
@@ -225,6 +230,24 @@ mod tests {
         }
     }
 
+    /// A transform that cancels the render.
+    struct CancellingTransform;
+
+    impl AstTransform for CancellingTransform {
+        fn name(&self) -> &str {
+            "cancelling"
+        }
+
+        fn transform(
+            &self,
+            _ast: &mut quarto_pandoc_types::pandoc::Pandoc,
+            ctx: &mut RenderContext,
+        ) -> Result<()> {
+            ctx.cancellation.cancel();
+            Ok(())
+        }
+    }
+
     #[test]
     fn test_empty_pipeline() {
         let pipeline = TransformPipeline::new();
@@ -367,6 +390,34 @@ mod tests {
         assert_eq!(*order.lock().unwrap(), vec![1]);
     }
 
+    #[test]
+    fn test_cancellation_stops_execution() {
+        let mut pipeline = TransformPipeline::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        pipeline.push(Box::new(CancellingTransform));
+
+        pipeline.push(Box::new(CountingTransform {
+            name: "after-cancel",
+            counter: counter.clone(),
+            my_order: 2,
+            order_tracker: order.clone(),
+        }));
+
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+        let mut ast = make_empty_ast();
+
+        let result = pipeline.execute(&mut ast, &mut ctx);
+
+        assert!(matches!(result, Err(crate::error::QuartoError::Cancelled)));
+        assert_eq!(counter.load(Ordering::SeqCst), 0);
+    }
+
     #[test]
     fn test_transform_names() {
         let mut pipeline = TransformPipeline::new();
@@ -31,14 +31,18 @@
 //! The transform converts this to a CustomNode with:
 //! - `type_name`: "Callout"
 //! - `slots`:
-//!   - "title": Inlines from the first Header (if present)
+//!   - "title": Inlines from the first Header (if present), or the `title`
+//!     attribute
 //!   - "content": Blocks (remaining blocks after title extraction)
-//! - `plain_data`: `{"type": "warning", "appearance": "default", ...}`
+//! - `plain_data`: `{"type": "warning", "appearance": "default", ...}`, where
+//!   `collapse` is `true`/`false` for collapsible callouts (from the
+//!   `collapse` attribute) and `null` otherwise
 //! - `attr`: Original Div attributes
 
 use quarto_pandoc_types::attr::Attr;
 use quarto_pandoc_types::block::{Block, Div};
 use quarto_pandoc_types::custom::{CustomNode, Slot};
+use quarto_pandoc_types::inline::{Inline, Str};
 use quarto_pandoc_types::pandoc::Pandoc;
 use serde_json::json;
 
@@ -195,9 +199,21 @@ fn convert_div_to_callout(div: &mut Div, callout_type: &str) -> CustomNode {
         }
     }
 
+    // Without a heading, the `title` attribute gives the title
+    if title_inlines.is_empty()
+        && let Some(title) = extract_attr_value(&div.attr, "title")
+    {
+        title_inlines = vec![Inline::Str(Str {
+            text: title,
+            source_info: div.source_info.clone(),
+        })];
+    }
+
     // Extract additional attributes from the div
     let appearance = extract_attr_value(&div.attr, "appearance").unwrap_or("default".to_string());
-    let collapse = extract_attr_value(&div.attr, "collapse").is_some_and(|v| v == "true");
+    // `collapse="true"` starts collapsed, `collapse="false"` starts expanded;
+    // without the attribute the callout isn't collapsible (null)
+    let collapse = extract_attr_value(&div.attr, "collapse").map(|v| v == "true");
     let icon = extract_attr_value(&div.attr, "icon").is_none_or(|v| v != "false");
 
     // Build the plain_data JSON
@@ -455,6 +471,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_convert_callout_title_and_collapse_attributes() {
+        let mut attr = callout_attr("note");
+        attr.2.insert("title".to_string(), "Read this".to_string());
+        attr.2.insert("collapse".to_string(), "false".to_string());
+        let mut div = Div {
+            attr,
+            content: vec![],
+            source_info: dummy_source_info(),
+            attr_source: AttrSourceInfo::empty(),
+        };
+
+        let custom = convert_div_to_callout(&mut div, "note");
+
+        match custom.get_slot("title") {
+            Some(Slot::Inlines(inlines)) => match &inlines[0] {
+                Inline::Str(s) => assert_eq!(s.text, "Read this"),
+                _ => panic!("Expected Str title"),
+            },
+            _ => panic!("Expected title slot with Inlines"),
+        }
+        // Collapsible, but starts expanded
+        assert_eq!(custom.plain_data["collapse"], false);
+
+        // Without the attribute, the callout isn't collapsible
+        let mut div = Div {
+            attr: callout_attr("note"),
+            content: vec![],
+            source_info: dummy_source_info(),
+            attr_source: AttrSourceInfo::empty(),
+        };
+        let custom = convert_div_to_callout(&mut div, "note");
+        assert!(custom.plain_data["collapse"].is_null());
+    }
+
     #[test]
     fn test_transform_name() {
         let transform = CalloutTransform::new();
@@ -25,8 +25,8 @@
 //! The transform produces this Div structure (which renders to the expected HTML):
 //!
 //! ```text
-//! Div.callout.callout-{type}
-//!   Div.callout-header
+//! Div.callout.callout-style-{default|simple}.callout-{type}.callout-titled
+//!   Div.callout-header.d-flex.align-content-center
 //!     Div.callout-icon-container
 //!       Plain[RawInline(html, "<i class=\"callout-icon\"></i>")]
 //!     Div.callout-title-container.flex-fill
@@ -34,6 +34,17 @@
 //!   Div.callout-body-container.callout-body
 //!     [content blocks...]
 //! ```
+//!
+//! This is the markup of Quarto 1.x, so the callout rules in quarto-sass
+//! (`_bootstrap-rules.scss`) style it. Appearances map to styles:
+//! `default` to `callout-style-default`, `simple` to `callout-style-simple`,
+//! and `minimal` to `callout-style-simple` with `no-icon`.
+//!
+//! Collapsible callouts (`collapse="true"` or `"false"`) get a Bootstrap
+//! collapse toggle: the header gets `data-bs-toggle="collapse"` and a
+//! `callout-btn-toggle` button, and the body is wrapped in
+//! `Div#callout-N.callout-N-contents.callout-collapse.collapse` (plus `show`
+//! when it starts expanded).
 
 use hashlink::LinkedHashMap;
 use quarto_pandoc_types::attr::{Attr, AttrSourceInfo};
@@ -73,64 +84,68 @@ impl AstTransform for CalloutResolveTransform {
     }
 
     fn transform(&self, ast: &mut Pandoc, _ctx: &mut RenderContext) -> Result<()> {
-        resolve_blocks(&mut ast.blocks);
+        let mut count = 0;
+        resolve_blocks(&mut ast.blocks, &mut count);
         Ok(())
     }
 }
 
 /// Resolve CustomNodes in a vector of blocks.
-fn resolve_blocks(blocks: &mut Vec<Block>) {
+///
+/// `count` numbers the callouts in document order; collapsible callouts use
+/// it for the ids that tie their header to their body.
+fn resolve_blocks(blocks: &mut Vec<Block>, count: &mut usize) {
     for block in blocks.iter_mut() {
-        resolve_block(block);
+        resolve_block(block, count);
     }
 }
 
 /// Resolve a single block, potentially converting CustomNode to Div.
-fn resolve_block(block: &mut Block) {
+fn resolve_block(block: &mut Block, count: &mut usize) {
     // First, recursively resolve any nested blocks
     match block {
         Block::BlockQuote(bq) => {
-            resolve_blocks(&mut bq.content);
+            resolve_blocks(&mut bq.content, count);
         }
         Block::OrderedList(ol) => {
             for item in &mut ol.content {
-                resolve_blocks(item);
+                resolve_blocks(item, count);
             }
         }
         Block::BulletList(bl) => {
             for item in &mut bl.content {
-                resolve_blocks(item);
+                resolve_blocks(item, count);
             }
         }
         Block::DefinitionList(dl) => {
             for (_term, defs) in &mut dl.content {
                 for def in defs {
-                    resolve_blocks(def);
+                    resolve_blocks(def, count);
                 }
             }
         }
         Block::Figure(fig) => {
-            resolve_blocks(&mut fig.content);
+            resolve_blocks(&mut fig.content, count);
         }
         Block::Div(div) => {
-            resolve_blocks(&mut div.content);
+            resolve_blocks(&mut div.content, count);
         }
         Block::Table(table) => {
             for body in &mut table.bodies {
                 for row in &mut body.body {
                     for cell in &mut row.cells {
-                        resolve_blocks(&mut cell.content);
+                        resolve_blocks(&mut cell.content, count);
                     }
                 }
             }
             for row in &mut table.head.rows {
                 for cell in &mut row.cells {
-                    resolve_blocks(&mut cell.content);
+                    resolve_blocks(&mut cell.content, count);
                 }
             }
             for row in &mut table.foot.rows {
                 for cell in &mut row.cells {
-                    resolve_blocks(&mut cell.content);
+                    resolve_blocks(&mut cell.content, count);
                 }
             }
         }
@@ -138,15 +153,16 @@ fn resolve_block(block: &mut Block) {
             // First resolve any nested blocks in slots
             for (_name, slot) in &mut custom.slots {
                 match slot {
-                    Slot::Block(b) => resolve_block(b),
-                    Slot::Blocks(bs) => resolve_blocks(bs),
+                    Slot::Block(b) => resolve_block(b, count),
+                    Slot::Blocks(bs) => resolve_blocks(bs, count),
                     _ => {}
                 }
             }
 
             // Then check if this is a Callout that should be resolved
             if custom.type_name == "Callout" {
-                let resolved_div = resolve_callout(custom);
+                *count += 1;
+                let resolved_div = resolve_callout(custom, *count);
                 *block = Block::Div(resolved_div);
             }
         }
@@ -155,58 +171,74 @@ fn resolve_block(block: &mut Block) {
     }
 }
 
+/// Callout attributes that are consumed by the transform rather than
+/// written to the output.
+const CALLOUT_ATTRIBUTES: &[&str] = &["appearance", "collapse", "icon", "title"];
+
 /// Resolve a Callout CustomNode to a Div with the expected HTML structure.
-fn resolve_callout(custom: &mut CustomNode) -> Div {
+///
+/// `index` is the callout's number in the document, used for the ids of
+/// collapsible callouts.
+fn resolve_callout(custom: &mut CustomNode, index: usize) -> Div {
     // Extract callout properties from plain_data
-    let callout_type = extract_string(&custom.plain_data, "type").unwrap_or("note");
+    let callout_type = extract_string(&custom.plain_data, "type")
+        .unwrap_or("note")
+        .to_string();
     let appearance = extract_string(&custom.plain_data, "appearance").unwrap_or("default");
-    let collapse = extract_bool(&custom.plain_data, "collapse").unwrap_or(false);
-    let icon = extract_bool(&custom.plain_data, "icon").unwrap_or(true);
+    // Some(collapsed) for collapsible callouts
+    let collapse = extract_bool(&custom.plain_data, "collapse");
+    // The minimal appearance is the simple style without an icon
+    let icon = extract_bool(&custom.plain_data, "icon").unwrap_or(true) && appearance != "minimal";
+    let style = if appearance == "default" {
+        "default"
+    } else {
+        "simple"
+    };
 
     let source_info = custom.source_info.clone();
 
-    // Build class list for outer div
-    let mut classes = vec!["callout".to_string(), format!("callout-{}", callout_type)];
-    if appearance != "default" {
-        classes.push(format!("callout-appearance-{}", appearance));
+    // Extract title and content from slots
+    let title_inlines = extract_title_inlines(custom, &callout_type);
+    let content_blocks = extract_content_blocks(custom);
+
+    // Build class list for outer div (Quarto 1.x markup, styled by the
+    // callout rules in quarto-sass)
+    let mut classes = vec![
+        "callout".to_string(),
+        format!("callout-style-{}", style),
+        format!("callout-{}", callout_type),
+    ];
+    if !icon {
+        classes.push("no-icon".to_string());
     }
-    if collapse {
-        classes.push("callout-collapse".to_string());
+    classes.push("callout-titled".to_string());
+    if content_blocks.is_empty() {
+        classes.push("callout-empty-content".to_string());
     }
 
-    // Include original non-callout classes from attr
+    // Include original non-callout classes and attributes from attr
     let (orig_id, orig_classes, orig_attrs) = &custom.attr;
     for cls in orig_classes {
         if !cls.starts_with("callout") {
             classes.push(cls.clone());
         }
     }
+    let mut attrs = orig_attrs.clone();
+    attrs.retain(|key, _| !CALLOUT_ATTRIBUTES.contains(&key.as_str()));
 
     // Build outer div attr
-    let outer_attr: Attr = (orig_id.clone(), classes, orig_attrs.clone());
-
-    // Extract title and content from slots
-    let title_inlines = extract_title_inlines(custom, callout_type);
-    let content_blocks = extract_content_blocks(custom);
+    let outer_attr: Attr = (orig_id.clone(), classes, attrs);
 
     // Build the inner structure
     let mut header_content = Vec::new();
 
     // Icon container (if enabled)
     if icon {
-        header_content.push(Block::Div(Div {
-            attr: make_attr(&["callout-icon-container"]),
-            content: vec![Block::Plain(Plain {
-                content: vec![Inline::RawInline(RawInline {
-                    format: "html".to_string(),
-                    text: "<i class=\"callout-icon\"></i>".to_string(),
-                    source_info: source_info.clone(),
-                })],
-                source_info: source_info.clone(),
-            })],
-            source_info: source_info.clone(),
-            attr_source: AttrSourceInfo::empty(),
-        }));
+        header_content.push(raw_html_div(
+            &["callout-icon-container"],
+            "<i class=\"callout-icon\"></i>",
+            &source_info,
+        ));
     }
 
     // Title container
@@ -220,22 +252,72 @@ fn resolve_callout(custom: &mut CustomNode) -> Div {
         attr_source: AttrSourceInfo::empty(),
     }));
 
-    // Header div
+    // Toggle button (collapsible callouts)
+    if collapse.is_some() {
+        header_content.push(raw_html_div(
+            &[
+                "callout-btn-toggle",
+                "d-inline-block",
+                "border-0",
+                "py-1",
+                "ps-1",
+                "pe-0",
+                "float-end",
+            ],
+            "<i class=\"callout-toggle\"></i>",
+            &source_info,
+        ));
+    }
+
+    // Header div; a collapsible header toggles the body via Bootstrap
+    let body_id = format!("callout-{}", index);
+    let mut header_attr = make_attr(&["callout-header", "d-flex", "align-content-center"]);
+    if let Some(collapsed) = collapse {
+        if collapsed {
+            header_attr.1.push("collapsed".to_string());
+        }
+        let header_attrs = &mut header_attr.2;
+        header_attrs.insert("data-bs-toggle".to_string(), "collapse".to_string());
+        header_attrs.insert(
+            "data-bs-target".to_string(),
+            format!(".{}-contents", body_id),
+        );
+        header_attrs.insert("aria-controls".to_string(), body_id.clone());
+        header_attrs.insert("aria-expanded".to_string(), (!collapsed).to_string());
+        header_attrs.insert("aria-label".to_string(), "Toggle callout".to_string());
+    }
     let header_div = Block::Div(Div {
-        attr: make_attr(&["callout-header"]),
+        attr: header_attr,
         content: header_content,
         source_info: source_info.clone(),
         attr_source: AttrSourceInfo::empty(),
     });
 
     // Body div
-    let body_div = Block::Div(Div {
+    let mut body_div = Block::Div(Div {
         attr: make_attr(&["callout-body-container", "callout-body"]),
         content: content_blocks,
         source_info: source_info.clone(),
         attr_source: AttrSourceInfo::empty(),
     });
 
+    // A collapsible body is wrapped in the Bootstrap collapse target
+    if let Some(collapsed) = collapse {
+        let contents_class = format!("{}-contents", body_id);
+        let mut collapse_attr =
+            make_attr(&[contents_class.as_str(), "callout-collapse", "collapse"]);
+        collapse_attr.0 = body_id;
+        if !collapsed {
+            collapse_attr.1.push("show".to_string());
+        }
+        body_div = Block::Div(Div {
+            attr: collapse_attr,
+            content: vec![body_div],
+            source_info: source_info.clone(),
+            attr_source: AttrSourceInfo::empty(),
+        });
+    }
+
     // Outer callout div
     Div {
         attr: outer_attr,
@@ -245,6 +327,23 @@ fn resolve_callout(custom: &mut CustomNode) -> Div {
     }
 }
 
+/// A Div with the given classes holding a single raw HTML snippet.
+fn raw_html_div(classes: &[&str], html: &str, source_info: &SourceInfo) -> Block {
+    Block::Div(Div {
+        attr: make_attr(classes),
+        content: vec![Block::Plain(Plain {
+            content: vec![Inline::RawInline(RawInline {
+                format: "html".to_string(),
+                text: html.to_string(),
+                source_info: source_info.clone(),
+            })],
+            source_info: source_info.clone(),
+        })],
+        source_info: source_info.clone(),
+        attr_source: AttrSourceInfo::empty(),
+    })
+}
+
 /// Extract title inlines from the CustomNode, with fallback to default.
 fn extract_title_inlines(custom: &CustomNode, callout_type: &str) -> Vec<Inline> {
     if let Some(title_slot) = custom.get_slot("title") {
@@ -469,7 +568,7 @@ mod tests {
         custom.plain_data = json!({"type": "note"});
         // No title slot - should use default
 
-        let resolved = resolve_callout(&mut custom);
+        let resolved = resolve_callout(&mut custom, 1);
 
         // Find the title container and check it has "Note"
         let header = &resolved.content[0];
@@ -496,7 +595,7 @@ mod tests {
         let mut custom = CustomNode::new("Callout", empty_attr(), dummy_source_info());
         custom.plain_data = json!({"type": "warning", "icon": false});
 
-        let resolved = resolve_callout(&mut custom);
+        let resolved = resolve_callout(&mut custom, 1);
 
         // Header should only have title container, no icon
         if let Block::Div(header_div) = &resolved.content[0] {
@@ -509,6 +608,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolve_callout_style_classes() {
+        let mut custom = CustomNode::new("Callout", empty_attr(), dummy_source_info());
+        custom.plain_data = json!({"type": "tip", "appearance": "minimal"});
+
+        let resolved = resolve_callout(&mut custom, 1);
+        let (_, classes, _) = &resolved.attr;
+        assert_eq!(
+            classes,
+            &vec![
+                "callout",
+                "callout-style-simple",
+                "callout-tip",
+                "no-icon",
+                "callout-titled",
+                "callout-empty-content",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_collapsible_callout() {
+        let mut attr = empty_attr();
+        attr.2.insert("collapse".to_string(), "true".to_string());
+        attr.2.insert("data-custom".to_string(), "kept".to_string());
+        let mut custom = CustomNode::new("Callout", attr, dummy_source_info());
+        custom.plain_data = json!({"type": "note", "collapse": true});
+
+        let resolved = resolve_callout(&mut custom, 3);
+
+        // Callout attributes aren't written to the output
+        assert!(!resolved.attr.2.contains_key("collapse"));
+        assert!(resolved.attr.2.contains_key("data-custom"));
+
+        let Block::Div(header) = &resolved.content[0] else {
+            panic!("Expected header Div");
+        };
+        assert!(header.attr.1.contains(&"collapsed".to_string()));
+        assert_eq!(header.attr.2["data-bs-toggle"], "collapse");
+        assert_eq!(header.attr.2["data-bs-target"], ".callout-3-contents");
+        assert_eq!(header.attr.2["aria-expanded"], "false");
+        // Icon, title and toggle button
+        assert_eq!(header.content.len(), 3);
+
+        let Block::Div(collapse) = &resolved.content[1] else {
+            panic!("Expected collapse Div");
+        };
+        assert_eq!(collapse.attr.0, "callout-3");
+        assert_eq!(
+            collapse.attr.1,
+            vec!["callout-3-contents", "callout-collapse", "collapse"]
+        );
+        let Block::Div(body) = &collapse.content[0] else {
+            panic!("Expected body Div");
+        };
+        assert!(body.attr.1.contains(&"callout-body".to_string()));
+    }
+
     #[test]
     fn test_resolve_nested_callout() {
         // Callout inside a blockquote
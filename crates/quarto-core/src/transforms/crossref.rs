@@ -0,0 +1,1095 @@
+/*
+ * crossref.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Transform that resolves cross-references.
+ */
+
+//! Cross-reference transform.
+//!
+//! Quarto cross-references are citations whose key starts with a known type
+//! prefix: `@fig-plot`, `@tbl-results`, `@sec-intro`, `@eq-energy`,
+//! `@thm-main`, .... The transform works in two passes:
+//!
+//! 1. **Index**: Find labeled targets in document order and number them per
+//!    type. Figures and tables get their number in the caption
+//!    ("Figure 1: ..."), equations get a `\tag{1}`, and theorem-like Divs get
+//!    a "Theorem 1" title. Sections take their number from the `number`
+//!    attribute set by [`NumberSectionsTransform`](super::NumberSectionsTransform).
+//! 2. **Resolve**: Replace each crossref citation with a link to its target,
+//!    e.g. `@fig-plot` becomes "Figure 1" linking to `#fig-plot`. With
+//!    `[-@fig-plot]` the link shows just the number.
+//!
+//! Citations that mix crossrefs with other keys are left for citation
+//! processing. A reference to a label that doesn't exist is rendered as
+//! `?@label` in bold, and reported as a warning at the reference.
+//!
+//! ## Labels
+//!
+//! | Prefix | Target |
+//! |--------|--------|
+//! | `fig-` | Figure, or Div with the label |
+//! | `tbl-` | Table, or Div with the label |
+//! | `lst-` | Code block |
+//! | `sec-` | Header or section Div |
+//! | `eq-`  | Display math with an attribute (`$$ ... $$ {#eq-label}`) |
+//! | `thm-`, `lem-`, `cor-`, `prp-`, `cnj-`, `def-`, `exm-`, `exr-` | Div |
+//!
+//! ## Configuration
+//!
+//! - `lang`: Selects the built-in type names (e.g. `de` gives "Abbildung");
+//!   unknown languages fall back to English
+//! - `crossref.<type>-title`: Name used in captions and theorem titles
+//!   (e.g. `fig-title: Fig.`)
+//! - `crossref.<type>-prefix`: Name used in references (defaults to the title)
+//! - `crossref.title-delim`: Delimiter after the number in captions
+//!   (default: `:`)
+//!
+//! A section without a number (no `number-sections`) is referenced by its
+//! heading text.
+//!
+//! ## Books
+//!
+//! In a book chapter, numbers are prefixed with the chapter number
+//! ("Figure 2.1"). When the render options carry [`ProjectCrossrefs`], each
+//! chapter publishes its targets there, and references to labels defined in
+//! another (already rendered) chapter link to that chapter's page.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use hashlink::LinkedHashMap;
+use pampa::filters::native::{Filter, Visit, walk_blocks};
+use quarto_analysis::AnalysisContext;
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_pandoc_types::attr::{AttrSourceInfo, TargetSourceInfo};
+use quarto_pandoc_types::block::{Block, CodeBlock, Div, Figure, Header, Plain};
+use quarto_pandoc_types::caption::Caption;
+use quarto_pandoc_types::inline::{
+    CitationMode, Cite, Inline, Link, Math, Space, Span, Str, Strong,
+};
+use quarto_pandoc_types::pandoc::Pandoc;
+use quarto_pandoc_types::table::Table;
+use quarto_pandoc_types::{Blocks, Inlines};
+use quarto_source_map::SourceInfo;
+
+use crate::Result;
+use crate::book::Book;
+use crate::render::RenderContext;
+use crate::transform::AstTransform;
+
+/// The type of a cross-reference target, given by its label prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CrossrefKind {
+    Figure,
+    Table,
+    Listing,
+    Section,
+    Equation,
+    Theorem,
+    Lemma,
+    Corollary,
+    Proposition,
+    Conjecture,
+    Definition,
+    Example,
+    Exercise,
+}
+
+impl CrossrefKind {
+    /// The kind for a label such as `fig-plot`, if its prefix is a known type.
+    pub fn from_label(label: &str) -> Option<Self> {
+        let (prefix, rest) = label.split_once('-')?;
+        if rest.is_empty() {
+            return None;
+        }
+        Some(match prefix {
+            "fig" => Self::Figure,
+            "tbl" => Self::Table,
+            "lst" => Self::Listing,
+            "sec" => Self::Section,
+            "eq" => Self::Equation,
+            "thm" => Self::Theorem,
+            "lem" => Self::Lemma,
+            "cor" => Self::Corollary,
+            "prp" => Self::Proposition,
+            "cnj" => Self::Conjecture,
+            "def" => Self::Definition,
+            "exm" => Self::Example,
+            "exr" => Self::Exercise,
+            _ => return None,
+        })
+    }
+
+    /// The label prefix for this kind (e.g. `fig`).
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Figure => "fig",
+            Self::Table => "tbl",
+            Self::Listing => "lst",
+            Self::Section => "sec",
+            Self::Equation => "eq",
+            Self::Theorem => "thm",
+            Self::Lemma => "lem",
+            Self::Corollary => "cor",
+            Self::Proposition => "prp",
+            Self::Conjecture => "cnj",
+            Self::Definition => "def",
+            Self::Example => "exm",
+            Self::Exercise => "exr",
+        }
+    }
+
+    /// Whether targets of this kind are theorem-like Divs.
+    pub fn is_theorem(self) -> bool {
+        matches!(
+            self,
+            Self::Theorem
+                | Self::Lemma
+                | Self::Corollary
+                | Self::Proposition
+                | Self::Conjecture
+                | Self::Definition
+                | Self::Example
+                | Self::Exercise
+        )
+    }
+
+    /// The name of this kind in `lang`, falling back to English.
+    fn localized_title(self, lang: &str) -> &'static str {
+        // Only the primary subtag matters: "de-CH" uses "de"
+        let lang = lang.split(['-', '_']).next().unwrap_or("en");
+        let titles = TITLES
+            .iter()
+            .find(|(l, _)| l.eq_ignore_ascii_case(lang))
+            .map_or(&TITLES[0].1, |(_, titles)| titles);
+        titles[self as usize]
+    }
+}
+
+/// Built-in type names per language, in `CrossrefKind` order.
+const TITLES: &[(&str, [&str; 13])] = &[
+    (
+        "en",
+        [
+            "Figure",
+            "Table",
+            "Listing",
+            "Section",
+            "Equation",
+            "Theorem",
+            "Lemma",
+            "Corollary",
+            "Proposition",
+            "Conjecture",
+            "Definition",
+            "Example",
+            "Exercise",
+        ],
+    ),
+    (
+        "de",
+        [
+            "Abbildung",
+            "Tabelle",
+            "Listing",
+            "Abschnitt",
+            "Gleichung",
+            "Theorem",
+            "Lemma",
+            "Korollar",
+            "Proposition",
+            "Vermutung",
+            "Definition",
+            "Beispiel",
+            "Übung",
+        ],
+    ),
+    (
+        "fr",
+        [
+            "Figure",
+            "Table",
+            "Listing",
+            "Section",
+            "Équation",
+            "Théorème",
+            "Lemme",
+            "Corollaire",
+            "Proposition",
+            "Conjecture",
+            "Définition",
+            "Exemple",
+            "Exercice",
+        ],
+    ),
+    (
+        "es",
+        [
+            "Figura",
+            "Tabla",
+            "Listado",
+            "Sección",
+            "Ecuación",
+            "Teorema",
+            "Lema",
+            "Corolario",
+            "Proposición",
+            "Conjetura",
+            "Definición",
+            "Ejemplo",
+            "Ejercicio",
+        ],
+    ),
+    (
+        "pt",
+        [
+            "Figura",
+            "Tabela",
+            "Listagem",
+            "Seção",
+            "Equação",
+            "Teorema",
+            "Lema",
+            "Corolário",
+            "Proposição",
+            "Conjectura",
+            "Definição",
+            "Exemplo",
+            "Exercício",
+        ],
+    ),
+];
+
+/// A labeled cross-reference target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossrefEntry {
+    pub kind: CrossrefKind,
+    /// The target's number, or `None` for an unnumbered section
+    pub number: Option<String>,
+    /// Heading text, for sections
+    pub title: Inlines,
+    /// Where the target is defined
+    pub source_info: SourceInfo,
+}
+
+/// Labeled targets of a document, by label.
+#[derive(Debug, Clone, Default)]
+pub struct CrossrefIndex {
+    entries: HashMap<String, CrossrefEntry>,
+}
+
+impl CrossrefIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The target with the given label.
+    pub fn get(&self, label: &str) -> Option<&CrossrefEntry> {
+        self.entries.get(label)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All targets, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &CrossrefEntry)> {
+        self.entries.iter()
+    }
+}
+
+/// Crossref targets shared by the documents of a project.
+///
+/// Cloning shares the same store, so one instance can be handed to every
+/// document render of a project.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectCrossrefs {
+    /// Target and the project-relative output path of its page, by label
+    entries: Arc<Mutex<HashMap<String, (String, CrossrefEntry)>>>,
+}
+
+impl ProjectCrossrefs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the targets of the page at `href` (project-relative).
+    pub fn publish(&self, href: &str, index: &CrossrefIndex) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        for (label, entry) in index.iter() {
+            entries.insert(label.clone(), (href.to_string(), entry.clone()));
+        }
+    }
+
+    /// The target with the given label and the output path of its page.
+    pub fn get(&self, label: &str) -> Option<(String, CrossrefEntry)> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.get(label).cloned()
+    }
+}
+
+/// Crossref names and delimiters, from `lang` and `crossref` options.
+struct CrossrefOptions {
+    lang: String,
+    overrides: Option<serde_json::Value>,
+    title_delim: String,
+    /// Book chapter number prefixed to target numbers
+    chapter: Option<String>,
+}
+
+impl CrossrefOptions {
+    fn from_context(ctx: &RenderContext) -> Self {
+        let lang = ctx
+            .format_metadata("lang")
+            .and_then(|v| v.as_str())
+            .unwrap_or("en")
+            .to_string();
+        let overrides = ctx.format_metadata("crossref").cloned();
+        let title_delim = overrides
+            .as_ref()
+            .and_then(|v| v.get("title-delim"))
+            .and_then(|v| v.as_str())
+            .unwrap_or(":")
+            .to_string();
+        let chapter = Book::from_project(ctx.project).and_then(|book| {
+            book.chapter(&ctx.document.input)
+                .and_then(|(_, chapter)| chapter.number.clone())
+        });
+        Self {
+            lang,
+            overrides,
+            title_delim,
+            chapter,
+        }
+    }
+
+    fn option(&self, kind: CrossrefKind, name: &str) -> Option<String> {
+        self.overrides
+            .as_ref()?
+            .get(format!("{}-{}", kind.key(), name))?
+            .as_str()
+            .map(String::from)
+    }
+
+    /// Name used in captions and theorem titles.
+    fn title(&self, kind: CrossrefKind) -> String {
+        self.option(kind, "title")
+            .unwrap_or_else(|| kind.localized_title(&self.lang).to_string())
+    }
+
+    /// Name used in references.
+    fn prefix(&self, kind: CrossrefKind) -> String {
+        self.option(kind, "prefix")
+            .unwrap_or_else(|| self.title(kind))
+    }
+}
+
+/// Transform that numbers labeled targets and resolves references to them.
+///
+/// Runs after [`SectionizeTransform`](super::SectionizeTransform), so
+/// section ids and numbers are on the section Divs the links point to.
+pub struct CrossrefTransform;
+
+impl CrossrefTransform {
+    /// Create a new cross-reference transform.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CrossrefTransform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AstTransform for CrossrefTransform {
+    fn name(&self) -> &str {
+        "crossref"
+    }
+
+    fn transform(&self, ast: &mut Pandoc, ctx: &mut RenderContext) -> Result<()> {
+        let options = CrossrefOptions::from_context(ctx);
+
+        let mut indexer = Indexer {
+            options: &options,
+            index: CrossrefIndex::new(),
+            counters: HashMap::new(),
+            diagnostics: Vec::new(),
+        };
+        // Neither pass stops early
+        let _ = walk_blocks(&mut indexer, &mut ast.blocks);
+        let Indexer {
+            index, diagnostics, ..
+        } = indexer;
+
+        let page = project_relative_output(ctx);
+        let shared = ctx.options.crossrefs.clone();
+        if let Some(shared) = &shared {
+            shared.publish(&page, &index);
+        }
+
+        let mut resolver = Resolver {
+            options: &options,
+            index: &index,
+            shared: shared.as_ref(),
+            root: "../".repeat(page.matches('/').count()),
+            diagnostics,
+        };
+        let _ = walk_blocks(&mut resolver, &mut ast.blocks);
+
+        for diagnostic in resolver.diagnostics {
+            ctx.add_diagnostic(diagnostic);
+        }
+
+        Ok(())
+    }
+}
+
+/// First pass: numbers labeled targets and records them in the index.
+struct Indexer<'a> {
+    options: &'a CrossrefOptions,
+    index: CrossrefIndex,
+    counters: HashMap<CrossrefKind, usize>,
+    diagnostics: Vec<DiagnosticMessage>,
+}
+
+impl Indexer<'_> {
+    /// Number the next target of `kind` and record it. Returns the number,
+    /// or `None` if the label is already taken.
+    fn add_numbered(
+        &mut self,
+        label: &str,
+        kind: CrossrefKind,
+        source_info: &SourceInfo,
+    ) -> Option<String> {
+        if self.is_duplicate(label, source_info) {
+            return None;
+        }
+        let counter = self.counters.entry(kind).or_default();
+        *counter += 1;
+        let number = match &self.options.chapter {
+            Some(chapter) => format!("{}.{}", chapter, counter),
+            None => counter.to_string(),
+        };
+        self.index.entries.insert(
+            label.to_string(),
+            CrossrefEntry {
+                kind,
+                number: Some(number.clone()),
+                title: Vec::new(),
+                source_info: source_info.clone(),
+            },
+        );
+        Some(number)
+    }
+
+    fn add_section(
+        &mut self,
+        label: &str,
+        number: Option<&String>,
+        header: &Header,
+        source_info: &SourceInfo,
+    ) {
+        if self.is_duplicate(label, source_info) {
+            return;
+        }
+        self.index.entries.insert(
+            label.to_string(),
+            CrossrefEntry {
+                kind: CrossrefKind::Section,
+                number: number.cloned(),
+                title: header.content.clone(),
+                source_info: source_info.clone(),
+            },
+        );
+    }
+
+    fn is_duplicate(&mut self, label: &str, source_info: &SourceInfo) -> bool {
+        if !self.index.entries.contains_key(label) {
+            return false;
+        }
+        self.diagnostics.push(
+            DiagnosticMessageBuilder::warning("Duplicate cross-reference label")
+                .problem(format!("The label `{}` is used more than once", label))
+                .add_hint("References resolve to the first target with this label")
+                .with_location(source_info.clone())
+                .build(),
+        );
+        true
+    }
+
+    /// "Figure 1" style title for a numbered target.
+    fn title(&self, kind: CrossrefKind, number: &str) -> String {
+        format!("{}\u{a0}{}", self.options.title(kind), number)
+    }
+}
+
+impl Filter for Indexer<'_> {
+    fn name(&self) -> &str {
+        "crossref-index"
+    }
+
+    fn visit_figure(&mut self, node: &mut Figure) -> Visit<Block> {
+        if CrossrefKind::from_label(&node.attr.0) == Some(CrossrefKind::Figure)
+            && let Some(number) = self.add_numbered(
+                &node.attr.0.clone(),
+                CrossrefKind::Figure,
+                &node.source_info,
+            )
+        {
+            let title = self.title(CrossrefKind::Figure, &number);
+            prefix_caption(&mut node.caption, &title, &self.options.title_delim);
+        }
+        Visit::Continue
+    }
+
+    fn visit_table(&mut self, node: &mut Table) -> Visit<Block> {
+        if CrossrefKind::from_label(&node.attr.0) == Some(CrossrefKind::Table)
+            && let Some(number) =
+                self.add_numbered(&node.attr.0.clone(), CrossrefKind::Table, &node.source_info)
+        {
+            let title = self.title(CrossrefKind::Table, &number);
+            prefix_caption(&mut node.caption, &title, &self.options.title_delim);
+        }
+        Visit::Continue
+    }
+
+    fn visit_code_block(&mut self, node: &mut CodeBlock) -> Visit<Block> {
+        if CrossrefKind::from_label(&node.attr.0) == Some(CrossrefKind::Listing) {
+            self.add_numbered(
+                &node.attr.0.clone(),
+                CrossrefKind::Listing,
+                &node.source_info,
+            );
+        }
+        Visit::Continue
+    }
+
+    fn visit_header(&mut self, node: &mut Header) -> Visit<Block> {
+        if CrossrefKind::from_label(&node.attr.0) == Some(CrossrefKind::Section) {
+            let label = node.attr.0.clone();
+            self.add_section(&label, node.attr.2.get("number"), node, &node.source_info);
+        }
+        Visit::Continue
+    }
+
+    fn visit_div(&mut self, node: &mut Div) -> Visit<Block> {
+        let label = node.attr.0.clone();
+        match CrossrefKind::from_label(&label) {
+            Some(CrossrefKind::Section) => {
+                if let Some(Block::Header(header)) = node.content.first() {
+                    self.add_section(&label, node.attr.2.get("number"), header, &node.source_info);
+                }
+            }
+            Some(kind @ (CrossrefKind::Figure | CrossrefKind::Table)) => {
+                self.add_numbered(&label, kind, &node.source_info);
+            }
+            Some(kind) if kind.is_theorem() => {
+                if let Some(number) = self.add_numbered(&label, kind, &node.source_info) {
+                    let title = self.title(kind, &number);
+                    let name = node.attr.2.get("name").cloned();
+                    add_theorem_title(&mut node.content, title, name);
+                }
+            }
+            _ => {}
+        }
+        Visit::Continue
+    }
+
+    fn visit_span(&mut self, node: &mut Span) -> Visit<Inline> {
+        if CrossrefKind::from_label(&node.attr.0) == Some(CrossrefKind::Equation)
+            && let Some(number) = self.add_numbered(
+                &node.attr.0.clone(),
+                CrossrefKind::Equation,
+                &node.source_info,
+            )
+        {
+            for inline in &mut node.content {
+                if let Inline::Math(Math { text, .. }) = inline {
+                    *text = format!("{} \\tag{{{}}}", text.trim_end(), number);
+                }
+            }
+        }
+        Visit::Continue
+    }
+}
+
+/// Second pass: replaces crossref citations with links to their targets.
+struct Resolver<'a> {
+    options: &'a CrossrefOptions,
+    index: &'a CrossrefIndex,
+    /// Targets of other documents in the project
+    shared: Option<&'a ProjectCrossrefs>,
+    /// Prefix from the current page to the project's output root
+    root: String,
+    diagnostics: Vec<DiagnosticMessage>,
+}
+
+impl Resolver<'_> {
+    /// The link text for a reference to `entry`.
+    fn link_text(&self, entry: &CrossrefEntry, mode: CitationMode, si: &SourceInfo) -> Inlines {
+        match &entry.number {
+            Some(number) if mode == CitationMode::SuppressAuthor => vec![str_inline(number, si)],
+            Some(number) => vec![str_inline(
+                &format!("{}\u{a0}{}", self.options.prefix(entry.kind), number),
+                si,
+            )],
+            None => entry.title.clone(),
+        }
+    }
+}
+
+impl Filter for Resolver<'_> {
+    fn name(&self) -> &str {
+        "crossref-resolve"
+    }
+
+    fn visit_cite(&mut self, node: &mut Cite) -> Visit<Inline> {
+        let is_crossref = !node.citations.is_empty()
+            && node
+                .citations
+                .iter()
+                .all(|c| CrossrefKind::from_label(&c.id).is_some());
+        if !is_crossref {
+            return Visit::Continue;
+        }
+
+        let si = &node.source_info;
+        let mut result = Vec::new();
+        for (i, citation) in node.citations.iter().enumerate() {
+            if i > 0 {
+                result.push(str_inline(",", si));
+                result.push(space_inline(si));
+            }
+            if !citation.prefix.is_empty() {
+                result.extend(citation.prefix.iter().cloned());
+                result.push(space_inline(si));
+            }
+
+            let target = match self.index.get(&citation.id) {
+                Some(entry) => Some((entry.clone(), format!("#{}", citation.id))),
+                None => self
+                    .shared
+                    .and_then(|shared| shared.get(&citation.id))
+                    .map(|(page, entry)| (entry, format!("{}{}#{}", self.root, page, citation.id))),
+            };
+
+            match target {
+                Some((entry, href)) => result.push(Inline::Link(Link {
+                    attr: (
+                        String::new(),
+                        vec!["quarto-xref".to_string()],
+                        LinkedHashMap::new(),
+                    ),
+                    content: self.link_text(&entry, citation.mode, si),
+                    target: (href, String::new()),
+                    source_info: si.clone(),
+                    attr_source: AttrSourceInfo::empty(),
+                    target_source: TargetSourceInfo::empty(),
+                })),
+                None => {
+                    let location = citation.id_source.clone().unwrap_or_else(|| si.clone());
+                    self.diagnostics.push(
+                        DiagnosticMessageBuilder::warning("Unresolved cross-reference")
+                            .problem(format!("No target is labeled `{}`", citation.id))
+                            .add_hint(
+                                "Check that the label is spelled the same at the target and the reference",
+                            )
+                            .with_location(location)
+                            .build(),
+                    );
+                    result.push(Inline::Strong(Strong {
+                        content: vec![str_inline(&format!("?@{}", citation.id), si)],
+                        source_info: si.clone(),
+                    }));
+                }
+            }
+
+            result.extend(citation.suffix.iter().cloned());
+        }
+
+        Visit::Replace(result)
+    }
+}
+
+/// The current document's output path relative to the project, e.g.
+/// `chapters/intro.html`.
+fn project_relative_output(ctx: &RenderContext) -> String {
+    let input = &ctx.document.input;
+    let mut relative = input
+        .strip_prefix(&ctx.project.dir)
+        .unwrap_or(input)
+        .to_path_buf();
+    relative.set_extension(&ctx.format.output_extension);
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+/// Put "Figure 1:" in front of a caption. Empty captions are left alone.
+fn prefix_caption(caption: &mut Caption, title: &str, delim: &str) {
+    let Some(content) = caption.long.as_mut().and_then(first_inlines) else {
+        return;
+    };
+    if content.is_empty() {
+        return;
+    }
+    let si = caption.source_info.clone();
+    content.splice(
+        0..0,
+        [
+            str_inline(&format!("{}{}", title, delim), &si),
+            space_inline(&si),
+        ],
+    );
+}
+
+/// Put a "Theorem 1 (Name)" title at the start of a theorem Div.
+fn add_theorem_title(content: &mut Blocks, title: String, name: Option<String>) {
+    let text = match name {
+        Some(name) => format!("{} ({})", title, name),
+        None => title,
+    };
+    let si = SourceInfo::default();
+    let title = Inline::Span(Span {
+        attr: (
+            String::new(),
+            vec!["theorem-title".to_string()],
+            LinkedHashMap::new(),
+        ),
+        content: vec![Inline::Strong(Strong {
+            content: vec![str_inline(&text, &si)],
+            source_info: si.clone(),
+        })],
+        source_info: si.clone(),
+        attr_source: AttrSourceInfo::empty(),
+    });
+
+    match first_inlines(content) {
+        Some(inlines) => {
+            inlines.splice(0..0, [title, space_inline(&si)]);
+        }
+        None => content.insert(
+            0,
+            Block::Plain(Plain {
+                content: vec![title],
+                source_info: si,
+            }),
+        ),
+    }
+}
+
+/// The inlines of the leading paragraph, if `blocks` starts with one.
+fn first_inlines(blocks: &mut Blocks) -> Option<&mut Inlines> {
+    match blocks.first_mut()? {
+        Block::Plain(Plain { content, .. }) => Some(content),
+        Block::Paragraph(para) => Some(&mut para.content),
+        _ => None,
+    }
+}
+
+fn str_inline(text: &str, source_info: &SourceInfo) -> Inline {
+    Inline::Str(Str {
+        text: text.to_string(),
+        source_info: source_info.clone(),
+    })
+}
+
+fn space_inline(source_info: &SourceInfo) -> Inline {
+    Inline::Space(Space {
+        source_info: source_info.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::render::BinaryDependencies;
+    use quarto_pandoc_types::block::Paragraph;
+    use quarto_pandoc_types::inline::Citation;
+    use std::path::PathBuf;
+
+    fn make_test_project() -> ProjectContext {
+        ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![DocumentInfo::from_path("/project/doc.qmd")],
+            output_dir: PathBuf::from("/project"),
+        }
+    }
+
+    fn make_figure(id: &str, caption: &str) -> Block {
+        Block::Figure(Figure {
+            attr: (id.to_string(), vec![], LinkedHashMap::new()),
+            caption: Caption {
+                short: None,
+                long: Some(vec![Block::Plain(Plain {
+                    content: vec![str_inline(caption, &SourceInfo::default())],
+                    source_info: SourceInfo::default(),
+                })]),
+                source_info: SourceInfo::default(),
+            },
+            content: vec![],
+            source_info: SourceInfo::default(),
+            attr_source: AttrSourceInfo::empty(),
+        })
+    }
+
+    fn make_ref(id: &str, mode: CitationMode) -> Block {
+        Block::Paragraph(Paragraph {
+            content: vec![Inline::Cite(Cite {
+                citations: vec![Citation {
+                    id: id.to_string(),
+                    prefix: vec![],
+                    suffix: vec![],
+                    mode,
+                    note_num: 0,
+                    hash: 0,
+                    id_source: None,
+                }],
+                content: vec![str_inline(&format!("@{}", id), &SourceInfo::default())],
+                source_info: SourceInfo::default(),
+            })],
+            source_info: SourceInfo::default(),
+        })
+    }
+
+    fn run(blocks: Vec<Block>, metadata: serde_json::Value) -> (Vec<Block>, usize) {
+        let mut ast = Pandoc {
+            meta: quarto_pandoc_types::ConfigValue::default(),
+            blocks,
+        };
+
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let format = Format::html().with_metadata(metadata);
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+
+        CrossrefTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+        (ast.blocks, ctx.diagnostics.len())
+    }
+
+    fn para_inlines(block: &Block) -> &Inlines {
+        match block {
+            Block::Paragraph(para) => &para.content,
+            _ => panic!("expected Paragraph"),
+        }
+    }
+
+    fn link_text(inline: &Inline) -> (&str, String) {
+        let Inline::Link(link) = inline else {
+            panic!("expected Link, got {:?}", inline);
+        };
+        let Inline::Str(s) = &link.content[0] else {
+            panic!("expected Str");
+        };
+        (link.target.0.as_str(), s.text.clone())
+    }
+
+    #[test]
+    fn test_from_label() {
+        assert_eq!(
+            CrossrefKind::from_label("fig-plot"),
+            Some(CrossrefKind::Figure)
+        );
+        assert_eq!(
+            CrossrefKind::from_label("thm-main"),
+            Some(CrossrefKind::Theorem)
+        );
+        assert_eq!(CrossrefKind::from_label("fig-"), None);
+        assert_eq!(CrossrefKind::from_label("smith2020"), None);
+        assert_eq!(CrossrefKind::from_label("foo-bar"), None);
+    }
+
+    #[test]
+    fn test_resolves_figure_references() {
+        let (blocks, diagnostics) = run(
+            vec![
+                make_figure("fig-a", "First"),
+                make_figure("fig-b", "Second"),
+                make_ref("fig-b", CitationMode::NormalCitation),
+                make_ref("fig-a", CitationMode::SuppressAuthor),
+            ],
+            serde_json::json!({}),
+        );
+        assert_eq!(diagnostics, 0);
+
+        assert_eq!(
+            link_text(&para_inlines(&blocks[2])[0]),
+            ("#fig-b", "Figure\u{a0}2".to_string())
+        );
+        assert_eq!(
+            link_text(&para_inlines(&blocks[3])[0]),
+            ("#fig-a", "1".to_string())
+        );
+
+        // The caption shows the number
+        let Block::Figure(figure) = &blocks[1] else {
+            panic!("expected Figure");
+        };
+        let Some(Block::Plain(plain)) = figure.caption.long.as_ref().and_then(|c| c.first()) else {
+            panic!("expected Plain caption");
+        };
+        let Inline::Str(title) = &plain.content[0] else {
+            panic!("expected Str");
+        };
+        assert_eq!(title.text, "Figure\u{a0}2:");
+    }
+
+    #[test]
+    fn test_unresolved_reference_reports_diagnostic() {
+        let (blocks, diagnostics) = run(
+            vec![make_ref("fig-missing", CitationMode::NormalCitation)],
+            serde_json::json!({}),
+        );
+        assert_eq!(diagnostics, 1);
+
+        let Inline::Strong(strong) = &para_inlines(&blocks[0])[0] else {
+            panic!("expected Strong");
+        };
+        let Inline::Str(s) = &strong.content[0] else {
+            panic!("expected Str");
+        };
+        assert_eq!(s.text, "?@fig-missing");
+    }
+
+    #[test]
+    fn test_localized_and_configured_prefixes() {
+        let (blocks, _) = run(
+            vec![
+                make_figure("fig-a", "Plot"),
+                make_ref("fig-a", CitationMode::NormalCitation),
+            ],
+            serde_json::json!({ "lang": "de-DE" }),
+        );
+        assert_eq!(
+            link_text(&para_inlines(&blocks[1])[0]).1,
+            "Abbildung\u{a0}1"
+        );
+
+        let (blocks, _) = run(
+            vec![
+                make_figure("fig-a", "Plot"),
+                make_ref("fig-a", CitationMode::NormalCitation),
+            ],
+            serde_json::json!({ "crossref": { "fig-prefix": "Fig." } }),
+        );
+        assert_eq!(link_text(&para_inlines(&blocks[1])[0]).1, "Fig.\u{a0}1");
+    }
+
+    #[test]
+    fn test_section_references_use_section_numbers() {
+        let mut attrs = LinkedHashMap::new();
+        attrs.insert("number".to_string(), "2.1".to_string());
+        let header = Block::Header(Header {
+            level: 2,
+            attr: ("sec-methods".to_string(), vec![], attrs),
+            content: vec![str_inline("Methods", &SourceInfo::default())],
+            source_info: SourceInfo::default(),
+            attr_source: AttrSourceInfo::empty(),
+        });
+        let (blocks, diagnostics) = run(
+            vec![
+                header,
+                make_ref("sec-methods", CitationMode::NormalCitation),
+            ],
+            serde_json::json!({}),
+        );
+        assert_eq!(diagnostics, 0);
+        assert_eq!(
+            link_text(&para_inlines(&blocks[1])[0]),
+            ("#sec-methods", "Section\u{a0}2.1".to_string())
+        );
+    }
+
+    fn run_chapter(
+        chapter: &str,
+        blocks: Vec<Block>,
+        shared: &ProjectCrossrefs,
+    ) -> (Vec<Block>, usize) {
+        let mut ast = Pandoc {
+            meta: quarto_pandoc_types::ConfigValue::default(),
+            blocks,
+        };
+
+        let project = ProjectContext {
+            config: Some(crate::project::ProjectConfig {
+                project_type: crate::project::ProjectType::Book,
+                raw: serde_json::json!({
+                    "book": { "chapters": ["index.qmd", "intro.qmd", "chapters/results.qmd"] }
+                }),
+                ..Default::default()
+            }),
+            is_single_file: false,
+            ..make_test_project()
+        };
+        let doc = DocumentInfo::from_path(format!("/project/{}", chapter));
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries).with_options(
+            crate::render::RenderOptions {
+                crossrefs: Some(shared.clone()),
+                ..Default::default()
+            },
+        );
+
+        CrossrefTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+        (ast.blocks, ctx.diagnostics.len())
+    }
+
+    #[test]
+    fn test_book_chapter_numbers_and_cross_chapter_references() {
+        let shared = ProjectCrossrefs::new();
+
+        let (results, _) = run_chapter(
+            "chapters/results.qmd",
+            vec![
+                make_figure("fig-a", "First"),
+                make_figure("fig-b", "Second"),
+                make_ref("fig-a", CitationMode::NormalCitation),
+            ],
+            &shared,
+        );
+        assert_eq!(
+            link_text(&para_inlines(&results[2])[0]),
+            ("#fig-a", "Figure\u{a0}2.1".to_string())
+        );
+
+        let (intro, diagnostics) = run_chapter(
+            "intro.qmd",
+            vec![
+                make_figure("fig-c", "Third"),
+                make_ref("fig-b", CitationMode::NormalCitation),
+            ],
+            &shared,
+        );
+        assert_eq!(diagnostics, 0);
+        assert_eq!(
+            link_text(&para_inlines(&intro[1])[0]),
+            ("chapters/results.html#fig-b", "Figure\u{a0}2.2".to_string())
+        );
+
+        // Links from a nested chapter climb to the project root
+        let (results, _) = run_chapter(
+            "chapters/results.qmd",
+            vec![make_ref("fig-c", CitationMode::SuppressAuthor)],
+            &shared,
+        );
+        assert_eq!(
+            link_text(&para_inlines(&results[0])[0]),
+            ("../intro.html#fig-c", "1.1".to_string())
+        );
+    }
+}
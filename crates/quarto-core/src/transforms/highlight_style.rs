@@ -0,0 +1,222 @@
+/*
+ * highlight_style.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Transform that produces the syntax highlighting stylesheet.
+ */
+
+//! Highlight style transform.
+//!
+//! The HTML writer marks up highlighted code with Pandoc's token classes
+//! (`kw`, `st`, ...). This transform resolves the `highlight-style` option
+//! to one of pampa's built-in styles and produces the matching stylesheet:
+//!
+//! - stored in the ArtifactStore as `css:highlight` (at
+//!   [`HIGHLIGHT_CSS_ARTIFACT_PATH`](crate::pipeline::HIGHLIGHT_CSS_ARTIFACT_PATH))
+//! - inserted at `rendered.highlighting-css` for the HTML template to inline
+//!
+//! For PDF output the LaTeX writer wraps tokens in `\KeywordTok`-style
+//! macros instead; their definitions are inserted at
+//! `rendered.highlighting-macros` for the render-latex stage.
+//!
+//! `highlight-style: none` disables the stylesheet (and, for PDF, highlighting).
+
+use std::path::PathBuf;
+
+use pampa::highlighting::HighlightStyle;
+use pampa::highlighting::styles::STYLE_NAMES;
+use quarto_error_reporting::DiagnosticMessageBuilder;
+use quarto_pandoc_types::config_value::ConfigValue;
+use quarto_pandoc_types::pandoc::Pandoc;
+use quarto_source_map::SourceInfo;
+
+use crate::Result;
+use crate::artifact::Artifact;
+use crate::format::FormatIdentifier;
+use crate::pipeline::HIGHLIGHT_CSS_ARTIFACT_PATH;
+use crate::render::RenderContext;
+use crate::transform::AstTransform;
+
+/// Transform that produces the stylesheet for the `highlight-style` option.
+///
+/// The style is read from the format metadata (`format.html.highlight-style`),
+/// falling back to the document's top-level `highlight-style`, then to
+/// Pandoc's default, `pygments`. Unknown style names produce a warning and
+/// the default style.
+pub struct HighlightStyleTransform;
+
+impl HighlightStyleTransform {
+    /// Create a new highlight style transform.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for HighlightStyleTransform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AstTransform for HighlightStyleTransform {
+    fn name(&self) -> &str {
+        "highlight-style"
+    }
+
+    fn transform(&self, ast: &mut Pandoc, ctx: &mut RenderContext) -> Result<()> {
+        let is_pdf = ctx.format.identifier == FormatIdentifier::Pdf;
+        if !ctx.format.is_html() && !is_pdf {
+            return Ok(());
+        }
+
+        let name = ctx
+            .format_metadata("highlight-style")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .or_else(|| {
+                ast.meta
+                    .get("highlight-style")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            });
+
+        let style = match name.as_deref() {
+            Some("none") => return Ok(()),
+            None => HighlightStyle::default(),
+            Some(name) => HighlightStyle::named(name).unwrap_or_else(|| {
+                ctx.diagnostics.push(
+                    DiagnosticMessageBuilder::warning("Unknown highlight style")
+                        .problem(format!("Highlight style `{}` is not recognized", name))
+                        .add_hint(format!("Use one of: {}, or `none`", STYLE_NAMES.join(", ")))
+                        .build(),
+                );
+                HighlightStyle::default()
+            }),
+        };
+
+        if is_pdf {
+            ast.meta.insert_path(
+                &["rendered", "highlighting-macros"],
+                ConfigValue::new_string(style.to_latex(), SourceInfo::default()),
+            );
+            return Ok(());
+        }
+
+        let css = style.to_css();
+        ctx.artifacts.store(
+            "css:highlight",
+            Artifact::from_string(css.clone(), "text/css")
+                .with_path(PathBuf::from(HIGHLIGHT_CSS_ARTIFACT_PATH))
+                .with_metadata("style", serde_json::json!(style.name)),
+        );
+        ast.meta.insert_path(
+            &["rendered", "highlighting-css"],
+            ConfigValue::new_string(&css, SourceInfo::default()),
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::render::BinaryDependencies;
+
+    fn make_test_project() -> ProjectContext {
+        ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![DocumentInfo::from_path("/project/doc.qmd")],
+            output_dir: PathBuf::from("/project"),
+        }
+    }
+
+    fn run(format: &Format) -> (Pandoc, Option<Artifact>, usize) {
+        let mut ast = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![],
+        };
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, format, &binaries);
+
+        HighlightStyleTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+        let artifact = ctx.artifacts.get("css:highlight").cloned();
+        (ast, artifact, ctx.diagnostics.len())
+    }
+
+    #[test]
+    fn test_default_style() {
+        let (ast, artifact, diagnostics) = run(&Format::html());
+        let artifact = artifact.unwrap();
+        assert_eq!(artifact.content_type, "text/css");
+        assert_eq!(
+            artifact.path,
+            Some(PathBuf::from(HIGHLIGHT_CSS_ARTIFACT_PATH))
+        );
+        assert!(artifact.as_string().contains("code span.kw"));
+        assert!(ast.meta.contains_path(&["rendered", "highlighting-css"]));
+        assert_eq!(diagnostics, 0);
+    }
+
+    #[test]
+    fn test_named_style() {
+        let format = Format::html().with_metadata(serde_json::json!({
+            "highlight-style": "breezedark"
+        }));
+        let (_, artifact, _) = run(&format);
+        assert!(artifact.unwrap().as_string().contains("#232629"));
+    }
+
+    #[test]
+    fn test_none_disables_stylesheet() {
+        let format = Format::html().with_metadata(serde_json::json!({
+            "highlight-style": "none"
+        }));
+        let (ast, artifact, _) = run(&format);
+        assert!(artifact.is_none());
+        assert!(!ast.meta.contains_path(&["rendered", "highlighting-css"]));
+    }
+
+    #[test]
+    fn test_unknown_style_warns() {
+        let format = Format::html().with_metadata(serde_json::json!({
+            "highlight-style": "no-such-style"
+        }));
+        let (_, artifact, diagnostics) = run(&format);
+        assert!(artifact.is_some());
+        assert_eq!(diagnostics, 1);
+    }
+
+    #[test]
+    fn test_pdf_highlighting_macros() {
+        let (ast, artifact, _) = run(&Format::pdf());
+        assert!(artifact.is_none());
+        let macros = ast
+            .meta
+            .get_path(&["rendered", "highlighting-macros"])
+            .and_then(|v| v.as_str())
+            .unwrap();
+        assert!(macros.contains("\\newcommand{\\KeywordTok}"));
+
+        let format = Format::pdf().with_metadata(serde_json::json!({
+            "highlight-style": "none"
+        }));
+        let (ast, _, _) = run(&format);
+        assert!(!ast.meta.contains_path(&["rendered", "highlighting-macros"]));
+    }
+
+    #[test]
+    fn test_skips_other_formats() {
+        let (ast, artifact, _) = run(&Format::typst());
+        assert!(artifact.is_none());
+        assert!(!ast.meta.contains_path(&["rendered"]));
+    }
+}
@@ -0,0 +1,595 @@
+/*
+ * listing.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Transform that renders document listings.
+ */
+
+//! Listing transform for HTML output.
+//!
+//! For a page with `listing:` metadata, this transform collects the listed
+//! documents (see [`crate::listing`]) and renders them into the page:
+//!
+//! - `default` - one row per document: thumbnail, title, categories,
+//!   description, date and author
+//! - `table` - a table of date, title and author
+//! - `grid` - a card per document
+//!
+//! With `categories: true`, the listing starts with category filter chips
+//! (with counts); a small script shows only the documents of the clicked
+//! category.
+//!
+//! Each listing is rendered into the Div whose id is the listing's `id`
+//! (e.g. `::: {#listing}`), or, if the page has none, into a new Div
+//! appended to the page. Links point from the listing page to the listed
+//! documents' output files.
+//!
+//! Reading the listed documents needs the render context's runtime; without
+//! one the transform does nothing.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use hashlink::LinkedHashMap;
+use quarto_pandoc_types::Blocks;
+use quarto_pandoc_types::attr::AttrSourceInfo;
+use quarto_pandoc_types::block::{Block, Div, RawBlock};
+use quarto_pandoc_types::config_value::{ConfigValue, ConfigValueKind};
+use quarto_pandoc_types::pandoc::Pandoc;
+use quarto_source_map::SourceInfo;
+use serde_json::Value;
+
+use crate::Result;
+use crate::listing::{Listing, ListingItem, ListingType};
+use crate::render::RenderContext;
+use crate::transform::AstTransform;
+
+/// Shows only the items of the clicked category chip.
+const CATEGORY_FILTER_SCRIPT: &str = r#"<script>
+document.querySelectorAll(".quarto-listing-category .category").forEach((chip) => {
+  chip.addEventListener("click", () => {
+    const listing = chip.closest(".quarto-listing");
+    const category = chip.dataset.category;
+    listing.querySelectorAll(".quarto-listing-category .category").forEach((other) => {
+      other.classList.toggle("active", other === chip);
+    });
+    listing.querySelectorAll("[data-categories]").forEach((item) => {
+      const categories = item.dataset.categories.split(",");
+      item.style.display = !category || categories.includes(category) ? "" : "none";
+    });
+  });
+});
+</script>"#;
+
+/// Transform that renders `listing:` pages.
+pub struct ListingTransform;
+
+impl ListingTransform {
+    /// Create a new listing transform.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ListingTransform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AstTransform for ListingTransform {
+    fn name(&self) -> &str {
+        "listing"
+    }
+
+    fn transform(&self, ast: &mut Pandoc, ctx: &mut RenderContext) -> Result<()> {
+        let Some(listing) = ast.meta.get("listing") else {
+            return Ok(());
+        };
+        let listings = Listing::from_metadata(&meta_to_json(listing));
+        let Some(runtime) = ctx.runtime.clone() else {
+            return Ok(());
+        };
+
+        let page = ctx.document.input.as_path();
+        let links = Links::new(ctx);
+        let mut has_categories = false;
+
+        for listing in &listings {
+            let items = listing.items(ctx.project, page, runtime.as_ref());
+            has_categories |= listing.categories;
+
+            let mut html = String::new();
+            if listing.categories {
+                html.push_str(&render_categories(&items));
+            }
+            html.push_str(&match listing.listing_type {
+                ListingType::Default => render_default(&items, &links),
+                ListingType::Table => render_table(&items, &links),
+                ListingType::Grid => render_grid(&items, &links),
+            });
+
+            let classes = [
+                "quarto-listing".to_string(),
+                format!(
+                    "quarto-listing-container-{}",
+                    type_name(listing.listing_type)
+                ),
+            ];
+            let content = vec![raw_html(html)];
+            match find_div(&mut ast.blocks, &listing.id) {
+                Some(div) => {
+                    for class in classes {
+                        if !div.attr.1.contains(&class) {
+                            div.attr.1.push(class);
+                        }
+                    }
+                    div.content = content;
+                }
+                None => ast.blocks.push(Block::Div(Div {
+                    attr: (listing.id.clone(), classes.into(), LinkedHashMap::new()),
+                    content,
+                    source_info: SourceInfo::default(),
+                    attr_source: AttrSourceInfo::empty(),
+                })),
+            }
+        }
+
+        if has_categories {
+            ast.blocks
+                .push(raw_html(CATEGORY_FILTER_SCRIPT.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Links from the listing page to listed documents and their images.
+struct Links<'a> {
+    /// Directory of the listing page
+    dir: &'a Path,
+    /// Prefix from the listing page to the project directory
+    root: String,
+    /// Output extension for listed documents
+    extension: &'a str,
+}
+
+impl<'a> Links<'a> {
+    fn new(ctx: &'a RenderContext) -> Self {
+        let dir = ctx.document.input.parent().unwrap_or(Path::new(""));
+        let depth = dir
+            .strip_prefix(&ctx.project.dir)
+            .map_or(0, |relative| relative.components().count());
+        Self {
+            dir,
+            root: "../".repeat(depth),
+            extension: ctx.format.output_extension.as_str(),
+        }
+    }
+
+    /// A path relative to the listing page, with `/` separators.
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(self.dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// The href of a listed document's output file.
+    fn page(&self, item: &ListingItem) -> String {
+        let input = self.relative(&item.input);
+        match input.rsplit_once('.') {
+            Some((stem, "qmd" | "md" | "ipynb")) => format!("{}.{}", stem, self.extension),
+            _ => input,
+        }
+    }
+
+    /// The src of a listed document's image, which is relative to the
+    /// document (or, starting with `/`, to the project).
+    fn image(&self, item: &ListingItem) -> Option<String> {
+        let image = item.image.as_deref()?;
+        Some(if image.contains("://") || image.starts_with("data:") {
+            image.to_string()
+        } else if let Some(rest) = image.strip_prefix('/') {
+            format!("{}{}", self.root, rest)
+        } else {
+            let dir = item.input.parent().unwrap_or(Path::new(""));
+            self.relative(&dir.join(image))
+        })
+    }
+}
+
+fn type_name(listing_type: ListingType) -> &'static str {
+    match listing_type {
+        ListingType::Default => "default",
+        ListingType::Table => "table",
+        ListingType::Grid => "grid",
+    }
+}
+
+/// Attributes shared by every listed item, for filtering and sorting.
+fn item_data(index: usize, item: &ListingItem) -> String {
+    format!(
+        "data-index=\"{}\" data-categories=\"{}\" data-listing-date-sort=\"{}\"",
+        index,
+        html_escape(&item.categories.join(",")),
+        html_escape(item.date.as_deref().unwrap_or_default())
+    )
+}
+
+fn render_categories(items: &[ListingItem]) -> String {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for category in items.iter().flat_map(|item| &item.categories) {
+        *counts.entry(category.as_str()).or_default() += 1;
+    }
+
+    let mut html = format!(
+        "<div class=\"quarto-listing-category category-default\">\n\
+         <div class=\"category active\" data-category=\"\">All <span class=\"quarto-category-count\">({})</span></div>\n",
+        items.len()
+    );
+    for (category, count) in counts {
+        html.push_str(&format!(
+            "<div class=\"category\" data-category=\"{0}\">{0} <span class=\"quarto-category-count\">({1})</span></div>\n",
+            html_escape(category),
+            count
+        ));
+    }
+    html.push_str("</div>\n");
+    html
+}
+
+fn render_default(items: &[ListingItem], links: &Links) -> String {
+    let mut html = String::from("<div class=\"list quarto-listing-default\">\n");
+    for (index, item) in items.iter().enumerate() {
+        let href = html_escape(&links.page(item));
+        html.push_str(&format!(
+            "<div class=\"quarto-post image-right\" {}>\n",
+            item_data(index, item)
+        ));
+        if let Some(src) = links.image(item) {
+            html.push_str(&format!(
+                "<div class=\"thumbnail\"><a href=\"{}\" class=\"no-external\"><img src=\"{}\" class=\"thumbnail-image\" alt=\"\"></a></div>\n",
+                href,
+                html_escape(&src)
+            ));
+        }
+        html.push_str(&format!(
+            "<div class=\"body\">\n\
+             <h3 class=\"no-anchor listing-title\"><a href=\"{}\" class=\"no-external\">{}</a></h3>\n",
+            href,
+            html_escape(&item.title)
+        ));
+        if !item.categories.is_empty() {
+            html.push_str("<div class=\"listing-categories\">");
+            for category in &item.categories {
+                html.push_str(&format!(
+                    "<div class=\"listing-category\">{}</div>",
+                    html_escape(category)
+                ));
+            }
+            html.push_str("</div>\n");
+        }
+        if let Some(description) = &item.description {
+            html.push_str(&format!(
+                "<div class=\"listing-description\">{}</div>\n",
+                html_escape(description)
+            ));
+        }
+        html.push_str("</div>\n<div class=\"metadata\">\n");
+        if let Some(date) = &item.date {
+            html.push_str(&format!(
+                "<div class=\"listing-date\">{}</div>\n",
+                html_escape(date)
+            ));
+        }
+        if let Some(author) = &item.author {
+            html.push_str(&format!(
+                "<div class=\"listing-author\">{}</div>\n",
+                html_escape(author)
+            ));
+        }
+        html.push_str("</div>\n</div>\n");
+    }
+    html.push_str("</div>\n");
+    html
+}
+
+fn render_table(items: &[ListingItem], links: &Links) -> String {
+    let mut html = String::from(
+        "<table class=\"quarto-listing-table table\">\n\
+         <thead><tr><th class=\"listing-date\">Date</th><th class=\"listing-title\">Title</th><th class=\"listing-author\">Author</th></tr></thead>\n\
+         <tbody class=\"list\">\n",
+    );
+    for (index, item) in items.iter().enumerate() {
+        html.push_str(&format!(
+            "<tr {}><td class=\"listing-date\">{}</td><td class=\"listing-title\"><a href=\"{}\">{}</a></td><td class=\"listing-author\">{}</td></tr>\n",
+            item_data(index, item),
+            html_escape(item.date.as_deref().unwrap_or_default()),
+            html_escape(&links.page(item)),
+            html_escape(&item.title),
+            html_escape(item.author.as_deref().unwrap_or_default())
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+fn render_grid(items: &[ListingItem], links: &Links) -> String {
+    let mut html = String::from("<div class=\"list grid quarto-listing-cols-3\">\n");
+    for (index, item) in items.iter().enumerate() {
+        html.push_str(&format!(
+            "<div class=\"g-col-1\" {}>\n\
+             <a href=\"{}\" class=\"quarto-grid-link\">\n\
+             <div class=\"quarto-grid-item card h-100 card-left\">\n",
+            item_data(index, item),
+            html_escape(&links.page(item))
+        ));
+        if let Some(src) = links.image(item) {
+            html.push_str(&format!(
+                "<p class=\"card-img-top\"><img src=\"{}\" class=\"thumbnail-image card-img\" alt=\"\"></p>\n",
+                html_escape(&src)
+            ));
+        }
+        html.push_str(&format!(
+            "<div class=\"card-body post-contents\">\n\
+             <h5 class=\"no-anchor card-title listing-title\">{}</h5>\n",
+            html_escape(&item.title)
+        ));
+        if let Some(description) = &item.description {
+            html.push_str(&format!(
+                "<div class=\"card-text listing-description\">{}</div>\n",
+                html_escape(description)
+            ));
+        }
+        html.push_str("</div>\n");
+        if item.date.is_some() || item.author.is_some() {
+            html.push_str("<div class=\"card-footer listing-card-footer\">\n");
+            if let Some(date) = &item.date {
+                html.push_str(&format!(
+                    "<div class=\"listing-date\">{}</div>\n",
+                    html_escape(date)
+                ));
+            }
+            if let Some(author) = &item.author {
+                html.push_str(&format!(
+                    "<div class=\"listing-author\">{}</div>\n",
+                    html_escape(author)
+                ));
+            }
+            html.push_str("</div>\n");
+        }
+        html.push_str("</div>\n</a>\n</div>\n");
+    }
+    html.push_str("</div>\n");
+    html
+}
+
+fn raw_html(text: String) -> Block {
+    Block::RawBlock(RawBlock {
+        format: "html".to_string(),
+        text,
+        source_info: SourceInfo::default(),
+    })
+}
+
+/// Find the Div with the given id, searching nested Divs (e.g. sections).
+fn find_div<'a>(blocks: &'a mut Blocks, id: &str) -> Option<&'a mut Div> {
+    for block in blocks.iter_mut() {
+        if let Block::Div(div) = block {
+            if div.attr.0 == id {
+                return Some(div);
+            }
+            if let Some(found) = find_div(&mut div.content, id) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+/// Convert document metadata to JSON, with markdown strings as plain text.
+pub(super) fn meta_to_json(meta: &ConfigValue) -> Value {
+    if let Some(b) = meta.as_bool() {
+        return Value::Bool(b);
+    }
+    if let Some(i) = meta.as_int() {
+        return Value::from(i);
+    }
+    match &meta.value {
+        ConfigValueKind::Array(items) => Value::Array(items.iter().map(meta_to_json).collect()),
+        ConfigValueKind::Map(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|entry| (entry.key.clone(), meta_to_json(&entry.value)))
+                .collect(),
+        ),
+        _ => meta.as_plain_text().map_or(Value::Null, Value::String),
+    }
+}
+
+/// Escape HTML special characters.
+pub(super) fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::render::BinaryDependencies;
+    use quarto_system_runtime::NativeRuntime;
+    use std::sync::Arc;
+
+    const POSTS: &[(&str, &str)] = &[
+        (
+            "posts/welcome/index.qmd",
+            "---\ntitle: Welcome\ndate: 2024-01-05\ncategories: [news]\nimage: thumb.png\n---\n",
+        ),
+        (
+            "posts/code.qmd",
+            "---\ntitle: Post With Code\ndate: 2024-03-10\nauthor: Ada\ncategories: [news, code]\ndescription: Some <code>\n---\n",
+        ),
+    ];
+
+    fn project(dir: &Path) -> ProjectContext {
+        let mut files = vec![DocumentInfo::from_path(dir.join("index.qmd"))];
+        for (path, content) in POSTS {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, content).unwrap();
+            files.push(DocumentInfo::from_path(path));
+        }
+        ProjectContext {
+            dir: dir.to_path_buf(),
+            config: None,
+            is_single_file: false,
+            files,
+            output_dir: dir.join("_site"),
+        }
+    }
+
+    fn listing_meta(entries: &[(&str, ConfigValue)]) -> ConfigValue {
+        let mut meta = ConfigValue::default();
+        for (key, value) in entries {
+            meta.insert_path(&["listing", *key], value.clone());
+        }
+        meta
+    }
+
+    fn string(s: &str) -> ConfigValue {
+        ConfigValue::new_string(s, SourceInfo::default())
+    }
+
+    fn render(meta: ConfigValue, blocks: Blocks) -> Pandoc {
+        let temp = tempfile::tempdir().unwrap();
+        let project = project(temp.path());
+        let doc = DocumentInfo::from_path(temp.path().join("index.qmd"));
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries)
+            .with_runtime(Arc::new(NativeRuntime::new()));
+
+        let mut ast = Pandoc { meta, blocks };
+        ListingTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+        ast
+    }
+
+    fn div_html(block: &Block) -> (&Div, &str) {
+        let Block::Div(div) = block else {
+            panic!("expected a Div, got {:?}", block);
+        };
+        let Some(Block::RawBlock(raw)) = div.content.first() else {
+            panic!("expected raw HTML in the listing Div");
+        };
+        (div, raw.text.as_str())
+    }
+
+    #[test]
+    fn test_transform_name() {
+        assert_eq!(ListingTransform::new().name(), "listing");
+    }
+
+    #[test]
+    fn test_default_listing_appended() {
+        let ast = render(listing_meta(&[("contents", string("posts"))]), vec![]);
+
+        assert_eq!(ast.blocks.len(), 1);
+        let (div, html) = div_html(&ast.blocks[0]);
+        assert_eq!(div.attr.0, "listing");
+        assert!(
+            div.attr
+                .1
+                .contains(&"quarto-listing-container-default".to_string())
+        );
+
+        // Newest first, linked to output files
+        let code = html.find("Post With Code").unwrap();
+        let welcome = html.find("Welcome").unwrap();
+        assert!(code < welcome);
+        assert!(html.contains("href=\"posts/code.html\""));
+        assert!(html.contains("href=\"posts/welcome/index.html\""));
+        assert!(html.contains("<img src=\"posts/welcome/thumb.png\""));
+        assert!(html.contains("<div class=\"listing-date\">2024-03-10</div>"));
+        assert!(html.contains("<div class=\"listing-author\">Ada</div>"));
+        assert!(html.contains("Some &lt;code&gt;"));
+        assert!(!html.contains("quarto-listing-category"));
+    }
+
+    #[test]
+    fn test_listing_fills_existing_div() {
+        let placeholder = Block::Div(Div {
+            attr: (
+                "posts".to_string(),
+                vec!["column-page".to_string()],
+                LinkedHashMap::new(),
+            ),
+            content: vec![],
+            source_info: SourceInfo::default(),
+            attr_source: AttrSourceInfo::empty(),
+        });
+        let meta = listing_meta(&[("id", string("posts")), ("type", string("table"))]);
+        let ast = render(meta, vec![placeholder]);
+
+        assert_eq!(ast.blocks.len(), 1);
+        let (div, html) = div_html(&ast.blocks[0]);
+        assert_eq!(
+            div.attr.1,
+            vec![
+                "column-page",
+                "quarto-listing",
+                "quarto-listing-container-table"
+            ]
+        );
+        assert!(html.starts_with("<table class=\"quarto-listing-table table\">"));
+        assert!(html.contains(
+            "<td class=\"listing-title\"><a href=\"posts/code.html\">Post With Code</a></td>"
+        ));
+    }
+
+    #[test]
+    fn test_grid_listing_with_categories() {
+        let meta = listing_meta(&[
+            ("type", string("grid")),
+            (
+                "categories",
+                ConfigValue::new_bool(true, SourceInfo::default()),
+            ),
+        ]);
+        let ast = render(meta, vec![]);
+
+        assert_eq!(ast.blocks.len(), 2);
+        let (_, html) = div_html(&ast.blocks[0]);
+        assert!(
+            html.contains(
+                "data-category=\"\">All <span class=\"quarto-category-count\">(2)</span>"
+            )
+        );
+        assert!(html.contains(
+            "data-category=\"news\">news <span class=\"quarto-category-count\">(2)</span>"
+        ));
+        assert!(html.contains(
+            "data-category=\"code\">code <span class=\"quarto-category-count\">(1)</span>"
+        ));
+        assert!(html.contains("data-categories=\"news,code\""));
+        assert!(html.contains("class=\"quarto-grid-item card h-100 card-left\""));
+
+        let Block::RawBlock(script) = &ast.blocks[1] else {
+            panic!("expected the category filter script");
+        };
+        assert!(script.text.starts_with("<script>"));
+    }
+
+    #[test]
+    fn test_no_listing_metadata() {
+        let ast = render(ConfigValue::default(), vec![]);
+        assert!(ast.blocks.is_empty());
+    }
+}
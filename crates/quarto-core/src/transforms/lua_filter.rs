@@ -0,0 +1,175 @@
+/*
+ * transforms/lua_filter.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Transform that runs the document's Lua filters.
+ */
+
+//! Lua filter transform.
+//!
+//! Runs the Lua filters listed under `filters:` in the document metadata
+//! and in the format's metadata (where extension formats list theirs).
+//! Entries are paths relative to the document, or names of extensions
+//! contributing filters. As in Quarto 1.x, filters listed before a
+//! `quarto` entry (or all of them, without one) run before Quarto's
+//! transforms and those after it run once they're done:
+//!
+//! ```yaml
+//! filters:
+//!   - lightbox        # the lightbox extension's filters
+//!   - quarto
+//!   - cleanup.lua     # runs last
+//! ```
+//!
+//! The pipeline includes a [`LuaFilterTransform::pre`] and a
+//! [`LuaFilterTransform::post`] transform, each running its share of the
+//! filters through [`pampa::lua`].
+
+use std::path::{Path, PathBuf};
+
+use pampa::lua::apply_lua_filters;
+use pampa::pandoc::ASTContext;
+use quarto_analysis::AnalysisContext;
+use quarto_pandoc_types::pandoc::Pandoc;
+use serde_json::Value;
+
+use crate::error::{QuartoError, Result};
+use crate::extension::contributions::{document_extensions, resolve_filters};
+use crate::format::{Format, FormatIdentifier};
+use crate::render::RenderContext;
+use crate::transform::AstTransform;
+
+/// Whether filters run before or after Quarto's transforms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterPhase {
+    Pre,
+    Post,
+}
+
+/// Transform that runs the document's Lua filters of one phase.
+pub struct LuaFilterTransform {
+    phase: FilterPhase,
+}
+
+impl LuaFilterTransform {
+    /// Run the filters listed before `quarto` (or without it).
+    pub fn pre() -> Self {
+        Self {
+            phase: FilterPhase::Pre,
+        }
+    }
+
+    /// Run the filters listed after `quarto`.
+    pub fn post() -> Self {
+        Self {
+            phase: FilterPhase::Post,
+        }
+    }
+
+    /// The filter scripts this transform runs for the document.
+    fn filters(&self, ast: &Pandoc, ctx: &RenderContext) -> Result<Vec<PathBuf>> {
+        let mut entries = filter_entries(ctx.format.get_metadata("filters"));
+        entries.extend(filter_entries(
+            ast.meta.get("filters").map(|v| v.to_json()).as_ref(),
+        ));
+        if entries.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let document_dir = ctx.document.input.parent().unwrap_or(Path::new(""));
+        let extensions = match ctx.runtime.as_deref() {
+            Some(runtime) => document_extensions(document_dir, &ctx.project.dir, runtime)?,
+            None => Vec::new(),
+        };
+        let (pre, post) = resolve_filters(&entries, document_dir, &extensions)?;
+        Ok(match self.phase {
+            FilterPhase::Pre => pre,
+            FilterPhase::Post => post,
+        })
+    }
+}
+
+impl AstTransform for LuaFilterTransform {
+    fn name(&self) -> &str {
+        match self.phase {
+            FilterPhase::Pre => "lua-filters-pre",
+            FilterPhase::Post => "lua-filters-post",
+        }
+    }
+
+    fn transform(&self, ast: &mut Pandoc, ctx: &mut RenderContext) -> Result<()> {
+        let filters = self.filters(ast, ctx)?;
+        if filters.is_empty() {
+            return Ok(());
+        }
+        if let Some(filter) = filters
+            .iter()
+            .find(|f| f.extension().and_then(|e| e.to_str()) != Some("lua"))
+        {
+            return Err(QuartoError::other(format!(
+                "Filter {} is not a Lua filter (only .lua filters are supported)",
+                filter.display()
+            )));
+        }
+
+        let (filtered, _, diagnostics) = apply_lua_filters(
+            std::mem::take(ast),
+            ASTContext::default(),
+            &filters,
+            lua_format(ctx.format),
+        )
+        .map_err(|e| QuartoError::other(e.to_string()))?;
+        *ast = filtered;
+        for diagnostic in diagnostics {
+            ctx.add_diagnostic(diagnostic);
+        }
+        Ok(())
+    }
+}
+
+/// The entries of a `filters` list: strings, or maps with a `path`.
+fn filter_entries(filters: Option<&Value>) -> Vec<String> {
+    let entry = |value: &Value| match value {
+        Value::String(path) => Some(path.clone()),
+        Value::Object(map) => map.get("path").and_then(Value::as_str).map(String::from),
+        _ => None,
+    };
+    match filters {
+        Some(Value::Array(items)) => items.iter().filter_map(entry).collect(),
+        Some(value) => entry(value).into_iter().collect(),
+        None => Vec::new(),
+    }
+}
+
+/// The `FORMAT` Lua scripts see for a format, as Pandoc names it.
+pub(crate) fn lua_format(format: &Format) -> &'static str {
+    match format.identifier {
+        FormatIdentifier::Pdf => "latex",
+        identifier => identifier.as_str(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_filter_entries() {
+        assert_eq!(
+            filter_entries(Some(
+                &json!(["a.lua", {"path": "b.lua", "at": "pre-ast"}, 3])
+            )),
+            ["a.lua", "b.lua"]
+        );
+        assert_eq!(filter_entries(Some(&json!("a.lua"))), ["a.lua"]);
+        assert!(filter_entries(None).is_empty());
+    }
+
+    #[test]
+    fn test_lua_format() {
+        assert_eq!(lua_format(&Format::pdf()), "latex");
+        assert_eq!(lua_format(&Format::html()), "html");
+        assert_eq!(lua_format(&Format::typst()), "typst");
+    }
+}
@@ -12,14 +12,25 @@
 //! - [`AppendixStructureTransform`] - Consolidates appendix content into single container
 //! - [`CalloutTransform`] - Converts callout Divs to CustomNodes
 //! - [`CalloutResolveTransform`] - Resolves Callout CustomNodes to standard Div structure
+//! - [`CrossrefTransform`] - Numbers labeled targets and resolves `@fig-`, `@tbl-`, ... references
 //! - [`FootnotesTransform`] - Extracts footnotes and creates footnotes section
+//! - [`HighlightStyleTransform`] - Produces the syntax highlighting stylesheet
+//! - [`ListingTransform`] - Renders `listing:` pages (blog post indexes)
+//! - [`LuaFilterTransform`] - Runs the document's Lua filters (native only)
 //! - [`MetadataNormalizeTransform`] - Normalizes document metadata (adds pagetitle, etc.)
+//! - [`NativeFilterTransform`] - Runs a native Rust filter
+//! - [`NumberSectionsTransform`] - Assigns section numbers to headers
+//! - [`PanelTransform`] - Restructures tabset and layout panel Divs
 //! - [`ResourceCollectorTransform`] - Collects resource dependencies (images, etc.)
+//! - [`RevealjsSlidesTransform`] - Splits `format: revealjs` documents into slides
 //! - [`SectionizeTransform`] - Wraps headers in section Divs (analogous to Pandoc's --section-divs)
-//! - [`ShortcodeResolveTransform`] - Resolves shortcodes to their content
+//! - [`ShortcodeResolveTransform`] - Resolves shortcodes to their content, using the
+//!   handlers of a [`ShortcodeRegistry`]
+//! - [`SocialMetadataTransform`] - Renders OpenGraph, Twitter card and favicon metadata
 //! - [`TitleBlockTransform`] - Adds title header from metadata if not present
 //! - [`TocGenerateTransform`] - Generates TOC from document headings
 //! - [`TocRenderTransform`] - Renders TOC metadata to HTML
+//! - [`WebsiteNavigationTransform`] - Renders website navbar, sidebar, breadcrumbs and footer
 //!
 //! These transforms implement [`AstTransform`](crate::transform::AstTransform) and
 //! can be added to a [`TransformPipeline`](crate::transform::TransformPipeline).
@@ -28,24 +39,58 @@ mod appendix;
 mod callout;
 mod callout_resolve;
 mod config;
+mod crossref;
 mod footnotes;
+mod highlight_style;
+mod listing;
+#[cfg(not(target_arch = "wasm32"))]
+mod lua_filter;
 mod metadata_normalize;
+mod native_filter;
+mod number_sections;
+mod panels;
 mod resource_collector;
+mod revealjs;
 mod sectionize;
 mod shortcode_resolve;
+mod shortcodes;
+mod social_metadata;
 mod title_block;
 mod toc_generate;
 mod toc_render;
+mod website_navigation;
 
 pub use appendix::AppendixStructureTransform;
 pub use callout::CalloutTransform;
 pub use callout_resolve::CalloutResolveTransform;
 pub use config::{AppendixStyle, ReferenceLocation};
+pub use crossref::{
+    CrossrefEntry, CrossrefIndex, CrossrefKind, CrossrefTransform, ProjectCrossrefs,
+};
 pub use footnotes::FootnotesTransform;
+pub use highlight_style::HighlightStyleTransform;
+pub use listing::ListingTransform;
+#[cfg(not(target_arch = "wasm32"))]
+pub use lua_filter::{FilterPhase, LuaFilterTransform};
 pub use metadata_normalize::MetadataNormalizeTransform;
+pub use native_filter::NativeFilterTransform;
+pub use number_sections::NumberSectionsTransform;
+pub use panels::PanelTransform;
 pub use resource_collector::ResourceCollectorTransform;
+pub use revealjs::RevealjsSlidesTransform;
 pub use sectionize::SectionizeTransform;
-pub use shortcode_resolve::ShortcodeResolveTransform;
+pub use shortcode_resolve::{
+    IncludeShortcodeHandler, MetaShortcodeHandler, ShortcodeContext, ShortcodeError,
+    ShortcodeHandler, ShortcodeRegistry, ShortcodeResolveTransform, ShortcodeResult,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use shortcodes::LuaShortcodeHandler;
+pub use shortcodes::{
+    EmbedShortcodeHandler, EnvShortcodeHandler, KbdShortcodeHandler, PagebreakShortcodeHandler,
+    VideoShortcodeHandler,
+};
+pub use social_metadata::SocialMetadataTransform;
 pub use title_block::TitleBlockTransform;
 pub use toc_generate::TocGenerateTransform;
 pub use toc_render::TocRenderTransform;
+pub use website_navigation::WebsiteNavigationTransform;
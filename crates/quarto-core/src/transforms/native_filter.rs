@@ -0,0 +1,151 @@
+/*
+ * native_filter.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Transform that runs a native Rust filter.
+ */
+
+//! Native filter transform.
+//!
+//! Wraps a filter written against pampa's native filter API
+//! ([`pampa::filters::native`]) so it can run as a step of the render
+//! pipeline. The filter edits the AST in place; there is no JSON round trip
+//! as with JSON or Lua filters.
+//!
+//! Filters can be added from a [`FilterRegistry`], which lets extensions
+//! compiled as Rust register filters by name:
+//!
+//! ```ignore
+//! let mut registry = FilterRegistry::new();
+//! registry.register("my-filter", || Box::new(MyFilter::default()));
+//!
+//! pipeline.push(Box::new(
+//!     NativeFilterTransform::from_registry(&registry, "my-filter").unwrap(),
+//! ));
+//! ```
+
+use pampa::filters::native::{Filter, FilterFactory, FilterRegistry, walk_document};
+use quarto_pandoc_types::pandoc::Pandoc;
+use std::sync::Arc;
+
+use crate::Result;
+use crate::render::RenderContext;
+use crate::transform::AstTransform;
+
+/// Transform that runs a native Rust filter on the document.
+///
+/// A fresh filter is created for each document, so filters can keep
+/// per-document state.
+pub struct NativeFilterTransform {
+    name: String,
+    factory: FilterFactory,
+}
+
+impl NativeFilterTransform {
+    /// Create a transform from a filter factory.
+    pub fn new<F>(name: impl Into<String>, factory: F) -> Self
+    where
+        F: Fn() -> Box<dyn Filter> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            factory: Arc::new(factory),
+        }
+    }
+
+    /// Create a transform for the filter registered under `name`.
+    ///
+    /// Returns `None` if no filter is registered under `name`.
+    pub fn from_registry(registry: &FilterRegistry, name: &str) -> Option<Self> {
+        registry.factory(name).map(|factory| Self {
+            name: name.to_string(),
+            factory,
+        })
+    }
+}
+
+impl AstTransform for NativeFilterTransform {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn transform(&self, ast: &mut Pandoc, _ctx: &mut RenderContext) -> Result<()> {
+        let mut filter = (self.factory)();
+        // A filter that stops early has finished its work; that's not an error
+        let _ = walk_document(filter.as_mut(), ast);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pampa::filters::native::Visit;
+    use quarto_pandoc_types::ConfigValue;
+    use quarto_pandoc_types::block::{Block, Paragraph};
+    use quarto_pandoc_types::inline::{Inline, Str};
+    use quarto_source_map::SourceInfo;
+    use std::path::PathBuf;
+
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::render::{BinaryDependencies, RenderContext};
+
+    struct Shout;
+
+    impl Filter for Shout {
+        fn name(&self) -> &str {
+            "shout"
+        }
+
+        fn visit_str(&mut self, node: &mut Str) -> Visit<Inline> {
+            node.text = node.text.to_uppercase();
+            Visit::Continue
+        }
+    }
+
+    fn make_test_project() -> ProjectContext {
+        ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![DocumentInfo::from_path("/project/doc.qmd")],
+            output_dir: PathBuf::from("/project"),
+        }
+    }
+
+    #[test]
+    fn test_runs_registered_filter() {
+        let mut registry = FilterRegistry::new();
+        registry.register("shout", || Box::new(Shout));
+        assert!(NativeFilterTransform::from_registry(&registry, "missing").is_none());
+        let transform = NativeFilterTransform::from_registry(&registry, "shout").unwrap();
+        assert_eq!(transform.name(), "shout");
+
+        let mut ast = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![Block::Paragraph(Paragraph {
+                content: vec![Inline::Str(Str {
+                    text: "hello".to_string(),
+                    source_info: SourceInfo::default(),
+                })],
+                source_info: SourceInfo::default(),
+            })],
+        };
+
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+        transform.transform(&mut ast, &mut ctx).unwrap();
+
+        let Block::Paragraph(para) = &ast.blocks[0] else {
+            panic!("expected Paragraph");
+        };
+        let Inline::Str(s) = &para.content[0] else {
+            panic!("expected Str");
+        };
+        assert_eq!(s.text, "HELLO");
+    }
+}
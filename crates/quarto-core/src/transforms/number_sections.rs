@@ -0,0 +1,430 @@
+/*
+ * number_sections.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Transform that numbers document sections.
+ */
+
+//! Section numbering transform.
+//!
+//! When `number-sections: true` is set, this transform gives every heading a
+//! hierarchical section number ("1", "1.2", "1.2.1", ...) and stores it in
+//! the header's `number` attribute. Writers render the number from there:
+//! the HTML writer emits it as `data-number` plus a
+//! `<span class="header-section-number">` before the heading text, and the
+//! TOC picks it up for its entries.
+//!
+//! ## Configuration
+//!
+//! - `number-sections`: `true` to enable numbering
+//! - `number-depth`: Deepest level to number, counted from the top numbered
+//!   level (default: all levels)
+//! - `number-offset`: Starting counters, either a single integer for the top
+//!   level or a list such as `[2, 1]` (Pandoc semantics: the first heading at
+//!   a level increments its counter, so an offset of `2` numbers the first
+//!   section "3")
+//!
+//! In a book project, chapters are numbered by default and numbers are
+//! prefixed with the chapter number: the chapter's level-one heading gets
+//! "2" and its sections "2.1", "2.1.1", .... The preface and part pages are
+//! not numbered.
+//!
+//! Headings with the `unnumbered` class get no number and don't advance the
+//! counters. As in the TOC, numbering starts at the shallowest numbered
+//! heading level, so a document whose top headings are `##` is numbered
+//! "1", "1.1", ... rather than "0.1", "0.1.1", ....
+
+use quarto_pandoc_types::block::Block;
+use quarto_pandoc_types::pandoc::Pandoc;
+
+use crate::Result;
+use crate::book::Book;
+use crate::render::RenderContext;
+use crate::transform::AstTransform;
+
+/// Transform that assigns section numbers to headers.
+///
+/// Runs before [`TitleBlockTransform`](super::TitleBlockTransform), so the
+/// title header it adds isn't numbered, and before
+/// [`SectionizeTransform`](super::SectionizeTransform), which copies the
+/// `number` attribute onto the section Div. Documents that are already
+/// sectionized are handled too: section Divs get the number of their header.
+pub struct NumberSectionsTransform;
+
+impl NumberSectionsTransform {
+    /// Create a new section numbering transform.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for NumberSectionsTransform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AstTransform for NumberSectionsTransform {
+    fn name(&self) -> &str {
+        "number-sections"
+    }
+
+    fn transform(&self, ast: &mut Pandoc, ctx: &mut RenderContext) -> Result<()> {
+        let chapter = Book::from_project(ctx.project).and_then(|book| {
+            book.chapter(&ctx.document.input)
+                .map(|(_, chapter)| chapter.number.clone())
+        });
+
+        // Book chapters are numbered unless turned off
+        let enabled = ctx
+            .format_metadata("number-sections")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(chapter.is_some());
+
+        // Unnumbered book pages (the preface, part pages) get no numbers
+        if !enabled || chapter == Some(None) {
+            return Ok(());
+        }
+        let chapter = chapter.flatten();
+
+        let depth = ctx
+            .format_metadata("number-depth")
+            .and_then(|v| v.as_i64())
+            .map(|d| d.max(0) as usize);
+
+        let offset: Vec<usize> = match ctx.format_metadata("number-offset") {
+            Some(serde_json::Value::Array(items)) => items
+                .iter()
+                .map(|v| v.as_i64().unwrap_or(0).max(0) as usize)
+                .collect(),
+            Some(v) => v
+                .as_i64()
+                .map(|n| vec![n.max(0) as usize])
+                .unwrap_or_default(),
+            None => Vec::new(),
+        };
+
+        // In a chapter, level one is the chapter itself
+        let top = match &chapter {
+            Some(_) => 2,
+            None => match top_level(&ast.blocks) {
+                Some(top) => top,
+                None => return Ok(()),
+            },
+        };
+
+        let mut numbering = Numbering {
+            top,
+            depth,
+            counters: offset,
+            chapter,
+        };
+        numbering.number_blocks(&mut ast.blocks);
+
+        Ok(())
+    }
+}
+
+/// Section counters while walking the document.
+struct Numbering {
+    /// Level of the shallowest numbered heading
+    top: usize,
+    /// Deepest relative level to number, if limited
+    depth: Option<usize>,
+    /// Counter for each level, starting at the top level
+    counters: Vec<usize>,
+    /// Book chapter number, numbering level-one headings and prefixing
+    /// all other numbers
+    chapter: Option<String>,
+}
+
+impl Numbering {
+    fn number_blocks(&mut self, blocks: &mut [Block]) {
+        for block in blocks {
+            match block {
+                Block::Header(header) => {
+                    if is_unnumbered(&header.attr.1) {
+                        continue;
+                    }
+                    if let Some(number) = self.next(header.level) {
+                        header.attr.2.insert("number".to_string(), number);
+                    }
+                }
+                Block::Div(div) => {
+                    self.number_blocks(&mut div.content);
+
+                    // A section Div carries the number of its header
+                    if div.attr.1.iter().any(|c| c == "section")
+                        && let Some(Block::Header(header)) = div.content.first()
+                        && let Some(number) = header.attr.2.get("number")
+                    {
+                        let number = number.clone();
+                        div.attr.2.insert("number".to_string(), number);
+                    }
+                }
+                Block::BlockQuote(bq) => {
+                    self.number_blocks(&mut bq.content);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Advance the counters for a heading at `level` and return its number,
+    /// or `None` if the heading is deeper than `number-depth`.
+    fn next(&mut self, level: usize) -> Option<String> {
+        if let Some(chapter) = &self.chapter
+            && level == 1
+        {
+            self.counters.clear();
+            return Some(chapter.clone());
+        }
+
+        // Headings above the top level can only be unnumbered ones
+        let depth = level.checked_sub(self.top)? + 1;
+        // The chapter number counts as a level for `number-depth`
+        let numbered_depth = depth + usize::from(self.chapter.is_some());
+        if self.depth.is_some_and(|max| numbered_depth > max) {
+            return None;
+        }
+
+        if self.counters.len() < depth {
+            self.counters.resize(depth, 0);
+        }
+        self.counters[depth - 1] += 1;
+        self.counters.truncate(depth);
+
+        let number = self
+            .counters
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(".");
+        Some(match &self.chapter {
+            Some(chapter) => format!("{}.{}", chapter, number),
+            None => number,
+        })
+    }
+}
+
+/// The level of the shallowest numbered heading, if there is one.
+fn top_level(blocks: &[Block]) -> Option<usize> {
+    blocks
+        .iter()
+        .filter_map(|block| match block {
+            Block::Header(header) if !is_unnumbered(&header.attr.1) => Some(header.level),
+            Block::Div(div) => top_level(&div.content),
+            Block::BlockQuote(bq) => top_level(&bq.content),
+            _ => None,
+        })
+        .min()
+}
+
+fn is_unnumbered(classes: &[String]) -> bool {
+    classes.iter().any(|c| c == "unnumbered")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::render::BinaryDependencies;
+    use hashlink::LinkedHashMap;
+    use quarto_pandoc_types::block::{Div, Header, Paragraph};
+    use quarto_pandoc_types::inline::{Inline, Str};
+    use quarto_source_map::SourceInfo;
+    use std::path::PathBuf;
+
+    fn make_test_project() -> ProjectContext {
+        ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![DocumentInfo::from_path("/project/doc.qmd")],
+            output_dir: PathBuf::from("/project"),
+        }
+    }
+
+    fn make_header(level: usize, classes: Vec<&str>, text: &str) -> Block {
+        Block::Header(Header {
+            level,
+            attr: (
+                String::new(),
+                classes.into_iter().map(String::from).collect(),
+                LinkedHashMap::new(),
+            ),
+            content: vec![Inline::Str(Str {
+                text: text.to_string(),
+                source_info: SourceInfo::default(),
+            })],
+            source_info: SourceInfo::default(),
+            attr_source: quarto_pandoc_types::attr::AttrSourceInfo::empty(),
+        })
+    }
+
+    fn make_para(text: &str) -> Block {
+        Block::Paragraph(Paragraph {
+            content: vec![Inline::Str(Str {
+                text: text.to_string(),
+                source_info: SourceInfo::default(),
+            })],
+            source_info: SourceInfo::default(),
+        })
+    }
+
+    fn run(blocks: Vec<Block>, metadata: serde_json::Value) -> Vec<Block> {
+        let mut ast = Pandoc {
+            meta: quarto_pandoc_types::ConfigValue::default(),
+            blocks,
+        };
+
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let format = Format::html().with_metadata(metadata);
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+
+        let transform = NumberSectionsTransform::new();
+        transform.transform(&mut ast, &mut ctx).unwrap();
+        ast.blocks
+    }
+
+    fn numbers(blocks: &[Block]) -> Vec<Option<&str>> {
+        blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Header(header) => Some(header.attr.2.get("number").map(String::as_str)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let blocks = run(vec![make_header(1, vec![], "Intro")], serde_json::json!({}));
+        assert_eq!(numbers(&blocks), vec![None]);
+    }
+
+    #[test]
+    fn test_numbers_headers() {
+        let blocks = run(
+            vec![
+                make_header(2, vec![], "Intro"),
+                make_para("Content."),
+                make_header(3, vec![], "Background"),
+                make_header(2, vec!["unnumbered"], "Aside"),
+                make_header(3, vec![], "Details"),
+                make_header(2, vec![], "Methods"),
+                make_header(4, vec![], "Deep"),
+            ],
+            serde_json::json!({ "number-sections": true }),
+        );
+        assert_eq!(
+            numbers(&blocks),
+            vec![
+                Some("1"),
+                Some("1.1"),
+                None,
+                Some("1.2"),
+                Some("2"),
+                Some("2.0.1")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_number_depth_and_offset() {
+        let blocks = run(
+            vec![
+                make_header(1, vec![], "Intro"),
+                make_header(2, vec![], "Background"),
+                make_header(3, vec![], "Details"),
+                make_header(1, vec![], "Methods"),
+            ],
+            serde_json::json!({
+                "number-sections": true,
+                "number-depth": 2,
+                "number-offset": [2, 4]
+            }),
+        );
+        assert_eq!(
+            numbers(&blocks),
+            vec![Some("3"), Some("3.1"), None, Some("4")]
+        );
+    }
+
+    #[test]
+    fn test_section_div_gets_header_number() {
+        let section = Block::Div(Div {
+            attr: (
+                "intro".to_string(),
+                vec!["section".to_string()],
+                LinkedHashMap::new(),
+            ),
+            content: vec![make_header(1, vec![], "Intro"), make_para("Content.")],
+            source_info: SourceInfo::default(),
+            attr_source: quarto_pandoc_types::attr::AttrSourceInfo::empty(),
+        });
+        let blocks = run(
+            vec![section],
+            serde_json::json!({ "number-sections": true }),
+        );
+
+        let Block::Div(div) = &blocks[0] else {
+            panic!("expected Div");
+        };
+        assert_eq!(div.attr.2.get("number").map(String::as_str), Some("1"));
+        assert_eq!(numbers(&div.content), vec![Some("1")]);
+    }
+
+    fn run_in_book(blocks: Vec<Block>, chapter: &str) -> Vec<Block> {
+        let mut ast = Pandoc {
+            meta: quarto_pandoc_types::ConfigValue::default(),
+            blocks,
+        };
+
+        let project = ProjectContext {
+            config: Some(crate::project::ProjectConfig {
+                project_type: crate::project::ProjectType::Book,
+                raw: serde_json::json!({
+                    "book": { "chapters": ["index.qmd", "intro.qmd", "methods.qmd"] }
+                }),
+                ..Default::default()
+            }),
+            is_single_file: false,
+            ..make_test_project()
+        };
+        let doc = DocumentInfo::from_path(format!("/project/{}", chapter));
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+
+        NumberSectionsTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+        ast.blocks
+    }
+
+    #[test]
+    fn test_book_chapters_prefix_numbers() {
+        let chapter = || {
+            vec![
+                make_header(1, vec![], "Methods"),
+                make_header(2, vec![], "Data"),
+                make_header(3, vec![], "Sources"),
+                make_header(2, vec![], "Models"),
+            ]
+        };
+
+        let blocks = run_in_book(chapter(), "methods.qmd");
+        assert_eq!(
+            numbers(&blocks),
+            vec![Some("2"), Some("2.1"), Some("2.1.1"), Some("2.2")]
+        );
+
+        // The preface is not numbered
+        let blocks = run_in_book(chapter(), "index.qmd");
+        assert_eq!(numbers(&blocks), vec![None, None, None, None]);
+    }
+}
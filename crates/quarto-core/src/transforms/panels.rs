@@ -0,0 +1,646 @@
+/*
+ * panels.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Transform that restructures tabset and layout panels.
+ */
+
+//! Panel transform: tabsets and layouts.
+//!
+//! ## Tabsets
+//!
+//! A `::: {.panel-tabset}` Div is split into tabs at its first heading
+//! level: each heading starts a tab, titled by the heading, holding the
+//! blocks up to the next heading of that level or above.
+//!
+//! ```text
+//! Div.panel-tabset
+//!   Div.tab-pane
+//!     Header.unnumbered.unlisted [tab title]
+//!     [tab content...]
+//!   Div.tab-pane
+//!     ...
+//! ```
+//!
+//! The HTML writer renders this as Bootstrap tab markup (a `role="tablist"`
+//! nav plus `role="tabpanel"` panes). Tab headings are marked `unnumbered`
+//! and `unlisted` so they stay out of section numbering and the TOC.
+//!
+//! ## Layouts
+//!
+//! A Div with the `panel-layout` class or a layout attribute arranges its
+//! child blocks in a CSS grid:
+//!
+//! - `layout-ncol`: Number of columns
+//! - `layout-nrow`: Number of rows (columns are derived from the cell count)
+//! - `layout`: Explicit rows of relative widths, e.g. `[[1,1],[1]]` or
+//!   `[40,-20,40]` for a single row; negative widths are empty spacers
+//! - `layout-valign`: Vertical alignment of cells (`top`, `center`, `bottom`)
+//!
+//! ```text
+//! Div.quarto-layout-panel
+//!   Div.quarto-layout-row {style="grid-template-columns: 1fr 1fr;"}
+//!     Div.quarto-layout-cell
+//!       [block]
+//! ```
+//!
+//! A Div without layout attributes puts all its blocks in one row.
+//!
+//! ## Dependencies
+//!
+//! When a document has tabsets, the tab script and styles are stored as the
+//! `js:panel-tabset` and `css:panel-tabset` artifacts; layouts store
+//! `css:panel-layout`. The same content is inlined at `rendered.panels` for
+//! the HTML template. The script handles tab switching without Bootstrap's
+//! JavaScript.
+
+use hashlink::LinkedHashMap;
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_pandoc_types::ConfigValue;
+use quarto_pandoc_types::attr::{Attr, AttrSourceInfo};
+use quarto_pandoc_types::block::{Block, Div};
+use quarto_pandoc_types::pandoc::Pandoc;
+use quarto_source_map::SourceInfo;
+
+use crate::Result;
+use crate::artifact::Artifact;
+use crate::render::RenderContext;
+use crate::transform::AstTransform;
+
+/// Styles for tabsets, matching Bootstrap's `nav-tabs` look.
+const TABSET_CSS: &str = r#".panel-tabset > .nav-tabs {
+  display: flex;
+  flex-wrap: wrap;
+  list-style: none;
+  margin: 0;
+  padding-left: 0;
+  border-bottom: 1px solid #dee2e6;
+}
+.panel-tabset > .nav-tabs .nav-link {
+  display: block;
+  margin-bottom: -1px;
+  padding: 0.5rem 1rem;
+  border: 1px solid transparent;
+  text-decoration: none;
+}
+.panel-tabset > .nav-tabs .nav-link.active {
+  border-color: #dee2e6 #dee2e6 #fff;
+  background-color: #fff;
+}
+.panel-tabset > .tab-content {
+  padding: 1em;
+  border: 1px solid #dee2e6;
+  border-top: none;
+}
+.panel-tabset > .tab-content > .tab-pane {
+  display: none;
+}
+.panel-tabset > .tab-content > .tab-pane.active {
+  display: block;
+}
+"#;
+
+/// Tab switching for tabsets.
+const TABSET_JS: &str = r#"document.addEventListener("DOMContentLoaded", function () {
+  document.querySelectorAll('.panel-tabset [role="tab"]').forEach(function (tab) {
+    tab.addEventListener("click", function (event) {
+      event.preventDefault();
+      var list = tab.closest('[role="tablist"]');
+      list.querySelectorAll('[role="tab"]').forEach(function (other) {
+        var selected = other === tab;
+        other.classList.toggle("active", selected);
+        other.setAttribute("aria-selected", selected ? "true" : "false");
+        var pane = document.getElementById(other.getAttribute("aria-controls"));
+        if (pane) {
+          pane.classList.toggle("active", selected);
+        }
+      });
+    });
+  });
+});
+"#;
+
+/// Styles for layout panels. Rows collapse to one column on small screens.
+const LAYOUT_CSS: &str = r#".quarto-layout-panel {
+  margin-bottom: 1em;
+}
+.quarto-layout-row {
+  display: grid;
+  gap: 1em;
+}
+.quarto-layout-cell {
+  min-width: 0;
+}
+.quarto-layout-cell img {
+  max-width: 100%;
+}
+@media (max-width: 767.98px) {
+  .quarto-layout-row {
+    grid-template-columns: 1fr !important;
+  }
+}
+"#;
+
+/// Attributes that control the layout and are dropped from the output.
+const LAYOUT_ATTRIBUTES: &[&str] = &["layout", "layout-ncol", "layout-nrow", "layout-valign"];
+
+/// Transform that restructures tabset and layout panel Divs.
+///
+/// Runs before [`SectionizeTransform`](super::SectionizeTransform) and
+/// [`NumberSectionsTransform`](super::NumberSectionsTransform), so tab
+/// headings are inside their panes before sections and numbers are
+/// assigned. Only applies to HTML formats.
+pub struct PanelTransform;
+
+impl PanelTransform {
+    /// Create a new panel transform.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for PanelTransform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AstTransform for PanelTransform {
+    fn name(&self) -> &str {
+        "panels"
+    }
+
+    fn transform(&self, ast: &mut Pandoc, ctx: &mut RenderContext) -> Result<()> {
+        if !ctx.format.is_html() {
+            return Ok(());
+        }
+
+        let mut panels = Panels::default();
+        panels.resolve_blocks(&mut ast.blocks);
+        ctx.diagnostics.append(&mut panels.diagnostics);
+
+        let mut head = Vec::new();
+        if panels.has_tabset {
+            ctx.artifacts.store(
+                "css:panel-tabset",
+                Artifact::from_string(TABSET_CSS, "text/css"),
+            );
+            ctx.artifacts.store(
+                "js:panel-tabset",
+                Artifact::from_string(TABSET_JS, "text/javascript"),
+            );
+            head.push(format!("<style>\n{}</style>", TABSET_CSS));
+            head.push(format!("<script>\n{}</script>", TABSET_JS));
+        }
+        if panels.has_layout {
+            ctx.artifacts.store(
+                "css:panel-layout",
+                Artifact::from_string(LAYOUT_CSS, "text/css"),
+            );
+            head.push(format!("<style>\n{}</style>", LAYOUT_CSS));
+        }
+
+        if !head.is_empty() {
+            ast.meta.insert_path(
+                &["rendered", "panels"],
+                ConfigValue::new_string(head.join("\n"), SourceInfo::default()),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// State collected while restructuring panels.
+#[derive(Default)]
+struct Panels {
+    has_tabset: bool,
+    has_layout: bool,
+    diagnostics: Vec<DiagnosticMessage>,
+}
+
+impl Panels {
+    fn resolve_blocks(&mut self, blocks: &mut [Block]) {
+        for block in blocks {
+            match block {
+                Block::Div(div) => {
+                    // Inner panels first, so nested tabsets and layouts work
+                    self.resolve_blocks(&mut div.content);
+                    if has_class(&div.attr, "panel-tabset") {
+                        self.resolve_tabset(div);
+                    } else if is_layout(&div.attr) {
+                        self.resolve_layout(div);
+                    }
+                }
+                Block::BlockQuote(bq) => self.resolve_blocks(&mut bq.content),
+                _ => {}
+            }
+        }
+    }
+
+    /// Split a tabset's content into tab panes at its first heading level.
+    fn resolve_tabset(&mut self, div: &mut Div) {
+        let Some(level) = div.content.iter().find_map(|block| match block {
+            Block::Header(header) => Some(header.level),
+            _ => None,
+        }) else {
+            self.diagnostics.push(
+                DiagnosticMessageBuilder::warning("Tabset without tabs")
+                    .problem("This `.panel-tabset` has no headings to make tabs from")
+                    .add_hint("Start each tab with a heading, e.g. `## Tab title`")
+                    .with_location(div.source_info.clone())
+                    .build(),
+            );
+            return;
+        };
+
+        let mut content = Vec::new();
+        let mut panes: Vec<Div> = Vec::new();
+        for block in std::mem::take(&mut div.content) {
+            match block {
+                Block::Header(mut header) if header.level <= level => {
+                    for class in ["unnumbered", "unlisted"] {
+                        if !header.attr.1.iter().any(|c| c == class) {
+                            header.attr.1.push(class.to_string());
+                        }
+                    }
+                    panes.push(Div {
+                        attr: (
+                            String::new(),
+                            vec!["tab-pane".to_string()],
+                            LinkedHashMap::new(),
+                        ),
+                        source_info: header.source_info.clone(),
+                        content: vec![Block::Header(header)],
+                        attr_source: AttrSourceInfo::empty(),
+                    });
+                }
+                block => match panes.last_mut() {
+                    Some(pane) => pane.content.push(block),
+                    // Content before the first tab stays outside the panes
+                    None => content.push(block),
+                },
+            }
+        }
+
+        content.extend(panes.into_iter().map(Block::Div));
+        div.content = content;
+        self.has_tabset = true;
+    }
+
+    /// Arrange a layout Div's blocks in rows of grid cells.
+    fn resolve_layout(&mut self, div: &mut Div) {
+        let attrs = &div.attr.2;
+        let cells = div.content.len();
+
+        let rows = if let Some(layout) = attrs.get("layout") {
+            match parse_layout(layout) {
+                Some(rows) => rows,
+                None => {
+                    self.invalid_layout(
+                        div,
+                        format!("`layout=\"{}\"` is not a list of widths", layout),
+                    );
+                    return;
+                }
+            }
+        } else if let Some(ncol) = attrs.get("layout-ncol") {
+            match ncol.trim().parse::<usize>() {
+                Ok(ncol) if ncol > 0 => equal_rows(cells, ncol),
+                _ => {
+                    self.invalid_layout(
+                        div,
+                        format!("`layout-ncol=\"{}\"` is not a positive number", ncol),
+                    );
+                    return;
+                }
+            }
+        } else if let Some(nrow) = attrs.get("layout-nrow") {
+            match nrow.trim().parse::<usize>() {
+                Ok(nrow) if nrow > 0 => equal_rows(cells, cells.div_ceil(nrow).max(1)),
+                _ => {
+                    self.invalid_layout(
+                        div,
+                        format!("`layout-nrow=\"{}\"` is not a positive number", nrow),
+                    );
+                    return;
+                }
+            }
+        } else {
+            equal_rows(cells, cells.max(1))
+        };
+
+        let align_items = match attrs.get("layout-valign").map(String::as_str) {
+            Some("top") => Some("start"),
+            Some("center") => Some("center"),
+            Some("bottom") => Some("end"),
+            _ => None,
+        };
+
+        let mut blocks = std::mem::take(&mut div.content).into_iter();
+        let mut row_blocks = Vec::new();
+        for widths in rows {
+            let columns: Vec<String> = widths.iter().map(|w| format!("{}fr", w.abs())).collect();
+            let mut style = format!("grid-template-columns: {};", columns.join(" "));
+            if let Some(align) = align_items {
+                style.push_str(&format!(" align-items: {};", align));
+            }
+
+            let mut cells = Vec::new();
+            for width in widths {
+                if width < 0.0 {
+                    cells.push(layout_div(&["quarto-layout-spacer"], None, Vec::new()));
+                } else if let Some(block) = blocks.next() {
+                    cells.push(layout_div(&["quarto-layout-cell"], None, vec![block]));
+                }
+            }
+            row_blocks.push(layout_div(&["quarto-layout-row"], Some(style), cells));
+        }
+        // Blocks beyond the layout's cells go below the rows
+        row_blocks.extend(blocks);
+
+        div.content = row_blocks;
+        div.attr.1.retain(|c| c != "panel-layout");
+        div.attr.1.insert(0, "quarto-layout-panel".to_string());
+        div.attr
+            .2
+            .retain(|k, _| !LAYOUT_ATTRIBUTES.contains(&k.as_str()));
+        self.has_layout = true;
+    }
+
+    fn invalid_layout(&mut self, div: &Div, problem: String) {
+        self.diagnostics.push(
+            DiagnosticMessageBuilder::warning("Invalid panel layout")
+                .problem(problem)
+                .add_hint("Use e.g. `layout-ncol=2` or `layout=\"[[1,1],[1]]\"`")
+                .with_location(div.source_info.clone())
+                .build(),
+        );
+    }
+}
+
+fn has_class(attr: &Attr, class: &str) -> bool {
+    attr.1.iter().any(|c| c == class)
+}
+
+fn is_layout(attr: &Attr) -> bool {
+    has_class(attr, "panel-layout") || LAYOUT_ATTRIBUTES.iter().any(|k| attr.2.contains_key(*k))
+}
+
+/// Parse a `layout` attribute: a list of rows, or a single row, of widths.
+fn parse_layout(layout: &str) -> Option<Vec<Vec<f64>>> {
+    let value: serde_json::Value = serde_json::from_str(layout).ok()?;
+    let items = value.as_array()?;
+    let rows: Vec<&serde_json::Value> = if items.iter().all(|item| item.is_number()) {
+        vec![&value]
+    } else {
+        items.iter().collect()
+    };
+
+    rows.into_iter()
+        .map(|row| {
+            let widths = row
+                .as_array()?
+                .iter()
+                .map(|w| w.as_f64().filter(|w| *w != 0.0))
+                .collect::<Option<Vec<f64>>>()?;
+            (!widths.is_empty()).then_some(widths)
+        })
+        .collect()
+}
+
+/// Rows of `ncol` equal-width cells holding `cells` blocks.
+fn equal_rows(cells: usize, ncol: usize) -> Vec<Vec<f64>> {
+    (0..cells.div_ceil(ncol).max(1))
+        .map(|_| vec![1.0; ncol])
+        .collect()
+}
+
+fn layout_div(classes: &[&str], style: Option<String>, content: Vec<Block>) -> Block {
+    let mut attrs = LinkedHashMap::new();
+    if let Some(style) = style {
+        attrs.insert("style".to_string(), style);
+    }
+    Block::Div(Div {
+        attr: (
+            String::new(),
+            classes.iter().map(|c| c.to_string()).collect(),
+            attrs,
+        ),
+        content,
+        source_info: SourceInfo::default(),
+        attr_source: AttrSourceInfo::empty(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::render::BinaryDependencies;
+    use quarto_pandoc_types::block::{Header, Paragraph};
+    use quarto_pandoc_types::inline::{Inline, Str};
+    use std::path::PathBuf;
+
+    fn make_test_project() -> ProjectContext {
+        ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![DocumentInfo::from_path("/project/doc.qmd")],
+            output_dir: PathBuf::from("/project"),
+        }
+    }
+
+    fn make_header(level: usize, text: &str) -> Block {
+        Block::Header(Header {
+            level,
+            attr: (String::new(), vec![], LinkedHashMap::new()),
+            content: vec![Inline::Str(Str {
+                text: text.to_string(),
+                source_info: SourceInfo::default(),
+            })],
+            source_info: SourceInfo::default(),
+            attr_source: AttrSourceInfo::empty(),
+        })
+    }
+
+    fn make_para(text: &str) -> Block {
+        Block::Paragraph(Paragraph {
+            content: vec![Inline::Str(Str {
+                text: text.to_string(),
+                source_info: SourceInfo::default(),
+            })],
+            source_info: SourceInfo::default(),
+        })
+    }
+
+    fn make_div(classes: Vec<&str>, attrs: Vec<(&str, &str)>, content: Vec<Block>) -> Block {
+        Block::Div(Div {
+            attr: (
+                String::new(),
+                classes.into_iter().map(String::from).collect(),
+                attrs
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ),
+            content,
+            source_info: SourceInfo::default(),
+            attr_source: AttrSourceInfo::empty(),
+        })
+    }
+
+    fn run(blocks: Vec<Block>) -> (Pandoc, Output) {
+        let mut ast = Pandoc {
+            meta: ConfigValue::default(),
+            blocks,
+        };
+
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+
+        PanelTransform::new().transform(&mut ast, &mut ctx).unwrap();
+        let output = Output {
+            artifacts: ["css:panel-tabset", "js:panel-tabset", "css:panel-layout"]
+                .into_iter()
+                .filter(|key| ctx.artifacts.contains(key))
+                .collect(),
+            diagnostics: ctx.diagnostics.len(),
+        };
+        (ast, output)
+    }
+
+    struct Output {
+        artifacts: Vec<&'static str>,
+        diagnostics: usize,
+    }
+
+    fn as_div(block: &Block) -> &Div {
+        match block {
+            Block::Div(div) => div,
+            _ => panic!("expected Div"),
+        }
+    }
+
+    #[test]
+    fn test_tabset_splits_at_headings() {
+        let tabset = make_div(
+            vec!["panel-tabset"],
+            vec![],
+            vec![
+                make_header(2, "R"),
+                make_para("r code"),
+                make_header(3, "Details"),
+                make_para("more"),
+                make_header(2, "Python"),
+                make_para("py code"),
+            ],
+        );
+        let (ast, output) = run(vec![tabset]);
+
+        let div = as_div(&ast.blocks[0]);
+        assert_eq!(div.content.len(), 2);
+        let first = as_div(&div.content[0]);
+        assert_eq!(first.attr.1, vec!["tab-pane"]);
+        assert_eq!(first.content.len(), 4);
+        let Block::Header(header) = &first.content[0] else {
+            panic!("expected Header");
+        };
+        assert_eq!(header.attr.1, vec!["unnumbered", "unlisted"]);
+        assert_eq!(as_div(&div.content[1]).content.len(), 2);
+
+        assert_eq!(
+            output.artifacts,
+            vec!["css:panel-tabset", "js:panel-tabset"]
+        );
+        assert!(ast.meta.contains_path(&["rendered", "panels"]));
+    }
+
+    #[test]
+    fn test_tabset_without_headings_warns() {
+        let tabset = make_div(vec!["panel-tabset"], vec![], vec![make_para("text")]);
+        let (ast, output) = run(vec![tabset]);
+
+        assert_eq!(output.diagnostics, 1);
+        assert!(output.artifacts.is_empty());
+        assert!(matches!(
+            as_div(&ast.blocks[0]).content[0],
+            Block::Paragraph(_)
+        ));
+    }
+
+    #[test]
+    fn test_layout_ncol() {
+        let layout = make_div(
+            vec![],
+            vec![("layout-ncol", "2"), ("layout-valign", "center")],
+            vec![make_para("a"), make_para("b"), make_para("c")],
+        );
+        let (ast, output) = run(vec![layout]);
+
+        let panel = as_div(&ast.blocks[0]);
+        assert_eq!(panel.attr.1, vec!["quarto-layout-panel"]);
+        assert!(panel.attr.2.is_empty());
+        assert_eq!(panel.content.len(), 2);
+
+        let row = as_div(&panel.content[0]);
+        assert_eq!(row.attr.1, vec!["quarto-layout-row"]);
+        assert_eq!(
+            row.attr.2.get("style").map(String::as_str),
+            Some("grid-template-columns: 1fr 1fr; align-items: center;")
+        );
+        assert_eq!(row.content.len(), 2);
+        assert_eq!(as_div(&panel.content[1]).content.len(), 1);
+
+        assert_eq!(output.artifacts, vec!["css:panel-layout"]);
+    }
+
+    #[test]
+    fn test_layout_with_widths_and_spacers() {
+        let layout = make_div(
+            vec!["panel-layout"],
+            vec![("layout", "[[40,-20,40],[1]]")],
+            vec![make_para("a"), make_para("b"), make_para("c")],
+        );
+        let (ast, _) = run(vec![layout]);
+
+        let panel = as_div(&ast.blocks[0]);
+        let first = as_div(&panel.content[0]);
+        assert_eq!(
+            first.attr.2.get("style").map(String::as_str),
+            Some("grid-template-columns: 40fr 20fr 40fr;")
+        );
+        let classes: Vec<&str> = first
+            .content
+            .iter()
+            .map(|cell| as_div(cell).attr.1[0].as_str())
+            .collect();
+        assert_eq!(
+            classes,
+            vec![
+                "quarto-layout-cell",
+                "quarto-layout-spacer",
+                "quarto-layout-cell"
+            ]
+        );
+        assert_eq!(as_div(&panel.content[1]).content.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_layout_warns() {
+        let layout = make_div(
+            vec![],
+            vec![("layout", "two columns")],
+            vec![make_para("a")],
+        );
+        let (ast, output) = run(vec![layout]);
+
+        assert_eq!(output.diagnostics, 1);
+        assert!(as_div(&ast.blocks[0]).attr.2.contains_key("layout"));
+    }
+}
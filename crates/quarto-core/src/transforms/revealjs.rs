@@ -0,0 +1,569 @@
+/*
+ * revealjs.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Transform that splits a document into reveal.js slides.
+ */
+
+//! Slides transform for `format: revealjs`.
+//!
+//! ## Slides
+//!
+//! The document is split into slides at headings of the slide level
+//! (`slide-level`, 2 by default) and at horizontal rules. Headings above the
+//! slide level start a vertical stack, with the heading on a title slide:
+//!
+//! ```text
+//! Div.section                            (vertical stack)
+//!   Div.section.title-slide.slide.level1
+//!     Header [section title]
+//!   Div.section.slide.level2
+//!     Header [slide title]
+//!     [slide content...]
+//! ```
+//!
+//! Slides take the heading's id, classes and attributes, so `background-*`
+//! attributes become reveal.js `data-background-*` attributes. Content
+//! before the first heading and after a horizontal rule forms an untitled
+//! slide. Since every top-level block ends up in a slide,
+//! [`SectionizeTransform`](super::SectionizeTransform) leaves the slides
+//! alone.
+//!
+//! ## Slide Content
+//!
+//! - A `. . .` paragraph pauses the slide: the content after it is wrapped
+//!   in a `.fragment` Div.
+//! - Lists are shown item by item inside `::: incremental` Divs, or
+//!   everywhere with `incremental: true` except inside `::: nonincremental`
+//!   Divs. Such lists are wrapped in an `.incremental` Div; the reveal.js
+//!   init script makes their items fragments.
+//! - `::: notes` Divs become `<aside class="notes">` speaker notes.
+//!
+//! ## Dependencies
+//!
+//! The reveal.js runtime is stored in the ArtifactStore (see
+//! [`crate::revealjs`]) and its markup inserted at `rendered.revealjs.head`
+//! and `rendered.revealjs.scripts` for the revealjs template.
+
+use hashlink::LinkedHashMap;
+use quarto_pandoc_types::ConfigValue;
+use quarto_pandoc_types::attr::{Attr, AttrSourceInfo};
+use quarto_pandoc_types::block::{Block, Div, Header, RawBlock};
+use quarto_pandoc_types::inline::Inline;
+use quarto_pandoc_types::pandoc::Pandoc;
+use quarto_source_map::SourceInfo;
+
+use crate::Result;
+use crate::format::FormatIdentifier;
+use crate::render::RenderContext;
+use crate::revealjs::store_revealjs_dependencies;
+use crate::transform::AstTransform;
+
+/// Heading level that starts slides when the format sets no `slide-level`.
+const DEFAULT_SLIDE_LEVEL: usize = 2;
+
+/// Transform that splits a document into reveal.js slides.
+///
+/// Runs before [`SectionizeTransform`](super::SectionizeTransform), which
+/// would otherwise nest the sections of headings below the slide level
+/// inside slides. Only applies to `format: revealjs`.
+pub struct RevealjsSlidesTransform;
+
+impl RevealjsSlidesTransform {
+    /// Create a new slides transform.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for RevealjsSlidesTransform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AstTransform for RevealjsSlidesTransform {
+    fn name(&self) -> &str {
+        "revealjs-slides"
+    }
+
+    fn transform(&self, ast: &mut Pandoc, ctx: &mut RenderContext) -> Result<()> {
+        if ctx.format.identifier != FormatIdentifier::Revealjs {
+            return Ok(());
+        }
+
+        let slide_level = ctx
+            .format
+            .get_metadata("slide-level")
+            .and_then(|v| v.as_u64())
+            .map_or(DEFAULT_SLIDE_LEVEL, |level| level as usize);
+        let incremental = ctx.format.get_metadata_bool("incremental").unwrap_or(false);
+
+        ast.blocks = split_slides(std::mem::take(&mut ast.blocks), slide_level, incremental);
+
+        let dependencies = store_revealjs_dependencies(ctx.format, &mut ctx.artifacts);
+        ast.meta.insert_path(
+            &["rendered", "revealjs", "head"],
+            ConfigValue::new_string(&dependencies.head, SourceInfo::default()),
+        );
+        ast.meta.insert_path(
+            &["rendered", "revealjs", "scripts"],
+            ConfigValue::new_string(&dependencies.scripts, SourceInfo::default()),
+        );
+
+        Ok(())
+    }
+}
+
+/// A slide being collected.
+struct Slide {
+    attr: Attr,
+    content: Vec<Block>,
+    /// Whether the slide was started by a heading (untitled slides without
+    /// content are dropped)
+    titled: bool,
+}
+
+impl Slide {
+    fn untitled(level: usize) -> Self {
+        Self {
+            attr: (String::new(), slide_classes(level), LinkedHashMap::new()),
+            content: Vec::new(),
+            titled: false,
+        }
+    }
+
+    /// A slide for `header`, which moves its attributes to the slide.
+    /// Headings without text (`## {background-color="black"}`) only set
+    /// the slide's attributes.
+    fn from_header(header: Header, slide_level: usize) -> Self {
+        let (id, classes, attrs) = header.attr;
+        let mut slide_classes = slide_classes(header.level);
+        if header.level < slide_level {
+            slide_classes.insert(1, "title-slide".to_string());
+        }
+        slide_classes.extend(classes);
+
+        let mut content = Vec::new();
+        if !header.content.is_empty() {
+            content.push(Block::Header(Header {
+                level: header.level,
+                attr: (String::new(), vec![], LinkedHashMap::new()),
+                content: header.content,
+                source_info: header.source_info,
+                attr_source: AttrSourceInfo::empty(),
+            }));
+        }
+        Self {
+            attr: (id, slide_classes, attrs),
+            content,
+            titled: true,
+        }
+    }
+
+    fn into_block(self, incremental: bool) -> Option<Block> {
+        if !self.titled && self.content.is_empty() {
+            return None;
+        }
+        Some(make_div(
+            self.attr,
+            slide_content(self.content, incremental),
+        ))
+    }
+}
+
+fn slide_classes(level: usize) -> Vec<String> {
+    vec![
+        "section".to_string(),
+        "slide".to_string(),
+        format!("level{}", level),
+    ]
+}
+
+fn make_div(attr: Attr, content: Vec<Block>) -> Block {
+    Block::Div(Div {
+        attr,
+        content,
+        source_info: SourceInfo::default(),
+        attr_source: AttrSourceInfo::empty(),
+    })
+}
+
+fn class_div(class: &str, content: Vec<Block>) -> Block {
+    make_div(
+        (String::new(), vec![class.to_string()], LinkedHashMap::new()),
+        content,
+    )
+}
+
+/// Split top-level blocks into slides and vertical stacks.
+fn split_slides(blocks: Vec<Block>, slide_level: usize, incremental: bool) -> Vec<Block> {
+    let mut output = Vec::new();
+    // The slides of the open vertical stack, if any
+    let mut stack: Option<Vec<Block>> = None;
+    let mut current: Option<Slide> = None;
+
+    fn close_slide(
+        current: &mut Option<Slide>,
+        stack: &mut Option<Vec<Block>>,
+        output: &mut Vec<Block>,
+        incremental: bool,
+    ) {
+        if let Some(block) = current.take().and_then(|s| s.into_block(incremental)) {
+            match stack {
+                Some(slides) => slides.push(block),
+                None => output.push(block),
+            }
+        }
+    }
+
+    fn close_stack(stack: &mut Option<Vec<Block>>, output: &mut Vec<Block>) {
+        match stack.take() {
+            Some(mut slides) if slides.len() == 1 => output.push(slides.remove(0)),
+            Some(slides) if !slides.is_empty() => output.push(class_div("section", slides)),
+            _ => {}
+        }
+    }
+
+    for block in blocks {
+        match block {
+            Block::Header(header) if header.level <= slide_level => {
+                close_slide(&mut current, &mut stack, &mut output, incremental);
+                if header.level < slide_level {
+                    close_stack(&mut stack, &mut output);
+                    stack = Some(Vec::new());
+                }
+                current = Some(Slide::from_header(header, slide_level));
+            }
+            Block::HorizontalRule(_) => {
+                close_slide(&mut current, &mut stack, &mut output, incremental);
+                current = Some(Slide::untitled(slide_level));
+            }
+            block => current
+                .get_or_insert_with(|| Slide::untitled(slide_level))
+                .content
+                .push(block),
+        }
+    }
+    close_slide(&mut current, &mut stack, &mut output, incremental);
+    close_stack(&mut stack, &mut output);
+    output
+}
+
+/// The content of a slide: pauses split it into fragments.
+fn slide_content(blocks: Vec<Block>, incremental: bool) -> Vec<Block> {
+    let mut segments: Vec<Vec<Block>> = vec![Vec::new()];
+    for block in blocks {
+        if is_pause(&block) {
+            segments.push(Vec::new());
+        } else if let Some(segment) = segments.last_mut() {
+            segment.push(block);
+        }
+    }
+
+    let mut segments = segments.into_iter();
+    let mut content = convert_blocks(segments.next().unwrap_or_default(), incremental, false);
+    for segment in segments {
+        content.push(class_div(
+            "fragment",
+            convert_blocks(segment, incremental, false),
+        ));
+    }
+    content
+}
+
+/// Whether `block` is a `. . .` pause.
+fn is_pause(block: &Block) -> bool {
+    let Block::Paragraph(para) = block else {
+        return false;
+    };
+    let mut dots = 0;
+    for inline in &para.content {
+        match inline {
+            Inline::Str(s) if s.text == "." => dots += 1,
+            Inline::Space(_) => {}
+            _ => return false,
+        }
+    }
+    dots == 3
+}
+
+/// Convert speaker notes and mark incremental lists.
+///
+/// `marked` is set for the content of an `.incremental` Div, whose lists
+/// the init script already finds.
+fn convert_blocks(blocks: Vec<Block>, incremental: bool, marked: bool) -> Vec<Block> {
+    let mut output = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Div(div) if has_class(&div.attr, "notes") => {
+                output.push(raw_html("<aside class=\"notes\">"));
+                output.extend(convert_blocks(div.content, false, false));
+                output.push(raw_html("</aside>"));
+            }
+            Block::Div(mut div) => {
+                let is_incremental = has_class(&div.attr, "incremental");
+                let incremental = if is_incremental {
+                    true
+                } else if has_class(&div.attr, "nonincremental") {
+                    false
+                } else {
+                    incremental
+                };
+                div.content = convert_blocks(div.content, incremental, is_incremental);
+                output.push(Block::Div(div));
+            }
+            list @ (Block::BulletList(_) | Block::OrderedList(_)) if incremental && !marked => {
+                output.push(class_div("incremental", vec![list]));
+            }
+            block => output.push(block),
+        }
+    }
+    output
+}
+
+fn has_class(attr: &Attr, class: &str) -> bool {
+    attr.1.iter().any(|c| c == class)
+}
+
+fn raw_html(text: &str) -> Block {
+    Block::RawBlock(RawBlock {
+        format: "html".to_string(),
+        text: text.to_string(),
+        source_info: SourceInfo::default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectContext};
+    use crate::render::BinaryDependencies;
+    use quarto_pandoc_types::block::{BulletList, HorizontalRule, Paragraph};
+    use quarto_pandoc_types::inline::{Space, Str};
+    use std::path::PathBuf;
+
+    fn make_test_project() -> ProjectContext {
+        ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![DocumentInfo::from_path("/project/slides.qmd")],
+            output_dir: PathBuf::from("/project"),
+        }
+    }
+
+    fn str_inline(text: &str) -> Inline {
+        Inline::Str(Str {
+            text: text.to_string(),
+            source_info: SourceInfo::default(),
+        })
+    }
+
+    fn make_header(level: usize, id: &str, text: &str) -> Block {
+        Block::Header(Header {
+            level,
+            attr: (id.to_string(), vec![], LinkedHashMap::new()),
+            content: if text.is_empty() {
+                vec![]
+            } else {
+                vec![str_inline(text)]
+            },
+            source_info: SourceInfo::default(),
+            attr_source: AttrSourceInfo::empty(),
+        })
+    }
+
+    fn make_para(text: &str) -> Block {
+        Block::Paragraph(Paragraph {
+            content: vec![str_inline(text)],
+            source_info: SourceInfo::default(),
+        })
+    }
+
+    fn make_pause() -> Block {
+        let space = || {
+            Inline::Space(Space {
+                source_info: SourceInfo::default(),
+            })
+        };
+        Block::Paragraph(Paragraph {
+            content: vec![
+                str_inline("."),
+                space(),
+                str_inline("."),
+                space(),
+                str_inline("."),
+            ],
+            source_info: SourceInfo::default(),
+        })
+    }
+
+    fn make_list() -> Block {
+        Block::BulletList(BulletList {
+            content: vec![vec![make_para("a")], vec![make_para("b")]],
+            source_info: SourceInfo::default(),
+        })
+    }
+
+    fn run_with_format(blocks: Vec<Block>, format: Format) -> (Pandoc, Vec<String>) {
+        let mut ast = Pandoc {
+            meta: ConfigValue::default(),
+            blocks,
+        };
+
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/slides.qmd");
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+
+        RevealjsSlidesTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+        let artifacts = ["js:revealjs", "css:revealjs", "js:revealjs-init"]
+            .into_iter()
+            .filter(|key| ctx.artifacts.contains(key))
+            .map(String::from)
+            .collect();
+        (ast, artifacts)
+    }
+
+    fn run(blocks: Vec<Block>) -> Pandoc {
+        run_with_format(blocks, Format::revealjs()).0
+    }
+
+    fn as_div(block: &Block) -> &Div {
+        match block {
+            Block::Div(div) => div,
+            _ => panic!("expected Div, got {:?}", block),
+        }
+    }
+
+    #[test]
+    fn test_splits_at_slide_level_and_rules() {
+        let ast = run(vec![
+            make_para("intro"),
+            make_header(2, "first", "First"),
+            make_para("one"),
+            make_header(3, "", "Detail"),
+            Block::HorizontalRule(HorizontalRule {
+                source_info: SourceInfo::default(),
+            }),
+            make_para("two"),
+        ]);
+
+        assert_eq!(ast.blocks.len(), 3);
+        let intro = as_div(&ast.blocks[0]);
+        assert_eq!(intro.attr.1, vec!["section", "slide", "level2"]);
+        assert_eq!(intro.content.len(), 1);
+
+        let first = as_div(&ast.blocks[1]);
+        assert_eq!(first.attr.0, "first");
+        // The level-3 heading stays on the slide
+        assert_eq!(first.content.len(), 3);
+        let Block::Header(header) = &first.content[0] else {
+            panic!("expected Header");
+        };
+        assert!(header.attr.0.is_empty());
+
+        let untitled = as_div(&ast.blocks[2]);
+        assert!(untitled.attr.0.is_empty());
+        assert!(matches!(untitled.content[0], Block::Paragraph(_)));
+    }
+
+    #[test]
+    fn test_vertical_stacks() {
+        let ast = run(vec![
+            make_header(1, "part", "Part"),
+            make_header(2, "a", "A"),
+            make_para("a"),
+            make_header(2, "b", ""),
+            make_para("b"),
+            make_header(1, "end", "End"),
+        ]);
+
+        assert_eq!(ast.blocks.len(), 2);
+        let stack = as_div(&ast.blocks[0]);
+        assert_eq!(stack.attr.1, vec!["section"]);
+        assert_eq!(stack.content.len(), 3);
+        assert_eq!(
+            as_div(&stack.content[0]).attr.1,
+            vec!["section", "title-slide", "slide", "level1"]
+        );
+        // A heading without text only contributes its attributes
+        let untitled = as_div(&stack.content[2]);
+        assert_eq!(untitled.attr.0, "b");
+        assert_eq!(untitled.content.len(), 1);
+
+        // A stack with just its title slide is not wrapped
+        assert_eq!(as_div(&ast.blocks[1]).attr.0, "end");
+    }
+
+    #[test]
+    fn test_pauses_and_notes() {
+        let ast = run(vec![
+            make_header(2, "s", "Slide"),
+            make_para("before"),
+            make_pause(),
+            make_para("after"),
+            class_div("notes", vec![make_para("say this")]),
+        ]);
+
+        let slide = as_div(&ast.blocks[0]);
+        assert_eq!(slide.content.len(), 3);
+        let fragment = as_div(&slide.content[2]);
+        assert_eq!(fragment.attr.1, vec!["fragment"]);
+        assert_eq!(fragment.content.len(), 4);
+        let Block::RawBlock(aside) = &fragment.content[1] else {
+            panic!("expected notes");
+        };
+        assert_eq!(aside.text, "<aside class=\"notes\">");
+    }
+
+    #[test]
+    fn test_incremental_lists() {
+        let format = Format::revealjs().with_metadata(serde_json::json!({ "incremental": true }));
+        let (ast, _) = run_with_format(
+            vec![
+                make_header(2, "s", "Slide"),
+                make_list(),
+                class_div("nonincremental", vec![make_list()]),
+            ],
+            format,
+        );
+        let slide = as_div(&ast.blocks[0]);
+        assert_eq!(as_div(&slide.content[1]).attr.1, vec!["incremental"]);
+        let non = as_div(&slide.content[2]);
+        assert!(matches!(non.content[0], Block::BulletList(_)));
+
+        // Lists directly in an `.incremental` Div are not wrapped again
+        let ast = run(vec![class_div("incremental", vec![make_list()])]);
+        let slide = as_div(&ast.blocks[0]);
+        assert!(matches!(
+            as_div(&slide.content[0]).content[0],
+            Block::BulletList(_)
+        ));
+    }
+
+    #[test]
+    fn test_stores_runtime() {
+        let (ast, artifacts) = run_with_format(vec![make_para("hi")], Format::revealjs());
+        assert_eq!(
+            artifacts,
+            vec!["js:revealjs", "css:revealjs", "js:revealjs-init"]
+        );
+        assert!(ast.meta.contains_path(&["rendered", "revealjs", "head"]));
+        assert!(ast.meta.contains_path(&["rendered", "revealjs", "scripts"]));
+    }
+
+    #[test]
+    fn test_skips_other_formats() {
+        let (ast, artifacts) = run_with_format(
+            vec![make_header(2, "s", "Slide"), make_para("text")],
+            Format::html(),
+        );
+        assert_eq!(ast.blocks.len(), 2);
+        assert!(artifacts.is_empty());
+    }
+}
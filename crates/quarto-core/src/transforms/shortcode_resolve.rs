@@ -14,6 +14,34 @@
 //!
 //! Currently supported:
 //! - `meta` - Insert metadata values from document frontmatter
+//! - `env` - Insert the value of an environment variable
+//! - `kbd` - Render a keyboard shortcut, optionally per operating system
+//! - `pagebreak` - Insert a page break
+//! - `video` - Embed a video (YouTube, Vimeo, or a video file)
+//! - `embed` - Embed the outputs of a cell from a Jupyter notebook
+//!
+//! `include` shortcodes are resolved when the document is parsed (see
+//! [`crate::include`]); one that is left here was not alone in its
+//! paragraph and is reported as misplaced.
+//!
+//! ## Handlers and the Registry
+//!
+//! Each shortcode is implemented by a [`ShortcodeHandler`], looked up by
+//! name in a [`ShortcodeRegistry`]. Handlers return inline or block
+//! content; block content replaces the enclosing paragraph when the
+//! shortcode stands alone in it. Handlers can report diagnostics through
+//! their [`ShortcodeContext`] in addition to returning an error.
+//!
+//! The registry is the extension point for user-defined shortcodes: hosts
+//! can register their own handlers, replacing any built-in of the same
+//! name, and build the transform with
+//! [`ShortcodeResolveTransform::with_registry`]. On native targets the
+//! transform also registers a [`LuaShortcodeHandler`] for each shortcode
+//! contributed by the extensions the document can see (see
+//! [`crate::extension::contributions`]); these take precedence over the
+//! transform's registry.
+//!
+//! [`LuaShortcodeHandler`]: super::LuaShortcodeHandler
 //!
 //! ## Error Handling
 //!
@@ -29,6 +57,10 @@
 //! - Metadata normalization sees resolved content, not shortcode placeholders
 
 use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+
 use quarto_pandoc_types::block::{
     Block, BlockQuote, BulletList, DefinitionList, Div, Figure, Header, LineBlock, OrderedList,
     Paragraph, Plain,
@@ -36,16 +68,22 @@ use quarto_pandoc_types::block::{
 use quarto_pandoc_types::config_value::{ConfigValue, ConfigValueKind};
 use quarto_pandoc_types::inline::{
     Cite, Code, Delete, EditComment, Emph, Highlight, Image, Inline, Insert, Link, Note, Quoted,
-    SmallCaps, Span, Str, Strikeout, Strong, Subscript, Superscript, Underline,
+    RawInline, SmallCaps, Span, Str, Strikeout, Strong, Subscript, Superscript, Underline,
 };
 use quarto_pandoc_types::pandoc::Pandoc;
 use quarto_pandoc_types::shortcode::{Shortcode, ShortcodeArg};
 use quarto_pandoc_types::table::Table;
 use quarto_source_map::SourceInfo;
+use quarto_system_runtime::SystemRuntime;
 
 use quarto_analysis::AnalysisContext;
 
+use super::shortcodes::{
+    EmbedShortcodeHandler, EnvShortcodeHandler, KbdShortcodeHandler, PagebreakShortcodeHandler,
+    VideoShortcodeHandler,
+};
 use crate::Result;
+use crate::format::Format;
 use crate::render::RenderContext;
 use crate::transform::AstTransform;
 
@@ -61,6 +99,11 @@ pub struct ShortcodeError {
 pub enum ShortcodeResult {
     /// Resolved to inline content
     Inlines(Vec<Inline>),
+    /// Resolved to block content
+    ///
+    /// Replaces the enclosing paragraph if the shortcode is alone in it;
+    /// otherwise the blocks are flattened to inlines.
+    Blocks(Vec<Block>),
     /// Error - renders visible content AND emits diagnostic
     Error(ShortcodeError),
     /// Shortcode should be preserved as literal text (e.g., escaped shortcodes)
@@ -73,11 +116,30 @@ pub struct ShortcodeContext<'a> {
     pub metadata: &'a ConfigValue,
     /// Source info for the shortcode (for error reporting)
     pub source_info: &'a SourceInfo,
+    /// Output format being rendered
+    pub format: &'a Format,
+    /// Directory of the document, for resolving relative paths
+    pub document_dir: &'a Path,
+    /// Runtime for file and environment access, if available
+    pub runtime: Option<&'a dyn SystemRuntime>,
+    /// Whether the shortcode is alone in its paragraph, where block
+    /// content replaces the paragraph
+    pub block: bool,
+    /// Diagnostics reported by the handler
+    pub diagnostics: RefCell<Vec<DiagnosticMessage>>,
+}
+
+impl ShortcodeContext<'_> {
+    /// Report a diagnostic without failing the shortcode.
+    pub fn add_diagnostic(&self, diagnostic: DiagnosticMessage) {
+        self.diagnostics.borrow_mut().push(diagnostic);
+    }
 }
 
 /// Trait for shortcode handlers.
 ///
-/// Each built-in shortcode (meta, var, env, etc.) implements this trait.
+/// Each built-in shortcode (meta, env, video, etc.) implements this trait,
+/// as do handlers for user-defined shortcodes.
 pub trait ShortcodeHandler: Send + Sync {
     /// The shortcode name (e.g., "meta", "var", "env")
     fn name(&self) -> &str;
@@ -134,6 +196,30 @@ impl ShortcodeHandler for MetaShortcodeHandler {
     }
 }
 
+/// Handler for `include` shortcodes that survived parsing.
+///
+/// Includes are spliced in at parse time, but only when the shortcode is
+/// the only content of its paragraph. One found here is used inline.
+pub struct IncludeShortcodeHandler;
+
+impl ShortcodeHandler for IncludeShortcodeHandler {
+    fn name(&self) -> &str {
+        "include"
+    }
+
+    fn resolve(&self, _shortcode: &Shortcode, ctx: &ShortcodeContext) -> ShortcodeResult {
+        let diagnostic = DiagnosticMessageBuilder::warning("Misplaced include")
+            .problem("The `include` shortcode must be on a line of its own")
+            .add_hint("Put `{{< include file.qmd >}}` in its own paragraph")
+            .with_location(ctx.source_info.clone())
+            .build();
+        ShortcodeResult::Error(ShortcodeError {
+            key: "include".to_string(),
+            diagnostic,
+        })
+    }
+}
+
 /// Convert a ConfigValue to inline content.
 fn config_value_to_inlines(value: &ConfigValue) -> Vec<Inline> {
     // Use helper methods on ConfigValue for scalar types
@@ -211,6 +297,12 @@ fn flatten_blocks_to_inlines(blocks: &[Block]) -> Vec<Inline> {
                 }
                 result.extend(para.content.clone());
             }
+            // Raw output (e.g. a video embed) stays raw
+            Block::RawBlock(raw) => result.push(Inline::RawInline(RawInline {
+                format: raw.format.clone(),
+                text: raw.text.clone(),
+                source_info: raw.source_info.clone(),
+            })),
             // For other block types, recursively extract inlines
             _ => {
                 // Skip complex blocks - they don't make sense in inline context
@@ -220,17 +312,66 @@ fn flatten_blocks_to_inlines(blocks: &[Block]) -> Vec<Inline> {
     result
 }
 
+/// Shortcode handlers, by name.
+///
+/// Registering a handler replaces any handler already registered under
+/// the same name, so user-defined shortcodes can override built-ins.
+#[derive(Default)]
+pub struct ShortcodeRegistry {
+    handlers: HashMap<String, Box<dyn ShortcodeHandler>>,
+}
+
+impl ShortcodeRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in handlers.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(MetaShortcodeHandler));
+        registry.register(Box::new(IncludeShortcodeHandler));
+        registry.register(Box::new(EnvShortcodeHandler));
+        registry.register(Box::new(KbdShortcodeHandler));
+        registry.register(Box::new(PagebreakShortcodeHandler));
+        registry.register(Box::new(VideoShortcodeHandler));
+        registry.register(Box::new(EmbedShortcodeHandler));
+        registry
+    }
+
+    /// Register a handler under its name.
+    pub fn register(&mut self, handler: Box<dyn ShortcodeHandler>) {
+        self.handlers.insert(handler.name().to_string(), handler);
+    }
+
+    /// Get the handler for a shortcode name.
+    pub fn get(&self, name: &str) -> Option<&dyn ShortcodeHandler> {
+        self.handlers.get(name).map(|handler| handler.as_ref())
+    }
+
+    /// List the registered shortcode names, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.handlers.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+}
+
 /// Transform that resolves shortcodes in the AST.
 pub struct ShortcodeResolveTransform {
-    handlers: Vec<Box<dyn ShortcodeHandler>>,
+    registry: ShortcodeRegistry,
 }
 
 impl ShortcodeResolveTransform {
-    /// Create a new shortcode resolve transform with default handlers.
+    /// Create a new shortcode resolve transform with the built-in handlers.
     pub fn new() -> Self {
-        Self {
-            handlers: vec![Box::new(MetaShortcodeHandler)],
-        }
+        Self::with_registry(ShortcodeRegistry::with_builtins())
+    }
+
+    /// Create a shortcode resolve transform using the handlers of `registry`.
+    pub fn with_registry(registry: ShortcodeRegistry) -> Self {
+        Self { registry }
     }
 
     /// Resolve a shortcode using the appropriate handler.
@@ -241,10 +382,8 @@ impl ShortcodeResolveTransform {
         }
 
         // Find and call handler
-        for handler in &self.handlers {
-            if handler.name() == shortcode.name {
-                return handler.resolve(shortcode, ctx);
-            }
+        if let Some(handler) = self.registry.get(&shortcode.name) {
+            return handler.resolve(shortcode, ctx);
         }
 
         // Unknown shortcode - create error with diagnostic
@@ -276,7 +415,16 @@ impl AstTransform for ShortcodeResolveTransform {
         let mut diagnostics: Vec<DiagnosticMessage> = Vec::new();
 
         // Resolve shortcodes in all blocks
-        resolve_blocks(&mut ast.blocks, self, &ast.meta, &mut diagnostics);
+        let extensions = extension_shortcodes(ctx);
+        let env = ResolveEnv {
+            transform: self,
+            extensions: &extensions,
+            metadata: &ast.meta,
+            format: ctx.format,
+            document_dir: ctx.document.input.parent().unwrap_or(Path::new("")),
+            runtime: ctx.runtime.as_deref(),
+        };
+        resolve_blocks(&mut ast.blocks, &env, &mut diagnostics);
 
         // Add any diagnostics to the render context
         for diagnostic in diagnostics {
@@ -287,71 +435,190 @@ impl AstTransform for ShortcodeResolveTransform {
     }
 }
 
+/// Handlers for the shortcodes contributed by the document's extensions.
+///
+/// An extension whose shortcodes can't be loaded is reported and skipped,
+/// so documents that don't use it still render.
+#[cfg(not(target_arch = "wasm32"))]
+fn extension_shortcodes(ctx: &mut RenderContext) -> ShortcodeRegistry {
+    use super::LuaShortcodeHandler;
+    use crate::extension::contributions::document_extensions;
+
+    let mut registry = ShortcodeRegistry::new();
+    let Some(runtime) = ctx.runtime.clone() else {
+        return registry;
+    };
+    let document_dir = ctx.document.input.parent().unwrap_or(Path::new(""));
+    let handlers = document_extensions(document_dir, &ctx.project.dir, runtime.as_ref())
+        .and_then(|extensions| LuaShortcodeHandler::from_extensions(&extensions));
+    match handlers {
+        Ok(handlers) => {
+            for handler in handlers {
+                registry.register(Box::new(handler));
+            }
+        }
+        Err(e) => ctx.add_diagnostic(
+            DiagnosticMessageBuilder::warning("Extension shortcodes unavailable")
+                .problem(e.to_string())
+                .build(),
+        ),
+    }
+    registry
+}
+
+#[cfg(target_arch = "wasm32")]
+fn extension_shortcodes(_ctx: &mut RenderContext) -> ShortcodeRegistry {
+    ShortcodeRegistry::new()
+}
+
+/// What the traversal needs to build each shortcode's context.
+struct ResolveEnv<'a> {
+    transform: &'a ShortcodeResolveTransform,
+    /// Handlers for the shortcodes of the document's extensions
+    extensions: &'a ShortcodeRegistry,
+    metadata: &'a ConfigValue,
+    format: &'a Format,
+    document_dir: &'a Path,
+    runtime: Option<&'a dyn SystemRuntime>,
+}
+
+impl ResolveEnv<'_> {
+    /// Resolve a shortcode, collecting the diagnostics its handler reports.
+    fn resolve(
+        &self,
+        shortcode: &Shortcode,
+        block: bool,
+        diagnostics: &mut Vec<DiagnosticMessage>,
+    ) -> ShortcodeResult {
+        let ctx = ShortcodeContext {
+            metadata: self.metadata,
+            source_info: &shortcode.source_info,
+            format: self.format,
+            document_dir: self.document_dir,
+            runtime: self.runtime,
+            block,
+            diagnostics: RefCell::new(Vec::new()),
+        };
+        let result = match self.extensions.get(&shortcode.name) {
+            Some(handler) if !shortcode.is_escaped => handler.resolve(shortcode, &ctx),
+            _ => self.transform.resolve_shortcode(shortcode, &ctx),
+        };
+        diagnostics.extend(ctx.diagnostics.into_inner());
+        result
+    }
+}
+
 /// Resolve shortcodes in a vector of blocks.
 fn resolve_blocks(
     blocks: &mut Vec<Block>,
-    transform: &ShortcodeResolveTransform,
-    metadata: &ConfigValue,
+    env: &ResolveEnv,
     diagnostics: &mut Vec<DiagnosticMessage>,
 ) {
-    for block in blocks.iter_mut() {
-        resolve_block(block, transform, metadata, diagnostics);
+    let mut i = 0;
+    while i < blocks.len() {
+        if let Some(replacement) = resolve_lone_shortcode(&blocks[i], env, diagnostics) {
+            let replacement_len = replacement.len();
+            blocks.splice(i..=i, replacement);
+            i += replacement_len;
+        } else {
+            resolve_block(&mut blocks[i], env, diagnostics);
+            i += 1;
+        }
     }
 }
 
-/// Resolve shortcodes in a single block.
-fn resolve_block(
-    block: &mut Block,
-    transform: &ShortcodeResolveTransform,
-    metadata: &ConfigValue,
+/// Resolve a paragraph that holds nothing but a shortcode.
+///
+/// Returns the blocks replacing the paragraph, or `None` if the block is
+/// not such a paragraph (or the shortcode is escaped).
+fn resolve_lone_shortcode(
+    block: &Block,
+    env: &ResolveEnv,
     diagnostics: &mut Vec<DiagnosticMessage>,
-) {
+) -> Option<Vec<Block>> {
+    let (Block::Paragraph(Paragraph { content, .. }) | Block::Plain(Plain { content, .. })) = block
+    else {
+        return None;
+    };
+    let mut inlines = content
+        .iter()
+        .filter(|inline| !matches!(inline, Inline::Space(_) | Inline::SoftBreak(_)));
+    let (Some(Inline::Shortcode(shortcode)), None) = (inlines.next(), inlines.next()) else {
+        return None;
+    };
+    if shortcode.is_escaped {
+        return None;
+    }
+
+    let with_content = |content: Vec<Inline>| {
+        let mut block = block.clone();
+        if let Block::Paragraph(Paragraph { content: c, .. })
+        | Block::Plain(Plain { content: c, .. }) = &mut block
+        {
+            *c = content;
+        }
+        block
+    };
+
+    match env.resolve(shortcode, true, diagnostics) {
+        ShortcodeResult::Blocks(blocks) => Some(blocks),
+        ShortcodeResult::Inlines(inlines) => Some(vec![with_content(inlines)]),
+        ShortcodeResult::Error(error) => {
+            diagnostics.push(error.diagnostic);
+            Some(vec![with_content(vec![make_error_inline(&error.key)])])
+        }
+        ShortcodeResult::Preserve => None,
+    }
+}
+
+/// Resolve shortcodes in a single block.
+fn resolve_block(block: &mut Block, env: &ResolveEnv, diagnostics: &mut Vec<DiagnosticMessage>) {
     match block {
         Block::Plain(Plain { content, .. }) | Block::Paragraph(Paragraph { content, .. }) => {
-            resolve_inlines(content, transform, metadata, diagnostics);
+            resolve_inlines(content, env, diagnostics);
         }
         Block::LineBlock(LineBlock { content, .. }) => {
             for line in content {
-                resolve_inlines(line, transform, metadata, diagnostics);
+                resolve_inlines(line, env, diagnostics);
             }
         }
         Block::Header(Header { content, .. }) => {
-            resolve_inlines(content, transform, metadata, diagnostics);
+            resolve_inlines(content, env, diagnostics);
         }
         Block::BlockQuote(BlockQuote { content, .. }) => {
-            resolve_blocks(content, transform, metadata, diagnostics);
+            resolve_blocks(content, env, diagnostics);
         }
         Block::OrderedList(OrderedList { content, .. }) => {
             for item in content {
-                resolve_blocks(item, transform, metadata, diagnostics);
+                resolve_blocks(item, env, diagnostics);
             }
         }
         Block::BulletList(BulletList { content, .. }) => {
             for item in content {
-                resolve_blocks(item, transform, metadata, diagnostics);
+                resolve_blocks(item, env, diagnostics);
             }
         }
         Block::DefinitionList(DefinitionList { content, .. }) => {
             for (term, defs) in content {
-                resolve_inlines(term, transform, metadata, diagnostics);
+                resolve_inlines(term, env, diagnostics);
                 for def in defs {
-                    resolve_blocks(def, transform, metadata, diagnostics);
+                    resolve_blocks(def, env, diagnostics);
                 }
             }
         }
         Block::Figure(Figure {
             content, caption, ..
         }) => {
-            resolve_blocks(content, transform, metadata, diagnostics);
+            resolve_blocks(content, env, diagnostics);
             if let Some(short) = &mut caption.short {
-                resolve_inlines(short, transform, metadata, diagnostics);
+                resolve_inlines(short, env, diagnostics);
             }
             if let Some(long) = &mut caption.long {
-                resolve_blocks(long, transform, metadata, diagnostics);
+                resolve_blocks(long, env, diagnostics);
             }
         }
         Block::Div(Div { content, .. }) => {
-            resolve_blocks(content, transform, metadata, diagnostics);
+            resolve_blocks(content, env, diagnostics);
         }
         Block::Table(Table {
             caption,
@@ -362,29 +629,29 @@ fn resolve_block(
         }) => {
             // Table caption
             if let Some(short) = &mut caption.short {
-                resolve_inlines(short, transform, metadata, diagnostics);
+                resolve_inlines(short, env, diagnostics);
             }
             if let Some(long) = &mut caption.long {
-                resolve_blocks(long, transform, metadata, diagnostics);
+                resolve_blocks(long, env, diagnostics);
             }
             // Table head
             for row in &mut head.rows {
                 for cell in &mut row.cells {
-                    resolve_blocks(&mut cell.content, transform, metadata, diagnostics);
+                    resolve_blocks(&mut cell.content, env, diagnostics);
                 }
             }
             // Table bodies
             for body in bodies {
                 for row in &mut body.body {
                     for cell in &mut row.cells {
-                        resolve_blocks(&mut cell.content, transform, metadata, diagnostics);
+                        resolve_blocks(&mut cell.content, env, diagnostics);
                     }
                 }
             }
             // Table foot
             for row in &mut foot.rows {
                 for cell in &mut row.cells {
-                    resolve_blocks(&mut cell.content, transform, metadata, diagnostics);
+                    resolve_blocks(&mut cell.content, env, diagnostics);
                 }
             }
         }
@@ -393,14 +660,14 @@ fn resolve_block(
             for slot in custom.slots.values_mut() {
                 match slot {
                     quarto_pandoc_types::custom::Slot::Block(b) => {
-                        resolve_block(b, transform, metadata, diagnostics);
+                        resolve_block(b, env, diagnostics);
                     }
                     quarto_pandoc_types::custom::Slot::Blocks(bs) => {
-                        resolve_blocks(bs, transform, metadata, diagnostics);
+                        resolve_blocks(bs, env, diagnostics);
                     }
                     quarto_pandoc_types::custom::Slot::Inline(i) => {
                         let mut inlines = vec![i.as_ref().clone()];
-                        resolve_inlines(&mut inlines, transform, metadata, diagnostics);
+                        resolve_inlines(&mut inlines, env, diagnostics);
                         if inlines.len() == 1 {
                             **i = inlines.pop().unwrap();
                         }
@@ -408,7 +675,7 @@ fn resolve_block(
                         // back into a single Inline slot - keep the original
                     }
                     quarto_pandoc_types::custom::Slot::Inlines(is) => {
-                        resolve_inlines(is, transform, metadata, diagnostics);
+                        resolve_inlines(is, env, diagnostics);
                     }
                 }
             }
@@ -427,19 +694,20 @@ fn resolve_block(
 /// Resolve shortcodes in a vector of inlines.
 fn resolve_inlines(
     inlines: &mut Vec<Inline>,
-    transform: &ShortcodeResolveTransform,
-    metadata: &ConfigValue,
+    env: &ResolveEnv,
     diagnostics: &mut Vec<DiagnosticMessage>,
 ) {
     let mut i = 0;
     while i < inlines.len() {
         if let Inline::Shortcode(shortcode) = &inlines[i] {
-            let shortcode_ctx = ShortcodeContext {
-                metadata,
-                source_info: &shortcode.source_info,
-            };
-
-            match transform.resolve_shortcode(shortcode, &shortcode_ctx) {
+            match env.resolve(shortcode, false, diagnostics) {
+                ShortcodeResult::Blocks(blocks) => {
+                    // Block content in inline context: keep its text
+                    let replacement = flatten_blocks_to_inlines(&blocks);
+                    let replacement_len = replacement.len();
+                    inlines.splice(i..=i, replacement);
+                    i += replacement_len.max(1);
+                }
                 ShortcodeResult::Inlines(replacement) => {
                     // Replace shortcode with resolved inlines
                     let replacement_len = replacement.len();
@@ -465,19 +733,14 @@ fn resolve_inlines(
             }
         } else {
             // Recurse into inline containers
-            recurse_inline(&mut inlines[i], transform, metadata, diagnostics);
+            recurse_inline(&mut inlines[i], env, diagnostics);
             i += 1;
         }
     }
 }
 
 /// Recurse into an inline element to resolve nested shortcodes.
-fn recurse_inline(
-    inline: &mut Inline,
-    transform: &ShortcodeResolveTransform,
-    metadata: &ConfigValue,
-    diagnostics: &mut Vec<DiagnosticMessage>,
-) {
+fn recurse_inline(inline: &mut Inline, env: &ResolveEnv, diagnostics: &mut Vec<DiagnosticMessage>) {
     match inline {
         Inline::Emph(Emph { content, .. })
         | Inline::Underline(Underline { content, .. })
@@ -489,45 +752,45 @@ fn recurse_inline(
         | Inline::Insert(Insert { content, .. })
         | Inline::Delete(Delete { content, .. })
         | Inline::Highlight(Highlight { content, .. }) => {
-            resolve_inlines(content, transform, metadata, diagnostics);
+            resolve_inlines(content, env, diagnostics);
         }
         Inline::Quoted(Quoted { content, .. }) => {
-            resolve_inlines(content, transform, metadata, diagnostics);
+            resolve_inlines(content, env, diagnostics);
         }
         Inline::Cite(Cite { content, .. }) => {
-            resolve_inlines(content, transform, metadata, diagnostics);
+            resolve_inlines(content, env, diagnostics);
         }
         Inline::Link(Link { content, .. }) | Inline::Image(Image { content, .. }) => {
-            resolve_inlines(content, transform, metadata, diagnostics);
+            resolve_inlines(content, env, diagnostics);
         }
         Inline::Note(Note { content, .. }) => {
-            resolve_blocks(content, transform, metadata, diagnostics);
+            resolve_blocks(content, env, diagnostics);
         }
         Inline::Span(Span { content, .. }) => {
-            resolve_inlines(content, transform, metadata, diagnostics);
+            resolve_inlines(content, env, diagnostics);
         }
         Inline::EditComment(EditComment { content, .. }) => {
-            resolve_inlines(content, transform, metadata, diagnostics);
+            resolve_inlines(content, env, diagnostics);
         }
         Inline::Custom(custom) => {
             // Resolve shortcodes in custom inline node slots
             for slot in custom.slots.values_mut() {
                 match slot {
                     quarto_pandoc_types::custom::Slot::Inlines(is) => {
-                        resolve_inlines(is, transform, metadata, diagnostics);
+                        resolve_inlines(is, env, diagnostics);
                     }
                     quarto_pandoc_types::custom::Slot::Inline(i) => {
                         let mut inlines = vec![i.as_ref().clone()];
-                        resolve_inlines(&mut inlines, transform, metadata, diagnostics);
+                        resolve_inlines(&mut inlines, env, diagnostics);
                         if inlines.len() == 1 {
                             **i = inlines.pop().unwrap();
                         }
                     }
                     quarto_pandoc_types::custom::Slot::Blocks(bs) => {
-                        resolve_blocks(bs, transform, metadata, diagnostics);
+                        resolve_blocks(bs, env, diagnostics);
                     }
                     quarto_pandoc_types::custom::Slot::Block(b) => {
-                        resolve_block(b, transform, metadata, diagnostics);
+                        resolve_block(b, env, diagnostics);
                     }
                 }
             }
@@ -633,13 +896,12 @@ fn shortcode_to_literal(shortcode: &Shortcode) -> Inline {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::format::Format;
     use crate::project::{DocumentInfo, ProjectContext};
     use crate::render::BinaryDependencies;
     use quarto_pandoc_types::config_value::ConfigMapEntry;
     use quarto_source_map::{FileId, Location, Range};
-    use std::collections::HashMap;
     use std::path::PathBuf;
+    use std::sync::LazyLock;
 
     fn dummy_source_info() -> SourceInfo {
         SourceInfo::from_range(
@@ -682,6 +944,22 @@ mod tests {
         }
     }
 
+    fn make_context<'a>(
+        metadata: &'a ConfigValue,
+        source_info: &'a SourceInfo,
+    ) -> ShortcodeContext<'a> {
+        static HTML: LazyLock<Format> = LazyLock::new(Format::html);
+        ShortcodeContext {
+            metadata,
+            source_info,
+            format: &HTML,
+            document_dir: Path::new("/project"),
+            runtime: None,
+            block: false,
+            diagnostics: RefCell::new(Vec::new()),
+        }
+    }
+
     fn make_map_entry(key: &str, value: ConfigValue) -> ConfigMapEntry {
         ConfigMapEntry {
             key: key.to_string(),
@@ -736,10 +1014,7 @@ mod tests {
             dummy_source_info(),
         );
 
-        let ctx = ShortcodeContext {
-            metadata: &meta,
-            source_info: &shortcode.source_info,
-        };
+        let ctx = make_context(&meta, &shortcode.source_info);
 
         let result = handler.resolve(&shortcode, &ctx);
         match result {
@@ -768,10 +1043,7 @@ mod tests {
             dummy_source_info(),
         );
 
-        let ctx = ShortcodeContext {
-            metadata: &meta,
-            source_info: &shortcode.source_info,
-        };
+        let ctx = make_context(&meta, &shortcode.source_info);
 
         let result = handler.resolve(&shortcode, &ctx);
         match result {
@@ -789,10 +1061,7 @@ mod tests {
 
         let meta = ConfigValue::default();
 
-        let ctx = ShortcodeContext {
-            metadata: &meta,
-            source_info: &shortcode.source_info,
-        };
+        let ctx = make_context(&meta, &shortcode.source_info);
 
         let result = handler.resolve(&shortcode, &ctx);
         match result {
@@ -815,10 +1084,8 @@ mod tests {
             source_info: dummy_source_info(),
         };
 
-        let ctx = ShortcodeContext {
-            metadata: &ConfigValue::default(),
-            source_info: &shortcode.source_info,
-        };
+        let metadata = ConfigValue::default();
+        let ctx = make_context(&metadata, &shortcode.source_info);
 
         let result = transform.resolve_shortcode(&shortcode, &ctx);
         assert!(matches!(result, ShortcodeResult::Preserve));
@@ -830,10 +1097,8 @@ mod tests {
 
         let shortcode = make_shortcode("unknown", vec![]);
 
-        let ctx = ShortcodeContext {
-            metadata: &ConfigValue::default(),
-            source_info: &shortcode.source_info,
-        };
+        let metadata = ConfigValue::default();
+        let ctx = make_context(&metadata, &shortcode.source_info);
 
         let result = transform.resolve_shortcode(&shortcode, &ctx);
         match result {
@@ -844,6 +1109,130 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_registry_builtins() {
+        let registry = ShortcodeRegistry::with_builtins();
+        assert_eq!(
+            registry.names(),
+            vec![
+                "embed",
+                "env",
+                "include",
+                "kbd",
+                "meta",
+                "pagebreak",
+                "video"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_registry_override() {
+        struct Shout;
+        impl ShortcodeHandler for Shout {
+            fn name(&self) -> &str {
+                "meta"
+            }
+            fn resolve(&self, _shortcode: &Shortcode, ctx: &ShortcodeContext) -> ShortcodeResult {
+                ctx.add_diagnostic(DiagnosticMessageBuilder::info("Shouted").build());
+                ShortcodeResult::Inlines(vec![Inline::Str(Str {
+                    text: "META".to_string(),
+                    source_info: SourceInfo::default(),
+                })])
+            }
+        }
+
+        let mut registry = ShortcodeRegistry::with_builtins();
+        registry.register(Box::new(Shout));
+        let transform = ShortcodeResolveTransform::with_registry(registry);
+
+        let mut ast = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![Block::Paragraph(Paragraph {
+                content: vec![
+                    Inline::Str(Str {
+                        text: "Say".to_string(),
+                        source_info: dummy_source_info(),
+                    }),
+                    Inline::Shortcode(make_shortcode("meta", vec!["title"])),
+                ],
+                source_info: dummy_source_info(),
+            })],
+        };
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+        transform.transform(&mut ast, &mut ctx).unwrap();
+
+        let Block::Paragraph(para) = &ast.blocks[0] else {
+            panic!("Expected Paragraph");
+        };
+        assert!(matches!(&para.content[1], Inline::Str(s) if s.text == "META"));
+        assert_eq!(ctx.diagnostics.len(), 1);
+        assert_eq!(ctx.diagnostics[0].title, "Shouted");
+    }
+
+    #[test]
+    fn test_block_shortcode_replaces_paragraph() {
+        let transform = ShortcodeResolveTransform::new();
+        let paragraph = |content: Vec<Inline>| {
+            Block::Paragraph(Paragraph {
+                content,
+                source_info: dummy_source_info(),
+            })
+        };
+        let text = |text: &str| {
+            Inline::Str(Str {
+                text: text.to_string(),
+                source_info: dummy_source_info(),
+            })
+        };
+        let mut ast = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![
+                paragraph(vec![Inline::Shortcode(make_shortcode("pagebreak", vec![]))]),
+                paragraph(vec![
+                    text("Break"),
+                    Inline::Shortcode(make_shortcode("pagebreak", vec![])),
+                ]),
+            ],
+        };
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+        transform.transform(&mut ast, &mut ctx).unwrap();
+
+        // Alone in its paragraph, the page break replaces it
+        assert!(matches!(&ast.blocks[0], Block::RawBlock(raw) if raw.format == "html"));
+        // Inline, it is flattened to raw inline content
+        let Block::Paragraph(para) = &ast.blocks[1] else {
+            panic!("Expected Paragraph");
+        };
+        assert!(matches!(&para.content[1], Inline::RawInline(raw) if raw.format == "html"));
+        assert!(ctx.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_inline_include() {
+        let transform = ShortcodeResolveTransform::new();
+        let metadata = ConfigValue::default();
+        let source_info = dummy_source_info();
+        let ctx = make_context(&metadata, &source_info);
+
+        let shortcode = make_shortcode("include", vec!["_part.qmd"]);
+        match transform.resolve_shortcode(&shortcode, &ctx) {
+            ShortcodeResult::Error(error) => {
+                assert_eq!(error.key, "include");
+                assert_eq!(error.diagnostic.title, "Misplaced include");
+            }
+            _ => panic!("Expected Error"),
+        }
+    }
+
     #[test]
     fn test_make_error_inline() {
         let inline = make_error_inline("meta:title");
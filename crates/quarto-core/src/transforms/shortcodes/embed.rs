@@ -0,0 +1,348 @@
+/*
+ * transforms/shortcodes/embed.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * The embed shortcode.
+ */
+
+use hashlink::LinkedHashMap;
+use quarto_error_reporting::DiagnosticMessageBuilder;
+use quarto_pandoc_types::attr::AttrSourceInfo;
+use quarto_pandoc_types::block::{Block, CodeBlock, Div, RawBlock};
+use quarto_pandoc_types::shortcode::Shortcode;
+use quarto_source_map::SourceInfo;
+use serde_json::Value;
+
+use super::{keyword, missing_argument, positional};
+use crate::transforms::shortcode_resolve::{
+    ShortcodeContext, ShortcodeError, ShortcodeHandler, ShortcodeResult,
+};
+
+/// Handler for the `embed` shortcode.
+///
+/// Usage: `{{< embed notebook.ipynb#cell-label [echo=true] >}}`
+///
+/// Embeds the outputs of one cell of a Jupyter notebook, read through the
+/// system runtime relative to the document. The cell is found by its `id`,
+/// a `#| label:` option in its source, or a tag. With `echo=true` the
+/// cell's code is shown above its outputs.
+pub struct EmbedShortcodeHandler;
+
+impl ShortcodeHandler for EmbedShortcodeHandler {
+    fn name(&self) -> &str {
+        "embed"
+    }
+
+    fn resolve(&self, shortcode: &Shortcode, ctx: &ShortcodeContext) -> ShortcodeResult {
+        let usage = "{{< embed notebook.ipynb#cell-label >}}";
+        let Some(target) = positional(shortcode, 0) else {
+            return missing_argument("embed", usage, ctx);
+        };
+        let Some((notebook, label)) = target
+            .split_once('#')
+            .filter(|(_, label)| !label.is_empty())
+        else {
+            return embed_error(
+                ctx,
+                format!("`{}` does not name a cell", target),
+                format!("Use `{}`", usage),
+            );
+        };
+        if !notebook.ends_with(".ipynb") {
+            return embed_error(
+                ctx,
+                format!("`{}` is not a Jupyter notebook", notebook),
+                "Only cells of `.ipynb` notebooks can be embedded".to_string(),
+            );
+        }
+
+        let path = ctx.document_dir.join(notebook);
+        let Some(source) = ctx
+            .runtime
+            .and_then(|runtime| runtime.file_read_string(&path).ok())
+        else {
+            return embed_error(
+                ctx,
+                format!("Could not read `{}`", path.display()),
+                "Notebook paths are relative to the document".to_string(),
+            );
+        };
+        let Ok(notebook_json) = serde_json::from_str::<Value>(&source) else {
+            return embed_error(
+                ctx,
+                format!("`{}` is not valid notebook JSON", notebook),
+                "Check that the notebook was saved correctly".to_string(),
+            );
+        };
+        let Some(cell) = find_cell(&notebook_json, label) else {
+            return embed_error(
+                ctx,
+                format!("No cell labeled `{}` in `{}`", label, notebook),
+                "Give the cell an id, a `#| label:` option or a tag".to_string(),
+            );
+        };
+
+        let mut content = Vec::new();
+        if keyword(shortcode, "echo").as_deref() == Some("true") {
+            let language = notebook_json
+                .pointer("/metadata/kernelspec/language")
+                .or_else(|| notebook_json.pointer("/metadata/language_info/name"))
+                .and_then(Value::as_str)
+                .unwrap_or("python");
+            let code = nb_text(cell.get("source"))
+                .lines()
+                .filter(|line| !line.starts_with("#|"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            content.push(code_block(
+                &code,
+                vec![language.to_string(), "cell-code".to_string()],
+                ctx.source_info,
+            ));
+        }
+        let outputs = cell.get("outputs").and_then(Value::as_array);
+        for output in outputs.into_iter().flatten() {
+            content.extend(output_block(output, ctx.source_info));
+        }
+
+        let mut attrs = LinkedHashMap::new();
+        attrs.insert("data-notebook".to_string(), notebook.to_string());
+        ShortcodeResult::Blocks(vec![Block::Div(Div {
+            attr: (
+                String::new(),
+                vec!["cell".to_string(), "quarto-embed-nb-cell".to_string()],
+                attrs,
+            ),
+            content,
+            source_info: ctx.source_info.clone(),
+            attr_source: AttrSourceInfo::empty(),
+        })])
+    }
+}
+
+fn embed_error(ctx: &ShortcodeContext, problem: String, hint: String) -> ShortcodeResult {
+    let diagnostic = DiagnosticMessageBuilder::warning("Cannot embed notebook cell")
+        .problem(problem)
+        .add_hint(hint)
+        .with_location(ctx.source_info.clone())
+        .build();
+    ShortcodeResult::Error(ShortcodeError {
+        key: "embed".to_string(),
+        diagnostic,
+    })
+}
+
+/// Notebook text fields are a string or a list of lines.
+fn nb_text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+/// The code cell with `label` as its id, `#| label:` option or tag.
+fn find_cell<'a>(notebook: &'a Value, label: &str) -> Option<&'a Value> {
+    let cells = notebook.get("cells")?.as_array()?;
+    cells
+        .iter()
+        .filter(|cell| cell.get("cell_type").and_then(Value::as_str) == Some("code"))
+        .find(|cell| {
+            cell.get("id").and_then(Value::as_str) == Some(label)
+                || nb_text(cell.get("source")).lines().any(|line| {
+                    line.strip_prefix("#|")
+                        .and_then(|option| option.trim().strip_prefix("label:"))
+                        .is_some_and(|value| value.trim() == label)
+                })
+                || cell
+                    .pointer("/metadata/tags")
+                    .and_then(Value::as_array)
+                    .is_some_and(|tags| tags.iter().any(|tag| tag.as_str() == Some(label)))
+        })
+}
+
+fn code_block(text: &str, classes: Vec<String>, source_info: &SourceInfo) -> Block {
+    Block::CodeBlock(CodeBlock {
+        attr: (String::new(), classes, LinkedHashMap::new()),
+        text: text.trim_end().to_string(),
+        source_info: source_info.clone(),
+        attr_source: AttrSourceInfo::empty(),
+    })
+}
+
+/// A notebook output as a `cell-output` Div.
+fn output_block(output: &Value, source_info: &SourceInfo) -> Option<Block> {
+    let raw_html = |text: String| {
+        Block::RawBlock(RawBlock {
+            format: "html".to_string(),
+            text,
+            source_info: source_info.clone(),
+        })
+    };
+
+    let (kind, block) = match output.get("output_type").and_then(Value::as_str)? {
+        "stream" => {
+            let name = output
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("stdout");
+            (
+                name.to_string(),
+                code_block(&nb_text(output.get("text")), Vec::new(), source_info),
+            )
+        }
+        "display_data" | "execute_result" => {
+            let data = output.get("data")?;
+            let block = if let Some(html) = data.get("text/html") {
+                raw_html(nb_text(Some(html)))
+            } else if let Some(svg) = data.get("image/svg+xml") {
+                raw_html(nb_text(Some(svg)))
+            } else if let Some((mime, image)) = ["image/png", "image/jpeg", "image/gif"]
+                .iter()
+                .find_map(|mime| data.get(*mime).map(|image| (*mime, image)))
+            {
+                let base64: String = nb_text(Some(image))
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .collect();
+                raw_html(format!("<img src=\"data:{};base64,{}\">", mime, base64))
+            } else {
+                code_block(&nb_text(data.get("text/plain")), Vec::new(), source_info)
+            };
+            ("display".to_string(), block)
+        }
+        "error" => {
+            let name = output
+                .get("ename")
+                .and_then(Value::as_str)
+                .unwrap_or("Error");
+            let value = output.get("evalue").and_then(Value::as_str).unwrap_or("");
+            (
+                "error".to_string(),
+                code_block(&format!("{}: {}", name, value), Vec::new(), source_info),
+            )
+        }
+        _ => return None,
+    };
+
+    Some(Block::Div(Div {
+        attr: (
+            String::new(),
+            vec!["cell-output".to_string(), format!("cell-output-{}", kind)],
+            LinkedHashMap::new(),
+        ),
+        content: vec![block],
+        source_info: source_info.clone(),
+        attr_source: AttrSourceInfo::empty(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::shortcodes::test_support::{context, shortcode};
+    use quarto_system_runtime::NativeRuntime;
+    use serde_json::json;
+
+    fn notebook() -> Value {
+        json!({
+            "metadata": { "kernelspec": { "language": "python" } },
+            "cells": [
+                { "cell_type": "markdown", "source": "# Analysis" },
+                {
+                    "cell_type": "code",
+                    "id": "abc123",
+                    "source": ["#| label: fig-plot\n", "plot()\n"],
+                    "outputs": [
+                        { "output_type": "stream", "name": "stdout", "text": ["done\n"] },
+                        {
+                            "output_type": "display_data",
+                            "data": { "image/png": "iVBORw0K\nGgo=", "text/plain": "<Figure>" }
+                        }
+                    ]
+                },
+                {
+                    "cell_type": "code",
+                    "metadata": { "tags": ["summary"] },
+                    "source": "df.describe()",
+                    "outputs": [
+                        { "output_type": "execute_result", "data": { "text/html": "<table></table>" } }
+                    ]
+                }
+            ]
+        })
+    }
+
+    fn embed(args: &[&str], kwargs: &[(&str, &str)]) -> ShortcodeResult {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp.path().join("analysis.ipynb"),
+            serde_json::to_string(&notebook()).unwrap(),
+        )
+        .unwrap();
+        let runtime = NativeRuntime::new();
+        let ctx = context(temp.path(), Some(&runtime));
+        EmbedShortcodeHandler.resolve(&shortcode("embed", args, kwargs), &ctx)
+    }
+
+    fn div(result: ShortcodeResult) -> Div {
+        let ShortcodeResult::Blocks(mut blocks) = result else {
+            panic!("Expected Blocks");
+        };
+        let Block::Div(div) = blocks.remove(0) else {
+            panic!("Expected Div");
+        };
+        div
+    }
+
+    #[test]
+    fn test_find_cell() {
+        let notebook = notebook();
+        assert!(find_cell(&notebook, "abc123").is_some());
+        assert!(find_cell(&notebook, "fig-plot").is_some());
+        assert!(find_cell(&notebook, "summary").is_some());
+        assert!(find_cell(&notebook, "Analysis").is_none());
+    }
+
+    #[test]
+    fn test_embed_cell_outputs() {
+        let div = div(embed(&["analysis.ipynb#fig-plot"], &[]));
+        assert_eq!(div.attr.1, vec!["cell", "quarto-embed-nb-cell"]);
+        assert_eq!(div.content.len(), 2);
+
+        let Block::Div(image) = &div.content[1] else {
+            panic!("Expected output Div");
+        };
+        assert_eq!(image.attr.1[1], "cell-output-display");
+        let Block::RawBlock(raw) = &image.content[0] else {
+            panic!("Expected RawBlock");
+        };
+        assert_eq!(raw.text, "<img src=\"data:image/png;base64,iVBORw0KGgo=\">");
+    }
+
+    #[test]
+    fn test_embed_with_echo() {
+        let div = div(embed(&["analysis.ipynb#summary"], &[("echo", "true")]));
+        let Block::CodeBlock(code) = &div.content[0] else {
+            panic!("Expected CodeBlock");
+        };
+        assert_eq!(code.text, "df.describe()");
+        assert_eq!(code.attr.1, vec!["python", "cell-code"]);
+    }
+
+    #[test]
+    fn test_embed_errors() {
+        for target in [
+            "analysis.ipynb",
+            "analysis.qmd#cell",
+            "missing.ipynb#cell",
+            "analysis.ipynb#nope",
+        ] {
+            assert!(
+                matches!(embed(&[target], &[]), ShortcodeResult::Error(_)),
+                "{} should fail",
+                target
+            );
+        }
+    }
+}
@@ -0,0 +1,100 @@
+/*
+ * transforms/shortcodes/env.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * The env shortcode.
+ */
+
+use quarto_error_reporting::DiagnosticMessageBuilder;
+use quarto_pandoc_types::inline::{Inline, Str};
+use quarto_pandoc_types::shortcode::Shortcode;
+
+use super::{missing_argument, positional};
+use crate::transforms::shortcode_resolve::{
+    ShortcodeContext, ShortcodeError, ShortcodeHandler, ShortcodeResult,
+};
+
+/// Handler for the `env` shortcode.
+///
+/// Usage: `{{< env NAME >}}` or `{{< env NAME default >}}`
+///
+/// Inserts the value of an environment variable, read through the system
+/// runtime. An unset variable without a default is reported and rendered
+/// as `?env:NAME`.
+pub struct EnvShortcodeHandler;
+
+impl ShortcodeHandler for EnvShortcodeHandler {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn resolve(&self, shortcode: &Shortcode, ctx: &ShortcodeContext) -> ShortcodeResult {
+        let Some(name) = positional(shortcode, 0) else {
+            return missing_argument("env", "{{< env NAME >}}", ctx);
+        };
+
+        let value = ctx
+            .runtime
+            .and_then(|runtime| runtime.env_get(&name).ok().flatten())
+            .or_else(|| positional(shortcode, 1));
+        match value {
+            Some(text) => ShortcodeResult::Inlines(vec![Inline::Str(Str {
+                text,
+                source_info: ctx.source_info.clone(),
+            })]),
+            None => {
+                let diagnostic = DiagnosticMessageBuilder::warning("Unknown environment variable")
+                    .problem(format!("Environment variable `{}` is not set", name))
+                    .add_hint("Set the variable, or give a default: `{{< env NAME default >}}`")
+                    .with_location(ctx.source_info.clone())
+                    .build();
+                ShortcodeResult::Error(ShortcodeError {
+                    key: format!("env:{}", name),
+                    diagnostic,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::shortcodes::test_support::{context, shortcode};
+    use quarto_system_runtime::NativeRuntime;
+    use std::path::Path;
+
+    fn text(result: ShortcodeResult) -> String {
+        match result {
+            ShortcodeResult::Inlines(inlines) => match &inlines[0] {
+                Inline::Str(s) => s.text.clone(),
+                other => panic!("Expected Str, got {:?}", other),
+            },
+            ShortcodeResult::Error(error) => format!("?{}", error.key),
+            _ => panic!("Expected Inlines or Error"),
+        }
+    }
+
+    #[test]
+    fn test_env_values_and_defaults() {
+        let runtime = NativeRuntime::new();
+        let ctx = context(Path::new("/project"), Some(&runtime));
+
+        // PATH is set in any test environment
+        let path = std::env::var("PATH").unwrap();
+        assert_eq!(
+            text(EnvShortcodeHandler.resolve(&shortcode("env", &["PATH"], &[]), &ctx)),
+            path
+        );
+
+        let unset = "QUARTO_TEST_SURELY_UNSET_VARIABLE";
+        assert_eq!(
+            text(EnvShortcodeHandler.resolve(&shortcode("env", &[unset, "fallback"], &[]), &ctx)),
+            "fallback"
+        );
+        assert_eq!(
+            text(EnvShortcodeHandler.resolve(&shortcode("env", &[unset], &[]), &ctx)),
+            format!("?env:{}", unset)
+        );
+    }
+}
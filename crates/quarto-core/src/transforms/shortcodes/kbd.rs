@@ -0,0 +1,110 @@
+/*
+ * transforms/shortcodes/kbd.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * The kbd shortcode.
+ */
+
+use quarto_pandoc_types::attr::AttrSourceInfo;
+use quarto_pandoc_types::inline::{Code, Inline, RawInline};
+use quarto_pandoc_types::shortcode::Shortcode;
+
+use super::{html_escape, keyword, missing_argument, positional};
+use crate::transforms::shortcode_resolve::{ShortcodeContext, ShortcodeHandler, ShortcodeResult};
+
+/// Operating systems that can have their own shortcut, in display order.
+const OPERATING_SYSTEMS: [&str; 3] = ["win", "mac", "linux"];
+
+/// Handler for the `kbd` shortcode.
+///
+/// Usage: `{{< kbd Ctrl-C >}}` or
+/// `{{< kbd Ctrl-C mac=Command-C linux=Ctrl-Shift-C >}}`
+///
+/// Renders a `<kbd>` element in HTML. Per-OS shortcuts are kept as
+/// `data-*` attributes so the page can show the one matching the reader's
+/// system; the positional shortcut (or else the first OS shortcut) is the
+/// visible default. Other formats get the default shortcut as code.
+pub struct KbdShortcodeHandler;
+
+impl ShortcodeHandler for KbdShortcodeHandler {
+    fn name(&self) -> &str {
+        "kbd"
+    }
+
+    fn resolve(&self, shortcode: &Shortcode, ctx: &ShortcodeContext) -> ShortcodeResult {
+        let per_os: Vec<(&str, String)> = OPERATING_SYSTEMS
+            .iter()
+            .filter_map(|os| keyword(shortcode, os).map(|keys| (*os, keys)))
+            .collect();
+        let Some(default) =
+            positional(shortcode, 0).or_else(|| per_os.first().map(|(_, keys)| keys.clone()))
+        else {
+            return missing_argument("kbd", "{{< kbd Ctrl-C >}}", ctx);
+        };
+
+        let inline = if ctx.format.identifier.is_html_based() {
+            let attrs: String = per_os
+                .iter()
+                .map(|(os, keys)| format!(" data-{}=\"{}\"", os, html_escape(keys)))
+                .collect();
+            Inline::RawInline(RawInline {
+                format: "html".to_string(),
+                text: format!("<kbd{}>{}</kbd>", attrs, html_escape(&default)),
+                source_info: ctx.source_info.clone(),
+            })
+        } else {
+            Inline::Code(Code {
+                attr: (String::new(), vec!["kbd".to_string()], Default::default()),
+                text: default,
+                source_info: ctx.source_info.clone(),
+                attr_source: AttrSourceInfo::empty(),
+            })
+        };
+        ShortcodeResult::Inlines(vec![inline])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::shortcodes::test_support::{context, shortcode};
+    use std::path::Path;
+
+    fn html(shortcode: &Shortcode) -> String {
+        let ctx = context(Path::new("/project"), None);
+        match KbdShortcodeHandler.resolve(shortcode, &ctx) {
+            ShortcodeResult::Inlines(inlines) => match &inlines[0] {
+                Inline::RawInline(raw) => raw.text.clone(),
+                other => panic!("Expected RawInline, got {:?}", other),
+            },
+            _ => panic!("Expected Inlines"),
+        }
+    }
+
+    #[test]
+    fn test_kbd_single_shortcut() {
+        assert_eq!(
+            html(&shortcode("kbd", &["Shift-Ctrl-P"], &[])),
+            "<kbd>Shift-Ctrl-P</kbd>"
+        );
+    }
+
+    #[test]
+    fn test_kbd_per_os_shortcuts() {
+        assert_eq!(
+            html(&shortcode(
+                "kbd",
+                &[],
+                &[("mac", "Shift-Command-O"), ("win", "Shift-Control-O")]
+            )),
+            "<kbd data-win=\"Shift-Control-O\" data-mac=\"Shift-Command-O\">Shift-Control-O</kbd>"
+        );
+    }
+
+    #[test]
+    fn test_kbd_without_keys() {
+        let ctx = context(Path::new("/project"), None);
+        let result = KbdShortcodeHandler.resolve(&shortcode("kbd", &[], &[]), &ctx);
+        assert!(matches!(result, ShortcodeResult::Error(_)));
+    }
+}
@@ -0,0 +1,169 @@
+/*
+ * transforms/shortcodes/lua.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Shortcodes implemented by Lua scripts.
+ */
+
+//! Shortcodes implemented by Lua scripts.
+//!
+//! Extensions contribute shortcode scripts (`contributes: shortcodes:`),
+//! each defining one or more shortcodes. Every shortcode of a script is
+//! registered as a [`LuaShortcodeHandler`], which runs the script's
+//! function with the shortcode's arguments through [`pampa::lua`].
+
+use std::path::{Path, PathBuf};
+
+use pampa::lua::shortcode::{
+    LuaShortcodeCall, LuaShortcodeOutput, call_lua_shortcode, lua_shortcode_names,
+};
+use quarto_error_reporting::DiagnosticMessageBuilder;
+use quarto_pandoc_types::shortcode::Shortcode;
+
+use super::arg_text;
+use crate::error::{QuartoError, Result};
+use crate::extension::Extension;
+use crate::transforms::lua_filter::lua_format;
+use crate::transforms::shortcode_resolve::{
+    ShortcodeContext, ShortcodeError, ShortcodeHandler, ShortcodeResult,
+};
+
+/// Handler for a shortcode defined by a Lua script.
+pub struct LuaShortcodeHandler {
+    name: String,
+    script: PathBuf,
+}
+
+impl LuaShortcodeHandler {
+    pub fn new(name: impl Into<String>, script: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            script: script.into(),
+        }
+    }
+
+    /// Create a handler for each shortcode `script` defines.
+    pub fn from_script(script: &Path) -> Result<Vec<Self>> {
+        let names = lua_shortcode_names(script, "html").map_err(|e| {
+            QuartoError::other(format!(
+                "Failed to load shortcodes from {}: {}",
+                script.display(),
+                e
+            ))
+        })?;
+        Ok(names
+            .into_iter()
+            .map(|name| Self::new(name, script))
+            .collect())
+    }
+
+    /// Create handlers for the shortcodes of `extensions`.
+    ///
+    /// Later extensions' shortcodes come after earlier ones', so
+    /// registering them in order lets nearer extensions win.
+    pub fn from_extensions(extensions: &[Extension]) -> Result<Vec<Self>> {
+        let mut handlers = Vec::new();
+        for extension in extensions.iter().rev() {
+            for script in extension.shortcodes() {
+                handlers.extend(Self::from_script(&script)?);
+            }
+        }
+        Ok(handlers)
+    }
+}
+
+impl ShortcodeHandler for LuaShortcodeHandler {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn resolve(&self, shortcode: &Shortcode, ctx: &ShortcodeContext) -> ShortcodeResult {
+        let args: Vec<String> = shortcode
+            .positional_args
+            .iter()
+            .filter_map(arg_text)
+            .collect();
+        let mut kwargs: Vec<(String, String)> = shortcode
+            .keyword_args
+            .iter()
+            .filter_map(|(key, value)| Some((key.clone(), arg_text(value)?)))
+            .collect();
+        kwargs.sort();
+        let call = LuaShortcodeCall {
+            name: &self.name,
+            args: &args,
+            kwargs: &kwargs,
+            meta: ctx.metadata,
+            context: if ctx.block { "block" } else { "inline" },
+        };
+
+        match call_lua_shortcode(&self.script, &call, lua_format(ctx.format)) {
+            Ok((output, diagnostics)) => {
+                for diagnostic in diagnostics {
+                    ctx.add_diagnostic(diagnostic);
+                }
+                match output {
+                    LuaShortcodeOutput::Inlines(inlines) => ShortcodeResult::Inlines(inlines),
+                    LuaShortcodeOutput::Blocks(blocks) => ShortcodeResult::Blocks(blocks),
+                }
+            }
+            Err(e) => {
+                let diagnostic = DiagnosticMessageBuilder::error("Shortcode failed")
+                    .problem(format!("The `{}` shortcode failed: {}", self.name, e))
+                    .add_note(format!("Defined in {}", self.script.display()))
+                    .with_location(ctx.source_info.clone())
+                    .build();
+                ShortcodeResult::Error(ShortcodeError {
+                    key: self.name.clone(),
+                    diagnostic,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::shortcodes::test_support::{context, shortcode};
+    use quarto_pandoc_types::inline::{Inline, Str};
+
+    #[test]
+    fn test_lua_shortcode() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("greet.lua");
+        std::fs::write(
+            &script,
+            r#"
+return {
+  greet = function(args, kwargs)
+    local text = "Hello, " .. pandoc.utils.stringify(args[1])
+    if kwargs["punct"] then
+      text = text .. pandoc.utils.stringify(kwargs["punct"])
+    end
+    return pandoc.Str(text)
+  end,
+  broken = function() error("boom") end,
+}
+"#,
+        )
+        .unwrap();
+
+        let handlers = LuaShortcodeHandler::from_script(&script).unwrap();
+        let names: Vec<_> = handlers.iter().map(|h| h.name()).collect();
+        assert_eq!(names, ["broken", "greet"]);
+
+        let ctx = context(dir.path(), None);
+        match handlers[1].resolve(&shortcode("greet", &["world"], &[("punct", "!")]), &ctx) {
+            ShortcodeResult::Inlines(inlines) => assert!(matches!(
+                &inlines[..],
+                [Inline::Str(Str { text, .. })] if text == "Hello, world!"
+            )),
+            _ => panic!("Expected Inlines"),
+        }
+        match handlers[0].resolve(&shortcode("broken", &[], &[]), &ctx) {
+            ShortcodeResult::Error(error) => assert_eq!(error.key, "broken"),
+            _ => panic!("Expected Error"),
+        }
+    }
+}
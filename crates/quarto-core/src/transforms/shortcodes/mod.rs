@@ -0,0 +1,135 @@
+/*
+ * transforms/shortcodes/mod.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Built-in shortcode handlers.
+ */
+
+//! Built-in shortcode handlers.
+//!
+//! - [`EnvShortcodeHandler`] - `{{< env NAME [default] >}}`
+//! - [`KbdShortcodeHandler`] - `{{< kbd Ctrl-C mac=Command-C >}}`
+//! - [`PagebreakShortcodeHandler`] - `{{< pagebreak >}}`
+//! - [`VideoShortcodeHandler`] - `{{< video https://youtu.be/... >}}`
+//! - [`EmbedShortcodeHandler`] - `{{< embed notebook.ipynb#cell-label >}}`
+//!
+//! `meta` and the `include` fallback live with the transform in
+//! `shortcode_resolve`. All of these are registered by
+//! [`ShortcodeRegistry::with_builtins`](crate::transforms::ShortcodeRegistry::with_builtins).
+//!
+//! [`LuaShortcodeHandler`] runs the shortcodes extensions define in Lua.
+
+mod embed;
+mod env;
+mod kbd;
+#[cfg(not(target_arch = "wasm32"))]
+mod lua;
+mod pagebreak;
+mod video;
+
+pub use embed::EmbedShortcodeHandler;
+pub use env::EnvShortcodeHandler;
+pub use kbd::KbdShortcodeHandler;
+#[cfg(not(target_arch = "wasm32"))]
+pub use lua::LuaShortcodeHandler;
+pub use pagebreak::PagebreakShortcodeHandler;
+pub use video::VideoShortcodeHandler;
+
+use quarto_error_reporting::DiagnosticMessageBuilder;
+use quarto_pandoc_types::shortcode::{Shortcode, ShortcodeArg};
+
+use super::shortcode_resolve::{ShortcodeContext, ShortcodeError, ShortcodeResult};
+
+/// The text of a scalar shortcode argument.
+fn arg_text(arg: &ShortcodeArg) -> Option<String> {
+    match arg {
+        ShortcodeArg::String(s) => Some(s.clone()),
+        ShortcodeArg::Number(n) => Some(n.to_string()),
+        ShortcodeArg::Boolean(b) => Some(b.to_string()),
+        ShortcodeArg::Shortcode(_) | ShortcodeArg::KeyValue(_) => None,
+    }
+}
+
+/// The text of the `index`th positional argument.
+fn positional(shortcode: &Shortcode, index: usize) -> Option<String> {
+    shortcode.positional_args.get(index).and_then(arg_text)
+}
+
+/// The text of the keyword argument `name`.
+fn keyword(shortcode: &Shortcode, name: &str) -> Option<String> {
+    shortcode.keyword_args.get(name).and_then(arg_text)
+}
+
+/// Error result for a shortcode missing its required argument.
+fn missing_argument(name: &str, usage: &str, ctx: &ShortcodeContext) -> ShortcodeResult {
+    let diagnostic = DiagnosticMessageBuilder::warning("Missing shortcode argument")
+        .problem(format!("The `{}` shortcode requires an argument", name))
+        .add_hint(format!("Use `{}`", usage))
+        .with_location(ctx.source_info.clone())
+        .build();
+    ShortcodeResult::Error(ShortcodeError {
+        key: name.to_string(),
+        diagnostic,
+    })
+}
+
+/// Escape HTML special characters.
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod test_support {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::LazyLock;
+
+    use quarto_pandoc_types::config_value::ConfigValue;
+    use quarto_pandoc_types::shortcode::{Shortcode, ShortcodeArg};
+    use quarto_source_map::SourceInfo;
+    use quarto_system_runtime::SystemRuntime;
+
+    use crate::format::Format;
+    use crate::transforms::shortcode_resolve::ShortcodeContext;
+
+    static METADATA: LazyLock<ConfigValue> = LazyLock::new(ConfigValue::default);
+    static SOURCE_INFO: LazyLock<SourceInfo> = LazyLock::new(SourceInfo::default);
+    static HTML: LazyLock<Format> = LazyLock::new(Format::html);
+
+    /// A context for an HTML document in `dir`.
+    pub fn context<'a>(
+        dir: &'a Path,
+        runtime: Option<&'a dyn SystemRuntime>,
+    ) -> ShortcodeContext<'a> {
+        ShortcodeContext {
+            metadata: &METADATA,
+            source_info: &SOURCE_INFO,
+            format: &HTML,
+            document_dir: dir,
+            runtime,
+            block: false,
+            diagnostics: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// A shortcode with string positional and keyword arguments.
+    pub fn shortcode(name: &str, args: &[&str], kwargs: &[(&str, &str)]) -> Shortcode {
+        Shortcode {
+            is_escaped: false,
+            name: name.to_string(),
+            positional_args: args
+                .iter()
+                .map(|arg| ShortcodeArg::String(arg.to_string()))
+                .collect(),
+            keyword_args: kwargs
+                .iter()
+                .map(|(k, v)| (k.to_string(), ShortcodeArg::String(v.to_string())))
+                .collect::<HashMap<_, _>>(),
+            source_info: SourceInfo::default(),
+        }
+    }
+}
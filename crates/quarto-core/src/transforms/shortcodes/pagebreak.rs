@@ -0,0 +1,63 @@
+/*
+ * transforms/shortcodes/pagebreak.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * The pagebreak shortcode.
+ */
+
+use quarto_pandoc_types::block::{Block, RawBlock};
+use quarto_pandoc_types::shortcode::Shortcode;
+
+use crate::format::FormatIdentifier;
+use crate::transforms::shortcode_resolve::{ShortcodeContext, ShortcodeHandler, ShortcodeResult};
+
+/// Handler for the `pagebreak` shortcode.
+///
+/// Usage: `{{< pagebreak >}}`
+///
+/// Inserts a raw page break for the output format: a `\newpage` for PDF,
+/// a `#pagebreak()` for Typst, a page break run for Word and a
+/// `page-break-after` div for HTML-based formats.
+pub struct PagebreakShortcodeHandler;
+
+impl ShortcodeHandler for PagebreakShortcodeHandler {
+    fn name(&self) -> &str {
+        "pagebreak"
+    }
+
+    fn resolve(&self, _shortcode: &Shortcode, ctx: &ShortcodeContext) -> ShortcodeResult {
+        let (format, text) = match ctx.format.identifier {
+            FormatIdentifier::Pdf => ("latex", "\\newpage{}"),
+            FormatIdentifier::Typst => ("typst", "#pagebreak()"),
+            FormatIdentifier::Docx => ("openxml", "<w:p><w:r><w:br w:type=\"page\"/></w:r></w:p>"),
+            _ => ("html", "<div style=\"page-break-after: always;\"></div>"),
+        };
+        ShortcodeResult::Blocks(vec![Block::RawBlock(RawBlock {
+            format: format.to_string(),
+            text: text.to_string(),
+            source_info: ctx.source_info.clone(),
+        })])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::shortcodes::test_support::{context, shortcode};
+    use std::path::Path;
+
+    #[test]
+    fn test_html_pagebreak() {
+        let ctx = context(Path::new("/project"), None);
+        let result = PagebreakShortcodeHandler.resolve(&shortcode("pagebreak", &[], &[]), &ctx);
+
+        let ShortcodeResult::Blocks(blocks) = result else {
+            panic!("Expected Blocks");
+        };
+        let Block::RawBlock(raw) = &blocks[0] else {
+            panic!("Expected RawBlock");
+        };
+        assert_eq!(raw.format, "html");
+        assert!(raw.text.contains("page-break-after: always"));
+    }
+}
@@ -0,0 +1,214 @@
+/*
+ * transforms/shortcodes/video.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * The video shortcode.
+ */
+
+use quarto_pandoc_types::block::{Block, RawBlock};
+use quarto_pandoc_types::inline::{Inline, Str};
+use quarto_pandoc_types::shortcode::Shortcode;
+
+use super::{html_escape, keyword, missing_argument, positional};
+use crate::transforms::shortcode_resolve::{ShortcodeContext, ShortcodeHandler, ShortcodeResult};
+
+/// Permissions granted to embedded players.
+const IFRAME_ALLOW: &str =
+    "accelerometer; autoplay; clipboard-write; encrypted-media; gyroscope; picture-in-picture";
+
+/// Handler for the `video` shortcode.
+///
+/// Usage: `{{< video URL [title=...] [width=...] [height=...] [start=...] [aspect-ratio=16x9] >}}`
+///
+/// YouTube and Vimeo links become their embedded players; anything else
+/// is treated as a video file and played with a `<video>` element. `start`
+/// is in seconds. With `aspect-ratio` (`1x1`, `4x3`, `16x9` or `21x9`) the
+/// player fills a responsive box of that ratio. Non-HTML formats get the
+/// URL as text.
+pub struct VideoShortcodeHandler;
+
+/// Where a video URL points.
+#[derive(Debug, PartialEq)]
+enum VideoSource {
+    YouTube(String),
+    Vimeo(String),
+    File(String),
+}
+
+impl VideoSource {
+    fn parse(url: &str) -> Self {
+        let rest = url
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_start_matches("www.");
+
+        let youtube_id = if let Some(query) = rest.strip_prefix("youtube.com/watch?") {
+            query.split('&').find_map(|param| param.strip_prefix("v="))
+        } else {
+            rest.strip_prefix("youtu.be/")
+                .or_else(|| rest.strip_prefix("youtube.com/embed/"))
+        };
+        if let Some(id) = youtube_id {
+            return Self::YouTube(id.split(['?', '&', '#']).next().unwrap_or(id).to_string());
+        }
+
+        if let Some(id) = rest
+            .strip_prefix("vimeo.com/")
+            .or_else(|| rest.strip_prefix("player.vimeo.com/video/"))
+            .and_then(|path| path.split(['/', '?', '#']).next())
+            .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        {
+            return Self::Vimeo(id.to_string());
+        }
+
+        Self::File(url.to_string())
+    }
+
+    /// HTML for the player, starting `start` seconds in.
+    fn player_html(&self, start: Option<&str>, sizing: &str) -> String {
+        let iframe = |src: String| {
+            format!(
+                "<iframe data-external=\"1\" src=\"{}\"{} frameborder=\"0\" allow=\"{}\" allowfullscreen></iframe>",
+                html_escape(&src),
+                sizing,
+                IFRAME_ALLOW
+            )
+        };
+        match self {
+            Self::YouTube(id) => iframe(match start {
+                Some(start) => format!("https://www.youtube.com/embed/{}?start={}", id, start),
+                None => format!("https://www.youtube.com/embed/{}", id),
+            }),
+            Self::Vimeo(id) => iframe(match start {
+                Some(start) => format!("https://player.vimeo.com/video/{}#t={}s", id, start),
+                None => format!("https://player.vimeo.com/video/{}", id),
+            }),
+            Self::File(src) => {
+                let src = match start {
+                    Some(start) => format!("{}#t={}", src, start),
+                    None => src.clone(),
+                };
+                format!(
+                    "<video{} controls><source src=\"{}\"></video>",
+                    sizing,
+                    html_escape(&src)
+                )
+            }
+        }
+    }
+}
+
+impl ShortcodeHandler for VideoShortcodeHandler {
+    fn name(&self) -> &str {
+        "video"
+    }
+
+    fn resolve(&self, shortcode: &Shortcode, ctx: &ShortcodeContext) -> ShortcodeResult {
+        let Some(url) = positional(shortcode, 0).or_else(|| keyword(shortcode, "src")) else {
+            return missing_argument("video", "{{< video URL >}}", ctx);
+        };
+
+        if !ctx.format.identifier.is_html_based() {
+            return ShortcodeResult::Inlines(vec![Inline::Str(Str {
+                text: url,
+                source_info: ctx.source_info.clone(),
+            })]);
+        }
+
+        let aspect_ratio = keyword(shortcode, "aspect-ratio")
+            .filter(|ratio| ["1x1", "4x3", "16x9", "21x9"].contains(&ratio.as_str()));
+        // A responsive box sizes the player itself
+        let sizing: String = if aspect_ratio.is_some() {
+            String::new()
+        } else {
+            ["width", "height"]
+                .iter()
+                .filter_map(|attr| {
+                    keyword(shortcode, attr)
+                        .map(|value| format!(" {}=\"{}\"", attr, html_escape(&value)))
+                })
+                .collect()
+        };
+        let title = keyword(shortcode, "title")
+            .map(|title| format!(" title=\"{}\"", html_escape(&title)))
+            .unwrap_or_default();
+
+        let player = VideoSource::parse(&url)
+            .player_html(keyword(shortcode, "start").as_deref(), &(title + &sizing));
+        let html = match aspect_ratio {
+            Some(ratio) => format!(
+                "<div class=\"quarto-video ratio ratio-{}\">{}</div>",
+                ratio, player
+            ),
+            None => format!("<div class=\"quarto-video\">{}</div>", player),
+        };
+        ShortcodeResult::Blocks(vec![Block::RawBlock(RawBlock {
+            format: "html".to_string(),
+            text: html,
+            source_info: ctx.source_info.clone(),
+        })])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transforms::shortcodes::test_support::{context, shortcode};
+    use std::path::Path;
+
+    fn html(shortcode: &Shortcode) -> String {
+        let ctx = context(Path::new("/project"), None);
+        match VideoShortcodeHandler.resolve(shortcode, &ctx) {
+            ShortcodeResult::Blocks(blocks) => match &blocks[0] {
+                Block::RawBlock(raw) => raw.text.clone(),
+                other => panic!("Expected RawBlock, got {:?}", other),
+            },
+            _ => panic!("Expected Blocks"),
+        }
+    }
+
+    #[test]
+    fn test_parse_video_sources() {
+        assert_eq!(
+            VideoSource::parse("https://www.youtube.com/watch?v=wo9vZccmqwc&t=3"),
+            VideoSource::YouTube("wo9vZccmqwc".to_string())
+        );
+        assert_eq!(
+            VideoSource::parse("https://youtu.be/wo9vZccmqwc"),
+            VideoSource::YouTube("wo9vZccmqwc".to_string())
+        );
+        assert_eq!(
+            VideoSource::parse("https://vimeo.com/548291297"),
+            VideoSource::Vimeo("548291297".to_string())
+        );
+        assert_eq!(
+            VideoSource::parse("media/intro.mp4"),
+            VideoSource::File("media/intro.mp4".to_string())
+        );
+    }
+
+    #[test]
+    fn test_youtube_player() {
+        let html = html(&shortcode(
+            "video",
+            &["https://youtu.be/wo9vZccmqwc"],
+            &[("start", "30"), ("title", "Intro"), ("width", "400")],
+        ));
+        assert!(html.starts_with("<div class=\"quarto-video\"><iframe"));
+        assert!(html.contains("src=\"https://www.youtube.com/embed/wo9vZccmqwc?start=30\""));
+        assert!(html.contains(" title=\"Intro\" width=\"400\""));
+    }
+
+    #[test]
+    fn test_video_file_with_aspect_ratio() {
+        let html = html(&shortcode(
+            "video",
+            &["intro.mp4"],
+            &[("aspect-ratio", "16x9"), ("width", "400")],
+        ));
+        assert_eq!(
+            html,
+            "<div class=\"quarto-video ratio ratio-16x9\"><video controls><source src=\"intro.mp4\"></video></div>"
+        );
+    }
+}
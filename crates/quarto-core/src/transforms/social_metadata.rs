@@ -0,0 +1,522 @@
+/*
+ * social_metadata.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Transform that renders OpenGraph, Twitter card and favicon metadata.
+ */
+
+//! Social metadata transform.
+//!
+//! Renders the `<meta>`/`<link>` tags that link previews and browser tabs
+//! use, into `rendered.social-meta` for the template's `<head>`:
+//!
+//! - `open-graph` - `og:title`, `og:description`, `og:image`, `og:url`, ...
+//! - `twitter-card` - `twitter:card`, `twitter:title`, `twitter:image`, ...
+//! - `favicon` - `<link rel="icon">`, falling back to the small brand logo
+//!
+//! `open-graph` and `twitter-card` are enabled with `true` or a map of
+//! options, under `website:` for every page or in a document's metadata
+//! (which wins). Titles and descriptions come from the card options, then
+//! the document, then the website. The image comes from the card options,
+//! the document's `image`, the first listed document's image on a listing
+//! page, or the website's `image`.
+//!
+//! Local images are checked: a missing image is dropped with a warning
+//! rather than producing a broken preview. With `site-url` set, image and
+//! page URLs are absolute, as the OpenGraph protocol requires.
+
+use std::path::{Component, Path};
+
+use quarto_error_reporting::DiagnosticMessageBuilder;
+use quarto_pandoc_types::ConfigValue;
+use quarto_pandoc_types::pandoc::Pandoc;
+use quarto_source_map::SourceInfo;
+use serde_json::{Map, Value};
+
+use super::listing::{html_escape, meta_to_json};
+use crate::Result;
+use crate::brand::brand_logo;
+use crate::listing::Listing;
+use crate::render::RenderContext;
+use crate::transform::AstTransform;
+
+/// Brand logo sizes usable as a favicon, in order of preference.
+const FAVICON_LOGO_SIZES: &[&str] = &["small", "medium", "large"];
+
+/// Transform that renders social card and favicon metadata.
+pub struct SocialMetadataTransform;
+
+impl SocialMetadataTransform {
+    /// Create a new social metadata transform.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SocialMetadataTransform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AstTransform for SocialMetadataTransform {
+    fn name(&self) -> &str {
+        "social-metadata"
+    }
+
+    fn transform(&self, ast: &mut Pandoc, ctx: &mut RenderContext) -> Result<()> {
+        let mut page = Page::new(ctx);
+        let mut tags = Vec::new();
+
+        if let Some(href) = page.favicon(ast, ctx) {
+            tags.push(format!(
+                "<link rel=\"icon\" href=\"{}\"{}>",
+                html_escape(&href),
+                icon_type(&href)
+                    .map(|t| format!(" type=\"{}\"", t))
+                    .unwrap_or_default()
+            ));
+        }
+
+        if let Some(card) = page.card("open-graph", ast, ctx) {
+            let mut og = vec![
+                ("og:title", card.title.clone()),
+                ("og:description", card.description.clone()),
+                ("og:image", card.image.clone()),
+                ("og:image:alt", card.option("image-alt")),
+                ("og:url", page.url.clone()),
+                (
+                    "og:site_name",
+                    card.option("site-name").or_else(|| page.site_title.clone()),
+                ),
+                ("og:locale", card.option("locale")),
+            ];
+            og.retain(|(_, value)| value.is_some());
+            tags.extend(og.into_iter().map(|(property, value)| {
+                meta_tag("property", property, &value.unwrap_or_default())
+            }));
+        }
+
+        if let Some(card) = page.card("twitter-card", ast, ctx) {
+            let style = card.option("card-style").unwrap_or_else(|| {
+                if card.image.is_some() {
+                    "summary_large_image".to_string()
+                } else {
+                    "summary".to_string()
+                }
+            });
+            let mut twitter = vec![
+                ("twitter:card", Some(style)),
+                ("twitter:title", card.title.clone()),
+                ("twitter:description", card.description.clone()),
+                ("twitter:image", card.image.clone()),
+                ("twitter:image:alt", card.option("image-alt")),
+                ("twitter:creator", card.option("creator")),
+                ("twitter:site", card.option("site")),
+            ];
+            twitter.retain(|(_, value)| value.is_some());
+            tags.extend(
+                twitter
+                    .into_iter()
+                    .map(|(name, value)| meta_tag("name", name, &value.unwrap_or_default())),
+            );
+        }
+
+        if !tags.is_empty() {
+            ast.meta.insert_path(
+                &["rendered", "social-meta"],
+                ConfigValue::new_string(tags.join("\n"), SourceInfo::default()),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The options and resolved content of one card.
+struct Card {
+    options: Map<String, Value>,
+    title: Option<String>,
+    description: Option<String>,
+    image: Option<String>,
+}
+
+impl Card {
+    fn option(&self, key: &str) -> Option<String> {
+        self.options
+            .get(key)
+            .and_then(Value::as_str)
+            .map(String::from)
+    }
+}
+
+/// What the cards need to know about the page being rendered.
+struct Page {
+    /// The project's `website:` (or `book:`) configuration
+    website: Option<Value>,
+    /// Project-relative directory of the document, with a trailing `/`
+    dir: String,
+    /// Prefix from the page to the project's output root
+    root: String,
+    /// `site-url`, without a trailing `/`
+    site_url: Option<String>,
+    /// Absolute URL of the page (with `site-url`)
+    url: Option<String>,
+    /// Website title
+    site_title: Option<String>,
+    /// Images already reported missing
+    missing: Vec<String>,
+}
+
+impl Page {
+    fn new(ctx: &RenderContext) -> Self {
+        let website = ctx.project.config.as_ref().and_then(|config| {
+            config
+                .raw
+                .get("website")
+                .or_else(|| config.raw.get("book"))
+                .cloned()
+        });
+        let string = |key: &str| {
+            website
+                .as_ref()
+                .and_then(|w| w.get(key))
+                .and_then(Value::as_str)
+                .map(String::from)
+        };
+        let site_url = string("site-url").map(|url| url.trim_end_matches('/').to_string());
+        let site_title = string("title");
+
+        let input = ctx
+            .document
+            .input
+            .strip_prefix(&ctx.project.dir)
+            .unwrap_or(&ctx.document.input)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let (dir, depth) = match input.rsplit_once('/') {
+            Some((dir, _)) => (format!("{}/", dir), dir.matches('/').count() + 1),
+            None => (String::new(), 0),
+        };
+        let output = match input.rsplit_once('.') {
+            Some((stem, _)) => format!("{}.{}", stem, ctx.format.output_extension),
+            None => input.clone(),
+        };
+        let url = site_url.as_ref().map(|site| format!("{}/{}", site, output));
+
+        Self {
+            website,
+            dir,
+            root: "../".repeat(depth),
+            site_url,
+            url,
+            site_title,
+            missing: Vec::new(),
+        }
+    }
+
+    fn website_str(&self, key: &str) -> Option<&str> {
+        self.website.as_ref()?.get(key)?.as_str()
+    }
+
+    /// The card configured under `key`, or `None` if it isn't enabled.
+    fn card(&mut self, key: &str, ast: &Pandoc, ctx: &mut RenderContext) -> Option<Card> {
+        let document = ast
+            .meta
+            .get(key)
+            .map(meta_to_json)
+            .or_else(|| ctx.format_metadata(key).cloned());
+        let site = self.website.as_ref().and_then(|w| w.get(key));
+
+        let enabled = |value: Option<&Value>| match value {
+            Some(Value::Bool(b)) => Some(*b),
+            Some(Value::Object(_)) => Some(true),
+            _ => None,
+        };
+        if !enabled(document.as_ref())
+            .or(enabled(site))
+            .unwrap_or(false)
+        {
+            return None;
+        }
+
+        let as_map = |value: Option<&Value>| match value {
+            Some(Value::Object(map)) => map.clone(),
+            _ => Map::new(),
+        };
+        let doc_options = as_map(document.as_ref());
+        let site_options = as_map(site);
+        let mut options = site_options.clone();
+        options.extend(doc_options.clone());
+
+        let doc_text = |name: &str| ast.meta.get(name).and_then(ConfigValue::as_plain_text);
+        let pick = |name: &str, fallback: Option<String>| {
+            doc_options
+                .get(name)
+                .and_then(Value::as_str)
+                .map(String::from)
+                .or(fallback)
+                .or_else(|| {
+                    site_options
+                        .get(name)
+                        .and_then(Value::as_str)
+                        .map(String::from)
+                })
+        };
+        let title = pick("title", doc_text("pagetitle").or_else(|| doc_text("title")))
+            .or_else(|| self.site_title.clone());
+        let description = pick("description", doc_text("description"))
+            .or_else(|| self.website_str("description").map(String::from));
+
+        // Candidate images, each with the project-relative directory it is
+        // relative to
+        let mut images: Vec<(String, String)> = Vec::new();
+        let string = |value: Option<&Value>| value.and_then(Value::as_str).map(String::from);
+        images.extend(string(doc_options.get("image")).map(|i| (i, self.dir.clone())));
+        images.extend(doc_text("image").map(|i| (i, self.dir.clone())));
+        images.extend(string(site_options.get("image")).map(|i| (i, String::new())));
+        images.extend(self.listing_image(ast, ctx));
+        images.extend(
+            self.website_str("image")
+                .map(|i| (i.to_string(), String::new())),
+        );
+
+        let image = images
+            .into_iter()
+            .find_map(|(image, dir)| self.image_href(&image, &dir, ctx));
+
+        Some(Card {
+            options,
+            title,
+            description,
+            image,
+        })
+    }
+
+    /// The image of the first listed document that has one.
+    fn listing_image(&self, ast: &Pandoc, ctx: &RenderContext) -> Option<(String, String)> {
+        let listing = ast.meta.get("listing")?;
+        let runtime = ctx.runtime.clone()?;
+        Listing::from_metadata(&meta_to_json(listing))
+            .iter()
+            .flat_map(|listing| listing.items(ctx.project, &ctx.document.input, runtime.as_ref()))
+            .find_map(|item| {
+                let image = item.image?;
+                let dir = item
+                    .input
+                    .parent()
+                    .and_then(|dir| dir.strip_prefix(&ctx.project.dir).ok())
+                    .map(|dir| dir.to_string_lossy().replace('\\', "/"))
+                    .filter(|dir| !dir.is_empty())
+                    .map(|dir| format!("{}/", dir))
+                    .unwrap_or_default();
+                Some((image, dir))
+            })
+    }
+
+    /// The href of an image given relative to the project-relative `dir`
+    /// (or, starting with `/`, to the project); `None` with a warning if a
+    /// local image doesn't exist.
+    fn image_href(&mut self, image: &str, dir: &str, ctx: &mut RenderContext) -> Option<String> {
+        if image.contains("://") {
+            return Some(image.to_string());
+        }
+        let path = match image.strip_prefix('/') {
+            Some(rest) => normalize(rest),
+            None => normalize(&format!("{}{}", dir, image)),
+        };
+
+        if let Some(runtime) = &ctx.runtime
+            && !runtime
+                .is_file(&ctx.project.dir.join(&path))
+                .unwrap_or(false)
+        {
+            if self.missing.contains(&path) {
+                return None;
+            }
+            self.missing.push(path);
+            ctx.diagnostics.push(
+                DiagnosticMessageBuilder::warning("Social card image not found")
+                    .problem(format!("The image `{}` does not exist", image))
+                    .add_hint("The card is rendered without an image")
+                    .build(),
+            );
+            return None;
+        }
+
+        Some(match &self.site_url {
+            Some(site) => format!("{}/{}", site, path),
+            None => format!("{}{}", self.root, path),
+        })
+    }
+
+    /// The favicon href: the website's `favicon` (relative to the project),
+    /// else the document's, else the small brand logo.
+    fn favicon(&mut self, ast: &Pandoc, ctx: &mut RenderContext) -> Option<String> {
+        if let Some(favicon) = self.website_str("favicon").map(String::from) {
+            return self.image_href(&favicon, "", ctx);
+        }
+        if let Some(favicon) = ast.meta.get("favicon").and_then(ConfigValue::as_plain_text) {
+            let dir = self.dir.clone();
+            return self.image_href(&favicon, &dir, ctx);
+        }
+        let (logo, _) = brand_logo(&ast.meta, FAVICON_LOGO_SIZES)?;
+        Some(logo)
+    }
+}
+
+/// A `<meta>` tag with the name in attribute `attr`.
+fn meta_tag(attr: &str, name: &str, content: &str) -> String {
+    format!(
+        "<meta {}=\"{}\" content=\"{}\">",
+        attr,
+        name,
+        html_escape(content)
+    )
+}
+
+/// The MIME type of an icon, from its extension.
+fn icon_type(href: &str) -> Option<&'static str> {
+    let ext = href.rsplit_once('.')?.1.to_ascii_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "ico" => Some("image/x-icon"),
+        "svg" => Some("image/svg+xml"),
+        "gif" => Some("image/gif"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        _ => None,
+    }
+}
+
+/// Resolve `.` and `..` in a `/`-separated relative path.
+fn normalize(path: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::ParentDir => {
+                parts.pop();
+            }
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            _ => {}
+        }
+    }
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectConfig, ProjectContext};
+    use crate::render::BinaryDependencies;
+    use quarto_system_runtime::NativeRuntime;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn string(s: &str) -> ConfigValue {
+        ConfigValue::new_string(s, SourceInfo::default())
+    }
+
+    fn render(dir: &Path, website: Value, meta: &[(&str, &str)]) -> (Option<String>, Vec<String>) {
+        std::fs::create_dir_all(dir.join("posts/images")).unwrap();
+        std::fs::write(dir.join("posts/images/card.png"), "png").unwrap();
+        std::fs::write(dir.join("favicon.ico"), "ico").unwrap();
+
+        let project = ProjectContext {
+            dir: dir.to_path_buf(),
+            config: Some(ProjectConfig {
+                raw: json!({ "website": website }),
+                ..ProjectConfig::default()
+            }),
+            is_single_file: false,
+            files: vec![],
+            output_dir: dir.join("_site"),
+        };
+        let doc = DocumentInfo::from_path(dir.join("posts/hello.qmd"));
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries)
+            .with_runtime(Arc::new(NativeRuntime::new()));
+
+        let mut ast = Pandoc::default();
+        for (key, value) in meta {
+            ast.meta.insert_path(&[*key], string(value));
+        }
+        SocialMetadataTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+
+        let rendered = ast
+            .meta
+            .get_path(&["rendered", "social-meta"])
+            .and_then(ConfigValue::as_str)
+            .map(String::from);
+        let warnings = ctx.diagnostics.iter().map(|d| d.title.clone()).collect();
+        (rendered, warnings)
+    }
+
+    #[test]
+    fn test_transform_name() {
+        assert_eq!(SocialMetadataTransform::new().name(), "social-metadata");
+    }
+
+    #[test]
+    fn test_open_graph_and_twitter_card() {
+        let temp = tempfile::tempdir().unwrap();
+        let (html, warnings) = render(
+            temp.path(),
+            json!({
+                "title": "My Site",
+                "site-url": "https://example.com/",
+                "favicon": "favicon.ico",
+                "open-graph": true,
+                "twitter-card": { "creator": "@me" },
+            }),
+            &[
+                ("title", "Hello & welcome"),
+                ("description", "A first post"),
+                ("image", "images/card.png"),
+            ],
+        );
+        let html = html.unwrap();
+
+        assert!(warnings.is_empty());
+        assert!(html.contains(
+            r#"<link rel="icon" href="https://example.com/favicon.ico" type="image/x-icon">"#
+        ));
+        assert!(html.contains(r#"<meta property="og:title" content="Hello &amp; welcome">"#));
+        assert!(html.contains(r#"<meta property="og:description" content="A first post">"#));
+        assert!(html.contains(
+            r#"<meta property="og:image" content="https://example.com/posts/images/card.png">"#
+        ));
+        assert!(html.contains(
+            r#"<meta property="og:url" content="https://example.com/posts/hello.html">"#
+        ));
+        assert!(html.contains(r#"<meta property="og:site_name" content="My Site">"#));
+        assert!(html.contains(r#"<meta name="twitter:card" content="summary_large_image">"#));
+        assert!(html.contains(r#"<meta name="twitter:creator" content="@me">"#));
+    }
+
+    #[test]
+    fn test_document_options_and_missing_image() {
+        let temp = tempfile::tempdir().unwrap();
+        let (html, warnings) = render(
+            temp.path(),
+            json!({ "open-graph": true, "twitter-card": true, "image": "missing.png" }),
+            &[("title", "Hello")],
+        );
+        let html = html.unwrap();
+
+        // Reported once, though both cards looked for it
+        assert_eq!(warnings, vec!["Social card image not found".to_string()]);
+        assert!(!html.contains("og:image"));
+        assert!(html.contains(r#"<meta name="twitter:card" content="summary">"#));
+        assert!(!html.contains("og:url"));
+    }
+
+    #[test]
+    fn test_nothing_configured() {
+        let temp = tempfile::tempdir().unwrap();
+        let (html, _) = render(temp.path(), json!({}), &[("title", "Hello")]);
+        assert_eq!(html, None);
+    }
+}
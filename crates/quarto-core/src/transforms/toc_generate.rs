@@ -12,7 +12,10 @@
 //!
 //! - Checks if `toc: true` or `toc: auto` is set in format metadata
 //! - Skips if `navigation.toc` already exists (user-provided or from earlier filter)
+//! - Gives headings without an ID an automatic one, so TOC entries can link to them
 //! - Delegates to `pampa::toc::generate_toc` for the actual TOC extraction
+//! - Takes section numbers from the `number` attributes set by
+//!   [`NumberSectionsTransform`](super::NumberSectionsTransform)
 //! - Stores the result in document metadata for later rendering
 //!
 //! ## Configuration
@@ -36,7 +39,7 @@
 //!         children: [...]
 //! ```
 
-use pampa::toc::{TocConfig, generate_toc};
+use pampa::toc::{TocConfig, assign_section_ids, generate_toc};
 use quarto_pandoc_types::pandoc::Pandoc;
 
 use crate::Result;
@@ -105,7 +108,16 @@ impl AstTransform for TocGenerateTransform {
             .map(String::from)
             .or_else(|| Some("Table of Contents".to_string()));
 
-        let config = TocConfig { depth, title };
+        let config = TocConfig {
+            depth,
+            title,
+            // Numbers come from the headers' `number` attributes
+            number_sections: false,
+            auto_identifiers: true,
+        };
+
+        // Give headings without an ID the one their TOC entry links to
+        assign_section_ids(&mut ast.blocks);
 
         // Generate TOC from document blocks
         let toc = generate_toc(&ast.blocks, &config);
@@ -235,6 +247,45 @@ mod tests {
         assert_eq!(entries[1].get("id").unwrap().as_str(), Some("methods"));
     }
 
+    #[test]
+    fn test_assigns_ids_and_numbers() {
+        let mut ast = Pandoc {
+            meta: quarto_pandoc_types::ConfigValue::default(),
+            blocks: vec![
+                make_header(2, "", "Results"),
+                make_para("Content."),
+                make_header(2, "", "Results"),
+            ],
+        };
+
+        let project = make_test_project();
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let format = Format::html().with_metadata(serde_json::json!({
+            "toc": true,
+            "number-sections": true
+        }));
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+
+        crate::transforms::NumberSectionsTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+        let transform = TocGenerateTransform::new();
+        transform.transform(&mut ast, &mut ctx).unwrap();
+
+        // Headers get the ids the TOC links to
+        let Block::Header(second) = &ast.blocks[2] else {
+            panic!("expected Header");
+        };
+        assert_eq!(second.attr.0, "results-1");
+
+        let toc = ast.meta.get_path(&["navigation", "toc"]).unwrap();
+        let entries = toc.get("entries").unwrap().as_array().unwrap();
+        assert_eq!(entries[0].get("id").unwrap().as_str(), Some("results"));
+        assert_eq!(entries[1].get("id").unwrap().as_str(), Some("results-1"));
+        assert_eq!(entries[1].get("number").unwrap().as_str(), Some("2"));
+    }
+
     #[test]
     fn test_generates_toc_with_string_auto() {
         let mut ast = Pandoc {
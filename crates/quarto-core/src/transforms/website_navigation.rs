@@ -0,0 +1,1035 @@
+/*
+ * website_navigation.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Transform that renders website navigation to HTML.
+ */
+
+//! Website navigation transform for HTML output.
+//!
+//! For pages of a project with a `website:` section in `_quarto.yml`, this
+//! transform renders the site navigation from that configuration and stores
+//! it in document metadata for the template:
+//!
+//! - `rendered.navigation.navbar` - from `website.navbar` (`left`/`right`
+//!   items, optional `menu` dropdowns), titled by `website.title`, with
+//!   `navbar.logo` or else the brand's logo (see [`crate::brand`])
+//! - `rendered.navigation.sidebar` - from `website.sidebar` (a sidebar or a
+//!   list of sidebars; the one containing the current page is used).
+//!   `contents: auto` lists every project file.
+//! - `rendered.navigation.breadcrumbs` - the sidebar sections leading to the
+//!   current page (disable with `website.bread-crumbs: false`)
+//! - `rendered.navigation.footer` - from `website.page-footer` (a string, or
+//!   `left`/`center`/`right` strings or item lists)
+//! - `rendered.navigation.page-nav` - previous/next chapter links (books)
+//!
+//! Book projects read the same options from `book:`. Without a `sidebar`,
+//! a book gets one listing its chapters in order, numbered, with parts as
+//! sections.
+//!
+//! Items are either an `href` string or a map with `href`, `text`, `icon`
+//! and (navbar) `menu` or (sidebar) `section` + `contents`. Hrefs are
+//! relative to the project directory; `.qmd`/`.md` targets are mapped to
+//! their output files and made relative to the current page. The item for
+//! the current page gets the `active` class and `aria-current="page"`.
+//! Items without `text` use the target page's title.
+//!
+//! As with [`TocRenderTransform`](super::TocRenderTransform), any part a
+//! document already provides under `rendered.navigation` is left alone.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use quarto_pandoc_types::config_value::ConfigValue;
+use quarto_pandoc_types::pandoc::Pandoc;
+use quarto_source_map::SourceInfo;
+use serde_json::Value;
+
+use crate::Result;
+use crate::book::{Book, BookChapter};
+use crate::brand::{LOGO_SIZES, brand_logo};
+use crate::render::RenderContext;
+use crate::transform::AstTransform;
+
+/// Transform that renders website navbar, sidebar, breadcrumbs and footer.
+pub struct WebsiteNavigationTransform;
+
+impl WebsiteNavigationTransform {
+    /// Create a new website navigation transform.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for WebsiteNavigationTransform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AstTransform for WebsiteNavigationTransform {
+    fn name(&self) -> &str {
+        "website-navigation"
+    }
+
+    fn transform(&self, ast: &mut Pandoc, ctx: &mut RenderContext) -> Result<()> {
+        let Some(config) = ctx.project.config.as_ref() else {
+            return Ok(());
+        };
+        // Books keep their navigation options under `book:`
+        let book = Book::from_project(ctx.project);
+        let Some(website) = config
+            .raw
+            .get("website")
+            .or_else(|| book.as_ref().and(config.raw.get("book")))
+        else {
+            return Ok(());
+        };
+
+        let site = Site::new(ctx);
+        let mut rendered = Vec::new();
+
+        if let Some(navbar) = website.get("navbar").filter(|n| n.as_bool() != Some(false)) {
+            let logo = match navbar.get("logo").and_then(Value::as_str) {
+                Some(logo) => Some((
+                    site.link(logo),
+                    navbar
+                        .get("logo-alt")
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                )),
+                None => brand_logo(&ast.meta, &LOGO_SIZES),
+            };
+            rendered.push(("navbar", site.render_navbar(website, navbar, logo)));
+        }
+
+        let sidebar = match website.get("sidebar") {
+            Some(sidebar) => site.select_sidebar(sidebar),
+            None => book.as_ref().map(|book| site.book_sidebar(book)),
+        };
+        if let Some(sidebar) = sidebar {
+            let trail = site.active_trail(&sidebar.items);
+            let breadcrumbs = website.get("bread-crumbs").and_then(Value::as_bool) != Some(false);
+            if breadcrumbs && trail.len() > 1 {
+                rendered.push(("breadcrumbs", site.render_breadcrumbs(&trail)));
+            }
+            rendered.push(("sidebar", site.render_sidebar(&sidebar)));
+        }
+
+        if let Some(footer) = website.get("page-footer") {
+            rendered.push(("footer", site.render_footer(footer)));
+        }
+
+        if let Some(book) = &book {
+            rendered.push(("page-nav", site.render_page_nav(book, &ctx.document.input)));
+        }
+
+        for (slot, html) in rendered {
+            if html.is_empty() || ast.meta.contains_path(&["rendered", "navigation", slot]) {
+                continue;
+            }
+            ast.meta.insert_path(
+                &["rendered", "navigation", slot],
+                ConfigValue::new_string(&html, SourceInfo::default()),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// A navigation entry from the website configuration.
+#[derive(Debug, Clone, Default)]
+struct NavItem {
+    text: Option<String>,
+    href: Option<String>,
+    icon: Option<String>,
+    /// Navbar dropdown entries or sidebar section contents
+    children: Vec<NavItem>,
+}
+
+/// A sidebar from the website configuration.
+#[derive(Debug, Default)]
+struct Sidebar {
+    title: Option<String>,
+    items: Vec<NavItem>,
+}
+
+/// The current page's place in the website.
+struct Site<'a> {
+    /// Project directory, which hrefs are relative to
+    dir: &'a Path,
+    /// Output path of the current page, relative to the project directory
+    current: String,
+    /// Prefix from the current page to the project's output root
+    root: String,
+    /// Output extension for `.qmd`/`.md` targets
+    extension: &'a str,
+    /// Page titles by project-relative output path
+    titles: HashMap<String, String>,
+    /// Project-relative input paths, for `contents: auto`
+    inputs: Vec<String>,
+}
+
+impl<'a> Site<'a> {
+    fn new(ctx: &'a RenderContext) -> Self {
+        let mut site = Self {
+            dir: &ctx.project.dir,
+            current: String::new(),
+            root: String::new(),
+            extension: ctx.format.output_extension.as_str(),
+            titles: HashMap::new(),
+            inputs: Vec::new(),
+        };
+
+        let current_input = site.relative(&ctx.document.input);
+        let depth = current_input.matches('/').count();
+        site.root = if depth == 0 {
+            "./".to_string()
+        } else {
+            "../".repeat(depth)
+        };
+        site.current = site.output_of(&current_input);
+
+        for doc in &ctx.project.files {
+            let input = site.relative(&doc.input);
+            if let Some(title) = &doc.title {
+                site.titles.insert(site.output_of(&input), title.clone());
+            }
+            site.inputs.push(input);
+        }
+
+        site
+    }
+
+    /// A path relative to the project directory, with `/` separators.
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(self.dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Map a project-relative target to its project-relative output path.
+    fn output_of(&self, target: &str) -> String {
+        let target = target.trim_start_matches('/');
+        match target.rsplit_once('.') {
+            Some((stem, "qmd" | "md" | "ipynb")) => format!("{}.{}", stem, self.extension),
+            _ => target.to_string(),
+        }
+    }
+
+    /// Whether an href points outside the project's pages.
+    fn is_external(href: &str) -> bool {
+        href.contains("://") || href.starts_with('#') || href.starts_with("mailto:")
+    }
+
+    /// The href to use from the current page.
+    fn link(&self, href: &str) -> String {
+        if Self::is_external(href) {
+            href.to_string()
+        } else {
+            format!("{}{}", self.root, self.output_of(href))
+        }
+    }
+
+    fn is_active(&self, item: &NavItem) -> bool {
+        item.href
+            .as_deref()
+            .is_some_and(|href| !Self::is_external(href) && self.output_of(href) == self.current)
+    }
+
+    /// Display text: explicit text, the target page's title, or its stem.
+    fn text(&self, item: &NavItem) -> String {
+        if let Some(text) = &item.text {
+            return text.clone();
+        }
+        let Some(href) = &item.href else {
+            return String::new();
+        };
+        if let Some(title) = self.titles.get(&self.output_of(href)) {
+            return title.clone();
+        }
+        Path::new(href)
+            .file_stem()
+            .map_or_else(|| href.clone(), |stem| stem.to_string_lossy().into_owned())
+    }
+
+    /// `logo` is the href and alt text of the navbar logo.
+    fn render_navbar(
+        &self,
+        website: &Value,
+        navbar: &Value,
+        logo: Option<(String, String)>,
+    ) -> String {
+        let title = match navbar.get("title") {
+            Some(Value::Bool(false)) => None,
+            Some(title) => title.as_str(),
+            None => website.get("title").and_then(Value::as_str),
+        };
+
+        let mut html = String::from(
+            "<nav class=\"navbar navbar-expand-lg\" data-bs-theme=\"dark\">\n\
+             <div class=\"navbar-container container-fluid\">\n",
+        );
+        if title.is_some() || logo.is_some() {
+            html.push_str(&format!(
+                "<div class=\"navbar-brand-container mx-auto\">\n\
+                 <a class=\"navbar-brand\" href=\"{}index.{}\">\n",
+                self.root, self.extension,
+            ));
+            if let Some((src, alt)) = &logo {
+                html.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\" class=\"navbar-logo\">\n",
+                    html_escape(src),
+                    html_escape(alt)
+                ));
+            }
+            if let Some(title) = title {
+                html.push_str(&format!(
+                    "<span class=\"navbar-title\">{}</span>\n",
+                    html_escape(title)
+                ));
+            }
+            html.push_str("</a>\n</div>\n");
+        }
+        html.push_str(
+            "<button class=\"navbar-toggler\" type=\"button\" data-bs-toggle=\"collapse\" \
+             data-bs-target=\"#navbarCollapse\" aria-controls=\"navbarCollapse\" \
+             aria-expanded=\"false\" aria-label=\"Toggle navigation\">\n\
+             <span class=\"navbar-toggler-icon\"></span>\n\
+             </button>\n\
+             <div class=\"collapse navbar-collapse\" id=\"navbarCollapse\">\n",
+        );
+
+        for (side, margin) in [("left", "me-auto"), ("right", "ms-auto")] {
+            let items = parse_items(navbar.get(side));
+            if items.is_empty() {
+                continue;
+            }
+            html.push_str(&format!(
+                "<ul class=\"navbar-nav navbar-nav-scroll {}\">\n",
+                margin
+            ));
+            for (i, item) in items.iter().enumerate() {
+                html.push_str(&self.render_navbar_item(item, &format!("{}-{}", side, i + 1)));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        html.push_str("</div>\n</div>\n</nav>\n");
+        html
+    }
+
+    fn render_navbar_item(&self, item: &NavItem, id: &str) -> String {
+        if item.children.is_empty() {
+            return format!(
+                "<li class=\"nav-item\">\n{}</li>\n",
+                self.render_link(item, "nav-link", "menu-text")
+            );
+        }
+
+        let menu_id = format!("nav-menu-{}", id);
+        let mut html = format!(
+            "<li class=\"nav-item dropdown\">\n\
+             <a class=\"nav-link dropdown-toggle\" href=\"#\" id=\"{}\" role=\"button\" \
+             data-bs-toggle=\"dropdown\" aria-expanded=\"false\">\n\
+             {}<span class=\"menu-text\">{}</span>\n\
+             </a>\n\
+             <ul class=\"dropdown-menu\" aria-labelledby=\"{}\">\n",
+            menu_id,
+            icon_html(item),
+            html_escape(&self.text(item)),
+            menu_id
+        );
+        for child in &item.children {
+            if child.href.is_none() && child.text.as_deref().is_some_and(is_separator) {
+                html.push_str("<li><hr class=\"dropdown-divider\"></li>\n");
+            } else if child.href.is_none() {
+                html.push_str(&format!(
+                    "<li class=\"dropdown-header\">{}</li>\n",
+                    html_escape(&self.text(child))
+                ));
+            } else {
+                html.push_str(&format!(
+                    "<li>\n{}</li>\n",
+                    self.render_link(child, "dropdown-item", "dropdown-text")
+                ));
+            }
+        }
+        html.push_str("</ul>\n</li>\n");
+        html
+    }
+
+    /// Render an `<a>` for an item, marking it active for the current page.
+    fn render_link(&self, item: &NavItem, class: &str, text_class: &str) -> String {
+        let href = item.href.as_deref().unwrap_or("#");
+        let active = if self.is_active(item) {
+            " active\" aria-current=\"page"
+        } else {
+            ""
+        };
+        format!(
+            "<a class=\"{}{}\" href=\"{}\">\n{}<span class=\"{}\">{}</span>\n</a>\n",
+            class,
+            active,
+            html_escape(&self.link(href)),
+            icon_html(item),
+            text_class,
+            html_escape(&self.text(item))
+        )
+    }
+
+    /// Pick the sidebar to show: the first one containing the current page,
+    /// or the first sidebar.
+    fn select_sidebar(&self, config: &Value) -> Option<Sidebar> {
+        let configs: Vec<&Value> = match config {
+            Value::Array(sidebars) => sidebars.iter().collect(),
+            Value::Object(_) => vec![config],
+            _ => return None,
+        };
+
+        let sidebars: Vec<Sidebar> = configs
+            .into_iter()
+            .map(|sidebar| Sidebar {
+                title: sidebar
+                    .get("title")
+                    .and_then(Value::as_str)
+                    .map(String::from),
+                items: self.sidebar_contents(sidebar.get("contents")),
+            })
+            .collect();
+
+        let index = sidebars
+            .iter()
+            .position(|sidebar| !self.active_trail(&sidebar.items).is_empty())
+            .unwrap_or(0);
+        sidebars.into_iter().nth(index)
+    }
+
+    fn sidebar_contents(&self, contents: Option<&Value>) -> Vec<NavItem> {
+        if contents.and_then(Value::as_str) == Some("auto") {
+            return self
+                .inputs
+                .iter()
+                .map(|input| NavItem {
+                    href: Some(input.clone()),
+                    ..Default::default()
+                })
+                .collect();
+        }
+        parse_items(contents)
+    }
+
+    /// The items from the top of a sidebar down to the current page, or
+    /// nothing if the page isn't in it.
+    fn active_trail<'i>(&self, items: &'i [NavItem]) -> Vec<&'i NavItem> {
+        for item in items {
+            if self.is_active(item) {
+                return vec![item];
+            }
+            let trail = self.active_trail(&item.children);
+            if !trail.is_empty() {
+                let mut full = vec![item];
+                full.extend(trail);
+                return full;
+            }
+        }
+        Vec::new()
+    }
+
+    fn render_sidebar(&self, sidebar: &Sidebar) -> String {
+        let mut html = String::from(
+            "<nav id=\"quarto-sidebar\" class=\"sidebar collapse collapse-horizontal \
+             quarto-sidebar-collapse-item sidebar-navigation floating overflow-auto\">\n",
+        );
+        if let Some(title) = &sidebar.title {
+            html.push_str(&format!(
+                "<div class=\"pt-lg-2 mt-2 text-left sidebar-header\">\n\
+                 <div class=\"sidebar-title mb-0 py-0\">\n\
+                 <a href=\"{}index.{}\">{}</a>\n\
+                 </div>\n\
+                 </div>\n",
+                self.root,
+                self.extension,
+                html_escape(title)
+            ));
+        }
+        html.push_str(
+            "<div class=\"sidebar-menu-container\">\n<ul class=\"list-unstyled mt-1\">\n",
+        );
+        let mut section = 0;
+        html.push_str(&self.render_sidebar_items(&sidebar.items, 1, &mut section));
+        html.push_str("</ul>\n</div>\n</nav>\n");
+        html
+    }
+
+    fn render_sidebar_items(&self, items: &[NavItem], depth: usize, section: &mut usize) -> String {
+        let mut html = String::new();
+        for item in items {
+            if item.children.is_empty() {
+                html.push_str(&format!(
+                    "<li class=\"sidebar-item\">\n<div class=\"sidebar-item-container\">\n{}</div>\n</li>\n",
+                    self.render_link(item, "sidebar-item-text sidebar-link", "menu-text")
+                ));
+                continue;
+            }
+
+            *section += 1;
+            let id = format!("quarto-sidebar-section-{}", section);
+            let expanded = !self.active_trail(&item.children).is_empty();
+            let header = if item.href.is_some() {
+                self.render_link(item, "sidebar-item-text sidebar-link", "menu-text")
+            } else {
+                format!(
+                    "<a class=\"sidebar-item-text sidebar-link text-start\" data-bs-toggle=\"collapse\" \
+                     data-bs-target=\"#{}\" role=\"navigation\" aria-expanded=\"{}\">\n\
+                     <span class=\"menu-text\">{}</span>\n\
+                     </a>\n",
+                    id,
+                    expanded,
+                    html_escape(&self.text(item))
+                )
+            };
+            html.push_str(&format!(
+                "<li class=\"sidebar-item sidebar-item-section\">\n\
+                 <div class=\"sidebar-item-container\">\n{}</div>\n\
+                 <ul id=\"{}\" class=\"collapse list-unstyled sidebar-section depth{}{}\">\n",
+                header,
+                id,
+                depth,
+                if expanded { " show" } else { "" }
+            ));
+            html.push_str(&self.render_sidebar_items(&item.children, depth + 1, section));
+            html.push_str("</ul>\n</li>\n");
+        }
+        html
+    }
+
+    /// The default sidebar of a book: its chapters, with parts as sections.
+    fn book_sidebar(&self, book: &Book) -> Sidebar {
+        let mut items: Vec<NavItem> = Vec::new();
+        let mut current_part = None;
+
+        for chapter in &book.chapters {
+            let href = self.relative(&chapter.input);
+            let title = self.text(&NavItem {
+                href: Some(href.clone()),
+                ..Default::default()
+            });
+            let item = NavItem {
+                text: Some(match &chapter.number {
+                    Some(number) => format!("{}\u{a0}\u{a0}{}", number, title),
+                    None => title,
+                }),
+                href: Some(href),
+                ..Default::default()
+            };
+
+            let Some(part_index) = chapter.part else {
+                current_part = None;
+                items.push(item);
+                continue;
+            };
+            let part = &book.parts[part_index];
+            if current_part != Some(part_index) {
+                current_part = Some(part_index);
+                items.push(NavItem {
+                    text: part.input.is_none().then(|| part.title.clone()),
+                    href: part.input.as_ref().map(|input| self.relative(input)),
+                    ..Default::default()
+                });
+            }
+            // A part page is the section's own link
+            if part.input.as_ref() != Some(&chapter.input)
+                && let Some(section) = items.last_mut()
+            {
+                section.children.push(item);
+            }
+        }
+
+        Sidebar {
+            title: book.title.clone(),
+            items,
+        }
+    }
+
+    /// Previous/next chapter links for a book page.
+    fn render_page_nav(&self, book: &Book, input: &Path) -> String {
+        let previous = book.previous(input);
+        let next = book.next(input);
+        if previous.is_none() && next.is_none() {
+            return String::new();
+        }
+
+        let link = |chapter: Option<&BookChapter>, arrow: &str, before: bool| {
+            let Some(chapter) = chapter else {
+                return String::new();
+            };
+            let item = NavItem {
+                href: Some(self.relative(&chapter.input)),
+                ..Default::default()
+            };
+            let title = self.text(&item);
+            let label = match &chapter.number {
+                Some(number) => format!("{}\u{a0}\u{a0}{}", number, title),
+                None => title,
+            };
+            let icon = format!("<i class=\"bi bi-arrow-{}-short\"></i>", arrow);
+            let text = format!(
+                "<span class=\"nav-page-text\">{}</span>",
+                html_escape(&label)
+            );
+            format!(
+                "<a href=\"{}\" class=\"pagination-link\" aria-label=\"{}\">\n{}\n</a>\n",
+                html_escape(&self.link(item.href.as_deref().unwrap_or_default())),
+                html_escape(&label),
+                if before {
+                    format!("{} {}", icon, text)
+                } else {
+                    format!("{} {}", text, icon)
+                }
+            )
+        };
+
+        format!(
+            "<nav class=\"page-navigation\">\n\
+             <div class=\"nav-page nav-page-previous\">\n{}</div>\n\
+             <div class=\"nav-page nav-page-next\">\n{}</div>\n\
+             </nav>\n",
+            link(previous, "left", true),
+            link(next, "right", false)
+        )
+    }
+
+    fn render_breadcrumbs(&self, trail: &[&NavItem]) -> String {
+        let mut html = String::from(
+            "<nav class=\"quarto-page-breadcrumbs\" aria-label=\"breadcrumb\">\n<ol class=\"breadcrumb\">\n",
+        );
+        for (i, item) in trail.iter().enumerate() {
+            let text = html_escape(&self.text(item));
+            let current = i + 1 == trail.len();
+            let class = if current {
+                "breadcrumb-item active\" aria-current=\"page"
+            } else {
+                "breadcrumb-item"
+            };
+            match &item.href {
+                Some(href) => html.push_str(&format!(
+                    "<li class=\"{}\"><a href=\"{}\">{}</a></li>\n",
+                    class,
+                    html_escape(&self.link(href)),
+                    text
+                )),
+                None => html.push_str(&format!("<li class=\"{}\">{}</li>\n", class, text)),
+            }
+        }
+        html.push_str("</ol>\n</nav>\n");
+        html
+    }
+
+    fn render_footer(&self, footer: &Value) -> String {
+        let parts: Vec<(&str, Option<&Value>)> = match footer {
+            Value::String(_) => vec![("center", Some(footer))],
+            Value::Object(_) => ["left", "center", "right"]
+                .into_iter()
+                .map(|side| (side, footer.get(side)))
+                .collect(),
+            _ => return String::new(),
+        };
+
+        let mut html = String::from("<footer class=\"footer\">\n<div class=\"nav-footer\">\n");
+        for (side, content) in parts {
+            let inner = match content {
+                Some(Value::String(text)) => html_escape(text),
+                Some(items) => {
+                    let items = parse_items(Some(items));
+                    let mut list = String::from("<ul class=\"footer-items list-unstyled\">\n");
+                    for item in &items {
+                        list.push_str(&format!(
+                            "<li class=\"nav-item\">\n{}</li>\n",
+                            self.render_link(item, "nav-link", "menu-text")
+                        ));
+                    }
+                    list.push_str("</ul>\n");
+                    list
+                }
+                None => String::new(),
+            };
+            html.push_str(&format!(
+                "<div class=\"nav-footer-{}\">\n{}\n</div>\n",
+                side, inner
+            ));
+        }
+        html.push_str("</div>\n</footer>\n");
+        html
+    }
+}
+
+/// Parse a list of navigation items (or a single item).
+fn parse_items(value: Option<&Value>) -> Vec<NavItem> {
+    match value {
+        Some(Value::Array(items)) => items.iter().filter_map(parse_item).collect(),
+        Some(item) => parse_item(item).into_iter().collect(),
+        None => Vec::new(),
+    }
+}
+
+fn parse_item(value: &Value) -> Option<NavItem> {
+    match value {
+        Value::String(href) if is_separator(href) => Some(NavItem {
+            text: Some(href.clone()),
+            ..Default::default()
+        }),
+        Value::String(href) => Some(NavItem {
+            href: Some(href.clone()),
+            ..Default::default()
+        }),
+        Value::Object(map) => {
+            let string = |key: &str| map.get(key).and_then(Value::as_str).map(String::from);
+            let children = map.get("menu").or_else(|| map.get("contents"));
+            Some(NavItem {
+                text: string("text").or_else(|| string("section")),
+                href: string("href").or_else(|| {
+                    // `section: page.qmd` names the section's own page
+                    string("section").filter(|s| s.ends_with(".qmd") || s.ends_with(".md"))
+                }),
+                icon: string("icon"),
+                children: parse_items(children),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn is_separator(text: &str) -> bool {
+    text.starts_with("---")
+}
+
+fn icon_html(item: &NavItem) -> String {
+    match &item.icon {
+        Some(icon) => format!(
+            "<i class=\"bi bi-{}\" role=\"img\"></i>\n",
+            html_escape(icon)
+        ),
+        None => String::new(),
+    }
+}
+
+/// Escape HTML special characters.
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Format;
+    use crate::project::{DocumentInfo, ProjectConfig, ProjectContext, ProjectType};
+    use crate::render::BinaryDependencies;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    fn make_project(website: Value) -> ProjectContext {
+        let page = |path: &str, title: &str| DocumentInfo {
+            title: Some(title.to_string()),
+            ..DocumentInfo::from_path(path)
+        };
+        ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: Some(ProjectConfig {
+                project_type: ProjectType::Website,
+                raw: json!({ "website": website }),
+                ..Default::default()
+            }),
+            is_single_file: false,
+            files: vec![
+                page("/project/index.qmd", "Home"),
+                page("/project/about.qmd", "About Us"),
+                page("/project/guide/install.qmd", "Installing"),
+                page("/project/guide/usage.qmd", "Usage"),
+            ],
+            output_dir: PathBuf::from("/project/_site"),
+        }
+    }
+
+    fn render(website: Value, page: &str) -> Pandoc {
+        render_project(&make_project(website), page)
+    }
+
+    fn render_project(project: &ProjectContext, page: &str) -> Pandoc {
+        let doc = DocumentInfo::from_path(format!("/project/{}", page));
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(project, &doc, &format, &binaries);
+
+        let mut ast = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![],
+        };
+        WebsiteNavigationTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+        ast
+    }
+
+    fn rendered(ast: &Pandoc, slot: &str) -> Option<String> {
+        ast.meta
+            .get_path(&["rendered", "navigation", slot])
+            .and_then(|v| v.as_str())
+            .map(String::from)
+    }
+
+    #[test]
+    fn test_transform_name() {
+        assert_eq!(
+            WebsiteNavigationTransform::new().name(),
+            "website-navigation"
+        );
+    }
+
+    #[test]
+    fn test_skips_without_website_config() {
+        let project = ProjectContext {
+            dir: PathBuf::from("/project"),
+            config: None,
+            is_single_file: true,
+            files: vec![DocumentInfo::from_path("/project/doc.qmd")],
+            output_dir: PathBuf::from("/project"),
+        };
+        let doc = DocumentInfo::from_path("/project/doc.qmd");
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+        let mut ast = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![],
+        };
+
+        WebsiteNavigationTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+        assert!(!ast.meta.contains_path(&["rendered", "navigation"]));
+    }
+
+    #[test]
+    fn test_navbar_scaffold_config() {
+        // The navbar emitted by `quarto create project website`
+        let website = json!({
+            "title": "My Site",
+            "navbar": { "left": [{ "href": "index.qmd", "text": "Home" }, "about.qmd"] }
+        });
+
+        let ast = render(website.clone(), "index.qmd");
+        let navbar = rendered(&ast, "navbar").unwrap();
+        assert!(navbar.contains("<span class=\"navbar-title\">My Site</span>"));
+        assert!(
+            navbar.contains(
+                "<a class=\"nav-link active\" aria-current=\"page\" href=\"./index.html\">"
+            )
+        );
+        assert!(navbar.contains("<a class=\"nav-link\" href=\"./about.html\">"));
+        // Items without text use the page title
+        assert!(navbar.contains("<span class=\"menu-text\">About Us</span>"));
+
+        // From a nested page, links climb to the site root
+        let ast = render(website, "guide/usage.qmd");
+        let navbar = rendered(&ast, "navbar").unwrap();
+        assert!(navbar.contains("<a class=\"nav-link\" href=\"../index.html\">"));
+        assert!(navbar.contains("href=\"../about.html\""));
+    }
+
+    #[test]
+    fn test_navbar_logo() {
+        // An explicit navbar logo is relative to the project
+        let website = json!({
+            "navbar": { "logo": "images/logo.png", "logo-alt": "Acme" }
+        });
+        let navbar = rendered(&render(website, "guide/usage.qmd"), "navbar").unwrap();
+        assert!(
+            navbar.contains("<img src=\"../images/logo.png\" alt=\"Acme\" class=\"navbar-logo\">")
+        );
+        assert!(!navbar.contains("navbar-title"));
+
+        // Otherwise the brand logo recorded in metadata is used
+        let project = make_project(json!({ "title": "Site", "navbar": { "left": [] } }));
+        let doc = DocumentInfo::from_path("/project/about.qmd");
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+        let mut ast = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![],
+        };
+        ast.meta.insert_path(
+            &["brand", "logo", "medium", "path"],
+            ConfigValue::new_path("logo.svg".to_string(), SourceInfo::default()),
+        );
+        WebsiteNavigationTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+        let navbar = rendered(&ast, "navbar").unwrap();
+        assert!(navbar.contains(
+            "<img src=\"logo.svg\" alt=\"\" class=\"navbar-logo\">\n<span class=\"navbar-title\">Site</span>"
+        ));
+    }
+
+    #[test]
+    fn test_navbar_menu() {
+        let website = json!({
+            "navbar": { "right": [{
+                "text": "Guide",
+                "menu": ["guide/install.qmd", "---", { "text": "GitHub", "href": "https://github.com" }]
+            }] }
+        });
+
+        let navbar = rendered(&render(website, "about.qmd"), "navbar").unwrap();
+        assert!(navbar.contains("<ul class=\"navbar-nav navbar-nav-scroll ms-auto\">"));
+        assert!(navbar.contains("id=\"nav-menu-right-1\""));
+        assert!(navbar.contains(
+            "<a class=\"dropdown-item\" href=\"./guide/install.html\">\n<span class=\"dropdown-text\">Installing</span>"
+        ));
+        assert!(navbar.contains("<hr class=\"dropdown-divider\">"));
+        assert!(navbar.contains("href=\"https://github.com\""));
+    }
+
+    #[test]
+    fn test_sidebar_and_breadcrumbs() {
+        let website = json!({
+            "sidebar": {
+                "title": "Docs",
+                "contents": [
+                    "index.qmd",
+                    { "section": "Guide", "contents": ["guide/install.qmd", "guide/usage.qmd"] }
+                ]
+            }
+        });
+
+        let ast = render(website.clone(), "guide/usage.qmd");
+        let sidebar = rendered(&ast, "sidebar").unwrap();
+        assert!(sidebar.contains("<nav id=\"quarto-sidebar\""));
+        assert!(sidebar.contains(
+            "<ul id=\"quarto-sidebar-section-1\" class=\"collapse list-unstyled sidebar-section depth1 show\">"
+        ));
+        assert!(sidebar.contains(
+            "<a class=\"sidebar-item-text sidebar-link active\" aria-current=\"page\" href=\"../guide/usage.html\">"
+        ));
+
+        let breadcrumbs = rendered(&ast, "breadcrumbs").unwrap();
+        assert!(breadcrumbs.contains("<li class=\"breadcrumb-item\">Guide</li>"));
+        assert!(breadcrumbs.contains(
+            "<li class=\"breadcrumb-item active\" aria-current=\"page\"><a href=\"../guide/usage.html\">Usage</a></li>"
+        ));
+
+        // Top-level pages have no breadcrumbs and a collapsed section
+        let ast = render(website, "index.qmd");
+        assert!(rendered(&ast, "breadcrumbs").is_none());
+        assert!(rendered(&ast, "sidebar").unwrap().contains("depth1\">"));
+    }
+
+    #[test]
+    fn test_sidebar_auto_and_selection() {
+        let website = json!({
+            "sidebar": [
+                { "title": "Main", "contents": ["index.qmd"] },
+                { "title": "All", "contents": "auto" }
+            ]
+        });
+
+        let sidebar = rendered(&render(website, "about.qmd"), "sidebar").unwrap();
+        assert!(sidebar.contains(">All</a>"));
+        assert!(sidebar.contains("<span class=\"menu-text\">Installing</span>"));
+    }
+
+    #[test]
+    fn test_footer() {
+        let website = json!({
+            "page-footer": { "left": "Copyright <2025>", "right": ["about.qmd"] }
+        });
+
+        let footer = rendered(&render(website, "index.qmd"), "footer").unwrap();
+        assert!(footer.contains("<div class=\"nav-footer-left\">\nCopyright &lt;2025&gt;\n</div>"));
+        assert!(footer.contains("<a class=\"nav-link\" href=\"./about.html\">"));
+
+        let footer = rendered(
+            &render(json!({ "page-footer": "Hi" }), "index.qmd"),
+            "footer",
+        );
+        assert!(
+            footer
+                .unwrap()
+                .contains("<div class=\"nav-footer-center\">\nHi\n</div>")
+        );
+    }
+
+    #[test]
+    fn test_keeps_user_provided_navigation() {
+        let project = make_project(json!({ "navbar": { "left": ["index.qmd"] } }));
+        let doc = DocumentInfo::from_path("/project/index.qmd");
+        let format = Format::html();
+        let binaries = BinaryDependencies::new();
+        let mut ctx = RenderContext::new(&project, &doc, &format, &binaries);
+        let mut ast = Pandoc {
+            meta: ConfigValue::default(),
+            blocks: vec![],
+        };
+        ast.meta.insert_path(
+            &["rendered", "navigation", "navbar"],
+            ConfigValue::new_string("<nav>custom</nav>", SourceInfo::default()),
+        );
+
+        WebsiteNavigationTransform::new()
+            .transform(&mut ast, &mut ctx)
+            .unwrap();
+        assert_eq!(
+            rendered(&ast, "navbar").as_deref(),
+            Some("<nav>custom</nav>")
+        );
+    }
+
+    #[test]
+    fn test_book_sidebar_and_page_navigation() {
+        let mut project = make_project(json!({}));
+        project.config = Some(ProjectConfig {
+            project_type: ProjectType::Book,
+            raw: json!({
+                "book": {
+                    "title": "The Book",
+                    "chapters": [
+                        "index.qmd",
+                        "about.qmd",
+                        { "part": "Guide", "chapters": ["guide/install.qmd", "guide/usage.qmd"] }
+                    ]
+                }
+            }),
+            ..Default::default()
+        });
+
+        let ast = render_project(&project, "guide/install.qmd");
+
+        let sidebar = rendered(&ast, "sidebar").unwrap();
+        assert!(sidebar.contains(">The Book</a>"));
+        assert!(sidebar.contains("<span class=\"menu-text\">1\u{a0}\u{a0}About Us</span>"));
+        assert!(sidebar.contains("<span class=\"menu-text\">Guide</span>"));
+        assert!(sidebar.contains(
+            "aria-current=\"page\" href=\"../guide/install.html\">\n<span class=\"menu-text\">2\u{a0}\u{a0}Installing</span>"
+        ));
+
+        let breadcrumbs = rendered(&ast, "breadcrumbs").unwrap();
+        assert!(breadcrumbs.contains("<li class=\"breadcrumb-item\">Guide</li>"));
+
+        let page_nav = rendered(&ast, "page-nav").unwrap();
+        assert!(page_nav.contains(
+            "<div class=\"nav-page nav-page-previous\">\n<a href=\"../about.html\" class=\"pagination-link\" aria-label=\"1\u{a0}\u{a0}About Us\">"
+        ));
+        assert!(page_nav.contains("<a href=\"../guide/usage.html\""));
+        assert!(page_nav.contains(
+            "<span class=\"nav-page-text\">3\u{a0}\u{a0}Usage</span> <i class=\"bi bi-arrow-right-short\"></i>"
+        ));
+    }
+}
@@ -0,0 +1,228 @@
+/*
+ * typst.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Typst compilation for `format: typst`.
+ */
+
+//! Typst compilation for `format: typst`.
+//!
+//! The document is written as a `.typ` file next to the output and compiled
+//! with the Typst binary (`QUARTO_TYPST` or `typst` on the PATH). Typst
+//! needs no reruns and leaves no auxiliary files behind.
+//!
+//! Typst reports errors against lines of the `.typ` file, which the user
+//! never wrote. Errors are mapped back to the `.qmd` block that produced the
+//! offending line with the writer's [`LineMap`], so they are shown against
+//! the document source.
+
+use std::path::Path;
+
+use pampa::writers::typst::LineMap;
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_system_runtime::SystemRuntime;
+
+/// A diagnostic from `typst compile --diagnostic-format short`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypstError {
+    /// File the error was reported in, as given to Typst
+    pub file: String,
+    /// 1-based line
+    pub line: usize,
+    /// 1-based column
+    pub column: usize,
+    /// The error message
+    pub message: String,
+    /// Hints Typst gave for this error
+    pub hints: Vec<String>,
+}
+
+/// The diagnostic for a missing Typst binary.
+pub fn typst_not_found() -> DiagnosticMessage {
+    DiagnosticMessageBuilder::error("Typst not found")
+        .problem("`format: typst` needs the Typst binary, which was not found")
+        .add_hint("Install Typst and make sure it is on your PATH")
+        .add_hint("Or point QUARTO_TYPST at the binary")
+        .build()
+}
+
+/// Compile `typ_path` to `pdf_path` with the Typst binary.
+///
+/// On failure, returns diagnostics for the Typst errors, located in the
+/// source document through `line_map` where possible.
+pub fn compile(
+    runtime: &dyn SystemRuntime,
+    binary: &Path,
+    typ_path: &Path,
+    pdf_path: &Path,
+    line_map: Option<&LineMap>,
+) -> Result<(), Vec<DiagnosticMessage>> {
+    let command = binary.to_string_lossy();
+    let typ = typ_path.to_string_lossy();
+    let pdf = pdf_path.to_string_lossy();
+    let args = ["compile", "--diagnostic-format", "short", &*typ, &*pdf];
+
+    let output = runtime.exec_command(&command, &args, None).map_err(|e| {
+        vec![
+            DiagnosticMessageBuilder::error("Failed to run Typst")
+                .problem(format!("Could not run `{}`: {}", command, e))
+                .build(),
+        ]
+    })?;
+    if output.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(compilation_diagnostics(&stderr, typ_path, line_map))
+}
+
+/// Parse the errors (with their hints) from Typst's short diagnostic
+/// format, `file:line:col: error: message`.
+pub fn parse_errors(stderr: &str) -> Vec<TypstError> {
+    let mut errors: Vec<TypstError> = Vec::new();
+    for line in stderr.lines() {
+        if let Some((location, message)) = line.split_once(": error: ") {
+            // Paths may contain `:`, so split the location from the right
+            let mut parts = location.rsplitn(3, ':');
+            let (Some(column), Some(line_number), Some(file)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let (Ok(line_number), Ok(column)) = (line_number.parse(), column.parse()) else {
+                continue;
+            };
+            errors.push(TypstError {
+                file: file.to_string(),
+                line: line_number,
+                column,
+                message: message.to_string(),
+                hints: Vec::new(),
+            });
+        } else if let Some((_, hint)) = line.split_once(": hint: ")
+            && let Some(error) = errors.last_mut()
+        {
+            error.hints.push(hint.to_string());
+        }
+    }
+    errors
+}
+
+/// Diagnostics for a failed Typst run.
+fn compilation_diagnostics(
+    stderr: &str,
+    typ_path: &Path,
+    line_map: Option<&LineMap>,
+) -> Vec<DiagnosticMessage> {
+    let errors = parse_errors(stderr);
+    if errors.is_empty() {
+        let problem = stderr.trim();
+        return vec![
+            DiagnosticMessageBuilder::error("Typst compilation failed")
+                .problem(if problem.is_empty() {
+                    "`typst` exited with an error"
+                } else {
+                    problem
+                })
+                .build(),
+        ];
+    }
+
+    let typ_name = typ_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    errors
+        .into_iter()
+        .map(|error| {
+            let mut builder =
+                DiagnosticMessageBuilder::error("Typst compilation failed").problem(format!(
+                    "{} (at {}:{}:{})",
+                    error.message, typ_name, error.line, error.column
+                ));
+            // Errors in the generated file map back to the source block;
+            // errors in imported files have no source location
+            let in_document = Path::new(&error.file).file_name() == typ_path.file_name();
+            if in_document
+                && let Some(source_info) = line_map.and_then(|map| map.lookup(error.line))
+            {
+                builder = builder.with_location(source_info.clone());
+            }
+            for hint in error.hints {
+                builder = builder.add_hint(hint);
+            }
+            builder
+                .add_hint("Render with `--debug` to keep the .typ file for inspection")
+                .build()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        let stderr = concat!(
+            "C:\\docs\\doc.typ:12:5: error: unknown variable: foo\n",
+            "C:\\docs\\doc.typ:12:5: hint: if you meant to display literal text, escape it\n",
+            "doc.typ:3:1: warning: unused import\n",
+            "doc.typ:20:2: error: expected expression\n",
+        );
+        let errors = parse_errors(stderr);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].file, "C:\\docs\\doc.typ");
+        assert_eq!((errors[0].line, errors[0].column), (12, 5));
+        assert_eq!(errors[0].message, "unknown variable: foo");
+        assert_eq!(errors[0].hints.len(), 1);
+        assert_eq!(errors[1].message, "expected expression");
+    }
+
+    #[test]
+    fn test_unparseable_output() {
+        let diagnostics =
+            compilation_diagnostics("panicked somewhere\n", Path::new("/tmp/doc.typ"), None);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].problem.as_ref().unwrap().as_str(),
+            "panicked somewhere"
+        );
+    }
+
+    #[test]
+    fn test_errors_are_mapped_to_source() {
+        let (pandoc, _context, _warnings) = pampa::readers::qmd::read(
+            b"# Title\n\nA #bad paragraph\n",
+            false,
+            "doc.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .unwrap();
+        let mut buf = Vec::new();
+        let (_, line_map) = pampa::writers::typst::write_with_line_map(&pandoc, &mut buf).unwrap();
+        // The body follows a 10-line preamble
+        let line_map = line_map.shifted(10);
+
+        let diagnostics = compilation_diagnostics(
+            "/tmp/doc.typ:13:3: error: unknown variable: bad\n/tmp/other.typ:1:1: error: boom\n",
+            Path::new("/tmp/doc.typ"),
+            Some(&line_map),
+        );
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].location.as_ref(), line_map.lookup(13));
+        assert!(diagnostics[0].location.is_some());
+        assert!(
+            diagnostics[0]
+                .problem
+                .as_ref()
+                .unwrap()
+                .as_str()
+                .contains("at doc.typ:13:3")
+        );
+        assert!(diagnostics[1].location.is_none());
+    }
+}
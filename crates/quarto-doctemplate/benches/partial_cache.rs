@@ -0,0 +1,126 @@
+//! Compile-time benchmark for project renders
+//!
+//! A project render compiles the format template, with all its partials, once
+//! per page. This benchmark compiles a template with several partials for a
+//! batch of pages three ways: parsing everything each time, with a
+//! `CachingResolver`, and by deserializing a compiled template.
+//!
+//! Run with: cargo bench --bench partial_cache
+
+use quarto_doctemplate::{
+    CachingResolver, MemoryResolver, PartialResolver, Template, TemplateContext, TemplateValue,
+};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const PAGES: usize = 500;
+
+const MAIN: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+$metadata()$
+$styles()$
+</head>
+<body>
+$header()$
+$for(include-before)$
+$include-before$
+$endfor$
+$body$
+$footer()$
+</body>
+</html>"#;
+
+const PARTIALS: &[(&str, &str)] = &[
+    (
+        "metadata",
+        r#"<meta charset="utf-8">
+$if(title)$<title>$title$</title>$endif$
+$for(author)$<meta name="author" content="$author$">
+$endfor$
+$if(description)$<meta name="description" content="$description/chomp$">$endif$"#,
+    ),
+    (
+        "styles",
+        r#"<style>
+$if(mainfont)$body { font-family: $mainfont$; }$endif$
+$if(fontsize)$body { font-size: $fontsize$; }$endif$
+$if(linestretch)$body { line-height: $linestretch$; }$endif$
+</style>"#,
+    ),
+    (
+        "header",
+        r#"<header>
+$if(title)$<h1 class="title">$title$</h1>$endif$
+$if(subtitle)$<p class="subtitle">$subtitle$</p>$endif$
+$for(author)$<p class="author">$author/uppercase$</p>
+$endfor$
+$if(date)$<p class="date">$date$</p>$endif$
+</header>"#,
+    ),
+    (
+        "footer",
+        r#"<footer>
+$if(copyright)$<p>$copyright$</p>$endif$
+$for(include-after)$$include-after$
+$endfor$
+</footer>"#,
+    ),
+];
+
+fn context(page: usize) -> TemplateContext {
+    let mut ctx = TemplateContext::new();
+    ctx.insert("title", TemplateValue::String(format!("Page {page}")));
+    ctx.insert(
+        "author",
+        TemplateValue::List(vec![TemplateValue::String("Ann".to_string())]),
+    );
+    ctx.insert("body", TemplateValue::String("<p>Body</p>".to_string()));
+    ctx
+}
+
+fn render_batch(compile: impl Fn() -> Template) -> Duration {
+    let start = Instant::now();
+    for page in 0..PAGES {
+        let template = compile();
+        template.render(&context(page)).unwrap();
+    }
+    start.elapsed()
+}
+
+fn compile(resolver: &impl PartialResolver) -> Template {
+    Template::compile_with_resolver(MAIN, Path::new("template.html"), resolver, 0).unwrap()
+}
+
+fn main() {
+    println!("Compiling and rendering {PAGES} pages\n");
+
+    let resolver = MemoryResolver::with_partials(PARTIALS.iter().copied());
+    let uncached = render_batch(|| compile(&resolver));
+
+    let caching = CachingResolver::new(resolver.clone());
+    let cached = render_batch(|| compile(&caching));
+
+    let json = serde_json::to_string(&compile(&resolver)).unwrap();
+    let deserialized = render_batch(|| serde_json::from_str(&json).unwrap());
+
+    let per_page = |d: Duration| d / PAGES as u32;
+    println!("{:<28} {:>12} {:>12}", "Strategy", "Total", "Per page");
+    println!("{}", "-".repeat(54));
+    for (name, duration) in [
+        ("Parse every partial", uncached),
+        ("CachingResolver", cached),
+        ("Deserialize compiled (JSON)", deserialized),
+    ] {
+        println!(
+            "{:<28} {:>12.2?} {:>12.2?}",
+            name,
+            duration,
+            per_page(duration)
+        );
+    }
+    println!(
+        "\nCachingResolver speedup: {:.1}x",
+        uncached.as_secs_f64() / cached.as_secs_f64()
+    );
+}
@@ -0,0 +1,597 @@
+/*
+ * analyze.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Static analysis of templates.
+//!
+//! [`Template::analyze`] checks a template against a [`TemplateSchema`], a
+//! description of the variables its context will provide, without rendering
+//! it. It reports:
+//!
+//! - variables that are never defined (`Q-10-2`)
+//! - unknown pipes and invalid pipe arguments (`Q-10-6`, `Q-10-7`)
+//! - conditionals and loops on variables that are never defined, which are
+//!   always false (`Q-10-8`)
+//! - branches that can never be taken, because an earlier condition is always
+//!   true or tests the same variable (`Q-10-9`)
+//!
+//! The checks that don't depend on the context are also reported as warnings
+//! by [`Template::render_with_diagnostics`] and friends.
+
+use crate::ast::{
+    BreakableSpace, Conditional, ForLoop, Nesting, Partial, Pipe, PipeArg, TemplateNode,
+    VariableRef,
+};
+use crate::context::{TemplateContext, TemplateValue};
+use crate::eval_context::DiagnosticCollector;
+use crate::parser::Template;
+use crate::pipes::PIPE_NAMES;
+use quarto_error_reporting::DiagnosticMessage;
+use quarto_source_map::SourceInfo;
+use std::collections::HashMap;
+
+/// The variables a template's context provides, for [`Template::analyze`].
+#[derive(Debug, Clone, Default)]
+pub struct TemplateSchema {
+    /// Known variables.
+    variables: HashMap<String, VariableSchema>,
+    /// Whether variables that aren't listed may also be defined.
+    open: bool,
+}
+
+/// What is known about one variable in a [`TemplateSchema`].
+#[derive(Debug, Clone, Default)]
+pub struct VariableSchema {
+    /// The variable is always defined, with a truthy value.
+    required: bool,
+    /// The fields of the value (or of each element, for an array); `None`
+    /// when they are unknown.
+    fields: Option<TemplateSchema>,
+}
+
+impl TemplateSchema {
+    /// A schema with no variables. Add them with [`Self::with_variable`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A schema that allows any variable, so that only the checks that don't
+    /// depend on the context apply.
+    pub fn open() -> Self {
+        Self {
+            variables: HashMap::new(),
+            open: true,
+        }
+    }
+
+    /// Add a variable to the schema.
+    pub fn with_variable(mut self, name: impl Into<String>, variable: VariableSchema) -> Self {
+        self.variables.insert(name.into(), variable);
+        self
+    }
+
+    /// The schema of a sample context: its variables, with those that have
+    /// truthy values required.
+    pub fn from_context(context: &TemplateContext) -> Self {
+        Self {
+            variables: context
+                .bindings()
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), VariableSchema::from_value(value)))
+                .collect(),
+            open: false,
+        }
+    }
+
+    fn from_map(map: &HashMap<String, TemplateValue>) -> Self {
+        Self {
+            variables: map
+                .iter()
+                .map(|(name, value)| (name.clone(), VariableSchema::from_value(value)))
+                .collect(),
+            open: false,
+        }
+    }
+}
+
+impl VariableSchema {
+    /// A variable that may be undefined or falsy.
+    pub fn optional() -> Self {
+        Self::default()
+    }
+
+    /// A variable that is always defined with a truthy value.
+    pub fn required() -> Self {
+        Self {
+            required: true,
+            fields: None,
+        }
+    }
+
+    /// Declare the variable's fields (or those of its elements, for an
+    /// array). Fields not in `fields` are then undefined.
+    pub fn with_fields(mut self, fields: TemplateSchema) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+
+    fn from_value(value: &TemplateValue) -> Self {
+        let fields = match value {
+            TemplateValue::Map(map) => TemplateSchema::from_map(map),
+            // Elements may differ, so their fields are all optional
+            TemplateValue::List(items) => {
+                let mut fields = TemplateSchema::new();
+                for item in items {
+                    if let TemplateValue::Map(map) = item {
+                        for (name, value) in map {
+                            fields.variables.entry(name.clone()).or_insert_with(|| {
+                                let mut field = VariableSchema::from_value(value);
+                                field.required = false;
+                                field
+                            });
+                        }
+                    }
+                }
+                fields
+            }
+            _ => TemplateSchema::new(),
+        };
+        Self {
+            required: value.is_truthy(),
+            fields: Some(fields),
+        }
+    }
+
+    /// Look up a field path below this variable; `None` if it's undefined.
+    fn field(&self, path: &[&str]) -> Option<VariableSchema> {
+        let Some((first, rest)) = path.split_first() else {
+            return Some(self.clone());
+        };
+        let Some(fields) = &self.fields else {
+            return Some(VariableSchema::optional());
+        };
+        match fields.variables.get(*first) {
+            Some(field) => field.field(rest).map(|mut field| {
+                field.required &= self.required;
+                field
+            }),
+            None if fields.open => Some(VariableSchema::optional()),
+            None => None,
+        }
+    }
+}
+
+impl Template {
+    /// Check this template against the variables its context will provide.
+    ///
+    /// Returns warnings, sorted by source location, for undefined variables,
+    /// unknown pipes, always-false conditionals and unreachable branches.
+    /// Pass [`TemplateSchema::open`] to only run the checks that don't depend
+    /// on the context.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let template = Template::compile("$if(draft)$DRAFT$endif$ $title$")?;
+    /// let schema = TemplateSchema::new().with_variable("title", VariableSchema::required());
+    ///
+    /// // `draft` is never defined, so the conditional is always false
+    /// let diagnostics = template.analyze(&schema);
+    /// assert_eq!(diagnostics[0].code.as_deref(), Some("Q-10-8"));
+    /// ```
+    pub fn analyze(&self, schema: &TemplateSchema) -> Vec<DiagnosticMessage> {
+        let mut analyzer = Analyzer {
+            scopes: vec![schema.clone()],
+            diagnostics: DiagnosticCollector::new(),
+            partials: Vec::new(),
+        };
+        analyzer.nodes(&self.nodes);
+        analyzer.diagnostics.into_diagnostics()
+    }
+}
+
+struct Analyzer {
+    /// Variables in scope, innermost last.
+    scopes: Vec<TemplateSchema>,
+    diagnostics: DiagnosticCollector,
+    /// Partials being analyzed, to stop at recursive partials.
+    partials: Vec<String>,
+}
+
+impl Analyzer {
+    fn nodes(&mut self, nodes: &[TemplateNode]) {
+        for node in nodes {
+            self.node(node);
+        }
+    }
+
+    fn node(&mut self, node: &TemplateNode) {
+        match node {
+            TemplateNode::Literal(_) | TemplateNode::Comment(_) => {}
+            TemplateNode::Variable(var) => {
+                self.check_pipes(&var.pipes);
+                if self.lookup(var).is_none() {
+                    self.diagnostics.warn_with_code(
+                        "Q-10-2",
+                        format!("Undefined variable: {}", var.path.join(".")),
+                        var.source_info.clone(),
+                    );
+                }
+            }
+            TemplateNode::Conditional(conditional) => self.conditional(conditional),
+            TemplateNode::ForLoop(for_loop) => self.for_loop(for_loop),
+            TemplateNode::Partial(partial) => self.partial(partial),
+            TemplateNode::Nesting(Nesting { children, .. })
+            | TemplateNode::BreakableSpace(BreakableSpace { children, .. }) => self.nodes(children),
+        }
+    }
+
+    fn conditional(&mut self, conditional: &Conditional) {
+        let Conditional {
+            branches,
+            else_branch,
+            source_info,
+        } = conditional;
+
+        let mut always_taken = false;
+        let mut tested: Vec<&VariableRef> = Vec::new();
+        for (condition, body) in branches {
+            self.check_pipes(&condition.pipes);
+            let path = condition.path.join(".");
+            if always_taken {
+                self.diagnostics.warn_with_code(
+                    "Q-10-9",
+                    format!("Unreachable branch: an earlier condition is always true ({path})"),
+                    condition.source_info.clone(),
+                );
+                continue;
+            }
+            if tested
+                .iter()
+                .any(|earlier| same_condition(earlier, condition))
+            {
+                self.diagnostics.warn_with_code(
+                    "Q-10-9",
+                    format!("Unreachable branch: {path} was already tested"),
+                    condition.source_info.clone(),
+                );
+                continue;
+            }
+            tested.push(condition);
+
+            match self.lookup(condition) {
+                None => self.diagnostics.warn_with_code(
+                    "Q-10-8",
+                    format!("Condition is always false: {path} is never defined"),
+                    condition.source_info.clone(),
+                ),
+                Some(variable) => {
+                    always_taken = variable.required;
+                    self.nodes(body);
+                }
+            }
+        }
+
+        if let Some(else_body) = else_branch {
+            if always_taken {
+                let location = else_body.first().map_or(source_info, node_source_info);
+                self.diagnostics.warn_with_code(
+                    "Q-10-9",
+                    "Unreachable else branch: an earlier condition is always true",
+                    location.clone(),
+                );
+            } else {
+                self.nodes(else_body);
+            }
+        }
+    }
+
+    fn for_loop(&mut self, for_loop: &ForLoop) {
+        let ForLoop {
+            var,
+            body,
+            separator,
+            ..
+        } = for_loop;
+
+        self.check_pipes(&var.pipes);
+        let Some(variable) = self.lookup(var) else {
+            self.diagnostics.warn_with_code(
+                "Q-10-8",
+                format!("Loop never runs: {} is never defined", var.path.join(".")),
+                var.source_info.clone(),
+            );
+            return;
+        };
+
+        // Each iteration binds the item to the variable name and `it`
+        let item = VariableSchema {
+            required: false,
+            fields: variable.fields,
+        };
+        let var_name = var.path.last().map_or("", |s| s.as_str());
+        self.scopes.push(
+            TemplateSchema::new()
+                .with_variable(var_name, item.clone())
+                .with_variable("it", item),
+        );
+        self.nodes(body);
+        self.scopes.pop();
+
+        // The separator is evaluated outside the loop's scope
+        if let Some(separator) = separator {
+            self.nodes(separator);
+        }
+    }
+
+    fn partial(&mut self, partial: &Partial) {
+        let Partial {
+            name,
+            var,
+            pipes,
+            resolved,
+            ..
+        } = partial;
+
+        self.check_pipes(pipes);
+        // Unresolved partials are reported when rendering
+        let Some(nodes) = resolved else {
+            return;
+        };
+        if self.partials.contains(name) {
+            return;
+        }
+
+        let scope = match var {
+            None => None,
+            Some(var) => {
+                let Some(variable) = self.lookup(var) else {
+                    self.diagnostics.warn_with_code(
+                        "Q-10-2",
+                        format!("Undefined variable: {}", var.path.join(".")),
+                        var.source_info.clone(),
+                    );
+                    return;
+                };
+                // The partial sees the item's fields, its name and `it`
+                let item = VariableSchema {
+                    required: false,
+                    fields: variable.fields.clone(),
+                };
+                let var_name = var.path.last().map_or("", |s| s.as_str());
+                let fields = variable.fields.unwrap_or_else(TemplateSchema::open);
+                Some(
+                    fields
+                        .with_variable(var_name, item.clone())
+                        .with_variable("it", item),
+                )
+            }
+        };
+
+        let has_scope = scope.is_some();
+        self.scopes.extend(scope);
+        self.partials.push(name.clone());
+        self.nodes(nodes);
+        self.partials.pop();
+        if has_scope {
+            self.scopes.pop();
+        }
+    }
+
+    /// Look up a variable in scope; `None` if it is never defined.
+    fn lookup(&self, var: &VariableRef) -> Option<VariableSchema> {
+        let path: Vec<&str> = var.path.iter().flat_map(|s| s.split('.')).collect();
+        let (first, rest) = path.split_first()?;
+
+        let mut open = false;
+        for scope in self.scopes.iter().rev() {
+            if let Some(variable) = scope.variables.get(*first) {
+                let variable = variable.field(rest)?;
+                // Pipes can change the shape of the value
+                return Some(if var.pipes.is_empty() {
+                    variable
+                } else {
+                    VariableSchema::optional()
+                });
+            }
+            open |= scope.open;
+        }
+        open.then(VariableSchema::optional)
+    }
+
+    fn check_pipes(&mut self, pipes: &[Pipe]) {
+        for pipe in pipes {
+            let name = pipe.name.as_str();
+            if !PIPE_NAMES.contains(&name) {
+                self.diagnostics.warn_with_code(
+                    "Q-10-6",
+                    format!("Unknown pipe: {name}"),
+                    pipe.source_info.clone(),
+                );
+                continue;
+            }
+            let valid = match name {
+                "left" | "right" | "center" => {
+                    matches!(pipe.args.first(), Some(PipeArg::Integer(n)) if *n > 0)
+                        && pipe.args.len() <= 3
+                        && pipe.args[1..]
+                            .iter()
+                            .all(|arg| matches!(arg, PipeArg::String(_)))
+                }
+                _ => pipe.args.is_empty(),
+            };
+            if !valid {
+                let expected = match name {
+                    "left" | "right" | "center" => "a width and up to two quoted borders",
+                    _ => "no arguments",
+                };
+                self.diagnostics.warn_with_code(
+                    "Q-10-7",
+                    format!("Invalid arguments for pipe {name}: expected {expected}"),
+                    pipe.source_info.clone(),
+                );
+            }
+        }
+    }
+}
+
+/// Whether two conditions test the same thing.
+fn same_condition(a: &VariableRef, b: &VariableRef) -> bool {
+    let path = |var: &VariableRef| var.path.join(".");
+    path(a) == path(b)
+        && a.pipes.len() == b.pipes.len()
+        && a.pipes
+            .iter()
+            .zip(&b.pipes)
+            .all(|(a, b)| a.name == b.name && a.args == b.args)
+}
+
+fn node_source_info(node: &TemplateNode) -> &SourceInfo {
+    match node {
+        TemplateNode::Literal(n) => &n.source_info,
+        TemplateNode::Variable(n) => &n.source_info,
+        TemplateNode::Conditional(n) => &n.source_info,
+        TemplateNode::ForLoop(n) => &n.source_info,
+        TemplateNode::Partial(n) => &n.source_info,
+        TemplateNode::Nesting(n) => &n.source_info,
+        TemplateNode::BreakableSpace(n) => &n.source_info,
+        TemplateNode::Comment(n) => &n.source_info,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolver::MemoryResolver;
+    use quarto_source_map::FileId;
+    use std::path::Path;
+
+    fn codes(template: &str, schema: &TemplateSchema) -> Vec<String> {
+        Template::compile(template)
+            .unwrap()
+            .analyze(schema)
+            .into_iter()
+            .filter_map(|d| d.code)
+            .collect()
+    }
+
+    fn schema() -> TemplateSchema {
+        let author = TemplateSchema::new()
+            .with_variable("name", VariableSchema::required())
+            .with_variable("email", VariableSchema::optional());
+        TemplateSchema::new()
+            .with_variable("title", VariableSchema::required())
+            .with_variable("subtitle", VariableSchema::optional())
+            .with_variable("authors", VariableSchema::optional().with_fields(author))
+    }
+
+    #[test]
+    fn test_undefined_variables() {
+        assert!(codes("$title$ $subtitle$", &schema()).is_empty());
+        assert_eq!(codes("$titel$", &schema()), vec!["Q-10-2"]);
+        // Fields are checked when known
+        assert_eq!(
+            codes("$for(authors)$$it.name$ $authors.phone$$endfor$", &schema()),
+            vec!["Q-10-2"]
+        );
+        // Loop variables go out of scope after the loop
+        assert_eq!(
+            codes("$for(authors)$$it.name$$endfor$$it$", &schema()),
+            vec!["Q-10-2"]
+        );
+        // An open schema allows anything
+        assert!(codes("$anything.at.all$", &TemplateSchema::open()).is_empty());
+    }
+
+    #[test]
+    fn test_always_false_and_unreachable() {
+        assert_eq!(
+            codes("$if(draft)$DRAFT$draft$$endif$", &schema()),
+            vec!["Q-10-8"]
+        );
+        assert_eq!(
+            codes("$for(chapters)$$chapters$$endfor$", &schema()),
+            vec!["Q-10-8"]
+        );
+        // `title` is always true, so nothing after it can run
+        assert_eq!(
+            codes("$if(title)$a$elseif(subtitle)$b$else$c$endif$", &schema()),
+            vec!["Q-10-9", "Q-10-9"]
+        );
+        // Testing the same variable twice, with any schema
+        assert_eq!(
+            codes(
+                "$if(x)$a$elseif(y)$b$elseif(x)$c$endif$",
+                &TemplateSchema::open()
+            ),
+            vec!["Q-10-9"]
+        );
+        assert!(codes("$if(subtitle)$a$else$b$endif$", &schema()).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_pipes() {
+        let location = SourceInfo::original(FileId(0), 0, 0);
+        let var = VariableRef::with_pipes(
+            vec!["title".to_string()],
+            vec![
+                Pipe::new("shout", location.clone()),
+                Pipe::new("left", location.clone()),
+                Pipe::with_args("uppercase", vec![PipeArg::Integer(1)], location.clone()),
+                Pipe::with_args("right", vec![PipeArg::Integer(8)], location.clone()),
+            ],
+            location,
+        );
+        let template = Template {
+            nodes: vec![TemplateNode::Variable(var)],
+            source: String::new(),
+        };
+        let codes: Vec<_> = template
+            .analyze(&schema())
+            .into_iter()
+            .filter_map(|d| d.code)
+            .collect();
+        assert_eq!(codes, vec!["Q-10-6", "Q-10-7", "Q-10-7"]);
+    }
+
+    #[test]
+    fn test_partials() {
+        let mut resolver = MemoryResolver::new();
+        resolver.add("author", "$name$ $email$ $phone$ $title$");
+        let template = Template::compile_with_resolver(
+            "$authors:author()$",
+            Path::new("main.html"),
+            &resolver,
+            0,
+        )
+        .unwrap();
+        let diagnostics = template.analyze(&schema());
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.title.as_str()).collect();
+        assert_eq!(messages, vec!["Undefined variable: phone"]);
+    }
+
+    #[test]
+    fn test_from_context() {
+        let mut author = HashMap::new();
+        author.insert("name".to_string(), TemplateValue::String("Ann".to_string()));
+        let mut ctx = TemplateContext::new();
+        ctx.insert("title", TemplateValue::String("T".to_string()));
+        ctx.insert("draft", TemplateValue::Bool(false));
+        ctx.insert(
+            "authors",
+            TemplateValue::List(vec![TemplateValue::Map(author)]),
+        );
+        let schema = TemplateSchema::from_context(&ctx);
+
+        assert!(codes("$if(draft)$draft$else$$title$$endif$", &schema).is_empty());
+        assert_eq!(
+            codes("$if(title)$$title$$else$none$endif$", &schema),
+            vec!["Q-10-9"]
+        );
+        assert_eq!(
+            codes("$for(authors)$$it.name$$it.age$$endfor$", &schema),
+            vec!["Q-10-2"]
+        );
+    }
+}
@@ -7,11 +7,15 @@
 //!
 //! This module defines the abstract syntax tree for parsed templates.
 //! Each node includes source location information for error reporting.
+//!
+//! All AST types implement `Serialize` and `Deserialize`, so compiled
+//! templates can be stored and loaded without re-parsing.
 
 use quarto_source_map::SourceInfo;
+use serde::{Deserialize, Serialize};
 
 /// A node in the template AST.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TemplateNode {
     /// Literal text to be output as-is.
     Literal(Literal),
@@ -39,7 +43,7 @@ pub enum TemplateNode {
 }
 
 /// Literal text node.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Literal {
     /// The literal text content.
     pub text: String,
@@ -48,7 +52,7 @@ pub struct Literal {
 }
 
 /// Conditional block: `$if(var)$...$else$...$endif$`
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Conditional {
     /// List of (condition, body) pairs for if/elseif branches.
     pub branches: Vec<(VariableRef, Vec<TemplateNode>)>,
@@ -59,7 +63,7 @@ pub struct Conditional {
 }
 
 /// For loop: `$for(var)$...$sep$...$endfor$`
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ForLoop {
     /// Variable to iterate over.
     pub var: VariableRef,
@@ -72,7 +76,7 @@ pub struct ForLoop {
 }
 
 /// Partial (sub-template): `$partial()$` or `$var:partial()$`
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Partial {
     /// Partial template name.
     pub name: String,
@@ -93,7 +97,7 @@ pub struct Partial {
 }
 
 /// Nesting directive: `$^$` marks indentation point.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Nesting {
     /// Content affected by nesting.
     pub children: Vec<TemplateNode>,
@@ -102,7 +106,7 @@ pub struct Nesting {
 }
 
 /// Breakable space block: `$~$...$~$`
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BreakableSpace {
     /// Content with breakable spaces.
     pub children: Vec<TemplateNode>,
@@ -111,7 +115,7 @@ pub struct BreakableSpace {
 }
 
 /// Comment (not rendered): `$-- comment`
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Comment {
     /// The comment text.
     pub text: String,
@@ -120,7 +124,7 @@ pub struct Comment {
 }
 
 /// A reference to a variable, possibly with pipes and separator.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VariableRef {
     /// Path components (e.g., `["employee", "salary"]` for `employee.salary`).
     pub path: Vec<String>,
@@ -171,7 +175,7 @@ impl VariableRef {
 }
 
 /// A pipe transformation applied to a value.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Pipe {
     /// Pipe name (e.g., "uppercase", "left").
     pub name: String,
@@ -202,7 +206,7 @@ impl Pipe {
 }
 
 /// An argument to a pipe.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PipeArg {
     /// Integer argument (e.g., width in `left 20`).
     Integer(i64),
@@ -0,0 +1,631 @@
+/*
+ * bundle.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Template bundle format for self-contained template distribution.
+//!
+//! A template bundle contains a main template and all its partials, along
+//! with the resources (CSS, JS) the template refers to and defaults for its
+//! metadata. It is fully self-contained and suitable for use in environments
+//! without filesystem access (e.g., WASM), which is how custom formats ship
+//! complete templates.
+//!
+//! Bundles are stored as JSON, or loaded from a directory or a zip archive
+//! with this layout:
+//!
+//! ```text
+//! template.html        main template (any extension)
+//! title-block.html     partials: other files with the main template's extension
+//! metadata.json        metadata defaults (optional)
+//! resources/           resources, keyed by their path below `resources/`
+//!   styles.css
+//! ```
+//!
+//! A zip archive may also have all of this inside a single top-level
+//! directory, as produced by zipping the directory itself.
+
+use crate::context::{TemplateContext, TemplateValue};
+use crate::error::TemplateError;
+use crate::parser::Template;
+use crate::resolver::MemoryResolver;
+use quarto_source_map::SourceContext;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+
+/// A self-contained template bundle.
+///
+/// Bundle format (JSON):
+/// ```json
+/// {
+///   "version": "1.0.0",
+///   "main": "<!DOCTYPE html><html>$body$</html>",
+///   "partials": {
+///     "header": "<header>$title$</header>",
+///     "footer": "<footer>$date$</footer>"
+///   },
+///   "resources": {
+///     "styles.css": "body { margin: 0; }"
+///   },
+///   "metadata": {
+///     "lang": "en"
+///   }
+/// }
+/// ```
+///
+/// Version semantics:
+/// - Missing `version`: Best-effort parsing, no schema guarantees
+/// - `version: "1.0.0"`: Conforms to quarto-doctemplate 1.0.0 schema
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateBundle {
+    /// Schema version (semver string). Optional for backwards compatibility.
+    #[serde(default)]
+    pub version: Option<String>,
+
+    /// The main template source.
+    pub main: String,
+
+    /// Partial templates, keyed by name.
+    #[serde(default)]
+    pub partials: HashMap<String, String>,
+
+    /// Resources (CSS, JS) the template refers to, keyed by relative path.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub resources: HashMap<String, String>,
+
+    /// Metadata defaults, which document metadata overrides.
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Error type for bundle operations.
+#[derive(Debug)]
+pub enum BundleError {
+    /// JSON parsing failed.
+    JsonParse(serde_json::Error),
+    /// Template compilation failed.
+    TemplateCompile(TemplateError),
+    /// Unsupported bundle version.
+    UnsupportedVersion(String),
+    /// Reading a bundle file or directory failed.
+    Io(PathBuf, std::io::Error),
+    /// Reading a zip archive failed.
+    Zip(zip::result::ZipError),
+    /// A directory or archive doesn't have the bundle layout.
+    InvalidLayout(String),
+}
+
+impl std::fmt::Display for BundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BundleError::JsonParse(e) => write!(f, "failed to parse bundle JSON: {}", e),
+            BundleError::TemplateCompile(e) => write!(f, "failed to compile template: {}", e),
+            BundleError::UnsupportedVersion(v) => write!(f, "unsupported bundle version: {}", v),
+            BundleError::Io(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            BundleError::Zip(e) => write!(f, "failed to read bundle archive: {}", e),
+            BundleError::InvalidLayout(message) => {
+                write!(f, "invalid template bundle: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BundleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BundleError::JsonParse(e) => Some(e),
+            BundleError::TemplateCompile(e) => Some(e),
+            BundleError::Io(_, e) => Some(e),
+            BundleError::Zip(e) => Some(e),
+            BundleError::UnsupportedVersion(_) | BundleError::InvalidLayout(_) => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for BundleError {
+    fn from(e: serde_json::Error) -> Self {
+        BundleError::JsonParse(e)
+    }
+}
+
+impl From<TemplateError> for BundleError {
+    fn from(e: TemplateError) -> Self {
+        BundleError::TemplateCompile(e)
+    }
+}
+
+impl From<zip::result::ZipError> for BundleError {
+    fn from(e: zip::result::ZipError) -> Self {
+        BundleError::Zip(e)
+    }
+}
+
+/// Currently supported bundle versions.
+const SUPPORTED_VERSIONS: &[&str] = &["1.0.0"];
+
+impl TemplateBundle {
+    /// Create a new template bundle.
+    pub fn new(main: impl Into<String>) -> Self {
+        Self {
+            version: Some("1.0.0".to_string()),
+            main: main.into(),
+            partials: HashMap::new(),
+            resources: HashMap::new(),
+            metadata: serde_json::Map::new(),
+        }
+    }
+
+    /// Add a partial to the bundle.
+    pub fn with_partial(mut self, name: impl Into<String>, content: impl Into<String>) -> Self {
+        self.partials.insert(name.into(), content.into());
+        self
+    }
+
+    /// Add a resource to the bundle.
+    pub fn with_resource(mut self, path: impl Into<String>, content: impl Into<String>) -> Self {
+        self.resources.insert(path.into(), content.into());
+        self
+    }
+
+    /// Add a metadata default to the bundle.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.metadata.insert(key.into(), value);
+        self
+    }
+
+    /// Load a bundle from a path: a directory, a zip archive (`.zip`) or a
+    /// JSON bundle (anything else).
+    pub fn load(path: &Path) -> Result<Self, BundleError> {
+        if path.is_dir() {
+            return Self::from_dir(path);
+        }
+        let read_error = |e| BundleError::Io(path.to_path_buf(), e);
+        if path.extension().is_some_and(|ext| ext == "zip") {
+            let bytes = std::fs::read(path).map_err(read_error)?;
+            Self::from_zip(&bytes)
+        } else {
+            let json = std::fs::read_to_string(path).map_err(read_error)?;
+            Self::from_json(&json)
+        }
+    }
+
+    /// Load a bundle from a directory with the bundle layout.
+    pub fn from_dir(dir: &Path) -> Result<Self, BundleError> {
+        let mut files = Vec::new();
+        read_dir_files(dir, "", &mut files)?;
+        Self::from_files(files)
+    }
+
+    /// Load a bundle from the bytes of a zip archive with the bundle layout.
+    pub fn from_zip(bytes: &[u8]) -> Result<Self, BundleError> {
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+        let mut files = Vec::new();
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index)?;
+            if file.is_dir() {
+                continue;
+            }
+            // Skip entries that would escape the archive (`../`, absolute paths)
+            let Some(path) = file.enclosed_name() else {
+                continue;
+            };
+            let path = path
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let mut content = Vec::new();
+            file.read_to_end(&mut content)
+                .map_err(|e| BundleError::Io(PathBuf::from(&path), e))?;
+            files.push((path, content));
+        }
+        Self::from_files(files)
+    }
+
+    /// Build a bundle from files in the bundle layout, given as
+    /// (`/`-separated relative path, content) pairs.
+    fn from_files(mut files: Vec<(String, Vec<u8>)>) -> Result<Self, BundleError> {
+        strip_common_directory(&mut files);
+
+        let is_main =
+            |path: &str| !path.contains('/') && path.split('.').next() == Some("template");
+        let mut mains = files.iter().filter(|(path, _)| is_main(path));
+        let (main_path, main) = match (mains.next(), mains.next()) {
+            (Some(main), None) => main.clone(),
+            (None, _) => {
+                return Err(BundleError::InvalidLayout(
+                    "no main template (template.*) found".to_string(),
+                ));
+            }
+            (Some(_), Some(_)) => {
+                return Err(BundleError::InvalidLayout(
+                    "more than one main template (template.*) found".to_string(),
+                ));
+            }
+        };
+        let extension = Path::new(&main_path).extension();
+
+        let mut bundle = Self::new(into_text(&main_path, main)?);
+        for (path, content) in files {
+            if path == main_path {
+                continue;
+            }
+            if let Some(resource) = path.strip_prefix("resources/") {
+                let resource = resource.to_string();
+                bundle
+                    .resources
+                    .insert(resource, into_text(&path, content)?);
+            } else if path == "metadata.json" {
+                match serde_json::from_slice(&content)? {
+                    serde_json::Value::Object(metadata) => bundle.metadata = metadata,
+                    _ => {
+                        return Err(BundleError::InvalidLayout(
+                            "metadata.json must contain an object".to_string(),
+                        ));
+                    }
+                }
+            } else if !path.contains('/') && Path::new(&path).extension() == extension {
+                // Partials are referenced without the extension: $title-block()$
+                let name = Path::new(&path)
+                    .file_stem()
+                    .map_or_else(String::new, |stem| stem.to_string_lossy().to_string());
+                bundle.partials.insert(name, into_text(&path, content)?);
+            }
+        }
+        Ok(bundle)
+    }
+
+    /// Fill in the bundle's metadata defaults for variables `context` doesn't
+    /// define.
+    pub fn apply_metadata_defaults(&self, context: &mut TemplateContext) {
+        for (key, value) in &self.metadata {
+            if context.get(key).is_none() {
+                context.insert(key.clone(), TemplateValue::from(value));
+            }
+        }
+    }
+
+    /// Parse a bundle from JSON.
+    pub fn from_json(json: &str) -> Result<Self, BundleError> {
+        let bundle: Self = serde_json::from_str(json)?;
+        bundle.validate_version()?;
+        Ok(bundle)
+    }
+
+    /// Serialize the bundle to JSON.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Validate the bundle version.
+    fn validate_version(&self) -> Result<(), BundleError> {
+        if let Some(version) = &self.version
+            && !SUPPORTED_VERSIONS.contains(&version.as_str())
+        {
+            return Err(BundleError::UnsupportedVersion(version.clone()));
+        }
+        // No version = best-effort, no error
+        Ok(())
+    }
+
+    /// Create a memory resolver from this bundle's partials.
+    pub fn to_resolver(&self) -> MemoryResolver {
+        MemoryResolver::with_partials(self.partials.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+
+    /// Compile the bundle into a ready-to-use template.
+    ///
+    /// This parses the main template and resolves all partials from the bundle.
+    /// The resulting `Template` can be used for rendering.
+    ///
+    /// This creates an internal `SourceContext` for standalone use.
+    /// For integrated use with a shared context, use [`compile_with_context`].
+    ///
+    /// # Arguments
+    ///
+    /// * `template_name` - A name for the template (used in error messages).
+    ///   Typically "bundle" or the source filename.
+    pub fn compile(&self, template_name: &str) -> Result<Template, BundleError> {
+        let resolver = self.to_resolver();
+        let path = Path::new(template_name);
+        let template = Template::compile_with_resolver(&self.main, path, &resolver, 0)?;
+        Ok(template)
+    }
+
+    /// Compile the bundle into a ready-to-use template with a shared `SourceContext`.
+    ///
+    /// This allows the template and its partials to share the same `SourceContext`
+    /// as the main document, ensuring unique file IDs across all files. This is
+    /// essential for correct diagnostic reporting.
+    ///
+    /// # Arguments
+    ///
+    /// * `template_name` - A name for the template (used in error messages).
+    /// * `source_context` - The shared source context (template files will be added to this)
+    pub fn compile_with_context(
+        &self,
+        template_name: &str,
+        source_context: &mut SourceContext,
+    ) -> Result<Template, BundleError> {
+        let resolver = self.to_resolver();
+        let path = Path::new(template_name);
+        let template = Template::compile_with_resolver_and_context(
+            &self.main,
+            path,
+            &resolver,
+            0,
+            source_context,
+        )?;
+        Ok(template)
+    }
+}
+
+/// Collect the files below `dir`, with paths relative to the bundle root.
+fn read_dir_files(
+    dir: &Path,
+    prefix: &str,
+    files: &mut Vec<(String, Vec<u8>)>,
+) -> Result<(), BundleError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| BundleError::Io(dir.to_path_buf(), e))?;
+    for entry in entries {
+        let entry = entry.map_err(|e| BundleError::Io(dir.to_path_buf(), e))?;
+        let path = entry.path();
+        let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            read_dir_files(&path, &format!("{}/", name), files)?;
+        } else {
+            let content = std::fs::read(&path).map_err(|e| BundleError::Io(path.clone(), e))?;
+            files.push((name, content));
+        }
+    }
+    Ok(())
+}
+
+/// Remove a top-level directory that contains every file, as in an archive
+/// made by zipping a bundle directory.
+fn strip_common_directory(files: &mut [(String, Vec<u8>)]) {
+    let Some(directory) = files
+        .first()
+        .and_then(|(path, _)| path.split_once('/'))
+        .map(|(directory, _)| format!("{}/", directory))
+    else {
+        return;
+    };
+    if files.iter().all(|(path, _)| path.starts_with(&directory)) {
+        for (path, _) in files.iter_mut() {
+            path.drain(..directory.len());
+        }
+    }
+}
+
+fn into_text(path: &str, content: Vec<u8>) -> Result<String, BundleError> {
+    String::from_utf8(content)
+        .map_err(|_| BundleError::InvalidLayout(format!("{} is not UTF-8 text", path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolver::PartialResolver;
+
+    #[test]
+    fn test_bundle_new() {
+        let bundle = TemplateBundle::new("Hello $name$!");
+        assert_eq!(bundle.version, Some("1.0.0".to_string()));
+        assert_eq!(bundle.main, "Hello $name$!");
+        assert!(bundle.partials.is_empty());
+    }
+
+    #[test]
+    fn test_bundle_with_partial() {
+        let bundle =
+            TemplateBundle::new("$header()$content").with_partial("header", "<h1>$title$</h1>");
+
+        assert_eq!(bundle.partials.len(), 1);
+        assert_eq!(
+            bundle.partials.get("header"),
+            Some(&"<h1>$title$</h1>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bundle_from_json() {
+        let json = r#"{
+            "version": "1.0.0",
+            "main": "Hello $name$!",
+            "partials": {
+                "header": "<h1>$title$</h1>"
+            }
+        }"#;
+
+        let bundle = TemplateBundle::from_json(json).unwrap();
+        assert_eq!(bundle.version, Some("1.0.0".to_string()));
+        assert_eq!(bundle.main, "Hello $name$!");
+        assert_eq!(
+            bundle.partials.get("header"),
+            Some(&"<h1>$title$</h1>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_bundle_from_json_no_version() {
+        let json = r#"{
+            "main": "Hello $name$!"
+        }"#;
+
+        let bundle = TemplateBundle::from_json(json).unwrap();
+        assert_eq!(bundle.version, None);
+        assert_eq!(bundle.main, "Hello $name$!");
+    }
+
+    #[test]
+    fn test_bundle_from_json_unsupported_version() {
+        let json = r#"{
+            "version": "99.0.0",
+            "main": "Hello"
+        }"#;
+
+        let result = TemplateBundle::from_json(json);
+        assert!(matches!(result, Err(BundleError::UnsupportedVersion(_))));
+    }
+
+    #[test]
+    fn test_bundle_to_json() {
+        let bundle = TemplateBundle::new("Hello!").with_partial("footer", "Goodbye!");
+
+        let json = bundle.to_json().unwrap();
+        assert!(json.contains("\"version\": \"1.0.0\""));
+        assert!(json.contains("\"main\": \"Hello!\""));
+        assert!(json.contains("\"footer\": \"Goodbye!\""));
+    }
+
+    #[test]
+    fn test_bundle_to_resolver() {
+        let bundle = TemplateBundle::new("main")
+            .with_partial("a", "content a")
+            .with_partial("b", "content b");
+
+        let resolver = bundle.to_resolver();
+        assert_eq!(
+            resolver.get_partial("a", Path::new("test")),
+            Some("content a".to_string())
+        );
+        assert_eq!(
+            resolver.get_partial("b", Path::new("test")),
+            Some("content b".to_string())
+        );
+        assert_eq!(resolver.get_partial("c", Path::new("test")), None);
+    }
+
+    #[test]
+    fn test_bundle_compile() {
+        let bundle = TemplateBundle::new("Hello $name$!");
+        let template = bundle.compile("test.html").unwrap();
+
+        let mut ctx = crate::TemplateContext::new();
+        ctx.insert("name", crate::TemplateValue::String("World".to_string()));
+
+        let result = template.render(&ctx).unwrap();
+        assert_eq!(result, "Hello World!");
+    }
+
+    #[test]
+    fn test_bundle_compile_with_partials() {
+        let bundle =
+            TemplateBundle::new("$header()$\nContent").with_partial("header", "<h1>$title$</h1>");
+
+        let template = bundle.compile("test.html").unwrap();
+
+        let mut ctx = crate::TemplateContext::new();
+        ctx.insert(
+            "title",
+            crate::TemplateValue::String("My Title".to_string()),
+        );
+
+        let result = template.render(&ctx).unwrap();
+        assert_eq!(result, "<h1>My Title</h1>\nContent");
+    }
+
+    fn write_bundle_dir(dir: &Path) {
+        std::fs::write(
+            dir.join("template.html"),
+            "$title-block()$<body>$body$</body>",
+        )
+        .unwrap();
+        std::fs::write(dir.join("title-block.html"), "<h1>$title$</h1>").unwrap();
+        std::fs::write(dir.join("metadata.json"), r#"{"title": "Untitled"}"#).unwrap();
+        std::fs::write(dir.join("README.md"), "Not a partial").unwrap();
+        std::fs::create_dir_all(dir.join("resources/css")).unwrap();
+        std::fs::write(dir.join("resources/css/styles.css"), "h1 {}").unwrap();
+    }
+
+    fn assert_loaded(bundle: &TemplateBundle) {
+        assert_eq!(bundle.main, "$title-block()$<body>$body$</body>");
+        assert_eq!(bundle.partials.len(), 1);
+        assert_eq!(bundle.partials["title-block"], "<h1>$title$</h1>");
+        assert_eq!(bundle.resources["css/styles.css"], "h1 {}");
+        assert_eq!(bundle.metadata["title"], "Untitled");
+    }
+
+    #[test]
+    fn test_bundle_from_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        write_bundle_dir(dir.path());
+
+        let bundle = TemplateBundle::load(dir.path()).unwrap();
+        assert_loaded(&bundle);
+
+        // Metadata defaults only fill in what the document doesn't set
+        let template = bundle.compile("template.html").unwrap();
+        let mut ctx = crate::TemplateContext::new();
+        ctx.insert("body", crate::TemplateValue::String("Hi".to_string()));
+        bundle.apply_metadata_defaults(&mut ctx);
+        assert_eq!(
+            template.render(&ctx).unwrap(),
+            "<h1>Untitled</h1><body>Hi</body>"
+        );
+        ctx.insert("title", crate::TemplateValue::String("Mine".to_string()));
+        bundle.apply_metadata_defaults(&mut ctx);
+        assert_eq!(
+            template.render(&ctx).unwrap(),
+            "<h1>Mine</h1><body>Hi</body>"
+        );
+    }
+
+    #[test]
+    fn test_bundle_from_zip() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        // Zipped with a top-level directory, as `zip -r fmt.zip fmt/` does
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for (path, content) in [
+            ("fmt/template.html", "$title-block()$<body>$body$</body>"),
+            ("fmt/title-block.html", "<h1>$title$</h1>"),
+            ("fmt/metadata.json", r#"{"title": "Untitled"}"#),
+            ("fmt/resources/css/styles.css", "h1 {}"),
+        ] {
+            zip.start_file(path, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        let bytes = zip.finish().unwrap().into_inner();
+
+        assert_loaded(&TemplateBundle::from_zip(&bytes).unwrap());
+    }
+
+    #[test]
+    fn test_bundle_invalid_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("other.html"), "x").unwrap();
+        assert!(matches!(
+            TemplateBundle::from_dir(dir.path()),
+            Err(BundleError::InvalidLayout(_))
+        ));
+
+        std::fs::write(dir.path().join("template.html"), "x").unwrap();
+        std::fs::write(dir.path().join("template.tex"), "x").unwrap();
+        assert!(matches!(
+            TemplateBundle::from_dir(dir.path()),
+            Err(BundleError::InvalidLayout(_))
+        ));
+    }
+
+    #[test]
+    fn test_bundle_json_resources_and_metadata() {
+        let bundle = TemplateBundle::new("$body$")
+            .with_resource("styles.css", "body {}")
+            .with_metadata("lang", serde_json::json!("fr"));
+        let loaded = TemplateBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+        assert_eq!(loaded.resources["styles.css"], "body {}");
+        assert_eq!(loaded.metadata["lang"], "fr");
+
+        // Bundles without them still serialize as before
+        let json = TemplateBundle::new("$body$").to_json().unwrap();
+        assert!(!json.contains("resources"));
+        assert!(!json.contains("metadata"));
+    }
+}
@@ -142,6 +142,27 @@ impl TemplateValue {
     }
 }
 
+impl From<&serde_json::Value> for TemplateValue {
+    /// Convert JSON (e.g. metadata defaults) to a template value. Numbers
+    /// become their text, as in Pandoc metadata.
+    fn from(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => TemplateValue::Null,
+            serde_json::Value::Bool(b) => TemplateValue::Bool(*b),
+            serde_json::Value::Number(n) => TemplateValue::String(n.to_string()),
+            serde_json::Value::String(s) => TemplateValue::String(s.clone()),
+            serde_json::Value::Array(items) => {
+                TemplateValue::List(items.iter().map(TemplateValue::from).collect())
+            }
+            serde_json::Value::Object(map) => TemplateValue::Map(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), TemplateValue::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
 /// A context for template evaluation containing variable bindings.
 #[derive(Debug, Clone, Default)]
 pub struct TemplateContext {
@@ -179,6 +200,17 @@ impl TemplateContext {
         self.get(path[0]).and_then(|v| v.get_path(&path[1..]))
     }
 
+    /// All visible bindings, with inner scopes shadowing their parents.
+    pub(crate) fn bindings(&self) -> HashMap<&str, &TemplateValue> {
+        let mut bindings = self
+            .parent
+            .as_ref()
+            .map(|parent| parent.bindings())
+            .unwrap_or_default();
+        bindings.extend(self.variables.iter().map(|(k, v)| (k.as_str(), v)));
+        bindings
+    }
+
     /// Create a child context for a nested scope (e.g., for loop iteration).
     ///
     /// The child context inherits access to parent variables.
@@ -8,15 +8,18 @@
 //! This module implements the evaluation of parsed templates against a context.
 //! The evaluator produces a `Doc` tree that can be rendered to a string.
 
+use crate::analyze::TemplateSchema;
 use crate::ast::TemplateNode;
 use crate::ast::VariableRef;
-use crate::ast::{BreakableSpace, Comment, Conditional, ForLoop, Literal, Nesting, Partial};
+use crate::ast::{BreakableSpace, Comment, Conditional, ForLoop, Literal, Nesting, Partial, Pipe};
 use crate::context::{TemplateContext, TemplateValue};
 use crate::doc::{Doc, concat_docs, intersperse_docs};
 use crate::error::TemplateResult;
 use crate::eval_context::EvalContext;
 use crate::parser::Template;
+use crate::pipes::{apply_pipes, apply_pipes_to_text};
 use quarto_error_reporting::DiagnosticMessage;
+use std::borrow::Cow;
 
 impl Template {
     /// Render this template with the given context.
@@ -78,6 +81,7 @@ impl Template {
         context: &TemplateContext,
     ) -> (Result<String, ()>, Vec<DiagnosticMessage>) {
         let mut eval_ctx = EvalContext::new(context);
+        self.lint(&mut eval_ctx);
         let result = evaluate_nodes(&self.nodes, &mut eval_ctx);
 
         let diagnostics = eval_ctx.into_diagnostics();
@@ -105,6 +109,7 @@ impl Template {
         context: &TemplateContext,
     ) -> (Result<String, ()>, Vec<DiagnosticMessage>) {
         let mut eval_ctx = EvalContext::new(context).with_strict_mode(true);
+        self.lint(&mut eval_ctx);
         let result = evaluate_nodes(&self.nodes, &mut eval_ctx);
 
         let diagnostics = eval_ctx.into_diagnostics();
@@ -126,10 +131,19 @@ impl Template {
         context: &TemplateContext,
     ) -> (TemplateResult<Doc>, Vec<DiagnosticMessage>) {
         let mut eval_ctx = EvalContext::new(context);
+        self.lint(&mut eval_ctx);
         let result = evaluate_nodes(&self.nodes, &mut eval_ctx);
         let diagnostics = eval_ctx.into_diagnostics();
         (result, diagnostics)
     }
+
+    /// Report the problems [`Template::analyze`] finds regardless of the
+    /// context (unknown pipes, unreachable branches) as warnings.
+    fn lint(&self, ctx: &mut EvalContext) {
+        for diagnostic in self.analyze(&TemplateSchema::open()) {
+            ctx.add_diagnostic(diagnostic);
+        }
+    }
 }
 
 /// Evaluate a list of template nodes to a Doc.
@@ -192,13 +206,26 @@ fn resolve_variable<'a>(
     variables.get_path(&path)
 }
 
+/// Resolve a variable reference and apply its pipes.
+fn resolve_piped<'a>(
+    var: &VariableRef,
+    variables: &'a TemplateContext,
+) -> Option<Cow<'a, TemplateValue>> {
+    let value = resolve_variable(var, variables)?;
+    if var.pipes.is_empty() {
+        Some(Cow::Borrowed(value))
+    } else {
+        Some(Cow::Owned(apply_pipes(value.clone(), &var.pipes)))
+    }
+}
+
 /// Render a variable reference to a Doc.
 fn render_variable(var: &VariableRef, ctx: &mut EvalContext) -> Doc {
-    match resolve_variable(var, ctx.variables) {
+    match resolve_piped(var, ctx.variables) {
         Some(value) => {
             // Handle literal separator for arrays: $var[, ]$
             if let Some(sep) = &var.separator
-                && let TemplateValue::List(items) = value
+                && let TemplateValue::List(items) = value.as_ref()
             {
                 let docs: Vec<Doc> = items
                     .iter()
@@ -206,7 +233,6 @@ fn render_variable(var: &VariableRef, ctx: &mut EvalContext) -> Doc {
                     .collect();
                 return intersperse_docs(docs, Doc::text(sep));
             }
-            // TODO: Apply pipes
             // Strip final newline from variable values (matches Pandoc's removeFinalNl)
             value.to_doc().remove_final_newline()
         }
@@ -231,7 +257,7 @@ fn evaluate_conditional(
 ) -> TemplateResult<Doc> {
     // Try each if/elseif branch
     for (condition, body) in branches {
-        if let Some(value) = resolve_variable(condition, ctx.variables)
+        if let Some(value) = resolve_piped(condition, ctx.variables)
             && value.is_truthy()
         {
             return evaluate_nodes(body, ctx);
@@ -253,14 +279,14 @@ fn evaluate_for_loop(
     separator: &Option<Vec<TemplateNode>>,
     ctx: &mut EvalContext,
 ) -> TemplateResult<Doc> {
-    let value = resolve_variable(var, ctx.variables);
+    let value = resolve_piped(var, ctx.variables);
 
     // Determine what to iterate over
-    let items: Vec<&TemplateValue> = match value {
+    let items: Vec<&TemplateValue> = match value.as_deref() {
         Some(TemplateValue::List(items)) => items.iter().collect(),
-        Some(TemplateValue::Map(_)) => vec![value.unwrap()], // Single iteration over map
-        Some(v) if v.is_truthy() => vec![v],                 // Single iteration for truthy scalars
-        _ => vec![],                                         // No iterations for null/falsy
+        Some(map @ TemplateValue::Map(_)) => vec![map], // Single iteration over map
+        Some(v) if v.is_truthy() => vec![v],            // Single iteration for truthy scalars
+        _ => vec![],                                    // No iterations for null/falsy
     };
 
     if items.is_empty() {
@@ -306,10 +332,13 @@ fn evaluate_for_loop(
 ///
 /// Partials come in two forms:
 /// - Bare partial: `$partial()$` - evaluated with current context
-/// - Applied partial: `$var:partial()$` - evaluated with var's value as context
+/// - Applied partial: `$var:partial()$` - evaluated once per value of `var`
+///   (once per item for arrays), like `$for(var)$$partial()$$endfor$`
 ///
-/// For applied partials with array values, the partial is evaluated once per item,
-/// with optional separator between iterations.
+/// In an applied partial, the value is bound to `it` and to the variable's
+/// name, and a map's fields are also bound directly; the rest of the
+/// context stays visible. Pipes apply to each rendering of the partial,
+/// before the optional separator joins them.
 fn evaluate_partial(partial: &Partial, ctx: &mut EvalContext) -> TemplateResult<Doc> {
     let Partial {
         name,
@@ -334,60 +363,61 @@ fn evaluate_partial(partial: &Partial, ctx: &mut EvalContext) -> TemplateResult<
         }
     };
 
-    // TODO: Apply pipes to partial output
-    let _ = pipes;
+    let Some(var_ref) = var else {
+        // Bare partial: evaluate with current context
+        let result = evaluate_nodes(nodes, ctx)?;
+        return Ok(apply_partial_pipes(result, pipes));
+    };
 
-    match var {
+    // Applied partial: evaluate with var's value bound
+    let items: Vec<&TemplateValue> = match resolve_variable(var_ref, ctx.variables) {
         None => {
-            // Bare partial: evaluate with current context
-            evaluate_nodes(nodes, ctx)
+            // Variable not found - emit warning/error
+            let var_path = var_ref.path.join(".");
+            ctx.warn_or_error_with_code(
+                "Q-10-2",
+                format!("Undefined variable: {}", var_path),
+                &var_ref.source_info,
+            );
+            return Ok(Doc::Empty);
         }
-        Some(var_ref) => {
-            // Applied partial: evaluate with var's value as context
-            let value = resolve_variable(var_ref, ctx.variables);
-
-            match value {
-                None => {
-                    // Variable not found - emit warning/error
-                    let var_path = var_ref.path.join(".");
-                    ctx.warn_or_error_with_code(
-                        "Q-10-2",
-                        format!("Undefined variable: {}", var_path),
-                        &var_ref.source_info,
-                    );
-                    Ok(Doc::Empty)
-                }
-                Some(TemplateValue::List(items)) => {
-                    // Iterate over list items
-                    let mut results = Vec::new();
-                    for item in items {
-                        let item_ctx = item.to_context();
-                        let mut child_ctx = ctx.child(&item_ctx);
-                        let result = evaluate_nodes(nodes, &mut child_ctx)?;
-                        results.push(result);
-                        ctx.merge_diagnostics(child_ctx);
-                    }
-
-                    // Join with separator
-                    if let Some(sep) = separator {
-                        Ok(intersperse_docs(results, Doc::text(sep)))
-                    } else {
-                        Ok(concat_docs(results))
-                    }
-                }
-                Some(value) => {
-                    // Single value: evaluate once with value as context
-                    let item_ctx = value.to_context();
-                    let mut child_ctx = ctx.child(&item_ctx);
-                    let result = evaluate_nodes(nodes, &mut child_ctx)?;
-                    ctx.merge_diagnostics(child_ctx);
-                    Ok(result)
-                }
+        Some(TemplateValue::List(items)) => items.iter().collect(),
+        Some(value) => vec![value],
+    };
+
+    let var_name = var_ref.path.last().map_or("", |s| s.as_str());
+    let mut results = Vec::new();
+    for item in items {
+        let mut child_vars = ctx.variables.child();
+        if let TemplateValue::Map(fields) = item {
+            for (key, value) in fields {
+                child_vars.insert(key.clone(), value.clone());
             }
         }
+        child_vars.insert(var_name, item.clone());
+        child_vars.insert("it", item.clone());
+
+        let mut child_ctx = ctx.child(&child_vars);
+        let result = evaluate_nodes(nodes, &mut child_ctx)?;
+        results.push(apply_partial_pipes(result, pipes));
+        ctx.merge_diagnostics(child_ctx);
+    }
+
+    // Join with separator
+    match separator {
+        Some(sep) => Ok(intersperse_docs(results, Doc::text(sep))),
+        None => Ok(concat_docs(results)),
     }
 }
 
+/// Apply pipes to the rendered output of a partial.
+fn apply_partial_pipes(doc: Doc, pipes: &[Pipe]) -> Doc {
+    if pipes.is_empty() {
+        return doc;
+    }
+    Doc::text(apply_pipes_to_text(doc.render(None), pipes))
+}
+
 // Re-export the old evaluate function for backwards compatibility
 // (kept as a module-level function in case anyone was using it)
 
@@ -453,6 +483,19 @@ mod tests {
         assert_eq!(diagnostics[0].code.as_deref(), Some("Q-10-2"));
     }
 
+    #[test]
+    fn test_unreachable_branch_warning() {
+        let template = compile("$if(a)$A$elseif(a)$B$endif$");
+        let mut ctx = ctx();
+        ctx.insert("a", TemplateValue::Bool(true));
+        let (result, diagnostics) = template.render_with_diagnostics(&ctx);
+
+        // Lint warnings don't stop rendering
+        assert_eq!(result.unwrap(), "A");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("Q-10-9"));
+    }
+
     #[test]
     fn test_missing_variable_strict_mode() {
         let template = compile("Hello, $name$!");
@@ -565,11 +608,6 @@ mod tests {
 
     #[test]
     fn test_conditional_elseif() {
-        // Note: The tree-sitter grammar currently has issues with elseif/else parsing
-        // when they appear without whitespace. Use braces syntax or whitespace for now.
-        // TODO: Fix this by implementing an external scanner in tree-sitter
-
-        // Using brace syntax which parses correctly
         let template = compile("${if(a)}A${elseif(b)}B${else}C${endif}");
 
         // a is true
@@ -590,6 +628,23 @@ mod tests {
         assert_eq!(template.render(&ctx3).unwrap(), "C");
     }
 
+    #[test]
+    fn test_conditional_elseif_chain() {
+        let template = compile("$if(a)$A$elseif(b)$B$elseif(c.d)$D$else$E$endif$");
+        let render = |key: &str, value: TemplateValue| {
+            let mut ctx = ctx();
+            ctx.insert(key, value);
+            template.render(&ctx).unwrap()
+        };
+
+        assert_eq!(render("a", TemplateValue::Bool(true)), "A");
+        assert_eq!(render("b", TemplateValue::Bool(true)), "B");
+        let mut c = HashMap::new();
+        c.insert("d".to_string(), TemplateValue::String("yes".to_string()));
+        assert_eq!(render("c", TemplateValue::Map(c)), "D");
+        assert_eq!(render("b", TemplateValue::Bool(false)), "E");
+    }
+
     #[test]
     fn test_for_loop_basic() {
         let template = compile("$for(x)$$x$$endfor$");
@@ -862,6 +917,75 @@ mod tests {
         assert_eq!(template.render(&ctx).unwrap(), "<b>Alice</b>");
     }
 
+    #[test]
+    fn test_applied_partial_sees_outer_context() {
+        // `it` and the variable name are bound to the item; other variables stay visible
+        let template = compile_with_partials(
+            "$authors:author()[; ]$",
+            [("author", "$it.name$ ($authors.role$, $journal$)")],
+        );
+
+        let mut ctx = ctx();
+        let author = |name: &str| {
+            let mut m = HashMap::new();
+            m.insert("name".to_string(), TemplateValue::String(name.to_string()));
+            m.insert(
+                "role".to_string(),
+                TemplateValue::String("author".to_string()),
+            );
+            TemplateValue::Map(m)
+        };
+        ctx.insert(
+            "authors",
+            TemplateValue::List(vec![author("A"), author("B")]),
+        );
+        ctx.insert("journal", TemplateValue::String("J".to_string()));
+
+        assert_eq!(
+            template.render(&ctx).unwrap(),
+            "A (author, J); B (author, J)"
+        );
+    }
+
+    #[test]
+    fn test_applied_partial_on_loop_item() {
+        let template = compile_with_partials(
+            "$for(items)$$it:item.html()$$sep$,$endfor$",
+            [("item.html", "<$it$>")],
+        );
+
+        let mut ctx = ctx();
+        ctx.insert(
+            "items",
+            TemplateValue::List(vec![
+                TemplateValue::String("a".to_string()),
+                TemplateValue::String("b".to_string()),
+            ]),
+        );
+
+        assert_eq!(template.render(&ctx).unwrap(), "<a>,<b>");
+    }
+
+    #[test]
+    fn test_partial_pipes() {
+        // Pipes apply to each rendering of the partial, before the separator
+        let template = compile_with_partials(
+            "$items:item()[, ]/uppercase$|$footer()/left 6 \"[\" \"]\"$",
+            [("item", "<$it$>"), ("footer", "end")],
+        );
+
+        let mut ctx = ctx();
+        ctx.insert(
+            "items",
+            TemplateValue::List(vec![
+                TemplateValue::String("a".to_string()),
+                TemplateValue::String("b".to_string()),
+            ]),
+        );
+
+        assert_eq!(template.render(&ctx).unwrap(), "<A>, <B>|[end   ]");
+    }
+
     #[test]
     fn test_partial_missing_variable_warning() {
         // Undefined variable in applied partial should emit warning
@@ -10,14 +10,19 @@
 //!
 //! - Variable interpolation: `$variable$` or `${variable}`
 //! - Nested field access: `$employee.salary$`
-//! - Conditionals: `$if(var)$...$else$...$endif$`
+//! - Conditionals: `$if(var)$...$elseif(other)$...$else$...$endif$`
 //! - For loops: `$for(items)$...$sep$...$endfor$`
-//! - Partials: `$partial()$` or `$var:partial()$`
-//! - Pipes: `$var/uppercase$`, `$var/left 20 "" ""$`
+//! - Partials: `$partial()$` or `$var:partial()$` (with `it` bound to each
+//!   value of `var`), optionally followed by pipes: `$var:partial()/uppercase$`
+//! - Pipes: `$var/uppercase$`, `$names/rest/first$`, `$var/left 20 "" ""$`
+//!   (the full doctemplates set; see [`pipes`])
 //! - Nesting directive: `$^$` for indentation control
 //! - Breakable spaces: `$~$...$~$`
 //! - Comments: `$-- comment`
 //!
+//! [`Template::analyze`] checks a template against the variables its context
+//! will provide (see [`analyze`]).
+//!
 //! # Architecture
 //!
 //! The template engine is **independent of Pandoc AST types**. It defines its own
@@ -41,23 +46,30 @@
 //! assert_eq!(output, "Hello, World!");
 //! ```
 
+pub mod analyze;
 pub mod ast;
+pub mod bundle;
 pub mod context;
 pub mod doc;
 pub mod error;
 pub mod eval_context;
 pub mod evaluator;
 pub mod parser;
+pub mod pipes;
 pub mod resolver;
 
 // Re-export main types at crate root
+pub use analyze::{TemplateSchema, VariableSchema};
 pub use ast::{
     BreakableSpace, Comment, Conditional, ForLoop, Literal, Nesting, Partial, Pipe, PipeArg,
     TemplateNode, VariableRef,
 };
+pub use bundle::{BundleError, TemplateBundle};
 pub use context::{TemplateContext, TemplateValue};
 pub use doc::Doc;
 pub use error::TemplateError;
 pub use eval_context::{DiagnosticCollector, EvalContext};
 pub use parser::Template;
-pub use resolver::{FileSystemResolver, MemoryResolver, NullResolver, PartialResolver};
+pub use resolver::{
+    CachingResolver, FileSystemResolver, MemoryResolver, NullResolver, PartialResolver,
+};
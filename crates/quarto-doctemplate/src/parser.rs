@@ -16,11 +16,17 @@ use crate::error::{TemplateError, TemplateResult};
 use crate::resolver::{PartialResolver, remove_final_newline, resolve_partial_path};
 use quarto_source_map::{FileId, SourceContext, SourceInfo};
 use quarto_treesitter_ast::bottomup_traverse_concrete_tree;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 use tree_sitter::{Node, Parser};
 
 /// A compiled template ready for evaluation.
-#[derive(Debug, Clone)]
+///
+/// Templates can be serialized (e.g. with `serde_json`) and deserialized to
+/// skip parsing. Their source locations refer to file IDs in the
+/// `SourceContext` they were compiled with, so diagnostics from a
+/// deserialized template need that same context to be reported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Template {
     /// The parsed template AST.
     pub(crate) nodes: Vec<TemplateNode>,
@@ -152,7 +158,7 @@ impl Template {
     /// Internal: Parse a template with a pre-assigned file ID.
     ///
     /// This is the core parsing logic, separated from SourceContext management.
-    fn parse_with_file_id(source: &str, file_id: FileId) -> TemplateResult<Self> {
+    pub(crate) fn parse_with_file_id(source: &str, file_id: FileId) -> TemplateResult<Self> {
         // Set up tree-sitter parser
         let mut parser = Parser::new();
         let language = tree_sitter_doctemplate::LANGUAGE;
@@ -359,17 +365,26 @@ fn resolve_partials_with_context(
                 // Determine the path for this partial (for nested partial resolution)
                 let partial_path = resolve_partial_path(&partial.name, template_path);
 
-                // Parse the partial using the shared source context
-                let partial_template = Template::compile_with_resolver_and_context(
-                    partial_source,
+                // Parse the partial (the resolver may reuse an earlier parse)
+                // with a file ID from the shared source context
+                let file_id = source_context.add_file(
+                    partial_path.to_string_lossy().to_string(),
+                    Some(partial_source.to_string()),
+                );
+                let mut partial_nodes = resolver.parse_partial(partial_source, file_id)?;
+
+                // Then resolve its own partials
+                resolve_partials_with_context(
+                    &mut partial_nodes,
                     &partial_path,
                     resolver,
                     depth + 1,
+                    max_depth,
                     source_context,
                 )?;
 
                 // Store the resolved nodes
-                partial.resolved = Some(partial_template.nodes);
+                partial.resolved = Some(partial_nodes);
             }
 
             // Recurse into nested structures
@@ -580,7 +595,11 @@ fn visit_node(
         // Pipes
         "pipe" => {
             // The pipe node contains the actual pipe type as a child
-            for (kind, _) in &children {
+            for (kind, child) in &children {
+                // Pipes with arguments (`left 20 "| " ""`) are already built
+                if let Intermediate::Pipe(pipe) = child {
+                    return Intermediate::Pipe(pipe.clone());
+                }
                 if kind.starts_with("pipe_") {
                     let pipe_name = kind.strip_prefix("pipe_").unwrap_or(kind);
                     return Intermediate::Pipe(Pipe::new(pipe_name, source_info));
@@ -814,6 +833,7 @@ fn extract_interpolation_parts(children: Vec<(String, Intermediate)>) -> Interpo
     let mut pipes = Vec::new();
     let mut separator = None;
     let mut partial_name = None;
+    let mut bare_partial = None;
 
     for (kind, child) in children {
         match child {
@@ -821,13 +841,9 @@ fn extract_interpolation_parts(children: Vec<(String, Intermediate)>) -> Interpo
             Intermediate::Pipe(pipe) => pipes.push(pipe),
             Intermediate::LiteralSeparator(sep) => separator = Some(sep),
             Intermediate::Partial(name) => partial_name = Some(name),
-            // Bare partial is already fully parsed
+            // Bare partial; pipes following it are its siblings
             Intermediate::BarePartial(name, bare_pipes, source_info) => {
-                return InterpolationResult::BarePartial {
-                    partial_name: name,
-                    pipes: bare_pipes,
-                    source_info,
-                };
+                bare_partial = Some((name, bare_pipes, source_info));
             }
             // Also check for _interpolation which passes through
             Intermediate::Node(TemplateNode::Variable(var)) if kind == "_interpolation" => {
@@ -852,7 +868,14 @@ fn extract_interpolation_parts(children: Vec<(String, Intermediate)>) -> Interpo
         }
     }
 
-    if let Some(name) = partial_name {
+    if let Some((name, mut bare_pipes, source_info)) = bare_partial {
+        bare_pipes.append(&mut pipes);
+        InterpolationResult::BarePartial {
+            partial_name: name,
+            pipes: bare_pipes,
+            source_info,
+        }
+    } else if let Some(name) = partial_name {
         InterpolationResult::AppliedPartial {
             var_ref,
             partial_name: name,
@@ -1551,4 +1574,28 @@ mod tests {
         let nodes = collect_nodes(children);
         assert_eq!(nodes.len(), 1);
     }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let resolver =
+            crate::resolver::MemoryResolver::with_partials([("item", "<li>$it/uppercase$</li>")]);
+        let template = Template::compile_with_resolver(
+            "$if(items)$<ul>$items:item()[\n]$</ul>$else$none$endif$",
+            Path::new("list.html"),
+            &resolver,
+            0,
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&template).unwrap();
+        let loaded: Template = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.nodes, template.nodes);
+
+        let mut ctx = crate::TemplateContext::new();
+        ctx.insert(
+            "items",
+            crate::TemplateValue::List(vec![crate::TemplateValue::String("a".to_string())]),
+        );
+        assert_eq!(loaded.render(&ctx).unwrap(), "<ul><li>A</li></ul>");
+    }
 }
@@ -0,0 +1,390 @@
+/*
+ * pipes.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Pipe transformations.
+//!
+//! Pipes transform a value before it is output: `$title/uppercase$`,
+//! `$authors/first/uppercase$`, `$author:card()/left 20 "| " ""$`. They are
+//! applied left to right, each to the result of the previous one, and follow
+//! the semantics of Pandoc's doctemplates:
+//!
+//! - `pairs`: a map or array becomes an array of `{key, value}` maps (array
+//!   keys are 1-based indices)
+//! - `first`, `last`, `rest`, `allbutlast`: select from a non-empty array
+//! - `uppercase`, `lowercase`, `chomp`, `alpha`, `roman`: transform text,
+//!   including the text inside arrays and maps
+//! - `length`: number of characters, array elements or map entries
+//! - `reverse`: reverse text or an array
+//! - `left`, `right`, `center`: lay out text in a block of a given width
+//! - `nowrap`: no effect, since output is never wrapped
+//!
+//! Pipes that don't apply to a value leave it unchanged, as do unknown pipes.
+
+use crate::ast::{Pipe, PipeArg};
+use crate::context::TemplateValue;
+
+/// The names of all pipes.
+pub const PIPE_NAMES: &[&str] = &[
+    "pairs",
+    "first",
+    "last",
+    "rest",
+    "allbutlast",
+    "uppercase",
+    "lowercase",
+    "length",
+    "reverse",
+    "chomp",
+    "nowrap",
+    "alpha",
+    "roman",
+    "left",
+    "right",
+    "center",
+];
+
+/// Apply `pipes` in order to `value`.
+pub fn apply_pipes(value: TemplateValue, pipes: &[Pipe]) -> TemplateValue {
+    pipes.iter().fold(value, apply_pipe)
+}
+
+/// Apply `pipes` in order to `text`, such as the rendered output of a partial.
+pub fn apply_pipes_to_text(text: String, pipes: &[Pipe]) -> String {
+    apply_pipes(TemplateValue::String(text), pipes).render()
+}
+
+fn apply_pipe(value: TemplateValue, pipe: &Pipe) -> TemplateValue {
+    match pipe.name.as_str() {
+        "pairs" => pairs(value),
+        "first" => select(value, |items| items.into_iter().next()),
+        "last" => select(value, |items| items.into_iter().last()),
+        "rest" => match value {
+            TemplateValue::List(mut items) if !items.is_empty() => {
+                items.remove(0);
+                TemplateValue::List(items)
+            }
+            other => other,
+        },
+        "allbutlast" => match value {
+            TemplateValue::List(mut items) if !items.is_empty() => {
+                items.pop();
+                TemplateValue::List(items)
+            }
+            other => other,
+        },
+        "length" => {
+            let length = match &value {
+                TemplateValue::List(items) => items.len(),
+                TemplateValue::Map(map) => map.len(),
+                TemplateValue::Null => 0,
+                other => other.render().chars().count(),
+            };
+            TemplateValue::String(length.to_string())
+        }
+        "reverse" => match value {
+            TemplateValue::List(mut items) => {
+                items.reverse();
+                TemplateValue::List(items)
+            }
+            TemplateValue::String(s) => TemplateValue::String(s.chars().rev().collect()),
+            other => other,
+        },
+        "uppercase" => map_text(value, &|s| s.to_uppercase()),
+        "lowercase" => map_text(value, &|s| s.to_lowercase()),
+        "chomp" => map_text(value, &|s| s.trim_end_matches('\n').to_string()),
+        "alpha" => map_text(value, &|s| match s.trim().parse::<i64>() {
+            Ok(n) if n > 0 => {
+                let letter = b'a' + ((n - 1) % 26) as u8;
+                (letter as char).to_string()
+            }
+            _ => s.to_string(),
+        }),
+        "roman" => map_text(value, &|s| match s.trim().parse::<i64>() {
+            Ok(n) if (1..4000).contains(&n) => to_roman(n),
+            _ => s.to_string(),
+        }),
+        "left" | "right" | "center" => match value {
+            TemplateValue::String(s) => TemplateValue::String(align(&s, pipe)),
+            other => other,
+        },
+        // No line wrapping yet, so nothing to prevent
+        "nowrap" => value,
+        _ => value,
+    }
+}
+
+/// `pairs`: turn a map or array into an array of `{key, value}` maps.
+fn pairs(value: TemplateValue) -> TemplateValue {
+    let pair = |key: String, value: TemplateValue| {
+        let mut map = std::collections::HashMap::new();
+        map.insert("key".to_string(), TemplateValue::String(key));
+        map.insert("value".to_string(), value);
+        TemplateValue::Map(map)
+    };
+    match value {
+        TemplateValue::Map(map) => {
+            // Pandoc's maps are ordered by key
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            TemplateValue::List(entries.into_iter().map(|(k, v)| pair(k, v)).collect())
+        }
+        TemplateValue::List(items) => TemplateValue::List(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| pair((i + 1).to_string(), v))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Pick one element of a non-empty array; anything else is unchanged.
+fn select(
+    value: TemplateValue,
+    pick: impl FnOnce(Vec<TemplateValue>) -> Option<TemplateValue>,
+) -> TemplateValue {
+    match value {
+        TemplateValue::List(items) if !items.is_empty() => {
+            pick(items).unwrap_or(TemplateValue::Null)
+        }
+        other => other,
+    }
+}
+
+/// Transform every text value, including those nested in arrays and maps.
+fn map_text(value: TemplateValue, f: &dyn Fn(&str) -> String) -> TemplateValue {
+    match value {
+        TemplateValue::String(s) => TemplateValue::String(f(&s)),
+        TemplateValue::List(items) => {
+            TemplateValue::List(items.into_iter().map(|v| map_text(v, f)).collect())
+        }
+        TemplateValue::Map(map) => {
+            TemplateValue::Map(map.into_iter().map(|(k, v)| (k, map_text(v, f))).collect())
+        }
+        other => other,
+    }
+}
+
+/// Lay out `text` in a block of the pipe's width, with optional borders.
+fn align(text: &str, pipe: &Pipe) -> String {
+    let width = match pipe.args.first() {
+        Some(PipeArg::Integer(n)) => usize::try_from(*n).unwrap_or(0),
+        _ => 0,
+    };
+    let border = |index: usize| match pipe.args.get(index) {
+        Some(PipeArg::String(s)) => unescape_border(s),
+        _ => String::new(),
+    };
+    let (left_border, right_border) = (border(1), border(2));
+
+    text.split('\n')
+        .map(|line| {
+            let padding = width.saturating_sub(line.chars().count());
+            let (before, after) = match pipe.name.as_str() {
+                "right" => (padding, 0),
+                "center" => (padding / 2, padding - padding / 2),
+                _ => (0, padding),
+            };
+            format!(
+                "{}{}{}{}{}",
+                left_border,
+                " ".repeat(before),
+                line,
+                " ".repeat(after),
+                right_border
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Borders are written as quoted strings, where `\"` is a quote and `\\` a
+/// backslash.
+fn unescape_border(border: &str) -> String {
+    let mut result = String::with_capacity(border.len());
+    let mut chars = border.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(next @ ('"' | '\\'))) => {
+                result.push(next);
+                chars.next();
+            }
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+fn to_roman(mut n: i64) -> String {
+    const NUMERALS: [(i64, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut result = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            result.push_str(numeral);
+            n -= value;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_source_map::{FileId, SourceInfo};
+
+    fn pipe(name: &str, args: Vec<PipeArg>) -> Pipe {
+        Pipe::with_args(name, args, SourceInfo::original(FileId(0), 0, 0))
+    }
+
+    fn apply(text: &str, pipes: Vec<Pipe>) -> String {
+        apply_pipes_to_text(text.to_string(), &pipes)
+    }
+
+    #[test]
+    fn test_case_and_chomp() {
+        assert_eq!(apply("Hello", vec![pipe("uppercase", vec![])]), "HELLO");
+        assert_eq!(apply("Hello", vec![pipe("lowercase", vec![])]), "hello");
+        assert_eq!(apply("text\n\n", vec![pipe("chomp", vec![])]), "text");
+        // Pipes apply in order
+        assert_eq!(
+            apply("ab\n", vec![pipe("chomp", vec![]), pipe("reverse", vec![])]),
+            "ba"
+        );
+    }
+
+    #[test]
+    fn test_numbering() {
+        assert_eq!(apply("3", vec![pipe("alpha", vec![])]), "c");
+        assert_eq!(apply("27", vec![pipe("alpha", vec![])]), "a");
+        assert_eq!(apply("14", vec![pipe("roman", vec![])]), "xiv");
+        assert_eq!(apply("x", vec![pipe("roman", vec![])]), "x");
+        assert_eq!(apply("héllo", vec![pipe("length", vec![])]), "5");
+    }
+
+    #[test]
+    fn test_alignment() {
+        let args = |n| {
+            vec![
+                PipeArg::Integer(n),
+                PipeArg::String("| ".to_string()),
+                PipeArg::String(" |".to_string()),
+            ]
+        };
+        assert_eq!(apply("ab", vec![pipe("left", args(4))]), "| ab   |");
+        assert_eq!(apply("ab", vec![pipe("right", args(4))]), "|   ab |");
+        assert_eq!(apply("ab", vec![pipe("center", args(5))]), "|  ab   |");
+        assert_eq!(
+            apply("a\nbc", vec![pipe("left", vec![PipeArg::Integer(3)])]),
+            "a  \nbc "
+        );
+    }
+
+    fn list(items: &[&str]) -> TemplateValue {
+        TemplateValue::List(
+            items
+                .iter()
+                .map(|s| TemplateValue::String(s.to_string()))
+                .collect(),
+        )
+    }
+
+    fn text(s: &str) -> TemplateValue {
+        TemplateValue::String(s.to_string())
+    }
+
+    #[test]
+    fn test_array_selection() {
+        let abc = || list(&["a", "b", "c"]);
+        assert_eq!(apply_pipes(abc(), &[pipe("first", vec![])]), text("a"));
+        assert_eq!(apply_pipes(abc(), &[pipe("last", vec![])]), text("c"));
+        assert_eq!(
+            apply_pipes(abc(), &[pipe("rest", vec![])]),
+            list(&["b", "c"])
+        );
+        assert_eq!(
+            apply_pipes(abc(), &[pipe("allbutlast", vec![])]),
+            list(&["a", "b"])
+        );
+        assert_eq!(
+            apply_pipes(abc(), &[pipe("reverse", vec![])]),
+            list(&["c", "b", "a"])
+        );
+        // Empty arrays and non-arrays are unchanged
+        assert_eq!(apply_pipes(list(&[]), &[pipe("first", vec![])]), list(&[]));
+        assert_eq!(apply_pipes(text("x"), &[pipe("rest", vec![])]), text("x"));
+    }
+
+    #[test]
+    fn test_pairs() {
+        let pairs = apply_pipes(list(&["a", "b"]), &[pipe("pairs", vec![])]);
+        let TemplateValue::List(items) = pairs else {
+            panic!("expected a list");
+        };
+        assert_eq!(items[1].get_path(&["key"]), Some(&text("2")));
+        assert_eq!(items[1].get_path(&["value"]), Some(&text("b")));
+
+        let mut map = std::collections::HashMap::new();
+        map.insert("z".to_string(), text("last"));
+        map.insert("a".to_string(), text("first"));
+        let pairs = apply_pipes(TemplateValue::Map(map), &[pipe("pairs", vec![])]);
+        let keys = apply_pipes(pairs, &[pipe("first", vec![])]);
+        assert_eq!(keys.get_path(&["key"]), Some(&text("a")));
+    }
+
+    #[test]
+    fn test_composition() {
+        let names = || list(&["ann", "bob"]);
+        assert_eq!(
+            apply_pipes(names(), &[pipe("last", vec![]), pipe("uppercase", vec![])]),
+            text("BOB")
+        );
+        // Text pipes reach into arrays
+        assert_eq!(
+            apply_pipes(names(), &[pipe("uppercase", vec![])]),
+            list(&["ANN", "BOB"])
+        );
+        assert_eq!(apply_pipes(names(), &[pipe("length", vec![])]), text("2"));
+        assert_eq!(
+            apply_pipes(
+                names(),
+                &[
+                    pipe("first", vec![]),
+                    pipe("right", vec![PipeArg::Integer(5)])
+                ]
+            ),
+            text("  ann")
+        );
+        // Alignment has no effect on arrays
+        assert_eq!(
+            apply_pipes(names(), &[pipe("left", vec![PipeArg::Integer(5)])]),
+            names()
+        );
+    }
+
+    #[test]
+    fn test_border_escapes() {
+        let args = vec![
+            PipeArg::Integer(1),
+            PipeArg::String("\\\"".to_string()),
+            PipeArg::String("\\\\".to_string()),
+        ];
+        assert_eq!(apply("x", vec![pipe("left", args)]), "\"x\\");
+    }
+}
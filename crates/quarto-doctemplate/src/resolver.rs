@@ -6,9 +6,17 @@
 //! Partial template resolution.
 //!
 //! This module provides traits and implementations for loading partial templates
-//! from various sources (filesystem, memory, etc.).
+//! from various sources (filesystem, memory, etc.), and [`CachingResolver`],
+//! which keeps parsed partials so that compiling many templates that share
+//! partials (as in a project render) parses each partial once.
 
+use crate::ast::TemplateNode;
+use crate::error::TemplateResult;
+use crate::parser::Template;
+use quarto_source_map::{FileId, SourceInfo};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 /// Trait for loading partial templates.
 ///
@@ -24,6 +32,15 @@ pub trait PartialResolver {
     /// # Returns
     /// The partial template source text, or `None` if not found.
     fn get_partial(&self, name: &str, base_path: &Path) -> Option<String>;
+
+    /// Parse the source of a partial, with source locations in `file_id`.
+    ///
+    /// The returned nodes have not had their own partials resolved. The
+    /// default implementation parses every time; [`CachingResolver`] reuses
+    /// earlier parses of the same source.
+    fn parse_partial(&self, source: &str, file_id: FileId) -> TemplateResult<Vec<TemplateNode>> {
+        Template::parse_with_file_id(source, file_id).map(|template| template.nodes)
+    }
 }
 
 /// Resolver that loads partials from the filesystem.
@@ -98,6 +115,139 @@ impl PartialResolver for MemoryResolver {
     }
 }
 
+/// Resolver that caches parsed partials from another resolver.
+///
+/// Partials are loaded by the wrapped resolver as usual, but their parsed
+/// ASTs are cached by source text, so a partial used by many templates (or
+/// by the same template compiled for many pages) is only parsed once. Cached
+/// ASTs get the file ID of each new compilation, so diagnostics still point
+/// at the right file.
+///
+/// The cache is shared between threads, so one resolver can serve a whole
+/// parallel project render.
+#[derive(Debug, Default)]
+pub struct CachingResolver<R> {
+    inner: R,
+    parsed: Mutex<HashMap<String, Arc<[TemplateNode]>>>,
+}
+
+impl<R: PartialResolver> CachingResolver<R> {
+    /// Wrap `inner` with an empty cache.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            parsed: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The wrapped resolver.
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    /// Number of distinct partials in the cache.
+    pub fn len(&self) -> usize {
+        self.cache().len()
+    }
+
+    /// Check if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.cache().is_empty()
+    }
+
+    /// Drop all cached partials.
+    pub fn clear(&self) {
+        self.cache().clear();
+    }
+
+    fn cache(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<[TemplateNode]>>> {
+        // A panic while holding the lock can't leave the map inconsistent
+        self.parsed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<R: PartialResolver> PartialResolver for CachingResolver<R> {
+    fn get_partial(&self, name: &str, base_path: &Path) -> Option<String> {
+        self.inner.get_partial(name, base_path)
+    }
+
+    fn parse_partial(&self, source: &str, file_id: FileId) -> TemplateResult<Vec<TemplateNode>> {
+        let cached = self.cache().get(source).cloned();
+        let nodes = match cached {
+            Some(nodes) => nodes,
+            None => {
+                let nodes: Arc<[TemplateNode]> = self.inner.parse_partial(source, file_id)?.into();
+                self.cache().insert(source.to_string(), nodes.clone());
+                nodes
+            }
+        };
+        let mut nodes = nodes.to_vec();
+        set_file_id(&mut nodes, file_id);
+        Ok(nodes)
+    }
+}
+
+/// Point the source locations of freshly parsed `nodes` at `file_id`.
+///
+/// Parsing only produces `Original` locations, and unresolved partials have
+/// no nodes from other files, so every location is in the same file.
+fn set_file_id(nodes: &mut [TemplateNode], file_id: FileId) {
+    fn set(source_info: &mut SourceInfo, file_id: FileId) {
+        if let SourceInfo::Original { file_id: id, .. } = source_info {
+            *id = file_id;
+        }
+    }
+    fn set_var(var: &mut crate::ast::VariableRef, file_id: FileId) {
+        set(&mut var.source_info, file_id);
+        for pipe in &mut var.pipes {
+            set(&mut pipe.source_info, file_id);
+        }
+    }
+
+    for node in nodes {
+        match node {
+            TemplateNode::Literal(literal) => set(&mut literal.source_info, file_id),
+            TemplateNode::Variable(var) => set_var(var, file_id),
+            TemplateNode::Conditional(conditional) => {
+                set(&mut conditional.source_info, file_id);
+                for (condition, body) in &mut conditional.branches {
+                    set_var(condition, file_id);
+                    set_file_id(body, file_id);
+                }
+                if let Some(else_branch) = &mut conditional.else_branch {
+                    set_file_id(else_branch, file_id);
+                }
+            }
+            TemplateNode::ForLoop(for_loop) => {
+                set(&mut for_loop.source_info, file_id);
+                set_var(&mut for_loop.var, file_id);
+                set_file_id(&mut for_loop.body, file_id);
+                if let Some(separator) = &mut for_loop.separator {
+                    set_file_id(separator, file_id);
+                }
+            }
+            TemplateNode::Partial(partial) => {
+                set(&mut partial.source_info, file_id);
+                if let Some(var) = &mut partial.var {
+                    set_var(var, file_id);
+                }
+                for pipe in &mut partial.pipes {
+                    set(&mut pipe.source_info, file_id);
+                }
+            }
+            TemplateNode::Nesting(nesting) => {
+                set(&mut nesting.source_info, file_id);
+                set_file_id(&mut nesting.children, file_id);
+            }
+            TemplateNode::BreakableSpace(space) => {
+                set(&mut space.source_info, file_id);
+                set_file_id(&mut space.children, file_id);
+            }
+            TemplateNode::Comment(comment) => set(&mut comment.source_info, file_id),
+        }
+    }
+}
+
 /// Resolve the path to a partial file.
 ///
 /// Follows Pandoc/doctemplates path resolution rules:
@@ -221,4 +371,87 @@ mod tests {
             Some("content b".to_string())
         );
     }
+
+    /// Counts how often partials are parsed.
+    #[derive(Default)]
+    struct CountingResolver {
+        partials: MemoryResolver,
+        parses: std::sync::atomic::AtomicUsize,
+    }
+
+    impl PartialResolver for CountingResolver {
+        fn get_partial(&self, name: &str, base_path: &Path) -> Option<String> {
+            self.partials.get_partial(name, base_path)
+        }
+
+        fn parse_partial(
+            &self,
+            source: &str,
+            file_id: FileId,
+        ) -> TemplateResult<Vec<TemplateNode>> {
+            self.parses
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Template::parse_with_file_id(source, file_id).map(|template| template.nodes)
+        }
+    }
+
+    #[test]
+    fn test_caching_resolver() {
+        let mut counting = CountingResolver::default();
+        counting.partials.add("header", "<h1>$title$</h1>");
+        counting.partials.add("footer", "$header()$<footer/>");
+        let resolver = CachingResolver::new(counting);
+
+        let mut ctx = crate::TemplateContext::new();
+        ctx.insert("title", crate::TemplateValue::String("Hello".to_string()));
+        for _ in 0..3 {
+            let template = Template::compile_with_resolver(
+                "$header()$ $footer()$",
+                Path::new("page.html"),
+                &resolver,
+                0,
+            )
+            .unwrap();
+            assert_eq!(
+                template.render(&ctx).unwrap(),
+                "<h1>Hello</h1> <h1>Hello</h1><footer/>"
+            );
+        }
+
+        // Each distinct partial is parsed once
+        assert_eq!(resolver.len(), 2);
+        let parses = &resolver.inner().parses;
+        assert_eq!(parses.load(std::sync::atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_caching_resolver_file_ids() {
+        let resolver = CachingResolver::new(MemoryResolver::with_partials([("p", "$x$")]));
+        let mut source_context = quarto_source_map::SourceContext::new();
+        let mut file_ids = Vec::new();
+        for _ in 0..2 {
+            let template = Template::compile_with_resolver_and_context(
+                "$p()$",
+                Path::new("main.html"),
+                &resolver,
+                0,
+                &mut source_context,
+            )
+            .unwrap();
+            let TemplateNode::Partial(partial) = &template.nodes[0] else {
+                panic!("expected a partial");
+            };
+            let TemplateNode::Variable(var) = &partial.resolved.as_ref().unwrap()[0] else {
+                panic!("expected a variable");
+            };
+            let SourceInfo::Original { file_id, .. } = var.source_info else {
+                panic!("expected an original location");
+            };
+            file_ids.push(file_id);
+        }
+
+        // The cached partial is attributed to each compilation's own file
+        assert_ne!(file_ids[0], file_ids[1]);
+        assert_eq!(source_context.get_file(file_ids[1]).unwrap().path, "p.html");
+    }
 }
@@ -162,3 +162,36 @@ fn test_variable_final_newline_stripped() {
     // Trailing newline should be stripped from the value
     assert_eq!(result, "Value: test!");
 }
+
+/// Test multiline elseif chain: the first truthy branch is taken.
+///
+/// Template:
+/// ```
+/// $if(missing)$
+/// missing
+/// $elseif(also-missing)$
+/// also missing
+/// $elseif(title)$
+/// title: $title$
+/// $else$
+/// none
+/// $endif$
+/// ```
+#[test]
+fn test_elseif_chain_matches_pandoc() {
+    let template = load_template("07-elseif-chain.template");
+    let result = template.render(&test_context()).unwrap();
+
+    assert_eq!(result, "title: Hello World\n");
+}
+
+/// Test applied partial with separator and pipe.
+///
+/// Template: `$items:08-item()[, ]/uppercase$`, partial: `<$it$>`
+#[test]
+fn test_partial_with_pipe_matches_pandoc() {
+    let template = load_template("08-partial-pipes.template");
+    let result = template.render(&test_context()).unwrap();
+
+    assert_eq!(result, "<ONE>, <TWO>, <THREE>\n");
+}
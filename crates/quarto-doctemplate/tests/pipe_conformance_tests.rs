@@ -0,0 +1,102 @@
+/*
+ * pipe_conformance_tests.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Conformance tests for pipes, modeled on the pipe examples in Pandoc's
+ * doctemplates documentation and test suite. Each case is a template and the
+ * output doctemplates produces for it against `context()`.
+ */
+
+use quarto_doctemplate::{Template, TemplateContext, TemplateValue};
+use std::collections::HashMap;
+
+fn text(s: &str) -> TemplateValue {
+    TemplateValue::String(s.to_string())
+}
+
+fn context() -> TemplateContext {
+    let mut employee = HashMap::new();
+    employee.insert("name".to_string(), text("Sam"));
+    employee.insert("salary".to_string(), text("30000"));
+    employee.insert("title".to_string(), text("manager"));
+
+    let mut ctx = TemplateContext::new();
+    ctx.insert("title", text("Hello World"));
+    ctx.insert("name", text("Sam"));
+    ctx.insert("num", text("3"));
+    ctx.insert("lines", text("one\ntwo\n\n"));
+    ctx.insert(
+        "names",
+        TemplateValue::List(vec![text("Sam"), text("Sue"), text("Pat")]),
+    );
+    ctx.insert(
+        "indices",
+        TemplateValue::List(vec![text("1"), text("4"), text("9"), text("28")]),
+    );
+    ctx.insert("employee", TemplateValue::Map(employee));
+    ctx
+}
+
+const CASES: &[(&str, &str)] = &[
+    // Text pipes
+    ("$title/uppercase$", "HELLO WORLD"),
+    ("$title/lowercase$", "hello world"),
+    ("$title/uppercase/lowercase$", "hello world"),
+    ("$title/length$", "11"),
+    ("$title/reverse$", "dlroW olleH"),
+    ("[$lines/chomp$]", "[one\ntwo]"),
+    ("$name/nowrap$", "Sam"),
+    // Numbering
+    ("$num/alpha$", "c"),
+    ("$num/alpha/uppercase$", "C"),
+    ("$num/roman$", "iii"),
+    ("$indices/roman[, ]$", "i, iv, ix, xxviii"),
+    ("$indices/alpha[ ]$", "a d i b"),
+    ("$name/roman$", "Sam"),
+    // Array pipes
+    ("$names/first$", "Sam"),
+    ("$names/last$", "Pat"),
+    ("$names/rest[, ]$", "Sue, Pat"),
+    ("$names/allbutlast[, ]$", "Sam, Sue"),
+    ("$names/reverse[, ]$", "Pat, Sue, Sam"),
+    ("$names/length$", "3"),
+    ("$names/uppercase[, ]$", "SAM, SUE, PAT"),
+    ("$names/rest/first/uppercase$", "SUE"),
+    ("$name/first$", "Sam"),
+    // Map pipes
+    ("$employee/length$", "3"),
+    ("$employee/pairs/length$", "3"),
+    ("$employee.name/uppercase$", "SAM"),
+    // Alignment
+    ("$name/left 5 \"|\" \"|\"$", "|Sam  |"),
+    ("$name/right 5 \"|\" \"|\"$", "|  Sam|"),
+    ("$name/center 7 \"[\" \"]\"$", "[  Sam  ]"),
+    ("$name/left 5 \"\" \"\"$|", "Sam  |"),
+    ("$names/last/right 4 \"\" \"\"$", " Pat"),
+];
+
+#[test]
+fn test_pipe_conformance() {
+    let ctx = context();
+    let failures: Vec<String> = CASES
+        .iter()
+        .filter_map(|(source, expected)| {
+            let template = match Template::compile(source) {
+                Ok(template) => template,
+                Err(err) => return Some(format!("{source}: failed to parse: {err:?}")),
+            };
+            match template.render(&ctx) {
+                Ok(output) if output == *expected => None,
+                Ok(output) => Some(format!("{source}: expected {expected:?}, got {output:?}")),
+                Err(err) => Some(format!("{source}: failed to render: {err:?}")),
+            }
+        })
+        .collect();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn test_pipes_in_loop_body() {
+    let template = Template::compile("$for(names)$$names/lowercase$$sep$ $endfor$").unwrap();
+    assert_eq!(template.render(&context()).unwrap(), "sam sue pat");
+}
@@ -4,7 +4,7 @@
 //! guidelines directly in the API, making it easy to construct well-structured error messages.
 
 use crate::diagnostic::{
-    DetailItem, DetailKind, DiagnosticKind, DiagnosticMessage, MessageContent,
+    DetailItem, DetailKind, DiagnosticKind, DiagnosticMessage, Fix, MessageContent,
 };
 
 /// Builder for creating diagnostic messages following tidyverse guidelines.
@@ -56,6 +56,9 @@ pub struct DiagnosticMessageBuilder {
 
     /// Source location for this diagnostic
     location: Option<quarto_source_map::SourceInfo>,
+
+    /// Suggested fixes
+    fixes: Vec<Fix>,
 }
 
 impl DiagnosticMessageBuilder {
@@ -72,6 +75,7 @@ impl DiagnosticMessageBuilder {
             details: Vec::new(),
             hints: Vec::new(),
             location: None,
+            fixes: Vec::new(),
         }
     }
 
@@ -384,6 +388,27 @@ impl DiagnosticMessageBuilder {
         self
     }
 
+    /// Add a suggested fix.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use quarto_error_reporting::{DiagnosticMessageBuilder, Fix, FixEdit};
+    /// use quarto_source_map::{FileId, SourceInfo};
+    ///
+    /// let end = SourceInfo::original(FileId(0), 12, 12);
+    /// let error = DiagnosticMessageBuilder::error("Unclosed Star Emphasis")
+    ///     .with_location(end.clone())
+    ///     .add_fix(Fix::new("Insert closing `*`", vec![FixEdit::new(end, "*")]))
+    ///     .build();
+    ///
+    /// assert_eq!(error.fixes.len(), 1);
+    /// ```
+    pub fn add_fix(mut self, fix: Fix) -> Self {
+        self.fixes.push(fix);
+        self
+    }
+
     /// Build the diagnostic message.
     ///
     /// This consumes the builder and returns the constructed `DiagnosticMessage`.
@@ -408,6 +433,7 @@ impl DiagnosticMessageBuilder {
             details: self.details,
             hints: self.hints,
             location: self.location,
+            fixes: self.fixes,
         }
     }
 
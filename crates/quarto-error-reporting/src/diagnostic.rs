@@ -119,6 +119,63 @@ pub struct DetailItem {
     pub location: Option<quarto_source_map::SourceInfo>,
 }
 
+/// A text replacement made by a [`Fix`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixEdit {
+    /// The source text to replace (empty to insert at its start)
+    pub location: quarto_source_map::SourceInfo,
+    /// The replacement text
+    pub new_text: String,
+}
+
+impl FixEdit {
+    /// Create an edit replacing the text at `location`.
+    pub fn new(location: quarto_source_map::SourceInfo, new_text: impl Into<String>) -> Self {
+        Self {
+            location,
+            new_text: new_text.into(),
+        }
+    }
+
+    /// Create an edit inserting text at the start of `location`.
+    pub fn insert_before(
+        location: &quarto_source_map::SourceInfo,
+        text: impl Into<String>,
+    ) -> Self {
+        let point = match location {
+            quarto_source_map::SourceInfo::Original {
+                file_id,
+                start_offset,
+                ..
+            } => quarto_source_map::SourceInfo::original(*file_id, *start_offset, *start_offset),
+            _ => quarto_source_map::SourceInfo::substring(location.clone(), 0, 0),
+        };
+        Self::new(point, text)
+    }
+}
+
+/// A suggested fix for a diagnostic: edits that resolve it.
+///
+/// Fixes are offered by editors as quick fixes and applied by tools such as
+/// `qmd-syntax-helper`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fix {
+    /// Short description of the fix (e.g., "Insert closing `*`")
+    pub title: String,
+    /// The edits, which must not overlap
+    pub edits: Vec<FixEdit>,
+}
+
+impl Fix {
+    /// Create a fix from its edits.
+    pub fn new(title: impl Into<String>, edits: Vec<FixEdit>) -> Self {
+        Self {
+            title: title.into(),
+            edits,
+        }
+    }
+}
+
 /// A diagnostic message following tidyverse-style structure.
 ///
 /// Structure:
@@ -128,6 +185,7 @@ pub struct DetailItem {
 /// 4. **Problem**: What went wrong (the "must" or "can't" statement)
 /// 5. **Details**: Specific information (bulleted, max 5 per tidyverse)
 /// 6. **Hints**: Optional guidance for fixing (ends with ?)
+/// 7. **Fixes**: Optional edits that resolve the problem
 ///
 /// # Example
 ///
@@ -184,6 +242,10 @@ pub struct DiagnosticMessage {
     /// mapped back through multiple processing steps to the original source file.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub location: Option<quarto_source_map::SourceInfo>,
+
+    /// Suggested fixes for the problem
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fixes: Vec<Fix>,
 }
 
 impl DiagnosticMessage {
@@ -224,6 +286,7 @@ impl DiagnosticMessage {
             details: Vec::new(),
             hints: Vec::new(),
             location: None,
+            fixes: Vec::new(),
         }
     }
 
@@ -541,6 +604,10 @@ impl DiagnosticMessage {
             obj["location"] = json!(location); // quarto-source-map::SourceInfo is Serialize
         }
 
+        if !self.fixes.is_empty() {
+            obj["fixes"] = json!(self.fixes);
+        }
+
         obj
     }
 
@@ -1104,6 +1171,38 @@ mod tests {
         assert!(json.get("location").is_none());
     }
 
+    #[test]
+    fn test_fixes_in_to_json() {
+        use crate::builder::DiagnosticMessageBuilder;
+
+        let location =
+            quarto_source_map::SourceInfo::original(quarto_source_map::FileId(0), 100, 110);
+
+        let msg = DiagnosticMessageBuilder::error("Unclosed Star Emphasis")
+            .with_location(location.clone())
+            .add_fix(Fix::new(
+                "Insert closing `*`",
+                vec![FixEdit::insert_before(&location, "*")],
+            ))
+            .build();
+
+        let json = msg.to_json();
+        let fix = &json["fixes"][0];
+        assert_eq!(fix["title"], "Insert closing `*`");
+        assert_eq!(fix["edits"][0]["new_text"], "*");
+        let original = &fix["edits"][0]["location"]["Original"];
+        assert_eq!(original["start_offset"], 100);
+        assert_eq!(original["end_offset"], 100);
+
+        // No fixes field without fixes
+        assert!(
+            DiagnosticMessage::error("No fix")
+                .to_json()
+                .get("fixes")
+                .is_none()
+        );
+    }
+
     #[test]
     fn test_text_render_options_disable_hyperlinks() {
         use crate::builder::DiagnosticMessageBuilder;
@@ -65,5 +65,6 @@ pub mod macros;
 pub use builder::DiagnosticMessageBuilder;
 pub use catalog::{ERROR_CATALOG, ErrorCodeInfo, get_docs_url, get_error_info, get_subsystem};
 pub use diagnostic::{
-    DetailItem, DetailKind, DiagnosticKind, DiagnosticMessage, MessageContent, TextRenderOptions,
+    DetailItem, DetailKind, DiagnosticKind, DiagnosticMessage, Fix, FixEdit, MessageContent,
+    TextRenderOptions,
 };
@@ -0,0 +1,576 @@
+//! Token-based authentication and per-project authorization.
+//!
+//! When any tokens are configured, every REST and WebSocket request must
+//! present one, either as an `Authorization: Bearer <token>` header or (for
+//! browser WebSocket clients, which cannot set headers) as a `?token=` query
+//! parameter. A token's [`Role`] is looked up by token and project: a token
+//! can have a role on every project, roles on individual projects, or both,
+//! so one token file can serve a multi-project hub.
+//!
+//! Roles are enforced in two places:
+//! - REST handlers check the role attached to the request by [`require_auth`]
+//! - WebSocket sync connections for read-only peers pass incoming messages
+//!   through [`filter_read_only_message`], which strips document changes so
+//!   the peer can receive updates but never write them
+//!
+//! Requests also carry the [`Principal`] they authenticated as, which is
+//! who comments are attributed to: a token's `name`, never an author the
+//! client supplies.
+//!
+//! With no tokens configured, authentication is disabled and every request
+//! is treated as [`Role::Admin`] from [`Principal::Local`]. This keeps the
+//! default local workflow (`quarto hub` on 127.0.0.1) unchanged.
+//!
+//! Tokens are never taken from the command line, where they would show up
+//! in the process list: they come from `--auth-file` or [`AUTH_TOKEN_ENV`].
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use axum::{
+    Json,
+    extract::{Request, State},
+    http::{StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::context::SharedContext;
+use crate::error::{Error, Result};
+
+/// Environment variable holding a token with admin access to every project.
+pub const AUTH_TOKEN_ENV: &str = "QUARTO_HUB_AUTH_TOKEN";
+
+/// Access level granted by a token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    /// Can read documents and receive sync updates, but not change anything.
+    ReadOnly,
+    /// Can read and edit documents.
+    Editor,
+    /// Full access, including hub administration.
+    Admin,
+}
+
+impl Role {
+    /// Whether this role may modify documents.
+    pub fn can_write(self) -> bool {
+        self >= Role::Editor
+    }
+
+    /// Whether this role may perform administrative operations.
+    pub fn can_admin(self) -> bool {
+        self >= Role::Admin
+    }
+}
+
+/// Who a request authenticated as.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Principal {
+    /// Authentication is disabled, so requests come from the local user.
+    Local,
+    /// A configured token, with its `name` if it has one.
+    Token(Option<String>),
+}
+
+impl Principal {
+    /// The author to record for a comment action, given the author the
+    /// client asked for.
+    ///
+    /// A named token always authors as its name, and asking for another
+    /// author is an error; tokens without a name can't author comments.
+    /// Without authentication the local user names themselves.
+    pub fn author(&self, requested: Option<&str>) -> Result<String> {
+        match self {
+            Principal::Token(Some(name)) => match requested {
+                Some(requested) if requested != name => Err(Error::Author(format!(
+                    "`{}` does not match the token's name `{}`",
+                    requested, name
+                ))),
+                _ => Ok(name.clone()),
+            },
+            Principal::Token(None) => Err(Error::Author(
+                "the token has no name to author as".to_string(),
+            )),
+            Principal::Local => requested
+                .filter(|author| !author.is_empty())
+                .map(String::from)
+                .ok_or_else(|| Error::Author("missing author".to_string())),
+        }
+    }
+}
+
+/// A single token and the roles it grants.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenEntry {
+    /// Human-readable label for the token holder (used in logs only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// The bearer token itself
+    pub token: String,
+
+    /// Role granted on every project not listed in `projects`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+
+    /// Roles granted on individual projects, by project name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub projects: BTreeMap<String, Role>,
+}
+
+impl TokenEntry {
+    /// The role this token grants on `project`, if any.
+    pub fn role_on(&self, project: &str) -> Option<Role> {
+        self.projects.get(project).copied().or(self.role)
+    }
+}
+
+/// Authentication configuration for the hub.
+///
+/// Loaded from a JSON file passed with `--auth-file`. Projects are named as
+/// in the hub's routes (`/projects/{name}`); a single-project hub is named
+/// after its directory.
+///
+/// ```json
+/// {
+///   "tokens": [
+///     { "name": "alice", "token": "s3cret", "role": "editor" },
+///     { "name": "bob", "token": "b0b", "projects": { "thesis": "admin", "blog": "read-only" } },
+///     { "name": "ci", "token": "r3ad", "role": "read-only", "projects": { "blog": "editor" } }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub tokens: Vec<TokenEntry>,
+}
+
+impl AuthConfig {
+    /// Load the configuration from a JSON file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| Error::AuthConfig(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Load the token file, if any, plus the admin token from
+    /// [`AUTH_TOKEN_ENV`] when it is set.
+    pub fn load_with_env(path: Option<&Path>) -> Result<Self> {
+        let mut config = match path {
+            Some(path) => Self::load(path)?,
+            None => Self::default(),
+        };
+        if let Ok(token) = std::env::var(AUTH_TOKEN_ENV)
+            && !token.is_empty()
+        {
+            config.add_token(token, Role::Admin);
+        }
+        Ok(config)
+    }
+
+    /// Add a token with the given role on every project.
+    pub fn add_token(&mut self, token: impl Into<String>, role: Role) {
+        self.tokens.push(TokenEntry {
+            name: None,
+            token: token.into(),
+            role: Some(role),
+            projects: BTreeMap::new(),
+        });
+    }
+
+    /// Whether authentication is required (any tokens configured).
+    pub fn is_enabled(&self) -> bool {
+        !self.tokens.is_empty()
+    }
+
+    /// Resolve the role for a presented token on `project`.
+    ///
+    /// Returns `Some(Role::Admin)` for any input when authentication is
+    /// disabled, and `None` when the token is missing, unknown, or grants
+    /// no role on the project.
+    pub fn authenticate(&self, token: Option<&str>, project: &str) -> Option<Role> {
+        self.authenticate_as(token, project).map(|(role, _)| role)
+    }
+
+    /// Resolve the role and the principal for a presented token on
+    /// `project`, as in [`authenticate`](Self::authenticate).
+    pub fn authenticate_as(&self, token: Option<&str>, project: &str) -> Option<(Role, Principal)> {
+        if !self.is_enabled() {
+            return Some((Role::Admin, Principal::Local));
+        }
+        let presented = Sha256::digest(token?.as_bytes());
+        // Compare digests rather than raw strings so the comparison time
+        // doesn't depend on how much of the token matched.
+        self.tokens
+            .iter()
+            .filter(|entry| Sha256::digest(entry.token.as_bytes()) == presented)
+            .filter_map(|entry| {
+                let role = entry.role_on(project)?;
+                debug!(name = ?entry.name, ?role, project, "Authenticated request");
+                Some((role, Principal::Token(entry.name.clone())))
+            })
+            .max_by_key(|(role, _)| *role)
+    }
+}
+
+/// Extract the token from the `Authorization` header or `token` query parameter.
+pub(crate) fn request_token(request: &Request) -> Option<String> {
+    if let Some(value) = request.headers().get(header::AUTHORIZATION)
+        && let Ok(value) = value.to_str()
+        && let Some(token) = value.strip_prefix("Bearer ")
+    {
+        return Some(token.trim().to_string());
+    }
+
+    request.uri().query().and_then(|query| {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("token=").map(percent_decode))
+    })
+}
+
+/// Minimal percent-decoding for query parameter values.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                match std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            b'+' => out.push(b' '),
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Axum middleware that authenticates every request.
+///
+/// On success the caller's [`Role`] and [`Principal`] are inserted into the
+/// request extensions so handlers can take them with `Extension<Role>` and
+/// `Extension<Principal>`.
+pub async fn require_auth(
+    State(ctx): State<SharedContext>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let token = request_token(&request);
+    match ctx.authenticate_as(token.as_deref()) {
+        Some((role, principal)) => {
+            request.extensions_mut().insert(role);
+            request.extensions_mut().insert(principal);
+            next.run(request).await
+        }
+        None => {
+            warn!(uri = %request.uri().path(), "Rejected unauthenticated request");
+            (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Bearer")],
+                Json(serde_json::json!({ "error": "Missing or invalid bearer token" })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Rewrite an incoming sync message from a read-only peer.
+///
+/// `request` and `sync` messages carry an automerge sync message whose
+/// `changes` would be applied to our documents. Those changes are removed
+/// while heads, needs and haves are kept, so the peer still receives
+/// updates. All other message types pass through unchanged.
+///
+/// Returns `None` if the message should be dropped because it can't be
+/// decoded well enough to be made safe.
+pub fn filter_read_only_message(message: Vec<u8>) -> Option<Vec<u8>> {
+    match strip_changes(&message) {
+        Ok(Some(rewritten)) => {
+            debug!("Stripped changes from read-only peer sync message");
+            Some(rewritten)
+        }
+        Ok(None) => Some(message),
+        Err(e) => {
+            warn!(error = %e, "Dropping undecodable message from read-only peer");
+            None
+        }
+    }
+}
+
+/// Returns `Ok(None)` when the message needs no rewriting.
+fn strip_changes(message: &[u8]) -> std::result::Result<Option<Vec<u8>>, String> {
+    let mut decoder = minicbor::Decoder::new(message);
+    let len = decoder
+        .map()
+        .map_err(|e| e.to_string())?
+        .ok_or("indefinite-length message map")?;
+
+    let mut message_type = None;
+    let mut data_span = None;
+    for _ in 0..len {
+        let key = decoder.str().map_err(|e| e.to_string())?;
+        match key {
+            "type" => message_type = Some(decoder.str().map_err(|e| e.to_string())?),
+            "data" => {
+                let start = decoder.position();
+                decoder.bytes().map_err(|e| e.to_string())?;
+                data_span = Some((start, decoder.position()));
+            }
+            _ => decoder.skip().map_err(|e| e.to_string())?,
+        }
+    }
+
+    if !matches!(message_type, Some("request" | "sync")) {
+        return Ok(None);
+    }
+    let (start, end) = data_span.ok_or("sync message without data")?;
+    let data = minicbor::Decoder::new(&message[start..end])
+        .bytes()
+        .map_err(|e| e.to_string())?;
+
+    let mut sync_message = automerge::sync::Message::decode(data).map_err(|e| e.to_string())?;
+    if sync_message.changes.is_empty() {
+        return Ok(None);
+    }
+    sync_message.changes = automerge::sync::ChunkList::empty();
+
+    let mut encoded_data = minicbor::Encoder::new(Vec::new());
+    encoded_data
+        .bytes(&sync_message.encode())
+        .map_err(|e| e.to_string())?;
+
+    let mut rewritten = Vec::with_capacity(message.len());
+    rewritten.extend_from_slice(&message[..start]);
+    rewritten.extend_from_slice(&encoded_data.into_writer());
+    rewritten.extend_from_slice(&message[end..]);
+    Ok(Some(rewritten))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use automerge::{AutoCommit, ROOT, ReadDoc, sync::SyncDoc, transaction::Transactable};
+
+    fn config() -> AuthConfig {
+        let mut config = AuthConfig::default();
+        config.add_token("reader", Role::ReadOnly);
+        config.add_token("writer", Role::Editor);
+        config
+    }
+
+    fn wire_message(message_type: &str, data: &[u8]) -> Vec<u8> {
+        let mut encoder = minicbor::Encoder::new(Vec::new());
+        encoder.map(5).unwrap();
+        encoder.str("type").unwrap().str(message_type).unwrap();
+        encoder.str("documentId").unwrap().str("doc").unwrap();
+        encoder.str("senderId").unwrap().str("peer-a").unwrap();
+        encoder.str("targetId").unwrap().str("peer-b").unwrap();
+        encoder.str("data").unwrap().bytes(data).unwrap();
+        encoder.into_writer()
+    }
+
+    fn wire_data(message: &[u8]) -> Vec<u8> {
+        let mut decoder = minicbor::Decoder::new(message);
+        let len = decoder.map().unwrap().unwrap();
+        for _ in 0..len {
+            if decoder.str().unwrap() == "data" {
+                return decoder.bytes().unwrap().to_vec();
+            }
+            decoder.skip().unwrap();
+        }
+        panic!("no data field");
+    }
+
+    #[test]
+    fn test_role_ordering() {
+        assert!(!Role::ReadOnly.can_write());
+        assert!(Role::Editor.can_write());
+        assert!(!Role::Editor.can_admin());
+        assert!(Role::Admin.can_write() && Role::Admin.can_admin());
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let config = config();
+        assert_eq!(
+            config.authenticate(Some("reader"), "blog"),
+            Some(Role::ReadOnly)
+        );
+        assert_eq!(
+            config.authenticate(Some("writer"), "blog"),
+            Some(Role::Editor)
+        );
+        assert_eq!(config.authenticate(Some("nope"), "blog"), None);
+        assert_eq!(config.authenticate(None, "blog"), None);
+    }
+
+    #[test]
+    fn test_authenticate_per_project() {
+        let config: AuthConfig = serde_json::from_str(
+            r#"{"tokens": [
+                {"token": "bob", "projects": {"thesis": "admin", "blog": "read-only"}},
+                {"token": "ci", "role": "read-only", "projects": {"blog": "editor"}}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.authenticate(Some("bob"), "thesis"),
+            Some(Role::Admin)
+        );
+        assert_eq!(
+            config.authenticate(Some("bob"), "blog"),
+            Some(Role::ReadOnly)
+        );
+        assert_eq!(config.authenticate(Some("bob"), "notes"), None);
+        assert_eq!(config.authenticate(Some("ci"), "blog"), Some(Role::Editor));
+        assert_eq!(
+            config.authenticate(Some("ci"), "notes"),
+            Some(Role::ReadOnly)
+        );
+    }
+
+    #[test]
+    fn test_disabled_auth_allows_everything() {
+        let config = AuthConfig::default();
+        assert!(!config.is_enabled());
+        assert_eq!(config.authenticate(None, "blog"), Some(Role::Admin));
+    }
+
+    #[test]
+    fn test_authenticate_as_named_token() {
+        let config: AuthConfig = serde_json::from_str(
+            r#"{"tokens": [{"name": "alice", "token": "a", "role": "editor"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.authenticate_as(Some("a"), "blog"),
+            Some((Role::Editor, Principal::Token(Some("alice".to_string()))))
+        );
+        assert_eq!(config.authenticate_as(Some("writer"), "blog"), None);
+        assert_eq!(
+            AuthConfig::default().authenticate_as(None, "blog"),
+            Some((Role::Admin, Principal::Local))
+        );
+    }
+
+    #[test]
+    fn test_principal_author() {
+        let alice = Principal::Token(Some("alice".to_string()));
+        assert_eq!(alice.author(None).unwrap(), "alice");
+        assert_eq!(alice.author(Some("alice")).unwrap(), "alice");
+        // A client can't comment as someone else
+        assert!(matches!(alice.author(Some("bob")), Err(Error::Author(_))));
+        assert!(Principal::Token(None).author(Some("bob")).is_err());
+
+        assert_eq!(Principal::Local.author(Some("bob")).unwrap(), "bob");
+        assert!(Principal::Local.author(None).is_err());
+        assert!(Principal::Local.author(Some("")).is_err());
+    }
+
+    #[test]
+    fn test_parse_auth_file() {
+        let config: AuthConfig = serde_json::from_str(
+            r#"{"tokens": [{"name": "ci", "token": "t", "role": "read-only"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.tokens[0].role, Some(Role::ReadOnly));
+        assert!(config.tokens[0].projects.is_empty());
+        assert_eq!(config.tokens[0].name.as_deref(), Some("ci"));
+    }
+
+    #[test]
+    fn test_request_token_sources() {
+        let request = Request::builder()
+            .uri("/api/files")
+            .header(header::AUTHORIZATION, "Bearer abc")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        assert_eq!(request_token(&request).as_deref(), Some("abc"));
+
+        let request = Request::builder()
+            .uri("/ws?foo=1&token=a%2Fb")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        assert_eq!(request_token(&request).as_deref(), Some("a/b"));
+
+        let request = Request::builder()
+            .uri("/ws")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        assert_eq!(request_token(&request), None);
+    }
+
+    #[test]
+    fn test_read_only_sync_changes_are_stripped() {
+        // A peer with a local edit generates a sync message carrying changes.
+        let mut peer = AutoCommit::new();
+        peer.put(ROOT, "title", "hacked").unwrap();
+        let mut state = automerge::sync::State::new();
+        let sync_message = peer.sync().generate_sync_message(&mut state).unwrap();
+        let heads = sync_message.heads.clone();
+        let original = wire_message("sync", &sync_message.encode());
+
+        // First message only advertises heads; force changes by simulating
+        // a second round where the peer knows we have nothing.
+        let mut server = AutoCommit::new();
+        let mut server_state = automerge::sync::State::new();
+        server
+            .sync()
+            .receive_sync_message(
+                &mut server_state,
+                automerge::sync::Message::decode(&wire_data(&original)).unwrap(),
+            )
+            .unwrap();
+        let reply = server
+            .sync()
+            .generate_sync_message(&mut server_state)
+            .unwrap();
+        peer.sync().receive_sync_message(&mut state, reply).unwrap();
+        let with_changes = peer.sync().generate_sync_message(&mut state).unwrap();
+        assert!(!with_changes.changes.is_empty());
+
+        let wire = wire_message("sync", &with_changes.encode());
+        let filtered = filter_read_only_message(wire.clone()).unwrap();
+        assert_ne!(filtered, wire);
+
+        let decoded = automerge::sync::Message::decode(&wire_data(&filtered)).unwrap();
+        assert!(decoded.changes.is_empty());
+        assert_eq!(decoded.heads, heads);
+
+        // Applying the filtered message leaves the server document untouched.
+        server
+            .sync()
+            .receive_sync_message(&mut server_state, decoded)
+            .unwrap();
+        assert!(server.get(ROOT, "title").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_read_only_other_messages_pass_through() {
+        let message = wire_message("ephemeral", b"presence");
+        assert_eq!(filter_read_only_message(message.clone()), Some(message));
+    }
+
+    #[test]
+    fn test_read_only_garbage_is_dropped() {
+        assert_eq!(filter_read_only_message(vec![0xff, 0x00]), None);
+    }
+}
@@ -0,0 +1,207 @@
+//! Resumable backfill of document changes.
+//!
+//! A client that reconnects after being offline presents the heads it
+//! already has, and the hub streams only the changes it is missing
+//! (`GET /api/documents/{id}/changes?since=<heads>`). This lets a client
+//! catch up on a long-lived document in one bounded download before
+//! resuming live sync over the WebSocket, instead of going through many
+//! sync-protocol round trips.
+//!
+//! The response body is a sequence of frames, each a big-endian `u32`
+//! length followed by the raw bytes of one change, in causal order, so the
+//! client can apply changes as they arrive. Changes are read from the
+//! document in batches and handed to the connection through a small bounded
+//! channel: a slow client holds up the reader instead of making the hub
+//! buffer the whole history.
+
+use std::str::FromStr;
+
+use automerge::{Automerge, ChangeHash, ReadDoc};
+use futures::Stream;
+use samod::DocHandle;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use crate::error::{Error, Result};
+
+/// Number of changes read from the document per batch.
+pub const BACKFILL_BATCH_SIZE: usize = 64;
+
+/// Number of batches buffered ahead of the connection.
+const BACKFILL_BUFFER: usize = 4;
+
+/// The changes a client is missing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillPlan {
+    /// Current heads of the document
+    pub heads: Vec<ChangeHash>,
+
+    /// Heads presented by the client that the hub does not have. The client
+    /// holds changes the hub has not seen and should push them over sync.
+    pub unknown: Vec<ChangeHash>,
+
+    /// Hashes of the changes to send, in causal order
+    pub changes: Vec<ChangeHash>,
+}
+
+/// Parse the heads presented by a client.
+pub fn parse_since(heads: &[String]) -> Result<Vec<ChangeHash>> {
+    heads
+        .iter()
+        .map(|head| {
+            ChangeHash::from_str(head)
+                .map_err(|_| Error::Sync(format!("invalid change hash: {}", head)))
+        })
+        .collect()
+}
+
+/// Work out which changes a client with heads `since` is missing.
+///
+/// Heads the hub does not know are reported in [`BackfillPlan::unknown`] and
+/// otherwise ignored, so the client receives everything that is not an
+/// ancestor of the heads both sides share.
+pub fn plan(doc: &Automerge, since: &[ChangeHash]) -> BackfillPlan {
+    let (known, unknown): (Vec<_>, Vec<_>) = since
+        .iter()
+        .copied()
+        .partition(|hash| doc.get_change_by_hash(hash).is_some());
+    BackfillPlan {
+        heads: doc.get_heads(),
+        unknown,
+        changes: doc.get_changes(&known).iter().map(|c| c.hash()).collect(),
+    }
+}
+
+/// Append one change to a backfill body.
+pub fn encode_frame(out: &mut Vec<u8>, change: &[u8]) {
+    out.extend_from_slice(&(change.len() as u32).to_be_bytes());
+    out.extend_from_slice(change);
+}
+
+/// Split a backfill body into the raw bytes of its changes.
+pub fn decode_frames(mut body: &[u8]) -> Result<Vec<&[u8]>> {
+    let mut frames = Vec::new();
+    while !body.is_empty() {
+        let Some((len, rest)) = body.split_first_chunk::<4>() else {
+            return Err(Error::Sync("truncated backfill frame header".to_string()));
+        };
+        let len = u32::from_be_bytes(*len) as usize;
+        if rest.len() < len {
+            return Err(Error::Sync("truncated backfill frame".to_string()));
+        }
+        let (frame, rest) = rest.split_at(len);
+        frames.push(frame);
+        body = rest;
+    }
+    Ok(frames)
+}
+
+/// Stream the given changes of a document as backfill frames.
+///
+/// The stream ends early, and the reader task stops, if the client goes
+/// away.
+pub fn stream_changes(
+    handle: DocHandle,
+    changes: Vec<ChangeHash>,
+) -> impl Stream<Item = std::io::Result<Vec<u8>>> {
+    let (tx, rx) = mpsc::channel(BACKFILL_BUFFER);
+    tokio::spawn(async move {
+        for batch in changes.chunks(BACKFILL_BATCH_SIZE) {
+            let frames = handle.with_document(|doc| {
+                let mut out = Vec::new();
+                for hash in batch {
+                    if let Some(change) = doc.get_change_by_hash(hash) {
+                        encode_frame(&mut out, change.raw_bytes());
+                    }
+                }
+                out
+            });
+            if tx.send(Ok(frames)).await.is_err() {
+                debug!(document_id = %handle.document_id(), "Backfill client went away");
+                return;
+            }
+        }
+    });
+    futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|item| (item, rx))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use automerge::transaction::Transactable;
+    use automerge::{ObjType, ROOT};
+
+    fn doc_with_edits(n: usize) -> Automerge {
+        let mut doc = Automerge::new();
+        let mut tx = doc.transaction();
+        tx.put_object(ROOT, "text", ObjType::Text).unwrap();
+        tx.commit();
+        let text = doc.get(ROOT, "text").unwrap().unwrap().1;
+        for i in 0..n {
+            let mut tx = doc.transaction();
+            tx.splice_text(&text, i, 0, "x").unwrap();
+            tx.commit();
+        }
+        doc
+    }
+
+    #[test]
+    fn test_plan_sends_only_missing_changes() {
+        let client = doc_with_edits(3);
+        let mut server = client.fork();
+        let text = server.get(ROOT, "text").unwrap().unwrap().1;
+        for _ in 0..5 {
+            let mut tx = server.transaction();
+            tx.splice_text(&text, 0, 0, "y").unwrap();
+            tx.commit();
+        }
+
+        let plan = plan(&server, &client.get_heads());
+        assert_eq!(plan.heads, server.get_heads());
+        assert!(plan.unknown.is_empty());
+        assert_eq!(plan.changes.len(), 5);
+
+        // Applying the frames brings the client up to date
+        let mut body = Vec::new();
+        for hash in &plan.changes {
+            encode_frame(
+                &mut body,
+                server.get_change_by_hash(hash).unwrap().raw_bytes(),
+            );
+        }
+        let mut client = client;
+        for frame in decode_frames(&body).unwrap() {
+            client.load_incremental(frame).unwrap();
+        }
+        assert_eq!(client.get_heads(), server.get_heads());
+    }
+
+    #[test]
+    fn test_plan_reports_unknown_heads() {
+        let server = doc_with_edits(2);
+        let mut client = server.fork();
+        let mut tx = client.transaction();
+        tx.put(ROOT, "local", "edit").unwrap();
+        tx.commit();
+
+        let plan = plan(&server, &client.get_heads());
+        assert_eq!(plan.unknown, client.get_heads());
+        // With no shared heads, the whole history is sent
+        assert_eq!(plan.changes.len(), server.get_changes(&[]).len());
+
+        let plan_from_start = super::plan(&server, &[]);
+        assert_eq!(plan_from_start.changes, plan.changes);
+    }
+
+    #[test]
+    fn test_decode_rejects_truncated_body() {
+        let mut body = Vec::new();
+        encode_frame(&mut body, b"abc");
+        assert_eq!(decode_frames(&body).unwrap(), vec![&b"abc"[..]]);
+        assert!(decode_frames(&body[..5]).is_err());
+        assert!(decode_frames(&body[..2]).is_err());
+        assert!(parse_since(&["nothex".to_string()]).is_err());
+    }
+}
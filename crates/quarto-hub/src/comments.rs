@@ -0,0 +1,649 @@
+//! Comment and suggestion threads.
+//!
+//! Threads live in the Automerge document they annotate, so they sync to
+//! every client like any other edit:
+//!
+//! ```text
+//! ROOT
+//! ├── text: Text                  // the document source
+//! └── comments: Map               // thread id -> thread
+//!     └── <id>: Map
+//!         ├── author: String
+//!         ├── createdAt: Int       // milliseconds since the unix epoch
+//!         ├── suggestion: String   // optional replacement for the anchored text
+//!         ├── resolved: Bool
+//!         ├── resolvedBy: String   // optional
+//!         ├── resolvedAt: Int      // optional
+//!         └── messages: List<Map { id, author, body, createdAt }>
+//! ```
+//!
+//! A thread is anchored to a range of the source with a `comment:<id>` mark
+//! on the text object. Automerge moves marks along with concurrent edits, so
+//! the anchor keeps covering the same source (and hence the same AST nodes)
+//! as the document changes. If the anchored text is deleted entirely the
+//! thread is kept but has no anchor.
+//!
+//! Offsets count Unicode code points, the text encoding of the hub's
+//! Automerge documents.
+
+use automerge::{
+    Automerge, ObjId, ObjType, ROOT, ReadDoc, ScalarValue, Value,
+    marks::{ExpandMark, Mark},
+    transaction::{CommitOptions, Transactable},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+use crate::history::now_millis;
+
+/// Key of the comments map at the document root
+pub const COMMENTS_KEY: &str = "comments";
+
+/// Prefix of the marks anchoring threads to the text
+const MARK_PREFIX: &str = "comment:";
+
+/// A range of the document source, in Unicode code points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Anchor {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A single message in a thread.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    pub id: String,
+    pub author: String,
+    pub body: String,
+    pub created_at: i64,
+}
+
+/// A comment or suggestion thread with its current anchor.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentThread {
+    pub id: String,
+
+    /// Current range of the anchored source, or `None` if it was deleted
+    pub anchor: Option<Anchor>,
+
+    /// The anchored source text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<String>,
+
+    /// Proposed replacement for the anchored text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+
+    pub author: String,
+    pub created_at: i64,
+    pub resolved: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<i64>,
+    pub messages: Vec<Comment>,
+}
+
+/// A new thread to create.
+#[derive(Debug, Clone)]
+pub struct NewThread {
+    pub start: usize,
+    pub end: usize,
+    pub author: String,
+    pub body: String,
+    pub suggestion: Option<String>,
+}
+
+fn comments_err(e: automerge::AutomergeError) -> Error {
+    Error::Comments(e.to_string())
+}
+
+/// List the threads of a document, ordered by creation time.
+pub fn list_threads(doc: &Automerge) -> Result<Vec<CommentThread>> {
+    let Some(comments) = comments_obj(doc)? else {
+        return Ok(Vec::new());
+    };
+    let text_obj = text_obj(doc)?;
+    let text = doc.text(&text_obj).map_err(comments_err)?;
+    let marks = doc.marks(&text_obj).map_err(comments_err)?;
+
+    let mut threads = Vec::new();
+    for id in doc.keys(&comments) {
+        let Some((Value::Object(ObjType::Map), thread)) =
+            doc.get(&comments, id.as_str()).map_err(comments_err)?
+        else {
+            continue;
+        };
+        let anchor = anchor_of(&marks, &id);
+        threads.push(CommentThread {
+            quote: anchor.map(|a| text.chars().skip(a.start).take(a.end - a.start).collect()),
+            anchor,
+            suggestion: get_str(doc, &thread, "suggestion")?,
+            author: get_str(doc, &thread, "author")?.unwrap_or_default(),
+            created_at: get_i64(doc, &thread, "createdAt")?.unwrap_or_default(),
+            resolved: matches!(
+                doc.get(&thread, "resolved").map_err(comments_err)?,
+                Some((Value::Scalar(s), _)) if matches!(s.as_ref(), ScalarValue::Boolean(true))
+            ),
+            resolved_by: get_str(doc, &thread, "resolvedBy")?,
+            resolved_at: get_i64(doc, &thread, "resolvedAt")?,
+            messages: messages_of(doc, &thread)?,
+            id,
+        });
+    }
+    threads.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+    Ok(threads)
+}
+
+/// Get a single thread.
+pub fn get_thread(doc: &Automerge, thread_id: &str) -> Result<CommentThread> {
+    list_threads(doc)?
+        .into_iter()
+        .find(|t| t.id == thread_id)
+        .ok_or_else(|| Error::Comments(format!("no such thread: {}", thread_id)))
+}
+
+/// Create a thread anchored to `new.start..new.end`, returning its ID.
+pub fn create_thread(doc: &mut Automerge, new: &NewThread) -> Result<String> {
+    let text_obj = text_obj(doc)?;
+    let length = doc.length(&text_obj);
+    if new.start >= new.end || new.end > length {
+        return Err(Error::Comments(format!(
+            "invalid range {}..{} (document length {})",
+            new.start, new.end, length
+        )));
+    }
+
+    let now = now_millis();
+    let id = new_id(doc, &[&now.to_string(), &new.start.to_string(), &new.body]);
+    let options = CommitOptions::default()
+        .with_message(format!("Add comment {}", id))
+        .with_time(now);
+    doc.transact_with::<_, _, automerge::AutomergeError, _>(
+        |_| options,
+        |tx| {
+            let comments = match tx.get(ROOT, COMMENTS_KEY)? {
+                Some((Value::Object(ObjType::Map), obj)) => obj,
+                _ => tx.put_object(ROOT, COMMENTS_KEY, ObjType::Map)?,
+            };
+            let thread = tx.put_object(&comments, id.as_str(), ObjType::Map)?;
+            tx.put(&thread, "author", new.author.as_str())?;
+            tx.put(&thread, "createdAt", now)?;
+            tx.put(&thread, "resolved", false)?;
+            if let Some(suggestion) = &new.suggestion {
+                tx.put(&thread, "suggestion", suggestion.as_str())?;
+            }
+            let messages = tx.put_object(&thread, "messages", ObjType::List)?;
+            put_message(
+                tx,
+                &messages,
+                0,
+                &format!("{}-0", id),
+                &new.author,
+                &new.body,
+                now,
+            )?;
+            tx.mark(
+                &text_obj,
+                Mark::new(mark_name(&id), true, new.start, new.end),
+                ExpandMark::None,
+            )?;
+            Ok(())
+        },
+    )
+    .map_err(|e| comments_err(e.error))?;
+    Ok(id)
+}
+
+/// Add a reply to a thread, returning the ID of the new message.
+pub fn reply(doc: &mut Automerge, thread_id: &str, author: &str, body: &str) -> Result<String> {
+    let thread = thread_obj(doc, thread_id)?;
+    let messages = match doc.get(&thread, "messages").map_err(comments_err)? {
+        Some((Value::Object(ObjType::List), obj)) => obj,
+        _ => {
+            return Err(Error::Comments(format!(
+                "thread {} has no messages",
+                thread_id
+            )));
+        }
+    };
+    let now = now_millis();
+    let index = doc.length(&messages);
+    let id = new_id(doc, &[thread_id, &now.to_string(), body]);
+    let options = CommitOptions::default()
+        .with_message(format!("Reply to comment {}", thread_id))
+        .with_time(now);
+    doc.transact_with::<_, _, automerge::AutomergeError, _>(
+        |_| options,
+        |tx| put_message(tx, &messages, index, &id, author, body, now),
+    )
+    .map_err(|e| comments_err(e.error))?;
+    Ok(id)
+}
+
+/// Mark a thread resolved (or reopen it), attributing the change to `author`.
+pub fn set_resolved(
+    doc: &mut Automerge,
+    thread_id: &str,
+    resolved: bool,
+    author: &str,
+) -> Result<()> {
+    let thread = thread_obj(doc, thread_id)?;
+    let now = now_millis();
+    let verb = if resolved { "Resolve" } else { "Reopen" };
+    let options = CommitOptions::default()
+        .with_message(format!("{} comment {}", verb, thread_id))
+        .with_time(now);
+    doc.transact_with::<_, _, automerge::AutomergeError, _>(
+        |_| options,
+        |tx| {
+            tx.put(&thread, "resolved", resolved)?;
+            if resolved {
+                tx.put(&thread, "resolvedBy", author)?;
+                tx.put(&thread, "resolvedAt", now)?;
+            } else {
+                tx.delete(&thread, "resolvedBy")?;
+                tx.delete(&thread, "resolvedAt")?;
+            }
+            Ok(())
+        },
+    )
+    .map_err(|e| comments_err(e.error))?;
+    Ok(())
+}
+
+/// Apply a thread's suggestion to the anchored text and resolve the thread.
+pub fn accept_suggestion(doc: &mut Automerge, thread_id: &str, author: &str) -> Result<()> {
+    let thread = get_thread(doc, thread_id)?;
+    let Some(suggestion) = thread.suggestion else {
+        return Err(Error::Comments(format!(
+            "thread {} has no suggestion",
+            thread_id
+        )));
+    };
+    let Some(anchor) = thread.anchor else {
+        return Err(Error::Comments(format!(
+            "the text of thread {} has been deleted",
+            thread_id
+        )));
+    };
+    let text_obj = text_obj(doc)?;
+    let thread_obj = thread_obj(doc, thread_id)?;
+    let now = now_millis();
+    let options = CommitOptions::default()
+        .with_message(format!("Accept suggestion {}", thread_id))
+        .with_time(now);
+    doc.transact_with::<_, _, automerge::AutomergeError, _>(
+        |_| options,
+        |tx| {
+            // Insert before deleting so the replacement stays anchored
+            tx.splice_text(&text_obj, anchor.start, 0, &suggestion)?;
+            let len = suggestion.chars().count();
+            tx.mark(
+                &text_obj,
+                Mark::new(mark_name(thread_id), true, anchor.start, anchor.start + len),
+                ExpandMark::None,
+            )?;
+            tx.splice_text(
+                &text_obj,
+                anchor.start + len,
+                (anchor.end - anchor.start) as isize,
+                "",
+            )?;
+            tx.put(&thread_obj, "resolved", true)?;
+            tx.put(&thread_obj, "resolvedBy", author)?;
+            tx.put(&thread_obj, "resolvedAt", now)?;
+            Ok(())
+        },
+    )
+    .map_err(|e| comments_err(e.error))?;
+    Ok(())
+}
+
+/// Insert open threads into the source as margin notes, for previews.
+///
+/// Each note is placed after the block containing the end of its anchor
+/// (never inside front matter or a fenced code block), so the anchored
+/// content renders unchanged with the discussion next to it.
+pub fn annotate_source(text: &str, threads: &[CommentThread]) -> String {
+    let mut notes: Vec<(usize, usize, String)> = threads
+        .iter()
+        .filter(|t| !t.resolved)
+        .enumerate()
+        .filter_map(|(order, t)| {
+            let anchor = t.anchor?;
+            let end = text
+                .char_indices()
+                .nth(anchor.end.saturating_sub(1))
+                .map_or(text.len(), |(i, _)| i);
+            Some((block_end(text, end), order, margin_note(t)))
+        })
+        .collect();
+    // Insert from the back so earlier offsets stay valid, keeping notes on
+    // the same block in thread order
+    notes.sort_by_key(|note| std::cmp::Reverse((note.0, note.1)));
+
+    let mut annotated = text.to_string();
+    for (offset, _, note) in notes {
+        annotated.insert_str(offset, &note);
+    }
+    annotated
+}
+
+/// Byte offset just after the block containing byte `offset`: the end of
+/// the first blank line that follows it outside front matter and code fences.
+fn block_end(text: &str, offset: usize) -> usize {
+    let mut in_front_matter = false;
+    let mut fence: Option<(char, usize)> = None;
+    let mut line_start = 0;
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        let line_end = line_start + line.len();
+        let trimmed = line.trim();
+        if index == 0 && trimmed == "---" {
+            in_front_matter = true;
+        } else if in_front_matter {
+            if trimmed == "---" || trimmed == "..." {
+                in_front_matter = false;
+                if line_end > offset {
+                    return line_end;
+                }
+            }
+        } else if let Some((marker, len)) = fence {
+            if trimmed.len() >= len && trimmed.chars().all(|c| c == marker) {
+                fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let marker = trimmed.chars().next().unwrap_or('`');
+            fence = Some((marker, trimmed.chars().take_while(|c| *c == marker).count()));
+        } else if trimmed.is_empty() && line_start > offset {
+            return line_end;
+        }
+        line_start = line_end;
+    }
+    text.len()
+}
+
+fn margin_note(thread: &CommentThread) -> String {
+    let mut note = format!(
+        "\n\n::: {{.column-margin .hub-comment #comment-{}}}\n",
+        thread.id
+    );
+    for message in &thread.messages {
+        note.push_str(&format!("**{}:** {}\n\n", message.author, message.body));
+    }
+    if let Some(suggestion) = &thread.suggestion {
+        note.push_str(&format!("*Suggested:* {}\n\n", suggestion));
+    }
+    note.push_str(":::\n\n");
+    note
+}
+
+fn mark_name(thread_id: &str) -> String {
+    format!("{}{}", MARK_PREFIX, thread_id)
+}
+
+/// The range covered by the marks of a thread.
+fn anchor_of(marks: &[Mark], thread_id: &str) -> Option<Anchor> {
+    let name = mark_name(thread_id);
+    marks
+        .iter()
+        .filter(|m| m.name() == name && !matches!(m.value(), ScalarValue::Null))
+        .fold(None, |anchor: Option<Anchor>, m| {
+            Some(match anchor {
+                Some(a) => Anchor {
+                    start: a.start.min(m.start),
+                    end: a.end.max(m.end),
+                },
+                None => Anchor {
+                    start: m.start,
+                    end: m.end,
+                },
+            })
+        })
+}
+
+fn new_id(doc: &Automerge, parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(doc.get_actor().to_hex_string());
+    for part in parts {
+        hasher.update(part.as_bytes());
+    }
+    hex::encode(hasher.finalize())[..16].to_string()
+}
+
+fn put_message(
+    tx: &mut impl Transactable,
+    messages: &ObjId,
+    index: usize,
+    id: &str,
+    author: &str,
+    body: &str,
+    now: i64,
+) -> std::result::Result<(), automerge::AutomergeError> {
+    let message = tx.insert_object(messages, index, ObjType::Map)?;
+    tx.put(&message, "id", id)?;
+    tx.put(&message, "author", author)?;
+    tx.put(&message, "body", body)?;
+    tx.put(&message, "createdAt", now)?;
+    Ok(())
+}
+
+fn text_obj(doc: &Automerge) -> Result<ObjId> {
+    match doc.get(ROOT, "text").map_err(comments_err)? {
+        Some((Value::Object(ObjType::Text), obj)) => Ok(obj),
+        _ => Err(Error::Comments("not a text document".to_string())),
+    }
+}
+
+fn comments_obj(doc: &Automerge) -> Result<Option<ObjId>> {
+    Ok(match doc.get(ROOT, COMMENTS_KEY).map_err(comments_err)? {
+        Some((Value::Object(ObjType::Map), obj)) => Some(obj),
+        _ => None,
+    })
+}
+
+fn thread_obj(doc: &Automerge, thread_id: &str) -> Result<ObjId> {
+    let thread = match comments_obj(doc)? {
+        Some(comments) => doc.get(&comments, thread_id).map_err(comments_err)?,
+        None => None,
+    };
+    match thread {
+        Some((Value::Object(ObjType::Map), obj)) => Ok(obj),
+        _ => Err(Error::Comments(format!("no such thread: {}", thread_id))),
+    }
+}
+
+fn messages_of(doc: &Automerge, thread: &ObjId) -> Result<Vec<Comment>> {
+    let Some((Value::Object(ObjType::List), messages)) =
+        doc.get(thread, "messages").map_err(comments_err)?
+    else {
+        return Ok(Vec::new());
+    };
+    let mut result = Vec::new();
+    for index in 0..doc.length(&messages) {
+        if let Some((Value::Object(ObjType::Map), message)) =
+            doc.get(&messages, index).map_err(comments_err)?
+        {
+            result.push(Comment {
+                id: get_str(doc, &message, "id")?.unwrap_or_default(),
+                author: get_str(doc, &message, "author")?.unwrap_or_default(),
+                body: get_str(doc, &message, "body")?.unwrap_or_default(),
+                created_at: get_i64(doc, &message, "createdAt")?.unwrap_or_default(),
+            });
+        }
+    }
+    Ok(result)
+}
+
+fn get_str(doc: &Automerge, obj: &ObjId, key: &str) -> Result<Option<String>> {
+    Ok(match doc.get(obj, key).map_err(comments_err)? {
+        Some((Value::Scalar(s), _)) => match s.as_ref() {
+            ScalarValue::Str(s) => Some(s.to_string()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+fn get_i64(doc: &Automerge, obj: &ObjId, key: &str) -> Result<Option<i64>> {
+    Ok(match doc.get(obj, key).map_err(comments_err)? {
+        Some((Value::Scalar(s), _)) => match s.as_ref() {
+            ScalarValue::Int(n) | ScalarValue::Timestamp(n) => Some(*n),
+            ScalarValue::Uint(n) => i64::try_from(*n).ok(),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_doc(text: &str) -> Automerge {
+        let mut doc = Automerge::new();
+        doc.transact::<_, _, automerge::AutomergeError>(|tx| {
+            let obj = tx.put_object(ROOT, "text", ObjType::Text)?;
+            tx.update_text(&obj, text)
+        })
+        .unwrap();
+        doc
+    }
+
+    fn set_text(doc: &mut Automerge, text: &str) {
+        let obj = text_obj(doc).unwrap();
+        doc.transact::<_, _, automerge::AutomergeError>(|tx| tx.update_text(&obj, text))
+            .unwrap();
+    }
+
+    fn new_thread(start: usize, end: usize, suggestion: Option<&str>) -> NewThread {
+        NewThread {
+            start,
+            end,
+            author: "Ada".to_string(),
+            body: "Is this right?".to_string(),
+            suggestion: suggestion.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_create_and_list_thread() {
+        let mut doc = text_doc("Hello world\n");
+        assert!(list_threads(&doc).unwrap().is_empty());
+
+        let id = create_thread(&mut doc, &new_thread(6, 11, None)).unwrap();
+        let threads = list_threads(&doc).unwrap();
+        assert_eq!(threads.len(), 1);
+        let thread = &threads[0];
+        assert_eq!(thread.id, id);
+        assert_eq!(thread.anchor, Some(Anchor { start: 6, end: 11 }));
+        assert_eq!(thread.quote.as_deref(), Some("world"));
+        assert_eq!(thread.author, "Ada");
+        assert!(!thread.resolved);
+        assert_eq!(thread.messages.len(), 1);
+        assert_eq!(thread.messages[0].body, "Is this right?");
+
+        assert!(create_thread(&mut doc, &new_thread(4, 4, None)).is_err());
+        assert!(create_thread(&mut doc, &new_thread(0, 100, None)).is_err());
+    }
+
+    #[test]
+    fn test_anchor_follows_edits() {
+        let mut doc = text_doc("Hello world\n");
+        let id = create_thread(&mut doc, &new_thread(6, 11, None)).unwrap();
+
+        set_text(&mut doc, "Oh, hello brave world\n");
+        let thread = get_thread(&doc, &id).unwrap();
+        assert_eq!(thread.quote.as_deref(), Some("world"));
+        assert_eq!(thread.anchor, Some(Anchor { start: 16, end: 21 }));
+
+        set_text(&mut doc, "Oh, hello\n");
+        let thread = get_thread(&doc, &id).unwrap();
+        assert_eq!(thread.anchor, None);
+        assert_eq!(thread.quote, None);
+    }
+
+    #[test]
+    fn test_reply_resolve_and_reopen() {
+        let mut doc = text_doc("Hello world\n");
+        let id = create_thread(&mut doc, &new_thread(0, 5, None)).unwrap();
+
+        reply(&mut doc, &id, "Grace", "Looks fine").unwrap();
+        set_resolved(&mut doc, &id, true, "Grace").unwrap();
+        let thread = get_thread(&doc, &id).unwrap();
+        assert_eq!(thread.messages.len(), 2);
+        assert_eq!(thread.messages[1].author, "Grace");
+        assert!(thread.resolved);
+        assert_eq!(thread.resolved_by.as_deref(), Some("Grace"));
+        assert!(thread.resolved_at.is_some());
+
+        set_resolved(&mut doc, &id, false, "Ada").unwrap();
+        let thread = get_thread(&doc, &id).unwrap();
+        assert!(!thread.resolved);
+        assert_eq!(thread.resolved_by, None);
+
+        assert!(reply(&mut doc, "missing", "Ada", "?").is_err());
+    }
+
+    #[test]
+    fn test_accept_suggestion() {
+        let mut doc = text_doc("Hello wrld\n");
+        let id = create_thread(&mut doc, &new_thread(6, 10, Some("world"))).unwrap();
+
+        accept_suggestion(&mut doc, &id, "Ada").unwrap();
+        let thread = get_thread(&doc, &id).unwrap();
+        assert_eq!(doc.text(text_obj(&doc).unwrap()).unwrap(), "Hello world\n");
+        assert_eq!(thread.quote.as_deref(), Some("world"));
+        assert!(thread.resolved);
+
+        let plain = create_thread(&mut doc, &new_thread(0, 5, None)).unwrap();
+        assert!(accept_suggestion(&mut doc, &plain, "Ada").is_err());
+    }
+
+    #[test]
+    fn test_annotate_source_places_notes_after_blocks() {
+        let text = "---\ntitle: T\n---\n\nFirst para\ncontinues.\n\n```{python}\nx = 1\n\ny = 2\n```\n\nLast";
+        let thread = |id: &str, start: usize, end: usize, resolved: bool| CommentThread {
+            id: id.to_string(),
+            anchor: Some(Anchor { start, end }),
+            quote: None,
+            suggestion: None,
+            author: "Ada".to_string(),
+            created_at: 0,
+            resolved,
+            resolved_by: None,
+            resolved_at: None,
+            messages: vec![Comment {
+                id: format!("{}-0", id),
+                author: "Ada".to_string(),
+                body: format!("note {}", id),
+                created_at: 0,
+            }],
+        };
+        let para = text.find("First").unwrap();
+        let code = text.find("x = 1").unwrap();
+        let annotated = annotate_source(
+            text,
+            &[
+                thread("a", para, para + 5, false),
+                thread("b", code, code + 5, false),
+                thread("c", para, para + 5, true),
+            ],
+        );
+
+        // The paragraph note follows the paragraph, not the middle of it
+        let note_a = annotated.find("#comment-a").unwrap();
+        assert!(annotated.find("continues.").unwrap() < note_a);
+        assert!(note_a < annotated.find("```{python}").unwrap());
+        // The code note is not placed inside the fenced block
+        let note_b = annotated.find("#comment-b").unwrap();
+        assert!(annotated.find("y = 2\n```").unwrap() < note_b);
+        assert!(note_b < annotated.find("Last").unwrap());
+        // Resolved threads are not shown
+        assert!(!annotated.contains("#comment-c"));
+        assert!(annotated.contains("**Ada:** note a"));
+    }
+}
@@ -3,26 +3,35 @@
 //! Contains the automerge repo and storage manager.
 
 use std::path::Path;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use automerge::{Automerge, ObjType, ROOT, transaction::Transactable};
-use samod::Repo;
 use samod::storage::TokioFilesystemStorage;
+use samod::{DocumentId, Repo};
 use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, info, warn};
 
+use crate::auth::{AuthConfig, Principal, Role};
 use crate::discovery::ProjectFiles;
 use crate::error::Result;
+use crate::ignore::{SyncFilter, SyncPolicy};
 use crate::index::{IndexDocument, load_or_create_index};
 use crate::peer::spawn_peer_connection;
+use crate::presence::{PresenceRegistry, spawn_presence_listener};
+use crate::render::{DocumentRenderer, RenderCache, spawn_render_invalidator};
 use crate::resource::{create_binary_document, detect_mime_type};
-use crate::storage::StorageManager;
+use crate::storage::{DEFAULT_COMPACTION_THRESHOLD, StorageManager};
 use crate::sync::{SyncAllResult, SyncResult, sync_all_documents, sync_file_by_path};
 use crate::sync_state::SyncState;
 
 /// Configuration for the hub.
 #[derive(Debug, Clone)]
 pub struct HubConfig {
+    /// Name of the project, which token roles are looked up by.
+    /// Default: "project".
+    pub project: String,
+
     /// Port to listen on
     pub port: u16,
 
@@ -45,17 +54,33 @@ pub struct HubConfig {
     /// Debounce duration for filesystem events in milliseconds.
     /// Default: 500ms.
     pub watch_debounce_ms: u64,
+
+    /// Authentication tokens and roles.
+    /// Default: no tokens (authentication disabled).
+    pub auth: AuthConfig,
+
+    /// Size limit and binary-file policy for synced files.
+    /// Default: 10 MiB, all binary files.
+    pub sync_policy: SyncPolicy,
+
+    /// Renderer for `/api/render/{path}`.
+    /// Default: None (server-side rendering disabled).
+    pub renderer: Option<Arc<dyn DocumentRenderer>>,
 }
 
 impl Default for HubConfig {
     fn default() -> Self {
         Self {
+            project: "project".to_string(),
             port: 3000,
             host: "127.0.0.1".to_string(),
             peers: Vec::new(),
             sync_interval_secs: Some(30),
             watch_enabled: true,
             watch_debounce_ms: 500,
+            auth: AuthConfig::default(),
+            sync_policy: SyncPolicy::default(),
+            renderer: None,
         }
     }
 }
@@ -72,9 +97,18 @@ pub struct HubContext {
     /// Hub configuration
     config: RwLock<HubConfig>,
 
+    /// Authentication configuration (fixed for the lifetime of the server)
+    auth: AuthConfig,
+
+    /// Name of the project, which token roles are looked up by
+    project: String,
+
     /// Discovered project files
     project_files: ProjectFiles,
 
+    /// Which files are synced (ignore files and sync policy)
+    sync_filter: SyncFilter,
+
     /// samod Repo - handles document storage, sync, and concurrency internally.
     /// Clone is cheap: Repo wraps Arc<Mutex<Inner>>.
     repo: Repo,
@@ -84,6 +118,15 @@ pub struct HubContext {
 
     /// Sync state for filesystem synchronization (protected by Mutex for interior mutability)
     sync_state: Mutex<SyncState>,
+
+    /// Latest presence (identity, cursor, selection) of peers on each document
+    presence: Arc<PresenceRegistry>,
+
+    /// Renderer for server-side previews (fixed for the lifetime of the server)
+    renderer: Option<Arc<dyn DocumentRenderer>>,
+
+    /// Rendered pages keyed by document heads
+    render_cache: Arc<RenderCache>,
 }
 
 impl HubContext {
@@ -94,8 +137,9 @@ impl HubContext {
     /// 2. Loads or creates the index document
     /// 3. Reconciles discovered .qmd files with the index
     pub async fn new(mut storage: StorageManager, config: HubConfig) -> Result<Self> {
-        // Discover project files
-        let project_files = ProjectFiles::discover(storage.project_root());
+        // Discover project files, honoring .gitignore/.quartoignore and the sync policy
+        let sync_filter = SyncFilter::load(storage.project_root(), config.sync_policy.clone());
+        let project_files = ProjectFiles::discover_filtered(storage.project_root(), &sync_filter);
 
         info!(
             qmd_count = project_files.qmd_files.len(),
@@ -104,6 +148,12 @@ impl HubContext {
             "Discovered project files"
         );
 
+        // Fold documents left in many chunks into single snapshots while
+        // nothing else is using the storage directory
+        if let Err(e) = storage.compact(DEFAULT_COMPACTION_THRESHOLD) {
+            warn!(error = %e, "Failed to compact document storage");
+        }
+
         // Initialize samod repo with filesystem storage
         let automerge_dir = storage.automerge_dir();
         info!(automerge_dir = %automerge_dir.display(), "Initializing samod repo");
@@ -152,13 +202,35 @@ impl HubContext {
             "Initial filesystem sync complete"
         );
 
+        // Listen for presence messages on every project document, and drop
+        // cached renders when a document changes
+        let presence = Arc::new(PresenceRegistry::new());
+        let render_cache = Arc::new(RenderCache::new());
+        for (path, doc_id) in index.get_all_files() {
+            let Ok(doc_id) = DocumentId::from_str(&doc_id) else {
+                continue;
+            };
+            if let Ok(Some(handle)) = repo.find(doc_id).await {
+                if config.renderer.is_some() {
+                    spawn_render_invalidator(handle.clone(), path, render_cache.clone());
+                }
+                spawn_presence_listener(handle, presence.clone());
+            }
+        }
+
         Ok(Self {
             storage,
+            auth: config.auth.clone(),
+            project: config.project.clone(),
+            renderer: config.renderer.clone(),
             config: RwLock::new(config),
             project_files,
+            sync_filter,
             repo,
             index,
             sync_state: Mutex::new(sync_state_guard),
+            presence,
+            render_cache,
         })
     }
 
@@ -172,11 +244,32 @@ impl HubContext {
         self.config.read().await.clone()
     }
 
+    /// Get the authentication configuration.
+    pub fn auth(&self) -> &AuthConfig {
+        &self.auth
+    }
+
+    /// Resolve the role a presented token grants on this project.
+    pub fn authenticate(&self, token: Option<&str>) -> Option<Role> {
+        self.auth.authenticate(token, &self.project)
+    }
+
+    /// Resolve the role and the principal a presented token grants on this
+    /// project.
+    pub fn authenticate_as(&self, token: Option<&str>) -> Option<(Role, Principal)> {
+        self.auth.authenticate_as(token, &self.project)
+    }
+
     /// Get discovered project files.
     pub fn project_files(&self) -> &ProjectFiles {
         &self.project_files
     }
 
+    /// Get the filter deciding which files are synced.
+    pub fn sync_filter(&self) -> &SyncFilter {
+        &self.sync_filter
+    }
+
     /// Get reference to the samod repo.
     pub fn repo(&self) -> &Repo {
         &self.repo
@@ -187,6 +280,21 @@ impl HubContext {
         &self.index
     }
 
+    /// Get the presence registry.
+    pub fn presence(&self) -> &PresenceRegistry {
+        &self.presence
+    }
+
+    /// Get the renderer for server-side previews, if one is configured.
+    pub fn renderer(&self) -> Option<&Arc<dyn DocumentRenderer>> {
+        self.renderer.as_ref()
+    }
+
+    /// Get the cache of rendered pages.
+    pub fn render_cache(&self) -> &RenderCache {
+        &self.render_cache
+    }
+
     /// Perform a full sync of all documents with the filesystem.
     ///
     /// This is called on shutdown to ensure all changes are persisted.
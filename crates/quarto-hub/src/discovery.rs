@@ -1,12 +1,14 @@
 //! Project file discovery
 //!
-//! Walks the project directory to find `.qmd` files, config files, and binary resources.
+//! Walks the project directory to find `.qmd` files, config files, and binary resources,
+//! skipping files excluded by the project's [`SyncFilter`].
 
 use std::path::{Path, PathBuf};
 
-use tracing::debug;
+use tracing::{debug, info};
 use walkdir::WalkDir;
 
+use crate::ignore::{SyncFilter, SyncPolicy};
 use crate::resource::is_binary_extension;
 
 /// Discovered files in a Quarto project.
@@ -29,18 +31,34 @@ impl ProjectFiles {
     /// - Hidden directories (starting with `.`)
     /// - `node_modules`
     /// - `_site`, `_book`, and other output directories
+    /// - Files matched by `.gitignore` or `.quartoignore`
+    /// - Files over the default size limit
     pub fn discover(project_root: &Path) -> Self {
+        Self::discover_filtered(
+            project_root,
+            &SyncFilter::load(project_root, SyncPolicy::default()),
+        )
+    }
+
+    /// Discover the files of a Quarto project accepted by `filter`.
+    pub fn discover_filtered(project_root: &Path, filter: &SyncFilter) -> Self {
         let mut files = ProjectFiles::default();
 
         let walker = WalkDir::new(project_root)
             .follow_links(false)
             .into_iter()
-            .filter_entry(|e| !is_ignored(e));
+            .filter_entry(|e| !is_ignored(e, project_root, filter));
 
         for entry in walker.filter_map(|e| e.ok()) {
             let path = entry.path();
 
             if path.is_file() {
+                let size = entry.metadata().map_or(0, |m| m.len());
+                if !filter.within_size_limit(size) {
+                    info!(path = %path.display(), size, "Skipping file over the sync size limit");
+                    continue;
+                }
+
                 // Check for config files first (by name)
                 if let Some(file_name) = path.file_name()
                     && (file_name == "_quarto.yml" || file_name == "_quarto.yaml")
@@ -65,13 +83,13 @@ impl ProjectFiles {
                 }
 
                 // Check for binary resource files (images, PDFs, etc.)
-                if let Some(ext_str) = ext {
-                    if is_binary_extension(ext_str) {
-                        if let Ok(relative) = path.strip_prefix(project_root) {
-                            debug!(?relative, "Discovered binary file");
-                            files.binary_files.push(relative.to_path_buf());
-                        }
-                    }
+                if let Some(ext_str) = ext
+                    && is_binary_extension(ext_str)
+                    && filter.allows_binary(ext_str)
+                    && let Ok(relative) = path.strip_prefix(project_root)
+                {
+                    debug!(?relative, "Discovered binary file");
+                    files.binary_files.push(relative.to_path_buf());
                 }
             }
         }
@@ -109,24 +127,16 @@ impl ProjectFiles {
 }
 
 /// Check if a directory entry should be ignored during traversal.
-fn is_ignored(entry: &walkdir::DirEntry) -> bool {
+fn is_ignored(entry: &walkdir::DirEntry, project_root: &Path, filter: &SyncFilter) -> bool {
     // Never filter the root directory (depth 0)
     if entry.depth() == 0 {
         return false;
     }
 
-    let name = entry.file_name().to_string_lossy();
-
-    // Skip hidden directories (but not the root, which we already handled)
-    if name.starts_with('.') && entry.file_type().is_dir() {
-        return true;
-    }
-
-    // Skip common non-source directories
-    matches!(
-        name.as_ref(),
-        "node_modules" | "_site" | "_book" | "_freeze" | "renv" | "venv" | "__pycache__" | "target"
-    )
+    entry
+        .path()
+        .strip_prefix(project_root)
+        .is_ok_and(|relative| filter.is_ignored(relative, entry.file_type().is_dir()))
 }
 
 #[cfg(test)]
@@ -289,4 +299,43 @@ mod tests {
         assert!(text.contains(&&PathBuf::from("_quarto.yml")));
         assert!(text.contains(&&PathBuf::from("index.qmd")));
     }
+
+    #[test]
+    fn test_respects_ignore_files() {
+        let temp = TempDir::new().unwrap();
+
+        fs::write(temp.path().join(".gitignore"), "drafts/\n*.png\n").unwrap();
+        fs::write(temp.path().join(".quartoignore"), "!logo.png\n").unwrap();
+        fs::write(temp.path().join("index.qmd"), "# Hello").unwrap();
+        fs::create_dir(temp.path().join("drafts")).unwrap();
+        fs::write(temp.path().join("drafts/wip.qmd"), "# WIP").unwrap();
+        fs::write(temp.path().join("logo.png"), [0x89, 0x50, 0x4E, 0x47]).unwrap();
+        fs::write(temp.path().join("plot.png"), [0x89, 0x50, 0x4E, 0x47]).unwrap();
+
+        let files = ProjectFiles::discover(temp.path());
+
+        assert_eq!(files.qmd_files, vec![PathBuf::from("index.qmd")]);
+        assert_eq!(files.binary_files, vec![PathBuf::from("logo.png")]);
+    }
+
+    #[test]
+    fn test_size_limit_and_binary_policy() {
+        use crate::ignore::BinaryPolicy;
+
+        let temp = TempDir::new().unwrap();
+
+        fs::write(temp.path().join("index.qmd"), "# Hello").unwrap();
+        fs::write(temp.path().join("big.qmd"), "x".repeat(200)).unwrap();
+        fs::write(temp.path().join("logo.png"), [0x89, 0x50, 0x4E, 0x47]).unwrap();
+        fs::write(temp.path().join("paper.pdf"), b"PDF").unwrap();
+
+        let filter = SyncFilter::new(SyncPolicy {
+            max_file_size: Some(100),
+            binary_files: BinaryPolicy::Images,
+        });
+        let files = ProjectFiles::discover_filtered(temp.path(), &filter);
+
+        assert_eq!(files.qmd_files, vec![PathBuf::from("index.qmd")]);
+        assert_eq!(files.binary_files, vec![PathBuf::from("logo.png")]);
+    }
 }
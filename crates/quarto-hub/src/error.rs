@@ -41,6 +41,21 @@ pub enum Error {
 
     #[error("Sync error: {0}")]
     Sync(String),
+
+    #[error("History error: {0}")]
+    History(String),
+
+    #[error("Comment error: {0}")]
+    Comments(String),
+
+    #[error("Invalid auth config: {0}")]
+    AuthConfig(String),
+
+    #[error("Invalid author: {0}")]
+    Author(String),
+
+    #[error("Invalid project registry: {0}")]
+    ProjectRegistry(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,201 @@
+//! Conflict-aware export of documents to the working tree.
+//!
+//! The hub writes document contents back to the project files (usually a git
+//! working tree) while other tools edit the same files. Writes must therefore
+//! never clobber an edit made on disk after the hub read the file:
+//!
+//! - every write goes to a temporary file in the same directory and is
+//!   renamed over the target, so readers never see a partial file
+//! - before writing, the file is compared to the [`FileSnapshot`] taken when
+//!   it was read (modification time and size, then content hash); if it
+//!   changed in between, the write is refused with [`WriteOutcome::Conflict`]
+//!
+//! On a conflict the sync layer reconciles again: the new disk content is
+//! merged into the document as a three-way merge against the content it has
+//! already incorporated (see [`crate::sync::sync_document`]).
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use crate::error::{Error, Result};
+
+/// How many times a write is retried when the file keeps changing on disk.
+pub const MAX_WRITE_ATTEMPTS: usize = 3;
+
+/// The state of a file when the hub read it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSnapshot {
+    /// Modification time, if the platform reports one
+    pub modified: Option<SystemTime>,
+
+    /// Size in bytes
+    pub len: u64,
+
+    /// Hex-encoded SHA-256 of the content
+    pub hash: String,
+}
+
+impl FileSnapshot {
+    /// Snapshot of a file whose content was just read.
+    pub fn of(path: &Path, content: &[u8]) -> Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(Self {
+            modified: metadata.modified().ok(),
+            len: content.len() as u64,
+            hash: content_hash(content),
+        })
+    }
+
+    /// Read a file and snapshot it.
+    pub fn read(path: &Path) -> Result<(Vec<u8>, Self)> {
+        let content = std::fs::read(path)?;
+        let snapshot = Self::of(path, &content)?;
+        Ok((content, snapshot))
+    }
+
+    /// Read a text file and snapshot it.
+    pub fn read_to_string(path: &Path) -> Result<(String, Self)> {
+        let (content, snapshot) = Self::read(path)?;
+        let content = String::from_utf8(content).map_err(|e| {
+            Error::Sync(format!("file {} is not valid UTF-8: {}", path.display(), e))
+        })?;
+        Ok((content, snapshot))
+    }
+
+    /// Whether the file still has the content it had when snapshotted.
+    ///
+    /// A different size or modification time means it changed. Equal
+    /// metadata is confirmed with the content hash, since modification
+    /// times can be coarse.
+    pub fn is_current(&self, path: &Path) -> Result<bool> {
+        let metadata = match std::fs::metadata(path) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        if metadata.len() != self.len || metadata.modified().ok() != self.modified {
+            return Ok(false);
+        }
+        Ok(content_hash(&std::fs::read(path)?) == self.hash)
+    }
+}
+
+/// Result of a guarded write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteOutcome {
+    /// The new content was written
+    Written,
+    /// The file changed on disk since it was read; nothing was written
+    Conflict,
+}
+
+/// Write `content` to `path` unless the file changed since `expected` was
+/// taken.
+pub fn write_if_unchanged(
+    path: &Path,
+    content: &[u8],
+    expected: &FileSnapshot,
+) -> Result<WriteOutcome> {
+    if !expected.is_current(path)? {
+        debug!(path = %path.display(), "File changed on disk, not overwriting");
+        return Ok(WriteOutcome::Conflict);
+    }
+    write_atomic(path, content)?;
+    Ok(WriteOutcome::Written)
+}
+
+/// Atomically replace the content of `path`.
+///
+/// The content is written and flushed to a temporary file next to the
+/// target, which is then renamed over it. The permissions of an existing
+/// file are preserved.
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let temp = temp_path(path);
+    let result = (|| {
+        let mut file = std::fs::File::create(&temp)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        if let Ok(metadata) = std::fs::metadata(path) {
+            std::fs::set_permissions(&temp, metadata.permissions())?;
+        }
+        std::fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result.map_err(|e| Error::Sync(format!("failed to write {}: {}", path.display(), e)))
+}
+
+/// Temporary file used while writing `path`: hidden, in the same directory
+/// (so the rename stays on one filesystem) and unique per process.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.hub-{}.tmp", name, std::process::id()))
+}
+
+fn content_hash(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_atomic_replaces_content() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("doc.qmd");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"second");
+        // No temporary files are left behind
+        assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_write_if_unchanged() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("doc.qmd");
+        std::fs::write(&path, "original").unwrap();
+
+        let (_, snapshot) = FileSnapshot::read(&path).unwrap();
+        assert!(snapshot.is_current(&path).unwrap());
+        assert_eq!(
+            write_if_unchanged(&path, b"from hub", &snapshot).unwrap(),
+            WriteOutcome::Written
+        );
+
+        // The snapshot is stale once the file has been rewritten
+        assert_eq!(
+            write_if_unchanged(&path, b"again", &snapshot).unwrap(),
+            WriteOutcome::Conflict
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "from hub");
+    }
+
+    #[test]
+    fn test_deleted_file_is_a_conflict() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("doc.qmd");
+        std::fs::write(&path, "original").unwrap();
+
+        let (_, snapshot) = FileSnapshot::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            write_if_unchanged(&path, b"from hub", &snapshot).unwrap(),
+            WriteOutcome::Conflict
+        );
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,445 @@
+//! Document history and time travel.
+//!
+//! Automerge keeps the full change graph of every document, so the hub can
+//! answer history questions without any extra bookkeeping:
+//! - list the changes that make up a document (author, timestamp, message)
+//! - reconstruct a document as it was at a given set of heads
+//! - compute a structured diff between two sets of heads
+//! - restore an old version by writing it as a new change on top of the
+//!   current state (history is never rewritten)
+//!
+//! Heads are exchanged as hex-encoded change hashes, the same format used for
+//! sync checkpoints in `sync-state.json`.
+
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use automerge::{
+    Automerge, ChangeHash, ObjType, Prop, ROOT, ReadDoc, ScalarValue, Value,
+    transaction::{CommitOptions, Transactable},
+};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+use crate::resource::{DocumentType, detect_document_type};
+
+/// Summary of a single change in a document's history.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeInfo {
+    /// Hex-encoded change hash
+    pub hash: String,
+
+    /// Hex-encoded actor ID of the peer that made the change
+    pub actor: String,
+
+    /// Sequence number of this change for its actor
+    pub seq: u64,
+
+    /// Milliseconds since the unix epoch, if the author recorded a time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+
+    /// Commit message, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// Hashes of the changes this change depends on
+    pub deps: Vec<String>,
+}
+
+/// A document's content at a point in history.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Snapshot {
+    Text {
+        text: String,
+    },
+    Binary {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
+        size: usize,
+    },
+}
+
+/// One entry of a structured diff between two versions.
+///
+/// `path` is the location of the modified object from the document root,
+/// e.g. `["text"]` for edits to a text document's content.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum DiffEntry {
+    /// Text inserted at `index`
+    Insert {
+        path: Vec<serde_json::Value>,
+        index: usize,
+        text: String,
+    },
+    /// `length` items deleted starting at `index`
+    Delete {
+        path: Vec<serde_json::Value>,
+        index: usize,
+        length: usize,
+    },
+    /// A map key or list element was set
+    Put {
+        path: Vec<serde_json::Value>,
+        prop: serde_json::Value,
+        value: serde_json::Value,
+    },
+    /// A map key was removed
+    Remove {
+        path: Vec<serde_json::Value>,
+        prop: serde_json::Value,
+    },
+}
+
+/// List every change in the document, in causal order.
+pub fn list_changes(doc: &Automerge) -> Vec<ChangeInfo> {
+    doc.get_changes(&[])
+        .iter()
+        .map(|change| ChangeInfo {
+            hash: change.hash().to_string(),
+            actor: change.actor_id().to_hex_string(),
+            seq: change.seq(),
+            timestamp: (change.timestamp() != 0).then_some(change.timestamp()),
+            message: change.message().cloned(),
+            deps: change.deps().iter().map(|h| h.to_string()).collect(),
+        })
+        .collect()
+}
+
+/// Parse hex-encoded heads and check that they exist in the document.
+///
+/// An empty list is valid and refers to the empty document.
+pub fn parse_heads(doc: &Automerge, heads: &[String]) -> Result<Vec<ChangeHash>> {
+    heads
+        .iter()
+        .map(|head| {
+            let hash = ChangeHash::from_str(head)
+                .map_err(|_| Error::History(format!("invalid change hash: {}", head)))?;
+            if doc.get_change_by_hash(&hash).is_none() {
+                return Err(Error::History(format!("unknown change hash: {}", head)));
+            }
+            Ok(hash)
+        })
+        .collect()
+}
+
+/// Reconstruct the document content at the given heads.
+pub fn snapshot_at(doc: &Automerge, heads: &[ChangeHash]) -> Result<Snapshot> {
+    let history_err = |e: automerge::AutomergeError| Error::History(e.to_string());
+
+    match detect_document_type(doc) {
+        DocumentType::Text => {
+            let text = match doc.get_at(ROOT, "text", heads).map_err(history_err)? {
+                Some((_, text_obj)) => doc.text_at(&text_obj, heads).map_err(history_err)?,
+                // The text object didn't exist yet at these heads
+                None => String::new(),
+            };
+            Ok(Snapshot::Text { text })
+        }
+        DocumentType::Binary => {
+            let scalar = |key: &str| -> Result<Option<ScalarValue>> {
+                Ok(match doc.get_at(ROOT, key, heads).map_err(history_err)? {
+                    Some((Value::Scalar(s), _)) => Some(s.into_owned()),
+                    _ => None,
+                })
+            };
+            let size = match scalar("content")? {
+                Some(ScalarValue::Bytes(bytes)) => bytes.len(),
+                _ => 0,
+            };
+            let as_string = |value: Option<ScalarValue>| match value {
+                Some(ScalarValue::Str(s)) => Some(s.to_string()),
+                _ => None,
+            };
+            Ok(Snapshot::Binary {
+                mime_type: as_string(scalar("mimeType")?),
+                hash: as_string(scalar("hash")?),
+                size,
+            })
+        }
+        DocumentType::Invalid => Err(Error::History(
+            "document is neither a text nor a binary document".to_string(),
+        )),
+    }
+}
+
+/// Compute a structured diff from `before` to `after`.
+pub fn diff(doc: &Automerge, before: &[ChangeHash], after: &[ChangeHash]) -> Vec<DiffEntry> {
+    use automerge::PatchAction;
+
+    doc.diff(before, after)
+        .into_iter()
+        .filter_map(|patch| {
+            let path: Vec<serde_json::Value> =
+                patch.path.iter().map(|(_, prop)| prop_json(prop)).collect();
+            match patch.action {
+                PatchAction::SpliceText { index, value, .. } => Some(DiffEntry::Insert {
+                    path,
+                    index,
+                    text: value.make_string(),
+                }),
+                PatchAction::DeleteSeq { index, length } => Some(DiffEntry::Delete {
+                    path,
+                    index,
+                    length,
+                }),
+                PatchAction::PutMap { key, value, .. } => Some(DiffEntry::Put {
+                    path,
+                    prop: serde_json::Value::String(key),
+                    value: value_json(&value.0),
+                }),
+                PatchAction::PutSeq { index, value, .. } => Some(DiffEntry::Put {
+                    path,
+                    prop: index.into(),
+                    value: value_json(&value.0),
+                }),
+                PatchAction::DeleteMap { key } => Some(DiffEntry::Remove {
+                    path,
+                    prop: serde_json::Value::String(key),
+                }),
+                // List inserts, counters, conflicts and marks don't occur in
+                // hub documents; skip them rather than guess at a shape.
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Restore the document to its content at `heads`.
+///
+/// The old content is written as a new change on top of the current state,
+/// so the restore itself shows up in history and can be undone. Returns the
+/// new heads.
+pub fn restore(doc: &mut Automerge, heads: &[ChangeHash]) -> Result<Vec<ChangeHash>> {
+    let history_err = |e: automerge::AutomergeError| Error::History(e.to_string());
+    let message = format!(
+        "Restore to {}",
+        heads
+            .iter()
+            .map(|h| h.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    let options = CommitOptions::default()
+        .with_message(message)
+        .with_time(now_millis());
+
+    match detect_document_type(doc) {
+        DocumentType::Text => {
+            let Snapshot::Text { text } = snapshot_at(doc, heads)? else {
+                unreachable!("text documents produce text snapshots");
+            };
+            doc.transact_with::<_, _, automerge::AutomergeError, _>(
+                |_| options,
+                |tx| {
+                    let text_obj = match tx.get(ROOT, "text")? {
+                        Some((_, obj)) => obj,
+                        None => tx.put_object(ROOT, "text", ObjType::Text)?,
+                    };
+                    tx.update_text(&text_obj, &text)?;
+                    Ok(())
+                },
+            )
+            .map_err(|e| history_err(e.error))?;
+        }
+        DocumentType::Binary => {
+            let old_values = ["content", "mimeType", "hash"]
+                .into_iter()
+                .map(|key| {
+                    let value = match doc.get_at(ROOT, key, heads).map_err(history_err)? {
+                        Some((Value::Scalar(s), _)) => Some(s.into_owned()),
+                        _ => None,
+                    };
+                    Ok((key, value))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            doc.transact_with::<_, _, automerge::AutomergeError, _>(
+                |_| options,
+                |tx| {
+                    for (key, value) in &old_values {
+                        match value {
+                            Some(value) => tx.put(ROOT, *key, value.clone())?,
+                            None => tx.delete(ROOT, *key)?,
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|e| history_err(e.error))?;
+        }
+        DocumentType::Invalid => {
+            return Err(Error::History(
+                "document is neither a text nor a binary document".to_string(),
+            ));
+        }
+    }
+
+    Ok(doc.get_heads())
+}
+
+fn prop_json(prop: &Prop) -> serde_json::Value {
+    match prop {
+        Prop::Map(key) => serde_json::Value::String(key.clone()),
+        Prop::Seq(index) => (*index).into(),
+    }
+}
+
+fn value_json(value: &Value<'_>) -> serde_json::Value {
+    match value {
+        Value::Object(obj_type) => serde_json::Value::String(format!("<{}>", obj_type)),
+        Value::Scalar(scalar) => match scalar.as_ref() {
+            ScalarValue::Str(s) => serde_json::Value::String(s.to_string()),
+            ScalarValue::Int(i) => (*i).into(),
+            ScalarValue::Uint(u) => (*u).into(),
+            ScalarValue::F64(f) => (*f).into(),
+            ScalarValue::Counter(c) => i64::from(c).into(),
+            ScalarValue::Timestamp(t) => (*t).into(),
+            ScalarValue::Boolean(b) => (*b).into(),
+            ScalarValue::Null => serde_json::Value::Null,
+            ScalarValue::Bytes(bytes) => format!("<{} bytes>", bytes.len()).into(),
+            other => other.to_string().into(),
+        },
+    }
+}
+
+pub(crate) fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::create_binary_document;
+
+    fn text_doc(versions: &[&str]) -> (Automerge, Vec<Vec<ChangeHash>>) {
+        let mut doc = Automerge::new();
+        let mut heads = Vec::new();
+        let text_obj = doc
+            .transact::<_, _, automerge::AutomergeError>(|tx| {
+                tx.put_object(ROOT, "text", ObjType::Text)
+            })
+            .unwrap()
+            .result;
+        for version in versions {
+            doc.transact::<_, _, automerge::AutomergeError>(|tx| {
+                tx.update_text(&text_obj, version)
+            })
+            .unwrap();
+            heads.push(doc.get_heads());
+        }
+        (doc, heads)
+    }
+
+    fn text_of(doc: &Automerge) -> String {
+        match snapshot_at(doc, &doc.get_heads()).unwrap() {
+            Snapshot::Text { text } => text,
+            other => panic!("expected text snapshot, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_list_changes() {
+        let (doc, heads) = text_doc(&["one", "two"]);
+        let changes = list_changes(&doc);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[2].hash, heads[1][0].to_string());
+        assert_eq!(changes[2].deps, vec![heads[0][0].to_string()]);
+        assert_eq!(changes[2].seq, 3);
+        assert!(changes[2].timestamp.is_none());
+    }
+
+    #[test]
+    fn test_parse_heads() {
+        let (doc, heads) = text_doc(&["one"]);
+        let hex = heads[0][0].to_string();
+        assert_eq!(parse_heads(&doc, &[hex]).unwrap(), heads[0]);
+        assert!(parse_heads(&doc, &["nothex".to_string()]).is_err());
+        assert!(parse_heads(&doc, &["00".repeat(32)]).is_err());
+        assert!(parse_heads(&doc, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_at() {
+        let (doc, heads) = text_doc(&["first draft", "second draft"]);
+        assert_eq!(
+            snapshot_at(&doc, &heads[0]).unwrap(),
+            Snapshot::Text {
+                text: "first draft".to_string()
+            }
+        );
+        assert_eq!(text_of(&doc), "second draft");
+    }
+
+    #[test]
+    fn test_binary_snapshot() {
+        let doc = create_binary_document(b"\x89PNG....", "image/png").unwrap();
+        let Snapshot::Binary {
+            mime_type, size, ..
+        } = snapshot_at(&doc, &doc.get_heads()).unwrap()
+        else {
+            panic!("expected binary snapshot");
+        };
+        assert_eq!(mime_type.as_deref(), Some("image/png"));
+        assert_eq!(size, 8);
+    }
+
+    #[test]
+    fn test_diff() {
+        let (doc, heads) = text_doc(&["hello world", "hello there world"]);
+        let entries = diff(&doc, &heads[0], &heads[1]);
+        assert_eq!(
+            entries,
+            vec![DiffEntry::Insert {
+                path: vec!["text".into()],
+                index: 6,
+                text: "there ".to_string(),
+            }]
+        );
+
+        let entries = diff(&doc, &heads[1], &heads[0]);
+        assert_eq!(
+            entries,
+            vec![DiffEntry::Delete {
+                path: vec!["text".into()],
+                index: 6,
+                length: 6,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_restore_text() {
+        let (mut doc, heads) = text_doc(&["original", "edited"]);
+        let new_heads = restore(&mut doc, &heads[0]).unwrap();
+        assert_eq!(text_of(&doc), "original");
+        assert_ne!(new_heads, heads[0]);
+
+        // The restore is a new change with a message and timestamp
+        let last = list_changes(&doc).pop().unwrap();
+        assert!(last.message.unwrap().starts_with("Restore to "));
+        assert!(last.timestamp.is_some());
+    }
+
+    #[test]
+    fn test_restore_binary() {
+        let mut doc = create_binary_document(b"v1", "text/plain").unwrap();
+        let v1 = doc.get_heads();
+        doc.transact::<_, _, automerge::AutomergeError>(|tx| {
+            tx.put(ROOT, "content", ScalarValue::Bytes(b"version 2".to_vec()))
+        })
+        .unwrap();
+
+        restore(&mut doc, &v1).unwrap();
+        assert_eq!(
+            crate::resource::read_binary_content(&doc).as_deref(),
+            Some(&b"v1"[..])
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Selective filesystem sync.
+//!
+//! Decides which project files become Automerge documents. A file is synced
+//! unless:
+//! - it lives in a hidden or well-known output/dependency directory
+//!   (`.git`, `.venv`, `node_modules`, `_site`, ...)
+//! - it matches a rule in the project's `.gitignore` or `.quartoignore`
+//!   (gitignore syntax; `.quartoignore` is read last, so `!pattern` there
+//!   can re-include files git ignores)
+//! - it is larger than the configured size limit
+//! - it is a binary file excluded by the binary-file policy
+//!
+//! The same filter is applied by discovery and by the filesystem watcher.
+
+use std::path::{Component, Path};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// Name of the hub-specific ignore file at the project root
+pub const QUARTOIGNORE_FILE: &str = ".quartoignore";
+
+/// Default size limit for synced files (10 MiB)
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Directories that are never synced, in addition to hidden directories.
+const IGNORED_DIRS: &[&str] = &[
+    "node_modules",
+    "_site",
+    "_book",
+    "_freeze",
+    "renv",
+    "venv",
+    "__pycache__",
+    "target",
+];
+
+/// Which binary files to sync.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryPolicy {
+    /// Sync all binary resources (images, PDFs, ...)
+    #[default]
+    All,
+    /// Sync images only
+    Images,
+    /// Sync no binary files
+    None,
+}
+
+impl FromStr for BinaryPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(Self::All),
+            "images" => Ok(Self::Images),
+            "none" => Ok(Self::None),
+            other => Err(format!(
+                "invalid binary file policy '{}' (expected all, images or none)",
+                other
+            )),
+        }
+    }
+}
+
+/// Size and binary-file limits for synced files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncPolicy {
+    /// Files larger than this many bytes are not synced (`None` for no limit)
+    pub max_file_size: Option<u64>,
+
+    /// Which binary files are synced
+    pub binary_files: BinaryPolicy,
+}
+
+impl Default for SyncPolicy {
+    fn default() -> Self {
+        Self {
+            max_file_size: Some(DEFAULT_MAX_FILE_SIZE),
+            binary_files: BinaryPolicy::All,
+        }
+    }
+}
+
+/// One line of an ignore file.
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    pattern: glob::Pattern,
+    /// `!pattern`: re-include matching paths
+    negated: bool,
+    /// `pattern/`: only match directories
+    dir_only: bool,
+    /// The pattern contains a `/`, so it matches the whole relative path
+    /// rather than the file name at any depth
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let line = line.strip_prefix('/').unwrap_or(line);
+        match glob::Pattern::new(line) {
+            Ok(pattern) => Some(Self {
+                pattern,
+                negated,
+                dir_only,
+                anchored,
+            }),
+            Err(e) => {
+                warn!(pattern = %line, error = %e, "Ignoring invalid ignore pattern");
+                None
+            }
+        }
+    }
+
+    fn matches(&self, relative: &Path, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let options = glob::MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        if self.anchored {
+            self.pattern.matches_path_with(relative, options)
+        } else {
+            relative
+                .file_name()
+                .is_some_and(|name| self.pattern.matches_with(&name.to_string_lossy(), options))
+        }
+    }
+}
+
+/// Decides which project files are synced.
+#[derive(Debug, Clone, Default)]
+pub struct SyncFilter {
+    rules: Vec<IgnoreRule>,
+    policy: SyncPolicy,
+}
+
+impl SyncFilter {
+    /// Create a filter with the built-in rules only.
+    pub fn new(policy: SyncPolicy) -> Self {
+        Self {
+            rules: Vec::new(),
+            policy,
+        }
+    }
+
+    /// Load the `.gitignore` and `.quartoignore` files at the project root.
+    pub fn load(project_root: &Path, policy: SyncPolicy) -> Self {
+        let mut filter = Self::new(policy);
+        for name in [".gitignore", QUARTOIGNORE_FILE] {
+            if let Ok(content) = std::fs::read_to_string(project_root.join(name)) {
+                debug!(file = name, "Loaded ignore rules");
+                filter.add_rules(&content);
+            }
+        }
+        filter
+    }
+
+    /// Add rules in gitignore syntax. Later rules take precedence.
+    pub fn add_rules(&mut self, content: &str) {
+        self.rules
+            .extend(content.lines().filter_map(IgnoreRule::parse));
+    }
+
+    /// The size and binary-file limits.
+    pub fn policy(&self) -> &SyncPolicy {
+        &self.policy
+    }
+
+    /// Whether a single entry (not its ancestors) is ignored by the built-in
+    /// rules or the ignore files.
+    pub fn is_ignored(&self, relative: &Path, is_dir: bool) -> bool {
+        if is_dir
+            && relative.file_name().is_some_and(|name| {
+                let name = name.to_string_lossy();
+                name.starts_with('.') || IGNORED_DIRS.contains(&name.as_ref())
+            })
+        {
+            return true;
+        }
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(relative, is_dir))
+            .is_some_and(|rule| !rule.negated)
+    }
+
+    /// Whether a file is ignored, either itself or through one of its
+    /// directories.
+    pub fn is_path_ignored(&self, relative: &Path) -> bool {
+        if relative
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            return true;
+        }
+        relative
+            .ancestors()
+            .skip(1)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .any(|dir| self.is_ignored(dir, true))
+            || self.is_ignored(relative, false)
+    }
+
+    /// Whether a file of `size` bytes is within the size limit.
+    pub fn within_size_limit(&self, size: u64) -> bool {
+        self.policy.max_file_size.is_none_or(|max| size <= max)
+    }
+
+    /// Whether the binary-file policy allows a file with this extension.
+    pub fn allows_binary(&self, extension: &str) -> bool {
+        match self.policy.binary_files {
+            BinaryPolicy::All => true,
+            BinaryPolicy::Images => crate::resource::mime_type_from_extension(extension)
+                .is_some_and(|mime| mime.starts_with("image/")),
+            BinaryPolicy::None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(rules: &str) -> SyncFilter {
+        let mut filter = SyncFilter::default();
+        filter.add_rules(rules);
+        filter
+    }
+
+    #[test]
+    fn test_builtin_directories() {
+        let filter = SyncFilter::default();
+        assert!(filter.is_path_ignored(Path::new("_site/index.qmd")));
+        assert!(filter.is_path_ignored(Path::new(".venv/lib/x.qmd")));
+        assert!(filter.is_path_ignored(Path::new("docs/node_modules/a.qmd")));
+        assert!(!filter.is_path_ignored(Path::new("docs/index.qmd")));
+        assert!(filter.is_path_ignored(Path::new("../outside.qmd")));
+    }
+
+    #[test]
+    fn test_gitignore_syntax() {
+        let filter =
+            filter("# data\n*.csv\n/drafts\nbuild/\nnotes/*.qmd\n!notes/keep.qmd\n**/tmp/**\n");
+        // Unanchored patterns match at any depth
+        assert!(filter.is_path_ignored(Path::new("data.csv")));
+        assert!(filter.is_path_ignored(Path::new("a/b/data.csv")));
+        // Anchored patterns match from the root only
+        assert!(filter.is_path_ignored(Path::new("drafts/post.qmd")));
+        assert!(!filter.is_path_ignored(Path::new("blog/drafts/post.qmd")));
+        // Directory-only patterns
+        assert!(filter.is_path_ignored(Path::new("src/build/out.qmd")));
+        assert!(!filter.is_ignored(Path::new("build"), false));
+        // Negation re-includes
+        assert!(filter.is_path_ignored(Path::new("notes/a.qmd")));
+        assert!(!filter.is_path_ignored(Path::new("notes/keep.qmd")));
+        assert!(filter.is_path_ignored(Path::new("x/tmp/y.qmd")));
+    }
+
+    #[test]
+    fn test_policy_limits() {
+        let filter = SyncFilter::new(SyncPolicy {
+            max_file_size: Some(100),
+            binary_files: BinaryPolicy::Images,
+        });
+        assert!(filter.within_size_limit(100));
+        assert!(!filter.within_size_limit(101));
+        assert!(filter.allows_binary("png"));
+        assert!(!filter.allows_binary("pdf"));
+
+        let none = SyncFilter::new(SyncPolicy {
+            max_file_size: None,
+            binary_files: BinaryPolicy::None,
+        });
+        assert!(none.within_size_limit(u64::MAX));
+        assert!(!none.allows_binary("png"));
+
+        assert_eq!("images".parse(), Ok(BinaryPolicy::Images));
+        assert!("some".parse::<BinaryPolicy>().is_err());
+    }
+}
@@ -5,12 +5,27 @@
 //! - Automerge-based CRDT document management
 //! - WebSocket sync protocol for real-time collaboration
 //! - REST API for document operations
+//! - Token-based authentication with per-project roles
+//! - Cached server-side HTML previews of project documents
+//! - Comment and suggestion threads anchored to document source
+//! - Selective filesystem sync with `.gitignore`/`.quartoignore` rules
+//! - Hosting several project roots from one hub process
+//! - Resumable backfill of missed changes and compaction of stored documents
 
+pub mod auth;
+pub mod backfill;
+pub mod comments;
 pub mod context;
 pub mod discovery;
 pub mod error;
+pub mod export;
+pub mod history;
+pub mod ignore;
 pub mod index;
 pub mod peer;
+pub mod presence;
+pub mod projects;
+pub mod render;
 pub mod resource;
 pub mod server;
 pub mod storage;
@@ -22,6 +37,7 @@ pub mod watch;
 #[cfg(test)]
 mod automerge_api_tests;
 
+pub use auth::{AuthConfig, Role};
 pub use context::HubContext;
 pub use error::{Error, Result};
 pub use index::IndexDocument;
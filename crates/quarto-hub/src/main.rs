@@ -6,15 +6,25 @@ use clap::Parser;
 use tracing::info;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use quarto_hub::{StorageManager, context::HubConfig, server};
+use quarto_hub::ignore::{BinaryPolicy, SyncPolicy};
+use quarto_hub::projects::{ProjectRegistry, project_name};
+use quarto_hub::server::HubProject;
+use quarto_hub::{AuthConfig, StorageManager, context::HubConfig, server};
 
 #[derive(Parser, Debug)]
 #[command(name = "hub")]
 #[command(about = "Collaborative editing server for Quarto projects")]
 struct Args {
-    /// Project root directory (defaults to current directory)
-    #[arg(short, long)]
-    project: Option<PathBuf>,
+    /// Project root directory (defaults to current directory).
+    /// Can be specified multiple times to serve several projects, each
+    /// under /projects/{name} where name is the directory name.
+    #[arg(short, long = "project", value_name = "DIR")]
+    projects: Vec<PathBuf>,
+
+    /// JSON registry of projects to serve, each under /projects/{name}.
+    /// Combined with any --project directories.
+    #[arg(long, value_name = "PATH")]
+    projects_file: Option<PathBuf>,
 
     /// Port to listen on
     #[arg(short = 'P', long, default_value = "3000")]
@@ -45,6 +55,24 @@ struct Args {
     /// Default: 500ms.
     #[arg(long, default_value = "500")]
     watch_debounce: u64,
+
+    /// JSON file of bearer tokens and their roles (read-only, editor, admin).
+    /// When any token is configured, all REST and WebSocket requests must
+    /// authenticate. A token in QUARTO_HUB_AUTH_TOKEN is added with admin
+    /// access to every project.
+    #[arg(long, value_name = "PATH")]
+    auth_file: Option<PathBuf>,
+
+    /// Maximum size of synced files in MiB (0 for no limit).
+    /// Larger files are not turned into documents.
+    /// Default: 10 MiB.
+    #[arg(long, value_name = "MIB", default_value = "10")]
+    max_file_size: u64,
+
+    /// Which binary files to sync: all, images or none.
+    /// Default: all.
+    #[arg(long, value_name = "POLICY", default_value = "all")]
+    binary_files: BinaryPolicy,
 }
 
 #[tokio::main]
@@ -60,25 +88,74 @@ async fn main() -> anyhow::Result<()> {
 
     let args = Args::parse();
 
-    // Determine project root (canonicalize to ensure consistent paths for file watching)
-    let project_root = args
-        .project
-        .unwrap_or_else(|| std::env::current_dir().expect("Failed to get current directory"));
-    let project_root = project_root
-        .canonicalize()
-        .expect("Failed to canonicalize project root");
-
-    info!(project_root = %project_root.display(), "Starting hub");
+    // Load authentication tokens (shared by every project unless the
+    // registry gives a project its own token file)
+    let auth = AuthConfig::load_with_env(args.auth_file.as_deref())?;
+
+    if args.projects.len() <= 1 && args.projects_file.is_none() {
+        // Determine project root (canonicalize to ensure consistent paths for file watching)
+        let project_root =
+            args.projects.first().cloned().unwrap_or_else(|| {
+                std::env::current_dir().expect("Failed to get current directory")
+            });
+        let project_root = project_root
+            .canonicalize()
+            .expect("Failed to canonicalize project root");
+
+        info!(project_root = %project_root.display(), "Starting hub");
+
+        // Initialize storage (acquires lockfile)
+        let mut storage = StorageManager::new(&project_root)?;
+        let name = project_name(&project_root);
+        let config = project_config(&args, &mut storage, name, auth)?;
+
+        server::run_server(storage, config).await?;
+        return Ok(());
+    }
+
+    let mut registry = ProjectRegistry::from_roots(&args.projects)?;
+    if let Some(path) = &args.projects_file {
+        registry.extend(ProjectRegistry::load(path)?)?;
+    }
+
+    let mut projects = Vec::new();
+    for entry in registry.projects {
+        let project_root = entry.root.canonicalize().map_err(|e| {
+            anyhow::anyhow!("project '{}' ({}): {}", entry.name, entry.root.display(), e)
+        })?;
+        info!(project = %entry.name, project_root = %project_root.display(), "Adding project");
+
+        let mut storage = StorageManager::new(&project_root)?;
+        let auth = match &entry.auth_file {
+            Some(path) => AuthConfig::load_with_env(Some(path))?,
+            None => auth.clone(),
+        };
+        let config = project_config(&args, &mut storage, entry.name.clone(), auth)?;
+        projects.push(HubProject {
+            name: entry.name,
+            storage,
+            config,
+        });
+    }
+
+    server::run_projects(projects, &args.host, args.port).await?;
 
-    // Initialize storage (acquires lockfile)
-    let mut storage = StorageManager::new(&project_root)?;
+    Ok(())
+}
 
+/// Build the configuration of a project from the command line.
+fn project_config(
+    args: &Args,
+    storage: &mut StorageManager,
+    project: String,
+    auth: AuthConfig,
+) -> anyhow::Result<HubConfig> {
     // Determine peers: CLI peers override stored peers
     let peers = if !args.peers.is_empty() {
         // CLI peers provided - use them and persist
         storage.set_peers(args.peers.clone())?;
         info!(peers = ?args.peers, "Using peers from CLI (persisted to hub.json)");
-        args.peers
+        args.peers.clone()
     } else {
         // Use stored peers
         let stored_peers = storage.peers().to_vec();
@@ -88,23 +165,26 @@ async fn main() -> anyhow::Result<()> {
         stored_peers
     };
 
-    // Configure and run server
     let sync_interval_secs = if args.sync_interval == 0 {
         None
     } else {
         Some(args.sync_interval)
     };
 
-    let config = HubConfig {
+    Ok(HubConfig {
+        project,
         port: args.port,
-        host: args.host,
+        host: args.host.clone(),
         peers,
         sync_interval_secs,
         watch_enabled: !args.no_watch,
         watch_debounce_ms: args.watch_debounce,
-    };
-
-    server::run_server(storage, config).await?;
-
-    Ok(())
+        auth,
+        sync_policy: SyncPolicy {
+            max_file_size: (args.max_file_size > 0).then(|| args.max_file_size * 1024 * 1024),
+            binary_files: args.binary_files,
+        },
+        // The standalone binary has no render pipeline; use `quarto hub`
+        renderer: None,
+    })
 }
@@ -0,0 +1,390 @@
+//! Presence and cursor awareness.
+//!
+//! Clients announce who they are, which file they have open and where their
+//! cursor and selection are by broadcasting ephemeral messages on the
+//! document's automerge handle. samod relays these messages to every other
+//! peer that has the document open, so clients see each other without any
+//! help from the hub.
+//!
+//! The hub also listens on each document's ephemeral channel and keeps the
+//! latest presence per peer in a [`PresenceRegistry`], which backs the
+//! `/api/presence` endpoint (e.g. for showing who is active in a project
+//! without opening every document).
+//!
+//! Message payloads are CBOR maps (as encoded by automerge-repo's
+//! `handle.broadcast()`):
+//!
+//! ```json
+//! { "type": "presence", "peerId": "...", "userId": "...", "userName": "...",
+//!   "userColor": "#3498db", "filePath": "index.qmd",
+//!   "cursor": 42, "selection": { "start": 40, "end": 42 },
+//!   "cursorRef": "...", "selectionRef": { "start": "...", "end": "..." } }
+//! { "type": "leave", "peerId": "..." }
+//! ```
+//!
+//! `cursor`/`selection` are UTF-16 offsets into the text at the time the
+//! message was sent. `cursorRef`/`selectionRef` are automerge cursors for the
+//! same positions, which stay attached to the surrounding characters while
+//! the text is concurrently edited.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use samod::DocHandle;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, trace};
+
+/// How long a peer stays in the registry without sending an update.
+pub const PRESENCE_STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// A range of UTF-16 offsets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OffsetRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// A range of automerge cursors.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CursorRange {
+    pub start: String,
+    pub end: String,
+}
+
+/// An ephemeral presence message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PresenceMessage {
+    /// A peer's current identity and position
+    #[serde(rename_all = "camelCase")]
+    Presence {
+        peer_id: String,
+        user_id: String,
+        user_name: String,
+        user_color: String,
+        #[serde(default)]
+        file_path: Option<String>,
+        #[serde(default)]
+        cursor: Option<u64>,
+        #[serde(default)]
+        selection: Option<OffsetRange>,
+        #[serde(default)]
+        cursor_ref: Option<String>,
+        #[serde(default)]
+        selection_ref: Option<CursorRange>,
+    },
+    /// A peer closed the document
+    #[serde(rename_all = "camelCase")]
+    Leave { peer_id: String },
+}
+
+impl PresenceMessage {
+    /// Decode a CBOR-encoded ephemeral message.
+    ///
+    /// Returns `None` for payloads that aren't presence messages; the
+    /// ephemeral channel is shared with other features.
+    pub fn decode(payload: &[u8]) -> Option<Self> {
+        let mut decoder = minicbor::Decoder::new(payload);
+        let value = cbor_to_json(&mut decoder).ok()?;
+        serde_json::from_value(value).ok()
+    }
+}
+
+/// A peer's presence as reported by `/api/presence`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeerPresence {
+    pub peer_id: String,
+    pub user_id: String,
+    pub user_name: String,
+    pub user_color: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection: Option<OffsetRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_ref: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_ref: Option<CursorRange>,
+    /// Milliseconds since the last update from this peer
+    pub idle_ms: u64,
+}
+
+/// Active peers on one document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocumentPresence {
+    pub document_id: String,
+    pub peers: Vec<PeerPresence>,
+}
+
+/// Latest presence per peer, per document.
+#[derive(Debug, Default)]
+pub struct PresenceRegistry {
+    documents: Mutex<HashMap<String, HashMap<String, (PresenceMessage, Instant)>>>,
+}
+
+impl PresenceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a presence message received on `document_id`.
+    pub fn apply(&self, document_id: &str, message: PresenceMessage) {
+        self.apply_at(document_id, message, Instant::now());
+    }
+
+    fn apply_at(&self, document_id: &str, message: PresenceMessage, now: Instant) {
+        let mut documents = self.documents.lock().unwrap();
+        match &message {
+            PresenceMessage::Presence { peer_id, .. } => {
+                documents
+                    .entry(document_id.to_string())
+                    .or_default()
+                    .insert(peer_id.clone(), (message.clone(), now));
+            }
+            PresenceMessage::Leave { peer_id } => {
+                if let Some(peers) = documents.get_mut(document_id) {
+                    peers.remove(peer_id);
+                    if peers.is_empty() {
+                        documents.remove(document_id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Current presence on all documents, dropping peers that have been
+    /// silent for longer than `stale_after`.
+    pub fn snapshot(&self, stale_after: Duration) -> Vec<DocumentPresence> {
+        self.snapshot_at(stale_after, Instant::now())
+    }
+
+    fn snapshot_at(&self, stale_after: Duration, now: Instant) -> Vec<DocumentPresence> {
+        let mut documents = self.documents.lock().unwrap();
+        documents.retain(|_, peers| {
+            peers.retain(|_, (_, seen)| now.duration_since(*seen) <= stale_after);
+            !peers.is_empty()
+        });
+
+        let mut result: Vec<DocumentPresence> = documents
+            .iter()
+            .map(|(document_id, peers)| {
+                let mut peers: Vec<PeerPresence> = peers
+                    .values()
+                    .filter_map(|(message, seen)| peer_presence(message, now.duration_since(*seen)))
+                    .collect();
+                peers.sort_by(|a, b| a.peer_id.cmp(&b.peer_id));
+                DocumentPresence {
+                    document_id: document_id.clone(),
+                    peers,
+                }
+            })
+            .collect();
+        result.sort_by(|a, b| a.document_id.cmp(&b.document_id));
+        result
+    }
+}
+
+fn peer_presence(message: &PresenceMessage, idle: Duration) -> Option<PeerPresence> {
+    let PresenceMessage::Presence {
+        peer_id,
+        user_id,
+        user_name,
+        user_color,
+        file_path,
+        cursor,
+        selection,
+        cursor_ref,
+        selection_ref,
+    } = message.clone()
+    else {
+        return None;
+    };
+    Some(PeerPresence {
+        peer_id,
+        user_id,
+        user_name,
+        user_color,
+        file_path,
+        cursor,
+        selection,
+        cursor_ref,
+        selection_ref,
+        idle_ms: idle.as_millis() as u64,
+    })
+}
+
+/// Spawn a background task that feeds a document's ephemeral messages into
+/// the registry. The task ends when the document's actor shuts down.
+pub fn spawn_presence_listener(handle: DocHandle, registry: Arc<PresenceRegistry>) {
+    tokio::spawn(async move {
+        let document_id = handle.document_id().to_string();
+        let mut messages = std::pin::pin!(handle.ephemera());
+        while let Some(payload) = messages.next().await {
+            match PresenceMessage::decode(&payload) {
+                Some(message) => {
+                    trace!(document_id = %document_id, message = ?message, "Presence update");
+                    registry.apply(&document_id, message);
+                }
+                None => {
+                    trace!(document_id = %document_id, "Ignoring non-presence ephemeral message")
+                }
+            }
+        }
+        debug!(document_id = %document_id, "Presence listener stopped");
+    });
+}
+
+/// Convert a CBOR value to JSON.
+///
+/// Only the subset of CBOR produced for plain JS objects is supported:
+/// definite-length maps with string keys, arrays, strings, numbers, booleans
+/// and null. Tags are unwrapped and byte strings become arrays of numbers.
+fn cbor_to_json(decoder: &mut minicbor::Decoder<'_>) -> Result<serde_json::Value, String> {
+    use minicbor::data::Type;
+    use serde_json::Value;
+
+    let err = |e: minicbor::decode::Error| e.to_string();
+    Ok(match decoder.datatype().map_err(err)? {
+        Type::Bool => Value::Bool(decoder.bool().map_err(err)?),
+        Type::Null | Type::Undefined => {
+            decoder.skip().map_err(err)?;
+            Value::Null
+        }
+        Type::U8 | Type::U16 | Type::U32 | Type::U64 => decoder.u64().map_err(err)?.into(),
+        Type::I8 | Type::I16 | Type::I32 | Type::I64 => decoder.i64().map_err(err)?.into(),
+        Type::F32 => f64::from(decoder.f32().map_err(err)?).into(),
+        Type::F64 => decoder.f64().map_err(err)?.into(),
+        Type::String => Value::String(decoder.str().map_err(err)?.to_string()),
+        Type::Bytes => decoder.bytes().map_err(err)?.to_vec().into(),
+        Type::Array => {
+            let len = decoder.array().map_err(err)?.ok_or("indefinite array")?;
+            let mut items = Vec::new();
+            for _ in 0..len {
+                items.push(cbor_to_json(decoder)?);
+            }
+            Value::Array(items)
+        }
+        Type::Map => {
+            let len = decoder.map().map_err(err)?.ok_or("indefinite map")?;
+            let mut map = serde_json::Map::new();
+            for _ in 0..len {
+                let key = decoder.str().map_err(err)?.to_string();
+                map.insert(key, cbor_to_json(decoder)?);
+            }
+            Value::Object(map)
+        }
+        Type::Tag => {
+            decoder.tag().map_err(err)?;
+            cbor_to_json(decoder)?
+        }
+        other => return Err(format!("unsupported CBOR type: {}", other)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_presence(peer: &str, file: &str, cursor: u64) -> Vec<u8> {
+        let mut encoder = minicbor::Encoder::new(Vec::new());
+        encoder.map(8).unwrap();
+        encoder.str("type").unwrap().str("presence").unwrap();
+        encoder.str("peerId").unwrap().str(peer).unwrap();
+        encoder.str("userId").unwrap().str("user-1").unwrap();
+        encoder.str("userName").unwrap().str("Ada").unwrap();
+        encoder.str("userColor").unwrap().str("#3498db").unwrap();
+        encoder.str("filePath").unwrap().str(file).unwrap();
+        encoder.str("cursor").unwrap().u64(cursor).unwrap();
+        encoder.str("selection").unwrap().null().unwrap();
+        encoder.into_writer()
+    }
+
+    fn presence(peer: &str) -> PresenceMessage {
+        PresenceMessage::decode(&encode_presence(peer, "index.qmd", 3)).unwrap()
+    }
+
+    #[test]
+    fn test_decode_presence() {
+        let message = presence("peer-a");
+        let PresenceMessage::Presence {
+            peer_id,
+            file_path,
+            cursor,
+            selection,
+            cursor_ref,
+            ..
+        } = message
+        else {
+            panic!("expected presence message");
+        };
+        assert_eq!(peer_id, "peer-a");
+        assert_eq!(file_path.as_deref(), Some("index.qmd"));
+        assert_eq!(cursor, Some(3));
+        assert_eq!(selection, None);
+        assert_eq!(cursor_ref, None);
+    }
+
+    #[test]
+    fn test_decode_leave_and_other_messages() {
+        let mut encoder = minicbor::Encoder::new(Vec::new());
+        encoder.map(2).unwrap();
+        encoder.str("type").unwrap().str("leave").unwrap();
+        encoder.str("peerId").unwrap().str("peer-a").unwrap();
+        assert_eq!(
+            PresenceMessage::decode(&encoder.into_writer()),
+            Some(PresenceMessage::Leave {
+                peer_id: "peer-a".to_string()
+            })
+        );
+
+        let mut encoder = minicbor::Encoder::new(Vec::new());
+        encoder.map(1).unwrap();
+        encoder.str("type").unwrap().str("comment-typing").unwrap();
+        assert_eq!(PresenceMessage::decode(&encoder.into_writer()), None);
+        assert_eq!(PresenceMessage::decode(&[0xff]), None);
+    }
+
+    #[test]
+    fn test_registry_tracks_and_removes_peers() {
+        let registry = PresenceRegistry::new();
+        registry.apply("doc-1", presence("peer-a"));
+        registry.apply("doc-1", presence("peer-b"));
+
+        let snapshot = registry.snapshot(PRESENCE_STALE_AFTER);
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].peers.len(), 2);
+        assert_eq!(snapshot[0].peers[0].user_name, "Ada");
+
+        registry.apply(
+            "doc-1",
+            PresenceMessage::Leave {
+                peer_id: "peer-a".to_string(),
+            },
+        );
+        registry.apply(
+            "doc-1",
+            PresenceMessage::Leave {
+                peer_id: "peer-b".to_string(),
+            },
+        );
+        assert!(registry.snapshot(PRESENCE_STALE_AFTER).is_empty());
+    }
+
+    #[test]
+    fn test_registry_drops_stale_peers() {
+        let registry = PresenceRegistry::new();
+        let start = Instant::now();
+        registry.apply_at("doc-1", presence("peer-a"), start);
+        registry.apply_at("doc-1", presence("peer-b"), start + Duration::from_secs(20));
+
+        let later = start + Duration::from_secs(40);
+        let snapshot = registry.snapshot_at(PRESENCE_STALE_AFTER, later);
+        assert_eq!(snapshot[0].peers.len(), 1);
+        assert_eq!(snapshot[0].peers[0].peer_id, "peer-b");
+        assert_eq!(snapshot[0].peers[0].idle_ms, 20_000);
+    }
+}
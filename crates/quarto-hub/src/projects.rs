@@ -0,0 +1,214 @@
+//! Hosting several projects in one hub.
+//!
+//! A hub started with more than one project (`--project` given repeatedly,
+//! or a registry file) serves each of them under its own prefix:
+//!
+//! ```text
+//! /projects/{name}/api/...   REST API of the project
+//! /projects/{name}/ws        automerge sync (also /projects/{name}/)
+//! /api/projects              projects visible to the caller's token
+//! ```
+//!
+//! Every project keeps its own storage (`<root>/.quarto/hub/`), samod repo,
+//! sync state, watcher and authentication tokens, exactly as when it is
+//! hosted alone.
+//!
+//! The registry file is JSON:
+//!
+//! ```json
+//! {
+//!   "projects": [
+//!     { "name": "docs", "root": "../docs" },
+//!     { "name": "blog", "root": "/srv/blog", "authFile": "/etc/hub/blog-tokens.json" }
+//!   ]
+//! }
+//! ```
+//!
+//! Relative paths are resolved against the directory of the registry file.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+
+/// A project served by the hub.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectEntry {
+    /// URL-safe name, used as the route prefix
+    pub name: String,
+
+    /// Project root directory
+    pub root: PathBuf,
+
+    /// Token file for this project, replacing the hub-wide tokens
+    #[serde(default)]
+    pub auth_file: Option<PathBuf>,
+}
+
+/// The projects served by the hub.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ProjectRegistry {
+    pub projects: Vec<ProjectEntry>,
+}
+
+impl ProjectRegistry {
+    /// Load a registry file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let mut registry: Self = serde_json::from_str(&content)
+            .map_err(|e| Error::ProjectRegistry(format!("{}: {}", path.display(), e)))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        for project in &mut registry.projects {
+            project.root = base.join(&project.root);
+            if let Some(auth_file) = &project.auth_file {
+                project.auth_file = Some(base.join(auth_file));
+            }
+        }
+        registry.validate()?;
+        Ok(registry)
+    }
+
+    /// Build a registry from project roots, naming each project after its
+    /// directory (with a numeric suffix if two directories share a name).
+    pub fn from_roots(roots: &[PathBuf]) -> Result<Self> {
+        let mut used = HashSet::new();
+        let mut projects = Vec::new();
+        for root in roots {
+            let base = project_name(root);
+            let mut name = base.clone();
+            let mut n = 2;
+            while !used.insert(name.clone()) {
+                name = format!("{}-{}", base, n);
+                n += 1;
+            }
+            projects.push(ProjectEntry {
+                name,
+                root: root.clone(),
+                auth_file: None,
+            });
+        }
+        let registry = Self { projects };
+        registry.validate()?;
+        Ok(registry)
+    }
+
+    /// Add the projects of another registry.
+    pub fn extend(&mut self, other: ProjectRegistry) -> Result<()> {
+        self.projects.extend(other.projects);
+        self.validate()
+    }
+
+    /// Check that names are valid route segments and unique.
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for project in &self.projects {
+            if !is_valid_name(&project.name) {
+                return Err(Error::ProjectRegistry(format!(
+                    "invalid project name '{}' (use letters, digits, '-', '_' and '.')",
+                    project.name
+                )));
+            }
+            if !names.insert(project.name.as_str()) {
+                return Err(Error::ProjectRegistry(format!(
+                    "duplicate project name '{}'",
+                    project.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Default name of the project at `root`: its directory name, made URL-safe.
+pub fn project_name(root: &Path) -> String {
+    root.file_name()
+        .map(|name| slugify(&name.to_string_lossy()))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "project".to_string())
+}
+
+/// Turn a directory name into a project name.
+fn slugify(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    slug.trim_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_from_roots_names_projects_after_directories() {
+        let registry = ProjectRegistry::from_roots(&[
+            PathBuf::from("/work/docs"),
+            PathBuf::from("/other/docs"),
+            PathBuf::from("/work/My Blog"),
+        ])
+        .unwrap();
+        let names: Vec<_> = registry.projects.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["docs", "docs-2", "My-Blog"]);
+    }
+
+    #[test]
+    fn test_load_resolves_relative_paths() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("projects.json");
+        std::fs::write(
+            &path,
+            r#"{"projects": [
+                {"name": "docs", "root": "docs"},
+                {"name": "blog", "root": "/srv/blog", "authFile": "blog-tokens.json"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let registry = ProjectRegistry::load(&path).unwrap();
+        assert_eq!(registry.projects[0].root, temp.path().join("docs"));
+        assert_eq!(registry.projects[0].auth_file, None);
+        assert_eq!(registry.projects[1].root, PathBuf::from("/srv/blog"));
+        assert_eq!(
+            registry.projects[1].auth_file,
+            Some(temp.path().join("blog-tokens.json"))
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_and_duplicate_names() {
+        let entry = |name: &str| ProjectEntry {
+            name: name.to_string(),
+            root: PathBuf::from("/p"),
+            auth_file: None,
+        };
+        let registry = |names: &[&str]| ProjectRegistry {
+            projects: names.iter().map(|n| entry(n)).collect(),
+        };
+
+        assert!(registry(&["docs", "blog"]).validate().is_ok());
+        assert!(registry(&["docs", "docs"]).validate().is_err());
+        assert!(registry(&["a/b"]).validate().is_err());
+        assert!(registry(&[".."]).validate().is_err());
+        assert!(registry(&[""]).validate().is_err());
+    }
+}
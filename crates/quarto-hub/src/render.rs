@@ -0,0 +1,263 @@
+//! Server-side rendering of project documents
+//!
+//! Thin clients (mobile, review links) can ask the hub for an HTML preview of
+//! a document instead of loading the WASM renderer. The hub renders the
+//! current Automerge state of the document through a [`DocumentRenderer`]
+//! supplied by the embedding binary (the `quarto` CLI provides the native
+//! pipeline), and caches the output keyed by the document heads.
+//!
+//! Cache entries are keyed by the heads of the document and of the project
+//! config documents, so a stale entry is never served. Listeners on each
+//! document additionally drop entries as soon as a change arrives, so the
+//! cache does not hold on to outdated output.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use futures::StreamExt;
+use samod::DocHandle;
+use tracing::{debug, trace};
+
+/// A request to render one document of the project.
+#[derive(Debug, Clone)]
+pub struct RenderRequest {
+    /// Absolute path of the project root
+    pub project_root: PathBuf,
+
+    /// Path of the document, relative to the project root
+    pub path: String,
+
+    /// Current content of the document (from its Automerge state)
+    pub content: String,
+
+    /// Directory to write the output and its resources to.
+    /// Resources must be written relative to `<output_dir>/<path>` the way
+    /// they are relative to the document, so links in the HTML resolve when
+    /// the hub serves them from `/api/render/`.
+    pub output_dir: PathBuf,
+}
+
+/// The result of rendering a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedPage {
+    /// The rendered HTML
+    pub html: String,
+
+    /// Warnings and errors reported by the pipeline, as plain text
+    pub diagnostics: Vec<String>,
+}
+
+/// Renders a document to HTML.
+///
+/// Rendering is synchronous and may be slow; the hub calls it from a
+/// blocking task.
+pub trait DocumentRenderer: Send + Sync + std::fmt::Debug {
+    fn render(&self, request: &RenderRequest) -> std::result::Result<RenderedPage, String>;
+}
+
+/// Whether a path names a document the hub can render.
+pub fn is_renderable(path: &str) -> bool {
+    Path::new(path).extension().is_some_and(|ext| ext == "qmd")
+}
+
+/// Whether a path names a project config document, whose changes affect the
+/// rendering of every document.
+pub fn is_config_path(path: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .is_some_and(|name| name == "_quarto.yml" || name == "_quarto.yaml")
+}
+
+/// Resolve a request path to a file under `output_dir`, rejecting paths that
+/// would escape it.
+pub fn resolve_output_path(output_dir: &Path, path: &str) -> Option<PathBuf> {
+    use std::path::Component;
+
+    let relative = Path::new(path);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+    Some(output_dir.join(relative))
+}
+
+/// Content type of a rendered output resource (stylesheets, scripts, fonts
+/// and images written next to the rendered HTML).
+pub fn resource_content_type(path: &str, content: &[u8]) -> String {
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase);
+    match ext.as_deref() {
+        Some("html") => "text/html; charset=utf-8".to_string(),
+        Some("css") => "text/css; charset=utf-8".to_string(),
+        Some("js" | "mjs") => "text/javascript; charset=utf-8".to_string(),
+        Some("json" | "map") => "application/json".to_string(),
+        Some("svg") => "image/svg+xml".to_string(),
+        Some("woff") => "font/woff".to_string(),
+        Some("woff2") => "font/woff2".to_string(),
+        _ => crate::resource::detect_mime_type(content, Some(path)),
+    }
+}
+
+/// Build the cache key of a render from the heads of the document and of the
+/// project config documents.
+pub fn cache_key(document_heads: &[String], config_heads: &[(String, Vec<String>)]) -> String {
+    let mut key = document_heads.join(",");
+    let mut config_heads = config_heads.to_vec();
+    config_heads.sort();
+    for (path, heads) in config_heads {
+        key.push('|');
+        key.push_str(&path);
+        key.push('=');
+        key.push_str(&heads.join(","));
+    }
+    key
+}
+
+/// Rendered pages by document path, with the key they were rendered at.
+#[derive(Debug, Default)]
+pub struct RenderCache {
+    entries: Mutex<HashMap<String, (String, Arc<RenderedPage>)>>,
+}
+
+impl RenderCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cached page for `path`, if it was rendered at `key`.
+    pub fn get(&self, path: &str, key: &str) -> Option<Arc<RenderedPage>> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(path)
+            .filter(|(cached_key, _)| cached_key == key)
+            .map(|(_, page)| page.clone())
+    }
+
+    /// Store the page rendered for `path` at `key`.
+    pub fn insert(&self, path: &str, key: String, page: Arc<RenderedPage>) {
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), (key, page));
+    }
+
+    /// Drop the cached page of `path`.
+    pub fn invalidate(&self, path: &str) {
+        self.entries.lock().unwrap().remove(path);
+    }
+
+    /// Drop all cached pages.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Number of cached pages.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Spawn a task that drops cached renders when the document at `path`
+/// changes. Changes to a config document clear the whole cache.
+pub fn spawn_render_invalidator(handle: DocHandle, path: String, cache: Arc<RenderCache>) {
+    tokio::spawn(async move {
+        let config = is_config_path(&path);
+        let mut changes = std::pin::pin!(handle.changes());
+        while changes.next().await.is_some() {
+            trace!(path = %path, "Invalidating cached render");
+            if config {
+                cache.clear();
+            } else {
+                cache.invalidate(&path);
+            }
+        }
+        debug!(path = %path, "Render invalidator stopped");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(html: &str) -> Arc<RenderedPage> {
+        Arc::new(RenderedPage {
+            html: html.to_string(),
+            diagnostics: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn test_renderable_and_config_paths() {
+        assert!(is_renderable("index.qmd"));
+        assert!(is_renderable("posts/a.qmd"));
+        assert!(!is_renderable("_quarto.yml"));
+        assert!(!is_renderable("image.png"));
+
+        assert!(is_config_path("_quarto.yml"));
+        assert!(is_config_path("sub/_quarto.yaml"));
+        assert!(!is_config_path("index.qmd"));
+    }
+
+    #[test]
+    fn test_resolve_output_path_stays_in_output_dir() {
+        let dir = Path::new("/hub/render");
+        assert_eq!(
+            resolve_output_path(dir, "posts/a_files/bootstrap.css"),
+            Some(PathBuf::from("/hub/render/posts/a_files/bootstrap.css"))
+        );
+        assert_eq!(resolve_output_path(dir, "../secret"), None);
+        assert_eq!(resolve_output_path(dir, "a/../../secret"), None);
+        assert_eq!(resolve_output_path(dir, "/etc/passwd"), None);
+    }
+
+    #[test]
+    fn test_cache_key_is_independent_of_config_order() {
+        let doc = vec!["a1".to_string()];
+        let root = ("_quarto.yml".to_string(), vec!["c1".to_string()]);
+        let sub = ("sub/_quarto.yml".to_string(), vec!["c2".to_string()]);
+
+        let key = cache_key(&doc, &[root.clone(), sub.clone()]);
+        assert_eq!(key, cache_key(&doc, &[sub, root.clone()]));
+        assert_ne!(key, cache_key(&doc, &[root]));
+        assert_ne!(key, cache_key(&["a2".to_string()], &[]));
+    }
+
+    #[test]
+    fn test_cache_hits_only_at_same_key() {
+        let cache = RenderCache::new();
+        cache.insert("index.qmd", "k1".to_string(), page("<p>one</p>"));
+
+        assert_eq!(cache.get("index.qmd", "k1").unwrap().html, "<p>one</p>");
+        assert!(cache.get("index.qmd", "k2").is_none());
+        assert!(cache.get("other.qmd", "k1").is_none());
+
+        // A newer render replaces the entry
+        cache.insert("index.qmd", "k2".to_string(), page("<p>two</p>"));
+        assert!(cache.get("index.qmd", "k1").is_none());
+        assert_eq!(cache.get("index.qmd", "k2").unwrap().html, "<p>two</p>");
+    }
+
+    #[test]
+    fn test_cache_invalidation() {
+        let cache = RenderCache::new();
+        cache.insert("a.qmd", "k".to_string(), page("a"));
+        cache.insert("b.qmd", "k".to_string(), page("b"));
+
+        cache.invalidate("a.qmd");
+        assert!(cache.get("a.qmd", "k").is_none());
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -5,24 +5,32 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use axum::{
-    Json, Router,
+    Extension, Json, Router,
     extract::{
-        Path, State,
-        ws::{WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
     },
-    http::StatusCode,
+    http::{StatusCode, header},
+    middleware,
     response::IntoResponse,
-    routing::get,
+    routing::{get, post},
 };
-use samod::DocumentId;
+use futures::StreamExt;
+use samod::{DocHandle, DocumentId};
 use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
 use tokio::sync::watch;
 use tower_http::trace::TraceLayer;
 use tracing::{debug, info};
 
+use crate::auth::{Principal, Role, filter_read_only_message, request_token, require_auth};
+use crate::backfill;
+use crate::comments::{self, CommentThread, NewThread};
 use crate::context::{HubConfig, HubContext, SharedContext};
 use crate::error::Result;
+use crate::history::{self, ChangeInfo, DiffEntry, Snapshot};
+use crate::presence::{PRESENCE_STALE_AFTER, PeerPresence};
+use crate::render::{self, RenderRequest};
 use crate::storage::StorageManager;
 use crate::watch::{FileWatcher, WatchConfig, WatchEvent};
 
@@ -77,6 +85,132 @@ struct UpdateDocumentRequest {
     value: String,
 }
 
+/// Active peers on a document
+#[derive(Serialize)]
+struct DocumentPresenceEntry {
+    document_id: String,
+    path: Option<String>,
+    peers: Vec<PeerPresence>,
+}
+
+/// Presence across the project
+#[derive(Serialize)]
+struct PresenceResponse {
+    documents: Vec<DocumentPresenceEntry>,
+}
+
+/// Document history response
+#[derive(Serialize)]
+struct HistoryResponse {
+    document_id: String,
+    heads: Vec<String>,
+    changes: Vec<ChangeInfo>,
+}
+
+/// Document content at a point in history
+#[derive(Serialize)]
+struct SnapshotResponse {
+    document_id: String,
+    heads: Vec<String>,
+    #[serde(flatten)]
+    snapshot: Snapshot,
+}
+
+/// Structured diff between two versions
+#[derive(Serialize)]
+struct DiffResponse {
+    document_id: String,
+    from: Vec<String>,
+    to: Vec<String>,
+    changes: Vec<DiffEntry>,
+}
+
+/// Query parameters selecting a version (comma-separated change hashes).
+/// A missing `heads` means the current version.
+#[derive(Deserialize)]
+struct SnapshotQuery {
+    heads: Option<String>,
+}
+
+/// Query parameters for a diff. A missing `to` means the current version.
+#[derive(Deserialize)]
+struct DiffQuery {
+    from: String,
+    to: Option<String>,
+}
+
+/// Restore document request
+#[derive(Deserialize)]
+struct RestoreRequest {
+    heads: Vec<String>,
+}
+
+/// Summary of an export of all documents to the working tree
+#[derive(Serialize)]
+struct ExportResponse {
+    /// Files rewritten from their documents
+    written: usize,
+    /// Files whose local edits were merged into their documents
+    merged: usize,
+    /// Files whose edits were taken into their documents unchanged
+    imported: usize,
+    unchanged: usize,
+    skipped: usize,
+    errors: Vec<ExportErrorEntry>,
+}
+
+#[derive(Serialize)]
+struct ExportErrorEntry {
+    path: String,
+    error: String,
+}
+
+/// Comment threads of a document
+#[derive(Serialize)]
+struct CommentsResponse {
+    document_id: String,
+    threads: Vec<CommentThread>,
+}
+
+/// New comment thread request. `start`/`end` are Unicode code point offsets.
+///
+/// The author of this and the other comment requests is the authenticated
+/// principal (see [`Principal::author`]); a client-supplied `author` must
+/// match it.
+#[derive(Deserialize)]
+struct CreateCommentRequest {
+    start: usize,
+    end: usize,
+    author: Option<String>,
+    body: String,
+    suggestion: Option<String>,
+}
+
+/// Reply to a comment thread
+#[derive(Deserialize)]
+struct ReplyRequest {
+    author: Option<String>,
+    body: String,
+}
+
+/// Resolve, reopen or accept a comment thread
+#[derive(Deserialize)]
+struct ThreadActionRequest {
+    author: Option<String>,
+}
+
+/// Query parameters for a render. `comments=false` hides open threads.
+#[derive(Deserialize)]
+struct RenderQuery {
+    comments: Option<bool>,
+}
+
+/// Query parameters for a backfill: the heads the client already has
+#[derive(Deserialize)]
+struct ChangesQuery {
+    since: Option<String>,
+}
+
 /// Health check endpoint
 async fn health(State(ctx): State<SharedContext>) -> impl IntoResponse {
     let response = HealthResponse {
@@ -170,13 +304,25 @@ async fn get_document(
 ///
 /// This is a simple endpoint that puts a key-value pair into the document.
 /// In a real implementation, the document schema would be more structured.
+/// Requires the editor role.
 async fn update_document(
     State(ctx): State<SharedContext>,
+    Extension(role): Extension<Role>,
     Path(doc_id_str): Path<String>,
     Json(request): Json<UpdateDocumentRequest>,
 ) -> impl IntoResponse {
     use automerge::{ROOT, transaction::Transactable};
 
+    if !role.can_write() {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse {
+                error: "Read-only access".to_string(),
+            }),
+        )
+            .into_response();
+    }
+
     // Validate the document ID format
     let doc_id = match DocumentId::from_str(&doc_id_str) {
         Ok(id) => id,
@@ -236,6 +382,559 @@ async fn update_document(
     }
 }
 
+/// Build a JSON error response.
+fn error_response(status: StatusCode, error: impl Into<String>) -> axum::response::Response {
+    (
+        status,
+        Json(ErrorResponse {
+            error: error.into(),
+        }),
+    )
+        .into_response()
+}
+
+/// Look up a document handle by its string ID, mapping failures to responses.
+async fn find_document(
+    ctx: &HubContext,
+    doc_id_str: &str,
+) -> std::result::Result<DocHandle, axum::response::Response> {
+    let doc_id = DocumentId::from_str(doc_id_str)
+        .map_err(|_| error_response(StatusCode::BAD_REQUEST, "Invalid document ID format"))?;
+    match ctx.repo().find(doc_id).await {
+        Ok(Some(handle)) => Ok(handle),
+        Ok(None) => Err(error_response(StatusCode::NOT_FOUND, "Document not found")),
+        Err(_stopped) => Err(error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Repository is stopped",
+        )),
+    }
+}
+
+/// Split a comma-separated list of change hashes.
+fn split_heads(heads: &str) -> Vec<String> {
+    heads
+        .split(',')
+        .map(str::trim)
+        .filter(|h| !h.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn heads_to_strings(heads: &[automerge::ChangeHash]) -> Vec<String> {
+    heads.iter().map(|h| h.to_string()).collect()
+}
+
+/// List who is active on which document
+async fn list_presence(State(ctx): State<SharedContext>) -> impl IntoResponse {
+    let paths: std::collections::HashMap<String, String> = ctx
+        .index()
+        .get_all_files()
+        .into_iter()
+        .map(|(path, id)| (id, path))
+        .collect();
+    let documents = ctx
+        .presence()
+        .snapshot(PRESENCE_STALE_AFTER)
+        .into_iter()
+        .map(|doc| DocumentPresenceEntry {
+            path: paths.get(&doc.document_id).cloned(),
+            document_id: doc.document_id,
+            peers: doc.peers,
+        })
+        .collect();
+    Json(PresenceResponse { documents })
+}
+
+/// List the changes in a document's history
+async fn document_history(
+    State(ctx): State<SharedContext>,
+    Path(doc_id_str): Path<String>,
+) -> impl IntoResponse {
+    let handle = match find_document(&ctx, &doc_id_str).await {
+        Ok(handle) => handle,
+        Err(response) => return response,
+    };
+    let (heads, changes) =
+        handle.with_document(|doc| (doc.get_heads(), history::list_changes(doc)));
+    Json(HistoryResponse {
+        document_id: doc_id_str,
+        heads: heads_to_strings(&heads),
+        changes,
+    })
+    .into_response()
+}
+
+/// Stream the changes a reconnecting client is missing.
+///
+/// The body is a sequence of length-prefixed changes (see
+/// [`crate::backfill`]). Headers report the document heads the backfill
+/// brings the client to and any client heads the hub does not have.
+async fn document_changes(
+    State(ctx): State<SharedContext>,
+    Path(doc_id_str): Path<String>,
+    Query(query): Query<ChangesQuery>,
+) -> impl IntoResponse {
+    let handle = match find_document(&ctx, &doc_id_str).await {
+        Ok(handle) => handle,
+        Err(response) => return response,
+    };
+    let since = match backfill::parse_since(&split_heads(query.since.as_deref().unwrap_or(""))) {
+        Ok(since) => since,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    };
+    let plan = handle.with_document(|doc| backfill::plan(doc, &since));
+    debug!(
+        document_id = %doc_id_str,
+        changes = plan.changes.len(),
+        unknown_heads = plan.unknown.len(),
+        "Backfilling document changes"
+    );
+
+    let headers = [
+        (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+        (
+            header::HeaderName::from_static("x-quarto-heads"),
+            heads_to_strings(&plan.heads).join(","),
+        ),
+        (
+            header::HeaderName::from_static("x-quarto-unknown-heads"),
+            heads_to_strings(&plan.unknown).join(","),
+        ),
+        (
+            header::HeaderName::from_static("x-quarto-change-count"),
+            plan.changes.len().to_string(),
+        ),
+    ];
+    let body = axum::body::Body::from_stream(backfill::stream_changes(handle, plan.changes));
+    (headers, body).into_response()
+}
+
+/// Get a document's content at a given version
+async fn document_snapshot(
+    State(ctx): State<SharedContext>,
+    Path(doc_id_str): Path<String>,
+    Query(query): Query<SnapshotQuery>,
+) -> impl IntoResponse {
+    let handle = match find_document(&ctx, &doc_id_str).await {
+        Ok(handle) => handle,
+        Err(response) => return response,
+    };
+    let result = handle.with_document(|doc| {
+        let heads = match &query.heads {
+            Some(heads) => history::parse_heads(doc, &split_heads(heads))?,
+            None => doc.get_heads(),
+        };
+        Ok::<_, crate::error::Error>((history::snapshot_at(doc, &heads)?, heads))
+    });
+    match result {
+        Ok((snapshot, heads)) => Json(SnapshotResponse {
+            document_id: doc_id_str,
+            heads: heads_to_strings(&heads),
+            snapshot,
+        })
+        .into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+/// Compute a structured diff between two versions of a document
+async fn document_diff(
+    State(ctx): State<SharedContext>,
+    Path(doc_id_str): Path<String>,
+    Query(query): Query<DiffQuery>,
+) -> impl IntoResponse {
+    let handle = match find_document(&ctx, &doc_id_str).await {
+        Ok(handle) => handle,
+        Err(response) => return response,
+    };
+    let result = handle.with_document(|doc| {
+        let from = history::parse_heads(doc, &split_heads(&query.from))?;
+        let to = match &query.to {
+            Some(to) => history::parse_heads(doc, &split_heads(to))?,
+            None => doc.get_heads(),
+        };
+        let changes = history::diff(doc, &from, &to);
+        Ok::<_, crate::error::Error>((from, to, changes))
+    });
+    match result {
+        Ok((from, to, changes)) => Json(DiffResponse {
+            document_id: doc_id_str,
+            from: heads_to_strings(&from),
+            to: heads_to_strings(&to),
+            changes,
+        })
+        .into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+/// Restore a document to an earlier version (requires the editor role).
+///
+/// The old content is applied as a new change, so connected clients receive
+/// it through normal sync and the restore can itself be undone.
+async fn restore_document(
+    State(ctx): State<SharedContext>,
+    Extension(role): Extension<Role>,
+    Path(doc_id_str): Path<String>,
+    Json(request): Json<RestoreRequest>,
+) -> impl IntoResponse {
+    if !role.can_write() {
+        return error_response(StatusCode::FORBIDDEN, "Read-only access");
+    }
+    let handle = match find_document(&ctx, &doc_id_str).await {
+        Ok(handle) => handle,
+        Err(response) => return response,
+    };
+    let result = handle.with_document(|doc| {
+        let heads = history::parse_heads(doc, &request.heads)?;
+        history::restore(doc, &heads)
+    });
+    match result {
+        Ok(heads) => {
+            info!(document_id = %doc_id_str, restored_to = ?request.heads, "Restored document");
+            Json(serde_json::json!({
+                "status": "restored",
+                "document_id": doc_id_str,
+                "heads": heads_to_strings(&heads),
+            }))
+            .into_response()
+        }
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+/// Write all documents back to the project files (requires the editor role).
+///
+/// Local edits made on disk are merged into the documents rather than
+/// overwritten, so this is safe to run before committing the working tree.
+async fn export_documents(
+    State(ctx): State<SharedContext>,
+    Extension(role): Extension<Role>,
+) -> impl IntoResponse {
+    if !role.can_write() {
+        return error_response(StatusCode::FORBIDDEN, "Read-only access");
+    }
+    let result = ctx.sync_all().await;
+    let response = ExportResponse {
+        written: result.automerge_changed,
+        merged: result.both_changed,
+        imported: result.filesystem_changed,
+        unchanged: result.no_changes,
+        skipped: result.skipped,
+        errors: result
+            .errors
+            .into_iter()
+            .map(|e| ExportErrorEntry {
+                path: e.file_path,
+                error: e.error,
+            })
+            .collect(),
+    };
+    Json(response).into_response()
+}
+
+/// List the comment threads of a document
+async fn list_comments(
+    State(ctx): State<SharedContext>,
+    Path(doc_id_str): Path<String>,
+) -> impl IntoResponse {
+    let handle = match find_document(&ctx, &doc_id_str).await {
+        Ok(handle) => handle,
+        Err(response) => return response,
+    };
+    match handle.with_document(|doc| comments::list_threads(doc)) {
+        Ok(threads) => Json(CommentsResponse {
+            document_id: doc_id_str,
+            threads,
+        })
+        .into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+/// Start a comment or suggestion thread (requires the editor role)
+async fn create_comment(
+    State(ctx): State<SharedContext>,
+    Extension(role): Extension<Role>,
+    Extension(principal): Extension<Principal>,
+    Path(doc_id_str): Path<String>,
+    Json(request): Json<CreateCommentRequest>,
+) -> impl IntoResponse {
+    if !role.can_write() {
+        return error_response(StatusCode::FORBIDDEN, "Read-only access");
+    }
+    let author = match comment_author(&principal, request.author.as_deref()) {
+        Ok(author) => author,
+        Err(response) => return response,
+    };
+    let handle = match find_document(&ctx, &doc_id_str).await {
+        Ok(handle) => handle,
+        Err(response) => return response,
+    };
+    let new = NewThread {
+        start: request.start,
+        end: request.end,
+        author,
+        body: request.body,
+        suggestion: request.suggestion,
+    };
+    let result = handle.with_document(|doc| {
+        let id = comments::create_thread(doc, &new)?;
+        comments::get_thread(doc, &id)
+    });
+    match result {
+        Ok(thread) => {
+            info!(document_id = %doc_id_str, thread_id = %thread.id, "Created comment thread");
+            (StatusCode::CREATED, Json(thread)).into_response()
+        }
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+/// Reply to a comment thread (requires the editor role)
+async fn reply_to_comment(
+    State(ctx): State<SharedContext>,
+    Extension(role): Extension<Role>,
+    Extension(principal): Extension<Principal>,
+    Path((doc_id_str, thread_id)): Path<(String, String)>,
+    Json(request): Json<ReplyRequest>,
+) -> impl IntoResponse {
+    update_thread(
+        &ctx,
+        role,
+        &principal,
+        request.author.as_deref(),
+        &doc_id_str,
+        &thread_id,
+        |doc, author| comments::reply(doc, &thread_id, author, &request.body).map(|_| ()),
+    )
+    .await
+}
+
+/// Mark a comment thread resolved (requires the editor role)
+async fn resolve_comment(
+    State(ctx): State<SharedContext>,
+    Extension(role): Extension<Role>,
+    Extension(principal): Extension<Principal>,
+    Path((doc_id_str, thread_id)): Path<(String, String)>,
+    Json(request): Json<ThreadActionRequest>,
+) -> impl IntoResponse {
+    update_thread(
+        &ctx,
+        role,
+        &principal,
+        request.author.as_deref(),
+        &doc_id_str,
+        &thread_id,
+        |doc, author| comments::set_resolved(doc, &thread_id, true, author),
+    )
+    .await
+}
+
+/// Reopen a resolved comment thread (requires the editor role)
+async fn reopen_comment(
+    State(ctx): State<SharedContext>,
+    Extension(role): Extension<Role>,
+    Extension(principal): Extension<Principal>,
+    Path((doc_id_str, thread_id)): Path<(String, String)>,
+    Json(request): Json<ThreadActionRequest>,
+) -> impl IntoResponse {
+    update_thread(
+        &ctx,
+        role,
+        &principal,
+        request.author.as_deref(),
+        &doc_id_str,
+        &thread_id,
+        |doc, author| comments::set_resolved(doc, &thread_id, false, author),
+    )
+    .await
+}
+
+/// Apply a thread's suggested replacement and resolve it (requires the
+/// editor role)
+async fn accept_suggestion(
+    State(ctx): State<SharedContext>,
+    Extension(role): Extension<Role>,
+    Extension(principal): Extension<Principal>,
+    Path((doc_id_str, thread_id)): Path<(String, String)>,
+    Json(request): Json<ThreadActionRequest>,
+) -> impl IntoResponse {
+    update_thread(
+        &ctx,
+        role,
+        &principal,
+        request.author.as_deref(),
+        &doc_id_str,
+        &thread_id,
+        |doc, author| comments::accept_suggestion(doc, &thread_id, author),
+    )
+    .await
+}
+
+/// The author of a comment action by `principal`, or the response rejecting
+/// the `requested` author.
+fn comment_author(
+    principal: &Principal,
+    requested: Option<&str>,
+) -> std::result::Result<String, axum::response::Response> {
+    principal
+        .author(requested)
+        .map_err(|e| error_response(StatusCode::FORBIDDEN, e.to_string()))
+}
+
+/// Apply `update` to a document as the author of the action, and respond
+/// with the updated thread.
+async fn update_thread(
+    ctx: &HubContext,
+    role: Role,
+    principal: &Principal,
+    requested_author: Option<&str>,
+    doc_id_str: &str,
+    thread_id: &str,
+    update: impl FnOnce(&mut automerge::Automerge, &str) -> Result<()>,
+) -> axum::response::Response {
+    if !role.can_write() {
+        return error_response(StatusCode::FORBIDDEN, "Read-only access");
+    }
+    let author = match comment_author(principal, requested_author) {
+        Ok(author) => author,
+        Err(response) => return response,
+    };
+    let handle = match find_document(ctx, doc_id_str).await {
+        Ok(handle) => handle,
+        Err(response) => return response,
+    };
+    let result = handle.with_document(|doc| {
+        update(doc, &author)?;
+        comments::get_thread(doc, thread_id)
+    });
+    match result {
+        Ok(thread) => Json(thread).into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, e.to_string()),
+    }
+}
+
+/// Render a project document to HTML, or serve a resource of a rendered page.
+///
+/// `.qmd` paths are rendered from the current Automerge state of the
+/// document. The output is cached until the document or a project config
+/// document changes; the `X-Quarto-Render-Cache` header reports `hit` or
+/// `miss`. Open comment threads are shown as margin notes unless
+/// `?comments=false` is given. Other paths are served from the render output directory, so
+/// stylesheets and scripts linked from the page resolve.
+async fn render_document(
+    State(ctx): State<SharedContext>,
+    Path(path): Path<String>,
+    Query(query): Query<RenderQuery>,
+) -> axum::response::Response {
+    let Some(renderer) = ctx.renderer().cloned() else {
+        return error_response(
+            StatusCode::NOT_IMPLEMENTED,
+            "Server-side rendering is not enabled on this hub",
+        );
+    };
+    let output_dir = ctx.storage().hub_dir().join("render");
+
+    if !render::is_renderable(&path) {
+        let Some(file) = render::resolve_output_path(&output_dir, &path) else {
+            return error_response(StatusCode::BAD_REQUEST, "Invalid path");
+        };
+        return match tokio::fs::read(&file).await {
+            Ok(content) => (
+                [(
+                    header::CONTENT_TYPE,
+                    render::resource_content_type(&path, &content),
+                )],
+                content,
+            )
+                .into_response(),
+            Err(_) => error_response(StatusCode::NOT_FOUND, "Not found"),
+        };
+    }
+
+    let Some(doc_id) = ctx.index().get_file(&path) else {
+        return error_response(StatusCode::NOT_FOUND, "Document not found");
+    };
+    let handle = match find_document(&ctx, &doc_id).await {
+        Ok(handle) => handle,
+        Err(response) => return response,
+    };
+    let show_comments = query.comments.unwrap_or(true);
+    let current = handle.with_document(|doc| {
+        let heads = doc.get_heads();
+        let snapshot = history::snapshot_at(doc, &heads)?;
+        let threads = match &snapshot {
+            Snapshot::Text { .. } if show_comments => comments::list_threads(doc)?,
+            _ => Vec::new(),
+        };
+        Ok::<_, crate::error::Error>((heads, snapshot, threads))
+    });
+    let (heads, content) = match current {
+        Ok((heads, Snapshot::Text { text }, threads)) => {
+            (heads, comments::annotate_source(&text, &threads))
+        }
+        Ok(_) => return error_response(StatusCode::BAD_REQUEST, "Not a text document"),
+        Err(e) => return error_response(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+
+    // Project config affects every document, so its version is part of the key
+    let mut config_heads = Vec::new();
+    for (config_path, config_id) in ctx.index().get_all_files() {
+        if !render::is_config_path(&config_path) {
+            continue;
+        }
+        if let Ok(config_handle) = find_document(&ctx, &config_id).await {
+            let heads = config_handle.with_document(|doc| doc.get_heads());
+            config_heads.push((config_path, heads_to_strings(&heads)));
+        }
+    }
+    let mut key = render::cache_key(&heads_to_strings(&heads), &config_heads);
+    if show_comments {
+        key.push_str("|comments");
+    }
+
+    if let Some(page) = ctx.render_cache().get(&path, &key) {
+        debug!(path = %path, "Serving cached render");
+        return html_response(&page.html, "hit");
+    }
+
+    let request = RenderRequest {
+        project_root: ctx.storage().project_root().to_path_buf(),
+        path: path.clone(),
+        content,
+        output_dir,
+    };
+    let rendered = tokio::task::spawn_blocking(move || renderer.render(&request)).await;
+    match rendered {
+        Ok(Ok(page)) => {
+            info!(path = %path, diagnostics = page.diagnostics.len(), "Rendered document");
+            let page = Arc::new(page);
+            ctx.render_cache().insert(&path, key, page.clone());
+            html_response(&page.html, "miss")
+        }
+        Ok(Err(e)) => error_response(StatusCode::UNPROCESSABLE_ENTITY, e),
+        Err(e) => error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Render task failed: {}", e),
+        ),
+    }
+}
+
+fn html_response(html: &str, cache_status: &'static str) -> axum::response::Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+            (
+                header::HeaderName::from_static("x-quarto-render-cache"),
+                cache_status,
+            ),
+        ],
+        html.to_string(),
+    )
+        .into_response()
+}
+
 /// 404 handler
 async fn not_found() -> impl IntoResponse {
     (StatusCode::NOT_FOUND, "Not found")
@@ -244,16 +943,34 @@ async fn not_found() -> impl IntoResponse {
 /// WebSocket upgrade handler for automerge sync.
 ///
 /// Clients connect here to sync documents in real-time.
-async fn ws_handler(ws: WebSocketUpgrade, State(ctx): State<SharedContext>) -> impl IntoResponse {
-    ws.on_upgrade(|socket| handle_websocket(socket, ctx))
+async fn ws_handler(
+    ws: WebSocketUpgrade,
+    State(ctx): State<SharedContext>,
+    Extension(role): Extension<Role>,
+) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| handle_websocket(socket, ctx, role))
 }
 
 /// Handle an upgraded WebSocket connection.
-async fn handle_websocket(socket: WebSocket, ctx: SharedContext) {
+///
+/// Read-only peers have the changes stripped from every sync message they
+/// send, so they receive updates but cannot modify documents.
+async fn handle_websocket(socket: WebSocket, ctx: SharedContext, role: Role) {
     // accept_axum returns immediately; the connection runs in the background
-    match ctx.repo().accept_axum(socket) {
+    let accepted = if role.can_write() {
+        ctx.repo().accept_axum(socket)
+    } else {
+        ctx.repo().accept_axum(socket.filter_map(|message| {
+            futures::future::ready(match message {
+                Ok(Message::Binary(data)) => filter_read_only_message(data.to_vec())
+                    .map(|data| Ok(Message::Binary(data.into()))),
+                other => Some(other),
+            })
+        }))
+    };
+    match accepted {
         Ok(connection) => {
-            info!(peer_info = ?connection.info(), "WebSocket client connected");
+            info!(peer_info = ?connection.info(), role = ?role, "WebSocket client connected");
             // The connection is managed by samod and stays alive until the WebSocket closes.
             // We can optionally wait for it to finish if we want to log disconnection:
             let reason = connection.finished().await;
@@ -271,20 +988,96 @@ fn build_router(ctx: SharedContext) -> Router {
         .route("/health", get(health))
         .route("/api/files", get(list_files))
         .route("/api/documents", get(list_documents))
+        .route("/api/presence", get(list_presence))
+        .route("/api/export", post(export_documents))
         .route(
             "/api/documents/{id}",
             get(get_document).put(update_document),
         )
+        .route("/api/documents/{id}/history", get(document_history))
+        .route("/api/documents/{id}/changes", get(document_changes))
+        .route("/api/documents/{id}/snapshot", get(document_snapshot))
+        .route("/api/documents/{id}/diff", get(document_diff))
+        .route("/api/documents/{id}/restore", post(restore_document))
+        .route(
+            "/api/documents/{id}/comments",
+            get(list_comments).post(create_comment),
+        )
+        .route(
+            "/api/documents/{id}/comments/{thread}/replies",
+            post(reply_to_comment),
+        )
+        .route(
+            "/api/documents/{id}/comments/{thread}/resolve",
+            post(resolve_comment),
+        )
+        .route(
+            "/api/documents/{id}/comments/{thread}/reopen",
+            post(reopen_comment),
+        )
+        .route(
+            "/api/documents/{id}/comments/{thread}/accept",
+            post(accept_suggestion),
+        )
+        .route("/api/render/{*path}", get(render_document))
         // WebSocket endpoint for automerge sync
         // Root path "/" is the standard location used by sync.automerge.org
         // "/ws" is kept for backward compatibility
         .route("/", get(ws_handler))
         .route("/ws", get(ws_handler))
         .fallback(not_found)
+        .layer(middleware::from_fn_with_state(ctx.clone(), require_auth))
         .layer(TraceLayer::new_for_http())
         .with_state(ctx)
 }
 
+/// A project hosted by a multi-project hub.
+pub struct HubProject {
+    /// Route prefix of the project (`/projects/{name}`)
+    pub name: String,
+
+    /// Storage of the project (holds its lockfile)
+    pub storage: StorageManager,
+
+    /// Configuration of the project. `host` and `port` are ignored; the
+    /// hub binds the address given to [`run_projects`].
+    pub config: HubConfig,
+}
+
+/// A project listed by `/api/projects`
+#[derive(Serialize)]
+struct ProjectResponseEntry {
+    name: String,
+    role: Role,
+    qmd_file_count: usize,
+}
+
+/// Projects visible to the caller
+#[derive(Serialize)]
+struct ProjectsResponse {
+    projects: Vec<ProjectResponseEntry>,
+}
+
+/// List the projects whose tokens accept the caller's token.
+async fn list_projects(
+    State(projects): State<Arc<[(String, SharedContext)]>>,
+    request: axum::extract::Request,
+) -> impl IntoResponse {
+    let token = request_token(&request);
+    let projects = projects
+        .iter()
+        .filter_map(|(name, ctx)| {
+            let role = ctx.authenticate(token.as_deref())?;
+            Some(ProjectResponseEntry {
+                name: name.clone(),
+                role,
+                qmd_file_count: ctx.project_files().qmd_files.len(),
+            })
+        })
+        .collect();
+    Json(ProjectsResponse { projects })
+}
+
 /// Run the hub server.
 ///
 /// This function blocks until the server is shut down.
@@ -295,97 +1088,203 @@ fn build_router(ctx: SharedContext) -> Router {
 /// sync all documents to the filesystem for crash resilience.
 pub async fn run_server(storage: StorageManager, config: HubConfig) -> Result<()> {
     let addr = format!("{}:{}", config.host, config.port);
-    let sync_interval = config.sync_interval_secs;
-    let watch_enabled = config.watch_enabled;
-    let watch_debounce_ms = config.watch_debounce_ms;
-    let project_root = storage.project_root().to_path_buf();
+    warn_if_unauthenticated(&config, &config.host, None);
 
     // HubContext::new is now async (initializes samod repo and performs initial sync)
-    let ctx = Arc::new(HubContext::new(storage, config).await?);
-    let ctx_for_sync = ctx.clone();
-    let ctx_for_watch = ctx.clone();
-    let ctx_for_shutdown = ctx.clone();
-
-    let router = build_router(ctx);
+    let ctx = Arc::new(HubContext::new(storage, config.clone()).await?);
+    let router = build_router(ctx.clone());
 
     let listener = TcpListener::bind(&addr).await?;
     info!(%addr, "Hub server listening");
 
-    // Create shutdown signal channel
-    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let shutdown_rx = spawn_shutdown_listener();
+    let tasks = spawn_project_tasks(ctx, &config, &shutdown_rx);
+
+    serve(listener, router, shutdown_rx).await?;
+    tasks.finish().await;
+
+    Ok(())
+}
 
-    // Spawn task to listen for OS signals and trigger shutdown
+/// Run a hub serving several projects.
+///
+/// Each project gets its own storage, samod repo, sync state, watcher and
+/// tokens, and its routes (REST API and WebSocket sync) are served under
+/// `/projects/{name}`. `/api/projects` lists the projects the caller's
+/// token gives access to.
+///
+/// Like [`run_server`], this blocks until shutdown and then performs a final
+/// filesystem sync of every project.
+pub async fn run_projects(projects: Vec<HubProject>, host: &str, port: u16) -> Result<()> {
+    let addr = format!("{}:{}", host, port);
+
+    let mut contexts = Vec::new();
+    let mut configs = Vec::new();
+    let mut router = Router::new();
+    for project in projects {
+        let name = project.name;
+        warn_if_unauthenticated(&project.config, host, Some(&name));
+        info!(
+            project = %name,
+            root = %project.storage.project_root().display(),
+            "Loading project"
+        );
+        let ctx = Arc::new(HubContext::new(project.storage, project.config.clone()).await?);
+        router = router.nest(&format!("/projects/{}", name), build_router(ctx.clone()));
+        contexts.push((name, ctx));
+        configs.push(project.config);
+    }
+
+    let projects: Arc<[(String, SharedContext)]> = contexts.into();
+    let router = router
+        .route(
+            "/api/projects",
+            get(list_projects).with_state(projects.clone()),
+        )
+        .fallback(not_found);
+
+    let listener = TcpListener::bind(&addr).await?;
+    info!(%addr, projects = projects.len(), "Hub server listening");
+
+    let shutdown_rx = spawn_shutdown_listener();
+    let tasks: Vec<_> = projects
+        .iter()
+        .zip(&configs)
+        .map(|((_, ctx), config)| spawn_project_tasks(ctx.clone(), config, &shutdown_rx))
+        .collect();
+
+    serve(listener, router, shutdown_rx).await?;
+    for ((name, _), tasks) in projects.iter().zip(tasks) {
+        debug!(project = %name, "Stopping project");
+        tasks.finish().await;
+    }
+
+    Ok(())
+}
+
+/// Log whether a project requires authentication, warning when it does not
+/// and the hub is reachable from the network.
+fn warn_if_unauthenticated(config: &HubConfig, host: &str, project: Option<&str>) {
+    if config.auth.is_enabled() {
+        info!(
+            project = project.unwrap_or_default(),
+            tokens = config.auth.tokens.len(),
+            "Token authentication enabled"
+        );
+    } else if !is_loopback_host(host) {
+        tracing::warn!(
+            project = project.unwrap_or_default(),
+            host = %host,
+            "Authentication is disabled and the hub is reachable from the network; \
+             anyone who can connect can edit this project. Use --auth-file or QUARTO_HUB_AUTH_TOKEN."
+        );
+    }
+}
+
+/// Spawn a task that listens for OS signals and flips the returned channel
+/// to `true` on shutdown.
+fn spawn_shutdown_listener() -> watch::Receiver<bool> {
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
     tokio::spawn(async move {
         wait_for_shutdown_signal().await;
         let _ = shutdown_tx.send(true);
     });
+    shutdown_rx
+}
+
+/// Serve `router` until shutdown is signaled.
+async fn serve(
+    listener: TcpListener,
+    router: Router,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> Result<()> {
+    axum::serve(listener, router)
+        .with_graceful_shutdown(async move {
+            // Wait until shutdown is signaled
+            let _ = shutdown_rx.wait_for(|&v| v).await;
+            info!("Server shutting down...");
+        })
+        .await
+        .map_err(|e| crate::error::Error::Server(e.to_string()))
+}
+
+/// Background tasks (periodic sync and filesystem watcher) of one project.
+struct ProjectTasks {
+    ctx: SharedContext,
+    handles: Vec<tokio::task::JoinHandle<()>>,
+}
+
+impl ProjectTasks {
+    /// Wait for the tasks to stop, then perform a final filesystem sync.
+    async fn finish(self) {
+        debug!("Waiting for background tasks to finish...");
+        for handle in self.handles {
+            let _ = handle.await;
+        }
+
+        info!("Performing final filesystem sync before shutdown...");
+        let sync_result = self.ctx.sync_all().await;
+        info!(
+            synced = sync_result.total_synced(),
+            errors = sync_result.errors.len(),
+            "Final filesystem sync complete"
+        );
+    }
+}
+
+/// Spawn the periodic sync and filesystem watcher tasks of a project, as
+/// configured.
+fn spawn_project_tasks(
+    ctx: SharedContext,
+    config: &HubConfig,
+    shutdown_rx: &watch::Receiver<bool>,
+) -> ProjectTasks {
+    let mut handles = Vec::new();
 
     // Spawn periodic sync task if interval is configured
-    let periodic_sync_handle = if let Some(interval_secs) = sync_interval {
+    if let Some(interval_secs) = config.sync_interval_secs {
+        let ctx = ctx.clone();
         let shutdown_rx = shutdown_rx.clone();
         info!(interval_secs = interval_secs, "Starting periodic sync task");
-        Some(tokio::spawn(async move {
-            run_periodic_sync(ctx_for_sync, interval_secs, shutdown_rx).await;
-        }))
+        handles.push(tokio::spawn(async move {
+            run_periodic_sync(ctx, interval_secs, shutdown_rx).await;
+        }));
     } else {
         debug!("Periodic sync disabled");
-        None
-    };
+    }
 
     // Spawn file watcher task if enabled
-    let watcher_handle = if watch_enabled {
-        let shutdown_rx = shutdown_rx.clone();
+    if config.watch_enabled {
         let watch_config = WatchConfig {
-            debounce_ms: watch_debounce_ms,
+            debounce_ms: config.watch_debounce_ms,
+            filter: ctx.sync_filter().clone(),
         };
-        match FileWatcher::new(&project_root, watch_config) {
+        match FileWatcher::new(ctx.storage().project_root(), watch_config) {
             Ok(watcher) => {
+                let ctx = ctx.clone();
+                let shutdown_rx = shutdown_rx.clone();
                 info!("Starting filesystem watcher");
-                Some(tokio::spawn(async move {
-                    run_file_watcher(ctx_for_watch, watcher, shutdown_rx).await;
-                }))
+                handles.push(tokio::spawn(async move {
+                    run_file_watcher(ctx, watcher, shutdown_rx).await;
+                }));
             }
             Err(e) => {
                 tracing::warn!(error = %e, "Failed to start filesystem watcher, continuing without it");
-                None
             }
         }
     } else {
         debug!("Filesystem watcher disabled");
-        None
-    };
-
-    // Run server with graceful shutdown
-    let mut shutdown_rx_server = shutdown_rx.clone();
-    axum::serve(listener, router)
-        .with_graceful_shutdown(async move {
-            // Wait until shutdown is signaled
-            let _ = shutdown_rx_server.wait_for(|&v| v).await;
-            info!("Server shutting down...");
-        })
-        .await
-        .map_err(|e| crate::error::Error::Server(e.to_string()))?;
-
-    // Wait for background tasks to finish
-    if let Some(handle) = periodic_sync_handle {
-        debug!("Waiting for periodic sync task to finish...");
-        let _ = handle.await;
-    }
-    if let Some(handle) = watcher_handle {
-        debug!("Waiting for file watcher task to finish...");
-        let _ = handle.await;
     }
 
-    // Perform final sync on shutdown
-    info!("Performing final filesystem sync before shutdown...");
-    let sync_result = ctx_for_shutdown.sync_all().await;
-    info!(
-        synced = sync_result.total_synced(),
-        errors = sync_result.errors.len(),
-        "Final filesystem sync complete"
-    );
+    ProjectTasks { ctx, handles }
+}
 
-    Ok(())
+/// Whether the bind host only accepts local connections.
+fn is_loopback_host(host: &str) -> bool {
+    host == "localhost"
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
 }
 
 /// Run periodic filesystem sync in a background task.
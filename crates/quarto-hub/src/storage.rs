@@ -1,6 +1,14 @@
 //! Storage management for the hub
 //!
 //! Manages the `.quarto/hub/` directory structure and lockfile.
+//!
+//! Documents are stored by samod under `.quarto/hub/automerge/` as snapshot
+//! chunks and incremental changes. samod compacts a document it holds once
+//! enough changes accumulate, but chunks left over from interrupted
+//! compactions, and documents that were never loaded again, stay split up.
+//! [`StorageManager::compact`] folds such documents into a single snapshot
+//! while no repo is using the directory, keeping storage bounded for
+//! long-lived documents.
 
 use std::fs::{self, File};
 use std::io::Write;
@@ -8,6 +16,7 @@ use std::path::{Path, PathBuf};
 
 use fs2::FileExt;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tracing::{debug, info, warn};
 
 use crate::error::{Error, Result};
@@ -18,6 +27,9 @@ use crate::error::{Error, Result};
 /// The hub will check this version on startup and can perform migrations.
 pub const CURRENT_HUB_VERSION: u32 = 1;
 
+/// Documents stored in at least this many chunks are compacted on startup.
+pub const DEFAULT_COMPACTION_THRESHOLD: usize = 8;
+
 /// Hub configuration stored in `.quarto/hub/hub.json`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HubStorageConfig {
@@ -239,6 +251,135 @@ impl StorageManager {
     }
 }
 
+impl StorageManager {
+    /// Compact every stored document made of at least `min_chunks` chunks
+    /// into a single snapshot.
+    ///
+    /// Must run before the samod repo is started on this directory: samod
+    /// tracks the chunks it wrote and expects to be the only one deleting
+    /// them. A document whose chunks fail to load is left untouched.
+    pub fn compact(&self, min_chunks: usize) -> Result<CompactionStats> {
+        let mut stats = CompactionStats::default();
+        let automerge_dir = self.automerge_dir();
+        if !automerge_dir.is_dir() {
+            return Ok(stats);
+        }
+        // samod splays document directories by the first two characters of
+        // the document ID: automerge/<ab>/<cdef...>/{snapshot,incremental}/
+        for prefix in fs::read_dir(&automerge_dir)? {
+            let prefix = prefix?.path();
+            if !prefix.is_dir() {
+                continue;
+            }
+            for doc_dir in fs::read_dir(&prefix)? {
+                let doc_dir = doc_dir?.path();
+                if !doc_dir.is_dir() {
+                    continue;
+                }
+                match compact_document(&doc_dir, min_chunks) {
+                    Ok(Some(doc_stats)) => stats.add(doc_stats),
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!(dir = %doc_dir.display(), error = %e, "Failed to compact document");
+                    }
+                }
+            }
+        }
+        if stats.documents > 0 {
+            info!(
+                documents = stats.documents,
+                chunks_removed = stats.chunks_removed,
+                bytes_before = stats.bytes_before,
+                bytes_after = stats.bytes_after,
+                "Compacted document storage"
+            );
+        }
+        Ok(stats)
+    }
+}
+
+/// What a storage compaction did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactionStats {
+    /// Documents rewritten as a single snapshot
+    pub documents: usize,
+
+    /// Chunk files removed
+    pub chunks_removed: usize,
+
+    /// Size of the compacted documents before compaction
+    pub bytes_before: u64,
+
+    /// Size of the compacted documents after compaction
+    pub bytes_after: u64,
+}
+
+impl CompactionStats {
+    fn add(&mut self, other: CompactionStats) {
+        self.documents += other.documents;
+        self.chunks_removed += other.chunks_removed;
+        self.bytes_before += other.bytes_before;
+        self.bytes_after += other.bytes_after;
+    }
+}
+
+/// Compact the chunks of one document directory, if there are at least
+/// `min_chunks` of them.
+fn compact_document(doc_dir: &Path, min_chunks: usize) -> Result<Option<CompactionStats>> {
+    let mut chunks = Vec::new();
+    for kind in ["snapshot", "incremental"] {
+        let dir = doc_dir.join(kind);
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_file() {
+                chunks.push(path);
+            }
+        }
+    }
+    if chunks.len() < min_chunks.max(2) {
+        return Ok(None);
+    }
+
+    let mut doc = automerge::Automerge::new();
+    let mut bytes_before = 0;
+    for chunk in &chunks {
+        let data = fs::read(chunk)?;
+        bytes_before += data.len() as u64;
+        doc.load_incremental(&data)
+            .map_err(|e| Error::Automerge(format!("failed to load {}: {}", chunk.display(), e)))?;
+    }
+
+    // Name the snapshot the way samod names its own compactions: the
+    // SHA-256 of the document heads
+    let mut hasher = Sha256::new();
+    for head in doc.get_heads() {
+        hasher.update(head.as_ref());
+    }
+    let snapshot = doc_dir
+        .join("snapshot")
+        .join(hex::encode(hasher.finalize()));
+    let data = doc.save();
+    fs::create_dir_all(doc_dir.join("snapshot"))?;
+    crate::export::write_atomic(&snapshot, &data)?;
+
+    let mut chunks_removed = 0;
+    for chunk in chunks.iter().filter(|chunk| **chunk != snapshot) {
+        fs::remove_file(chunk)?;
+        chunks_removed += 1;
+    }
+    debug!(dir = %doc_dir.display(), chunks_removed, "Compacted document");
+
+    Ok(Some(CompactionStats {
+        documents: 1,
+        chunks_removed,
+        bytes_before,
+        bytes_after: data.len() as u64,
+    }))
+}
+
 impl Drop for StorageManager {
     fn drop(&mut self) {
         // Lock is automatically released when file is closed.
@@ -312,6 +453,59 @@ mod tests {
         assert!(matches!(result, Err(Error::HubAlreadyRunning)));
     }
 
+    #[test]
+    fn test_compact_folds_chunks_into_one_snapshot() {
+        use automerge::transaction::Transactable;
+
+        let temp = TempDir::new().unwrap();
+        let manager = StorageManager::new(temp.path()).unwrap();
+        let doc_dir = manager.automerge_dir().join("ab").join("cdef");
+        fs::create_dir_all(doc_dir.join("incremental")).unwrap();
+        fs::create_dir_all(doc_dir.join("snapshot")).unwrap();
+
+        // A snapshot plus incremental changes, as samod leaves them
+        let mut doc = automerge::Automerge::new();
+        let mut tx = doc.transaction();
+        tx.put(automerge::ROOT, "n", 0).unwrap();
+        tx.commit();
+        fs::write(doc_dir.join("snapshot").join("old"), doc.save()).unwrap();
+        let heads = doc.get_heads();
+        for i in 1..10 {
+            let mut tx = doc.transaction();
+            tx.put(automerge::ROOT, "n", i).unwrap();
+            tx.commit();
+        }
+        for change in doc.get_changes(&heads) {
+            fs::write(
+                doc_dir.join("incremental").join(change.hash().to_string()),
+                change.raw_bytes(),
+            )
+            .unwrap();
+        }
+
+        // Below the threshold nothing happens
+        assert_eq!(manager.compact(11).unwrap(), CompactionStats::default());
+
+        let stats = manager.compact(DEFAULT_COMPACTION_THRESHOLD).unwrap();
+        assert_eq!(stats.documents, 1);
+        assert_eq!(stats.chunks_removed, 10);
+        assert_eq!(
+            fs::read_dir(doc_dir.join("incremental")).unwrap().count(),
+            0
+        );
+
+        let snapshots: Vec<_> = fs::read_dir(doc_dir.join("snapshot"))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(snapshots.len(), 1);
+        let loaded = automerge::Automerge::load(&fs::read(&snapshots[0]).unwrap()).unwrap();
+        assert_eq!(loaded.get_heads(), doc.get_heads());
+
+        // Compacting again is a no-op
+        assert_eq!(manager.compact(1).unwrap(), CompactionStats::default());
+    }
+
     #[test]
     fn test_storage_manager_nonexistent_project() {
         let result = StorageManager::new("/nonexistent/path/that/does/not/exist");
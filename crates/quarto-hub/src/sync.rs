@@ -15,11 +15,12 @@
 use std::path::Path;
 use std::str::FromStr;
 
-use automerge::{ROOT, ReadDoc, transaction::Transactable};
+use automerge::{Automerge, ChangeHash, ROOT, ReadDoc, transaction::Transactable};
 use samod::{DocHandle, DocumentId, Repo};
 use tracing::{debug, info, warn};
 
 use crate::error::{Error, Result};
+use crate::export::{FileSnapshot, MAX_WRITE_ATTEMPTS, WriteOutcome, write_if_unchanged};
 use crate::index::IndexDocument;
 use crate::resource::{
     self, DocumentType, compute_hash, detect_document_type, detect_mime_type, read_binary_content,
@@ -38,6 +39,11 @@ use crate::sync_state::{SyncState, sha256_hash};
 /// 6. Write merged state back to filesystem
 /// 7. Update sync checkpoint
 ///
+/// The write in step 6 is atomic and refused if the file changed on disk
+/// since step 2. In that case the sync starts over from the content just
+/// merged, so the new local edits are merged in (a three-way merge against
+/// what the document already contains) instead of being overwritten.
+///
 /// # Arguments
 /// * `doc_handle` - Handle to the automerge document
 /// * `file_path` - Path to the filesystem file
@@ -52,122 +58,7 @@ pub fn sync_document(
     sync_state: &mut SyncState,
 ) -> Result<SyncResult> {
     let doc_id = doc_handle.document_id().to_string();
-
-    // Read filesystem content first (outside of with_document to avoid holding lock while doing IO)
-    let fs_content = std::fs::read_to_string(file_path).map_err(|e| {
-        Error::Sync(format!(
-            "failed to read file {}: {}",
-            file_path.display(),
-            e
-        ))
-    })?;
-
-    let result = doc_handle.with_document(|doc| {
-        // 1. Get sync checkpoint (use current heads if none exists or invalid)
-        let checkpoint_heads = sync_state.get_heads(&doc_id);
-        let last_sync_heads = checkpoint_heads
-            .filter(|heads| {
-                // Validate that all checkpoint heads exist in document history
-                heads.iter().all(|h| doc.get_change_by_hash(h).is_some())
-            })
-            .unwrap_or_else(|| doc.get_heads());
-
-        let current_heads = doc.get_heads();
-        let heads_unchanged = last_sync_heads == current_heads;
-
-        // Check if filesystem content matches what we synced last time
-        let last_content_hash = sync_state.get_content_hash(&doc_id);
-        let fs_content_hash = sha256_hash(&fs_content);
-        let fs_unchanged = last_content_hash == Some(fs_content_hash.as_str());
-
-        // Early exit: if nothing changed, we're done
-        if heads_unchanged && fs_unchanged {
-            debug!(doc_id = %doc_id, "No changes detected, skipping sync");
-            return Ok(SyncResult::NoChanges);
-        }
-
-        // 3. Fork at sync checkpoint (with fallback if fork_at fails)
-        let mut forked = doc.fork_at(&last_sync_heads).unwrap_or_else(|e| {
-            warn!(
-                doc_id = %doc_id,
-                error = %e,
-                "fork_at failed, falling back to current state"
-            );
-            doc.fork()
-        });
-
-        // 4. Apply filesystem content to fork
-        let text_obj = forked
-            .get(ROOT, "text")
-            .map_err(|e| Error::Sync(format!("failed to get text object: {:?}", e)))?
-            .ok_or_else(|| {
-                Error::Sync(format!(
-                    "document {} has no text field - was it initialized correctly?",
-                    doc_id
-                ))
-            })?
-            .1;
-
-        forked
-            .transact::<_, _, automerge::AutomergeError>(|tx| {
-                tx.update_text(&text_obj, &fs_content)?;
-                Ok(())
-            })
-            .map_err(|e| Error::Sync(format!("failed to update text in fork: {:?}", e)))?;
-
-        // 5. Merge fork back into main document
-        doc.merge(&mut forked)
-            .map_err(|e| Error::Sync(format!("failed to merge fork: {:?}", e)))?;
-
-        // 6. Read merged content and write back to filesystem
-        let merged_text_obj = doc
-            .get(ROOT, "text")
-            .map_err(|e| Error::Sync(format!("failed to get merged text object: {:?}", e)))?
-            .ok_or_else(|| Error::Sync("merged document has no text field".to_string()))?
-            .1;
-
-        let merged_content = doc
-            .text(&merged_text_obj)
-            .map_err(|e| Error::Sync(format!("failed to read merged text: {:?}", e)))?;
-
-        // Determine what kind of sync happened
-        let result_type = if !heads_unchanged && !fs_unchanged {
-            SyncResult::BothChanged {
-                merged_len: merged_content.len(),
-            }
-        } else if !heads_unchanged {
-            SyncResult::AutomergeChanged {
-                new_len: merged_content.len(),
-            }
-        } else {
-            SyncResult::FilesystemChanged {
-                new_len: merged_content.len(),
-            }
-        };
-
-        // Write merged content back to filesystem (only if it differs)
-        let merged_content_hash = sha256_hash(&merged_content);
-        if merged_content_hash != fs_content_hash {
-            std::fs::write(file_path, &merged_content).map_err(|e| {
-                Error::Sync(format!(
-                    "failed to write merged content to {}: {}",
-                    file_path.display(),
-                    e
-                ))
-            })?;
-            debug!(
-                doc_id = %doc_id,
-                path = %file_path.display(),
-                "Wrote merged content to filesystem"
-            );
-        }
-
-        // 7. Update sync checkpoint
-        let new_heads = doc.get_heads();
-        sync_state.set_checkpoint(&doc_id, &new_heads, &merged_content_hash);
-
-        Ok(result_type)
-    });
+    let result = sync_text_with_retries(doc_handle, &doc_id, file_path, sync_state);
 
     match &result {
         Ok(SyncResult::NoChanges) => {
@@ -210,6 +101,186 @@ pub fn sync_document(
     result
 }
 
+/// Run the text sync algorithm until the merged content is written without a
+/// concurrent edit on disk.
+fn sync_text_with_retries(
+    doc_handle: &DocHandle,
+    doc_id: &str,
+    file_path: &Path,
+    sync_state: &mut SyncState,
+) -> Result<SyncResult> {
+    for _ in 0..MAX_WRITE_ATTEMPTS {
+        // Read filesystem content first (outside of with_document to avoid holding lock while doing IO)
+        let (fs_content, snapshot) = FileSnapshot::read_to_string(file_path).map_err(|e| {
+            Error::Sync(format!(
+                "failed to read file {}: {}",
+                file_path.display(),
+                e
+            ))
+        })?;
+        let fs_content_hash = sha256_hash(&fs_content);
+
+        let reconciled =
+            doc_handle.with_document(|doc| reconcile_text(doc, doc_id, &fs_content, sync_state))?;
+        let Some(reconciled) = reconciled else {
+            return Ok(SyncResult::NoChanges);
+        };
+
+        // Write merged content back to filesystem (only if it differs)
+        let merged_content_hash = sha256_hash(&reconciled.merged_content);
+        if merged_content_hash != fs_content_hash {
+            match write_if_unchanged(file_path, reconciled.merged_content.as_bytes(), &snapshot)? {
+                WriteOutcome::Written => {
+                    debug!(
+                        doc_id = %doc_id,
+                        path = %file_path.display(),
+                        "Wrote merged content to filesystem"
+                    );
+                }
+                WriteOutcome::Conflict => {
+                    // The document now contains `fs_content`; merge the newer
+                    // disk content relative to it on the next attempt
+                    info!(
+                        doc_id = %doc_id,
+                        path = %file_path.display(),
+                        "File changed on disk during sync, merging again"
+                    );
+                    sync_state.set_checkpoint(doc_id, &reconciled.fs_heads, &fs_content_hash);
+                    continue;
+                }
+            }
+        }
+
+        // 7. Update sync checkpoint
+        sync_state.set_checkpoint(doc_id, &reconciled.merged_heads, &merged_content_hash);
+        return Ok(reconciled.result);
+    }
+
+    Err(Error::Sync(format!(
+        "file {} kept changing on disk; gave up after {} attempts",
+        file_path.display(),
+        MAX_WRITE_ATTEMPTS
+    )))
+}
+
+/// A text document after merging in the content of its file.
+struct Reconciled {
+    /// What kind of sync happened
+    result: SyncResult,
+
+    /// Document content after the merge
+    merged_content: String,
+
+    /// Heads at which the document content is `merged_content`
+    merged_heads: Vec<ChangeHash>,
+
+    /// Heads at which the document content is exactly the file content
+    /// that was merged in (the fork after applying it)
+    fs_heads: Vec<ChangeHash>,
+}
+
+/// Steps 1 and 3-5 of the sync algorithm: merge `fs_content` into the
+/// document as changes made since the sync checkpoint.
+///
+/// Returns `None` if neither the document nor the file changed since the
+/// checkpoint.
+fn reconcile_text(
+    doc: &mut Automerge,
+    doc_id: &str,
+    fs_content: &str,
+    sync_state: &SyncState,
+) -> Result<Option<Reconciled>> {
+    // 1. Get sync checkpoint (use current heads if none exists or invalid)
+    let checkpoint_heads = sync_state.get_heads(doc_id);
+    let last_sync_heads = checkpoint_heads
+        .filter(|heads| {
+            // Validate that all checkpoint heads exist in document history
+            heads.iter().all(|h| doc.get_change_by_hash(h).is_some())
+        })
+        .unwrap_or_else(|| doc.get_heads());
+
+    let current_heads = doc.get_heads();
+    let heads_unchanged = last_sync_heads == current_heads;
+
+    // Check if filesystem content matches what we synced last time
+    let last_content_hash = sync_state.get_content_hash(doc_id);
+    let fs_content_hash = sha256_hash(fs_content);
+    let fs_unchanged = last_content_hash == Some(fs_content_hash.as_str());
+
+    // Early exit: if nothing changed, we're done
+    if heads_unchanged && fs_unchanged {
+        debug!(doc_id = %doc_id, "No changes detected, skipping sync");
+        return Ok(None);
+    }
+
+    // 3. Fork at sync checkpoint (with fallback if fork_at fails)
+    let mut forked = doc.fork_at(&last_sync_heads).unwrap_or_else(|e| {
+        warn!(
+            doc_id = %doc_id,
+            error = %e,
+            "fork_at failed, falling back to current state"
+        );
+        doc.fork()
+    });
+
+    // 4. Apply filesystem content to fork
+    let text_obj = forked
+        .get(ROOT, "text")
+        .map_err(|e| Error::Sync(format!("failed to get text object: {:?}", e)))?
+        .ok_or_else(|| {
+            Error::Sync(format!(
+                "document {} has no text field - was it initialized correctly?",
+                doc_id
+            ))
+        })?
+        .1;
+
+    forked
+        .transact::<_, _, automerge::AutomergeError>(|tx| {
+            tx.update_text(&text_obj, fs_content)?;
+            Ok(())
+        })
+        .map_err(|e| Error::Sync(format!("failed to update text in fork: {:?}", e)))?;
+    let fs_heads = forked.get_heads();
+
+    // 5. Merge fork back into main document
+    doc.merge(&mut forked)
+        .map_err(|e| Error::Sync(format!("failed to merge fork: {:?}", e)))?;
+
+    // 6. Read merged content
+    let merged_text_obj = doc
+        .get(ROOT, "text")
+        .map_err(|e| Error::Sync(format!("failed to get merged text object: {:?}", e)))?
+        .ok_or_else(|| Error::Sync("merged document has no text field".to_string()))?
+        .1;
+
+    let merged_content = doc
+        .text(&merged_text_obj)
+        .map_err(|e| Error::Sync(format!("failed to read merged text: {:?}", e)))?;
+
+    // Determine what kind of sync happened
+    let result = if !heads_unchanged && !fs_unchanged {
+        SyncResult::BothChanged {
+            merged_len: merged_content.len(),
+        }
+    } else if !heads_unchanged {
+        SyncResult::AutomergeChanged {
+            new_len: merged_content.len(),
+        }
+    } else {
+        SyncResult::FilesystemChanged {
+            new_len: merged_content.len(),
+        }
+    };
+
+    Ok(Some(Reconciled {
+        result,
+        merged_content,
+        merged_heads: doc.get_heads(),
+        fs_heads,
+    }))
+}
+
 /// Synchronize a binary document with its corresponding filesystem file.
 ///
 /// Binary files use simpler last-writer-wins semantics:
@@ -232,98 +303,7 @@ pub fn sync_binary_document(
 ) -> Result<SyncResult> {
     let doc_id = doc_handle.document_id().to_string();
 
-    // Read filesystem content
-    let fs_content = std::fs::read(file_path).map_err(|e| {
-        Error::Sync(format!(
-            "failed to read binary file {}: {}",
-            file_path.display(),
-            e
-        ))
-    })?;
-    let fs_hash = compute_hash(&fs_content);
-
-    let result = doc_handle.with_document(|doc| {
-        // Get document content hash
-        let doc_hash = read_content_hash(doc);
-
-        // Get last synced hash from checkpoint
-        let checkpoint_hash = sync_state.get_content_hash(&doc_id);
-
-        // Determine what changed
-        let doc_unchanged = doc_hash.as_deref() == checkpoint_hash;
-        let fs_unchanged = checkpoint_hash == Some(fs_hash.as_str());
-
-        // Early exit: no changes
-        if doc_unchanged && fs_unchanged {
-            debug!(doc_id = %doc_id, "No changes detected in binary file, skipping sync");
-            return Ok(SyncResult::NoChanges);
-        }
-
-        // Determine sync direction
-        let (result_type, final_content, final_hash) = if !doc_unchanged && fs_unchanged {
-            // Document changed, filesystem unchanged -> write doc to filesystem
-            let content = read_binary_content(doc).ok_or_else(|| {
-                Error::Sync(format!(
-                    "failed to read binary content from document {}",
-                    doc_id
-                ))
-            })?;
-            let hash = doc_hash.unwrap_or_else(|| compute_hash(&content));
-            (
-                SyncResult::AutomergeChanged {
-                    new_len: content.len(),
-                },
-                content,
-                hash,
-            )
-        } else {
-            // Filesystem changed (or both changed) -> filesystem wins
-            // For binary files, we don't have meaningful merge, so local edits take precedence
-            let result_type = if !doc_unchanged && !fs_unchanged {
-                SyncResult::BothChanged {
-                    merged_len: fs_content.len(),
-                }
-            } else {
-                SyncResult::FilesystemChanged {
-                    new_len: fs_content.len(),
-                }
-            };
-
-            // Update document with filesystem content
-            let mime_type = detect_mime_type(&fs_content, file_path.to_str());
-            doc.transact::<_, _, automerge::AutomergeError>(|tx| {
-                tx.put(ROOT, "content", fs_content.clone())?;
-                tx.put(ROOT, "mimeType", mime_type)?;
-                tx.put(ROOT, "hash", fs_hash.clone())?;
-                Ok(())
-            })
-            .map_err(|e| Error::Sync(format!("failed to update binary document: {:?}", e)))?;
-
-            (result_type, fs_content, fs_hash)
-        };
-
-        // Write to filesystem if needed (only if doc changed)
-        if matches!(result_type, SyncResult::AutomergeChanged { .. }) {
-            std::fs::write(file_path, &final_content).map_err(|e| {
-                Error::Sync(format!(
-                    "failed to write binary content to {}: {}",
-                    file_path.display(),
-                    e
-                ))
-            })?;
-            debug!(
-                doc_id = %doc_id,
-                path = %file_path.display(),
-                "Wrote binary content to filesystem"
-            );
-        }
-
-        // Update sync checkpoint
-        let new_heads = doc.get_heads();
-        sync_state.set_checkpoint(&doc_id, &new_heads, &final_hash);
-
-        Ok(result_type)
-    });
+    let result = sync_binary_with_retries(doc_handle, &doc_id, file_path, sync_state);
 
     match &result {
         Ok(SyncResult::NoChanges) => {
@@ -366,6 +346,125 @@ pub fn sync_binary_document(
     result
 }
 
+/// Run the binary sync algorithm until it completes without a concurrent
+/// edit on disk.
+fn sync_binary_with_retries(
+    doc_handle: &DocHandle,
+    doc_id: &str,
+    file_path: &Path,
+    sync_state: &mut SyncState,
+) -> Result<SyncResult> {
+    for _ in 0..MAX_WRITE_ATTEMPTS {
+        // Read filesystem content
+        let (fs_content, snapshot) = FileSnapshot::read(file_path).map_err(|e| {
+            Error::Sync(format!(
+                "failed to read binary file {}: {}",
+                file_path.display(),
+                e
+            ))
+        })?;
+        let fs_hash = compute_hash(&fs_content);
+
+        let result = doc_handle.with_document(|doc| -> Result<Option<SyncResult>> {
+            // Get document content hash
+            let doc_hash = read_content_hash(doc);
+
+            // Get last synced hash from checkpoint
+            let checkpoint_hash = sync_state.get_content_hash(doc_id);
+
+            // Determine what changed
+            let doc_unchanged = doc_hash.as_deref() == checkpoint_hash;
+            let fs_unchanged = checkpoint_hash == Some(fs_hash.as_str());
+
+            // Early exit: no changes
+            if doc_unchanged && fs_unchanged {
+                debug!(doc_id = %doc_id, "No changes detected in binary file, skipping sync");
+                return Ok(Some(SyncResult::NoChanges));
+            }
+
+            // Determine sync direction
+            let (result_type, final_content, final_hash) = if !doc_unchanged && fs_unchanged {
+                // Document changed, filesystem unchanged -> write doc to filesystem
+                let content = read_binary_content(doc).ok_or_else(|| {
+                    Error::Sync(format!(
+                        "failed to read binary content from document {}",
+                        doc_id
+                    ))
+                })?;
+                let hash = doc_hash.unwrap_or_else(|| compute_hash(&content));
+                (
+                    SyncResult::AutomergeChanged {
+                        new_len: content.len(),
+                    },
+                    content,
+                    hash,
+                )
+            } else {
+                // Filesystem changed (or both changed) -> filesystem wins
+                // For binary files, we don't have meaningful merge, so local edits take precedence
+                let result_type = if !doc_unchanged && !fs_unchanged {
+                    SyncResult::BothChanged {
+                        merged_len: fs_content.len(),
+                    }
+                } else {
+                    SyncResult::FilesystemChanged {
+                        new_len: fs_content.len(),
+                    }
+                };
+
+                // Update document with filesystem content
+                let mime_type = detect_mime_type(&fs_content, file_path.to_str());
+                doc.transact::<_, _, automerge::AutomergeError>(|tx| {
+                    tx.put(ROOT, "content", fs_content.clone())?;
+                    tx.put(ROOT, "mimeType", mime_type)?;
+                    tx.put(ROOT, "hash", fs_hash.clone())?;
+                    Ok(())
+                })
+                .map_err(|e| Error::Sync(format!("failed to update binary document: {:?}", e)))?;
+
+                (result_type, fs_content, fs_hash)
+            };
+
+            // Write to filesystem if needed (only if doc changed)
+            if matches!(result_type, SyncResult::AutomergeChanged { .. }) {
+                if write_if_unchanged(file_path, &final_content, &snapshot)?
+                    == WriteOutcome::Conflict
+                {
+                    // Local edit since the read; the document was not modified,
+                    // so simply start over
+                    return Ok(None);
+                }
+                debug!(
+                    doc_id = %doc_id,
+                    path = %file_path.display(),
+                    "Wrote binary content to filesystem"
+                );
+            }
+
+            // Update sync checkpoint
+            let new_heads = doc.get_heads();
+            sync_state.set_checkpoint(doc_id, &new_heads, &final_hash);
+
+            Ok(Some(result_type))
+        })?;
+
+        if let Some(result) = result {
+            return Ok(result);
+        }
+        info!(
+            doc_id = %doc_id,
+            path = %file_path.display(),
+            "Binary file changed on disk during sync, syncing again"
+        );
+    }
+
+    Err(Error::Sync(format!(
+        "file {} kept changing on disk; gave up after {} attempts",
+        file_path.display(),
+        MAX_WRITE_ATTEMPTS
+    )))
+}
+
 /// Synchronize a document based on its type (text or binary).
 ///
 /// Detects the document type and dispatches to the appropriate sync function.
@@ -1216,4 +1315,35 @@ mod tests {
         let result = sync_document_auto(&handle, &file_path, &mut sync_state);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_reconcile_again_after_concurrent_disk_edit() {
+        let temp = TempDir::new().unwrap();
+        let mut sync_state = SyncState::load(temp.path()).unwrap();
+        let doc_id = "doc";
+
+        // Synced at "a\nb\nc\n"
+        let mut doc = create_doc_with_text("a\nb\nc\n");
+        sync_state.set_checkpoint(doc_id, &doc.get_heads(), &sha256_hash("a\nb\nc\n"));
+
+        // A collaborator edits the first line, the file's last line is edited
+        let (_, text_obj) = doc.get(ROOT, "text").unwrap().unwrap();
+        doc.transact::<_, _, automerge::AutomergeError>(|tx| {
+            tx.update_text(&text_obj, "A\nb\nc\n")
+        })
+        .unwrap();
+        let first = reconcile_text(&mut doc, doc_id, "a\nb\nC\n", &sync_state)
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.merged_content, "A\nb\nC\n");
+
+        // The write is refused because the middle line was edited on disk
+        // meanwhile; the retry merges relative to the content already merged
+        sync_state.set_checkpoint(doc_id, &first.fs_heads, &sha256_hash("a\nb\nC\n"));
+        let second = reconcile_text(&mut doc, doc_id, "a\nB\nC\n", &sync_state)
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.merged_content, "A\nB\nC\n");
+        assert!(matches!(second.result, SyncResult::BothChanged { .. }));
+    }
 }
@@ -13,6 +13,7 @@ use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
 use crate::error::{Error, Result};
+use crate::ignore::SyncFilter;
 
 /// Default debounce duration for filesystem events (in milliseconds).
 /// This batches rapid file saves into a single event.
@@ -30,12 +31,16 @@ pub enum WatchEvent {
 pub struct WatchConfig {
     /// Debounce duration in milliseconds
     pub debounce_ms: u64,
+
+    /// Files to leave out of sync (ignore rules and size limit)
+    pub filter: SyncFilter,
 }
 
 impl Default for WatchConfig {
     fn default() -> Self {
         Self {
             debounce_ms: DEFAULT_DEBOUNCE_MS,
+            filter: SyncFilter::default(),
         }
     }
 }
@@ -55,10 +60,13 @@ pub struct FileWatcher {
 impl FileWatcher {
     /// Create a new filesystem watcher for the given project root.
     ///
-    /// The watcher will recursively watch for changes to .qmd files.
+    /// The watcher will recursively watch for changes to .qmd files that
+    /// are not excluded by `config.filter`.
     pub fn new(project_root: &Path, config: WatchConfig) -> Result<Self> {
         let (event_tx, event_rx) = mpsc::unbounded_channel();
         let project_root = project_root.to_path_buf();
+        let filter = config.filter;
+        let watched_root = project_root.clone();
 
         // Create a debounced watcher
         let mut debouncer = new_debouncer(
@@ -67,8 +75,10 @@ impl FileWatcher {
                 match res {
                     Ok(events) => {
                         for event in events {
-                            // Filter for .qmd files
-                            if is_qmd_file(&event.path) {
+                            // Filter for synced .qmd files
+                            if is_qmd_file(&event.path)
+                                && is_synced(&filter, &watched_root, &event.path)
+                            {
                                 debug!(path = %event.path.display(), "File change detected");
                                 if event_tx.send(WatchEvent::Modified(event.path)).is_err() {
                                     // Receiver dropped, watcher should stop
@@ -118,6 +128,17 @@ fn is_qmd_file(path: &Path) -> bool {
         .is_some_and(|ext| ext.eq_ignore_ascii_case("qmd"))
 }
 
+/// Check if a changed file is included in sync.
+fn is_synced(filter: &SyncFilter, project_root: &Path, path: &Path) -> bool {
+    let Ok(relative) = path.strip_prefix(project_root) else {
+        return false;
+    };
+    if filter.is_path_ignored(relative) {
+        return false;
+    }
+    std::fs::metadata(path).map_or(true, |m| filter.within_size_limit(m.len()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -133,6 +154,22 @@ mod tests {
         assert!(!is_qmd_file(Path::new("test")));
     }
 
+    #[test]
+    fn test_is_synced() {
+        let root = Path::new("/project");
+        let mut filter = SyncFilter::default();
+        filter.add_rules("drafts/\n");
+
+        assert!(is_synced(&filter, root, Path::new("/project/index.qmd")));
+        assert!(!is_synced(
+            &filter,
+            root,
+            Path::new("/project/drafts/a.qmd")
+        ));
+        assert!(!is_synced(&filter, root, Path::new("/project/_site/a.qmd")));
+        assert!(!is_synced(&filter, root, Path::new("/elsewhere/a.qmd")));
+    }
+
     #[tokio::test]
     async fn test_watcher_creation() {
         let temp = TempDir::new().unwrap();
@@ -153,7 +190,14 @@ mod tests {
         // Wait a bit for the file to be fully created
         tokio::time::sleep(Duration::from_millis(100)).await;
 
-        let mut watcher = FileWatcher::new(&temp_path, WatchConfig { debounce_ms: 100 }).unwrap();
+        let mut watcher = FileWatcher::new(
+            &temp_path,
+            WatchConfig {
+                debounce_ms: 100,
+                ..WatchConfig::default()
+            },
+        )
+        .unwrap();
 
         // Modify the file
         std::fs::write(&qmd_path, "modified content").unwrap();
@@ -184,7 +228,14 @@ mod tests {
 
         tokio::time::sleep(Duration::from_millis(100)).await;
 
-        let mut watcher = FileWatcher::new(&temp_path, WatchConfig { debounce_ms: 100 }).unwrap();
+        let mut watcher = FileWatcher::new(
+            &temp_path,
+            WatchConfig {
+                debounce_ms: 100,
+                ..WatchConfig::default()
+            },
+        )
+        .unwrap();
 
         // Modify the txt file (should be ignored)
         std::fs::write(&txt_path, "modified").unwrap();
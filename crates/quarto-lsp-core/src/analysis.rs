@@ -1,7 +1,8 @@
 //! Unified document analysis for extracting all intelligence data.
 //!
 //! This module provides `analyze_document()` which performs a single parse
-//! and extracts symbols, folding ranges, and diagnostics together.
+//! and extracts symbols, folding ranges, diagnostics, and document links
+//! together.
 //! This is more efficient than calling separate functions when you need
 //! multiple pieces of data.
 //!
@@ -14,7 +15,9 @@
 //! - `MetaShortcodeTransform` - Resolves `{{< meta key >}}` shortcodes so that
 //!   headers like `# {{< meta title >}}` appear correctly in the outline.
 
+use crate::diagnostics::{convert_fix, lint_messages};
 use crate::document::Document;
+use crate::document_links::get_document_links;
 use crate::types::{
     DetailKind, Diagnostic, DiagnosticDetail, DiagnosticSeverity, DocumentAnalysis, FoldingRange,
     FoldingRangeKind, MessageContent, Position, Range, Symbol, SymbolKind,
@@ -33,6 +36,7 @@ use quarto_source_map::SourceContext;
 /// - Symbols for document outline and navigation
 /// - Folding ranges for code folding
 /// - Diagnostics for errors and warnings
+/// - Links to URLs and files
 ///
 /// Before extracting symbols, this function runs analysis transforms to resolve
 /// shortcodes and other constructs that affect the document outline.
@@ -50,7 +54,22 @@ use quarto_source_map::SourceContext;
 /// println!("Found {} diagnostics", analysis.diagnostics.len());
 /// ```
 pub fn analyze_document(doc: &Document) -> DocumentAnalysis {
+    analyze(doc).analysis
+}
+
+/// The analysis of a document, as kept by the document.
+#[derive(Debug)]
+pub(crate) struct CachedAnalysis {
+    pub(crate) analysis: DocumentAnalysis,
+    /// Whether the document parsed. Without a parse, there are no symbols
+    /// or folding ranges to update incrementally.
+    pub(crate) parsed: bool,
+}
+
+/// Analyze a document (see [`analyze_document`]).
+pub(crate) fn analyze(doc: &Document) -> CachedAnalysis {
     let source_context = doc.create_source_context();
+    let document_links = get_document_links(doc);
 
     // Parse with pampa (single parse for all analysis)
     let result = pampa::readers::qmd::read(
@@ -85,17 +104,37 @@ pub fn analyze_document(doc: &Document) -> DocumentAnalysis {
                     diagnostics.push(d);
                 }
             }
-
-            DocumentAnalysis::with_data(symbols, folding_ranges, diagnostics, source_context)
+            diagnostics.extend(lint_diagnostics(doc, &source_context));
+
+            CachedAnalysis {
+                analysis: DocumentAnalysis::with_data(
+                    symbols,
+                    folding_ranges,
+                    diagnostics,
+                    document_links,
+                    source_context,
+                ),
+                parsed: true,
+            }
         }
         Err(errors) => {
             // Parsing failed - return diagnostics but empty symbols/folding ranges
-            let diagnostics = errors
+            let mut diagnostics: Vec<Diagnostic> = errors
                 .iter()
                 .filter_map(|msg| convert_diagnostic(msg, &source_context))
                 .collect();
-
-            DocumentAnalysis::with_data(Vec::new(), Vec::new(), diagnostics, source_context)
+            diagnostics.extend(lint_diagnostics(doc, &source_context));
+
+            CachedAnalysis {
+                analysis: DocumentAnalysis::with_data(
+                    Vec::new(),
+                    Vec::new(),
+                    diagnostics,
+                    document_links,
+                    source_context,
+                ),
+                parsed: false,
+            }
         }
     }
 }
@@ -474,9 +513,21 @@ fn convert_diagnostic(msg: &DiagnosticMessage, ctx: &SourceContext) -> Option<Di
         diagnostic = diagnostic.with_hint(MessageContent::from(hint));
     }
 
+    for fix in &msg.fixes {
+        diagnostic = diagnostic.with_fix(convert_fix(fix, ctx));
+    }
+
     Some(diagnostic)
 }
 
+/// Diagnostics for problems in the text that the parser accepts.
+fn lint_diagnostics(doc: &Document, ctx: &SourceContext) -> Vec<Diagnostic> {
+    lint_messages(doc.content())
+        .iter()
+        .filter_map(|msg| convert_diagnostic(msg, ctx))
+        .collect()
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -546,7 +597,7 @@ fn code_block_to_range(code_block: &CodeBlock, ctx: &SourceContext) -> Option<Ra
 }
 
 /// Extract plain text from a list of inlines.
-fn inlines_to_text(inlines: &Inlines) -> String {
+pub(crate) fn inlines_to_text(inlines: &Inlines) -> String {
     let mut text = String::new();
     for inline in inlines {
         inline_to_text(inline, &mut text);
@@ -619,6 +670,7 @@ fn inline_to_text(inline: &Inline, text: &mut String) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::DocumentAnalysisJson;
 
     #[test]
     fn analyze_document_basic() {
@@ -792,4 +844,21 @@ Content.
             .collect();
         assert!(!warnings.is_empty(), "Should have warning for missing key");
     }
+
+    #[test]
+    fn document_links_in_analysis() {
+        let doc = Document::new(
+            "test.qmd",
+            "---\nbibliography: refs.bib\n---\n\nSee [intro](intro.qmd).\n",
+        );
+
+        let analysis = DocumentAnalysisJson::from(analyze_document(&doc));
+
+        let targets: Vec<&str> = analysis
+            .document_links
+            .iter()
+            .map(|link| link.target.as_str())
+            .collect();
+        assert_eq!(targets, vec!["refs.bib", "intro.qmd"]);
+    }
 }
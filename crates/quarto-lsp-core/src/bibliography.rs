@@ -0,0 +1,240 @@
+//! Bibliographies of a document, for citation completion and hover.
+//!
+//! Entries come from the files named by `bibliography` (BibTeX, CSL JSON or
+//! CSL YAML) and from inline `references` in front matter. CSL entries keep
+//! their full item, so they can be formatted with a citation style; BibTeX
+//! entries only carry their key and title.
+
+use crate::completions::{front_matter, is_citation_char};
+use crate::types::{Location, Position, Range};
+use crate::workspace::{Workspace, join_path};
+
+/// A bibliography entry: its citation key and title.
+pub(crate) struct BibEntry {
+    pub(crate) key: String,
+    pub(crate) title: Option<String>,
+    /// The entry as a CSL JSON item, for CSL bibliographies
+    pub(crate) reference: Option<serde_json::Value>,
+    /// The bibliography file the entry is from, or `None` for inline
+    /// `references`
+    pub(crate) file: Option<String>,
+}
+
+/// The entries of the bibliographies in front matter, or in the project
+/// configuration if the document names none, and of inline `references`.
+pub(crate) fn bibliography_entries(lines: &[&str], workspace: &dyn Workspace) -> Vec<BibEntry> {
+    let mut entries = Vec::new();
+    let mut files = Vec::new();
+    if let Some(meta) = front_matter(lines) {
+        files = string_list(&meta.yaml["bibliography"]);
+        entries.extend(yaml_references(&meta.yaml["references"]));
+    }
+    if files.is_empty()
+        && let Some(project_dir) = workspace.project_dir()
+        && let Some(config) = ["_quarto.yml", "_quarto.yaml"]
+            .iter()
+            .find_map(|name| workspace.read_file(&join_path(&project_dir, name)))
+        && let Ok(config) = quarto_yaml::parse(&config)
+    {
+        files = string_list(&config.yaml["bibliography"])
+            .into_iter()
+            .map(|file| join_path(&project_dir, &file))
+            .collect();
+    }
+    for file in files {
+        if let Some(content) = workspace.read_file(&file) {
+            entries.extend(
+                parse_bibliography(&file, &content)
+                    .into_iter()
+                    .map(|entry| BibEntry {
+                        file: Some(file.clone()),
+                        ..entry
+                    }),
+            );
+        }
+    }
+    entries
+}
+
+/// The location of an entry's key in its bibliography file, or in the
+/// document (`content`) for inline `references`.
+pub(crate) fn entry_location(
+    entry: &BibEntry,
+    content: &str,
+    workspace: &dyn Workspace,
+) -> Option<Location> {
+    let file_content;
+    let content = match &entry.file {
+        Some(file) => {
+            file_content = workspace.read_file(file)?;
+            &file_content
+        }
+        None => content,
+    };
+    let range = key_range(content, &entry.key)?;
+    Some(Location {
+        path: entry.file.clone(),
+        range,
+    })
+}
+
+/// The range of `key` where it is defined: `@book{key,` in BibTeX,
+/// `"id": "key"` in CSL JSON and `id: key` in CSL YAML.
+fn key_range(content: &str, key: &str) -> Option<Range> {
+    content.lines().enumerate().find_map(|(index, line)| {
+        let text = line.trim_start().trim_start_matches("- ").trim_start();
+        if !(text.starts_with('@') || text.starts_with("id:") || text.contains("\"id\"")) {
+            return None;
+        }
+        let (start, _) = line.match_indices(key).find(|(start, _)| {
+            let before = line[..*start].chars().next_back();
+            let after = line[start + key.len()..].chars().next();
+            !before.is_some_and(is_citation_char) && !after.is_some_and(is_citation_char)
+        })?;
+        let column = line[..start].chars().count() as u32;
+        let width = key.chars().count() as u32;
+        Some(Range::new(
+            Position::new(index as u32, column),
+            Position::new(index as u32, column + width),
+        ))
+    })
+}
+
+/// The entries of a bibliography file, by its extension.
+fn parse_bibliography(path: &str, content: &str) -> Vec<BibEntry> {
+    let extension = path.rsplit('.').next().unwrap_or("").to_lowercase();
+    match extension.as_str() {
+        "bib" | "bibtex" => bibtex_entries(content),
+        "json" => serde_json::from_str::<serde_json::Value>(content)
+            .map(|json| csl_json_entries(&json))
+            .unwrap_or_default(),
+        "yaml" | "yml" => quarto_yaml::parse(content)
+            .map(|yaml| {
+                let references = &yaml.yaml["references"];
+                if references.is_badvalue() {
+                    yaml_references(&yaml.yaml)
+                } else {
+                    yaml_references(references)
+                }
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// The entries of a BibTeX file: `@type{key, title = {...}, ...}`.
+fn bibtex_entries(content: &str) -> Vec<BibEntry> {
+    let mut entries = Vec::new();
+    for chunk in content.split('@').skip(1) {
+        let Some(open) = chunk.find(['{', '(']) else {
+            continue;
+        };
+        let entry_type = chunk[..open].trim().to_lowercase();
+        if matches!(entry_type.as_str(), "comment" | "string" | "preamble") {
+            continue;
+        }
+        let body = &chunk[open + 1..];
+        let Some(comma) = body.find(',') else {
+            continue;
+        };
+        let key = body[..comma].trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            continue;
+        }
+        entries.push(BibEntry {
+            key: key.to_string(),
+            title: bibtex_title(&body[comma + 1..]),
+            reference: None,
+            file: None,
+        });
+    }
+    entries
+}
+
+/// The `title` field of a BibTeX entry body.
+fn bibtex_title(body: &str) -> Option<String> {
+    body.split(",\n").find_map(|field| {
+        let (name, value) = field.split_once('=')?;
+        if !name.trim().eq_ignore_ascii_case("title") {
+            return None;
+        }
+        let title: String = value
+            .trim()
+            .trim_end_matches(['}', ')'])
+            .chars()
+            .filter(|c| !matches!(c, '{' | '}' | '"'))
+            .collect();
+        let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+        (!title.is_empty()).then_some(title)
+    })
+}
+
+/// The entries of a CSL JSON bibliography.
+fn csl_json_entries(json: &serde_json::Value) -> Vec<BibEntry> {
+    let items = match json {
+        serde_json::Value::Array(items) => items.as_slice(),
+        serde_json::Value::Object(_) => std::slice::from_ref(json),
+        _ => &[],
+    };
+    items.iter().filter_map(csl_entry).collect()
+}
+
+/// The entries of a YAML list of CSL references (`references:`).
+fn yaml_references(references: &yaml_rust2::Yaml) -> Vec<BibEntry> {
+    references
+        .as_vec()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| csl_entry(&yaml_to_json(item)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The entry for a CSL JSON item, if it has an `id`.
+fn csl_entry(item: &serde_json::Value) -> Option<BibEntry> {
+    let key = match &item["id"] {
+        serde_json::Value::String(id) => id.clone(),
+        serde_json::Value::Number(id) => id.to_string(),
+        _ => return None,
+    };
+    let title = item["title"].as_str().map(String::from);
+    Some(BibEntry {
+        key,
+        title,
+        reference: Some(item.clone()),
+        file: None,
+    })
+}
+
+/// Convert a CSL YAML value to its CSL JSON form.
+fn yaml_to_json(yaml: &yaml_rust2::Yaml) -> serde_json::Value {
+    use yaml_rust2::Yaml;
+    match yaml {
+        Yaml::String(s) => serde_json::Value::String(s.clone()),
+        Yaml::Integer(i) => serde_json::Value::from(*i),
+        Yaml::Real(r) => r.parse::<f64>().map_or_else(
+            |_| serde_json::Value::String(r.clone()),
+            serde_json::Value::from,
+        ),
+        Yaml::Boolean(b) => serde_json::Value::Bool(*b),
+        Yaml::Array(items) => items.iter().map(yaml_to_json).collect(),
+        Yaml::Hash(hash) => hash
+            .iter()
+            .filter_map(|(key, value)| Some((key.as_str()?.to_string(), yaml_to_json(value))))
+            .collect(),
+        Yaml::Null | Yaml::BadValue | Yaml::Alias(_) => serde_json::Value::Null,
+    }
+}
+
+/// A YAML string or list of strings.
+pub(crate) fn string_list(value: &yaml_rust2::Yaml) -> Vec<String> {
+    match value.as_vec() {
+        Some(items) => items
+            .iter()
+            .filter_map(|item| item.as_str().map(String::from))
+            .collect(),
+        None => value.as_str().map(String::from).into_iter().collect(),
+    }
+}
@@ -0,0 +1,147 @@
+//! Code actions for QMD documents.
+//!
+//! The quick fixes offered for a range are the fixes of the diagnostics
+//! overlapping it:
+//!
+//! - **Unclosed delimiters** (`*emph`, `[++insert`, ...): insert the
+//!   closing delimiter where the block ends
+//! - **Apostrophes read as quotes** (`d'`code``): escape the apostrophe
+//! - **Unclosed divs**: add the closing `:::` at the end of the document
+//! - **Div fences without a space** (`:::{.callout-note}`): add the space
+//! - **Missing newline at the end of the file**: add it
+//! - **Misspelled words**: replace the word with a suggestion, or add it
+//!   to the project's words file (see [`crate::spelling`])
+
+use crate::diagnostics::get_diagnostics;
+use crate::document::Document;
+use crate::types::{CodeAction, Diagnostic, Range};
+
+/// Get the quick fixes for the diagnostics overlapping a range.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, Position, Range, get_code_actions};
+///
+/// let doc = Document::new("test.qmd", "Text *emph\n");
+/// let range = Range::point(Position::new(0, 5));
+/// let actions = get_code_actions(&doc, range);
+/// assert_eq!(actions[0].title, "Insert closing `*`");
+/// ```
+pub fn get_code_actions(doc: &Document, range: Range) -> Vec<CodeAction> {
+    get_fix_actions(&get_diagnostics(doc).diagnostics, range)
+}
+
+/// Get the quick fixes of the given diagnostics that overlap a range, for
+/// diagnostics found separately, such as misspellings.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{get_fix_actions, get_spelling_diagnostics};
+///
+/// let diagnostics = get_spelling_diagnostics(&doc, &dictionary, &config);
+/// let actions = get_fix_actions(&diagnostics, range);
+/// ```
+pub fn get_fix_actions(diagnostics: &[Diagnostic], range: Range) -> Vec<CodeAction> {
+    diagnostics
+        .iter()
+        .filter(|diagnostic| overlaps(diagnostic.range, range))
+        .flat_map(|diagnostic| {
+            diagnostic
+                .fixes
+                .iter()
+                .map(|fix| CodeAction {
+                    title: fix.title.clone(),
+                    diagnostic: diagnostic.clone(),
+                    edits: fix.edits.clone(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Whether two ranges overlap or touch, so that a cursor at either end of
+/// a diagnostic gets its fixes.
+fn overlaps(a: Range, b: Range) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Position;
+
+    /// The document after applying the first action at `position`.
+    fn fix(content: &str, position: Position) -> String {
+        let doc = Document::new("test.qmd", content);
+        let actions = get_code_actions(&doc, Range::point(position));
+        let action = actions.first().expect("a code action");
+        let lines: Vec<&str> = content.split('\n').collect();
+        let offset = |position: Position| {
+            lines[..position.line as usize]
+                .iter()
+                .map(|line| line.len() + 1)
+                .sum::<usize>()
+                + position.character as usize
+        };
+        let mut edits = action.edits.clone();
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.location.range.start));
+        let mut fixed = content.to_string();
+        for edit in edits {
+            let range = edit.location.range;
+            fixed.replace_range(offset(range.start)..offset(range.end), &edit.new_text);
+        }
+        fixed
+    }
+
+    #[test]
+    fn insert_closing_delimiter() {
+        assert_eq!(fix("Text *emph\n", Position::new(0, 10)), "Text *emph*\n");
+    }
+
+    #[test]
+    fn escape_apostrophe() {
+        assert_eq!(
+            fix("d'`code` here\n", Position::new(0, 13)),
+            "d\\'`code` here\n"
+        );
+    }
+
+    #[test]
+    fn close_div() {
+        assert_eq!(
+            fix("::: {.callout-note}\nText\n", Position::new(0, 1)),
+            "::: {.callout-note}\nText\n:::\n"
+        );
+    }
+
+    #[test]
+    fn closed_divs_and_code_have_no_actions() {
+        let doc = Document::new(
+            "test.qmd",
+            "::: {.note}\n```\n:::\n```\n:::\n\n::: outer\n:::: inner\nText\n::::\n:::\n",
+        );
+        let range = Range::new(Position::new(0, 0), Position::new(11, 0));
+        assert!(get_code_actions(&doc, range).is_empty());
+    }
+
+    #[test]
+    fn space_after_div_fence() {
+        assert_eq!(
+            fix(":::{.callout-note}\nText\n:::\n", Position::new(0, 0)),
+            "::: {.callout-note}\nText\n:::\n"
+        );
+    }
+
+    #[test]
+    fn newline_at_end_of_file() {
+        assert_eq!(fix("Text", Position::new(0, 4)), "Text\n");
+    }
+
+    #[test]
+    fn no_actions_away_from_diagnostics() {
+        let doc = Document::new("test.qmd", "Fine.\n\nText *emph\n");
+        assert!(get_code_actions(&doc, Range::point(Position::new(0, 2))).is_empty());
+    }
+}
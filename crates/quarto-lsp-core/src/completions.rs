@@ -0,0 +1,645 @@
+//! Completions for QMD documents.
+//!
+//! This module offers completions at a position in a document:
+//!
+//! - **Front matter** keys and values, from the Quarto schema
+//!   (see [`quarto_config::config_completion_model`])
+//! - **Citations** after `@`, from the document's and the project's
+//!   bibliographies and inline `references`
+//! - **Cross-references** after `@`, from the labels defined in the document
+//! - **File paths** in link and image targets, `{{< include >}}` shortcodes
+//!   and path-valued front matter options
+//! - **Shortcode names** after `{{<`
+//!
+//! Completion works on the document text rather than the parsed AST, so it
+//! keeps working while the user is in the middle of typing a construct that
+//! doesn't parse yet.
+//!
+//! Files are read through the [`Workspace`] trait, so the same analysis runs
+//! natively (reading the filesystem) and in WASM (reading from a virtual
+//! file system).
+
+use crate::bibliography::bibliography_entries;
+use crate::document::Document;
+use crate::types::{CompletionItem, CompletionItemKind, Position, Range};
+use crate::workspace::Workspace;
+use quarto_yaml_validation::CompletionNode;
+use std::collections::HashSet;
+
+/// Cross-reference label prefixes and the kinds they label.
+const CROSSREF_KINDS: &[(&str, &str)] = &[
+    ("fig", "Figure"),
+    ("tbl", "Table"),
+    ("lst", "Listing"),
+    ("sec", "Section"),
+    ("eq", "Equation"),
+    ("thm", "Theorem"),
+    ("lem", "Lemma"),
+    ("cor", "Corollary"),
+    ("prp", "Proposition"),
+    ("cnj", "Conjecture"),
+    ("def", "Definition"),
+    ("exm", "Example"),
+    ("exr", "Exercise"),
+];
+
+/// Built-in shortcodes and their descriptions.
+const SHORTCODES: &[(&str, &str)] = &[
+    ("meta", "Insert a document metadata value"),
+    ("var", "Insert a value from _variables.yml"),
+    ("env", "Insert an environment variable"),
+    ("include", "Include the contents of another file"),
+    ("embed", "Embed a cell from a notebook"),
+    ("kbd", "Show a keyboard shortcut"),
+    ("pagebreak", "Insert a page break"),
+    ("video", "Embed a video"),
+];
+
+/// Front matter options whose values are file paths.
+const PATH_OPTIONS: &[&str] = &[
+    "bibliography",
+    "csl",
+    "css",
+    "template",
+    "output-file",
+    "include-in-header",
+    "include-before-body",
+    "include-after-body",
+    "metadata-files",
+    "resources",
+];
+
+/// Get the completions at a position in a document.
+///
+/// Returns an empty list when nothing can be completed at the position.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, NoWorkspace, Position, get_completions};
+///
+/// let doc = Document::new("test.qmd", "---\ntoc-d\n---\n");
+/// let items = get_completions(&doc, Position::new(1, 5), &NoWorkspace);
+/// assert!(items.iter().any(|item| item.label == "toc-depth"));
+/// ```
+pub fn get_completions(
+    doc: &Document,
+    position: Position,
+    workspace: &dyn Workspace,
+) -> Vec<CompletionItem> {
+    let lines: Vec<&str> = doc.content().lines().collect();
+    let line_index = position.line as usize;
+    // The cursor may sit on a new, still empty last line
+    let line = lines.get(line_index).copied().unwrap_or("");
+    let prefix = line_prefix(line, position.character);
+
+    if let Some(end) = front_matter_end(&lines)
+        && line_index > 0
+        && line_index < end
+    {
+        return front_matter_completions(&lines[1..line_index], prefix, position, workspace);
+    }
+
+    if let Some(partial) = shortcode_name(prefix) {
+        return shortcode_completions(partial, position);
+    }
+    if let Some(partial) = include_path(prefix).or_else(|| link_target(prefix)) {
+        return path_completions(partial, position, workspace);
+    }
+    if let Some(partial) = citation_key(prefix) {
+        let mut items = crossref_completions(&lines, partial, position);
+        items.extend(citation_completions(&lines, partial, position, workspace));
+        return items;
+    }
+    Vec::new()
+}
+
+/// Get the completions after `@` that don't depend on the cursor: the
+/// cross-reference labels and citation keys available to a document.
+///
+/// This lets clients that analyze a document once (such as the hub client)
+/// complete `@` references without asking for completions on every
+/// keystroke. The items have empty ranges at the start of the document; the
+/// client replaces the word being typed instead.
+pub fn get_reference_completions(doc: &Document, workspace: &dyn Workspace) -> Vec<CompletionItem> {
+    let lines: Vec<&str> = doc.content().lines().collect();
+    let position = Position::new(0, 0);
+    let mut items = crossref_completions(&lines, "", position);
+    items.extend(citation_completions(&lines, "", position, workspace));
+    items
+}
+
+// ============================================================================
+// Front Matter
+// ============================================================================
+
+/// Completions for a front matter line. `previous` holds the front matter
+/// lines before the cursor's line.
+fn front_matter_completions(
+    previous: &[&str],
+    prefix: &str,
+    position: Position,
+    workspace: &dyn Workspace,
+) -> Vec<CompletionItem> {
+    let model = quarto_config::config_completion_model();
+    let mut indent = prefix.len() - prefix.trim_start().len();
+    let mut text = prefix.trim_start();
+    let in_list = text.starts_with("- ") || text == "-";
+    if in_list {
+        text = text.trim_start_matches('-').trim_start();
+        indent = prefix.len() - text.len();
+    }
+    let path = parent_keys(previous, indent);
+    let path: Vec<&str> = path.iter().map(String::as_str).collect();
+
+    match text.split_once(':') {
+        // `key: value`
+        Some((key, value)) => {
+            let partial = value.trim_start();
+            let range = word_range(position, partial);
+            if PATH_OPTIONS.contains(&key.trim()) {
+                return path_completions(partial, position, workspace);
+            }
+            let mut key_path = path.clone();
+            key_path.push(key.trim());
+            let Some(node) = model.at_path(&key_path) else {
+                return Vec::new();
+            };
+            value_items(node, partial, range)
+        }
+        // A list item: either a value or the first key of a mapping
+        None if in_list => {
+            let Some(node) = model.at_path(&path) else {
+                return Vec::new();
+            };
+            let range = word_range(position, text);
+            if let Some(last) = path.last()
+                && PATH_OPTIONS.contains(last)
+            {
+                return path_completions(text, position, workspace);
+            }
+            let mut items = value_items(node, text, range);
+            if let Some(item_node) = &node.items {
+                items.extend(value_items(item_node, text, range));
+                items.extend(key_items(item_node, text, range));
+            }
+            items
+        }
+        // A key
+        None => {
+            let Some(node) = model.at_path(&path) else {
+                return Vec::new();
+            };
+            let siblings = sibling_keys(previous, indent);
+            key_items(node, text, word_range(position, text))
+                .into_iter()
+                .filter(|item| !siblings.contains(&item.label))
+                .collect()
+        }
+    }
+}
+
+/// Completions for the keys of `node` starting with `partial`.
+fn key_items(node: &CompletionNode, partial: &str, range: Range) -> Vec<CompletionItem> {
+    node.keys
+        .iter()
+        .filter(|(key, _)| key.starts_with(partial))
+        .map(|(key, child)| {
+            let item = CompletionItem::new(key.clone(), CompletionItemKind::Key, range)
+                .with_insert_text(format!("{}: ", key));
+            let item = match &child.description {
+                Some(description) => item.with_detail(description.clone()),
+                None => item,
+            };
+            match &child.documentation {
+                Some(documentation) => item.with_documentation(documentation.clone()),
+                None => item,
+            }
+        })
+        .collect()
+}
+
+/// Completions for the values of `node` starting with `partial`.
+fn value_items(node: &CompletionNode, partial: &str, range: Range) -> Vec<CompletionItem> {
+    node.values
+        .iter()
+        .filter(|value| value.starts_with(partial))
+        .map(|value| CompletionItem::new(value.clone(), CompletionItemKind::Value, range))
+        .collect()
+}
+
+/// The keys of the mappings enclosing a line indented by `indent`, outermost
+/// first, found by walking back through the previous lines.
+pub(crate) fn parent_keys(previous: &[&str], indent: usize) -> Vec<String> {
+    let mut keys = Vec::new();
+    let mut limit = indent;
+    for line in previous.iter().rev() {
+        let Some((line_indent, key)) = key_line(line) else {
+            continue;
+        };
+        if line_indent < limit {
+            keys.push(key.to_string());
+            limit = line_indent;
+            if limit == 0 {
+                break;
+            }
+        }
+    }
+    keys.reverse();
+    keys
+}
+
+/// The keys already written at `indent` in the enclosing mapping.
+fn sibling_keys(previous: &[&str], indent: usize) -> HashSet<String> {
+    let mut keys = HashSet::new();
+    for line in previous.iter().rev() {
+        let Some((line_indent, key)) = key_line(line) else {
+            continue;
+        };
+        if line_indent < indent {
+            break;
+        }
+        if line_indent == indent {
+            keys.insert(key.to_string());
+        }
+    }
+    keys
+}
+
+/// The indentation and key of a `key:` line. List item markers count as
+/// indentation, so `- key:` is a key inside the item's mapping.
+pub(crate) fn key_line(line: &str) -> Option<(usize, &str)> {
+    let text = line.trim_start().trim_start_matches("- ").trim_start();
+    if text.is_empty() || text.starts_with('#') {
+        return None;
+    }
+    let (key, _) = text.split_once(':')?;
+    let key = key.trim();
+    if key.is_empty() || key.contains(' ') {
+        return None;
+    }
+    Some((line.len() - text.len(), key))
+}
+
+/// The line index of the front matter's closing `---`, if the document
+/// starts with front matter. Unclosed front matter extends to the end of
+/// the document.
+pub(crate) fn front_matter_end(lines: &[&str]) -> Option<usize> {
+    if lines.first().map(|line| line.trim_end()) != Some("---") {
+        return None;
+    }
+    let end = lines
+        .iter()
+        .enumerate()
+        .skip(1)
+        .find(|(_, line)| matches!(line.trim_end(), "---" | "..."))
+        .map_or(lines.len() + 1, |(i, _)| i);
+    Some(end)
+}
+
+/// The front matter of the document, parsed.
+pub(crate) fn front_matter(lines: &[&str]) -> Option<quarto_yaml::YamlWithSourceInfo> {
+    let end = front_matter_end(lines)?.min(lines.len());
+    let yaml = lines[1..end].join("\n");
+    quarto_yaml::parse(&yaml).ok()
+}
+
+// ============================================================================
+// Citations and Cross-references
+// ============================================================================
+
+/// The citation key being typed after `@`, if any.
+fn citation_key(prefix: &str) -> Option<&str> {
+    let at = prefix.rfind('@')?;
+    let before = prefix[..at].chars().next_back();
+    if before.is_some_and(|c| !(c.is_whitespace() || matches!(c, '[' | ';' | '-' | '('))) {
+        // An email address or other text
+        return None;
+    }
+    let key = &prefix[at + 1..];
+    key.chars().all(is_citation_char).then_some(key)
+}
+
+pub(crate) fn is_citation_char(c: char) -> bool {
+    c.is_alphanumeric()
+        || matches!(
+            c,
+            '_' | '-' | ':' | '.' | '#' | '$' | '%' | '&' | '+' | '?' | '~' | '/'
+        )
+}
+
+/// Completions for the cross-reference labels defined in the document.
+fn crossref_completions(lines: &[&str], partial: &str, position: Position) -> Vec<CompletionItem> {
+    let range = word_range(position, partial);
+    let mut seen = HashSet::new();
+    crossref_labels(lines)
+        .into_iter()
+        .filter(|label| label.starts_with(partial) && seen.insert(label.clone()))
+        .filter_map(|label| {
+            let kind = crossref_kind(&label)?;
+            Some(CompletionItem::new(label, CompletionItemKind::CrossRef, range).with_detail(kind))
+        })
+        .collect()
+}
+
+/// The labels defined in the document: `#id`s in attributes (`{#fig-plot}`)
+/// and `label` cell options (`#| label: fig-plot`).
+pub(crate) fn crossref_labels(lines: &[&str]) -> Vec<String> {
+    let mut labels = Vec::new();
+    for line in lines {
+        if let Some(label) = line
+            .trim_start()
+            .strip_prefix("#|")
+            .and_then(|option| option.trim_start().strip_prefix("label:"))
+        {
+            labels.push(label.trim().trim_matches(['"', '\'']).to_string());
+            continue;
+        }
+        let mut rest = *line;
+        while let Some(open) = rest.find('{') {
+            let attributes = &rest[open + 1..];
+            let close = attributes.find('}').unwrap_or(attributes.len());
+            labels.extend(
+                attributes[..close]
+                    .split_whitespace()
+                    .filter_map(|token| token.strip_prefix('#'))
+                    .map(String::from),
+            );
+            rest = &attributes[close..];
+        }
+    }
+    labels
+}
+
+/// The kind of target a label refers to, from its prefix.
+pub(crate) fn crossref_kind(label: &str) -> Option<&'static str> {
+    let (prefix, rest) = label.split_once('-')?;
+    if rest.is_empty() {
+        return None;
+    }
+    CROSSREF_KINDS
+        .iter()
+        .find(|(key, _)| *key == prefix)
+        .map(|(_, kind)| *kind)
+}
+
+/// Completions for the citation keys of the document's bibliographies.
+fn citation_completions(
+    lines: &[&str],
+    partial: &str,
+    position: Position,
+    workspace: &dyn Workspace,
+) -> Vec<CompletionItem> {
+    let range = word_range(position, partial);
+    let mut seen = HashSet::new();
+    bibliography_entries(lines, workspace)
+        .into_iter()
+        .filter(|entry| entry.key.starts_with(partial) && seen.insert(entry.key.clone()))
+        .map(|entry| {
+            let item = CompletionItem::new(entry.key, CompletionItemKind::Citation, range);
+            match entry.title {
+                Some(title) => item.with_detail(title),
+                None => item,
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// Shortcodes and Paths
+// ============================================================================
+
+/// The shortcode name being typed after `{{<`, if any.
+fn shortcode_name(prefix: &str) -> Option<&str> {
+    let open = prefix.rfind("{{<")?;
+    let name = prefix[open + 3..].trim_start();
+    (!name.contains([' ', '>'])).then_some(name)
+}
+
+/// Completions for built-in shortcode names.
+fn shortcode_completions(partial: &str, position: Position) -> Vec<CompletionItem> {
+    let range = word_range(position, partial);
+    SHORTCODES
+        .iter()
+        .filter(|(name, _)| name.starts_with(partial))
+        .map(|(name, description)| {
+            CompletionItem::new(*name, CompletionItemKind::Shortcode, range)
+                .with_detail(*description)
+        })
+        .collect()
+}
+
+/// The path being typed in `{{< include path`, if any.
+fn include_path(prefix: &str) -> Option<&str> {
+    let open = prefix.rfind("{{<")?;
+    let path = prefix[open + 3..].trim_start().strip_prefix("include ")?;
+    let path = path.trim_start();
+    (!path.contains([' ', '>'])).then_some(path)
+}
+
+/// The link or image target being typed in `[text](target`, if any.
+fn link_target(prefix: &str) -> Option<&str> {
+    let open = prefix.rfind("](")?;
+    let target = &prefix[open + 2..];
+    let is_path = !target.contains([')', ' ', '#', '?'])
+        && !target.contains("://")
+        && !target.starts_with("mailto:");
+    is_path.then_some(target)
+}
+
+/// Completions for the files and directories matching a partial path.
+fn path_completions(
+    partial: &str,
+    position: Position,
+    workspace: &dyn Workspace,
+) -> Vec<CompletionItem> {
+    let (dir, name) = match partial.rfind('/') {
+        Some(slash) => (&partial[..slash + 1], &partial[slash + 1..]),
+        None => ("", partial),
+    };
+    let range = word_range(position, name);
+    let mut entries = workspace.read_dir(dir.trim_end_matches('/'));
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    entries
+        .into_iter()
+        // Hidden files are only offered once a `.` has been typed
+        .filter(|entry| {
+            entry.name.starts_with(name) && (name.starts_with('.') || !entry.name.starts_with('.'))
+        })
+        .map(|entry| {
+            if entry.is_dir {
+                CompletionItem::new(entry.name.clone(), CompletionItemKind::Folder, range)
+                    .with_insert_text(format!("{}/", entry.name))
+            } else {
+                CompletionItem::new(entry.name, CompletionItemKind::File, range)
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// The text of `line` before the cursor at `character`.
+pub(crate) fn line_prefix(line: &str, character: u32) -> &str {
+    let end = line
+        .char_indices()
+        .nth(character as usize)
+        .map_or(line.len(), |(i, _)| i);
+    &line[..end]
+}
+
+/// The range of `word`, which ends at the cursor.
+fn word_range(position: Position, word: &str) -> Range {
+    let width = word.chars().count() as u32;
+    Range::new(
+        Position::new(position.line, position.character.saturating_sub(width)),
+        position,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::{MemoryWorkspace, NoWorkspace};
+
+    /// Completions at the last `|` in `content` (earlier ones may be cell
+    /// option markers).
+    fn complete(content: &str, workspace: &dyn Workspace) -> Vec<CompletionItem> {
+        let offset = content.rfind('|').expect("cursor marker");
+        let before = &content[..offset];
+        let line = before.matches('\n').count() as u32;
+        let character = before.rsplit('\n').next().unwrap().chars().count() as u32;
+        let doc = Document::new("test.qmd", format!("{}{}", before, &content[offset + 1..]));
+        get_completions(&doc, Position::new(line, character), workspace)
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|item| item.label.as_str()).collect()
+    }
+
+    #[test]
+    fn front_matter_keys() {
+        let items = complete("---\ntitle: Test\ntoc|\n---\n", &NoWorkspace);
+        assert_eq!(labels(&items), vec!["toc", "toc-depth", "toc-title"]);
+        assert_eq!(items[1].text(), "toc-depth: ");
+        assert_eq!(items[1].range.start, Position::new(2, 0));
+    }
+
+    #[test]
+    fn front_matter_skips_existing_keys() {
+        let items = complete("---\ntoc: true\ntoc|\n---\n", &NoWorkspace);
+        assert_eq!(labels(&items), vec!["toc-depth", "toc-title"]);
+    }
+
+    #[test]
+    fn front_matter_nested_keys() {
+        let items = complete("---\nformat:\n  html:\n    toc-l|\n---\n", &NoWorkspace);
+        assert_eq!(labels(&items), vec!["toc-location"]);
+    }
+
+    #[test]
+    fn front_matter_values() {
+        let items = complete("---\nengine: j|\n---\n", &NoWorkspace);
+        assert_eq!(labels(&items), vec!["jupyter", "julia"]);
+        assert_eq!(items[0].range.start, Position::new(1, 8));
+
+        let items = complete("---\nformat:\n  html:\n    toc: |\n", &NoWorkspace);
+        assert_eq!(labels(&items), vec!["true", "false"]);
+    }
+
+    #[test]
+    fn front_matter_path_values() {
+        let workspace = MemoryWorkspace::default()
+            .with_file("refs.bib", "")
+            .with_file("data/more.bib", "");
+        let items = complete("---\nbibliography: |\n---\n", &workspace);
+        assert_eq!(labels(&items), vec!["data", "refs.bib"]);
+        assert_eq!(items[0].text(), "data/");
+    }
+
+    #[test]
+    fn crossref_labels_from_document() {
+        let content = "# Intro {#sec-intro}\n\n![Plot](plot.png){#fig-plot width=50%}\n\n```{r}\n#| label: tbl-data\n```\n\nSee @|\n";
+        let items = complete(content, &NoWorkspace);
+        assert_eq!(labels(&items), vec!["sec-intro", "fig-plot", "tbl-data"]);
+        assert_eq!(items[1].detail.as_deref(), Some("Figure"));
+
+        let content = "![Plot](plot.png){#fig-plot}\n\n# Intro {#sec-intro}\n\nSee @fig|\n";
+        assert_eq!(labels(&complete(content, &NoWorkspace)), vec!["fig-plot"]);
+    }
+
+    #[test]
+    fn citations_from_bibliographies() {
+        let workspace = MemoryWorkspace::default()
+            .with_file(
+                "refs.bib",
+                "@article{knuth1984,\n  title = {Literate {P}rogramming},\n  year = 1984\n}\n@comment{ignored}\n",
+            )
+            .with_file("refs.json", r#"[{"id": "xie2015", "title": "Dynamic Documents"}]"#);
+        let content = "---\nbibliography: [refs.bib, refs.json]\nreferences:\n  - id: inline2020\n    title: Inline\n---\n\nAs shown [@|\n";
+        let items = complete(content, &workspace);
+        assert_eq!(labels(&items), vec!["inline2020", "knuth1984", "xie2015"]);
+        assert_eq!(items[1].detail.as_deref(), Some("Literate Programming"));
+        assert_eq!(items[1].kind, CompletionItemKind::Citation);
+    }
+
+    #[test]
+    fn reference_completions_for_whole_document() {
+        let workspace = MemoryWorkspace::default()
+            .with_file("refs.bib", "@book{knuth1984,\n  title = {TeX}\n}\n");
+        let doc = Document::new(
+            "test.qmd",
+            "---\nbibliography: refs.bib\n---\n\n![Plot](plot.png){#fig-plot}\n\nSee @fig-plot.\n",
+        );
+        let items = get_reference_completions(&doc, &workspace);
+        assert_eq!(labels(&items), vec!["fig-plot", "knuth1984"]);
+        assert_eq!(items[0].kind, CompletionItemKind::CrossRef);
+        assert_eq!(items[1].kind, CompletionItemKind::Citation);
+        assert_eq!(
+            items[1].range,
+            Range::new(Position::new(0, 0), Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn citations_from_project_bibliography() {
+        let workspace = MemoryWorkspace::default()
+            .with_project_dir("..")
+            .with_file("../_quarto.yml", "bibliography: refs.bib\n")
+            .with_file("../refs.bib", "@book{lamport1994, title = {LaTeX}}\n");
+        let items = complete("Cite @lam|\n", &workspace);
+        assert_eq!(labels(&items), vec!["lamport1994"]);
+    }
+
+    #[test]
+    fn no_citations_in_email_addresses() {
+        let items = complete("Mail me@|\n", &NoWorkspace);
+        assert!(items.is_empty());
+    }
+
+    #[test]
+    fn shortcode_names() {
+        let items = complete("{{< me|\n", &NoWorkspace);
+        assert_eq!(labels(&items), vec!["meta"]);
+        assert_eq!(items[0].range.start, Position::new(0, 4));
+    }
+
+    #[test]
+    fn link_and_include_paths() {
+        let workspace = MemoryWorkspace::default()
+            .with_file("chapters/intro.qmd", "")
+            .with_file("chapters/methods.qmd", "")
+            .with_file(".hidden", "");
+        let items = complete("See [intro](chapters/in|)\n", &workspace);
+        assert_eq!(labels(&items), vec!["intro.qmd"]);
+        assert_eq!(items[0].range.start, Position::new(0, 21));
+
+        let items = complete("{{< include |\n", &workspace);
+        assert_eq!(labels(&items), vec!["chapters"]);
+
+        let items = complete("[site](https://|\n", &workspace);
+        assert!(items.is_empty());
+    }
+}
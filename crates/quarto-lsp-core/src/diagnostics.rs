@@ -1,14 +1,21 @@
 //! Diagnostic extraction from Quarto documents.
 //!
 //! This module provides functions to extract diagnostics (errors and warnings)
-//! from QMD documents by parsing them with `pampa`.
+//! from QMD documents by parsing them with `pampa`, and from a few checks of
+//! the text that the parser accepts (unclosed divs, `:::{` fences, a missing
+//! newline at the end of the file).
+//!
+//! Diagnostics carry the fixes attached to them, which are offered as code
+//! actions.
 
+use crate::completions::front_matter_end;
 use crate::document::Document;
 use crate::types::{
-    DetailKind, Diagnostic, DiagnosticDetail, DiagnosticSeverity, MessageContent, Position, Range,
+    DetailKind, Diagnostic, DiagnosticDetail, DiagnosticFix, DiagnosticSeverity, Location,
+    MessageContent, Position, Range, TextEdit,
 };
-use quarto_error_reporting::DiagnosticMessage;
-use quarto_source_map::SourceContext;
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder, Fix, FixEdit};
+use quarto_source_map::{FileId, SourceContext, SourceInfo};
 
 /// Result of analyzing a document for diagnostics.
 #[derive(Debug)]
@@ -48,7 +55,7 @@ pub fn get_diagnostics(doc: &Document) -> DiagnosticResult {
         None,                 // parent_source_info
     );
 
-    let diagnostics = match result {
+    let mut diagnostics: Vec<Diagnostic> = match result {
         Ok((_pandoc, _ast_context, warnings)) => {
             // Parsing succeeded, convert warnings to diagnostics
             warnings
@@ -65,14 +72,130 @@ pub fn get_diagnostics(doc: &Document) -> DiagnosticResult {
         }
     };
 
+    diagnostics.extend(
+        lint_messages(doc.content())
+            .iter()
+            .filter_map(|msg| convert_diagnostic(msg, &source_context)),
+    );
+
     DiagnosticResult {
         diagnostics,
         source_context,
     }
 }
 
+/// Warnings for problems in the text that the parser accepts.
+pub(crate) fn lint_messages(content: &str) -> Vec<DiagnosticMessage> {
+    let mut messages = div_fence_messages(content);
+    if !content.is_empty() && !content.ends_with('\n') {
+        let end = SourceInfo::original(FileId(0), content.len(), content.len());
+        messages.push(
+            DiagnosticMessageBuilder::warning("Missing Newline at End of File")
+                .with_code("Q-7-1")
+                .with_location(end.clone())
+                .problem("The document does not end with a newline")
+                .add_fix(Fix::new(
+                    "Add a newline at the end of the file",
+                    vec![FixEdit::new(end, "\n")],
+                ))
+                .build(),
+        );
+    }
+    messages
+}
+
+/// Warnings for div fences without a space before their attributes, and
+/// for divs that are never closed.
+fn div_fence_messages(content: &str) -> Vec<DiagnosticMessage> {
+    let lines: Vec<&str> = content.split('\n').collect();
+    let body_start = front_matter_end(&lines).map_or(0, |end| (end + 1).min(lines.len()));
+    let mut messages = Vec::new();
+    // Byte ranges of the `:::` of the divs that are open
+    let mut open_divs: Vec<(usize, usize)> = Vec::new();
+    // The fence of the code block we're in, if any
+    let mut code_fence: Option<&str> = None;
+
+    let mut offset: usize = lines[..body_start].iter().map(|line| line.len() + 1).sum();
+    for line in &lines[body_start..] {
+        let trimmed = line.trim_start();
+        let fence_start = offset + (line.len() - trimmed.len());
+        offset += line.len() + 1;
+
+        let fence_char = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'));
+        if let Some(fence) = code_fence {
+            if trimmed.starts_with(fence)
+                && trimmed.trim_start_matches(['`', '~']).trim().is_empty()
+            {
+                code_fence = None;
+            }
+            continue;
+        }
+        if let Some(c) = fence_char {
+            let fence = &trimmed[..trimmed.len() - trimmed.trim_start_matches(c).len()];
+            if fence.len() >= 3 {
+                code_fence = Some(fence);
+                continue;
+            }
+        }
+
+        let rest = trimmed.trim_start_matches(':');
+        let colons = trimmed.len() - rest.len();
+        if colons < 3 {
+            continue;
+        }
+        let fence_end = fence_start + colons;
+        if rest.trim().is_empty() {
+            open_divs.pop();
+            continue;
+        }
+        open_divs.push((fence_start, fence_end));
+        if rest.starts_with('{') {
+            let fence = SourceInfo::original(FileId(0), fence_start, fence_end);
+            let after_fence = SourceInfo::original(FileId(0), fence_end, fence_end);
+            messages.push(
+                DiagnosticMessageBuilder::warning("Missing Space After Div Fence")
+                    .with_code("Q-2-4")
+                    .with_location(fence)
+                    .problem("A space is required after `:::` when specifying div attributes")
+                    .add_fix(Fix::new(
+                        "Add a space after `:::`",
+                        vec![FixEdit::new(after_fence, " ")],
+                    ))
+                    .build(),
+            );
+        }
+    }
+
+    let end = SourceInfo::original(FileId(0), content.len(), content.len());
+    let closer = if content.is_empty() || content.ends_with('\n') {
+        ":::\n"
+    } else {
+        "\n:::\n"
+    };
+    for (start, fence_end) in open_divs {
+        messages.push(
+            DiagnosticMessageBuilder::warning("Unclosed Div")
+                .with_code("Q-2-38")
+                .with_location(SourceInfo::original(FileId(0), start, fence_end))
+                .problem(
+                    "I reached the end of the document before finding a closing `:::` for the div",
+                )
+                .add_hint("Add a line with `:::` where the div ends?")
+                .add_fix(Fix::new(
+                    "Close the div at the end of the document",
+                    vec![FixEdit::new(end.clone(), closer)],
+                ))
+                .build(),
+        );
+    }
+    messages
+}
+
 /// Convert a quarto-error-reporting DiagnosticMessage to our Diagnostic type.
-fn convert_diagnostic(msg: &DiagnosticMessage, ctx: &SourceContext) -> Option<Diagnostic> {
+pub(crate) fn convert_diagnostic(
+    msg: &DiagnosticMessage,
+    ctx: &SourceContext,
+) -> Option<Diagnostic> {
     // Get the range from the diagnostic location
     let range = if let Some(loc) = &msg.location {
         source_info_to_range(loc, ctx)
@@ -126,9 +249,26 @@ fn convert_diagnostic(msg: &DiagnosticMessage, ctx: &SourceContext) -> Option<Di
         diagnostic = diagnostic.with_hint(MessageContent::from(hint));
     }
 
+    for fix in &msg.fixes {
+        diagnostic = diagnostic.with_fix(convert_fix(fix, ctx));
+    }
+
     Some(diagnostic)
 }
 
+/// Convert a quarto-error-reporting Fix to our DiagnosticFix type.
+pub(crate) fn convert_fix(fix: &Fix, ctx: &SourceContext) -> DiagnosticFix {
+    let edits = fix
+        .edits
+        .iter()
+        .map(|edit| {
+            let range = source_info_to_range(&edit.location, ctx);
+            TextEdit::new(Location::in_document(range), edit.new_text.clone())
+        })
+        .collect();
+    DiagnosticFix::new(fix.title.clone(), edits)
+}
+
 /// Convert a SourceInfo to a Range using the SourceContext.
 fn source_info_to_range(loc: &quarto_source_map::SourceInfo, ctx: &SourceContext) -> Range {
     // Map start position
@@ -0,0 +1,574 @@
+//! Hunspell dictionaries, for spell checking.
+//!
+//! A dictionary is a pair of files: the `.dic` file lists the words of the
+//! language with the flags of the affixes they take, and the `.aff` file
+//! defines the affixes. This reads the parts of the format that matter for
+//! checking and suggesting words of prose:
+//!
+//! - **Affixes**: prefixes and suffixes (`PFX`, `SFX`), with their
+//!   conditions and cross products
+//! - **Flags**: the flag types (`FLAG long`, `FLAG num`, `FLAG UTF-8`) and
+//!   flag aliases (`AF`)
+//! - **Word flags**: `FORBIDDENWORD`, `NOSUGGEST`, `KEEPCASE` and
+//!   `NEEDAFFIX`
+//! - **Suggestions**: the characters to try (`TRY`) and common mistakes
+//!   (`REP`)
+//!
+//! Compound words aren't supported.
+
+use std::collections::HashMap;
+
+/// The most suggestions given for a word.
+const MAX_SUGGESTIONS: usize = 5;
+
+/// An affix flag.
+type Flag = u32;
+
+/// How flags are written in the dictionary.
+#[derive(Debug, Clone, Copy, Default)]
+enum FlagType {
+    /// One ASCII character per flag.
+    #[default]
+    Short,
+    /// Two characters per flag.
+    Long,
+    /// Comma-separated numbers.
+    Numeric,
+    /// One Unicode character per flag.
+    Utf8,
+}
+
+/// A character of an affix condition.
+#[derive(Debug, Clone)]
+enum CharClass {
+    Any,
+    Char(char),
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharClass::Any => true,
+            CharClass::Char(expected) => c == *expected,
+            CharClass::Set { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+/// A prefix or suffix rule.
+#[derive(Debug, Clone)]
+struct Affix {
+    flag: Flag,
+    /// The text removed from the stem.
+    strip: String,
+    /// The text added to the stem.
+    add: String,
+    /// What the start (prefixes) or end (suffixes) of the stem must be.
+    condition: Vec<CharClass>,
+    /// Whether the rule combines with affixes of the other kind.
+    cross_product: bool,
+}
+
+impl Affix {
+    /// The stem of a word with this prefix, if the rule applies to it.
+    fn prefix_stem(&self, word: &str) -> Option<String> {
+        let rest = word.strip_prefix(self.add.as_str())?;
+        if rest.is_empty() && self.strip.is_empty() {
+            return None;
+        }
+        let stem = format!("{}{}", self.strip, rest);
+        let chars: Vec<char> = stem.chars().take(self.condition.len()).collect();
+        self.condition_matches(&chars).then_some(stem)
+    }
+
+    /// The stem of a word with this suffix, if the rule applies to it.
+    fn suffix_stem(&self, word: &str) -> Option<String> {
+        let rest = word.strip_suffix(self.add.as_str())?;
+        if rest.is_empty() && self.strip.is_empty() {
+            return None;
+        }
+        let stem = format!("{}{}", rest, self.strip);
+        let mut chars: Vec<char> = stem.chars().rev().take(self.condition.len()).collect();
+        chars.reverse();
+        self.condition_matches(&chars).then_some(stem)
+    }
+
+    fn condition_matches(&self, chars: &[char]) -> bool {
+        chars.len() == self.condition.len()
+            && self
+                .condition
+                .iter()
+                .zip(chars)
+                .all(|(class, &c)| class.matches(c))
+    }
+}
+
+/// A Hunspell dictionary.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::Dictionary;
+///
+/// let dictionary = Dictionary::new("SFX S Y 1\nSFX S 0 s .\n", "1\nword/S\n");
+/// assert!(dictionary.check("words"));
+/// assert_eq!(dictionary.suggest("wrods"), vec!["words"]);
+/// ```
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    /// The words, with the flags of all their entries.
+    words: HashMap<String, Vec<Flag>>,
+    prefixes: Vec<Affix>,
+    suffixes: Vec<Affix>,
+    /// The characters to try in suggestions, most frequent first.
+    try_chars: Vec<char>,
+    /// Common mistakes, and what to replace them with.
+    replacements: Vec<(String, String)>,
+    forbidden: Option<Flag>,
+    no_suggest: Option<Flag>,
+    keep_case: Option<Flag>,
+    need_affix: Option<Flag>,
+}
+
+impl Dictionary {
+    /// Read a dictionary from the contents of its `.aff` and `.dic` files.
+    pub fn new(aff: &str, dic: &str) -> Self {
+        let mut dictionary = Dictionary::default();
+        let mut flag_type = FlagType::default();
+        let mut aliases: Vec<Vec<Flag>> = Vec::new();
+        let mut aliases_declared = false;
+        let mut replacements_declared = false;
+        // Whether each affix flag combines with the other kind, by whether
+        // it's a prefix
+        let mut affix_classes: HashMap<(bool, Flag), bool> = HashMap::new();
+
+        for line in aff.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["FLAG", kind, ..] => {
+                    flag_type = match *kind {
+                        "long" => FlagType::Long,
+                        "num" => FlagType::Numeric,
+                        "UTF-8" => FlagType::Utf8,
+                        _ => FlagType::Short,
+                    };
+                }
+                ["AF", flags, ..] => {
+                    // The first line is the number of aliases
+                    if aliases_declared {
+                        aliases.push(parse_flags(flags, flag_type, &[]));
+                    }
+                    aliases_declared = true;
+                }
+                ["TRY", chars, ..] => dictionary.try_chars = chars.chars().collect(),
+                ["REP", from, to, ..] if replacements_declared => {
+                    dictionary
+                        .replacements
+                        .push((from.replace('_', " "), to.replace('_', " ")));
+                }
+                ["REP", ..] => replacements_declared = true,
+                ["FORBIDDENWORD", flag, ..] => {
+                    dictionary.forbidden = parse_flags(flag, flag_type, &[]).first().copied();
+                }
+                ["NOSUGGEST", flag, ..] => {
+                    dictionary.no_suggest = parse_flags(flag, flag_type, &[]).first().copied();
+                }
+                ["KEEPCASE", flag, ..] => {
+                    dictionary.keep_case = parse_flags(flag, flag_type, &[]).first().copied();
+                }
+                ["NEEDAFFIX", flag, ..] => {
+                    dictionary.need_affix = parse_flags(flag, flag_type, &[]).first().copied();
+                }
+                [kind @ ("PFX" | "SFX"), flag, rest @ ..] if !rest.is_empty() => {
+                    let is_prefix = *kind == "PFX";
+                    let Some(&flag) = parse_flags(flag, flag_type, &[]).first() else {
+                        continue;
+                    };
+                    // The first line of a class is its header
+                    let Some(&cross_product) = affix_classes.get(&(is_prefix, flag)) else {
+                        affix_classes.insert((is_prefix, flag), rest[0] == "Y");
+                        continue;
+                    };
+                    let [strip, add, condition @ ..] = rest else {
+                        continue;
+                    };
+                    // Continuation classes after the added text aren't supported
+                    let add = add.split('/').next().unwrap_or_default();
+                    let affix = Affix {
+                        flag,
+                        strip: affix_text(strip),
+                        add: affix_text(add),
+                        condition: parse_condition(condition.first().unwrap_or(&".")),
+                        cross_product,
+                    };
+                    if is_prefix {
+                        dictionary.prefixes.push(affix);
+                    } else {
+                        dictionary.suffixes.push(affix);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut lines = dic.lines();
+        // The first line is the number of words
+        if let Some(first) = lines.next()
+            && first.trim().parse::<usize>().is_err()
+        {
+            dictionary.add_entry(first, flag_type, &aliases);
+        }
+        for line in lines {
+            dictionary.add_entry(line, flag_type, &aliases);
+        }
+        dictionary
+    }
+
+    /// Add a line of a `.dic` file: a word, its flags after a `/`, and
+    /// morphological fields after whitespace.
+    fn add_entry(&mut self, line: &str, flag_type: FlagType, aliases: &[Vec<Flag>]) {
+        let entry = line.split(['\t', ' ']).next().unwrap_or_default();
+        if entry.is_empty() {
+            return;
+        }
+        // A `/` preceded by a backslash is part of the word
+        let slash = entry
+            .char_indices()
+            .skip(1)
+            .find(|&(i, c)| c == '/' && !entry[..i].ends_with('\\'))
+            .map(|(i, _)| i);
+        let (word, flags) = match slash {
+            Some(slash) => (
+                &entry[..slash],
+                parse_flags(&entry[slash + 1..], flag_type, aliases),
+            ),
+            None => (entry, Vec::new()),
+        };
+        self.words
+            .entry(word.replace("\\/", "/"))
+            .or_default()
+            .extend(flags);
+    }
+
+    /// Whether a word is spelled correctly.
+    ///
+    /// Words can be capitalized or upper case versions of the dictionary's
+    /// words (`Hello` and `HELLO` for `hello`, `PARIS` for `Paris`), unless
+    /// the dictionary keeps their case.
+    pub fn check(&self, word: &str) -> bool {
+        if word.is_empty() || self.check_form(word, false) {
+            return true;
+        }
+        let lower = word.to_lowercase();
+        if lower == word {
+            return false;
+        }
+        let mut chars = word.chars();
+        let capitalized = chars.next().is_some_and(char::is_uppercase)
+            && chars.as_str().to_lowercase() == chars.as_str();
+        let upper = word.to_uppercase() == word;
+        if capitalized || upper {
+            if self.check_form(&lower, true) {
+                return true;
+            }
+            if upper && self.check_form(&capitalize(&lower), true) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether a word is in the dictionary, by itself or with affixes.
+    /// `case_changed` is whether the word's case was changed to look it up.
+    fn check_form(&self, word: &str, case_changed: bool) -> bool {
+        if let Some(flags) = self.words.get(word) {
+            if self.has(flags, self.forbidden) {
+                return false;
+            }
+            if !self.has(flags, self.need_affix)
+                && !(case_changed && self.has(flags, self.keep_case))
+            {
+                return true;
+            }
+        }
+
+        let has_affix = |stem: &str, affixes: &[Flag]| {
+            self.words.get(stem).is_some_and(|flags| {
+                affixes.iter().all(|affix| flags.contains(affix))
+                    && !self.has(flags, self.forbidden)
+                    && !(case_changed && self.has(flags, self.keep_case))
+            })
+        };
+        for suffix in &self.suffixes {
+            if let Some(stem) = suffix.suffix_stem(word)
+                && has_affix(&stem, &[suffix.flag])
+            {
+                return true;
+            }
+        }
+        for prefix in &self.prefixes {
+            let Some(stem) = prefix.prefix_stem(word) else {
+                continue;
+            };
+            if has_affix(&stem, &[prefix.flag]) {
+                return true;
+            }
+            if !prefix.cross_product {
+                continue;
+            }
+            for suffix in self.suffixes.iter().filter(|suffix| suffix.cross_product) {
+                if let Some(stem) = suffix.suffix_stem(&stem)
+                    && has_affix(&stem, &[prefix.flag, suffix.flag])
+                {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn has(&self, flags: &[Flag], flag: Option<Flag>) -> bool {
+        flag.is_some_and(|flag| flags.contains(&flag))
+    }
+
+    /// Suggestions for a misspelled word, best first.
+    ///
+    /// Suggestions are the words one edit away: a common mistake corrected,
+    /// two letters swapped, a letter removed, replaced or added, or a space
+    /// added.
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let chars: Vec<char> = word.chars().collect();
+        let mut candidates: Vec<String> = Vec::new();
+        for (from, to) in &self.replacements {
+            for (i, _) in word.match_indices(from.as_str()) {
+                candidates.push(format!("{}{}{}", &word[..i], to, &word[i + from.len()..]));
+            }
+        }
+        let with = |f: &dyn Fn(&mut Vec<char>)| {
+            let mut chars = chars.clone();
+            f(&mut chars);
+            chars.into_iter().collect::<String>()
+        };
+        for i in 1..chars.len() {
+            candidates.push(with(&|chars| chars.swap(i - 1, i)));
+        }
+        for i in 0..chars.len() {
+            candidates.push(with(&|chars| {
+                chars.remove(i);
+            }));
+        }
+        for i in 0..chars.len() {
+            for &c in &self.try_chars {
+                if c != chars[i] {
+                    candidates.push(with(&|chars| chars[i] = c));
+                }
+            }
+        }
+        for i in 0..=chars.len() {
+            for &c in &self.try_chars {
+                candidates.push(with(&|chars| chars.insert(i, c)));
+            }
+        }
+        for i in 1..chars.len() {
+            candidates.push(with(&|chars| chars.insert(i, ' ')));
+        }
+
+        let mut suggestions: Vec<String> = Vec::new();
+        for candidate in candidates {
+            if suggestions.len() == MAX_SUGGESTIONS {
+                break;
+            }
+            if candidate != word
+                && !suggestions.contains(&candidate)
+                && candidate
+                    .split(' ')
+                    .all(|part| self.check(part) && self.suggestable(part))
+            {
+                suggestions.push(candidate);
+            }
+        }
+        suggestions
+    }
+
+    /// Whether a word may be suggested.
+    fn suggestable(&self, word: &str) -> bool {
+        !self
+            .words
+            .get(word)
+            .is_some_and(|flags| self.has(flags, self.no_suggest))
+    }
+}
+
+/// The text of an affix, where `0` is no text.
+fn affix_text(text: &str) -> String {
+    if text == "0" {
+        String::new()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Parse the flags of a word or affix. With flag aliases, flags are the
+/// number of an alias.
+fn parse_flags(text: &str, flag_type: FlagType, aliases: &[Vec<Flag>]) -> Vec<Flag> {
+    if !aliases.is_empty()
+        && let Ok(alias) = text.parse::<usize>()
+    {
+        return alias
+            .checked_sub(1)
+            .and_then(|i| aliases.get(i))
+            .cloned()
+            .unwrap_or_default();
+    }
+    match flag_type {
+        FlagType::Short | FlagType::Utf8 => text.chars().map(Flag::from).collect(),
+        FlagType::Long => {
+            let chars: Vec<char> = text.chars().collect();
+            chars
+                .chunks(2)
+                .map(|pair| pair.iter().fold(0, |flag, &c| (flag << 16) | Flag::from(c)))
+                .collect()
+        }
+        FlagType::Numeric => text
+            .split(',')
+            .filter_map(|flag| flag.trim().parse().ok())
+            .collect(),
+    }
+}
+
+/// Parse an affix condition, such as `[^aeiou]y`.
+fn parse_condition(condition: &str) -> Vec<CharClass> {
+    if condition == "." {
+        return Vec::new();
+    }
+    let mut classes = Vec::new();
+    let mut chars = condition.chars();
+    while let Some(c) = chars.next() {
+        classes.push(match c {
+            '.' => CharClass::Any,
+            '[' => {
+                let mut set: Vec<char> = chars.by_ref().take_while(|&c| c != ']').collect();
+                let negated = set.first() == Some(&'^');
+                if negated {
+                    set.remove(0);
+                }
+                CharClass::Set {
+                    chars: set,
+                    negated,
+                }
+            }
+            c => CharClass::Char(c),
+        });
+    }
+    classes
+}
+
+/// A word with its first letter in upper case.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "SET UTF-8
+TRY esianrtolcdugmphbyfvkwz'
+KEEPCASE K
+NOSUGGEST N
+FORBIDDENWORD !
+
+REP 1
+REP alot a_lot
+
+PFX A Y 1
+PFX A   0     re         .
+
+SFX D Y 4
+SFX D   0     d          e
+SFX D   y     ied        [^aeiou]y
+SFX D   0     ed         [^ey]
+SFX D   0     ed         [aeiou]y
+
+SFX S N 1
+SFX S   0     s          .
+";
+
+    const DIC: &str = "8
+a
+lot/S
+try/AD
+work/ADS
+play/D
+Paris
+iOS/K
+darn/N
+";
+
+    fn dictionary() -> Dictionary {
+        Dictionary::new(AFF, DIC)
+    }
+
+    #[test]
+    fn words_and_affixes() {
+        let dictionary = dictionary();
+        for word in [
+            "work", "works", "worked", "rework", "reworked", "tried", "played",
+        ] {
+            assert!(dictionary.check(word), "{}", word);
+        }
+        // Conditions, and suffixes the word doesn't take
+        for word in ["tryed", "plaied", "plays", "reworks", "wrk"] {
+            assert!(!dictionary.check(word), "{}", word);
+        }
+    }
+
+    #[test]
+    fn case() {
+        let dictionary = dictionary();
+        assert!(dictionary.check("Worked"));
+        assert!(dictionary.check("WORKED"));
+        assert!(dictionary.check("PARIS"));
+        assert!(!dictionary.check("paris"));
+        assert!(!dictionary.check("wOrked"));
+        // Words whose case is kept
+        assert!(dictionary.check("iOS"));
+        assert!(!dictionary.check("IOS"));
+    }
+
+    #[test]
+    fn suggestions() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.suggest("wrok"), vec!["work"]);
+        assert_eq!(dictionary.suggest("workd"), vec!["work", "works", "worked"]);
+        assert_eq!(dictionary.suggest("alot"), vec!["a lot", "lot"]);
+        // Words that aren't suggested
+        assert!(dictionary.suggest("dar").is_empty());
+    }
+
+    #[test]
+    fn forbidden_words() {
+        let dictionary = Dictionary::new(AFF, "2\nwork/S\nworks/!\n");
+        assert!(dictionary.check("work"));
+        assert!(!dictionary.check("works"));
+    }
+
+    #[test]
+    fn flag_types() {
+        let long = Dictionary::new("FLAG long\nSFX Aa Y 1\nSFX Aa 0 s .\n", "1\nword/AaBb\n");
+        assert!(long.check("words"));
+        let numeric = Dictionary::new("FLAG num\nSFX 12 Y 1\nSFX 12 0 s .\n", "1\nword/3,12\n");
+        assert!(numeric.check("words"));
+        let aliased = Dictionary::new(
+            "AF 2\nAF B\nAF SB\nSFX S Y 1\nSFX S 0 s .\n",
+            "2\nword/2\nthing/1\n",
+        );
+        assert!(aliased.check("words"));
+        assert!(!aliased.check("things"));
+    }
+}
@@ -3,8 +3,22 @@
 //! This module provides a simple document representation that can be used
 //! for language analysis. The design anticipates future workspace-wide features
 //! where documents may come from either the editor (in-memory) or the filesystem.
+//!
+//! Documents keep their tree-sitter syntax tree and their analysis once
+//! computed. [`Document::apply_edit`] re-parses incrementally and re-analyzes
+//! only the sections an edit touches, so that editing a large document
+//! doesn't re-analyze all of it on every keystroke.
+
+use std::sync::{Arc, OnceLock};
 
 use quarto_source_map::{SourceContext, SourceInfo};
+use tree_sitter::{InputEdit, Point};
+use tree_sitter_qmd::{MarkdownParser, MarkdownTree};
+
+use crate::analysis::{CachedAnalysis, analyze};
+use crate::completions::{front_matter_end, line_prefix};
+use crate::incremental::{Edit, update_analysis};
+use crate::types::{DocumentAnalysis, Position, Range};
 
 /// A document for language analysis.
 ///
@@ -19,6 +33,10 @@ pub struct Document {
     content: String,
     /// Version number for tracking changes (optional, used by LSP).
     version: Option<i32>,
+    /// The syntax tree, parsed on first use.
+    tree: OnceLock<Option<MarkdownTree>>,
+    /// The analysis, computed on first use.
+    analysis: OnceLock<Arc<CachedAnalysis>>,
 }
 
 impl Document {
@@ -28,6 +46,8 @@ impl Document {
             uri: uri.into(),
             content: content.into(),
             version: None,
+            tree: OnceLock::new(),
+            analysis: OnceLock::new(),
         }
     }
 
@@ -37,6 +57,8 @@ impl Document {
             uri: uri.into(),
             content: content.into(),
             version: Some(version),
+            tree: OnceLock::new(),
+            analysis: OnceLock::new(),
         }
     }
 
@@ -68,14 +90,123 @@ impl Document {
     /// Update the document content.
     pub fn set_content(&mut self, content: impl Into<String>) {
         self.content = content.into();
+        self.tree = OnceLock::new();
+        self.analysis = OnceLock::new();
     }
 
     /// Update the document content with a new version.
     pub fn set_content_with_version(&mut self, content: impl Into<String>, version: i32) {
-        self.content = content.into();
+        self.set_content(content);
         self.version = Some(version);
     }
 
+    /// Replace the text in a range of the document.
+    ///
+    /// The syntax tree is re-parsed incrementally, and the analysis is
+    /// updated by re-analyzing only the top-level sections the edit touches.
+    /// Edits in the front matter, which affects the whole document, discard
+    /// the analysis instead, and it is recomputed when next needed.
+    pub fn apply_edit(&mut self, range: Range, text: &str) {
+        let start_byte = self.offset(range.start);
+        let old_end_byte = self.offset(range.end).max(start_byte);
+        let edit = Edit {
+            start: position_at(&self.content, start_byte),
+            old_end: position_at(&self.content, old_end_byte),
+            new_end: Position::default(),
+        };
+        let in_front_matter = {
+            let lines: Vec<&str> = self.content.lines().collect();
+            front_matter_end(&lines).is_some_and(|end| edit.start.line as usize <= end)
+        };
+        let start_point = point_at(&self.content, start_byte);
+        let old_end_point = point_at(&self.content, old_end_byte);
+
+        let old_tree = self.tree.take().flatten();
+        let old_analysis = self.analysis.take();
+        self.content.replace_range(start_byte..old_end_byte, text);
+        let new_end_byte = start_byte + text.len();
+        let edit = Edit {
+            new_end: position_at(&self.content, new_end_byte),
+            ..edit
+        };
+
+        // Keep a syntax tree only if there was one or an analysis to update
+        if old_tree.is_none() && old_analysis.is_none() {
+            return;
+        }
+        let mut parser = MarkdownParser::default();
+        // The edited text, and where the syntax tree changed
+        let mut changed: Vec<std::ops::Range<usize>> = Vec::new();
+        let tree = match old_tree {
+            Some(mut old_tree) => {
+                old_tree.edit(&InputEdit {
+                    start_byte,
+                    old_end_byte,
+                    new_end_byte,
+                    start_position: start_point,
+                    old_end_position: old_end_point,
+                    new_end_position: point_at(&self.content, new_end_byte),
+                });
+                let tree = parser.parse(self.content.as_bytes(), Some(&old_tree));
+                if let Some(tree) = &tree {
+                    changed.extend(
+                        tree.block_tree()
+                            .changed_ranges(old_tree.block_tree())
+                            .map(|range| range.start_byte..range.end_byte),
+                    );
+                }
+                tree
+            }
+            None => parser.parse(self.content.as_bytes(), None),
+        };
+        changed.push(start_byte..new_end_byte);
+        let _ = self.tree.set(tree);
+
+        if let Some(old_analysis) = old_analysis
+            && !in_front_matter
+            && let Some(analysis) = update_analysis(self, &old_analysis, &edit, &changed)
+        {
+            let _ = self.analysis.set(Arc::new(analysis));
+        }
+    }
+
+    /// The syntax tree of the document, or `None` if it couldn't be parsed.
+    pub fn tree(&self) -> Option<&MarkdownTree> {
+        self.tree
+            .get_or_init(|| MarkdownParser::default().parse(self.content.as_bytes(), None))
+            .as_ref()
+    }
+
+    /// The analysis of the document (see
+    /// [`analyze_document`](crate::analyze_document)), computed on first use
+    /// and kept up to date by [`apply_edit`](Self::apply_edit).
+    pub fn analysis(&self) -> &DocumentAnalysis {
+        &self
+            .analysis
+            .get_or_init(|| Arc::new(analyze(self)))
+            .analysis
+    }
+
+    /// Whether the analysis has been computed.
+    #[cfg(test)]
+    pub(crate) fn is_analyzed(&self) -> bool {
+        self.analysis.get().is_some()
+    }
+
+    /// The byte offset of a position, clamped to the document.
+    fn offset(&self, position: Position) -> usize {
+        let mut line_start = 0;
+        for _ in 0..position.line {
+            match self.content[line_start..].find('\n') {
+                Some(newline) => line_start += newline + 1,
+                None => return self.content.len(),
+            }
+        }
+        let rest = &self.content[line_start..];
+        let line = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        line_start + line_prefix(line, position.character).len()
+    }
+
     /// Create a SourceContext for this document.
     ///
     /// This is used to track source locations during parsing.
@@ -103,6 +234,24 @@ impl Document {
     }
 }
 
+/// The position of a byte offset of `content`.
+fn position_at(content: &str, offset: usize) -> Position {
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Position::new(
+        before.matches('\n').count() as u32,
+        before[line_start..].chars().count() as u32,
+    )
+}
+
+/// The tree-sitter point (row and byte column) of a byte offset of
+/// `content`.
+fn point_at(content: &str, offset: usize) -> Point {
+    let before = &content[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Point::new(before.matches('\n').count(), offset - line_start)
+}
+
 /// A document store for managing multiple documents.
 ///
 /// This is a simple in-memory store for documents. Future versions may
@@ -132,6 +281,14 @@ impl DocumentStore {
         }
     }
 
+    /// Replace the text in a range of a document.
+    pub fn edit(&mut self, uri: &str, range: Range, text: &str, version: i32) {
+        if let Some(doc) = self.documents.get_mut(uri) {
+            doc.apply_edit(range, text);
+            doc.version = Some(version);
+        }
+    }
+
     /// Close a document (remove from store).
     pub fn close(&mut self, uri: &str) {
         self.documents.remove(uri);
@@ -190,6 +347,46 @@ mod tests {
         assert_eq!(doc.version(), Some(2));
     }
 
+    #[test]
+    fn apply_edits() {
+        let mut doc = Document::new("test.qmd", "# Año\n\nText\n");
+        doc.apply_edit(Range::new(Position::new(0, 2), Position::new(0, 5)), "Mes");
+        assert_eq!(doc.content(), "# Mes\n\nText\n");
+
+        // Insertion at the end, and an edit past the end
+        doc.apply_edit(Range::point(Position::new(3, 0)), "More\n");
+        doc.apply_edit(Range::point(Position::new(9, 0)), "!");
+        assert_eq!(doc.content(), "# Mes\n\nText\nMore\n!");
+
+        // Deletion across lines
+        doc.apply_edit(Range::new(Position::new(0, 5), Position::new(2, 0)), "");
+        assert_eq!(doc.content(), "# MesText\nMore\n!");
+    }
+
+    #[test]
+    fn edits_keep_the_syntax_tree_up_to_date() {
+        let mut doc = Document::new("test.qmd", "# Intro\n\nText\n");
+        assert!(doc.tree().is_some());
+        doc.apply_edit(Range::point(Position::new(2, 0)), "## Sub\n\n");
+
+        let tree = doc.tree().unwrap().block_tree().root_node().to_sexp();
+        let fresh = MarkdownParser::default()
+            .parse(doc.content_bytes(), None)
+            .unwrap()
+            .block_tree()
+            .root_node()
+            .to_sexp();
+        assert_eq!(tree, fresh);
+    }
+
+    #[test]
+    fn set_content_discards_analysis() {
+        let mut doc = Document::new("test.qmd", "# One\n");
+        assert_eq!(doc.analysis().symbols.len(), 1);
+        doc.set_content("# One\n\n# Two\n");
+        assert_eq!(doc.analysis().symbols.len(), 2);
+    }
+
     #[test]
     fn document_store_lifecycle() {
         let mut store = DocumentStore::new();
@@ -207,6 +404,12 @@ mod tests {
         assert_eq!(store.get("file:///a.qmd").unwrap().content(), "updated a");
         assert_eq!(store.get("file:///a.qmd").unwrap().version(), Some(2));
 
+        // Edit
+        let range = Range::new(Position::new(0, 0), Position::new(0, 7));
+        store.edit("file:///a.qmd", range, "edited", 3);
+        assert_eq!(store.get("file:///a.qmd").unwrap().content(), "edited a");
+        assert_eq!(store.get("file:///a.qmd").unwrap().version(), Some(3));
+
         // Close
         store.close("file:///a.qmd");
         assert_eq!(store.len(), 1);
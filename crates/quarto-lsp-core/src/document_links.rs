@@ -0,0 +1,271 @@
+//! Document links for QMD documents.
+//!
+//! Links are found for:
+//!
+//! - **Links** (`[text](other.qmd)`) and autolinks (`<https://quarto.org>`)
+//! - **Images** (`![alt](plot.png)`)
+//! - **Includes** (`{{< include _intro.qmd >}}`)
+//! - **Bibliography files** in the front matter (`bibliography: refs.bib`)
+//!
+//! Links to anchors in the document itself (`[text](#sec-intro)`) are left
+//! to go-to-definition. Links in the body are found in the tree-sitter
+//! syntax tree, so that those in code are skipped.
+
+use quarto_treesitter_ast::{TraversePhase, topdown_traverse_concrete_tree};
+use tree_sitter::Node;
+
+use crate::bibliography::string_list;
+use crate::completions::{front_matter, front_matter_end};
+use crate::document::Document;
+use crate::types::{DocumentLink, DocumentLinkKind, Position, Range};
+
+/// Get the links of a document, in document order.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, get_document_links};
+///
+/// let doc = Document::new("test.qmd", "See [the intro](intro.qmd).\n");
+/// let links = get_document_links(&doc);
+/// assert_eq!(links[0].target, "intro.qmd");
+/// ```
+pub fn get_document_links(doc: &Document) -> Vec<DocumentLink> {
+    let content = doc.content();
+    let mut collector = LinkCollector {
+        lines: content.lines().collect(),
+        links: Vec::new(),
+    };
+    collector.bibliography();
+    if let Some(tree) = doc.tree() {
+        let mut cursor = tree.walk_cursor();
+        topdown_traverse_concrete_tree(&mut cursor, &mut |node, phase| {
+            phase == TraversePhase::Exit || collector.visit(node)
+        });
+    }
+
+    let mut links = collector.links;
+    links.sort_by_key(|link| link.range.start);
+    links
+}
+
+struct LinkCollector<'a> {
+    lines: Vec<&'a str>,
+    links: Vec<DocumentLink>,
+}
+
+impl LinkCollector<'_> {
+    /// Add the link of `node`, returning whether to visit its children.
+    fn visit(&mut self, node: &Node) -> bool {
+        match node.kind() {
+            "metadata" | "code_fence_content" => false,
+            // Link text may contain an image
+            "pandoc_span" | "pandoc_image" => {
+                let url = children(node)
+                    .find(|child| child.kind() == "target")
+                    .and_then(|target| children(&target).find(|child| child.kind() == "url"));
+                if let Some(url) = url {
+                    let kind = if node.kind() == "pandoc_image" {
+                        DocumentLinkKind::Image
+                    } else {
+                        DocumentLinkKind::Link
+                    };
+                    self.push(&url, 0, kind);
+                }
+                true
+            }
+            "autolink" => {
+                self.push(node, 1, DocumentLinkKind::Link);
+                false
+            }
+            "shortcode" => {
+                let mut parts = children(node);
+                let is_include = parts
+                    .find(|child| child.kind() == "shortcode_name")
+                    .is_some_and(|name| self.text(&name) == "include");
+                if is_include {
+                    match parts.next() {
+                        Some(path) if path.kind() == "shortcode_string" => {
+                            self.push(&path, 1, DocumentLinkKind::Include);
+                        }
+                        Some(path) if path.kind() == "shortcode_naked_string" => {
+                            self.push(&path, 0, DocumentLinkKind::Include);
+                        }
+                        _ => {}
+                    }
+                }
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// Add the bibliography files of the front matter.
+    fn bibliography(&mut self) {
+        let Some(end) = front_matter_end(&self.lines) else {
+            return;
+        };
+        let Some(meta) = front_matter(&self.lines) else {
+            return;
+        };
+        let end = end.min(self.lines.len());
+        let Some(mut row) = (1..end).find(|&row| self.lines[row].starts_with("bibliography:"))
+        else {
+            return;
+        };
+
+        // The files are written in order after the key
+        let mut offset = "bibliography:".len();
+        for file in string_list(&meta.yaml["bibliography"]) {
+            while row < end {
+                let line = self.lines[row];
+                if let Some(found) = line.get(offset..).and_then(|rest| rest.find(&file)) {
+                    let start = offset + found;
+                    offset = start + file.len();
+                    let range = Range::new(
+                        Position::new(row as u32, line[..start].chars().count() as u32),
+                        Position::new(row as u32, line[..offset].chars().count() as u32),
+                    );
+                    self.links.push(DocumentLink::new(
+                        range,
+                        file,
+                        DocumentLinkKind::Bibliography,
+                    ));
+                    break;
+                }
+                row += 1;
+                offset = 0;
+            }
+        }
+    }
+
+    /// The source text of a node on a single line.
+    fn text(&self, node: &Node) -> &str {
+        let start = node.start_position();
+        let end = node.end_position();
+        let line = self.lines.get(start.row).copied().unwrap_or_default();
+        let end_column = if end.row == start.row {
+            end.column
+        } else {
+            line.len()
+        };
+        line.get(start.column..end_column)
+            .unwrap_or_default()
+            .trim()
+    }
+
+    /// Add a link to the text of a node without `delimiter` bytes at each
+    /// end (the quotes of a string, the brackets of an autolink).
+    fn push(&mut self, node: &Node, delimiter: usize, kind: DocumentLinkKind) {
+        let text = self.text(node);
+        let Some(target) = text.get(delimiter..text.len().saturating_sub(delimiter)) else {
+            return;
+        };
+        if target.is_empty() || target.starts_with('#') {
+            return;
+        }
+
+        let row = node.start_position().row;
+        let line = self.lines[row];
+        let rest = &line[node.start_position().column..];
+        let start = line.len() - rest.trim_start().len() + delimiter;
+        let end = start + target.len();
+        let column = |offset: usize| line[..offset].chars().count() as u32;
+        let range = Range::new(
+            Position::new(row as u32, column(start)),
+            Position::new(row as u32, column(end)),
+        );
+        self.links.push(DocumentLink::new(range, target, kind));
+    }
+}
+
+fn children<'tree>(node: &Node<'tree>) -> impl Iterator<Item = Node<'tree>> {
+    (0..node.child_count()).filter_map(|i| node.child(i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text, target and kind of each link.
+    fn links(content: &str) -> Vec<(String, String, DocumentLinkKind)> {
+        let doc = Document::new("test.qmd", content);
+        let lines: Vec<&str> = content.lines().collect();
+        get_document_links(&doc)
+            .into_iter()
+            .map(|link| {
+                let range = link.range;
+                let text: String = lines[range.start.line as usize]
+                    .chars()
+                    .skip(range.start.character as usize)
+                    .take((range.end.character - range.start.character) as usize)
+                    .collect();
+                (text, link.target, link.kind)
+            })
+            .collect()
+    }
+
+    fn link(target: &str, kind: DocumentLinkKind) -> (String, String, DocumentLinkKind) {
+        (target.to_string(), target.to_string(), kind)
+    }
+
+    #[test]
+    fn links_images_and_autolinks() {
+        use DocumentLinkKind::*;
+        assert_eq!(
+            links(
+                "Él ve [a](a.qmd \"A\"), [b](#sec-b) and <https://quarto.org>.\n\n[![Plot](plot.png){width=50%}](big.png)\n"
+            ),
+            vec![
+                link("a.qmd", Link),
+                link("https://quarto.org", Link),
+                link("plot.png", Image),
+                link("big.png", Link),
+            ]
+        );
+    }
+
+    #[test]
+    fn includes() {
+        use DocumentLinkKind::*;
+        assert_eq!(
+            links("{{< include _a.qmd >}}\n\n{{< include \"_b c.qmd\" >}}\n\n{{< var x >}}\n"),
+            vec![link("_a.qmd", Include), link("_b c.qmd", Include)]
+        );
+    }
+
+    #[test]
+    fn bibliography_files() {
+        use DocumentLinkKind::*;
+        assert_eq!(
+            links(
+                "---\ntitle: refs.bib\nbibliography:\n  - more-refs.bib\n  - refs.bib\n---\n\nText\n"
+            ),
+            vec![
+                link("more-refs.bib", Bibliography),
+                link("refs.bib", Bibliography)
+            ]
+        );
+        assert_eq!(
+            links("---\nbibliography: [refs.bib, more-refs.bib]\n---\n"),
+            vec![
+                link("refs.bib", Bibliography),
+                link("more-refs.bib", Bibliography)
+            ]
+        );
+    }
+
+    #[test]
+    fn no_links_in_code() {
+        assert!(links("```{python}\n# [a](a.qmd)\n```\n\n`[b](b.qmd)`\n").is_empty());
+    }
+
+    #[test]
+    fn urls_and_files() {
+        let range = Range::default();
+        let url = DocumentLink::new(range, "https://quarto.org", DocumentLinkKind::Link);
+        let file = DocumentLink::new(range, "docs/intro.qmd#setup", DocumentLinkKind::Link);
+        assert!(url.is_url());
+        assert!(!file.is_url());
+    }
+}
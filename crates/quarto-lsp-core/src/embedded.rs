@@ -0,0 +1,224 @@
+//! Embedded documents for the code of executable cells.
+//!
+//! Editors can delegate completion, hover and diagnostics inside code cells
+//! to the language servers of the cells' languages: the code of the cells
+//! of each language is exposed as an [`EmbeddedDocument`], whose positions
+//! translate to and from positions in the QMD document.
+//!
+//! Only executable cells (```` ```{python} ````) are embedded, not plain code
+//! blocks (```` ```python ````) or raw blocks (```` ```{=html} ````). Cell
+//! option lines (`#| echo: false`) are kept, since they are comments in the
+//! cell's language.
+
+use crate::completions::front_matter_end;
+use crate::document::Document;
+use crate::index::closes_fence;
+use crate::types::{EmbeddedDocument, EmbeddedPosition, Position, Range};
+
+/// Get the embedded documents of a document, one per language, in the order
+/// the languages first appear.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, get_embedded_documents};
+///
+/// let doc = Document::new("test.qmd", "Text\n\n```{python}\nx = 1\n```\n");
+/// let documents = get_embedded_documents(&doc);
+/// assert_eq!(documents[0].language, "python");
+/// assert_eq!(documents[0].content, "\n\n\nx = 1\n\n");
+/// ```
+pub fn get_embedded_documents(doc: &Document) -> Vec<EmbeddedDocument> {
+    let lines: Vec<&str> = doc.content().lines().collect();
+    let mut documents: Vec<(EmbeddedDocument, Vec<&str>)> = Vec::new();
+    for cell in code_cells(&lines) {
+        let index = match documents
+            .iter()
+            .position(|(document, _)| document.language == cell.language)
+        {
+            Some(index) => index,
+            None => {
+                let document = EmbeddedDocument {
+                    language: cell.language.to_string(),
+                    content: String::new(),
+                    cells: Vec::new(),
+                };
+                documents.push((document, vec![""; lines.len()]));
+                documents.len() - 1
+            }
+        };
+        let (document, code) = &mut documents[index];
+        for (line, text) in code
+            .iter_mut()
+            .zip(lines.iter())
+            .take(cell.end + 1)
+            .skip(cell.start)
+        {
+            // Blank lines may be less indented than the fence
+            *line = text.get(cell.indent..).unwrap_or_default();
+        }
+        let end_character = code[cell.end].chars().count() + cell.indent;
+        document.cells.push(Range::new(
+            Position::new(cell.start as u32, cell.indent as u32),
+            Position::new(cell.end as u32, end_character as u32),
+        ));
+    }
+
+    documents
+        .into_iter()
+        .map(|(mut document, code)| {
+            document.content = code.iter().map(|line| format!("{}\n", line)).collect();
+            document
+        })
+        .collect()
+}
+
+/// Get the embedded document of the cell at a position, and the position
+/// in it, or `None` if the position isn't in the code of a cell.
+pub fn get_embedded_document_at(doc: &Document, position: Position) -> Option<EmbeddedPosition> {
+    get_embedded_documents(doc)
+        .into_iter()
+        .find_map(|document| {
+            let position = document.to_embedded(position)?;
+            Some(EmbeddedPosition { document, position })
+        })
+}
+
+/// The code lines of an executable cell.
+pub(crate) struct CodeCell<'a> {
+    pub(crate) language: &'a str,
+    /// The first line of code.
+    pub(crate) start: usize,
+    /// The last line of code.
+    pub(crate) end: usize,
+    /// The indentation of the fence, in bytes.
+    pub(crate) indent: usize,
+}
+
+/// The executable cells of a document that have code. Front matter is
+/// skipped.
+pub(crate) fn code_cells<'a>(lines: &[&'a str]) -> Vec<CodeCell<'a>> {
+    let body_start = front_matter_end(lines).map_or(0, |end| end + 1);
+    let mut cells = Vec::new();
+    let mut fence: Option<(usize, &str)> = None;
+    for (index, line) in lines.iter().enumerate().skip(body_start) {
+        let text = line.trim_start();
+        match fence {
+            Some((open, text_open)) => {
+                if closes_fence(text_open, text) {
+                    fence = None;
+                    if let Some(language) = cell_language(text_open)
+                        && index > open + 1
+                    {
+                        cells.push(CodeCell {
+                            language,
+                            start: open + 1,
+                            end: index - 1,
+                            indent: lines[open].len() - text_open.len(),
+                        });
+                    }
+                }
+            }
+            None if text.starts_with("```") => fence = Some((index, text)),
+            None => {}
+        }
+    }
+    cells
+}
+
+/// The language of an executable cell from its opening fence
+/// (```` ```{python} ````, ```` ``` {r label="fig"} ````).
+fn cell_language(fence: &str) -> Option<&str> {
+    let info = fence.trim_start_matches('`').trim_start();
+    let info = info.strip_prefix('{')?;
+    let end = info.find(['}', ' ', ',']).unwrap_or(info.len());
+    let language = &info[..end];
+    language
+        .chars()
+        .next()
+        .is_some_and(char::is_alphabetic)
+        .then_some(language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_document_per_language() {
+        let doc = Document::new(
+            "test.qmd",
+            "---\ntitle: \"```{r}\"\n---\n\n```{python}\n#| echo: false\nx = 1\n```\n\n```{r}\ny <- 2\n```\n\n```{python}\nprint(x)\n```\n",
+        );
+        let documents = get_embedded_documents(&doc);
+        assert_eq!(documents.len(), 2);
+
+        assert_eq!(documents[0].language, "python");
+        assert_eq!(
+            documents[0].content,
+            "\n\n\n\n\n#| echo: false\nx = 1\n\n\n\n\n\n\n\nprint(x)\n\n"
+        );
+        assert_eq!(
+            documents[0].cells,
+            vec![
+                Range::new(Position::new(5, 0), Position::new(6, 5)),
+                Range::new(Position::new(14, 0), Position::new(14, 8)),
+            ]
+        );
+
+        assert_eq!(documents[1].language, "r");
+        assert_eq!(documents[1].content.lines().count(), 16);
+        assert_eq!(documents[1].content.lines().nth(10), Some("y <- 2"));
+    }
+
+    #[test]
+    fn only_executable_cells() {
+        let doc = Document::new(
+            "test.qmd",
+            "```python\nx = 1\n```\n\n```{=html}\n<br>\n```\n\n```{.python}\nx = 2\n```\n\n```{ojs}\n```\n",
+        );
+        assert!(get_embedded_documents(&doc).is_empty());
+    }
+
+    #[test]
+    fn fence_attributes() {
+        assert_eq!(cell_language("```{python}"), Some("python"));
+        assert_eq!(cell_language("``` {r label=\"fig\"}"), Some("r"));
+        assert_eq!(cell_language("````{julia, echo=FALSE}"), Some("julia"));
+        assert_eq!(cell_language("```{=latex}"), None);
+        assert_eq!(cell_language("```{#id .python}"), None);
+    }
+
+    #[test]
+    fn indented_cells_translate_positions() {
+        let doc = Document::new(
+            "test.qmd",
+            "1. Item\n\n   ```{python}\n   import os\n\n   os.getcwd()\n   ```\n",
+        );
+        let document = &get_embedded_documents(&doc)[0];
+        assert_eq!(document.content, "\n\n\nimport os\n\nos.getcwd()\n\n");
+
+        let position = Position::new(5, 6);
+        let embedded = document.to_embedded(position).unwrap();
+        assert_eq!(embedded, Position::new(5, 3));
+        assert_eq!(document.to_document(embedded), position);
+        assert_eq!(document.to_embedded(Position::new(0, 2)), None);
+        assert_eq!(
+            document.range_to_document(Range::new(Position::new(3, 0), Position::new(3, 6))),
+            Range::new(Position::new(3, 3), Position::new(3, 9))
+        );
+    }
+
+    #[test]
+    fn document_at_position() {
+        let doc = Document::new(
+            "test.qmd",
+            "```{r}\nx <- 1\n```\n\n```{julia}\ny = 2\n```\n",
+        );
+        let at = get_embedded_document_at(&doc, Position::new(5, 3)).unwrap();
+        assert_eq!(at.document.language, "julia");
+        assert_eq!(at.position, Position::new(5, 3));
+        assert!(get_embedded_document_at(&doc, Position::new(3, 0)).is_none());
+        assert!(get_embedded_document_at(&doc, Position::new(4, 2)).is_none());
+    }
+}
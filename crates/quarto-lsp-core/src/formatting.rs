@@ -0,0 +1,399 @@
+//! Formatting QMD documents with the qmd writer.
+//!
+//! The document body is read with `readers::qmd` and written back with
+//! `writers::qmd`, so formatting gives the writer's canonical markdown
+//! (wrapped to the configured width). Front matter is kept as written.
+//!
+//! The result is returned as minimal edits: the formatted text is diffed
+//! line by line against the original, and only changed lines are replaced.
+//! Documents that don't parse are left alone, and so are documents the
+//! writer can't reproduce: formatting only applies when the formatted text
+//! reads back to the same document as the original.
+
+use pampa::filter_context::FilterContext;
+use pampa::filters::{Filter, FilterReturn, topdown_traverse_blocks};
+use pampa::pandoc::{Blocks, Inline, Pandoc, Space};
+use pampa::writers::incremental::block_source_info;
+use pampa::writers::qmd::{BulletMarker, QmdWriterOptions};
+use quarto_ast_reconcile::structural_eq_blocks;
+
+use crate::completions::front_matter_end;
+use crate::document::Document;
+use crate::types::{FormattingOptions, Location, Position, Range, TextEdit};
+
+/// Above this many line pairs, changed regions are replaced whole instead
+/// of diffed.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Get the edits that format a whole document.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, FormattingOptions, format_document};
+///
+/// let doc = Document::new("test.qmd", "Some\ntext.\n");
+/// let options = FormattingOptions { wrap_width: Some(72), ..Default::default() };
+/// let edits = format_document(&doc, &options);
+/// assert_eq!(edits[0].new_text, "Some text.\n");
+/// ```
+pub fn format_document(doc: &Document, options: &FormattingOptions) -> Vec<TextEdit> {
+    let content = doc.content();
+    let body_start = body_start(content);
+    let body = &content[body_start..];
+    let Some(formatted) = format_qmd(body, options) else {
+        return Vec::new();
+    };
+
+    let mut formatted_content = content[..body_start].to_string();
+    if body_start > 0 && !formatted.is_empty() {
+        formatted_content.push('\n');
+    }
+    formatted_content.push_str(&formatted);
+    diff_edits(content, &formatted_content, 0)
+}
+
+/// Get the edits that format the blocks overlapping a range.
+///
+/// The range is extended to whole top-level blocks, so a selection inside a
+/// paragraph formats the paragraph.
+pub fn format_range(doc: &Document, range: Range, options: &FormattingOptions) -> Vec<TextEdit> {
+    let content = doc.content();
+    let body_start = body_start(content);
+    let body = &content[body_start..];
+    let Ok((pandoc, _context, _warnings)) = pampa::readers::qmd::read(
+        body.as_bytes(),
+        false,
+        doc.filename(),
+        &mut std::io::sink(),
+        true,
+        None,
+    ) else {
+        return Vec::new();
+    };
+
+    let line_starts = line_starts(content);
+    let range_start = offset_of(&line_starts, range.start.line);
+    // A selection of whole lines ends at the start of the next line
+    let end_line = if range.end.character == 0 && range.end.line > range.start.line {
+        range.end.line
+    } else {
+        range.end.line + 1
+    };
+    let range_end = offset_of(&line_starts, end_line);
+    let spans: Vec<(usize, usize)> = pandoc
+        .blocks
+        .iter()
+        .map(|block| {
+            let source_info = block_source_info(block);
+            (
+                body_start + source_info.start_offset(),
+                body_start + source_info.end_offset(),
+            )
+        })
+        .filter(|&(start, end)| start < range_end && end > range_start)
+        .collect();
+    let (Some(&(start, _)), Some(&(_, end))) = (spans.first(), spans.last()) else {
+        return Vec::new();
+    };
+
+    // Whole lines, from the start of the first block's line to the end of
+    // the last block's line (without the blank lines after it)
+    let start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+    let mut end = if content[..end].ends_with('\n') {
+        end
+    } else {
+        content[end..]
+            .find('\n')
+            .map_or(content.len(), |i| end + i + 1)
+    };
+    while content[start..end].ends_with("\n\n") {
+        end -= 1;
+    }
+    let Some(formatted) = format_qmd(&content[start..end], options) else {
+        return Vec::new();
+    };
+    let first_line = content[..start].matches('\n').count() as u32;
+    diff_edits(&content[start..end], &formatted, first_line)
+}
+
+/// The byte offset where the body starts, after the front matter and its
+/// closing delimiter.
+fn body_start(content: &str) -> usize {
+    let lines: Vec<&str> = content.lines().collect();
+    match front_matter_end(&lines) {
+        Some(end) if end < lines.len() => offset_of(&line_starts(content), end as u32 + 1),
+        _ => 0,
+    }
+}
+
+/// Read `text` and write it back with the qmd writer, or `None` if it
+/// doesn't parse, can't be written, or the written text doesn't read back to
+/// the same blocks (source locations and line wrapping aside).
+fn format_qmd(text: &str, options: &FormattingOptions) -> Option<String> {
+    let pandoc = read_qmd(text)?;
+
+    let writer_options = QmdWriterOptions {
+        wrap_width: options.wrap_width,
+        bullet_marker: match options.bullet_marker {
+            '-' => BulletMarker::Dash,
+            '+' => BulletMarker::Plus,
+            _ => BulletMarker::Asterisk,
+        },
+        compact_tables: options.compact_tables,
+        ..Default::default()
+    };
+    let mut buf = Vec::new();
+    pampa::writers::qmd::write_with_options(&pandoc, &mut buf, &writer_options).ok()?;
+    let formatted = String::from_utf8(buf).ok()?;
+
+    let reread = read_qmd(&formatted)?;
+    structural_eq_blocks(&unwrapped_blocks(pandoc), &unwrapped_blocks(reread)).then_some(formatted)
+}
+
+fn read_qmd(text: &str) -> Option<Pandoc> {
+    let (pandoc, _context, _warnings) = pampa::readers::qmd::read(
+        text.as_bytes(),
+        false,
+        "<format>",
+        &mut std::io::sink(),
+        true,
+        None,
+    )
+    .ok()?;
+    Some(pandoc)
+}
+
+/// The blocks of `pandoc` with soft breaks turned into spaces, since
+/// rewrapping moves them without changing the document.
+fn unwrapped_blocks(pandoc: Pandoc) -> Blocks {
+    let mut filter = Filter::new().with_soft_break(|soft_break, _| {
+        let space = Inline::Space(Space {
+            source_info: soft_break.source_info,
+        });
+        FilterReturn::FilterResult(vec![space], false)
+    });
+    topdown_traverse_blocks(pandoc.blocks, &mut filter, &mut FilterContext::new())
+}
+
+/// The byte offset of the start of each line.
+fn line_starts(content: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// The byte offset of the start of `line`, or the end of the content past
+/// the last line.
+fn offset_of(line_starts: &[usize], line: u32) -> usize {
+    match line_starts.get(line as usize) {
+        Some(&offset) => offset,
+        None => *line_starts.last().unwrap_or(&0),
+    }
+}
+
+/// The edits that turn `original` into `formatted`, each replacing a run of
+/// changed lines. Lines are numbered from `first_line`.
+fn diff_edits(original: &str, formatted: &str, first_line: u32) -> Vec<TextEdit> {
+    let old: Vec<&str> = original.split_inclusive('\n').collect();
+    let new: Vec<&str> = formatted.split_inclusive('\n').collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_changed = &old[prefix..old.len() - suffix];
+    let new_changed = &new[prefix..new.len() - suffix];
+
+    let hunks = if old_changed.len() * new_changed.len() > MAX_DIFF_CELLS {
+        vec![(0..old_changed.len(), 0..new_changed.len())]
+    } else {
+        diff_hunks(old_changed, new_changed)
+    };
+
+    hunks
+        .into_iter()
+        .map(|(old_lines, new_lines)| {
+            let start = line_position(&old, prefix + old_lines.start, first_line);
+            let end = line_position(&old, prefix + old_lines.end, first_line);
+            TextEdit::new(
+                Location::in_document(Range::new(start, end)),
+                new_changed[new_lines].concat(),
+            )
+        })
+        .collect()
+}
+
+/// The position of the start of line `index` of `lines`, or the end of the
+/// last line when it has no newline.
+fn line_position(lines: &[&str], index: usize, first_line: u32) -> Position {
+    match lines.last() {
+        Some(last) if index == lines.len() && !last.ends_with('\n') => Position::new(
+            first_line + index as u32 - 1,
+            last.encode_utf16().count() as u32,
+        ),
+        _ => Position::new(first_line + index as u32, 0),
+    }
+}
+
+/// The runs of lines that differ between `old` and `new`, from their
+/// longest common subsequence, as pairs of line ranges.
+fn diff_hunks(old: &[&str], new: &[&str]) -> Vec<(std::ops::Range<usize>, std::ops::Range<usize>)> {
+    // lengths[i][j]: the LCS length of old[i..] and new[j..]
+    let mut lengths = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let (mut hunk_i, mut hunk_j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            if (hunk_i, hunk_j) != (i, j) {
+                hunks.push((hunk_i..i, hunk_j..j));
+            }
+            i += 1;
+            j += 1;
+            (hunk_i, hunk_j) = (i, j);
+        } else if j == new.len() || (i < old.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    if (hunk_i, hunk_j) != (i, j) {
+        hunks.push((hunk_i..i, hunk_j..j));
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(content: &str, edits: &[TextEdit]) -> String {
+        let lines: Vec<&str> = content.split_inclusive('\n').collect();
+        let offset = |position: Position| -> usize {
+            let line_start: usize = lines[..position.line as usize]
+                .iter()
+                .map(|l| l.len())
+                .sum();
+            line_start + position.character as usize
+        };
+        let mut result = content.to_string();
+        for edit in edits.iter().rev() {
+            let range = edit.location.range;
+            result.replace_range(offset(range.start)..offset(range.end), &edit.new_text);
+        }
+        result
+    }
+
+    fn wrap(width: usize) -> FormattingOptions {
+        FormattingOptions {
+            wrap_width: Some(width),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diff_replaces_only_changed_lines() {
+        let edits = diff_edits("a\nb\nc\nd\n", "a\nB\nc\nd\ne\n", 0);
+        assert_eq!(edits.len(), 2);
+        assert_eq!(
+            edits[0].location.range,
+            Range::new(Position::new(1, 0), Position::new(2, 0))
+        );
+        assert_eq!(edits[0].new_text, "B\n");
+        assert_eq!(
+            edits[1].location.range,
+            Range::new(Position::new(4, 0), Position::new(4, 0))
+        );
+        assert_eq!(edits[1].new_text, "e\n");
+
+        let edits = diff_edits("a\nb", "a\nb\n", 3);
+        assert_eq!(
+            edits[0].location.range,
+            Range::new(Position::new(4, 0), Position::new(4, 1))
+        );
+        assert!(diff_edits("same\n", "same\n", 0).is_empty());
+    }
+
+    #[test]
+    fn format_document_keeps_front_matter() {
+        let content = "---\ntitle: \"Hi\"   # comment\n---\n\nSome\ntext.\n\n-   one\n-   two\n";
+        let doc = Document::new("test.qmd", content);
+        let options = FormattingOptions {
+            wrap_width: Some(72),
+            bullet_marker: '-',
+            ..Default::default()
+        };
+
+        let edits = format_document(&doc, &options);
+        assert!(edits.iter().all(|edit| edit.location.range.start.line >= 4));
+        assert_eq!(
+            apply(content, &edits),
+            "---\ntitle: \"Hi\"   # comment\n---\n\nSome text.\n\n- one\n- two\n"
+        );
+    }
+
+    #[test]
+    fn format_formatted_document_is_a_no_op() {
+        let doc = Document::new("test.qmd", "# Title\n\nSome text.\n");
+        assert!(format_document(&doc, &wrap(72)).is_empty());
+    }
+
+    #[test]
+    fn format_keeps_shortcode_arguments() {
+        let content = "See\n{{< video https://example.com/a.mp4 title=\"A clip\" width=300 >}}.\n";
+        let doc = Document::new("test.qmd", content);
+
+        let edits = format_document(&doc, &wrap(72));
+        assert_eq!(
+            apply(content, &edits),
+            "See {{< video https://example.com/a.mp4 title=\"A clip\" width=300 >}}.\n"
+        );
+    }
+
+    #[test]
+    fn format_keeps_table_caption_ids() {
+        let content = "| a | b |\n|---|---|\n| 1 | 2 |\n\n: Results {#tbl-results}\n";
+        let doc = Document::new("test.qmd", content);
+
+        let edits = format_document(&doc, &wrap(72));
+        assert_eq!(
+            apply(content, &edits),
+            "| a   | b   |\n| --- | --- |\n| 1   | 2   |\n\n: Results {#tbl-results}\n"
+        );
+    }
+
+    #[test]
+    fn format_skips_documents_that_do_not_round_trip() {
+        // The writer turns a figure with an id into a div that doesn't read
+        // back as the same figure
+        let doc = Document::new("test.qmd", "Some\ntext.\n\n![A cat](cat.png){#fig-cat}\n");
+        assert!(format_document(&doc, &wrap(72)).is_empty());
+    }
+
+    #[test]
+    fn format_range_formats_overlapping_blocks() {
+        let content = "One\ntwo.\n\nThree\nfour.\n\nFive\nsix.\n";
+        let doc = Document::new("test.qmd", content);
+        let range = Range::new(Position::new(4, 1), Position::new(4, 2));
+
+        let edits = format_range(&doc, range, &wrap(72));
+        assert_eq!(
+            apply(content, &edits),
+            "One\ntwo.\n\nThree four.\n\nFive\nsix.\n"
+        );
+    }
+}
@@ -0,0 +1,453 @@
+//! Hover information for QMD documents.
+//!
+//! This module describes the element under the cursor, as markdown:
+//!
+//! - **Front matter keys**: the option's description and values, from the
+//!   Quarto schema (see [`quarto_config::config_completion_model`])
+//! - **Cell options** (`#| fig-cap: ...`) in executable code cells
+//! - **Citations** (`@key`): the bibliography entry, formatted with the
+//!   document's citation style (or Chicago author-date) by `quarto-citeproc`
+//! - **Cross-references** (`@fig-plot`): the kind of target and its caption
+//! - **Link targets**: the heading or element an `#id` links to, or whether
+//!   a linked file exists and its title
+//!
+//! Like completion, hover works on the document text, so it is available
+//! while the document doesn't parse.
+
+use crate::bibliography::{bibliography_entries, string_list};
+use crate::completions::{
+    crossref_kind, crossref_labels, front_matter, front_matter_end, key_line, line_prefix,
+    parent_keys,
+};
+use crate::document::Document;
+use crate::index::citations;
+use crate::types::{Hover, Position, Range};
+use crate::workspace::{Workspace, join_path};
+use quarto_citeproc::{Processor, Reference};
+use quarto_yaml_validation::CompletionNode;
+
+/// Get the hover information at a position in a document.
+///
+/// Returns `None` when there is nothing to describe at the position.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, NoWorkspace, Position, get_hover};
+///
+/// let doc = Document::new("test.qmd", "---\ntoc: true\n---\n");
+/// let hover = get_hover(&doc, Position::new(1, 1), &NoWorkspace).unwrap();
+/// assert!(hover.contents.contains("table of contents"));
+/// ```
+pub fn get_hover(doc: &Document, position: Position, workspace: &dyn Workspace) -> Option<Hover> {
+    let lines: Vec<&str> = doc.content().lines().collect();
+    let line_index = position.line as usize;
+    let line = *lines.get(line_index)?;
+    let cursor = line_prefix(line, position.character).len();
+    let span = |start: usize, end: usize| {
+        let column = |offset: usize| line[..offset].chars().count() as u32;
+        Range::new(
+            Position::new(position.line, column(start)),
+            Position::new(position.line, column(end)),
+        )
+    };
+
+    if let Some(end) = front_matter_end(&lines)
+        && line_index > 0
+        && line_index < end
+    {
+        let (indent, key) = key_line(line)?;
+        let key_end = indent + key.len();
+        if !(indent..=key_end).contains(&cursor) {
+            return None;
+        }
+        let mut path = parent_keys(&lines[1..line_index], indent);
+        path.push(key.to_string());
+        let path: Vec<&str> = path.iter().map(String::as_str).collect();
+        let node = quarto_config::config_completion_model().at_path(&path)?;
+        return Some(Hover::new(
+            option_docs(&path.join("."), node),
+            span(indent, key_end),
+        ));
+    }
+
+    match code_fence(&lines, line_index) {
+        Some(fence) if fence.trim_start().starts_with("```{") => {
+            let (start, key) = cell_option(line)?;
+            let key_end = start + key.len();
+            if !(start..=key_end).contains(&cursor) {
+                return None;
+            }
+            let node = quarto_config::cell_option_completion_model()
+                .keys
+                .get(key)?;
+            return Some(Hover::new(option_docs(key, node), span(start, key_end)));
+        }
+        Some(_) => return None,
+        None => {}
+    }
+
+    if let Some((start, end, key)) = citation_at(line, cursor) {
+        let contents = if crossref_kind(key).is_some()
+            && crossref_labels(&lines).iter().any(|label| label == key)
+        {
+            crossref_docs(&lines, key)
+        } else {
+            citation_docs(&lines, key, workspace)?
+        };
+        return Some(Hover::new(contents, span(start, end)));
+    }
+
+    let (start, end, target) = link_target_at(line, cursor)?;
+    Some(Hover::new(
+        link_docs(&lines, target, workspace),
+        span(start, end),
+    ))
+}
+
+// ============================================================================
+// Options
+// ============================================================================
+
+/// Markdown documentation of an option: its name, description and values.
+fn option_docs(name: &str, node: &CompletionNode) -> String {
+    let mut sections = vec![format!("`{}`", name)];
+    sections.extend(node.description.clone());
+    sections.extend(node.documentation.clone());
+    if !node.values.is_empty() {
+        let values: Vec<String> = node
+            .values
+            .iter()
+            .map(|value| format!("`{}`", value))
+            .collect();
+        sections.push(format!("Values: {}", values.join(", ")));
+    } else if !node.types.is_empty() {
+        sections.push(format!("Type: {}", node.types.join(" | ")));
+    }
+    sections.join("\n\n")
+}
+
+/// The opening fence line of the code block containing line `index`, if
+/// the line is inside one.
+fn code_fence<'a>(lines: &[&'a str], index: usize) -> Option<&'a str> {
+    let mut open: Option<&str> = None;
+    for line in &lines[..index] {
+        let text = line.trim_start();
+        match open {
+            Some(fence) => {
+                let width = fence.trim_start().chars().take_while(|c| *c == '`').count();
+                let text = text.trim_end();
+                if text.len() >= width && text.chars().all(|c| c == '`') {
+                    open = None;
+                }
+            }
+            None if text.starts_with("```") => open = Some(line),
+            None => {}
+        }
+    }
+    open
+}
+
+/// The byte offset and key of a cell option line (`#| key: value`).
+fn cell_option(line: &str) -> Option<(usize, &str)> {
+    let text = line.trim_start().strip_prefix("#|")?.trim_start();
+    let (key, _) = text.split_once(':')?;
+    let key = key.trim_end();
+    (!key.is_empty()).then_some((line.len() - text.len(), key))
+}
+
+// ============================================================================
+// Citations and Cross-references
+// ============================================================================
+
+/// The byte span and key of the citation (`@key`) under the cursor.
+fn citation_at(line: &str, cursor: usize) -> Option<(usize, usize, &str)> {
+    citations(line)
+        .into_iter()
+        .find(|(at, end, _)| (*at..=*end).contains(&cursor))
+}
+
+/// Markdown documentation of a cross-reference: its kind and caption.
+fn crossref_docs(lines: &[&str], label: &str) -> String {
+    let kind = crossref_kind(label).unwrap_or("Cross-reference");
+    let mut contents = format!("**{}** `{}`", kind, label);
+    if let Some(caption) = crossref_caption(lines, label) {
+        contents.push_str("\n\n");
+        contents.push_str(&caption);
+    }
+    contents
+}
+
+/// The caption of the element labeled `label`: a heading's text, an
+/// image's alt text, a table caption or a cell's `fig-cap`/`tbl-cap`.
+fn crossref_caption(lines: &[&str], label: &str) -> Option<String> {
+    let (index, line) = lines.iter().enumerate().find(|(_, line)| {
+        crossref_labels(std::slice::from_ref(*line))
+            .iter()
+            .any(|l| l == label)
+    })?;
+
+    let text = line.trim_start();
+    if text.starts_with("#|") {
+        return lines[index + 1..]
+            .iter()
+            .map_while(|line| cell_option(line).map(|_| line.trim_start()))
+            .chain(
+                lines[..index]
+                    .iter()
+                    .rev()
+                    .map_while(|line| cell_option(line).map(|_| line.trim_start())),
+            )
+            .find_map(|option| {
+                let (key, value) = option.strip_prefix("#|")?.split_once(':')?;
+                matches!(key.trim(), "fig-cap" | "tbl-cap" | "lst-cap")
+                    .then(|| value.trim().trim_matches(['"', '\'']).to_string())
+            });
+    }
+    let before_attributes = text[..text.find('{').unwrap_or(text.len())].trim();
+    let caption = if let Some(image) = before_attributes.strip_prefix("![") {
+        image.rsplit_once("](")?.0
+    } else if text.starts_with('#') {
+        before_attributes.trim_start_matches('#')
+    } else {
+        before_attributes.strip_prefix(':')?
+    };
+    let caption = caption.trim();
+    (!caption.is_empty()).then(|| caption.to_string())
+}
+
+/// Markdown documentation of a citation: the bibliography entry, formatted
+/// when it is a CSL entry, or its title.
+fn citation_docs(lines: &[&str], key: &str, workspace: &dyn Workspace) -> Option<String> {
+    let entry = bibliography_entries(lines, workspace)
+        .into_iter()
+        .find(|entry| entry.key == key)?;
+    let formatted = entry
+        .reference
+        .as_ref()
+        .and_then(|reference| format_reference(reference, &csl_style(lines, workspace)));
+    Some(match (formatted, entry.title) {
+        (Some(formatted), _) => formatted,
+        (None, Some(title)) => format!("`@{}`\n\n{}", key, title),
+        (None, None) => format!("`@{}`", key),
+    })
+}
+
+/// The citation style named by the document's `csl` option, or the default
+/// style.
+fn csl_style(lines: &[&str], workspace: &dyn Workspace) -> String {
+    front_matter(lines)
+        .and_then(|meta| string_list(&meta.yaml["csl"]).into_iter().next())
+        .and_then(|path| workspace.read_file(&path))
+        .unwrap_or_else(|| quarto_citeproc::DEFAULT_STYLE.to_string())
+}
+
+/// Format a CSL JSON item as a bibliography entry. Falls back to the
+/// default style if `style` doesn't parse.
+fn format_reference(reference: &serde_json::Value, style: &str) -> Option<String> {
+    let style = quarto_csl::parse_csl(style)
+        .ok()
+        .or_else(|| quarto_csl::parse_csl(quarto_citeproc::DEFAULT_STYLE).ok())?;
+    let reference: Reference = serde_json::from_value(reference.clone()).ok()?;
+    let id = reference.id.clone();
+    let mut processor = Processor::new(style);
+    processor.add_reference(reference);
+    processor
+        .format_bibliography_entry(&id)
+        .ok()
+        .flatten()
+        .filter(|entry| !entry.trim().is_empty())
+}
+
+// ============================================================================
+// Links
+// ============================================================================
+
+/// The byte span and target of the link target (`[text](target)`) under
+/// the cursor. A link title (`"title"`) is not part of the target.
+pub(crate) fn link_target_at(line: &str, cursor: usize) -> Option<(usize, usize, &str)> {
+    line.match_indices("](").find_map(|(open, _)| {
+        let start = open + 2;
+        let end = start + line[start..].find(')')?;
+        if !(start..=end).contains(&cursor) {
+            return None;
+        }
+        let raw = line[start..end].trim();
+        let target = raw.split_whitespace().next()?;
+        let target = target.trim_start_matches('<').trim_end_matches('>');
+        Some((start, end, target))
+    })
+}
+
+/// Markdown documentation of a link target: where an `#id` is defined, or
+/// whether a linked file exists and its title.
+fn link_docs(lines: &[&str], target: &str, workspace: &dyn Workspace) -> String {
+    if let Some(id) = target.strip_prefix('#') {
+        return match lines.iter().position(|line| {
+            crossref_labels(std::slice::from_ref(line))
+                .iter()
+                .any(|l| l == id)
+        }) {
+            Some(index) => {
+                let mut contents = format!("`#{}` (line {})", id, index + 1);
+                if let Some(caption) = crossref_caption(lines, id) {
+                    contents.push_str("\n\n");
+                    contents.push_str(&caption);
+                }
+                contents
+            }
+            None => format!("`#{}` is not defined in this document", id),
+        };
+    }
+    if target.contains("://") || target.starts_with("mailto:") {
+        return format!("<{}>", target);
+    }
+
+    let path = target
+        .split(['#', '?'])
+        .next()
+        .unwrap_or(target)
+        .trim_start_matches("./");
+    let (dir, name) = match path.rfind('/') {
+        Some(slash) => (&path[..slash], &path[slash + 1..]),
+        None => ("", path),
+    };
+    let exists = workspace
+        .read_dir(dir)
+        .iter()
+        .any(|entry| entry.name == name);
+    if !exists {
+        return format!("`{}` (file not found)", path);
+    }
+    let title = workspace
+        .read_file(&join_path(dir, name))
+        .filter(|_| name.ends_with(".qmd") || name.ends_with(".md"))
+        .and_then(|content| {
+            let lines: Vec<&str> = content.lines().collect();
+            front_matter(&lines)?.yaml["title"]
+                .as_str()
+                .map(String::from)
+        });
+    match title {
+        Some(title) => format!("**{}**\n\n`{}`", title, path),
+        None => format!("`{}`", path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::{MemoryWorkspace, NoWorkspace};
+
+    /// Hover at the last `|` in `content` (earlier ones may be cell option
+    /// markers).
+    fn hover_at(content: &str, workspace: &dyn Workspace) -> Option<Hover> {
+        let offset = content.rfind('|').expect("cursor marker");
+        let before = &content[..offset];
+        let line = before.matches('\n').count() as u32;
+        let character = before.rsplit('\n').next().unwrap().chars().count() as u32;
+        let doc = Document::new("test.qmd", format!("{}{}", before, &content[offset + 1..]));
+        get_hover(&doc, Position::new(line, character), workspace)
+    }
+
+    #[test]
+    fn front_matter_option_docs() {
+        let hover = hover_at(
+            "---\nformat:\n  html:\n    toc-lo|cation: left\n---\n",
+            &NoWorkspace,
+        )
+        .unwrap();
+        assert!(hover.contents.starts_with("`format.html.toc-location`"));
+        assert!(
+            hover
+                .contents
+                .contains("Where to place the table of contents")
+        );
+        assert!(hover.contents.contains("`left-body`"));
+        assert_eq!(
+            hover.range,
+            Range::new(Position::new(3, 4), Position::new(3, 16))
+        );
+
+        assert!(hover_at("---\ntoc: tr|ue\n---\n", &NoWorkspace).is_none());
+        assert!(hover_at("---\nmy-own|-key: 1\n---\n", &NoWorkspace).is_none());
+    }
+
+    #[test]
+    fn cell_option_docs() {
+        let content = "```{r}\n#| fig-c|ap: A plot\nplot(1)\n```\n";
+        let hover = hover_at(content, &NoWorkspace).unwrap();
+        assert!(hover.contents.contains("Figure caption"));
+
+        let hover = hover_at("```{python}\n#| ec|ho: false\n```\n", &NoWorkspace).unwrap();
+        assert!(hover.contents.contains("`fenced`"));
+    }
+
+    #[test]
+    fn no_hover_in_plain_code_blocks() {
+        let content = "```python\n@prop|erty\ndef x(self): ...\n```\n";
+        assert!(hover_at(content, &NoWorkspace).is_none());
+    }
+
+    #[test]
+    fn citation_preview() {
+        let content = "---\nreferences:\n  - id: knuth1984\n    type: article-journal\n    title: Literate Programming\n    author:\n      - family: Knuth\n        given: Donald E.\n    container-title: The Computer Journal\n    issued:\n      date-parts: [[1984]]\n---\n\nAs shown by @knu|th1984.\n";
+        let hover = hover_at(content, &NoWorkspace).unwrap();
+        assert!(hover.contents.contains("Knuth"), "{}", hover.contents);
+        assert!(hover.contents.contains("Literate Programming"));
+        assert!(hover.contents.contains("1984"));
+        assert_eq!(
+            hover.range,
+            Range::new(Position::new(13, 12), Position::new(13, 22))
+        );
+    }
+
+    #[test]
+    fn bibtex_citation_shows_title() {
+        let workspace = MemoryWorkspace::default().with_file(
+            "refs.bib",
+            "@book{lamport1994,\n  title = {{LaTeX}: A Document Preparation System}\n}\n",
+        );
+        let content = "---\nbibliography: refs.bib\n---\n\n[@lamport1994|]\n";
+        let hover = hover_at(content, &workspace).unwrap();
+        assert_eq!(
+            hover.contents,
+            "`@lamport1994`\n\nLaTeX: A Document Preparation System"
+        );
+
+        assert!(hover_at("[@unknown|]\n", &workspace).is_none());
+    }
+
+    #[test]
+    fn crossref_captions() {
+        let content = "![A scatter plot](plot.png){#fig-plot}\n\nSee @fig-pl|ot.\n";
+        let hover = hover_at(content, &NoWorkspace).unwrap();
+        assert_eq!(hover.contents, "**Figure** `fig-plot`\n\nA scatter plot");
+
+        let content = "```{r}\n#| label: tbl-data\n#| tbl-cap: \"Raw data\"\nhead(x)\n```\n\nSee @tbl-data|\n";
+        let hover = hover_at(content, &NoWorkspace).unwrap();
+        assert_eq!(hover.contents, "**Table** `tbl-data`\n\nRaw data");
+    }
+
+    #[test]
+    fn link_targets() {
+        let workspace = MemoryWorkspace::default()
+            .with_file("chapters/intro.qmd", "---\ntitle: Introduction\n---\n");
+        let hover = hover_at("See [intro](chapters/in|tro.qmd#scope)\n", &workspace).unwrap();
+        assert_eq!(hover.contents, "**Introduction**\n\n`chapters/intro.qmd`");
+        assert_eq!(
+            hover.range,
+            Range::new(Position::new(0, 12), Position::new(0, 36))
+        );
+
+        let hover = hover_at("[gone](missing.qmd|)\n", &workspace).unwrap();
+        assert_eq!(hover.contents, "`missing.qmd` (file not found)");
+
+        let content = "# Methods {#sec-methods}\n\nSee [methods](#sec-me|thods)\n";
+        let hover = hover_at(content, &NoWorkspace).unwrap();
+        assert_eq!(hover.contents, "`#sec-methods` (line 1)\n\nMethods");
+
+        let hover = hover_at("[site](https://quarto.org|)\n", &NoWorkspace).unwrap();
+        assert_eq!(hover.contents, "<https://quarto.org>");
+    }
+}
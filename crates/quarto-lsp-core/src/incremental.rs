@@ -0,0 +1,342 @@
+//! Incremental updates of a document's analysis after an edit.
+//!
+//! Only the top-level sections the edit touches (those where the syntax tree
+//! changed) are analyzed again. The text analyzed is the front matter, blank
+//! lines down to the sections and the sections themselves, so that positions
+//! in its analysis are positions in the document and `{{< meta >}}`
+//! shortcodes still resolve. The symbols, folding ranges and diagnostics of
+//! the rest of the document are kept, moved by the lines and columns the
+//! edit added or removed.
+
+use std::ops::Range as ByteRange;
+
+use crate::analysis::{CachedAnalysis, analyze};
+use crate::document::Document;
+use crate::document_links::get_document_links;
+use crate::types::{Diagnostic, DocumentAnalysis, FoldingRange, Position, Range, Symbol};
+
+/// A replacement of text, in positions of the document.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Edit {
+    /// The start of the replaced text.
+    pub(crate) start: Position,
+    /// The end of the replaced text, before the edit.
+    pub(crate) old_end: Position,
+    /// The end of the new text, after the edit.
+    pub(crate) new_end: Position,
+}
+
+impl Edit {
+    /// Where a position from before the edit is after it. Positions in the
+    /// replaced text move to the end of the new text.
+    fn position(&self, position: Position) -> Position {
+        if position <= self.start {
+            position
+        } else if position < self.old_end {
+            self.new_end
+        } else if position.line == self.old_end.line {
+            Position::new(
+                self.new_end.line,
+                self.new_end.character + (position.character - self.old_end.character),
+            )
+        } else {
+            Position::new(self.line(position.line), position.character)
+        }
+    }
+
+    fn range(&self, range: Range) -> Range {
+        Range::new(self.position(range.start), self.position(range.end))
+    }
+
+    fn line(&self, line: u32) -> u32 {
+        if line <= self.start.line {
+            line
+        } else if line < self.old_end.line {
+            self.new_end.line
+        } else {
+            line - self.old_end.line + self.new_end.line
+        }
+    }
+}
+
+/// Update the analysis of a document after an edit, given the byte ranges
+/// of the document where the syntax tree changed.
+///
+/// Returns `None` when the document must be analyzed again as a whole: the
+/// front matter changed, the document didn't parse, or the changes aren't
+/// in a section.
+pub(crate) fn update_analysis(
+    doc: &Document,
+    old: &CachedAnalysis,
+    edit: &Edit,
+    changed: &[ByteRange<usize>],
+) -> Option<CachedAnalysis> {
+    if !old.parsed {
+        return None;
+    }
+    let content = doc.content();
+    let root = doc.tree()?.block_tree().root_node();
+
+    // The top-level sections with changes
+    let mut span: Option<ByteRange<usize>> = None;
+    let mut front_matter_end = 0;
+    for node in (0..root.child_count()).filter_map(|i| root.child(i)) {
+        let touched = changed
+            .iter()
+            .any(|range| range.start <= node.end_byte() && node.start_byte() <= range.end);
+        if node.kind() == "metadata" {
+            if touched {
+                return None;
+            }
+            front_matter_end = node.end_byte();
+        } else if touched {
+            span = Some(match span {
+                Some(span) => span.start.min(node.start_byte())..span.end.max(node.end_byte()),
+                None => node.byte_range(),
+            });
+        }
+    }
+    let span = span?;
+
+    let front_matter = &content[..front_matter_end];
+    let at_line_start = |offset: usize| offset == 0 || content[..offset].ends_with('\n');
+    if !at_line_start(front_matter_end) || !at_line_start(span.start) {
+        return None;
+    }
+    let start_line = content[..span.start].matches('\n').count();
+    let padding = start_line.checked_sub(front_matter.matches('\n').count())?;
+    let text = format!(
+        "{}{}{}",
+        front_matter,
+        "\n".repeat(padding),
+        &content[span.clone()]
+    );
+    let partial = analyze(&Document::new(doc.uri(), text));
+    if !partial.parsed {
+        return None;
+    }
+
+    // Lines from the first line of the sections to the line after them
+    let end_line = if span.end >= content.len() {
+        u32::MAX
+    } else {
+        let line = content[..span.end].matches('\n').count() as u32;
+        line + u32::from(!at_line_start(span.end))
+    };
+    let in_span = |line: u32| (start_line as u32..end_line).contains(&line);
+
+    let old = &old.analysis;
+    let partial = partial.analysis;
+    let mut symbols: Vec<Symbol> = old
+        .symbols
+        .iter()
+        .map(|symbol| shift_symbol(edit, symbol))
+        .filter(|symbol| !in_span(symbol.range.start.line))
+        .chain(
+            partial
+                .symbols
+                .into_iter()
+                .filter(|symbol| in_span(symbol.range.start.line)),
+        )
+        .collect();
+    symbols.sort_by_key(|symbol| symbol.range.start);
+
+    let mut folding_ranges: Vec<FoldingRange> = old
+        .folding_ranges
+        .iter()
+        .map(|range| FoldingRange {
+            start_line: edit.line(range.start_line),
+            end_line: edit.line(range.end_line),
+            ..range.clone()
+        })
+        .filter(|range| !in_span(range.start_line))
+        .chain(
+            partial
+                .folding_ranges
+                .into_iter()
+                .filter(|range| in_span(range.start_line)),
+        )
+        .collect();
+    folding_ranges.sort_by_key(|range| range.start_line);
+
+    let mut diagnostics: Vec<Diagnostic> = old
+        .diagnostics
+        .iter()
+        .map(|diagnostic| shift_diagnostic(edit, diagnostic))
+        .filter(|diagnostic| !in_span(diagnostic.range.start.line))
+        .chain(
+            partial
+                .diagnostics
+                .into_iter()
+                .filter(|diagnostic| in_span(diagnostic.range.start.line)),
+        )
+        .collect();
+    diagnostics.sort_by_key(|diagnostic| diagnostic.range.start);
+
+    Some(CachedAnalysis {
+        analysis: DocumentAnalysis::with_data(
+            symbols,
+            folding_ranges,
+            diagnostics,
+            get_document_links(doc),
+            doc.create_source_context(),
+        ),
+        parsed: true,
+    })
+}
+
+fn shift_symbol(edit: &Edit, symbol: &Symbol) -> Symbol {
+    Symbol {
+        range: edit.range(symbol.range),
+        selection_range: edit.range(symbol.selection_range),
+        children: symbol
+            .children
+            .iter()
+            .map(|child| shift_symbol(edit, child))
+            .collect(),
+        ..symbol.clone()
+    }
+}
+
+fn shift_diagnostic(edit: &Edit, diagnostic: &Diagnostic) -> Diagnostic {
+    let mut diagnostic = diagnostic.clone();
+    diagnostic.range = edit.range(diagnostic.range);
+    for range in diagnostic
+        .details
+        .iter_mut()
+        .filter_map(|detail| detail.range.as_mut())
+    {
+        *range = edit.range(*range);
+    }
+    for location in diagnostic
+        .fixes
+        .iter_mut()
+        .flat_map(|fix| fix.edits.iter_mut())
+        .map(|edit| &mut edit.location)
+        .filter(|location| location.path.is_none())
+    {
+        location.range = edit.range(location.range);
+    }
+    diagnostic
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyze_document;
+
+    /// The document after replacing the text of `range` in `content`, with
+    /// its analysis computed before the edit, and whether the edit updated
+    /// the analysis rather than discarding it.
+    fn edited(content: &str, range: Range, text: &str) -> (Document, bool) {
+        let mut doc = Document::new("test.qmd", content);
+        doc.analysis();
+        doc.apply_edit(range, text);
+        let updated = doc.is_analyzed();
+        (doc, updated)
+    }
+
+    /// Whether the updated analysis of a document is that of analyzing it
+    /// from scratch, up to the order of diagnostics.
+    fn assert_same_analysis(doc: &Document) {
+        let sorted = |analysis: &DocumentAnalysis| {
+            let mut folding_ranges = analysis.folding_ranges.clone();
+            folding_ranges.sort_by_key(|range| range.start_line);
+            let mut diagnostics = analysis.diagnostics.clone();
+            diagnostics.sort_by_key(|diagnostic| diagnostic.range.start);
+            (folding_ranges, diagnostics)
+        };
+        let updated = doc.analysis();
+        let fresh = analyze_document(&Document::new("test.qmd", doc.content()));
+        assert_eq!(updated.symbols, fresh.symbols);
+        assert_eq!(sorted(updated), sorted(&fresh));
+        assert_eq!(updated.document_links, fresh.document_links);
+    }
+
+    const CONTENT: &str = "---\ntitle: Report\n---\n\n# Intro\n\nSee [a](a.qmd).\n\n```{python}\nx = 1\n```\n\n# {{< meta title >}}\n\n## Details\n\nText\n";
+
+    #[test]
+    fn edit_within_a_section() {
+        let at = Position::new(6, 4);
+        let (doc, updated) = edited(CONTENT, Range::new(at, at), "the *emphasized* ");
+        assert!(updated);
+        assert_same_analysis(&doc);
+        assert_eq!(doc.analysis().document_links[0].range.start.character, 25);
+    }
+
+    #[test]
+    fn adding_lines_moves_later_sections() {
+        let at = Position::new(10, 0);
+        let (doc, updated) = edited(CONTENT, Range::new(at, at), "y = 2\nz = 3\n");
+        assert!(updated);
+        assert_same_analysis(&doc);
+        let names: Vec<&str> = doc
+            .analysis()
+            .symbols
+            .iter()
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Intro", "Report"]);
+        assert_eq!(doc.analysis().symbols[1].range.start.line, 14);
+    }
+
+    #[test]
+    fn adding_and_removing_headings() {
+        let at = Position::new(7, 0);
+        let (doc, updated) = edited(CONTENT, Range::new(at, at), "# New\n\n");
+        assert!(updated);
+        assert_same_analysis(&doc);
+        assert_eq!(doc.analysis().symbols.len(), 3);
+
+        let (doc, updated) = edited(
+            CONTENT,
+            Range::new(Position::new(12, 0), Position::new(14, 0)),
+            "",
+        );
+        assert!(updated);
+        assert_same_analysis(&doc);
+        let intro = &doc.analysis().symbols[0];
+        assert_eq!(intro.children.last().unwrap().name, "Details");
+    }
+
+    #[test]
+    fn edits_of_the_front_matter_reanalyze_the_document() {
+        let (doc, updated) = edited(
+            CONTENT,
+            Range::new(Position::new(1, 7), Position::new(1, 13)),
+            "Paper",
+        );
+        assert!(!updated);
+        assert_same_analysis(&doc);
+        assert_eq!(doc.analysis().symbols[1].name, "Paper");
+    }
+
+    #[test]
+    fn diagnostics_in_the_edited_section() {
+        let at = Position::new(16, 4);
+        let (mut doc, updated) = edited(CONTENT, Range::new(at, at), "\n\n::: {.note}\nOpen");
+        assert!(updated);
+        assert_same_analysis(&doc);
+        assert!(!doc.analysis().diagnostics.is_empty());
+
+        let end = Position::new(19, 4);
+        doc.apply_edit(Range::new(end, end), "\n:::");
+        assert!(doc.is_analyzed());
+        assert_same_analysis(&doc);
+    }
+
+    #[test]
+    fn shifting_positions() {
+        let edit = Edit {
+            start: Position::new(2, 4),
+            old_end: Position::new(3, 1),
+            new_end: Position::new(2, 6),
+        };
+        assert_eq!(edit.position(Position::new(1, 9)), Position::new(1, 9));
+        assert_eq!(edit.position(Position::new(2, 4)), Position::new(2, 4));
+        assert_eq!(edit.position(Position::new(2, 8)), Position::new(2, 6));
+        assert_eq!(edit.position(Position::new(3, 5)), Position::new(2, 10));
+        assert_eq!(edit.position(Position::new(5, 3)), Position::new(4, 3));
+        assert_eq!(edit.line(5), 4);
+    }
+}
@@ -0,0 +1,385 @@
+//! Project-wide index of cross-reference labels and citations.
+//!
+//! The index records where labels are defined (`{#fig-plot}` attributes and
+//! `#| label:` cell options) and where labels and citation keys are used
+//! (`@fig-plot`, `[@knuth1984]`, `[text](#sec-intro)`), in a document and in
+//! the other documents of its project. It backs go-to-definition and
+//! find-references.
+//!
+//! Like completion, indexing works on the document text: front matter and
+//! code blocks are skipped, except for the `label` option of executable
+//! code cells.
+
+use crate::completions::{front_matter_end, is_citation_char};
+use crate::document::Document;
+use crate::types::{Location, Position, Range};
+use crate::workspace::{Workspace, join_path};
+
+/// An occurrence of a label or citation key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// The label or citation key, without `#` or `@`.
+    pub name: String,
+    /// Where it occurs.
+    pub location: Location,
+}
+
+/// Definitions and uses of labels and citation keys across a project.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolIndex {
+    definitions: Vec<IndexEntry>,
+    references: Vec<IndexEntry>,
+}
+
+impl SymbolIndex {
+    /// Create an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The index of a document and, if it is in a project, of the other
+    /// `.qmd` and `.md` files of the project.
+    ///
+    /// The document is indexed from its content rather than from disk, so
+    /// unsaved changes are taken into account.
+    pub fn for_document(doc: &Document, workspace: &dyn Workspace) -> Self {
+        let mut index = Self::new();
+        index.add_file(None, doc.content());
+        if let Some(project_dir) = workspace.project_dir() {
+            let current = document_in_project(doc.uri(), &project_dir);
+            for file in project_files(workspace, &project_dir, "") {
+                if Some(&file) == current.as_ref() {
+                    continue;
+                }
+                let path = join_path(&project_dir, &file);
+                if let Some(content) = workspace.read_file(&path) {
+                    index.add_file(Some(&path), &content);
+                }
+            }
+        }
+        index
+    }
+
+    /// Index the content of a file. `path` is relative to the document's
+    /// directory, or `None` for the document itself.
+    pub fn add_file(&mut self, path: Option<&str>, content: &str) {
+        let location = |line: usize, text: &str, start: usize, end: usize| {
+            let column = |offset: usize| text[..offset].chars().count() as u32;
+            let range = Range::new(
+                Position::new(line as u32, column(start)),
+                Position::new(line as u32, column(end)),
+            );
+            Location {
+                path: path.map(String::from),
+                range,
+            }
+        };
+        let entry = |name: &str, location: Location| IndexEntry {
+            name: name.to_string(),
+            location,
+        };
+
+        let lines: Vec<&str> = content.lines().collect();
+        let body_start = front_matter_end(&lines).map_or(0, |end| end + 1);
+        let mut fence: Option<&str> = None;
+        for (index, line) in lines.iter().enumerate().skip(body_start) {
+            let text = line.trim_start();
+            if let Some(open) = fence {
+                if closes_fence(open, text) {
+                    fence = None;
+                } else if open.starts_with("```{")
+                    && let Some((start, label)) = cell_label(line)
+                {
+                    let end = start + label.len();
+                    self.definitions
+                        .push(entry(label, location(index, line, start, end)));
+                }
+                continue;
+            }
+            if text.starts_with("```") {
+                fence = Some(text);
+                continue;
+            }
+            for (start, id) in attribute_ids(line) {
+                let end = start + id.len();
+                self.definitions
+                    .push(entry(id, location(index, line, start, end)));
+            }
+            for (at, end, key) in citations(line) {
+                self.references
+                    .push(entry(key, location(index, line, at + 1, end)));
+            }
+            for (start, id) in anchor_links(line) {
+                let end = start + id.len();
+                self.references
+                    .push(entry(id, location(index, line, start, end)));
+            }
+        }
+    }
+
+    /// The locations where `name` is defined.
+    pub fn definitions<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Location> + 'a {
+        self.definitions
+            .iter()
+            .filter(move |entry| entry.name == name)
+            .map(|entry| &entry.location)
+    }
+
+    /// The locations where `name` is used.
+    pub fn references<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Location> + 'a {
+        self.references
+            .iter()
+            .filter(move |entry| entry.name == name)
+            .map(|entry| &entry.location)
+    }
+
+    /// All the definitions, in the order they were indexed.
+    pub(crate) fn all_definitions(&self) -> &[IndexEntry] {
+        &self.definitions
+    }
+
+    /// The label or citation key defined or used at a position in the
+    /// document itself (the end of a name counts as part of it).
+    pub fn name_at(&self, position: Position) -> Option<&str> {
+        self.entry_at(position).map(|entry| entry.name.as_str())
+    }
+
+    /// The definition or use at a position in the document itself.
+    pub fn entry_at(&self, position: Position) -> Option<&IndexEntry> {
+        self.definitions
+            .iter()
+            .chain(&self.references)
+            .find(|entry| {
+                let range = entry.location.range;
+                entry.location.path.is_none() && range.start <= position && position <= range.end
+            })
+    }
+}
+
+/// Whether `text` closes the code block opened by the fence `open`.
+pub(crate) fn closes_fence(open: &str, text: &str) -> bool {
+    let width = open.chars().take_while(|c| *c == '`').count();
+    let text = text.trim_end();
+    text.len() >= width && text.chars().all(|c| c == '`')
+}
+
+/// The byte offset and value of a `#| label: ...` cell option.
+fn cell_label(line: &str) -> Option<(usize, &str)> {
+    let option = line.trim_start().strip_prefix("#|")?.trim_start();
+    let value = option.strip_prefix("label:")?.trim();
+    let label = value.trim_matches(['"', '\'']);
+    let start = line.len() - line.trim_start().len() + line.trim_start().rfind(label)?;
+    (!label.is_empty()).then_some((start, label))
+}
+
+/// The byte offsets and values of the `#id`s in the attributes of a line
+/// (`# Intro {#sec-intro}`, `![Plot](plot.png){#fig-plot}`).
+fn attribute_ids(line: &str) -> Vec<(usize, &str)> {
+    let mut ids = Vec::new();
+    let mut offset = 0;
+    while let Some(open) = line[offset..].find('{') {
+        let start = offset + open + 1;
+        let end = line[start..]
+            .find('}')
+            .map_or(line.len(), |close| start + close);
+        let mut token_start = start;
+        for token in line[start..end].split(' ') {
+            if let Some(id) = token.strip_prefix('#')
+                && !id.is_empty()
+            {
+                ids.push((token_start + 1, id));
+            }
+            token_start += token.len() + 1;
+        }
+        offset = end;
+    }
+    ids
+}
+
+/// The citations (`@key`) of a line: the byte offsets of the `@` and of the
+/// end of the key, and the key.
+pub(crate) fn citations(line: &str) -> Vec<(usize, usize, &str)> {
+    line.match_indices('@')
+        .filter_map(|(at, _)| {
+            let before = line[..at].chars().next_back();
+            if before.is_some_and(|c| !(c.is_whitespace() || matches!(c, '[' | ';' | '-' | '('))) {
+                // An email address or other text
+                return None;
+            }
+            let rest = &line[at + 1..];
+            let key = rest
+                .find(|c| !is_citation_char(c))
+                .map_or(rest, |end| &rest[..end]);
+            // Trailing punctuation ends the sentence, not the key
+            let key = key.trim_end_matches(|c: char| !(c.is_alphanumeric() || c == '_'));
+            (!key.is_empty()).then_some((at, at + 1 + key.len(), key))
+        })
+        .collect()
+}
+
+/// The byte offsets and ids of links to anchors in the same document
+/// (`[text](#sec-intro)`).
+fn anchor_links(line: &str) -> Vec<(usize, &str)> {
+    line.match_indices("](#")
+        .filter_map(|(open, _)| {
+            let start = open + 3;
+            let end = start + line[start..].find([')', ' '])?;
+            (end > start).then_some((start, &line[start..end]))
+        })
+        .collect()
+}
+
+/// The `.qmd` and `.md` files under `dir` of the project, relative to the
+/// project directory. Hidden directories and directories starting with `_`
+/// (`_site`, `_freeze`, `_extensions`) are skipped.
+pub(crate) fn project_files(
+    workspace: &dyn Workspace,
+    project_dir: &str,
+    dir: &str,
+) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut entries = workspace.read_dir(&join_path(project_dir, dir));
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    for entry in entries {
+        let path = join_path(dir, &entry.name);
+        if entry.is_dir {
+            if !entry.name.starts_with(['.', '_']) {
+                files.extend(project_files(workspace, project_dir, &path));
+            }
+        } else if entry.name.ends_with(".qmd") || entry.name.ends_with(".md") {
+            files.push(path);
+        }
+    }
+    files
+}
+
+/// The path of the document relative to the project directory, from its
+/// URI: the directory is `project_dir`'s number of `..` steps up from the
+/// document, so the path is that many trailing URI segments plus the file
+/// name.
+fn document_in_project(uri: &str, project_dir: &str) -> Option<String> {
+    let depth = project_dir
+        .split('/')
+        .filter(|segment| *segment == "..")
+        .count();
+    let segments: Vec<&str> = uri.rsplit('/').take(depth + 1).collect();
+    if segments.len() < depth + 1 {
+        return None;
+    }
+    let path: Vec<String> = segments.iter().rev().map(|s| percent_decode(s)).collect();
+    Some(path.join("/"))
+}
+
+/// Decode `%XX` escapes in a URI segment.
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = segment
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::MemoryWorkspace;
+
+    fn names(locations: Vec<&Location>) -> Vec<(Option<&str>, u32, u32)> {
+        locations
+            .into_iter()
+            .map(|location| {
+                (
+                    location.path.as_deref(),
+                    location.range.start.line,
+                    location.range.start.character,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn indexes_definitions_and_references() {
+        let content = "---\ntitle: \"@not-a-citation\"\n---\n\n# Intro {#sec-intro}\n\n![Plot](plot.png){#fig-plot width=50%}\n\n```{r}\n#| label: tbl-data\nx <- c(\"@nope\")\n```\n\nSee @fig-plot, @tbl-data and [the intro](#sec-intro) [@knuth1984].\n";
+        let mut index = SymbolIndex::new();
+        index.add_file(None, content);
+
+        assert_eq!(
+            names(index.definitions("sec-intro").collect()),
+            vec![(None, 4, 10)]
+        );
+        assert_eq!(
+            names(index.definitions("fig-plot").collect()),
+            vec![(None, 6, 19)]
+        );
+        assert_eq!(
+            names(index.definitions("tbl-data").collect()),
+            vec![(None, 9, 10)]
+        );
+        assert_eq!(
+            names(index.references("fig-plot").collect()),
+            vec![(None, 13, 5)]
+        );
+        assert_eq!(
+            names(index.references("sec-intro").collect()),
+            vec![(None, 13, 42)]
+        );
+        assert_eq!(
+            names(index.references("knuth1984").collect()),
+            vec![(None, 13, 55)]
+        );
+        assert_eq!(index.references("nope").count(), 0);
+        assert_eq!(index.references("not-a-citation").count(), 0);
+
+        assert_eq!(index.name_at(Position::new(13, 7)), Some("fig-plot"));
+        assert_eq!(index.name_at(Position::new(6, 20)), Some("fig-plot"));
+        assert_eq!(index.name_at(Position::new(13, 0)), None);
+    }
+
+    #[test]
+    fn indexes_project_documents() {
+        let workspace = MemoryWorkspace::default()
+            .with_project_dir("..")
+            .with_file("../_quarto.yml", "project:\n  type: book\n")
+            .with_file("../index.qmd", "See @fig-plot.\n")
+            .with_file("../chapters/plots.qmd", "stale copy {#fig-plot}\n")
+            .with_file("../_site/index.md", "@fig-plot\n");
+        let doc = Document::new(
+            "file:///book/chapters/plots.qmd",
+            "![Plot](plot.png){#fig-plot}\n",
+        );
+        let index = SymbolIndex::for_document(&doc, &workspace);
+
+        assert_eq!(
+            names(index.definitions("fig-plot").collect()),
+            vec![(None, 0, 19)]
+        );
+        assert_eq!(
+            names(index.references("fig-plot").collect()),
+            vec![(Some("../index.qmd"), 0, 5)]
+        );
+    }
+
+    #[test]
+    fn document_path_in_project() {
+        assert_eq!(
+            document_in_project("file:///book/chapters/my%20plots.qmd", "..").as_deref(),
+            Some("chapters/my plots.qmd")
+        );
+        assert_eq!(
+            document_in_project("file:///book/index.qmd", ".").as_deref(),
+            Some("index.qmd")
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Inlay hints for QMD documents.
+//!
+//! Two kinds of labels are shown inline in the editor:
+//!
+//! - **Inherited cell options**: after the opening fence of an executable
+//!   cell, the `execute` options it gets from the front matter or the
+//!   project configuration and doesn't set itself (`echo: false`)
+//! - **Cross-reference numbers**: after a cross-reference, the number it
+//!   renders as (`@fig-plot` → `Figure 3`)
+//!
+//! Targets are numbered per type in document order, as when rendering.
+//! Sections are only numbered with `number-sections`, and then
+//! hierarchically, so references to sections get no hint.
+
+use crate::bibliography::string_list;
+use crate::completions::{crossref_kind, front_matter};
+use crate::document::Document;
+use crate::embedded::code_cells;
+use crate::index::SymbolIndex;
+use crate::types::{InlayHint, InlayHintKind, Position, Range};
+use crate::workspace::{Workspace, join_path};
+use pampa::pandoc::cell_options::option_prefix;
+use yaml_rust2::Yaml;
+
+/// Get the inlay hints on the lines of a range.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, NoWorkspace, Position, Range, get_inlay_hints};
+///
+/// let doc = Document::new("test.qmd", "![Plot](plot.png){#fig-plot}\n\nSee @fig-plot.\n");
+/// let range = Range::new(Position::new(0, 0), Position::new(3, 0));
+/// let hints = get_inlay_hints(&doc, range, &NoWorkspace);
+/// assert_eq!(hints[0].label, "Figure 1");
+/// ```
+pub fn get_inlay_hints(doc: &Document, range: Range, workspace: &dyn Workspace) -> Vec<InlayHint> {
+    let lines: Vec<&str> = doc.content().lines().collect();
+    let mut hints = cell_option_hints(&lines, workspace);
+    hints.extend(crossref_hints(doc, &lines));
+    hints.retain(|hint| {
+        range.start.line <= hint.position.line && hint.position.line <= range.end.line
+    });
+    hints.sort_by_key(|hint| hint.position);
+    hints
+}
+
+/// The `execute` options of executable cells that come from the front
+/// matter or the project configuration.
+fn cell_option_hints(lines: &[&str], workspace: &dyn Workspace) -> Vec<InlayHint> {
+    let options = inherited_options(lines, workspace);
+    if options.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hints = Vec::new();
+    for cell in code_cells(lines) {
+        let prefix = option_prefix(cell.language);
+        let own: Vec<&str> = lines[cell.start..=cell.end]
+            .iter()
+            .map_while(|line| line.trim_start().strip_prefix(prefix))
+            .filter_map(|option| option.trim_start().split_once(':'))
+            .map(|(key, _)| key.trim_end())
+            .collect();
+
+        let fence = cell.start - 1;
+        let position = Position::new(fence as u32, lines[fence].chars().count() as u32);
+        for (key, value, source) in &options {
+            if own.contains(&key.as_str()) {
+                continue;
+            }
+            hints.push(
+                InlayHint::new(
+                    position,
+                    format!("{}: {}", key, value),
+                    InlayHintKind::CellOption,
+                )
+                .with_tooltip(format!("Inherited from {}", source)),
+            );
+        }
+    }
+    hints
+}
+
+/// The `execute` options of the project configuration and of the front
+/// matter, which overrides it, with where each comes from.
+fn inherited_options(lines: &[&str], workspace: &dyn Workspace) -> Vec<(String, String, String)> {
+    let mut options: Vec<(String, String, String)> = Vec::new();
+    let mut add = |execute: &Yaml, source: &str| {
+        let Some(entries) = execute.as_hash() else {
+            return;
+        };
+        for (key, value) in entries {
+            let (Some(key), Some(value)) = (key.as_str(), scalar_text(value)) else {
+                continue;
+            };
+            options.retain(|(other, _, _)| other != key);
+            options.push((key.to_string(), value, source.to_string()));
+        }
+    };
+
+    if let Some(project_dir) = workspace.project_dir()
+        && let Some((name, config)) = ["_quarto.yml", "_quarto.yaml"].iter().find_map(|name| {
+            let config = workspace.read_file(&join_path(&project_dir, name))?;
+            Some((name, config))
+        })
+        && let Ok(config) = quarto_yaml::parse(&config)
+    {
+        add(&config.yaml["execute"], &format!("`{}`", name));
+    }
+    if let Some(meta) = front_matter(lines) {
+        add(&meta.yaml["execute"], "the front matter");
+    }
+    options
+}
+
+/// The text of a YAML scalar or list, or `None` for mappings.
+fn scalar_text(value: &Yaml) -> Option<String> {
+    match value {
+        Yaml::Boolean(b) => Some(b.to_string()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Real(text) | Yaml::String(text) => Some(text.clone()),
+        Yaml::Array(_) => Some(string_list(value).join(", ")).filter(|text| !text.is_empty()),
+        _ => None,
+    }
+}
+
+/// The numbers of the cross-references of the document.
+fn crossref_hints(doc: &Document, lines: &[&str]) -> Vec<InlayHint> {
+    let mut index = SymbolIndex::new();
+    index.add_file(None, doc.content());
+
+    // Number the targets of each type in document order
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    let mut numbers: Vec<(&str, String)> = Vec::new();
+    for entry in index.all_definitions() {
+        let Some((prefix, _)) = entry.name.split_once('-') else {
+            continue;
+        };
+        let Some(kind) = crossref_kind(&entry.name) else {
+            continue;
+        };
+        if prefix == "sec" || numbers.iter().any(|(name, _)| *name == entry.name) {
+            continue;
+        }
+        let count = match counts.iter_mut().find(|(other, _)| *other == prefix) {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                counts.push((prefix, 1));
+                1
+            }
+        };
+        numbers.push((&entry.name, format!("{} {}", kind, count)));
+    }
+
+    numbers
+        .iter()
+        .flat_map(|(name, number)| {
+            index
+                .references(name)
+                .filter(|location| is_citation(lines, location.range))
+                .map(move |location| {
+                    InlayHint::new(location.range.end, number.clone(), InlayHintKind::CrossRef)
+                })
+        })
+        .collect()
+}
+
+/// Whether a reference is a citation (`@fig-plot`) rather than a link to
+/// an anchor (`[plot](#fig-plot)`).
+fn is_citation(lines: &[&str], range: Range) -> bool {
+    let Some(start) = (range.start.character as usize).checked_sub(1) else {
+        return false;
+    };
+    lines
+        .get(range.start.line as usize)
+        .and_then(|line| line.chars().nth(start))
+        == Some('@')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::{MemoryWorkspace, NoWorkspace};
+
+    fn hints(content: &str, workspace: &dyn Workspace) -> Vec<(u32, u32, String)> {
+        let doc = Document::new("test.qmd", content);
+        let range = Range::new(Position::new(0, 0), Position::new(u32::MAX, 0));
+        get_inlay_hints(&doc, range, workspace)
+            .into_iter()
+            .map(|hint| (hint.position.line, hint.position.character, hint.label))
+            .collect()
+    }
+
+    #[test]
+    fn crossref_numbers_per_type() {
+        let content = "![A](a.png){#fig-a}\n\n```{python}\n#| label: tbl-t\nx\n```\n\n![B](b.png){#fig-b}\n\nSee @fig-b, @tbl-t and [@fig-a; @fig-missing]. [A](#fig-a)\n";
+        assert_eq!(
+            hints(content, &NoWorkspace),
+            vec![
+                (9, 10, "Figure 2".to_string()),
+                (9, 18, "Table 1".to_string()),
+                (9, 30, "Figure 1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn sections_have_no_numbers() {
+        assert!(hints("# Intro {#sec-intro}\n\nSee @sec-intro.\n", &NoWorkspace).is_empty());
+    }
+
+    #[test]
+    fn inherited_cell_options() {
+        let workspace = MemoryWorkspace::default()
+            .with_project_dir(".")
+            .with_file("_quarto.yml", "execute:\n  echo: false\n  warning: false\n");
+        let content = "---\nexecute:\n  warning: true\n---\n\n```{python}\n#| echo: true\nx = 1\n```\n\n```{r}\ny <- 2\n```\n";
+        assert_eq!(
+            hints(content, &workspace),
+            vec![
+                (5, 11, "warning: true".to_string()),
+                (10, 6, "echo: false".to_string()),
+                (10, 6, "warning: true".to_string()),
+            ]
+        );
+
+        let doc = Document::new("test.qmd", content);
+        let range = Range::new(Position::new(10, 0), Position::new(10, 0));
+        let tooltips: Vec<Option<String>> = get_inlay_hints(&doc, range, &workspace)
+            .into_iter()
+            .map(|hint| hint.tooltip)
+            .collect();
+        assert_eq!(
+            tooltips,
+            vec![
+                Some("Inherited from `_quarto.yml`".to_string()),
+                Some("Inherited from the front matter".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_cell_hints_without_inherited_options() {
+        assert!(hints("```{python}\nx = 1\n```\n", &NoWorkspace).is_empty());
+    }
+
+    #[test]
+    fn hints_in_range() {
+        let doc = Document::new("test.qmd", "![A](a.png){#fig-a}\n\n@fig-a\n\n@fig-a\n");
+        let range = Range::new(Position::new(3, 0), Position::new(4, 0));
+        let hints = get_inlay_hints(&doc, range, &NoWorkspace);
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].position, Position::new(4, 6));
+    }
+}
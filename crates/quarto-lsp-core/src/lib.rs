@@ -37,20 +37,116 @@
 //! let symbols = get_symbols(&doc);
 //! let diagnostics = get_diagnostics(&doc);
 //! let folding_ranges = get_folding_ranges(&doc);
+//!
+//! // Completions at a position, with files read through a `Workspace`:
+//! let completions = get_completions(&doc, Position::new(1, 4), &NoWorkspace);
+//!
+//! // The cross-reference labels and citation keys to complete after `@`,
+//! // anywhere in the document:
+//! let references = get_reference_completions(&doc, &NoWorkspace);
+//!
+//! // Hover information (markdown) at a position:
+//! let hover = get_hover(&doc, Position::new(1, 1), &NoWorkspace);
+//!
+//! // Definitions and references of labels, citations and includes:
+//! let definitions = get_definition(&doc, Position::new(4, 6), &NoWorkspace);
+//! let references = get_references(&doc, Position::new(4, 6), &NoWorkspace, true);
+//!
+//! // Edits renaming a label or citation key across the project:
+//! let edits = get_rename_edits(&doc, Position::new(4, 6), "fig-new", &NoWorkspace, true)?;
+//!
+//! // Minimal edits formatting the document with the qmd writer:
+//! let edits = format_document(&doc, &FormattingOptions::default());
+//!
+//! // Broken links, missing includes, duplicate labels and an invalid
+//! // `_quarto.yml` across the project:
+//! let files = get_project_diagnostics(&workspace);
+//!
+//! // Quick fixes for the diagnostics in a range:
+//! let actions = get_code_actions(&doc, Range::point(Position::new(4, 6)));
+//!
+//! // Inherited cell options and cross-reference numbers shown inline:
+//! let hints = get_inlay_hints(&doc, range, &NoWorkspace);
+//!
+//! // The code of executable cells as documents of their own, for other
+//! // language servers:
+//! let documents = get_embedded_documents(&doc);
+//! let at = get_embedded_document_at(&doc, Position::new(8, 2));
+//!
+//! // Ranges the selection expands through, from the word at a position to
+//! // its section, and links to URLs, images, includes and bibliographies:
+//! let selections = get_selection_ranges(&doc, &[Position::new(4, 6)]);
+//! let links = get_document_links(&doc);
+//!
+//! // Highlighting of shortcodes, cell options, crossrefs, attributes and math:
+//! let tokens = get_semantic_tokens(&doc);
+//!
+//! // Misspelled words of the prose, checked with a Hunspell dictionary:
+//! if let Some(config) = get_spelling_config(&doc, &workspace) {
+//!     let dictionary = Dictionary::new(&aff, &dic);
+//!     let diagnostics = get_spelling_diagnostics(&doc, &dictionary, &config);
+//! }
+//!
+//! // Long sentences, repeated words, images without alt text, skipped
+//! // heading levels and TODO markers, as configured by the `lint` option:
+//! let config = get_prose_lint_config(&doc, &workspace);
+//! let diagnostics = get_prose_lint_diagnostics(&doc, &config);
 //! ```
 
 pub mod analysis;
+mod bibliography;
+pub mod code_actions;
+pub mod completions;
 pub mod diagnostics;
+pub mod dictionary;
 pub mod document;
+pub mod document_links;
+pub mod embedded;
+pub mod formatting;
+pub mod hover;
+mod incremental;
+pub mod index;
+pub mod inlay_hints;
+pub mod navigation;
+pub mod project;
+mod prose;
+pub mod prose_lint;
+pub mod rename;
+pub mod selection_ranges;
+pub mod semantic_tokens;
+pub mod spelling;
 pub mod symbols;
 pub mod types;
+pub mod workspace;
 
 // Re-export main types and functions for convenience
 pub use analysis::analyze_document;
+pub use code_actions::{get_code_actions, get_fix_actions};
+pub use completions::{get_completions, get_reference_completions};
 pub use diagnostics::get_diagnostics;
+pub use dictionary::Dictionary;
 pub use document::Document;
+pub use document_links::get_document_links;
+pub use embedded::{get_embedded_document_at, get_embedded_documents};
+pub use formatting::{format_document, format_range};
+pub use hover::get_hover;
+pub use index::SymbolIndex;
+pub use inlay_hints::get_inlay_hints;
+pub use navigation::{get_definition, get_references};
+pub use project::{FileDiagnostics, get_project_diagnostics};
+pub use prose_lint::{
+    LintRule, ProseLintConfig, get_prose_lint_config, get_prose_lint_diagnostics,
+    get_prose_lint_messages,
+};
+pub use rename::{RenameError, get_rename_edits, prepare_rename};
+pub use selection_ranges::get_selection_ranges;
+pub use semantic_tokens::{get_semantic_tokens, get_semantic_tokens_in_range};
+pub use spelling::{SpellingConfig, WordsFile, get_spelling_config, get_spelling_diagnostics};
 pub use symbols::{get_folding_ranges, get_symbols};
 pub use types::{
-    Diagnostic, DiagnosticSeverity, DocumentAnalysis, DocumentAnalysisJson, FoldingRange,
-    FoldingRangeKind, Position, Range, Symbol, SymbolKind,
+    CodeAction, CompletionItem, CompletionItemKind, Diagnostic, DiagnosticFix, DiagnosticSeverity,
+    DocumentAnalysis, DocumentAnalysisJson, DocumentLink, DocumentLinkKind, EmbeddedDocument,
+    EmbeddedPosition, FoldingRange, FoldingRangeKind, FormattingOptions, Hover, Location, Position,
+    Range, SelectionRange, SemanticToken, SemanticTokenKind, Symbol, SymbolKind, TextEdit,
 };
+pub use workspace::{NoWorkspace, Workspace, WorkspaceEntry};
@@ -0,0 +1,251 @@
+//! Go-to-definition and find-references for QMD documents.
+//!
+//! Definitions are found for:
+//!
+//! - **Cross-references** (`@fig-plot`, `[text](#sec-intro)`): the labeled
+//!   heading, figure or code cell, in the document or elsewhere in its
+//!   project (see [`SymbolIndex`])
+//! - **Citations** (`@knuth1984`): the entry in the BibTeX or CSL
+//!   bibliography, or in the front matter's `references`
+//! - **Includes** (`{{< include _intro.qmd >}}`) and links to files: the
+//!   included or linked file
+//!
+//! References are the uses of a label or citation key across the project,
+//! found from either a use or the definition.
+
+use crate::bibliography::{bibliography_entries, entry_location};
+use crate::completions::line_prefix;
+use crate::document::Document;
+use crate::hover::link_target_at;
+use crate::index::SymbolIndex;
+use crate::types::{Location, Position, Range};
+use crate::workspace::Workspace;
+
+/// Get the definitions of the element at a position in a document.
+///
+/// Returns an empty list when there is nothing to go to.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, NoWorkspace, Position, get_definition};
+///
+/// let doc = Document::new("test.qmd", "# Intro {#sec-intro}\n\nSee @sec-intro.\n");
+/// let definitions = get_definition(&doc, Position::new(2, 6), &NoWorkspace);
+/// assert_eq!(definitions[0].range.start, Position::new(0, 10));
+/// ```
+pub fn get_definition(
+    doc: &Document,
+    position: Position,
+    workspace: &dyn Workspace,
+) -> Vec<Location> {
+    let lines: Vec<&str> = doc.content().lines().collect();
+    let Some(line) = lines.get(position.line as usize) else {
+        return Vec::new();
+    };
+    let cursor = line_prefix(line, position.character).len();
+
+    let file = include_at(line, cursor).or_else(|| {
+        link_target_at(line, cursor)
+            .map(|(_, _, target)| target)
+            .filter(|target| !target.starts_with('#') && !target.contains(':'))
+    });
+    if let Some(file) = file {
+        let path = file.split(['#', '?']).next().unwrap_or(file);
+        return match workspace.read_file(path) {
+            Some(_) => vec![Location::in_file(path, Range::default())],
+            None => Vec::new(),
+        };
+    }
+
+    let index = SymbolIndex::for_document(doc, workspace);
+    let Some(name) = index.name_at(position) else {
+        return Vec::new();
+    };
+    let definitions: Vec<Location> = index.definitions(name).cloned().collect();
+    if !definitions.is_empty() {
+        return definitions;
+    }
+    citation_definition(doc, &lines, name, workspace)
+        .into_iter()
+        .collect()
+}
+
+/// Get the uses of the label or citation key at a position in a document,
+/// across its project. With `include_declaration`, the definitions come
+/// first.
+pub fn get_references(
+    doc: &Document,
+    position: Position,
+    workspace: &dyn Workspace,
+    include_declaration: bool,
+) -> Vec<Location> {
+    let index = SymbolIndex::for_document(doc, workspace);
+    let Some(name) = index.name_at(position) else {
+        return Vec::new();
+    };
+    let mut locations = Vec::new();
+    if include_declaration {
+        locations.extend(index.definitions(name).cloned());
+        if locations.is_empty() {
+            let lines: Vec<&str> = doc.content().lines().collect();
+            locations.extend(citation_definition(doc, &lines, name, workspace));
+        }
+    }
+    locations.extend(index.references(name).cloned());
+    locations
+}
+
+/// The bibliography entry of a citation key.
+pub(crate) fn citation_definition(
+    doc: &Document,
+    lines: &[&str],
+    key: &str,
+    workspace: &dyn Workspace,
+) -> Option<Location> {
+    let entry = bibliography_entries(lines, workspace)
+        .into_iter()
+        .find(|entry| entry.key == key)?;
+    entry_location(&entry, doc.content(), workspace)
+}
+
+/// The path of the `{{< include path >}}` shortcode under the cursor.
+fn include_at(line: &str, cursor: usize) -> Option<&str> {
+    line.match_indices("{{<").find_map(|(open, _)| {
+        let inner = &line[open + 3..];
+        let close = inner.find(">}}");
+        let end = close.map_or(line.len(), |close| open + 3 + close + 3);
+        if !(open..=end).contains(&cursor) {
+            return None;
+        }
+        let inner = &inner[..close.unwrap_or(inner.len())];
+        let path = inner.trim().strip_prefix("include ")?.trim();
+        let path = path.trim_matches(['"', '\'']);
+        (!path.is_empty()).then_some(path)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::{MemoryWorkspace, NoWorkspace};
+
+    /// The document and cursor position for `content`, with the cursor at
+    /// the last `|`.
+    fn at_cursor(content: &str) -> (Document, Position) {
+        let offset = content.rfind('|').expect("cursor marker");
+        let before = &content[..offset];
+        let line = before.matches('\n').count() as u32;
+        let character = before.rsplit('\n').next().unwrap().chars().count() as u32;
+        let doc = Document::new(
+            "file:///book/chapters/doc.qmd",
+            format!("{}{}", before, &content[offset + 1..]),
+        );
+        (doc, Position::new(line, character))
+    }
+
+    fn starts(locations: &[Location]) -> Vec<(Option<&str>, u32, u32)> {
+        locations
+            .iter()
+            .map(|location| {
+                (
+                    location.path.as_deref(),
+                    location.range.start.line,
+                    location.range.start.character,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn crossref_definition_in_document() {
+        let (doc, position) =
+            at_cursor("```{r}\n#| label: fig-plot\nplot(1)\n```\n\nSee @fig-pl|ot.\n");
+        let definitions = get_definition(&doc, position, &NoWorkspace);
+        assert_eq!(starts(&definitions), vec![(None, 1, 10)]);
+    }
+
+    #[test]
+    fn crossref_definition_in_project() {
+        let workspace = MemoryWorkspace::default()
+            .with_project_dir("..")
+            .with_file("../_quarto.yml", "project:\n  type: book\n")
+            .with_file("../intro.qmd", "# Introduction {#sec-intro}\n");
+        let (doc, position) = at_cursor("As in @sec-intro|, ...\n");
+        let definitions = get_definition(&doc, position, &workspace);
+        assert_eq!(starts(&definitions), vec![(Some("../intro.qmd"), 0, 17)]);
+    }
+
+    #[test]
+    fn citation_definitions() {
+        let workspace = MemoryWorkspace::default()
+            .with_file(
+                "refs.bib",
+                "@comment{x}\n\n@article{knuth1984,\n  title = {Literate Programming}\n}\n",
+            )
+            .with_file(
+                "refs.json",
+                "[\n  {\n    \"id\": \"xie2015\",\n    \"title\": \"Dynamic Documents\"\n  }\n]\n",
+            );
+        let content = "---\nbibliography: [refs.bib, refs.json]\nreferences:\n  - id: inline2020\n---\n\n[@knuth1984; @xie2015; @inline2020|]\n";
+        let (doc, position) = at_cursor(content);
+        assert_eq!(
+            starts(&get_definition(&doc, position, &workspace)),
+            vec![(None, 3, 8)]
+        );
+        let position = Position::new(6, 4);
+        assert_eq!(
+            starts(&get_definition(&doc, position, &workspace)),
+            vec![(Some("refs.bib"), 2, 9)]
+        );
+        let position = Position::new(6, 16);
+        assert_eq!(
+            starts(&get_definition(&doc, position, &workspace)),
+            vec![(Some("refs.json"), 2, 11)]
+        );
+    }
+
+    #[test]
+    fn include_and_link_definitions() {
+        let workspace = MemoryWorkspace::default().with_file("_intro.qmd", "Intro\n");
+        let (doc, position) = at_cursor("{{< include _in|tro.qmd >}}\n");
+        assert_eq!(
+            starts(&get_definition(&doc, position, &workspace)),
+            vec![(Some("_intro.qmd"), 0, 0)]
+        );
+
+        let (doc, position) = at_cursor("[Intro](_intro.qmd#start|)\n");
+        assert_eq!(
+            starts(&get_definition(&doc, position, &workspace)),
+            vec![(Some("_intro.qmd"), 0, 0)]
+        );
+
+        let (doc, position) = at_cursor("{{< include miss|ing.qmd >}}\n");
+        assert!(get_definition(&doc, position, &workspace).is_empty());
+    }
+
+    #[test]
+    fn references_across_project() {
+        let workspace = MemoryWorkspace::default()
+            .with_project_dir("..")
+            .with_file("../_quarto.yml", "project:\n  type: book\n")
+            .with_file(
+                "../index.qmd",
+                "See @fig-plot and [the plot](chapters/doc.qmd).\n",
+            )
+            .with_file("../chapters/doc.qmd", "stale {#fig-plot}\n");
+        let (doc, position) = at_cursor("![Plot](plot.png){#fig-pl|ot}\n\nAs @fig-plot shows.\n");
+
+        let references = get_references(&doc, position, &workspace, false);
+        assert_eq!(
+            starts(&references),
+            vec![(None, 2, 4), (Some("../index.qmd"), 0, 5)]
+        );
+
+        let references = get_references(&doc, Position::new(2, 6), &workspace, true);
+        assert_eq!(
+            starts(&references),
+            vec![(None, 0, 19), (None, 2, 4), (Some("../index.qmd"), 0, 5)]
+        );
+    }
+}
@@ -0,0 +1,525 @@
+//! Diagnostics across a project.
+//!
+//! Some problems only show when the documents of a project are looked at
+//! together, rather than one at a time:
+//!
+//! - **Broken internal links**: links to documents that don't exist
+//!   (`[Intro](intro.qmd)`), and to cross-reference anchors that aren't
+//!   defined (`[Intro](#sec-intro)`, `[Intro](intro.qmd#sec-intro)`)
+//! - **Missing included files** (`{{< include _intro.qmd >}}`)
+//! - **Duplicate cross-reference labels**, defined in more than one place
+//! - **Invalid project configuration**: a `_quarto.yml` that isn't valid
+//!   YAML, or doesn't match the Quarto schema
+//!
+//! Files are read through the [`Workspace`], so editors can have the
+//! unsaved content of open documents analyzed.
+
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_source_map::{SourceContext, SourceInfo};
+
+use crate::completions::{crossref_kind, front_matter_end};
+use crate::diagnostics::convert_diagnostic;
+use crate::index::{SymbolIndex, closes_fence, project_files};
+use crate::types::{Diagnostic, DiagnosticSeverity, Location, Position, Range};
+use crate::workspace::{Workspace, join_path};
+
+/// The diagnostics of a file of a project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDiagnostics {
+    /// The file, relative to the workspace directory.
+    pub path: String,
+    /// The problems found in the file.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Get the diagnostics of the files of the workspace's project.
+///
+/// There is an entry for the project configuration and for every `.qmd`
+/// and `.md` file of the project, including the files without problems, so
+/// that diagnostics published for them earlier can be cleared. Outside a
+/// project there are no entries.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::get_project_diagnostics;
+///
+/// for file in get_project_diagnostics(&workspace) {
+///     println!("{}: {} problems", file.path, file.diagnostics.len());
+/// }
+/// ```
+pub fn get_project_diagnostics(workspace: &dyn Workspace) -> Vec<FileDiagnostics> {
+    let Some(project_dir) = workspace.project_dir() else {
+        return Vec::new();
+    };
+
+    let mut results: Vec<FileDiagnostics> = config_diagnostics(workspace, &project_dir)
+        .into_iter()
+        .collect();
+
+    let files: Vec<(String, String)> = project_files(workspace, &project_dir, "")
+        .into_iter()
+        .filter_map(|file| {
+            let path = join_path(&project_dir, &file);
+            let content = workspace.read_file(&path)?;
+            Some((path, content))
+        })
+        .collect();
+    let mut index = SymbolIndex::new();
+    for (path, content) in &files {
+        index.add_file(Some(path), content);
+    }
+
+    let duplicates = duplicate_labels(&index);
+    for (path, content) in &files {
+        let mut diagnostics = file_diagnostics(workspace, &index, &project_dir, path, content);
+        diagnostics.extend(
+            duplicates
+                .iter()
+                .filter(|(file, _)| file == path)
+                .map(|(_, diagnostic)| diagnostic.clone()),
+        );
+        diagnostics.sort_by_key(|diagnostic| diagnostic.range.start);
+        results.push(FileDiagnostics {
+            path: path.clone(),
+            diagnostics,
+        });
+    }
+    results
+}
+
+/// The problems of the project configuration: YAML syntax errors, and the
+/// options the Quarto schema doesn't accept.
+fn config_diagnostics(workspace: &dyn Workspace, project_dir: &str) -> Option<FileDiagnostics> {
+    let (path, content) = ["_quarto.yml", "_quarto.yaml"].iter().find_map(|name| {
+        let path = join_path(project_dir, name);
+        let content = workspace.read_file(&path)?;
+        Some((path, content))
+    })?;
+
+    let mut source_context = SourceContext::new();
+    let file_id = source_context.add_file(path.clone(), Some(content.clone()));
+    let parent = SourceInfo::original(file_id, 0, content.len());
+    let mut messages = Vec::new();
+    match quarto_config::config_value_from_source(&content, parent, &mut messages) {
+        Ok(config) => messages.extend(quarto_config::validate_config(&config, &source_context)),
+        Err(error) => messages.push(yaml_error_message(&error)),
+    }
+
+    let diagnostics = messages
+        .iter()
+        .filter_map(|msg| convert_diagnostic(msg, &source_context))
+        .collect();
+    Some(FileDiagnostics { path, diagnostics })
+}
+
+/// The diagnostic of a YAML syntax error.
+fn yaml_error_message(error: &quarto_yaml::Error) -> DiagnosticMessage {
+    let (problem, location) = match error {
+        quarto_yaml::Error::ParseError { message, location }
+        | quarto_yaml::Error::InvalidStructure { message, location } => (message.clone(), location),
+        quarto_yaml::Error::UnexpectedEof { location } => {
+            ("Unexpected end of file".to_string(), location)
+        }
+    };
+    let mut builder = DiagnosticMessageBuilder::error("YAML Syntax Error")
+        .with_code("Q-1-1")
+        .problem(problem);
+    if let Some(location) = location {
+        builder = builder.with_location(location.clone());
+    }
+    builder.build()
+}
+
+/// The broken links and missing includes of a document. Front matter and
+/// code blocks are skipped.
+fn file_diagnostics(
+    workspace: &dyn Workspace,
+    index: &SymbolIndex,
+    project_dir: &str,
+    path: &str,
+    content: &str,
+) -> Vec<Diagnostic> {
+    let dir = path.rsplit_once('/').map_or("", |(dir, _)| dir);
+    let resolve = |target: &str| match target.strip_prefix('/') {
+        Some(target) => normalize(&join_path(project_dir, target)),
+        None => normalize(&join_path(dir, target)),
+    };
+
+    let mut diagnostics = Vec::new();
+    let lines: Vec<&str> = content.lines().collect();
+    let body_start = front_matter_end(&lines).map_or(0, |end| end + 1);
+    let mut fence: Option<&str> = None;
+    for (row, line) in lines.iter().enumerate().skip(body_start) {
+        let text = line.trim_start();
+        if let Some(open) = fence {
+            if closes_fence(open, text) {
+                fence = None;
+            }
+            continue;
+        }
+        if text.starts_with("```") {
+            fence = Some(text);
+            continue;
+        }
+
+        let range = |start: usize, end: usize| {
+            let column = |offset: usize| line[..offset].chars().count() as u32;
+            Range::new(
+                Position::new(row as u32, column(start)),
+                Position::new(row as u32, column(end)),
+            )
+        };
+
+        for (start, target) in links(line) {
+            let (file, anchor) = match target.split_once('#') {
+                Some((file, anchor)) => (file, Some(anchor)),
+                None => (target, None),
+            };
+            let problem = if file.is_empty() {
+                // Anchors of sections and figures of other chapters resolve
+                // in books, so any document of the project may define them
+                anchor
+                    .filter(|anchor| crossref_kind(anchor).is_some())
+                    .filter(|anchor| index.definitions(anchor).next().is_none())
+                    .map(|anchor| format!("`#{}` isn't defined in the project", anchor))
+            } else if !is_document(file) {
+                None
+            } else {
+                let file_path = resolve(file);
+                if workspace.read_file(&file_path).is_none() {
+                    Some(format!("`{}` doesn't exist", file))
+                } else {
+                    anchor
+                        .filter(|anchor| crossref_kind(anchor).is_some())
+                        .filter(|anchor| {
+                            !index.definitions(anchor).any(|location| {
+                                location.path.as_deref().map(normalize).as_deref()
+                                    == Some(file_path.as_str())
+                            })
+                        })
+                        .map(|anchor| format!("`#{}` isn't defined in `{}`", anchor, file))
+                }
+            };
+            if let Some(problem) = problem {
+                diagnostics.push(
+                    Diagnostic::new(
+                        range(start, start + target.len()),
+                        DiagnosticSeverity::Warning,
+                        "Broken Internal Link",
+                    )
+                    .with_code("Q-2-39")
+                    .with_problem(problem),
+                );
+            }
+        }
+
+        for (start, include) in includes(line) {
+            if workspace.read_file(&resolve(include)).is_none() {
+                diagnostics.push(
+                    Diagnostic::new(
+                        range(start, start + include.len()),
+                        DiagnosticSeverity::Warning,
+                        "Missing Included File",
+                    )
+                    .with_code("Q-2-40")
+                    .with_problem(format!("`{}` doesn't exist", include)),
+                );
+            }
+        }
+    }
+    diagnostics
+}
+
+/// A diagnostic for every definition of a cross-reference label that is
+/// defined more than once, with the file of the definition.
+fn duplicate_labels(index: &SymbolIndex) -> Vec<(String, Diagnostic)> {
+    let definitions = index.all_definitions();
+    let mut duplicates = Vec::new();
+    for entry in definitions {
+        if crossref_kind(&entry.name).is_none() {
+            continue;
+        }
+        let others: Vec<String> = definitions
+            .iter()
+            .filter(|other| other.name == entry.name && other.location != entry.location)
+            .map(|other| describe(&other.location))
+            .collect();
+        if others.is_empty() {
+            continue;
+        }
+        let diagnostic = Diagnostic::new(
+            entry.location.range,
+            DiagnosticSeverity::Warning,
+            "Duplicate Cross-reference Label",
+        )
+        .with_code("Q-2-41")
+        .with_problem(format!(
+            "`{}` is also defined at {}",
+            entry.name,
+            others.join(", ")
+        ))
+        .with_hint("Give each figure, table, section or equation its own label");
+        let path = entry.location.path.clone().unwrap_or_default();
+        duplicates.push((path, diagnostic));
+    }
+    duplicates
+}
+
+/// A location as `path:line`, with a 1-based line.
+fn describe(location: &Location) -> String {
+    format!(
+        "{}:{}",
+        location.path.as_deref().unwrap_or_default(),
+        location.range.start.line + 1
+    )
+}
+
+/// Whether a link target names a document, whose existence is checked.
+/// Links to other files (images, data, rendered pages) aren't checked.
+fn is_document(target: &str) -> bool {
+    let path = target.split('?').next().unwrap_or(target);
+    [".qmd", ".md", ".ipynb"]
+        .iter()
+        .any(|extension| path.ends_with(extension))
+}
+
+/// The byte offsets and targets of the links of a line, other than URLs
+/// (`[text](target)`, `![alt](target)`).
+fn links(line: &str) -> Vec<(usize, &str)> {
+    line.match_indices("](")
+        .filter_map(|(open, _)| {
+            let start = open + 2;
+            let end = start + line[start..].find([')', ' '])?;
+            let target = &line[start..end];
+            (!target.is_empty() && !target.contains(':')).then_some((start, target))
+        })
+        .collect()
+}
+
+/// The byte offsets and paths of the `{{< include path >}}` shortcodes of a
+/// line.
+fn includes(line: &str) -> Vec<(usize, &str)> {
+    line.match_indices("{{<")
+        .filter_map(|(open, _)| {
+            let inner_start = open + 3;
+            let inner_end = inner_start + line[inner_start..].find(">}}")?;
+            let inner = &line[inner_start..inner_end];
+            let path = inner.trim().strip_prefix("include ")?.trim();
+            let path = path.trim_matches(['"', '\'']);
+            let start = inner_start + inner.find(path)?;
+            (!path.is_empty()).then_some((start, path))
+        })
+        .collect()
+}
+
+/// Remove the `.` and `dir/..` segments of a path.
+fn normalize(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." if segments.last().is_some_and(|last| *last != "..") => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::MemoryWorkspace;
+
+    /// The code, line and problem of the diagnostics of each file with
+    /// problems.
+    fn problems(workspace: &MemoryWorkspace) -> Vec<(String, Vec<(String, u32, String)>)> {
+        get_project_diagnostics(workspace)
+            .into_iter()
+            .filter(|file| !file.diagnostics.is_empty())
+            .map(|file| {
+                let diagnostics = file
+                    .diagnostics
+                    .iter()
+                    .map(|diagnostic| {
+                        (
+                            diagnostic.code.clone().unwrap_or_default(),
+                            diagnostic.range.start.line,
+                            diagnostic
+                                .problem
+                                .as_ref()
+                                .map(|problem| problem.as_str().to_string())
+                                .unwrap_or_default(),
+                        )
+                    })
+                    .collect();
+                (file.path, diagnostics)
+            })
+            .collect()
+    }
+
+    fn project() -> MemoryWorkspace {
+        MemoryWorkspace::default()
+            .with_project_dir(".")
+            .with_file("_quarto.yml", "project:\n  type: book\n")
+    }
+
+    #[test]
+    fn valid_project_has_no_problems() {
+        let workspace = project()
+            .with_file("index.qmd", "See [intro](chapters/intro.qmd#sec-intro).\n")
+            .with_file(
+                "chapters/intro.qmd",
+                "# Intro {#sec-intro}\n\n{{< include ../_shared.qmd >}}\n\n[Home](/index.qmd)\n",
+            )
+            .with_file("_shared.qmd", "Shared text.\n");
+        assert!(problems(&workspace).is_empty());
+
+        let paths: Vec<String> = get_project_diagnostics(&workspace)
+            .into_iter()
+            .map(|file| file.path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "_quarto.yml".to_string(),
+                "_shared.qmd".to_string(),
+                "chapters/intro.qmd".to_string(),
+                "index.qmd".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn broken_links() {
+        let workspace = project()
+            .with_file(
+                "index.qmd",
+                "[a](missing.qmd) [b](other.qmd#sec-nope) [c](#fig-nope)\n\n[d](#intro) [e](https://quarto.org) ![f](plot.png)\n",
+            )
+            .with_file("other.qmd", "# Other {#sec-other}\n");
+        assert_eq!(
+            problems(&workspace),
+            vec![(
+                "index.qmd".to_string(),
+                vec![
+                    (
+                        "Q-2-39".to_string(),
+                        0,
+                        "`missing.qmd` doesn't exist".to_string()
+                    ),
+                    (
+                        "Q-2-39".to_string(),
+                        0,
+                        "`#sec-nope` isn't defined in `other.qmd`".to_string()
+                    ),
+                    (
+                        "Q-2-39".to_string(),
+                        0,
+                        "`#fig-nope` isn't defined in the project".to_string()
+                    ),
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn missing_includes() {
+        let workspace = project().with_file(
+            "index.qmd",
+            "Text\n\n{{< include _missing.qmd >}}\n\n```\n{{< include _in-code.qmd >}}\n```\n",
+        );
+        let diagnostics = &get_project_diagnostics(&workspace)[1].diagnostics;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("Q-2-40"));
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(2, 12), Position::new(2, 24))
+        );
+    }
+
+    #[test]
+    fn duplicate_labels_across_files() {
+        let workspace = project()
+            .with_file("a.qmd", "![Plot](a.png){#fig-plot}\n")
+            .with_file("b.qmd", "```{python}\n#| label: fig-plot\nplot()\n```\n")
+            .with_file("c.qmd", "# Intro {#intro}\n\n# Intro {#intro}\n");
+        assert_eq!(
+            problems(&workspace),
+            vec![
+                (
+                    "a.qmd".to_string(),
+                    vec![(
+                        "Q-2-41".to_string(),
+                        0,
+                        "`fig-plot` is also defined at b.qmd:2".to_string()
+                    )]
+                ),
+                (
+                    "b.qmd".to_string(),
+                    vec![(
+                        "Q-2-41".to_string(),
+                        1,
+                        "`fig-plot` is also defined at a.qmd:1".to_string()
+                    )]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_config() {
+        let workspace = MemoryWorkspace::default().with_project_dir(".").with_file(
+            "_quarto.yml",
+            "project:\n  type: website\n  outputdir: _site\n",
+        );
+        let results = get_project_diagnostics(&workspace);
+        assert_eq!(results[0].path, "_quarto.yml");
+        assert_eq!(results[0].diagnostics.len(), 1);
+        assert_eq!(results[0].diagnostics[0].range.start.line, 2);
+
+        let workspace = MemoryWorkspace::default()
+            .with_project_dir(".")
+            .with_file("_quarto.yml", "project:\n  type: [website\n");
+        let results = get_project_diagnostics(&workspace);
+        assert_eq!(results[0].diagnostics[0].code.as_deref(), Some("Q-1-1"));
+        assert_eq!(
+            results[0].diagnostics[0].severity,
+            DiagnosticSeverity::Error
+        );
+    }
+
+    #[test]
+    fn paths_are_relative_to_the_workspace_directory() {
+        let workspace = MemoryWorkspace::default()
+            .with_project_dir("..")
+            .with_file("../_quarto.yml", "project:\n  type: book\n")
+            .with_file("../chapters/one.qmd", "[Two](../two.qmd)\n");
+        assert_eq!(
+            problems(&workspace),
+            vec![(
+                "../chapters/one.qmd".to_string(),
+                vec![(
+                    "Q-2-39".to_string(),
+                    0,
+                    "`../two.qmd` doesn't exist".to_string()
+                )]
+            )]
+        );
+    }
+
+    #[test]
+    fn no_diagnostics_outside_a_project() {
+        let workspace = MemoryWorkspace::default().with_file("index.qmd", "[a](missing.qmd)\n");
+        assert!(get_project_diagnostics(&workspace).is_empty());
+    }
+
+    #[test]
+    fn normalizes_paths() {
+        assert_eq!(normalize("chapters/../index.qmd"), "index.qmd");
+        assert_eq!(normalize("../../a/./b.qmd"), "../../a/b.qmd");
+        assert_eq!(normalize("a/../../b.qmd"), "../b.qmd");
+    }
+}
@@ -0,0 +1,315 @@
+//! The prose of a document.
+//!
+//! Spell checking and the prose lint look at the text a reader reads:
+//! paragraphs, headings, lists, tables, captions, image descriptions and
+//! notes. Code, math, raw content, citations, shortcodes and comments
+//! aren't prose, and neither are URLs and email addresses.
+
+use std::collections::HashSet;
+use std::ops::Range as ByteRange;
+
+use pampa::pandoc::{Block, Inline, Pandoc};
+use quarto_source_map::SourceInfo;
+
+use crate::analysis::inlines_to_text;
+
+/// The text of each block of prose: the byte ranges of its strings, in
+/// document order.
+pub(crate) fn prose_blocks(pandoc: &Pandoc) -> Vec<Vec<ByteRange<usize>>> {
+    let mut blocks = Vec::new();
+    collect_blocks(&pandoc.blocks, &mut blocks);
+
+    // Figures have their image's description as caption, and notes are
+    // collected before the block they're in
+    let mut seen = HashSet::new();
+    for block in &mut blocks {
+        block.retain(|range| seen.insert(range.start));
+    }
+    blocks.retain(|block| !block.is_empty());
+    blocks.sort_by_key(|block| block[0].start);
+    blocks
+}
+
+fn collect_blocks(blocks: &[Block], prose: &mut Vec<Vec<ByteRange<usize>>>) {
+    for block in blocks {
+        collect_block(block, prose);
+    }
+}
+
+fn collect_block(block: &Block, prose: &mut Vec<Vec<ByteRange<usize>>>) {
+    let inlines = |content: &[Inline], prose: &mut Vec<Vec<ByteRange<usize>>>| {
+        let mut strs = Vec::new();
+        collect_inlines(content, &mut strs, prose);
+        prose.push(strs);
+    };
+    match block {
+        Block::Plain(plain) => inlines(&plain.content, prose),
+        Block::Paragraph(para) => inlines(&para.content, prose),
+        Block::Header(header) => inlines(&header.content, prose),
+        Block::CaptionBlock(caption) => inlines(&caption.content, prose),
+        Block::NoteDefinitionPara(note) => inlines(&note.content, prose),
+        Block::LineBlock(line_block) => {
+            for line in &line_block.content {
+                inlines(line, prose);
+            }
+        }
+        Block::BlockQuote(quote) => collect_blocks(&quote.content, prose),
+        Block::Div(div) => collect_blocks(&div.content, prose),
+        Block::NoteDefinitionFencedBlock(note) => collect_blocks(&note.content, prose),
+        Block::OrderedList(list) => {
+            for item in &list.content {
+                collect_blocks(item, prose);
+            }
+        }
+        Block::BulletList(list) => {
+            for item in &list.content {
+                collect_blocks(item, prose);
+            }
+        }
+        Block::DefinitionList(list) => {
+            for (term, definitions) in &list.content {
+                inlines(term, prose);
+                for definition in definitions {
+                    collect_blocks(definition, prose);
+                }
+            }
+        }
+        Block::Figure(figure) => {
+            collect_blocks(figure.caption.long.as_deref().unwrap_or_default(), prose);
+            collect_blocks(&figure.content, prose);
+        }
+        Block::Table(table) => {
+            collect_blocks(table.caption.long.as_deref().unwrap_or_default(), prose);
+            let rows = table
+                .head
+                .rows
+                .iter()
+                .chain(
+                    table
+                        .bodies
+                        .iter()
+                        .flat_map(|body| body.head.iter().chain(&body.body)),
+                )
+                .chain(&table.foot.rows);
+            for cell in rows.flat_map(|row| &row.cells) {
+                collect_blocks(&cell.content, prose);
+            }
+        }
+        Block::Custom(custom) => {
+            for slot in custom.slots.values() {
+                match slot {
+                    pampa::pandoc::custom::Slot::Block(block) => collect_block(block, prose),
+                    pampa::pandoc::custom::Slot::Blocks(blocks) => collect_blocks(blocks, prose),
+                    pampa::pandoc::custom::Slot::Inline(inline) => {
+                        inlines(std::slice::from_ref(&**inline), prose)
+                    }
+                    pampa::pandoc::custom::Slot::Inlines(content) => inlines(content, prose),
+                }
+            }
+        }
+        Block::CodeBlock(_)
+        | Block::RawBlock(_)
+        | Block::HorizontalRule(_)
+        | Block::BlockMetadata(_) => {}
+    }
+}
+
+/// Collect the strings of prose inlines, and the blocks of notes.
+fn collect_inlines(
+    inlines: &[Inline],
+    strs: &mut Vec<ByteRange<usize>>,
+    prose: &mut Vec<Vec<ByteRange<usize>>>,
+) {
+    for inline in inlines {
+        match inline {
+            Inline::Str(s) => {
+                if !is_address(&s.text)
+                    && let Some(range) = byte_range(&s.source_info)
+                {
+                    strs.push(range);
+                }
+            }
+            Inline::Emph(emph) => collect_inlines(&emph.content, strs, prose),
+            Inline::Underline(underline) => collect_inlines(&underline.content, strs, prose),
+            Inline::Strong(strong) => collect_inlines(&strong.content, strs, prose),
+            Inline::Strikeout(strikeout) => collect_inlines(&strikeout.content, strs, prose),
+            Inline::Superscript(sup) => collect_inlines(&sup.content, strs, prose),
+            Inline::Subscript(sub) => collect_inlines(&sub.content, strs, prose),
+            Inline::SmallCaps(small_caps) => collect_inlines(&small_caps.content, strs, prose),
+            Inline::Quoted(quoted) => collect_inlines(&quoted.content, strs, prose),
+            Inline::Span(span) => collect_inlines(&span.content, strs, prose),
+            Inline::Insert(insert) => collect_inlines(&insert.content, strs, prose),
+            Inline::Delete(delete) => collect_inlines(&delete.content, strs, prose),
+            Inline::Highlight(highlight) => collect_inlines(&highlight.content, strs, prose),
+            Inline::Image(image) => collect_inlines(&image.content, strs, prose),
+            // The text of autolinks is their URL
+            Inline::Link(link) => {
+                if inlines_to_text(&link.content) != link.target.0 {
+                    collect_inlines(&link.content, strs, prose);
+                }
+            }
+            Inline::Note(note) => collect_blocks(&note.content, prose),
+            Inline::Custom(custom) => {
+                for slot in custom.slots.values() {
+                    match slot {
+                        pampa::pandoc::custom::Slot::Inline(inline) => {
+                            collect_inlines(std::slice::from_ref(&**inline), strs, prose)
+                        }
+                        pampa::pandoc::custom::Slot::Inlines(content) => {
+                            collect_inlines(content, strs, prose)
+                        }
+                        pampa::pandoc::custom::Slot::Block(block) => collect_block(block, prose),
+                        pampa::pandoc::custom::Slot::Blocks(blocks) => {
+                            collect_blocks(blocks, prose)
+                        }
+                    }
+                }
+            }
+            Inline::Cite(_)
+            | Inline::Code(_)
+            | Inline::Space(_)
+            | Inline::SoftBreak(_)
+            | Inline::LineBreak(_)
+            | Inline::Math(_)
+            | Inline::RawInline(_)
+            | Inline::Shortcode(_)
+            | Inline::NoteReference(_)
+            | Inline::Attr(_, _)
+            | Inline::EditComment(_) => {}
+        }
+    }
+}
+
+/// Whether a string is a URL or an email address.
+fn is_address(text: &str) -> bool {
+    text.contains("://") || text.starts_with("www.") || text.contains('@')
+}
+
+/// The byte range of a node in the document, if it comes from the
+/// document.
+pub(crate) fn byte_range(source_info: &SourceInfo) -> Option<ByteRange<usize>> {
+    match source_info {
+        SourceInfo::Original {
+            start_offset,
+            end_offset,
+            ..
+        } => Some(*start_offset..*end_offset),
+        SourceInfo::Substring {
+            parent,
+            start_offset,
+            end_offset,
+        } => {
+            let parent = byte_range(parent)?;
+            Some(parent.start + start_offset..parent.start + end_offset)
+        }
+        // Merged strings span their pieces
+        SourceInfo::Concat { pieces } => {
+            let ranges: Vec<ByteRange<usize>> = pieces
+                .iter()
+                .filter_map(|piece| byte_range(&piece.source_info))
+                .collect();
+            let start = ranges.iter().map(|range| range.start).min()?;
+            let end = ranges.iter().map(|range| range.end).max()?;
+            Some(start..end)
+        }
+        SourceInfo::FilterProvenance { .. } => None,
+    }
+}
+
+/// The words of a block of prose, with their byte ranges in `content`.
+///
+/// Words are letters with apostrophes between them (`don't`). Words with
+/// digits (`3rd`, `mp4`) and the user names of email addresses aren't words
+/// of prose.
+pub(crate) fn words<'a>(
+    content: &'a str,
+    block: &[ByteRange<usize>],
+) -> Vec<(ByteRange<usize>, &'a str)> {
+    let mut words = Vec::new();
+    for range in block {
+        let Some(text) = content.get(range.clone()) else {
+            continue;
+        };
+        let mut start: Option<usize> = None;
+        let mut push = |start: usize, end: usize| {
+            let word = text[start..end].trim_matches(is_apostrophe);
+            let start = range.start + start + text[start..end].find(word).unwrap_or(0);
+            let end = start + word.len();
+            // The user name of an email address, which the parser reads as
+            // a string before a citation
+            let user_name = content[end..].starts_with('@');
+            if !word.is_empty() && !user_name && !word.chars().any(|c| c.is_ascii_digit()) {
+                words.push((start..end, word));
+            }
+        };
+        for (i, c) in text.char_indices() {
+            let in_word = c.is_alphanumeric() || is_apostrophe(c);
+            match (start, in_word) {
+                (None, true) => start = Some(i),
+                (Some(from), false) => {
+                    push(from, i);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(from) = start {
+            push(from, text.len());
+        }
+    }
+    words
+}
+
+fn is_apostrophe(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prose_words(content: &str) -> Vec<&str> {
+        let (pandoc, _, _) = pampa::readers::qmd::read(
+            content.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .unwrap();
+        prose_blocks(&pandoc)
+            .iter()
+            .flat_map(|block| words(content, block))
+            .map(|(range, word)| {
+                assert_eq!(&content[range], word);
+                word
+            })
+            .collect()
+    }
+
+    #[test]
+    fn words_of_prose() {
+        assert_eq!(
+            prose_words("# A *title*\n\nIt's the 3rd [link](https://example.com).\n"),
+            vec!["A", "title", "It's", "the", "link"]
+        );
+    }
+
+    #[test]
+    fn skips_what_isnt_prose() {
+        let content = "Code `x` and $y$, @knuth, {{< meta title >}}, \
+                       <https://example.com> and me@example.com.\n\n\
+                       ```python\nprint()\n```\n";
+        assert_eq!(prose_words(content), vec!["Code", "and", "and"]);
+    }
+
+    #[test]
+    fn notes_tables_and_images() {
+        let content = "![Alt text](a.png)\n\nText.^[In a note]\n\n| Head |\n|------|\n| Cell |\n";
+        assert_eq!(
+            prose_words(content),
+            vec!["Alt", "text", "Text", "In", "a", "note", "Head", "Cell"]
+        );
+    }
+}
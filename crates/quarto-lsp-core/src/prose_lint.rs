@@ -0,0 +1,825 @@
+//! Style checks of a document's prose.
+//!
+//! Each [`LintRule`] looks at the Pandoc AST of a document and reports
+//! [`Finding`]s: long sentences, repeated words, images without alt text,
+//! skipped heading levels and TODO markers. Rules are on by default, and are
+//! configured by a `lint` option in the project configuration or the front
+//! matter, which overrides it rule by rule:
+//!
+//! ```yaml
+//! lint:
+//!   long-sentences:
+//!     max-words: 30        # options of the rule
+//!     severity: warning
+//!   repeated-words: error  # a severity: error, warning, info or hint
+//!   todo: false            # turns the rule off
+//! ```
+//!
+//! `lint: false` turns every rule off. Findings are reported as diagnostics
+//! by the language server and by `quarto check`.
+
+use std::collections::HashMap;
+use std::ops::Range as ByteRange;
+
+use pampa::filters::native::{Filter, Visit, walk_blocks};
+use pampa::pandoc::{Block, Header, Image, Inline, Pandoc, RawBlock, RawInline};
+use quarto_error_reporting::{
+    DiagnosticKind, DiagnosticMessage, DiagnosticMessageBuilder, Fix, FixEdit,
+};
+use quarto_source_map::{FileId, SourceInfo};
+use yaml_rust2::Yaml;
+
+use crate::analysis::inlines_to_text;
+use crate::completions::front_matter;
+use crate::diagnostics::convert_diagnostic;
+use crate::document::Document;
+use crate::prose::{byte_range, prose_blocks, words};
+use crate::types::Diagnostic;
+use crate::workspace::{Workspace, join_path};
+
+/// The rules that are checked.
+pub const RULES: &[&dyn LintRule] = &[
+    &LongSentences,
+    &RepeatedWords,
+    &MissingAltText,
+    &HeadingLevels,
+    &TodoMarkers,
+];
+
+/// A style check of prose.
+pub trait LintRule: Sync {
+    /// The rule's name, its key in the `lint` option.
+    fn name(&self) -> &'static str;
+
+    /// The error code of the rule's findings.
+    fn code(&self) -> &'static str;
+
+    /// The severity of the rule's findings, unless one is configured.
+    fn default_severity(&self) -> DiagnosticKind {
+        DiagnosticKind::Warning
+    }
+
+    /// Check a document, with the rule's configured options.
+    fn check(&self, ctx: &LintContext, options: &HashMap<String, Yaml>) -> Vec<Finding>;
+}
+
+/// What a rule found in a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// The byte range of what was found.
+    pub range: ByteRange<usize>,
+    pub title: String,
+    pub problem: String,
+    pub hint: Option<String>,
+    pub fix: Option<FindingFix>,
+}
+
+/// An edit of the document fixing a finding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FindingFix {
+    pub title: String,
+    /// The byte range replaced.
+    pub range: ByteRange<usize>,
+    pub new_text: String,
+}
+
+impl Finding {
+    pub fn new(
+        range: ByteRange<usize>,
+        title: impl Into<String>,
+        problem: impl Into<String>,
+    ) -> Self {
+        Self {
+            range,
+            title: title.into(),
+            problem: problem.into(),
+            hint: None,
+            fix: None,
+        }
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    pub fn with_fix(
+        mut self,
+        title: impl Into<String>,
+        range: ByteRange<usize>,
+        new_text: impl Into<String>,
+    ) -> Self {
+        self.fix = Some(FindingFix {
+            title: title.into(),
+            range,
+            new_text: new_text.into(),
+        });
+        self
+    }
+
+    /// The diagnostic message of the finding.
+    fn into_message(self, code: &str, kind: DiagnosticKind) -> DiagnosticMessage {
+        let location =
+            |range: ByteRange<usize>| SourceInfo::original(FileId(0), range.start, range.end);
+        let mut builder = DiagnosticMessageBuilder::new(kind, self.title)
+            .with_code(code)
+            .with_location(location(self.range))
+            .problem(self.problem);
+        if let Some(hint) = self.hint {
+            builder = builder.add_hint(hint);
+        }
+        if let Some(fix) = self.fix {
+            builder = builder.add_fix(Fix::new(
+                fix.title,
+                vec![FixEdit::new(location(fix.range), fix.new_text)],
+            ));
+        }
+        builder.build()
+    }
+}
+
+/// A parsed document, as rules see it.
+pub struct LintContext<'a> {
+    content: &'a str,
+    pandoc: &'a Pandoc,
+    prose: Vec<Vec<ByteRange<usize>>>,
+}
+
+impl<'a> LintContext<'a> {
+    pub fn new(content: &'a str, pandoc: &'a Pandoc) -> Self {
+        Self {
+            content,
+            pandoc,
+            prose: prose_blocks(pandoc),
+        }
+    }
+
+    /// The document's text.
+    pub fn content(&self) -> &'a str {
+        self.content
+    }
+
+    /// The document's AST.
+    pub fn pandoc(&self) -> &'a Pandoc {
+        self.pandoc
+    }
+
+    /// The words of each block of prose, with their byte ranges.
+    pub fn prose_words(&self) -> Vec<Vec<(ByteRange<usize>, &'a str)>> {
+        self.prose
+            .iter()
+            .map(|block| words(self.content, block))
+            .collect()
+    }
+
+    /// Walk the document's blocks with a filter. The filter sees a copy of
+    /// the blocks, so its edits are discarded.
+    pub fn walk(&self, filter: &mut dyn Filter) {
+        let mut blocks = self.pandoc.blocks.clone();
+        let _ = walk_blocks(filter, &mut blocks);
+    }
+}
+
+/// How the rules are configured.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProseLintConfig {
+    /// Whether any rule is checked.
+    pub enabled: bool,
+    /// The configuration of rules, by name.
+    pub rules: HashMap<String, RuleConfig>,
+}
+
+impl Default for ProseLintConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: HashMap::new(),
+        }
+    }
+}
+
+impl ProseLintConfig {
+    /// Apply a `lint` option over this configuration.
+    pub fn apply(&mut self, lint: &Yaml) {
+        match lint {
+            Yaml::Boolean(enabled) => self.enabled = *enabled,
+            Yaml::Hash(rules) => {
+                self.enabled = true;
+                for (name, rule) in rules {
+                    if let Some(name) = name.as_str() {
+                        self.rules.entry(name.to_string()).or_default().apply(rule);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// How a rule is configured.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleConfig {
+    pub enabled: bool,
+    /// The severity of the rule's findings, if not the rule's default.
+    pub severity: Option<DiagnosticKind>,
+    /// The rule's options, such as `max-words`.
+    pub options: HashMap<String, Yaml>,
+}
+
+impl Default for RuleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            severity: None,
+            options: HashMap::new(),
+        }
+    }
+}
+
+impl RuleConfig {
+    /// Apply the configuration of the rule in a `lint` option: `true` or
+    /// `false`, a severity, or a mapping of options.
+    fn apply(&mut self, rule: &Yaml) {
+        match rule {
+            Yaml::Boolean(enabled) => self.enabled = *enabled,
+            Yaml::String(severity) => {
+                self.enabled = true;
+                self.severity = parse_severity(severity).or(self.severity);
+            }
+            Yaml::Hash(options) => {
+                self.enabled = true;
+                for (key, value) in options {
+                    match key.as_str() {
+                        Some("severity") => {
+                            self.severity =
+                                value.as_str().and_then(parse_severity).or(self.severity)
+                        }
+                        Some(key) => {
+                            self.options.insert(key.to_string(), value.clone());
+                        }
+                        None => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse_severity(severity: &str) -> Option<DiagnosticKind> {
+    match severity {
+        "error" => Some(DiagnosticKind::Error),
+        "warning" => Some(DiagnosticKind::Warning),
+        "info" | "information" => Some(DiagnosticKind::Info),
+        "hint" | "note" => Some(DiagnosticKind::Note),
+        _ => None,
+    }
+}
+
+/// Get how a document's prose is linted, from the `lint` option of its
+/// project and its front matter.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, NoWorkspace, get_prose_lint_config};
+///
+/// let doc = Document::new("test.qmd", "---\nlint:\n  todo: false\n---\n");
+/// let config = get_prose_lint_config(&doc, &NoWorkspace);
+/// assert!(!config.rules["todo"].enabled);
+/// ```
+pub fn get_prose_lint_config(doc: &Document, workspace: &dyn Workspace) -> ProseLintConfig {
+    let mut config = ProseLintConfig::default();
+    if let Some(project_dir) = workspace.project_dir()
+        && let Some(project_config) = ["_quarto.yml", "_quarto.yaml"]
+            .iter()
+            .find_map(|name| workspace.read_file(&join_path(&project_dir, name)))
+        && let Ok(project_config) = quarto_yaml::parse(&project_config)
+    {
+        config.apply(&project_config.yaml["lint"]);
+    }
+    let lines: Vec<&str> = doc.content().lines().collect();
+    if let Some(meta) = front_matter(&lines) {
+        config.apply(&meta.yaml["lint"]);
+    }
+    config
+}
+
+/// Lint a document's prose with the built-in [`RULES`], as diagnostic
+/// messages located in the document (`FileId(0)`).
+pub fn get_prose_lint_messages(doc: &Document, config: &ProseLintConfig) -> Vec<DiagnosticMessage> {
+    lint_with_rules(doc, config, RULES)
+}
+
+/// Lint a document's prose with the given rules.
+pub fn lint_with_rules(
+    doc: &Document,
+    config: &ProseLintConfig,
+    rules: &[&dyn LintRule],
+) -> Vec<DiagnosticMessage> {
+    if !config.enabled {
+        return Vec::new();
+    }
+    let Ok((pandoc, _, _)) = pampa::readers::qmd::read(
+        doc.content_bytes(),
+        false, // loose mode
+        doc.filename(),
+        &mut std::io::sink(), // discard verbose output
+        true,                 // prune_errors
+        None,                 // parent_source_info
+    ) else {
+        return Vec::new();
+    };
+
+    let ctx = LintContext::new(doc.content(), &pandoc);
+    let no_options = HashMap::new();
+    let mut findings = Vec::new();
+    for rule in rules {
+        let rule_config = config.rules.get(rule.name());
+        if rule_config.is_some_and(|rule_config| !rule_config.enabled) {
+            continue;
+        }
+        let kind = rule_config
+            .and_then(|rule_config| rule_config.severity)
+            .unwrap_or_else(|| rule.default_severity());
+        let options = rule_config.map_or(&no_options, |rule_config| &rule_config.options);
+        for finding in rule.check(&ctx, options) {
+            findings.push((finding, rule.code(), kind));
+        }
+    }
+    findings.sort_by_key(|(finding, _, _)| finding.range.start);
+    findings
+        .into_iter()
+        .map(|(finding, code, kind)| finding.into_message(code, kind))
+        .collect()
+}
+
+/// Lint a document's prose (see [`get_prose_lint_messages`]).
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, ProseLintConfig, get_prose_lint_diagnostics};
+///
+/// let doc = Document::new("test.qmd", "The the end.\n");
+/// let diagnostics = get_prose_lint_diagnostics(&doc, &ProseLintConfig::default());
+/// assert_eq!(diagnostics[0].title, "Repeated Word");
+/// ```
+pub fn get_prose_lint_diagnostics(doc: &Document, config: &ProseLintConfig) -> Vec<Diagnostic> {
+    let source_context = doc.create_source_context();
+    get_prose_lint_messages(doc, config)
+        .iter()
+        .filter_map(|msg| convert_diagnostic(msg, &source_context))
+        .collect()
+}
+
+/// The number of words of a sentence, unless `max-words` is configured.
+const DEFAULT_MAX_WORDS: usize = 40;
+
+/// Sentences with more than `max-words` words.
+pub struct LongSentences;
+
+impl LintRule for LongSentences {
+    fn name(&self) -> &'static str {
+        "long-sentences"
+    }
+
+    fn code(&self) -> &'static str {
+        "Q-2-43"
+    }
+
+    fn default_severity(&self) -> DiagnosticKind {
+        DiagnosticKind::Info
+    }
+
+    fn check(&self, ctx: &LintContext, options: &HashMap<String, Yaml>) -> Vec<Finding> {
+        let max_words = options
+            .get("max-words")
+            .and_then(Yaml::as_i64)
+            .map_or(DEFAULT_MAX_WORDS, |max| max.max(1) as usize);
+        let content = ctx.content();
+        let mut findings = Vec::new();
+        for block in ctx.prose_words() {
+            let mut sentence: Vec<&ByteRange<usize>> = Vec::new();
+            for (i, (range, _)) in block.iter().enumerate() {
+                sentence.push(range);
+                let gap_end = block
+                    .get(i + 1)
+                    .map_or(content.len(), |(next, _)| next.start);
+                let ends_sentence = block.get(i + 1).is_none()
+                    || content[range.end..gap_end].contains(['.', '!', '?']);
+                if !ends_sentence {
+                    continue;
+                }
+                if sentence.len() > max_words {
+                    findings.push(
+                        Finding::new(
+                            sentence[0].start..range.end,
+                            "Long Sentence",
+                            format!(
+                                "This sentence has {} words, more than the {} of `max-words`",
+                                sentence.len(),
+                                max_words
+                            ),
+                        )
+                        .with_hint("Split it into shorter sentences?"),
+                    );
+                }
+                sentence.clear();
+            }
+        }
+        findings
+    }
+}
+
+/// The same word twice in a row, as in "the the".
+pub struct RepeatedWords;
+
+impl LintRule for RepeatedWords {
+    fn name(&self) -> &'static str {
+        "repeated-words"
+    }
+
+    fn code(&self) -> &'static str {
+        "Q-2-44"
+    }
+
+    fn check(&self, ctx: &LintContext, _options: &HashMap<String, Yaml>) -> Vec<Finding> {
+        let content = ctx.content();
+        let mut findings = Vec::new();
+        for block in ctx.prose_words() {
+            for pair in block.windows(2) {
+                let [(first, word), (second, next)] = pair else {
+                    continue;
+                };
+                let gap = &content[first.end..second.start];
+                if !gap.is_empty()
+                    && gap.chars().all(char::is_whitespace)
+                    && word.to_lowercase() == next.to_lowercase()
+                {
+                    findings.push(
+                        Finding::new(
+                            second.clone(),
+                            "Repeated Word",
+                            format!("`{}` is repeated", next),
+                        )
+                        .with_fix(
+                            format!("Remove the repeated `{}`", next),
+                            first.end..second.end,
+                            "",
+                        ),
+                    );
+                }
+            }
+        }
+        findings
+    }
+}
+
+/// Images without a description for readers who can't see them.
+pub struct MissingAltText;
+
+impl LintRule for MissingAltText {
+    fn name(&self) -> &'static str {
+        "alt-text"
+    }
+
+    fn code(&self) -> &'static str {
+        "Q-2-45"
+    }
+
+    fn check(&self, ctx: &LintContext, _options: &HashMap<String, Yaml>) -> Vec<Finding> {
+        let mut images = ImagesWithoutAlt(Vec::new());
+        ctx.walk(&mut images);
+        images
+            .0
+            .into_iter()
+            .map(|(range, target)| {
+                Finding::new(
+                    range,
+                    "Missing Alt Text",
+                    format!("The image `{}` has no description", target),
+                )
+                .with_hint(
+                    "Describe the image between its brackets, or with a `fig-alt` attribute, \
+                     for readers who can't see it",
+                )
+            })
+            .collect()
+    }
+}
+
+/// Collects the images without alt text, and their targets.
+struct ImagesWithoutAlt(Vec<(ByteRange<usize>, String)>);
+
+impl Filter for ImagesWithoutAlt {
+    fn name(&self) -> &str {
+        "images-without-alt"
+    }
+
+    fn visit_image(&mut self, image: &mut Image) -> Visit<Inline> {
+        let described = !inlines_to_text(&image.content).trim().is_empty()
+            || ["fig-alt", "alt"]
+                .iter()
+                .any(|key| image.attr.2.get(*key).is_some_and(|alt| !alt.is_empty()));
+        if !described && let Some(range) = byte_range(&image.source_info) {
+            self.0.push((range, image.target.0.clone()));
+        }
+        Visit::Continue
+    }
+}
+
+/// Headings more than one level below the heading before them, as a level-4
+/// heading right after a level-2 one.
+pub struct HeadingLevels;
+
+impl LintRule for HeadingLevels {
+    fn name(&self) -> &'static str {
+        "heading-levels"
+    }
+
+    fn code(&self) -> &'static str {
+        "Q-2-46"
+    }
+
+    fn check(&self, ctx: &LintContext, _options: &HashMap<String, Yaml>) -> Vec<Finding> {
+        let mut headings = Headings(Vec::new());
+        ctx.walk(&mut headings);
+        let content = ctx.content();
+        let mut findings = Vec::new();
+        for pair in headings.0.windows(2) {
+            let [(previous, _), (level, range)] = pair else {
+                continue;
+            };
+            if *level <= previous + 1 {
+                continue;
+            }
+            let mut finding = Finding::new(
+                range.clone(),
+                "Skipped Heading Level",
+                format!(
+                    "This level-{} heading follows a level-{} heading",
+                    level, previous
+                ),
+            );
+            // ATX headings change level with their number of `#`s
+            let marker = content[range.start..]
+                .chars()
+                .take_while(|c| *c == '#')
+                .count();
+            if marker == *level {
+                finding = finding.with_fix(
+                    format!("Change to a level-{} heading", previous + 1),
+                    range.start..range.start + marker,
+                    "#".repeat(previous + 1),
+                );
+            }
+            findings.push(finding);
+        }
+        findings
+    }
+}
+
+/// Collects the levels and ranges of headings, in document order.
+struct Headings(Vec<(usize, ByteRange<usize>)>);
+
+impl Filter for Headings {
+    fn name(&self) -> &str {
+        "headings"
+    }
+
+    fn visit_header(&mut self, header: &mut Header) -> Visit<Block> {
+        if let Some(range) = byte_range(&header.source_info) {
+            self.0.push((header.level, range));
+        }
+        Visit::Continue
+    }
+}
+
+/// The words that mark unfinished work.
+const TODO_MARKERS: &[&str] = &["TODO", "FIXME", "XXX"];
+
+/// Markers of unfinished work, in the prose and in HTML comments.
+pub struct TodoMarkers;
+
+impl LintRule for TodoMarkers {
+    fn name(&self) -> &'static str {
+        "todo"
+    }
+
+    fn code(&self) -> &'static str {
+        "Q-2-47"
+    }
+
+    fn default_severity(&self) -> DiagnosticKind {
+        DiagnosticKind::Info
+    }
+
+    fn check(&self, ctx: &LintContext, _options: &HashMap<String, Yaml>) -> Vec<Finding> {
+        let content = ctx.content();
+        let mut markers: Vec<ByteRange<usize>> = ctx
+            .prose_words()
+            .into_iter()
+            .flatten()
+            .filter(|(_, word)| TODO_MARKERS.contains(word))
+            .map(|(range, _)| range)
+            .collect();
+
+        let mut comments = Comments(Vec::new());
+        ctx.walk(&mut comments);
+        for comment in comments.0 {
+            let text = &content[comment.clone()];
+            for marker in TODO_MARKERS {
+                for (offset, _) in text.match_indices(marker) {
+                    let is_word = |c: Option<char>| !c.is_some_and(char::is_alphanumeric);
+                    if is_word(text[..offset].chars().next_back())
+                        && is_word(text[offset + marker.len()..].chars().next())
+                    {
+                        let start = comment.start + offset;
+                        markers.push(start..start + marker.len());
+                    }
+                }
+            }
+        }
+
+        markers
+            .into_iter()
+            .map(|range| {
+                let marker = &content[range.clone()];
+                Finding::new(
+                    range.clone(),
+                    "TODO Marker",
+                    format!("`{}` marks unfinished work", marker),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Collects the ranges of HTML comments.
+struct Comments(Vec<ByteRange<usize>>);
+
+impl Comments {
+    fn push(&mut self, format: &str, text: &str, source_info: &SourceInfo) {
+        if format == "html"
+            && text.trim_start().starts_with("<!--")
+            && let Some(range) = byte_range(source_info)
+        {
+            self.0.push(range);
+        }
+    }
+}
+
+impl Filter for Comments {
+    fn name(&self) -> &str {
+        "comments"
+    }
+
+    fn visit_raw_block(&mut self, raw: &mut RawBlock) -> Visit<Block> {
+        self.push(&raw.format, &raw.text, &raw.source_info);
+        Visit::Continue
+    }
+
+    fn visit_raw_inline(&mut self, raw: &mut RawInline) -> Visit<Inline> {
+        self.push(&raw.format, &raw.text, &raw.source_info);
+        Visit::Continue
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DiagnosticSeverity, Position, Range};
+    use crate::workspace::{MemoryWorkspace, NoWorkspace};
+
+    fn lint(content: &str) -> Vec<Diagnostic> {
+        let doc = Document::new("test.qmd", content);
+        get_prose_lint_diagnostics(&doc, &get_prose_lint_config(&doc, &NoWorkspace))
+    }
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics
+            .iter()
+            .filter_map(|diagnostic| diagnostic.code.as_deref())
+            .collect()
+    }
+
+    #[test]
+    fn long_sentences() {
+        let content = "---\nlint:\n  long-sentences:\n    max-words: 5\n---\n\n\
+                       One two three four five six. One two three.\n";
+        let results = lint(content);
+        assert_eq!(codes(&results), vec!["Q-2-43"]);
+        assert_eq!(results[0].severity, DiagnosticSeverity::Information);
+        assert_eq!(results[0].range.start, Position::new(6, 0));
+        assert_eq!(results[0].range.end, Position::new(6, 27));
+    }
+
+    #[test]
+    fn repeated_words() {
+        let content = "It is the\nthe end, end of it.\n";
+        let results = lint(content);
+        assert_eq!(codes(&results), vec!["Q-2-44"]);
+        let fix = &results[0].fixes[0];
+        assert_eq!(fix.title, "Remove the repeated `the`");
+        assert_eq!(fix.edits[0].new_text, "");
+        assert_eq!(
+            fix.edits[0].location.range,
+            Range::new(Position::new(0, 9), Position::new(1, 3))
+        );
+    }
+
+    #[test]
+    fn missing_alt_text() {
+        let content = "![](a.png)\n\n![A plot](b.png)\n\nSee ![](c.png){fig-alt=\"A map\"}.\n";
+        let results = lint(content);
+        assert_eq!(codes(&results), vec!["Q-2-45"]);
+        assert_eq!(
+            results[0].problem.as_ref().unwrap().as_str(),
+            "The image `a.png` has no description"
+        );
+    }
+
+    #[test]
+    fn heading_levels() {
+        let content = "# One\n\n## Two\n\n#### Four\n\n## Two\n";
+        let results = lint(content);
+        assert_eq!(codes(&results), vec!["Q-2-46"]);
+        let fix = &results[0].fixes[0];
+        assert_eq!(fix.title, "Change to a level-3 heading");
+        assert_eq!(fix.edits[0].new_text, "###");
+        assert_eq!(
+            fix.edits[0].location.range,
+            Range::new(Position::new(4, 0), Position::new(4, 4))
+        );
+    }
+
+    #[test]
+    fn todo_markers() {
+        let content = "TODO: write this. Not TODOS.\n\n<!-- FIXME later -->\n";
+        let results = lint(content);
+        assert_eq!(codes(&results), vec!["Q-2-47", "Q-2-47"]);
+        assert_eq!(
+            results[1].range,
+            Range::new(Position::new(2, 5), Position::new(2, 10))
+        );
+    }
+
+    #[test]
+    fn config_from_project_and_front_matter() {
+        let workspace = MemoryWorkspace::default().with_project_dir("").with_file(
+            "_quarto.yml",
+            "lint:\n  todo: false\n  repeated-words: error\n  alt-text:\n    severity: hint\n",
+        );
+        let doc = Document::new(
+            "test.qmd",
+            "---\nlint:\n  todo: true\n---\n\nTODO the the ![](a.png)\n",
+        );
+        let config = get_prose_lint_config(&doc, &workspace);
+        let results = get_prose_lint_diagnostics(&doc, &config);
+        let severities: Vec<(&str, DiagnosticSeverity)> = results
+            .iter()
+            .map(|diagnostic| (diagnostic.code.as_deref().unwrap(), diagnostic.severity))
+            .collect();
+        assert_eq!(
+            severities,
+            vec![
+                ("Q-2-47", DiagnosticSeverity::Information),
+                ("Q-2-44", DiagnosticSeverity::Error),
+                ("Q-2-45", DiagnosticSeverity::Hint),
+            ]
+        );
+
+        let doc = Document::new("test.qmd", "---\nlint: false\n---\n\nThe the end.\n");
+        assert!(
+            get_prose_lint_diagnostics(&doc, &get_prose_lint_config(&doc, &workspace)).is_empty()
+        );
+    }
+
+    struct Exclamations;
+
+    impl LintRule for Exclamations {
+        fn name(&self) -> &'static str {
+            "exclamations"
+        }
+
+        fn code(&self) -> &'static str {
+            "X-1"
+        }
+
+        fn check(&self, ctx: &LintContext, _options: &HashMap<String, Yaml>) -> Vec<Finding> {
+            ctx.content()
+                .match_indices('!')
+                .map(|(i, _)| Finding::new(i..i + 1, "Exclamation", "An exclamation mark"))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn rules_of_their_own() {
+        let doc = Document::new("test.qmd", "Wow!\n");
+        let messages = lint_with_rules(&doc, &ProseLintConfig::default(), &[&Exclamations]);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].code.as_deref(), Some("X-1"));
+    }
+}
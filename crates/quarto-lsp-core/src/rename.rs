@@ -0,0 +1,212 @@
+//! Renaming cross-reference labels and citation keys.
+//!
+//! Renaming a label (`#fig-plot`, `#| label: fig-plot`) updates its
+//! definition and every `@fig-plot` and `(#fig-plot)` across the project.
+//! Renaming a citation key updates its citations and, optionally, the key
+//! of its entry in the bibliography (BibTeX, CSL JSON, CSL YAML or the
+//! front matter's `references`).
+//!
+//! Edits are computed from the project's [`SymbolIndex`]; applying them is
+//! left to the caller.
+
+use crate::completions::{crossref_kind, is_citation_char};
+use crate::document::Document;
+use crate::index::SymbolIndex;
+use crate::navigation::citation_definition;
+use crate::types::{Position, Range, TextEdit};
+use crate::workspace::Workspace;
+
+/// Why a rename can't be done.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RenameError {
+    /// There is no label or citation key at the position.
+    #[error("No label or citation key to rename here")]
+    NothingToRename,
+
+    /// The new name isn't a valid label or citation key.
+    #[error("`{0}` is not a valid label or citation key")]
+    InvalidName(String),
+
+    /// Renaming would change a cross-reference label into one without a
+    /// known prefix (`fig-`, `tbl-`, ...), which would no longer be numbered.
+    #[error("`{0}` is not a cross-reference label (expected a prefix such as `fig-` or `sec-`)")]
+    NotALabel(String),
+
+    /// The new name is already defined in the project.
+    #[error("`{0}` is already defined")]
+    AlreadyDefined(String),
+}
+
+/// The range of the label or citation key that would be renamed at a
+/// position, if any.
+pub fn prepare_rename(doc: &Document, position: Position) -> Option<Range> {
+    let mut index = SymbolIndex::new();
+    index.add_file(None, doc.content());
+    index.entry_at(position).map(|entry| entry.location.range)
+}
+
+/// The edits that rename the label or citation key at a position to
+/// `new_name`, across the project.
+///
+/// A leading `@` or `#` in `new_name` is ignored. With
+/// `rename_bibliography_entry`, a citation key is also renamed in its
+/// bibliography entry.
+pub fn get_rename_edits(
+    doc: &Document,
+    position: Position,
+    new_name: &str,
+    workspace: &dyn Workspace,
+    rename_bibliography_entry: bool,
+) -> Result<Vec<TextEdit>, RenameError> {
+    let index = SymbolIndex::for_document(doc, workspace);
+    let name = index
+        .name_at(position)
+        .ok_or(RenameError::NothingToRename)?;
+
+    let new_name = new_name.trim();
+    let new_name = new_name
+        .strip_prefix(['@', '#'])
+        .unwrap_or(new_name)
+        .to_string();
+    if !is_valid_name(&new_name) {
+        return Err(RenameError::InvalidName(new_name));
+    }
+    if new_name == name {
+        return Ok(Vec::new());
+    }
+    if index.definitions(&new_name).next().is_some() {
+        return Err(RenameError::AlreadyDefined(new_name));
+    }
+
+    let definitions: Vec<_> = index.definitions(name).cloned().collect();
+    if !definitions.is_empty()
+        && crossref_kind(name).is_some()
+        && crossref_kind(&new_name).is_none()
+    {
+        return Err(RenameError::NotALabel(new_name));
+    }
+
+    let mut locations = definitions;
+    if locations.is_empty() && rename_bibliography_entry {
+        let lines: Vec<&str> = doc.content().lines().collect();
+        locations.extend(citation_definition(doc, &lines, name, workspace));
+    }
+    locations.extend(index.references(name).cloned());
+    Ok(locations
+        .into_iter()
+        .map(|location| TextEdit::new(location, new_name.clone()))
+        .collect())
+}
+
+/// Whether `name` can be written as a label or citation key: citation
+/// characters, starting and ending with a letter, digit or `_`.
+fn is_valid_name(name: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    name.starts_with(is_word) && name.ends_with(is_word) && name.chars().all(is_citation_char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::{MemoryWorkspace, NoWorkspace};
+
+    fn edits(edits: &[TextEdit]) -> Vec<(Option<&str>, u32, u32, u32)> {
+        edits
+            .iter()
+            .map(|edit| {
+                let range = edit.location.range;
+                (
+                    edit.location.path.as_deref(),
+                    range.start.line,
+                    range.start.character,
+                    range.end.character,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rename_label_across_project() {
+        let workspace = MemoryWorkspace::default()
+            .with_project_dir(".")
+            .with_file("_quarto.yml", "project:\n  type: book\n")
+            .with_file("results.qmd", "As @fig-plot shows.\n");
+        let doc = Document::new(
+            "file:///book/index.qmd",
+            "![Plot](plot.png){#fig-plot}\n\nSee @fig-plot and [the plot](#fig-plot).\n",
+        );
+
+        let result =
+            get_rename_edits(&doc, Position::new(2, 6), "fig-scatter", &workspace, true).unwrap();
+        assert_eq!(
+            edits(&result),
+            vec![
+                (None, 0, 19, 27),
+                (None, 2, 5, 13),
+                (None, 2, 30, 38),
+                (Some("results.qmd"), 0, 4, 12),
+            ]
+        );
+        assert!(result.iter().all(|edit| edit.new_text == "fig-scatter"));
+
+        assert_eq!(
+            prepare_rename(&doc, Position::new(0, 20)),
+            Some(Range::new(Position::new(0, 19), Position::new(0, 27)))
+        );
+    }
+
+    #[test]
+    fn rename_citation_key_and_entry() {
+        let workspace = MemoryWorkspace::default().with_file(
+            "refs.bib",
+            "@article{knuth1984,\n  title = {Literate Programming}\n}\n",
+        );
+        let doc = Document::new(
+            "test.qmd",
+            "---\nbibliography: refs.bib\n---\n\n[@knuth1984, p. 3] and @knuth1984.\n",
+        );
+
+        let result =
+            get_rename_edits(&doc, Position::new(4, 3), "@knuth84", &workspace, true).unwrap();
+        assert_eq!(
+            edits(&result),
+            vec![
+                (Some("refs.bib"), 0, 9, 18),
+                (None, 4, 2, 11),
+                (None, 4, 24, 33)
+            ]
+        );
+        assert_eq!(result[0].new_text, "knuth84");
+
+        let result =
+            get_rename_edits(&doc, Position::new(4, 3), "knuth84", &workspace, false).unwrap();
+        assert_eq!(result.len(), 2);
+    }
+
+    #[test]
+    fn rename_errors() {
+        let doc = Document::new(
+            "test.qmd",
+            "# Intro {#sec-intro}\n\n# Methods {#sec-methods}\n\nSee @sec-intro.\n",
+        );
+        let rename = |position, name| get_rename_edits(&doc, position, name, &NoWorkspace, true);
+
+        assert_eq!(
+            rename(Position::new(4, 1), "sec-start"),
+            Err(RenameError::NothingToRename)
+        );
+        assert_eq!(
+            rename(Position::new(4, 6), "sec intro"),
+            Err(RenameError::InvalidName("sec intro".to_string()))
+        );
+        assert_eq!(
+            rename(Position::new(4, 6), "introduction"),
+            Err(RenameError::NotALabel("introduction".to_string()))
+        );
+        assert_eq!(
+            rename(Position::new(4, 6), "sec-methods"),
+            Err(RenameError::AlreadyDefined("sec-methods".to_string()))
+        );
+        assert_eq!(rename(Position::new(4, 6), "sec-intro"), Ok(Vec::new()));
+    }
+}
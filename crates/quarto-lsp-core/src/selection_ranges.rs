@@ -0,0 +1,195 @@
+//! Selection ranges for QMD documents.
+//!
+//! Expanding the selection at a position goes through the nodes of the
+//! tree-sitter syntax tree that contain it: the word, the inlines around it
+//! (emphasis, a link), the block (a paragraph, list item, code cell), the
+//! sections it is in and finally the whole document.
+//!
+//! Ranges are trimmed of surrounding whitespace, so that selecting a block
+//! doesn't select the line break after it.
+
+use tree_sitter::{Node, Point};
+
+use crate::completions::line_prefix;
+use crate::document::Document;
+use crate::types::{Position, Range, SelectionRange};
+
+/// Get the selection ranges at positions of a document, one for each
+/// position.
+///
+/// A position outside the document gets an empty range at the position.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, Position, get_selection_ranges};
+///
+/// let doc = Document::new("test.qmd", "# Intro\n\nSome *emphasized words*.\n");
+/// let selection = &get_selection_ranges(&doc, &[Position::new(2, 8)])[0];
+/// // "emphasized", "*emphasized words*", the paragraph, the section, the document
+/// assert_eq!(selection.ranges().len(), 5);
+/// ```
+pub fn get_selection_ranges(doc: &Document, positions: &[Position]) -> Vec<SelectionRange> {
+    let content = doc.content();
+    let tree = doc.tree();
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+
+    positions
+        .iter()
+        .map(|&position| {
+            let ranges = tree.map_or_else(Vec::new, |tree| {
+                let root = tree.block_tree().root_node();
+                enclosing_ranges(root, content, &line_starts, position)
+            });
+            ranges
+                .into_iter()
+                .rev()
+                .fold(None, |parent: Option<SelectionRange>, range| {
+                    let selection = SelectionRange::new(range);
+                    Some(match parent {
+                        Some(parent) => selection.with_parent(parent),
+                        None => selection,
+                    })
+                })
+                .unwrap_or_else(|| SelectionRange::new(Range::point(position)))
+        })
+        .collect()
+}
+
+/// The distinct ranges of the nodes containing a position, from the
+/// innermost.
+fn enclosing_ranges(
+    root: Node,
+    content: &str,
+    line_starts: &[usize],
+    position: Position,
+) -> Vec<Range> {
+    let Some(&line_start) = line_starts.get(position.line as usize) else {
+        return Vec::new();
+    };
+    let line = content[line_start..].split('\n').next().unwrap_or_default();
+    let point = Point::new(
+        position.line as usize,
+        line_prefix(line, position.character).len(),
+    );
+
+    let mut ranges: Vec<Range> = Vec::new();
+    let mut node = root.descendant_for_point_range(point, point);
+    while let Some(current) = node {
+        if let Some(range) = trimmed_range(&current, content, line_starts)
+            && range.start <= position
+            && position <= range.end
+            && ranges.last() != Some(&range)
+        {
+            ranges.push(range);
+        }
+        node = current.parent();
+    }
+    ranges
+}
+
+/// The range of a node without its surrounding whitespace, or `None` if it
+/// is all whitespace.
+fn trimmed_range(node: &Node, content: &str, line_starts: &[usize]) -> Option<Range> {
+    let text = content.get(node.byte_range())?;
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    let start = node.start_byte() + (text.len() - text.trim_start().len());
+    let end = start + trimmed.len();
+    Some(Range::new(
+        position_at(content, line_starts, start),
+        position_at(content, line_starts, end),
+    ))
+}
+
+/// The position of a byte offset.
+fn position_at(content: &str, line_starts: &[usize], offset: usize) -> Position {
+    let line = line_starts.partition_point(|&start| start <= offset) - 1;
+    let column = content[line_starts[line]..offset].chars().count();
+    Position::new(line as u32, column as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text of each range of the selection at a position.
+    fn expansions(content: &str, position: Position) -> Vec<String> {
+        let doc = Document::new("test.qmd", content);
+        let lines: Vec<&str> = content.lines().collect();
+        get_selection_ranges(&doc, &[position])[0]
+            .ranges()
+            .iter()
+            .map(|range| {
+                let mut text = String::new();
+                for line in range.start.line..=range.end.line {
+                    let chars = lines
+                        .get(line as usize)
+                        .copied()
+                        .unwrap_or_default()
+                        .chars();
+                    let from = if line == range.start.line {
+                        range.start.character as usize
+                    } else {
+                        0
+                    };
+                    if line == range.end.line {
+                        let to = range.end.character as usize;
+                        text.extend(chars.skip(from).take(to.saturating_sub(from)));
+                    } else {
+                        text.extend(chars.skip(from));
+                        text.push('\n');
+                    }
+                }
+                text
+            })
+            .collect()
+    }
+
+    #[test]
+    fn word_inline_block_section_document() {
+        let content = "# Intro\n\nSome *emphasized words*.\n\n## Más\n\nLa *niña* ríe.\n";
+        assert_eq!(
+            expansions(content, Position::new(6, 5)),
+            vec![
+                "niña",
+                "*niña*",
+                "La *niña* ríe.",
+                "## Más\n\nLa *niña* ríe.",
+                "# Intro\n\nSome *emphasized words*.\n\n## Más\n\nLa *niña* ríe.",
+            ]
+        );
+    }
+
+    #[test]
+    fn code_cells() {
+        let content = "Text\n\n```{python}\nx = 1\ny = 2\n```\n";
+        assert_eq!(
+            expansions(content, Position::new(4, 2)),
+            vec![
+                "x = 1\ny = 2",
+                "```{python}\nx = 1\ny = 2\n```",
+                "Text\n\n```{python}\nx = 1\ny = 2\n```",
+            ]
+        );
+    }
+
+    #[test]
+    fn one_selection_per_position() {
+        let doc = Document::new("test.qmd", "A *b* c\n");
+        let selections = get_selection_ranges(&doc, &[Position::new(0, 3), Position::new(5, 0)]);
+        assert_eq!(selections.len(), 2);
+        assert_eq!(
+            selections[0].range,
+            Range::new(Position::new(0, 3), Position::new(0, 4))
+        );
+        assert_eq!(
+            selections[1],
+            SelectionRange::new(Range::point(Position::new(5, 0)))
+        );
+    }
+}
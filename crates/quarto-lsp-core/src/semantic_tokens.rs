@@ -0,0 +1,353 @@
+//! Semantic tokens for QMD documents.
+//!
+//! This module finds the Quarto constructs that markdown grammars in
+//! editors don't know about, so they can be highlighted:
+//!
+//! - **Shortcodes** (`{{< var foo >}}`): delimiters, name and arguments
+//! - **Cell options** (`#| echo: false`) in executable code cells
+//! - **Cross-references** (`@fig-plot`), but not other citations
+//! - **Attributes** (`{#id .class key="value"}`) of divs, spans, headings
+//!   and code
+//! - **Math** (`$x$`, `$$ ... $$`)
+//! - **Raw formats** (`{=latex}`) of raw blocks and inlines
+//!
+//! Tokens are found in the tree-sitter syntax tree rather than the Pandoc
+//! AST, so documents are highlighted while they have errors.
+
+use pampa::pandoc::cell_options::option_prefix;
+use quarto_treesitter_ast::{TraversePhase, topdown_traverse_concrete_tree};
+use tree_sitter::Node;
+
+use crate::completions::crossref_kind;
+use crate::document::Document;
+use crate::types::{Position, Range, SemanticToken, SemanticTokenKind};
+
+/// Get the semantic tokens of a document, in document order.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, SemanticTokenKind, get_semantic_tokens};
+///
+/// let doc = Document::new("test.qmd", "{{< var title >}}\n");
+/// let tokens = get_semantic_tokens(&doc);
+/// assert_eq!(tokens[1].kind, SemanticTokenKind::ShortcodeName);
+/// ```
+pub fn get_semantic_tokens(doc: &Document) -> Vec<SemanticToken> {
+    let content = doc.content();
+    let Some(tree) = doc.tree() else {
+        return Vec::new();
+    };
+
+    let mut collector = TokenCollector {
+        lines: content.lines().collect(),
+        tokens: Vec::new(),
+    };
+    let mut cursor = tree.walk_cursor();
+    topdown_traverse_concrete_tree(&mut cursor, &mut |node, phase| {
+        phase == TraversePhase::Exit || collector.visit(node)
+    });
+
+    let mut tokens = collector.tokens;
+    tokens.sort_by_key(|token| token.range.start);
+    tokens
+}
+
+/// Get the semantic tokens on the lines of a range.
+pub fn get_semantic_tokens_in_range(doc: &Document, range: Range) -> Vec<SemanticToken> {
+    get_semantic_tokens(doc)
+        .into_iter()
+        .filter(|token| token.range.start.line >= range.start.line)
+        .filter(|token| token.range.start.line <= range.end.line)
+        .collect()
+}
+
+struct TokenCollector<'a> {
+    lines: Vec<&'a str>,
+    tokens: Vec<SemanticToken>,
+}
+
+impl TokenCollector<'_> {
+    /// Add the tokens of `node`, returning whether to visit its children.
+    fn visit(&mut self, node: &Node) -> bool {
+        let kind = match node.kind() {
+            // Front matter is YAML, and code is highlighted by its language
+            "metadata" | "code_fence_content" => return false,
+            "pandoc_code_block" => {
+                self.cell_options(node);
+                return true;
+            }
+            "citation" => {
+                let is_crossref = (0..node.child_count())
+                    .filter_map(|i| node.child(i))
+                    .filter(|child| child.kind().starts_with("citation_id"))
+                    .any(|id| crossref_kind(self.text(&id)).is_some());
+                if is_crossref {
+                    self.push_node(node, SemanticTokenKind::CrossRef);
+                }
+                return false;
+            }
+            "pandoc_math" | "pandoc_display_math" => {
+                self.push_node(node, SemanticTokenKind::Math);
+                return false;
+            }
+            "shortcode_delimiter" => SemanticTokenKind::ShortcodeDelimiter,
+            "shortcode_name" => SemanticTokenKind::ShortcodeName,
+            "shortcode_string" | "shortcode_naked_string" | "shortcode_number" => {
+                SemanticTokenKind::ShortcodeArgument
+            }
+            "attribute_id" => SemanticTokenKind::AttributeId,
+            "attribute_class" => SemanticTokenKind::AttributeClass,
+            "key_value_key" => SemanticTokenKind::AttributeKey,
+            "key_value_value" => SemanticTokenKind::AttributeValue,
+            "raw_specifier" => SemanticTokenKind::RawFormat,
+            _ => return true,
+        };
+        self.push_node(node, kind);
+        false
+    }
+
+    /// Add the tokens of the option lines of an executable code block.
+    fn cell_options(&mut self, block: &Node) {
+        let mut children = (0..block.child_count()).filter_map(|i| block.child(i));
+        let Some(language) = children
+            .clone()
+            .filter(|child| child.kind() == "attribute_specifier")
+            .flat_map(|spec| (0..spec.child_count()).filter_map(move |i| spec.child(i)))
+            .find(|child| child.kind() == "language_specifier")
+        else {
+            return;
+        };
+        let Some(code) = children.find(|child| child.kind() == "code_fence_content") else {
+            return;
+        };
+        let prefix = option_prefix(self.text(&language));
+
+        // Option lines are indented like the block's fence
+        let indent = block.start_position().column;
+        for row in code.start_position().row..code.end_position().row {
+            let Some(rest) = self.lines.get(row).and_then(|line| line.get(indent..)) else {
+                break;
+            };
+            let Some(yaml) = rest.strip_prefix(prefix) else {
+                break;
+            };
+            let marker_end = indent + prefix.len();
+            self.push(row, indent, marker_end, SemanticTokenKind::CellOptionMarker);
+
+            let yaml_start = marker_end + usize::from(yaml.starts_with(' '));
+            let yaml = yaml.strip_prefix(' ').unwrap_or(yaml);
+            match yaml.split_once(':') {
+                Some((key, _)) if !yaml.starts_with([' ', '\t', '-']) => {
+                    let key_end = yaml_start + key.len();
+                    self.push(row, yaml_start, key_end, SemanticTokenKind::CellOptionKey);
+                    let line_end = yaml_start + yaml.len();
+                    self.push(
+                        row,
+                        key_end + 1,
+                        line_end,
+                        SemanticTokenKind::CellOptionValue,
+                    );
+                }
+                // Continuation lines of a multi-line value
+                _ => {
+                    let line_end = yaml_start + yaml.len();
+                    self.push(
+                        row,
+                        yaml_start,
+                        line_end,
+                        SemanticTokenKind::CellOptionValue,
+                    );
+                }
+            }
+        }
+    }
+
+    /// The source text of a node on a single line.
+    fn text(&self, node: &Node) -> &str {
+        let start = node.start_position();
+        let end = node.end_position();
+        let line = self.lines.get(start.row).copied().unwrap_or_default();
+        let end_column = if end.row == start.row {
+            end.column
+        } else {
+            line.len()
+        };
+        line.get(start.column..end_column).unwrap_or_default()
+    }
+
+    /// Add a token for a node, split at line ends.
+    fn push_node(&mut self, node: &Node, kind: SemanticTokenKind) {
+        let start = node.start_position();
+        let end = node.end_position();
+        for row in start.row..=end.row {
+            let line_len = self.lines.get(row).map_or(0, |line| line.len());
+            let from = if row == start.row { start.column } else { 0 };
+            let to = if row == end.row {
+                end.column.min(line_len)
+            } else {
+                line_len
+            };
+            self.push(row, from, to, kind);
+        }
+    }
+
+    /// Add a token for the bytes `start..end` of a line, without their
+    /// surrounding whitespace.
+    fn push(&mut self, row: usize, start: usize, end: usize, kind: SemanticTokenKind) {
+        let Some(line) = self.lines.get(row) else {
+            return;
+        };
+        let Some(text) = line.get(start..end) else {
+            return;
+        };
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return;
+        }
+        let start = start + (text.len() - text.trim_start().len());
+        let end = start + trimmed.len();
+        let column = |offset: usize| line[..offset].chars().count() as u32;
+        self.tokens.push(SemanticToken::new(
+            Range::new(
+                Position::new(row as u32, column(start)),
+                Position::new(row as u32, column(end)),
+            ),
+            kind,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text and kind of each token.
+    fn tokens(content: &str) -> Vec<(String, SemanticTokenKind)> {
+        let doc = Document::new("test.qmd", content);
+        let lines: Vec<&str> = content.lines().collect();
+        get_semantic_tokens(&doc)
+            .iter()
+            .map(|token| {
+                let range = token.range;
+                assert_eq!(range.start.line, range.end.line);
+                let text: String = lines[range.start.line as usize]
+                    .chars()
+                    .skip(range.start.character as usize)
+                    .take((range.end.character - range.start.character) as usize)
+                    .collect();
+                (text, token.kind)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shortcodes() {
+        use SemanticTokenKind::*;
+        assert_eq!(
+            tokens("Hi {{< var foo \"bar\" 3 >}}\n"),
+            vec![
+                ("{{<".to_string(), ShortcodeDelimiter),
+                ("var".to_string(), ShortcodeName),
+                ("foo".to_string(), ShortcodeArgument),
+                ("\"bar\"".to_string(), ShortcodeArgument),
+                ("3".to_string(), ShortcodeArgument),
+                (">}}".to_string(), ShortcodeDelimiter),
+            ]
+        );
+    }
+
+    #[test]
+    fn crossrefs_but_not_citations() {
+        assert_eq!(
+            tokens("See @fig-plot and @knuth84.\n"),
+            vec![("@fig-plot".to_string(), SemanticTokenKind::CrossRef)]
+        );
+    }
+
+    #[test]
+    fn cell_options() {
+        use SemanticTokenKind::*;
+        assert_eq!(
+            tokens(
+                "```{python}\n#| label: fig-a\n#| fig-cap:\n#|   - A\nx = 1  # not #| an option\n```\n"
+            ),
+            vec![
+                ("#|".to_string(), CellOptionMarker),
+                ("label".to_string(), CellOptionKey),
+                ("fig-a".to_string(), CellOptionValue),
+                ("#|".to_string(), CellOptionMarker),
+                ("fig-cap".to_string(), CellOptionKey),
+                ("#|".to_string(), CellOptionMarker),
+                ("- A".to_string(), CellOptionValue),
+            ]
+        );
+    }
+
+    #[test]
+    fn cell_options_use_language_prefix() {
+        let kinds: Vec<SemanticTokenKind> = tokens("```{ojs}\n//| echo: false\n```\n")
+            .into_iter()
+            .map(|(_, kind)| kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SemanticTokenKind::CellOptionMarker,
+                SemanticTokenKind::CellOptionKey,
+                SemanticTokenKind::CellOptionValue,
+            ]
+        );
+    }
+
+    #[test]
+    fn div_attributes() {
+        use SemanticTokenKind::*;
+        assert_eq!(
+            tokens("::: {#note .callout-note title=\"Hi\"}\nText\n:::\n"),
+            vec![
+                ("#note".to_string(), AttributeId),
+                (".callout-note".to_string(), AttributeClass),
+                ("title".to_string(), AttributeKey),
+                ("\"Hi\"".to_string(), AttributeValue),
+            ]
+        );
+    }
+
+    #[test]
+    fn display_math_is_split_into_lines() {
+        assert_eq!(
+            tokens("$$\nx^2\n$$\n"),
+            vec![
+                ("$$".to_string(), SemanticTokenKind::Math),
+                ("x^2".to_string(), SemanticTokenKind::Math),
+                ("$$".to_string(), SemanticTokenKind::Math),
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_formats() {
+        assert_eq!(
+            tokens("```{=latex}\n\\newpage\n```\n\n`<br>`{=html}\n"),
+            vec![
+                ("=latex".to_string(), SemanticTokenKind::RawFormat),
+                ("=html".to_string(), SemanticTokenKind::RawFormat),
+            ]
+        );
+    }
+
+    #[test]
+    fn front_matter_has_no_tokens() {
+        assert!(tokens("---\ntitle: \"{{< var x >}} $y$\"\n---\n").is_empty());
+    }
+
+    #[test]
+    fn tokens_in_range() {
+        let doc = Document::new("test.qmd", "$a$\n\n$b$\n\n$c$\n");
+        let range = Range::new(Position::new(1, 0), Position::new(2, 0));
+        let tokens = get_semantic_tokens_in_range(&doc, range);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].range.start.line, 2);
+    }
+}
@@ -0,0 +1,316 @@
+//! Spell checking of prose.
+//!
+//! The words of a document's prose (see the `prose` module) are checked
+//! against a Hunspell [`Dictionary`]. Spell checking is turned on by a
+//! `spelling` option in the project configuration or the front matter,
+//! which overrides it:
+//!
+//! ```yaml
+//! spelling:
+//!   language: en_GB          # the language of the dictionary (en_US)
+//!   dictionary: dict/en_GB   # a dictionary of the project, rather than
+//!                            # one installed for the language
+//!   words: [Quarto, knitr]   # words that are spelled correctly
+//!   words-file: words.txt    # a file of such words, one per line
+//! ```
+//!
+//! `spelling: true` checks with the defaults. Misspelled words are reported
+//! with fixes replacing them with the dictionary's suggestions, and adding
+//! them to the words file.
+
+use std::collections::HashSet;
+
+use yaml_rust2::Yaml;
+
+use crate::bibliography::string_list;
+use crate::completions::front_matter;
+use crate::dictionary::Dictionary;
+use crate::document::Document;
+use crate::prose::{prose_blocks, words};
+use crate::types::{
+    Diagnostic, DiagnosticFix, DiagnosticSeverity, Location, Position, Range, TextEdit,
+};
+use crate::workspace::{Workspace, join_path};
+
+/// The language of the dictionary when none is configured.
+const DEFAULT_LANGUAGE: &str = "en_US";
+
+/// How a document is spell checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpellingConfig {
+    /// The language of the dictionary, such as `en_US`.
+    pub language: String,
+    /// The dictionary's path without the `.aff` and `.dic` extensions,
+    /// relative to the document's directory, if it isn't the dictionary
+    /// installed for the language.
+    pub dictionary: Option<String>,
+    /// The words that are spelled correctly, from the configuration and the
+    /// words file.
+    pub words: Vec<String>,
+    /// The words file, to add words to.
+    pub words_file: Option<WordsFile>,
+}
+
+impl Default for SpellingConfig {
+    fn default() -> Self {
+        Self {
+            language: DEFAULT_LANGUAGE.to_string(),
+            dictionary: None,
+            words: Vec::new(),
+            words_file: None,
+        }
+    }
+}
+
+/// A file of words that are spelled correctly, one per line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordsFile {
+    /// The file's path, relative to the document's directory.
+    pub path: String,
+    /// The end of the file, where words are added.
+    pub end: Position,
+    /// Whether the file ends with a line break.
+    pub ends_with_newline: bool,
+}
+
+/// Get how a document is spell checked, or `None` if it isn't.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Document, NoWorkspace, get_spelling_config};
+///
+/// let doc = Document::new("test.qmd", "---\nspelling:\n  words: [Quarto]\n---\n");
+/// let config = get_spelling_config(&doc, &NoWorkspace).unwrap();
+/// assert_eq!(config.language, "en_US");
+/// ```
+pub fn get_spelling_config(doc: &Document, workspace: &dyn Workspace) -> Option<SpellingConfig> {
+    let mut config: Option<SpellingConfig> = None;
+    let mut words_file: Option<String> = None;
+    let mut apply = |spelling: &Yaml, dir: &str| match spelling {
+        Yaml::Boolean(false) => config = None,
+        Yaml::Boolean(true) => {
+            config.get_or_insert_with(SpellingConfig::default);
+        }
+        Yaml::Hash(_) => {
+            let config = config.get_or_insert_with(SpellingConfig::default);
+            if let Some(language) = spelling["language"].as_str() {
+                config.language = language.to_string();
+                config.dictionary = None;
+            }
+            if let Some(dictionary) = spelling["dictionary"].as_str() {
+                config.dictionary = Some(join_path(dir, dictionary));
+            }
+            config.words.extend(string_list(&spelling["words"]));
+            if let Some(file) = spelling["words-file"].as_str() {
+                words_file = Some(join_path(dir, file));
+            }
+        }
+        _ => {}
+    };
+
+    if let Some(project_dir) = workspace.project_dir()
+        && let Some(project_config) = ["_quarto.yml", "_quarto.yaml"]
+            .iter()
+            .find_map(|name| workspace.read_file(&join_path(&project_dir, name)))
+        && let Ok(project_config) = quarto_yaml::parse(&project_config)
+    {
+        apply(&project_config.yaml["spelling"], &project_dir);
+    }
+    let lines: Vec<&str> = doc.content().lines().collect();
+    if let Some(meta) = front_matter(&lines) {
+        apply(&meta.yaml["spelling"], "");
+    }
+
+    let mut config = config?;
+    if let Some(path) = words_file
+        && let Some(content) = workspace.read_file(&path)
+    {
+        config.words.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(String::from),
+        );
+        let last_line = content.split('\n').next_back().unwrap_or_default();
+        config.words_file = Some(WordsFile {
+            path,
+            end: Position::new(
+                content.matches('\n').count() as u32,
+                last_line.chars().count() as u32,
+            ),
+            ends_with_newline: content.is_empty() || content.ends_with('\n'),
+        });
+    }
+    Some(config)
+}
+
+/// Get the misspelled words of a document's prose.
+///
+/// Every misspelling has a fix for each of the dictionary's suggestions,
+/// and one adding the word to the words file if there is one.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use quarto_lsp_core::{Dictionary, Document, get_spelling_diagnostics};
+///
+/// let doc = Document::new("test.qmd", "Teh end.\n");
+/// let diagnostics = get_spelling_diagnostics(&doc, &dictionary, &config);
+/// assert_eq!(diagnostics[0].fixes[0].title, "Change to `The`");
+/// ```
+pub fn get_spelling_diagnostics(
+    doc: &Document,
+    dictionary: &Dictionary,
+    config: &SpellingConfig,
+) -> Vec<Diagnostic> {
+    let content = doc.content();
+    let Ok((pandoc, _, _)) = pampa::readers::qmd::read(
+        doc.content_bytes(),
+        false, // loose mode
+        doc.filename(),
+        &mut std::io::sink(), // discard verbose output
+        true,                 // prune_errors
+        None,                 // parent_source_info
+    ) else {
+        return Vec::new();
+    };
+
+    let known: HashSet<&str> = config.words.iter().map(String::as_str).collect();
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let position = |offset: usize| {
+        let line = line_starts.partition_point(|&start| start <= offset) - 1;
+        let character = content[line_starts[line]..offset].chars().count();
+        Position::new(line as u32, character as u32)
+    };
+
+    let mut diagnostics = Vec::new();
+    for block in prose_blocks(&pandoc) {
+        for (range, word) in words(content, &block) {
+            let lower = word.to_lowercase();
+            if known.contains(word) || known.contains(lower.as_str()) || dictionary.check(word) {
+                continue;
+            }
+            let range = Range::new(position(range.start), position(range.end));
+            let mut diagnostic =
+                Diagnostic::new(range, DiagnosticSeverity::Information, "Misspelled Word")
+                    .with_code("Q-2-42")
+                    .with_problem(format!(
+                        "`{}` isn't in the {} dictionary",
+                        word, config.language
+                    ));
+            for suggestion in dictionary.suggest(word) {
+                diagnostic = diagnostic.with_fix(DiagnosticFix::new(
+                    format!("Change to `{}`", suggestion),
+                    vec![TextEdit::new(Location::in_document(range), suggestion)],
+                ));
+            }
+            if let Some(file) = &config.words_file {
+                let text = if file.ends_with_newline {
+                    format!("{}\n", word)
+                } else {
+                    format!("\n{}\n", word)
+                };
+                diagnostic = diagnostic.with_fix(DiagnosticFix::new(
+                    format!("Add `{}` to `{}`", word, file.path),
+                    vec![TextEdit::new(
+                        Location::in_file(file.path.clone(), Range::point(file.end)),
+                        text,
+                    )],
+                ));
+            }
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::{MemoryWorkspace, NoWorkspace};
+
+    const AFF: &str = "TRY esianrtolcdugmphbyfvkwz\n\nSFX S Y 1\nSFX S 0 s .\n";
+    const DIC: &str = "6\nthe/S\nend\nword/S\nis\nhere\nsection\n";
+
+    fn diagnostics(content: &str, config: &SpellingConfig) -> Vec<Diagnostic> {
+        let doc = Document::new("test.qmd", content);
+        get_spelling_diagnostics(&doc, &Dictionary::new(AFF, DIC), config)
+    }
+
+    #[test]
+    fn misspelled_words_and_suggestions() {
+        let results = diagnostics(
+            "# Section\n\nTeh wrod is `hree` here.\n",
+            &SpellingConfig::default(),
+        );
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].range,
+            Range::new(Position::new(2, 0), Position::new(2, 3))
+        );
+        assert_eq!(results[0].code.as_deref(), Some("Q-2-42"));
+        let titles: Vec<&str> = results[1]
+            .fixes
+            .iter()
+            .map(|fix| fix.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Change to `word`"]);
+        assert_eq!(results[1].fixes[0].edits[0].new_text, "word");
+    }
+
+    #[test]
+    fn configured_words() {
+        let config = SpellingConfig {
+            words: vec!["quarto".to_string()],
+            ..SpellingConfig::default()
+        };
+        assert!(diagnostics("Quarto is here.\n", &config).is_empty());
+    }
+
+    #[test]
+    fn config_from_project_and_front_matter() {
+        let workspace = MemoryWorkspace::default()
+            .with_project_dir("..")
+            .with_file(
+                "../_quarto.yml",
+                "spelling:\n  dictionary: dict/en_GB\n  words: [Quarto]\n  words-file: words.txt\n",
+            )
+            .with_file("../words.txt", "knitr\nPandoc");
+        let doc = Document::new(
+            "test.qmd",
+            "---\nspelling:\n  words: [Julia]\n---\n\nText.\n",
+        );
+        let config = get_spelling_config(&doc, &workspace).unwrap();
+        assert_eq!(config.language, "en_US");
+        assert_eq!(config.dictionary.as_deref(), Some("../dict/en_GB"));
+        assert_eq!(config.words, vec!["Quarto", "Julia", "knitr", "Pandoc"]);
+        let file = config.words_file.as_ref().unwrap();
+        assert_eq!(file.path, "../words.txt");
+        assert_eq!(file.end, Position::new(1, 6));
+
+        // Adding a word to the words file
+        let results = diagnostics("Nmae.\n", &config);
+        let fix = results[0].fixes.last().unwrap();
+        assert_eq!(fix.title, "Add `Nmae` to `../words.txt`");
+        assert_eq!(fix.edits[0].location.path.as_deref(), Some("../words.txt"));
+        assert_eq!(fix.edits[0].new_text, "\nNmae\n");
+    }
+
+    #[test]
+    fn spelling_is_off_unless_configured() {
+        let doc = Document::new("test.qmd", "Text.\n");
+        assert!(get_spelling_config(&doc, &NoWorkspace).is_none());
+        let doc = Document::new("test.qmd", "---\nspelling: true\n---\n\nText.\n");
+        assert!(get_spelling_config(&doc, &NoWorkspace).is_some());
+
+        let workspace = MemoryWorkspace::default()
+            .with_project_dir("")
+            .with_file("_quarto.yml", "spelling: true\n");
+        let doc = Document::new("test.qmd", "---\nspelling: false\n---\n\nText.\n");
+        assert!(get_spelling_config(&doc, &workspace).is_none());
+    }
+}
@@ -6,10 +6,9 @@
 //! Analysis transforms from `quarto-analysis` are run before extracting symbols
 //! to resolve shortcodes and other constructs that affect the document outline.
 
-use crate::analysis::analyze_document;
 use crate::document::Document;
 use crate::types::{FoldingRange, Position, Range, Symbol, SymbolKind};
-use pampa::pandoc::{Block, CodeBlock, Header, Inline, Inlines, Pandoc};
+use pampa::pandoc::{Block, CodeBlock, Header, Inlines, Pandoc};
 use quarto_analysis::DocumentAnalysisContext;
 use quarto_analysis::transforms::{
     AnalysisTransform, MetaShortcodeTransform, run_analysis_transforms,
@@ -53,6 +52,9 @@ pub fn get_symbols(doc: &Document) -> Vec<Symbol> {
             let transforms: Vec<&dyn AnalysisTransform> = vec![&MetaShortcodeTransform];
             let _ = run_analysis_transforms(&mut pandoc, &mut analysis_ctx, &transforms);
 
+            // Use the same section ids as the rendered TOC
+            pampa::toc::assign_section_ids(&mut pandoc.blocks);
+
             extract_symbols(&pandoc, &source_context, doc.content())
         }
         Err(_) => {
@@ -64,8 +66,8 @@ pub fn get_symbols(doc: &Document) -> Vec<Symbol> {
 
 /// Get folding ranges for code folding.
 ///
-/// This uses `analyze_document()` internally to extract folding ranges
-/// for YAML frontmatter, code cells, and sections.
+/// This uses the document's analysis (see `analyze_document()`) to get
+/// folding ranges for YAML frontmatter, code cells, and sections.
 ///
 /// # Example
 ///
@@ -79,7 +81,7 @@ pub fn get_symbols(doc: &Document) -> Vec<Symbol> {
 /// }
 /// ```
 pub fn get_folding_ranges(doc: &Document) -> Vec<FoldingRange> {
-    analyze_document(doc).folding_ranges
+    doc.analysis().folding_ranges.clone()
 }
 
 /// Extract symbols from a parsed Pandoc document.
@@ -148,12 +150,13 @@ fn header_to_symbol(header: &Header, ctx: &SourceContext, content: &str) -> Opti
     // Selection range is just the header text line
     let selection_range = range;
 
-    Some(Symbol::new(
-        name,
-        SymbolKind::String,
-        range,
-        selection_range,
-    ))
+    let symbol = Symbol::new(name, SymbolKind::String, range, selection_range);
+    let id = &header.attr.0;
+    Some(if id.is_empty() {
+        symbol
+    } else {
+        symbol.with_detail(format!("#{}", id))
+    })
 }
 
 /// Convert a code block to a Symbol (only for executable code blocks).
@@ -244,74 +247,7 @@ fn code_block_to_range(code_block: &CodeBlock, ctx: &SourceContext) -> Option<Ra
 
 /// Extract plain text from a list of inlines.
 fn inlines_to_text(inlines: &Inlines) -> String {
-    let mut text = String::new();
-    for inline in inlines {
-        inline_to_text(inline, &mut text);
-    }
-    text.trim().to_string()
-}
-
-/// Recursively extract text from an inline element.
-fn inline_to_text(inline: &Inline, text: &mut String) {
-    match inline {
-        Inline::Str(s) => text.push_str(&s.text),
-        Inline::Space(_) => text.push(' '),
-        Inline::SoftBreak(_) => text.push(' '),
-        Inline::LineBreak(_) => {} // Skip hard breaks
-        Inline::Emph(emph) => {
-            for child in &emph.content {
-                inline_to_text(child, text);
-            }
-        }
-        Inline::Strong(strong) => {
-            for child in &strong.content {
-                inline_to_text(child, text);
-            }
-        }
-        Inline::Strikeout(s) => {
-            for child in &s.content {
-                inline_to_text(child, text);
-            }
-        }
-        Inline::Superscript(s) => {
-            for child in &s.content {
-                inline_to_text(child, text);
-            }
-        }
-        Inline::Subscript(s) => {
-            for child in &s.content {
-                inline_to_text(child, text);
-            }
-        }
-        Inline::SmallCaps(s) => {
-            for child in &s.content {
-                inline_to_text(child, text);
-            }
-        }
-        Inline::Quoted(q) => {
-            for child in &q.content {
-                inline_to_text(child, text);
-            }
-        }
-        Inline::Link(link) => {
-            for child in &link.content {
-                inline_to_text(child, text);
-            }
-        }
-        Inline::Span(span) => {
-            for child in &span.content {
-                inline_to_text(child, text);
-            }
-        }
-        Inline::Code(code) => {
-            text.push_str(&code.text);
-        }
-        Inline::Math(math) => {
-            text.push_str(&math.text);
-        }
-        // Skip other inline types (Image, Note, RawInline, Cite)
-        _ => {}
-    }
+    pampa::toc::inlines_to_text(inlines).trim().to_string()
 }
 
 /// Build a hierarchical symbol structure from flat symbols with levels.
@@ -396,6 +332,21 @@ Final content.
         assert_eq!(symbols[1].name, "Section 2");
     }
 
+    #[test]
+    fn header_symbols_show_section_ids() {
+        let doc = Document::new(
+            "test.qmd",
+            "# Results\n\nText.\n\n# Results\n\n# Methods {#meth}\n",
+        );
+
+        let symbols = get_symbols(&doc);
+        let details: Vec<Option<&str>> = symbols.iter().map(|s| s.detail.as_deref()).collect();
+        assert_eq!(
+            details,
+            vec![Some("#results"), Some("#results-1"), Some("#meth")]
+        );
+    }
+
     #[test]
     fn extract_code_cells() {
         let doc = Document::new(
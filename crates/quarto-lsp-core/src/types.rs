@@ -155,6 +155,7 @@ impl DiagnosticDetail {
 /// - `problem`: What went wrong (the "must" or "can't" statement)
 /// - `details`: Specific information (bulleted, max 5 per tidyverse)
 /// - `hints`: Suggestions for fixing
+/// - `fixes`: Edits that fix the issue, offered as quick fixes
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Diagnostic {
@@ -179,6 +180,9 @@ pub struct Diagnostic {
     /// Suggestions for fixing the issue.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub hints: Vec<MessageContent>,
+    /// Edits that fix the issue.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub fixes: Vec<DiagnosticFix>,
 }
 
 impl Diagnostic {
@@ -193,6 +197,7 @@ impl Diagnostic {
             problem: None,
             details: Vec::new(),
             hints: Vec::new(),
+            fixes: Vec::new(),
         }
     }
 
@@ -220,6 +225,12 @@ impl Diagnostic {
         self
     }
 
+    /// Add a fix.
+    pub fn with_fix(mut self, fix: DiagnosticFix) -> Self {
+        self.fixes.push(fix);
+        self
+    }
+
     /// Get a combined message for simplified display (title + problem).
     ///
     /// This is useful for contexts that only support a single message string,
@@ -233,6 +244,38 @@ impl Diagnostic {
     }
 }
 
+/// A fix for a diagnostic, matching `quarto_error_reporting::Fix`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticFix {
+    /// Short description of the fix (e.g., "Insert closing `*`").
+    pub title: String,
+    /// The edits making the fix.
+    pub edits: Vec<TextEdit>,
+}
+
+impl DiagnosticFix {
+    /// Create a new fix.
+    pub fn new(title: impl Into<String>, edits: Vec<TextEdit>) -> Self {
+        Self {
+            title: title.into(),
+            edits,
+        }
+    }
+}
+
+/// A quick fix offered for a range of a document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeAction {
+    /// Short description of the action.
+    pub title: String,
+    /// The diagnostic the action fixes.
+    pub diagnostic: Diagnostic,
+    /// The edits making the action.
+    pub edits: Vec<TextEdit>,
+}
+
 /// Symbol kinds for document outline, matching LSP SymbolKind.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -405,6 +448,446 @@ impl FoldingRange {
     }
 }
 
+// ============================================================================
+// Completion Types
+// ============================================================================
+
+/// The kind of a completion item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CompletionItemKind {
+    /// A YAML key in front matter.
+    Key,
+    /// A YAML value in front matter.
+    Value,
+    /// A citation key from a bibliography.
+    Citation,
+    /// A cross-reference label (`@fig-plot`).
+    CrossRef,
+    /// A file path.
+    File,
+    /// A directory path.
+    Folder,
+    /// A shortcode name.
+    Shortcode,
+}
+
+/// A completion offered at a position in a document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionItem {
+    /// The text shown in the completion list.
+    pub label: String,
+    /// The kind of this completion.
+    pub kind: CompletionItemKind,
+    /// A short description, e.g., the title of a citation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Longer documentation, e.g., the schema description of a key.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+    /// The text to insert, if different from the label.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insert_text: Option<String>,
+    /// The range replaced by this completion (the word being typed).
+    pub range: Range,
+}
+
+impl CompletionItem {
+    /// Create a new completion item.
+    pub fn new(label: impl Into<String>, kind: CompletionItemKind, range: Range) -> Self {
+        Self {
+            label: label.into(),
+            kind,
+            detail: None,
+            documentation: None,
+            insert_text: None,
+            range,
+        }
+    }
+
+    /// Set the detail for this completion.
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Set the documentation for this completion.
+    pub fn with_documentation(mut self, documentation: impl Into<String>) -> Self {
+        self.documentation = Some(documentation.into());
+        self
+    }
+
+    /// Set the text to insert for this completion.
+    pub fn with_insert_text(mut self, insert_text: impl Into<String>) -> Self {
+        self.insert_text = Some(insert_text.into());
+        self
+    }
+
+    /// The text inserted when this completion is accepted.
+    pub fn text(&self) -> &str {
+        self.insert_text.as_deref().unwrap_or(&self.label)
+    }
+}
+
+// ============================================================================
+// Hover Types
+// ============================================================================
+
+/// Information shown when hovering over a position in a document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hover {
+    /// The hover text, as markdown.
+    pub contents: String,
+    /// The range of the hovered element.
+    pub range: Range,
+}
+
+impl Hover {
+    /// Create a new hover.
+    pub fn new(contents: impl Into<String>, range: Range) -> Self {
+        Self {
+            contents: contents.into(),
+            range,
+        }
+    }
+}
+
+// ============================================================================
+// Navigation Types
+// ============================================================================
+
+/// A range in the document or in another file of its workspace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    /// The file's path relative to the document's directory, or `None` for
+    /// the document itself.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// The range in the file.
+    pub range: Range,
+}
+
+impl Location {
+    /// A location in the document itself.
+    pub fn in_document(range: Range) -> Self {
+        Self { path: None, range }
+    }
+
+    /// A location in another file.
+    pub fn in_file(path: impl Into<String>, range: Range) -> Self {
+        Self {
+            path: Some(path.into()),
+            range,
+        }
+    }
+}
+
+/// A replacement of the text at a location.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    /// The text to replace.
+    pub location: Location,
+    /// The replacement text.
+    pub new_text: String,
+}
+
+impl TextEdit {
+    /// Create a new edit.
+    pub fn new(location: Location, new_text: impl Into<String>) -> Self {
+        Self {
+            location,
+            new_text: new_text.into(),
+        }
+    }
+}
+
+/// How documents are formatted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FormattingOptions {
+    /// Column at which paragraphs are wrapped, or `None` to keep their line
+    /// breaks.
+    pub wrap_width: Option<usize>,
+    /// Marker for bullet list items: `*`, `-` or `+`.
+    pub bullet_marker: char,
+    /// Write pipe table cells without padding them to their column's width.
+    pub compact_tables: bool,
+}
+
+impl Default for FormattingOptions {
+    fn default() -> Self {
+        Self {
+            wrap_width: None,
+            bullet_marker: '*',
+            compact_tables: false,
+        }
+    }
+}
+
+// ============================================================================
+// Semantic Token Types
+// ============================================================================
+
+/// The kind of a Quarto construct highlighted by a semantic token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SemanticTokenKind {
+    /// The `{{<` or `>}}` around a shortcode.
+    ShortcodeDelimiter,
+    /// The name of a shortcode (`var` in `{{< var foo >}}`).
+    ShortcodeName,
+    /// An argument of a shortcode.
+    ShortcodeArgument,
+    /// The comment prefix of a cell option line (`#|`).
+    CellOptionMarker,
+    /// The key of a cell option (`echo` in `#| echo: false`).
+    CellOptionKey,
+    /// The value of a cell option.
+    CellOptionValue,
+    /// A cross-reference (`@fig-plot`).
+    CrossRef,
+    /// An identifier in attributes (`#fig-plot` in `{#fig-plot}`).
+    AttributeId,
+    /// A class in attributes (`.callout-note`).
+    AttributeClass,
+    /// The key of a key-value attribute.
+    AttributeKey,
+    /// The value of a key-value attribute.
+    AttributeValue,
+    /// Inline or display math.
+    Math,
+    /// The format of a raw block or inline (`=latex` in `{=latex}`).
+    RawFormat,
+}
+
+/// A highlighted Quarto construct.
+///
+/// Tokens never span lines: constructs over several lines (such as display
+/// math) give one token per line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticToken {
+    /// The range of the token, within one line.
+    pub range: Range,
+    /// The kind of construct.
+    pub kind: SemanticTokenKind,
+}
+
+impl SemanticToken {
+    /// Create a new semantic token.
+    pub fn new(range: Range, kind: SemanticTokenKind) -> Self {
+        Self { range, kind }
+    }
+}
+
+// ============================================================================
+// Inlay Hint Types
+// ============================================================================
+
+/// What an inlay hint shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InlayHintKind {
+    /// A cell option a code cell inherits from the front matter or the
+    /// project configuration (`echo: false`).
+    CellOption,
+    /// The number of a cross-reference (`Figure 3`).
+    CrossRef,
+}
+
+/// A label shown inline in the editor, after a position.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHint {
+    /// Where the label is shown.
+    pub position: Position,
+    /// The label.
+    pub label: String,
+    /// What the label shows.
+    pub kind: InlayHintKind,
+    /// More about the label, shown on hover.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<String>,
+}
+
+impl InlayHint {
+    /// Create a new inlay hint.
+    pub fn new(position: Position, label: impl Into<String>, kind: InlayHintKind) -> Self {
+        Self {
+            position,
+            label: label.into(),
+            kind,
+            tooltip: None,
+        }
+    }
+
+    /// Set the tooltip.
+    pub fn with_tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+}
+
+// ============================================================================
+// Selection Range Types
+// ============================================================================
+
+/// A range to select at a position, within the larger ranges that expanding
+/// the selection goes through.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectionRange {
+    /// The range.
+    pub range: Range,
+    /// The next larger range, containing this one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Box<SelectionRange>>,
+}
+
+impl SelectionRange {
+    /// Create a selection range without a parent.
+    pub fn new(range: Range) -> Self {
+        Self {
+            range,
+            parent: None,
+        }
+    }
+
+    /// Set the next larger range.
+    pub fn with_parent(mut self, parent: SelectionRange) -> Self {
+        self.parent = Some(Box::new(parent));
+        self
+    }
+
+    /// The ranges from this one to the largest.
+    pub fn ranges(&self) -> Vec<Range> {
+        std::iter::successors(Some(self), |selection| selection.parent.as_deref())
+            .map(|selection| selection.range)
+            .collect()
+    }
+}
+
+// ============================================================================
+// Document Link Types
+// ============================================================================
+
+/// What a document link comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DocumentLinkKind {
+    /// A link (`[text](target)`) or autolink (`<https://quarto.org>`).
+    Link,
+    /// An image (`![alt](plot.png)`).
+    Image,
+    /// An include shortcode (`{{< include _intro.qmd >}}`).
+    Include,
+    /// A bibliography file in the front matter.
+    Bibliography,
+}
+
+/// A link from a range of the document to a URL or file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentLink {
+    /// The range of the target in the document.
+    pub range: Range,
+    /// The target as written: a URL, or a file path relative to the
+    /// document's directory (or to the project directory if it starts with
+    /// `/`), possibly with a `#fragment`.
+    pub target: String,
+    /// What the link comes from.
+    pub kind: DocumentLinkKind,
+}
+
+impl DocumentLink {
+    /// Create a new document link.
+    pub fn new(range: Range, target: impl Into<String>, kind: DocumentLinkKind) -> Self {
+        Self {
+            range,
+            target: target.into(),
+            kind,
+        }
+    }
+
+    /// Whether the target is a URL (`https://quarto.org`) rather than a file.
+    pub fn is_url(&self) -> bool {
+        self.target.split_once(':').is_some_and(|(scheme, _)| {
+            scheme.len() > 1
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        })
+    }
+}
+
+// ============================================================================
+// Embedded Document Types
+// ============================================================================
+
+/// The code of the executable cells of one language in a document, as a
+/// document of its own (a "virtual document"), so that editors can have a
+/// language server for that language analyze it.
+///
+/// The embedded document has the same lines as the document: the lines of
+/// the cells' code, without the indentation of their fences, and blank
+/// lines everywhere else.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedDocument {
+    /// The language of the cells (`python`, `r`, `julia`, `ojs`).
+    pub language: String,
+    /// The code.
+    pub content: String,
+    /// The ranges of the cells' code in the document, without their fences.
+    /// Each range starts at the indentation of its cell's fence.
+    pub cells: Vec<Range>,
+}
+
+impl EmbeddedDocument {
+    /// The cell whose code contains a line of the document.
+    fn cell(&self, line: u32) -> Option<&Range> {
+        self.cells
+            .iter()
+            .find(|cell| cell.start.line <= line && line <= cell.end.line)
+    }
+
+    /// The position in the embedded document of a position in the
+    /// document, or `None` if it isn't in the code of one of the cells.
+    pub fn to_embedded(&self, position: Position) -> Option<Position> {
+        let cell = self.cell(position.line)?;
+        let character = position.character.saturating_sub(cell.start.character);
+        Some(Position::new(position.line, character))
+    }
+
+    /// The position in the document of a position in the embedded document.
+    pub fn to_document(&self, position: Position) -> Position {
+        let indent = self
+            .cell(position.line)
+            .map_or(0, |cell| cell.start.character);
+        Position::new(position.line, position.character + indent)
+    }
+
+    /// The range in the document of a range in the embedded document, such
+    /// as the range of a diagnostic or an edit from another language server.
+    pub fn range_to_document(&self, range: Range) -> Range {
+        Range::new(self.to_document(range.start), self.to_document(range.end))
+    }
+}
+
+/// A position in the embedded document of a cell.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedPosition {
+    /// The embedded document of the cell's language.
+    pub document: EmbeddedDocument,
+    /// The position in the embedded document.
+    pub position: Position,
+}
+
 // ============================================================================
 // Rich Diagnostic Types (matching quarto-error-reporting::DiagnosticMessage)
 // ============================================================================
@@ -498,6 +981,7 @@ use quarto_source_map::SourceContext;
 /// - Symbols for document outline and navigation
 /// - Folding ranges for code folding
 /// - Diagnostics for errors and warnings
+/// - Links to URLs and files
 /// - Source context for location mapping (internal use)
 ///
 /// Using this struct is more efficient than calling separate functions,
@@ -510,6 +994,8 @@ pub struct DocumentAnalysis {
     pub folding_ranges: Vec<FoldingRange>,
     /// Diagnostics (errors and warnings).
     pub diagnostics: Vec<Diagnostic>,
+    /// Links to URLs and files.
+    pub document_links: Vec<DocumentLink>,
     /// Source context for byte offset → line/column mapping.
     /// This is for internal use and is not serialized.
     pub source_context: SourceContext,
@@ -522,6 +1008,7 @@ impl DocumentAnalysis {
             symbols: Vec::new(),
             folding_ranges: Vec::new(),
             diagnostics: Vec::new(),
+            document_links: Vec::new(),
             source_context,
         }
     }
@@ -531,12 +1018,14 @@ impl DocumentAnalysis {
         symbols: Vec<Symbol>,
         folding_ranges: Vec<FoldingRange>,
         diagnostics: Vec<Diagnostic>,
+        document_links: Vec<DocumentLink>,
         source_context: SourceContext,
     ) -> Self {
         Self {
             symbols,
             folding_ranges,
             diagnostics,
+            document_links,
             source_context,
         }
     }
@@ -554,6 +1043,20 @@ pub struct DocumentAnalysisJson {
     pub folding_ranges: Vec<FoldingRange>,
     /// Diagnostics (errors and warnings).
     pub diagnostics: Vec<Diagnostic>,
+    /// Links to URLs and files.
+    pub document_links: Vec<DocumentLink>,
+    /// Completions that don't depend on the cursor, e.g. the cross-reference
+    /// labels and citation keys to complete after `@`.
+    #[serde(default)]
+    pub completion_items: Vec<CompletionItem>,
+}
+
+impl DocumentAnalysisJson {
+    /// Add completion items to the analysis.
+    pub fn with_completion_items(mut self, items: Vec<CompletionItem>) -> Self {
+        self.completion_items = items;
+        self
+    }
 }
 
 impl From<&DocumentAnalysis> for DocumentAnalysisJson {
@@ -562,6 +1065,8 @@ impl From<&DocumentAnalysis> for DocumentAnalysisJson {
             symbols: analysis.symbols.clone(),
             folding_ranges: analysis.folding_ranges.clone(),
             diagnostics: analysis.diagnostics.clone(),
+            document_links: analysis.document_links.clone(),
+            completion_items: Vec::new(),
         }
     }
 }
@@ -572,6 +1077,8 @@ impl From<DocumentAnalysis> for DocumentAnalysisJson {
             symbols: analysis.symbols,
             folding_ranges: analysis.folding_ranges,
             diagnostics: analysis.diagnostics,
+            document_links: analysis.document_links,
+            completion_items: Vec::new(),
         }
     }
 }
@@ -0,0 +1,130 @@
+//! Access to the files around a document.
+//!
+//! Completion and hover read bibliographies, the project configuration and
+//! directory listings through the [`Workspace`] trait, so the same analysis
+//! runs natively (reading the filesystem) and in WASM (reading from a
+//! virtual file system).
+
+/// A directory entry in the workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkspaceEntry {
+    /// The entry's file name.
+    pub name: String,
+    /// Whether the entry is a directory.
+    pub is_dir: bool,
+}
+
+impl WorkspaceEntry {
+    /// A file entry.
+    pub fn file(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            is_dir: false,
+        }
+    }
+
+    /// A directory entry.
+    pub fn dir(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            is_dir: true,
+        }
+    }
+}
+
+/// Access to the files around a document.
+///
+/// All paths are relative to the directory of the document being analyzed,
+/// using `/` as the separator.
+pub trait Workspace {
+    /// Read a file, or `None` if it doesn't exist or can't be read.
+    fn read_file(&self, path: &str) -> Option<String>;
+
+    /// The entries of a directory (`""` is the document's directory).
+    fn read_dir(&self, path: &str) -> Vec<WorkspaceEntry>;
+
+    /// The project directory (containing `_quarto.yml`), if the document is
+    /// in a project.
+    fn project_dir(&self) -> Option<String> {
+        None
+    }
+}
+
+/// A workspace without files, for documents that only exist in memory.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoWorkspace;
+
+impl Workspace for NoWorkspace {
+    fn read_file(&self, _path: &str) -> Option<String> {
+        None
+    }
+
+    fn read_dir(&self, _path: &str) -> Vec<WorkspaceEntry> {
+        Vec::new()
+    }
+}
+
+/// Join a workspace-relative directory and a path.
+pub(crate) fn join_path(dir: &str, path: &str) -> String {
+    if dir.is_empty() || dir == "." {
+        path.to_string()
+    } else if path.is_empty() {
+        dir.to_string()
+    } else {
+        format!("{}/{}", dir.trim_end_matches('/'), path)
+    }
+}
+
+/// A workspace backed by a map of paths to contents.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct MemoryWorkspace {
+    files: std::collections::HashMap<String, String>,
+    project_dir: Option<String>,
+}
+
+#[cfg(test)]
+impl MemoryWorkspace {
+    pub(crate) fn with_file(mut self, path: &str, content: &str) -> Self {
+        self.files.insert(path.to_string(), content.to_string());
+        self
+    }
+
+    pub(crate) fn with_project_dir(mut self, dir: &str) -> Self {
+        self.project_dir = Some(dir.to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+impl Workspace for MemoryWorkspace {
+    fn read_file(&self, path: &str) -> Option<String> {
+        self.files.get(path).cloned()
+    }
+
+    fn read_dir(&self, path: &str) -> Vec<WorkspaceEntry> {
+        let prefix = if path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", path)
+        };
+        let mut entries: Vec<WorkspaceEntry> = Vec::new();
+        for file in self.files.keys() {
+            let Some(rest) = file.strip_prefix(&prefix) else {
+                continue;
+            };
+            let entry = match rest.split_once('/') {
+                Some((dir, _)) => WorkspaceEntry::dir(dir),
+                None => WorkspaceEntry::file(rest),
+            };
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+        entries
+    }
+
+    fn project_dir(&self) -> Option<String> {
+        self.project_dir.clone()
+    }
+}
@@ -1,21 +1,51 @@
 //! LSP capability negotiation.
 
 use tower_lsp::lsp_types::{
-    OneOf, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
-    TextDocumentSyncOptions,
+    ClientCapabilities, CodeActionKind, CodeActionOptions, CodeActionProviderCapability,
+    CompletionOptions, DocumentLinkOptions, HoverProviderCapability, OneOf, RenameOptions,
+    SelectionRangeProviderCapability, SemanticTokenModifier, SemanticTokenType,
+    SemanticTokensFullOptions, SemanticTokensLegend, SemanticTokensOptions,
+    SemanticTokensServerCapabilities, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextDocumentSyncOptions,
 };
 
-/// Get the server capabilities to report to the client.
-pub fn server_capabilities() -> ServerCapabilities {
+/// The token types of semantic tokens, indexed by their `token_type`.
+pub const SEMANTIC_TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::MACRO,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::STRING,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::CLASS,
+    SemanticTokenType::KEYWORD,
+];
+
+/// The token modifiers of semantic tokens, indexed by their bit in
+/// `token_modifiers_bitset`.
+pub const SEMANTIC_TOKEN_MODIFIERS: &[SemanticTokenModifier] =
+    &[SemanticTokenModifier::DECLARATION];
+
+/// Get the server capabilities to report to a client.
+///
+/// Semantic tokens are only offered to clients that support them, since
+/// clients that don't may otherwise still request them.
+pub fn server_capabilities(client: &ClientCapabilities) -> ServerCapabilities {
+    let semantic_tokens_supported = client
+        .text_document
+        .as_ref()
+        .is_some_and(|text_document| text_document.semantic_tokens.is_some());
+
     ServerCapabilities {
         // Text document synchronization
         text_document_sync: Some(TextDocumentSyncCapability::Options(
             TextDocumentSyncOptions {
                 // We want to know when documents are opened/closed
                 open_close: Some(true),
-                // Full document sync for now (simpler implementation)
-                // Can be changed to Incremental later for better performance
-                change: Some(TextDocumentSyncKind::FULL),
+                // Incremental sync, so that documents are re-parsed and
+                // re-analyzed only where they changed
+                change: Some(TextDocumentSyncKind::INCREMENTAL),
                 // We don't need will_save notifications
                 will_save: None,
                 will_save_wait_until: None,
@@ -27,12 +57,70 @@ pub fn server_capabilities() -> ServerCapabilities {
         // Document symbols (outline)
         document_symbol_provider: Some(OneOf::Left(true)),
 
-        // Features to be added in future phases:
-        // hover_provider: Some(HoverProviderCapability::Simple(true)),
-        // completion_provider: Some(CompletionOptions { ... }),
-        // definition_provider: Some(OneOf::Left(true)),
-        // references_provider: Some(OneOf::Left(true)),
-        // document_formatting_provider: Some(OneOf::Left(true)),
+        // Completion of front matter, citations, crossrefs, paths and shortcodes
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(
+                ["@", "/", "<", ":", "("]
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect(),
+            ),
+            ..Default::default()
+        }),
+
+        // Hover documentation for options, citations, crossrefs and links
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+
+        // Go to the definitions and uses of labels, citations and includes
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+
+        // Renaming labels and citation keys, checked before the user types
+        // the new name
+        rename_provider: Some(OneOf::Right(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: Default::default(),
+        })),
+
+        // Formatting through the qmd writer
+        document_formatting_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
+
+        // Quick fixes attached to diagnostics
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+            ..Default::default()
+        })),
+
+        // Highlighting of Quarto constructs that markdown grammars miss
+        semantic_tokens_provider: semantic_tokens_supported.then(|| {
+            SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+                legend: SemanticTokensLegend {
+                    token_types: SEMANTIC_TOKEN_TYPES.to_vec(),
+                    token_modifiers: SEMANTIC_TOKEN_MODIFIERS.to_vec(),
+                },
+                range: Some(true),
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                work_done_progress_options: Default::default(),
+            })
+        }),
+
+        // Expanding the selection from a word to its section
+        selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+
+        // Links to URLs, images, included files and bibliographies
+        document_link_provider: Some(DocumentLinkOptions {
+            resolve_provider: Some(false),
+            work_done_progress_options: Default::default(),
+        }),
+
+        // Inherited cell options and cross-reference numbers
+        inlay_hint_provider: Some(OneOf::Left(true)),
+
+        // The code of executable cells as documents for other language
+        // servers (`quarto/embeddedDocuments`, `quarto/embeddedDocumentAt`)
+        experimental: Some(serde_json::json!({ "embeddedDocuments": true })),
+
         ..Default::default()
     }
 }
@@ -43,13 +131,131 @@ mod tests {
 
     #[test]
     fn capabilities_include_document_sync() {
-        let caps = server_capabilities();
-        assert!(caps.text_document_sync.is_some());
+        let caps = server_capabilities(&ClientCapabilities::default());
+        match caps.text_document_sync {
+            Some(TextDocumentSyncCapability::Options(options)) => {
+                assert_eq!(options.change, Some(TextDocumentSyncKind::INCREMENTAL));
+            }
+            other => panic!("Expected text document sync options, got {:?}", other),
+        }
     }
 
     #[test]
     fn capabilities_include_document_symbols() {
-        let caps = server_capabilities();
+        let caps = server_capabilities(&ClientCapabilities::default());
         assert!(caps.document_symbol_provider.is_some());
     }
+
+    #[test]
+    fn capabilities_include_completion() {
+        let caps = server_capabilities(&ClientCapabilities::default());
+        let completion = caps.completion_provider.expect("completion provider");
+        assert!(
+            completion
+                .trigger_characters
+                .unwrap()
+                .contains(&"@".to_string())
+        );
+    }
+
+    #[test]
+    fn capabilities_include_hover() {
+        let caps = server_capabilities(&ClientCapabilities::default());
+        assert_eq!(
+            caps.hover_provider,
+            Some(HoverProviderCapability::Simple(true))
+        );
+    }
+
+    #[test]
+    fn capabilities_include_navigation() {
+        let caps = server_capabilities(&ClientCapabilities::default());
+        assert_eq!(caps.definition_provider, Some(OneOf::Left(true)));
+        assert_eq!(caps.references_provider, Some(OneOf::Left(true)));
+    }
+
+    #[test]
+    fn capabilities_include_formatting() {
+        let caps = server_capabilities(&ClientCapabilities::default());
+        assert_eq!(caps.document_formatting_provider, Some(OneOf::Left(true)));
+        assert_eq!(
+            caps.document_range_formatting_provider,
+            Some(OneOf::Left(true))
+        );
+    }
+
+    #[test]
+    fn capabilities_include_quick_fixes() {
+        let caps = server_capabilities(&ClientCapabilities::default());
+        match caps.code_action_provider {
+            Some(CodeActionProviderCapability::Options(options)) => assert_eq!(
+                options.code_action_kinds,
+                Some(vec![CodeActionKind::QUICKFIX])
+            ),
+            other => panic!("Expected code action options, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn capabilities_include_selection_ranges() {
+        let caps = server_capabilities(&ClientCapabilities::default());
+        assert_eq!(
+            caps.selection_range_provider,
+            Some(SelectionRangeProviderCapability::Simple(true))
+        );
+    }
+
+    #[test]
+    fn capabilities_include_document_links() {
+        let caps = server_capabilities(&ClientCapabilities::default());
+        let options = caps.document_link_provider.expect("document link options");
+        assert_eq!(options.resolve_provider, Some(false));
+    }
+
+    #[test]
+    fn capabilities_include_inlay_hints() {
+        let caps = server_capabilities(&ClientCapabilities::default());
+        assert_eq!(caps.inlay_hint_provider, Some(OneOf::Left(true)));
+    }
+
+    #[test]
+    fn capabilities_include_embedded_documents() {
+        let caps = server_capabilities(&ClientCapabilities::default());
+        let experimental = caps.experimental.expect("experimental capabilities");
+        assert_eq!(experimental["embeddedDocuments"], true);
+    }
+
+    #[test]
+    fn capabilities_include_rename() {
+        let caps = server_capabilities(&ClientCapabilities::default());
+        match caps.rename_provider {
+            Some(OneOf::Right(options)) => assert_eq!(options.prepare_provider, Some(true)),
+            other => panic!("Expected rename options, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn capabilities_include_semantic_tokens_for_supporting_clients() {
+        use tower_lsp::lsp_types::{
+            SemanticTokensClientCapabilities, TextDocumentClientCapabilities,
+        };
+
+        let caps = server_capabilities(&ClientCapabilities::default());
+        assert!(caps.semantic_tokens_provider.is_none());
+
+        let client = ClientCapabilities {
+            text_document: Some(TextDocumentClientCapabilities {
+                semantic_tokens: Some(SemanticTokensClientCapabilities::default()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        match server_capabilities(&client).semantic_tokens_provider {
+            Some(SemanticTokensServerCapabilities::SemanticTokensOptions(options)) => {
+                assert_eq!(options.legend.token_types, SEMANTIC_TOKEN_TYPES);
+                assert_eq!(options.range, Some(true));
+            }
+            other => panic!("Expected semantic token options, got {:?}", other),
+        }
+    }
 }
@@ -1,12 +1,30 @@
 //! Conversion between quarto-lsp-core types and tower_lsp::lsp_types.
 
+use std::collections::HashMap;
+
 use tower_lsp::lsp_types::{
-    Diagnostic as LspDiagnostic, DiagnosticSeverity as LspSeverity,
-    DocumentSymbol as LspDocumentSymbol, NumberOrString, Position as LspPosition,
-    Range as LspRange, SymbolKind as LspSymbolKind,
+    CodeAction as LspCodeAction, CodeActionKind, CompletionItem as LspCompletionItem,
+    CompletionItemKind as LspCompletionItemKind, CompletionTextEdit, Diagnostic as LspDiagnostic,
+    DiagnosticSeverity as LspSeverity, DocumentLink as LspDocumentLink,
+    DocumentSymbol as LspDocumentSymbol, Documentation, Hover as LspHover, HoverContents,
+    InlayHint as LspInlayHint, InlayHintKind as LspInlayHintKind, InlayHintLabel, InlayHintTooltip,
+    Location as LspLocation, MarkupContent, MarkupKind, NumberOrString, Position as LspPosition,
+    Range as LspRange, SelectionRange as LspSelectionRange, SemanticToken as LspSemanticToken,
+    SemanticTokenType, SymbolKind as LspSymbolKind, TextEdit as LspTextEdit, Url, WorkspaceEdit,
+};
+
+use quarto_lsp_core::types::{
+    CodeAction, CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, DocumentLink,
+    DocumentLinkKind, Hover, InlayHint, InlayHintKind, Location, Position, Range, SelectionRange,
+    SemanticToken, SemanticTokenKind, Symbol, SymbolKind, TextEdit,
 };
 
-use quarto_lsp_core::types::{Diagnostic, DiagnosticSeverity, Position, Range, Symbol, SymbolKind};
+use crate::capabilities::SEMANTIC_TOKEN_TYPES;
+
+/// Convert an lsp-types Position to a quarto-lsp-core Position.
+pub fn position_from_lsp(pos: &LspPosition) -> Position {
+    Position::new(pos.line, pos.character)
+}
 
 /// Convert a quarto-lsp-core Position to an lsp-types Position.
 pub fn position_to_lsp(pos: &Position) -> LspPosition {
@@ -16,6 +34,14 @@ pub fn position_to_lsp(pos: &Position) -> LspPosition {
     }
 }
 
+/// Convert an lsp-types Range to a quarto-lsp-core Range.
+pub fn range_from_lsp(range: &LspRange) -> Range {
+    Range::new(
+        position_from_lsp(&range.start),
+        position_from_lsp(&range.end),
+    )
+}
+
 /// Convert a quarto-lsp-core Range to an lsp-types Range.
 pub fn range_to_lsp(range: &Range) -> LspRange {
     LspRange {
@@ -131,6 +157,223 @@ pub fn symbol_to_lsp(symbol: &Symbol) -> LspDocumentSymbol {
     }
 }
 
+/// Convert a quarto-lsp-core CompletionItemKind to an lsp-types CompletionItemKind.
+pub fn completion_kind_to_lsp(kind: &CompletionItemKind) -> LspCompletionItemKind {
+    match kind {
+        CompletionItemKind::Key => LspCompletionItemKind::PROPERTY,
+        CompletionItemKind::Value => LspCompletionItemKind::VALUE,
+        CompletionItemKind::Citation | CompletionItemKind::CrossRef => {
+            LspCompletionItemKind::REFERENCE
+        }
+        CompletionItemKind::File => LspCompletionItemKind::FILE,
+        CompletionItemKind::Folder => LspCompletionItemKind::FOLDER,
+        CompletionItemKind::Shortcode => LspCompletionItemKind::FUNCTION,
+    }
+}
+
+/// Convert a quarto-lsp-core CompletionItem to an lsp-types CompletionItem.
+///
+/// The item replaces the word being typed through a text edit, so clients
+/// don't need to agree with us on word boundaries (keys contain `-`,
+/// citation keys contain `:`).
+pub fn completion_to_lsp(item: &CompletionItem) -> LspCompletionItem {
+    LspCompletionItem {
+        label: item.label.clone(),
+        kind: Some(completion_kind_to_lsp(&item.kind)),
+        detail: item.detail.clone(),
+        documentation: item.documentation.clone().map(Documentation::String),
+        text_edit: Some(CompletionTextEdit::Edit(LspTextEdit {
+            range: range_to_lsp(&item.range),
+            new_text: item.text().to_string(),
+        })),
+        ..Default::default()
+    }
+}
+
+/// Convert a quarto-lsp-core Hover to an lsp-types Hover, with markdown contents.
+pub fn hover_to_lsp(hover: &Hover) -> LspHover {
+    LspHover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: hover.contents.clone(),
+        }),
+        range: Some(range_to_lsp(&hover.range)),
+    }
+}
+
+/// Convert a quarto-lsp-core Location to an lsp-types Location.
+///
+/// Paths are relative to the directory of the document at `document_uri`;
+/// a location without a path is in the document itself.
+pub fn location_to_lsp(location: &Location, document_uri: &Url) -> Option<LspLocation> {
+    let uri = match &location.path {
+        Some(path) => document_uri.join(path).ok()?,
+        None => document_uri.clone(),
+    };
+    Some(LspLocation {
+        uri,
+        range: range_to_lsp(&location.range),
+    })
+}
+
+/// Convert quarto-lsp-core TextEdits within a document to lsp-types TextEdits.
+pub fn text_edits_to_lsp(edits: &[TextEdit]) -> Vec<LspTextEdit> {
+    edits
+        .iter()
+        .map(|edit| LspTextEdit {
+            range: range_to_lsp(&edit.location.range),
+            new_text: edit.new_text.clone(),
+        })
+        .collect()
+}
+
+/// Convert quarto-lsp-core TextEdits to an lsp-types WorkspaceEdit, grouping
+/// the edits by file.
+///
+/// Paths are relative to the directory of the document at `document_uri`.
+pub fn workspace_edit_to_lsp(edits: &[TextEdit], document_uri: &Url) -> WorkspaceEdit {
+    let mut changes: HashMap<Url, Vec<LspTextEdit>> = HashMap::new();
+    for edit in edits {
+        if let Some(location) = location_to_lsp(&edit.location, document_uri) {
+            changes.entry(location.uri).or_default().push(LspTextEdit {
+                range: location.range,
+                new_text: edit.new_text.clone(),
+            });
+        }
+    }
+    WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    }
+}
+
+/// Convert a quarto-lsp-core CodeAction to an lsp-types quick fix.
+pub fn code_action_to_lsp(action: &CodeAction, document_uri: &Url) -> LspCodeAction {
+    LspCodeAction {
+        title: action.title.clone(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic_to_lsp(&action.diagnostic)]),
+        edit: Some(workspace_edit_to_lsp(&action.edits, document_uri)),
+        ..Default::default()
+    }
+}
+
+/// Convert a quarto-lsp-core InlayHint to lsp-types.
+///
+/// Inherited cell options are shown like parameters; cross-reference
+/// numbers have no matching kind.
+pub fn inlay_hint_to_lsp(hint: &InlayHint) -> LspInlayHint {
+    LspInlayHint {
+        position: position_to_lsp(&hint.position),
+        label: InlayHintLabel::String(hint.label.clone()),
+        kind: match hint.kind {
+            InlayHintKind::CellOption => Some(LspInlayHintKind::PARAMETER),
+            InlayHintKind::CrossRef => None,
+        },
+        text_edits: None,
+        tooltip: hint.tooltip.clone().map(InlayHintTooltip::String),
+        padding_left: Some(true),
+        padding_right: None,
+        data: None,
+    }
+}
+
+/// Convert a quarto-lsp-core SelectionRange and its parents to lsp-types.
+pub fn selection_range_to_lsp(selection: &SelectionRange) -> LspSelectionRange {
+    LspSelectionRange {
+        range: range_to_lsp(&selection.range),
+        parent: selection
+            .parent
+            .as_deref()
+            .map(|parent| Box::new(selection_range_to_lsp(parent))),
+    }
+}
+
+/// Convert a quarto-lsp-core DocumentLink to lsp-types.
+///
+/// File targets are relative to the directory of the document at
+/// `document_uri`, or to the project directory at `project_uri` if they
+/// start with `/`. Returns `None` for targets that can't be resolved.
+pub fn document_link_to_lsp(
+    link: &DocumentLink,
+    document_uri: &Url,
+    project_uri: Option<&Url>,
+) -> Option<LspDocumentLink> {
+    let target = if link.is_url() {
+        Url::parse(&link.target).ok()?
+    } else if let Some(path) = link.target.strip_prefix('/') {
+        project_uri?.join(path).ok()?
+    } else {
+        document_uri.join(&link.target).ok()?
+    };
+    let tooltip = match link.kind {
+        DocumentLinkKind::Link | DocumentLinkKind::Image => None,
+        DocumentLinkKind::Include => Some("Open included file"),
+        DocumentLinkKind::Bibliography => Some("Open bibliography"),
+    };
+    Some(LspDocumentLink {
+        range: range_to_lsp(&link.range),
+        target: Some(target),
+        tooltip: tooltip.map(String::from),
+        data: None,
+    })
+}
+
+/// The legend index and modifier bits of a quarto-lsp-core SemanticTokenKind.
+///
+/// Attribute identifiers declare the labels that cross-references use, so
+/// both are variables and identifiers carry the declaration modifier.
+pub fn semantic_token_kind_to_lsp(kind: &SemanticTokenKind) -> (u32, u32) {
+    let (token_type, modifiers) = match kind {
+        SemanticTokenKind::ShortcodeDelimiter => (SemanticTokenType::OPERATOR, 0),
+        SemanticTokenKind::ShortcodeName => (SemanticTokenType::MACRO, 0),
+        SemanticTokenKind::ShortcodeArgument => (SemanticTokenType::PARAMETER, 0),
+        SemanticTokenKind::CellOptionMarker => (SemanticTokenType::COMMENT, 0),
+        SemanticTokenKind::CellOptionKey | SemanticTokenKind::AttributeKey => {
+            (SemanticTokenType::PROPERTY, 0)
+        }
+        SemanticTokenKind::CellOptionValue
+        | SemanticTokenKind::AttributeValue
+        | SemanticTokenKind::Math => (SemanticTokenType::STRING, 0),
+        SemanticTokenKind::CrossRef => (SemanticTokenType::VARIABLE, 0),
+        SemanticTokenKind::AttributeId => (SemanticTokenType::VARIABLE, 1),
+        SemanticTokenKind::AttributeClass => (SemanticTokenType::CLASS, 0),
+        SemanticTokenKind::RawFormat => (SemanticTokenType::KEYWORD, 0),
+    };
+    let index = SEMANTIC_TOKEN_TYPES
+        .iter()
+        .position(|legend_type| *legend_type == token_type)
+        .expect("semantic token type is in the legend");
+    (index as u32, modifiers)
+}
+
+/// Convert quarto-lsp-core SemanticTokens, in document order, to the
+/// relative encoding of lsp-types SemanticTokens.
+pub fn semantic_tokens_to_lsp(tokens: &[SemanticToken]) -> Vec<LspSemanticToken> {
+    let mut previous = Position::default();
+    tokens
+        .iter()
+        .map(|token| {
+            let start = token.range.start;
+            let delta_line = start.line - previous.line;
+            let delta_start = if delta_line == 0 {
+                start.character - previous.character
+            } else {
+                start.character
+            };
+            previous = start;
+            let (token_type, token_modifiers_bitset) = semantic_token_kind_to_lsp(&token.kind);
+            LspSemanticToken {
+                delta_line,
+                delta_start,
+                length: token.range.end.character - start.character,
+                token_type,
+                token_modifiers_bitset,
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,4 +467,194 @@ mod tests {
         assert!(lsp_diag.message.contains("Hints:"));
         assert!(lsp_diag.message.contains("Did you mean 'format'?"));
     }
+
+    #[test]
+    fn test_completion_conversion() {
+        let core_item = CompletionItem::new(
+            "toc-depth",
+            CompletionItemKind::Key,
+            Range::new(Position::new(1, 0), Position::new(1, 3)),
+        )
+        .with_detail("Depth of the table of contents")
+        .with_insert_text("toc-depth: ");
+
+        let lsp_item = completion_to_lsp(&core_item);
+        assert_eq!(lsp_item.label, "toc-depth");
+        assert_eq!(lsp_item.kind, Some(LspCompletionItemKind::PROPERTY));
+        match lsp_item.text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => {
+                assert_eq!(edit.new_text, "toc-depth: ");
+                assert_eq!(edit.range.end.character, 3);
+            }
+            other => panic!("Expected a text edit, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hover_conversion() {
+        let core_hover = Hover::new(
+            "`toc`\n\nInclude a table of contents",
+            Range::new(Position::new(1, 0), Position::new(1, 3)),
+        );
+
+        let lsp_hover = hover_to_lsp(&core_hover);
+        match lsp_hover.contents {
+            HoverContents::Markup(markup) => {
+                assert_eq!(markup.kind, MarkupKind::Markdown);
+                assert!(markup.value.starts_with("`toc`"));
+            }
+            other => panic!("Expected markup contents, got {:?}", other),
+        }
+        assert_eq!(lsp_hover.range.unwrap().end.character, 3);
+    }
+
+    #[test]
+    fn test_location_conversion() {
+        let document = Url::parse("file:///book/chapters/intro.qmd").unwrap();
+        let range = Range::new(Position::new(2, 9), Position::new(2, 18));
+
+        let lsp_location = location_to_lsp(&Location::in_document(range), &document).unwrap();
+        assert_eq!(lsp_location.uri, document);
+        assert_eq!(lsp_location.range.start.character, 9);
+
+        let lsp_location = location_to_lsp(&Location::in_file("../refs.bib", range), &document);
+        assert_eq!(lsp_location.unwrap().uri.as_str(), "file:///book/refs.bib");
+    }
+
+    #[test]
+    fn test_workspace_edit_conversion() {
+        let document = Url::parse("file:///book/index.qmd").unwrap();
+        let range = Range::new(Position::new(0, 19), Position::new(0, 27));
+        let edits = vec![
+            TextEdit::new(Location::in_document(range), "fig-scatter"),
+            TextEdit::new(Location::in_file("results.qmd", range), "fig-scatter"),
+            TextEdit::new(Location::in_document(range), "fig-scatter"),
+        ];
+
+        let changes = workspace_edit_to_lsp(&edits, &document).changes.unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[&document].len(), 2);
+        let results = Url::parse("file:///book/results.qmd").unwrap();
+        assert_eq!(changes[&results][0].new_text, "fig-scatter");
+    }
+
+    #[test]
+    fn test_semantic_tokens_are_relative() {
+        let token = |line, start, end, kind| {
+            SemanticToken::new(
+                Range::new(Position::new(line, start), Position::new(line, end)),
+                kind,
+            )
+        };
+        let tokens = semantic_tokens_to_lsp(&[
+            token(1, 4, 7, SemanticTokenKind::ShortcodeDelimiter),
+            token(1, 8, 11, SemanticTokenKind::ShortcodeName),
+            token(3, 2, 9, SemanticTokenKind::AttributeId),
+        ]);
+        let encoded: Vec<(u32, u32, u32, u32)> = tokens
+            .iter()
+            .map(|t| {
+                (
+                    t.delta_line,
+                    t.delta_start,
+                    t.length,
+                    t.token_modifiers_bitset,
+                )
+            })
+            .collect();
+        assert_eq!(encoded, vec![(1, 4, 3, 0), (0, 4, 3, 0), (2, 2, 7, 1)]);
+        assert_eq!(
+            SEMANTIC_TOKEN_TYPES[tokens[1].token_type as usize],
+            SemanticTokenType::MACRO
+        );
+    }
+
+    #[test]
+    fn test_code_action_conversion() {
+        let diagnostic = Diagnostic::new(
+            Range::new(Position::new(0, 10), Position::new(0, 11)),
+            DiagnosticSeverity::Error,
+            "Unclosed Star Emphasis",
+        );
+        let edit = TextEdit::new(
+            Location::in_document(Range::point(Position::new(0, 10))),
+            "*",
+        );
+        let action = CodeAction {
+            title: "Insert closing `*`".to_string(),
+            diagnostic,
+            edits: vec![edit],
+        };
+        let uri = Url::parse("file:///project/doc.qmd").unwrap();
+
+        let lsp_action = code_action_to_lsp(&action, &uri);
+        assert_eq!(lsp_action.kind, Some(CodeActionKind::QUICKFIX));
+        assert_eq!(lsp_action.diagnostics.unwrap().len(), 1);
+        let changes = lsp_action.edit.unwrap().changes.unwrap();
+        assert_eq!(changes[&uri][0].new_text, "*");
+    }
+
+    #[test]
+    fn test_inlay_hint_conversion() {
+        let hint = InlayHint::new(Position::new(2, 9), "Figure 1", InlayHintKind::CrossRef);
+        let lsp = inlay_hint_to_lsp(&hint);
+        assert_eq!(lsp.position, LspPosition::new(2, 9));
+        assert_eq!(lsp.kind, None);
+        assert_eq!(lsp.padding_left, Some(true));
+        match lsp.label {
+            InlayHintLabel::String(label) => assert_eq!(label, "Figure 1"),
+            other => panic!("Expected a string label, got {:?}", other),
+        }
+
+        let hint = InlayHint::new(
+            Position::new(0, 11),
+            "echo: false",
+            InlayHintKind::CellOption,
+        )
+        .with_tooltip("Inherited from `_quarto.yml`");
+        let lsp = inlay_hint_to_lsp(&hint);
+        assert_eq!(lsp.kind, Some(LspInlayHintKind::PARAMETER));
+        assert!(matches!(lsp.tooltip, Some(InlayHintTooltip::String(_))));
+    }
+
+    #[test]
+    fn test_selection_range_conversion() {
+        let word = Range::new(Position::new(0, 3), Position::new(0, 4));
+        let line = Range::new(Position::new(0, 0), Position::new(0, 7));
+        let selection = SelectionRange::new(word).with_parent(SelectionRange::new(line));
+
+        let lsp = selection_range_to_lsp(&selection);
+        assert_eq!(lsp.range, range_to_lsp(&word));
+        let parent = lsp.parent.expect("parent range");
+        assert_eq!(parent.range, range_to_lsp(&line));
+        assert!(parent.parent.is_none());
+    }
+
+    #[test]
+    fn test_document_link_conversion() {
+        let uri = Url::parse("file:///project/chapters/doc.qmd").unwrap();
+        let project = Url::parse("file:///project/").unwrap();
+        let target = |target: &str, project: Option<&Url>| {
+            let link = DocumentLink::new(Range::default(), target, DocumentLinkKind::Link);
+            document_link_to_lsp(&link, &uri, project).and_then(|link| link.target)
+        };
+
+        assert_eq!(
+            target("https://quarto.org", None).unwrap().as_str(),
+            "https://quarto.org/"
+        );
+        assert_eq!(
+            target("../refs.bib", None).unwrap().as_str(),
+            "file:///project/refs.bib"
+        );
+        assert_eq!(
+            target("/index.qmd#intro", Some(&project)).unwrap().as_str(),
+            "file:///project/index.qmd#intro"
+        );
+        assert_eq!(target("/index.qmd", None), None);
+
+        let link = DocumentLink::new(Range::default(), "_a.qmd", DocumentLinkKind::Include);
+        let lsp = document_link_to_lsp(&link, &uri, None).unwrap();
+        assert_eq!(lsp.tooltip.as_deref(), Some("Open included file"));
+    }
 }
@@ -0,0 +1,204 @@
+//! Publishing diagnostics.
+//!
+//! The diagnostics of a file are those of the document itself, its
+//! misspelled words and its prose lint findings, while it is open, and those
+//! found by analyzing its project (broken links, missing includes, duplicate labels, an invalid
+//! `_quarto.yml`). Projects are
+//! analyzed in the background a moment after the last change to one of
+//! their files, so that typing doesn't start an analysis of the whole
+//! project on every keystroke.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{Mutex, RwLock};
+use tower_lsp::Client;
+use tower_lsp::lsp_types::{Diagnostic, MessageType, Url};
+
+use quarto_lsp_core::NoWorkspace;
+use quarto_lsp_core::document::{Document, DocumentStore};
+
+use crate::convert;
+use crate::spelling::SpellChecker;
+use crate::workspace::FsWorkspace;
+
+/// How long a project must go without changes before it is analyzed.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Publishes the diagnostics of documents and of their projects.
+#[derive(Clone)]
+pub struct DiagnosticsPublisher {
+    client: Client,
+    documents: Arc<RwLock<DocumentStore>>,
+    spell_checker: SpellChecker,
+    /// The diagnostics of the last analysis of each project, by file.
+    project_diagnostics: Arc<RwLock<HashMap<Url, Vec<Diagnostic>>>>,
+    /// The number of analyses requested for each project directory, so that
+    /// a delayed analysis only runs if no other was requested since.
+    requests: Arc<Mutex<HashMap<PathBuf, u64>>>,
+}
+
+impl DiagnosticsPublisher {
+    /// Create a publisher for the documents of `documents`.
+    pub fn new(
+        client: Client,
+        documents: Arc<RwLock<DocumentStore>>,
+        spell_checker: SpellChecker,
+    ) -> Self {
+        Self {
+            client,
+            documents,
+            spell_checker,
+            project_diagnostics: Arc::default(),
+            requests: Arc::default(),
+        }
+    }
+
+    /// Publish the diagnostics of a file: those of the document's analysis,
+    /// its misspellings and its prose lint findings if it is open, and those of the last analysis
+    /// of its project.
+    pub async fn publish(&self, uri: Url) {
+        let mut diagnostics: Vec<Diagnostic> = {
+            let documents = self.documents.read().await;
+            documents
+                .get(uri.as_str())
+                .map(|doc| {
+                    doc.analysis()
+                        .diagnostics
+                        .iter()
+                        .chain(&self.spell_checker.diagnostics(&uri, doc))
+                        .chain(&prose_lint_diagnostics(&uri, doc))
+                        .map(convert::diagnostic_to_lsp)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        if let Some(project) = self.project_diagnostics.read().await.get(&uri) {
+            diagnostics.extend(project.iter().cloned());
+        }
+        self.client
+            .publish_diagnostics(uri, diagnostics, None)
+            .await;
+    }
+
+    /// Analyze the project of the file at `uri` in the background, once its
+    /// files stop changing.
+    pub async fn schedule_project(&self, uri: &Url) {
+        let Ok(path) = uri.to_file_path() else {
+            return;
+        };
+        let root = path
+            .parent()
+            .and_then(|dir| FsWorkspace::for_dir(dir.to_path_buf()).project_root());
+        let Some(root) = root else {
+            // Deleting `_quarto.yml` ends its project
+            if is_config(&path)
+                && let Some(dir) = path.parent()
+            {
+                self.replace_project(dir, Vec::new()).await;
+            }
+            return;
+        };
+
+        let request = {
+            let mut requests = self.requests.lock().await;
+            let count = requests.entry(root.clone()).or_default();
+            *count += 1;
+            *count
+        };
+        let publisher = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(DEBOUNCE).await;
+            if publisher.requests.lock().await.get(&root) == Some(&request) {
+                publisher.analyze_project(root).await;
+            }
+        });
+    }
+
+    /// Analyze a project, reading open documents from the editor, and
+    /// publish the diagnostics that changed.
+    async fn analyze_project(&self, root: PathBuf) {
+        let open: HashMap<PathBuf, String> = {
+            let documents = self.documents.read().await;
+            documents
+                .uris()
+                .filter_map(|uri| {
+                    let path = Url::parse(uri).ok()?.to_file_path().ok()?;
+                    Some((path, documents.get(uri)?.content().to_string()))
+                })
+                .collect()
+        };
+        let workspace = FsWorkspace::for_dir(root.clone()).with_documents(open);
+        let analysis = tokio::task::spawn_blocking(move || {
+            quarto_lsp_core::get_project_diagnostics(&workspace)
+                .into_iter()
+                .filter_map(|file| {
+                    let diagnostics = file
+                        .diagnostics
+                        .iter()
+                        .map(convert::diagnostic_to_lsp)
+                        .collect();
+                    Some((workspace.uri(&file.path)?, diagnostics))
+                })
+                .collect()
+        })
+        .await;
+        match analysis {
+            Ok(files) => self.replace_project(&root, files).await,
+            Err(err) => {
+                let message = format!("Project analysis of {} failed: {}", root.display(), err);
+                self.client.log_message(MessageType::WARNING, message).await;
+            }
+        }
+    }
+
+    /// Replace the project diagnostics of the files under `root`, and
+    /// publish the files whose diagnostics changed.
+    async fn replace_project(&self, root: &Path, files: Vec<(Url, Vec<Diagnostic>)>) {
+        let changed: Vec<Url> = {
+            let mut project_diagnostics = self.project_diagnostics.write().await;
+            let mut previous = HashMap::new();
+            project_diagnostics.retain(|uri, diagnostics| {
+                let in_project = uri.to_file_path().is_ok_and(|path| path.starts_with(root));
+                if in_project {
+                    previous.insert(uri.clone(), std::mem::take(diagnostics));
+                }
+                !in_project
+            });
+
+            let mut changed = Vec::new();
+            for (uri, diagnostics) in files {
+                if previous.remove(&uri).unwrap_or_default() != diagnostics {
+                    changed.push(uri.clone());
+                }
+                if !diagnostics.is_empty() {
+                    project_diagnostics.insert(uri, diagnostics);
+                }
+            }
+            // Files that are no longer part of the project
+            changed.extend(previous.into_keys());
+            changed
+        };
+        for uri in changed {
+            self.publish(uri).await;
+        }
+    }
+}
+
+/// The prose lint findings of the open document at `uri`, with the `lint`
+/// configuration of its project.
+pub fn prose_lint_diagnostics(uri: &Url, doc: &Document) -> Vec<quarto_lsp_core::Diagnostic> {
+    let config = match FsWorkspace::for_document(uri) {
+        Some(workspace) => quarto_lsp_core::get_prose_lint_config(doc, &workspace),
+        None => quarto_lsp_core::get_prose_lint_config(doc, &NoWorkspace),
+    };
+    quarto_lsp_core::get_prose_lint_diagnostics(doc, &config)
+}
+
+/// Whether a path is a project configuration file.
+fn is_config(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == "_quarto.yml" || name == "_quarto.yaml")
+}
@@ -40,6 +40,9 @@
 
 pub mod capabilities;
 pub mod convert;
+pub mod diagnostics;
 pub mod server;
+pub mod spelling;
+pub mod workspace;
 
 pub use server::run_server;
@@ -1,15 +1,48 @@
 //! LSP server implementation using tower-lsp.
 
+use serde::Deserialize;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::RwLock;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
 use quarto_lsp_core::document::DocumentStore;
+use quarto_lsp_core::{EmbeddedDocument, EmbeddedPosition, FormattingOptions, NoWorkspace};
 
 use crate::capabilities::server_capabilities;
 use crate::convert;
+use crate::diagnostics::{DiagnosticsPublisher, prose_lint_diagnostics};
+use crate::spelling::SpellChecker;
+use crate::workspace::FsWorkspace;
+
+/// Server settings, from the client's `initializationOptions`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Settings {
+    /// Whether renaming a citation key also renames its bibliography entry.
+    rename_bibliography_entries: bool,
+    /// How documents are formatted.
+    formatting: FormattingOptions,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            rename_bibliography_entries: true,
+            formatting: FormattingOptions::default(),
+        }
+    }
+}
+
+/// Parameters of the `quarto/embeddedDocuments` request.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedDocumentsParams {
+    /// The document whose code cells are requested.
+    pub text_document: TextDocumentIdentifier,
+}
 
 /// The Quarto language server.
 pub struct QuartoLanguageServer {
@@ -17,46 +50,111 @@ pub struct QuartoLanguageServer {
     client: Client,
     /// Document store for managing open documents.
     documents: Arc<RwLock<DocumentStore>>,
+    /// Settings from initialization.
+    settings: Arc<RwLock<Settings>>,
+    /// Publishes the diagnostics of documents and projects.
+    diagnostics: DiagnosticsPublisher,
+    /// Checks the spelling of documents.
+    spell_checker: SpellChecker,
+    /// Whether the client lets us register file watchers.
+    watch_files: AtomicBool,
 }
 
 impl QuartoLanguageServer {
     /// Create a new language server instance.
     pub fn new(client: Client) -> Self {
+        let documents = Arc::new(RwLock::new(DocumentStore::new()));
+        let spell_checker = SpellChecker::default();
         Self {
+            diagnostics: DiagnosticsPublisher::new(
+                client.clone(),
+                documents.clone(),
+                spell_checker.clone(),
+            ),
+            spell_checker,
             client,
-            documents: Arc::new(RwLock::new(DocumentStore::new())),
+            documents,
+            settings: Arc::new(RwLock::new(Settings::default())),
+            watch_files: AtomicBool::new(false),
         }
     }
 
-    /// Publish diagnostics for a document.
-    async fn publish_diagnostics(&self, uri: Url) {
+    /// Handle `quarto/embeddedDocuments`: the code of the executable cells
+    /// of a document, one embedded document per language.
+    pub async fn embedded_documents(
+        &self,
+        params: EmbeddedDocumentsParams,
+    ) -> Result<Vec<EmbeddedDocument>> {
         let documents = self.documents.read().await;
-        let uri_str = uri.as_str();
-
-        if let Some(doc) = documents.get(uri_str) {
-            // Get diagnostics from quarto-lsp-core
-            let result = quarto_lsp_core::get_diagnostics(doc);
+        let Some(doc) = documents.get(params.text_document.uri.as_str()) else {
+            return Ok(Vec::new());
+        };
+        Ok(quarto_lsp_core::get_embedded_documents(doc))
+    }
 
-            // Convert to LSP diagnostics
-            let diagnostics: Vec<Diagnostic> = result
-                .diagnostics
-                .iter()
-                .map(convert::diagnostic_to_lsp)
-                .collect();
+    /// Handle `quarto/embeddedDocumentAt`: the embedded document of the cell
+    /// at a position, and the position in it, so that the client can
+    /// forward a request there to the language server of the cell's
+    /// language and map the result back with the cell ranges.
+    pub async fn embedded_document_at(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<EmbeddedPosition>> {
+        let documents = self.documents.read().await;
+        let Some(doc) = documents.get(params.text_document.uri.as_str()) else {
+            return Ok(None);
+        };
+        let position = convert::position_from_lsp(&params.position);
+        Ok(quarto_lsp_core::get_embedded_document_at(doc, position))
+    }
 
-            // Publish diagnostics
-            self.client
-                .publish_diagnostics(uri, diagnostics, None)
-                .await;
+    /// Watch the project configuration, documents and dictionaries on disk,
+    /// so projects are analyzed again when files change outside the editor.
+    async fn register_file_watchers(&self) {
+        let watchers = ["**/_quarto.{yml,yaml}", "**/*.{qmd,md}", "**/*.{aff,dic}"]
+            .iter()
+            .map(|glob| FileSystemWatcher {
+                glob_pattern: GlobPattern::String(glob.to_string()),
+                kind: None,
+            })
+            .collect();
+        let options = DidChangeWatchedFilesRegistrationOptions { watchers };
+        let registration = Registration {
+            id: "quarto-project-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+        if let Err(err) = self.client.register_capability(vec![registration]).await {
+            let message = format!("Could not watch project files: {}", err);
+            self.client.log_message(MessageType::WARNING, message).await;
         }
     }
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for QuartoLanguageServer {
-    async fn initialize(&self, _params: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        if let Some(options) = params.initialization_options {
+            match serde_json::from_value::<Settings>(options) {
+                Ok(settings) => *self.settings.write().await = settings,
+                Err(err) => {
+                    let message = format!("Ignoring invalid initialization options: {}", err);
+                    self.client.log_message(MessageType::WARNING, message).await;
+                }
+            }
+        }
+
+        let watch_files = params
+            .capabilities
+            .workspace
+            .as_ref()
+            .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+            .and_then(|watched_files| watched_files.dynamic_registration)
+            .unwrap_or(false);
+        self.watch_files.store(watch_files, Ordering::Relaxed);
+
         Ok(InitializeResult {
-            capabilities: server_capabilities(),
+            capabilities: server_capabilities(&params.capabilities),
             server_info: Some(ServerInfo {
                 name: "quarto-lsp".to_string(),
                 version: Some(env!("CARGO_PKG_VERSION").to_string()),
@@ -68,6 +166,9 @@ impl LanguageServer for QuartoLanguageServer {
         self.client
             .log_message(MessageType::INFO, "Quarto LSP server initialized")
             .await;
+        if self.watch_files.load(Ordering::Relaxed) {
+            self.register_file_watchers().await;
+        }
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -84,24 +185,37 @@ impl LanguageServer for QuartoLanguageServer {
             documents.open(uri.as_str(), text, version);
         }
 
-        // Publish diagnostics for the opened document
-        self.publish_diagnostics(uri).await;
+        // Publish diagnostics for the opened document, then for its project
+        self.diagnostics.publish(uri.clone()).await;
+        self.diagnostics.schedule_project(&uri).await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri.clone();
         let version = params.text_document.version;
 
-        // We're using full document sync, so take the last change
-        if let Some(change) = params.content_changes.into_iter().last() {
-            {
-                let mut documents = self.documents.write().await;
-                documents.change(uri.as_str(), change.text, version);
+        if params.content_changes.is_empty() {
+            return;
+        }
+        {
+            let mut documents = self.documents.write().await;
+            // Changes apply in order: edits of a range, or the whole text
+            for change in params.content_changes {
+                match change.range {
+                    Some(range) => documents.edit(
+                        uri.as_str(),
+                        convert::range_from_lsp(&range),
+                        &change.text,
+                        version,
+                    ),
+                    None => documents.change(uri.as_str(), change.text, version),
+                }
             }
-
-            // Publish diagnostics for the changed document
-            self.publish_diagnostics(uri).await;
         }
+
+        // Publish diagnostics for the changed document, then for its project
+        self.diagnostics.publish(uri.clone()).await;
+        self.diagnostics.schedule_project(&uri).await;
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
@@ -112,8 +226,17 @@ impl LanguageServer for QuartoLanguageServer {
             documents.close(uri.as_str());
         }
 
-        // Clear diagnostics for closed document
-        self.client.publish_diagnostics(uri, Vec::new(), None).await;
+        // Only the project's diagnostics remain for a closed document
+        self.diagnostics.publish(uri).await;
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        for change in params.changes {
+            if let Ok(path) = change.uri.to_file_path() {
+                self.spell_checker.file_changed(&path);
+            }
+            self.diagnostics.schedule_project(&change.uri).await;
+        }
     }
 
     async fn document_symbol(
@@ -132,6 +255,270 @@ impl LanguageServer for QuartoLanguageServer {
             Ok(None)
         }
     }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = convert::position_from_lsp(&params.text_document_position.position);
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(uri.as_str()) else {
+            return Ok(None);
+        };
+        let items = match FsWorkspace::for_document(&uri) {
+            Some(workspace) => quarto_lsp_core::get_completions(doc, position, &workspace),
+            None => quarto_lsp_core::get_completions(doc, position, &NoWorkspace),
+        };
+        let lsp_items: Vec<CompletionItem> = items.iter().map(convert::completion_to_lsp).collect();
+        Ok(Some(CompletionResponse::Array(lsp_items)))
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = convert::position_from_lsp(&params.text_document_position_params.position);
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(uri.as_str()) else {
+            return Ok(None);
+        };
+        let hover = match FsWorkspace::for_document(&uri) {
+            Some(workspace) => quarto_lsp_core::get_hover(doc, position, &workspace),
+            None => quarto_lsp_core::get_hover(doc, position, &NoWorkspace),
+        };
+        Ok(hover.as_ref().map(convert::hover_to_lsp))
+    }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = convert::position_from_lsp(&params.text_document_position_params.position);
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(uri.as_str()) else {
+            return Ok(None);
+        };
+        let definitions = match FsWorkspace::for_document(&uri) {
+            Some(workspace) => quarto_lsp_core::get_definition(doc, position, &workspace),
+            None => quarto_lsp_core::get_definition(doc, position, &NoWorkspace),
+        };
+        let locations: Vec<Location> = definitions
+            .iter()
+            .filter_map(|location| convert::location_to_lsp(location, &uri))
+            .collect();
+        Ok((!locations.is_empty()).then_some(GotoDefinitionResponse::Array(locations)))
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = convert::position_from_lsp(&params.text_document_position.position);
+        let include_declaration = params.context.include_declaration;
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(uri.as_str()) else {
+            return Ok(None);
+        };
+        let references = match FsWorkspace::for_document(&uri) {
+            Some(workspace) => {
+                quarto_lsp_core::get_references(doc, position, &workspace, include_declaration)
+            }
+            None => {
+                quarto_lsp_core::get_references(doc, position, &NoWorkspace, include_declaration)
+            }
+        };
+        let locations: Vec<Location> = references
+            .iter()
+            .filter_map(|location| convert::location_to_lsp(location, &uri))
+            .collect();
+        Ok(Some(locations))
+    }
+
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        let options = self.settings.read().await.formatting.clone();
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(params.text_document.uri.as_str()) else {
+            return Ok(None);
+        };
+        let edits = quarto_lsp_core::format_document(doc, &options);
+        Ok(Some(convert::text_edits_to_lsp(&edits)))
+    }
+
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let range = convert::range_from_lsp(&params.range);
+        let options = self.settings.read().await.formatting.clone();
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(params.text_document.uri.as_str()) else {
+            return Ok(None);
+        };
+        let edits = quarto_lsp_core::format_range(doc, range, &options);
+        Ok(Some(convert::text_edits_to_lsp(&edits)))
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+        let range = convert::range_from_lsp(&params.range);
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(uri.as_str()) else {
+            return Ok(None);
+        };
+        let mut actions = quarto_lsp_core::get_code_actions(doc, range);
+        actions.extend(quarto_lsp_core::get_fix_actions(
+            &self.spell_checker.diagnostics(&uri, doc),
+            range,
+        ));
+        actions.extend(quarto_lsp_core::get_fix_actions(
+            &prose_lint_diagnostics(&uri, doc),
+            range,
+        ));
+        Ok(Some(
+            actions
+                .iter()
+                .map(|action| {
+                    CodeActionOrCommand::CodeAction(convert::code_action_to_lsp(action, &uri))
+                })
+                .collect(),
+        ))
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(params.text_document.uri.as_str()) else {
+            return Ok(None);
+        };
+        let tokens = quarto_lsp_core::get_semantic_tokens(doc);
+        Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: None,
+            data: convert::semantic_tokens_to_lsp(&tokens),
+        })))
+    }
+
+    async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> Result<Option<SemanticTokensRangeResult>> {
+        let range = convert::range_from_lsp(&params.range);
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(params.text_document.uri.as_str()) else {
+            return Ok(None);
+        };
+        let tokens = quarto_lsp_core::get_semantic_tokens_in_range(doc, range);
+        Ok(Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+            result_id: None,
+            data: convert::semantic_tokens_to_lsp(&tokens),
+        })))
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let uri = params.text_document.uri;
+        let range = convert::range_from_lsp(&params.range);
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(uri.as_str()) else {
+            return Ok(None);
+        };
+        let hints = match FsWorkspace::for_document(&uri) {
+            Some(workspace) => quarto_lsp_core::get_inlay_hints(doc, range, &workspace),
+            None => quarto_lsp_core::get_inlay_hints(doc, range, &NoWorkspace),
+        };
+        Ok(Some(hints.iter().map(convert::inlay_hint_to_lsp).collect()))
+    }
+
+    async fn selection_range(
+        &self,
+        params: SelectionRangeParams,
+    ) -> Result<Option<Vec<SelectionRange>>> {
+        let positions: Vec<_> = params
+            .positions
+            .iter()
+            .map(convert::position_from_lsp)
+            .collect();
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(params.text_document.uri.as_str()) else {
+            return Ok(None);
+        };
+        let selections = quarto_lsp_core::get_selection_ranges(doc, &positions);
+        Ok(Some(
+            selections
+                .iter()
+                .map(convert::selection_range_to_lsp)
+                .collect(),
+        ))
+    }
+
+    async fn document_link(&self, params: DocumentLinkParams) -> Result<Option<Vec<DocumentLink>>> {
+        let uri = params.text_document.uri;
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(uri.as_str()) else {
+            return Ok(None);
+        };
+        let project_uri = FsWorkspace::for_document(&uri)
+            .and_then(|workspace| workspace.project_root())
+            .and_then(|root| Url::from_directory_path(root).ok());
+        let links = quarto_lsp_core::get_document_links(doc);
+        Ok(Some(
+            links
+                .iter()
+                .filter_map(|link| convert::document_link_to_lsp(link, &uri, project_uri.as_ref()))
+                .collect(),
+        ))
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let position = convert::position_from_lsp(&params.position);
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(params.text_document.uri.as_str()) else {
+            return Ok(None);
+        };
+        Ok(quarto_lsp_core::prepare_rename(doc, position)
+            .map(|range| PrepareRenameResponse::Range(convert::range_to_lsp(&range))))
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = convert::position_from_lsp(&params.text_document_position.position);
+        let rename_entries = self.settings.read().await.rename_bibliography_entries;
+        let documents = self.documents.read().await;
+
+        let Some(doc) = documents.get(uri.as_str()) else {
+            return Ok(None);
+        };
+        let new_name = &params.new_name;
+        let edits = match FsWorkspace::for_document(&uri) {
+            Some(workspace) => quarto_lsp_core::get_rename_edits(
+                doc,
+                position,
+                new_name,
+                &workspace,
+                rename_entries,
+            ),
+            None => quarto_lsp_core::get_rename_edits(
+                doc,
+                position,
+                new_name,
+                &NoWorkspace,
+                rename_entries,
+            ),
+        }
+        .map_err(|err| tower_lsp::jsonrpc::Error::invalid_params(err.to_string()))?;
+        Ok(Some(convert::workspace_edit_to_lsp(&edits, &uri)))
+    }
 }
 
 /// Run the LSP server over stdio.
@@ -139,6 +526,15 @@ pub async fn run_server() {
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
 
-    let (service, socket) = LspService::new(QuartoLanguageServer::new);
+    let (service, socket) = LspService::build(QuartoLanguageServer::new)
+        .custom_method(
+            "quarto/embeddedDocuments",
+            QuartoLanguageServer::embedded_documents,
+        )
+        .custom_method(
+            "quarto/embeddedDocumentAt",
+            QuartoLanguageServer::embedded_document_at,
+        )
+        .finish();
     Server::new(stdin, stdout, socket).serve(service).await;
 }
@@ -0,0 +1,137 @@
+//! Spell checking of open documents.
+//!
+//! Dictionaries are those of the project (`spelling.dictionary`), or the
+//! Hunspell dictionaries installed for the configured language, looked up
+//! in the directories of `DICPATH` and the usual system directories. They
+//! are read once and kept until their files change.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use quarto_lsp_core::document::Document;
+use quarto_lsp_core::{Diagnostic, Dictionary, SpellingConfig};
+use tower_lsp::lsp_types::Url;
+
+use crate::workspace::FsWorkspace;
+
+/// Directories where Hunspell dictionaries are installed.
+const SYSTEM_DICTIONARY_DIRS: &[&str] = &[
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/usr/local/share/hunspell",
+    "/Library/Spelling",
+];
+
+/// Checks the spelling of documents, keeping the dictionaries it reads.
+#[derive(Clone, Default)]
+pub struct SpellChecker {
+    /// The dictionaries read, by path without extension, or `None` for
+    /// dictionaries that couldn't be read.
+    dictionaries: Arc<Mutex<HashMap<PathBuf, Option<Arc<Dictionary>>>>>,
+}
+
+impl SpellChecker {
+    /// The misspelled words of the document at `uri`, if it is spell
+    /// checked.
+    pub fn diagnostics(&self, uri: &Url, doc: &Document) -> Vec<Diagnostic> {
+        let Some(workspace) = FsWorkspace::for_document(uri) else {
+            return Vec::new();
+        };
+        let Some(config) = quarto_lsp_core::get_spelling_config(doc, &workspace) else {
+            return Vec::new();
+        };
+        let Some(dictionary) = self.dictionary(&workspace, &config) else {
+            return Vec::new();
+        };
+        quarto_lsp_core::get_spelling_diagnostics(doc, &dictionary, &config)
+    }
+
+    /// Forget the dictionary a changed file belongs to, if it is one.
+    pub fn file_changed(&self, path: &Path) {
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "aff" || extension == "dic")
+        {
+            self.lock().remove(&path.with_extension(""));
+        }
+    }
+
+    /// The dictionary of a configuration.
+    fn dictionary(
+        &self,
+        workspace: &FsWorkspace,
+        config: &SpellingConfig,
+    ) -> Option<Arc<Dictionary>> {
+        let base = match &config.dictionary {
+            Some(path) => workspace.resolve(path),
+            None => system_dictionary(&config.language)?,
+        };
+        if let Some(dictionary) = self.lock().get(&base) {
+            return dictionary.clone();
+        }
+        let dictionary = read_dictionary(&base).map(Arc::new);
+        self.lock().insert(base, dictionary.clone());
+        dictionary
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, Option<Arc<Dictionary>>>> {
+        self.dictionaries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// The dictionary installed for a language, without extension.
+fn system_dictionary(language: &str) -> Option<PathBuf> {
+    let dicpath = std::env::var("DICPATH").unwrap_or_default();
+    std::env::split_paths(&dicpath)
+        .chain(SYSTEM_DICTIONARY_DIRS.iter().map(PathBuf::from))
+        .map(|dir| dir.join(language))
+        .find(|base| base.with_extension("aff").is_file())
+}
+
+/// Read the `.aff` and `.dic` files of a dictionary.
+fn read_dictionary(base: &Path) -> Option<Dictionary> {
+    let aff = std::fs::read_to_string(base.with_extension("aff")).ok()?;
+    let dic = std::fs::read_to_string(base.with_extension("dic")).ok()?;
+    Some(Dictionary::new(&aff, &dic))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_dictionaries() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("dict")).unwrap();
+        std::fs::write(root.path().join("dict/en.aff"), "TRY tesx\n").unwrap();
+        std::fs::write(root.path().join("dict/en.dic"), "1\ntext\n").unwrap();
+
+        let uri = Url::from_file_path(root.path().join("doc.qmd")).unwrap();
+        let doc = Document::new(
+            uri.as_str(),
+            "---\nspelling:\n  dictionary: dict/en\n---\n\nText tetx.\n",
+        );
+        let checker = SpellChecker::default();
+        let diagnostics = checker.diagnostics(&uri, &doc);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].fixes[0].title, "Change to `text`");
+
+        // Changed dictionaries are read again
+        std::fs::write(root.path().join("dict/en.dic"), "2\ntext\ntetx\n").unwrap();
+        assert_eq!(checker.diagnostics(&uri, &doc).len(), 1);
+        checker.file_changed(&root.path().join("dict/en.dic"));
+        assert!(checker.diagnostics(&uri, &doc).is_empty());
+    }
+
+    #[test]
+    fn documents_without_spelling_config() {
+        let root = tempfile::tempdir().unwrap();
+        let uri = Url::from_file_path(root.path().join("doc.qmd")).unwrap();
+        let doc = Document::new(uri.as_str(), "Tetx.\n");
+        assert!(SpellChecker::default().diagnostics(&uri, &doc).is_empty());
+    }
+}
@@ -0,0 +1,163 @@
+//! Filesystem access for language analysis.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use quarto_lsp_core::{Workspace, WorkspaceEntry};
+use tower_lsp::lsp_types::Url;
+
+/// The files around a document on disk.
+///
+/// Paths are resolved against the document's directory. The project
+/// directory is the nearest ancestor containing `_quarto.yml`.
+pub struct FsWorkspace {
+    dir: PathBuf,
+    /// Contents read instead of the files on disk, by absolute path.
+    documents: HashMap<PathBuf, String>,
+}
+
+impl FsWorkspace {
+    /// The workspace of the document at `uri`, or `None` if it isn't a file.
+    pub fn for_document(uri: &Url) -> Option<Self> {
+        let path = uri.to_file_path().ok()?;
+        Some(Self::for_dir(path.parent()?.to_path_buf()))
+    }
+
+    /// The workspace of the files in `dir`.
+    pub fn for_dir(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            documents: HashMap::new(),
+        }
+    }
+
+    /// Read the given contents instead of the files on disk, such as the
+    /// unsaved contents of the documents open in the editor.
+    pub fn with_documents(mut self, documents: HashMap<PathBuf, String>) -> Self {
+        self.documents = documents;
+        self
+    }
+
+    /// The absolute project directory, if there is one.
+    pub fn project_root(&self) -> Option<PathBuf> {
+        let project_dir = self.project_dir()?;
+        self.dir
+            .ancestors()
+            .nth(project_dir.split('/').filter(|s| *s == "..").count())
+            .map(Path::to_path_buf)
+    }
+
+    /// The URI of a file of the workspace.
+    pub fn uri(&self, path: &str) -> Option<Url> {
+        Url::from_file_path(self.resolve(path)).ok()
+    }
+
+    /// The absolute path of a file of the workspace.
+    pub fn resolve(&self, path: &str) -> PathBuf {
+        self.dir.join(path)
+    }
+}
+
+impl Workspace for FsWorkspace {
+    fn read_file(&self, path: &str) -> Option<String> {
+        let path = self.resolve(path);
+        match self.documents.get(&path) {
+            Some(content) => Some(content.clone()),
+            None => std::fs::read_to_string(path).ok(),
+        }
+    }
+
+    fn read_dir(&self, path: &str) -> Vec<WorkspaceEntry> {
+        let Ok(entries) = std::fs::read_dir(self.resolve(path)) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let name = entry.file_name().into_string().ok()?;
+                Some(if entry.path().is_dir() {
+                    WorkspaceEntry::dir(name)
+                } else {
+                    WorkspaceEntry::file(name)
+                })
+            })
+            .collect()
+    }
+
+    fn project_dir(&self) -> Option<String> {
+        project_dir(&self.dir)
+    }
+}
+
+/// The project directory of files in `dir`, relative to `dir`.
+fn project_dir(dir: &Path) -> Option<String> {
+    let depth = dir.ancestors().position(|ancestor| {
+        ancestor.join("_quarto.yml").is_file() || ancestor.join("_quarto.yaml").is_file()
+    })?;
+    Some(if depth == 0 {
+        ".".to_string()
+    } else {
+        vec![".."; depth].join("/")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_dir_is_nearest_ancestor_with_config() {
+        let root = tempfile::tempdir().unwrap();
+        let chapter = root.path().join("chapters").join("one");
+        std::fs::create_dir_all(&chapter).unwrap();
+        std::fs::write(root.path().join("_quarto.yml"), "project:\n  type: book\n").unwrap();
+
+        assert_eq!(project_dir(&chapter).as_deref(), Some("../.."));
+        assert_eq!(project_dir(root.path()).as_deref(), Some("."));
+    }
+
+    #[test]
+    fn reads_files_relative_to_document() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("data")).unwrap();
+        std::fs::write(root.path().join("refs.bib"), "@book{key,}").unwrap();
+
+        let uri = Url::from_file_path(root.path().join("doc.qmd")).unwrap();
+        let workspace = FsWorkspace::for_document(&uri).unwrap();
+        assert_eq!(
+            workspace.read_file("refs.bib").as_deref(),
+            Some("@book{key,}")
+        );
+
+        let mut entries = workspace.read_dir("");
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(
+            entries,
+            vec![
+                WorkspaceEntry::dir("data"),
+                WorkspaceEntry::file("refs.bib")
+            ]
+        );
+    }
+
+    #[test]
+    fn project_root_and_open_documents() {
+        let root = tempfile::tempdir().unwrap();
+        let chapters = root.path().join("chapters");
+        std::fs::create_dir(&chapters).unwrap();
+        std::fs::write(root.path().join("_quarto.yml"), "project:\n  type: book\n").unwrap();
+        std::fs::write(chapters.join("one.qmd"), "Saved\n").unwrap();
+
+        let uri = Url::from_file_path(chapters.join("one.qmd")).unwrap();
+        let workspace = FsWorkspace::for_document(&uri).unwrap();
+        assert_eq!(workspace.project_root(), Some(root.path().to_path_buf()));
+
+        let open = HashMap::from([(chapters.join("one.qmd"), "Unsaved\n".to_string())]);
+        let workspace = FsWorkspace::for_dir(root.path().to_path_buf()).with_documents(open);
+        assert_eq!(
+            workspace.read_file("chapters/one.qmd").as_deref(),
+            Some("Unsaved\n")
+        );
+        assert_eq!(workspace.uri("chapters/one.qmd"), Some(uri));
+    }
+}
@@ -259,6 +259,100 @@ impl LspTestHarness {
         });
         self.request("textDocument/documentSymbol", params)
     }
+
+    /// Request completions at a position.
+    fn completion(&mut self, uri: &str, line: u32, character: u32) -> serde_json::Value {
+        let params = serde_json::json!({
+            "textDocument": {
+                "uri": uri
+            },
+            "position": {
+                "line": line,
+                "character": character
+            }
+        });
+        self.request("textDocument/completion", params)
+    }
+
+    /// Request hover information at a position.
+    fn hover(&mut self, uri: &str, line: u32, character: u32) -> serde_json::Value {
+        let params = serde_json::json!({
+            "textDocument": {
+                "uri": uri
+            },
+            "position": {
+                "line": line,
+                "character": character
+            }
+        });
+        self.request("textDocument/hover", params)
+    }
+
+    /// Request the definitions of the element at a position.
+    fn definition(&mut self, uri: &str, line: u32, character: u32) -> serde_json::Value {
+        let params = serde_json::json!({
+            "textDocument": {
+                "uri": uri
+            },
+            "position": {
+                "line": line,
+                "character": character
+            }
+        });
+        self.request("textDocument/definition", params)
+    }
+
+    /// Request the references to the element at a position.
+    fn references(&mut self, uri: &str, line: u32, character: u32) -> serde_json::Value {
+        let params = serde_json::json!({
+            "textDocument": {
+                "uri": uri
+            },
+            "position": {
+                "line": line,
+                "character": character
+            },
+            "context": {
+                "includeDeclaration": true
+            }
+        });
+        self.request("textDocument/references", params)
+    }
+
+    /// Request the edits that format a document.
+    fn formatting(&mut self, uri: &str) -> serde_json::Value {
+        let params = serde_json::json!({
+            "textDocument": {
+                "uri": uri
+            },
+            "options": {
+                "tabSize": 2,
+                "insertSpaces": true
+            }
+        });
+        self.request("textDocument/formatting", params)
+    }
+
+    /// Request the edits that rename the element at a position.
+    fn rename(
+        &mut self,
+        uri: &str,
+        line: u32,
+        character: u32,
+        new_name: &str,
+    ) -> serde_json::Value {
+        let params = serde_json::json!({
+            "textDocument": {
+                "uri": uri
+            },
+            "position": {
+                "line": line,
+                "character": character
+            },
+            "newName": new_name
+        });
+        self.request("textDocument/rename", params)
+    }
 }
 
 impl Drop for LspTestHarness {
@@ -548,3 +642,197 @@ plot(x, y)
         "Expected at least one symbol for the header"
     );
 }
+
+// =============================================================================
+// Completion Tests
+// =============================================================================
+
+#[test]
+fn test_completion_front_matter_keys() {
+    let mut harness = LspTestHarness::new();
+    harness.initialize();
+
+    let uri = "file:///test/completion.qmd";
+    let content = "---\ntitle: Test\ntoc-d\n---\n\n# Section\n";
+
+    harness.open_document(uri, content, 1);
+    let _ = harness.wait_for_diagnostics(uri, Duration::from_secs(5));
+
+    let response = harness.completion(uri, 2, 5);
+    let items = response["result"]
+        .as_array()
+        .expect("Expected array of completion items");
+    let labels: Vec<&str> = items
+        .iter()
+        .filter_map(|item| item["label"].as_str())
+        .collect();
+    assert_eq!(labels, vec!["toc-depth"]);
+    assert_eq!(items[0]["textEdit"]["newText"], "toc-depth: ");
+}
+
+#[test]
+fn test_completion_crossrefs() {
+    let mut harness = LspTestHarness::new();
+    harness.initialize();
+
+    let uri = "file:///test/crossrefs.qmd";
+    let content = "# Intro {#sec-intro}\n\n![Plot](plot.png){#fig-plot}\n\nSee @fig\n";
+
+    harness.open_document(uri, content, 1);
+    let _ = harness.wait_for_diagnostics(uri, Duration::from_secs(5));
+
+    let response = harness.completion(uri, 4, 8);
+    let items = response["result"]
+        .as_array()
+        .expect("Expected array of completion items");
+    let labels: Vec<&str> = items
+        .iter()
+        .filter_map(|item| item["label"].as_str())
+        .collect();
+    assert_eq!(labels, vec!["fig-plot"]);
+}
+
+// =============================================================================
+// Hover Tests
+// =============================================================================
+
+#[test]
+fn test_hover_front_matter_option() {
+    let mut harness = LspTestHarness::new();
+    harness.initialize();
+
+    let uri = "file:///test/hover.qmd";
+    let content = "---\ntitle: Test\ntoc: true\n---\n\n# Heading\n";
+
+    harness.open_document(uri, content, 1);
+    let _ = harness.wait_for_diagnostics(uri, Duration::from_secs(5));
+
+    let response = harness.hover(uri, 2, 1);
+    let contents = &response["result"]["contents"];
+    assert_eq!(contents["kind"], "markdown");
+    let value = contents["value"].as_str().expect("Expected hover text");
+    assert!(
+        value.contains("Include a table of contents"),
+        "Unexpected hover: {}",
+        value
+    );
+    assert_eq!(response["result"]["range"]["end"]["character"], 3);
+}
+
+#[test]
+fn test_hover_crossref() {
+    let mut harness = LspTestHarness::new();
+    harness.initialize();
+
+    let uri = "file:///test/hover-crossref.qmd";
+    let content = "![A plot](plot.png){#fig-plot}\n\nSee @fig-plot.\n";
+
+    harness.open_document(uri, content, 1);
+    let _ = harness.wait_for_diagnostics(uri, Duration::from_secs(5));
+
+    let response = harness.hover(uri, 2, 7);
+    let value = response["result"]["contents"]["value"]
+        .as_str()
+        .expect("Expected hover text");
+    assert_eq!(value, "**Figure** `fig-plot`\n\nA plot");
+
+    let response = harness.hover(uri, 1, 0);
+    assert!(response["result"].is_null());
+}
+
+// =============================================================================
+// Navigation Tests
+// =============================================================================
+
+#[test]
+fn test_definition_and_references() {
+    let mut harness = LspTestHarness::new();
+    harness.initialize();
+
+    let uri = "file:///test/navigation.qmd";
+    let content = "```{r}\n#| label: fig-plot\nplot(1)\n```\n\nSee @fig-plot and @fig-plot.\n";
+
+    harness.open_document(uri, content, 1);
+    let _ = harness.wait_for_diagnostics(uri, Duration::from_secs(5));
+
+    let response = harness.definition(uri, 5, 6);
+    let locations = response["result"]
+        .as_array()
+        .expect("Expected array of locations");
+    assert_eq!(locations.len(), 1);
+    assert_eq!(locations[0]["uri"], uri);
+    assert_eq!(locations[0]["range"]["start"]["line"], 1);
+    assert_eq!(locations[0]["range"]["start"]["character"], 10);
+
+    let response = harness.references(uri, 1, 12);
+    let locations = response["result"]
+        .as_array()
+        .expect("Expected array of locations");
+    let lines: Vec<(u64, u64)> = locations
+        .iter()
+        .map(|location| {
+            (
+                location["range"]["start"]["line"].as_u64().unwrap(),
+                location["range"]["start"]["character"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(lines, vec![(1, 10), (5, 5), (5, 19)]);
+}
+
+#[test]
+fn test_rename_crossref_label() {
+    let mut harness = LspTestHarness::new();
+    harness.initialize();
+
+    let uri = "file:///test/rename.qmd";
+    let content = "![Plot](plot.png){#fig-plot}\n\nSee @fig-plot.\n";
+
+    harness.open_document(uri, content, 1);
+    let _ = harness.wait_for_diagnostics(uri, Duration::from_secs(5));
+
+    let response = harness.rename(uri, 2, 6, "fig-scatter");
+    let edits = response["result"]["changes"][uri]
+        .as_array()
+        .expect("Expected edits for the document");
+    let ranges: Vec<(u64, u64, u64)> = edits
+        .iter()
+        .map(|edit| {
+            assert_eq!(edit["newText"], "fig-scatter");
+            (
+                edit["range"]["start"]["line"].as_u64().unwrap(),
+                edit["range"]["start"]["character"].as_u64().unwrap(),
+                edit["range"]["end"]["character"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(ranges, vec![(0, 19, 27), (2, 5, 13)]);
+
+    let response = harness.rename(uri, 2, 6, "scatter");
+    assert!(response["error"]["message"].is_string());
+}
+
+// =============================================================================
+// Formatting Tests
+// =============================================================================
+
+#[test]
+fn test_formatting_edits_changed_lines() {
+    let mut harness = LspTestHarness::new();
+    harness.initialize();
+
+    let uri = "file:///test/format.qmd";
+    let content = "---\ntitle: Test\n---\n\n# Title\n\n-   one\n-   two\n";
+
+    harness.open_document(uri, content, 1);
+    let _ = harness.wait_for_diagnostics(uri, Duration::from_secs(5));
+
+    let response = harness.formatting(uri);
+    let edits = response["result"]
+        .as_array()
+        .expect("Expected array of edits");
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0]["range"]["start"]["line"], 6);
+    assert_eq!(edits[0]["range"]["end"]["line"], 8);
+    assert_eq!(edits[0]["newText"], "* one\n* two\n");
+}
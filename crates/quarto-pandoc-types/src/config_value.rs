@@ -9,7 +9,7 @@
  * document metadata (frontmatter).
  */
 
-use crate::block::Blocks;
+use crate::block::{Block, Blocks};
 use crate::inline::{Inline, Inlines};
 use quarto_source_map::SourceInfo;
 use serde::{Deserialize, Serialize};
@@ -683,6 +683,42 @@ impl ConfigValue {
         }
     }
 
+    /// Convert this value to plain JSON.
+    ///
+    /// Scalars keep their YAML type, markdown content becomes its plain
+    /// text, and `!path`/`!glob`/`!expr` values become their strings.
+    pub fn to_json(&self) -> serde_json::Value {
+        match &self.value {
+            ConfigValueKind::Scalar(yaml) => yaml_to_serde_value(yaml),
+            ConfigValueKind::PandocInlines(inlines) => {
+                serde_json::Value::String(inlines_to_plain_text(inlines))
+            }
+            ConfigValueKind::PandocBlocks(blocks) => {
+                let paragraphs: Vec<String> = blocks
+                    .iter()
+                    .filter_map(|block| match block {
+                        Block::Plain(p) => Some(inlines_to_plain_text(&p.content)),
+                        Block::Paragraph(p) => Some(inlines_to_plain_text(&p.content)),
+                        _ => None,
+                    })
+                    .collect();
+                serde_json::Value::String(paragraphs.join("\n\n"))
+            }
+            ConfigValueKind::Path(s) | ConfigValueKind::Glob(s) | ConfigValueKind::Expr(s) => {
+                serde_json::Value::String(s.clone())
+            }
+            ConfigValueKind::Array(items) => {
+                serde_json::Value::Array(items.iter().map(ConfigValue::to_json).collect())
+            }
+            ConfigValueKind::Map(entries) => serde_json::Value::Object(
+                entries
+                    .iter()
+                    .map(|entry| (entry.key.clone(), entry.value.to_json()))
+                    .collect(),
+            ),
+        }
+    }
+
     /// Check if this is a null/empty value.
     pub fn is_null(&self) -> bool {
         matches!(&self.value, ConfigValueKind::Scalar(Yaml::Null))
@@ -1057,6 +1093,45 @@ mod tests {
         assert!(!int_val.is_null());
     }
 
+    #[test]
+    fn test_to_json() {
+        use crate::inline::{Inline, Str};
+
+        let title = ConfigValue::new_inlines(
+            vec![Inline::Str(Str {
+                text: "Report".to_string(),
+                source_info: SourceInfo::default(),
+            })],
+            SourceInfo::default(),
+        );
+        let value = ConfigValue::new_map(
+            vec![
+                ConfigMapEntry {
+                    key: "title".to_string(),
+                    key_source: SourceInfo::default(),
+                    value: title,
+                },
+                ConfigMapEntry {
+                    key: "sizes".to_string(),
+                    key_source: SourceInfo::default(),
+                    value: ConfigValue::new_array(
+                        vec![
+                            ConfigValue::new_scalar(Yaml::Integer(1), SourceInfo::default()),
+                            ConfigValue::new_bool(true, SourceInfo::default()),
+                            ConfigValue::null(SourceInfo::default()),
+                        ],
+                        SourceInfo::default(),
+                    ),
+                },
+            ],
+            SourceInfo::default(),
+        );
+        assert_eq!(
+            value.to_json(),
+            serde_json::json!({"title": "Report", "sizes": [1, true, null]})
+        );
+    }
+
     #[test]
     fn test_is_empty() {
         // Empty map
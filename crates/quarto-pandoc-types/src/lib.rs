@@ -18,6 +18,7 @@ pub mod custom;
 pub mod inline;
 pub mod list;
 pub mod meta;
+pub mod node_id;
 pub mod pandoc;
 pub mod shortcode;
 pub mod table;
@@ -39,6 +40,7 @@ pub use inline::{
 };
 pub use list::{ListAttributes, ListNumberDelim, ListNumberStyle};
 pub use meta::{Meta, MetaValue};
+pub use node_id::{BlockPath, NodeId, NodeIds, assign_node_ids, block_at};
 pub use pandoc::Pandoc;
 pub use shortcode::{Shortcode, ShortcodeArg};
 pub use table::{Alignment, Cell, ColSpec, ColWidth, Row, Table, TableBody, TableFoot, TableHead};
@@ -0,0 +1,314 @@
+/*
+ * node_id.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Stable block identities for precise preview updates.
+ */
+
+//! Stable identities for blocks that survive edits and reconciliation.
+//!
+//! [`assign_node_ids`] gives every block, including blocks nested in
+//! containers, a [`NodeId`] derived from:
+//!
+//! - a content hash that ignores source locations. Leaf blocks hash their
+//!   whole content. Containers hash only their kind and attributes, so
+//!   editing a child does not change the container's identity;
+//! - the parent's id;
+//! - a positional salt: the number of earlier siblings with the same
+//!   content hash.
+//!
+//! Editing, inserting or removing a block changes only that block's id (and
+//! the ids of later identical siblings), so a preview can patch exactly the
+//! DOM nodes whose ids changed instead of diffing by index. Because ids are
+//! computed from the AST alone, a reconciled document gets the same ids as
+//! the document it kept nodes from.
+//!
+//! Ids live in a side table keyed by [`BlockPath`] rather than in the AST,
+//! so they never affect structural comparison; [`block_at`] finds the block
+//! at a path. They are computed with `std`'s `DefaultHasher`, so ids from
+//! different builds are not comparable.
+
+use crate::block::Block;
+use crate::custom::Slot;
+use crate::table::{Row, Table};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// A stable identity for a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NodeId(pub u64);
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "n{:016x}", self.0)
+    }
+}
+
+/// The position of a block in a document: the index of the block among the
+/// top-level blocks, followed by its index among each enclosing container's
+/// child blocks.
+///
+/// A container's child blocks are numbered in document order across all of
+/// its block sequences (e.g. all items of a list, all cells of a table).
+pub type BlockPath = Vec<usize>;
+
+/// Ids assigned by [`assign_node_ids`], keyed by block path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeIds {
+    ids: HashMap<BlockPath, NodeId>,
+}
+
+impl NodeIds {
+    /// The id of the block at `path`.
+    pub fn get(&self, path: &[usize]) -> Option<NodeId> {
+        self.ids.get(path).copied()
+    }
+
+    /// The path of the block with `id`.
+    pub fn path_of(&self, id: NodeId) -> Option<&BlockPath> {
+        self.ids
+            .iter()
+            .find_map(|(path, node_id)| (*node_id == id).then_some(path))
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&BlockPath, NodeId)> {
+        self.ids.iter().map(|(path, id)| (path, *id))
+    }
+}
+
+/// Parent id used for top-level blocks.
+const ROOT: NodeId = NodeId(0);
+
+/// Serialized field names holding source locations, which don't count as
+/// content.
+const SOURCE_FIELDS: &[&str] = &[
+    "source_info",
+    "attr_source",
+    "target_source",
+    "id_source",
+    "key_source",
+];
+
+/// Assign a stable [`NodeId`] to every block in `blocks`, recursively.
+pub fn assign_node_ids(blocks: &[Block]) -> NodeIds {
+    let mut ids = NodeIds::default();
+    let mut path = Vec::new();
+    assign_children(blocks.iter().collect(), ROOT, &mut path, &mut ids);
+    ids
+}
+
+/// The block at `path` in `blocks`, numbered as in [`assign_node_ids`].
+pub fn block_at<'a>(blocks: &'a [Block], path: &[usize]) -> Option<&'a Block> {
+    let (first, rest) = path.split_first()?;
+    let mut block = blocks.get(*first)?;
+    for index in rest {
+        block = *child_blocks(block).get(*index)?;
+    }
+    Some(block)
+}
+
+fn assign_children(children: Vec<&Block>, parent: NodeId, path: &mut BlockPath, ids: &mut NodeIds) {
+    let mut occurrences: HashMap<u64, u64> = HashMap::new();
+    for (index, block) in children.into_iter().enumerate() {
+        let content = content_hash(block);
+        let occurrence = occurrences.entry(content).or_insert(0);
+        let id = NodeId(hash_of(&(parent.0, content, *occurrence)));
+        *occurrence += 1;
+
+        path.push(index);
+        ids.ids.insert(path.clone(), id);
+        assign_children(child_blocks(block), id, path, ids);
+        path.pop();
+    }
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hash of the parts of a block that identify it: kind and attributes for
+/// containers, everything but source locations for leaf blocks.
+fn content_hash(block: &Block) -> u64 {
+    let key = match block {
+        Block::Div(div) => serde_json::to_string(&("Div", &div.attr)),
+        Block::BlockQuote(_) => Ok("BlockQuote".to_string()),
+        Block::BulletList(_) => Ok("BulletList".to_string()),
+        Block::OrderedList(list) => serde_json::to_string(&("OrderedList", &list.attr)),
+        Block::DefinitionList(_) => Ok("DefinitionList".to_string()),
+        Block::Figure(figure) => serde_json::to_string(&("Figure", &figure.attr)),
+        Block::Table(table) => serde_json::to_string(&("Table", &table.attr)),
+        Block::NoteDefinitionFencedBlock(note) => {
+            serde_json::to_string(&("NoteDefinitionFencedBlock", &note.id))
+        }
+        Block::Custom(custom) => serde_json::to_string(&(
+            "Custom",
+            &custom.type_name,
+            &custom.attr,
+            &custom.plain_data,
+        )),
+        _ => serde_json::to_value(block).map(|mut value| {
+            strip_source_fields(&mut value);
+            value.to_string()
+        }),
+    };
+    // AST nodes always serialize; fall back to the kind alone just in case
+    hash_of(&key.unwrap_or_else(|_| format!("{:?}", std::mem::discriminant(block))))
+}
+
+fn strip_source_fields(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, _| !SOURCE_FIELDS.contains(&key.as_str()));
+            map.values_mut().for_each(strip_source_fields);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_source_fields),
+        _ => {}
+    }
+}
+
+/// A container's child blocks, in document order.
+fn child_blocks(block: &Block) -> Vec<&Block> {
+    match block {
+        Block::Div(div) => div.content.iter().collect(),
+        Block::BlockQuote(quote) => quote.content.iter().collect(),
+        Block::Figure(figure) => figure.content.iter().collect(),
+        Block::NoteDefinitionFencedBlock(note) => note.content.iter().collect(),
+        Block::BulletList(list) => list.content.iter().flatten().collect(),
+        Block::OrderedList(list) => list.content.iter().flatten().collect(),
+        Block::DefinitionList(list) => list
+            .content
+            .iter()
+            .flat_map(|(_, definitions)| definitions.iter().flatten())
+            .collect(),
+        Block::Table(table) => table_rows(table)
+            .flat_map(|row| row.cells.iter())
+            .flat_map(|cell| cell.content.iter())
+            .collect(),
+        Block::Custom(custom) => custom
+            .slots
+            .values()
+            .flat_map(|slot| match slot {
+                Slot::Block(block) => std::slice::from_ref(block.as_ref()),
+                Slot::Blocks(blocks) => blocks.as_slice(),
+                Slot::Inline(_) | Slot::Inlines(_) => &[] as &[Block],
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn table_rows(table: &Table) -> impl Iterator<Item = &Row> {
+    table
+        .head
+        .rows
+        .iter()
+        .chain(
+            table
+                .bodies
+                .iter()
+                .flat_map(|body| body.head.iter().chain(body.body.iter())),
+        )
+        .chain(table.foot.rows.iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attr::AttrSourceInfo;
+    use crate::block::{Div, Paragraph};
+    use crate::inline::{Inline, Str};
+    use quarto_source_map::{FileId, SourceInfo};
+
+    fn para(text: &str, offset: usize) -> Block {
+        Block::Paragraph(Paragraph {
+            content: vec![Inline::Str(Str {
+                text: text.to_string(),
+                source_info: SourceInfo::original(FileId(0), offset, offset + text.len()),
+            })],
+            source_info: SourceInfo::original(FileId(0), offset, offset + text.len()),
+        })
+    }
+
+    fn div(content: Vec<Block>) -> Block {
+        Block::Div(Div {
+            attr: ("box".to_string(), vec![], Default::default()),
+            content,
+            source_info: SourceInfo::original(FileId(0), 0, 0),
+            attr_source: AttrSourceInfo::empty(),
+        })
+    }
+
+    #[test]
+    fn test_ids_ignore_source_locations() {
+        let a = assign_node_ids(&[para("one", 0)]);
+        let b = assign_node_ids(&[para("one", 100)]);
+        assert_eq!(a.get(&[0]), b.get(&[0]));
+    }
+
+    #[test]
+    fn test_ids_survive_insertions() {
+        let before = assign_node_ids(&[para("one", 0), para("two", 5)]);
+        let after = assign_node_ids(&[para("new", 0), para("one", 5), para("two", 10)]);
+        assert_eq!(before.get(&[0]), after.get(&[1]));
+        assert_eq!(before.get(&[1]), after.get(&[2]));
+        assert_eq!(after.path_of(before.get(&[1]).unwrap()), Some(&vec![2]));
+    }
+
+    #[test]
+    fn test_edited_block_gets_new_id() {
+        let before = assign_node_ids(&[para("one", 0), para("two", 5)]);
+        let after = assign_node_ids(&[para("uno", 0), para("two", 5)]);
+        assert_ne!(before.get(&[0]), after.get(&[0]));
+        assert_eq!(before.get(&[1]), after.get(&[1]));
+    }
+
+    #[test]
+    fn test_identical_siblings_are_salted() {
+        let ids = assign_node_ids(&[para("same", 0), para("same", 5)]);
+        assert_eq!(ids.len(), 2);
+        assert_ne!(ids.get(&[0]), ids.get(&[1]));
+    }
+
+    #[test]
+    fn test_container_id_survives_child_edit() {
+        let before = assign_node_ids(&[div(vec![para("one", 0), para("two", 5)])]);
+        let after = assign_node_ids(&[div(vec![para("uno", 0), para("two", 5)])]);
+        assert_eq!(before.get(&[0]), after.get(&[0]));
+        assert_ne!(before.get(&[0, 0]), after.get(&[0, 0]));
+        assert_eq!(before.get(&[0, 1]), after.get(&[0, 1]));
+    }
+
+    #[test]
+    fn test_block_at_follows_paths() {
+        let blocks = [para("one", 0), div(vec![para("two", 5), para("three", 10)])];
+        assert_eq!(block_at(&blocks, &[1, 1]), Some(&para("three", 10)));
+        assert_eq!(block_at(&blocks, &[0]), Some(&blocks[0]));
+        assert!(block_at(&blocks, &[0, 0]).is_none());
+        assert!(block_at(&blocks, &[]).is_none());
+        for (path, _) in assign_node_ids(&blocks).iter() {
+            assert!(block_at(&blocks, path).is_some());
+        }
+    }
+
+    #[test]
+    fn test_nested_ids_depend_on_parent() {
+        let ids = assign_node_ids(&[para("one", 0), div(vec![para("one", 5)])]);
+        assert_eq!(ids.len(), 3);
+        assert_ne!(ids.get(&[0]), ids.get(&[1, 0]));
+        assert!(ids.get(&[0, 0]).is_none());
+    }
+}
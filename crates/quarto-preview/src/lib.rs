@@ -0,0 +1,29 @@
+/*
+ * quarto-preview
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Rendering for the quarto-hub preview.
+ */
+
+//! Rendering for the quarto-hub preview.
+//!
+//! The hub's browser client (`wasm-quarto-hub-client`) renders documents
+//! from its virtual file system. What its render entry points do beyond
+//! calling the pipeline lives here, written against [`SystemRuntime`] so it
+//! compiles to both native and WASM targets and can be tested natively:
+//!
+//! - [`outputs`]: the binary files a rendered page refers to, returned with
+//!   it so the preview can serve them.
+//! - [`project`]: rendering every document of a project into the runtime's
+//!   file system, with links between documents pointing at their outputs.
+//! - [`render_patch`]: patches between two renders of a page, so the preview
+//!   updates without a full reload.
+//!
+//! [`SystemRuntime`]: quarto_system_runtime::SystemRuntime
+
+pub mod outputs;
+pub mod project;
+pub mod render_patch;
+
+pub use outputs::render_outputs;
+pub use project::{DocumentRender, ProjectRender, render_project};
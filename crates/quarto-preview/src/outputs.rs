@@ -0,0 +1,185 @@
+/*
+ * outputs.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Binary outputs of a render.
+//!
+//! A rendered page refers to files the preview can't fetch from a server:
+//! images (including the figures of frozen execution results), fonts and
+//! downloadable files. They are returned with the page, base64-encoded and
+//! keyed by their URL relative to the document, so the preview can serve
+//! them itself.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use base64::Engine;
+use quarto_core::ArtifactStore;
+use quarto_system_runtime::SystemRuntime;
+use quarto_wasm_protocol::RenderOutput;
+
+/// Collect the binary artifacts of a render: images and other resources the
+/// document refers to (read through the runtime) and generated non-text files.
+///
+/// Text artifacts (CSS, JavaScript) are left out; the page links or inlines
+/// them already. Returns `None` when there is nothing to serve.
+pub fn render_outputs(
+    runtime: &dyn SystemRuntime,
+    artifacts: &ArtifactStore,
+    doc_dir: &Path,
+) -> Option<BTreeMap<String, RenderOutput>> {
+    let mut outputs = BTreeMap::new();
+    for (_key, artifact) in artifacts.iter() {
+        let Some(path) = &artifact.path else {
+            continue;
+        };
+        if artifact.is_text() {
+            continue;
+        }
+        let content = if artifact.content.is_empty() {
+            match runtime.file_read(path) {
+                Ok(content) => content,
+                Err(_) => continue,
+            }
+        } else {
+            artifact.content.clone()
+        };
+        let content_type = if artifact.content_type == "application/octet-stream" {
+            content_type_for(path).to_string()
+        } else {
+            artifact.content_type.clone()
+        };
+        let url = path
+            .strip_prefix(doc_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        outputs.insert(
+            url,
+            RenderOutput {
+                content_type,
+                content: base64::engine::general_purpose::STANDARD.encode(&content),
+            },
+        );
+    }
+    (!outputs.is_empty()).then_some(outputs)
+}
+
+/// Guess the MIME type of a binary output from its extension.
+pub fn content_type_for(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+    match ext.as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        Some("ico") => "image/x-icon",
+        Some("pdf") => "application/pdf",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("zip") => "application/zip",
+        Some("csv") => "text/csv",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_core::{
+        Artifact, BinaryDependencies, Format, HtmlRenderConfig, ProjectContext, RenderContext,
+        RenderOptions, render_qmd_to_html,
+    };
+    use quarto_system_runtime::NativeRuntime;
+    use std::fs;
+    use std::sync::Arc;
+
+    const PNG: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+    #[test]
+    fn test_content_type_for() {
+        assert_eq!(content_type_for(Path::new("plot-1.PNG")), "image/png");
+        assert_eq!(content_type_for(Path::new("font.woff2")), "font/woff2");
+        assert_eq!(
+            content_type_for(Path::new("data.bin")),
+            "application/octet-stream"
+        );
+    }
+
+    #[test]
+    fn test_render_outputs_skip_text_and_missing_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let runtime = NativeRuntime::new();
+        let mut artifacts = ArtifactStore::new();
+        artifacts.store(
+            "css:styles",
+            Artifact::from_string("p {}", "text/css").with_path(dir.join("styles.css")),
+        );
+        artifacts.store(
+            "resource:image:0",
+            Artifact::from_path(dir.join("missing.png"), "application/octet-stream"),
+        );
+        artifacts.store(
+            "intermediate:html:0",
+            Artifact::from_string("x", "text/html"),
+        );
+        assert!(render_outputs(&runtime, &artifacts, dir).is_none());
+    }
+
+    #[test]
+    fn test_render_outputs_include_frozen_plot() {
+        // A frozen result refers to the figures its execution wrote next to
+        // the document; they must come back with the page
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let figure = dir.join("index_files/figure-html/plot-1.png");
+        fs::create_dir_all(figure.parent().unwrap()).unwrap();
+        fs::write(&figure, PNG).unwrap();
+        let input = dir.join("index.qmd");
+        let content = "---\ntitle: Plot\n---\n\n![](index_files/figure-html/plot-1.png)\n";
+        fs::write(&input, content).unwrap();
+
+        let runtime: Arc<dyn SystemRuntime> = Arc::new(NativeRuntime::new());
+        let project = ProjectContext::discover(&input, runtime.as_ref()).unwrap();
+        let binaries = BinaryDependencies::new();
+        let format = Format::html();
+        let options = RenderOptions {
+            execute: false,
+            ..Default::default()
+        };
+        let mut ctx = RenderContext::new(&project, &project.files[0], &format, &binaries)
+            .with_options(options);
+        pollster::block_on(render_qmd_to_html(
+            content.as_bytes(),
+            &input.to_string_lossy(),
+            &mut ctx,
+            &HtmlRenderConfig::default(),
+            runtime.clone(),
+        ))
+        .unwrap();
+
+        let doc_dir = project.files[0].input.parent().unwrap();
+        let outputs = render_outputs(runtime.as_ref(), &ctx.artifacts, doc_dir).unwrap();
+        let plot = &outputs["index_files/figure-html/plot-1.png"];
+        assert_eq!(plot.content_type, "image/png");
+        assert_eq!(
+            base64::engine::general_purpose::STANDARD
+                .decode(&plot.content)
+                .unwrap(),
+            PNG
+        );
+    }
+}
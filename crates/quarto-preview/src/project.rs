@@ -0,0 +1,426 @@
+/*
+ * project.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Project renders for the preview.
+//!
+//! The project is found from `_quarto.yml` in a directory or its parents.
+//! Documents are rendered in the project's order (chapter order for books),
+//! sharing cross-references, without executing code, and their outputs are
+//! written through the runtime under the project's output directory (e.g.
+//! `_site/`). Links between documents point to their outputs, and the
+//! resources documents refer to are copied to the output directory.
+//!
+//! A document that fails to render doesn't stop the others; its error is in
+//! its [`DocumentRender`].
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use quarto_core::{
+    BinaryDependencies, Format, HtmlRenderConfig, ProjectContext, ProjectCrossrefs, QuartoError,
+    RenderContext, RenderOptions, extract_format_metadata, render_qmd_to_html,
+};
+use quarto_error_reporting::DiagnosticMessage;
+use quarto_source_map::SourceContext;
+use quarto_system_runtime::SystemRuntime;
+
+/// The render of a project.
+pub struct ProjectRender {
+    pub project: ProjectContext,
+    /// The documents, in the order they were rendered
+    pub documents: Vec<DocumentRender>,
+    /// The resources copied to the output directory
+    pub resources: BTreeSet<PathBuf>,
+}
+
+impl ProjectRender {
+    /// Whether every document rendered.
+    pub fn success(&self) -> bool {
+        self.documents.iter().all(|doc| doc.result.is_ok())
+    }
+
+    /// A path of the project relative to its directory, with `/` separators.
+    pub fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.project.dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+}
+
+/// The render of a document of a project.
+pub struct DocumentRender {
+    pub input: PathBuf,
+    /// Where the output was written
+    pub output: PathBuf,
+    /// The render's warnings, or why it failed
+    pub result: Result<(Vec<DiagnosticMessage>, SourceContext), QuartoError>,
+}
+
+/// Render the project `project_dir` is in, writing its outputs through
+/// `runtime`.
+///
+/// Fails when there is no project: no `_quarto.yml` in `project_dir` or its
+/// parents.
+pub async fn render_project(
+    project_dir: &str,
+    runtime: Arc<dyn SystemRuntime>,
+) -> Result<ProjectRender, QuartoError> {
+    let project = ProjectContext::discover(project_dir, runtime.as_ref())
+        .map_err(|e| QuartoError::other(format!("Failed to discover project: {}", e)))?;
+    if project.config.is_none() {
+        return Err(QuartoError::other(format!(
+            "No _quarto.yml found in {} or its parents",
+            project_dir
+        )));
+    }
+
+    let binaries = BinaryDependencies::new();
+    let crossrefs = ProjectCrossrefs::new();
+    let outputs = document_outputs(&project, &binaries);
+
+    let mut documents = Vec::new();
+    let mut resources = BTreeSet::new();
+    for doc in &project.files {
+        let content = match runtime.file_read(&doc.input) {
+            Ok(content) => content,
+            Err(e) => {
+                documents.push(DocumentRender {
+                    input: doc.input.clone(),
+                    output: outputs[&doc.input].clone(),
+                    result: Err(QuartoError::other(format!("Failed to read file: {}", e))),
+                });
+                continue;
+            }
+        };
+        let format_metadata = std::str::from_utf8(&content)
+            .ok()
+            .and_then(|content| extract_format_metadata(content, "html").ok())
+            .unwrap_or_default();
+        let format = Format::html().with_metadata(format_metadata);
+        let options = RenderOptions {
+            verbose: false,
+            execute: false,
+            use_freeze: false,
+            output_path: None,
+            crossrefs: Some(crossrefs.clone()),
+            ..Default::default()
+        };
+        let mut ctx = RenderContext::new(&project, doc, &format, &binaries).with_options(options);
+        let output_path = ctx.output_path();
+        let source_name = doc.input.to_string_lossy();
+
+        let result = render_qmd_to_html(
+            &content,
+            &source_name,
+            &mut ctx,
+            &HtmlRenderConfig::default(),
+            runtime.clone(),
+        )
+        .await;
+        let result = result.and_then(|output| {
+            for (key, artifact) in ctx.artifacts.iter() {
+                let Some(path) = &artifact.path else {
+                    continue;
+                };
+                if key.starts_with("resource:") {
+                    if let Some(copied) = copy_resource(runtime.as_ref(), &project, path) {
+                        resources.insert(copied);
+                    }
+                } else {
+                    runtime
+                        .file_write(path, &artifact.content)
+                        .map_err(|e| QuartoError::other(e.to_string()))?;
+                }
+            }
+            let html = resolve_document_links(&output.html, &doc.input, &output_path, &outputs);
+            runtime
+                .file_write(&output_path, html.as_bytes())
+                .map_err(|e| QuartoError::other(e.to_string()))?;
+            Ok((output.diagnostics, output.source_context))
+        });
+        documents.push(DocumentRender {
+            input: doc.input.clone(),
+            output: output_path,
+            result,
+        });
+    }
+
+    Ok(ProjectRender {
+        project,
+        documents,
+        resources,
+    })
+}
+
+/// Where every document of the project goes, by input.
+fn document_outputs(
+    project: &ProjectContext,
+    binaries: &BinaryDependencies,
+) -> HashMap<PathBuf, PathBuf> {
+    let format = Format::html();
+    project
+        .files
+        .iter()
+        .map(|doc| {
+            let output = RenderContext::new(project, doc, &format, binaries).output_path();
+            (doc.input.clone(), output)
+        })
+        .collect()
+}
+
+/// Copy a resource of the project to the output directory, returning where
+/// it was copied. Resources outside the project, missing ones, and those of
+/// projects that render next to their inputs aren't copied.
+fn copy_resource(
+    runtime: &dyn SystemRuntime,
+    project: &ProjectContext,
+    path: &Path,
+) -> Option<PathBuf> {
+    if project.output_dir == project.dir {
+        return None;
+    }
+    let path = normalize_path(path);
+    let relative = path.strip_prefix(&project.dir).ok()?;
+    let content = runtime.file_read(&path).ok()?;
+    let destination = project.output_dir.join(relative);
+    runtime.file_write(&destination, &content).ok()?;
+    Some(destination)
+}
+
+/// Point the links of a rendered page to other documents of the project at
+/// their outputs: `chapter.qmd#sec-x` becomes `chapter.html#sec-x`.
+fn resolve_document_links(
+    html: &str,
+    input: &Path,
+    output: &Path,
+    outputs: &HashMap<PathBuf, PathBuf>,
+) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find("href=\"") {
+        let value_start = start + "href=\"".len();
+        let Some(len) = rest[value_start..].find('"') else {
+            break;
+        };
+        let href = &rest[value_start..value_start + len];
+        result.push_str(&rest[..value_start]);
+        match resolve_href(href, input, output, outputs) {
+            Some(resolved) => result.push_str(&resolved),
+            None => result.push_str(href),
+        }
+        rest = &rest[value_start + len..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The href of a link to another document of the project, from a page.
+fn resolve_href(
+    href: &str,
+    input: &Path,
+    output: &Path,
+    outputs: &HashMap<PathBuf, PathBuf>,
+) -> Option<String> {
+    if href.is_empty()
+        || href.contains("://")
+        || href.starts_with(['#', '/'])
+        || href.starts_with("mailto:")
+    {
+        return None;
+    }
+    let (path, fragment) = match href.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (href, None),
+    };
+    let target = outputs.get(&normalize_path(&input.parent()?.join(path)))?;
+    let mut resolved = relative_path(target, output.parent()?);
+    if let Some(fragment) = fragment {
+        resolved.push('#');
+        resolved.push_str(fragment);
+    }
+    Some(resolved)
+}
+
+/// Resolve `.` and `..` in a path, without touching the filesystem.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// The relative URL of `path` from the directory `from`.
+fn relative_path(path: &Path, from: &Path) -> String {
+    let path: Vec<Component> = path.components().collect();
+    let from: Vec<Component> = from.components().collect();
+    let common = path.iter().zip(&from).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        path[common..]
+            .iter()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_system_runtime::NativeRuntime;
+    use std::fs;
+
+    fn write(dir: &Path, path: &str, content: &str) {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn render(dir: &Path) -> Result<ProjectRender, QuartoError> {
+        let runtime: Arc<dyn SystemRuntime> = Arc::new(NativeRuntime::new());
+        pollster::block_on(render_project(&dir.to_string_lossy(), runtime))
+    }
+
+    #[test]
+    fn test_resolve_href() {
+        let outputs = HashMap::from([
+            (
+                PathBuf::from("/p/about.qmd"),
+                PathBuf::from("/p/_site/about.html"),
+            ),
+            (
+                PathBuf::from("/p/posts/a.qmd"),
+                PathBuf::from("/p/_site/posts/a.html"),
+            ),
+        ]);
+        let resolve = |href, input: &str, output: &str| {
+            resolve_href(href, Path::new(input), Path::new(output), &outputs)
+        };
+
+        let (index, index_out) = ("/p/index.qmd", "/p/_site/index.html");
+        assert_eq!(
+            resolve("about.qmd#sec-x", index, index_out).as_deref(),
+            Some("about.html#sec-x")
+        );
+        assert_eq!(
+            resolve("./posts/a.qmd", index, index_out).as_deref(),
+            Some("posts/a.html")
+        );
+        assert_eq!(
+            resolve("../about.qmd", "/p/posts/a.qmd", "/p/_site/posts/a.html").as_deref(),
+            Some("../about.html")
+        );
+        for href in [
+            "#sec-x",
+            "https://quarto.org/about.qmd",
+            "/about.qmd",
+            "missing.qmd",
+        ] {
+            assert_eq!(resolve(href, index, index_out), None, "{}", href);
+        }
+    }
+
+    #[test]
+    fn test_resolve_document_links() {
+        let outputs = HashMap::from([(
+            PathBuf::from("/p/about.qmd"),
+            PathBuf::from("/p/_site/about.html"),
+        )]);
+        let html = r#"<a href="about.qmd">About</a> <a href="https://x.org">X</a>"#;
+        assert_eq!(
+            resolve_document_links(
+                html,
+                Path::new("/p/index.qmd"),
+                Path::new("/p/_site/index.html"),
+                &outputs
+            ),
+            r#"<a href="about.html">About</a> <a href="https://x.org">X</a>"#
+        );
+    }
+
+    #[test]
+    fn test_render_project() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        write(
+            dir,
+            "_quarto.yml",
+            "project:\n  type: website\n  output-dir: _site\n",
+        );
+        write(
+            dir,
+            "index.qmd",
+            "---\ntitle: Home\n---\n\nSee [the post](posts/post.qmd#sec-more).\n\n![](logo.png)\n",
+        );
+        write(
+            dir,
+            "posts/post.qmd",
+            "---\ntitle: Post\n---\n\n## More {#sec-more}\n\nBack [home](../index.qmd).\n",
+        );
+        fs::write(dir.join("logo.png"), [0x89, 0x50, 0x4E, 0x47]).unwrap();
+
+        // Discovery works from a subdirectory of the project
+        let render = render(&dir.join("posts")).unwrap();
+        assert!(render.success());
+        let outputs: Vec<String> = render
+            .documents
+            .iter()
+            .map(|doc| render.relative(&doc.output))
+            .collect();
+        assert_eq!(outputs, ["_site/index.html", "_site/posts/post.html"]);
+        let resources: Vec<String> = render
+            .resources
+            .iter()
+            .map(|path| render.relative(path))
+            .collect();
+        assert_eq!(resources, ["_site/logo.png"]);
+
+        let index = fs::read_to_string(dir.join("_site/index.html")).unwrap();
+        assert!(index.contains(r#"href="posts/post.html#sec-more""#));
+        let post = fs::read_to_string(dir.join("_site/posts/post.html")).unwrap();
+        assert!(post.contains(r#"href="../index.html""#));
+        assert!(dir.join("_site/logo.png").is_file());
+    }
+
+    #[test]
+    fn test_render_project_keeps_going_after_a_failure() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        write(
+            dir,
+            "_quarto.yml",
+            "project:\n  type: website\n  output-dir: _site\n",
+        );
+        write(dir, "a.qmd", "---\ntitle: A\n---\n\nFine.\n");
+        write(dir, "b.qmd", "An *a **b\n");
+
+        let render = render(dir).unwrap();
+        assert!(!render.success());
+        let failed: Vec<String> = render
+            .documents
+            .iter()
+            .filter(|doc| doc.result.is_err())
+            .map(|doc| render.relative(&doc.input))
+            .collect();
+        assert_eq!(failed, ["b.qmd"]);
+        assert!(dir.join("_site/a.html").is_file());
+    }
+
+    #[test]
+    fn test_render_without_project() {
+        let temp = tempfile::tempdir().unwrap();
+        write(temp.path(), "doc.qmd", "Hello\n");
+        let error = render(temp.path()).err().unwrap();
+        assert!(error.to_string().starts_with("No _quarto.yml found in"));
+    }
+}
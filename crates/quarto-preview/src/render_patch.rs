@@ -0,0 +1,581 @@
+/*
+ * render_patch.rs
+ * Copyright (c) 2025 Posit, PBC
+ *
+ * Patches between two renders of a document, for updating the preview
+ * without a full reload.
+ */
+
+//! Patches between renders.
+//!
+//! The content of a rendered page (the children of
+//! `<main id="quarto-document-content">`) is split into regions: its
+//! top-level elements, and the children of its `<section>`s, recursively.
+//! Every region has a stable node ID in a `data-quarto-node` attribute:
+//!
+//! - Blocks rendered with `node-ids` carry the ID the HTML writer gave them
+//!   from `quarto_pandoc_types::assign_node_ids`. A block's ID changes only
+//!   when its own content does, and a container (such as a section) keeps
+//!   its ID when only its children change.
+//! - Other regions (those added by the template) get an ID from their HTML:
+//!   their opening tag for sections, their whole HTML otherwise. Identical
+//!   regions get the same ID with an occurrence suffix.
+//!
+//! A patch between two renders removes the regions whose IDs are gone and
+//! inserts the new ones after their preceding sibling; regions with the same
+//! ID are kept, so the preview keeps their DOM state (scroll position,
+//! widgets).
+//!
+//! Pages whose structure can't be split (no `<main>`, text directly in it)
+//! and renders whose page outside `<main>` changed (title, styles,
+//! navigation) need a full reload.
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use quarto_wasm_protocol::{RegionInsert, RenderPatch};
+
+/// The attribute holding node IDs.
+const NODE_ATTRIBUTE: &str = "data-quarto-node";
+
+/// Elements without content or closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Elements whose content is raw text.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea"];
+
+/// What is remembered of a render, to patch the next one against.
+#[derive(Debug, Clone)]
+pub struct RenderState {
+    /// Hash of the page outside the content regions.
+    shell: u64,
+    regions: Vec<RegionKey>,
+}
+
+#[derive(Debug, Clone)]
+struct RegionKey {
+    id: String,
+    children: Option<Vec<RegionKey>>,
+}
+
+/// A region of the content, as byte ranges of the page.
+struct Region {
+    id: String,
+    start: usize,
+    end: usize,
+    /// The position of the end of the opening tag, where the ID goes, or
+    /// `None` if the region already has one.
+    id_position: Option<usize>,
+    children: Option<Vec<Region>>,
+}
+
+/// Add node IDs to the regions of a rendered page that have none, returning the page with IDs and the
+/// state to patch the next render against, or `None` if the page can't be
+/// split into regions.
+pub fn annotate(html: &str) -> Option<(String, RenderState)> {
+    let (content_start, content_end) = content_range(html)?;
+    let mut counts = HashMap::new();
+    let regions = split_regions(html, content_start, content_end, &mut counts)?;
+
+    let mut insertions = Vec::new();
+    collect_insertions(&regions, &mut insertions);
+    insertions.sort_by_key(|(position, _)| *position);
+
+    let mut annotated = String::with_capacity(html.len() + insertions.len() * 32);
+    let mut last = 0;
+    for (position, id) in &insertions {
+        annotated.push_str(&html[last..*position]);
+        annotated.push_str(&format!(" {}=\"{}\"", NODE_ATTRIBUTE, id));
+        last = *position;
+    }
+    annotated.push_str(&html[last..]);
+
+    let state = RenderState {
+        shell: hash(&(&html[..content_start], &html[content_end..])),
+        regions: keys(&regions),
+    };
+    Some((annotated, state))
+}
+
+/// The patch from the render of `previous` to the annotated page `html`.
+pub fn diff(previous: &RenderState, html: &str, current: &RenderState) -> RenderPatch {
+    if previous.shell != current.shell {
+        return RenderPatch::full_reload();
+    }
+    // The annotated page splits into the same regions, with IDs in place
+    let Some((content_start, content_end)) = content_range(html) else {
+        return RenderPatch::full_reload();
+    };
+    let Some(regions) = split_regions(html, content_start, content_end, &mut HashMap::new()) else {
+        return RenderPatch::full_reload();
+    };
+
+    let mut patch = RenderPatch::default();
+    diff_level(
+        &previous.regions,
+        &current.regions,
+        &regions,
+        None,
+        html,
+        &mut patch,
+    );
+    patch
+}
+
+fn diff_level(
+    previous: &[RegionKey],
+    current: &[RegionKey],
+    regions: &[Region],
+    parent: Option<&str>,
+    html: &str,
+    patch: &mut RenderPatch,
+) {
+    let matches = longest_common_subsequence(previous, current);
+    let mut matched_previous = vec![false; previous.len()];
+    let mut matched_current = vec![None; current.len()];
+    for &(i, j) in &matches {
+        matched_previous[i] = true;
+        matched_current[j] = Some(i);
+    }
+
+    for (key, matched) in previous.iter().zip(&matched_previous) {
+        if !matched {
+            patch.remove.push(key.id.clone());
+        }
+    }
+
+    let mut after: Option<String> = None;
+    for ((key, region), matched) in current.iter().zip(regions).zip(&matched_current) {
+        match matched {
+            Some(i) => {
+                if let (Some(previous_children), Some(children), Some(child_regions)) =
+                    (&previous[*i].children, &key.children, &region.children)
+                {
+                    diff_level(
+                        previous_children,
+                        children,
+                        child_regions,
+                        Some(&key.id),
+                        html,
+                        patch,
+                    );
+                }
+            }
+            None => patch.insert.push(RegionInsert {
+                id: key.id.clone(),
+                parent: parent.map(String::from),
+                after: after.clone(),
+                html: html[region.start..region.end].to_string(),
+            }),
+        }
+        after = Some(key.id.clone());
+    }
+}
+
+/// The pairs of indices of the longest common subsequence of IDs.
+fn longest_common_subsequence(a: &[RegionKey], b: &[RegionKey]) -> Vec<(usize, usize)> {
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if a[i].id == b[j].id {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].id == b[j].id {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+fn keys(regions: &[Region]) -> Vec<RegionKey> {
+    regions
+        .iter()
+        .map(|region| RegionKey {
+            id: region.id.clone(),
+            children: region.children.as_deref().map(keys),
+        })
+        .collect()
+}
+
+fn collect_insertions(regions: &[Region], insertions: &mut Vec<(usize, String)>) {
+    for region in regions {
+        if let Some(position) = region.id_position {
+            insertions.push((position, region.id.clone()));
+        }
+        if let Some(children) = &region.children {
+            collect_insertions(children, insertions);
+        }
+    }
+}
+
+/// The byte range of the content of `<main id="quarto-document-content">`.
+fn content_range(html: &str) -> Option<(usize, usize)> {
+    let id = html.find("id=\"quarto-document-content\"")?;
+    let open = html[..id].rfind("<main")?;
+    let start = open + tag_end(&html[open..])?;
+    let end = html.rfind("</main>")?;
+    (start <= end).then_some((start, end))
+}
+
+/// Split `html[start..end]` into regions, or `None` if it has text outside
+/// elements.
+fn split_regions(
+    html: &str,
+    start: usize,
+    end: usize,
+    counts: &mut HashMap<String, usize>,
+) -> Option<Vec<Region>> {
+    let mut regions = Vec::new();
+    let mut position = start;
+    while position < end {
+        let rest = &html[position..end];
+        let trimmed = rest.trim_start();
+        position += rest.len() - trimmed.len();
+        if trimmed.is_empty() {
+            break;
+        }
+        if trimmed.starts_with("<!--") {
+            position += trimmed.find("-->")? + 3;
+            continue;
+        }
+        let name = tag_name(trimmed)?;
+        let open_end = position + tag_end(trimmed)?;
+        let element_end = position + element_length(trimmed, &name)?;
+        if element_end > end {
+            return None;
+        }
+
+        // The ID goes before `>` or `/>`, and the space before them
+        let tag_close = if html[..open_end].ends_with("/>") {
+            open_end - 2
+        } else {
+            open_end - 1
+        };
+        let id_position = html[..tag_close].trim_end().len();
+        let children = if name == "section" {
+            let close_start = html[..element_end].rfind("</")?;
+            split_regions(html, open_end, close_start, counts)
+        } else {
+            None
+        };
+        // IDs from the writer, or from annotating the page, are kept
+        let (id, id_position) = match node_id(&html[position..open_end]) {
+            Some(id) => (id, None),
+            None => {
+                // Sections that split are identified by their opening tag,
+                // other regions by their whole HTML; nested IDs don't count
+                let identity = if children.is_some() {
+                    &html[position..open_end]
+                } else {
+                    &html[position..element_end]
+                };
+                let base = format!("q{:016x}", hash(&without_node_ids(identity)));
+                let count = counts.entry(base.clone()).or_insert(0);
+                *count += 1;
+                let id = if *count == 1 {
+                    base
+                } else {
+                    format!("{}-{}", base, count)
+                };
+                (id, Some(id_position))
+            }
+        };
+
+        regions.push(Region {
+            id,
+            start: position,
+            end: element_end,
+            id_position,
+            children,
+        });
+        position = element_end;
+    }
+    Some(regions)
+}
+
+/// HTML without its node ID attributes.
+fn without_node_ids(html: &str) -> String {
+    let marker = format!(" {}=\"", NODE_ATTRIBUTE);
+    let mut result = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find(&marker) {
+        result.push_str(&rest[..start]);
+        let value = &rest[start + marker.len()..];
+        let Some(end) = value.find('"') else {
+            break;
+        };
+        rest = &value[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The node ID in an opening tag.
+fn node_id(open_tag: &str) -> Option<String> {
+    let marker = format!("{}=\"", NODE_ATTRIBUTE);
+    let value = &open_tag[open_tag.find(&marker)? + marker.len()..];
+    Some(value[..value.find('"')?].to_string())
+}
+
+/// The lowercase name of the tag `html` starts with.
+fn tag_name(html: &str) -> Option<String> {
+    let name: String = html
+        .strip_prefix('<')?
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    (!name.is_empty()).then(|| name.to_ascii_lowercase())
+}
+
+/// The length of the tag `html` starts with, up to its `>`.
+fn tag_end(html: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in html.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The length of the element `html` starts with, through its closing tag.
+fn element_length(html: &str, name: &str) -> Option<usize> {
+    let open_end = tag_end(html)?;
+    if VOID_ELEMENTS.contains(&name) || html[..open_end].ends_with("/>") {
+        return Some(open_end);
+    }
+    if RAW_TEXT_ELEMENTS.contains(&name) {
+        let close = format!("</{}", name);
+        let rest = html[open_end..].to_ascii_lowercase();
+        let close_start = open_end + rest.find(&close)?;
+        return Some(close_start + tag_end(&html[close_start..])?);
+    }
+
+    let mut depth = 1;
+    let mut position = open_end;
+    while depth > 0 {
+        let next = position + html[position..].find('<')?;
+        let rest = &html[next..];
+        if rest.starts_with("<!--") {
+            position = next + rest.find("-->")? + 3;
+        } else if rest.starts_with("</") {
+            depth -= 1;
+            position = next + tag_end(rest)?;
+        } else if let Some(child) = tag_name(rest) {
+            if RAW_TEXT_ELEMENTS.contains(&child.as_str()) {
+                position = next + element_length(rest, &child)?;
+            } else {
+                let child_end = next + tag_end(rest)?;
+                if !VOID_ELEMENTS.contains(&child.as_str()) && !html[..child_end].ends_with("/>") {
+                    depth += 1;
+                }
+                position = child_end;
+            }
+        } else {
+            // `<` in text, or a doctype
+            position = next + 1;
+        }
+    }
+    Some(position)
+}
+
+/// A short hex digest of a rendered page.
+pub fn fingerprint(html: &str) -> String {
+    format!("{:016x}", hash(&html))
+}
+
+fn hash(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pampa::writers::html::{HtmlConfig, write_with_config};
+    use quarto_pandoc_types::{NodeIds, assign_node_ids};
+
+    fn page(title: &str, content: &str) -> String {
+        format!(
+            "<html><head><title>{}</title></head><body>\
+             <main class=\"content\" id=\"quarto-document-content\">{}</main>\
+             </body></html>",
+            title, content
+        )
+    }
+
+    /// A page with `qmd` written by the HTML writer with node IDs, and the
+    /// IDs of its blocks.
+    fn rendered_page(qmd: &str) -> (String, NodeIds) {
+        let (pandoc, _context, _warnings) = pampa::readers::qmd::read(
+            qmd.as_bytes(),
+            false,
+            "test.qmd",
+            &mut std::io::sink(),
+            true,
+            None,
+        )
+        .unwrap();
+        let config = HtmlConfig {
+            node_ids: true,
+            ..Default::default()
+        };
+        let mut body = Vec::new();
+        write_with_config(&pandoc, &mut body, config).unwrap();
+        let body = String::from_utf8(body).unwrap();
+        (page("Doc", &body), assign_node_ids(&pandoc.blocks))
+    }
+
+    /// Annotate two renders and diff them.
+    fn patch(before: &str, after: &str) -> (String, String, RenderPatch) {
+        let (before_html, before_state) = annotate(before).unwrap();
+        let (after_html, after_state) = annotate(after).unwrap();
+        let patch = diff(&before_state, &after_html, &after_state);
+        (before_html, after_html, patch)
+    }
+
+    /// The node ID of the first element in `html` whose opening tag contains
+    /// `marker`.
+    fn id_of(html: &str, marker: &str) -> String {
+        let start = html.find(marker).unwrap();
+        let open = html[..start].rfind('<').unwrap();
+        node_id(&html[open..open + tag_end(&html[open..]).unwrap()]).unwrap()
+    }
+
+    #[test]
+    fn test_section_keeps_id_when_content_changes() {
+        let before = page(
+            "Doc",
+            "<section id=\"intro\" class=\"level2\"><h2>Intro</h2><p>One</p><p>Two</p></section>",
+        );
+        let after = page(
+            "Doc",
+            "<section id=\"intro\" class=\"level2\"><h2>Intro</h2><p>One</p><p>Deux</p></section>",
+        );
+        let (before_html, after_html, patch) = patch(&before, &after);
+
+        let section = id_of(&before_html, "id=\"intro\"");
+        assert_eq!(section, id_of(&after_html, "id=\"intro\""));
+        assert!(!patch.full_reload);
+        assert_eq!(patch.remove, [id_of(&before_html, ">Two<")]);
+        assert_eq!(patch.insert.len(), 1);
+        assert_eq!(patch.insert[0].parent.as_deref(), Some(section.as_str()));
+        assert!(patch.insert[0].html.contains("Deux"));
+        assert!(!patch.remove.contains(&section));
+    }
+
+    #[test]
+    fn test_patch_after_in_block_edit() {
+        let qmd = "::: {#intro .section}\n\n## Intro\n\nOne.\n\nTwo.\n\n:::\n\nAfter.\n";
+        let (before, before_ids) = rendered_page(qmd);
+        let (after, after_ids) = rendered_page(&qmd.replace("Two.", "Two, edited."));
+        let (before_html, _, patch) = patch(&before, &after);
+        let id = |ids: &NodeIds, path: &[usize]| ids.get(path).unwrap().to_string();
+
+        // Every region already has the writer's ID
+        assert_eq!(before_html, before);
+        assert_eq!(id(&before_ids, &[0]), id(&after_ids, &[0]));
+        assert!(!patch.full_reload);
+        assert_eq!(patch.remove, [id(&before_ids, &[0, 2])]);
+        assert_eq!(patch.insert.len(), 1);
+        let insert = &patch.insert[0];
+        assert_eq!(insert.id, id(&after_ids, &[0, 2]));
+        assert_eq!(insert.parent, Some(id(&after_ids, &[0])));
+        assert_eq!(insert.after, Some(id(&after_ids, &[0, 1])));
+        assert!(insert.html.contains("Two, edited."), "{}", insert.html);
+    }
+
+    #[test]
+    fn test_insert_after_preceding_sibling() {
+        let before = page("Doc", "<p>One</p><p>Three</p>");
+        let after = page("Doc", "<p>One</p><p>Two</p><p>Three</p>");
+        let (_, after_html, patch) = patch(&before, &after);
+
+        assert!(patch.remove.is_empty());
+        assert_eq!(patch.insert.len(), 1);
+        let insert = &patch.insert[0];
+        assert_eq!(insert.parent, None);
+        assert_eq!(insert.after, Some(id_of(&after_html, ">One<")));
+        assert_eq!(insert.id, id_of(&after_html, ">Two<"));
+
+        // A new first region has no preceding sibling
+        let (_, _, patch) = self::patch(
+            &page("Doc", "<p>One</p>"),
+            &page("Doc", "<p>Zero</p><p>One</p>"),
+        );
+        assert_eq!(patch.insert[0].after, None);
+    }
+
+    #[test]
+    fn test_identical_regions_get_distinct_ids() {
+        let (html, _) = annotate(&page("Doc", "<p>Same</p><p>Same</p>")).unwrap();
+        let ids: Vec<String> = html
+            .match_indices(NODE_ATTRIBUTE)
+            .map(|(i, _)| node_id(&html[i..]).unwrap())
+            .collect();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[1], format!("{}-2", ids[0]));
+    }
+
+    #[test]
+    fn test_full_reload_when_shell_changes() {
+        let (_, _, patch) = patch(&page("One", "<p>Text</p>"), &page("Two", "<p>Text</p>"));
+        assert!(patch.full_reload);
+        assert!(patch.insert.is_empty() && patch.remove.is_empty());
+    }
+
+    #[test]
+    fn test_unsplittable_pages() {
+        assert!(annotate("<html><body><p>No main</p></body></html>").is_none());
+        assert!(annotate(&page("Doc", "Loose text<p>Para</p>")).is_none());
+    }
+
+    #[test]
+    fn test_void_and_raw_text_elements() {
+        let content = "<hr><img src=\"a.png\" /><script>if (a < b) { document.write(\"</p>\"); }</script>\
+                       <style>p > a { color: red; }</style><p>After</p>";
+        let (html, state) = annotate(&page("Doc", content)).unwrap();
+        assert_eq!(state.regions.len(), 5);
+        // IDs go inside the opening tags, before `/>` for self-closing ones
+        assert!(html.contains(&format!("<hr {}=\"", NODE_ATTRIBUTE)));
+        assert!(html.contains("src=\"a.png\" data-quarto-node=\""));
+        assert!(html.contains("\" /><script"));
+        // Markup inside raw text doesn't end or open elements
+        assert!(html.contains("document.write(\"</p>\"); }</script>"));
+
+        let changed = content.replace("red", "blue");
+        let (_, _, patch) = patch(&page("Doc", content), &page("Doc", &changed));
+        assert_eq!(patch.remove.len(), 1);
+        assert_eq!(patch.insert.len(), 1);
+        assert!(patch.insert[0].html.starts_with("<style"));
+    }
+
+    #[test]
+    fn test_unchanged_render() {
+        let content = "<section id=\"a\"><h2>A</h2><p>X</p></section><p>Y</p>";
+        let (_, _, patch) = patch(&page("Doc", content), &page("Doc", content));
+        assert!(!patch.full_reload);
+        assert!(patch.insert.is_empty() && patch.remove.is_empty());
+    }
+}
@@ -49,7 +49,7 @@ pub use choices::{
 pub use scaffold::{
     ProjectScaffold, ScaffoldContent, ScaffoldFileDef, ScaffoldedFile, get_scaffold,
 };
-pub use types::{CreateError, CreateProjectOptions, ProjectFile, ProjectType};
+pub use types::{CreateError, CreateProjectOptions, ProjectEngine, ProjectFile, ProjectType};
 
 use quarto_system_runtime::SystemRuntime;
 use serde_json::json;
@@ -133,6 +133,9 @@ pub struct CreateFromChoiceOptions {
 
     /// Project title (used in templates)
     pub title: String,
+
+    /// Engine of the project's documents (used in templates)
+    pub engine: ProjectEngine,
 }
 
 impl CreateFromChoiceOptions {
@@ -141,8 +144,15 @@ impl CreateFromChoiceOptions {
         Self {
             choice_id: choice_id.into(),
             title: title.into(),
+            engine: ProjectEngine::default(),
         }
     }
+
+    /// Set the engine of the project's documents.
+    pub fn with_engine(mut self, engine: ProjectEngine) -> Self {
+        self.engine = engine;
+        self
+    }
 }
 
 /// Create a new project from a user-facing choice.
@@ -200,7 +210,7 @@ pub async fn create_project_from_choice(
     })?;
 
     // Render the scaffold
-    create_scaffolded_files(runtime, &scaffold, &options.title).await
+    create_scaffolded_files(runtime, &scaffold, &options.title, options.engine).await
 }
 
 /// Create files from a project scaffold.
@@ -214,6 +224,7 @@ pub async fn create_project_from_choice(
 /// * `runtime` - The system runtime to use for EJS template rendering
 /// * `scaffold` - The project scaffold definition
 /// * `title` - Project title (used in templates)
+/// * `engine` - Engine of the project's documents (used in templates)
 ///
 /// # Returns
 ///
@@ -222,6 +233,7 @@ pub async fn create_scaffolded_files(
     runtime: &dyn SystemRuntime,
     scaffold: &ProjectScaffold,
     title: &str,
+    engine: ProjectEngine,
 ) -> Result<Vec<ScaffoldedFile>, CreateError> {
     // Check if JS execution is available (needed for EJS templates)
     if !runtime.js_available() {
@@ -235,6 +247,7 @@ pub async fn create_scaffolded_files(
         "title": title,
         "projectType": scaffold.target.project_type.id(),
         "template": scaffold.target.template,
+        "engine": engine.id(),
     });
 
     let mut files = Vec::with_capacity(scaffold.files.len());
@@ -340,6 +353,16 @@ mod tests {
         assert!(ids.contains(&"website"));
     }
 
+    #[test]
+    fn test_project_engine_from_str() {
+        assert_eq!(
+            "Jupyter".parse::<ProjectEngine>().unwrap(),
+            ProjectEngine::Jupyter
+        );
+        assert_eq!(ProjectEngine::default(), ProjectEngine::Markdown);
+        assert!("julia".parse::<ProjectEngine>().is_err());
+    }
+
     #[test]
     fn test_create_project_options() {
         let options = CreateProjectOptions::new(ProjectType::Website, "My Site");
@@ -456,6 +479,37 @@ mod integration_tests {
         assert!(quarto_yml.contains("type: website"));
     }
 
+    #[test]
+    fn test_create_project_from_choice_engine() {
+        let runtime = NativeRuntime::new();
+        let index = |engine| {
+            let options = CreateFromChoiceOptions::new("website", "My Website").with_engine(engine);
+            let files = pollster::block_on(create_project_from_choice(&runtime, options)).unwrap();
+            files
+                .into_iter()
+                .find_map(|f| match f {
+                    ScaffoldedFile::Text { path, content }
+                        if path.to_str() == Some("index.qmd") =>
+                    {
+                        Some(content)
+                    }
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        let jupyter = index(ProjectEngine::Jupyter);
+        assert!(jupyter.contains("jupyter: python3\n---"));
+        assert!(jupyter.contains("```{python}"));
+
+        let knitr = index(ProjectEngine::Knitr);
+        assert!(knitr.contains("```{r}"));
+        assert!(!knitr.contains("jupyter:"));
+
+        let markdown = index(ProjectEngine::Markdown);
+        assert!(!markdown.contains("```"));
+    }
+
     #[test]
     fn test_create_project_from_choice_default() {
         let runtime = NativeRuntime::new();
@@ -492,8 +546,8 @@ mod integration_tests {
     #[test]
     fn test_create_project_from_choice_unimplemented() {
         let runtime = NativeRuntime::new();
-        // "blog" is defined but marked as unimplemented
-        let options = CreateFromChoiceOptions::new("blog", "My Blog");
+        // "manuscript" is defined but marked as unimplemented
+        let options = CreateFromChoiceOptions::new("manuscript", "My Manuscript");
 
         let result = pollster::block_on(create_project_from_choice(&runtime, options));
 
@@ -213,18 +213,30 @@ pub fn get_scaffold(target: &ProjectTypeWithTemplate) -> Option<ProjectScaffold>
                             templates::website::INDEX_QMD,
                         )),
                 ),
-                Some("blog") => {
-                    // Blog template - not yet implemented, will be added later
-                    None
-                }
+                Some("blog") => Some(blog_scaffold()),
                 Some(_) => None, // Unknown template
             }
         }
+        ProjectType::Blog => Some(blog_scaffold()),
         // Not yet implemented
-        ProjectType::Blog | ProjectType::Manuscript | ProjectType::Book => None,
+        ProjectType::Manuscript | ProjectType::Book => None,
     }
 }
 
+/// The blog scaffold: a website whose home page lists the posts.
+fn blog_scaffold() -> ProjectScaffold {
+    use crate::templates::blog;
+
+    ProjectScaffold::with_template(ProjectType::Website, "blog")
+        .add_file(ScaffoldFileDef::template("_quarto.yml", blog::QUARTO_YML))
+        .add_file(ScaffoldFileDef::template("index.qmd", blog::INDEX_QMD))
+        .add_file(ScaffoldFileDef::template("about.qmd", blog::ABOUT_QMD))
+        .add_file(
+            ScaffoldFileDef::template("index.qmd", blog::WELCOME_POST_QMD)
+                .in_subdirectory("posts/welcome"),
+        )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -276,6 +288,24 @@ mod tests {
         assert!(paths.contains(&"index.qmd"));
     }
 
+    #[test]
+    fn test_get_scaffold_blog() {
+        let target = ProjectTypeWithTemplate::with_template(ProjectType::Website, "blog");
+        let scaffold = get_scaffold(&target).unwrap();
+        assert_eq!(scaffold.target.to_id_string(), "website:blog");
+
+        let paths: Vec<_> = scaffold.files.iter().map(|f| f.full_path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("_quarto.yml"),
+                PathBuf::from("index.qmd"),
+                PathBuf::from("about.qmd"),
+                PathBuf::from("posts/welcome/index.qmd"),
+            ]
+        );
+    }
+
     #[test]
     fn test_get_scaffold_unknown_template() {
         let target = ProjectTypeWithTemplate::with_template(ProjectType::Website, "nonexistent");
@@ -25,6 +25,22 @@ pub mod website {
     pub const INDEX_QMD: &str = include_str!("../resources/templates/website/index.qmd.ejs");
 }
 
+/// Templates for blog projects (a website with a post listing).
+pub mod blog {
+    /// `_quarto.yml` template for blog projects.
+    pub const QUARTO_YML: &str = include_str!("../resources/templates/blog/_quarto.yml.ejs");
+
+    /// `index.qmd` template (the post listing) for blog projects.
+    pub const INDEX_QMD: &str = include_str!("../resources/templates/blog/index.qmd.ejs");
+
+    /// `about.qmd` template for blog projects.
+    pub const ABOUT_QMD: &str = include_str!("../resources/templates/blog/about.qmd.ejs");
+
+    /// `posts/welcome/index.qmd` template for blog projects.
+    pub const WELCOME_POST_QMD: &str =
+        include_str!("../resources/templates/blog/posts/welcome/index.qmd.ejs");
+}
+
 /// A template file with its target path.
 #[derive(Debug, Clone)]
 pub struct TemplateFile {
@@ -48,8 +64,9 @@ pub fn get_templates(project_type: ProjectType) -> &'static [TemplateFile] {
     match project_type {
         ProjectType::Default => &DEFAULT_TEMPLATES,
         ProjectType::Website => &WEBSITE_TEMPLATES,
+        ProjectType::Blog => &BLOG_TEMPLATES,
         // Not yet implemented - fall back to default
-        ProjectType::Blog | ProjectType::Manuscript | ProjectType::Book => &DEFAULT_TEMPLATES,
+        ProjectType::Manuscript | ProjectType::Book => &DEFAULT_TEMPLATES,
     }
 }
 
@@ -63,6 +80,14 @@ static WEBSITE_TEMPLATES: [TemplateFile; 2] = [
     TemplateFile::new("index.qmd", website::INDEX_QMD),
 ];
 
+/// Templates for blog project type.
+static BLOG_TEMPLATES: [TemplateFile; 4] = [
+    TemplateFile::new("_quarto.yml", blog::QUARTO_YML),
+    TemplateFile::new("index.qmd", blog::INDEX_QMD),
+    TemplateFile::new("about.qmd", blog::ABOUT_QMD),
+    TemplateFile::new("posts/welcome/index.qmd", blog::WELCOME_POST_QMD),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -85,6 +110,22 @@ mod tests {
         assert!(paths.contains(&"index.qmd"));
     }
 
+    #[test]
+    fn test_blog_templates_exist() {
+        let templates = get_templates(ProjectType::Blog);
+        let paths: Vec<_> = templates.iter().map(|t| t.path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "_quarto.yml",
+                "index.qmd",
+                "about.qmd",
+                "posts/welcome/index.qmd"
+            ]
+        );
+        assert!(templates[1].template.contains("listing:"));
+    }
+
     #[test]
     fn test_templates_are_valid_ejs() {
         // Check that templates contain EJS syntax
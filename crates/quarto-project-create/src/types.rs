@@ -97,7 +97,11 @@ impl ProjectType {
     ///
     /// Some project types are defined but not yet implemented.
     pub fn implemented() -> &'static [ProjectType] {
-        &[ProjectType::Default, ProjectType::Website]
+        &[
+            ProjectType::Default,
+            ProjectType::Website,
+            ProjectType::Blog,
+        ]
     }
 }
 
@@ -115,6 +119,80 @@ impl std::str::FromStr for ProjectType {
     }
 }
 
+/// Computational engine a project's documents use.
+///
+/// Templates use the engine to choose the front matter and example code
+/// cells of the documents they create.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectEngine {
+    /// Plain markdown, no code execution
+    #[default]
+    Markdown,
+
+    /// Python code cells run with Jupyter
+    Jupyter,
+
+    /// R code cells run with knitr
+    Knitr,
+}
+
+impl ProjectEngine {
+    /// Get the display name for this engine.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ProjectEngine::Markdown => "Markdown",
+            ProjectEngine::Jupyter => "Jupyter (Python)",
+            ProjectEngine::Knitr => "Knitr (R)",
+        }
+    }
+
+    /// Get the lowercase identifier for this engine.
+    pub fn id(&self) -> &'static str {
+        match self {
+            ProjectEngine::Markdown => "markdown",
+            ProjectEngine::Jupyter => "jupyter",
+            ProjectEngine::Knitr => "knitr",
+        }
+    }
+
+    /// Parse an engine from a string identifier.
+    pub fn from_id(id: &str) -> Result<Self, CreateError> {
+        match id.to_lowercase().as_str() {
+            "markdown" => Ok(ProjectEngine::Markdown),
+            "jupyter" => Ok(ProjectEngine::Jupyter),
+            "knitr" => Ok(ProjectEngine::Knitr),
+            _ => Err(CreateError::InvalidConfig(format!(
+                "Unknown engine: {}",
+                id
+            ))),
+        }
+    }
+
+    /// List all engines.
+    pub fn all() -> &'static [ProjectEngine] {
+        &[
+            ProjectEngine::Markdown,
+            ProjectEngine::Jupyter,
+            ProjectEngine::Knitr,
+        ]
+    }
+}
+
+impl std::fmt::Display for ProjectEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display_name())
+    }
+}
+
+impl std::str::FromStr for ProjectEngine {
+    type Err = CreateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ProjectEngine::from_id(s)
+    }
+}
+
 /// A file to be created as part of a project.
 #[derive(Debug, Clone)]
 pub struct ProjectFile {
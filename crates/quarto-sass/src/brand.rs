@@ -0,0 +1,777 @@
+//! Brand support: reading `_brand.yml`.
+//!
+//! Copyright (c) 2025 Posit, PBC
+//!
+//! A brand file describes an organization's colors, typography and logos
+//! (see <https://posit-dev.github.io/brand-yml/>). This module parses the
+//! file, validates it against a schema (reporting problems with their
+//! source locations), and maps its tokens onto Bootstrap SCSS variables
+//! as a [`SassLayer`].
+//!
+//! ```yaml
+//! color:
+//!   palette:
+//!     blue: "#447099"
+//!   primary: blue
+//!   background: "#ffffff"
+//! typography:
+//!   fonts:
+//!     - family: Inter
+//!       source: google
+//!   base: Inter
+//!   headings:
+//!     family: Inter
+//!     weight: 600
+//! logo:
+//!   images:
+//!     icon: logos/icon.png
+//!   small: icon
+//!   medium:
+//!     path: logos/logo.svg
+//!     alt: Acme
+//! ```
+//!
+//! Theme colors and typography colors may name a palette entry. Logo
+//! paths are relative to the brand file.
+
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_source_map::{SourceContext, SourceInfo};
+use quarto_system_runtime::SystemRuntime;
+use quarto_yaml_validation::error::ValidationErrorKind;
+use quarto_yaml_validation::{Schema, SchemaRegistry};
+use yaml_rust2::Yaml;
+
+use crate::types::SassLayer;
+
+/// File name of a brand file.
+pub const BRAND_FILE: &str = "_brand.yml";
+
+/// Theme color names and the Bootstrap variables they set.
+const THEME_COLORS: &[(&str, Option<&str>)] = &[
+    ("foreground", Some("body-color")),
+    ("background", Some("body-bg")),
+    ("primary", Some("primary")),
+    ("secondary", Some("secondary")),
+    ("tertiary", None),
+    ("success", Some("success")),
+    ("info", Some("info")),
+    ("warning", Some("warning")),
+    ("danger", Some("danger")),
+    ("light", Some("light")),
+    ("dark", Some("dark")),
+];
+
+/// Schema for brand files.
+const BRAND_SCHEMA: &str = r#"
+object:
+  closed: true
+  properties:
+    meta: any
+    color:
+      object:
+        closed: true
+        properties:
+          palette:
+            record: string
+          foreground: string
+          background: string
+          primary: string
+          secondary: string
+          tertiary: string
+          success: string
+          info: string
+          warning: string
+          danger: string
+          light: string
+          dark: string
+    typography:
+      object:
+        closed: true
+        properties:
+          fonts:
+            arrayOf:
+              object:
+                closed: true
+                properties:
+                  family: string
+                  source:
+                    enum: [google, bunny, system]
+                  weight:
+                    maybeArrayOf:
+                      anyOf:
+                        - number
+                        - string
+                  style:
+                    maybeArrayOf:
+                      enum: [normal, italic]
+                required: [family]
+          base:
+            anyOf:
+              - string
+              - object:
+                  closed: true
+                  properties:
+                    family: string
+                    size: string
+                    line-height:
+                      anyOf: [number, string]
+                    weight:
+                      anyOf: [number, string]
+          headings:
+            anyOf:
+              - string
+              - object:
+                  closed: true
+                  properties:
+                    family: string
+                    weight:
+                      anyOf: [number, string]
+                    line-height:
+                      anyOf: [number, string]
+                    color: string
+          monospace:
+            anyOf:
+              - string
+              - object:
+                  closed: true
+                  properties:
+                    family: string
+                    size: string
+                    weight:
+                      anyOf: [number, string]
+          link:
+            object:
+              closed: true
+              properties:
+                color: string
+                weight:
+                  anyOf: [number, string]
+                decoration: string
+    logo:
+      anyOf:
+        - string
+        - object:
+            closed: true
+            properties:
+              images:
+                record:
+                  anyOf:
+                    - string
+                    - object:
+                        closed: true
+                        properties:
+                          path: string
+                          alt: string
+                        required: [path]
+              small:
+                ref: brand-logo-size
+              medium:
+                ref: brand-logo-size
+              large:
+                ref: brand-logo-size
+"#;
+
+/// Schema for a logo size: an image name or path, a path with alt text,
+/// or light and dark variants.
+const BRAND_LOGO_SIZE_SCHEMA: &str = r#"
+anyOf:
+  - string
+  - object:
+      closed: true
+      properties:
+        path: string
+        alt: string
+      required: [path]
+  - object:
+      closed: true
+      properties:
+        light: string
+        dark: string
+"#;
+
+static BRAND: Lazy<Schema> = Lazy::new(|| {
+    let yaml = quarto_yaml::parse(BRAND_SCHEMA).expect("brand schema is valid YAML");
+    Schema::from_yaml(&yaml).expect("brand schema is a valid schema")
+});
+
+static REGISTRY: Lazy<SchemaRegistry> = Lazy::new(|| {
+    let yaml = quarto_yaml::parse(BRAND_LOGO_SIZE_SCHEMA).expect("logo schema is valid YAML");
+    let mut registry = SchemaRegistry::new();
+    registry.register(
+        "brand-logo-size".to_string(),
+        Schema::from_yaml(&yaml).expect("logo schema is a valid schema"),
+    );
+    registry
+});
+
+/// A parsed brand file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Brand {
+    /// Directory containing the brand file; logo paths are relative to it
+    pub dir: PathBuf,
+    pub color: BrandColor,
+    pub typography: BrandTypography,
+    pub logo: BrandLogo,
+}
+
+/// Brand colors.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BrandColor {
+    /// Named colors, in file order
+    pub palette: Vec<(String, String)>,
+    /// Theme colors (`primary`, `background`, ...), in file order.
+    /// Values may name a palette entry.
+    pub theme: Vec<(String, String)>,
+}
+
+/// Brand typography.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BrandTypography {
+    /// Fonts to load
+    pub fonts: Vec<BrandFont>,
+    pub base: Option<BrandFontStyle>,
+    pub headings: Option<BrandFontStyle>,
+    pub monospace: Option<BrandFontStyle>,
+    pub link: Option<BrandFontStyle>,
+}
+
+/// A font to load.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BrandFont {
+    pub family: String,
+    /// `google`, `bunny` or `system` (the default)
+    pub source: String,
+    /// Weights to load (e.g. `400`, `700`)
+    pub weights: Vec<String>,
+    /// Whether to load italic styles
+    pub italic: bool,
+}
+
+/// Font settings for one kind of text.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BrandFontStyle {
+    pub family: Option<String>,
+    pub size: Option<String>,
+    pub weight: Option<String>,
+    pub line_height: Option<String>,
+    pub color: Option<String>,
+    pub decoration: Option<String>,
+}
+
+/// Brand logos by size.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BrandLogo {
+    pub small: Option<BrandImage>,
+    pub medium: Option<BrandImage>,
+    pub large: Option<BrandImage>,
+}
+
+/// A logo image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrandImage {
+    /// Path of the image, resolved against the brand file's directory
+    pub path: PathBuf,
+    pub alt: Option<String>,
+}
+
+impl BrandLogo {
+    /// The logo for navigation bars: the smallest available.
+    pub fn navbar(&self) -> Option<&BrandImage> {
+        self.small
+            .as_ref()
+            .or(self.medium.as_ref())
+            .or(self.large.as_ref())
+    }
+
+    /// The logo for title blocks: medium, else the largest available.
+    pub fn title(&self) -> Option<&BrandImage> {
+        self.medium
+            .as_ref()
+            .or(self.large.as_ref())
+            .or(self.small.as_ref())
+    }
+}
+
+/// Find the brand file for a document: `_brand.yml` in the first of `dirs`
+/// that has one (typically the project directory, then the document's).
+pub fn find_brand_file(dirs: &[&Path], runtime: &dyn SystemRuntime) -> Option<PathBuf> {
+    dirs.iter()
+        .map(|dir| dir.join(BRAND_FILE))
+        .find(|path| runtime.is_file(path).unwrap_or(false))
+}
+
+/// Read, validate and parse a brand file.
+///
+/// The file is added to `source_context` so that the returned diagnostics
+/// point into it.
+pub fn load_brand(
+    path: &Path,
+    runtime: &dyn SystemRuntime,
+    source_context: &mut SourceContext,
+) -> Result<Brand, Vec<DiagnosticMessage>> {
+    let content = runtime.file_read_string(path).map_err(|e| {
+        vec![
+            DiagnosticMessageBuilder::error("Brand file not readable")
+                .problem(format!("Could not read `{}`: {}", path.display(), e))
+                .build(),
+        ]
+    })?;
+    let file_id =
+        source_context.add_file(path.to_string_lossy().into_owned(), Some(content.clone()));
+    let parent = SourceInfo::original(file_id, 0, content.len());
+
+    let yaml = quarto_yaml::parse_with_parent(&content, parent.clone()).map_err(|e| {
+        vec![
+            DiagnosticMessageBuilder::error("Invalid brand file")
+                .with_location(parent.clone())
+                .problem(format!("`{}` is not valid YAML: {}", BRAND_FILE, e))
+                .build(),
+        ]
+    })?;
+
+    // An empty brand file is a valid, empty brand
+    let validation = if yaml.yaml.is_null() {
+        Ok(())
+    } else {
+        quarto_yaml_validation::validate(&yaml, &BRAND, &REGISTRY, source_context)
+    };
+    if let Err(error) = validation {
+        // Point unknown keys at the key itself rather than its mapping
+        let location = match (&error.kind, &error.yaml_node) {
+            (ValidationErrorKind::UnknownProperty { property }, Some(node)) => node
+                .as_hash()
+                .and_then(|entries| {
+                    entries
+                        .iter()
+                        .find(|entry| entry.key.yaml.as_str() == Some(property.as_str()))
+                })
+                .map_or_else(|| node.source_info.clone(), |entry| entry.key_span.clone()),
+            (_, Some(node)) => node.source_info.clone(),
+            (_, None) => parent,
+        };
+        let problem = if error.instance_path.is_empty() {
+            format!("The brand file must be a mapping: {}", error.message())
+        } else {
+            format!(
+                "Invalid value for `{}`: {}",
+                error.instance_path,
+                error.message()
+            )
+        };
+        return Err(vec![
+            DiagnosticMessageBuilder::error("Invalid brand file")
+                .with_location(location)
+                .problem(problem)
+                .add_hint("See https://posit-dev.github.io/brand-yml/ for the brand file format")
+                .build(),
+        ]);
+    }
+
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    Ok(Brand::from_yaml(&yaml.yaml, dir))
+}
+
+impl Brand {
+    /// Build a brand from already validated YAML.
+    pub fn from_yaml(yaml: &Yaml, dir: PathBuf) -> Self {
+        let color = &yaml["color"];
+        let palette = entries(&color["palette"])
+            .filter_map(|(name, value)| Some((name, scalar(value)?)))
+            .collect();
+        let theme = THEME_COLORS
+            .iter()
+            .filter_map(|(name, _)| Some((name.to_string(), scalar(&color[*name])?)))
+            .collect();
+
+        let typography = &yaml["typography"];
+        let fonts = typography["fonts"]
+            .as_vec()
+            .map(|fonts| fonts.iter().filter_map(parse_font).collect())
+            .unwrap_or_default();
+
+        let logo = parse_logo(&yaml["logo"], &dir);
+
+        Self {
+            color: BrandColor { palette, theme },
+            typography: BrandTypography {
+                fonts,
+                base: parse_font_style(&typography["base"]),
+                headings: parse_font_style(&typography["headings"]),
+                monospace: parse_font_style(&typography["monospace"]),
+                link: parse_font_style(&typography["link"]),
+            },
+            logo,
+            dir,
+        }
+    }
+
+    /// Resolve a color value: a palette or theme color name, or a literal.
+    pub fn resolve_color(&self, value: &str) -> String {
+        let mut value = value;
+        // Names may chain (theme → palette); the bound guards against cycles
+        for _ in 0..=self.color.palette.len() + self.color.theme.len() {
+            let named = self
+                .color
+                .palette
+                .iter()
+                .chain(&self.color.theme)
+                .find(|(name, _)| name == value);
+            match named {
+                Some((_, next)) if next != value => value = next.as_str(),
+                _ => break,
+            }
+        }
+        value.to_string()
+    }
+
+    /// The brand as a SASS layer: `$brand-*` variables for every color,
+    /// Bootstrap variable defaults for theme colors and typography, and
+    /// imports for web fonts.
+    pub fn to_sass_layer(&self) -> SassLayer {
+        let mut layer = SassLayer::new();
+
+        for font in &self.typography.fonts {
+            if let Some(url) = font.import_url() {
+                layer.uses.push_str(&format!("@import url(\"{}\");\n", url));
+            }
+        }
+
+        let mut defaults = Vec::new();
+        for (name, value) in &self.color.palette {
+            defaults.push((format!("brand-{}", name), self.resolve_color(value)));
+        }
+        for (name, value) in &self.color.theme {
+            let value = self.resolve_color(value);
+            defaults.push((format!("brand-{}", name), value.clone()));
+            if let Some((_, Some(variable))) =
+                THEME_COLORS.iter().find(|(n, _)| *n == name.as_str())
+            {
+                defaults.push((variable.to_string(), value));
+            }
+        }
+
+        let typography = &self.typography;
+        let font_variables: [(&Option<BrandFontStyle>, &[(&str, FontField)]); 4] = [
+            (
+                &typography.base,
+                &[
+                    ("font-family-base", FontField::Family),
+                    ("font-size-base", FontField::Size),
+                    ("font-weight-base", FontField::Weight),
+                    ("line-height-base", FontField::LineHeight),
+                ],
+            ),
+            (
+                &typography.headings,
+                &[
+                    ("headings-font-family", FontField::Family),
+                    ("headings-font-weight", FontField::Weight),
+                    ("headings-line-height", FontField::LineHeight),
+                    ("headings-color", FontField::Color),
+                ],
+            ),
+            (
+                &typography.monospace,
+                &[
+                    ("font-family-monospace", FontField::Family),
+                    ("code-font-size", FontField::Size),
+                ],
+            ),
+            (
+                &typography.link,
+                &[
+                    ("link-color", FontField::Color),
+                    ("link-decoration", FontField::Decoration),
+                ],
+            ),
+        ];
+        for (style, fields) in font_variables {
+            let Some(style) = style else {
+                continue;
+            };
+            for (variable, field) in fields {
+                if let Some(value) = self.font_value(style, *field) {
+                    defaults.push((variable.to_string(), value));
+                }
+            }
+        }
+
+        for (variable, value) in defaults {
+            layer
+                .defaults
+                .push_str(&format!("${}: {} !default;\n", variable, value));
+        }
+        layer
+    }
+
+    fn font_value(&self, style: &BrandFontStyle, field: FontField) -> Option<String> {
+        match field {
+            FontField::Family => style.family.as_ref().map(|f| format!("\"{}\"", f)),
+            FontField::Size => style.size.clone(),
+            FontField::Weight => style.weight.clone(),
+            FontField::LineHeight => style.line_height.clone(),
+            FontField::Color => style.color.as_deref().map(|c| self.resolve_color(c)),
+            FontField::Decoration => style.decoration.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum FontField {
+    Family,
+    Size,
+    Weight,
+    LineHeight,
+    Color,
+    Decoration,
+}
+
+impl BrandFont {
+    /// The stylesheet URL for a web font, or `None` for system fonts.
+    pub fn import_url(&self) -> Option<String> {
+        let host = match self.source.as_str() {
+            "google" => "https://fonts.googleapis.com/css2",
+            "bunny" => "https://fonts.bunny.net/css2",
+            _ => return None,
+        };
+        let family = self.family.replace(' ', "+");
+        let weights = if self.weights.is_empty() {
+            vec!["400".to_string(), "700".to_string()]
+        } else {
+            self.weights.clone()
+        };
+        let axes = if self.italic {
+            let tuples: Vec<String> = [0, 1]
+                .iter()
+                .flat_map(|ital| weights.iter().map(move |w| format!("{},{}", ital, w)))
+                .collect();
+            format!("ital,wght@{}", tuples.join(";"))
+        } else {
+            format!("wght@{}", weights.join(";"))
+        };
+        Some(format!("{}?family={}:{}&display=swap", host, family, axes))
+    }
+}
+
+/// The entries of a YAML mapping with string keys.
+fn entries(yaml: &Yaml) -> impl Iterator<Item = (String, &Yaml)> {
+    yaml.as_hash()
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| Some((key.as_str()?.to_string(), value)))
+}
+
+/// A scalar as text.
+fn scalar(yaml: &Yaml) -> Option<String> {
+    match yaml {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.clone()),
+        Yaml::Integer(n) => Some(n.to_string()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// A scalar or a list of scalars, as text.
+fn scalars(yaml: &Yaml) -> Vec<String> {
+    match yaml.as_vec() {
+        Some(items) => items.iter().filter_map(scalar).collect(),
+        None => scalar(yaml).into_iter().collect(),
+    }
+}
+
+fn parse_font(yaml: &Yaml) -> Option<BrandFont> {
+    Some(BrandFont {
+        family: yaml["family"].as_str()?.to_string(),
+        source: yaml["source"].as_str().unwrap_or("system").to_string(),
+        weights: scalars(&yaml["weight"]),
+        italic: scalars(&yaml["style"]).iter().any(|s| s == "italic"),
+    })
+}
+
+/// A font style: a family name, or a map of settings.
+fn parse_font_style(yaml: &Yaml) -> Option<BrandFontStyle> {
+    if let Some(family) = yaml.as_str() {
+        return Some(BrandFontStyle {
+            family: Some(family.to_string()),
+            ..Default::default()
+        });
+    }
+    yaml.as_hash()?;
+    Some(BrandFontStyle {
+        family: scalar(&yaml["family"]),
+        size: scalar(&yaml["size"]),
+        weight: scalar(&yaml["weight"]),
+        line_height: scalar(&yaml["line-height"]),
+        color: scalar(&yaml["color"]),
+        decoration: scalar(&yaml["decoration"]),
+    })
+}
+
+/// Logos: a single path used for every size, or `small`/`medium`/`large`
+/// entries naming an image from `images` or giving a path.
+fn parse_logo(yaml: &Yaml, dir: &Path) -> BrandLogo {
+    if let Some(path) = yaml.as_str() {
+        let image = BrandImage {
+            path: dir.join(path),
+            alt: None,
+        };
+        return BrandLogo {
+            small: Some(image.clone()),
+            medium: Some(image.clone()),
+            large: Some(image),
+        };
+    }
+
+    let images: Vec<(String, &Yaml)> = entries(&yaml["images"]).collect();
+    let image = |yaml: &Yaml| -> Option<BrandImage> {
+        // Light and dark variants: use the light one
+        let yaml = if yaml["light"].is_badvalue() {
+            yaml
+        } else {
+            &yaml["light"]
+        };
+        let (path, alt) = match yaml.as_str() {
+            Some(name) => match images.iter().find(|(n, _)| n == name) {
+                Some((_, image)) => match image.as_str() {
+                    Some(path) => (path.to_string(), None),
+                    None => (image["path"].as_str()?.to_string(), scalar(&image["alt"])),
+                },
+                None => (name.to_string(), None),
+            },
+            None => (yaml["path"].as_str()?.to_string(), scalar(&yaml["alt"])),
+        };
+        Some(BrandImage {
+            path: dir.join(path),
+            alt,
+        })
+    };
+
+    BrandLogo {
+        small: image(&yaml["small"]),
+        medium: image(&yaml["medium"]),
+        large: image(&yaml["large"]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_system_runtime::NativeRuntime;
+
+    fn parse(yaml: &str) -> Brand {
+        let yaml = quarto_yaml::parse(yaml).unwrap();
+        Brand::from_yaml(&yaml.yaml, PathBuf::from("/brand"))
+    }
+
+    fn load(content: &str) -> (Result<Brand, Vec<DiagnosticMessage>>, SourceContext) {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join(BRAND_FILE);
+        std::fs::write(&path, content).unwrap();
+        let mut source_context = SourceContext::new();
+        let result = load_brand(&path, &NativeRuntime::new(), &mut source_context);
+        (result, source_context)
+    }
+
+    #[test]
+    fn test_colors_map_to_bootstrap_variables() {
+        let brand = parse(
+            "color:\n  palette:\n    blue: \"#447099\"\n    white: \"#fff\"\n  \
+             primary: blue\n  background: white\n  tertiary: \"#ccc\"\n",
+        );
+        let layer = brand.to_sass_layer();
+        assert_eq!(
+            layer.defaults,
+            "$brand-blue: #447099 !default;\n\
+             $brand-white: #fff !default;\n\
+             $brand-background: #fff !default;\n\
+             $body-bg: #fff !default;\n\
+             $brand-primary: #447099 !default;\n\
+             $primary: #447099 !default;\n\
+             $brand-tertiary: #ccc !default;\n"
+        );
+    }
+
+    #[test]
+    fn test_typography_maps_to_font_variables() {
+        let brand = parse(
+            "color:\n  palette:\n    red: \"#c00\"\n\
+             typography:\n  fonts:\n    - family: Open Sans\n      source: google\n      \
+             weight: [400, 600]\n    - family: Menlo\n  base: Open Sans\n  \
+             headings:\n    family: Open Sans\n    weight: 600\n    color: red\n  \
+             monospace:\n    family: Menlo\n    size: 0.9em\n",
+        );
+        let layer = brand.to_sass_layer();
+        assert_eq!(
+            layer.uses,
+            "@import url(\"https://fonts.googleapis.com/css2?family=Open+Sans:wght@400;600&display=swap\");\n"
+        );
+        assert!(
+            layer
+                .defaults
+                .contains("$font-family-base: \"Open Sans\" !default;")
+        );
+        assert!(
+            layer
+                .defaults
+                .contains("$headings-font-weight: 600 !default;")
+        );
+        assert!(layer.defaults.contains("$headings-color: #c00 !default;"));
+        assert!(
+            layer
+                .defaults
+                .contains("$font-family-monospace: \"Menlo\" !default;")
+        );
+        assert!(layer.defaults.contains("$code-font-size: 0.9em !default;"));
+    }
+
+    #[test]
+    fn test_logo_sizes() {
+        let brand = parse(
+            "logo:\n  images:\n    icon:\n      path: icon.png\n      alt: Acme icon\n  \
+             small: icon\n  large:\n    light: wide-light.svg\n    dark: wide-dark.svg\n",
+        );
+        let small = brand.logo.small.as_ref().unwrap();
+        assert_eq!(small.path, PathBuf::from("/brand/icon.png"));
+        assert_eq!(small.alt.as_deref(), Some("Acme icon"));
+        assert_eq!(brand.logo.navbar(), Some(small));
+        assert_eq!(
+            brand.logo.title().unwrap().path,
+            PathBuf::from("/brand/wide-light.svg")
+        );
+
+        let single = parse("logo: logo.png\n");
+        assert_eq!(
+            single.logo.title().unwrap().path,
+            PathBuf::from("/brand/logo.png")
+        );
+    }
+
+    #[test]
+    fn test_load_brand() {
+        let (result, _) = load("color:\n  primary: \"#123456\"\n");
+        let brand = result.unwrap();
+        assert_eq!(brand.resolve_color("primary"), "#123456");
+    }
+
+    #[test]
+    fn test_invalid_brand_is_located() {
+        let (result, source_context) = load("color:\n  primary: \"#123456\"\n  fancy: red\n");
+        let errors = result.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].title, "Invalid brand file");
+
+        let location = errors[0].location.as_ref().unwrap();
+        let mapped = location.map_offset(0, &source_context).unwrap();
+        assert_eq!(mapped.location.row, 2);
+    }
+
+    #[test]
+    fn test_invalid_font_source() {
+        let (result, _) = load("typography:\n  fonts:\n    - family: Inter\n      source: cdn\n");
+        let errors = result.unwrap_err();
+        assert!(errors[0].to_text(None).contains("typography"));
+    }
+}
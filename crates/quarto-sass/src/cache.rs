@@ -0,0 +1,267 @@
+//! Compiled CSS cache.
+//!
+//! Copyright (c) 2025 Posit, PBC
+//!
+//! Compiling Bootstrap with grass dominates render time for themed
+//! documents, and most renders compile a bundle that has been compiled
+//! before. [`SassCache`] stores compiled CSS at
+//! `{project}/.quarto/cache/sass/{key}.css`, so an unchanged bundle skips
+//! compilation entirely.
+//!
+//! # Keys
+//!
+//! A key is a hash of the assembled SCSS, the minification setting, and the
+//! contents of every SCSS/CSS file under the load paths that exist on disk
+//! (embedded load paths are fixed per build and covered by the crate
+//! version). Editing a partial that the bundle `@import`s therefore changes
+//! the key even though the bundle text does not. Keys use `std`'s
+//! `DefaultHasher`, so a toolchain change may invalidate the cache; the
+//! worst case is recompilation.
+//!
+//! The cache also keeps downloaded web fonts under `fetch/` (see
+//! [`SassCache::fetch`]), so embedding them needs the network only once.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use quarto_sass::{SassCache, ThemeContext, compile_theme_css};
+//!
+//! let cache = SassCache::new(&project_dir);
+//! let context = ThemeContext::new(document_dir, &runtime).with_cache(&cache);
+//! let css = compile_theme_css(&config, &context)?;
+//! println!("SASS cache: {}", cache.stats());
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use quarto_system_runtime::SystemRuntime;
+
+/// Version of the cache entry format, mixed into every key.
+const CACHE_VERSION: u32 = 1;
+
+/// File extensions whose contents are part of a key.
+const SOURCE_EXTENSIONS: &[&str] = &["scss", "sass", "css"];
+
+/// Store of compiled CSS, keyed by bundle hash.
+///
+/// Counts hits and misses so renders can report how effective the cache
+/// was (see [`SassCache::stats`]).
+#[derive(Debug)]
+pub struct SassCache {
+    /// Directory holding the entries
+    dir: PathBuf,
+
+    /// Lookups that found an entry
+    hits: AtomicUsize,
+
+    /// Lookups that found nothing
+    misses: AtomicUsize,
+}
+
+/// Hit and miss counts of a [`SassCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SassCacheStats {
+    /// Compilations skipped because the CSS was cached
+    pub hits: usize,
+    /// Compilations that ran and were then cached
+    pub misses: usize,
+}
+
+impl fmt::Display for SassCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} hits, {} misses", self.hits, self.misses)
+    }
+}
+
+impl SassCache {
+    /// Create a cache within `project_dir`.
+    pub fn new(project_dir: &Path) -> Self {
+        Self::with_dir(project_dir.join(".quarto").join("cache").join("sass"))
+    }
+
+    /// Create a cache that stores its entries in `dir`.
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// The directory holding the entries.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Compute the key for an assembled bundle.
+    pub fn key(
+        scss: &str,
+        load_paths: &[PathBuf],
+        minified: bool,
+        runtime: &dyn SystemRuntime,
+    ) -> String {
+        let mut hasher = DefaultHasher::new();
+        CACHE_VERSION.hash(&mut hasher);
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        scss.hash(&mut hasher);
+        minified.hash(&mut hasher);
+        for load_path in load_paths {
+            load_path.hash(&mut hasher);
+            if runtime.is_dir(load_path).unwrap_or(false) {
+                hash_sources(load_path, runtime, &mut hasher);
+            }
+        }
+        format!("{:016x}", hasher.finish())
+    }
+
+    /// Look up the CSS stored for `key`, counting a hit or a miss.
+    pub fn get(&self, key: &str, runtime: &dyn SystemRuntime) -> Option<String> {
+        let css = runtime
+            .file_read(&self.entry_path(key))
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok());
+        let counter = if css.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        css
+    }
+
+    /// Store the CSS for `key`.
+    ///
+    /// Failures are ignored: a cache that can't be written only costs
+    /// recompilation next time.
+    pub fn put(&self, key: &str, css: &str, runtime: &dyn SystemRuntime) {
+        let _ = runtime
+            .dir_create(&self.dir, true)
+            .and_then(|_| runtime.file_write(&self.entry_path(key), css.as_bytes()));
+    }
+
+    /// Fetch `url` through the runtime, keeping the response in the cache
+    /// so later renders work without the network.
+    ///
+    /// Used for web fonts (see [`embed_web_fonts`](crate::embed_web_fonts));
+    /// these lookups are not counted in [`SassCache::stats`].
+    pub fn fetch(&self, url: &str, runtime: &dyn SystemRuntime) -> Option<Vec<u8>> {
+        let mut hasher = DefaultHasher::new();
+        CACHE_VERSION.hash(&mut hasher);
+        url.hash(&mut hasher);
+        let dir = self.dir.join("fetch");
+        let path = dir.join(format!("{:016x}", hasher.finish()));
+        if let Ok(content) = runtime.file_read(&path) {
+            return Some(content);
+        }
+        let (content, _) = runtime.fetch_url(url).ok()?;
+        let _ = runtime
+            .dir_create(&dir, true)
+            .and_then(|_| runtime.file_write(&path, &content));
+        Some(content)
+    }
+
+    /// Hits and misses so far.
+    pub fn stats(&self) -> SassCacheStats {
+        SassCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.css", key))
+    }
+}
+
+/// Hash the paths and contents of the SCSS/CSS files under `dir`, in a
+/// stable order. Hidden directories (like `.quarto` itself) are skipped.
+fn hash_sources(dir: &Path, runtime: &dyn SystemRuntime, hasher: &mut DefaultHasher) {
+    let Ok(mut entries) = runtime.dir_list(dir) else {
+        return;
+    };
+    entries.sort();
+    for entry in entries {
+        let hidden = entry
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if runtime.is_dir(&entry).unwrap_or(false) {
+            if !hidden {
+                hash_sources(&entry, runtime, hasher);
+            }
+            continue;
+        }
+        let is_source = entry
+            .extension()
+            .is_some_and(|ext| SOURCE_EXTENSIONS.iter().any(|s| ext == *s));
+        if is_source && let Ok(contents) = runtime.file_read(&entry) {
+            entry.hash(hasher);
+            contents.hash(hasher);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_system_runtime::NativeRuntime;
+
+    #[test]
+    fn test_key_depends_on_bundle_and_minification() {
+        let runtime = NativeRuntime::new();
+        let key = SassCache::key("a { color: red; }", &[], true, &runtime);
+
+        assert_eq!(
+            key,
+            SassCache::key("a { color: red; }", &[], true, &runtime)
+        );
+        assert_ne!(
+            key,
+            SassCache::key("a { color: blue; }", &[], true, &runtime)
+        );
+        assert_ne!(
+            key,
+            SassCache::key("a { color: red; }", &[], false, &runtime)
+        );
+    }
+
+    #[test]
+    fn test_key_depends_on_load_path_contents() {
+        let runtime = NativeRuntime::new();
+        let temp = tempfile::tempdir().unwrap();
+        let load_paths = vec![temp.path().to_path_buf()];
+        std::fs::write(temp.path().join("_vars.scss"), "$x: red;").unwrap();
+        std::fs::write(temp.path().join("notes.txt"), "one").unwrap();
+        let key = SassCache::key("@import 'vars';", &load_paths, true, &runtime);
+
+        std::fs::write(temp.path().join("notes.txt"), "two").unwrap();
+        assert_eq!(
+            key,
+            SassCache::key("@import 'vars';", &load_paths, true, &runtime)
+        );
+
+        std::fs::write(temp.path().join("_vars.scss"), "$x: blue;").unwrap();
+        assert_ne!(
+            key,
+            SassCache::key("@import 'vars';", &load_paths, true, &runtime)
+        );
+    }
+
+    #[test]
+    fn test_get_put_and_stats() {
+        let runtime = NativeRuntime::new();
+        let temp = tempfile::tempdir().unwrap();
+        let cache = SassCache::new(temp.path());
+        assert_eq!(cache.dir(), temp.path().join(".quarto/cache/sass"));
+
+        assert_eq!(cache.get("abc", &runtime), None);
+        cache.put("abc", "a{color:red}", &runtime);
+        assert_eq!(cache.get("abc", &runtime).as_deref(), Some("a{color:red}"));
+
+        assert_eq!(cache.stats(), SassCacheStats { hits: 1, misses: 1 });
+        assert_eq!(cache.stats().to_string(), "1 hits, 1 misses");
+    }
+}
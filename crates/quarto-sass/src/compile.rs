@@ -11,7 +11,8 @@
 //! 1. Extract `ThemeConfig` from `ConfigValue` (done by `ThemeConfig::from_config_value`)
 //! 2. Process theme specs into layers (done by `process_theme_specs`)
 //! 3. Assemble SCSS bundle (done by `assemble_with_user_layers`)
-//! 4. Compile SCSS to CSS (done by grass on native, dart-sass on WASM)
+//! 4. Compile SCSS to CSS (done by grass on native, dart-sass on WASM),
+//!    unless the context's [`SassCache`](crate::SassCache) has the result
 //!
 //! This module provides functions that orchestrate this entire flow.
 //!
@@ -40,12 +41,18 @@ use crate::bundle::assemble_with_user_layers;
 use crate::config::ThemeConfig;
 use crate::error::SassError;
 use crate::resources::default_load_paths;
-use crate::themes::{ThemeContext, process_theme_specs};
+use crate::themes::{
+    ThemeContext, ThemeLayerResult, ThemeSpec, load_quarto_customization_layer, process_theme_specs,
+};
 
 // Native-only imports
 #[cfg(not(target_arch = "wasm32"))]
+use crate::cache::SassCache;
+#[cfg(not(target_arch = "wasm32"))]
 use crate::resources::all_resources;
 #[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+#[cfg(not(target_arch = "wasm32"))]
 use std::sync::OnceLock;
 
 /// Cached default Bootstrap CSS (minified).
@@ -54,6 +61,32 @@ use std::sync::OnceLock;
 #[cfg(not(target_arch = "wasm32"))]
 static DEFAULT_CSS_CACHE: OnceLock<String> = OnceLock::new();
 
+/// Add the brand layer (if any) to processed theme layers.
+///
+/// The brand goes after the last built-in theme (and its customization
+/// layer), so brand colors and fonts override Bootswatch defaults while
+/// custom SCSS files listed after that theme still override the brand.
+/// Without built-in themes it follows the leading customization layer.
+fn add_brand_layer(result: &mut ThemeLayerResult, config: &ThemeConfig) -> Result<(), SassError> {
+    let Some(brand) = &config.brand else {
+        return Ok(());
+    };
+
+    if result.layers.is_empty() {
+        result.layers.push(load_quarto_customization_layer()?);
+    }
+    let position = match config.themes.iter().rposition(ThemeSpec::is_builtin) {
+        // Specs up to the last built-in: two layers per built-in, one per file
+        Some(last) => config.themes[..=last]
+            .iter()
+            .map(|spec| if spec.is_builtin() { 2 } else { 1 })
+            .sum(),
+        None => 1,
+    };
+    result.layers.insert(position, brand.to_sass_layer());
+    Ok(())
+}
+
 /// Compile CSS from theme configuration.
 ///
 /// This is the main entry point for the render pipeline. It takes a `ThemeConfig`
@@ -91,15 +124,20 @@ pub fn compile_theme_css(
     context: &ThemeContext<'_>,
 ) -> Result<String, SassError> {
     use crate::bundle::load_title_block_layer;
-    use quarto_system_runtime::sass_native::compile_scss_with_embedded;
 
-    if !config.has_themes() {
-        // No custom themes - use default Bootstrap
-        return compile_default_css(context.runtime(), config.minified);
+    if !config.has_themes() && config.brand.is_none() {
+        // No custom themes - use default Bootstrap (cached in memory unless
+        // there is a persistent cache)
+        if context.cache().is_none() {
+            return compile_default_css(context.runtime(), config.minified);
+        }
+        let scss = assemble_with_user_layers(&[load_title_block_layer()?])?;
+        return compile_with_cache(context, &scss, &default_load_paths(), config.minified);
     }
 
     // Process theme specs into layers
-    let result = process_theme_specs(&config.themes, context)?;
+    let mut result = process_theme_specs(&config.themes, context)?;
+    add_brand_layer(&mut result, config)?;
 
     // Build user layers: title block layer comes first (like TS Quarto),
     // then any theme layers
@@ -115,20 +153,59 @@ pub fn compile_theme_css(
     load_paths.extend(result.load_paths);
     load_paths.extend(context.load_paths().iter().cloned());
 
-    // Create a combined resource provider from all embedded resources
-    let resources = all_resources();
-
     // Compile
-    compile_scss_with_embedded(
-        context.runtime(),
-        &resources,
-        &scss,
-        &load_paths,
-        config.minified,
-    )
-    .map_err(|e| SassError::CompilationFailed {
-        message: e.to_string(),
-    })
+    compile_with_cache(context, &scss, &load_paths, config.minified)
+}
+
+/// Compile an assembled bundle, going through the context's cache if it
+/// has one.
+#[cfg(not(target_arch = "wasm32"))]
+fn compile_with_cache(
+    context: &ThemeContext<'_>,
+    scss: &str,
+    load_paths: &[PathBuf],
+    minified: bool,
+) -> Result<String, SassError> {
+    use quarto_system_runtime::sass_native::compile_scss_with_embedded;
+
+    let runtime = context.runtime();
+    let compile = || {
+        // Create a combined resource provider from all embedded resources
+        let resources = all_resources();
+        compile_scss_with_embedded(runtime, &resources, scss, load_paths, minified).map_err(|e| {
+            SassError::CompilationFailed {
+                message: e.to_string(),
+            }
+        })
+    };
+
+    let Some(cache) = context.cache() else {
+        return compile();
+    };
+    let key = SassCache::key(scss, load_paths, minified, runtime);
+    if let Some(css) = cache.get(&key, runtime) {
+        return Ok(css);
+    }
+    let css = compile()?;
+    cache.put(&key, &css, runtime);
+    Ok(css)
+}
+
+/// Compile the dark stylesheet of a light/dark theme pair.
+///
+/// Returns `None` if the config has no dark themes (see
+/// [`ThemeConfig::has_dark_mode`]). The dark stylesheet is a complete
+/// theme in its own right, compiled with the config's brand; the HTML page
+/// switches between it and the light stylesheet at runtime.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn compile_dark_theme_css(
+    config: &ThemeConfig,
+    context: &ThemeContext<'_>,
+) -> Result<Option<String>, SassError> {
+    config
+        .dark_config()
+        .map(|dark| compile_theme_css(&dark, context))
+        .transpose()
 }
 
 /// Compile CSS from ConfigValue directly.
@@ -290,13 +367,14 @@ pub async fn compile_theme_css(
 ) -> Result<String, SassError> {
     use crate::bundle::load_title_block_layer;
 
-    if !config.has_themes() {
+    if !config.has_themes() && config.brand.is_none() {
         // No custom themes - use default Bootstrap
         return compile_default_css(context.runtime(), config.minified).await;
     }
 
     // Process theme specs into layers
-    let result = process_theme_specs(&config.themes, context)?;
+    let mut result = process_theme_specs(&config.themes, context)?;
+    add_brand_layer(&mut result, config)?;
 
     // Build user layers: title block layer comes first (like TS Quarto),
     // then any theme layers
@@ -322,6 +400,20 @@ pub async fn compile_theme_css(
         })
 }
 
+/// Compile the dark stylesheet of a light/dark theme pair (WASM version).
+///
+/// Returns `None` if the config has no dark themes.
+#[cfg(target_arch = "wasm32")]
+pub async fn compile_dark_theme_css(
+    config: &ThemeConfig,
+    context: &ThemeContext<'_>,
+) -> Result<Option<String>, SassError> {
+    match config.dark_config() {
+        Some(dark) => compile_theme_css(&dark, context).await.map(Some),
+        None => Ok(None),
+    }
+}
+
 /// Compile CSS from ConfigValue directly (WASM version).
 ///
 /// This is a convenience function that combines config extraction and compilation.
@@ -379,7 +471,6 @@ pub async fn compile_default_css(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::themes::ThemeSpec;
     use quarto_system_runtime::NativeRuntime;
     use std::path::PathBuf;
 
@@ -477,6 +568,80 @@ mod tests {
         assert!(css.contains(".btn"));
     }
 
+    #[test]
+    fn test_compile_theme_css_cache_hit() {
+        let runtime = NativeRuntime::new();
+        let temp = tempfile::tempdir().unwrap();
+        let cache = crate::SassCache::new(temp.path());
+        let context = ThemeContext::new(PathBuf::from("/doc"), &runtime).with_cache(&cache);
+        let config = ThemeConfig::new(vec![ThemeSpec::parse("cosmo").unwrap()], true);
+
+        let first = compile_theme_css(&config, &context).unwrap();
+        let second = compile_theme_css(&config, &context).unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(cache.stats(), crate::SassCacheStats { hits: 1, misses: 1 });
+        assert_eq!(std::fs::read_dir(cache.dir()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_compile_dark_theme_css() {
+        let runtime = NativeRuntime::new();
+        let context = ThemeContext::new(PathBuf::from("/doc"), &runtime);
+        let mut config = ThemeConfig::new(vec![ThemeSpec::parse("cosmo").unwrap()], true);
+        assert!(compile_dark_theme_css(&config, &context).unwrap().is_none());
+
+        config.dark_themes = vec![ThemeSpec::parse("darkly").unwrap()];
+        let light = compile_theme_css(&config, &context).unwrap();
+        let dark = compile_dark_theme_css(&config, &context).unwrap().unwrap();
+
+        assert!(dark.contains(".btn"));
+        assert_ne!(light, dark);
+    }
+
+    #[test]
+    fn test_compile_theme_css_brand() {
+        use crate::brand::Brand;
+
+        let runtime = NativeRuntime::new();
+        let yaml = quarto_yaml::parse("color:\n  primary: \"#a1b2c3\"\n").unwrap();
+        let brand = Brand::from_yaml(&yaml.yaml, PathBuf::from("/doc"));
+        let config = ThemeConfig::default_bootstrap().with_brand(brand);
+        let context = ThemeContext::new(PathBuf::from("/doc"), &runtime);
+
+        let css = compile_theme_css(&config, &context).unwrap();
+
+        assert!(
+            css.contains("#a1b2c3"),
+            "Brand primary color should be used"
+        );
+    }
+
+    #[test]
+    fn test_brand_layer_follows_last_builtin_theme() {
+        use crate::brand::Brand;
+
+        let runtime = NativeRuntime::new();
+        let context = ThemeContext::new(PathBuf::from("/doc"), &runtime);
+        let yaml = quarto_yaml::parse("color:\n  primary: \"#a1b2c3\"\n").unwrap();
+        let brand = Brand::from_yaml(&yaml.yaml, PathBuf::from("/doc"));
+        let config = ThemeConfig::new(
+            vec![
+                ThemeSpec::parse("cosmo").unwrap(),
+                ThemeSpec::parse("flatly").unwrap(),
+            ],
+            true,
+        )
+        .with_brand(brand.clone());
+
+        let mut result = process_theme_specs(&config.themes, &context).unwrap();
+        add_brand_layer(&mut result, &config).unwrap();
+
+        // [cosmo, customize, flatly, customize, brand]
+        assert_eq!(result.layers.len(), 5);
+        assert_eq!(result.layers[4], brand.to_sass_layer());
+    }
+
     #[test]
     fn test_compile_css_from_config_empty() {
         use quarto_pandoc_types::ConfigValueKind;
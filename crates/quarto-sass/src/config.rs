@@ -23,6 +23,18 @@
 //!       - cosmo
 //!       - custom.scss
 //!
+//! # Light and dark themes, toggled at runtime
+//! format:
+//!   html:
+//!     theme:
+//!       light: cosmo
+//!       dark: [darkly, custom-dark.scss]
+//!
+//! # A built-in light/dark pair is read the same way
+//! format:
+//!   html:
+//!     theme: [cosmo, darkly]
+//!
 //! # No theme (absent) - uses default Bootstrap
 //! format:
 //!   html: {}
@@ -30,6 +42,7 @@
 
 use quarto_pandoc_types::ConfigValue;
 
+use crate::brand::Brand;
 use crate::error::SassError;
 use crate::themes::ThemeSpec;
 
@@ -57,16 +70,29 @@ pub struct ThemeConfig {
     /// Empty means use default Bootstrap theme (no Bootswatch customization).
     pub themes: Vec<ThemeSpec>,
 
+    /// Theme specifications for the dark color scheme.
+    ///
+    /// Empty means the document has no dark mode.
+    pub dark_themes: Vec<ThemeSpec>,
+
     /// Whether to produce minified CSS.
     ///
     /// Defaults to `true` for consistency with TypeScript Quarto.
     pub minified: bool,
+
+    /// Brand from `_brand.yml`, layered over built-in themes.
+    pub brand: Option<Brand>,
 }
 
 impl ThemeConfig {
     /// Create a new ThemeConfig with the given themes.
     pub fn new(themes: Vec<ThemeSpec>, minified: bool) -> Self {
-        Self { themes, minified }
+        Self {
+            themes,
+            dark_themes: Vec::new(),
+            minified,
+            brand: None,
+        }
     }
 
     /// Create config for default Bootstrap theme (no Bootswatch customization).
@@ -76,15 +102,26 @@ impl ThemeConfig {
     pub fn default_bootstrap() -> Self {
         Self {
             themes: Vec::new(),
+            dark_themes: Vec::new(),
             minified: true,
+            brand: None,
         }
     }
 
+    /// Apply a brand to this config.
+    pub fn with_brand(mut self, brand: Brand) -> Self {
+        self.brand = Some(brand);
+        self
+    }
+
     /// Extract theme config from merged ConfigValue.
     ///
     /// Looks for `format.html.theme` in the config. Supports:
     /// - String: single theme name or path (e.g., `"cosmo"`, `"custom.scss"`)
     /// - Array: multiple themes to layer (e.g., `["cosmo", "custom.scss"]`)
+    /// - Array of two built-in themes, light then dark (e.g., `["cosmo", "darkly"]`):
+    ///   a light/dark pair
+    /// - Map with `light` and/or `dark` keys, each a string or array
     /// - Null/absent: use default Bootstrap theme
     ///
     /// # Arguments
@@ -98,7 +135,7 @@ impl ThemeConfig {
     /// # Errors
     ///
     /// Returns `SassError::InvalidThemeConfig` if the theme configuration
-    /// has an unexpected structure (e.g., a map instead of string/array).
+    /// has an unexpected structure (e.g., a map without `light` or `dark`).
     ///
     /// # Example
     ///
@@ -127,11 +164,16 @@ impl ThemeConfig {
                     return Ok(Self::default_bootstrap());
                 }
 
-                // Try to extract themes
-                let themes = extract_theme_specs(value)?;
+                let (themes, dark_themes) = if value.is_map() {
+                    extract_light_dark_specs(value)?
+                } else {
+                    split_light_dark_pair(extract_theme_specs(value)?)
+                };
                 Ok(Self {
                     themes,
+                    dark_themes,
                     minified: true, // Always minified for TS Quarto parity
+                    brand: None,
                 })
             }
         }
@@ -149,6 +191,59 @@ impl ThemeConfig {
     pub fn theme_specs(&self) -> &[ThemeSpec] {
         &self.themes
     }
+
+    /// Check if this config pairs its themes with a dark color scheme.
+    pub fn has_dark_mode(&self) -> bool {
+        !self.dark_themes.is_empty()
+    }
+
+    /// The config for compiling the dark stylesheet, if there is one.
+    ///
+    /// It uses the dark themes with the same brand and minification.
+    pub fn dark_config(&self) -> Option<ThemeConfig> {
+        if !self.has_dark_mode() {
+            return None;
+        }
+        Some(Self {
+            themes: self.dark_themes.clone(),
+            dark_themes: Vec::new(),
+            minified: self.minified,
+            brand: self.brand.clone(),
+        })
+    }
+}
+
+/// Extract light and dark theme specifications from a `{light, dark}` map.
+///
+/// A map with only `dark` keeps the default Bootstrap theme for light mode.
+fn extract_light_dark_specs(
+    value: &ConfigValue,
+) -> Result<(Vec<ThemeSpec>, Vec<ThemeSpec>), SassError> {
+    let light = value.get("light");
+    let dark = value.get("dark");
+    if light.is_none() && dark.is_none() {
+        return Err(invalid_theme_type());
+    }
+    let specs = |value: Option<&ConfigValue>| match value {
+        Some(value) if !value.is_null() => extract_theme_specs(value),
+        _ => Ok(Vec::new()),
+    };
+    Ok((specs(light)?, specs(dark)?))
+}
+
+/// Split `[light, dark]` into a light/dark pair when both are built-in
+/// themes and only the second is dark; otherwise all themes are layered
+/// as usual.
+fn split_light_dark_pair(mut specs: Vec<ThemeSpec>) -> (Vec<ThemeSpec>, Vec<ThemeSpec>) {
+    let is_dark = |spec: &ThemeSpec| spec.as_builtin().map(|theme| theme.is_dark());
+    if let [light, dark] = specs.as_slice()
+        && is_dark(light) == Some(false)
+        && is_dark(dark) == Some(true)
+    {
+        let dark = specs.split_off(1);
+        return (specs, dark);
+    }
+    (specs, Vec::new())
 }
 
 /// Extract theme specifications from a ConfigValue.
@@ -178,14 +273,21 @@ fn extract_theme_specs(value: &ConfigValue) -> Result<Vec<ThemeSpec>, SassError>
     }
 
     // Neither string nor array - invalid
-    Err(SassError::InvalidThemeConfig {
-        message: "theme must be a string or array of strings".to_string(),
-    })
+    Err(invalid_theme_type())
+}
+
+fn invalid_theme_type() -> SassError {
+    SassError::InvalidThemeConfig {
+        message:
+            "theme must be a string or array of strings, or a map with `light` and `dark` keys"
+                .to_string(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::themes::BuiltInTheme;
     use quarto_pandoc_types::{ConfigMapEntry, ConfigValueKind};
     use quarto_source_map::SourceInfo;
     use yaml_rust2::Yaml;
@@ -603,6 +705,80 @@ mod tests {
         }
     }
 
+    // === Dark mode tests ===
+
+    /// Helper to create a config with format.html.theme set to `theme`
+    fn config_with_theme_value(theme: ConfigValue) -> ConfigValue {
+        let entry = |key: &str, value| ConfigMapEntry {
+            key: key.to_string(),
+            key_source: SourceInfo::default(),
+            value,
+        };
+        let map =
+            |key: &str, value| ConfigValue::new_map(vec![entry(key, value)], SourceInfo::default());
+        map("format", map("html", map("theme", theme)))
+    }
+
+    #[test]
+    fn test_from_config_value_light_dark_pair() {
+        let config = config_with_theme_array(&["cosmo", "darkly"]);
+        let theme_config = ThemeConfig::from_config_value(&config).unwrap();
+
+        assert!(theme_config.has_dark_mode());
+        assert_eq!(theme_config.themes.len(), 1);
+        assert_eq!(
+            theme_config.themes[0].as_builtin(),
+            Some(BuiltInTheme::Cosmo)
+        );
+        assert_eq!(
+            theme_config.dark_themes[0].as_builtin(),
+            Some(BuiltInTheme::Darkly)
+        );
+    }
+
+    #[test]
+    fn test_from_config_value_two_light_themes_are_layered() {
+        let config = config_with_theme_array(&["cosmo", "flatly"]);
+        let theme_config = ThemeConfig::from_config_value(&config).unwrap();
+
+        assert!(!theme_config.has_dark_mode());
+        assert_eq!(theme_config.themes.len(), 2);
+        assert!(theme_config.dark_config().is_none());
+    }
+
+    #[test]
+    fn test_from_config_value_light_dark_map() {
+        let theme = ConfigValue::new_map(
+            vec![
+                ConfigMapEntry {
+                    key: "light".to_string(),
+                    key_source: SourceInfo::default(),
+                    value: ConfigValue::new_string("cosmo", SourceInfo::default()),
+                },
+                ConfigMapEntry {
+                    key: "dark".to_string(),
+                    key_source: SourceInfo::default(),
+                    value: ConfigValue::new_array(
+                        vec![
+                            ConfigValue::new_string("darkly", SourceInfo::default()),
+                            ConfigValue::new_string("dark.scss", SourceInfo::default()),
+                        ],
+                        SourceInfo::default(),
+                    ),
+                },
+            ],
+            SourceInfo::default(),
+        );
+        let theme_config = ThemeConfig::from_config_value(&config_with_theme_value(theme)).unwrap();
+
+        assert_eq!(theme_config.themes.len(), 1);
+        let dark = theme_config.dark_config().unwrap();
+        assert_eq!(dark.themes.len(), 2);
+        assert!(dark.themes[1].is_custom());
+        assert!(!dark.has_dark_mode());
+        assert_eq!(dark.minified, theme_config.minified);
+    }
+
     // === theme_specs accessor test ===
 
     #[test]
@@ -0,0 +1,434 @@
+//! Web font embedding.
+//!
+//! Copyright (c) 2025 Posit, PBC
+//!
+//! Bootswatch themes load their fonts with `@import url($web-font-path)`,
+//! and brand fonts from Google or Bunny Fonts are imported the same way
+//! (see [`BrandFont::import_url`](crate::BrandFont::import_url)). Those
+//! imports survive compilation, so a rendered page needs the network to
+//! show its fonts.
+//!
+//! [`embed_web_fonts`] finds these imports in compiled CSS, fetches each
+//! font stylesheet and the font files it references through the
+//! [`SystemRuntime`], and replaces the import with the stylesheet's
+//! `@font-face` rules pointing at local copies under [`FONTS_DIR`]
+//! (relative to the CSS file). The caller writes the returned
+//! [`FontFile`]s next to the CSS. An import whose stylesheet or fonts
+//! can't be fetched is left as is, so the page still works online.
+//!
+//! With a [`SassCache`], downloads are kept in the cache and reused.
+
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use quarto_system_runtime::SystemRuntime;
+use regex::Regex;
+
+use crate::cache::SassCache;
+
+/// Directory, relative to the CSS file, that embedded fonts are written to.
+pub const FONTS_DIR: &str = "fonts";
+
+/// `@import` of a remote stylesheet, quoted or not, with or without `url()`.
+static REMOTE_IMPORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"@import\s*(?:url\(\s*)?["']?(https?://[^"')\s;]+)["']?\s*\)?[^;]*;"#)
+        .expect("valid regex")
+});
+
+/// `url(...)` references in a font stylesheet.
+static URL_REFERENCE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"url\(\s*["']?([^"')]+)["']?\s*\)"#).expect("valid regex"));
+
+/// A font file to write alongside the CSS.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontFile {
+    /// Path relative to the CSS file (`fonts/<name>`)
+    pub path: String,
+    /// File contents
+    pub content: Vec<u8>,
+}
+
+/// CSS with its web fonts embedded.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmbeddedFonts {
+    /// The CSS, with embedded imports replaced by `@font-face` rules
+    pub css: String,
+    /// Font files the CSS now references
+    pub files: Vec<FontFile>,
+    /// Stylesheets that couldn't be embedded and are still imported
+    pub remote: Vec<String>,
+}
+
+/// Embed the web fonts imported by `css`, downloading through `cache` if
+/// given.
+pub fn embed_web_fonts(
+    css: &str,
+    runtime: &dyn SystemRuntime,
+    cache: Option<&SassCache>,
+) -> EmbeddedFonts {
+    let fetch = |url: &str| match cache {
+        Some(cache) => cache.fetch(url, runtime),
+        None => runtime.fetch_url(url).ok().map(|(content, _)| content),
+    };
+    let mut result = EmbeddedFonts::default();
+    let mut names: HashMap<String, String> = HashMap::new();
+    let mut font_faces = Vec::new();
+    let mut kept = String::with_capacity(css.len());
+    let mut last = 0;
+
+    for import in REMOTE_IMPORT.captures_iter(css) {
+        let statement = import.get(0).expect("match");
+        let url = &import[1];
+        kept.push_str(&css[last..statement.start()]);
+        last = statement.end();
+        match embed_stylesheet(url, &fetch, &mut names, &mut result.files) {
+            Some(rules) => font_faces.push(rules),
+            None => {
+                kept.push_str(statement.as_str());
+                result.remote.push(url.to_string());
+            }
+        }
+    }
+    kept.push_str(&css[last..]);
+
+    // `@import` must precede other rules, so the font faces go last
+    result.css = kept;
+    for rules in font_faces {
+        if !result.css.is_empty() && !result.css.ends_with('\n') {
+            result.css.push('\n');
+        }
+        result.css.push_str(rules.trim());
+    }
+    result
+}
+
+/// Fetch a font stylesheet and its fonts, returning the stylesheet with
+/// its URLs rewritten to local files. Nothing is added to `names` or
+/// `files` unless every font could be fetched.
+fn embed_stylesheet(
+    url: &str,
+    fetch: &dyn Fn(&str) -> Option<Vec<u8>>,
+    names: &mut HashMap<String, String>,
+    files: &mut Vec<FontFile>,
+) -> Option<String> {
+    let stylesheet = String::from_utf8(fetch(url)?).ok()?;
+
+    // Local paths of this stylesheet's fonts, including ones embedded for
+    // an earlier stylesheet
+    let mut local_paths: HashMap<String, String> = HashMap::new();
+    let mut fetched = Vec::new();
+    for reference in URL_REFERENCE.captures_iter(&stylesheet) {
+        if reference[1].starts_with("data:") {
+            continue;
+        }
+        let font_url = resolve_url(url, &reference[1]);
+        if local_paths.contains_key(&font_url) {
+            continue;
+        }
+        let path = match names.get(&font_url) {
+            Some(path) => path.clone(),
+            None => {
+                let content = fetch(&font_url)?;
+                let taken = |name: &str| {
+                    names
+                        .values()
+                        .chain(local_paths.values())
+                        .any(|path| path.rsplit('/').next() == Some(name))
+                };
+                let path = format!("{}/{}", FONTS_DIR, unique_name(&font_url, taken));
+                fetched.push(FontFile {
+                    path: path.clone(),
+                    content,
+                });
+                path
+            }
+        };
+        local_paths.insert(font_url, path);
+    }
+
+    let rewritten =
+        URL_REFERENCE.replace_all(&stylesheet, |reference: &regex::Captures| match local_paths
+            .get(&resolve_url(url, &reference[1]))
+        {
+            Some(path) => format!("url(\"{}\")", path),
+            None => reference[0].to_string(),
+        });
+    names.extend(local_paths);
+    files.extend(fetched);
+    Some(rewritten.into_owned())
+}
+
+/// Resolve `reference` (from the stylesheet at `base`) to an absolute URL.
+fn resolve_url(base: &str, reference: &str) -> String {
+    if reference.contains("://") || reference.starts_with("data:") {
+        return reference.to_string();
+    }
+    if let Some(rest) = reference.strip_prefix("//") {
+        let scheme = base.split("://").next().unwrap_or("https");
+        return format!("{}://{}", scheme, rest);
+    }
+    let origin_end = base
+        .find("://")
+        .and_then(|i| base[i + 3..].find('/').map(|j| i + 3 + j))
+        .unwrap_or(base.len());
+    if reference.starts_with('/') {
+        return format!("{}{}", &base[..origin_end], reference);
+    }
+    let path = base.split(['?', '#']).next().unwrap_or(base);
+    let dir_end = path
+        .rfind('/')
+        .filter(|&i| i >= origin_end)
+        .unwrap_or(path.len());
+    format!("{}/{}", &path[..dir_end], reference)
+}
+
+/// A file name for the font at `url` for which `taken` is false.
+fn unique_name(url: &str, taken: impl Fn(&str) -> bool) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let base: String = path
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let base = if base.is_empty() {
+        "font".to_string()
+    } else {
+        base
+    };
+    if !taken(&base) {
+        return base;
+    }
+    (1..)
+        .map(|i| format!("{}-{}", i, base))
+        .find(|name| !taken(name))
+        .expect("an unused name exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quarto_system_runtime::{
+        CommandOutput, NativeRuntime, PathKind, PathMetadata, RuntimeError, RuntimeResult, TempDir,
+        XdgDirKind,
+    };
+    use std::path::{Path, PathBuf};
+
+    /// Native runtime whose network serves canned responses.
+    struct FetchRuntime {
+        inner: NativeRuntime,
+        responses: HashMap<&'static str, &'static str>,
+    }
+
+    impl FetchRuntime {
+        fn new(responses: &[(&'static str, &'static str)]) -> Self {
+            Self {
+                inner: NativeRuntime::new(),
+                responses: responses.iter().copied().collect(),
+            }
+        }
+    }
+
+    impl SystemRuntime for FetchRuntime {
+        fn file_read(&self, path: &Path) -> RuntimeResult<Vec<u8>> {
+            self.inner.file_read(path)
+        }
+        fn file_write(&self, path: &Path, contents: &[u8]) -> RuntimeResult<()> {
+            self.inner.file_write(path, contents)
+        }
+        fn path_exists(&self, path: &Path, kind: Option<PathKind>) -> RuntimeResult<bool> {
+            self.inner.path_exists(path, kind)
+        }
+        fn canonicalize(&self, path: &Path) -> RuntimeResult<PathBuf> {
+            self.inner.canonicalize(path)
+        }
+        fn path_metadata(&self, path: &Path) -> RuntimeResult<PathMetadata> {
+            self.inner.path_metadata(path)
+        }
+        fn file_copy(&self, src: &Path, dst: &Path) -> RuntimeResult<()> {
+            self.inner.file_copy(src, dst)
+        }
+        fn path_rename(&self, old: &Path, new: &Path) -> RuntimeResult<()> {
+            self.inner.path_rename(old, new)
+        }
+        fn file_remove(&self, path: &Path) -> RuntimeResult<()> {
+            self.inner.file_remove(path)
+        }
+        fn dir_create(&self, path: &Path, recursive: bool) -> RuntimeResult<()> {
+            self.inner.dir_create(path, recursive)
+        }
+        fn dir_remove(&self, path: &Path, recursive: bool) -> RuntimeResult<()> {
+            self.inner.dir_remove(path, recursive)
+        }
+        fn dir_list(&self, path: &Path) -> RuntimeResult<Vec<PathBuf>> {
+            self.inner.dir_list(path)
+        }
+        fn cwd(&self) -> RuntimeResult<PathBuf> {
+            self.inner.cwd()
+        }
+        fn temp_dir(&self, template: &str) -> RuntimeResult<TempDir> {
+            self.inner.temp_dir(template)
+        }
+        fn exec_pipe(&self, command: &str, args: &[&str], stdin: &[u8]) -> RuntimeResult<Vec<u8>> {
+            self.inner.exec_pipe(command, args, stdin)
+        }
+        fn exec_command(
+            &self,
+            command: &str,
+            args: &[&str],
+            stdin: Option<&[u8]>,
+        ) -> RuntimeResult<CommandOutput> {
+            self.inner.exec_command(command, args, stdin)
+        }
+        fn env_get(&self, name: &str) -> RuntimeResult<Option<String>> {
+            self.inner.env_get(name)
+        }
+        fn env_all(&self) -> RuntimeResult<HashMap<String, String>> {
+            self.inner.env_all()
+        }
+        fn fetch_url(&self, url: &str) -> RuntimeResult<(Vec<u8>, String)> {
+            self.responses
+                .get(url)
+                .map(|body| (body.as_bytes().to_vec(), "text/css".to_string()))
+                .ok_or_else(|| RuntimeError::Network(format!("404: {}", url)))
+        }
+        fn os_name(&self) -> &'static str {
+            self.inner.os_name()
+        }
+        fn arch(&self) -> &'static str {
+            self.inner.arch()
+        }
+        fn cpu_time(&self) -> RuntimeResult<u64> {
+            self.inner.cpu_time()
+        }
+        fn xdg_dir(&self, kind: XdgDirKind, subpath: Option<&Path>) -> RuntimeResult<PathBuf> {
+            self.inner.xdg_dir(kind, subpath)
+        }
+        fn stdout_write(&self, data: &[u8]) -> RuntimeResult<()> {
+            self.inner.stdout_write(data)
+        }
+        fn stderr_write(&self, data: &[u8]) -> RuntimeResult<()> {
+            self.inner.stderr_write(data)
+        }
+    }
+
+    const LATO_CSS: &str = "https://fonts.googleapis.com/css2?family=Lato&display=swap";
+
+    #[test]
+    fn test_embeds_imported_fonts() {
+        let runtime = FetchRuntime::new(&[
+            (
+                LATO_CSS,
+                "@font-face {\n  font-family: 'Lato';\n  src: url(https://fonts.gstatic.com/s/lato/v24/a.woff2) format('woff2');\n}\n\
+                 @font-face {\n  font-family: 'Lato';\n  src: url(/s/lato/v24/b.woff2) format('woff2');\n}\n",
+            ),
+            ("https://fonts.gstatic.com/s/lato/v24/a.woff2", "A"),
+            ("https://fonts.googleapis.com/s/lato/v24/b.woff2", "B"),
+        ]);
+        let css = format!("@import url({});body{{color:red}}", LATO_CSS);
+
+        let embedded = embed_web_fonts(&css, &runtime, None);
+
+        assert!(embedded.remote.is_empty());
+        assert!(!embedded.css.contains("@import"));
+        assert!(embedded.css.starts_with("body{color:red}\n@font-face"));
+        assert!(
+            embedded
+                .css
+                .contains("src: url(\"fonts/a.woff2\") format('woff2')")
+        );
+        assert!(embedded.css.contains("src: url(\"fonts/b.woff2\")"));
+        let files: Vec<(&str, &[u8])> = embedded
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.content.as_slice()))
+            .collect();
+        assert_eq!(
+            files,
+            vec![
+                ("fonts/a.woff2", b"A".as_slice()),
+                ("fonts/b.woff2", b"B".as_slice())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keeps_import_when_a_font_is_missing() {
+        let runtime = FetchRuntime::new(&[(
+            LATO_CSS,
+            "@font-face { src: url(https://fonts.gstatic.com/missing.woff2); }",
+        )]);
+        let css = format!("@import url(\"{}\");body{{color:red}}", LATO_CSS);
+
+        let embedded = embed_web_fonts(&css, &runtime, None);
+
+        assert_eq!(embedded.css, css);
+        assert!(embedded.files.is_empty());
+        assert_eq!(embedded.remote, vec![LATO_CSS]);
+    }
+
+    #[test]
+    fn test_same_file_names_are_made_unique() {
+        let runtime = FetchRuntime::new(&[
+            (
+                LATO_CSS,
+                "@font-face { src: url(https://a.example/font.woff2); }\n\
+                 @font-face { src: url(https://b.example/font.woff2); }",
+            ),
+            ("https://a.example/font.woff2", "A"),
+            ("https://b.example/font.woff2", "B"),
+        ]);
+
+        let embedded = embed_web_fonts(&format!("@import url({});", LATO_CSS), &runtime, None);
+
+        let paths: Vec<&str> = embedded.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["fonts/font.woff2", "fonts/1-font.woff2"]);
+    }
+
+    #[test]
+    fn test_cached_fonts_embed_offline() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache = SassCache::new(temp.path());
+        let css = format!("@import url({});", LATO_CSS);
+        let online = FetchRuntime::new(&[
+            (
+                LATO_CSS,
+                "@font-face { src: url(https://x.example/a.woff2); }",
+            ),
+            ("https://x.example/a.woff2", "A"),
+        ]);
+        let first = embed_web_fonts(&css, &online, Some(&cache));
+
+        let offline = FetchRuntime::new(&[]);
+        let second = embed_web_fonts(&css, &offline, Some(&cache));
+
+        assert!(second.remote.is_empty());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        let base = "https://fonts.example/css2?family=A";
+        assert_eq!(resolve_url(base, "https://x/y.woff2"), "https://x/y.woff2");
+        assert_eq!(resolve_url(base, "//x/y.woff2"), "https://x/y.woff2");
+        assert_eq!(
+            resolve_url(base, "/s/y.woff2"),
+            "https://fonts.example/s/y.woff2"
+        );
+        assert_eq!(
+            resolve_url(base, "y.woff2"),
+            "https://fonts.example/y.woff2"
+        );
+        assert_eq!(
+            resolve_url("https://h/a/b.css", "y.woff2"),
+            "https://h/a/y.woff2"
+        );
+    }
+}
@@ -10,24 +10,38 @@
 //! - Bootswatch theme support
 //! - Bundle assembly for compilation
 //! - Theme configuration extraction from ConfigValue
+//! - Brand (`_brand.yml`) parsing, validation and SCSS mapping
+//! - Compiled CSS caching keyed by bundle hash
+//! - Embedding of web fonts for offline use
 
+pub mod brand;
 pub mod bundle;
+pub mod cache;
 pub mod compile;
 pub mod config;
 mod error;
+pub mod fonts;
 mod layer;
 pub mod resources;
 pub mod themes;
 mod types;
 
+pub use brand::{
+    BRAND_FILE, Brand, BrandColor, BrandFont, BrandFontStyle, BrandImage, BrandLogo,
+    BrandTypography, find_brand_file, load_brand,
+};
 pub use bundle::{
     assemble_bootstrap, assemble_scss, assemble_themes, assemble_with_theme,
     assemble_with_user_layers, load_bootstrap_framework, load_quarto_layer, load_theme,
     load_title_block_layer,
 };
-pub use compile::{compile_css_from_config, compile_default_css, compile_theme_css};
+pub use cache::{SassCache, SassCacheStats};
+pub use compile::{
+    compile_css_from_config, compile_dark_theme_css, compile_default_css, compile_theme_css,
+};
 pub use config::ThemeConfig;
 pub use error::SassError;
+pub use fonts::{EmbeddedFonts, FONTS_DIR, FontFile, embed_web_fonts};
 pub use layer::{merge_layers, parse_layer, parse_layer_from_parts};
 pub use resources::{
     BOOTSTRAP_RESOURCES, CombinedResources, EmbeddedResources, QUARTO_BOOTSTRAP_RESOURCES,
@@ -21,6 +21,7 @@ use std::str::FromStr;
 
 use quarto_system_runtime::{PathKind, SystemRuntime};
 
+use crate::cache::SassCache;
 use crate::error::SassError;
 use crate::layer::parse_layer;
 use crate::resources::THEMES_RESOURCES;
@@ -335,6 +336,9 @@ pub struct ThemeContext<'a> {
     /// This enables cross-platform file access - native `std::fs` on CLI,
     /// VirtualFileSystem on WASM (hub-client).
     runtime: &'a dyn SystemRuntime,
+
+    /// Cache of compiled CSS (native compilation only).
+    cache: Option<&'a SassCache>,
 }
 
 impl std::fmt::Debug for ThemeContext<'_> {
@@ -343,6 +347,7 @@ impl std::fmt::Debug for ThemeContext<'_> {
             .field("document_dir", &self.document_dir)
             .field("load_paths", &self.load_paths)
             .field("runtime", &"<SystemRuntime>")
+            .field("cache", &self.cache.map(SassCache::dir))
             .finish()
     }
 }
@@ -362,6 +367,7 @@ impl<'a> ThemeContext<'a> {
             document_dir,
             load_paths: Vec::new(),
             runtime,
+            cache: None,
         }
     }
 
@@ -381,9 +387,16 @@ impl<'a> ThemeContext<'a> {
             document_dir,
             load_paths,
             runtime,
+            cache: None,
         }
     }
 
+    /// Use `cache` to skip compiling bundles that were compiled before.
+    pub fn with_cache(mut self, cache: &'a SassCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
     /// Get the document directory.
     pub fn document_dir(&self) -> &Path {
         &self.document_dir
@@ -399,6 +412,11 @@ impl<'a> ThemeContext<'a> {
         self.runtime
     }
 
+    /// Get the compiled CSS cache, if any.
+    pub fn cache(&self) -> Option<&'a SassCache> {
+        self.cache
+    }
+
     /// Add a load path.
     pub fn add_load_path(&mut self, path: PathBuf) {
         self.load_paths.push(path);
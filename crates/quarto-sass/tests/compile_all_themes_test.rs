@@ -148,6 +148,23 @@ fn test_compiled_css_has_bootstrap_classes() {
     assert!(css.contains(".form-control"), "Should have form classes");
 }
 
+/// Test compiled CSS styles the callout markup produced by the callout
+/// transforms (`callout-style-*`, per-type icons, collapse toggles).
+#[test]
+fn test_compiled_css_has_callouts() {
+    let css = compile_theme(BuiltInTheme::Cosmo).expect("Cosmo should compile");
+
+    for selector in [
+        ".callout.callout-style-default",
+        ".callout.callout-style-simple",
+        "div.callout-note.callout-titled .callout-icon::before",
+        "div.callout-warning.callout-style-default > .callout-header",
+        ".callout .callout-btn-toggle .callout-toggle::before",
+    ] {
+        assert!(css.contains(selector), "Should have {} rule", selector);
+    }
+}
+
 /// Test compiled CSS contains editorial mark styling.
 ///
 /// Editorial marks syntax:
@@ -15,7 +15,7 @@
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::fs;
-use std::io::{self, Write};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
@@ -259,23 +259,14 @@ impl SystemRuntime for NativeRuntime {
     // NETWORK
     // ═══════════════════════════════════════════════════════════════════════
 
-    fn fetch_url(&self, _url: &str) -> RuntimeResult<(Vec<u8>, String)> {
-        // Network support requires an HTTP client dependency
-        // For now, return NotSupported until we add reqwest or similar
-        Err(RuntimeError::NotSupported(
-            "Network fetch not yet implemented. Consider using pandoc.mediabag.fetch instead."
-                .to_string(),
-        ))
-
-        // Future implementation with reqwest:
-        // let response = self.http_client.get(url).send()?;
-        // let mime_type = response.headers()
-        //     .get("content-type")
-        //     .and_then(|v| v.to_str().ok())
-        //     .unwrap_or("application/octet-stream")
-        //     .to_string();
-        // let content = response.bytes()?.to_vec();
-        // Ok((content, mime_type))
+    fn fetch_url(&self, url: &str) -> RuntimeResult<(Vec<u8>, String)> {
+        let response = ureq::get(url)
+            .call()
+            .map_err(|e| RuntimeError::Network(format!("{}: {}", url, e)))?;
+        let mime_type = response.content_type().to_string();
+        let mut content = Vec::new();
+        response.into_reader().read_to_end(&mut content)?;
+        Ok((content, mime_type))
     }
 
     // ═══════════════════════════════════════════════════════════════════════
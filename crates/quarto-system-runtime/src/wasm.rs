@@ -20,6 +20,7 @@ use std::io;
 use std::path::{Component, Path, PathBuf};
 use std::sync::RwLock;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
@@ -131,6 +132,56 @@ fn not_found_error(path: &Path) -> RuntimeError {
     ))
 }
 
+/// The current time, from the browser clock.
+///
+/// `SystemTime::now()` panics on `wasm32-unknown-unknown`.
+fn now() -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(js_sys::Date::now() as u64)
+}
+
+/// Whether a path matches a glob pattern.
+///
+/// Both are split into `/`-separated segments. `**` matches any number of
+/// segments; in other segments, `*` matches any characters and `?` any one
+/// character.
+fn glob_matches(pattern: &Path, path: &Path) -> bool {
+    let pattern: Vec<String> = pattern
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let path: Vec<String> = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    segments_match(&pattern, &path)
+}
+
+fn segments_match(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| segments_match(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path_rest)) => {
+                let pattern: Vec<char> = first.chars().collect();
+                let segment: Vec<char> = segment.chars().collect();
+                segment_matches(&pattern, &segment) && segments_match(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn segment_matches(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|skip| segment_matches(rest, &text[skip..])),
+        Some(('?', rest)) => !text.is_empty() && segment_matches(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && segment_matches(rest, &text[1..]),
+    }
+}
+
 /// Virtual filesystem for WASM environments.
 ///
 /// This provides an in-memory filesystem that can be pre-populated with
@@ -139,7 +190,8 @@ fn not_found_error(path: &Path) -> RuntimeError {
 /// The VFS supports:
 /// - Files with arbitrary byte content
 /// - Directory structure (automatically created when files are added)
-/// - Standard operations: read, write, remove, list, copy
+/// - Modification times of files and directories
+/// - Standard operations: read, write, remove, list, glob, copy, rename
 ///
 /// Thread safety: Uses RwLock to satisfy Send + Sync trait bounds.
 /// In practice, WASM is single-threaded so this is never contended.
@@ -149,6 +201,8 @@ pub struct VirtualFileSystem {
     files: HashMap<PathBuf, Vec<u8>>,
     /// Directory entries (automatically includes parents of all files)
     directories: HashSet<PathBuf>,
+    /// Modification times of files and directories
+    modified: HashMap<PathBuf, SystemTime>,
     /// Project root directory (default working directory)
     project_root: PathBuf,
 }
@@ -159,6 +213,7 @@ impl VirtualFileSystem {
         let mut vfs = Self {
             files: HashMap::new(),
             directories: HashSet::new(),
+            modified: HashMap::new(),
             project_root: PathBuf::from("/project"),
         };
         // Create the root directory
@@ -172,6 +227,7 @@ impl VirtualFileSystem {
         let mut vfs = Self {
             files: HashMap::new(),
             directories: HashSet::new(),
+            modified: HashMap::new(),
             project_root: project_root.clone(),
         };
         // Create the root directory and project root
@@ -182,13 +238,21 @@ impl VirtualFileSystem {
 
     /// Add a file to the virtual filesystem.
     ///
-    /// This will automatically create all parent directories.
+    /// This will automatically create all parent directories. The file's
+    /// modification time is the current time.
     pub fn add_file(&mut self, path: &Path, contents: Vec<u8>) {
+        self.add_file_modified(path, contents, now());
+    }
+
+    /// Add a file with a given modification time (e.g. the time of the
+    /// change that produced it).
+    pub fn add_file_modified(&mut self, path: &Path, contents: Vec<u8>, modified: SystemTime) {
         let normalized = self.normalize_path(path);
         // Create parent directories
         if let Some(parent) = normalized.parent() {
             self.add_directory_and_parents(parent);
         }
+        self.modified.insert(normalized.clone(), modified);
         self.files.insert(normalized, contents);
     }
 
@@ -202,6 +266,7 @@ impl VirtualFileSystem {
     /// Returns true if the file existed and was removed.
     pub fn remove_file(&mut self, path: &Path) -> bool {
         let normalized = self.normalize_path(path);
+        self.modified.remove(&normalized);
         self.files.remove(&normalized).is_some()
     }
 
@@ -246,11 +311,14 @@ impl VirtualFileSystem {
 
         // Remove all files and directories under this path
         for file in files_under {
+            self.modified.remove(&file);
             self.files.remove(&file);
         }
         for dir in dirs_under {
+            self.modified.remove(&dir);
             self.directories.remove(&dir);
         }
+        self.modified.remove(&normalized);
         self.directories.remove(&normalized);
 
         Ok(())
@@ -261,6 +329,92 @@ impl VirtualFileSystem {
         self.files.keys().cloned().collect()
     }
 
+    /// List the files matching a glob pattern (see [`glob_matches`]), e.g.
+    /// `posts/**/*.qmd`. Relative patterns are relative to the project root.
+    pub fn glob(&self, pattern: &str) -> Vec<PathBuf> {
+        let pattern = self.normalize_path(Path::new(pattern));
+        let mut matches: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| glob_matches(&pattern, path))
+            .cloned()
+            .collect();
+        matches.sort();
+        matches
+    }
+
+    /// List the files under a directory, recursively.
+    pub fn list_files_under(&self, path: &Path) -> Vec<PathBuf> {
+        let normalized = self.normalize_path(path);
+        let mut files: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|p| p.starts_with(&normalized))
+            .cloned()
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Copy a file, giving the copy the current modification time.
+    pub fn copy_file(&mut self, src: &Path, dst: &Path) -> RuntimeResult<()> {
+        let contents = self.read_file(src)?;
+        self.add_file(dst, contents);
+        Ok(())
+    }
+
+    /// Rename a file or a directory (with everything under it), keeping
+    /// modification times.
+    pub fn rename(&mut self, old: &Path, new: &Path) -> RuntimeResult<()> {
+        let old = self.normalize_path(old);
+        let new = self.normalize_path(new);
+        if !self.exists(&old) {
+            return Err(not_found_error(&old));
+        }
+        if new.starts_with(&old) && new != old {
+            return Err(RuntimeError::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Cannot move a directory into itself",
+            )));
+        }
+
+        // Where a path under `old` moves to
+        let moved = |path: &Path| match path.strip_prefix(&old) {
+            Ok(rest) if !rest.as_os_str().is_empty() => new.join(rest),
+            _ => new.clone(),
+        };
+
+        let moved_files: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|p| p.starts_with(&old))
+            .cloned()
+            .collect();
+        for file in moved_files {
+            let target = moved(&file);
+            let contents = self.files.remove(&file).unwrap();
+            let modified = self.modified.remove(&file).unwrap_or_else(now);
+            self.add_file_modified(&target, contents, modified);
+        }
+
+        let moved_dirs: Vec<PathBuf> = self
+            .directories
+            .iter()
+            .filter(|p| p.starts_with(&old))
+            .cloned()
+            .collect();
+        for dir in moved_dirs {
+            let target = moved(&dir);
+            let modified = self.modified.remove(&dir);
+            self.directories.remove(&dir);
+            self.add_directory_and_parents(&target);
+            if let Some(modified) = modified {
+                self.modified.insert(target, modified);
+            }
+        }
+        Ok(())
+    }
+
     /// List contents of a directory.
     pub fn list_directory(&self, path: &Path) -> RuntimeResult<Vec<PathBuf>> {
         let normalized = self.normalize_path(path);
@@ -296,6 +450,7 @@ impl VirtualFileSystem {
     pub fn clear(&mut self) {
         self.files.clear();
         self.directories.clear();
+        self.modified.clear();
         // Re-add root
         self.directories.insert(PathBuf::from("/"));
         self.directories.insert(self.project_root.clone());
@@ -318,6 +473,9 @@ impl VirtualFileSystem {
                 || path == &project_root
                 || path.to_string_lossy().starts_with(preserved_prefix)
         });
+        let (files, directories) = (&self.files, &self.directories);
+        self.modified
+            .retain(|path, _| files.contains_key(path) || directories.contains(path));
 
         // Re-add root and project root in case they were removed
         self.directories.insert(PathBuf::from("/"));
@@ -351,6 +509,12 @@ impl VirtualFileSystem {
             .ok_or_else(|| not_found_error(&normalized))
     }
 
+    /// Get the modification time of a file or directory.
+    pub fn modified(&self, path: &Path) -> Option<SystemTime> {
+        let normalized = self.normalize_path(path);
+        self.modified.get(&normalized).copied()
+    }
+
     /// Get the size of a file.
     pub fn file_size(&self, path: &Path) -> Option<u64> {
         let normalized = self.normalize_path(path);
@@ -399,12 +563,15 @@ impl VirtualFileSystem {
         normalized
     }
 
-    /// Add a directory and all its parent directories.
+    /// Add a directory and all its parent directories, recording when the
+    /// new ones were created.
     fn add_directory_and_parents(&mut self, path: &Path) {
         let mut current = PathBuf::new();
         for component in path.components() {
             current.push(component);
-            self.directories.insert(current.clone());
+            if self.directories.insert(current.clone()) {
+                self.modified.insert(current.clone(), now());
+            }
         }
     }
 }
@@ -454,11 +621,29 @@ impl WasmRuntime {
         self.vfs.write().unwrap().remove_file(path)
     }
 
+    /// Add a file with a given modification time.
+    pub fn add_file_modified(&self, path: &Path, contents: Vec<u8>, modified: SystemTime) {
+        self.vfs
+            .write()
+            .unwrap()
+            .add_file_modified(path, contents, modified);
+    }
+
     /// List all files in the virtual filesystem.
     pub fn list_files(&self) -> Vec<PathBuf> {
         self.vfs.read().unwrap().list_files()
     }
 
+    /// List the files matching a glob pattern.
+    pub fn glob_files(&self, pattern: &str) -> Vec<PathBuf> {
+        self.vfs.read().unwrap().glob(pattern)
+    }
+
+    /// List the files under a directory, recursively.
+    pub fn list_files_under(&self, path: &Path) -> Vec<PathBuf> {
+        self.vfs.read().unwrap().list_files_under(path)
+    }
+
     /// Clear all files from the virtual filesystem.
     pub fn clear_files(&self) {
         self.vfs.write().unwrap().clear();
@@ -518,15 +703,15 @@ impl SystemRuntime for WasmRuntime {
             Ok(PathMetadata {
                 kind: PathKind::File,
                 size,
-                modified: None, // VFS doesn't track modification times
-                accessed: None,
+                modified: vfs.modified(path),
+                accessed: None, // VFS doesn't track access times
                 readonly: false,
             })
         } else if vfs.is_directory(path) {
             Ok(PathMetadata {
                 kind: PathKind::Directory,
                 size: 0,
-                modified: None,
+                modified: vfs.modified(path),
                 accessed: None,
                 readonly: false,
             })
@@ -536,15 +721,11 @@ impl SystemRuntime for WasmRuntime {
     }
 
     fn file_copy(&self, src: &Path, dst: &Path) -> RuntimeResult<()> {
-        let contents = self.file_read(src)?;
-        self.file_write(dst, &contents)
+        self.vfs.write().unwrap().copy_file(src, dst)
     }
 
     fn path_rename(&self, old: &Path, new: &Path) -> RuntimeResult<()> {
-        let contents = self.file_read(old)?;
-        self.file_write(new, &contents)?;
-        self.vfs.write().unwrap().remove_file(old);
-        Ok(())
+        self.vfs.write().unwrap().rename(old, new)
     }
 
     fn file_remove(&self, path: &Path) -> RuntimeResult<()> {
@@ -1007,6 +1188,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_vfs_glob() {
+        let mut vfs = VirtualFileSystem::new();
+        vfs.add_file(Path::new("/project/index.qmd"), b"".to_vec());
+        vfs.add_file(Path::new("/project/posts/a.qmd"), b"".to_vec());
+        vfs.add_file(Path::new("/project/posts/2024/b.qmd"), b"".to_vec());
+        vfs.add_file(Path::new("/project/posts/image.png"), b"".to_vec());
+
+        assert_eq!(
+            vfs.glob("posts/**/*.qmd"),
+            vec![
+                PathBuf::from("/project/posts/2024/b.qmd"),
+                PathBuf::from("/project/posts/a.qmd"),
+            ]
+        );
+        assert_eq!(vfs.glob("*.qmd"), vec![PathBuf::from("/project/index.qmd")]);
+        assert_eq!(
+            vfs.glob("/project/posts/?.qmd"),
+            vec![PathBuf::from("/project/posts/a.qmd")]
+        );
+    }
+
+    #[test]
+    fn test_vfs_rename_directory_keeps_modified() {
+        let mut vfs = VirtualFileSystem::new();
+        let modified = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        vfs.add_file_modified(Path::new("/project/drafts/a.qmd"), b"a".to_vec(), modified);
+        vfs.add_file_modified(
+            Path::new("/project/drafts/img/b.png"),
+            b"b".to_vec(),
+            modified,
+        );
+
+        vfs.rename(Path::new("/project/drafts"), Path::new("/project/posts"))
+            .unwrap();
+
+        assert!(!vfs.exists(Path::new("/project/drafts")));
+        assert!(!vfs.exists(Path::new("/project/drafts/img")));
+        assert_eq!(
+            vfs.read_file(Path::new("/project/posts/a.qmd")).unwrap(),
+            b"a"
+        );
+        assert!(vfs.is_directory(Path::new("/project/posts/img")));
+        assert_eq!(
+            vfs.modified(Path::new("/project/posts/img/b.png")),
+            Some(modified)
+        );
+        assert!(
+            vfs.rename(Path::new("/project/posts"), Path::new("/project/posts/x"))
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_wasm_runtime_process_not_supported() {
         let runtime = WasmRuntime::new();
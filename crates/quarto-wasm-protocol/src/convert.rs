@@ -0,0 +1,26 @@
+/*
+ * convert.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Responses of `wasm-qmd-parser` conversions.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Response of a conversion between formats.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(optional_fields)]
+pub struct ConvertResponse {
+    /// The converted document.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Why the conversion failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Errors, or warnings of a successful conversion, as written by
+    /// `DiagnosticMessage::to_json()`.
+    #[serde(default)]
+    #[ts(type = "Array<unknown>")]
+    pub diagnostics: Vec<serde_json::Value>,
+}
@@ -0,0 +1,68 @@
+/*
+ * diagnostic.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Diagnostics with line/column information, for Monaco.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticKind {
+    Error,
+    Warning,
+    Info,
+    Note,
+}
+
+/// Kind of a diagnostic detail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum DetailKind {
+    Error,
+    Info,
+    Note,
+}
+
+/// A detail of a diagnostic, with its own location.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(rename = "DiagnosticDetail", optional_fields)]
+pub struct JsonDiagnosticDetail {
+    pub kind: DetailKind,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_column: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<u32>,
+}
+
+/// A diagnostic message.
+///
+/// Line and column numbers are 1-based to match Monaco's expectations.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(rename = "Diagnostic", optional_fields)]
+pub struct JsonDiagnostic {
+    pub kind: DiagnosticKind,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<String>,
+    pub hints: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_column: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<u32>,
+    pub details: Vec<JsonDiagnosticDetail>,
+}
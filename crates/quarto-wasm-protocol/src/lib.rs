@@ -0,0 +1,128 @@
+/*
+ * lib.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! JSON response types of the Quarto WASM modules.
+//!
+//! `wasm-quarto-hub-client` and `wasm-qmd-parser` return JSON strings to
+//! their TypeScript clients. The types of those responses live here, so both
+//! sides are generated from one definition: [`typescript()`] renders them as
+//! TypeScript declarations, checked in as `hub-client/src/types/protocol.ts`
+//! and regenerated with `cargo xtask protocol-types`. A test fails when the
+//! checked-in file is out of date.
+//!
+//! [`PROTOCOL_VERSION`] is bumped on incompatible changes (removed or retyped
+//! fields); the modules export it as `protocol_version()`, and clients
+//! compare it with the version they were generated for.
+
+pub mod convert;
+pub mod diagnostic;
+pub mod render;
+pub mod vfs;
+
+pub use convert::ConvertResponse;
+pub use diagnostic::{DetailKind, DiagnosticKind, JsonDiagnostic, JsonDiagnosticDetail};
+pub use render::{
+    AsyncRenderResponse, IncrementalRenderResponse, ProjectRenderResponse, RegionInsert,
+    RenderOutput, RenderPatch, RenderProgress, RenderResponse, RenderedDocument,
+};
+pub use vfs::{VfsMetadata, VfsPathKind, VfsResponse};
+
+use ts_rs::TS;
+
+/// Version of the response types, bumped on incompatible changes.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Path of the generated TypeScript declarations, relative to the repository
+/// root.
+pub const TYPESCRIPT_PATH: &str = "hub-client/src/types/protocol.ts";
+
+/// Serialize a response to the JSON string returned to JavaScript.
+pub fn to_json<T: serde::Serialize>(response: &T) -> String {
+    serde_json::to_string(response).unwrap()
+}
+
+fn declaration<T: TS>(out: &mut String) {
+    out.push('\n');
+    if let Some(docs) = T::docs() {
+        out.push_str(&docs);
+    }
+    out.push_str("export ");
+    out.push_str(&T::decl());
+    out.push('\n');
+}
+
+/// The TypeScript declarations of every response type.
+pub fn typescript() -> String {
+    let mut out = String::from(
+        "// Generated by quarto-wasm-protocol. Do not edit: change the Rust types\n\
+         // and run `cargo xtask protocol-types`.\n",
+    );
+    out.push_str(&format!(
+        "\n/** Version of the response types; see `protocol_version()`. */\n\
+         export const PROTOCOL_VERSION = {};\n",
+        PROTOCOL_VERSION
+    ));
+
+    declaration::<DiagnosticKind>(&mut out);
+    declaration::<DetailKind>(&mut out);
+    declaration::<JsonDiagnosticDetail>(&mut out);
+    declaration::<JsonDiagnostic>(&mut out);
+
+    declaration::<VfsPathKind>(&mut out);
+    declaration::<VfsMetadata>(&mut out);
+    declaration::<VfsResponse>(&mut out);
+
+    declaration::<RenderOutput>(&mut out);
+    declaration::<RenderResponse>(&mut out);
+    declaration::<AsyncRenderResponse>(&mut out);
+    declaration::<RenderProgress>(&mut out);
+    declaration::<RegionInsert>(&mut out);
+    declaration::<RenderPatch>(&mut out);
+    declaration::<IncrementalRenderResponse>(&mut out);
+    declaration::<RenderedDocument>(&mut out);
+    declaration::<ProjectRenderResponse>(&mut out);
+
+    declaration::<ConvertResponse>(&mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn typescript_is_up_to_date() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../..")
+            .join(TYPESCRIPT_PATH);
+        let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            checked_in == typescript(),
+            "{} is out of date; run `cargo xtask protocol-types`",
+            TYPESCRIPT_PATH
+        );
+    }
+
+    #[test]
+    fn optional_fields_are_omitted() {
+        let json = to_json(&VfsResponse::ok());
+        assert_eq!(json, r#"{"success":true}"#);
+        let json = to_json(&VfsResponse::error("File not found"));
+        assert_eq!(json, r#"{"success":false,"error":"File not found"}"#);
+    }
+
+    #[test]
+    fn flattened_responses() {
+        let response = AsyncRenderResponse {
+            render: RenderResponse::error("Render was cancelled".to_string(), None),
+            cancelled: true,
+        };
+        assert_eq!(
+            to_json(&response),
+            r#"{"success":false,"error":"Render was cancelled","cancelled":true}"#
+        );
+    }
+}
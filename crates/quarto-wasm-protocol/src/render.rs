@@ -0,0 +1,196 @@
+/*
+ * render.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Responses of the render operations.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::diagnostic::JsonDiagnostic;
+
+/// Response of a document render.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(optional_fields)]
+pub struct RenderResponse {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html: Option<String>,
+    /// Structured diagnostics (errors) with line/column information for Monaco.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Vec<JsonDiagnostic>>,
+    /// Structured warnings with line/column information for Monaco.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Vec<JsonDiagnostic>>,
+    /// Binary files the page refers to (images, fonts, downloads), keyed by
+    /// their URL relative to the document, for the preview to serve.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outputs: Option<BTreeMap<String, RenderOutput>>,
+}
+
+impl RenderResponse {
+    /// A successful render; no warnings are left out of the JSON.
+    pub fn ok(
+        html: String,
+        warnings: Vec<JsonDiagnostic>,
+        outputs: Option<BTreeMap<String, RenderOutput>>,
+    ) -> Self {
+        Self {
+            success: true,
+            html: Some(html),
+            warnings: (!warnings.is_empty()).then_some(warnings),
+            outputs,
+            ..Self::default()
+        }
+    }
+
+    /// A failed render.
+    pub fn error(msg: String, diagnostics: Option<Vec<JsonDiagnostic>>) -> Self {
+        Self {
+            success: false,
+            error: Some(msg),
+            diagnostics,
+            ..Self::default()
+        }
+    }
+}
+
+/// A binary file of a render, as served to the preview.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RenderOutput {
+    /// MIME type of the content.
+    pub content_type: String,
+    /// Base64-encoded content.
+    pub content: String,
+}
+
+/// Response of a cancellable render.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct AsyncRenderResponse {
+    #[serde(flatten)]
+    pub render: RenderResponse,
+    /// Whether the render stopped because it was cancelled.
+    pub cancelled: bool,
+}
+
+/// Progress of a render: the pipeline stage about to run.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RenderProgress {
+    /// Name of the stage.
+    pub stage: String,
+    /// Zero-based index of the stage.
+    pub index: u32,
+    /// Number of stages of the pipeline.
+    pub total: u32,
+}
+
+/// The changes turning the previous render into the current one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+pub struct RenderPatch {
+    /// Whether the preview must load the whole page instead.
+    pub full_reload: bool,
+    /// IDs of the regions to remove.
+    pub remove: Vec<String>,
+    /// Regions to insert, in document order.
+    pub insert: Vec<RegionInsert>,
+}
+
+impl RenderPatch {
+    /// A patch asking for a full reload.
+    pub fn full_reload() -> Self {
+        Self {
+            full_reload: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// A region of the preview to insert.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+pub struct RegionInsert {
+    /// Node ID of the region (its `data-quarto-node` attribute).
+    pub id: String,
+    /// The ID of the section it is in, or `None` for the top level.
+    pub parent: Option<String>,
+    /// The ID of the sibling it follows, or `None` for the first child.
+    pub after: Option<String>,
+    /// The region's HTML, with the node IDs of its descendants.
+    pub html: String,
+}
+
+/// Response of an incremental render: a render plus a patch from the
+/// previous one.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(optional_fields)]
+pub struct IncrementalRenderResponse {
+    #[serde(flatten)]
+    pub render: RenderResponse,
+    /// Token identifying this render, to patch the next render against.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_token: Option<String>,
+    /// Changes from the render identified by `prev_state_token`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<RenderPatch>,
+}
+
+/// A document of a project render, in the manifest.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(optional_fields)]
+pub struct RenderedDocument {
+    /// The input file, relative to the project directory.
+    pub input: String,
+    /// The output file written to the VFS, relative to the project directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Structured diagnostics (errors) with line/column information.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Vec<JsonDiagnostic>>,
+    /// Structured warnings with line/column information.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Vec<JsonDiagnostic>>,
+}
+
+/// Response of a project render: a manifest of the outputs written to the
+/// VFS.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(optional_fields)]
+pub struct ProjectRenderResponse {
+    /// Whether every document rendered.
+    pub success: bool,
+    /// Why the project couldn't be rendered at all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The project directory, in the VFS.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_dir: Option<String>,
+    /// The output directory, relative to the project directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+    /// The documents, in render order.
+    pub documents: Vec<RenderedDocument>,
+    /// Resources (images, ...) copied to the output directory, relative to
+    /// the project directory.
+    pub resources: Vec<String>,
+    /// Problems of `_quarto.yml`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Vec<JsonDiagnostic>>,
+}
+
+impl ProjectRenderResponse {
+    /// A project that couldn't be rendered at all.
+    pub fn error(msg: String) -> Self {
+        Self {
+            success: false,
+            error: Some(msg),
+            ..Self::default()
+        }
+    }
+}
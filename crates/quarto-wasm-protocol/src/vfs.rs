@@ -0,0 +1,85 @@
+/*
+ * vfs.rs
+ * Copyright (c) 2025 Posit, PBC
+ */
+
+//! Responses of the virtual filesystem operations.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Response of a VFS operation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(optional_fields)]
+pub struct VfsResponse {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Paths, for listings.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<String>>,
+    /// File content (base64 for binary reads).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<VfsMetadata>,
+}
+
+impl VfsResponse {
+    pub fn ok() -> Self {
+        Self {
+            success: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn error(msg: &str) -> Self {
+        Self {
+            success: false,
+            error: Some(msg.to_string()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_files(paths: Vec<String>) -> Self {
+        Self {
+            files: Some(paths),
+            ..Self::ok()
+        }
+    }
+
+    pub fn with_metadata(metadata: VfsMetadata) -> Self {
+        Self {
+            metadata: Some(metadata),
+            ..Self::ok()
+        }
+    }
+
+    pub fn with_content(text: String) -> Self {
+        Self {
+            content: Some(text),
+            ..Self::ok()
+        }
+    }
+}
+
+/// Kind of a VFS path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+pub enum VfsPathKind {
+    File,
+    Directory,
+}
+
+/// Metadata of a VFS path.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(optional_fields)]
+pub struct VfsMetadata {
+    pub kind: VfsPathKind,
+    /// Size in bytes (0 for directories).
+    #[ts(type = "number")]
+    pub size: u64,
+    /// Modification time in milliseconds since the Unix epoch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<f64>,
+}
@@ -0,0 +1,322 @@
+//! Completion data derived from schemas.
+//!
+//! Editors complete YAML (front matter, `_quarto.yml`) from the same schemas
+//! used for validation. [`completion_model`] walks a schema once and returns
+//! a [`CompletionNode`] tree: for every value, the keys it may have (with
+//! their own subtrees), the values it may take and their descriptions.
+//! The tree serializes to JSON, so consumers such as the language server
+//! need no schema traversal of their own.
+//!
+//! # Example
+//!
+//! ```
+//! use quarto_yaml_validation::{Schema, SchemaRegistry, completion_model};
+//!
+//! let yaml = quarto_yaml::parse(r#"
+//! object:
+//!   properties:
+//!     toc: boolean
+//!     engine:
+//!       enum: [knitr, jupyter]
+//! "#).unwrap();
+//! let schema = Schema::from_yaml(&yaml).unwrap();
+//!
+//! let model = completion_model(&schema, &SchemaRegistry::new());
+//! let engine = model.at_path(&["engine"]).unwrap();
+//! assert_eq!(engine.values, vec!["knitr", "jupyter"]);
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::schema::{Schema, SchemaRegistry};
+
+/// Completions for a YAML value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionNode {
+    /// Short description of the value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Longer documentation of the value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+
+    /// Types the value may have (`string`, `object`, ...), in schema order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+
+    /// Values to offer: enum values, `completions` annotations, and
+    /// `true`/`false` for booleans
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+
+    /// Keys of a mapping value, by name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<String, CompletionNode>,
+
+    /// Completions for the items of an array value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<CompletionNode>>,
+}
+
+impl CompletionNode {
+    /// The node for the value at `path`, a sequence of mapping keys.
+    ///
+    /// Array items are stepped through, so `["format", "html"]` also finds
+    /// the keys of a list of mappings.
+    pub fn at_path(&self, path: &[&str]) -> Option<&CompletionNode> {
+        let Some((key, rest)) = path.split_first() else {
+            return Some(self);
+        };
+        let node = match self.keys.get(*key) {
+            Some(node) => node,
+            None => self.items.as_ref()?.keys.get(*key)?,
+        };
+        node.at_path(rest)
+    }
+
+    /// The model as JSON.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("completion model serializes to JSON")
+    }
+
+    /// Add the completions of an alternative for the same value.
+    fn merge(&mut self, other: CompletionNode) {
+        if self.description.is_none() {
+            self.description = other.description;
+        }
+        if self.documentation.is_none() {
+            self.documentation = other.documentation;
+        }
+        push_unique(&mut self.types, other.types);
+        push_unique(&mut self.values, other.values);
+        for (key, node) in other.keys {
+            match self.keys.get_mut(&key) {
+                Some(existing) => existing.merge(node),
+                None => {
+                    self.keys.insert(key, node);
+                }
+            }
+        }
+        if let Some(other_items) = other.items {
+            match &mut self.items {
+                Some(items) => items.merge(*other_items),
+                None => self.items = Some(other_items),
+            }
+        }
+    }
+}
+
+/// Build the completion model of `schema`.
+///
+/// References are resolved through `registry`; a reference already being
+/// expanded (a recursive schema) is not expanded again. Properties marked
+/// `hidden` are left out.
+pub fn completion_model(schema: &Schema, registry: &SchemaRegistry) -> CompletionNode {
+    let mut expanding = Vec::new();
+    walk(schema, registry, &mut expanding)
+}
+
+fn walk<'a>(
+    schema: &'a Schema,
+    registry: &'a SchemaRegistry,
+    expanding: &mut Vec<&'a str>,
+) -> CompletionNode {
+    let annotations = schema.annotations();
+    let mut node = CompletionNode {
+        description: annotations.description.clone(),
+        documentation: annotations.documentation.clone(),
+        ..Default::default()
+    };
+    if !matches!(
+        schema,
+        Schema::AnyOf(_) | Schema::AllOf(_) | Schema::Ref(_) | Schema::True
+    ) {
+        node.types.push(schema.type_name().to_string());
+    }
+
+    match schema {
+        Schema::Boolean(_) => node.values = vec!["true".to_string(), "false".to_string()],
+        Schema::Enum(e) => {
+            node.values = e
+                .values
+                .iter()
+                .map(|value| match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect();
+        }
+        Schema::AnyOf(any) => {
+            for alternative in &any.schemas {
+                node.merge(walk(alternative, registry, expanding));
+            }
+        }
+        Schema::AllOf(all) => {
+            for part in &all.schemas {
+                node.merge(walk(part, registry, expanding));
+            }
+        }
+        Schema::Array(array) => {
+            if let Some(items) = &array.items {
+                node.items = Some(Box::new(walk(items, registry, expanding)));
+            }
+        }
+        Schema::Object(object) => {
+            for base in object.base_schema.iter().flatten() {
+                node.merge(walk(base, registry, expanding));
+            }
+            for (key, property) in &object.properties {
+                if property.annotations().hidden == Some(true) {
+                    continue;
+                }
+                let child = walk(property, registry, expanding);
+                match node.keys.get_mut(key) {
+                    Some(existing) => existing.merge(child),
+                    None => {
+                        node.keys.insert(key.clone(), child);
+                    }
+                }
+            }
+        }
+        Schema::Ref(reference) => {
+            let id = reference.reference.as_str();
+            if !expanding.contains(&id)
+                && let Some(resolved) = registry.resolve(id)
+            {
+                expanding.push(id);
+                node.merge(walk(resolved, registry, expanding));
+                expanding.pop();
+            }
+        }
+        // Numbers, strings and `any` offer nothing beyond annotations
+        _ => {}
+    }
+
+    // `completions` annotations replace what the type would offer
+    if let Some(completions) = &annotations.completions {
+        node.values = completions.clone();
+    }
+    if let Some(additional) = &annotations.additional_completions {
+        push_unique(&mut node.values, additional.clone());
+    }
+    node
+}
+
+fn push_unique(target: &mut Vec<String>, values: Vec<String>) {
+    for value in values {
+        if !target.contains(&value) {
+            target.push(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(source: &str) -> Schema {
+        let yaml = quarto_yaml::parse(source).unwrap();
+        Schema::from_yaml(&yaml).unwrap()
+    }
+
+    #[test]
+    fn test_keys_values_and_descriptions() {
+        let schema = schema(
+            r#"
+object:
+  properties:
+    toc:
+      boolean:
+        description: Include a table of contents
+    engine:
+      enum: [knitr, jupyter]
+    theme:
+      maybeArrayOf: string
+    internal:
+      string:
+        hidden: true
+    format:
+      object:
+        properties:
+          html:
+            object:
+              properties:
+                code-fold:
+                  anyOf:
+                    - boolean
+                    - enum: [show]
+"#,
+        );
+        let model = completion_model(&schema, &SchemaRegistry::new());
+
+        assert_eq!(model.types, vec!["object"]);
+        assert_eq!(
+            model.keys.keys().collect::<Vec<_>>(),
+            vec!["engine", "format", "theme", "toc"]
+        );
+        let toc = model.at_path(&["toc"]).unwrap();
+        assert_eq!(
+            toc.description.as_deref(),
+            Some("Include a table of contents")
+        );
+        assert_eq!(toc.values, vec!["true", "false"]);
+
+        let theme = model.at_path(&["theme"]).unwrap();
+        assert_eq!(theme.types, vec!["string", "array"]);
+        assert!(theme.items.is_some());
+
+        let fold = model.at_path(&["format", "html", "code-fold"]).unwrap();
+        assert_eq!(fold.values, vec!["true", "false", "show"]);
+        assert_eq!(fold.types, vec!["boolean", "enum"]);
+    }
+
+    #[test]
+    fn test_recursive_references() {
+        let mut registry = SchemaRegistry::new();
+        registry.register(
+            "navitem".to_string(),
+            schema(
+                r#"
+object:
+  properties:
+    text: string
+    menu:
+      arrayOf:
+        ref: navitem
+"#,
+            ),
+        );
+        let model = completion_model(&schema("ref: navitem"), &registry);
+
+        let menu = model.at_path(&["menu"]).unwrap();
+        assert!(menu.items.as_ref().unwrap().keys.is_empty());
+        assert!(model.at_path(&["text"]).is_some());
+    }
+
+    #[test]
+    fn test_completion_annotations_and_json() {
+        let schema = schema(
+            r#"
+object:
+  properties:
+    lang:
+      string:
+        completions: [en, fr]
+"#,
+        );
+        let json = completion_model(&schema, &SchemaRegistry::new()).to_json();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "types": ["object"],
+                "keys": {
+                    "lang": { "types": ["string"], "values": ["en", "fr"] }
+                }
+            })
+        );
+    }
+}
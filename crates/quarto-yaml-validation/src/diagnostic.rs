@@ -196,6 +196,16 @@ impl ValidationDiagnostic {
         // Attach full SourceInfo for ariadne rendering
         if let Some(yaml_node) = &error.yaml_node {
             builder = builder.with_location(yaml_node.source_info.clone());
+            // The value is defined at an anchor; also show where it's used
+            if let Some((name, alias)) = &yaml_node.alias {
+                builder = builder.add_note_at(
+                    format!(
+                        "Defined with anchor `&{}` and used through this alias",
+                        name
+                    ),
+                    alias.clone(),
+                );
+            }
         }
 
         // Add human-readable details
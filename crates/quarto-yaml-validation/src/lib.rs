@@ -3,11 +3,13 @@
 // This crate provides schema-based validation for YAML content,
 // with support for Quarto's simplified JSON Schema subset.
 
+pub mod completion;
 pub mod diagnostic;
 pub mod error;
 pub mod schema;
 pub mod validator;
 
+pub use completion::{CompletionNode, completion_model};
 pub use diagnostic::{PathSegment, SourceRange, ValidationDiagnostic};
 pub use error::{ValidationError, ValidationResult};
 pub use schema::{Schema, SchemaRegistry, merge_object_schemas};
@@ -15,6 +15,19 @@
 //! Follows rust-analyzer's precedent of using owned data with reference counting
 //! for tree structures.
 //!
+//! ## Anchors and aliases
+//!
+//! Aliases (`*name`) and merge keys (`<<: *name`) are resolved while
+//! parsing. A node copied from an anchor keeps the source locations of its
+//! definition and records the alias it came through in
+//! [`YamlWithSourceInfo::alias`], so diagnostics can point at both.
+//!
+//! ## Writing
+//!
+//! [`YamlEditor`] changes values in existing YAML text (e.g. `_quarto.yml`)
+//! while keeping the formatting, comments and key order of everything it
+//! doesn't touch. [`emit_yaml`] writes new values in block style.
+//!
 //! ## Example
 //!
 //! ```rust,no_run
@@ -34,9 +47,11 @@
 
 mod error;
 mod parser;
+mod writer;
 mod yaml_with_source_info;
 
 pub use error::{Error, Result};
 pub use parser::{parse, parse_file, parse_with_parent};
 pub use quarto_source_map::SourceInfo; // Re-export from quarto-source-map
+pub use writer::{YamlEditor, emit_yaml};
 pub use yaml_with_source_info::{YamlHashEntry, YamlWithSourceInfo};
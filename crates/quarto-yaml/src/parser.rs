@@ -1,5 +1,7 @@
 //! YAML parser that builds YamlWithSourceInfo trees.
 
+use std::collections::HashMap;
+
 use crate::{Error, Result, SourceInfo, YamlHashEntry, YamlWithSourceInfo};
 use yaml_rust2::Yaml;
 use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
@@ -181,6 +183,9 @@ fn create_contiguous_span(start_info: &SourceInfo, end_info: &SourceInfo) -> Sou
     }
 }
 
+/// Key that merges the entries of other mappings into a mapping.
+const MERGE_KEY: &str = "<<";
+
 /// Builder that implements MarkedEventReceiver to construct YamlWithSourceInfo.
 struct YamlBuilder<'a> {
     /// The source text being parsed
@@ -194,6 +199,9 @@ struct YamlBuilder<'a> {
 
     /// The completed root node
     root: Option<YamlWithSourceInfo>,
+
+    /// Anchored nodes by anchor id, for resolving aliases
+    anchors: HashMap<usize, YamlWithSourceInfo>,
 }
 
 /// A node being constructed during parsing.
@@ -201,12 +209,14 @@ enum BuildNode {
     /// Building a sequence
     Sequence {
         start_marker: Marker,
+        anchor_id: usize,
         items: Vec<YamlWithSourceInfo>,
     },
 
     /// Building a mapping
     Mapping {
         start_marker: Marker,
+        anchor_id: usize,
         entries: Vec<(YamlWithSourceInfo, Option<YamlWithSourceInfo>)>,
     },
 }
@@ -218,9 +228,29 @@ impl<'a> YamlBuilder<'a> {
             parent,
             stack: Vec::new(),
             root: None,
+            anchors: HashMap::new(),
+        }
+    }
+
+    /// Remember a node defined with an anchor (`&name`); id 0 means none.
+    fn record_anchor(&mut self, anchor_id: usize, node: &YamlWithSourceInfo) {
+        if anchor_id != 0 {
+            self.anchors.insert(anchor_id, node.clone());
         }
     }
 
+    /// Find the alias (`*name`) at or after `marker`: its byte offset and
+    /// anchor name.
+    fn find_alias(&self, marker: &Marker) -> Option<(usize, String)> {
+        let rest = self.source.get(marker.index()..)?;
+        let star = rest.find('*')?;
+        let name: String = rest[star + 1..]
+            .chars()
+            .take_while(|c| !c.is_whitespace() && !matches!(c, ',' | '[' | ']' | '{' | '}'))
+            .collect();
+        Some((marker.index() + star, name))
+    }
+
     fn result(self) -> Result<YamlWithSourceInfo> {
         self.root.ok_or_else(|| Error::ParseError {
             message: "No YAML document found".into(),
@@ -395,7 +425,7 @@ impl<'a> MarkedEventReceiver for YamlBuilder<'a> {
             Event::DocumentStart => {}
             Event::DocumentEnd => {}
 
-            Event::Scalar(value, _style, _anchor_id, tag) => {
+            Event::Scalar(value, _style, anchor_id, tag) => {
                 // Capture tag information if present
                 let tag_info = tag.as_ref().map(|t| {
                     // The marker points to the start of the VALUE, not the tag
@@ -423,12 +453,14 @@ impl<'a> MarkedEventReceiver for YamlBuilder<'a> {
                 let yaml = parse_scalar_value(&value);
                 let node = YamlWithSourceInfo::new_scalar_with_tag(yaml, source_info, tag_info);
 
+                self.record_anchor(anchor_id, &node);
                 self.push_complete(node);
             }
 
-            Event::SequenceStart(_anchor_id, _tag) => {
+            Event::SequenceStart(anchor_id, _tag) => {
                 self.stack.push(BuildNode::Sequence {
                     start_marker: marker,
+                    anchor_id,
                     items: Vec::new(),
                 });
             }
@@ -438,6 +470,7 @@ impl<'a> MarkedEventReceiver for YamlBuilder<'a> {
 
                 if let BuildNode::Sequence {
                     start_marker,
+                    anchor_id,
                     items,
                 } = build_node
                 {
@@ -450,15 +483,17 @@ impl<'a> MarkedEventReceiver for YamlBuilder<'a> {
                     let yaml = Yaml::Array(yaml_items);
 
                     let node = YamlWithSourceInfo::new_array(yaml, source_info, items);
+                    self.record_anchor(anchor_id, &node);
                     self.push_complete(node);
                 } else {
                     panic!("Expected Sequence build node");
                 }
             }
 
-            Event::MappingStart(_anchor_id, _tag) => {
+            Event::MappingStart(anchor_id, _tag) => {
                 self.stack.push(BuildNode::Mapping {
                     start_marker: marker,
+                    anchor_id,
                     entries: Vec::new(),
                 });
             }
@@ -468,16 +503,23 @@ impl<'a> MarkedEventReceiver for YamlBuilder<'a> {
 
                 if let BuildNode::Mapping {
                     start_marker,
+                    anchor_id,
                     entries,
                 } = build_node
                 {
+                    let first_key_start = entries
+                        .first()
+                        .map(|(key, _)| key.source_info.start_offset());
+                    let entries = entries
+                        .into_iter()
+                        .map(|(key, value)| (key, value.expect("Mapping entry without value")))
+                        .collect();
+
                     // Build the hash entries
                     let mut hash_entries = Vec::new();
                     let mut yaml_pairs = Vec::new();
 
-                    for (key, value) in entries {
-                        let value = value.expect("Mapping entry without value");
-
+                    for (key, value) in resolve_merge_keys(entries) {
                         // Create YamlHashEntry
                         let key_span = key.source_info.clone();
                         let value_span = value.source_info.clone();
@@ -500,9 +542,9 @@ impl<'a> MarkedEventReceiver for YamlBuilder<'a> {
                     // Compute source_info for the entire object
                     // If we have entries, use the first key's start and the current marker's end
                     // Otherwise, use start_marker to current marker
-                    let source_info = if let Some(first_entry) = hash_entries.first() {
-                        // Get the start offset from the first key
-                        let first_key_start = first_entry.key.source_info.start_offset();
+                    // (merged entries are located at their anchor, so use the
+                    // first key written here)
+                    let source_info = if let Some(first_key_start) = first_key_start {
                         // Compute length from first key start to current marker
                         let len = marker.index().saturating_sub(first_key_start);
                         // Create SourceInfo starting from first key
@@ -517,27 +559,89 @@ impl<'a> MarkedEventReceiver for YamlBuilder<'a> {
                     let yaml = Yaml::Hash(yaml_pairs.into_iter().collect());
 
                     let node = YamlWithSourceInfo::new_hash(yaml, source_info, hash_entries);
+                    self.record_anchor(anchor_id, &node);
                     self.push_complete(node);
                 } else {
                     panic!("Expected Mapping build node");
                 }
             }
 
-            Event::Alias(_anchor_id) => {
-                // For now, we don't support aliases
-                // We could add support later by tracking anchors
-                let source_info = self.make_source_info(&marker, 0);
-                let node = YamlWithSourceInfo::new_scalar(Yaml::Null, source_info);
+            Event::Alias(anchor_id) => {
+                // A copy of the anchored node, keeping its source locations
+                // and recording where the alias is
+                let (offset, name) = self
+                    .find_alias(&marker)
+                    .unwrap_or((marker.index(), String::new()));
+                let alias_info = self.make_source_info_at_offset(offset, 1 + name.len());
+                let node = match self.anchors.get(&anchor_id) {
+                    Some(anchored) => {
+                        let mut node = anchored.clone();
+                        node.alias = Some((name, alias_info));
+                        node
+                    }
+                    None => YamlWithSourceInfo::new_scalar(Yaml::Null, alias_info),
+                };
                 self.push_complete(node);
             }
         }
     }
 }
 
+/// Replace `<<` merge keys by the entries of the mappings they name.
+///
+/// Keys written in the mapping itself win over merged keys, and in a list
+/// (`<<: [*a, *b]`) earlier mappings win over later ones. Merged values
+/// keep the location of their definition, and record the alias they were
+/// merged through.
+fn resolve_merge_keys(
+    entries: Vec<(YamlWithSourceInfo, YamlWithSourceInfo)>,
+) -> Vec<(YamlWithSourceInfo, YamlWithSourceInfo)> {
+    let is_merge = |key: &YamlWithSourceInfo| key.yaml.as_str() == Some(MERGE_KEY);
+    if !entries.iter().any(|(key, _)| is_merge(key)) {
+        return entries;
+    }
+
+    let mut seen: Vec<Yaml> = entries
+        .iter()
+        .filter(|(key, _)| !is_merge(key))
+        .map(|(key, _)| key.yaml.clone())
+        .collect();
+    let mut resolved = Vec::new();
+    for (key, value) in entries {
+        if !is_merge(&key) {
+            resolved.push((key, value));
+            continue;
+        }
+        let sources: Vec<&YamlWithSourceInfo> = match value.as_array() {
+            Some(items) if items.iter().all(YamlWithSourceInfo::is_hash) => items.iter().collect(),
+            _ if value.is_hash() => vec![&value],
+            // Not a mapping to merge: keep it as an ordinary entry
+            _ => {
+                resolved.push((key, value));
+                continue;
+            }
+        };
+        for source in sources {
+            for entry in source.as_hash().unwrap_or_default() {
+                if seen.contains(&entry.key.yaml) {
+                    continue;
+                }
+                seen.push(entry.key.yaml.clone());
+                let mut merged = entry.value.clone();
+                if merged.alias.is_none() {
+                    merged.alias = source.alias.clone();
+                }
+                resolved.push((entry.key.clone(), merged));
+            }
+        }
+    }
+    resolved
+}
+
 /// Parse a scalar string value into the appropriate Yaml type.
 ///
 /// This handles type inference: integers, floats, booleans, null, and strings.
-fn parse_scalar_value(value: &str) -> Yaml {
+pub(crate) fn parse_scalar_value(value: &str) -> Yaml {
     // Try to parse as integer
     if let Ok(i) = value.parse::<i64>() {
         return Yaml::Integer(i);
@@ -1406,4 +1510,70 @@ file: !path ./data.csv
         let file = yaml.get_hash_value("file").expect("file not found");
         assert_eq!(file.tag.as_ref().map(|(t, _)| t.as_str()), Some("path"));
     }
+
+    #[test]
+    fn test_alias_copies_anchored_node() {
+        let content = "base: &opts\n  toc: true\n  theme: cosmo\nother: *opts\n";
+        let yaml = parse(content).unwrap();
+
+        let other = yaml.get_hash_value("other").unwrap();
+        assert!(other.is_hash());
+        assert_eq!(other.yaml["theme"].as_str(), Some("cosmo"));
+        assert_eq!(yaml.yaml["other"]["toc"].as_bool(), Some(true));
+
+        // Values keep the location of their definition...
+        let theme = other.get_hash_value("theme").unwrap();
+        assert_eq!(
+            theme.source_info.start_offset(),
+            content.find("cosmo").unwrap()
+        );
+        // ...and the node records the alias it came through
+        let (name, at) = other.alias.as_ref().unwrap();
+        assert_eq!(name, "opts");
+        assert_eq!(at.start_offset(), content.find("*opts").unwrap());
+        assert_eq!(at.end_offset() - at.start_offset(), "*opts".len());
+        assert!(yaml.get_hash_value("base").unwrap().alias.is_none());
+    }
+
+    #[test]
+    fn test_alias_of_scalar_and_sequence() {
+        let yaml = parse("name: &n Quarto\nlist: &l [a, b]\ncopy: *n\nitems: *l\n").unwrap();
+        assert_eq!(yaml.yaml["copy"].as_str(), Some("Quarto"));
+        let items = yaml.get_hash_value("items").unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items.alias.as_ref().map(|(n, _)| n.as_str()), Some("l"));
+    }
+
+    #[test]
+    fn test_merge_key() {
+        let content =
+            "defaults: &d\n  toc: true\n  theme: cosmo\nhtml:\n  <<: *d\n  theme: darkly\n";
+        let yaml = parse(content).unwrap();
+
+        let html = yaml.get_hash_value("html").unwrap();
+        assert_eq!(html.len(), 2);
+        assert!(html.get_hash_value("<<").is_none());
+        assert_eq!(yaml.yaml["html"]["theme"].as_str(), Some("darkly"));
+        assert_eq!(yaml.yaml["html"]["toc"].as_bool(), Some(true));
+
+        let toc = html.get_hash_value("toc").unwrap();
+        assert_eq!(
+            toc.source_info.start_offset(),
+            content.find("true").unwrap()
+        );
+        assert_eq!(toc.alias.as_ref().map(|(n, _)| n.as_str()), Some("d"));
+        let theme = html.get_hash_value("theme").unwrap();
+        assert!(theme.alias.is_none());
+    }
+
+    #[test]
+    fn test_merge_key_list_precedence() {
+        let yaml =
+            parse("a: &a\n  x: 1\n  y: 1\nb: &b\n  y: 2\n  z: 2\nc:\n  <<: [*a, *b]\n  z: 3\n")
+                .unwrap();
+        let c = &yaml.yaml["c"];
+        assert_eq!(c["x"].as_i64(), Some(1));
+        assert_eq!(c["y"].as_i64(), Some(1));
+        assert_eq!(c["z"].as_i64(), Some(3));
+    }
 }
@@ -0,0 +1,735 @@
+//! Writing YAML: editing files in place and emitting new values.
+//!
+//! [`YamlEditor`] edits YAML text (typically `_quarto.yml`) by splicing
+//! only the parts that change, located through the source information of
+//! the parsed tree. Everything else (key order, comments, blank lines,
+//! quoting) is kept byte for byte:
+//!
+//! ```rust
+//! use quarto_yaml::YamlEditor;
+//! use yaml_rust2::Yaml;
+//!
+//! let mut editor = YamlEditor::new("project:\n  type: website # site\n").unwrap();
+//! editor.set(&["project", "output-dir"], &Yaml::String("docs".into())).unwrap();
+//! assert_eq!(
+//!     editor.as_str(),
+//!     "project:\n  type: website # site\n  output-dir: docs\n"
+//! );
+//! ```
+//!
+//! New values are written in block style by [`emit_yaml`], indented like
+//! their siblings. Edits are meant for block-style documents; flow-style
+//! (`{a: 1}`) mappings that need new keys are rewritten in block style.
+
+use yaml_rust2::Yaml;
+
+use crate::parser::parse_scalar_value;
+use crate::{Error, Result, YamlHashEntry, YamlWithSourceInfo, parse};
+
+/// Indentation added per nesting level in emitted YAML.
+const INDENT: usize = 2;
+
+/// Emit a value as a block-style YAML document.
+pub fn emit_yaml(yaml: &Yaml) -> String {
+    let mut out = if is_block(yaml) {
+        emit_block(yaml, 0)
+    } else {
+        emit_inline(yaml)
+    };
+    out.push('\n');
+    out
+}
+
+/// YAML text being edited, with the formatting of untouched parts kept.
+#[derive(Debug, Clone)]
+pub struct YamlEditor {
+    source: String,
+}
+
+/// Where a path leads in the document.
+enum Location<'a> {
+    /// The entry at the full path
+    Found(&'a YamlHashEntry),
+    /// The path stops at `depth`: `parent` (the mapping at `path[..depth]`,
+    /// `None` for an empty document) has no key `path[depth]`
+    Missing {
+        parent: Option<&'a YamlWithSourceInfo>,
+        parent_entry: Option<&'a YamlHashEntry>,
+        depth: usize,
+    },
+}
+
+impl YamlEditor {
+    /// Start editing `source`, which must be valid YAML.
+    pub fn new(source: impl Into<String>) -> Result<Self> {
+        let editor = Self {
+            source: source.into(),
+        };
+        editor.tree()?;
+        Ok(editor)
+    }
+
+    /// The current text.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// The current text, consuming the editor.
+    pub fn into_string(self) -> String {
+        self.source
+    }
+
+    /// Set the value at `path` (a sequence of mapping keys), replacing the
+    /// current value or adding the key (and any missing parent mappings).
+    ///
+    /// A scalar replacing a scalar keeps the rest of its line, including
+    /// any comment.
+    pub fn set(&mut self, path: &[&str], value: &Yaml) -> Result<()> {
+        let tree = self.tree()?;
+        let edit = match locate(tree.as_ref(), path)? {
+            Location::Found(entry) => self.replace_value(entry, value),
+            Location::Missing {
+                parent,
+                parent_entry,
+                depth,
+            } => {
+                let nested = nest(&path[depth + 1..], value.clone());
+                self.insert_entry(parent, parent_entry, path[depth], &nested)?
+            }
+        };
+        self.apply(edit)
+    }
+
+    /// Remove the entry at `path`. Returns whether it existed.
+    pub fn remove(&mut self, path: &[&str]) -> Result<bool> {
+        let tree = self.tree()?;
+        let Location::Found(entry) = locate(tree.as_ref(), path)? else {
+            return Ok(false);
+        };
+        let key_start = self.byte_offset(entry.key.source_info.start_offset());
+        let line_start = line_start(&self.source, key_start);
+        if !self.source[line_start..key_start].trim().is_empty() {
+            return Err(structure_error(format!(
+                "`{}` shares its line with other YAML and can't be removed",
+                path.join(".")
+            )));
+        }
+        let end = self.entry_end(key_start);
+        let end = line_end_with_newline(&self.source, end);
+        self.apply(vec![(line_start, end, String::new())])?;
+        Ok(true)
+    }
+
+    /// Append `value` to the sequence at `path`.
+    ///
+    /// A missing or null value becomes a one-item sequence, and a scalar
+    /// becomes a sequence of itself and `value`.
+    pub fn push(&mut self, path: &[&str], value: &Yaml) -> Result<()> {
+        let tree = self.tree()?;
+        let entry = match locate(tree.as_ref(), path)? {
+            Location::Found(entry) => entry,
+            Location::Missing { .. } => return self.set(path, &Yaml::Array(vec![value.clone()])),
+        };
+        let items = match &entry.value.yaml {
+            Yaml::Array(items) => items,
+            Yaml::Null => return self.set(path, &Yaml::Array(vec![value.clone()])),
+            Yaml::Hash(_) => {
+                return Err(structure_error(format!(
+                    "`{}` is a mapping, not a sequence",
+                    path.join(".")
+                )));
+            }
+            scalar => return self.set(path, &Yaml::Array(vec![scalar.clone(), value.clone()])),
+        };
+
+        let key_start = self.byte_offset(entry.key.source_info.start_offset());
+        let end = self.entry_end(key_start);
+        let inline = self.inline_value(key_start);
+        if inline.starts_with('[') {
+            // Flow sequence: add before the closing bracket
+            if is_block(value) {
+                let mut all = items.clone();
+                all.push(value.clone());
+                return self.set(path, &Yaml::Array(all));
+            }
+            let close = self.source[..end].rfind(']').ok_or_else(|| {
+                structure_error(format!("unterminated sequence at `{}`", path.join(".")))
+            })?;
+            let separator = if items.is_empty() { "" } else { ", " };
+            return self.apply(vec![(
+                close,
+                close,
+                format!("{}{}", separator, emit_inline(value)),
+            )]);
+        }
+
+        let Some(last) = entry.value.as_array().and_then(|items| items.last()) else {
+            return self.set(path, &Yaml::Array(vec![value.clone()]));
+        };
+        // Block sequence: add an item after the last one, at its indentation
+        let last_start = self.byte_offset(last.source_info.start_offset());
+        let last_line = line_start(&self.source, last_start);
+        let dash_column = self.source[last_line..last_start]
+            .rfind('-')
+            .unwrap_or_else(|| indentation(&self.source[last_line..]));
+        let item = emit_item(value, dash_column);
+        self.apply(vec![(end, end, format!("\n{}", item))])
+    }
+
+    fn tree(&self) -> Result<Option<YamlWithSourceInfo>> {
+        if self.source.lines().all(|line| !is_content(line)) {
+            return Ok(None);
+        }
+        parse(&self.source).map(Some)
+    }
+
+    /// Apply non-overlapping replacements `(start, end, text)`, checking
+    /// that the result is still valid YAML.
+    fn apply(&mut self, mut edits: Vec<(usize, usize, String)>) -> Result<()> {
+        // Last first, so earlier offsets stay valid
+        edits.sort_by_key(|(start, end, _)| std::cmp::Reverse((*start, *end)));
+        let mut source = self.source.clone();
+        for (start, end, text) in edits {
+            source.replace_range(start..end, &text);
+        }
+        parse(&source)?;
+        self.source = source;
+        Ok(())
+    }
+
+    /// Edits replacing the value of `entry` with `value`.
+    fn replace_value(&self, entry: &YamlHashEntry, value: &Yaml) -> Vec<(usize, usize, String)> {
+        let key_start = self.byte_offset(entry.key.source_info.start_offset());
+        let key_column = key_start - line_start(&self.source, key_start);
+        let colon = self.colon_after_key(key_start);
+        let line_end = line_end(&self.source, key_start);
+        let end = self.entry_end(key_start);
+        let inline = self.inline_value(key_start);
+
+        let block_value = || format!("\n{}", emit_block(value, key_column + INDENT));
+        if inline.is_empty() || inline.starts_with('#') {
+            // The old value (if any) is on the following lines; keep the
+            // key line and its comment
+            if is_block(value) {
+                vec![(line_end, end, block_value())]
+            } else {
+                vec![
+                    (colon, colon, format!(" {}", emit_inline(value))),
+                    (line_end, end, String::new()),
+                ]
+            }
+        } else if inline.starts_with(['|', '>', '[', '{']) || is_block(value) {
+            let text = if is_block(value) {
+                block_value()
+            } else {
+                format!(" {}", emit_inline(value))
+            };
+            vec![(colon, end, text)]
+        } else {
+            // A scalar on the key line: replace just its text
+            let start = colon
+                + (self.source[colon..line_end].len()
+                    - self.source[colon..line_end].trim_start().len());
+            let token_end = scalar_end(&self.source, start, line_end);
+            vec![(start, token_end, emit_inline(value))]
+        }
+    }
+
+    /// Edits adding `key: value` to the mapping `parent`.
+    fn insert_entry(
+        &self,
+        parent: Option<&YamlWithSourceInfo>,
+        parent_entry: Option<&YamlHashEntry>,
+        key: &str,
+        value: &Yaml,
+    ) -> Result<Vec<(usize, usize, String)>> {
+        let entries = parent
+            .and_then(YamlWithSourceInfo::as_hash)
+            .unwrap_or_default();
+        let Some(last) = entries.last() else {
+            return Ok(match parent_entry {
+                // An empty (`{}` or null) value: replace it with the new mapping
+                Some(entry) => self.replace_value(entry, &nest(&[key], value.clone())),
+                // An empty document: append
+                None => {
+                    let separator = if self.source.is_empty() || self.source.ends_with('\n') {
+                        ""
+                    } else {
+                        "\n"
+                    };
+                    let text = emit_block(&nest(&[key], value.clone()), 0);
+                    let end = self.source.len();
+                    vec![(end, end, format!("{}{}\n", separator, text))]
+                }
+            });
+        };
+
+        let first_start = self.byte_offset(entries[0].key.source_info.start_offset());
+        let flow = self.source[..first_start].trim_end().ends_with('{');
+        if flow {
+            let Some(entry) = parent_entry else {
+                return Err(structure_error(
+                    "can't add keys to a flow-style document".to_string(),
+                ));
+            };
+            let mut all = parent.map_or(Yaml::Null, |p| p.yaml.clone());
+            if let Yaml::Hash(hash) = &mut all {
+                hash.insert(Yaml::String(key.to_string()), value.clone());
+            }
+            return Ok(self.replace_value(entry, &all));
+        }
+
+        let column = first_start - line_start(&self.source, first_start);
+        let last_start = self.byte_offset(last.key.source_info.start_offset());
+        let end = self.entry_end(last_start);
+        let text = emit_block(&nest(&[key], value.clone()), column);
+        Ok(vec![(end, end, format!("\n{}", text))])
+    }
+
+    /// End (before the newline) of the last line belonging to the entry
+    /// whose key starts at `key_start`: its key line and all following
+    /// lines indented more (or list items at the key's indentation).
+    /// Trailing blank and comment lines are not included.
+    fn entry_end(&self, key_start: usize) -> usize {
+        let key_column = key_start - line_start(&self.source, key_start);
+        let mut end = line_end(&self.source, key_start);
+        let mut position = end;
+        while position < self.source.len() {
+            let next = position + 1;
+            let next_end = line_end(&self.source, next);
+            let line = &self.source[next..next_end];
+            if is_content(line) {
+                let column = indentation(line);
+                let item = line.trim_start().starts_with("- ") || line.trim() == "-";
+                if column > key_column || (column == key_column && item) {
+                    end = next_end;
+                } else {
+                    break;
+                }
+            }
+            position = next_end;
+        }
+        end
+    }
+
+    /// Offset just after the `:` following the key at `key_start`.
+    fn colon_after_key(&self, key_start: usize) -> usize {
+        let line_end = line_end(&self.source, key_start);
+        let key_end = scalar_end(&self.source, key_start, line_end);
+        self.source[key_end..line_end]
+            .find(':')
+            .map_or(key_end, |i| key_end + i + 1)
+    }
+
+    /// The text after the key's colon, on the key line, trimmed.
+    fn inline_value(&self, key_start: usize) -> &str {
+        let colon = self.colon_after_key(key_start);
+        self.source[colon..line_end(&self.source, key_start)].trim()
+    }
+
+    /// Byte offset of a character index (source offsets count characters).
+    fn byte_offset(&self, char_index: usize) -> usize {
+        self.source
+            .char_indices()
+            .nth(char_index)
+            .map_or(self.source.len(), |(byte, _)| byte)
+    }
+}
+
+/// Follow `path` through the mappings of `tree`.
+fn locate<'a>(tree: Option<&'a YamlWithSourceInfo>, path: &[&str]) -> Result<Location<'a>> {
+    let Some(mut node) = tree else {
+        return Ok(Location::Missing {
+            parent: None,
+            parent_entry: None,
+            depth: 0,
+        });
+    };
+    let mut node_entry: Option<&YamlHashEntry> = None;
+    for (depth, key) in path.iter().enumerate() {
+        if node.yaml.is_null() {
+            return Ok(Location::Missing {
+                parent: None,
+                parent_entry: node_entry,
+                depth,
+            });
+        }
+        let Some(entries) = node.as_hash() else {
+            return Err(structure_error(format!(
+                "`{}` is not a mapping",
+                path[..depth].join(".")
+            )));
+        };
+        match entries
+            .iter()
+            .find(|entry| entry.key.yaml.as_str() == Some(key))
+        {
+            Some(entry) if depth + 1 == path.len() => return Ok(Location::Found(entry)),
+            Some(entry) => {
+                node = &entry.value;
+                node_entry = Some(entry);
+            }
+            None => {
+                return Ok(Location::Missing {
+                    parent: Some(node),
+                    parent_entry: node_entry,
+                    depth,
+                });
+            }
+        }
+    }
+    Err(structure_error("empty path".to_string()))
+}
+
+/// `value` nested in mappings under `keys`.
+fn nest(keys: &[&str], value: Yaml) -> Yaml {
+    keys.iter().rev().fold(value, |value, key| {
+        let mut hash = yaml_rust2::yaml::Hash::new();
+        hash.insert(Yaml::String(key.to_string()), value);
+        Yaml::Hash(hash)
+    })
+}
+
+/// Whether a value is written on lines of its own (a non-empty collection).
+fn is_block(yaml: &Yaml) -> bool {
+    match yaml {
+        Yaml::Array(items) => !items.is_empty(),
+        Yaml::Hash(hash) => !hash.is_empty(),
+        _ => false,
+    }
+}
+
+/// A collection in block style, each line indented by `indent`; no
+/// trailing newline.
+fn emit_block(yaml: &Yaml, indent: usize) -> String {
+    let pad = " ".repeat(indent);
+    let lines: Vec<String> = match yaml {
+        Yaml::Hash(hash) => hash
+            .iter()
+            .map(|(key, value)| {
+                let key = format!("{}{}:", pad, emit_inline(key));
+                if is_block(value) {
+                    format!("{}\n{}", key, emit_block(value, indent + INDENT))
+                } else {
+                    format!("{} {}", key, emit_inline(value))
+                }
+            })
+            .collect(),
+        Yaml::Array(items) => items.iter().map(|item| emit_item(item, indent)).collect(),
+        scalar => vec![format!("{}{}", pad, emit_inline(scalar))],
+    };
+    lines.join("\n")
+}
+
+/// A sequence item (`- value`) with its dash at column `indent`.
+fn emit_item(item: &Yaml, indent: usize) -> String {
+    let pad = " ".repeat(indent);
+    if is_block(item) {
+        // The first line of the nested block goes after the dash
+        let nested = emit_block(item, indent + INDENT);
+        format!("{}- {}", pad, &nested[indent + INDENT..])
+    } else {
+        format!("{}- {}", pad, emit_inline(item))
+    }
+}
+
+/// A value written on one line: a scalar, or an empty collection.
+fn emit_inline(yaml: &Yaml) -> String {
+    match yaml {
+        Yaml::String(s) => emit_string(s),
+        Yaml::Integer(i) => i.to_string(),
+        Yaml::Real(r) => r.clone(),
+        Yaml::Boolean(b) => b.to_string(),
+        Yaml::Array(items) if items.is_empty() => "[]".to_string(),
+        Yaml::Hash(hash) if hash.is_empty() => "{}".to_string(),
+        Yaml::Array(items) => format!(
+            "[{}]",
+            items.iter().map(emit_inline).collect::<Vec<_>>().join(", ")
+        ),
+        Yaml::Hash(hash) => format!(
+            "{{{}}}",
+            hash.iter()
+                .map(|(key, value)| format!("{}: {}", emit_inline(key), emit_inline(value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Yaml::Null | Yaml::Alias(_) | Yaml::BadValue => "null".to_string(),
+    }
+}
+
+/// A string, plain when that reads back as the same string, else quoted.
+fn emit_string(s: &str) -> String {
+    let plain = !s.is_empty()
+        && s.trim() == s
+        && !s.starts_with([
+            '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%',
+            '@', '`',
+        ])
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.ends_with(':')
+        && !s.contains(['\n', '\t'])
+        && matches!(parse_scalar_value(s), Yaml::String(_));
+    if plain {
+        return s.to_string();
+    }
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// End of the scalar starting at `start` (quoted, or plain up to a `:`
+/// separator, a comment or the end of the line).
+fn scalar_end(source: &str, start: usize, line_end: usize) -> usize {
+    let text = &source[start..line_end];
+    let mut chars = text.char_indices();
+    match chars.next() {
+        Some((_, '"')) => {
+            let mut escaped = false;
+            for (i, c) in chars {
+                match c {
+                    '\\' if !escaped => escaped = true,
+                    '"' if !escaped => return start + i + 1,
+                    _ => escaped = false,
+                }
+            }
+            line_end
+        }
+        Some((_, '\'')) => {
+            let mut previous_quote = false;
+            for (i, c) in chars {
+                if c == '\'' {
+                    if previous_quote {
+                        previous_quote = false;
+                        continue;
+                    }
+                    // A single quote ends the scalar unless another follows
+                    if text[i + 1..].starts_with('\'') {
+                        previous_quote = true;
+                        continue;
+                    }
+                    return start + i + 1;
+                }
+            }
+            line_end
+        }
+        _ => {
+            let mut end = text.len();
+            if let Some(i) = text.find(" #") {
+                end = end.min(i);
+            }
+            if let Some(i) = text.find(": ") {
+                end = end.min(i);
+            }
+            if text[..end].ends_with(':') {
+                end -= 1;
+            }
+            start + text[..end].trim_end().len()
+        }
+    }
+}
+
+fn structure_error(message: String) -> Error {
+    Error::InvalidStructure {
+        message,
+        location: None,
+    }
+}
+
+fn is_content(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#')
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// End of the line containing `offset`, before its newline.
+fn line_end(source: &str, offset: usize) -> usize {
+    source[offset..]
+        .find('\n')
+        .map_or(source.len(), |i| offset + i)
+}
+
+/// `line_end` past the newline, if there is one.
+fn line_end_with_newline(source: &str, line_end: usize) -> usize {
+    if source[line_end..].starts_with('\n') {
+        line_end + 1
+    } else {
+        line_end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+# Site configuration
+project:
+  type: website
+  output-dir: _site # build here
+
+website:
+  title: \"My Site\"
+  navbar:
+    left:
+      - index.qmd
+      - about.qmd
+
+format:
+  html:
+    theme: cosmo
+";
+
+    fn string(s: &str) -> Yaml {
+        Yaml::String(s.to_string())
+    }
+
+    fn edit(f: impl FnOnce(&mut YamlEditor)) -> String {
+        let mut editor = YamlEditor::new(CONFIG).unwrap();
+        f(&mut editor);
+        editor.into_string()
+    }
+
+    #[test]
+    fn test_replace_scalar_keeps_comment() {
+        let result = edit(|e| e.set(&["project", "output-dir"], &string("docs")).unwrap());
+        assert_eq!(
+            result,
+            CONFIG.replace("output-dir: _site # build", "output-dir: docs # build")
+        );
+
+        let result = edit(|e| e.set(&["website", "title"], &string("Title: Two")).unwrap());
+        assert_eq!(
+            result,
+            CONFIG.replace("title: \"My Site\"", "title: \"Title: Two\"")
+        );
+    }
+
+    #[test]
+    fn test_add_keys() {
+        let result = edit(|e| {
+            e.set(&["format", "html", "toc"], &Yaml::Boolean(true))
+                .unwrap()
+        });
+        assert_eq!(
+            result,
+            CONFIG.replace("theme: cosmo\n", "theme: cosmo\n    toc: true\n")
+        );
+
+        // Missing parents are created, after the last entry of the mapping
+        let result = edit(|e| e.set(&["execute", "freeze"], &string("auto")).unwrap());
+        assert_eq!(result, format!("{}execute:\n  freeze: auto\n", CONFIG));
+
+        let result = edit(|e| {
+            e.set(&["project", "render"], &Yaml::Array(vec![string("*.qmd")]))
+                .unwrap()
+        });
+        assert_eq!(
+            result,
+            CONFIG.replace(
+                "# build here\n",
+                "# build here\n  render:\n    - \"*.qmd\"\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_replace_block_value() {
+        let result = edit(|e| e.set(&["website", "navbar"], &string("none")).unwrap());
+        assert_eq!(
+            result,
+            CONFIG.replace(
+                "navbar:\n    left:\n      - index.qmd\n      - about.qmd\n",
+                "navbar: none\n"
+            )
+        );
+
+        let mut hash = yaml_rust2::yaml::Hash::new();
+        hash.insert(string("theme"), string("darkly"));
+        let result = edit(|e| e.set(&["format", "html"], &Yaml::Hash(hash)).unwrap());
+        assert_eq!(result, CONFIG.replace("cosmo", "darkly"));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut editor = YamlEditor::new(CONFIG).unwrap();
+        assert!(editor.remove(&["website", "navbar"]).unwrap());
+        assert!(!editor.remove(&["website", "sidebar"]).unwrap());
+        assert!(editor.remove(&["project", "output-dir"]).unwrap());
+        assert_eq!(
+            editor.as_str(),
+            CONFIG
+                .replace(
+                    "  navbar:\n    left:\n      - index.qmd\n      - about.qmd\n",
+                    ""
+                )
+                .replace("  output-dir: _site # build here\n", "")
+        );
+    }
+
+    #[test]
+    fn test_push() {
+        let result = edit(|e| {
+            e.push(&["website", "navbar", "left"], &string("blog.qmd"))
+                .unwrap()
+        });
+        assert_eq!(
+            result,
+            CONFIG.replace("- about.qmd\n", "- about.qmd\n      - blog.qmd\n")
+        );
+
+        let mut editor = YamlEditor::new("resources: [a.csv]\nother: 1\n").unwrap();
+        editor.push(&["resources"], &string("b.csv")).unwrap();
+        editor.push(&["files"], &string("c.csv")).unwrap();
+        editor.push(&["other"], &Yaml::Integer(2)).unwrap();
+        assert_eq!(
+            editor.as_str(),
+            "resources: [a.csv, b.csv]\nother:\n  - 1\n  - 2\nfiles:\n  - c.csv\n"
+        );
+    }
+
+    #[test]
+    fn test_empty_document() {
+        let mut editor = YamlEditor::new("# nothing yet\n").unwrap();
+        editor
+            .set(&["project", "type"], &string("website"))
+            .unwrap();
+        assert_eq!(
+            editor.as_str(),
+            "# nothing yet\nproject:\n  type: website\n"
+        );
+    }
+
+    #[test]
+    fn test_emit_yaml() {
+        let mut inner = yaml_rust2::yaml::Hash::new();
+        inner.insert(string("text"), string("Home"));
+        inner.insert(string("href"), string("index.qmd"));
+        let mut root = yaml_rust2::yaml::Hash::new();
+        root.insert(string("title"), string("yes"));
+        root.insert(string("empty"), string(""));
+        root.insert(string("items"), Yaml::Array(vec![Yaml::Hash(inner)]));
+        root.insert(string("none"), Yaml::Array(vec![]));
+        assert_eq!(
+            emit_yaml(&Yaml::Hash(root)),
+            "title: \"yes\"\nempty: \"\"\nitems:\n  - text: Home\n    href: index.qmd\nnone: []\n"
+        );
+    }
+}
@@ -49,6 +49,14 @@ pub struct YamlWithSourceInfo {
     /// for tagged strings and enable error reporting on tags.
     pub tag: Option<(String, SourceInfo)>,
 
+    /// Alias this node was produced by, if any.
+    ///
+    /// For a node copied from an anchor by an alias (`*name`, or through a
+    /// `<<: *name` merge key), contains the anchor name and the source
+    /// location of the alias. `source_info` (and that of all children) stays
+    /// the location of the anchored node, where the value is defined.
+    pub alias: Option<(String, SourceInfo)>,
+
     /// Source-tracked children (parallel structure).
     ///
     /// This mirrors the structure of `yaml` but includes source location
@@ -103,6 +111,7 @@ impl YamlWithSourceInfo {
             yaml,
             source_info,
             tag: None,
+            alias: None,
             children: Children::None,
         }
     }
@@ -117,6 +126,7 @@ impl YamlWithSourceInfo {
             yaml,
             source_info,
             tag,
+            alias: None,
             children: Children::None,
         }
     }
@@ -131,6 +141,7 @@ impl YamlWithSourceInfo {
             yaml,
             source_info,
             tag: None,
+            alias: None,
             children: Children::Array(children),
         }
     }
@@ -141,6 +152,7 @@ impl YamlWithSourceInfo {
             yaml,
             source_info,
             tag: None,
+            alias: None,
             children: Children::Hash(entries),
         }
     }
@@ -1,8 +1,171 @@
-//! Add command implementation
+//! Add command implementation.
+//!
+//! `quarto add owner/repo[@ref]` installs the extensions of a GitHub
+//! repository into the `_extensions` directory of the current project (or
+//! of the current directory outside projects), and records them in the
+//! extensions lockfile. Extensions can run code when documents render, so
+//! the user is asked to trust them first unless `--no-prompt` is given.
+//!
+//! See [`quarto_core::extension`] for the layout of `_extensions`.
 
-use anyhow::Result;
-use quarto_core::QuartoError;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("add".to_string()).into())
+use anyhow::{Context, Result};
+use quarto_core::ProjectContext;
+use quarto_core::extension::install::{self, ExtensionSource, FetchError, FetchedExtensions};
+use quarto_core::extension::lock::ExtensionsLock;
+use quarto_core::extension::{EXTENSIONS_DIR, ExtensionId};
+use quarto_system_runtime::NativeRuntime;
+use tracing::info;
+
+use super::render::DiagnosticsReported;
+
+/// Arguments for the add command
+#[derive(Debug)]
+pub struct AddArgs {
+    /// Extension to add, `owner/repo[@ref]`
+    pub extension: String,
+    /// Install without asking for confirmation
+    pub no_prompt: bool,
+}
+
+/// Execute the add command
+pub fn execute(args: AddArgs) -> Result<()> {
+    let runtime = NativeRuntime::new();
+    let source = ExtensionSource::parse(&args.extension).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid extension '{}' (expected owner/repo or owner/repo@ref)",
+            args.extension
+        )
+    })?;
+    let dir = extensions_dir()?;
+    let mut lock = ExtensionsLock::load(&dir, &runtime)?;
+
+    info!("Downloading {}", source);
+    let fetched = install::resolve_commit(&source, &runtime)
+        .and_then(|commit| install::fetch(&source, &commit, &runtime))
+        .map_err(report_fetch_error)?;
+
+    for extension in &fetched.extensions {
+        let installed = dir.join(EXTENSIONS_DIR).join(extension.id.relative_dir());
+        if installed.exists() && lock.get(&extension.id).is_none() {
+            info!("{} is already installed and will be replaced", extension.id);
+        }
+    }
+    if !args.no_prompt && !confirm_trust(&fetched)? {
+        info!("Nothing was installed");
+        return Ok(());
+    }
+
+    install::install(&dir, &fetched, &mut lock, &runtime)?;
+    lock.save(&dir, &runtime)?;
+    for extension in &fetched.extensions {
+        info!(
+            "Added {}{}",
+            extension.id,
+            version_suffix(extension.manifest.version.as_deref())
+        );
+    }
+    Ok(())
+}
+
+/// Directory whose `_extensions` extensions are managed in: the project
+/// containing the current directory, or the current directory.
+pub(crate) fn extensions_dir() -> Result<PathBuf> {
+    let runtime = NativeRuntime::new();
+    let cwd = std::env::current_dir().context("Failed to get current directory")?;
+    let project =
+        ProjectContext::discover(&cwd, &runtime).context("Failed to discover project context")?;
+    Ok(project.dir)
+}
+
+/// Print why fetching failed and return the error to exit with.
+pub(crate) fn report_fetch_error(error: FetchError) -> anyhow::Error {
+    eprintln!("{}", error.to_diagnostic().to_text(None));
+    DiagnosticsReported.into()
+}
+
+/// Describe what is about to be installed and ask whether to trust it.
+///
+/// Fails without a terminal to ask on.
+pub(crate) fn confirm_trust(fetched: &FetchedExtensions) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Can't ask whether to trust {} without a terminal (use --no-prompt to install anyway)",
+            fetched.source
+        );
+    }
+    let mut stderr = std::io::stderr();
+    writeln!(stderr, "{}:", fetched.source)?;
+    for extension in &fetched.extensions {
+        let manifest = &extension.manifest;
+        writeln!(
+            stderr,
+            "  {}{}{}",
+            manifest.title.as_deref().unwrap_or(&extension.id.name),
+            version_suffix(manifest.version.as_deref()),
+            manifest
+                .author
+                .as_deref()
+                .map(|author| format!(" by {}", author))
+                .unwrap_or_default()
+        )?;
+        let kinds = manifest.contribution_kinds();
+        if !kinds.is_empty() {
+            writeln!(stderr, "    contributes {}", kinds.join(", "))?;
+        }
+    }
+    writeln!(
+        stderr,
+        "Extensions may run code when documents are rendered."
+    )?;
+    write!(
+        stderr,
+        "Do you trust the authors of {}? [y/N] ",
+        fetched.source.repository()
+    )?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Find installed extensions named by `target`: an extension id (`owner/name`
+/// or just `name`), or a source repository (`owner/repo`) in the lockfile.
+pub(crate) fn find_installed(
+    dir: &Path,
+    target: &str,
+    lock: &ExtensionsLock,
+) -> Result<Vec<ExtensionId>> {
+    let runtime = NativeRuntime::new();
+    let installed = quarto_core::extension::discover_extensions(dir, &runtime)?;
+    let by_id: Vec<_> = installed
+        .iter()
+        .filter(|e| e.id.to_string() == target || e.id.name == target)
+        .map(|e| e.id.clone())
+        .collect();
+    match by_id.len() {
+        1 => return Ok(by_id),
+        0 => {}
+        _ => anyhow::bail!(
+            "'{}' is ambiguous, name one of: {}",
+            target,
+            by_id
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+    let by_source = lock.installed_from(target.split('@').next().unwrap_or(target));
+    if by_source.is_empty() {
+        anyhow::bail!("No extension '{}' is installed", target);
+    }
+    Ok(by_source)
+}
+
+fn version_suffix(version: Option<&str>) -> String {
+    version.map(|v| format!(" {}", v)).unwrap_or_default()
 }
@@ -0,0 +1,240 @@
+//! Implementations of the functions in the [registry](super::registry).
+//!
+//! Each takes the JSON parameters of its call and returns its JSON result.
+//! Problems with the input (a document that doesn't parse, an invalid
+//! option) are part of the result, as diagnostics; errors are for calls
+//! that can't be answered at all.
+
+use std::path::PathBuf;
+
+use quarto_citeproc::{Citation, CitationItem, Processor, Reference};
+use quarto_config::SourceInfo;
+use quarto_error_reporting::{DiagnosticMessage, DiagnosticMessageBuilder};
+use quarto_sass::{ThemeConfig, ThemeContext, ThemeSpec, resolve_theme_spec};
+use quarto_source_map::SourceContext;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::registry::{CallError, params};
+
+/// Parameters of functions reading a source: its text, or a file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SourceParams {
+    text: Option<String>,
+    path: Option<String>,
+}
+
+impl SourceParams {
+    /// The source text, and the name diagnostics refer to it by.
+    fn read(self, default_name: &str) -> Result<(String, String), CallError> {
+        match (self.text, self.path) {
+            (Some(text), path) => Ok((text, path.unwrap_or_else(|| default_name.to_string()))),
+            (None, Some(path)) => match std::fs::read_to_string(&path) {
+                Ok(text) => Ok((text, path)),
+                Err(e) => Err(CallError::failed(format!("Failed to read {}: {}", path, e))),
+            },
+            (None, None) => Err(CallError::invalid_params(
+                "Either `text` or `path` is required",
+            )),
+        }
+    }
+}
+
+fn diagnostics_json(diagnostics: &[DiagnosticMessage]) -> Value {
+    diagnostics.iter().map(DiagnosticMessage::to_json).collect()
+}
+
+/// `document/parse`
+pub fn parse_document(value: Value) -> Result<Value, CallError> {
+    let (text, name) = params::<SourceParams>(value)?.read("<input>")?;
+    let parsed = pampa::readers::qmd::read(
+        text.as_bytes(),
+        false,
+        &name,
+        &mut std::io::sink(),
+        true,
+        None,
+    );
+    let (doc, context, warnings) = match parsed {
+        Ok(parsed) => parsed,
+        Err(diagnostics) => return Ok(json!({ "diagnostics": diagnostics_json(&diagnostics) })),
+    };
+
+    let mut ast = Vec::new();
+    pampa::writers::json::write(&doc, &context, &mut ast)
+        .map_err(|_| CallError::failed("Failed to write the document as JSON"))?;
+    let ast: Value = serde_json::from_slice(&ast).map_err(|e| CallError::failed(e.to_string()))?;
+    Ok(json!({ "ast": ast, "diagnostics": diagnostics_json(&warnings) }))
+}
+
+/// `config/validate`
+pub fn validate_config(value: Value) -> Result<Value, CallError> {
+    let (text, name) = params::<SourceParams>(value)?.read("_quarto.yml")?;
+    let mut source_context = SourceContext::new();
+    let file_id = source_context.add_file(name, Some(text.clone()));
+    let parent = SourceInfo::original(file_id, 0, text.len());
+
+    let mut diagnostics = Vec::new();
+    match quarto_config::config_value_from_source(&text, parent, &mut diagnostics) {
+        Ok(config) => {
+            diagnostics.extend(quarto_config::validate_config(&config, &source_context));
+        }
+        Err(e) => diagnostics.push(
+            DiagnosticMessageBuilder::error("YAML Syntax Error")
+                .with_code("Q-1-1")
+                .problem(e.to_string())
+                .build(),
+        ),
+    }
+    Ok(json!({
+        "valid": diagnostics.is_empty(),
+        "diagnostics": diagnostics_json(&diagnostics),
+    }))
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeParams {
+    theme: Value,
+    dir: Option<PathBuf>,
+    #[serde(default)]
+    css: bool,
+}
+
+/// `theme/resolve`
+pub fn resolve_theme(value: Value) -> Result<Value, CallError> {
+    let params = params::<ThemeParams>(value)?;
+    // JSON is YAML, so the theme is read as it would be from front matter
+    let yaml = json!({ "format": { "html": { "theme": params.theme } } }).to_string();
+    let config = quarto_config::config_value_from_source(&yaml, SourceInfo::default(), &mut vec![])
+        .map_err(|e| CallError::invalid_params(e.to_string()))?;
+    let config = ThemeConfig::from_config_value(&config)
+        .map_err(|e| CallError::invalid_params(e.to_string()))?;
+
+    let dir = match params.dir {
+        Some(dir) => dir,
+        None => std::env::current_dir().map_err(|e| CallError::failed(e.to_string()))?,
+    };
+    let context = ThemeContext::native(dir);
+    let mut result = json!({
+        "light": themes_json(&config.themes, &context)?,
+        "dark": themes_json(&config.dark_themes, &context)?,
+    });
+    if params.css {
+        let failed = |e: quarto_sass::SassError| CallError::failed(e.to_string());
+        result["css"] = quarto_sass::compile_theme_css(&config, &context)
+            .map_err(failed)?
+            .into();
+        if let Some(css) = quarto_sass::compile_dark_theme_css(&config, &context).map_err(failed)? {
+            result["darkCss"] = css.into();
+        }
+    }
+    Ok(result)
+}
+
+/// Resolve themes (checking that custom themes exist and parse) into
+/// `{"name": ...}` for built-in themes and `{"path": ...}` for custom ones.
+fn themes_json(specs: &[ThemeSpec], context: &ThemeContext<'_>) -> Result<Value, CallError> {
+    specs
+        .iter()
+        .map(|spec| {
+            let resolved =
+                resolve_theme_spec(spec, context).map_err(|e| CallError::failed(e.to_string()))?;
+            Ok(match (spec, resolved.source_path) {
+                (_, Some(path)) => json!({ "path": path }),
+                (ThemeSpec::BuiltIn(theme), None) => json!({ "name": theme.name() }),
+                (ThemeSpec::Custom(path), None) => json!({ "path": path }),
+            })
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CitationParams {
+    references: Vec<Reference>,
+    citation: Option<Citation>,
+    style: Option<String>,
+}
+
+/// `citation/format`
+pub fn format_citation(value: Value) -> Result<Value, CallError> {
+    let params = params::<CitationParams>(value)?;
+    let style = params
+        .style
+        .as_deref()
+        .unwrap_or(quarto_citeproc::DEFAULT_STYLE);
+    let style = quarto_csl::parse_csl(style)
+        .map_err(|e| CallError::invalid_params(format!("Invalid CSL style: {}", e)))?;
+
+    let citation = params.citation.unwrap_or_else(|| Citation {
+        items: params
+            .references
+            .iter()
+            .map(|reference| CitationItem {
+                id: reference.id.clone(),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    });
+    let mut processor = Processor::new(style);
+    processor.add_references(params.references);
+    let failed = |e: quarto_citeproc::Error| CallError::failed(e.to_string());
+    let formatted = processor.process_citation(&citation).map_err(failed)?;
+    let bibliography: Vec<Value> = processor
+        .generate_bibliography()
+        .map_err(failed)?
+        .into_iter()
+        .map(|(id, entry)| json!({ "id": id, "entry": entry }))
+        .collect();
+    Ok(json!({ "citation": formatted, "bibliography": bibliography }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_document() {
+        let result = parse_document(json!({ "text": "# Hello\n\nWorld\n" })).unwrap();
+        assert_eq!(result["ast"]["blocks"][0]["t"], "Header");
+        assert_eq!(result["diagnostics"], json!([]));
+
+        let error = parse_document(json!({})).err().unwrap();
+        assert_eq!(error.code, CallError::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let result = validate_config(json!({ "text": "project:\n  type: website\n" })).unwrap();
+        assert_eq!(result["valid"], true);
+
+        let result = validate_config(json!({ "text": "project:\n  type: blog\n" })).unwrap();
+        assert_eq!(result["valid"], false);
+        assert_eq!(result["diagnostics"][0]["kind"], "warning");
+    }
+
+    #[test]
+    fn test_format_citation() {
+        let result = format_citation(json!({
+            "references": [{
+                "id": "knuth84",
+                "type": "article-journal",
+                "title": "Literate Programming",
+                "author": [{ "family": "Knuth", "given": "Donald E." }],
+                "issued": { "date-parts": [[1984]] }
+            }]
+        }))
+        .unwrap();
+        assert_eq!(result["citation"], "(Knuth 1984)");
+        assert_eq!(result["bibliography"][0]["id"], "knuth84");
+        assert!(
+            result["bibliography"][0]["entry"]
+                .as_str()
+                .unwrap()
+                .contains("Literate Programming")
+        );
+    }
+}
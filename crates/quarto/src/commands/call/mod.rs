@@ -0,0 +1,135 @@
+//! Call command implementation.
+//!
+//! `quarto call` exposes Quarto's internals to editors and build tools
+//! without a full render: parsing a document, validating configuration,
+//! resolving a theme and formatting a citation (see [`registry`]).
+//!
+//! Without a function, it serves JSON-RPC 2.0 on stdin/stdout, one message
+//! per line, until stdin closes:
+//!
+//! ```text
+//! → {"jsonrpc": "2.0", "id": 1, "method": "document/parse@1", "params": {"text": "# Hi"}}
+//! ← {"jsonrpc": "2.0", "id": 1, "result": {"ast": {...}, "diagnostics": []}}
+//! ```
+//!
+//! `quarto call <function> [params]` makes a single call, with the
+//! parameters as a JSON object, and prints the result.
+
+mod functions;
+mod registry;
+
+use std::io::{BufRead, Write};
+
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+use self::registry::CallError;
+
+/// Arguments for the call command
+pub struct CallArgs {
+    pub function: Option<String>,
+    pub args: Vec<String>,
+}
+
+/// Execute the call command
+pub fn execute(args: CallArgs) -> Result<()> {
+    let Some(function) = args.function else {
+        let stdin = std::io::stdin();
+        return serve(stdin.lock(), std::io::stdout().lock());
+    };
+    let params = match args.args.as_slice() {
+        [] => Value::Null,
+        [params] => serde_json::from_str(params).context("Parameters must be a JSON object")?,
+        _ => anyhow::bail!("Parameters must be given as a single JSON object"),
+    };
+    let result = registry::call(&function, params)?;
+    println!("{}", serde_json::to_string_pretty(&result)?);
+    Ok(())
+}
+
+/// Answer JSON-RPC messages from `input` on `output`, one per line.
+fn serve(input: impl BufRead, mut output: impl Write) -> Result<()> {
+    for line in input.lines() {
+        let line = line.context("Failed to read from stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_message(&line) {
+            writeln!(output, "{}", response)?;
+            output.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// The response to a JSON-RPC message, or `None` for a notification (a
+/// request without an `id`).
+fn handle_message(line: &str) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(error_response(
+                Value::Null,
+                CallError::new(CallError::PARSE_ERROR, e.to_string()),
+            ));
+        }
+    };
+    let id = request.get("id").cloned();
+    let method = request.get("method").and_then(Value::as_str);
+    let result = match method {
+        Some(method) if request["jsonrpc"] == "2.0" => {
+            let params = request.get("params").cloned().unwrap_or(Value::Null);
+            registry::call(method, params)
+        }
+        _ => Err(CallError::new(
+            CallError::INVALID_REQUEST,
+            "Expected a JSON-RPC 2.0 request with a method",
+        )),
+    };
+
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => error_response(id, error),
+    })
+}
+
+fn error_response(id: Value, error: CallError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serve() {
+        let input = [
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "config/validate", "params": {"text": "toc: true"}}"#,
+            "",
+            r#"{"jsonrpc": "2.0", "method": "functions/list"}"#,
+            r#"{"jsonrpc": "2.0", "id": "b", "method": "document/render"}"#,
+            "not json",
+        ]
+        .join("\n");
+        let mut output = Vec::new();
+        serve(input.as_bytes(), &mut output).unwrap();
+
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["id"], 1);
+        assert_eq!(responses[0]["result"]["valid"], true);
+        assert_eq!(responses[1]["id"], "b");
+        assert_eq!(responses[1]["error"]["code"], CallError::METHOD_NOT_FOUND);
+        assert_eq!(responses[2]["id"], Value::Null);
+        assert_eq!(responses[2]["error"]["code"], CallError::PARSE_ERROR);
+    }
+}
@@ -0,0 +1,356 @@
+//! The functions `quarto call` exposes, with the schemas of their
+//! parameters and results.
+//!
+//! Each [`Function`] carries its own `version`, bumped on incompatible
+//! changes to its schemas (removed or retyped fields); adding optional
+//! parameters or result fields keeps the version. Callers can pin the
+//! version they were written for by calling `name@version`
+//! (`document/parse@1`), which fails once the function has moved on instead
+//! of returning a result of another shape. `functions/list` describes every
+//! function with its version and schemas.
+//!
+//! [`PROTOCOL_VERSION`] covers the JSON-RPC envelope and error codes.
+
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use super::functions;
+
+/// Version of the `quarto call` protocol: the JSON-RPC envelope, the error
+/// codes and the `functions/list` response.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// A function callable with `quarto call`.
+pub struct Function {
+    /// Name of the function, used as the JSON-RPC method
+    pub name: &'static str,
+    /// Version of the function's schemas
+    pub version: u32,
+    pub description: &'static str,
+    /// JSON Schema of the parameters
+    pub params: fn() -> Value,
+    /// JSON Schema of the result
+    pub result: fn() -> Value,
+    pub call: fn(Value) -> Result<Value, CallError>,
+}
+
+/// The functions, in the order they're listed.
+pub static FUNCTIONS: &[Function] = &[
+    Function {
+        name: "functions/list",
+        version: 1,
+        description: "List the functions with their versions and schemas",
+        params: || json!({ "type": "object", "properties": {} }),
+        result: || {
+            json!({
+                "type": "object",
+                "required": ["protocol", "functions"],
+                "properties": {
+                    "protocol": { "type": "integer" },
+                    "functions": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["name", "version", "description", "params", "result"],
+                            "properties": {
+                                "name": { "type": "string" },
+                                "version": { "type": "integer" },
+                                "description": { "type": "string" },
+                                "params": { "type": "object" },
+                                "result": { "type": "object" }
+                            }
+                        }
+                    }
+                }
+            })
+        },
+        call: |_| Ok(list()),
+    },
+    Function {
+        name: "document/parse",
+        version: 1,
+        description: "Parse a Quarto Markdown document into a Pandoc JSON AST",
+        params: || {
+            json!({
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string", "description": "Document source" },
+                    "path": {
+                        "type": "string",
+                        "description": "Document to read when `text` is not given; names the document in diagnostics"
+                    }
+                }
+            })
+        },
+        result: || {
+            json!({
+                "type": "object",
+                "required": ["diagnostics"],
+                "properties": {
+                    "ast": { "type": "object", "description": "Pandoc JSON AST, absent when parsing fails" },
+                    "diagnostics": diagnostics_schema()
+                }
+            })
+        },
+        call: functions::parse_document,
+    },
+    Function {
+        name: "config/validate",
+        version: 1,
+        description: "Validate project configuration or front matter against the Quarto schema",
+        params: || {
+            json!({
+                "type": "object",
+                "properties": {
+                    "text": { "type": "string", "description": "YAML configuration" },
+                    "path": {
+                        "type": "string",
+                        "description": "Configuration file to read when `text` is not given"
+                    }
+                }
+            })
+        },
+        result: || {
+            json!({
+                "type": "object",
+                "required": ["valid", "diagnostics"],
+                "properties": {
+                    "valid": { "type": "boolean" },
+                    "diagnostics": diagnostics_schema()
+                }
+            })
+        },
+        call: functions::validate_config,
+    },
+    Function {
+        name: "theme/resolve",
+        version: 1,
+        description: "Resolve an HTML `theme` option into its light and dark themes",
+        params: || {
+            json!({
+                "type": "object",
+                "required": ["theme"],
+                "properties": {
+                    "theme": {
+                        "description": "Value of `format.html.theme`: a name or path, a list, or `light`/`dark`"
+                    },
+                    "dir": {
+                        "type": "string",
+                        "description": "Directory custom theme paths are relative to (defaults to the working directory)"
+                    },
+                    "css": { "type": "boolean", "description": "Also compile the themes to CSS" }
+                }
+            })
+        },
+        result: || {
+            let themes = json!({
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "description": "Built-in theme" },
+                        "path": { "type": "string", "description": "Custom theme file" }
+                    }
+                }
+            });
+            json!({
+                "type": "object",
+                "required": ["light", "dark"],
+                "properties": {
+                    "light": themes,
+                    "dark": themes,
+                    "css": { "type": "string" },
+                    "darkCss": { "type": "string" }
+                }
+            })
+        },
+        call: functions::resolve_theme,
+    },
+    Function {
+        name: "citation/format",
+        version: 1,
+        description: "Format a citation and bibliography entries with a CSL style",
+        params: || {
+            json!({
+                "type": "object",
+                "required": ["references"],
+                "properties": {
+                    "references": {
+                        "type": "array",
+                        "items": { "type": "object" },
+                        "description": "CSL JSON items"
+                    },
+                    "citation": {
+                        "type": "object",
+                        "description": "CSL citation (`citationItems`, ...); defaults to citing every reference"
+                    },
+                    "style": {
+                        "type": "string",
+                        "description": "CSL style XML (defaults to Chicago author-date)"
+                    }
+                }
+            })
+        },
+        result: || {
+            json!({
+                "type": "object",
+                "required": ["citation", "bibliography"],
+                "properties": {
+                    "citation": { "type": "string" },
+                    "bibliography": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["id", "entry"],
+                            "properties": {
+                                "id": { "type": "string" },
+                                "entry": { "type": "string" }
+                            }
+                        }
+                    }
+                }
+            })
+        },
+        call: functions::format_citation,
+    },
+];
+
+/// Schema of a list of diagnostics, as written by `--log-format json-stream`.
+fn diagnostics_schema() -> Value {
+    json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "required": ["kind", "title"],
+            "properties": {
+                "kind": { "enum": ["error", "warning", "info", "note"] },
+                "title": { "type": "string" },
+                "code": { "type": "string" }
+            }
+        }
+    })
+}
+
+/// The `functions/list` response.
+fn list() -> Value {
+    let functions: Vec<Value> = FUNCTIONS
+        .iter()
+        .map(|function| {
+            json!({
+                "name": function.name,
+                "version": function.version,
+                "description": function.description,
+                "params": (function.params)(),
+                "result": (function.result)(),
+            })
+        })
+        .collect();
+    json!({ "protocol": PROTOCOL_VERSION, "functions": functions })
+}
+
+/// Find the function a method names, as `name` or `name@version`.
+pub fn find(method: &str) -> Result<&'static Function, CallError> {
+    let (name, version) = match method.split_once('@') {
+        Some((name, version)) => {
+            let version = version.parse::<u32>().map_err(|_| {
+                CallError::method_not_found(format!("Invalid version in {}", method))
+            })?;
+            (name, Some(version))
+        }
+        None => (method, None),
+    };
+    let function = FUNCTIONS
+        .iter()
+        .find(|function| function.name == name)
+        .ok_or_else(|| CallError::method_not_found(format!("Unknown function: {}", name)))?;
+    match version {
+        Some(version) if version != function.version => Err(CallError::method_not_found(format!(
+            "{} is at version {}, not {}",
+            name, function.version, version
+        ))),
+        _ => Ok(function),
+    }
+}
+
+/// Call the function a method names.
+pub fn call(method: &str, params: Value) -> Result<Value, CallError> {
+    (find(method)?.call)(params)
+}
+
+/// Decode a function's parameters, with missing parameters as `{}`.
+pub fn params<T: DeserializeOwned>(params: Value) -> Result<T, CallError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| CallError::invalid_params(e.to_string()))
+}
+
+/// A failed call, with its JSON-RPC error code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl CallError {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const INVALID_REQUEST: i64 = -32600;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    /// The function ran and failed (a file couldn't be read, say)
+    pub const FAILED: i64 = -32000;
+
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn method_not_found(message: impl Into<String>) -> Self {
+        Self::new(Self::METHOD_NOT_FOUND, message)
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(Self::INVALID_PARAMS, message)
+    }
+
+    pub fn failed(message: impl Into<String>) -> Self {
+        Self::new(Self::FAILED, message)
+    }
+}
+
+impl std::fmt::Display for CallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CallError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_versions() {
+        assert_eq!(find("document/parse").unwrap().name, "document/parse");
+        assert_eq!(find("document/parse@1").unwrap().version, 1);
+
+        let error = find("document/parse@2").err().unwrap();
+        assert_eq!(error.code, CallError::METHOD_NOT_FOUND);
+        assert_eq!(error.message, "document/parse is at version 1, not 2");
+        assert!(find("document/parse@x").is_err());
+        assert!(find("document/render").is_err());
+    }
+
+    #[test]
+    fn test_list_functions() {
+        let listing = call("functions/list", Value::Null).unwrap();
+        assert_eq!(listing["protocol"], PROTOCOL_VERSION);
+        let functions = listing["functions"].as_array().unwrap();
+        assert_eq!(functions.len(), FUNCTIONS.len());
+        for function in functions {
+            assert_eq!(function["params"]["type"], "object");
+            assert_eq!(function["result"]["type"], "object");
+        }
+    }
+}
@@ -1,8 +1,62 @@
-//! Check command implementation
+//! Check command implementation.
+//!
+//! `quarto check <path>` lints the prose of a document, or of every
+//! document of a project, with the rules of `quarto_lsp_core::prose_lint`
+//! as configured by the `lint` option. Findings are printed like render
+//! diagnostics, and the command fails if any of them is an error.
+//!
+//! Checking the installation (`quarto check`, `quarto check jupyter`, ...)
+//! is not implemented yet.
 
-use anyhow::Result;
-use quarto_core::QuartoError;
+use std::path::Path;
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("check".to_string()).into())
+use anyhow::{Context, Result};
+use quarto_core::{ProjectContext, QuartoError};
+use quarto_error_reporting::DiagnosticKind;
+use quarto_lsp::workspace::FsWorkspace;
+use quarto_lsp_core::Document;
+use quarto_source_map::SourceContext;
+use quarto_system_runtime::NativeRuntime;
+
+pub fn execute(target: Option<String>) -> Result<()> {
+    let Some(target) = target.filter(|target| Path::new(target).exists()) else {
+        return Err(QuartoError::NotImplemented("check".to_string()).into());
+    };
+
+    let runtime = NativeRuntime::new();
+    let project = ProjectContext::discover(&target, &runtime)
+        .context("Failed to discover project context")?;
+
+    let mut errors = 0;
+    for doc_info in &project.files {
+        errors += check_document(&doc_info.input, &project.dir)?;
+    }
+    if errors > 0 {
+        anyhow::bail!("Prose lint found {} error(s)", errors);
+    }
+    Ok(())
+}
+
+/// Lint the prose of a document and print the findings, returning the
+/// number of errors.
+fn check_document(path: &Path, project_dir: &Path) -> Result<usize> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let doc = Document::new(path.to_string_lossy(), content.as_str());
+    let workspace = FsWorkspace::for_dir(path.parent().unwrap_or(project_dir).to_path_buf());
+    let config = quarto_lsp_core::get_prose_lint_config(&doc, &workspace);
+
+    // Findings are located in the document as `FileId(0)`
+    let mut source_context = SourceContext::new();
+    let name = path.strip_prefix(project_dir).unwrap_or(path);
+    source_context.add_file(name.display().to_string(), Some(content));
+
+    let mut errors = 0;
+    for message in quarto_lsp_core::get_prose_lint_messages(&doc, &config) {
+        if message.kind == DiagnosticKind::Error {
+            errors += 1;
+        }
+        eprintln!("{}", message.to_text(Some(&source_context)));
+    }
+    Ok(errors)
 }
@@ -1,8 +1,149 @@
-//! Convert command implementation
+//! Convert command implementation.
+//!
+//! `quarto convert` converts between Jupyter notebooks and qmd documents,
+//! in the direction given by the input's extension:
+//! - `notebook.ipynb` becomes `notebook.qmd` (outputs are dropped)
+//! - `doc.qmd` becomes `doc.ipynb`; when the notebook already exists, the
+//!   outputs of code cells whose source is unchanged are kept, unless
+//!   `--strip-outputs` is given
+//!
+//! See [`quarto_core::notebook`] for how cells map to qmd.
 
-use anyhow::Result;
-use quarto_core::QuartoError;
+use std::path::{Path, PathBuf};
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("convert".to_string()).into())
+use anyhow::{Context, Result};
+use quarto_core::notebook::{Notebook, notebook_to_qmd, qmd_to_notebook};
+use tracing::{info, warn};
+
+/// Arguments for the convert command
+#[derive(Debug)]
+pub struct ConvertArgs {
+    /// Input file (`.ipynb` or `.qmd`)
+    pub input: String,
+    /// Output file (the input with the other extension by default)
+    pub output: Option<String>,
+    /// Don't keep outputs of an existing notebook
+    pub strip_outputs: bool,
+}
+
+/// Direction of a conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    NotebookToQmd,
+    QmdToNotebook,
+}
+
+/// Execute the convert command
+pub fn execute(args: ConvertArgs) -> Result<()> {
+    let input = PathBuf::from(&args.input);
+    let direction = direction(&input)?;
+    let output = match &args.output {
+        Some(output) => PathBuf::from(output),
+        None => input.with_extension(match direction {
+            Direction::NotebookToQmd => "qmd",
+            Direction::QmdToNotebook => "ipynb",
+        }),
+    };
+    if output == input {
+        anyhow::bail!("Output file is the same as the input: {}", output.display());
+    }
+
+    let content = std::fs::read_to_string(&input)
+        .with_context(|| format!("Failed to read {}", input.display()))?;
+    let converted = match direction {
+        Direction::NotebookToQmd => {
+            let notebook = Notebook::parse(&content)
+                .with_context(|| format!("Failed to read notebook {}", input.display()))?;
+            notebook_to_qmd(&notebook)
+        }
+        Direction::QmdToNotebook => {
+            let mut notebook = qmd_to_notebook(&content);
+            if !args.strip_outputs {
+                keep_outputs(&mut notebook, &output);
+            }
+            notebook.to_json()
+        }
+    };
+
+    std::fs::write(&output, converted)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    info!("Converted {} to {}", input.display(), output.display());
+    Ok(())
+}
+
+fn direction(input: &Path) -> Result<Direction> {
+    match input.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("ipynb") => Ok(Direction::NotebookToQmd),
+        Some(ext) if ext.eq_ignore_ascii_case("qmd") => Ok(Direction::QmdToNotebook),
+        _ => anyhow::bail!(
+            "Don't know how to convert {} (expected a .ipynb or .qmd file)",
+            input.display()
+        ),
+    }
+}
+
+/// Carry over the outputs of the notebook being replaced, if there is one.
+fn keep_outputs(notebook: &mut Notebook, output: &Path) {
+    let Ok(previous) = std::fs::read_to_string(output) else {
+        return;
+    };
+    match Notebook::parse(&previous) {
+        Ok(previous) => notebook.copy_outputs_from(&previous),
+        Err(e) => warn!("Not keeping outputs of {}: {}", output.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_keeps_outputs_of_unchanged_cells() {
+        let dir = tempfile::tempdir().unwrap();
+        let qmd = dir.path().join("doc.qmd");
+        let ipynb = dir.path().join("doc.ipynb");
+        std::fs::write(&qmd, "# Title\n\n```{python}\nprint(1)\n```\n").unwrap();
+        let convert = |input: &Path, strip_outputs| {
+            execute(ConvertArgs {
+                input: input.to_string_lossy().into_owned(),
+                output: None,
+                strip_outputs,
+            })
+            .unwrap();
+            Notebook::parse(&std::fs::read_to_string(&ipynb).unwrap()).unwrap()
+        };
+
+        // Simulate executing the notebook
+        let mut notebook = convert(&qmd, false);
+        notebook.cells[1].outputs =
+            vec![serde_json::json!({"output_type": "stream", "name": "stdout", "text": "1\n"})];
+        std::fs::write(&ipynb, notebook.to_json()).unwrap();
+
+        assert_eq!(convert(&qmd, false).cells[1].outputs.len(), 1);
+        assert!(convert(&qmd, true).cells[1].outputs.is_empty());
+
+        execute(ConvertArgs {
+            input: ipynb.to_string_lossy().into_owned(),
+            output: Some(dir.path().join("back.qmd").to_string_lossy().into_owned()),
+            strip_outputs: false,
+        })
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("back.qmd")).unwrap(),
+            "---\njupyter: python3\n---\n\n# Title\n\n```{python}\nprint(1)\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_direction() {
+        assert_eq!(
+            direction(Path::new("a.ipynb")).unwrap(),
+            Direction::NotebookToQmd
+        );
+        assert_eq!(
+            direction(Path::new("a.qmd")).unwrap(),
+            Direction::QmdToNotebook
+        );
+        assert!(direction(Path::new("a.md")).is_err());
+    }
 }
@@ -1,8 +1,434 @@
-//! Create command implementation
+//! Create command implementation.
+//!
+//! `quarto create project [type] [directory] [title]` scaffolds a new
+//! project with [`quarto_project_create`]. Unless `--no-prompt` is given, a
+//! wizard asks for what the arguments leave out: the type of project, the
+//! directory to create it in, its title, the engine of its documents
+//! (`--engine`) and whether to initialize a git repository (`--git`).
+//!
+//! With `--no-prompt`, the type defaults to `default`, the title to the
+//! directory's name and the engine to markdown. Adding `--json` prints what
+//! was created instead of logging it, for IDEs:
+//!
+//! ```json
+//! {"dir": "/home/me/mysite", "files": ["_quarto.yml", "index.qmd"], "git": false}
+//! ```
 
-use anyhow::Result;
-use quarto_core::QuartoError;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("create".to_string()).into())
+use anyhow::{Context, Result};
+use quarto_project_create::{
+    CreateFromChoiceOptions, ProjectChoice, ProjectEngine, ScaffoldedFile,
+    create_project_from_choice, find_implemented_choice, implemented_choices,
+};
+use quarto_system_runtime::{NativeRuntime, SystemRuntime};
+use serde::Serialize;
+use tracing::info;
+
+/// Ignore file written into new git repositories.
+const GITIGNORE: &str = "/.quarto/\n";
+
+/// Arguments for the create command
+#[derive(Debug)]
+pub struct CreateArgs {
+    /// What to create (`project`)
+    pub kind: Option<String>,
+    /// Project type, directory and title
+    pub args: Vec<String>,
+    /// Engine of the project's documents
+    pub engine: Option<String>,
+    /// Initialize a git repository
+    pub git: bool,
+    /// Take everything from the arguments instead of asking
+    pub no_prompt: bool,
+    /// Print what was created as JSON
+    pub json: bool,
+}
+
+/// What to create, and where.
+#[derive(Debug, Clone, PartialEq)]
+struct Choices {
+    choice: String,
+    dir: PathBuf,
+    title: String,
+    engine: ProjectEngine,
+    git: bool,
+}
+
+/// What was created, as printed by `--json`.
+#[derive(Debug, Serialize)]
+struct Created {
+    dir: PathBuf,
+    /// Files, relative to `dir`
+    files: Vec<PathBuf>,
+    git: bool,
+}
+
+/// Execute the create command
+pub fn execute(args: CreateArgs) -> Result<()> {
+    match args.kind.as_deref() {
+        None | Some("project") => {}
+        Some("extension") => anyhow::bail!("Creating extensions is not supported yet"),
+        Some(other) => anyhow::bail!("Unknown type '{}' (expected: project)", other),
+    }
+    if args.json && !args.no_prompt {
+        anyhow::bail!("--json requires --no-prompt");
+    }
+    let engine = args
+        .engine
+        .as_deref()
+        .map(str::parse::<ProjectEngine>)
+        .transpose()?;
+    let mut positional = args.args.into_iter();
+    let given = Given {
+        choice: positional.next(),
+        dir: positional.next(),
+        title: positional.next(),
+        engine,
+        git: args.git,
+    };
+
+    let choices = if args.no_prompt {
+        given.resolve()?
+    } else {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!(
+                "Can't ask what to create without a terminal (use --no-prompt with a type and directory)"
+            );
+        }
+        let mut prompter = Prompter {
+            input: std::io::stdin().lock(),
+            output: std::io::stderr(),
+        };
+        prompter.choices(given, git_available())?
+    };
+
+    let runtime = NativeRuntime::new();
+    let created = create(&choices, &runtime)?;
+    if args.json {
+        println!("{}", serde_json::to_string(&created)?);
+    } else {
+        for file in &created.files {
+            info!("Created {}", choices.dir.join(file).display());
+        }
+        if created.git {
+            info!("Initialized a git repository in {}", choices.dir.display());
+        }
+    }
+    Ok(())
+}
+
+/// What the command line gave.
+#[derive(Debug, Default)]
+struct Given {
+    choice: Option<String>,
+    dir: Option<String>,
+    title: Option<String>,
+    engine: Option<ProjectEngine>,
+    git: bool,
+}
+
+impl Given {
+    /// Fill in what wasn't given with defaults.
+    fn resolve(self) -> Result<Choices> {
+        let choice = find_choice(self.choice.as_deref().unwrap_or("default"))?;
+        let dir = self
+            .dir
+            .filter(|dir| !dir.trim().is_empty())
+            .ok_or_else(|| anyhow::anyhow!("No directory given to create the project in"))?;
+        let dir = PathBuf::from(dir);
+        let title = self.title.unwrap_or_else(|| default_title(&dir));
+        Ok(Choices {
+            choice: choice.id,
+            dir,
+            title,
+            engine: self.engine.unwrap_or_default(),
+            git: self.git,
+        })
+    }
+}
+
+/// Look up an implemented project type.
+fn find_choice(id: &str) -> Result<ProjectChoice> {
+    find_implemented_choice(id).ok_or_else(|| {
+        let ids: Vec<_> = implemented_choices().into_iter().map(|c| c.id).collect();
+        anyhow::anyhow!(
+            "Unknown project type '{}' (expected: {})",
+            id,
+            ids.join(", ")
+        )
+    })
+}
+
+/// Title of a project created in `dir`: the directory's name.
+fn default_title(dir: &Path) -> String {
+    dir.file_name().map_or_else(
+        || "Untitled".to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+/// Asks questions on `output` and reads the answers from `input`.
+struct Prompter<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    /// Ask for what wasn't given.
+    fn choices(&mut self, given: Given, git_available: bool) -> Result<Choices> {
+        let choice = match given.choice {
+            Some(id) => find_choice(&id)?,
+            None => {
+                let choices = implemented_choices();
+                let labels: Vec<_> = choices
+                    .iter()
+                    .map(|c| format!("{} - {}", c.name, c.description))
+                    .collect();
+                let index = self.select("Type of project", &labels, 0)?;
+                choices[index].clone()
+            }
+        };
+        let dir = match given.dir {
+            Some(dir) => dir,
+            None => self.ask("Directory", None)?,
+        };
+        let dir = PathBuf::from(dir);
+        let title = match given.title {
+            Some(title) => title,
+            None => self.ask("Title", Some(&default_title(&dir)))?,
+        };
+        let engine = match given.engine {
+            Some(engine) => engine,
+            None => {
+                let engines = ProjectEngine::all();
+                let labels: Vec<_> = engines.iter().map(ToString::to_string).collect();
+                engines[self.select("Engine", &labels, 0)?]
+            }
+        };
+        let git =
+            given.git || (git_available && self.confirm("Initialize a git repository?", false)?);
+        Ok(Choices {
+            choice: choice.id,
+            dir,
+            title,
+            engine,
+            git,
+        })
+    }
+
+    /// Read a line, failing at the end of the input.
+    fn read_line(&mut self) -> Result<String> {
+        self.output.flush()?;
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            anyhow::bail!("No answer given");
+        }
+        Ok(line.trim().to_string())
+    }
+
+    /// Ask for text; an empty answer takes the default, if any.
+    fn ask(&mut self, question: &str, default: Option<&str>) -> Result<String> {
+        loop {
+            match default {
+                Some(default) => write!(self.output, "{} [{}]: ", question, default)?,
+                None => write!(self.output, "{}: ", question)?,
+            }
+            let answer = self.read_line()?;
+            match (answer.is_empty(), default) {
+                (false, _) => return Ok(answer),
+                (true, Some(default)) => return Ok(default.to_string()),
+                (true, None) => continue,
+            }
+        }
+    }
+
+    /// Ask to pick one of `options` by number.
+    fn select(&mut self, question: &str, options: &[String], default: usize) -> Result<usize> {
+        writeln!(self.output, "{}:", question)?;
+        for (index, option) in options.iter().enumerate() {
+            writeln!(self.output, "  {}. {}", index + 1, option)?;
+        }
+        loop {
+            let answer = self.ask("Choose", Some(&(default + 1).to_string()))?;
+            match answer.parse::<usize>() {
+                Ok(number) if (1..=options.len()).contains(&number) => return Ok(number - 1),
+                _ => writeln!(self.output, "Enter a number from 1 to {}", options.len())?,
+            }
+        }
+    }
+
+    /// Ask a yes or no question.
+    fn confirm(&mut self, question: &str, default: bool) -> Result<bool> {
+        let hint = if default { "Y/n" } else { "y/N" };
+        write!(self.output, "{} [{}] ", question, hint)?;
+        let answer = self.read_line()?.to_lowercase();
+        Ok(match answer.as_str() {
+            "" => default,
+            answer => matches!(answer, "y" | "yes"),
+        })
+    }
+}
+
+/// Whether git can be run.
+fn git_available() -> bool {
+    Command::new("git")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Create the project: write its files and initialize git if asked.
+fn create(choices: &Choices, runtime: &dyn SystemRuntime) -> Result<Created> {
+    let dir = &choices.dir;
+    if runtime
+        .dir_list(dir)
+        .is_ok_and(|entries| !entries.is_empty())
+    {
+        anyhow::bail!("{} already exists and is not empty", dir.display());
+    }
+
+    let options =
+        CreateFromChoiceOptions::new(&choices.choice, &choices.title).with_engine(choices.engine);
+    let files = pollster::block_on(create_project_from_choice(runtime, options))?;
+    let mut created = Vec::with_capacity(files.len() + 1);
+    for file in &files {
+        let (path, content) = match file {
+            ScaffoldedFile::Text { path, content } => (path, content.as_bytes()),
+            ScaffoldedFile::Binary { path, content, .. } => (path, content.as_slice()),
+        };
+        write_file(dir, path, content, runtime)?;
+        created.push(path.clone());
+    }
+
+    if choices.git {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(["init", "--quiet"])
+            .output()
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "git init failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let gitignore = PathBuf::from(".gitignore");
+        if !created.contains(&gitignore) {
+            write_file(dir, &gitignore, GITIGNORE.as_bytes(), runtime)?;
+            created.push(gitignore);
+        }
+    }
+
+    let dir = std::path::absolute(dir).unwrap_or_else(|_| dir.clone());
+    Ok(Created {
+        dir,
+        files: created,
+        git: choices.git,
+    })
+}
+
+fn write_file(dir: &Path, path: &Path, content: &[u8], runtime: &dyn SystemRuntime) -> Result<()> {
+    let target = dir.join(path);
+    if let Some(parent) = target.parent() {
+        runtime
+            .dir_create(parent, true)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    runtime
+        .file_write(&target, content)
+        .with_context(|| format!("Failed to write {}", target.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(answers: &str, given: Given, git_available: bool) -> (Result<Choices>, String) {
+        let mut output = Vec::new();
+        let mut prompter = Prompter {
+            input: answers.as_bytes(),
+            output: &mut output,
+        };
+        let choices = prompter.choices(given, git_available);
+        (choices, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_resolve_given() {
+        let choices = Given {
+            dir: Some("sites/mysite".to_string()),
+            ..Default::default()
+        }
+        .resolve()
+        .unwrap();
+        assert_eq!(
+            choices,
+            Choices {
+                choice: "default".to_string(),
+                dir: PathBuf::from("sites/mysite"),
+                title: "mysite".to_string(),
+                engine: ProjectEngine::Markdown,
+                git: false,
+            }
+        );
+
+        assert!(Given::default().resolve().is_err());
+        let error = Given {
+            choice: Some("slides".to_string()),
+            dir: Some("x".to_string()),
+            ..Default::default()
+        }
+        .resolve()
+        .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Unknown project type 'slides'")
+        );
+    }
+
+    #[test]
+    fn test_prompt_choices() {
+        let website = implemented_choices()
+            .iter()
+            .position(|c| c.id == "website")
+            .unwrap();
+        let answers = format!("{}\n\nmysite\n\n7\n2\ny\n", website + 1);
+        let (choices, output) = prompt(&answers, Given::default(), true);
+        assert_eq!(
+            choices.unwrap(),
+            Choices {
+                choice: "website".to_string(),
+                dir: PathBuf::from("mysite"),
+                title: "mysite".to_string(),
+                engine: ProjectEngine::Jupyter,
+                git: true,
+            }
+        );
+        assert!(output.contains("Type of project:\n  1. "));
+        assert!(output.contains("Title [mysite]: "));
+        assert!(output.contains("Enter a number from 1 to 3"));
+    }
+
+    #[test]
+    fn test_prompt_only_what_is_missing() {
+        let given = Given {
+            choice: Some("blog".to_string()),
+            dir: Some("blog".to_string()),
+            engine: Some(ProjectEngine::Knitr),
+            ..Default::default()
+        };
+        let (choices, output) = prompt("My Blog\n", given, false);
+        let choices = choices.unwrap();
+        assert_eq!(choices.title, "My Blog");
+        assert_eq!(choices.engine, ProjectEngine::Knitr);
+        assert!(!choices.git);
+        assert_eq!(output, "Title [blog]: ");
+
+        let (choices, _) = prompt("", Given::default(), false);
+        assert!(choices.is_err());
+    }
 }
@@ -4,30 +4,52 @@
 //! collaborative editing for Quarto projects using Automerge CRDTs.
 
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use anyhow::Result;
-use quarto_hub::{StorageManager, context::HubConfig, server};
+use anyhow::{Context, Result};
+use quarto_core::{
+    BinaryDependencies, DocumentInfo, Format, HtmlRenderConfig, ProjectContext, QuartoError,
+    RenderContext, RenderOptions, extract_format_metadata, prepare_document,
+    render_prepared_to_html,
+};
+use quarto_hub::ignore::{BinaryPolicy, SyncPolicy};
+use quarto_hub::projects::{ProjectRegistry, project_name};
+use quarto_hub::render::{DocumentRenderer, RenderRequest, RenderedPage};
+use quarto_hub::server::HubProject;
+use quarto_hub::{AuthConfig, StorageManager, context::HubConfig, server};
+use quarto_system_runtime::{NativeRuntime, SystemRuntime};
 use tracing::info;
 
+use super::render::write_themed_resources;
+
 /// Arguments for the hub command.
 pub struct HubArgs {
-    pub project: Option<PathBuf>,
+    /// Project roots; more than one (or a registry) serves each project
+    /// under `/projects/{name}`
+    pub projects: Vec<PathBuf>,
+    pub projects_file: Option<PathBuf>,
     pub port: u16,
     pub host: String,
     pub peers: Vec<String>,
     pub sync_interval: u64,
     pub no_watch: bool,
     pub watch_debounce: u64,
+    pub auth_file: Option<PathBuf>,
+    /// Maximum size of synced files in MiB (0 for no limit)
+    pub max_file_size: u64,
+    pub binary_files: BinaryPolicy,
 }
 
 /// Execute the hub command.
 ///
-/// This starts a collaborative editing server for the given project.
+/// This starts a collaborative editing server for the given project, or for
+/// several projects when more than one is given.
 /// The server provides:
 /// - HTTP/WebSocket API for document synchronization
 /// - Automerge-based CRDT document management
 /// - Filesystem watching and sync
 /// - Peering with remote sync servers
+/// - Server-side HTML previews (`/api/render/{path}`)
 pub fn execute(args: HubArgs) -> Result<()> {
     // Build async runtime and run the server
     // We create a full tokio runtime (not pollster::block_on) because
@@ -38,25 +60,75 @@ pub fn execute(args: HubArgs) -> Result<()> {
 }
 
 async fn run_hub(args: HubArgs) -> Result<()> {
-    // Determine project root (canonicalize to ensure consistent paths for file watching)
-    let project_root = args
-        .project
-        .unwrap_or_else(|| std::env::current_dir().expect("Failed to get current directory"));
-    let project_root = project_root
-        .canonicalize()
-        .expect("Failed to canonicalize project root");
+    // Load authentication tokens (shared by every project unless the
+    // registry gives a project its own token file)
+    let auth = AuthConfig::load_with_env(args.auth_file.as_deref())?;
+
+    if args.projects.len() <= 1 && args.projects_file.is_none() {
+        // Determine project root (canonicalize to ensure consistent paths for file watching)
+        let project_root =
+            args.projects.first().cloned().unwrap_or_else(|| {
+                std::env::current_dir().expect("Failed to get current directory")
+            });
+        let project_root = project_root
+            .canonicalize()
+            .expect("Failed to canonicalize project root");
+
+        info!(project_root = %project_root.display(), "Starting hub");
+
+        // Initialize storage (acquires lockfile)
+        let mut storage = StorageManager::new(&project_root)?;
+        let name = project_name(&project_root);
+        let config = project_config(&args, &mut storage, name, auth)?;
+
+        server::run_server(storage, config).await?;
+        return Ok(());
+    }
+
+    let mut registry = ProjectRegistry::from_roots(&args.projects)?;
+    if let Some(path) = &args.projects_file {
+        registry.extend(ProjectRegistry::load(path)?)?;
+    }
 
-    info!(project_root = %project_root.display(), "Starting hub");
+    let mut projects = Vec::new();
+    for entry in registry.projects {
+        let project_root = entry
+            .root
+            .canonicalize()
+            .with_context(|| format!("project '{}' ({})", entry.name, entry.root.display()))?;
+        info!(project = %entry.name, project_root = %project_root.display(), "Adding project");
 
-    // Initialize storage (acquires lockfile)
-    let mut storage = StorageManager::new(&project_root)?;
+        let mut storage = StorageManager::new(&project_root)?;
+        let auth = match &entry.auth_file {
+            Some(path) => AuthConfig::load_with_env(Some(path))?,
+            None => auth.clone(),
+        };
+        let config = project_config(&args, &mut storage, entry.name.clone(), auth)?;
+        projects.push(HubProject {
+            name: entry.name,
+            storage,
+            config,
+        });
+    }
 
+    server::run_projects(projects, &args.host, args.port).await?;
+
+    Ok(())
+}
+
+/// Build the configuration of a project from the command line.
+fn project_config(
+    args: &HubArgs,
+    storage: &mut StorageManager,
+    project: String,
+    auth: AuthConfig,
+) -> Result<HubConfig> {
     // Determine peers: CLI peers override stored peers
     let peers = if !args.peers.is_empty() {
         // CLI peers provided - use them and persist
         storage.set_peers(args.peers.clone())?;
         info!(peers = ?args.peers, "Using peers from CLI (persisted to hub.json)");
-        args.peers
+        args.peers.clone()
     } else {
         // Use stored peers
         let stored_peers = storage.peers().to_vec();
@@ -66,23 +138,128 @@ async fn run_hub(args: HubArgs) -> Result<()> {
         stored_peers
     };
 
-    // Configure and run server
     let sync_interval_secs = if args.sync_interval == 0 {
         None
     } else {
         Some(args.sync_interval)
     };
 
-    let config = HubConfig {
+    Ok(HubConfig {
+        project,
         port: args.port,
-        host: args.host,
+        host: args.host.clone(),
         peers,
         sync_interval_secs,
         watch_enabled: !args.no_watch,
         watch_debounce_ms: args.watch_debounce,
+        auth,
+        sync_policy: SyncPolicy {
+            max_file_size: (args.max_file_size > 0).then(|| args.max_file_size * 1024 * 1024),
+            binary_files: args.binary_files,
+        },
+        renderer: Some(Arc::new(NativeRenderer)),
+    })
+}
+
+/// Renders hub documents to HTML with the native pipeline.
+///
+/// Code cells are not executed; previews show the document as written
+/// (with frozen outputs where the project has them).
+#[derive(Debug)]
+struct NativeRenderer;
+
+impl DocumentRenderer for NativeRenderer {
+    fn render(&self, request: &RenderRequest) -> std::result::Result<RenderedPage, String> {
+        render_page(request).map_err(|e| format!("{:#}", e))
+    }
+}
+
+fn render_page(request: &RenderRequest) -> Result<RenderedPage> {
+    let runtime = NativeRuntime::new();
+    let input = request.project_root.join(&request.path);
+    let project =
+        ProjectContext::discover(&input, &runtime).context("Failed to discover project context")?;
+    let doc_info = project
+        .files
+        .iter()
+        .find(|doc| doc.input == input)
+        .cloned()
+        .unwrap_or_else(|| DocumentInfo::from_path(&input));
+
+    let metadata =
+        extract_format_metadata(&request.content, "html").unwrap_or(serde_json::Value::Null);
+    let format = Format::html().with_metadata(metadata);
+    let binaries = BinaryDependencies::discover(&runtime);
+
+    let output_path = request
+        .output_dir
+        .join(&request.path)
+        .with_extension("html");
+    let output_dir = output_path
+        .parent()
+        .context("Could not determine output directory")?;
+    let stem = output_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .context("Could not determine output filename stem")?;
+    runtime
+        .dir_create(output_dir, true)
+        .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", output_dir.display(), e))?;
+
+    let options = RenderOptions {
+        use_freeze: true,
+        output_path: Some(output_path.clone()),
+        ..RenderOptions::default()
     };
+    let mut ctx = RenderContext::new(&project, &doc_info, &format, &binaries).with_options(options);
 
-    server::run_server(storage, config).await?;
+    let resource_paths = write_themed_resources(
+        &request.content,
+        &input,
+        &project.dir,
+        output_dir,
+        stem,
+        &runtime,
+        true,
+    )?;
+    let config = HtmlRenderConfig {
+        css_paths: &resource_paths.css,
+        dark_css_paths: &resource_paths.dark_css,
+        template: None,
+        math: None,
+    };
 
-    Ok(())
+    let runtime_arc: Arc<dyn SystemRuntime> = Arc::new(NativeRuntime::new());
+    let input_str = input.to_string_lossy();
+    let prepared = pollster::block_on(prepare_document(
+        request.content.as_bytes(),
+        &input_str,
+        &mut ctx,
+        runtime_arc.clone(),
+    ))
+    .map_err(render_error)?;
+    let output = pollster::block_on(render_prepared_to_html(
+        &prepared,
+        &mut ctx,
+        &config,
+        runtime_arc,
+    ))
+    .map_err(render_error)?;
+
+    Ok(RenderedPage {
+        diagnostics: output
+            .diagnostics
+            .iter()
+            .map(|d| d.to_text(Some(&output.source_context)))
+            .collect(),
+        html: output.html,
+    })
+}
+
+/// Parse errors carry their own formatting, so they are passed through as is.
+fn render_error(error: QuartoError) -> anyhow::Error {
+    match error {
+        QuartoError::Parse(parse_error) => anyhow::anyhow!("{}", parse_error),
+        e => anyhow::anyhow!("{}", e),
+    }
 }
@@ -1,8 +1,78 @@
-//! Install command implementation
+//! Install command implementation.
+//!
+//! `quarto install <tool>...` downloads the latest release of tools that
+//! rendering needs (`tinytex` for PDF output, `chromium` for screenshots)
+//! into Quarto's data directory, where renders find them. A tool already
+//! installed at the latest version is left alone. See
+//! [`quarto_core::tools`] for where tools are installed.
 
 use anyhow::Result;
-use quarto_core::QuartoError;
+use quarto_core::tools::{Tool, install};
+use quarto_system_runtime::NativeRuntime;
+use tracing::info;
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("install".to_string()).into())
+/// Arguments for the install command
+#[derive(Debug)]
+pub struct InstallArgs {
+    /// Tools to install
+    pub targets: Vec<String>,
+}
+
+/// Execute the install command
+pub fn execute(args: InstallArgs) -> Result<()> {
+    if args.targets.is_empty() {
+        anyhow::bail!("No tool given to install (expected: {})", tool_names());
+    }
+    let tools = args
+        .targets
+        .iter()
+        .map(|target| parse_tool(target))
+        .collect::<Result<Vec<_>>>()?;
+    let runtime = NativeRuntime::new();
+    for tool in tools {
+        install_tool(tool, &runtime)?;
+    }
+    Ok(())
+}
+
+/// Install the latest release of `tool`, unless it is already installed.
+pub(crate) fn install_tool(tool: Tool, runtime: &NativeRuntime) -> Result<()> {
+    let release = install::latest_release(tool, runtime)?;
+    if let Some(installed) = tool.installed(runtime)
+        && installed.version == release.version
+    {
+        info!("{} {} is already installed", tool, installed.version);
+        return Ok(());
+    }
+    info!("Downloading {} {}", tool, release.version);
+    let installed = install::install(&release, runtime)?;
+    info!(
+        "Installed {} {} in {}",
+        tool,
+        installed.version,
+        installed.dir.display()
+    );
+    Ok(())
+}
+
+/// Parse a tool name given on the command line.
+pub(crate) fn parse_tool(name: &str) -> Result<Tool> {
+    Tool::parse(name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown tool '{}' (expected: {})", name, tool_names()))
+}
+
+fn tool_names() -> String {
+    Tool::ALL.map(Tool::name).join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tool() {
+        assert_eq!(parse_tool("TinyTeX").unwrap(), Tool::TinyTex);
+        let error = parse_tool("pandoc").unwrap_err().to_string();
+        assert_eq!(error, "Unknown tool 'pandoc' (expected: tinytex, chromium)");
+    }
 }
@@ -1,8 +1,72 @@
-//! List command implementation
+//! List command implementation.
+//!
+//! `quarto list extensions` prints the extensions installed in the current
+//! project (or directory) with their version, what they contribute and,
+//! for extensions added with `quarto add`, where they came from.
 
 use anyhow::Result;
-use quarto_core::QuartoError;
+use quarto_core::extension::discover_extensions;
+use quarto_core::extension::lock::ExtensionsLock;
+use quarto_system_runtime::NativeRuntime;
+use tracing::info;
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("list".to_string()).into())
+use super::add::extensions_dir;
+
+/// Execute the list command
+pub fn execute(type_: Option<String>) -> Result<()> {
+    match type_.as_deref() {
+        None | Some("extensions") => list_extensions(),
+        Some(other) => anyhow::bail!("Unknown type '{}' (expected: extensions)", other),
+    }
+}
+
+fn list_extensions() -> Result<()> {
+    let runtime = NativeRuntime::new();
+    let dir = extensions_dir()?;
+    let extensions = discover_extensions(&dir, &runtime)?;
+    if extensions.is_empty() {
+        info!("No extensions are installed in {}", dir.display());
+        return Ok(());
+    }
+    let lock = ExtensionsLock::load(&dir, &runtime)?;
+
+    let rows: Vec<[String; 4]> = extensions
+        .iter()
+        .map(|extension| {
+            let source = lock.get(&extension.id).map_or_else(
+                || "(local)".to_string(),
+                |locked| match &locked.reference {
+                    Some(reference) => format!("{}@{}", locked.source, reference),
+                    None => locked.source.clone(),
+                },
+            );
+            [
+                extension.id.to_string(),
+                extension.manifest.version.clone().unwrap_or_default(),
+                extension.manifest.contribution_kinds().join(", "),
+                source,
+            ]
+        })
+        .collect();
+    print_table(["Id", "Version", "Contributes", "Source"], &rows);
+    Ok(())
+}
+
+/// Print rows in columns under a header.
+pub(crate) fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let header = header.map(String::from);
+    let mut widths = [0; N];
+    for row in std::iter::once(&header).chain(rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+    for row in std::iter::once(&header).chain(rows) {
+        let line: Vec<_> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        println!("{}", line.join("  ").trim_end());
+    }
 }
@@ -1,8 +1,467 @@
-//! Preview command implementation
+//! Preview command implementation.
+//!
+//! `quarto preview` renders a document or project to HTML and serves the
+//! output over a local HTTP server:
+//! - Input files are watched, and each changed document is re-rendered once
+//!   edits settle (`--no-watch-inputs` turns this off). Changes to includes
+//!   (`_`-prefixed files) re-render the previewed document.
+//! - HTML pages are served with a small script listening on a server-sent
+//!   events channel (`/__quarto/events`); after a re-render the browser
+//!   reloads the page, or navigates to the re-rendered page unless
+//!   `--no-navigate` is given.
+//! - The preview opens in the default browser unless `--no-browser` is given.
+//!
+//! Render errors are reported and the preview keeps running, so a broken
+//! edit can be fixed in place.
 
-use anyhow::Result;
-use quarto_core::QuartoError;
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("preview".to_string()).into())
+use anyhow::{Context, Result};
+use axum::Router;
+use axum::extract::{Path as UrlPath, State};
+use axum::http::{StatusCode, header};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::routing::get;
+use futures::Stream;
+use quarto_core::ProjectContext;
+use quarto_hub::ignore::{SyncFilter, SyncPolicy};
+use quarto_hub::render::{resolve_output_path, resource_content_type};
+use quarto_hub::watch::{FileWatcher, WatchConfig, WatchEvent};
+use quarto_system_runtime::NativeRuntime;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tracing::{debug, error, info, warn};
+
+use super::render::{DiagnosticsReported, RenderArgs};
+use crate::logging::LogFormat;
+
+/// Host the preview server binds to by default.
+const DEFAULT_HOST: &str = "127.0.0.1";
+
+/// Range of ports tried when no `--port` is given.
+const DEFAULT_PORTS: std::ops::Range<u16> = 3000..8000;
+
+/// Number of consecutive ports tried when a port is taken.
+const PORT_ATTEMPTS: u16 = 10;
+
+/// Debounce for input changes, in milliseconds.
+const WATCH_DEBOUNCE_MS: u64 = 300;
+
+/// Server-sent events channel announcing re-rendered pages.
+const EVENTS_PATH: &str = "/__quarto/events";
+
+/// Arguments for the preview command
+#[derive(Debug)]
+pub struct PreviewArgs {
+    /// File or project to preview
+    pub file: Option<String>,
+    /// Suggested port (a free port between 3000 and 8000 by default)
+    pub port: Option<u16>,
+    /// Hostname to bind to
+    pub host: Option<String>,
+    /// Formats to render before previewing (`none`, `all` or a format list)
+    pub render: String,
+    /// Only watch and re-render, without serving
+    pub no_serve: bool,
+    /// Reload the current page instead of navigating to re-rendered pages
+    pub no_navigate: bool,
+    /// Don't open a browser
+    pub no_browser: bool,
+    /// Don't re-render inputs when they change
+    pub no_watch_inputs: bool,
+    /// How diagnostics are printed (`--log-format`)
+    pub log_format: LogFormat,
+    /// Suppress console output
+    pub quiet: bool,
+    /// Active project profiles (`--profile`)
+    pub profiles: Vec<String>,
+}
+
+/// What is being previewed.
+#[derive(Debug, Clone)]
+struct PreviewTarget {
+    /// The file or project directory given on the command line
+    input: PathBuf,
+    /// Project root (the input's directory for a single file)
+    project_dir: PathBuf,
+    /// Whether the input is a single file outside any project
+    is_single_file: bool,
+    /// Directory served over HTTP
+    serve_dir: PathBuf,
+    /// Page the browser opens first
+    start_page: String,
+}
+
+/// Shared state of the preview server.
+struct PreviewState {
+    serve_dir: PathBuf,
+    start_page: String,
+    navigate: bool,
+    reload: broadcast::Sender<String>,
+}
+
+/// Execute the preview command
+pub fn execute(args: PreviewArgs) -> Result<()> {
+    // The server and the watcher run on tokio; renders run on blocking
+    // threads, as the render pipeline drives its futures with pollster.
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(run_preview(Arc::new(args)))
+}
+
+async fn run_preview(args: Arc<PreviewArgs>) -> Result<()> {
+    let target = resolve_target(&args)?;
+
+    // Initial render: a preview always needs HTML output
+    let to = match args.render.as_str() {
+        "none" => Some("html".to_string()),
+        // Render's default formats
+        "all" => None,
+        formats => Some(formats.to_string()),
+    };
+    render(render_args(&args, &target.input, to)).await?;
+
+    let (reload, _) = broadcast::channel(16);
+    if !args.no_watch_inputs {
+        let mut watcher = FileWatcher::new(
+            &target.project_dir,
+            WatchConfig {
+                debounce_ms: WATCH_DEBOUNCE_MS,
+                filter: SyncFilter::load(&target.project_dir, SyncPolicy::default()),
+            },
+        )?;
+        let args = args.clone();
+        let target = target.clone();
+        let reload = reload.clone();
+        tokio::spawn(async move {
+            while let Some(WatchEvent::Modified(path)) = watcher.recv().await {
+                rerender(&args, &target, &path, &reload).await;
+            }
+        });
+    }
+
+    if args.no_serve {
+        tokio::signal::ctrl_c().await?;
+        return Ok(());
+    }
+
+    let host = args.host.as_deref().unwrap_or(DEFAULT_HOST);
+    let listener = bind(host, args.port).await?;
+    let addr = listener.local_addr()?;
+    let url_host = if addr.ip().is_unspecified() {
+        "localhost".to_string()
+    } else {
+        addr.ip().to_string()
+    };
+    let url = format!("http://{}:{}{}", url_host, addr.port(), target.start_page);
+    info!("Preview server running at {}", url);
+    if !args.no_browser {
+        open_browser(&url);
+    }
+
+    let state = Arc::new(PreviewState {
+        serve_dir: target.serve_dir,
+        start_page: target.start_page,
+        navigate: !args.no_navigate,
+        reload,
+    });
+    let app = Router::new()
+        .route(EVENTS_PATH, get(reload_events))
+        .route("/", get(serve_index))
+        .route("/{*path}", get(serve_file))
+        .with_state(state);
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+/// Work out the project, output directory and start page of the input.
+fn resolve_target(args: &PreviewArgs) -> Result<PreviewTarget> {
+    let input = match &args.file {
+        Some(file) => PathBuf::from(file),
+        None => std::env::current_dir().context("Failed to get current directory")?,
+    };
+    let input = input
+        .canonicalize()
+        .with_context(|| format!("Input path does not exist: {}", input.display()))?;
+    let project =
+        ProjectContext::discover_with_profiles(&input, &args.profiles, &NativeRuntime::new())
+            .context("Failed to discover project context")?;
+
+    let serve_dir = if project.is_single_file {
+        project.dir.clone()
+    } else {
+        project.output_dir.clone()
+    };
+    let start_page = if input.is_file() {
+        output_page(&project.dir, &input).unwrap_or_else(|| "/".to_string())
+    } else {
+        "/".to_string()
+    };
+    Ok(PreviewTarget {
+        input,
+        project_dir: project.dir,
+        is_single_file: project.is_single_file,
+        serve_dir,
+        start_page,
+    })
+}
+
+/// Re-render after a change to `path` and announce the re-rendered page.
+async fn rerender(
+    args: &PreviewArgs,
+    target: &PreviewTarget,
+    path: &Path,
+    reload: &broadcast::Sender<String>,
+) {
+    if !path.is_file() {
+        return;
+    }
+    let is_include = path
+        .file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('_'));
+    let input = if is_include || target.is_single_file {
+        // Includes and other files next to a single document only matter
+        // through the previewed document
+        if !target.input.is_file() || (!is_include && path != target.input) {
+            return;
+        }
+        target.input.clone()
+    } else {
+        path.to_path_buf()
+    };
+
+    info!("Rendering {}", input.display());
+    match render(render_args(args, &input, Some("html".to_string()))).await {
+        Ok(()) => {
+            if let Some(page) = output_page(&target.project_dir, &input) {
+                // No receivers just means no browser is connected
+                let _ = reload.send(page);
+            }
+        }
+        // Already printed with source context
+        Err(e) if e.is::<DiagnosticsReported>() => {}
+        Err(e) => error!("{:#}", e),
+    }
+}
+
+/// Run a render on a blocking thread.
+async fn render(args: RenderArgs) -> Result<()> {
+    tokio::task::spawn_blocking(move || super::render::execute(args))
+        .await
+        .context("Render task failed")?
+}
+
+/// Arguments for rendering `input` to `to` (render's default when `None`).
+fn render_args(args: &PreviewArgs, input: &Path, to: Option<String>) -> RenderArgs {
+    RenderArgs {
+        input: Some(input.to_string_lossy().into_owned()),
+        to,
+        output: None,
+        output_dir: None,
+        metadata: Vec::new(),
+        log_format: args.log_format,
+        quiet: args.quiet,
+        debug: false,
+        execute: true,
+        execute_param: Vec::new(),
+        execute_params: None,
+        execute_dir: None,
+        execute_daemon: None,
+        execute_daemon_restart: false,
+        cache: None,
+        cache_refresh: false,
+        incremental: false,
+        profiles: args.profiles.clone(),
+    }
+}
+
+/// URL path of the HTML page rendered from `input`.
+fn output_page(project_dir: &Path, input: &Path) -> Option<String> {
+    let relative = input.strip_prefix(project_dir).ok()?.with_extension("html");
+    let segments: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    Some(format!("/{}", segments.join("/")))
+}
+
+/// Bind the preview server, trying the next ports if the first is taken.
+async fn bind(host: &str, port: Option<u16>) -> Result<TcpListener> {
+    let first = port.unwrap_or_else(random_port);
+    for port in first..first.saturating_add(PORT_ATTEMPTS) {
+        match TcpListener::bind((host, port)).await {
+            Ok(listener) => return Ok(listener),
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => {
+                debug!(port, "Port in use");
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to bind {}:{}", host, port));
+            }
+        }
+    }
+    anyhow::bail!(
+        "No free port found between {} and {}",
+        first,
+        first.saturating_add(PORT_ATTEMPTS - 1)
+    )
+}
+
+/// A port in [`DEFAULT_PORTS`], varying between runs.
+fn random_port() -> u16 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.subsec_nanos());
+    let span = u32::from(DEFAULT_PORTS.end - DEFAULT_PORTS.start);
+    DEFAULT_PORTS.start + (nanos % span) as u16
+}
+
+/// Open `url` in the default browser.
+fn open_browser(url: &str) {
+    #[cfg(target_os = "macos")]
+    let mut command = std::process::Command::new("open");
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = std::process::Command::new("xdg-open");
+
+    if let Err(e) = command
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+    {
+        warn!("Could not open a browser: {}", e);
+    }
+}
+
+/// Stream the pages re-rendered while a browser is connected.
+async fn reload_events(
+    State(state): State<Arc<PreviewState>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let rx = state.reload.subscribe();
+    let pages = futures::stream::unfold(rx, |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(page) => return Some((Ok(Event::default().data(page)), rx)),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    Sse::new(pages).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+}
+
+async fn serve_index(State(state): State<Arc<PreviewState>>) -> Response {
+    if state.start_page == "/" {
+        return read_page(&state, "index.html").await;
+    }
+    Redirect::temporary(&state.start_page).into_response()
+}
+
+async fn serve_file(
+    State(state): State<Arc<PreviewState>>,
+    UrlPath(path): UrlPath<String>,
+) -> Response {
+    read_page(&state, &path).await
+}
+
+/// Serve a file of the output directory, adding the reload script to pages.
+async fn read_page(state: &PreviewState, path: &str) -> Response {
+    let Some(mut file) = resolve_output_path(&state.serve_dir, path) else {
+        return (StatusCode::BAD_REQUEST, "Invalid path").into_response();
+    };
+    if file.is_dir() {
+        file.push("index.html");
+    }
+    let Ok(content) = tokio::fs::read(&file).await else {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    };
+
+    let content_type = resource_content_type(&file.to_string_lossy(), &content);
+    let body = if content_type.starts_with("text/html") {
+        inject_reload_script(&String::from_utf8_lossy(&content), state.navigate).into_bytes()
+    } else {
+        content
+    };
+    (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, "no-store".to_string()),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+/// Add the live-reload script to a page, before `</body>` when there is one.
+fn inject_reload_script(html: &str, navigate: bool) -> String {
+    let script = format!(
+        r#"<script>
+(() => {{
+  const navigate = {navigate};
+  const events = new EventSource("{EVENTS_PATH}");
+  events.onmessage = (e) => {{
+    const here = location.pathname.endsWith("/") ? location.pathname + "index.html" : location.pathname;
+    if (navigate && e.data && e.data !== here) {{
+      location.href = e.data;
+    }} else {{
+      location.reload();
+    }}
+  }};
+}})();
+</script>
+"#
+    );
+    match html.rfind("</body>") {
+        Some(index) => format!("{}{}{}", &html[..index], script, &html[index..]),
+        None => format!("{}{}", html, script),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_page() {
+        let project = Path::new("/work/site");
+        assert_eq!(
+            output_page(project, Path::new("/work/site/index.qmd")),
+            Some("/index.html".to_string())
+        );
+        assert_eq!(
+            output_page(project, Path::new("/work/site/posts/first.qmd")),
+            Some("/posts/first.html".to_string())
+        );
+        assert_eq!(output_page(project, Path::new("/elsewhere/a.qmd")), None);
+    }
+
+    #[test]
+    fn test_inject_reload_script() {
+        let html = inject_reload_script("<html><body><p>Hi</p></body></html>", false);
+        assert!(html.contains("const navigate = false;"));
+        assert!(html.contains(EVENTS_PATH));
+        assert!(html.ends_with("</script>\n</body></html>"));
+
+        // Fragments without a body get the script appended
+        let html = inject_reload_script("<p>Hi</p>", true);
+        assert!(html.starts_with("<p>Hi</p><script>"));
+        assert!(html.contains("const navigate = true;"));
+    }
+
+    #[test]
+    fn test_random_port_in_range() {
+        assert!(DEFAULT_PORTS.contains(&random_port()));
+    }
 }
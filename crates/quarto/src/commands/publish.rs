@@ -1,8 +0,0 @@
-//! Publish command implementation
-
-use anyhow::Result;
-use quarto_core::QuartoError;
-
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("publish".to_string()).into())
-}
@@ -0,0 +1,160 @@
+//! The `_publish.yml` record of publish destinations.
+//!
+//! Each entry names what was published (`project` for the whole project) and
+//! the sites it went to, per provider:
+//!
+//! ```yaml
+//! - source: project
+//!   netlify:
+//!     - id: 5a4f0b1e-0c2a-4a55-9f3e-0123456789ab
+//!       url: https://example.netlify.app
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// File recording publish destinations, next to `_quarto.yml`.
+pub const PUBLISH_FILE: &str = "_publish.yml";
+
+/// Source name for a whole-project publish.
+const PROJECT_SOURCE: &str = "project";
+
+/// A site published to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishTarget {
+    /// Provider-specific site identifier
+    pub id: String,
+
+    /// Public URL of the site
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// The destinations of one published source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PublishSource {
+    source: String,
+
+    /// Sites per provider name
+    #[serde(flatten)]
+    destinations: BTreeMap<String, Vec<PublishTarget>>,
+}
+
+/// Contents of `_publish.yml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublishConfig {
+    sources: Vec<PublishSource>,
+}
+
+impl PublishConfig {
+    /// Load the `_publish.yml` of a project (empty if there is none).
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(PUBLISH_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let sources: Option<Vec<PublishSource>> = serde_yaml::from_str(content)?;
+        Ok(Self {
+            sources: sources.unwrap_or_default(),
+        })
+    }
+
+    /// Write `_publish.yml`.
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        let path = project_dir.join(PUBLISH_FILE);
+        std::fs::write(&path, serde_yaml::to_string(&self.sources)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Providers the project has been published to.
+    pub fn providers(&self) -> Vec<&str> {
+        self.project()
+            .map(|source| source.destinations.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// The site the project was last published to with `provider`.
+    pub fn target(&self, provider: &str) -> Option<&PublishTarget> {
+        self.project()?.destinations.get(provider)?.first()
+    }
+
+    /// Record a publish, making `target` the default site for `provider`.
+    pub fn record(&mut self, provider: &str, target: PublishTarget) {
+        let index = match self
+            .sources
+            .iter()
+            .position(|source| source.source == PROJECT_SOURCE)
+        {
+            Some(index) => index,
+            None => {
+                self.sources.push(PublishSource {
+                    source: PROJECT_SOURCE.to_string(),
+                    destinations: BTreeMap::new(),
+                });
+                self.sources.len() - 1
+            }
+        };
+        let targets = self.sources[index]
+            .destinations
+            .entry(provider.to_string())
+            .or_default();
+        targets.retain(|t| t.id != target.id);
+        targets.insert(0, target);
+    }
+
+    fn project(&self) -> Option<&PublishSource> {
+        self.sources
+            .iter()
+            .find(|source| source.source == PROJECT_SOURCE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_round_trip() {
+        let mut config = PublishConfig::parse(
+            "- source: project\n  netlify:\n    - id: abc\n      url: https://a.netlify.app\n",
+        )
+        .unwrap();
+        assert_eq!(config.providers(), ["netlify"]);
+        assert_eq!(config.target("netlify").unwrap().id, "abc");
+
+        config.record(
+            "gh-pages",
+            PublishTarget {
+                id: "origin".to_string(),
+                url: None,
+            },
+        );
+        config.record(
+            "netlify",
+            PublishTarget {
+                id: "def".to_string(),
+                url: None,
+            },
+        );
+        assert_eq!(config.target("netlify").unwrap().id, "def");
+
+        let saved = serde_yaml::to_string(&config.sources).unwrap();
+        let reloaded = PublishConfig::parse(&saved).unwrap();
+        assert_eq!(reloaded, config);
+        assert_eq!(reloaded.providers(), ["gh-pages", "netlify"]);
+    }
+
+    #[test]
+    fn test_empty_file() {
+        assert_eq!(PublishConfig::parse("").unwrap(), PublishConfig::default());
+    }
+}
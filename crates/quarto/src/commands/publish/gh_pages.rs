@@ -0,0 +1,191 @@
+//! GitHub Pages provider.
+//!
+//! The site is committed to the `gh-pages` branch of the project's git
+//! repository and pushed to a remote (`origin` unless `--id` names another),
+//! for GitHub Pages to serve from that branch. The branch is prepared in a
+//! temporary worktree, so the project's own checkout is left untouched.
+//!
+//! The branch holds exactly the site: everything else is removed, except a
+//! `CNAME` file configuring a custom domain. A `.nojekyll` file is added so
+//! GitHub serves directories starting with `_`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use tracing::{debug, info};
+
+use super::config::PublishTarget;
+use super::{PublishProvider, site_files};
+
+/// Branch GitHub Pages serves the site from.
+const BRANCH: &str = "gh-pages";
+
+/// Remote pushed to when none is configured.
+const DEFAULT_REMOTE: &str = "origin";
+
+/// Commit message of each publish.
+const COMMIT_MESSAGE: &str = "Built site for gh-pages";
+
+/// Publishes to GitHub Pages.
+pub struct GhPages;
+
+impl PublishProvider for GhPages {
+    fn name(&self) -> &'static str {
+        "gh-pages"
+    }
+
+    fn describe(&self, target: Option<&PublishTarget>) -> String {
+        let remote = target.map_or(DEFAULT_REMOTE, |t| t.id.as_str());
+        format!("the {} branch of remote '{}'", BRANCH, remote)
+    }
+
+    fn publish(
+        &self,
+        site_dir: &Path,
+        project_dir: &Path,
+        target: Option<&PublishTarget>,
+    ) -> Result<PublishTarget> {
+        let remote = target.map_or(DEFAULT_REMOTE, |t| t.id.as_str());
+        let repo = PathBuf::from(
+            git(project_dir, &["rev-parse", "--show-toplevel"])
+                .context("The project is not in a git repository")?,
+        );
+        let remote_url = git(&repo, &["remote", "get-url", remote])
+            .with_context(|| format!("The repository has no remote '{}'", remote))?;
+
+        // An existing branch is updated; otherwise it starts from scratch
+        let branch_exists = git(&repo, &["fetch", remote, BRANCH]).is_ok();
+
+        let worktree = std::env::temp_dir().join(format!("quarto-publish-{}", std::process::id()));
+        let result = commit_site(&repo, &worktree, site_dir, branch_exists);
+        let pushed = result.and_then(|changed| {
+            if changed {
+                info!("Pushing {} to {}", BRANCH, remote);
+                git(&worktree, &["push", remote, &format!("HEAD:{}", BRANCH)])?;
+            } else {
+                info!("The {} branch is already up to date", BRANCH);
+            }
+            Ok(())
+        });
+        if let Err(e) = git(
+            &repo,
+            &["worktree", "remove", "--force", &worktree.to_string_lossy()],
+        ) {
+            debug!("Failed to remove publish worktree: {:#}", e);
+        }
+        pushed?;
+
+        let cname = std::fs::read_to_string(site_dir.join("CNAME")).ok();
+        Ok(PublishTarget {
+            id: remote.to_string(),
+            url: pages_url(&remote_url, cname.as_deref()),
+        })
+    }
+}
+
+/// Check out the branch in `worktree`, replace its contents with the site
+/// and commit. Returns whether anything changed.
+fn commit_site(repo: &Path, worktree: &Path, site_dir: &Path, branch_exists: bool) -> Result<bool> {
+    let worktree_arg = worktree.to_string_lossy();
+    if branch_exists {
+        git(
+            repo,
+            &["worktree", "add", "-B", BRANCH, &worktree_arg, "FETCH_HEAD"],
+        )?;
+    } else {
+        git(repo, &["worktree", "add", "--detach", &worktree_arg])?;
+        git(worktree, &["checkout", "--orphan", BRANCH])?;
+        git(worktree, &["rm", "-r", "-q", "-f", "--ignore-unmatch", "."])?;
+    }
+
+    for entry in std::fs::read_dir(worktree)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == ".git" || name == "CNAME" {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    for relative in site_files(site_dir)? {
+        let dest = worktree.join(&relative);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(site_dir.join(&relative), &dest)
+            .with_context(|| format!("Failed to copy {}", relative.display()))?;
+    }
+    std::fs::write(worktree.join(".nojekyll"), "")?;
+
+    git(worktree, &["add", "-A"])?;
+    if git(worktree, &["status", "--porcelain"])?.is_empty() {
+        return Ok(false);
+    }
+    git(worktree, &["commit", "-q", "-m", COMMIT_MESSAGE])?;
+    Ok(true)
+}
+
+/// Run git in `dir`, returning its trimmed output.
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The GitHub Pages URL of a repository: its custom domain when the site has
+/// a `CNAME`, otherwise `https://<owner>.github.io/<repo>/`.
+fn pages_url(remote_url: &str, cname: Option<&str>) -> Option<String> {
+    if let Some(domain) = cname.map(str::trim).filter(|d| !d.is_empty()) {
+        return Some(format!("https://{}/", domain));
+    }
+    let path = remote_url
+        .strip_prefix("git@github.com:")
+        .or_else(|| remote_url.strip_prefix("https://github.com/"))
+        .or_else(|| remote_url.strip_prefix("ssh://git@github.com/"))?;
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let (owner, repo) = path.split_once('/')?;
+    let owner = owner.to_lowercase();
+    if repo.eq_ignore_ascii_case(&format!("{}.github.io", owner)) {
+        Some(format!("https://{}.github.io/", owner))
+    } else {
+        Some(format!("https://{}.github.io/{}/", owner, repo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pages_url() {
+        assert_eq!(
+            pages_url("git@github.com:quarto-dev/docs.git", None).as_deref(),
+            Some("https://quarto-dev.github.io/docs/")
+        );
+        assert_eq!(
+            pages_url("https://github.com/Someone/someone.github.io", None).as_deref(),
+            Some("https://someone.github.io/")
+        );
+        assert_eq!(
+            pages_url("https://github.com/a/b.git", Some("docs.example.com\n")).as_deref(),
+            Some("https://docs.example.com/")
+        );
+        assert_eq!(pages_url("https://gitlab.com/a/b.git", None), None);
+    }
+}
@@ -0,0 +1,199 @@
+//! Publish command implementation.
+//!
+//! `quarto publish <provider> [path]` renders a project and deploys its
+//! output directory with one of the providers:
+//! - `gh-pages`: commits the site to the `gh-pages` branch and pushes it
+//! - `netlify`: uploads the site through the Netlify deploy API
+//!
+//! Destinations are recorded in `_publish.yml` (see [`config`]), so later
+//! publishes go to the same site without naming it again; the provider may
+//! then be omitted as well when the project has a single one. `--no-render`
+//! publishes the existing output as is, and `--dry-run` reports what would
+//! be published without rendering or deploying anything.
+
+mod config;
+mod gh_pages;
+mod netlify;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use quarto_core::ProjectContext;
+use quarto_system_runtime::NativeRuntime;
+use tracing::info;
+
+use self::config::{PUBLISH_FILE, PublishConfig, PublishTarget};
+use super::render::RenderArgs;
+use crate::logging::LogFormat;
+
+/// Arguments for the publish command
+#[derive(Debug)]
+pub struct PublishArgs {
+    /// Provider to publish to (from `_publish.yml` when omitted)
+    pub provider: Option<String>,
+    /// Project to publish
+    pub path: Option<String>,
+    /// Site to publish to, overriding `_publish.yml`
+    pub id: Option<String>,
+    /// Publish the existing output without rendering
+    pub no_render: bool,
+    /// Report what would be published without doing it
+    pub dry_run: bool,
+}
+
+/// A service sites can be published to.
+trait PublishProvider {
+    /// Name used on the command line and in `_publish.yml`
+    fn name(&self) -> &'static str;
+
+    /// Describe the destination, for messages
+    fn describe(&self, target: Option<&PublishTarget>) -> String;
+
+    /// Deploy the files of `site_dir` to `target`, or to a new site when
+    /// there is none, returning the site published to.
+    fn publish(
+        &self,
+        site_dir: &Path,
+        project_dir: &Path,
+        target: Option<&PublishTarget>,
+    ) -> Result<PublishTarget>;
+}
+
+/// All providers.
+fn providers() -> Vec<Box<dyn PublishProvider>> {
+    vec![Box::new(gh_pages::GhPages), Box::new(netlify::Netlify)]
+}
+
+/// Execute the publish command
+pub fn execute(args: PublishArgs) -> Result<()> {
+    let runtime = NativeRuntime::new();
+    let path = match &args.path {
+        Some(path) => PathBuf::from(path),
+        None => std::env::current_dir().context("Failed to get current directory")?,
+    };
+    let project =
+        ProjectContext::discover(&path, &runtime).context("Failed to discover project context")?;
+    if project.is_single_file {
+        anyhow::bail!("Only projects can be published; add a _quarto.yml to publish a document");
+    }
+    if project.output_dir == project.dir {
+        anyhow::bail!(
+            "The project has no output directory to publish (set project: output-dir in _quarto.yml)"
+        );
+    }
+
+    let mut config = PublishConfig::load(&project.dir)?;
+    let provider_name = match &args.provider {
+        Some(name) => name.clone(),
+        None => match config.providers().as_slice() {
+            [name] => name.to_string(),
+            _ => anyhow::bail!(
+                "No provider given (expected one of: {})",
+                provider_names().join(", ")
+            ),
+        },
+    };
+    let provider = providers()
+        .into_iter()
+        .find(|p| p.name() == provider_name)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown provider '{}' (expected one of: {})",
+                provider_name,
+                provider_names().join(", ")
+            )
+        })?;
+    let target = match &args.id {
+        Some(id) => Some(PublishTarget {
+            id: id.clone(),
+            url: None,
+        }),
+        None => config.target(provider.name()).cloned(),
+    };
+
+    if args.dry_run {
+        if !args.no_render {
+            info!("Would render {}", project.dir.display());
+        }
+        let files = if project.output_dir.is_dir() {
+            site_files(&project.output_dir)?
+        } else {
+            Vec::new()
+        };
+        info!(
+            "Would publish {} ({} files currently) to {}",
+            project.output_dir.display(),
+            files.len(),
+            provider.describe(target.as_ref())
+        );
+        for file in &files {
+            info!("  {}", file.display());
+        }
+        return Ok(());
+    }
+
+    if !args.no_render {
+        super::render::execute(render_args(&project.dir))?;
+    }
+    if !project.output_dir.is_dir() {
+        anyhow::bail!(
+            "Output directory {} does not exist (render the project first)",
+            project.output_dir.display()
+        );
+    }
+
+    info!(
+        "Publishing {} to {}",
+        project.output_dir.display(),
+        provider.describe(target.as_ref())
+    );
+    let published = provider.publish(&project.output_dir, &project.dir, target.as_ref())?;
+    match &published.url {
+        Some(url) => info!("Published to {}", url),
+        None => info!("Published to {}", provider.describe(Some(&published))),
+    }
+    config.record(provider.name(), published);
+    config.save(&project.dir)?;
+    info!("Recorded destination in {}", PUBLISH_FILE);
+    Ok(())
+}
+
+fn provider_names() -> Vec<&'static str> {
+    providers().iter().map(|p| p.name()).collect()
+}
+
+/// Arguments for rendering the whole project before publishing.
+fn render_args(project_dir: &Path) -> RenderArgs {
+    RenderArgs {
+        input: Some(project_dir.to_string_lossy().into_owned()),
+        to: None,
+        output: None,
+        output_dir: None,
+        metadata: Vec::new(),
+        log_format: LogFormat::Plain,
+        quiet: false,
+        debug: false,
+        execute: true,
+        execute_param: Vec::new(),
+        execute_params: None,
+        execute_dir: None,
+        execute_daemon: None,
+        execute_daemon_restart: false,
+        cache: None,
+        cache_refresh: false,
+        incremental: false,
+        profiles: Vec::new(),
+    }
+}
+
+/// Files of a site, relative to its root, in a stable order.
+fn site_files(site_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in walkdir::WalkDir::new(site_dir).sort_by_file_name() {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            files.push(entry.path().strip_prefix(site_dir)?.to_path_buf());
+        }
+    }
+    Ok(files)
+}
@@ -0,0 +1,195 @@
+//! Netlify provider.
+//!
+//! The site is uploaded as one zip archive through the Netlify deploy API,
+//! then the deploy is polled until Netlify has processed it. Requests are
+//! authenticated with a personal access token from `NETLIFY_AUTH_TOKEN`.
+//! Without a site id (`--id` or `_publish.yml`) a new site is created.
+
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use tracing::{debug, info};
+use zip::write::SimpleFileOptions;
+
+use super::config::PublishTarget;
+use super::{PublishProvider, site_files};
+
+/// Base URL of the Netlify API.
+const API_URL: &str = "https://api.netlify.com/api/v1";
+
+/// Environment variable holding the access token.
+const TOKEN_VAR: &str = "NETLIFY_AUTH_TOKEN";
+
+/// Interval between checks of a deploy's state.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Number of checks before giving up on a deploy.
+const POLL_ATTEMPTS: u32 = 150;
+
+/// Publishes to Netlify.
+pub struct Netlify;
+
+/// A Netlify site.
+#[derive(Debug, Deserialize)]
+struct Site {
+    id: String,
+    url: Option<String>,
+    ssl_url: Option<String>,
+}
+
+/// A deploy of a site.
+#[derive(Debug, Deserialize)]
+struct Deploy {
+    id: String,
+    state: String,
+    error_message: Option<String>,
+}
+
+impl PublishProvider for Netlify {
+    fn name(&self) -> &'static str {
+        "netlify"
+    }
+
+    fn describe(&self, target: Option<&PublishTarget>) -> String {
+        match target {
+            Some(target) => format!(
+                "Netlify site {}",
+                target.url.as_deref().unwrap_or(&target.id)
+            ),
+            None => "a new Netlify site".to_string(),
+        }
+    }
+
+    fn publish(
+        &self,
+        site_dir: &Path,
+        _project_dir: &Path,
+        target: Option<&PublishTarget>,
+    ) -> Result<PublishTarget> {
+        let token = std::env::var(TOKEN_VAR)
+            .map_err(|_| anyhow::anyhow!("Set {} to a Netlify personal access token", TOKEN_VAR))?;
+        let auth = format!("Bearer {}", token);
+
+        let site: Site = match target {
+            Some(target) => read_json(
+                ureq::get(&format!("{}/sites/{}", API_URL, target.id))
+                    .set("Authorization", &auth)
+                    .call(),
+            )
+            .with_context(|| format!("Netlify site {} not found", target.id))?,
+            None => {
+                info!("Creating Netlify site");
+                read_json(
+                    ureq::post(&format!("{}/sites", API_URL))
+                        .set("Authorization", &auth)
+                        .set("Content-Type", "application/json")
+                        .send_string("{}"),
+                )
+                .context("Failed to create Netlify site")?
+            }
+        };
+
+        let archive = zip_site(site_dir)?;
+        info!(
+            "Uploading {} KiB to Netlify site {}",
+            archive.len() / 1024,
+            site.id
+        );
+        let mut deploy: Deploy = read_json(
+            ureq::post(&format!("{}/sites/{}/deploys", API_URL, site.id))
+                .set("Authorization", &auth)
+                .set("Content-Type", "application/zip")
+                .send_bytes(&archive),
+        )
+        .context("Failed to upload site to Netlify")?;
+
+        for _ in 0..POLL_ATTEMPTS {
+            debug!(deploy = %deploy.id, state = %deploy.state, "Netlify deploy");
+            match deploy.state.as_str() {
+                "ready" => {
+                    return Ok(PublishTarget {
+                        id: site.id,
+                        url: site.ssl_url.or(site.url),
+                    });
+                }
+                "error" => anyhow::bail!(
+                    "Netlify deploy failed: {}",
+                    deploy.error_message.as_deref().unwrap_or("unknown error")
+                ),
+                _ => std::thread::sleep(POLL_INTERVAL),
+            }
+            deploy = read_json(
+                ureq::get(&format!("{}/deploys/{}", API_URL, deploy.id))
+                    .set("Authorization", &auth)
+                    .call(),
+            )?;
+        }
+        anyhow::bail!("Timed out waiting for Netlify deploy {}", deploy.id)
+    }
+}
+
+/// Decode a JSON API response, including the response body in errors.
+fn read_json<T: DeserializeOwned>(
+    response: std::result::Result<ureq::Response, ureq::Error>,
+) -> Result<T> {
+    match response {
+        Ok(response) => {
+            let body = response.into_string()?;
+            serde_json::from_str(&body).context("Unexpected response from Netlify")
+        }
+        Err(ureq::Error::Status(code, response)) => {
+            let body = response.into_string().unwrap_or_default();
+            anyhow::bail!("Netlify API returned {}: {}", code, body.trim())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Zip the files of a site, with paths relative to its root.
+fn zip_site(site_dir: &Path) -> Result<Vec<u8>> {
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for relative in site_files(site_dir)? {
+        let name: Vec<_> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        zip.start_file(name.join("/"), options)?;
+        let content = std::fs::read(site_dir.join(&relative))
+            .with_context(|| format!("Failed to read {}", relative.display()))?;
+        zip.write_all(&content)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_zip_site() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("site_libs")).unwrap();
+        std::fs::write(dir.path().join("index.html"), "<p>Hi</p>").unwrap();
+        std::fs::write(dir.path().join("site_libs/app.js"), "run()").unwrap();
+
+        let bytes = zip_site(dir.path()).unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let mut names: Vec<_> = archive.file_names().map(String::from).collect();
+        names.sort();
+        assert_eq!(names, ["index.html", "site_libs/app.js"]);
+
+        let mut content = String::new();
+        archive
+            .by_name("site_libs/app.js")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "run()");
+    }
+}
@@ -1,8 +1,42 @@
-//! Remove command implementation
+//! Remove command implementation.
+//!
+//! `quarto remove <extension>...` removes installed extensions, named by id
+//! (`owner/name` or `name`) or by the repository they were added from
+//! (`owner/repo`), and drops them from the extensions lockfile.
 
 use anyhow::Result;
-use quarto_core::QuartoError;
+use quarto_core::extension::install;
+use quarto_core::extension::lock::ExtensionsLock;
+use quarto_system_runtime::NativeRuntime;
+use tracing::info;
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("remove".to_string()).into())
+use super::add::{extensions_dir, find_installed};
+
+/// Arguments for the remove command
+#[derive(Debug)]
+pub struct RemoveArgs {
+    /// Extensions to remove
+    pub targets: Vec<String>,
+}
+
+/// Execute the remove command
+pub fn execute(args: RemoveArgs) -> Result<()> {
+    if args.targets.is_empty() {
+        anyhow::bail!("No extension given to remove");
+    }
+    let runtime = NativeRuntime::new();
+    let dir = extensions_dir()?;
+    let mut lock = ExtensionsLock::load(&dir, &runtime)?;
+
+    // Resolve every target before removing anything
+    let mut ids = Vec::new();
+    for target in &args.targets {
+        ids.extend(find_installed(&dir, target, &lock)?);
+    }
+    for id in &ids {
+        install::remove(&dir, id, &mut lock, &runtime)?;
+        info!("Removed {}", id);
+    }
+    lock.save(&dir, &runtime)?;
+    Ok(())
 }
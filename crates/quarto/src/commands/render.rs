@@ -17,12 +17,23 @@
 //! - HTML output (native Rust pipeline, no Pandoc)
 //! - Basic document structure
 //! - SASS theme compilation (Bootstrap/Bootswatch themes)
+//! - Code execution (`--execute-params`, `-P`, `--execute-daemon`, ...)
+//! - Multi-file projects, rendered in dependency order (incrementally with
+//!   `--no-clean`)
+//! - Website navigation (navbar, sidebar, breadcrumbs, footer)
+//! - Book projects (chapter numbering, cross-chapter references, prev/next)
+//! - Project profiles (`--profile`, `QUARTO_PROFILE`)
+//! - Warnings for unknown, mistyped and deprecated `_quarto.yml` options
+//! - PDF (LaTeX), Typst and revealjs output
+//! - Several formats at once (`--to html,pdf`), sharing one parse and
+//!   execution of each document
+//! - Metadata overrides (`-M KEY:VALUE`) and HTML output to stdout
+//!   (`--output -`)
+//! - Formats contributed by extensions in `_extensions` (`--to acm-pdf`)
 //!
-//! Not yet supported:
-//! - Code execution
-//! - Navigation (navbar, sidebar, footer)
-//! - Multi-file projects
-//! - Non-HTML formats
+//! Diagnostics are printed with source context (or as JSON lines with
+//! `--log-format json-stream`); any error diagnostic makes the command exit
+//! with a non-zero status.
 
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -30,13 +41,21 @@ use std::sync::Arc;
 use anyhow::{Context, Result};
 use tracing::{debug, info, warn};
 
+use quarto_core::extension::Extension;
+use quarto_core::extension::contributions::{document_extensions, find_format, merge_metadata};
 use quarto_core::{
-    BinaryDependencies, DocumentInfo, Format, FormatIdentifier, HtmlRenderConfig, ProjectContext,
-    QuartoError, RenderContext, RenderOptions, extract_format_metadata, render_qmd_to_html,
+    BinaryDependencies, DocumentInfo, Format, FormatIdentifier, HtmlRenderConfig, PdfRenderConfig,
+    PreparedDocument, ProjectContext, ProjectCrossrefs, ProjectRenderer, QuartoError,
+    RenderContext, RenderOptions, extract_format_metadata, prepare_document,
+    render_prepared_to_html, render_prepared_to_pdf,
 };
-use quarto_sass::{ThemeConfig, ThemeContext, ThemeSpec};
+use quarto_error_reporting::{DiagnosticKind, DiagnosticMessage};
+use quarto_sass::{SassCache, ThemeConfig, ThemeContext, ThemeSpec, find_brand_file, load_brand};
+use quarto_source_map::SourceContext;
 use quarto_system_runtime::{NativeRuntime, SystemRuntime};
 
+use crate::logging::LogFormat;
+
 /// Arguments for the render command
 #[derive(Debug)]
 pub struct RenderArgs {
@@ -48,11 +67,34 @@ pub struct RenderArgs {
     pub output: Option<String>,
     /// Output directory
     pub output_dir: Option<String>,
+    /// Metadata overrides (KEY:VALUE)
+    pub metadata: Vec<String>,
+    /// How diagnostics are printed (`--log-format`)
+    pub log_format: LogFormat,
     /// Suppress console output
     pub quiet: bool,
-    /// Leave intermediate files (not yet implemented)
-    #[allow(dead_code)]
+    /// Leave intermediate files (`.tex`/`.typ` and LaTeX auxiliary files)
     pub debug: bool,
+    /// Execute code cells
+    pub execute: bool,
+    /// Execution parameters (KEY:VALUE)
+    pub execute_param: Vec<String>,
+    /// YAML file with execution parameters
+    pub execute_params: Option<String>,
+    /// Working directory for code execution
+    pub execute_dir: Option<String>,
+    /// Kernel keepalive in seconds
+    pub execute_daemon: Option<u32>,
+    /// Restart keepalive kernel before render
+    pub execute_daemon_restart: bool,
+    /// Cache cell outputs (`Some(false)` for --no-cache)
+    pub cache: Option<bool>,
+    /// Force refresh of the cell output cache
+    pub cache_refresh: bool,
+    /// Only re-render changed project files (`--no-clean`)
+    pub incremental: bool,
+    /// Active project profiles (`--profile`)
+    pub profiles: Vec<String>,
 }
 
 /// Execute the render command
@@ -79,23 +121,44 @@ pub fn execute(args: RenderArgs) -> Result<()> {
         anyhow::bail!("Input path does not exist: {}", input_path.display());
     }
 
-    // Determine format
-    let format = match &args.to {
-        Some(format_str) => resolve_format(format_str)?,
-        None => Format::html(), // Default to HTML
+    // Discover project context
+    let project = ProjectContext::discover_with_profiles(&input_path, &args.profiles, &runtime)
+        .context("Failed to discover project context")?;
+
+    // Determine formats: `--to html,pdf` renders each from a single parse.
+    // Extensions of the input's directory (up to the project's) can
+    // contribute formats such as `acm-pdf`.
+    let formats = match &args.to {
+        Some(formats_str) => {
+            let input_dir = if runtime.is_dir(&input_path).unwrap_or(false) {
+                input_path.as_path()
+            } else {
+                input_path.parent().unwrap_or(Path::new(""))
+            };
+            let extensions = document_extensions(input_dir, &project.dir, &runtime)?;
+            resolve_formats(formats_str, &extensions)?
+        }
+        None => vec![Format::html()], // Default to HTML
     };
 
-    // Only HTML is supported in MVP
-    if !format.identifier.is_native() {
-        anyhow::bail!(
-            "Format '{}' is not yet supported. Only HTML is available in this version.",
-            format.identifier
-        );
+    // HTML, PDF and Typst are supported in MVP
+    for format in &formats {
+        if !format.identifier.is_native() && !is_pdf_format(format.identifier) {
+            anyhow::bail!(
+                "Format '{}' is not yet supported. Only HTML, PDF and Typst are available in this version.",
+                format.identifier
+            );
+        }
+    }
+    if formats.len() > 1 && args.output.is_some() {
+        anyhow::bail!("--output cannot be used when rendering to more than one format");
+    }
+    if writes_to_stdout(&args) && formats.iter().any(|f| is_pdf_format(f.identifier)) {
+        anyhow::bail!("--output - is only supported for HTML output");
+    }
+    if writes_to_stdout(&args) && !project.is_single_file {
+        anyhow::bail!("--output - cannot be used when rendering a project");
     }
-
-    // Discover project context
-    let project = ProjectContext::discover(&input_path, &runtime)
-        .context("Failed to discover project context")?;
 
     if !args.quiet {
         if project.is_single_file {
@@ -107,17 +170,199 @@ pub fn execute(args: RenderArgs) -> Result<()> {
                 project.project_type().as_str()
             );
         }
+        if let Some(config) = project.config.as_ref().filter(|c| !c.profiles.is_empty()) {
+            info!("Active profiles: {}", config.profiles.join(", "));
+        }
+    }
+    if let Some(config) = &project.config {
+        report_diagnostics(&config.diagnostics, &config.source_context, &args);
     }
 
     // Set up binary dependencies
     let binaries = BinaryDependencies::discover(&runtime);
 
-    // Render each file
-    for doc_info in &project.files {
-        render_document(doc_info, &project, &format, &binaries, &args, &runtime)?;
+    // Resolve execution parameters once for all documents
+    let shared = SharedRenderState {
+        params: resolve_execute_params(&args, &runtime)?,
+        metadata: resolve_metadata(&args)?,
+        crossrefs: ProjectCrossrefs::new(),
+        errors: std::sync::atomic::AtomicUsize::new(0),
+    };
+
+    if project.is_single_file {
+        for doc_info in &project.files {
+            render_document(
+                doc_info, &project, &formats, &binaries, &args, &shared, &runtime,
+            )?;
+        }
+        return shared.check_errors();
     }
 
-    Ok(())
+    // Render project files in dependency order
+    let renderer = ProjectRenderer::new(&project, &runtime)
+        .with_format(formats[0].clone())
+        .with_incremental(args.incremental);
+    let result = renderer
+        .render(|doc_info| {
+            render_document(
+                doc_info, &project, &formats, &binaries, &args, &shared, &runtime,
+            )
+            .map_err(|e| QuartoError::Other(format!("{:#}", e)))
+        })
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    if !args.quiet && !result.skipped.is_empty() {
+        info!(
+            "Skipped {} unchanged file(s); rendered {}",
+            result.skipped.len(),
+            result.rendered.len()
+        );
+    }
+    if !args.quiet {
+        for output in &result.outputs {
+            info!("Wrote {}", output.display());
+        }
+    }
+
+    shared.check_errors()
+}
+
+/// State shared by the renders of all documents of a project.
+struct SharedRenderState {
+    /// Execution parameters (`--execute-params`, `-P`)
+    params: serde_json::Map<String, serde_json::Value>,
+    /// Metadata overrides (`-M`)
+    metadata: serde_json::Map<String, serde_json::Value>,
+    /// Crossref targets published by rendered documents (book chapters)
+    crossrefs: ProjectCrossrefs,
+    /// Error diagnostics reported so far
+    errors: std::sync::atomic::AtomicUsize,
+}
+
+impl SharedRenderState {
+    /// Record the error diagnostics among `diagnostics`.
+    fn count_errors(&self, diagnostics: &[DiagnosticMessage]) {
+        let errors = diagnostics
+            .iter()
+            .filter(|d| d.kind == DiagnosticKind::Error)
+            .count();
+        self.errors
+            .fetch_add(errors, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Fail if any error diagnostic was reported.
+    fn check_errors(&self) -> Result<()> {
+        match self.errors.load(std::sync::atomic::Ordering::Relaxed) {
+            0 => Ok(()),
+            errors => anyhow::bail!("Render reported {} error(s)", errors),
+        }
+    }
+}
+
+/// Whether the output goes to stdout (`--output -`).
+fn writes_to_stdout(args: &RenderArgs) -> bool {
+    args.output.as_deref() == Some("-")
+}
+
+/// Print diagnostics to stderr: with source context, or as JSON lines with
+/// `--log-format json-stream`. Warnings are hidden by `--quiet`; errors are
+/// always printed.
+fn report_diagnostics(
+    diagnostics: &[DiagnosticMessage],
+    source_context: &SourceContext,
+    args: &RenderArgs,
+) {
+    for diagnostic in diagnostics {
+        if args.quiet && diagnostic.kind != DiagnosticKind::Error {
+            continue;
+        }
+        match args.log_format {
+            LogFormat::Plain => eprintln!("{}", diagnostic.to_text(Some(source_context))),
+            LogFormat::JsonStream => eprintln!("{}", diagnostic.to_json()),
+        }
+    }
+}
+
+/// Error returned once a failed parse has been reported.
+///
+/// Parse errors have rich ariadne formatting with their own "Error:" prefix,
+/// so they are printed directly rather than through anyhow; `main` exits
+/// with status 1 without printing this error again.
+#[derive(Debug)]
+pub struct DiagnosticsReported;
+
+impl std::fmt::Display for DiagnosticsReported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("render failed")
+    }
+}
+
+impl std::error::Error for DiagnosticsReported {}
+
+/// Report the diagnostics of a failed parse.
+fn report_parse_error(error: &quarto_core::ParseError, args: &RenderArgs) -> anyhow::Error {
+    report_diagnostics(&error.diagnostics, &error.source_context, args);
+    DiagnosticsReported.into()
+}
+
+/// Collect metadata overrides from `-M KEY:VALUE` flags.
+fn resolve_metadata(args: &RenderArgs) -> Result<serde_json::Map<String, serde_json::Value>> {
+    args.metadata
+        .iter()
+        .map(|param| parse_key_value("metadata", param))
+        .collect()
+}
+
+/// Collect execution parameters from `--execute-params` and `-P` flags.
+///
+/// The YAML file is read first; `-P KEY:VALUE` flags override its entries.
+/// Values are parsed as YAML scalars, so `-P n:10` yields a number and
+/// `-P show:true` a boolean.
+fn resolve_execute_params(
+    args: &RenderArgs,
+    runtime: &dyn SystemRuntime,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let mut params = serde_json::Map::new();
+
+    if let Some(file) = &args.execute_params {
+        let bytes = runtime
+            .file_read(Path::new(file))
+            .map_err(|e| anyhow::anyhow!("Failed to read execute params file {}: {}", file, e))?;
+        let value: serde_json::Value = serde_yaml::from_slice(&bytes)
+            .with_context(|| format!("Invalid YAML in execute params file {}", file))?;
+        match value {
+            serde_json::Value::Object(map) => params.extend(map),
+            serde_json::Value::Null => {}
+            _ => anyhow::bail!("Execute params file {} must contain a YAML mapping", file),
+        }
+    }
+
+    for param in &args.execute_param {
+        let (key, value) = parse_execute_param(param)?;
+        params.insert(key, value);
+    }
+
+    Ok(params)
+}
+
+/// Parse a `-P KEY:VALUE` execution parameter.
+fn parse_execute_param(param: &str) -> Result<(String, serde_json::Value)> {
+    parse_key_value("execute param", param)
+}
+
+/// Parse a `KEY:VALUE` flag (`-P`, `-M`). The value is read as a YAML
+/// scalar, falling back to a string.
+fn parse_key_value(kind: &str, param: &str) -> Result<(String, serde_json::Value)> {
+    let Some((key, value)) = param.split_once(':') else {
+        anyhow::bail!("Invalid {} '{}' (expected KEY:VALUE)", kind, param);
+    };
+    let key = key.trim();
+    if key.is_empty() {
+        anyhow::bail!("Invalid {} '{}' (empty key)", kind, param);
+    }
+    let value = serde_yaml::from_str(value.trim())
+        .unwrap_or_else(|_| serde_json::Value::String(value.trim().to_string()));
+    Ok((key.to_string(), value))
 }
 
 /// Resolve format string to Format (without metadata)
@@ -125,6 +370,42 @@ fn resolve_format(format_str: &str) -> Result<Format> {
     resolve_format_with_metadata(format_str, serde_json::Value::Null)
 }
 
+/// Resolve the comma-separated formats of `--to` (e.g. `html,pdf`)
+fn resolve_formats(formats_str: &str, extensions: &[Extension]) -> Result<Vec<Format>> {
+    let formats = formats_str
+        .split(',')
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .map(|f| resolve_format_with_extensions(f, extensions))
+        .collect::<Result<Vec<_>>>()?;
+    if formats.is_empty() {
+        anyhow::bail!("No output format given to --to");
+    }
+    Ok(formats)
+}
+
+/// Resolve a format string to a Format, which may be a format contributed
+/// by one of `extensions` (with the extension's format metadata)
+fn resolve_format_with_extensions(format_str: &str, extensions: &[Extension]) -> Result<Format> {
+    if FormatIdentifier::try_from(format_str).is_ok() {
+        return resolve_format(format_str);
+    }
+    let Some(extension_format) = find_format(extensions, format_str) else {
+        return resolve_format(format_str);
+    };
+    let format = resolve_format_with_metadata(&extension_format.base, extension_format.metadata)
+        .with_context(|| {
+            format!(
+                "Extension format '{}' builds on an unsupported format",
+                format_str
+            )
+        })?;
+    Ok(Format {
+        extension_format: Some(extension_format.name),
+        ..format
+    })
+}
+
 /// Resolve format string to Format with metadata
 fn resolve_format_with_metadata(format_str: &str, metadata: serde_json::Value) -> Result<Format> {
     let identifier =
@@ -146,16 +427,22 @@ fn resolve_format_with_metadata(format_str: &str, metadata: serde_json::Value) -
         .to_string(),
         native_pipeline: identifier.is_native(),
         metadata,
+        extension_format: None,
     })
 }
 
-/// Render a single document
+/// Render a single document to each of `formats`.
+///
+/// The document is parsed and its code executed once, for the first format
+/// (engines see its name, e.g. to choose figure formats); each format is then
+/// rendered from the same executed AST.
 fn render_document(
     doc_info: &DocumentInfo,
     project: &ProjectContext,
-    format: &Format,
+    formats: &[Format],
     binaries: &BinaryDependencies,
     args: &RenderArgs,
+    shared: &SharedRenderState,
     runtime: &dyn SystemRuntime,
 ) -> Result<()> {
     debug!("Rendering: {}", doc_info.input.display());
@@ -173,33 +460,112 @@ fn render_document(
     let input_str =
         std::str::from_utf8(&input_bytes).context("Input file contains invalid UTF-8")?;
 
-    // Extract format-specific metadata from frontmatter (e.g., toc, toc-depth)
-    let format_metadata = extract_format_metadata(input_str, "html").unwrap_or_else(|e| {
-        warn!("Failed to extract format metadata: {}. Using defaults.", e);
-        serde_json::Value::Null
-    });
-
-    // Create format with the extracted metadata
-    let format_with_metadata = Format {
-        identifier: format.identifier.clone(),
-        output_extension: format.output_extension.clone(),
-        native_pipeline: format.native_pipeline,
-        metadata: format_metadata,
+    // Create the formats with format-specific metadata from the frontmatter
+    // (e.g., toc, toc-depth), over the defaults of extension formats
+    let formats: Vec<Format> = formats
+        .iter()
+        .map(|format| {
+            let format_metadata =
+                extract_format_metadata(input_str, format.name()).unwrap_or_else(|e| {
+                    warn!("Failed to extract format metadata: {}. Using defaults.", e);
+                    serde_json::Value::Null
+                });
+            let mut metadata = format.metadata.clone();
+            merge_metadata(&mut metadata, &format_metadata);
+            Format {
+                identifier: format.identifier,
+                output_extension: format.output_extension.clone(),
+                native_pipeline: format.native_pipeline,
+                metadata,
+                extension_format: format.extension_format.clone(),
+            }
+        })
+        .collect();
+    let Some((first_format, other_formats)) = formats.split_first() else {
+        return Ok(());
     };
 
     // Create render context with the format that has metadata
     let options = RenderOptions {
         verbose: !args.quiet,
-        execute: false, // MVP: no code execution
+        execute: args.execute,
         use_freeze: false,
-        output_path: args.output.as_ref().map(PathBuf::from),
+        output_path: args
+            .output
+            .as_ref()
+            .filter(|_| !writes_to_stdout(args))
+            .map(PathBuf::from),
+        execute_params: shared.params.clone(),
+        execute_dir: args.execute_dir.as_ref().map(PathBuf::from),
+        // A one-shot render shouldn't leave kernels behind unless asked to
+        execute_daemon: Some(args.execute_daemon.unwrap_or(0)),
+        execute_daemon_restart: args.execute_daemon_restart,
+        cache: args.cache,
+        cache_refresh: args.cache_refresh,
+        crossrefs: Some(shared.crossrefs.clone()),
+        metadata: shared.metadata.clone(),
     };
 
-    let mut ctx = RenderContext::new(project, doc_info, &format_with_metadata, binaries)
-        .with_options(options);
-
-    // Determine output path (needed before rendering for CSS resource paths)
+    let mut ctx =
+        RenderContext::new(project, doc_info, first_format, binaries).with_options(options);
     let output_path = determine_output_path(&ctx, args)?;
+    ctx.options.output_path = Some(output_path);
+
+    // Parse and execute once, for all formats
+    let input_path_str = doc_info.input.to_string_lossy();
+    let runtime_arc: Arc<dyn SystemRuntime> = Arc::new(NativeRuntime::new());
+    let prepared = match pollster::block_on(prepare_document(
+        &input_bytes,
+        &input_path_str,
+        &mut ctx,
+        runtime_arc.clone(),
+    )) {
+        Ok(prepared) => prepared,
+        Err(QuartoError::Parse(parse_error)) => return Err(report_parse_error(&parse_error, args)),
+        Err(e) => return Err(anyhow::anyhow!("{}", e)),
+    };
+
+    render_format(
+        &prepared,
+        &mut ctx,
+        input_str,
+        args,
+        shared,
+        runtime,
+        &runtime_arc,
+    )?;
+    for format in other_formats {
+        // `--output` names a single file, so it is never set here
+        let mut format_ctx = ctx.for_format(format);
+        format_ctx.options.output_path = None;
+        let output_path = determine_output_path(&format_ctx, args)?;
+        format_ctx.options.output_path = Some(output_path);
+        render_format(
+            &prepared,
+            &mut format_ctx,
+            input_str,
+            args,
+            shared,
+            runtime,
+            &runtime_arc,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Render a prepared document to the format of `ctx`, writing the output
+/// (and, for HTML, its resources) to `ctx.options.output_path`.
+fn render_format(
+    prepared: &PreparedDocument,
+    ctx: &mut RenderContext,
+    input_str: &str,
+    args: &RenderArgs,
+    shared: &SharedRenderState,
+    runtime: &dyn SystemRuntime,
+    runtime_arc: &Arc<dyn SystemRuntime>,
+) -> Result<()> {
+    let output_path = ctx.output_path();
 
     // Create output directory if needed
     let output_dir = output_path
@@ -213,56 +579,64 @@ fn render_document(
         )
     })?;
 
+    if is_pdf_format(ctx.format.identifier) {
+        return render_pdf_document(prepared, ctx, args, shared, runtime_arc);
+    }
+
     // Get the output stem for resource directory naming
     let output_stem = output_path
         .file_stem()
         .and_then(|s| s.to_str())
         .ok_or_else(|| anyhow::anyhow!("Could not determine output filename stem"))?;
 
-    // Extract theme configuration from frontmatter and write resources
-    let resource_paths = write_themed_resources(
-        input_str,
-        &doc_info.input,
-        output_dir,
-        output_stem,
-        runtime,
-        args.quiet,
-    )?;
+    // Extract theme configuration from frontmatter and write resources.
+    // Presentations are styled by their reveal.js theme instead.
+    let resource_paths = if ctx.format.identifier == FormatIdentifier::Revealjs {
+        quarto_core::resources::HtmlResourcePaths::empty()
+    } else {
+        write_themed_resources(
+            input_str,
+            &ctx.document.input,
+            &ctx.project.dir,
+            output_dir,
+            output_stem,
+            runtime,
+            args.quiet,
+        )?
+    };
 
     // Use the unified pipeline to render
-    let input_path_str = doc_info.input.to_string_lossy();
     let config = HtmlRenderConfig {
         css_paths: &resource_paths.css,
+        dark_css_paths: &resource_paths.dark_css,
         template: None,
+        math: None,
     };
 
-    // Create Arc runtime for the async pipeline
-    let runtime_arc: Arc<dyn SystemRuntime> = Arc::new(NativeRuntime::new());
-
-    // Use pollster to run the async pipeline synchronously (expects bytes)
-    let output = match pollster::block_on(render_qmd_to_html(
-        &input_bytes,
-        &input_path_str,
-        &mut ctx,
+    // Use pollster to run the async pipeline synchronously
+    let output = match pollster::block_on(render_prepared_to_html(
+        prepared,
+        ctx,
         &config,
-        runtime_arc,
+        runtime_arc.clone(),
     )) {
         Ok(output) => output,
-        Err(QuartoError::Parse(parse_error)) => {
-            // Parse errors have rich ariadne formatting with their own "Error:" prefix.
-            // Print directly to avoid anyhow adding a duplicate prefix.
-            eprintln!("{}", parse_error);
-            std::process::exit(1);
-        }
+        Err(QuartoError::Parse(parse_error)) => return Err(report_parse_error(&parse_error, args)),
         Err(e) => return Err(anyhow::anyhow!("{}", e)),
     };
 
     // Report diagnostics with full ariadne-style source context
-    if !args.quiet && !output.diagnostics.is_empty() {
-        for diagnostic in &output.diagnostics {
-            // Use the source context for rich error rendering with source snippets
-            eprintln!("{}", diagnostic.to_text(Some(&output.source_context)));
-        }
+    report_diagnostics(&output.diagnostics, &output.source_context, args);
+    shared.count_errors(&output.diagnostics);
+
+    if writes_to_stdout(args) {
+        use std::io::Write;
+        let mut stdout = std::io::stdout().lock();
+        stdout
+            .write_all(output.html.as_bytes())
+            .and_then(|()| stdout.flush())
+            .context("Failed to write output to stdout")?;
+        return Ok(());
     }
 
     // Write output
@@ -283,10 +657,50 @@ fn render_document(
     Ok(())
 }
 
+/// Whether a format renders to PDF (through LaTeX or Typst)
+fn is_pdf_format(identifier: FormatIdentifier) -> bool {
+    matches!(identifier, FormatIdentifier::Pdf | FormatIdentifier::Typst)
+}
+
+/// Render a prepared document to PDF through LaTeX or Typst
+fn render_pdf_document(
+    prepared: &PreparedDocument,
+    ctx: &mut RenderContext,
+    args: &RenderArgs,
+    shared: &SharedRenderState,
+    runtime_arc: &Arc<dyn SystemRuntime>,
+) -> Result<()> {
+    let config = PdfRenderConfig { debug: args.debug };
+
+    let output = match pollster::block_on(render_prepared_to_pdf(
+        prepared,
+        ctx,
+        &config,
+        runtime_arc.clone(),
+    )) {
+        Ok(output) => output,
+        // Parse and compile errors carry their own formatting
+        Err(QuartoError::Parse(parse_error)) => return Err(report_parse_error(&parse_error, args)),
+        Err(e) => return Err(anyhow::anyhow!("{}", e)),
+    };
+
+    report_diagnostics(&output.diagnostics, &output.source_context, args);
+    shared.count_errors(&output.diagnostics);
+    if !args.quiet {
+        for file in &output.supporting_files {
+            info!("Kept: {}", file.display());
+        }
+        info!("Output: {}", output.pdf_path.display());
+    }
+
+    Ok(())
+}
+
 /// Determine the output path for a render
 fn determine_output_path(ctx: &RenderContext, args: &RenderArgs) -> Result<PathBuf> {
     // Priority: --output > --output-dir > format default
-    if let Some(output) = &args.output {
+    // (`--output -` writes to stdout; resources still go next to the default path)
+    if let Some(output) = args.output.as_ref().filter(|_| !writes_to_stdout(args)) {
         return Ok(PathBuf::from(output));
     }
 
@@ -326,20 +740,35 @@ fn determine_output_path(ctx: &RenderContext, args: &RenderArgs) -> Result<PathB
 
 /// Write HTML resources with theme support.
 ///
-/// Extracts theme configuration from frontmatter and compiles SASS accordingly.
-/// Falls back to default CSS if no theme is specified or if compilation fails.
+/// Extracts theme configuration from frontmatter and compiles SASS accordingly,
+/// applying the `_brand.yml` of the project (or the document's directory) if
+/// there is one. Falls back to default CSS if no theme or brand is specified
+/// or if compilation fails.
 ///
 /// TODO(ConfigValue): Replace `content` parameter with `config: &ConfigValue`
 /// from merged project/document configuration, then use
 /// `ThemeConfig::from_config_value(config)` instead of `extract_theme_config()`.
-fn write_themed_resources(
+pub(crate) fn write_themed_resources(
     content: &str,
     input_path: &Path,
+    project_dir: &Path,
     output_dir: &Path,
     stem: &str,
     runtime: &dyn SystemRuntime,
     quiet: bool,
 ) -> Result<quarto_core::resources::HtmlResourcePaths> {
+    let document_dir = input_path
+        .parent()
+        .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+
+    // Brand problems are reported with source locations by the render
+    // pipeline; here an invalid brand is just left out of the CSS
+    let brand = find_brand_file(&[project_dir, &document_dir], runtime).and_then(|path| {
+        load_brand(path.as_path(), runtime, &mut SourceContext::new())
+            .map_err(|_| warn!("Ignoring invalid brand file {}", path.display()))
+            .ok()
+    });
+
     // Try to extract theme config from frontmatter
     let theme_config = match extract_theme_config(content) {
         Ok(Some(config)) => {
@@ -348,6 +777,7 @@ fn write_themed_resources(
             }
             config
         }
+        Ok(None) if brand.is_some() => ThemeConfig::default_bootstrap(),
         Ok(None) => {
             // No theme specified - use default static CSS
             debug!("No theme specified, using default CSS");
@@ -364,13 +794,15 @@ fn write_themed_resources(
                 .context("Failed to write default HTML resources");
         }
     };
+    let theme_config = match brand {
+        Some(brand) => theme_config.with_brand(brand),
+        None => theme_config,
+    };
 
-    // Create theme context with the document's directory
-    let document_dir = input_path
-        .parent()
-        .map(|p| p.to_path_buf())
-        .unwrap_or_else(|| PathBuf::from("."));
-    let context = ThemeContext::new(document_dir, runtime);
+    // Create theme context with the document's directory, caching compiled
+    // CSS under the project's .quarto/cache/sass/
+    let cache = SassCache::new(project_dir);
+    let context = ThemeContext::new(document_dir, runtime).with_cache(&cache);
 
     // Try to compile themed CSS
     match quarto_core::resources::write_html_resources_with_sass(
@@ -382,7 +814,7 @@ fn write_themed_resources(
     ) {
         Ok(paths) => {
             if !quiet {
-                info!("Compiled theme CSS successfully");
+                info!("Compiled theme CSS successfully (cache: {})", cache.stats());
             }
             Ok(paths)
         }
@@ -447,37 +879,62 @@ fn extract_theme_config(content: &str) -> Result<Option<ThemeConfig>> {
 /// `ThemeConfig::from_config_value()` in quarto-sass/src/config.rs.
 fn theme_value_to_config(value: &serde_yaml::Value) -> Result<ThemeConfig> {
     match value {
-        serde_yaml::Value::String(s) => {
-            // Single theme name: "darkly"
-            let spec =
-                ThemeSpec::parse(s).map_err(|e| anyhow::anyhow!("Invalid theme '{}': {}", s, e))?;
-            Ok(ThemeConfig::new(vec![spec], true))
-        }
-        serde_yaml::Value::Sequence(arr) => {
-            // Array of themes: ["darkly", "custom.scss"]
-            let mut themes = Vec::new();
-            for v in arr {
-                if let Some(s) = v.as_str() {
-                    let spec = ThemeSpec::parse(s)
-                        .map_err(|e| anyhow::anyhow!("Invalid theme '{}': {}", s, e))?;
-                    themes.push(spec);
-                }
+        serde_yaml::Value::Mapping(map) => {
+            // Light and dark themes: {light: cosmo, dark: [darkly, dark.scss]}
+            let light = map.get("light");
+            let dark = map.get("dark");
+            if light.is_none() && dark.is_none() {
+                anyhow::bail!("Invalid theme value: a theme map needs `light` or `dark`");
             }
+            let mut config = ThemeConfig::new(theme_value_to_specs(light)?, true);
+            config.dark_themes = theme_value_to_specs(dark)?;
+            Ok(config)
+        }
+        serde_yaml::Value::String(_) | serde_yaml::Value::Sequence(_) => {
+            let mut themes = theme_value_to_specs(Some(value))?;
             if themes.is_empty() {
                 anyhow::bail!("Empty theme array");
             }
-            Ok(ThemeConfig::new(themes, true))
+            // A built-in light theme followed by a built-in dark theme is a
+            // light/dark pair: [cosmo, darkly]
+            let is_dark = |spec: &ThemeSpec| spec.as_builtin().map(|theme| theme.is_dark());
+            let is_pair = matches!(
+                themes.as_slice(),
+                [light, dark] if is_dark(light) == Some(false) && is_dark(dark) == Some(true)
+            );
+            let dark_themes = if is_pair {
+                themes.split_off(1)
+            } else {
+                Vec::new()
+            };
+            let mut config = ThemeConfig::new(themes, true);
+            config.dark_themes = dark_themes;
+            Ok(config)
         }
         serde_yaml::Value::Null => {
             // Explicit null - use default Bootstrap
             Ok(ThemeConfig::default_bootstrap())
         }
         _ => {
-            anyhow::bail!("Invalid theme value: expected string, array, or null");
+            anyhow::bail!("Invalid theme value: expected string, array, map, or null");
         }
     }
 }
 
+/// Parse a theme name or array of theme names (`None` or null: no themes).
+fn theme_value_to_specs(value: Option<&serde_yaml::Value>) -> Result<Vec<ThemeSpec>> {
+    let parse =
+        |s: &str| ThemeSpec::parse(s).map_err(|e| anyhow::anyhow!("Invalid theme '{}': {}", s, e));
+    match value {
+        None | Some(serde_yaml::Value::Null) => Ok(Vec::new()),
+        Some(serde_yaml::Value::String(s)) => Ok(vec![parse(s)?]),
+        Some(serde_yaml::Value::Sequence(arr)) => {
+            arr.iter().filter_map(|v| v.as_str()).map(parse).collect()
+        }
+        Some(_) => anyhow::bail!("Invalid theme value: expected string or array"),
+    }
+}
+
 /// Escape HTML special characters
 #[cfg(test)]
 fn html_escape(s: &str) -> String {
@@ -514,6 +971,71 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_resolve_formats() {
+        let formats = resolve_formats("html, pdf", &[]).unwrap();
+        let identifiers: Vec<_> = formats.iter().map(|f| f.identifier).collect();
+        assert_eq!(identifiers, [FormatIdentifier::Html, FormatIdentifier::Pdf]);
+
+        assert!(resolve_formats(" , ", &[]).is_err());
+        assert!(resolve_formats("html,unknown", &[]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_extension_format() {
+        let extensions = [Extension {
+            id: quarto_core::extension::ExtensionId::new(None, "acm"),
+            dir: PathBuf::from("/ext/acm"),
+            manifest: quarto_core::extension::ExtensionManifest::parse(
+                "contributes:\n  formats:\n    pdf:\n      documentclass: acmart\n",
+            )
+            .unwrap(),
+        }];
+        let format = resolve_format_with_extensions("acm-pdf", &extensions).unwrap();
+        assert_eq!(format.identifier, FormatIdentifier::Pdf);
+        assert_eq!(format.name(), "acm-pdf");
+        assert_eq!(format.get_metadata_string("documentclass"), Some("acmart"));
+        assert_eq!(
+            resolve_format_with_extensions("html", &extensions)
+                .unwrap()
+                .name(),
+            "html"
+        );
+        assert!(resolve_format_with_extensions("acm-docx", &extensions).is_err());
+    }
+
+    #[test]
+    fn test_parse_execute_param() {
+        let (key, value) = parse_execute_param("alpha:0.5").unwrap();
+        assert_eq!(key, "alpha");
+        assert_eq!(value, serde_json::json!(0.5));
+
+        let (_, value) = parse_execute_param("show: true").unwrap();
+        assert_eq!(value, serde_json::json!(true));
+
+        let (_, value) = parse_execute_param("url:https://example.com").unwrap();
+        assert_eq!(value, serde_json::json!("https://example.com"));
+    }
+
+    #[test]
+    fn test_parse_metadata_override() {
+        let (key, value) = parse_key_value("metadata", "toc:true").unwrap();
+        assert_eq!(key, "toc");
+        assert_eq!(value, serde_json::json!(true));
+
+        let (_, value) = parse_key_value("metadata", "title:A *Title*").unwrap();
+        assert_eq!(value, serde_json::json!("A *Title*"));
+
+        let err = parse_key_value("metadata", "title").unwrap_err();
+        assert!(err.to_string().contains("Invalid metadata"));
+    }
+
+    #[test]
+    fn test_parse_execute_param_invalid() {
+        assert!(parse_execute_param("alpha").is_err());
+        assert!(parse_execute_param(":1").is_err());
+    }
+
     #[test]
     fn test_html_escape() {
         assert_eq!(html_escape("Hello & World"), "Hello &amp; World");
@@ -1,8 +1,187 @@
 //! Run command implementation
+//!
+//! `quarto run <script> [args...]` runs a script with the interpreter for
+//! its language, chosen by extension:
+//! - `.ts` with Deno (`QUARTO_DENO` or `deno` on the PATH)
+//! - `.R` with Rscript (`QUARTO_R` or `Rscript` on the PATH)
+//! - `.py` with Python (`QUARTO_PYTHON`, or `python3`/`python` on the PATH)
+//! - `.lua` with Pandoc's Lua interpreter (`pandoc lua`)
+//!
+//! The script inherits the terminal, along with `QUARTO_BIN_PATH` and
+//! `QUARTO_VERSION` so it can call back into Quarto. Its exit code becomes
+//! Quarto's.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 use anyhow::Result;
-use quarto_core::QuartoError;
+use quarto_core::BinaryDependencies;
+use quarto_core::engine::find_rscript;
+use quarto_system_runtime::{NativeRuntime, SystemRuntime};
+
+/// Arguments for the run command
+pub struct RunArgs {
+    pub script: Option<String>,
+    pub args: Vec<String>,
+}
+
+/// Language of a script, from its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Language {
+    TypeScript,
+    R,
+    Python,
+    Lua,
+}
+
+impl Language {
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "ts" => Some(Language::TypeScript),
+            "r" => Some(Language::R),
+            "py" => Some(Language::Python),
+            "lua" => Some(Language::Lua),
+            _ => None,
+        }
+    }
+
+    fn display_name(self) -> &'static str {
+        match self {
+            Language::TypeScript => "Deno",
+            Language::R => "Rscript",
+            Language::Python => "Python",
+            Language::Lua => "Pandoc",
+        }
+    }
+
+    /// Find the interpreter for the language.
+    fn interpreter(self, runtime: &dyn SystemRuntime) -> Option<PathBuf> {
+        match self {
+            Language::TypeScript => runtime.find_binary("deno", "QUARTO_DENO"),
+            Language::R => find_rscript(),
+            Language::Python => runtime
+                .find_binary("python3", "QUARTO_PYTHON")
+                .or_else(|| runtime.find_binary("python", "QUARTO_PYTHON")),
+            Language::Lua => BinaryDependencies::discover(runtime).pandoc,
+        }
+    }
+
+    /// Arguments that come before the script's path.
+    fn interpreter_args(self) -> &'static [&'static str] {
+        match self {
+            Language::TypeScript => &["run", "--allow-all"],
+            Language::Lua => &["lua"],
+            Language::R | Language::Python => &[],
+        }
+    }
+
+    /// How to make the interpreter available, for when it is missing.
+    fn install_hint(self) -> &'static str {
+        match self {
+            Language::TypeScript => {
+                "Install Deno (https://deno.com) or point QUARTO_DENO at the binary."
+            }
+            Language::R => "Install R (https://cran.r-project.org) or point QUARTO_R at it.",
+            Language::Python => {
+                "Install Python 3 (https://www.python.org) or point QUARTO_PYTHON at the binary."
+            }
+            Language::Lua => "Install Pandoc or point QUARTO_PANDOC at the binary.",
+        }
+    }
+}
+
+/// Build the command running `script` with `interpreter`.
+fn command(language: Language, interpreter: &Path, script: &Path, args: &[String]) -> Command {
+    let mut command = Command::new(interpreter);
+    command
+        .args(language.interpreter_args())
+        .arg(script)
+        .args(args)
+        .env("QUARTO_VERSION", quarto_util::cli_version());
+    if let Some(bin) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        command.env("QUARTO_BIN_PATH", bin);
+    }
+    command
+}
+
+/// Execute the run command
+pub fn execute(args: RunArgs) -> Result<()> {
+    let Some(script) = args.script else {
+        anyhow::bail!("No script given to run");
+    };
+    let script = PathBuf::from(script);
+    if !script.is_file() {
+        anyhow::bail!("Script not found: {}", script.display());
+    }
+    let Some(language) = Language::from_path(&script) else {
+        anyhow::bail!(
+            "Can't run {}: scripts must be TypeScript (.ts), R (.R), Python (.py) or Lua (.lua)",
+            script.display()
+        );
+    };
+
+    let runtime = NativeRuntime::new();
+    let Some(interpreter) = language.interpreter(&runtime) else {
+        anyhow::bail!(
+            "{} is needed to run {}, but it wasn't found. {}",
+            language.display_name(),
+            script.display(),
+            language.install_hint()
+        );
+    };
+
+    // The script owns the terminal: its output and exit code are passed through
+    let status = command(language, &interpreter, &script, &args.args)
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", interpreter.display(), e))?;
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+
+    #[test]
+    fn test_language_from_path() {
+        assert_eq!(
+            Language::from_path(Path::new("a.ts")),
+            Some(Language::TypeScript)
+        );
+        assert_eq!(Language::from_path(Path::new("a.R")), Some(Language::R));
+        assert_eq!(Language::from_path(Path::new("a.r")), Some(Language::R));
+        assert_eq!(
+            Language::from_path(Path::new("dir/a.py")),
+            Some(Language::Python)
+        );
+        assert_eq!(Language::from_path(Path::new("a.lua")), Some(Language::Lua));
+        assert_eq!(Language::from_path(Path::new("a.sh")), None);
+        assert_eq!(Language::from_path(Path::new("Makefile")), None);
+    }
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("run".to_string()).into())
+    #[test]
+    fn test_command() {
+        let args = vec!["--flag".to_string(), "x".to_string()];
+        let command = command(
+            Language::TypeScript,
+            Path::new("/usr/bin/deno"),
+            Path::new("script.ts"),
+            &args,
+        );
+        assert_eq!(command.get_program(), "/usr/bin/deno");
+        let given: Vec<OsString> = command.get_args().map(OsString::from).collect();
+        assert_eq!(given, ["run", "--allow-all", "script.ts", "--flag", "x"]);
+        assert!(
+            command
+                .get_envs()
+                .any(|(key, value)| key == "QUARTO_VERSION" && value.is_some())
+        );
+    }
 }
@@ -1,8 +1,46 @@
-//! Tools command implementation
+//! Tools command implementation.
+//!
+//! `quarto tools` shows, for each tool `quarto install` manages, the
+//! version installed, the latest version available and where renders find
+//! it. A tool found elsewhere (on `PATH` or through its environment
+//! variable) is shown as external. Latest versions are looked up online
+//! and shown as unknown when that fails.
 
 use anyhow::Result;
-use quarto_core::QuartoError;
+use quarto_core::BinaryDependencies;
+use quarto_core::tools::{Tool, install};
+use quarto_system_runtime::NativeRuntime;
 
+use super::list::print_table;
+
+/// Execute the tools command
 pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("tools".to_string()).into())
+    let runtime = NativeRuntime::new();
+    let binaries = BinaryDependencies::discover(&runtime);
+    let rows: Vec<[String; 4]> = Tool::ALL
+        .into_iter()
+        .map(|tool| {
+            let latest = install::latest_release(tool, &runtime)
+                .map_or_else(|_| "(unknown)".to_string(), |release| release.version);
+            let (installed, path) = match tool.installed(&runtime) {
+                Some(installed) => (installed.version, installed.dir.display().to_string()),
+                None => match binary(tool, &binaries) {
+                    Some(path) => ("(external)".to_string(), path),
+                    None => ("(not installed)".to_string(), String::new()),
+                },
+            };
+            [tool.name().to_string(), installed, latest, path]
+        })
+        .collect();
+    print_table(["Tool", "Installed", "Latest", "Path"], &rows);
+    Ok(())
+}
+
+/// The binary renders use for what `tool` provides.
+fn binary(tool: Tool, binaries: &BinaryDependencies) -> Option<String> {
+    let path = match tool {
+        Tool::TinyTex => binaries.xelatex.as_ref(),
+        Tool::Chromium => binaries.chromium.as_ref(),
+    };
+    path.map(|path| path.display().to_string())
 }
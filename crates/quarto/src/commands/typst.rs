@@ -1,8 +1,25 @@
 //! Typst command implementation
+//!
+//! Runs the Typst binary used for `format: typst` (`QUARTO_TYPST` or `typst`
+//! on the PATH), forwarding all arguments.
 
 use anyhow::Result;
-use quarto_core::QuartoError;
+use quarto_core::BinaryDependencies;
+use quarto_system_runtime::NativeRuntime;
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("typst".to_string()).into())
+pub fn execute(args: Vec<String>) -> Result<()> {
+    let runtime = NativeRuntime::new();
+    let Some(typst) = BinaryDependencies::discover(&runtime).typst else {
+        anyhow::bail!("Typst not found. Install Typst or point QUARTO_TYPST at the binary.");
+    };
+
+    // Typst owns the terminal: its output and exit code are passed through
+    let status = std::process::Command::new(&typst)
+        .args(&args)
+        .status()
+        .map_err(|e| anyhow::anyhow!("Failed to run {}: {}", typst.display(), e))?;
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
 }
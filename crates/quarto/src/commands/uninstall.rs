@@ -1,8 +1,26 @@
-//! Uninstall command implementation
+//! Uninstall command implementation.
+//!
+//! `quarto uninstall <tool>` removes a tool installed with
+//! `quarto install`.
 
 use anyhow::Result;
-use quarto_core::QuartoError;
+use quarto_core::tools::install;
+use quarto_system_runtime::NativeRuntime;
+use tracing::info;
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("uninstall".to_string()).into())
+use super::install::parse_tool;
+
+/// Execute the uninstall command
+pub fn execute(tool: Option<String>) -> Result<()> {
+    let Some(name) = tool else {
+        anyhow::bail!("No tool given to uninstall");
+    };
+    let tool = parse_tool(&name)?;
+    let runtime = NativeRuntime::new();
+    if install::uninstall(tool, &runtime)? {
+        info!("Uninstalled {}", tool);
+    } else {
+        info!("{} is not installed", tool);
+    }
+    Ok(())
 }
@@ -1,8 +1,125 @@
-//! Update command implementation
+//! Update command implementation.
+//!
+//! `quarto update [extension...]` reinstalls extensions whose source has
+//! moved on since they were added: the ref recorded in the extensions
+//! lockfile is resolved again and the extensions are reinstalled when it
+//! points to a new commit. Extensions are named as for `quarto remove`;
+//! `owner/repo@ref` switches an extension to another ref. Without
+//! arguments, every extension in the lockfile is updated.
+//!
+//! Tools (`tinytex`, `chromium`) are updated to their latest release as by
+//! `quarto install`.
+
+use std::collections::BTreeMap;
 
 use anyhow::Result;
-use quarto_core::QuartoError;
+use quarto_core::extension::install::{self, ExtensionSource};
+use quarto_core::extension::lock::ExtensionsLock;
+use quarto_core::tools::Tool;
+use quarto_system_runtime::NativeRuntime;
+use tracing::info;
+
+use super::add::{confirm_trust, extensions_dir, find_installed, report_fetch_error};
+use super::install::install_tool;
+
+/// Arguments for the update command
+#[derive(Debug)]
+pub struct UpdateArgs {
+    /// Extensions to update (all when empty)
+    pub targets: Vec<String>,
+    /// Update without asking for confirmation
+    pub no_prompt: bool,
+}
+
+/// Execute the update command
+pub fn execute(args: UpdateArgs) -> Result<()> {
+    let runtime = NativeRuntime::new();
+    let (tools, targets): (Vec<_>, Vec<_>) = args
+        .targets
+        .iter()
+        .partition(|target| Tool::parse(target).is_some());
+    for tool in tools.iter().filter_map(|tool| Tool::parse(tool)) {
+        install_tool(tool, &runtime)?;
+    }
+    if !tools.is_empty() && targets.is_empty() {
+        return Ok(());
+    }
+
+    let dir = extensions_dir()?;
+    let mut lock = ExtensionsLock::load(&dir, &runtime)?;
+
+    // Sources to update, by repository
+    let mut sources = BTreeMap::new();
+    if targets.is_empty() {
+        if lock.extensions.is_empty() {
+            info!("No extensions added with quarto add are installed");
+            return Ok(());
+        }
+        for locked in lock.extensions.values() {
+            sources.insert(
+                locked.source.clone(),
+                locked_source(&locked.source, &locked.reference)?,
+            );
+        }
+    }
+    for target in targets {
+        let explicit = ExtensionSource::parse(target).filter(|s| s.reference.is_some());
+        if let Some(source) = explicit {
+            sources.insert(source.repository(), source);
+            continue;
+        }
+        for id in find_installed(&dir, target, &lock)? {
+            let Some(locked) = lock.get(&id) else {
+                anyhow::bail!("{} was not added with quarto add and can't be updated", id);
+            };
+            sources.insert(
+                locked.source.clone(),
+                locked_source(&locked.source, &locked.reference)?,
+            );
+        }
+    }
+
+    for source in sources.values() {
+        let commit = install::resolve_commit(source, &runtime).map_err(report_fetch_error)?;
+        let current = lock.installed_from(&source.repository());
+        let up_to_date = !current.is_empty()
+            && current.iter().all(|id| {
+                lock.get(id)
+                    .is_some_and(|l| l.commit == commit && l.reference == source.reference)
+            });
+        if up_to_date {
+            info!("{} is up to date", source);
+            continue;
+        }
+
+        info!("Downloading {}", source);
+        let fetched = install::fetch(source, &commit, &runtime).map_err(report_fetch_error)?;
+        if !args.no_prompt && !confirm_trust(&fetched)? {
+            info!("Skipped {}", source);
+            continue;
+        }
+        install::install(&dir, &fetched, &mut lock, &runtime)?;
+        lock.save(&dir, &runtime)?;
+        for extension in &fetched.extensions {
+            info!(
+                "Updated {} to {}",
+                extension.id,
+                extension
+                    .manifest
+                    .version
+                    .as_deref()
+                    .unwrap_or(&commit[..commit.len().min(7)])
+            );
+        }
+    }
+    Ok(())
+}
 
-pub fn execute() -> Result<()> {
-    Err(QuartoError::NotImplemented("update".to_string()).into())
+/// The source of a lockfile entry.
+fn locked_source(repository: &str, reference: &Option<String>) -> Result<ExtensionSource> {
+    let mut source = ExtensionSource::parse(repository).ok_or_else(|| {
+        anyhow::anyhow!("Invalid source '{}' in the extensions lockfile", repository)
+    })?;
+    source.reference = reference.clone();
+    Ok(source)
 }
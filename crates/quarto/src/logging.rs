@@ -0,0 +1,358 @@
+//! Logging setup for the CLI.
+//!
+//! Log messages go to stderr, so command output written to stdout (such as
+//! `quarto render --output -`) stays clean. `quarto render`, `quarto preview`
+//! and `quarto hub` can additionally write the log to a file (`--log`),
+//! choose the level (`--log-level`) and emit one JSON object per message
+//! (`--log-format json-stream`).
+//!
+//! Messages carry the tracing spans they were emitted in, such as the
+//! `render` span of a document and the `stage` span of a pipeline stage.
+//! Log files are meant to be shared (attached to bug reports, say), so the
+//! user's home directory is replaced by `~` in them.
+//!
+//! `RUST_LOG` takes precedence over `--log-level` when set.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context as LayerContext, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer, Registry};
+
+/// Format of log messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Plain,
+    /// One JSON object per line
+    JsonStream,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Self::Plain),
+            "json-stream" => Ok(Self::JsonStream),
+            other => Err(format!(
+                "invalid log format '{}' (expected plain or json-stream)",
+                other
+            )),
+        }
+    }
+}
+
+/// Parse a `--log-level` value (Quarto's level names).
+pub fn parse_log_level(s: &str) -> Result<Level, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "debug" => Ok(Level::DEBUG),
+        "info" => Ok(Level::INFO),
+        "warning" | "warn" => Ok(Level::WARN),
+        "error" | "critical" => Ok(Level::ERROR),
+        other => Err(format!(
+            "invalid log level '{}' (expected debug, info, warning, error or critical)",
+            other
+        )),
+    }
+}
+
+/// Logging options from the command line.
+#[derive(Debug, Clone, Default)]
+pub struct LogOptions {
+    /// Also write the log to this file (`--log`)
+    pub file: Option<PathBuf>,
+    /// Most verbose level to log (`--log-level`); defaults to info
+    pub level: Option<Level>,
+    /// Format of log messages (`--log-format`)
+    pub format: LogFormat,
+    /// Only log errors to the console (`--quiet`); the log file is unaffected
+    pub quiet: bool,
+}
+
+/// Install the global tracing subscriber.
+pub fn init(options: &LogOptions) -> Result<()> {
+    let level = options.level.unwrap_or(Level::INFO);
+    let console_level = if options.quiet { Level::ERROR } else { level };
+
+    let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> = vec![
+        output_layer(options.format, std::io::stderr)
+            .with_filter(filter(console_level))
+            .boxed(),
+    ];
+    if let Some(path) = &options.file {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create log file {}", path.display()))?;
+        let file = RedactingWriter {
+            writer: file,
+            home: home_dir(),
+        };
+        layers.push(
+            output_layer(options.format, Mutex::new(file))
+                .with_filter(filter(level))
+                .boxed(),
+        );
+    }
+
+    tracing_subscriber::registry().with(layers).init();
+    Ok(())
+}
+
+/// The filter for a log output: `RUST_LOG` if set, otherwise `level` for
+/// the `quarto*` crates.
+fn filter(level: Level) -> EnvFilter {
+    EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| format!("quarto={}", level.as_str().to_ascii_lowercase()).into())
+}
+
+/// A layer writing messages to `writer` in `format`.
+fn output_layer<W>(format: LogFormat, writer: W) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'w> tracing_subscriber::fmt::MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Plain => tracing_subscriber::fmt::layer().with_writer(writer).boxed(),
+        LogFormat::JsonStream => JsonLayer { writer }.boxed(),
+    }
+}
+
+/// Writes each event as a JSON object on its own line:
+/// `{"level": "INFO", "target": "quarto::commands::render", "message": "...", ...fields}`,
+/// with the fields of the spans it was emitted in, outermost first, as
+/// `"spans": [{"span": "render", "input": "...", "format": "html"}, ...]`.
+struct JsonLayer<W> {
+    writer: W,
+}
+
+impl<S, W> Layer<S> for JsonLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'w> tracing_subscriber::fmt::MakeWriter<'w> + Send + Sync + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: LayerContext<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = JsonFields::default();
+        fields.0.insert("span".to_string(), span.name().into());
+        attrs.record(&mut fields);
+        span.extensions_mut().insert(fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: LayerContext<'_, S>) {
+        if let Some(span) = ctx.span(id)
+            && let Some(fields) = span.extensions_mut().get_mut::<JsonFields>()
+        {
+            values.record(fields);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: LayerContext<'_, S>) {
+        let metadata = event.metadata();
+        let mut fields = JsonFields::default();
+        fields
+            .0
+            .insert("level".to_string(), metadata.level().as_str().into());
+        fields
+            .0
+            .insert("target".to_string(), metadata.target().into());
+        event.record(&mut fields);
+
+        let spans: Vec<serde_json::Value> = ctx
+            .event_scope(event)
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+            .filter_map(|span| {
+                let extensions = span.extensions();
+                let fields = extensions.get::<JsonFields>()?;
+                Some(serde_json::Value::Object(fields.0.clone()))
+            })
+            .collect();
+        if !spans.is_empty() {
+            fields.0.insert("spans".to_string(), spans.into());
+        }
+
+        let mut line = serde_json::Value::Object(fields.0).to_string();
+        line.push('\n');
+        let _ = std::io::Write::write_all(&mut self.writer.make_writer(), line.as_bytes());
+    }
+}
+
+/// Collects the fields of an event into a JSON object.
+#[derive(Default)]
+struct JsonFields(serde_json::Map<String, serde_json::Value>);
+
+impl Visit for JsonFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
+
+/// Writes to a log file, replacing the user's home directory with `~`.
+///
+/// Events are formatted before they're written, so each write holds whole
+/// lines and paths aren't split across writes.
+struct RedactingWriter<W> {
+    writer: W,
+    home: Option<String>,
+}
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &self.home {
+            Some(home) => {
+                let text = redact_home(&String::from_utf8_lossy(buf), home);
+                self.writer.write_all(text.as_bytes())?;
+            }
+            None => self.writer.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// The user's home directory, if it is worth redacting (not the root).
+fn home_dir() -> Option<String> {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()?;
+    let home = home.trim_end_matches(['/', '\\']);
+    (home.len() > 1).then(|| home.to_string())
+}
+
+/// Replace `home` with `~` in `text` wherever it starts a path, including
+/// in JSON strings (where Windows separators are escaped).
+fn redact_home(text: &str, home: &str) -> String {
+    let escaped = home.replace('\\', "\\\\");
+    let text = redact_prefix(text, home);
+    if escaped == home {
+        text
+    } else {
+        redact_prefix(&text, &escaped)
+    }
+}
+
+/// Replace `prefix` with `~` in `text` where it isn't followed by more of a
+/// file name (so `/home/al` is kept in `/home/alice`).
+fn redact_prefix(text: &str, prefix: &str) -> String {
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find(prefix) {
+        redacted.push_str(&rest[..idx]);
+        rest = &rest[idx + prefix.len()..];
+        let continues = rest
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+        redacted.push_str(if continues { prefix } else { "~" });
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_options() {
+        assert_eq!(parse_log_level("warning"), Ok(Level::WARN));
+        assert_eq!(parse_log_level("CRITICAL"), Ok(Level::ERROR));
+        assert!(parse_log_level("verbose").is_err());
+
+        assert_eq!("json-stream".parse(), Ok(LogFormat::JsonStream));
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_json_fields() {
+        let fields = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let writer = {
+            let fields = fields.clone();
+            move || TestWriter(fields.clone())
+        };
+        let subscriber = tracing_subscriber::registry().with(JsonLayer { writer });
+        tracing::subscriber::with_default(subscriber, || {
+            let _render = tracing::info_span!("render", input = "a.qmd").entered();
+            let _stage = tracing::info_span!("stage", name = "parse").entered();
+            tracing::warn!(path = "a.qmd", count = 2, "Rendered");
+        });
+
+        let output = String::from_utf8(fields.lock().unwrap().clone()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(value["level"], "WARN");
+        assert_eq!(value["message"], "Rendered");
+        assert_eq!(value["path"], "a.qmd");
+        assert_eq!(value["count"], 2);
+        assert_eq!(
+            value["spans"],
+            serde_json::json!([
+                {"span": "render", "input": "a.qmd"},
+                {"span": "stage", "name": "parse"},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_redact_home() {
+        assert_eq!(
+            redact_home("Rendered /home/alice/doc.qmd to /home/alice", "/home/alice"),
+            "Rendered ~/doc.qmd to ~"
+        );
+        assert_eq!(
+            redact_home("/home/alice2/doc.qmd", "/home/alice"),
+            "/home/alice2/doc.qmd"
+        );
+        assert_eq!(
+            redact_home(r#"{"path":"C:\\Users\\bob\\doc.qmd"}"#, r"C:\Users\bob"),
+            r#"{"path":"~\\doc.qmd"}"#
+        );
+    }
+
+    struct TestWriter(std::sync::Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for TestWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
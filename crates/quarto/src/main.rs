@@ -4,9 +4,9 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 mod commands;
+mod logging;
 
 #[derive(Parser)]
 #[command(name = "quarto")]
@@ -48,6 +48,10 @@ enum Commands {
         #[arg(long)]
         execute: bool,
 
+        /// Skip code execution
+        #[arg(long, conflicts_with = "execute")]
+        no_execute: bool,
+
         /// Execution parameter (KEY:VALUE)
         #[arg(short = 'P', long)]
         execute_param: Vec<String>,
@@ -80,6 +84,10 @@ enum Commands {
         #[arg(long)]
         cache: bool,
 
+        /// Do not cache execution output
+        #[arg(long, conflicts_with = "cache")]
+        no_cache: bool,
+
         /// Force refresh of execution cache
         #[arg(long)]
         cache_refresh: bool,
@@ -94,15 +102,15 @@ enum Commands {
 
         /// Path to log file
         #[arg(long)]
-        log: Option<String>,
+        log: Option<PathBuf>,
 
         /// Log level (debug, info, warning, error, critical)
-        #[arg(long)]
-        log_level: Option<String>,
+        #[arg(long, value_parser = logging::parse_log_level)]
+        log_level: Option<tracing::Level>,
 
         /// Log format (plain, json-stream)
-        #[arg(long)]
-        log_format: Option<String>,
+        #[arg(long, default_value = "plain")]
+        log_format: logging::LogFormat,
 
         /// Suppress console output
         #[arg(long)]
@@ -156,15 +164,15 @@ enum Commands {
 
         /// Path to log file
         #[arg(long)]
-        log: Option<String>,
+        log: Option<PathBuf>,
 
         /// Log level (debug, info, warning, error, critical)
-        #[arg(long)]
-        log_level: Option<String>,
+        #[arg(long, value_parser = logging::parse_log_level)]
+        log_level: Option<tracing::Level>,
 
         /// Log format (plain, json-stream)
-        #[arg(long)]
-        log_format: Option<String>,
+        #[arg(long, default_value = "plain")]
+        log_format: logging::LogFormat,
 
         /// Suppress console output
         #[arg(long)]
@@ -195,13 +203,29 @@ enum Commands {
 
     /// Create a Quarto project or extension
     Create {
-        /// Type of project or extension to create
+        /// What to create (project)
         #[arg(value_name = "TYPE")]
         type_: Option<String>,
 
-        /// Additional arguments
+        /// Project type (default, website, blog), directory and title
         #[arg(trailing_var_arg = true)]
         args: Vec<String>,
+
+        /// Engine of the project's documents (markdown, jupyter, knitr)
+        #[arg(long)]
+        engine: Option<String>,
+
+        /// Initialize a git repository in the project
+        #[arg(long)]
+        git: bool,
+
+        /// Don't ask for what the arguments leave out
+        #[arg(long)]
+        no_prompt: bool,
+
+        /// Print the created files as JSON (with --no-prompt)
+        #[arg(long)]
+        json: bool,
     },
 
     /// Automate document or project setup tasks
@@ -216,15 +240,23 @@ enum Commands {
 
     /// Add an extension to this folder or project
     Add {
-        /// Extension to add
+        /// Extension to add, as a GitHub repository (owner/repo[@ref])
         extension: String,
+
+        /// Install without asking whether to trust the extension
+        #[arg(long)]
+        no_prompt: bool,
     },
 
     /// Updates an extension or global dependency
     Update {
-        /// Targets to update
+        /// Targets to update (all extensions when omitted)
         #[arg(trailing_var_arg = true)]
         target: Vec<String>,
+
+        /// Update without asking whether to trust the extensions
+        #[arg(long)]
+        no_prompt: bool,
     },
 
     /// Removes an extension
@@ -236,12 +268,16 @@ enum Commands {
 
     /// Convert documents to alternate representations
     Convert {
-        /// Input file to convert
+        /// Input file to convert (.ipynb or .qmd)
         input: String,
 
-        /// Output format
+        /// Output file (defaults to the input with the other extension)
         #[arg(long)]
         output: Option<String>,
+
+        /// Don't keep the outputs of an existing notebook when converting to .ipynb
+        #[arg(long)]
+        strip_outputs: bool,
     },
 
     /// Run the version of Pandoc embedded within Quarto
@@ -282,7 +318,7 @@ enum Commands {
         target: Vec<String>,
     },
 
-    /// Removes an extension
+    /// Removes a global dependency (TinyTex or Chromium)
     Uninstall {
         /// Tool to uninstall
         tool: Option<String>,
@@ -293,25 +329,38 @@ enum Commands {
 
     /// Publish a document or project to a provider
     Publish {
-        /// Provider to publish to
+        /// Provider to publish to (gh-pages, netlify)
         provider: Option<String>,
 
         /// Path to publish
         path: Option<String>,
+
+        /// Site to publish to: a Netlify site id, or the git remote for gh-pages
+        #[arg(long)]
+        id: Option<String>,
+
+        /// Publish the existing output without rendering first
+        #[arg(long)]
+        no_render: bool,
+
+        /// Show what would be published without rendering or deploying
+        #[arg(long)]
+        dry_run: bool,
     },
 
-    /// Verify correct functioning of Quarto installation
+    /// Verify correct functioning of Quarto installation, or lint the prose
+    /// of a document or project
     Check {
-        /// Target to check
+        /// Target to check: a document or project directory
         target: Option<String>,
     },
 
     /// Access functions of Quarto subsystems such as its rendering engines
     Call {
-        /// Function to call
+        /// Function to call (serves JSON-RPC on stdin/stdout when omitted)
         function: Option<String>,
 
-        /// Arguments for the function
+        /// Parameters of the function, as a JSON object
         #[arg(trailing_var_arg = true)]
         args: Vec<String>,
     },
@@ -321,9 +370,16 @@ enum Commands {
 
     /// Start collaborative hub server for real-time editing
     Hub {
-        /// Project root directory (defaults to current directory)
-        #[arg(short, long)]
-        project: Option<PathBuf>,
+        /// Project root directory (defaults to current directory).
+        /// Can be specified multiple times to serve several projects, each
+        /// under /projects/{name} where name is the directory name.
+        #[arg(short, long = "project", value_name = "DIR")]
+        projects: Vec<PathBuf>,
+
+        /// JSON registry of projects to serve, each under /projects/{name}.
+        /// Combined with any --project directories.
+        #[arg(long, value_name = "PATH")]
+        projects_file: Option<PathBuf>,
 
         /// Port to listen on
         #[arg(short = 'P', long, default_value = "3000")]
@@ -352,73 +408,251 @@ enum Commands {
         /// Debounce duration for filesystem events in milliseconds.
         #[arg(long, default_value = "500")]
         watch_debounce: u64,
+
+        /// JSON file of bearer tokens and their roles (read-only, editor, admin).
+        /// When any token is configured, all REST and WebSocket requests must
+        /// authenticate. A token in QUARTO_HUB_AUTH_TOKEN is added with admin
+        /// access to every project.
+        #[arg(long, value_name = "PATH")]
+        auth_file: Option<PathBuf>,
+
+        /// Maximum size of synced files in MiB (0 for no limit).
+        #[arg(long, value_name = "MIB", default_value = "10")]
+        max_file_size: u64,
+
+        /// Which binary files to sync: all, images or none.
+        #[arg(long, value_name = "POLICY", default_value = "all")]
+        binary_files: quarto_hub::ignore::BinaryPolicy,
+
+        /// Path to log file
+        #[arg(long)]
+        log: Option<PathBuf>,
+
+        /// Log level (debug, info, warning, error, critical)
+        #[arg(long, value_parser = logging::parse_log_level)]
+        log_level: Option<tracing::Level>,
+
+        /// Log format (plain, json-stream)
+        #[arg(long, default_value = "plain")]
+        log_format: logging::LogFormat,
     },
 }
 
 fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "quarto=info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
     let cli = Cli::parse();
 
-    match cli.command {
+    // Initialize logging
+    let log_options = match &cli.command {
+        Commands::Render {
+            log,
+            log_level,
+            log_format,
+            quiet,
+            ..
+        }
+        | Commands::Preview {
+            log,
+            log_level,
+            log_format,
+            quiet,
+            ..
+        } => logging::LogOptions {
+            file: log.clone(),
+            level: *log_level,
+            format: *log_format,
+            quiet: *quiet,
+        },
+        Commands::Hub {
+            log,
+            log_level,
+            log_format,
+            ..
+        } => logging::LogOptions {
+            file: log.clone(),
+            level: *log_level,
+            format: *log_format,
+            quiet: false,
+        },
+        _ => logging::LogOptions::default(),
+    };
+    logging::init(&log_options)?;
+
+    let result = match cli.command {
         Commands::Render {
             input,
             to,
             output,
             output_dir,
+            metadata,
+            log_format,
             quiet,
             debug,
+            no_execute,
+            execute_param,
+            execute_params,
+            execute_dir,
+            execute_daemon,
+            execute_daemon_restart,
+            cache,
+            no_cache,
+            cache_refresh,
+            no_clean,
+            profile,
             ..
         } => commands::render::execute(commands::render::RenderArgs {
             input,
             to,
             output,
             output_dir,
+            metadata,
+            log_format,
             quiet,
             debug,
+            execute: !no_execute,
+            execute_param,
+            execute_params,
+            execute_dir,
+            execute_daemon,
+            execute_daemon_restart,
+            cache: match (cache, no_cache) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            cache_refresh,
+            incremental: no_clean,
+            profiles: profile,
+        }),
+        Commands::Preview {
+            file,
+            port,
+            host,
+            render,
+            no_serve,
+            no_navigate,
+            no_browser,
+            no_watch_inputs,
+            log_format,
+            quiet,
+            profile,
+            ..
+        } => commands::preview::execute(commands::preview::PreviewArgs {
+            file,
+            port,
+            host,
+            render,
+            no_serve,
+            no_navigate,
+            no_browser,
+            no_watch_inputs,
+            log_format,
+            quiet,
+            profiles: profile,
         }),
-        Commands::Preview { .. } => commands::preview::execute(),
         Commands::Serve { .. } => commands::serve::execute(),
-        Commands::Create { .. } => commands::create::execute(),
+        Commands::Create {
+            type_,
+            args,
+            engine,
+            git,
+            no_prompt,
+            json,
+        } => commands::create::execute(commands::create::CreateArgs {
+            kind: type_,
+            args,
+            engine,
+            git,
+            no_prompt,
+            json,
+        }),
         Commands::Use { .. } => commands::use_cmd::execute(),
-        Commands::Add { .. } => commands::add::execute(),
-        Commands::Update { .. } => commands::update::execute(),
-        Commands::Remove { .. } => commands::remove::execute(),
-        Commands::Convert { .. } => commands::convert::execute(),
+        Commands::Add {
+            extension,
+            no_prompt,
+        } => commands::add::execute(commands::add::AddArgs {
+            extension,
+            no_prompt,
+        }),
+        Commands::Update { target, no_prompt } => {
+            commands::update::execute(commands::update::UpdateArgs {
+                targets: target,
+                no_prompt,
+            })
+        }
+        Commands::Remove { target } => {
+            commands::remove::execute(commands::remove::RemoveArgs { targets: target })
+        }
+        Commands::Convert {
+            input,
+            output,
+            strip_outputs,
+        } => commands::convert::execute(commands::convert::ConvertArgs {
+            input,
+            output,
+            strip_outputs,
+        }),
         Commands::Pandoc { .. } => commands::pandoc::execute(),
-        Commands::Typst { .. } => commands::typst::execute(),
-        Commands::Run { .. } => commands::run::execute(),
-        Commands::List { .. } => commands::list::execute(),
-        Commands::Install { .. } => commands::install::execute(),
-        Commands::Uninstall { .. } => commands::uninstall::execute(),
+        Commands::Typst { args } => commands::typst::execute(args),
+        Commands::Run { script, args } => {
+            commands::run::execute(commands::run::RunArgs { script, args })
+        }
+        Commands::List { type_ } => commands::list::execute(type_),
+        Commands::Install { target } => {
+            commands::install::execute(commands::install::InstallArgs { targets: target })
+        }
+        Commands::Uninstall { tool } => commands::uninstall::execute(tool),
         Commands::Tools => commands::tools::execute(),
-        Commands::Publish { .. } => commands::publish::execute(),
-        Commands::Check { .. } => commands::check::execute(),
-        Commands::Call { .. } => commands::call::execute(),
+        Commands::Publish {
+            provider,
+            path,
+            id,
+            no_render,
+            dry_run,
+        } => commands::publish::execute(commands::publish::PublishArgs {
+            provider,
+            path,
+            id,
+            no_render,
+            dry_run,
+        }),
+        Commands::Check { target } => commands::check::execute(target),
+        Commands::Call { function, args } => {
+            commands::call::execute(commands::call::CallArgs { function, args })
+        }
         Commands::Lsp => commands::lsp::execute(),
         Commands::Hub {
-            project,
+            projects,
+            projects_file,
             port,
             host,
             peers,
             sync_interval,
             no_watch,
             watch_debounce,
+            auth_file,
+            max_file_size,
+            binary_files,
+            ..
         } => commands::hub::execute(commands::hub::HubArgs {
-            project,
+            projects,
+            projects_file,
             port,
             host,
             peers,
             sync_interval,
             no_watch,
             watch_debounce,
+            auth_file,
+            max_file_size,
+            binary_files,
         }),
+    };
+
+    // Diagnostics have already been printed with their source context
+    if let Err(e) = &result
+        && e.is::<commands::render::DiagnosticsReported>()
+    {
+        std::process::exit(1);
     }
+    result
 }
@@ -98,6 +98,7 @@ fn run_render(input_path: &Path, output_path: &Path) -> Result<(), String> {
         execute: false,
         use_freeze: false,
         output_path: Some(output_path.to_path_buf()),
+        ..Default::default()
     };
     let mut ctx = RenderContext::new(&project, &doc_info, &format, &binaries).with_options(options);
 
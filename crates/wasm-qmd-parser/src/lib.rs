@@ -22,6 +22,7 @@ use std::panic;
 use pampa::readers;
 use pampa::wasm_entry_points;
 use pampa::writers;
+use quarto_wasm_protocol::{ConvertResponse, PROTOCOL_VERSION};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(start)]
@@ -30,6 +31,12 @@ pub fn run() {
     panic::set_hook(Box::new(console_error_panic_hook::hook));
 }
 
+/// Version of the JSON response types (see `quarto-wasm-protocol`).
+#[wasm_bindgen]
+pub fn protocol_version() -> u32 {
+    PROTOCOL_VERSION
+}
+
 fn json_to_pandoc(
     input: &str,
 ) -> Result<(pampa::pandoc::Pandoc, pampa::pandoc::ASTContext), String> {
@@ -59,23 +66,6 @@ fn pandoc_to_json(
     }
 }
 
-fn pandoc_to_qmd(doc: &pampa::pandoc::Pandoc) -> Result<String, String> {
-    let mut buf = Vec::new();
-    match writers::qmd::write(doc, &mut buf) {
-        Ok(_) => {
-            // Nothing to do
-        }
-        Err(err) => {
-            return Err(format!("Unable to write as qmd: {:?}", err));
-        }
-    }
-
-    match String::from_utf8(buf) {
-        Ok(qmd) => Ok(qmd),
-        Err(err) => Err(format!("Unable to convert qmd to string: {:?}", err)),
-    }
-}
-
 #[wasm_bindgen]
 pub fn parse_qmd(input: JsValue, include_resolved_locations: JsValue) -> JsValue {
     let input = as_string(&input, "input");
@@ -93,22 +83,58 @@ pub fn write_qmd(input: JsValue) -> JsValue {
     JsValue::from_str(&json)
 }
 
+/// Convert a document between formats.
+///
+/// Returns the converted document. Failures (e.g. an unknown format) throw
+/// the `{ "error": "...", "diagnostics": [...] }` JSON of
+/// [`convert_with_options`] instead of aborting the module.
+#[wasm_bindgen]
+pub fn convert(
+    document: JsValue,
+    input_format: JsValue,
+    output_format: JsValue,
+) -> Result<JsValue, JsValue> {
+    let input = as_string(&document, "document");
+    let input_format = as_string(&input_format, "input_format");
+    let output_format = as_string(&output_format, "output_format");
+    let result = wasm_entry_points::convert(input.as_bytes(), &input_format, &output_format, "");
+    match serde_json::from_str::<ConvertResponse>(&result) {
+        Ok(ConvertResponse {
+            output: Some(output),
+            ..
+        }) => Ok(JsValue::from_str(&output)),
+        _ => Err(JsValue::from_str(&result)),
+    }
+}
+
+/// Convert a document between formats, with options.
+///
+/// # Arguments
+/// * `document` - Source text
+/// * `input_format` - "qmd" or "json", with optional `+ext`/`-ext` modifiers
+/// * `output_format` - "qmd", "json", "html" or "plaintext"
+/// * `options_json` - Options JSON, e.g. `{"loose": true, "json_source_location": "full", "source_positions": true}`
+///
+/// # Returns
+/// `ConvertResponse` JSON: `{ "output": "...", "diagnostics": [...] }` or `{ "error": "...", "diagnostics": [...] }`
 #[wasm_bindgen]
-pub fn convert(document: JsValue, input_format: JsValue, output_format: JsValue) -> JsValue {
+pub fn convert_with_options(
+    document: JsValue,
+    input_format: JsValue,
+    output_format: JsValue,
+    options_json: JsValue,
+) -> JsValue {
     let input = as_string(&document, "document");
     let input_format = as_string(&input_format, "input_format");
     let output_format = as_string(&output_format, "output_format");
-    let (doc, context) = match input_format.as_str() {
-        "qmd" => wasm_entry_points::qmd_to_pandoc(input.as_bytes()).unwrap(),
-        "json" => json_to_pandoc(&input).unwrap(),
-        _ => panic!("Unsupported input format: {}", input_format),
-    };
-    let output = match output_format.as_str() {
-        "qmd" => pandoc_to_qmd(&doc).unwrap(),
-        "json" => pandoc_to_json(&doc, &context).unwrap(),
-        _ => panic!("Unsupported output format: {}", output_format),
-    };
-    JsValue::from_str(&output)
+    let options_json = options_json.as_string().unwrap_or_default();
+    let result = wasm_entry_points::convert(
+        input.as_bytes(),
+        &input_format,
+        &output_format,
+        &options_json,
+    );
+    JsValue::from_str(&result)
 }
 
 fn as_string(value: &JsValue, name: &str) -> String {
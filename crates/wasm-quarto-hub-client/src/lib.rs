@@ -14,33 +14,49 @@
 #[cfg(target_arch = "wasm32")]
 pub mod c_shim;
 
-use std::path::Path;
-use std::sync::{Arc, OnceLock};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 
+use quarto_core::stage::{Cancellation, NoopObserver, PipelineObserver};
 use quarto_core::{
     BinaryDependencies, DocumentInfo, Format, HtmlRenderConfig, ProjectConfig, ProjectContext,
     QuartoError, RenderContext, RenderOptions, extract_format_metadata, render_qmd_to_html,
 };
 use quarto_error_reporting::{DiagnosticKind, DiagnosticMessage};
 use quarto_pandoc_types::ConfigValue;
+use quarto_preview::render_outputs;
+use quarto_preview::render_patch::{self, RenderState};
 use quarto_sass::{
     BOOTSTRAP_RESOURCES, RESOURCE_PATH_PREFIX, THEMES_RESOURCES, ThemeConfig, ThemeContext,
     compile_theme_css, themes::ThemeSpec,
 };
 use quarto_source_map::SourceContext;
-use quarto_system_runtime::{SystemRuntime, WasmRuntime};
+use quarto_system_runtime::{PathKind, SystemRuntime, WasmRuntime};
+use quarto_wasm_protocol::{
+    AsyncRenderResponse, DetailKind, DiagnosticKind as JsonDiagnosticKind,
+    IncrementalRenderResponse, JsonDiagnostic, JsonDiagnosticDetail, PROTOCOL_VERSION,
+    ProjectRenderResponse, RenderPatch, RenderProgress, RenderResponse, RenderedDocument,
+    VfsMetadata, VfsPathKind, VfsResponse, to_json,
+};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
 // Global runtime instance for VFS operations
-static RUNTIME: OnceLock<WasmRuntime> = OnceLock::new();
+static RUNTIME: OnceLock<Arc<WasmRuntime>> = OnceLock::new();
 
 fn get_runtime() -> &'static WasmRuntime {
+    get_shared_runtime()
+}
+
+/// The global runtime, for pipelines that read other files of the VFS
+/// (includes, project configuration).
+fn get_shared_runtime() -> &'static Arc<WasmRuntime> {
     RUNTIME.get_or_init(|| {
         let runtime = WasmRuntime::new();
         // Populate VFS with embedded Bootstrap SCSS resources
         populate_vfs_with_embedded_resources(&runtime);
-        runtime
+        Arc::new(runtime)
     })
 }
 
@@ -65,61 +81,13 @@ pub fn init() {
     console_error_panic_hook::set_once();
 }
 
-// ============================================================================
-// RESPONSE TYPES
-// ============================================================================
-
-#[derive(Serialize, Deserialize)]
-struct VfsResponse {
-    success: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    files: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
-}
-
-impl VfsResponse {
-    fn ok() -> String {
-        serde_json::to_string(&VfsResponse {
-            success: true,
-            error: None,
-            files: None,
-            content: None,
-        })
-        .unwrap()
-    }
-
-    fn error(msg: &str) -> String {
-        serde_json::to_string(&VfsResponse {
-            success: false,
-            error: Some(msg.to_string()),
-            files: None,
-            content: None,
-        })
-        .unwrap()
-    }
-
-    fn with_files(paths: Vec<String>) -> String {
-        serde_json::to_string(&VfsResponse {
-            success: true,
-            error: None,
-            files: Some(paths),
-            content: None,
-        })
-        .unwrap()
-    }
-
-    fn with_content(text: String) -> String {
-        serde_json::to_string(&VfsResponse {
-            success: true,
-            error: None,
-            files: None,
-            content: Some(text),
-        })
-        .unwrap()
-    }
+/// Version of the JSON response types (see `quarto-wasm-protocol`).
+///
+/// Clients compare it with the `PROTOCOL_VERSION` their types were generated
+/// for.
+#[wasm_bindgen]
+pub fn protocol_version() -> u32 {
+    PROTOCOL_VERSION
 }
 
 // ============================================================================
@@ -137,7 +105,7 @@ impl VfsResponse {
 #[wasm_bindgen]
 pub fn vfs_add_file(path: &str, content: &str) -> String {
     get_runtime().add_file(Path::new(path), content.as_bytes().to_vec());
-    VfsResponse::ok()
+    to_json(&VfsResponse::ok())
 }
 
 /// Add a binary file to the virtual filesystem.
@@ -151,7 +119,7 @@ pub fn vfs_add_file(path: &str, content: &str) -> String {
 #[wasm_bindgen]
 pub fn vfs_add_binary_file(path: &str, content: &[u8]) -> String {
     get_runtime().add_file(Path::new(path), content.to_vec());
-    VfsResponse::ok()
+    to_json(&VfsResponse::ok())
 }
 
 /// Remove a file from the virtual filesystem.
@@ -164,9 +132,9 @@ pub fn vfs_add_binary_file(path: &str, content: &[u8]) -> String {
 #[wasm_bindgen]
 pub fn vfs_remove_file(path: &str) -> String {
     if get_runtime().remove_file(Path::new(path)) {
-        VfsResponse::ok()
+        to_json(&VfsResponse::ok())
     } else {
-        VfsResponse::error("File not found")
+        to_json(&VfsResponse::error("File not found"))
     }
 }
 
@@ -181,7 +149,120 @@ pub fn vfs_list_files() -> String {
         .into_iter()
         .map(|p| p.to_string_lossy().to_string())
         .collect();
-    VfsResponse::with_files(paths)
+    to_json(&VfsResponse::with_files(paths))
+}
+
+/// List the files matching a glob pattern.
+///
+/// `**` matches any number of directories; `*` and `?` match characters
+/// within a file or directory name.
+///
+/// # Arguments
+/// * `pattern` - Glob pattern (e.g., "posts/**/*.qmd")
+///
+/// # Returns
+/// JSON: `{ "success": true, "files": ["path1", "path2", ...] }`
+#[wasm_bindgen]
+pub fn vfs_glob(pattern: &str) -> String {
+    let paths = get_runtime()
+        .glob_files(pattern)
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    to_json(&VfsResponse::with_files(paths))
+}
+
+/// List the files under a directory, recursively.
+///
+/// # Arguments
+/// * `dir` - Directory path (e.g., "posts")
+///
+/// # Returns
+/// JSON: `{ "success": true, "files": ["path1", "path2", ...] }`
+#[wasm_bindgen]
+pub fn vfs_list_files_under(dir: &str) -> String {
+    let paths = get_runtime()
+        .list_files_under(Path::new(dir))
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+    to_json(&VfsResponse::with_files(paths))
+}
+
+/// Create a directory (and its parents) in the virtual filesystem.
+///
+/// # Arguments
+/// * `path` - Directory path
+///
+/// # Returns
+/// JSON: `{ "success": true }`
+#[wasm_bindgen]
+pub fn vfs_create_dir(path: &str) -> String {
+    match get_runtime().dir_create(Path::new(path), true) {
+        Ok(()) => to_json(&VfsResponse::ok()),
+        Err(e) => to_json(&VfsResponse::error(&format!(
+            "Failed to create directory: {}",
+            e
+        ))),
+    }
+}
+
+/// Get the metadata of a file or directory.
+///
+/// # Arguments
+/// * `path` - File or directory path
+///
+/// # Returns
+/// JSON: `{ "success": true, "metadata": { "kind": "file", "size": 42, "mtime": 1700000000000 } }`
+/// or `{ "success": false, "error": "..." }`
+#[wasm_bindgen]
+pub fn vfs_stat(path: &str) -> String {
+    match get_runtime().path_metadata(Path::new(path)) {
+        Ok(metadata) => to_json(&VfsResponse::with_metadata(VfsMetadata {
+            kind: match metadata.kind {
+                PathKind::Directory => VfsPathKind::Directory,
+                _ => VfsPathKind::File,
+            },
+            size: metadata.size,
+            mtime: metadata
+                .modified
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as f64),
+        })),
+        Err(e) => to_json(&VfsResponse::error(&format!("Failed to stat path: {}", e))),
+    }
+}
+
+/// Rename (move) a file or a directory with everything under it.
+///
+/// # Arguments
+/// * `old_path` - Current path
+/// * `new_path` - New path
+///
+/// # Returns
+/// JSON: `{ "success": true }` or `{ "success": false, "error": "..." }`
+#[wasm_bindgen]
+pub fn vfs_rename(old_path: &str, new_path: &str) -> String {
+    match get_runtime().path_rename(Path::new(old_path), Path::new(new_path)) {
+        Ok(()) => to_json(&VfsResponse::ok()),
+        Err(e) => to_json(&VfsResponse::error(&format!("Failed to rename: {}", e))),
+    }
+}
+
+/// Copy a file.
+///
+/// # Arguments
+/// * `src` - Path of the file to copy
+/// * `dst` - Path of the copy
+///
+/// # Returns
+/// JSON: `{ "success": true }` or `{ "success": false, "error": "..." }`
+#[wasm_bindgen]
+pub fn vfs_copy_file(src: &str, dst: &str) -> String {
+    match get_runtime().file_copy(Path::new(src), Path::new(dst)) {
+        Ok(()) => to_json(&VfsResponse::ok()),
+        Err(e) => to_json(&VfsResponse::error(&format!("Failed to copy file: {}", e))),
+    }
 }
 
 /// Clear user files from the virtual filesystem.
@@ -194,7 +275,7 @@ pub fn vfs_list_files() -> String {
 #[wasm_bindgen]
 pub fn vfs_clear() -> String {
     get_runtime().clear_user_files(RESOURCE_PATH_PREFIX);
-    VfsResponse::ok()
+    to_json(&VfsResponse::ok())
 }
 
 /// Read a text file from the virtual filesystem.
@@ -210,10 +291,10 @@ pub fn vfs_read_file(path: &str) -> String {
 
     match runtime.file_read(Path::new(path)) {
         Ok(content) => match String::from_utf8(content) {
-            Ok(text) => VfsResponse::with_content(text),
-            Err(_) => VfsResponse::error("File is not valid UTF-8"),
+            Ok(text) => to_json(&VfsResponse::with_content(text)),
+            Err(_) => to_json(&VfsResponse::error("File is not valid UTF-8")),
         },
-        Err(e) => VfsResponse::error(&format!("Failed to read file: {}", e)),
+        Err(e) => to_json(&VfsResponse::error(&format!("Failed to read file: {}", e))),
     }
 }
 
@@ -234,9 +315,9 @@ pub fn vfs_read_binary_file(path: &str) -> String {
     match runtime.file_read(Path::new(path)) {
         Ok(content) => {
             let base64_content = base64::engine::general_purpose::STANDARD.encode(&content);
-            VfsResponse::with_content(base64_content)
+            to_json(&VfsResponse::with_content(base64_content))
         }
-        Err(e) => VfsResponse::error(&format!("Failed to read file: {}", e)),
+        Err(e) => to_json(&VfsResponse::error(&format!("Failed to read file: {}", e))),
     }
 }
 
@@ -244,45 +325,6 @@ pub fn vfs_read_binary_file(path: &str) -> String {
 // DIAGNOSTIC TYPES FOR JSON TRANSPORT
 // ============================================================================
 
-/// A diagnostic detail item for JSON serialization.
-#[derive(Serialize)]
-struct JsonDiagnosticDetail {
-    kind: String,
-    content: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    start_line: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    start_column: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    end_line: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    end_column: Option<u32>,
-}
-
-/// A diagnostic message for JSON serialization.
-///
-/// This struct is designed for transport to the TypeScript/Monaco layer.
-/// Line and column numbers are 1-based to match Monaco's expectations.
-#[derive(Serialize)]
-struct JsonDiagnostic {
-    kind: String,
-    title: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    code: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    problem: Option<String>,
-    hints: Vec<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    start_line: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    start_column: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    end_line: Option<u32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    end_column: Option<u32>,
-    details: Vec<JsonDiagnosticDetail>,
-}
-
 /// Convert a DiagnosticMessage to a JsonDiagnostic.
 ///
 /// Uses the SourceContext to map byte offsets to 1-based line/column numbers.
@@ -352,14 +394,14 @@ fn diagnostic_to_json(diag: &DiagnosticMessage, ctx: &SourceContext) -> JsonDiag
                     (None, None, None, None)
                 };
 
-            let kind_str = match detail.kind {
-                quarto_error_reporting::DetailKind::Error => "error",
-                quarto_error_reporting::DetailKind::Info => "info",
-                quarto_error_reporting::DetailKind::Note => "note",
+            let kind = match detail.kind {
+                quarto_error_reporting::DetailKind::Error => DetailKind::Error,
+                quarto_error_reporting::DetailKind::Info => DetailKind::Info,
+                quarto_error_reporting::DetailKind::Note => DetailKind::Note,
             };
 
             JsonDiagnosticDetail {
-                kind: kind_str.to_string(),
+                kind,
                 content: detail.content.as_str().to_string(),
                 start_line: d_start_line,
                 start_column: d_start_col,
@@ -370,18 +412,18 @@ fn diagnostic_to_json(diag: &DiagnosticMessage, ctx: &SourceContext) -> JsonDiag
         .collect();
 
     // Convert kind
-    let kind_str = match diag.kind {
-        DiagnosticKind::Error => "error",
-        DiagnosticKind::Warning => "warning",
-        DiagnosticKind::Info => "info",
-        DiagnosticKind::Note => "note",
+    let kind = match diag.kind {
+        DiagnosticKind::Error => JsonDiagnosticKind::Error,
+        DiagnosticKind::Warning => JsonDiagnosticKind::Warning,
+        DiagnosticKind::Info => JsonDiagnosticKind::Info,
+        DiagnosticKind::Note => JsonDiagnosticKind::Note,
     };
 
     // Convert hints
     let hints: Vec<String> = diag.hints.iter().map(|h| h.as_str().to_string()).collect();
 
     JsonDiagnostic {
-        kind: kind_str.to_string(),
+        kind,
         title: diag.title.clone(),
         code: diag.code.clone(),
         problem: diag.problem.as_ref().map(|p| p.as_str().to_string()),
@@ -403,21 +445,6 @@ fn diagnostics_to_json(diags: &[DiagnosticMessage], ctx: &SourceContext) -> Vec<
 // RENDERING API
 // ============================================================================
 
-#[derive(Serialize)]
-struct RenderResponse {
-    success: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    html: Option<String>,
-    /// Structured diagnostics (errors) with line/column information for Monaco.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    diagnostics: Option<Vec<JsonDiagnostic>>,
-    /// Structured warnings with line/column information for Monaco.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    warnings: Option<Vec<JsonDiagnostic>>,
-}
-
 /// Create a minimal project context for WASM rendering.
 fn create_wasm_project_context(path: &Path) -> ProjectContext {
     let dir = path.parent().unwrap_or(Path::new("/")).to_path_buf();
@@ -439,6 +466,19 @@ fn create_wasm_project_context(path: &Path) -> ProjectContext {
 /// JSON: `{ "success": true, "html": "..." }` or `{ "success": false, "error": "...", "diagnostics": [...] }`
 #[wasm_bindgen]
 pub async fn render_qmd(path: &str) -> String {
+    let response = render_vfs_qmd(path, false, Cancellation::new(), Arc::new(NoopObserver)).await;
+    to_json(&response)
+}
+
+/// Render a QMD file from the virtual filesystem into a `RenderResponse`,
+/// stopping early if `cancellation` is cancelled. With `node_ids`, blocks
+/// carry `data-quarto-node` attributes with their stable ids.
+async fn render_vfs_qmd(
+    path: &str,
+    node_ids: bool,
+    cancellation: Cancellation,
+    observer: Arc<dyn PipelineObserver>,
+) -> RenderResponse {
     let runtime = get_runtime();
     let path = Path::new(path);
 
@@ -446,14 +486,7 @@ pub async fn render_qmd(path: &str) -> String {
     let content = match runtime.file_read(path) {
         Ok(bytes) => bytes,
         Err(e) => {
-            return serde_json::to_string(&RenderResponse {
-                success: false,
-                error: Some(format!("Failed to read file: {}", e)),
-                html: None,
-                diagnostics: None,
-                warnings: None,
-            })
-            .unwrap();
+            return RenderResponse::error(format!("Failed to read file: {}", e), None);
         }
     };
 
@@ -467,17 +500,17 @@ pub async fn render_qmd(path: &str) -> String {
     let content_str = match std::str::from_utf8(&content) {
         Ok(s) => s,
         Err(_) => {
-            return serde_json::to_string(&RenderResponse {
-                success: false,
-                error: Some("Content is not valid UTF-8".to_string()),
-                html: None,
-                diagnostics: None,
-                warnings: None,
-            })
-            .unwrap();
+            return RenderResponse::error("Content is not valid UTF-8".to_string(), None);
         }
     };
-    let format_metadata = extract_format_metadata(content_str, "html").unwrap_or_default();
+    let mut format_metadata = extract_format_metadata(content_str, "html").unwrap_or_default();
+    if node_ids {
+        // Options other than a map (`html: default`) have nothing to keep
+        if !format_metadata.is_object() {
+            format_metadata = serde_json::Value::Object(Default::default());
+        }
+        format_metadata["node-ids"] = serde_json::Value::Bool(true);
+    }
     let format = Format::html().with_metadata(format_metadata);
 
     let options = RenderOptions {
@@ -485,9 +518,13 @@ pub async fn render_qmd(path: &str) -> String {
         execute: false,
         use_freeze: false,
         output_path: None,
+        ..Default::default()
     };
 
-    let mut ctx = RenderContext::new(&project, &doc, &format, &binaries).with_options(options);
+    let mut ctx = RenderContext::new(&project, &doc, &format, &binaries)
+        .with_options(options)
+        .with_cancellation(cancellation)
+        .with_observer(observer);
 
     // Use the unified async pipeline (same as CLI)
     let config = HtmlRenderConfig::default();
@@ -506,20 +543,15 @@ pub async fn render_qmd(path: &str) -> String {
                 }
             }
 
+            let outputs = render_outputs(
+                runtime,
+                &ctx.artifacts,
+                path.parent().unwrap_or(Path::new("/")),
+            );
+
             // Convert warnings to structured JSON with line/column info
             let warnings = diagnostics_to_json(&output.diagnostics, &output.source_context);
-            serde_json::to_string(&RenderResponse {
-                success: true,
-                error: None,
-                html: Some(output.html),
-                diagnostics: None,
-                warnings: if warnings.is_empty() {
-                    None
-                } else {
-                    Some(warnings)
-                },
-            })
-            .unwrap()
+            RenderResponse::ok(output.html, warnings, outputs)
         }
         Err(e) => {
             // Extract structured diagnostics from parse errors
@@ -532,18 +564,196 @@ pub async fn render_qmd(path: &str) -> String {
                 _ => (e.to_string(), None),
             };
 
-            serde_json::to_string(&RenderResponse {
-                success: false,
-                error: Some(error_msg),
-                html: None,
-                diagnostics,
-                warnings: None,
-            })
-            .unwrap()
+            RenderResponse::error(error_msg, diagnostics)
+        }
+    }
+}
+
+/// Render states of recent incremental renders, keyed by state token.
+static RENDER_STATES: Mutex<Vec<(String, RenderState)>> = Mutex::new(Vec::new());
+
+/// How many render states are kept for patching.
+const MAX_RENDER_STATES: usize = 16;
+
+/// Render a QMD file from the virtual filesystem, with a patch from a previous
+/// render.
+///
+/// The returned HTML carries `data-quarto-node` attributes identifying its
+/// content regions: the stable ids of the blocks they render, or IDs from
+/// the HTML of regions added by the template. The patch removes and inserts
+/// regions by those IDs, so the preview can update the page in place,
+/// keeping the scroll position and the state of unchanged regions. It asks for a full reload when
+/// `prev_state_token` is empty or unknown, or when the page outside its
+/// content changed.
+///
+/// # Arguments
+/// * `path` - Path to the QMD file in VFS (e.g., "index.qmd")
+/// * `prev_state_token` - `state_token` of the previous render, or `""`
+///
+/// # Returns
+/// JSON: `{ "success": true, "html": "...", "state_token": "...", "patch": { "full_reload": false, "remove": [...], "insert": [...] } }`
+/// or `{ "success": false, "error": "...", "diagnostics": [...] }`
+#[wasm_bindgen]
+pub async fn render_qmd_incremental(path: &str, prev_state_token: &str) -> String {
+    let mut render = render_vfs_qmd(path, true, Cancellation::new(), Arc::new(NoopObserver)).await;
+    let Some((html, state)) = render.html.as_deref().and_then(render_patch::annotate) else {
+        return to_json(&IncrementalRenderResponse {
+            patch: render.success.then(RenderPatch::full_reload),
+            render,
+            state_token: None,
+        });
+    };
+
+    let mut states = RENDER_STATES.lock().unwrap();
+    let patch = match states.iter().find(|(token, _)| token == prev_state_token) {
+        Some((_, previous)) => render_patch::diff(previous, &html, &state),
+        None => RenderPatch::full_reload(),
+    };
+    let state_token = format!("{}:{}", path, render_patch::fingerprint(&html));
+    states.retain(|(token, _)| *token != state_token);
+    if states.len() >= MAX_RENDER_STATES {
+        states.remove(0);
+    }
+    states.push((state_token.clone(), state));
+    drop(states);
+
+    render.html = Some(html);
+    to_json(&IncrementalRenderResponse {
+        render,
+        state_token: Some(state_token),
+        patch: Some(patch),
+    })
+}
+
+/// Cancellation tokens of the renders started with `render_qmd_async()`, by
+/// render handle.
+static RENDER_HANDLES: Mutex<Vec<(u32, Cancellation)>> = Mutex::new(Vec::new());
+
+/// The last render handle given out.
+static LAST_RENDER_HANDLE: AtomicU32 = AtomicU32::new(0);
+
+/// Reports the stages of a render to a JavaScript callback.
+///
+/// The callback receives a JSON string
+/// `{ "stage": "...", "index": 0, "total": 7 }` before each stage. Returning
+/// `false` from it cancels the render.
+struct JsProgressObserver {
+    callback: js_sys::Function,
+    cancellation: Cancellation,
+}
+
+// WASM is single-threaded: the callback never crosses threads.
+unsafe impl Send for JsProgressObserver {}
+unsafe impl Sync for JsProgressObserver {}
+
+impl PipelineObserver for JsProgressObserver {
+    fn on_stage_start(&self, name: &str, index: usize, total: usize) {
+        let progress = to_json(&RenderProgress {
+            stage: name.to_string(),
+            index: index as u32,
+            total: total as u32,
+        });
+        let result = self
+            .callback
+            .call1(&JsValue::NULL, &JsValue::from_str(&progress));
+        if matches!(result, Ok(value) if value == JsValue::FALSE) {
+            self.cancellation.cancel();
         }
     }
 }
 
+/// Create a handle for a cancellable render.
+///
+/// Pass the handle to `render_qmd_async()`, and to `cancel_render()` to stop
+/// that render, e.g. when the user keeps typing and a newer render starts.
+///
+/// # Returns
+/// The render handle
+#[wasm_bindgen]
+pub fn create_render_handle() -> u32 {
+    let handle = LAST_RENDER_HANDLE.fetch_add(1, Ordering::Relaxed) + 1;
+    RENDER_HANDLES
+        .lock()
+        .unwrap()
+        .push((handle, Cancellation::new()));
+    handle
+}
+
+/// Cancel the render of a handle from `create_render_handle()`.
+///
+/// Cancellation is cooperative: the render stops before its next pipeline
+/// stage or AST transform. Cancelling a handle before its render starts
+/// makes the render stop right away.
+///
+/// # Returns
+/// Whether the handle belongs to a render that hasn't finished
+#[wasm_bindgen]
+pub fn cancel_render(handle: u32) -> bool {
+    match RENDER_HANDLES
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(h, _)| *h == handle)
+    {
+        Some((_, cancellation)) => {
+            cancellation.cancel();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Render a QMD file from the virtual filesystem, cancellably and with
+/// progress events.
+///
+/// The render checks its handle between pipeline stages and AST transforms,
+/// so it stops early when `cancel_render(handle)` is called while it waits
+/// (e.g. on SASS compilation), or when `on_progress` returns `false`.
+///
+/// # Arguments
+/// * `path` - Path to the QMD file in VFS (e.g., "index.qmd")
+/// * `handle` - Render handle from `create_render_handle()`
+/// * `on_progress` - Optional callback receiving
+///   `'{ "stage": "...", "index": 0, "total": 7 }'` before each stage
+///
+/// # Returns
+/// JSON: `{ "success": true, "html": "...", "cancelled": false }`, or
+/// `{ "success": false, "error": "...", "cancelled": true }` for a cancelled render
+#[wasm_bindgen]
+pub async fn render_qmd_async(
+    path: &str,
+    handle: u32,
+    on_progress: Option<js_sys::Function>,
+) -> String {
+    let cancellation = RENDER_HANDLES
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|(h, _)| *h == handle)
+        .map(|(_, cancellation)| cancellation.clone());
+    let Some(cancellation) = cancellation else {
+        return to_json(&AsyncRenderResponse {
+            render: RenderResponse::error(format!("Unknown render handle: {}", handle), None),
+            cancelled: false,
+        });
+    };
+
+    let observer: Arc<dyn PipelineObserver> = match on_progress {
+        Some(callback) => Arc::new(JsProgressObserver {
+            callback,
+            cancellation: cancellation.clone(),
+        }),
+        None => Arc::new(NoopObserver),
+    };
+    let render = render_vfs_qmd(path, false, cancellation.clone(), observer).await;
+
+    RENDER_HANDLES.lock().unwrap().retain(|(h, _)| *h != handle);
+    to_json(&AsyncRenderResponse {
+        cancelled: !render.success && cancellation.is_cancelled(),
+        render,
+    })
+}
+
 /// Render QMD content directly (without reading from VFS).
 ///
 /// # Arguments
@@ -572,6 +782,7 @@ pub async fn render_qmd_content(content: &str, _template_bundle: &str) -> String
         execute: false,
         use_freeze: false,
         output_path: None,
+        ..Default::default()
     };
 
     let mut ctx = RenderContext::new(&project, &doc, &format, &binaries).with_options(options);
@@ -603,20 +814,15 @@ pub async fn render_qmd_content(content: &str, _template_bundle: &str) -> String
                 }
             }
 
+            let outputs = render_outputs(
+                runtime,
+                &ctx.artifacts,
+                path.parent().unwrap_or(Path::new("/")),
+            );
+
             // Convert warnings to structured JSON with line/column info
             let warnings = diagnostics_to_json(&output.diagnostics, &output.source_context);
-            serde_json::to_string(&RenderResponse {
-                success: true,
-                error: None,
-                html: Some(output.html),
-                diagnostics: None,
-                warnings: if warnings.is_empty() {
-                    None
-                } else {
-                    Some(warnings)
-                },
-            })
-            .unwrap()
+            to_json(&RenderResponse::ok(output.html, warnings, outputs))
         }
         Err(e) => {
             // Extract structured diagnostics from parse errors
@@ -629,18 +835,97 @@ pub async fn render_qmd_content(content: &str, _template_bundle: &str) -> String
                 _ => (e.to_string(), None),
             };
 
-            serde_json::to_string(&RenderResponse {
-                success: false,
-                error: Some(error_msg),
-                html: None,
-                diagnostics,
-                warnings: None,
-            })
-            .unwrap()
+            to_json(&RenderResponse::error(error_msg, diagnostics))
         }
     }
 }
 
+// ============================================================================
+// PROJECT RENDERING API
+// ============================================================================
+
+/// Render every document of the project in the VFS to HTML.
+///
+/// The project is found from `_quarto.yml` in `project_dir` or its parents.
+/// Documents are rendered in the project's order (chapter order for books),
+/// sharing cross-references, and their outputs are written to the VFS under
+/// the project's output directory (e.g. `_site/`). Links between documents
+/// point to their outputs, and the resources documents refer to are copied
+/// to the output directory.
+///
+/// A document that fails to render doesn't stop the others; its error and
+/// diagnostics are in its manifest entry.
+///
+/// # Arguments
+/// * `project_dir` - Project directory in VFS (e.g., "/project" or "")
+///
+/// # Returns
+/// JSON: `{ "success": true, "project_dir": "/project", "output_dir": "_site",
+/// "documents": [{ "input": "index.qmd", "output": "_site/index.html",
+/// "success": true, "warnings": [...] }], "resources": ["_site/plot.png"] }`
+/// or `{ "success": false, "error": "...", "documents": [], "resources": [] }`
+#[wasm_bindgen]
+pub async fn render_project(project_dir: &str) -> String {
+    let runtime: Arc<dyn SystemRuntime> = get_shared_runtime().clone();
+    let render = match quarto_preview::render_project(project_dir, runtime).await {
+        Ok(render) => render,
+        Err(e) => return to_json(&ProjectRenderResponse::error(e.to_string())),
+    };
+
+    let documents: Vec<RenderedDocument> = render
+        .documents
+        .iter()
+        .map(|doc| {
+            let mut entry = RenderedDocument {
+                input: render.relative(&doc.input),
+                output: None,
+                success: false,
+                error: None,
+                diagnostics: None,
+                warnings: None,
+            };
+            match &doc.result {
+                Ok((diagnostics, source_context)) => {
+                    let warnings = diagnostics_to_json(diagnostics, source_context);
+                    entry.success = true;
+                    entry.output = Some(render.relative(&doc.output));
+                    entry.warnings = (!warnings.is_empty()).then_some(warnings);
+                }
+                Err(e) => {
+                    if let QuartoError::Parse(parse_error) = e {
+                        entry.diagnostics = Some(diagnostics_to_json(
+                            &parse_error.diagnostics,
+                            &parse_error.source_context,
+                        ));
+                    }
+                    entry.error = Some(e.to_string());
+                }
+            }
+            entry
+        })
+        .collect();
+
+    let warnings = render
+        .project
+        .config
+        .as_ref()
+        .map(|config| diagnostics_to_json(&config.diagnostics, &config.source_context))
+        .unwrap_or_default();
+    to_json(&ProjectRenderResponse {
+        success: render.success(),
+        error: None,
+        project_dir: Some(render.project.dir.to_string_lossy().into_owned()),
+        output_dir: Some(render.relative(&render.project.output_dir)),
+        documents,
+        resources: render
+            .resources
+            .iter()
+            .map(|path| render.relative(path))
+            .collect(),
+        warnings: (!warnings.is_empty()).then_some(warnings),
+    })
+}
+
 // ============================================================================
 // RENDER OPTIONS API
 // ============================================================================
@@ -708,6 +993,7 @@ pub async fn render_qmd_content_with_options(
         execute: false,
         use_freeze: false,
         output_path: None,
+        ..Default::default()
     };
 
     let mut ctx = RenderContext::new(&project, &doc, &format, &binaries).with_options(options);
@@ -737,20 +1023,15 @@ pub async fn render_qmd_content_with_options(
                 }
             }
 
+            let outputs = render_outputs(
+                runtime,
+                &ctx.artifacts,
+                path.parent().unwrap_or(Path::new("/")),
+            );
+
             // Convert warnings to structured JSON with line/column info
             let warnings = diagnostics_to_json(&output.diagnostics, &output.source_context);
-            serde_json::to_string(&RenderResponse {
-                success: true,
-                error: None,
-                html: Some(output.html),
-                diagnostics: None,
-                warnings: if warnings.is_empty() {
-                    None
-                } else {
-                    Some(warnings)
-                },
-            })
-            .unwrap()
+            to_json(&RenderResponse::ok(output.html, warnings, outputs))
         }
         Err(e) => {
             // Extract structured diagnostics from parse errors
@@ -763,14 +1044,7 @@ pub async fn render_qmd_content_with_options(
                 _ => (e.to_string(), None),
             };
 
-            serde_json::to_string(&RenderResponse {
-                success: false,
-                error: Some(error_msg),
-                html: None,
-                diagnostics,
-                warnings: None,
-            })
-            .unwrap()
+            to_json(&RenderResponse::error(error_msg, diagnostics))
         }
     }
 }
@@ -1103,12 +1377,105 @@ pub async fn create_project(choice_id: &str, title: &str) -> String {
 // ============================================================================
 //
 // These functions provide the WASM entry points for language intelligence
-// features (document symbols, diagnostics, folding ranges).
+// features (document symbols, diagnostics, folding ranges, links and
+// completions).
 //
 // They use quarto-lsp-core which is transport-agnostic and compiles to both
 // native and WASM targets.
 
-use quarto_lsp_core::{Document, DocumentAnalysisJson, analyze_document};
+use quarto_lsp_core::{
+    Document, DocumentAnalysisJson, Workspace, WorkspaceEntry, analyze_document,
+    get_prose_lint_config, get_prose_lint_diagnostics, get_reference_completions,
+};
+
+/// The files around a document in the VFS.
+///
+/// Paths are resolved against the document's directory. The project
+/// directory is the nearest ancestor containing `_quarto.yml`.
+struct VfsWorkspace {
+    dir: PathBuf,
+}
+
+impl VfsWorkspace {
+    /// The workspace of the document at `path`.
+    fn for_document(path: &Path) -> Self {
+        Self {
+            dir: path.parent().unwrap_or(Path::new("")).to_path_buf(),
+        }
+    }
+}
+
+impl Workspace for VfsWorkspace {
+    fn read_file(&self, path: &str) -> Option<String> {
+        get_runtime().file_read_string(&self.dir.join(path)).ok()
+    }
+
+    fn read_dir(&self, path: &str) -> Vec<WorkspaceEntry> {
+        let runtime = get_runtime();
+        let Ok(entries) = runtime.dir_list(&self.dir.join(path)) else {
+            return Vec::new();
+        };
+        entries
+            .iter()
+            .filter_map(|entry| {
+                let name = entry.file_name()?.to_string_lossy().into_owned();
+                Some(if runtime.is_dir(entry).unwrap_or(false) {
+                    WorkspaceEntry::dir(name)
+                } else {
+                    WorkspaceEntry::file(name)
+                })
+            })
+            .collect()
+    }
+
+    fn project_dir(&self) -> Option<String> {
+        let runtime = get_runtime();
+        let dir = runtime.canonicalize(&self.dir).ok()?;
+        let depth = dir.ancestors().position(|ancestor| {
+            ["_quarto.yml", "_quarto.yaml"].iter().any(|name| {
+                runtime
+                    .path_exists(&ancestor.join(name), None)
+                    .unwrap_or(false)
+            })
+        })?;
+        Some(if depth == 0 {
+            ".".to_string()
+        } else {
+            vec![".."; depth].join("/")
+        })
+    }
+}
+
+/// Response for analyze_qmd().
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AnalyzeQmdResponse {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    analysis: Option<DocumentAnalysisJson>,
+}
+
+impl AnalyzeQmdResponse {
+    fn ok(analysis: DocumentAnalysisJson) -> String {
+        serde_json::to_string(&AnalyzeQmdResponse {
+            success: true,
+            error: None,
+            analysis: Some(analysis),
+        })
+        .unwrap()
+    }
+
+    fn error(msg: &str) -> String {
+        serde_json::to_string(&AnalyzeQmdResponse {
+            success: false,
+            error: Some(msg.to_string()),
+            analysis: None,
+        })
+        .unwrap()
+    }
+}
 
 /// Response for LSP analyze_document().
 #[derive(Serialize)]
@@ -1123,6 +1490,8 @@ struct LspAnalyzeResponse {
     folding_ranges: Option<Vec<quarto_lsp_core::FoldingRange>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     diagnostics: Option<Vec<quarto_lsp_core::Diagnostic>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    document_links: Option<Vec<quarto_lsp_core::DocumentLink>>,
 }
 
 impl LspAnalyzeResponse {
@@ -1133,6 +1502,7 @@ impl LspAnalyzeResponse {
             symbols: Some(analysis.symbols),
             folding_ranges: Some(analysis.folding_ranges),
             diagnostics: Some(analysis.diagnostics),
+            document_links: Some(analysis.document_links),
         })
         .unwrap()
     }
@@ -1144,6 +1514,7 @@ impl LspAnalyzeResponse {
             symbols: None,
             folding_ranges: None,
             diagnostics: None,
+            document_links: None,
         })
         .unwrap()
     }
@@ -1261,6 +1632,7 @@ impl LspDiagnosticsResponse {
 ///     console.log("Symbols:", result.symbols);
 ///     console.log("Folding ranges:", result.foldingRanges);
 ///     console.log("Diagnostics:", result.diagnostics);
+///     console.log("Links:", result.documentLinks);
 /// }
 /// ```
 #[wasm_bindgen]
@@ -1286,6 +1658,56 @@ pub fn lsp_analyze_document(path: &str) -> String {
     LspAnalyzeResponse::ok(json_analysis)
 }
 
+/// Analyze a QMD file in the VFS, returning all editor intelligence at once.
+///
+/// Like `lsp_analyze_document()`, plus what needs the files around the
+/// document: diagnostics of the prose lint configured by the document and
+/// its project, and the cross-reference labels and citation keys (from the
+/// bibliographies in the VFS) to complete after `@`.
+///
+/// # Arguments
+/// * `path` - Path to the file in VFS (e.g., "index.qmd")
+///
+/// # Returns
+/// JSON: `{ "success": true, "symbols": [...], "foldingRanges": [...], "diagnostics": [...], "documentLinks": [...], "completionItems": [...] }`
+/// or `{ "success": false, "error": "..." }`
+///
+/// # Example
+/// ```javascript
+/// const result = JSON.parse(analyze_qmd("index.qmd"));
+/// if (result.success) {
+///     for (const item of result.completionItems) {
+///         console.log(`@${item.label}: ${item.detail}`);
+///     }
+/// }
+/// ```
+#[wasm_bindgen]
+pub fn analyze_qmd(path: &str) -> String {
+    let runtime = get_runtime();
+    let file_path = Path::new(path);
+
+    // Read the file from VFS
+    let content = match runtime.file_read(file_path) {
+        Ok(bytes) => match String::from_utf8(bytes) {
+            Ok(text) => text,
+            Err(_) => return AnalyzeQmdResponse::error("File is not valid UTF-8"),
+        },
+        Err(e) => return AnalyzeQmdResponse::error(&format!("Failed to read file: {}", e)),
+    };
+
+    let doc = Document::new(path, &content);
+    let workspace = VfsWorkspace::for_document(file_path);
+
+    let mut analysis: DocumentAnalysisJson = analyze_document(&doc).into();
+    let lint_config = get_prose_lint_config(&doc, &workspace);
+    analysis
+        .diagnostics
+        .extend(get_prose_lint_diagnostics(&doc, &lint_config));
+    let completion_items = get_reference_completions(&doc, &workspace);
+
+    AnalyzeQmdResponse::ok(analysis.with_completion_items(completion_items))
+}
+
 /// Get document symbols for a file in the VFS.
 ///
 /// Convenience wrapper around lsp_analyze_document() for callers
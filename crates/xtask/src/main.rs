@@ -8,8 +8,10 @@
 //! Available commands:
 //! - `lint`: Run custom lint checks on the codebase
 //! - `verify`: Run full project verification (build + tests for Rust and hub-client)
+//! - `protocol-types`: Regenerate the TypeScript types of the WASM responses
 
 mod lint;
+mod protocol_types;
 mod verify;
 
 use anyhow::Result;
@@ -70,6 +72,12 @@ enum Command {
         #[arg(long)]
         e2e: bool,
     },
+
+    /// Regenerate the TypeScript types of the WASM responses.
+    ///
+    /// Writes the declarations of the `quarto-wasm-protocol` types to
+    /// hub-client/src/types/protocol.ts. Run this after changing them.
+    ProtocolTypes,
 }
 
 fn main() -> Result<()> {
@@ -96,5 +104,6 @@ fn main() -> Result<()> {
             };
             verify::run(&config)
         }
+        Command::ProtocolTypes => protocol_types::run(),
     }
 }
@@ -0,0 +1,25 @@
+//! Protocol types command - Regenerate the TypeScript declarations of the
+//! WASM response types.
+//!
+//! Writes `quarto_wasm_protocol::typescript()` to
+//! `hub-client/src/types/protocol.ts`. The `quarto-wasm-protocol` tests fail
+//! until this is run after changing the response types.
+
+use anyhow::{Context, Result};
+
+use crate::verify::find_project_root;
+
+/// Run the protocol-types command.
+pub fn run() -> Result<()> {
+    let path = find_project_root()?.join(quarto_wasm_protocol::TYPESCRIPT_PATH);
+    let typescript = quarto_wasm_protocol::typescript();
+
+    if std::fs::read_to_string(&path).is_ok_and(|current| current == typescript) {
+        println!("✓ {} is up to date", quarto_wasm_protocol::TYPESCRIPT_PATH);
+        return Ok(());
+    }
+    std::fs::write(&path, typescript)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    println!("✓ Wrote {}", quarto_wasm_protocol::TYPESCRIPT_PATH);
+    Ok(())
+}
@@ -112,7 +112,7 @@ pub fn run(config: &VerifyConfig) -> Result<()> {
 }
 
 /// Find the project root directory (where Cargo.toml with [workspace] lives).
-fn find_project_root() -> Result<std::path::PathBuf> {
+pub(crate) fn find_project_root() -> Result<std::path::PathBuf> {
     let mut dir = std::env::current_dir().context("Failed to get current directory")?;
 
     loop {
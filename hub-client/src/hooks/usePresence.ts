@@ -18,6 +18,7 @@ import {
   onPresenceChange,
   refreshIdentity,
   getLocalPeerId,
+  resolvePresence,
   type PresenceState,
 } from '../services/presenceService';
 
@@ -193,13 +194,17 @@ export function usePresence(
       const colorId = colorToId(user.userColor);
       ensureCursorStyle(user.userColor, colorId);
 
+      // Resolve Automerge cursors against our copy of the document. When they
+      // resolve, the positions are exact and need no compensation.
+      const resolved = resolvePresence(user);
+
       // Add cursor decoration
-      if (user.cursor !== null) {
+      if (resolved.cursor !== null) {
         try {
           const prevCursor = prevCursorsRef.current.get(user.peerId);
           const cursorModelLength = cursorModelLengthRef.current.get(user.peerId) ?? docLength;
 
-          let cursorToRender = user.cursor;
+          let cursorToRender = resolved.cursor;
 
           // Pre-compensation for single-char (and small multi-char) inserts:
           // When presence arrives before document sync, the cursor position is based
@@ -219,8 +224,8 @@ export function usePresence(
           // We don't want to pre-compensate for navigation since no document change is coming.
           const MAX_TYPING_DELTA = 2;
 
-          if (prevCursor !== undefined) {
-            const cursorDelta = user.cursor - prevCursor;
+          if (!resolved.exact && prevCursor !== undefined) {
+            const cursorDelta = resolved.cursor - prevCursor;
             const modelDelta = docLength - cursorModelLength;
 
             // Only pre-compensate for small forward movements (likely typing)
@@ -230,8 +235,8 @@ export function usePresence(
           }
 
           // Update tracking state when cursor changes
-          if (user.cursor !== prevCursor) {
-            prevCursorsRef.current.set(user.peerId, user.cursor);
+          if (resolved.cursor !== prevCursor) {
+            prevCursorsRef.current.set(user.peerId, resolved.cursor);
             cursorModelLengthRef.current.set(user.peerId, docLength);
           }
 
@@ -262,15 +267,16 @@ export function usePresence(
       // Add selection decoration (simpler handling - no pre-compensation needed
       // since typing usually clears selection, and selection changes are less
       // frequent than cursor movements)
-      if (user.selection && user.selection.start !== user.selection.end) {
+      const selection = resolved.selection;
+      if (selection && selection.start !== selection.end) {
         try {
           // Skip if selection extends beyond document length
-          if (user.selection.end > docLength) {
+          if (selection.end > docLength) {
             continue;
           }
 
-          const startPos = model.getPositionAt(user.selection.start);
-          const endPos = model.getPositionAt(user.selection.end);
+          const startPos = model.getPositionAt(selection.start);
+          const endPos = model.getPositionAt(selection.end);
           newDecorations.push({
             range: {
               startLineNumber: startPos.lineNumber,
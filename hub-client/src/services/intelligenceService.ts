@@ -14,7 +14,7 @@ import type {
   Diagnostic,
   FoldingRange,
   DocumentAnalysis,
-  LspAnalyzeResponse,
+  AnalyzeQmdResponse,
   LspSymbolsResponse,
   LspFoldingRangesResponse,
   LspDiagnosticsResponse,
@@ -23,7 +23,13 @@ import { initWasm } from './wasmRenderer';
 import { isQmdFile } from '../types/project';
 
 // Re-export types for convenience
-export type { Symbol, Diagnostic, FoldingRange, DocumentAnalysis } from '../types/intelligence';
+export type {
+  Symbol,
+  Diagnostic,
+  FoldingRange,
+  DocumentAnalysis,
+  CompletionItem,
+} from '../types/intelligence';
 
 // ============================================================================
 // Internal Helpers
@@ -50,27 +56,38 @@ async function getWasm(): Promise<typeof import('wasm-quarto-hub-client')> {
  * as it performs only one parse.
  *
  * @param path - File path in VFS (e.g., "index.qmd")
- * @returns Complete analysis (symbols, folding ranges, diagnostics)
+ * @returns Complete analysis (symbols, folding ranges, diagnostics, links,
+ *   and the cross-reference labels and citation keys to complete after `@`)
  */
 export async function analyzeDocument(path: string): Promise<DocumentAnalysis> {
+  const empty: DocumentAnalysis = {
+    symbols: [],
+    foldingRanges: [],
+    diagnostics: [],
+    documentLinks: [],
+    completionItems: [],
+  };
+
   // Only QMD files have intelligence data
   if (!isQmdFile(path)) {
-    return { symbols: [], foldingRanges: [], diagnostics: [] };
+    return empty;
   }
 
   const wasm = await getWasm();
-  const result: LspAnalyzeResponse = JSON.parse(wasm.lsp_analyze_document(path));
+  const result: AnalyzeQmdResponse = JSON.parse(wasm.analyze_qmd(path));
 
   if (result.success) {
     return {
       symbols: result.symbols ?? [],
       foldingRanges: result.foldingRanges ?? [],
       diagnostics: result.diagnostics ?? [],
+      documentLinks: result.documentLinks ?? [],
+      completionItems: result.completionItems ?? [],
     };
   }
 
   console.warn('Failed to analyze document:', result.error);
-  return { symbols: [], foldingRanges: [], diagnostics: [] };
+  return empty;
 }
 
 /**
@@ -15,12 +15,14 @@ import {
   updateSelection,
   updatePresence,
   getRemotePresences,
+  resolvePresence,
   onPresenceChange,
   getLocalIdentity,
   getLocalPeerId,
   _resetForTesting,
   _getStateForTesting,
 } from './presenceService';
+import { getFileHandle } from './automergeSync';
 
 // Mock the userSettings module
 vi.mock('./userSettings', () => ({
@@ -39,6 +41,26 @@ vi.mock('./automergeSync', () => ({
   getFileHandle: vi.fn().mockReturnValue(null),
 }));
 
+// Mock Automerge cursors: a cursor ref is "ref:<offset>", shifted by the
+// number of characters inserted since (tracked via the doc's text length)
+vi.mock('@automerge/automerge', () => ({
+  getCursor: vi.fn((_doc: unknown, _path: unknown, offset: number) => `ref:${offset}`),
+  getCursorPosition: vi.fn((doc: { text: string }, _path: unknown, ref: string) => {
+    const offset = Number(ref.slice('ref:'.length));
+    if (offset > doc.text.length) throw new Error('unknown cursor');
+    return offset + 1;
+  }),
+}));
+
+function mockHandle(text: string) {
+  return {
+    doc: () => ({ text }),
+    broadcast: vi.fn(),
+    on: vi.fn(),
+    off: vi.fn(),
+  };
+}
+
 describe('presenceService', () => {
   beforeEach(() => {
     _resetForTesting();
@@ -186,6 +208,8 @@ describe('presenceService', () => {
         filePath: 'file1.qmd',
         cursor: 10,
         selection: null,
+        cursorRef: null,
+        selectionRef: null,
         lastSeen: Date.now(),
       });
 
@@ -274,6 +298,8 @@ describe('presenceService', () => {
         filePath: 'index.qmd',
         cursor: 42,
         selection: { start: 40, end: 45 },
+        cursorRef: null,
+        selectionRef: null,
         lastSeen: Date.now(),
       });
 
@@ -284,6 +310,83 @@ describe('presenceService', () => {
     });
   });
 
+  describe('automerge cursors', () => {
+    it('should broadcast file path and cursor refs', async () => {
+      const handle = mockHandle('hello world');
+      vi.mocked(getFileHandle).mockReturnValueOnce(handle as never);
+      await initPresence();
+      setCurrentFile('doc.qmd');
+
+      updatePresence(5, { start: 2, end: 5 });
+
+      expect(handle.broadcast).toHaveBeenCalledWith(
+        expect.objectContaining({
+          type: 'presence',
+          filePath: 'doc.qmd',
+          cursor: 5,
+          cursorRef: 'ref:5',
+          selectionRef: { start: 'ref:2', end: 'ref:5' },
+        })
+      );
+    });
+
+    it('should resolve refs against the local document', () => {
+      const handle = mockHandle('hello world');
+      vi.mocked(getFileHandle).mockReturnValueOnce(handle as never);
+      setCurrentFile('doc.qmd');
+
+      const resolved = resolvePresence({
+        peerId: 'peer-1',
+        userId: 'user-1',
+        userName: 'User 1',
+        userColor: '#ff0000',
+        filePath: 'doc.qmd',
+        cursor: 5,
+        selection: { start: 2, end: 5 },
+        cursorRef: 'ref:5',
+        selectionRef: { start: 'ref:2', end: 'ref:5' },
+        lastSeen: Date.now(),
+      });
+
+      expect(resolved).toEqual({ cursor: 6, selection: { start: 3, end: 6 }, exact: true });
+    });
+
+    it('should fall back to offsets when refs cannot be resolved', () => {
+      const handle = mockHandle('hi');
+      vi.mocked(getFileHandle).mockReturnValueOnce(handle as never);
+      setCurrentFile('doc.qmd');
+
+      const resolved = resolvePresence({
+        peerId: 'peer-1',
+        userId: 'user-1',
+        userName: 'User 1',
+        userColor: '#ff0000',
+        filePath: 'doc.qmd',
+        cursor: 5,
+        selection: null,
+        cursorRef: 'ref:50',
+        selectionRef: null,
+        lastSeen: Date.now(),
+      });
+
+      expect(resolved).toEqual({ cursor: 5, selection: null, exact: false });
+    });
+
+    it('should re-broadcast presence as a heartbeat', async () => {
+      const handle = mockHandle('hello');
+      vi.mocked(getFileHandle).mockReturnValueOnce(handle as never);
+      await initPresence({ cleanupIntervalMs: 1000 });
+      setCurrentFile('doc.qmd');
+      handle.broadcast.mockClear();
+
+      vi.advanceTimersByTime(1000);
+
+      expect(handle.broadcast).toHaveBeenCalledWith(
+        expect.objectContaining({ type: 'presence', filePath: 'doc.qmd' })
+      );
+    });
+  });
+
   describe('stale presence cleanup', () => {
     beforeEach(async () => {
       await initPresence({
@@ -304,6 +407,8 @@ describe('presenceService', () => {
         filePath: 'index.qmd',
         cursor: 0,
         selection: null,
+        cursorRef: null,
+        selectionRef: null,
         lastSeen: Date.now() - 2000, // 2 seconds ago (stale)
       });
 
@@ -325,6 +430,8 @@ describe('presenceService', () => {
         filePath: 'index.qmd',
         cursor: 10,
         selection: null,
+        cursorRef: null,
+        selectionRef: null,
         lastSeen: Date.now(),
       });
 
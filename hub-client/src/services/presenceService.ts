@@ -3,9 +3,18 @@
  *
  * Manages real-time presence (cursors, selections) for collaborative editing.
  * Uses Automerge's ephemeral messaging to broadcast and receive presence state.
+ *
+ * Positions are sent both as UTF-16 offsets and as Automerge cursors
+ * (`cursorRef`/`selectionRef`). Offsets go stale as soon as anyone edits the
+ * text before them; cursors stay attached to the surrounding characters, so
+ * receivers resolve them against their own copy of the document and fall back
+ * to the offsets only when the cursor can't be resolved yet (e.g. the
+ * characters it refers to haven't synced).
  */
 
+import { getCursor, getCursorPosition } from '@automerge/automerge';
 import type { DocHandle, DocHandleEphemeralMessagePayload } from '@automerge/automerge-repo';
+import type { TextDocumentContent } from '@quarto/quarto-automerge-schema';
 import { getFileHandle } from './automergeSync';
 import { getUserIdentity } from './userSettings';
 import type { UserSettings } from './storage/types';
@@ -21,9 +30,23 @@ export interface PresenceState {
   filePath: string;
   cursor: number | null;
   selection: { start: number; end: number } | null;
+  /** Automerge cursor for `cursor`, if the sender provided one */
+  cursorRef: string | null;
+  /** Automerge cursors for `selection`, if the sender provided them */
+  selectionRef: { start: string; end: string } | null;
   lastSeen: number;
 }
 
+/**
+ * Positions of a remote user resolved against the local document.
+ */
+export interface ResolvedPresencePositions {
+  cursor: number | null;
+  selection: { start: number; end: number } | null;
+  /** True if the cursor was resolved from an Automerge cursor (not a raw offset) */
+  exact: boolean;
+}
+
 /**
  * Presence message broadcast via ephemeral messaging.
  */
@@ -33,8 +56,12 @@ interface PresenceMessage {
   userId: string;
   userName: string;
   userColor: string;
+  /** The file this presence applies to */
+  filePath?: string | null;
   cursor: number | null;
   selection: { start: number; end: number } | null;
+  cursorRef?: string | null;
+  selectionRef?: { start: string; end: string } | null;
 }
 
 /**
@@ -60,7 +87,7 @@ interface PresenceConfig {
   broadcastThrottleMs: number;
   /** How long before a user is considered stale (ms). Default: 5000 */
   staleThresholdMs: number;
-  /** How often to clean up stale presences (ms). Default: 2000 */
+  /** How often to clean up stale presences and re-broadcast our own (ms). Default: 2000 */
   cleanupIntervalMs: number;
 }
 
@@ -139,7 +166,11 @@ export async function initPresence(config?: Partial<PresenceConfig>): Promise<vo
   if (state.cleanupInterval) {
     clearInterval(state.cleanupInterval);
   }
-  state.cleanupInterval = setInterval(cleanupStalePresences, state.config.cleanupIntervalMs);
+  state.cleanupInterval = setInterval(() => {
+    cleanupStalePresences();
+    // Heartbeat so idle users don't go stale for everyone else
+    broadcastPresence();
+  }, state.config.cleanupIntervalMs);
 }
 
 /**
@@ -256,6 +287,28 @@ export function getRemotePresences(): PresenceState[] {
   return Array.from(state.remotePresences.values());
 }
 
+/**
+ * Resolve a remote user's cursor and selection against the current document.
+ *
+ * Uses the Automerge cursors when present and resolvable, otherwise the raw
+ * offsets from the message.
+ */
+export function resolvePresence(presence: PresenceState): ResolvedPresencePositions {
+  const doc = currentTextDoc();
+  const cursor = resolveCursor(doc, presence.cursorRef);
+  const selectionStart = resolveCursor(doc, presence.selectionRef?.start);
+  const selectionEnd = resolveCursor(doc, presence.selectionRef?.end);
+
+  return {
+    cursor: cursor ?? presence.cursor,
+    selection:
+      selectionStart !== null && selectionEnd !== null
+        ? { start: selectionStart, end: selectionEnd }
+        : presence.selection,
+    exact: cursor !== null,
+  };
+}
+
 /**
  * Subscribe to presence changes.
  * Returns an unsubscribe function.
@@ -340,9 +393,11 @@ function handleEphemeralMessage(message: EphemeralMessage): void {
       userId: message.userId,
       userName: message.userName,
       userColor: message.userColor,
-      filePath: state.currentFilePath!,
+      filePath: message.filePath ?? state.currentFilePath!,
       cursor: message.cursor,
       selection: message.selection,
+      cursorRef: message.cursorRef ?? null,
+      selectionRef: message.selectionRef ?? null,
       lastSeen: Date.now(),
     };
 
@@ -382,10 +437,21 @@ function broadcastPresence(): void {
     userId: state.identity.userId,
     userName: state.identity.userName,
     userColor: state.identity.userColor,
+    filePath: state.currentFilePath,
     cursor: state.localCursor,
     selection: state.localSelection,
   };
 
+  // Attach Automerge cursors so receivers can place us correctly even if the
+  // text changes before they render
+  const doc = currentTextDoc();
+  if (doc) {
+    message.cursorRef = makeCursorRef(doc, state.localCursor);
+    const start = makeCursorRef(doc, state.localSelection?.start ?? null);
+    const end = makeCursorRef(doc, state.localSelection?.end ?? null);
+    message.selectionRef = start !== null && end !== null ? { start, end } : null;
+  }
+
   state.currentHandle.broadcast(message);
   state.lastBroadcastTime = Date.now();
 }
@@ -403,6 +469,38 @@ function broadcastLeave(): void {
   state.currentHandle.broadcast(message);
 }
 
+function currentTextDoc(): TextDocumentContent | null {
+  try {
+    const doc = state.currentHandle?.doc() as TextDocumentContent | undefined;
+    return doc && typeof doc.text === 'string' ? doc : null;
+  } catch {
+    // Handle not ready yet
+    return null;
+  }
+}
+
+function makeCursorRef(doc: TextDocumentContent, offset: number | null): string | null {
+  if (offset === null) return null;
+  try {
+    return getCursor(doc, ['text'], offset);
+  } catch {
+    return null;
+  }
+}
+
+function resolveCursor(
+  doc: TextDocumentContent | null,
+  ref: string | null | undefined
+): number | null {
+  if (!doc || !ref) return null;
+  try {
+    return getCursorPosition(doc, ['text'], ref);
+  } catch {
+    // Refers to characters we haven't received yet
+    return null;
+  }
+}
+
 function cleanupStalePresences(): void {
   const now = Date.now();
   let changed = false;
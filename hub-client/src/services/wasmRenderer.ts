@@ -5,16 +5,21 @@
  * VFS operations, QMD rendering, and SASS compilation.
  */
 
-import type { Diagnostic, RenderResponse } from '../types/diagnostic';
+import type {
+  AsyncRenderResponse,
+  Diagnostic,
+  IncrementalRenderResponse,
+  ProjectRenderResponse,
+  RenderOutput,
+  RenderProgress,
+  RenderResponse,
+} from '../types/diagnostic';
+import { PROTOCOL_VERSION } from '../types/protocol';
+import type { VfsMetadata, VfsResponse } from '../types/protocol';
 import { getSassCache, computeHash } from './sassCache';
 
 // Response types from WASM module
-interface VfsResponse {
-  success: boolean;
-  error?: string;
-  files?: string[];
-  content?: string;
-}
+export type { VfsMetadata } from '../types/protocol';
 
 // Re-export Diagnostic type for convenience
 export type { Diagnostic } from '../types/diagnostic';
@@ -30,9 +35,24 @@ interface WasmModuleExtended {
   vfs_clear: () => string;
   vfs_read_file: (path: string) => string;
   vfs_read_binary_file: (path: string) => string;
+  vfs_glob: (pattern: string) => string;
+  vfs_list_files_under: (dir: string) => string;
+  vfs_create_dir: (path: string) => string;
+  vfs_stat: (path: string) => string;
+  vfs_rename: (oldPath: string, newPath: string) => string;
+  vfs_copy_file: (src: string, dst: string) => string;
   render_qmd: (path: string) => Promise<string>;
+  render_qmd_incremental: (path: string, prevStateToken: string) => Promise<string>;
+  create_render_handle: () => number;
+  cancel_render: (handle: number) => boolean;
+  render_qmd_async: (
+    path: string,
+    handle: number,
+    onProgress?: (progress: string) => boolean | void
+  ) => Promise<string>;
   render_qmd_content: (content: string, templateBundle: string) => Promise<string>;
   render_qmd_content_with_options: (content: string, templateBundle: string, options: string) => Promise<string>;
+  render_project: (projectDir: string) => Promise<string>;
   get_builtin_template: (name: string) => string;
   get_project_choices: () => string;
   create_project: (choiceId: string, title: string) => Promise<string>;
@@ -79,6 +99,16 @@ export async function initWasm(): Promise<void> {
         // Cast to extended type (includes SASS compilation functions)
         wasmModule = wasm as unknown as WasmModuleExtended;
 
+        // Responses are typed from the protocol version this client was
+        // generated for; a stale WASM build may return different JSON
+        const protocolVersion = wasm.protocol_version();
+        if (protocolVersion !== PROTOCOL_VERSION) {
+          console.warn(
+            `wasm-quarto-hub-client speaks protocol version ${protocolVersion}, ` +
+              `but hub-client expects ${PROTOCOL_VERSION}; rebuild the WASM module`
+          );
+        }
+
         // Load the HTML template bundle
         htmlTemplateBundle = wasm.get_builtin_template('html');
 
@@ -261,10 +291,85 @@ export function vfsReadBinaryFile(path: string): VfsResponse {
   return JSON.parse(wasm.vfs_read_binary_file(path));
 }
 
+/**
+ * List the files matching a glob pattern (e.g. `posts/**\/*.qmd`)
+ */
+export function vfsGlob(pattern: string): VfsResponse {
+  const wasm = getWasm();
+  return JSON.parse(wasm.vfs_glob(pattern));
+}
+
+/**
+ * List the files under a directory, recursively
+ */
+export function vfsListFilesUnder(dir: string): VfsResponse {
+  const wasm = getWasm();
+  return JSON.parse(wasm.vfs_list_files_under(dir));
+}
+
+/**
+ * Create a directory (and its parents) in the virtual filesystem
+ */
+export function vfsCreateDir(path: string): VfsResponse {
+  const wasm = getWasm();
+  return JSON.parse(wasm.vfs_create_dir(path));
+}
+
+/**
+ * Get the kind, size and modification time of a file or directory
+ */
+export function vfsStat(path: string): VfsResponse {
+  const wasm = getWasm();
+  return JSON.parse(wasm.vfs_stat(path));
+}
+
+/**
+ * Rename (move) a file or a directory with everything under it
+ */
+export function vfsRename(oldPath: string, newPath: string): VfsResponse {
+  const wasm = getWasm();
+  return JSON.parse(wasm.vfs_rename(oldPath, newPath));
+}
+
+/**
+ * Copy a file in the virtual filesystem
+ */
+export function vfsCopyFile(src: string, dst: string): VfsResponse {
+  const wasm = getWasm();
+  return JSON.parse(wasm.vfs_copy_file(src, dst));
+}
+
 // ============================================================================
 // Rendering Operations
 // ============================================================================
 
+/**
+ * A decoded render output, ready to post to a service worker (with `buffer`
+ * in the transfer list) or to wrap in a `Response`.
+ */
+export interface DecodedRenderOutput {
+  contentType: string;
+  buffer: ArrayBuffer;
+}
+
+/**
+ * Decode the base64 outputs of a render into ArrayBuffers, keyed by URL.
+ */
+export function decodeRenderOutputs(
+  outputs: Record<string, RenderOutput> | undefined
+): Map<string, DecodedRenderOutput> {
+  const decoded = new Map<string, DecodedRenderOutput>();
+  for (const [url, output] of Object.entries(outputs ?? {})) {
+    const binary = atob(output.content);
+    const bytes = new Uint8Array(binary.length);
+    for (let i = 0; i < binary.length; i++) {
+      bytes[i] = binary.charCodeAt(i);
+    }
+    decoded.set(url, { contentType: output.content_type, buffer: bytes.buffer });
+  }
+  return decoded;
+}
+
 /**
  * Render a QMD file from the virtual filesystem
  */
@@ -273,6 +378,55 @@ export async function renderQmd(path: string): Promise<RenderResponse> {
   return JSON.parse(await wasm.render_qmd(path));
 }
 
+/**
+ * Render a QMD file from the virtual filesystem, with a patch from the
+ * render identified by `prevStateToken` (empty for the first render).
+ *
+ * Apply the patch with `applyRenderPatch`, or load the HTML when the patch
+ * asks for a full reload.
+ */
+export async function renderQmdIncremental(
+  path: string,
+  prevStateToken: string = ''
+): Promise<IncrementalRenderResponse> {
+  const wasm = getWasm();
+  return JSON.parse(await wasm.render_qmd_incremental(path, prevStateToken));
+}
+
+/**
+ * A cancellable render of a QMD file from the virtual filesystem.
+ */
+export interface CancellableRender {
+  /** Resolves with the render, which has `cancelled: true` if it was cancelled. */
+  result: Promise<AsyncRenderResponse>;
+  /** Stop the render before its next pipeline stage or transform. */
+  cancel: () => void;
+}
+
+/**
+ * Start a cancellable render of a QMD file from the virtual filesystem.
+ *
+ * Cancel a stale render when a newer one starts (e.g. while the user keeps
+ * typing). `onProgress` is called before each pipeline stage; returning
+ * `false` from it also cancels the render.
+ */
+export function renderQmdCancellable(
+  path: string,
+  onProgress?: (progress: RenderProgress) => boolean | void
+): CancellableRender {
+  const wasm = getWasm();
+  const handle = wasm.create_render_handle();
+  const callback = onProgress
+    ? (progress: string) => onProgress(JSON.parse(progress))
+    : undefined;
+  return {
+    result: wasm.render_qmd_async(path, handle, callback).then((json) => JSON.parse(json)),
+    cancel: () => {
+      wasm.cancel_render(handle);
+    },
+  };
+}
+
 /**
  * Render QMD content directly (without VFS)
  */
@@ -308,6 +462,15 @@ export async function renderQmdContentWithOptions(
   return JSON.parse(await wasm.render_qmd_content_with_options(content, templateBundle, optionsJson));
 }
 
+/**
+ * Render every document of the project in the VFS, writing the outputs
+ * (e.g. under `_site/`) to the VFS.
+ */
+export async function renderProject(projectDir: string): Promise<ProjectRenderResponse> {
+  const wasm = getWasm();
+  return JSON.parse(await wasm.render_project(projectDir));
+}
+
 /**
  * Get a built-in template bundle
  */
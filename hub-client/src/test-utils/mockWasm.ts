@@ -10,16 +10,12 @@
  */
 
 import type { Diagnostic, RenderResponse } from '../types/diagnostic';
+import type { VfsResponse } from '../types/protocol';
 
 /**
- * VFS response type matching the real wasmRenderer.ts
+ * VFS response type, as returned by the real wasmRenderer.ts
  */
-export interface VfsResponse {
-  success: boolean;
-  error?: string;
-  files?: string[];
-  content?: string;
-}
+export type { VfsResponse };
 
 /**
  * Render result matching the real wasmRenderer.ts
@@ -112,6 +112,64 @@ export interface FoldingRange {
   kind?: FoldingRangeKind;
 }
 
+// ============================================================================
+// Document Link Types
+// ============================================================================
+
+/**
+ * What a document link comes from.
+ */
+export type DocumentLinkKind = 'link' | 'image' | 'include' | 'bibliography';
+
+/**
+ * A link from a range of the document to a URL or file.
+ */
+export interface DocumentLink {
+  /** The range of the target in the document (0-based). */
+  range: Range;
+  /**
+   * The target as written: a URL, or a file path relative to the document's
+   * directory (or to the project directory if it starts with `/`).
+   */
+  target: string;
+  /** What the link comes from. */
+  kind: DocumentLinkKind;
+}
+
+// ============================================================================
+// Completion Types
+// ============================================================================
+
+/**
+ * The kind of a completion item.
+ */
+export type CompletionItemKind =
+  | 'key'
+  | 'value'
+  | 'citation'
+  | 'crossRef'
+  | 'file'
+  | 'folder'
+  | 'shortcode';
+
+/**
+ * A completion item.
+ */
+export interface CompletionItem {
+  /** The text shown in the completion list. */
+  label: string;
+  /** The kind of this completion. */
+  kind: CompletionItemKind;
+  /** A short description, e.g., the title of a citation. */
+  detail?: string;
+  /** Longer documentation, e.g., the schema description of a key. */
+  documentation?: string;
+  /** The text to insert, if different from the label. */
+  insertText?: string;
+  /** The range replaced by this completion (0-based). */
+  range: Range;
+}
+
 // ============================================================================
 // Diagnostic Types (matching quarto-error-reporting::DiagnosticMessage)
 // ============================================================================
@@ -193,6 +251,10 @@ export interface DocumentAnalysis {
   foldingRanges: FoldingRange[];
   /** Diagnostics (errors and warnings). */
   diagnostics: Diagnostic[];
+  /** Links to URLs and files. */
+  documentLinks: DocumentLink[];
+  /** Cross-reference labels and citation keys to complete after `@`. */
+  completionItems: CompletionItem[];
 }
 
 // ============================================================================
@@ -208,6 +270,20 @@ export interface LspAnalyzeResponse {
   symbols?: Symbol[];
   foldingRanges?: FoldingRange[];
   diagnostics?: Diagnostic[];
+  documentLinks?: DocumentLink[];
+}
+
+/**
+ * Response from analyze_qmd().
+ */
+export interface AnalyzeQmdResponse {
+  success: boolean;
+  error?: string;
+  symbols?: Symbol[];
+  foldingRanges?: FoldingRange[];
+  diagnostics?: Diagnostic[];
+  documentLinks?: DocumentLink[];
+  completionItems?: CompletionItem[];
 }
 
 /**
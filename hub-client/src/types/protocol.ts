@@ -0,0 +1,267 @@
+// Generated by quarto-wasm-protocol. Do not edit: change the Rust types
+// and run `cargo xtask protocol-types`.
+
+/** Version of the response types; see `protocol_version()`. */
+export const PROTOCOL_VERSION = 1;
+
+/**
+ * Severity of a diagnostic.
+ */
+export type DiagnosticKind = "error" | "warning" | "info" | "note";
+
+/**
+ * Kind of a diagnostic detail.
+ */
+export type DetailKind = "error" | "info" | "note";
+
+/**
+ * A detail of a diagnostic, with its own location.
+ */
+export type DiagnosticDetail = { kind: DetailKind, content: string, start_line?: number, start_column?: number, end_line?: number, end_column?: number, };
+
+/**
+ * A diagnostic message.
+ *
+ * Line and column numbers are 1-based to match Monaco's expectations.
+ */
+export type Diagnostic = { kind: DiagnosticKind, title: string, code?: string, problem?: string, hints: Array<string>, start_line?: number, start_column?: number, end_line?: number, end_column?: number, details: Array<DiagnosticDetail>, };
+
+/**
+ * Kind of a VFS path.
+ */
+export type VfsPathKind = "file" | "directory";
+
+/**
+ * Metadata of a VFS path.
+ */
+export type VfsMetadata = { kind: VfsPathKind, 
+/**
+ * Size in bytes (0 for directories).
+ */
+size: number, 
+/**
+ * Modification time in milliseconds since the Unix epoch.
+ */
+mtime?: number, };
+
+/**
+ * Response of a VFS operation.
+ */
+export type VfsResponse = { success: boolean, error?: string, 
+/**
+ * Paths, for listings.
+ */
+files?: Array<string>, 
+/**
+ * File content (base64 for binary reads).
+ */
+content?: string, metadata?: VfsMetadata, };
+
+/**
+ * A binary file of a render, as served to the preview.
+ */
+export type RenderOutput = { 
+/**
+ * MIME type of the content.
+ */
+content_type: string, 
+/**
+ * Base64-encoded content.
+ */
+content: string, };
+
+/**
+ * Response of a document render.
+ */
+export type RenderResponse = { success: boolean, error?: string, html?: string, 
+/**
+ * Structured diagnostics (errors) with line/column information for Monaco.
+ */
+diagnostics?: Array<Diagnostic>, 
+/**
+ * Structured warnings with line/column information for Monaco.
+ */
+warnings?: Array<Diagnostic>, 
+/**
+ * Binary files the page refers to (images, fonts, downloads), keyed by
+ * their URL relative to the document, for the preview to serve.
+ */
+outputs?: { [key in string]?: RenderOutput }, };
+
+/**
+ * Response of a cancellable render.
+ */
+export type AsyncRenderResponse = { 
+/**
+ * Whether the render stopped because it was cancelled.
+ */
+cancelled: boolean, success: boolean, error?: string, html?: string, 
+/**
+ * Structured diagnostics (errors) with line/column information for Monaco.
+ */
+diagnostics?: Array<Diagnostic>, 
+/**
+ * Structured warnings with line/column information for Monaco.
+ */
+warnings?: Array<Diagnostic>, 
+/**
+ * Binary files the page refers to (images, fonts, downloads), keyed by
+ * their URL relative to the document, for the preview to serve.
+ */
+outputs?: { [key in string]?: RenderOutput }, };
+
+/**
+ * Progress of a render: the pipeline stage about to run.
+ */
+export type RenderProgress = { 
+/**
+ * Name of the stage.
+ */
+stage: string, 
+/**
+ * Zero-based index of the stage.
+ */
+index: number, 
+/**
+ * Number of stages of the pipeline.
+ */
+total: number, };
+
+/**
+ * A region of the preview to insert.
+ */
+export type RegionInsert = { 
+/**
+ * Node ID of the region (its `data-quarto-node` attribute).
+ */
+id: string, 
+/**
+ * The ID of the section it is in, or `None` for the top level.
+ */
+parent: string | null, 
+/**
+ * The ID of the sibling it follows, or `None` for the first child.
+ */
+after: string | null, 
+/**
+ * The region's HTML, with the node IDs of its descendants.
+ */
+html: string, };
+
+/**
+ * The changes turning the previous render into the current one.
+ */
+export type RenderPatch = { 
+/**
+ * Whether the preview must load the whole page instead.
+ */
+full_reload: boolean, 
+/**
+ * IDs of the regions to remove.
+ */
+remove: Array<string>, 
+/**
+ * Regions to insert, in document order.
+ */
+insert: Array<RegionInsert>, };
+
+/**
+ * Response of an incremental render: a render plus a patch from the
+ * previous one.
+ */
+export type IncrementalRenderResponse = { 
+/**
+ * Token identifying this render, to patch the next render against.
+ */
+state_token?: string, 
+/**
+ * Changes from the render identified by `prev_state_token`.
+ */
+patch?: RenderPatch, success: boolean, error?: string, html?: string, 
+/**
+ * Structured diagnostics (errors) with line/column information for Monaco.
+ */
+diagnostics?: Array<Diagnostic>, 
+/**
+ * Structured warnings with line/column information for Monaco.
+ */
+warnings?: Array<Diagnostic>, 
+/**
+ * Binary files the page refers to (images, fonts, downloads), keyed by
+ * their URL relative to the document, for the preview to serve.
+ */
+outputs?: { [key in string]?: RenderOutput }, };
+
+/**
+ * A document of a project render, in the manifest.
+ */
+export type RenderedDocument = { 
+/**
+ * The input file, relative to the project directory.
+ */
+input: string, 
+/**
+ * The output file written to the VFS, relative to the project directory.
+ */
+output?: string, success: boolean, error?: string, 
+/**
+ * Structured diagnostics (errors) with line/column information.
+ */
+diagnostics?: Array<Diagnostic>, 
+/**
+ * Structured warnings with line/column information.
+ */
+warnings?: Array<Diagnostic>, };
+
+/**
+ * Response of a project render: a manifest of the outputs written to the
+ * VFS.
+ */
+export type ProjectRenderResponse = { 
+/**
+ * Whether every document rendered.
+ */
+success: boolean, 
+/**
+ * Why the project couldn't be rendered at all.
+ */
+error?: string, 
+/**
+ * The project directory, in the VFS.
+ */
+project_dir?: string, 
+/**
+ * The output directory, relative to the project directory.
+ */
+output_dir?: string, 
+/**
+ * The documents, in render order.
+ */
+documents: Array<RenderedDocument>, 
+/**
+ * Resources (images, ...) copied to the output directory, relative to
+ * the project directory.
+ */
+resources: Array<string>, 
+/**
+ * Problems of `_quarto.yml`.
+ */
+warnings?: Array<Diagnostic>, };
+
+/**
+ * Response of a conversion between formats.
+ */
+export type ConvertResponse = { 
+/**
+ * The converted document.
+ */
+output?: string, 
+/**
+ * Why the conversion failed.
+ */
+error?: string, 
+/**
+ * Errors, or warnings of a successful conversion, as written by
+ * `DiagnosticMessage::to_json()`.
+ */
+diagnostics: Array<unknown>, };
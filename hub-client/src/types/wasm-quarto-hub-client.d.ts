@@ -3,6 +3,7 @@
  */
 declare module 'wasm-quarto-hub-client' {
   export function init(): void;
+  export function protocol_version(): number;
   export function vfs_add_file(path: string, content: string): string;
   export function vfs_add_binary_file(path: string, content: Uint8Array): string;
   export function vfs_remove_file(path: string): string;
@@ -10,13 +11,28 @@ declare module 'wasm-quarto-hub-client' {
   export function vfs_clear(): string;
   export function vfs_read_file(path: string): string;
   export function vfs_read_binary_file(path: string): string;
+  export function vfs_glob(pattern: string): string;
+  export function vfs_list_files_under(dir: string): string;
+  export function vfs_create_dir(path: string): string;
+  export function vfs_stat(path: string): string;
+  export function vfs_rename(old_path: string, new_path: string): string;
+  export function vfs_copy_file(src: string, dst: string): string;
   export function render_qmd(path: string): Promise<string>;
+  export function render_qmd_incremental(path: string, prev_state_token: string): Promise<string>;
+  export function create_render_handle(): number;
+  export function cancel_render(handle: number): boolean;
+  export function render_qmd_async(
+    path: string,
+    handle: number,
+    on_progress?: (progress: string) => boolean | void
+  ): Promise<string>;
   export function render_qmd_content(content: string, template_bundle: string): Promise<string>;
   export function render_qmd_content_with_options(
     content: string,
     template_bundle: string,
     options_json: string
   ): Promise<string>;
+  export function render_project(project_dir: string): Promise<string>;
   export function get_builtin_template(name: string): string;
 
   // JavaScript execution test functions (interstitial validation)
@@ -29,6 +45,7 @@ declare module 'wasm-quarto-hub-client' {
   export function create_project(choice_id: string, title: string): Promise<string>;
 
   // LSP intelligence functions
+  export function analyze_qmd(path: string): string;
   export function lsp_analyze_document(path: string): string;
   export function lsp_get_symbols(path: string): string;
   export function lsp_get_folding_ranges(path: string): string;
@@ -0,0 +1,84 @@
+/**
+ * Apply a render patch to the preview document.
+ *
+ * Patches come from `renderQmdIncremental`: regions of the page content are
+ * identified by their `data-quarto-node` attributes, so unchanged regions
+ * stay in place (keeping scroll position and widget state) while changed
+ * ones are removed and inserted.
+ */
+
+import type { RenderPatch } from '../types/diagnostic';
+
+const CONTENT_SELECTOR = '#quarto-document-content';
+
+function findNode(doc: Document, id: string): Element | null {
+  return doc.querySelector(`[data-quarto-node="${CSS.escape(id)}"]`);
+}
+
+/**
+ * Scripts parsed through `innerHTML` don't run; recreate them so they do.
+ */
+function runnableScript(doc: Document, script: Element): Element {
+  const runnable = doc.createElement('script');
+  for (const attr of Array.from(script.attributes)) {
+    runnable.setAttribute(attr.name, attr.value);
+  }
+  runnable.textContent = script.textContent;
+  return runnable;
+}
+
+/**
+ * Apply a patch to a document showing the previous render.
+ *
+ * Returns false when the patch can't be applied (it asks for a full reload,
+ * or the document doesn't match the previous render); the caller should then
+ * load the new HTML instead.
+ */
+export function applyRenderPatch(doc: Document, patch: RenderPatch): boolean {
+  if (patch.full_reload) return false;
+
+  const content = doc.querySelector(CONTENT_SELECTOR);
+  if (!content) return false;
+
+  // Check everything the patch refers to before changing anything
+  const removed = patch.remove.map((id) => findNode(doc, id));
+  if (removed.some((node) => node === null)) return false;
+
+  for (const node of removed) {
+    node!.remove();
+  }
+
+  for (const region of patch.insert) {
+    const parent = region.parent === null ? content : findNode(doc, region.parent);
+    const after = region.after === null ? null : findNode(doc, region.after);
+    if (!parent || (region.after !== null && !after)) return false;
+
+    const template = doc.createElement('template');
+    template.innerHTML = region.html;
+    let element = template.content.firstElementChild;
+    if (!element) return false;
+    if (element.tagName === 'SCRIPT') {
+      element = runnableScript(doc, element);
+    } else {
+      for (const script of Array.from(element.querySelectorAll('script'))) {
+        script.replaceWith(runnableScript(doc, script));
+      }
+    }
+
+    if (after) {
+      after.after(element);
+    } else {
+      // First region of its parent: before the regions it already has
+      const first = Array.from(parent.children).find((child) =>
+        child.hasAttribute('data-quarto-node')
+      );
+      if (first) {
+        first.before(element);
+      } else {
+        parent.append(element);
+      }
+    }
+  }
+
+  return true;
+}